@@ -0,0 +1,122 @@
+use newengine_ui::markup::UiTreeModel;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+#[derive(Debug)]
+struct Node {
+    name: String,
+    /// Logical path; `None` for folders.
+    path: Option<String>,
+    children: Vec<u64>,
+}
+
+/// Folder tree over the asset paths, feeding the `<tree id="assets">` markup widget.
+/// Node ids are indices into `nodes`; folders sort before files.
+#[derive(Debug, Default)]
+pub(crate) struct AssetTree {
+    nodes: Vec<Node>,
+    roots: Vec<u64>,
+    revision: u64,
+}
+
+impl AssetTree {
+    pub(crate) fn build(paths: &[String], revision: u64) -> Self {
+        #[derive(Default)]
+        struct Dir {
+            dirs: BTreeMap<String, Dir>,
+            files: Vec<(String, String)>,
+        }
+
+        let mut top = Dir::default();
+        for path in paths {
+            let mut dir = &mut top;
+            let mut parts = path.split('/').filter(|p| !p.is_empty()).peekable();
+            while let Some(part) = parts.next() {
+                if parts.peek().is_none() {
+                    dir.files.push((part.to_string(), path.clone()));
+                } else {
+                    dir = dir.dirs.entry(part.to_string()).or_default();
+                }
+            }
+        }
+
+        fn emit(dir: Dir, nodes: &mut Vec<Node>) -> Vec<u64> {
+            let mut ids = Vec::with_capacity(dir.dirs.len() + dir.files.len());
+            for (name, sub) in dir.dirs {
+                let id = nodes.len();
+                nodes.push(Node {
+                    name,
+                    path: None,
+                    children: Vec::new(),
+                });
+                let children = emit(sub, nodes);
+                nodes[id].children = children;
+                ids.push(id as u64);
+            }
+            let mut files = dir.files;
+            files.sort();
+            for (name, path) in files {
+                ids.push(nodes.len() as u64);
+                nodes.push(Node {
+                    name,
+                    path: Some(path),
+                    children: Vec::new(),
+                });
+            }
+            ids
+        }
+
+        let mut nodes = Vec::with_capacity(paths.len());
+        let roots = emit(top, &mut nodes);
+        Self {
+            nodes,
+            roots,
+            revision,
+        }
+    }
+
+    /// Logical path of a file node; `None` for folders and unknown ids.
+    #[inline]
+    pub(crate) fn path(&self, node: u64) -> Option<&str> {
+        self.nodes.get(node as usize)?.path.as_deref()
+    }
+
+    #[inline]
+    pub(crate) fn file_count(&self) -> usize {
+        self.nodes.iter().filter(|n| n.path.is_some()).count()
+    }
+}
+
+impl UiTreeModel for AssetTree {
+    #[inline]
+    fn child_count(&self, parent: Option<u64>) -> usize {
+        match parent {
+            None => self.roots.len(),
+            Some(p) => self.nodes.get(p as usize).map_or(0, |n| n.children.len()),
+        }
+    }
+
+    #[inline]
+    fn child(&self, parent: Option<u64>, index: usize) -> u64 {
+        match parent {
+            None => self.roots[index],
+            Some(p) => self.nodes[p as usize].children[index],
+        }
+    }
+
+    #[inline]
+    fn label(&self, node: u64) -> Cow<'_, str> {
+        Cow::Borrowed(self.nodes[node as usize].name.as_str())
+    }
+
+    #[inline]
+    fn value(&self, node: u64) -> Cow<'_, str> {
+        let n = &self.nodes[node as usize];
+        Cow::Borrowed(n.path.as_deref().unwrap_or(n.name.as_str()))
+    }
+
+    #[inline]
+    fn revision(&self) -> u64 {
+        self.revision
+    }
+}
@@ -0,0 +1,341 @@
+use newengine_platform_winit::egui;
+use serde::Deserialize;
+use std::collections::{BTreeMap, VecDeque};
+
+const COL_W: f32 = 220.0;
+const ROW_H: f32 = 46.0;
+const NODE_W: f32 = 190.0;
+const NODE_H: f32 = 34.0;
+const MAX_DEPTH: usize = 8;
+
+#[derive(Debug, Deserialize, Clone)]
+struct GraphNode {
+    #[serde(default)]
+    path: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    type_id: Option<String>,
+    #[serde(default)]
+    bytes: Option<u64>,
+    #[serde(default)]
+    transitive_bytes: u64,
+    #[serde(default)]
+    dirty: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct GraphResp {
+    #[serde(default)]
+    nodes: Vec<GraphNode>,
+    /// `[dependent, dependency]` node indices.
+    #[serde(default)]
+    edges: Vec<[usize; 2]>,
+}
+
+/// Fetched subgraph with a column per signed distance from the root:
+/// dependents to the left, dependencies to the right.
+#[derive(Debug, Default)]
+struct GraphView {
+    nodes: Vec<GraphNode>,
+    edges: Vec<[usize; 2]>,
+    pos: Vec<egui::Pos2>,
+    root: Option<usize>,
+}
+
+impl GraphView {
+    fn layout(resp: GraphResp, root_path: &str) -> Self {
+        let n = resp.nodes.len();
+        let root = resp.nodes.iter().position(|x| x.path == root_path);
+
+        let mut adj: Vec<Vec<(usize, i32)>> = vec![Vec::new(); n];
+        for &[a, b] in resp.edges.iter() {
+            if a < n && b < n {
+                adj[a].push((b, 1));
+                adj[b].push((a, -1));
+            }
+        }
+
+        // Signed BFS from the root; unreachable nodes share column 0 below it.
+        let mut col: Vec<Option<i32>> = vec![None; n];
+        if let Some(r) = root {
+            col[r] = Some(0);
+            let mut q = VecDeque::from([r]);
+            while let Some(cur) = q.pop_front() {
+                let c = col[cur].unwrap_or(0);
+                for &(next, step) in adj[cur].iter() {
+                    if col[next].is_none() {
+                        col[next] = Some(c + step);
+                        q.push_back(next);
+                    }
+                }
+            }
+        }
+
+        let min_col = col.iter().flatten().copied().min().unwrap_or(0);
+        let mut rows: BTreeMap<i32, usize> = BTreeMap::new();
+        let mut pos = Vec::with_capacity(n);
+        for c in col.iter() {
+            let c = c.unwrap_or(0);
+            let row = rows.entry(c).or_insert(0);
+            pos.push(egui::pos2((c - min_col) as f32 * COL_W, *row as f32 * ROW_H));
+            *row += 1;
+        }
+
+        Self {
+            nodes: resp.nodes,
+            edges: resp.edges,
+            pos,
+            root,
+        }
+    }
+
+    #[inline]
+    fn state_color(node: &GraphNode) -> egui::Color32 {
+        match node.state.as_str() {
+            "failed" => egui::Color32::from_rgb(220, 70, 70),
+            "ready" if node.dirty => egui::Color32::from_rgb(230, 150, 40),
+            "ready" => egui::Color32::from_rgb(90, 180, 110),
+            "loading" => egui::Color32::from_rgb(90, 150, 220),
+            _ => egui::Color32::from_gray(120),
+        }
+    }
+}
+
+#[inline]
+fn format_bytes(b: u64) -> String {
+    if b >= 1 << 20 {
+        format!("{:.1} MiB", b as f64 / (1u64 << 20) as f64)
+    } else if b >= 1 << 10 {
+        format!("{:.1} KiB", b as f64 / 1024.0)
+    } else {
+        format!("{b} B")
+    }
+}
+
+/// Asset dependency inspector backed by `asset.manager` / `asset.dep_graph`.
+#[derive(Debug)]
+pub(crate) struct DepGraphPanel {
+    pub(crate) open: bool,
+    path: String,
+    depth: usize,
+    view: Option<GraphView>,
+    selected: Option<usize>,
+    error: Option<String>,
+}
+
+impl Default for DepGraphPanel {
+    fn default() -> Self {
+        Self {
+            open: false,
+            path: String::new(),
+            depth: 2,
+            view: None,
+            selected: None,
+            error: None,
+        }
+    }
+}
+
+impl DepGraphPanel {
+    #[inline]
+    pub(crate) fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    fn call(&self, format: &str) -> Result<Vec<u8>, String> {
+        let args = format!("{} {} {format}", self.path.trim(), self.depth);
+        newengine_core::call_service_v1("asset.manager", "asset.dep_graph", args.as_bytes())
+    }
+
+    fn refresh(&mut self) {
+        self.selected = None;
+        self.error = None;
+        self.view = None;
+
+        if self.path.trim().is_empty() {
+            self.error = Some("enter a logical asset path".to_string());
+            return;
+        }
+
+        match self.call("json") {
+            Ok(bytes) => match serde_json::from_slice::<GraphResp>(&bytes) {
+                Ok(resp) => {
+                    let view = GraphView::layout(resp, self.path.trim());
+                    if view.root.is_none() {
+                        self.error = Some(format!("'{}' is not known to the asset store", self.path.trim()));
+                    }
+                    self.view = Some(view);
+                }
+                Err(e) => self.error = Some(format!("bad response json: {e}")),
+            },
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    pub(crate) fn ui(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Asset dependencies")
+            .open(&mut open)
+            .default_size([760.0, 420.0])
+            .resizable(true)
+            .show(ctx, |ui| {
+                self.toolbar(ui);
+                ui.separator();
+
+                if let Some(e) = &self.error {
+                    ui.colored_label(egui::Color32::from_rgb(255, 96, 96), e);
+                }
+
+                self.details(ui);
+                self.canvas(ui);
+            });
+        self.open = open;
+    }
+
+    fn toolbar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Asset:");
+            let resp = ui.add(
+                egui::TextEdit::singleline(&mut self.path)
+                    .desired_width(280.0)
+                    .hint_text("textures/brick.png")
+                    .font(egui::TextStyle::Monospace),
+            );
+            ui.label("Depth:");
+            ui.add(egui::DragValue::new(&mut self.depth).range(1..=MAX_DEPTH));
+
+            let submit = resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("Show").clicked() || submit {
+                self.refresh();
+            }
+
+            if ui
+                .button("Copy DOT")
+                .on_hover_text("Copy the subgraph in Graphviz format")
+                .clicked()
+            {
+                match self.call("dot") {
+                    Ok(bytes) => ui.ctx().copy_text(String::from_utf8_lossy(&bytes).to_string()),
+                    Err(e) => self.error = Some(e),
+                }
+            }
+        });
+    }
+
+    fn details(&self, ui: &mut egui::Ui) {
+        let Some(view) = &self.view else { return };
+        let Some(node) = self.selected.or(view.root).and_then(|i| view.nodes.get(i)) else {
+            return;
+        };
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new(&node.path).monospace().strong());
+            ui.label(format!("state: {}", node.state));
+            if let Some(t) = &node.type_id {
+                ui.label(format!("type: {t}"));
+            }
+            if let Some(b) = node.bytes {
+                ui.label(format!("size: {}", format_bytes(b)));
+            }
+            ui.label(format!("with deps: {}", format_bytes(node.transitive_bytes)));
+            if node.dirty {
+                ui.colored_label(egui::Color32::from_rgb(230, 150, 40), "dirty");
+            }
+        });
+        ui.separator();
+    }
+
+    fn canvas(&mut self, ui: &mut egui::Ui) {
+        let Some(view) = &self.view else { return };
+
+        let mut clicked: Option<usize> = None;
+        let mut recenter: Option<String> = None;
+
+        egui::ScrollArea::both().show(ui, |ui| {
+            let extent = view
+                .pos
+                .iter()
+                .fold(egui::vec2(0.0, 0.0), |acc, p| acc.max(p.to_vec2()))
+                + egui::vec2(NODE_W + 16.0, NODE_H + 16.0);
+            let (rect, _) = ui.allocate_exact_size(extent, egui::Sense::hover());
+            let origin = rect.min + egui::vec2(8.0, 8.0);
+            let painter = ui.painter_at(rect);
+
+            let node_rect = |i: usize| {
+                egui::Rect::from_min_size(origin + view.pos[i].to_vec2(), egui::vec2(NODE_W, NODE_H))
+            };
+
+            let edge_stroke = egui::Stroke::new(1.2, egui::Color32::from_gray(150));
+            for &[a, b] in view.edges.iter() {
+                if a >= view.pos.len() || b >= view.pos.len() {
+                    continue;
+                }
+                let (ra, rb) = (node_rect(a), node_rect(b));
+                let (from, to) = if rb.center().x >= ra.center().x {
+                    (ra.right_center(), rb.left_center())
+                } else {
+                    (ra.left_center(), rb.right_center())
+                };
+                painter.arrow(from, to - from, edge_stroke);
+            }
+
+            for (i, node) in view.nodes.iter().enumerate() {
+                let r = node_rect(i);
+                let id = ui.id().with(("dep_node", i));
+                let resp = ui.interact(r, id, egui::Sense::click());
+
+                let color = GraphView::state_color(node);
+                let fill = if Some(i) == self.selected {
+                    egui::Color32::from_gray(60)
+                } else {
+                    egui::Color32::from_gray(30)
+                };
+                let width = if Some(i) == view.root { 2.5 } else { 1.2 };
+                painter.rect(r, 4.0, fill, egui::Stroke::new(width, color));
+
+                let name = node.path.rsplit('/').next().unwrap_or(&node.path);
+                let size = node.bytes.map(format_bytes).unwrap_or_else(|| node.state.clone());
+                painter.text(
+                    r.left_top() + egui::vec2(6.0, 4.0),
+                    egui::Align2::LEFT_TOP,
+                    name,
+                    egui::FontId::monospace(12.0),
+                    egui::Color32::from_gray(230),
+                );
+                painter.text(
+                    r.left_bottom() + egui::vec2(6.0, -4.0),
+                    egui::Align2::LEFT_BOTTOM,
+                    size,
+                    egui::FontId::monospace(10.0),
+                    color,
+                );
+
+                let resp = resp.on_hover_text(format!(
+                    "{}\n{} | with deps {}\ndouble-click to focus",
+                    node.path,
+                    node.state,
+                    format_bytes(node.transitive_bytes)
+                ));
+                if resp.clicked() {
+                    clicked = Some(i);
+                }
+                if resp.double_clicked() {
+                    recenter = Some(node.path.clone());
+                }
+            }
+        });
+
+        if let Some(i) = clicked {
+            self.selected = Some(i);
+        }
+        if let Some(p) = recenter {
+            self.path = p;
+            self.refresh();
+        }
+    }
+}
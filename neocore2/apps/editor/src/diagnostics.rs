@@ -0,0 +1,190 @@
+use newengine_platform_winit::egui;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+const POLL_EVERY: Duration = Duration::from_secs(1);
+
+const ERROR_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 70, 70);
+const WARNING_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 150, 40);
+
+#[derive(Debug, Deserialize, Clone)]
+struct Issue {
+    #[serde(default)]
+    rule: String,
+    #[serde(default)]
+    path: String,
+    #[serde(default)]
+    severity: String,
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ValidationResp {
+    #[serde(default)]
+    rules: usize,
+    #[serde(default)]
+    errors: usize,
+    #[serde(default)]
+    warnings: usize,
+    #[serde(default)]
+    issues: Vec<Issue>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Content rule violations reported by the asset store (`asset.manager` /
+/// `asset.validation_json`), refreshed while open.
+#[derive(Debug)]
+pub(crate) struct DiagnosticsPanel {
+    pub(crate) open: bool,
+    show_errors: bool,
+    show_warnings: bool,
+    filter: String,
+    resp: ValidationResp,
+    last_poll: Option<Instant>,
+    error: Option<String>,
+}
+
+impl Default for DiagnosticsPanel {
+    fn default() -> Self {
+        Self {
+            open: false,
+            show_errors: true,
+            show_warnings: true,
+            filter: String::new(),
+            resp: ValidationResp::default(),
+            last_poll: None,
+            error: None,
+        }
+    }
+}
+
+impl DiagnosticsPanel {
+    #[inline]
+    pub(crate) fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    fn refresh(&mut self, reload: bool) {
+        self.last_poll = Some(Instant::now());
+
+        let payload: &[u8] = if reload { b"reload" } else { b"" };
+        match newengine_core::call_service_v1("asset.manager", "asset.validation_json", payload) {
+            Ok(bytes) => match serde_json::from_slice::<ValidationResp>(&bytes) {
+                Ok(resp) => {
+                    self.error = resp.error.clone();
+                    self.resp = resp;
+                }
+                Err(e) => self.error = Some(format!("bad response json: {e}")),
+            },
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    pub(crate) fn ui(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        if !matches!(self.last_poll, Some(t) if t.elapsed() < POLL_EVERY) {
+            self.refresh(false);
+        }
+        ctx.request_repaint_after(POLL_EVERY);
+
+        let mut open = self.open;
+        egui::Window::new("Diagnostics")
+            .open(&mut open)
+            .default_size([640.0, 360.0])
+            .resizable(true)
+            .show(ctx, |ui| {
+                self.toolbar(ui);
+                ui.separator();
+
+                if let Some(e) = &self.error {
+                    ui.colored_label(egui::Color32::from_rgb(255, 96, 96), e);
+                }
+
+                self.list(ui);
+            });
+        self.open = open;
+    }
+
+    fn toolbar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(
+                &mut self.show_errors,
+                egui::RichText::new(format!("Errors ({})", self.resp.errors)).color(ERROR_COLOR),
+            );
+            ui.checkbox(
+                &mut self.show_warnings,
+                egui::RichText::new(format!("Warnings ({})", self.resp.warnings))
+                    .color(WARNING_COLOR),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter)
+                    .desired_width(200.0)
+                    .hint_text("filter path or rule")
+                    .font(egui::TextStyle::Monospace),
+            );
+            if ui
+                .button("Reload rules")
+                .on_hover_text("Re-read validation.rules.json and re-check loaded assets")
+                .clicked()
+            {
+                self.refresh(true);
+            }
+            ui.label(format!("{} rule(s)", self.resp.rules));
+        });
+    }
+
+    fn list(&self, ui: &mut egui::Ui) {
+        if self.resp.rules == 0 {
+            ui.label("No validation rules: add validation.rules.json to the assets root.");
+            return;
+        }
+
+        let filter = self.filter.trim().to_ascii_lowercase();
+        let visible = self.resp.issues.iter().filter(|i| {
+            let shown = match i.severity.as_str() {
+                "error" => self.show_errors,
+                _ => self.show_warnings,
+            };
+            shown
+                && (filter.is_empty()
+                    || i.path.to_ascii_lowercase().contains(&filter)
+                    || i.rule.to_ascii_lowercase().contains(&filter))
+        });
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                egui::Grid::new("diagnostics_issues")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for i in visible {
+                            let color = if i.severity == "error" {
+                                ERROR_COLOR
+                            } else {
+                                WARNING_COLOR
+                            };
+                            ui.colored_label(color, &i.severity);
+                            ui.label(egui::RichText::new(&i.rule).monospace());
+                            if ui
+                                .add(
+                                    egui::Label::new(egui::RichText::new(&i.path).monospace())
+                                        .sense(egui::Sense::click()),
+                                )
+                                .on_hover_text("click to copy the path")
+                                .clicked()
+                            {
+                                ui.ctx().copy_text(i.path.clone());
+                            }
+                            ui.label(&i.message);
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
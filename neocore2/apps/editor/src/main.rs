@@ -3,27 +3,43 @@
 use crossbeam_channel::unbounded;
 
 use newengine_core::{
-    AssetManagerConfig, Bus, ConfigPaths, Engine, EngineConfig, EngineError, EngineResult, Services,
-    ShutdownToken, StartupConfig, StartupLoader,
+    Bus, CheckStatus, ConfigPaths, DryRunReport, Engine, EngineConfig, EngineError, EngineResult,
+    Services, ShutdownToken, StartupConfig, StartupLoader,
 };
+use newengine_core::console::DevConsoleModule;
+use newengine_core::crash::CrashConfig;
+use newengine_core::render::{LatencyMode, NullRenderModule, PresentMode};
 
+use newengine_camera::glam::Vec3;
+use newengine_camera::{CameraModule, CameraState, Perspective, Projection};
 use newengine_modules_logging::{ConsoleLoggerConfig, ConsoleLoggerModule};
-use newengine_modules_render_vulkan_ash::VulkanAshRenderModule;
+use newengine_modules_remote_console::{RemoteConsoleConfig, RemoteConsoleModule};
+use newengine_modules_render_vulkan_ash::{VulkanAshRenderModule, VulkanRenderConfig};
+use newengine_plugins_wasm::{WasmPluginConfig, WasmPluginsModule};
 
 use newengine_platform_winit::app::config::WinitAppIcon;
-use newengine_platform_winit::{run_winit_app_with_config, WinitAppConfig, WinitWindowPlacement};
+use newengine_platform_winit::{
+    run_winit_app_with_config, WinitAppConfig, WinitDpiMode, WinitFullscreen,
+    WinitWindowPlacement,
+};
 
 use newengine_ui::markup::UiMarkupDoc;
-use newengine_ui::UiBuildFn;
+use newengine_ui::{NotifyApi, ToastLevel, UiBuildFn};
 
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+mod asset_browser;
+mod dep_graph;
+mod diagnostics;
+mod palette;
 mod render_controller;
+mod timeline;
 mod ui;
 
 const FIXED_DT_MS: u32 = 16;
 const UI_MARKUP_PATH: &str = "ui/editor.xml";
+const EDITOR_LOG_CAPTURE: usize = 2000;
 
 struct AppServices;
 
@@ -50,32 +66,90 @@ fn winit_config_from_startup(startup: &StartupConfig) -> WinitAppConfig {
         }
     };
 
+    let fullscreen = match startup.window_fullscreen {
+        newengine_core::startup::WindowFullscreen::Windowed => WinitFullscreen::Windowed,
+        newengine_core::startup::WindowFullscreen::Borderless { monitor } => {
+            WinitFullscreen::Borderless { monitor }
+        }
+        newengine_core::startup::WindowFullscreen::Exclusive {
+            monitor,
+            size,
+            refresh_hz,
+        } => WinitFullscreen::Exclusive {
+            monitor,
+            size,
+            refresh_mhz: refresh_hz.map(|hz| hz.saturating_mul(1000)),
+        },
+    };
+
     WinitAppConfig {
         title: startup.window_title.clone(),
         size: startup.window_size,
         placement,
         ui_backend: startup.ui_backend.clone(),
+        fullscreen,
+        dpi_mode: if startup.window_dpi_aware {
+            WinitDpiMode::Logical
+        } else {
+            WinitDpiMode::Physical
+        },
         icon: None,
+        // Assets dragged onto the editor land in `<assets>/imported/`.
+        drop_import_dir: Some(std::path::PathBuf::from("imported")),
+        ..WinitAppConfig::default()
     }
 }
 
+/// Orbit-style preview camera looking at the origin.
+fn editor_camera() -> CameraState {
+    let mut state = CameraState {
+        projection: Projection::Perspective(Perspective::new(
+            60.0f32.to_radians(),
+            16.0 / 9.0,
+            0.01,
+            1000.0,
+        )),
+        ..CameraState::default()
+    };
+    state.look_at(Vec3::new(2.6, 1.8, 2.6), Vec3::ZERO, Vec3::Y);
+    state
+}
+
 #[inline]
 fn register_render_from_startup(engine: &mut Engine<()>, startup: &StartupConfig) -> EngineResult<()> {
     let backend = startup.render_backend.trim();
 
-    if backend.eq_ignore_ascii_case("vulkan_ash") || backend.eq_ignore_ascii_case("vulkan") {
-        engine.register_module(Box::new(VulkanAshRenderModule::new()))?;
+    if backend.eq_ignore_ascii_case("null") {
+        engine.register_module(Box::new(NullRenderModule::new()))?;
+    } else if backend.eq_ignore_ascii_case("vulkan_ash") || backend.eq_ignore_ascii_case("vulkan") {
+        let config = VulkanRenderConfig {
+            direct_upload: startup.render_direct_upload,
+            present_mode: PresentMode::from_vsync(startup.render_vsync),
+            swapchain_images: (startup.render_swapchain_images > 0)
+                .then_some(startup.render_swapchain_images),
+            latency_mode: if startup.render_low_latency {
+                LatencyMode::Low
+            } else {
+                LatencyMode::Throughput
+            },
+            sparse_textures: startup.render_sparse_textures,
+            bindless_textures: startup.render_bindless_textures,
+            frames_in_flight: startup.render_frames_in_flight,
+        };
+        engine.register_module(Box::new(VulkanAshRenderModule::new().with_config(config)))?;
+    } else {
+        return Err(EngineError::other(format!(
+            "unsupported render backend '{backend}'"
+        )));
+    }
 
-        engine.register_module(Box::new(
-            render_controller::EditorRenderController::new(startup.render_clear_color),
-        ))?;
+    engine.register_module(Box::new(CameraModule::new(editor_camera())))?;
 
-        return Ok(());
-    }
+    engine.register_module(Box::new(
+        render_controller::EditorRenderController::new(startup.render_clear_color),
+    ))?;
 
-    Err(EngineError::other(format!(
-        "unsupported render backend '{backend}'"
-    )))
+    Ok(())
 }
 
 fn build_engine_from_startup(startup: &StartupConfig) -> EngineResult<Engine<()>> {
@@ -85,12 +159,7 @@ fn build_engine_from_startup(startup: &StartupConfig) -> EngineResult<Engine<()>
     let services: Box<dyn Services> = Box::new(AppServices::new());
     let shutdown = ShutdownToken::new();
 
-    let assets = AssetManagerConfig::new(startup.assets_root.clone())
-        .with_pump_steps(startup.asset_pump_steps)
-        .with_filesystem_source(startup.asset_filesystem_source);
-
-    let config =
-        EngineConfig::new(FIXED_DT_MS, assets).with_plugins_dir(Some(startup.modules_dir.clone()));
+    let config = EngineConfig::from_startup(FIXED_DT_MS, startup);
 
     let mut engine: Engine<()> = Engine::new_with_config(config, services, bus, shutdown)?;
 
@@ -98,6 +167,16 @@ fn build_engine_from_startup(startup: &StartupConfig) -> EngineResult<Engine<()>
     // before Engine::start() so early plugin/importer logs are visible.
     engine.register_module(Box::new(ConsoleLoggerModule::new(configure_logger(startup))))?;
 
+    // Opt-in: only when a token is provided (NEWENGINE_REMOTE_CONSOLE_TOKEN).
+    if let Some(cfg) = RemoteConsoleConfig::from_env() {
+        engine.register_module(Box::new(RemoteConsoleModule::new(cfg)))?;
+    }
+
+    // Sandboxed `.wasm` plugins next to the DLL plugins, under the same permission policy.
+    let wasm = WasmPluginConfig::new(startup.modules_dir.clone())
+        .with_permissions(startup.plugin_permissions.clone());
+    engine.register_module(Box::new(WasmPluginsModule::new(wasm)))?;
+
     Ok(engine)
 }
 
@@ -105,6 +184,9 @@ fn build_engine_from_startup(startup: &StartupConfig) -> EngineResult<Engine<()>
 fn configure_logger(startup: &StartupConfig) -> ConsoleLoggerConfig {
     let mut cfg = ConsoleLoggerConfig::from_env();
 
+    // Backs the log panel; NEWENGINE_LOG_CAPTURE=0 turns it off.
+    cfg.capture.get_or_insert(EDITOR_LOG_CAPTURE);
+
     // If NEWENGINE_LOG is set, keep it as authoritative (filter string).
     if cfg.filter.is_some() {
         return cfg;
@@ -121,15 +203,7 @@ fn configure_logger(startup: &StartupConfig) -> ConsoleLoggerConfig {
 fn bootstrap_logging(startup: &StartupConfig) {
     // Ensure logs are available before Engine::start() and before plugin loading.
     // The ConsoleLoggerModule will later attempt to install the logger and will no-op.
-    let mut builder = env_logger::Builder::new();
-
-    if let Ok(level) = startup.log_level.parse::<log::LevelFilter>() {
-        builder.filter_level(level);
-    } else {
-        builder.filter_level(log::LevelFilter::Info);
-    }
-
-    let _ = builder.try_init();
+    let _ = newengine_modules_logging::try_init_with(&configure_logger(startup));
 }
 
 fn load_asset_blob_with_timeout(
@@ -201,12 +275,63 @@ fn try_load_window_icon(engine: &Engine<()>, startup: &StartupConfig) -> Option<
     }
 }
 
+/// `--validate`: dry run on the null render backend. Nothing but the JSON report goes to
+/// stdout, so CI can parse it; the exit code is non-zero when any check failed.
+fn run_validate(startup: &StartupConfig, config_warnings: &[String]) -> EngineResult<i32> {
+    let mut startup = startup.clone();
+    startup.render_backend = "null".to_owned();
+
+    let mut engine = build_engine_from_startup(&startup)?;
+    register_render_from_startup(&mut engine, &startup)?;
+
+    let ui_enabled = !matches!(startup.ui_backend, newengine_core::startup::UiBackend::Disabled);
+    let mut report = engine.validate_only_with(|engine, report| {
+        if ui_enabled {
+            check_ui_markup(engine, report);
+        }
+    })?;
+
+    for w in config_warnings.iter() {
+        report.check("config", CheckStatus::Warning, w.clone());
+    }
+
+    println!("{}", report.to_json());
+    Ok(report.exit_code())
+}
+
+fn check_ui_markup(engine: &Engine<()>, report: &mut DryRunReport) {
+    let Some(am) = engine.resources.get::<newengine_core::assets::AssetManager>() else {
+        return;
+    };
+    let mut pump = || am.pump();
+    let timeout = Duration::from_secs(5);
+    match UiMarkupDoc::load_from_store(am.store(), &mut pump, UI_MARKUP_PATH, timeout) {
+        Ok(_) => report.check("ui.markup", CheckStatus::Ok, UI_MARKUP_PATH),
+        Err(e) => report.check(
+            "ui.markup",
+            CheckStatus::Error,
+            format!("{UI_MARKUP_PATH}: {e}"),
+        ),
+    }
+}
+
 fn main() -> EngineResult<()> {
+    // The crash monitor is this executable started with a marker argument; it never returns.
+    newengine_core::crash::run_monitor_if_requested();
+
+    let validate = std::env::args().skip(1).any(|a| a == "--validate");
+
     let paths = ConfigPaths::from_startup_str("config.json");
     let (startup, report) = StartupLoader::load_json(&paths)?;
 
     // Bootstrap logging as early as possible, before any plugin/importer activity.
     bootstrap_logging(&startup);
+    newengine_core::crash::install(CrashConfig::from_env());
+
+    if validate {
+        let code = run_validate(&startup, &report.warnings)?;
+        std::process::exit(code);
+    }
 
     println!(
         "startup: loaded source={:?} file={:?} resolved_from={:?} overrides={}",
@@ -218,6 +343,16 @@ fn main() -> EngineResult<()> {
     for ov in report.overrides.iter() {
         println!("startup: override {}: '{}' -> '{}'", ov.key, ov.from, ov.to);
     }
+    // Queued until the UI provider draws its first frame.
+    for w in report.warnings.iter() {
+        log::warn!("startup: {w}");
+        NotifyApi::toast(ToastLevel::Warning, format!("config.json: {w}"), None);
+    }
+
+    // Accessibility must be known before the window exists (screen-reader adapter).
+    newengine_core::settings::apply_startup_ui_settings(&startup);
+    // Config and persisted values wait for the modules that register the variables.
+    newengine_core::cvars::apply_startup_cvars(&startup);
 
     let startup = Arc::new(startup);
 
@@ -226,6 +361,11 @@ fn main() -> EngineResult<()> {
     // 1) Register render (backend + controller) so the module set is complete before window creation.
     register_render_from_startup(&mut engine, &startup)?;
 
+    // Drop-down console overlay (`~`) drawn by the UI provider.
+    if !matches!(startup.ui_backend, newengine_core::startup::UiBackend::Disabled) {
+        engine.register_module(Box::new(DevConsoleModule::new().with_open(true)))?;
+    }
+
     // 2) Load plugins/importers BEFORE creating winit (required: plugins/providers must exist).
     engine.load_plugins_once()?;
 
@@ -264,6 +404,9 @@ fn main() -> EngineResult<()> {
         )
             .map_err(|e| EngineError::other(format!("ui: load failed: {e}")))?;
 
+        // Remote mirrors receive the composed tree; state follows as diffs from the UI build.
+        newengine_core::ui_remote::publish_doc(doc.to_remote_json());
+
         if let Ok(mut g) = shared_doc.lock() {
             *g = Some(doc);
         }
@@ -0,0 +1,466 @@
+use newengine_platform_winit::egui;
+use serde::Deserialize;
+
+use newengine_core::host_events::KeyCode;
+use newengine_ui::console::ConsoleSuggestions;
+
+const MAX_ASSET_FILES: usize = 4096;
+const MAX_VISIBLE: usize = 64;
+
+/// Editor-side operations that are not console commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EditorOp {
+    ToggleConsole,
+    ClearConsole,
+    RefreshCommands,
+    RescanAssets,
+    ToggleDepGraph,
+    ToggleTimeline,
+    ToggleDiagnostics,
+    TogglePreview,
+    Quit,
+}
+
+impl EditorOp {
+    const ALL: [(EditorOp, &'static str, &'static str); 9] = [
+        (EditorOp::ToggleConsole, "Toggle console", "Show or hide the engine console"),
+        (EditorOp::ClearConsole, "Clear console", "Drop console output"),
+        (EditorOp::RefreshCommands, "Refresh commands", "Re-read console commands from services"),
+        (EditorOp::RescanAssets, "Rescan assets", "Re-list assets from every source"),
+        (EditorOp::ToggleDepGraph, "Asset dependencies", "Show the dependency graph around an asset"),
+        (EditorOp::ToggleTimeline, "Event timeline", "Trace events, stages and asset transitions per frame"),
+        (EditorOp::ToggleDiagnostics, "Diagnostics", "Content rule violations found on import"),
+        (EditorOp::TogglePreview, "Model preview", "Show the offscreen-rendered model thumbnail"),
+        (EditorOp::Quit, "Quit", "Exit the editor"),
+    ];
+}
+
+/// What the palette asks the editor to do after Enter.
+#[derive(Debug, Clone)]
+pub(crate) enum PaletteAction {
+    /// Run a complete console line.
+    Exec(String),
+    /// Open the console with the line pre-filled (command still needs arguments).
+    Prefill(String),
+    Op(EditorOp),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    Command,
+    CVar,
+    Asset,
+    Editor,
+}
+
+impl EntryKind {
+    #[inline]
+    fn label(self) -> &'static str {
+        match self {
+            EntryKind::Command => "cmd",
+            EntryKind::CVar => "cvar",
+            EntryKind::Asset => "asset",
+            EntryKind::Editor => "editor",
+        }
+    }
+
+    /// Ties between equal fuzzy scores favour commands, then editor ops.
+    #[inline]
+    fn rank(self) -> i32 {
+        match self {
+            EntryKind::Command => 3,
+            EntryKind::Editor => 2,
+            EntryKind::CVar => 1,
+            EntryKind::Asset => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    kind: EntryKind,
+    title: String,
+    detail: String,
+    action: PaletteAction,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssetListItem {
+    #[serde(default)]
+    path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssetQueryResponse {
+    #[serde(default)]
+    entries: Vec<AssetListItem>,
+}
+
+/// Ctrl+P fuzzy launcher over console commands, cvars, assets and editor operations.
+///
+/// Commands and cvars come from the completion service (`command.suggest`), so anything a
+/// service registers shows up here without editor changes.
+#[derive(Debug)]
+pub(crate) struct CommandPalette {
+    open: bool,
+    query: String,
+    selected: usize,
+    focus_pending: bool,
+
+    entries: Vec<Entry>,
+    assets: Vec<String>,
+    assets_scanned: bool,
+
+    matches: Vec<(usize, i32)>,
+    last_query: Option<String>,
+}
+
+impl CommandPalette {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            selected: 0,
+            focus_pending: false,
+            entries: Vec::new(),
+            assets: Vec::new(),
+            assets_scanned: false,
+            matches: Vec::new(),
+            last_query: None,
+        }
+    }
+
+    pub(crate) fn rescan_assets(&mut self) {
+        self.assets_scanned = false;
+        if self.open {
+            self.rebuild_entries();
+        }
+    }
+
+    fn show(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+        self.focus_pending = true;
+        self.rebuild_entries();
+    }
+
+    #[inline]
+    fn hide(&mut self) {
+        self.open = false;
+        self.query.clear();
+    }
+
+    /// `keys` are this frame's key presses from the Input plugin.
+    pub(crate) fn ui(&mut self, ctx: &egui::Context, keys: &[u32]) -> Option<PaletteAction> {
+        let pressed = |code: KeyCode| keys.iter().any(|k| *k == code as u32);
+
+        // Modifier state is not exposed by the Input plugin; egui tracks it from the same
+        // window events.
+        let command = ctx.input(|i| i.modifiers.command);
+        if command && pressed(KeyCode::P) {
+            if self.open {
+                self.hide();
+            } else {
+                self.show();
+            }
+        }
+
+        if !self.open {
+            return None;
+        }
+
+        if pressed(KeyCode::Escape) {
+            self.hide();
+            return None;
+        }
+
+        self.refresh_matches();
+
+        if pressed(KeyCode::ArrowUp) {
+            self.selected = self.selected.saturating_sub(1);
+        } else if pressed(KeyCode::ArrowDown) {
+            self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1));
+        }
+
+        let mut action = None;
+        if pressed(KeyCode::Enter) {
+            action = self.accept();
+        }
+
+        // The palette owns these keys while open; the console input must not react too.
+        ctx.input_mut(|i| {
+            i.events.retain(|e| {
+                !matches!(
+                    e,
+                    egui::Event::Key { key: egui::Key::Enter, .. }
+                        | egui::Event::Key { key: egui::Key::ArrowUp, .. }
+                        | egui::Event::Key { key: egui::Key::ArrowDown, .. }
+                        | egui::Event::Key { key: egui::Key::Escape, .. }
+                )
+            });
+        });
+
+        if action.is_some() {
+            self.hide();
+            return action;
+        }
+
+        let mut clicked: Option<usize> = None;
+
+        let bg = egui::Color32::from_rgba_premultiplied(16, 16, 18, 248);
+        let stroke = egui::Stroke::new(1.0, egui::Color32::from_gray(70));
+        let width = (ctx.screen_rect().width() * 0.5).clamp(360.0, 720.0);
+
+        egui::Area::new(egui::Id::new("ne_command_palette"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 64.0))
+            .show(ctx, |ui| {
+                egui::Frame::none()
+                    .fill(bg)
+                    .stroke(stroke)
+                    .inner_margin(egui::Margin::symmetric(10.0, 8.0))
+                    .rounding(egui::Rounding::same(6.0))
+                    .show(ui, |ui| {
+                        ui.set_width(width);
+
+                        let resp = ui.add(
+                            egui::TextEdit::singleline(&mut self.query)
+                                .id(ui.make_persistent_id("ne_command_palette_input"))
+                                .desired_width(f32::INFINITY)
+                                .font(egui::TextStyle::Monospace)
+                                .hint_text("Command, cvar, asset or action"),
+                        );
+                        if self.focus_pending || !resp.has_focus() {
+                            resp.request_focus();
+                            self.focus_pending = false;
+                        }
+                        if resp.changed() {
+                            self.selected = 0;
+                            self.refresh_matches();
+                        }
+
+                        ui.add_space(6.0);
+
+                        if self.matches.is_empty() {
+                            let hint = if self.query.trim().is_empty() {
+                                "Nothing registered"
+                            } else {
+                                "No matches; Enter runs the text as a console line"
+                            };
+                            ui.label(
+                                egui::RichText::new(hint)
+                                    .monospace()
+                                    .color(egui::Color32::from_gray(150)),
+                            );
+                            return;
+                        }
+
+                        egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                            for (row, (idx, _)) in self.matches.iter().enumerate() {
+                                let e = &self.entries[*idx];
+                                let selected = row == self.selected;
+
+                                let text = if e.detail.is_empty() {
+                                    format!("{:<6} {}", e.kind.label(), e.title)
+                                } else {
+                                    format!("{:<6} {}  -  {}", e.kind.label(), e.title, e.detail)
+                                };
+                                let mut rt = egui::RichText::new(text).monospace();
+                                rt = if selected {
+                                    rt.strong().color(egui::Color32::from_gray(240))
+                                } else {
+                                    rt.color(egui::Color32::from_gray(195))
+                                };
+
+                                let r = ui.selectable_label(selected, rt);
+                                if selected && (pressed(KeyCode::ArrowUp) || pressed(KeyCode::ArrowDown)) {
+                                    r.scroll_to_me(None);
+                                }
+                                if r.clicked() {
+                                    clicked = Some(row);
+                                }
+                            }
+                        });
+                    });
+            });
+
+        if let Some(row) = clicked {
+            self.selected = row;
+            let action = self.accept();
+            self.hide();
+            return action;
+        }
+
+        None
+    }
+
+    fn accept(&mut self) -> Option<PaletteAction> {
+        if let Some((idx, _)) = self.matches.get(self.selected) {
+            return Some(self.entries[*idx].action.clone());
+        }
+
+        let line = self.query.trim();
+        if line.is_empty() {
+            None
+        } else {
+            Some(PaletteAction::Exec(line.to_string()))
+        }
+    }
+
+    fn refresh_matches(&mut self) {
+        if self.last_query.as_deref() == Some(self.query.as_str()) {
+            return;
+        }
+        self.last_query = Some(self.query.clone());
+
+        let q = self.query.trim();
+        self.matches.clear();
+        for (i, e) in self.entries.iter().enumerate() {
+            if let Some(score) = fuzzy_score(q, &e.title) {
+                self.matches.push((i, score));
+            }
+        }
+
+        let entries = &self.entries;
+        self.matches.sort_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then_with(|| entries[b.0].kind.rank().cmp(&entries[a.0].kind.rank()))
+                .then_with(|| entries[a.0].title.len().cmp(&entries[b.0].title.len()))
+                .then_with(|| entries[a.0].title.cmp(&entries[b.0].title))
+        });
+        self.matches.truncate(MAX_VISIBLE);
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    fn rebuild_entries(&mut self) {
+        self.entries.clear();
+        self.last_query = None;
+
+        for (op, title, detail) in EditorOp::ALL {
+            self.entries.push(Entry {
+                kind: EntryKind::Editor,
+                title: title.to_string(),
+                detail: detail.to_string(),
+                action: PaletteAction::Op(op),
+            });
+        }
+
+        if let Ok(bytes) = newengine_core::call_service_v1("engine.command", "command.suggest", &[]) {
+            if let Ok(r) = serde_json::from_slice::<ConsoleSuggestions>(&bytes) {
+                for it in r.items {
+                    let kind = match it.kind.as_str() {
+                        "cvar" => EntryKind::CVar,
+                        _ => EntryKind::Command,
+                    };
+                    // Completion inserts end with a space when arguments are still required.
+                    let action = if it.insert.ends_with(' ') {
+                        PaletteAction::Prefill(it.insert.clone())
+                    } else {
+                        PaletteAction::Exec(it.insert.clone())
+                    };
+                    let detail = if it.help.is_empty() { it.usage } else { it.help };
+                    self.entries.push(Entry {
+                        kind,
+                        title: it.display,
+                        detail,
+                        action,
+                    });
+                }
+            }
+        }
+
+        if !self.assets_scanned {
+            self.assets = collect_assets(MAX_ASSET_FILES);
+            self.assets_scanned = true;
+        }
+        for path in self.assets.iter() {
+            self.entries.push(Entry {
+                kind: EntryKind::Asset,
+                title: path.clone(),
+                detail: "open".to_string(),
+                action: PaletteAction::Exec(format!("asset.load {path}")),
+            });
+        }
+    }
+}
+
+/// Asset paths from the asset query service: every source (archives included) plus
+/// anything the store already knows, sorted.
+pub(crate) fn collect_assets(limit: usize) -> Vec<String> {
+    let req = format!("{{\"limit\":{limit}}}");
+    let Ok(bytes) = newengine_core::call_service_v1("asset.manager", "asset.query_json", req.as_bytes())
+    else {
+        return Vec::new();
+    };
+    serde_json::from_slice::<AssetQueryResponse>(&bytes)
+        .map(|r| {
+            r.entries
+                .into_iter()
+                .map(|x| x.path)
+                .filter(|p| !p.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Revision of the asset query results; a changed value means the listing is stale.
+pub(crate) fn asset_query_revision() -> Option<u64> {
+    #[derive(Deserialize)]
+    struct RevisionResponse {
+        revision: u64,
+    }
+
+    let bytes =
+        newengine_core::call_service_v1("asset.manager", "asset.query_revision", &[]).ok()?;
+    serde_json::from_slice::<RevisionResponse>(&bytes)
+        .ok()
+        .map(|r| r.revision)
+}
+
+/// Case-insensitive subsequence match. Consecutive runs and hits on word starts
+/// (after `.`, `_`, `/`, `-`, space, or at a lower→upper boundary) score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let t: Vec<char> = text.chars().collect();
+    let mut score = 0i32;
+    let mut ti = 0usize;
+    let mut prev_hit: Option<usize> = None;
+
+    for qc in query.chars().filter(|c| !c.is_whitespace()) {
+        let qc = qc.to_ascii_lowercase();
+        let mut found = None;
+        while ti < t.len() {
+            if t[ti].to_ascii_lowercase() == qc {
+                found = Some(ti);
+                ti += 1;
+                break;
+            }
+            ti += 1;
+        }
+        let i = found?;
+
+        score += 1;
+        if prev_hit.is_some_and(|p| p + 1 == i) {
+            score += 5;
+        }
+        let boundary = i == 0
+            || matches!(t[i - 1], '.' | '_' | '/' | '-' | ' ')
+            || (t[i - 1].is_lowercase() && t[i].is_uppercase());
+        if boundary {
+            score += 8;
+        }
+        if let Some(p) = prev_hit {
+            score -= ((i - p - 1) as i32).min(3);
+        } else {
+            score -= (i as i32).min(5);
+        }
+        prev_hit = Some(i);
+    }
+
+    Some(score)
+}
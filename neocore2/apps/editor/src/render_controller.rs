@@ -1,18 +1,38 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 use newengine_core::render::{
-    require_render_api, BeginFrameDesc, BindGroupDesc, BindGroupLayoutDesc, BindingKind,
-    BufferBinding, BufferDesc, BufferSlice, BufferUsage, DrawIndexedArgs, Extent2D, IndexFormat,
-    MemoryHint, PipelineDesc, PrimitiveTopology, RectI32, ShaderDesc, ShaderStage, TextureFormat,
-    VertexAttribute, VertexFormat, VertexLayout, Viewport,
+    require_render_api, BeginFrameDesc, BufferDesc, BufferSlice, BufferUsage, Extent2D,
+    GpuMaterial, GpuMesh, InstanceBatcher, MemoryHint, PipelineDesc, PrimitiveTopology, RectI32,
+    RenderApi, RenderAssetCache, RenderDeviceReset, RenderList, ShaderDesc, ShaderStage,
+    TextureFormat, TextureId, VertexAttribute, VertexFormat, VertexLayout, Viewport,
 };
-use newengine_core::{EngineError, EngineResult, Module, ModuleCtx};
+use newengine_core::{EngineError, EngineResult, EventSub, Module, ModuleCtx};
 use newengine_platform_winit::WinitWindowInitSize;
-use newengine_ui::draw::UiDrawList;
+use newengine_ui::draw::{UiDrawList, UiTexId};
 
-use newengine_assets::{AssetState, Model3dFormat, Model3dReader};
+use newengine_assets::{AssetId, AssetKey, AssetState, MaterialAsset, MeshAsset, Model3dReader};
+use newengine_camera::glam::{Mat4, Vec3};
+use newengine_camera::{ActiveCamera, CameraState};
 
 use shaderc::{CompileOptions, Compiler, OptimizationLevel, ShaderKind};
+use std::sync::Mutex;
+
+const PREVIEW_SIZE: u32 = 256;
+const PREVIEW_CLEAR: [f32; 4] = [0.12, 0.12, 0.14, 1.0];
+
+/// Model thumbnail rendered offscreen, shown by the editor UI.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PreviewImage {
+    pub(crate) texture: UiTexId,
+    pub(crate) size: [u32; 2],
+}
+
+static PREVIEW: Mutex<Option<PreviewImage>> = Mutex::new(None);
+
+#[inline]
+pub(crate) fn preview_image() -> Option<PreviewImage> {
+    PREVIEW.lock().ok().and_then(|g| *g)
+}
 
 #[derive(Clone, Copy)]
 struct DemoGpu {
@@ -24,18 +44,14 @@ struct DemoGpu {
 
 #[derive(Clone, Copy)]
 struct ModelGpu {
-    vb: newengine_core::render::BufferId,
-    ib: newengine_core::render::BufferId,
-    ubo: newengine_core::render::BufferId,
-
-    bgl: newengine_core::render::BindGroupLayoutId,
-    bg: newengine_core::render::BindGroupId,
-
-    vs: newengine_core::render::ShaderId,
-    fs: newengine_core::render::ShaderId,
-    pipeline: newengine_core::render::PipelineId,
+    mesh: GpuMesh,
+    material: GpuMaterial,
+}
 
-    index_count: u32,
+#[derive(Clone, Copy)]
+struct PreviewGpu {
+    target: TextureId,
+    dirty: bool,
 }
 
 pub struct EditorRenderController {
@@ -43,8 +59,14 @@ pub struct EditorRenderController {
     last_w: u32,
     last_h: u32,
     demo: Option<DemoGpu>,
+    assets: RenderAssetCache,
     model: Option<ModelGpu>,
     model_loaded_once: bool,
+    preview: Option<PreviewGpu>,
+    preview_unsupported: bool,
+    batcher: InstanceBatcher,
+    list: RenderList,
+    device_resets: Option<EventSub<RenderDeviceReset>>,
 }
 
 impl EditorRenderController {
@@ -55,8 +77,28 @@ impl EditorRenderController {
             last_w: 0,
             last_h: 0,
             demo: None,
+            assets: RenderAssetCache::new(TextureFormat::Bgra8Unorm, Some(TextureFormat::Depth32Float)),
             model: None,
             model_loaded_once: false,
+            preview: None,
+            preview_unsupported: false,
+            batcher: InstanceBatcher::new(),
+            list: RenderList::new(),
+            device_resets: None,
+        }
+    }
+
+    /// Drops every GPU handle after the backend recreated its device; the demo, model and
+    /// preview are built again on the next frame.
+    fn forget_gpu(&mut self) {
+        self.demo = None;
+        self.assets.forget_all();
+        self.model = None;
+        self.model_loaded_once = false;
+        self.preview = None;
+        self.batcher.forget();
+        if let Ok(mut g) = PREVIEW.lock() {
+            *g = None;
         }
     }
 
@@ -100,201 +142,6 @@ impl EditorRenderController {
         }
     }
 
-    fn decode_ne3d_mesh(bytes: &[u8]) -> EngineResult<(Vec<[f32; 3]>, Vec<[f32; 3]>, Vec<u32>)> {
-        fn need<'a>(bytes: &'a [u8], at: usize, len: usize, what: &str) -> EngineResult<&'a [u8]> {
-            let end = at.saturating_add(len);
-            if end > bytes.len() {
-                return Err(EngineError::other(format!("ne3d: truncated while reading {what}")));
-            }
-            Ok(&bytes[at..end])
-        }
-
-        if bytes.len() < 4 + 4 * 4 {
-            return Err(EngineError::other("ne3d: too short"));
-        }
-        if &bytes[0..4] != b"NE3D" {
-            return Err(EngineError::other("ne3d: bad magic"));
-        }
-
-        let mut at = 4usize;
-        let read_u32 = |b: &[u8]| u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
-
-        let ver = read_u32(need(bytes, at, 4, "version")?);
-        at += 4;
-        if ver != 1 {
-            return Err(EngineError::other(format!("ne3d: unsupported version {ver}")));
-        }
-
-        let vtx_count = read_u32(need(bytes, at, 4, "vertex_count")?) as usize;
-        at += 4;
-        let idx_count = read_u32(need(bytes, at, 4, "index_count")?) as usize;
-        at += 4;
-        let flags = read_u32(need(bytes, at, 4, "flags")?);
-        at += 4;
-
-        let has_normals = (flags & 0x1) != 0;
-
-        let mut pos: Vec<[f32; 3]> = Vec::with_capacity(vtx_count);
-        let mut nrm: Vec<[f32; 3]> = Vec::with_capacity(vtx_count);
-
-        for _ in 0..vtx_count {
-            let chunk = need(bytes, at, 12, "positions")?;
-            at += 12;
-            let x = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-            let y = f32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
-            let z = f32::from_le_bytes([chunk[8], chunk[9], chunk[10], chunk[11]]);
-            pos.push([x, y, z]);
-        }
-
-        if has_normals {
-            for _ in 0..vtx_count {
-                let chunk = need(bytes, at, 12, "normals")?;
-                at += 12;
-                let x = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-                let y = f32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
-                let z = f32::from_le_bytes([chunk[8], chunk[9], chunk[10], chunk[11]]);
-                nrm.push([x, y, z]);
-            }
-        } else {
-            nrm.resize(vtx_count, [0.0, 1.0, 0.0]);
-        }
-
-        let has_uvs = (flags & 0x2) != 0;
-        if has_uvs {
-            let uv_bytes = vtx_count
-                .checked_mul(8)
-                .ok_or_else(|| EngineError::other("ne3d: uv overflow"))?;
-            let _ = need(bytes, at, uv_bytes, "uvs")?;
-            at += uv_bytes;
-        }
-
-        let mut idx: Vec<u32> = Vec::with_capacity(idx_count);
-        for _ in 0..idx_count {
-            let chunk = need(bytes, at, 4, "indices")?;
-            at += 4;
-            idx.push(u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]));
-        }
-
-        Ok((pos, nrm, idx))
-    }
-
-    #[inline]
-    fn mat4_mul(a: [f32; 16], b: [f32; 16]) -> [f32; 16] {
-        let mut o = [0.0f32; 16];
-        for c in 0..4 {
-            for r in 0..4 {
-                o[c * 4 + r] = a[0 * 4 + r] * b[c * 4 + 0]
-                    + a[1 * 4 + r] * b[c * 4 + 1]
-                    + a[2 * 4 + r] * b[c * 4 + 2]
-                    + a[3 * 4 + r] * b[c * 4 + 3];
-            }
-        }
-        o
-    }
-
-    #[inline]
-    #[inline]
-    fn mat4_perspective(fov_y_rad: f32, aspect: f32, z_near: f32, z_far: f32) -> [f32; 16] {
-        let f = 1.0 / (0.5 * fov_y_rad).tan();
-        let nf = 1.0 / (z_near - z_far);
-
-        // Column-major, RH, Vulkan clip Z: [0..1], with Y flipped (negative).
-        // Matrix (row form):
-        // [ f/aspect, 0,   0,                     0 ]
-        // [ 0,       -f,   0,                     0 ]
-        // [ 0,        0,  z_far*nf,  z_far*z_near*nf ]
-        // [ 0,        0,  -1,                    0 ]
-        //
-        // Column-major memory layout:
-        // [ m00 m10 m20 m30 | m01 m11 m21 m31 | m02 m12 m22 m32 | m03 m13 m23 m33 ]
-        [
-            f / aspect, 0.0, 0.0, 0.0, //
-            0.0, -f, 0.0, 0.0,        //
-            0.0, 0.0, z_far * nf, -1.0, //
-            0.0, 0.0, z_far * z_near * nf, 0.0, //
-        ]
-    }
-
-
-    #[inline]
-    fn vec3_sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
-        [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
-    }
-
-    #[inline]
-    fn vec3_dot(a: [f32; 3], b: [f32; 3]) -> f32 {
-        a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
-    }
-
-    #[inline]
-    fn vec3_cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
-        [
-            a[1] * b[2] - a[2] * b[1],
-            a[2] * b[0] - a[0] * b[2],
-            a[0] * b[1] - a[1] * b[0],
-        ]
-    }
-
-    #[inline]
-    fn vec3_norm(v: [f32; 3]) -> [f32; 3] {
-        let l2 = Self::vec3_dot(v, v);
-        if l2 <= 0.0 {
-            return [0.0, 0.0, 0.0];
-        }
-        let inv = 1.0 / l2.sqrt();
-        [v[0] * inv, v[1] * inv, v[2] * inv]
-    }
-
-    #[inline]
-    fn mat4_scale_uniform(s: f32) -> [f32; 16] {
-        [
-            s, 0.0, 0.0, 0.0,
-            0.0, s, 0.0, 0.0,
-            0.0, 0.0, s, 0.0,
-            0.0, 0.0, 0.0, 1.0,
-        ]
-    }
-
-
-    #[inline]
-    fn mat4_look_at(eye: [f32; 3], center: [f32; 3], up: [f32; 3]) -> [f32; 16] {
-        let f = Self::vec3_norm(Self::vec3_sub(center, eye));
-        let s = Self::vec3_norm(Self::vec3_cross(f, up));
-        let u = Self::vec3_cross(s, f);
-
-        let tx = -Self::vec3_dot(s, eye);
-        let ty = -Self::vec3_dot(u, eye);
-        let tz = Self::vec3_dot(f, eye);
-
-        // Column-major view matrix:
-        // col0 = [ s.x, s.y, s.z, tx ]
-        // col1 = [ u.x, u.y, u.z, ty ]
-        // col2 = [ -f.x, -f.y, -f.z, tz ]
-        // col3 = [ 0, 0, 0, 1 ]
-        [
-            s[0], s[1], s[2], tx, //
-            u[0], u[1], u[2], ty, //
-            -f[0], -f[1], -f[2], tz, //
-            0.0, 0.0, 0.0, 1.0,
-        ]
-    }
-
-    #[inline]
-    fn mat4_rotation_y(a: f32) -> [f32; 16] {
-        let (s, c) = a.sin_cos();
-        // Column-major for:
-        // [ c, 0,  s, 0 ]
-        // [ 0, 1,  0, 0 ]
-        // [ -s,0,  c, 0 ]
-        // [ 0, 0,  0, 1 ]
-        [
-            c, 0.0, -s, 0.0, //
-            0.0, 1.0, 0.0, 0.0, //
-            s, 0.0, c, 0.0, //
-            0.0, 0.0, 0.0, 1.0,
-        ]
-    }
-
     fn compile_glsl(
         compiler: &Compiler,
         kind: ShaderKind,
@@ -392,7 +239,6 @@ void main() {
         &mut self,
         ctx: &ModuleCtx<'_, impl Send + 'static>,
         r: &mut dyn newengine_core::render::RenderApi,
-        target: Extent2D,
     ) -> EngineResult<()> {
         if self.model.is_some() || self.model_loaded_once {
             return Ok(());
@@ -410,81 +256,11 @@ void main() {
         let model = Model3dReader::from_blob_parts(blob.meta_json.as_ref(), &blob.payload)
             .map_err(|e| EngineError::other(format!("model: decode failed: {e}")))?;
 
-
-        let (pos, nrm, idx) = Self::decode_ne3d_mesh(&model.payload)?;
-        if pos.is_empty() || idx.is_empty() {
-            return Err(EngineError::other("model: empty geometry"));
-        }
-
-        let mut bb_min = [f32::INFINITY; 3];
-        let mut bb_max = [f32::NEG_INFINITY; 3];
-        for p in &pos {
-            bb_min[0] = bb_min[0].min(p[0]);
-            bb_min[1] = bb_min[1].min(p[1]);
-            bb_min[2] = bb_min[2].min(p[2]);
-            bb_max[0] = bb_max[0].max(p[0]);
-            bb_max[1] = bb_max[1].max(p[1]);
-            bb_max[2] = bb_max[2].max(p[2]);
-        }
-
-        let center = [
-            (bb_min[0] + bb_max[0]) * 0.5,
-            (bb_min[1] + bb_max[1]) * 0.5,
-            (bb_min[2] + bb_max[2]) * 0.5,
-        ];
-        let ext = [
-            (bb_max[0] - bb_min[0]).abs(),
-            (bb_max[1] - bb_min[1]).abs(),
-            (bb_max[2] - bb_min[2]).abs(),
-        ];
-        let radius = (0.5 * ext[0].max(ext[1]).max(ext[2])).max(0.001);
-        let inv_radius = 1.0 / radius;
-
-        let stride = 6 * std::mem::size_of::<f32>();
-        let mut vbytes: Vec<u8> = Vec::with_capacity(pos.len() * stride);
-
-        for (p, n) in pos.iter().zip(nrm.iter()) {
-            let px = (p[0] - center[0]) * inv_radius;
-            let py = (p[1] - center[1]) * inv_radius;
-            let pz = (p[2] - center[2]) * inv_radius;
-
-            vbytes.extend_from_slice(&px.to_ne_bytes());
-            vbytes.extend_from_slice(&py.to_ne_bytes());
-            vbytes.extend_from_slice(&pz.to_ne_bytes());
-            vbytes.extend_from_slice(&n[0].to_ne_bytes());
-            vbytes.extend_from_slice(&n[1].to_ne_bytes());
-            vbytes.extend_from_slice(&n[2].to_ne_bytes());
-        }
-
-        let mut ibytes: Vec<u8> = Vec::with_capacity(idx.len() * 4);
-        for i in &idx {
-            ibytes.extend_from_slice(&i.to_ne_bytes());
-        }
-
-        let vb = r.create_buffer(
-            BufferDesc::new(vbytes.len() as u64, BufferUsage::Vertex, MemoryHint::CpuToGpu)
-                .with_label("editor_model_vb"),
-        )?;
-        r.write_buffer(vb, 0, &vbytes)?;
-
-        let ib = r.create_buffer(
-            BufferDesc::new(ibytes.len() as u64, BufferUsage::Index, MemoryHint::CpuToGpu)
-                .with_label("editor_model_ib"),
-        )?;
-        r.write_buffer(ib, 0, &ibytes)?;
-
-        let ubo = r.create_buffer(
-            BufferDesc::new(64, BufferUsage::Uniform, MemoryHint::CpuToGpu).with_label("editor_model_ubo"),
-        )?;
-
-        let bgl = r.create_bind_group_layout(
-            BindGroupLayoutDesc::new(vec![BindingKind::UniformBuffer]).with_label("editor_model_bgl"),
-        )?;
-        let bg = r.create_bind_group(
-            BindGroupDesc::new(bgl)
-                .with_label("editor_model_bg")
-                .with_uniform0(BufferBinding::new(ubo, 0, 64)),
-        )?;
+        let mesh = MeshAsset::from_model3d(&model)
+            .map_err(|e| EngineError::other(format!("model: {e}")))?;
+        let mesh_gpu = self
+            .assets
+            .upload_mesh(r, AssetId::from_key(&AssetKey::new(MODEL_PATH, 0)), &mesh)?;
 
         let compiler = Compiler::new().ok_or_else(|| EngineError::other("shaderc: Compiler"))?;
 
@@ -493,14 +269,25 @@ layout(location = 0) in vec3 a_pos;
 layout(location = 1) in vec3 a_nrm;
 
 layout(set = 0, binding = 0) uniform Ubo {
-    mat4 u_mvp;
+    mat4 u_view_proj;
 } u;
 
+struct Instance {
+    mat4 model;
+    vec4 custom;
+    uvec4 skin;
+};
+
+layout(std430, set = 1, binding = 0) readonly buffer Instances {
+    Instance instances[];
+};
+
 layout(location = 0) out vec3 v_nrm;
 
 void main() {
-    v_nrm = a_nrm;
-    gl_Position = u.u_mvp * vec4(a_pos, 1.0);
+    mat4 model = instances[gl_InstanceIndex].model;
+    v_nrm = mat3(model) * a_nrm;
+    gl_Position = u.u_view_proj * model * vec4(a_pos, 1.0);
 }
 "#;
 
@@ -519,69 +306,91 @@ void main() {
         let vs_spv = Self::compile_glsl(&compiler, ShaderKind::Vertex, "editor_model.vert", VS_SRC)?;
         let fs_spv = Self::compile_glsl(&compiler, ShaderKind::Fragment, "editor_model.frag", FS_SRC)?;
 
-        let vs = r.create_shader(
-            ShaderDesc::new(ShaderStage::Vertex, "main", vs_spv).with_label("editor_model_vs"),
-        )?;
-        let fs = r.create_shader(
-            ShaderDesc::new(ShaderStage::Fragment, "main", fs_spv).with_label("editor_model_fs"),
+        let material = MaterialAsset::new("editor/model.vert", "editor/model.frag");
+        let instances = self.batcher.layout(r)?;
+        self.assets.set_instance_layout(Some(instances));
+        let material_gpu = self.assets.upload_material(
+            r,
+            AssetId::from_key(&AssetKey::new("editor/model.nemat", 0)),
+            &material,
+            vs_spv,
+            fs_spv,
         )?;
 
-        let layout = VertexLayout::new(
-            stride as u32,
-            vec![
-                VertexAttribute::new(0, 0, VertexFormat::Float32x3),
-                VertexAttribute::new(
-                    1,
-                    (3 * std::mem::size_of::<f32>()) as u32,
-                    VertexFormat::Float32x3,
-                ),
-            ],
-        );
+        self.model = Some(ModelGpu {
+            mesh: mesh_gpu,
+            material: material_gpu,
+        });
+        if let Some(p) = self.preview.as_mut() {
+            p.dirty = true;
+        }
 
-        let pipeline = r.create_pipeline(
-            PipelineDesc::new(vs, fs, TextureFormat::Bgra8Unorm)
-                .with_depth(TextureFormat::Depth32Float)
-                .with_label("editor_model_pipeline")
-                .with_topology(PrimitiveTopology::TriangleList)
-                .with_vertex_layouts(vec![layout])
-                .with_bind_group_layouts(vec![bgl]),
-        )?;
+        log::info!(
+            "model: loaded '{MODEL_PATH}' vertices={} indices={} radius={:.3}",
+            mesh_gpu.vertex_count,
+            mesh_gpu.index_count,
+            mesh_gpu.radius
+        );
 
-        let aspect = if target.height == 0 {
-            1.0
-        } else {
-            target.width as f32 / target.height as f32
-        };
+        Ok(())
+    }
 
-        let proj = Self::mat4_perspective(60.0f32.to_radians(), aspect, 0.01, 1000.0);
-        let view = Self::mat4_look_at([2.6, 1.8, 2.6], [0.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
-        let mvp = Self::mat4_mul(proj, view);
+    /// Renders the model (or the demo triangle) into the preview target when it changed.
+    fn render_preview(&mut self, r: &mut dyn RenderApi) -> EngineResult<()> {
+        if self.preview_unsupported {
+            return Ok(());
+        }
 
-        let mut ubytes: Vec<u8> = Vec::with_capacity(64);
-        for f in mvp {
-            ubytes.extend_from_slice(&f.to_ne_bytes());
+        let extent = Extent2D::new(PREVIEW_SIZE, PREVIEW_SIZE);
+        let preview = match self.preview {
+            Some(p) => p,
+            None => match r.create_render_target(extent, TextureFormat::Bgra8Unorm) {
+                Ok(target) => *self.preview.insert(PreviewGpu { target, dirty: true }),
+                Err(e) => {
+                    log::info!("preview: disabled ({e})");
+                    self.preview_unsupported = true;
+                    return Ok(());
+                }
+            },
+        };
+        if !preview.dirty {
+            return Ok(());
         }
-        r.write_buffer(ubo, 0, &ubytes)?;
 
-        self.model = Some(ModelGpu {
-            vb,
-            ib,
-            ubo,
-            bgl,
-            bg,
-            vs,
-            fs,
-            pipeline,
-            index_count: idx.len() as u32,
-        });
+        r.begin_render_target(preview.target, PREVIEW_CLEAR)?;
+        r.set_viewport(Viewport::full(extent))?;
+        r.set_scissor(RectI32::new(0, 0, PREVIEW_SIZE as i32, PREVIEW_SIZE as i32))?;
+
+        if let Some(ModelGpu { mesh, material }) = self.model {
+            let mut cam = CameraState::default();
+            cam.set_viewport(PREVIEW_SIZE, PREVIEW_SIZE);
+            cam.look_at(Vec3::new(1.6, 1.1, 1.6), Vec3::ZERO, Vec3::Y);
+            let (m, _) = cam.update(None, 0.0);
+
+            let model = Mat4::from_scale(Vec3::splat(1.0 / mesh.radius.max(0.001)))
+                * Mat4::from_translation(-Vec3::from_array(mesh.center));
+
+            self.list.clear();
+            self.list.push_static(mesh, material, model.to_cols_array());
+            self.batcher.draw(r, &self.list, &m.view_proj.to_cols_array())?;
+        } else if let Some(demo) = self.demo {
+            r.set_pipeline(demo.pipeline)?;
+            r.set_vertex_buffer(0, BufferSlice::new(demo.vb, 0))?;
+            r.draw(newengine_core::render::DrawArgs::new(3))?;
+        }
 
-        log::info!(
-            "model: loaded '{MODEL_PATH}' vertices={} indices={} radius={:.3}",
-            pos.len(),
-            idx.len(),
-            radius
-        );
+        r.end_render_target()?;
 
+        let texture = r.ui_texture(preview.target)?;
+        if let Ok(mut g) = PREVIEW.lock() {
+            *g = Some(PreviewImage {
+                texture,
+                size: [PREVIEW_SIZE, PREVIEW_SIZE],
+            });
+        }
+        if let Some(p) = self.preview.as_mut() {
+            p.dirty = false;
+        }
         Ok(())
     }
 }
@@ -591,7 +400,17 @@ impl<E: Send + 'static> Module<E> for EditorRenderController {
         "app.render_controller"
     }
 
+    fn init(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        self.device_resets = Some(ctx.events().subscribe::<RenderDeviceReset>());
+        Ok(())
+    }
+
     fn render(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if let Some(reset) = self.device_resets.as_ref().and_then(|s| s.try_recv()) {
+            log::info!("render: device reset generation={}; rebuilding GPU resources", reset.generation);
+            self.forget_gpu();
+        }
+
         let ui: Option<UiDrawList> = ctx.resources_mut().remove::<UiDrawList>();
 
         let (w, h) = ctx
@@ -600,6 +419,16 @@ impl<E: Send + 'static> Module<E> for EditorRenderController {
             .map(|s| (s.width, s.height))
             .unwrap_or((0, 0));
 
+        let view_proj = ctx
+            .resources_mut()
+            .get_mut::<ActiveCamera>()
+            .map_or(Mat4::IDENTITY, |c| {
+                if w > 0 && h > 0 {
+                    c.set_viewport(w, h);
+                }
+                c.matrices().view_proj
+            });
+
         let api = match require_render_api(ctx) {
             Ok(api) => api,
             Err(_) => return Ok(()),
@@ -619,8 +448,10 @@ impl<E: Send + 'static> Module<E> for EditorRenderController {
 
         self.build_demo(&mut **r)?;
         if w > 0 && h > 0 {
-            self.build_model(ctx, &mut **r, Extent2D::new(w, h))?;
+            self.build_model(ctx, &mut **r)?;
         }
+        self.batcher.begin_frame(&mut **r);
+        self.render_preview(&mut **r)?;
 
         r.begin_frame(BeginFrameDesc::new(self.clear_color))?;
 
@@ -629,27 +460,17 @@ impl<E: Send + 'static> Module<E> for EditorRenderController {
             r.set_viewport(Viewport::full(extent))?;
             r.set_scissor(RectI32::new(0, 0, w as i32, h as i32))?;
 
-            if let Some(model) = self.model {
-                let aspect = w as f32 / (h.max(1) as f32);
-                let proj = Self::mat4_perspective(60.0f32.to_radians(), aspect, 0.01, 1000.0);
-
+            if let Some(ModelGpu { mesh, material }) = self.model {
                 let a = (ctx.frame.unwrap().frame_index as f32) * 0.01;
-                let rot = Self::mat4_rotation_y(a);
-                let view = Self::mat4_look_at([2.6, 1.8, 2.6], [0.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
-
-                let mvp = Self::mat4_mul(Self::mat4_mul(proj, view), rot);
-
-                let mut ubytes: Vec<u8> = Vec::with_capacity(64);
-                for f in mvp {
-                    ubytes.extend_from_slice(&f.to_ne_bytes());
-                }
-                r.write_buffer(model.ubo, 0, &ubytes)?;
-
-                r.set_pipeline(model.pipeline)?;
-                r.set_bind_group(0, model.bg)?;
-                r.set_vertex_buffer(0, BufferSlice::new(model.vb, 0))?;
-                r.set_index_buffer(BufferSlice::new(model.ib, 0), IndexFormat::U32)?;
-                r.draw_indexed(DrawIndexedArgs::new(model.index_count))?;
+                // Fit the mesh into the unit sphere around the origin.
+                let model = Mat4::from_rotation_y(a)
+                    * Mat4::from_scale(Vec3::splat(1.0 / mesh.radius.max(0.001)))
+                    * Mat4::from_translation(-Vec3::from_array(mesh.center));
+
+                self.list.clear();
+                self.list.push_static(mesh, material, model.to_cols_array());
+                self.batcher
+                    .draw(&mut **r, &self.list, &view_proj.to_cols_array())?;
             } else if let Some(demo) = self.demo {
                 r.set_pipeline(demo.pipeline)?;
                 r.set_vertex_buffer(0, BufferSlice::new(demo.vb, 0))?;
@@ -658,6 +479,10 @@ impl<E: Send + 'static> Module<E> for EditorRenderController {
         }
 
         r.end_frame()?;
+        drop(r);
+
+        // Batching effectiveness of this frame, for overlays and diagnostics.
+        ctx.resources_mut().insert(self.batcher.stats());
         Ok(())
     }
 }
\ No newline at end of file
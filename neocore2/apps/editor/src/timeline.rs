@@ -0,0 +1,417 @@
+use newengine_platform_winit::egui;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+const FRAMES: usize = 240;
+const POLL_EVERY: Duration = Duration::from_millis(250);
+const STRIP_H: f32 = 64.0;
+const LANE_H: f32 = 18.0;
+const LABEL_W: f32 = 150.0;
+/// Frames slower than this multiple of the median are drawn as hitches.
+const HITCH_FACTOR: f32 = 1.5;
+
+const KINDS: [(&str, &str); 5] = [
+    ("stage", "Stages"),
+    ("event", "Events"),
+    ("bus", "Bus"),
+    ("asset", "Assets"),
+    ("marker", "Markers"),
+];
+
+#[derive(Debug, Deserialize, Clone)]
+struct TraceRecord {
+    #[serde(default)]
+    at_us: u64,
+    #[serde(default)]
+    dur_us: u64,
+    #[serde(default)]
+    kind: String,
+    #[serde(default)]
+    module: Option<String>,
+    #[serde(default)]
+    topic: String,
+    #[serde(default)]
+    detail: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct TraceFrame {
+    frame: u64,
+    #[serde(default)]
+    dur_us: u64,
+    #[serde(default)]
+    dropped: u32,
+    #[serde(default)]
+    records: Vec<TraceRecord>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct FramesResp {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    frames: Vec<TraceFrame>,
+}
+
+#[inline]
+fn kind_color(kind: &str) -> egui::Color32 {
+    match kind {
+        "stage" => egui::Color32::from_rgb(90, 150, 220),
+        "event" => egui::Color32::from_rgb(90, 180, 110),
+        "bus" => egui::Color32::from_rgb(170, 120, 210),
+        "asset" => egui::Color32::from_rgb(230, 150, 40),
+        "marker" => egui::Color32::from_rgb(220, 70, 70),
+        _ => egui::Color32::from_gray(150),
+    }
+}
+
+#[inline]
+fn split_list(s: &str) -> Vec<&str> {
+    s.split(',').map(str::trim).filter(|x| !x.is_empty()).collect()
+}
+
+/// Per-frame timeline of events, bus messages, module stages and asset transitions,
+/// backed by the `engine.trace` service.
+#[derive(Debug)]
+pub(crate) struct TimelinePanel {
+    pub(crate) open: bool,
+    recording: bool,
+    live: bool,
+    modules: String,
+    topics: String,
+    kinds: [bool; KINDS.len()],
+    frames: Vec<TraceFrame>,
+    /// Engine frame index; survives refreshes while the frame is retained.
+    selected: Option<u64>,
+    last_poll: Option<Instant>,
+    error: Option<String>,
+}
+
+impl Default for TimelinePanel {
+    fn default() -> Self {
+        Self {
+            open: false,
+            recording: false,
+            live: true,
+            modules: String::new(),
+            topics: String::new(),
+            kinds: [true; KINDS.len()],
+            frames: Vec::new(),
+            selected: None,
+            last_poll: None,
+            error: None,
+        }
+    }
+}
+
+impl TimelinePanel {
+    /// Opening the panel starts recording; closing it leaves the trace as it is.
+    pub(crate) fn toggle(&mut self) {
+        self.open = !self.open;
+        if self.open && !self.recording {
+            self.set_recording(true);
+        }
+    }
+
+    fn set_recording(&mut self, enabled: bool) {
+        let req = json!({ "enabled": enabled }).to_string();
+        match newengine_core::call_service_v1("engine.trace", "trace.enable", req.as_bytes()) {
+            Ok(_) => self.recording = enabled,
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.last_poll = Some(Instant::now());
+
+        let kinds: Vec<&str> = KINDS
+            .iter()
+            .zip(self.kinds.iter())
+            .filter(|(_, on)| **on)
+            .map(|((k, _), _)| *k)
+            .collect();
+        let req = json!({
+            "frames": FRAMES,
+            "modules": split_list(&self.modules),
+            "topics": split_list(&self.topics),
+            "kinds": kinds,
+        })
+        .to_string();
+
+        match newengine_core::call_service_v1("engine.trace", "trace.frames_json", req.as_bytes()) {
+            Ok(bytes) => match serde_json::from_slice::<FramesResp>(&bytes) {
+                Ok(resp) => {
+                    self.error = None;
+                    self.recording = resp.enabled;
+                    self.frames = resp.frames;
+                }
+                Err(e) => self.error = Some(format!("bad response json: {e}")),
+            },
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    pub(crate) fn ui(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        let due = !matches!(self.last_poll, Some(t) if t.elapsed() < POLL_EVERY);
+        if self.live && due {
+            self.refresh();
+        }
+        if self.live && self.recording {
+            ctx.request_repaint_after(POLL_EVERY);
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Event timeline")
+            .open(&mut open)
+            .default_size([900.0, 520.0])
+            .resizable(true)
+            .show(ctx, |ui| {
+                self.toolbar(ui);
+                ui.separator();
+
+                if let Some(e) = &self.error {
+                    ui.colored_label(egui::Color32::from_rgb(255, 96, 96), e);
+                }
+
+                self.frame_strip(ui);
+                ui.separator();
+                self.frame_detail(ui);
+            });
+        self.open = open;
+    }
+
+    fn toolbar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let mut recording = self.recording;
+            if ui.checkbox(&mut recording, "Record").changed() {
+                self.set_recording(recording);
+            }
+            ui.checkbox(&mut self.live, "Live")
+                .on_hover_text("Refresh while recording; turn off to inspect a fixed window");
+            if ui.button("Refresh").clicked() {
+                self.refresh();
+            }
+            if ui.button("Clear").clicked() {
+                let _ = newengine_core::call_service_v1("engine.trace", "trace.clear", &[]);
+                self.frames.clear();
+                self.selected = None;
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let mut changed = false;
+            ui.label("Modules:");
+            changed |= ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.modules)
+                        .desired_width(160.0)
+                        .hint_text("render, camera")
+                        .font(egui::TextStyle::Monospace),
+                )
+                .changed();
+            ui.label("Topics:");
+            changed |= ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.topics)
+                        .desired_width(160.0)
+                        .hint_text("resized, swapchain")
+                        .font(egui::TextStyle::Monospace),
+                )
+                .changed();
+            for ((kind, label), on) in KINDS.iter().zip(self.kinds.iter_mut()) {
+                changed |= ui
+                    .checkbox(on, egui::RichText::new(*label).color(kind_color(kind)))
+                    .changed();
+            }
+            if changed {
+                self.refresh();
+            }
+        });
+    }
+
+    /// One bar per frame, height by duration; hitches in red, frames with markers or asset
+    /// transitions get a dot above the bar.
+    fn frame_strip(&mut self, ui: &mut egui::Ui) {
+        if self.frames.is_empty() {
+            ui.label(if self.recording {
+                "Waiting for frames..."
+            } else {
+                "Not recording. Enable 'Record' to trace frames."
+            });
+            return;
+        }
+
+        let mut sorted: Vec<u64> = self.frames.iter().map(|f| f.dur_us).collect();
+        sorted.sort_unstable();
+        let median = sorted[sorted.len() / 2].max(1) as f32;
+        let max = (*sorted.last().unwrap_or(&1)).max(1) as f32;
+
+        let width = ui.available_width();
+        let (rect, resp) =
+            ui.allocate_exact_size(egui::vec2(width, STRIP_H + 8.0), egui::Sense::click());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, egui::Color32::from_gray(20));
+
+        let n = self.frames.len();
+        let bar_w = (width / FRAMES as f32).max(1.0);
+        let x0 = rect.right() - n as f32 * bar_w;
+
+        for (i, f) in self.frames.iter().enumerate() {
+            let h = (f.dur_us as f32 / max) * STRIP_H;
+            let x = x0 + i as f32 * bar_w;
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(x, rect.bottom() - h),
+                egui::pos2(x + bar_w - 1.0, rect.bottom()),
+            );
+            let color = if Some(f.frame) == self.selected {
+                egui::Color32::WHITE
+            } else if f.dur_us as f32 > median * HITCH_FACTOR {
+                egui::Color32::from_rgb(220, 70, 70)
+            } else {
+                egui::Color32::from_rgb(90, 150, 220)
+            };
+            painter.rect_filled(bar, 0.0, color);
+
+            let flagged = f
+                .records
+                .iter()
+                .find(|r| r.kind == "marker" || r.kind == "asset")
+                .map(|r| kind_color(&r.kind));
+            if let Some(c) = flagged {
+                painter.circle_filled(egui::pos2(x + bar_w * 0.5, rect.top() + 4.0), 2.5, c);
+            }
+        }
+
+        let median_y = rect.bottom() - (median / max) * STRIP_H;
+        painter.hline(
+            rect.x_range(),
+            median_y,
+            egui::Stroke::new(1.0, egui::Color32::from_gray(90)),
+        );
+
+        if let Some(p) = resp.hover_pos() {
+            let i = ((p.x - x0) / bar_w).floor();
+            if i >= 0.0 && (i as usize) < n {
+                let f = &self.frames[i as usize];
+                resp.clone().on_hover_text(format!(
+                    "frame {}\n{:.2} ms (median {:.2} ms)\n{} records",
+                    f.frame,
+                    f.dur_us as f32 / 1000.0,
+                    median / 1000.0,
+                    f.records.len()
+                ));
+                if resp.clicked() {
+                    self.selected = Some(f.frame);
+                    self.live = false;
+                }
+            }
+        }
+    }
+
+    /// Module stage spans on a per-frame time axis, then every record in order.
+    fn frame_detail(&self, ui: &mut egui::Ui) {
+        let Some(f) = self
+            .selected
+            .and_then(|s| self.frames.iter().find(|f| f.frame == s))
+        else {
+            ui.label("Click a frame to inspect it; live refresh pauses while one is selected.");
+            return;
+        };
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new(format!("frame {}", f.frame)).monospace().strong());
+            ui.label(format!("{:.2} ms", f.dur_us as f32 / 1000.0));
+            ui.label(format!("{} records", f.records.len()));
+            if f.dropped > 0 {
+                ui.colored_label(
+                    egui::Color32::from_rgb(230, 150, 40),
+                    format!("{} dropped", f.dropped),
+                );
+            }
+        });
+
+        let lanes: BTreeSet<&str> = f
+            .records
+            .iter()
+            .filter(|r| r.kind == "stage")
+            .filter_map(|r| r.module.as_deref())
+            .collect();
+
+        if !lanes.is_empty() {
+            let width = ui.available_width();
+            let height = lanes.len() as f32 * LANE_H + LANE_H;
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            let span = (rect.width() - LABEL_W).max(1.0);
+            let scale = span / f.dur_us.max(1) as f32;
+            let x_at = |us: u64| rect.left() + LABEL_W + us as f32 * scale;
+
+            for (row, lane) in lanes.iter().enumerate() {
+                let y = rect.top() + row as f32 * LANE_H;
+                painter.text(
+                    egui::pos2(rect.left(), y + 2.0),
+                    egui::Align2::LEFT_TOP,
+                    lane,
+                    egui::FontId::monospace(11.0),
+                    egui::Color32::from_gray(200),
+                );
+                for r in f.records.iter() {
+                    if r.kind != "stage" || r.module.as_deref() != Some(*lane) {
+                        continue;
+                    }
+                    let bar = egui::Rect::from_min_max(
+                        egui::pos2(x_at(r.at_us), y + 2.0),
+                        egui::pos2(
+                            x_at(r.at_us + r.dur_us).max(x_at(r.at_us) + 1.0),
+                            y + LANE_H - 2.0,
+                        ),
+                    );
+                    painter.rect_filled(bar, 2.0, kind_color("stage"));
+                }
+            }
+
+            // Instant records as ticks on the bottom row, so causes line up with the stages.
+            let y = rect.top() + lanes.len() as f32 * LANE_H;
+            for r in f.records.iter().filter(|r| r.kind != "stage") {
+                let x = x_at(r.at_us);
+                painter.vline(
+                    x,
+                    egui::Rangef::new(y + 2.0, y + LANE_H - 2.0),
+                    egui::Stroke::new(2.0, kind_color(&r.kind)),
+                );
+            }
+        }
+
+        ui.separator();
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            egui::Grid::new("trace_records")
+                .striped(true)
+                .num_columns(5)
+                .show(ui, |ui| {
+                    for r in f.records.iter() {
+                        let at_ms = r.at_us as f32 / 1000.0;
+                        let time = if r.dur_us > 0 {
+                            format!("+{at_ms:.3} ms ({:.3} ms)", r.dur_us as f32 / 1000.0)
+                        } else {
+                            format!("+{at_ms:.3} ms")
+                        };
+                        let module = r.module.as_deref().unwrap_or("-");
+                        let detail_color = egui::Color32::from_gray(170);
+
+                        ui.label(egui::RichText::new(time).monospace());
+                        ui.label(egui::RichText::new(&r.kind).monospace().color(kind_color(&r.kind)));
+                        ui.label(egui::RichText::new(module).monospace());
+                        ui.label(egui::RichText::new(&r.topic).monospace().strong());
+                        ui.label(egui::RichText::new(&r.detail).monospace().color(detail_color));
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
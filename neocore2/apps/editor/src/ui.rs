@@ -1,10 +1,24 @@
 use newengine_platform_winit::{egui, UiBuildFn};
-use newengine_ui::markup::{UiMarkupDoc, UiState};
+use newengine_ui::console::{ConsoleApi, ConsoleLineKind};
+use newengine_ui::markup::{
+    UiAction, UiBindings, UiEvent, UiEventKind, UiMarkupDoc, UiState, UiStateSync,
+};
 use serde::Deserialize;
 use std::any::Any;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use newengine_core::host_events::KeyCode;
+use crate::asset_browser::AssetTree;
+use crate::dep_graph::DepGraphPanel;
+use crate::diagnostics::DiagnosticsPanel;
+use crate::palette::{asset_query_revision, collect_assets, CommandPalette, EditorOp, PaletteAction};
+use crate::timeline::TimelinePanel;
+
+/// The asset browser lists far more than the palette; rows are virtualized.
+const MAX_BROWSER_ASSETS: usize = 64 * 1024;
+
+/// How often the browser asks the asset store whether its listing changed.
+const ASSET_POLL_EVERY: Duration = Duration::from_millis(500);
 
 #[derive(Debug, Deserialize, Default)]
 struct InputKeysTakeResponse {
@@ -14,589 +28,154 @@ struct InputKeysTakeResponse {
     released: Vec<u32>,
 }
 
-#[derive(Debug, Deserialize)]
-struct CommandExecResponse {
-    ok: bool,
-    #[serde(default)]
-    output: Option<String>,
-    #[serde(default)]
-    error: Option<String>,
-}
-
-#[derive(Debug, Deserialize, Clone, Default)]
-struct SuggestItem {
-    #[serde(default)]
-    kind: String,
-    #[serde(default)]
-    display: String,
-    #[serde(default)]
-    insert: String,
-    #[serde(default)]
-    help: String,
-    #[serde(default)]
-    usage: String,
+/// Drains this frame's key presses from the Input plugin.
+///
+/// Keys must come from the Input plugin (DLL). This keeps editor UI independent from
+/// winit/egui key handling and makes it work with any future platform backend.
+fn take_input_keys() -> Vec<u32> {
+    let Ok(bytes) = newengine_core::call_service_v1("kalitech.input.v1", "keys_take_json", &[])
+    else {
+        return Vec::new();
+    };
+
+    serde_json::from_slice::<InputKeysTakeResponse>(&bytes)
+        .map(|r| r.pressed)
+        .unwrap_or_default()
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
-struct SuggestResponse {
-    #[serde(default)]
-    signature: String,
-    #[serde(default)]
-    items: Vec<SuggestItem>,
-}
-
-#[derive(Debug)]
-struct ConsoleUi {
-    open: bool,
-    input: String,
-
-    // Keyboard edges are sourced from the Input plugin (DLL), not from egui/winit.
-    frame_keys_pressed: Vec<u32>,
-
-    lines: Vec<String>,
-    stick_to_bottom: bool,
-
-    filter: String,
-
-    history: Vec<String>,
-    hist_cursor: usize,
-
-    suggest: SuggestResponse,
-    suggest_open: bool,
-    suggest_selected: usize,
-    last_suggest_input: String,
+pub struct EditorUiBuild {
+    shared_doc: Arc<Mutex<Option<UiMarkupDoc>>>,
+    state: UiState,
+    palette: CommandPalette,
+    dep_graph: DepGraphPanel,
+    timeline: TimelinePanel,
+    diagnostics: DiagnosticsPanel,
+    preview_open: bool,
+    remote: UiStateSync,
+    /// `None` until the first frame and after a rescan request.
+    asset_tree: Option<Arc<AssetTree>>,
+    asset_rev: u64,
+    /// Store query revision the tree was built at.
+    asset_query_rev: Option<u64>,
+    asset_last_poll: Option<Instant>,
 }
 
-impl Default for ConsoleUi {
-    fn default() -> Self {
+impl EditorUiBuild {
+    #[inline]
+    pub fn new(shared_doc: Arc<Mutex<Option<UiMarkupDoc>>>) -> Self {
+        let mut state = UiState::default();
+        state.set_bindings(UiBindings::shared());
+        state.bindings().set("app.name", "NewEngine Editor");
         Self {
-            open: false,
-            input: String::new(),
-
-            frame_keys_pressed: Vec::new(),
-
-            lines: Vec::new(),
-            stick_to_bottom: true,
-
-            filter: String::new(),
-
-            history: Vec::new(),
-            hist_cursor: 0,
-
-            suggest: SuggestResponse {
-                signature: String::new(),
-                items: Vec::new(),
-            },
-            suggest_open: false,
-            suggest_selected: 0,
-            last_suggest_input: String::new(),
+            shared_doc,
+            state,
+            palette: CommandPalette::new(),
+            dep_graph: DepGraphPanel::default(),
+            timeline: TimelinePanel::default(),
+            diagnostics: DiagnosticsPanel::default(),
+            preview_open: false,
+            remote: UiStateSync::new(),
+            asset_tree: None,
+            asset_rev: 0,
+            asset_query_rev: None,
+            asset_last_poll: None,
         }
     }
-}
-
-impl ConsoleUi {
-    #[inline]
-    fn poll_input_keys(&mut self) {
-        self.frame_keys_pressed.clear();
-
-        // Keys must come from the Input plugin (DLL). This keeps the console independent from
-        // winit/egui key handling and makes it work with any future platform backend.
-        let Ok(bytes) = newengine_core::call_service_v1("kalitech.input.v1", "keys_take_json", &[])
-        else {
-            return;
-        };
 
-        let Ok(r) = serde_json::from_slice::<InputKeysTakeResponse>(&bytes) else {
+    /// Rebuilds the tree when the store's listing changed since the last build.
+    fn poll_asset_browser(&mut self) {
+        if self.asset_tree.is_some()
+            && matches!(self.asset_last_poll, Some(t) if t.elapsed() < ASSET_POLL_EVERY)
+        {
             return;
-        };
-
-        self.frame_keys_pressed = r.pressed;
-    }
-
-    #[inline]
-    fn key_pressed_any(&self, codes: &[u32]) -> bool {
-        self.frame_keys_pressed
-            .iter()
-            .any(|k| codes.iter().any(|c| c == k))
-    }
-
-    #[inline]
-    fn key_pressed(&self, code: u32) -> bool {
-        self.frame_keys_pressed.iter().any(|k| *k == code)
-    }
+        }
+        self.asset_last_poll = Some(Instant::now());
 
-    fn toggle_hotkey(&mut self) {
-        // Backtick is not part of newengine_core::host_events::KeyCode by design.
-        // We support several common encodings and rely on the platform layer to feed a stable
-        // key code into the input plugin.
-        const BACKTICK: [u32; 3] = [192, 96, 41];
-        if self.key_pressed_any(&BACKTICK) {
-            self.open = !self.open;
-            self.suggest_open = false;
+        let rev = asset_query_revision();
+        if self.asset_tree.is_none() || rev != self.asset_query_rev {
+            self.asset_query_rev = rev;
+            self.refresh_asset_browser();
         }
     }
 
-    fn ui(&mut self, ctx: &egui::Context) {
-        self.poll_input_keys();
-        self.toggle_hotkey();
-
-        if !self.open {
+    /// Re-queries the asset store and feeds the `<tree id="assets">` browser.
+    fn refresh_asset_browser(&mut self) {
+        let paths = collect_assets(MAX_BROWSER_ASSETS);
+        self.asset_rev += 1;
+        let tree = Arc::new(AssetTree::build(&paths, self.asset_rev));
+        self.state.bindings().set("assets.count", tree.file_count());
+        self.state.set_tree_model("assets", tree.clone());
+        self.asset_tree = Some(tree);
+    }
+
+    /// `on_activate="open_asset"`: loads the activated file of the browser.
+    fn handle_asset_events(&mut self, events: &[UiEvent]) {
+        let open = events.iter().any(|ev| {
+            ev.kind == UiEventKind::Activate
+                && ev.target_id == "assets"
+                && ev
+                    .resolved_actions()
+                    .any(|a| matches!(a, UiAction::Custom(ref c) if c == "open_asset"))
+        });
+        if !open {
             return;
         }
-
-        let screen_h = ctx.screen_rect().height();
-        let console_h = (screen_h * 0.40).clamp(260.0, 620.0);
-
-        let bg = egui::Color32::from_rgba_premultiplied(12, 12, 14, 238);
-        let stroke = egui::Stroke::new(1.0, egui::Color32::from_gray(60));
-
-        egui::TopBottomPanel::bottom("ne_engine_console")
-            .exact_height(console_h)
-            .resizable(false)
-            .frame(
-                egui::Frame::none()
-                    .fill(bg)
-                    .stroke(stroke)
-                    .inner_margin(egui::Margin::symmetric(12.0, 10.0)),
-            )
-            .show(ctx, |ui| {
-                self.header_row(ui);
-
-                ui.add_space(6.0);
-
-                let available = ui.available_height();
-                let log_h = (available * 0.60).max(160.0);
-
-                self.log_area(ui, log_h);
-
-                ui.add_space(6.0);
-
-                self.input_row(ui);
-
-                if self.suggest_open && !self.suggest.items.is_empty() {
-                    ui.add_space(4.0);
-                    self.suggest_panel(ui);
-                }
-            });
+        let path = self
+            .state
+            .selected("assets")
+            .and_then(|node| self.asset_tree.as_ref()?.path(node).map(str::to_string));
+        if let Some(path) = path {
+            ConsoleApi::exec(&format!("asset.load {path}"));
+        }
     }
 
-    fn header_row(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            ui.label(
-                egui::RichText::new("NE Console")
-                    .strong()
-                    .monospace()
-                    .color(egui::Color32::from_gray(220)),
-            );
-
-            ui.separator();
-
-            if ui.button("Help").clicked() {
-                self.exec_line("help");
-            }
-            if ui.button("Services").clicked() {
-                self.exec_line("services");
+    fn run_palette_action(&mut self, action: PaletteAction) {
+        match action {
+            PaletteAction::Exec(line) => {
+                ConsoleApi::exec(&line);
             }
-            if ui.button("Refresh").clicked() {
-                let _ = newengine_core::call_service_v1("engine.command", "command.refresh", &[]);
-                self.push_line("[refreshed]".to_string());
-                self.refresh_suggest();
-            }
-
-            ui.separator();
-
-            ui.label(
-                egui::RichText::new("Filter:")
-                    .monospace()
-                    .color(egui::Color32::from_gray(160)),
-            );
-
-            ui.add(
-                egui::TextEdit::singleline(&mut self.filter)
-                    .desired_width(180.0)
-                    .hint_text("text")
-                    .font(egui::TextStyle::Monospace),
-            );
-
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if ui.button("Close").clicked() {
-                    self.open = false;
-                    self.suggest_open = false;
-                }
-                if ui.button("Clear").clicked() {
-                    self.lines.clear();
+            PaletteAction::Prefill(line) => ConsoleApi::prefill(line),
+            PaletteAction::Op(op) => match op {
+                EditorOp::ToggleConsole => ConsoleApi::toggle(),
+                EditorOp::ClearConsole => ConsoleApi::clear(),
+                EditorOp::RefreshCommands => {
+                    let _ = newengine_core::call_service_v1("engine.command", "command.refresh", &[]);
+                    ConsoleApi::print(ConsoleLineKind::Output, "[refreshed]");
                 }
-                ui.checkbox(&mut self.stick_to_bottom, "Stick");
-            });
-        });
-    }
-
-    fn log_area(&mut self, ui: &mut egui::Ui, log_h: f32) {
-        let f = self.filter.trim().to_lowercase();
-
-        egui::ScrollArea::vertical()
-            .max_height(log_h)
-            .stick_to_bottom(self.stick_to_bottom)
-            .show(ui, |ui| {
-                for l in &self.lines {
-                    if !f.is_empty() && !l.to_lowercase().contains(&f) {
-                        continue;
-                    }
-
-                    let mut rt = egui::RichText::new(l).monospace();
-                    if l.starts_with("ERR:") {
-                        rt = rt.color(egui::Color32::from_rgb(255, 96, 96));
-                    } else if l.starts_with("> ") {
-                        rt = rt.color(egui::Color32::from_rgb(128, 220, 140));
-                    } else if l.starts_with('[') {
-                        rt = rt.color(egui::Color32::from_gray(190));
-                    }
-                    ui.label(rt);
+                EditorOp::RescanAssets => {
+                    self.palette.rescan_assets();
+                    self.asset_tree = None;
                 }
-            });
-    }
-
-    fn input_row(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            ui.label(egui::RichText::new("> ").monospace().strong());
-
-            let input_id = ui.make_persistent_id("ne_console_input");
-            let resp = ui.add(
-                egui::TextEdit::singleline(&mut self.input)
-                    .id(input_id)
-                    .desired_width(f32::INFINITY)
-                    .font(egui::TextStyle::Monospace)
-                    .hint_text("Type a command (Tab for suggestions)"),
-                // .lock_focus(true)  // REMOVE: мешает нормальному вводу
-            );
-
-            let has_focus = resp.has_focus();
-
-            let enter = self.key_pressed(KeyCode::Enter as u32);
-            let tab = self.key_pressed(KeyCode::Tab as u32);
-            let up = self.key_pressed(KeyCode::ArrowUp as u32);
-            let down = self.key_pressed(KeyCode::ArrowDown as u32);
-            let esc = self.key_pressed(KeyCode::Escape as u32);
-
-            // IMPORTANT:
-            // We drive these keys via Input plugin logic; egui must not also handle them for TextEdit.
-            if has_focus {
-                ui.ctx().input_mut(|i| {
-                    i.events.retain(|e| {
-                        !matches!(
-                        e,
-                        egui::Event::Key { key: egui::Key::Tab, .. }
-                            | egui::Event::Key { key: egui::Key::Enter, .. }
-                            | egui::Event::Key { key: egui::Key::ArrowUp, .. }
-                            | egui::Event::Key { key: egui::Key::ArrowDown, .. }
-                            | egui::Event::Key { key: egui::Key::Escape, .. }
-                    )
-                    });
-                });
-            }
-
-            if resp.changed() {
-                self.refresh_suggest();
-                if !self.suggest.items.is_empty() {
-                    self.suggest_open = true;
-                    self.suggest_selected = self
-                        .suggest_selected
-                        .min(self.suggest.items.len().saturating_sub(1));
+                EditorOp::ToggleDepGraph => self.dep_graph.toggle(),
+                EditorOp::ToggleTimeline => self.timeline.toggle(),
+                EditorOp::ToggleDiagnostics => self.diagnostics.toggle(),
+                EditorOp::TogglePreview => self.preview_open = !self.preview_open,
+                EditorOp::Quit => {
+                    let _ = newengine_core::call_service_v1("engine.command", "command.exec", b"quit");
                 }
-            }
-
-            if esc {
-                self.suggest_open = false;
-            }
-
-            if has_focus && tab {
-                // Tab = accept selected suggestion
-                self.refresh_suggest();
-                if !self.suggest.items.is_empty() {
-                    self.suggest_open = true;
-                    self.suggest_selected = self
-                        .suggest_selected
-                        .min(self.suggest.items.len().saturating_sub(1));
-
-                    let idx = self.suggest_selected;
-                    let ins = self.suggest.items[idx].insert.clone();
-                    if !ins.is_empty() {
-                        self.input = ins;
-                        self.refresh_suggest();
-                        self.suggest_open = true;
-                        self.suggest_selected = 0;
-                    }
-                }
-                resp.request_focus();
-            }
-
-            if self.suggest_open {
-                if up {
-                    self.suggest_selected = self.suggest_selected.saturating_sub(1);
-                    resp.request_focus();
-                } else if down {
-                    self.suggest_selected = (self.suggest_selected + 1)
-                        .min(self.suggest.items.len().saturating_sub(1));
-                    resp.request_focus();
-                }
-            } else {
-                if up {
-                    self.hist_up();
-                    self.refresh_suggest();
-                    resp.request_focus();
-                } else if down {
-                    self.hist_down();
-                    self.refresh_suggest();
-                    resp.request_focus();
-                }
-            }
-
-            if has_focus && enter {
-                let line = self.input.trim().to_string();
-                self.input.clear();
-                self.suggest_open = false;
-
-                if !line.is_empty() {
-                    self.exec_line(&line);
-                }
-
-                resp.request_focus();
-            }
-        });
-    }
-
-    fn suggest_panel(&mut self, ui: &mut egui::Ui) {
-        if self.suggest.items.is_empty() {
-            self.suggest_open = false;
-            self.suggest_selected = 0;
-            return;
-        }
-
-        self.suggest_selected = self
-            .suggest_selected
-            .min(self.suggest.items.len().saturating_sub(1));
-
-        let bg = egui::Color32::from_rgba_premultiplied(16, 16, 18, 245);
-        let stroke = egui::Stroke::new(1.0, egui::Color32::from_gray(55));
-
-        egui::Frame::none()
-            .fill(bg)
-            .stroke(stroke)
-            .inner_margin(egui::Margin::symmetric(10.0, 8.0))
-            .rounding(egui::Rounding::same(6.0))
-            .show(ui, |ui| {
-                if !self.suggest.signature.is_empty() {
-                    ui.label(
-                        egui::RichText::new(self.suggest.signature.clone())
-                            .monospace()
-                            .color(egui::Color32::from_gray(200)),
-                    );
-                    ui.add_space(6.0);
-                }
-
-                ui.columns(2, |cols| {
-                    let (left, right) = cols.split_at_mut(1);
-                    let left = &mut left[0];
-                    let right = &mut right[0];
-
-                    const SUGGEST_LIST_HEIGHT: f32 = 140.0;
-
-                    let mut click_select: Option<usize> = None;
-                    let mut accept_insert: Option<String> = None;
-                    let mut accept_refresh = false;
-
-                    egui::ScrollArea::vertical()
-                        .max_height(SUGGEST_LIST_HEIGHT)
-                        .show(left, |ui| {
-                            for (i, it) in self.suggest.items.iter().enumerate() {
-                                let selected = i == self.suggest_selected;
-
-                                let text = if it.help.is_empty() {
-                                    it.display.clone()
-                                } else {
-                                    format!("{}  -  {}", it.display, it.help)
-                                };
-
-                                let mut rt = egui::RichText::new(text).monospace();
-                                if selected {
-                                    rt = rt.strong().color(egui::Color32::from_gray(240));
-                                } else {
-                                    rt = rt.color(egui::Color32::from_gray(200));
-                                }
-
-                                let resp = ui.selectable_label(selected, rt);
-
-                                if resp.clicked() {
-                                    click_select = Some(i);
-                                }
-                                if resp.double_clicked() {
-                                    accept_insert = Some(it.insert.clone());
-                                    accept_refresh = true;
-                                }
-                            }
-                        });
-
-                    if let Some(i) = click_select {
-                        self.suggest_selected = i;
-                    }
-
-                    if let Some(ins) = accept_insert {
-                        self.input = ins;
-                        if accept_refresh {
-                            self.refresh_suggest();
-                            self.suggest_open = true;
-                            self.suggest_selected = 0;
-                        }
-                    }
-
-                    // Important: snapshot the selected element after potential mutations.
-                    if self.suggest.items.is_empty() {
-                        self.suggest_open = false;
-                        self.suggest_selected = 0;
-                        return;
-                    }
-
-                    self.suggest_selected = self
-                        .suggest_selected
-                        .min(self.suggest.items.len().saturating_sub(1));
-
-                    let it = self.suggest.items[self.suggest_selected].clone();
-
-                    right.label(
-                        egui::RichText::new(it.usage)
-                            .monospace()
-                            .strong()
-                            .color(egui::Color32::from_gray(230)),
-                    );
-                    right.add_space(4.0);
-
-                    if !it.help.is_empty() {
-                        right.label(
-                            egui::RichText::new(it.help)
-                                .monospace()
-                                .color(egui::Color32::from_gray(190)),
-                        );
-                    }
-
-                    if !it.kind.is_empty() {
-                        right.add_space(6.0);
-                        right.label(
-                            egui::RichText::new(format!("type: {}", it.kind))
-                                .monospace()
-                                .color(egui::Color32::from_gray(160)),
-                        );
-                    }
-                });
-            });
-    }
-
-    fn refresh_suggest(&mut self) {
-        let input = self.input.clone();
-        if input == self.last_suggest_input {
-            return;
-        }
-
-        self.last_suggest_input = input.clone();
-        self.suggest = SuggestResponse {
-            signature: String::new(),
-            items: Vec::new(),
-        };
-
-        match newengine_core::call_service_v1("engine.command", "command.suggest", input.as_bytes()) {
-            Ok(bytes) => {
-                if let Ok(r) = serde_json::from_slice::<SuggestResponse>(&bytes) {
-                    self.suggest = r;
-                    self.suggest_selected = self
-                        .suggest_selected
-                        .min(self.suggest.items.len().saturating_sub(1));
-                }
-            }
-            Err(_) => {}
-        }
-    }
-
-    fn push_line(&mut self, s: String) {
-        self.lines.push(s);
-        if self.lines.len() > 4000 {
-            self.lines.drain(0..512);
-        }
-    }
-
-    fn hist_up(&mut self) {
-        if self.history.is_empty() {
-            return;
+            },
         }
-        self.hist_cursor = (self.hist_cursor + 1).min(self.history.len());
-        let idx = self.history.len().saturating_sub(self.hist_cursor);
-        self.input = self.history.get(idx).cloned().unwrap_or_default();
     }
 
-    fn hist_down(&mut self) {
-        if self.history.is_empty() {
+    fn preview_ui(&mut self, ctx: &egui::Context) {
+        if !self.preview_open {
             return;
         }
-        self.hist_cursor = self.hist_cursor.saturating_sub(1);
-        let idx = self.history.len().saturating_sub(self.hist_cursor);
-        self.input = self.history.get(idx).cloned().unwrap_or_default();
-    }
-
-    fn exec_line(&mut self, line: &str) {
-        self.push_line(format!("> {line}"));
-
-        self.history.push(line.to_string());
-        if self.history.len() > 256 {
-            self.history.drain(0..32);
-        }
-        self.hist_cursor = 0;
 
-        match newengine_core::call_service_v1("engine.command", "command.exec", line.as_bytes()) {
-            Ok(bytes) => match serde_json::from_slice::<CommandExecResponse>(&bytes) {
-                Ok(r) => {
-                    if r.ok {
-                        let out = r.output.unwrap_or_default();
-                        let out = out.trim_end();
-                        if !out.is_empty() {
-                            for l in out.lines() {
-                                self.push_line(l.to_string());
-                            }
-                        }
-                    } else {
-                        self.push_line(format!(
-                            "ERR: {}",
-                            r.error.unwrap_or_else(|| "unknown error".to_string())
-                        ));
-                    }
+        let mut open = self.preview_open;
+        egui::Window::new("Model preview")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| match crate::render_controller::preview_image() {
+                Some(p) => {
+                    let id = egui::TextureId::User(p.texture.0 as u64);
+                    ui.image((id, egui::vec2(p.size[0] as f32, p.size[1] as f32)));
                 }
-                Err(e) => {
-                    self.push_line(format!("ERR: bad response json: {e}"));
-                    self.push_line(String::from_utf8_lossy(&bytes).to_string());
+                None => {
+                    ui.label("No preview: the render backend has no offscreen targets.");
                 }
-            },
-            Err(e) => self.push_line(format!("ERR: {e}")),
-        }
-    }
-}
-
-pub struct EditorUiBuild {
-    shared_doc: Arc<Mutex<Option<UiMarkupDoc>>>,
-    state: UiState,
-    console: ConsoleUi,
-}
-
-impl EditorUiBuild {
-    #[inline]
-    pub fn new(shared_doc: Arc<Mutex<Option<UiMarkupDoc>>>) -> Self {
-        let mut state = UiState::default();
-        state.set_var("app.name", "NewEngine Editor");
-        Self {
-            shared_doc,
-            state,
-            console: ConsoleUi {
-                open: true,
-                stick_to_bottom: true,
-                ..Default::default()
-            },
-        }
+            });
+        self.preview_open = open;
     }
 }
 
@@ -606,15 +185,35 @@ impl UiBuildFn for EditorUiBuild {
             return;
         };
 
+        self.poll_asset_browser();
+
         let maybe_doc = { self.shared_doc.lock().ok().and_then(|g| g.as_ref().cloned()) };
         if let Some(doc) = maybe_doc {
+            for ev in newengine_core::ui_remote::take_events() {
+                if let Err(e) = doc.apply_remote_event(&mut self.state, &ev) {
+                    log::warn!("ui.remote: {e}");
+                }
+            }
+
             doc.render(ctx, &mut self.state);
+
+            let events = self.state.drain_events();
+            newengine_core::ui_actions::dispatch(&events);
+            self.handle_asset_events(&events);
+            if let Some(diff) = self.remote.diff(&self.state, &events) {
+                newengine_core::ui_remote::publish_diff(diff);
+            }
         }
 
-        self.console.ui(ctx);
+        let keys = take_input_keys();
 
-        if self.state.take_clicked("quit") {
-            let _ = newengine_core::call_service_v1("engine.command", "command.exec", b"quit");
+        if let Some(action) = self.palette.ui(ctx, &keys) {
+            self.run_palette_action(action);
         }
+
+        self.dep_graph.ui(ctx);
+        self.timeline.ui(ctx);
+        self.diagnostics.ui(ctx);
+        self.preview_ui(ctx);
     }
 }
\ No newline at end of file
@@ -0,0 +1,242 @@
+use crate::pak::{normalize_entry_path, PakReader, PAK_EXTENSION};
+use crate::path::{CaseCollision, CaseIndex};
+use crate::source::AssetSource;
+use crate::stream::AssetReadStream;
+use crate::types::AssetError;
+
+use log::{info, warn};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Read-only `AssetSource` backed by a `.zip` or `.nepak` archive.
+///
+/// Logical paths are matched with `/` separators, so `ui/editor.xml` resolves the same on
+/// every platform. Register it like any other source; sources are queried in registration
+/// order, so a filesystem source added first lets loose files override packed ones.
+pub struct ArchiveSource {
+    path: PathBuf,
+    backend: Backend,
+    case_index: CaseIndex,
+}
+
+enum Backend {
+    Zip {
+        archive: Mutex<zip::ZipArchive<BufReader<File>>>,
+        /// normalized path -> name as stored in the zip
+        names: HashMap<String, String>,
+    },
+    Pak(PakReader),
+}
+
+impl ArchiveSource {
+    /// Opens an archive; the format is chosen by extension (`.nepak`, otherwise zip).
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, AssetError> {
+        let path = path.into();
+
+        let is_pak = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case(PAK_EXTENSION));
+
+        let backend = if is_pak {
+            Backend::Pak(PakReader::open(&path)?)
+        } else {
+            let file = File::open(&path).map_err(|e| {
+                AssetError::new(format!(
+                    "ArchiveSource: failed to open '{}': {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            let archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| {
+                AssetError::new(format!(
+                    "ArchiveSource: invalid zip '{}': {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            let names = archive
+                .file_names()
+                .filter(|n| !n.ends_with('/'))
+                .map(|n| (normalize_entry_path(n), n.to_string()))
+                .collect();
+            Backend::Zip {
+                archive: Mutex::new(archive),
+                names,
+            }
+        };
+
+        let mut src = Self {
+            path,
+            backend,
+            case_index: CaseIndex::default(),
+        };
+        src.case_index = CaseIndex::new(src.entries().iter().map(String::as_str));
+
+        info!(
+            target: "assets",
+            "archive.mount file='{}' kind='{}' entries={}",
+            src.path.display(),
+            src.kind(),
+            src.len()
+        );
+        for c in src.case_collisions() {
+            warn!(target: "assets", "archive.case_collision file='{}' {}", src.path.display(), c);
+        }
+        Ok(src)
+    }
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    #[inline]
+    pub fn kind(&self) -> &'static str {
+        match self.backend {
+            Backend::Zip { .. } => "zip",
+            Backend::Pak(_) => "nepak",
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        match &self.backend {
+            Backend::Zip { names, .. } => names.len(),
+            Backend::Pak(p) => p.len(),
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// All entry paths, sorted.
+    pub fn entries(&self) -> Vec<String> {
+        let mut out: Vec<String> = match &self.backend {
+            Backend::Zip { names, .. } => names.keys().cloned().collect(),
+            Backend::Pak(p) => p.entries().map(|e| e.path.clone()).collect(),
+        };
+        out.sort();
+        out
+    }
+
+    /// Entries that differ only by case.
+    #[inline]
+    pub fn case_collisions(&self) -> Vec<CaseCollision> {
+        self.case_index.collisions()
+    }
+
+    #[inline]
+    fn key(logical_path: &Path) -> String {
+        normalize_entry_path(&logical_path.to_string_lossy())
+    }
+
+    /// Byte range of a stored (uncompressed) zip entry, which can be read from the archive
+    /// file directly. `None` for compressed entries.
+    fn zip_stored_range(&self, key: &str) -> Result<Option<(u64, u64)>, AssetError> {
+        let Backend::Zip { archive, names } = &self.backend else {
+            return Ok(None);
+        };
+        let name = names.get(key).map(String::as_str).unwrap_or(key);
+        let mut a = archive.lock();
+        let f = a.by_name(name).map_err(|e| {
+            AssetError::new(format!(
+                "ArchiveSource: '{}' in '{}': {}",
+                key,
+                self.path.display(),
+                e
+            ))
+        })?;
+        Ok((f.compression() == zip::CompressionMethod::Stored).then(|| (f.data_start(), f.size())))
+    }
+}
+
+impl AssetSource for ArchiveSource {
+    fn exists(&self, logical_path: &Path) -> bool {
+        let key = Self::key(logical_path);
+        match &self.backend {
+            Backend::Zip { names, .. } => names.contains_key(&key),
+            Backend::Pak(p) => p.entry(&key).is_some(),
+        }
+    }
+
+    #[inline]
+    fn list(&self) -> Result<Vec<PathBuf>, AssetError> {
+        Ok(self
+            .entries()
+            .into_iter()
+            .filter(|e| !e.ends_with('/'))
+            .map(PathBuf::from)
+            .collect())
+    }
+
+    fn find_ignore_case(&self, logical_path: &Path) -> Result<Option<PathBuf>, AssetError> {
+        let key = Self::key(logical_path);
+        let found = self.case_index.resolve(&key).map_err(|e| {
+            AssetError::new(format!("ArchiveSource: '{}': {}", self.path.display(), e))
+        })?;
+        Ok(found.map(PathBuf::from))
+    }
+
+    fn read(&self, logical_path: &Path) -> Result<Vec<u8>, AssetError> {
+        let key = Self::key(logical_path);
+        match &self.backend {
+            Backend::Pak(p) => p.read(&key),
+            Backend::Zip { archive, names } => {
+                let name = names.get(&key).map(String::as_str).unwrap_or(&key);
+                let mut a = archive.lock();
+                let mut f = a.by_name(name).map_err(|e| {
+                    AssetError::new(format!(
+                        "ArchiveSource: '{}' in '{}': {}",
+                        key,
+                        self.path.display(),
+                        e
+                    ))
+                })?;
+                let mut out = Vec::with_capacity(f.size() as usize);
+                f.read_to_end(&mut out).map_err(|e| {
+                    AssetError::new(format!(
+                        "ArchiveSource: failed to read '{}' in '{}': {}",
+                        key,
+                        self.path.display(),
+                        e
+                    ))
+                })?;
+                Ok(out)
+            }
+        }
+    }
+
+    /// Stored zip entries and uncompressed `.nepak` entries are streamed from the archive
+    /// file; compressed entries are decompressed whole.
+    fn open_stream(&self, logical_path: &Path) -> Result<AssetReadStream, AssetError> {
+        let key = Self::key(logical_path);
+        if let Backend::Pak(p) = &self.backend {
+            return p.open_stream(&key);
+        }
+
+        let Some((start, size)) = self.zip_stored_range(&key)? else {
+            return Ok(AssetReadStream::from_bytes(self.read(logical_path)?, key));
+        };
+        let open_err = |e: std::io::Error| {
+            AssetError::new(format!(
+                "ArchiveSource: failed to open '{}' in '{}': {}",
+                key,
+                self.path.display(),
+                e
+            ))
+        };
+        let mut file = File::open(&self.path).map_err(open_err)?;
+        file.seek(SeekFrom::Start(start)).map_err(open_err)?;
+        Ok(AssetReadStream::new(
+            BufReader::new(file.take(size)),
+            Some(size),
+            key,
+        ))
+    }
+}
@@ -24,6 +24,9 @@ pub struct AudioMeta {
     pub bits_per_sample: u16,
     pub frames: u64,
     pub duration_sec: f64,
+    /// Loop region in sample frames from the asset's `.meta` sidecar.
+    pub loop_start: Option<u64>,
+    pub loop_end: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -129,6 +132,10 @@ fn parse_meta_json(meta_json: &str) -> Result<AudioMeta, AudioReadError> {
     let frames = v.get("frames").and_then(|x| x.as_u64()).unwrap_or(0);
     let duration_sec = v.get("duration_sec").and_then(|x| x.as_f64()).unwrap_or(0.0);
 
+    let settings = v.get("import_settings");
+    let loop_start = settings.and_then(|s| s.get("loop_start")).and_then(|x| x.as_u64());
+    let loop_end = settings.and_then(|s| s.get("loop_end")).and_then(|x| x.as_u64());
+
     Ok(AudioMeta {
         schema,
         container,
@@ -138,6 +145,8 @@ fn parse_meta_json(meta_json: &str) -> Result<AudioMeta, AudioReadError> {
         bits_per_sample,
         frames,
         duration_sec,
+        loop_start,
+        loop_end,
     })
 }
 
@@ -0,0 +1,265 @@
+use newengine_assets::patch::signing_key_from_hex;
+use newengine_assets::{
+    apply_patch, make_patch, ArchiveSource, ContentManifest, CookNotice, CookOptions, CookServer,
+    Cooker, PakCompression, PakOptions, PakWriter, SignedManifest, ValidationRules,
+    ValidationSeverity, VALIDATION_RULES_PATH,
+};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+const SERVE_LISTEN: &str = "127.0.0.1:7371";
+const SERVE_INTERVAL_MS: u64 = 250;
+
+const USAGE: &str = "usage:
+  nepak pack <assets_dir> <out.nepak> [--align <bytes>] [--no-compress] [--strict-case] [--rules <file>]
+  nepak serve <assets_dir> <out.nepak> [--manifest <out.json>] [--listen <addr>] [--interval <ms>]
+              [--align <bytes>] [--no-compress] [--strict-case] [--rules <file>]
+  nepak list <archive.(nepak|zip)>
+  nepak manifest <content_dir> <version> <out.json> [--prev <old.json> <old_dir> <patch_dir>]
+  nepak sign <manifest.json> <key_id> <secret_key_hex_file> <out.signed.json>
+  nepak diff <old_file> <new_file> <out.nedelta>
+  nepak patch <old_file> <in.nedelta> <out_file>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let res = match args.first().map(String::as_str) {
+        Some("pack") => pack(&args[1..]),
+        Some("serve") => serve(&args[1..]),
+        Some("list") => list(&args[1..]),
+        Some("manifest") => manifest(&args[1..]),
+        Some("sign") => sign(&args[1..]),
+        Some("diff") => diff(&args[1..]),
+        Some("patch") => patch(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn pack(args: &[String]) -> Result<(), String> {
+    let (Some(src), Some(out)) = (args.first(), args.get(1)) else {
+        return Err(USAGE.to_string());
+    };
+
+    let mut options = PakOptions::default();
+    let mut rules_file: Option<PathBuf> = None;
+    let mut it = args[2..].iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--align" => {
+                let v = it
+                    .next()
+                    .and_then(|s| s.parse::<u32>().ok())
+                    .ok_or("--align expects a number")?;
+                options = options.with_alignment(v);
+            }
+            "--no-compress" => options = options.with_compression(PakCompression::None),
+            "--strict-case" => options = options.with_strict_case(true),
+            "--rules" => {
+                rules_file = Some(PathBuf::from(it.next().ok_or("--rules expects a file")?))
+            }
+            other => return Err(format!("unknown option '{other}'\n{USAGE}")),
+        }
+    }
+
+    let mut w = PakWriter::new(options);
+    w.add_dir(&PathBuf::from(src)).map_err(|e| e.to_string())?;
+    for c in w.case_collisions() {
+        eprintln!("warning: {c}");
+    }
+
+    // Content rules: an explicit file, else the rules asset shipped with the content.
+    let rules_file = rules_file.or_else(|| {
+        let p = Path::new(src).join(VALIDATION_RULES_PATH);
+        p.is_file().then_some(p)
+    });
+    if let Some(path) = rules_file {
+        let rules = ValidationRules::from_file(&path).map_err(|e| e.to_string())?;
+        let issues = w.validate(&rules).map_err(|e| e.to_string())?;
+        for i in issues.iter() {
+            eprintln!("{i}");
+        }
+        let errors = issues
+            .iter()
+            .filter(|i| i.severity == ValidationSeverity::Error)
+            .count();
+        if errors > 0 {
+            return Err(format!("validation failed: {errors} error(s)"));
+        }
+    }
+
+    let stats = w.write_to(&PathBuf::from(out)).map_err(|e| e.to_string())?;
+
+    println!(
+        "packed {} entries: raw={} stored={} file={}",
+        stats.entries, stats.raw_bytes, stats.stored_bytes, stats.file_bytes
+    );
+    Ok(())
+}
+
+/// Watches `assets_dir` and keeps `out.nepak` (and the manifest) up to date, recooking only
+/// what changed. Connected editors get a notice after every written pack.
+fn serve(args: &[String]) -> Result<(), String> {
+    let (Some(src), Some(out)) = (args.first(), args.get(1)) else {
+        return Err(USAGE.to_string());
+    };
+
+    let mut options = CookOptions::new(src, out);
+    let mut listen = SERVE_LISTEN.to_string();
+    let mut interval = Duration::from_millis(SERVE_INTERVAL_MS);
+    let mut it = args[2..].iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--manifest" => {
+                let m = it.next().ok_or("--manifest expects a file")?;
+                options = options.with_manifest(Some(PathBuf::from(m)));
+            }
+            "--listen" => listen = it.next().ok_or("--listen expects an address")?.clone(),
+            "--interval" => {
+                let v = it
+                    .next()
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .ok_or("--interval expects milliseconds")?;
+                interval = Duration::from_millis(v.max(10));
+            }
+            "--align" => {
+                let v = it
+                    .next()
+                    .and_then(|s| s.parse::<u32>().ok())
+                    .ok_or("--align expects a number")?;
+                options.pak = options.pak.with_alignment(v);
+            }
+            "--no-compress" => options.pak = options.pak.with_compression(PakCompression::None),
+            "--strict-case" => options.pak = options.pak.with_strict_case(true),
+            "--rules" => {
+                let r = it.next().ok_or("--rules expects a file")?;
+                options = options.with_rules(Some(PathBuf::from(r)));
+            }
+            other => return Err(format!("unknown option '{other}'\n{USAGE}")),
+        }
+    }
+
+    let server = CookServer::spawn(listen.as_str()).map_err(|e| format!("{listen}: {e}"))?;
+    println!(
+        "serving '{}' -> '{}' (notices on {})",
+        src,
+        out,
+        server.local_addr()
+    );
+
+    let mut cooker = Cooker::new(options.clone());
+    loop {
+        match cooker.cook() {
+            Ok(Some(report)) => {
+                for i in report.issues.iter() {
+                    eprintln!("{i}");
+                }
+                match report.stats {
+                    Some(stats) => {
+                        println!(
+                            "version {}: cooked {} removed {} reused {} (file={} bytes, {} client(s))",
+                            report.version,
+                            report.cooked.len(),
+                            report.removed.len(),
+                            report.reused,
+                            stats.file_bytes,
+                            server.client_count()
+                        );
+                        server.broadcast(&CookNotice::from_report(&options, &report));
+                    }
+                    None if report.errors() > 0 => {
+                        eprintln!("not packed: {} error(s)", report.errors())
+                    }
+                    None => println!("version {}: up to date", report.version),
+                }
+            }
+            Ok(None) => {}
+            // Usually a file caught mid-write; the next pass picks it up again.
+            Err(e) => eprintln!("cook failed: {e}"),
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+fn list(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or(USAGE)?;
+    let src = ArchiveSource::open(path).map_err(|e| e.to_string())?;
+    for e in src.entries() {
+        println!("{e}");
+    }
+    Ok(())
+}
+
+fn manifest(args: &[String]) -> Result<(), String> {
+    let (Some(dir), Some(version), Some(out)) = (args.first(), args.get(1), args.get(2)) else {
+        return Err(USAGE.to_string());
+    };
+    let version: u64 = version.parse().map_err(|_| "version expects a number")?;
+    let dir = PathBuf::from(dir);
+
+    let mut m = ContentManifest::from_directory(&dir, version).map_err(|e| e.to_string())?;
+
+    match &args[3..] {
+        [] => {}
+        [flag, prev, old_dir, patch_dir] if flag == "--prev" => {
+            let prev: ContentManifest = serde_json::from_slice(&read(prev)?).map_err(|e| e.to_string())?;
+            let n = m
+                .build_patches(&prev, Path::new(old_dir), &dir, Path::new(patch_dir))
+                .map_err(|e| e.to_string())?;
+            println!("wrote {n} patches (from version {})", prev.version);
+        }
+        _ => return Err(USAGE.to_string()),
+    }
+
+    let json = serde_json::to_string_pretty(&m).map_err(|e| e.to_string())?;
+    std::fs::write(out, json).map_err(|e| format!("{out}: {e}"))?;
+    println!("manifest version={} entries={}", m.version, m.entries.len());
+    Ok(())
+}
+
+fn sign(args: &[String]) -> Result<(), String> {
+    let [manifest, key_id, key_file, out] = args else {
+        return Err(USAGE.to_string());
+    };
+    let m: ContentManifest = serde_json::from_slice(&read(manifest)?).map_err(|e| e.to_string())?;
+    let key_hex = String::from_utf8(read(key_file)?).map_err(|e| e.to_string())?;
+    let key = signing_key_from_hex(&key_hex).map_err(|e| e.to_string())?;
+
+    let signed = SignedManifest::sign(&m, key_id.as_str(), &key).map_err(|e| e.to_string())?;
+    std::fs::write(out, signed.to_json()).map_err(|e| format!("{out}: {e}"))?;
+    println!("signed version={} key_id={}", m.version, key_id);
+    Ok(())
+}
+
+fn diff(args: &[String]) -> Result<(), String> {
+    let [old, new, out] = args else {
+        return Err(USAGE.to_string());
+    };
+    let (old, new) = (read(old)?, read(new)?);
+    let delta = make_patch(&old, &new).map_err(|e| e.to_string())?;
+    std::fs::write(out, &delta).map_err(|e| format!("{out}: {e}"))?;
+    println!("delta {} bytes (new file {} bytes)", delta.len(), new.len());
+    Ok(())
+}
+
+fn patch(args: &[String]) -> Result<(), String> {
+    let [old, delta, out] = args else {
+        return Err(USAGE.to_string());
+    };
+    let bytes = apply_patch(&read(old)?, &read(delta)?).map_err(|e| e.to_string())?;
+    std::fs::write(out, &bytes).map_err(|e| format!("{out}: {e}"))?;
+    println!("patched {} bytes", bytes.len());
+    Ok(())
+}
+
+fn read(path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("{path}: {e}"))
+}
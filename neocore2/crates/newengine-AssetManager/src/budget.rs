@@ -0,0 +1,236 @@
+use crate::id::AssetId;
+use crate::texture::TEXTURE_TYPE_ID;
+use crate::types::AssetBlob;
+use crate::validate::AUDIO_TYPE_ID;
+
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Memory category a resident blob is accounted under, chosen by its type id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetCategory {
+    Texture,
+    Audio,
+    /// Everything else (materials, shaders, meshes, documents, ...).
+    Blob,
+}
+
+impl AssetCategory {
+    pub const ALL: [AssetCategory; 3] = [Self::Texture, Self::Audio, Self::Blob];
+
+    #[inline]
+    pub fn of(type_id: &str) -> Self {
+        match type_id {
+            TEXTURE_TYPE_ID => Self::Texture,
+            AUDIO_TYPE_ID => Self::Audio,
+            _ => Self::Blob,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Texture => "textures",
+            Self::Audio => "audio",
+            Self::Blob => "blobs",
+        }
+    }
+
+    #[inline]
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Resident-byte limits per category; `None` is unlimited.
+///
+/// Over a limit the store evicts the least recently used `Ready` assets of that category.
+/// Blobs still referenced outside the store (an [`AssetHandle`] or an `Arc` from `get_blob`)
+/// are never evicted, so a category can stay over its limit while everything in it is in use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryBudget {
+    pub textures: Option<u64>,
+    pub audio: Option<u64>,
+    pub blobs: Option<u64>,
+}
+
+impl MemoryBudget {
+    #[inline]
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Limits in MiB, as written in startup configs; 0 is unlimited.
+    #[inline]
+    pub fn from_mib(textures: u32, audio: u32, blobs: u32) -> Self {
+        let mib = |v: u32| (v != 0).then_some(v as u64 * 1024 * 1024);
+        Self {
+            textures: mib(textures),
+            audio: mib(audio),
+            blobs: mib(blobs),
+        }
+    }
+
+    #[inline]
+    pub fn with_textures(mut self, bytes: Option<u64>) -> Self {
+        self.textures = bytes;
+        self
+    }
+
+    #[inline]
+    pub fn with_audio(mut self, bytes: Option<u64>) -> Self {
+        self.audio = bytes;
+        self
+    }
+
+    #[inline]
+    pub fn with_blobs(mut self, bytes: Option<u64>) -> Self {
+        self.blobs = bytes;
+        self
+    }
+
+    #[inline]
+    pub fn limit(&self, category: AssetCategory) -> Option<u64> {
+        match category {
+            AssetCategory::Texture => self.textures,
+            AssetCategory::Audio => self.audio,
+            AssetCategory::Blob => self.blobs,
+        }
+    }
+}
+
+/// Memory use of one category, as reported by `AssetStore::memory_usage`.
+#[derive(Debug, Clone, Copy)]
+pub struct CategoryUsage {
+    pub category: AssetCategory,
+    pub resident_bytes: u64,
+    pub assets: usize,
+    pub limit: Option<u64>,
+    /// Assets evicted from this category since the store was created.
+    pub evictions: u64,
+}
+
+/// Shared reference to a ready asset's blob.
+///
+/// While any handle (or other clone of the blob) is alive the asset is pinned: budget
+/// eviction skips it. Unloading and reloading still replace the store's copy; the handle
+/// keeps the blob it was acquired with.
+#[derive(Debug, Clone)]
+pub struct AssetHandle {
+    id: AssetId,
+    blob: Arc<AssetBlob>,
+}
+
+impl AssetHandle {
+    #[inline]
+    pub(crate) fn new(id: AssetId, blob: Arc<AssetBlob>) -> Self {
+        Self { id, blob }
+    }
+
+    #[inline]
+    pub fn id(&self) -> AssetId {
+        self.id
+    }
+
+    #[inline]
+    pub fn blob(&self) -> &Arc<AssetBlob> {
+        &self.blob
+    }
+}
+
+impl Deref for AssetHandle {
+    type Target = AssetBlob;
+
+    #[inline]
+    fn deref(&self) -> &AssetBlob {
+        &self.blob
+    }
+}
+
+/// Resident bytes and recency of the blobs the store holds.
+#[derive(Debug, Default)]
+pub(crate) struct ResidentSet {
+    /// Use clock: bumped on every insert and access.
+    tick: u64,
+    entries: HashMap<AssetId, Resident>,
+    bytes: [u64; 3],
+    counts: [usize; 3],
+    evictions: [u64; 3],
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Resident {
+    category: AssetCategory,
+    bytes: u64,
+    last_used: u64,
+}
+
+impl ResidentSet {
+    pub(crate) fn insert(&mut self, id: AssetId, blob: &AssetBlob) {
+        self.remove(id);
+        self.tick += 1;
+        let category = AssetCategory::of(&blob.type_id);
+        let bytes = blob.payload.len() as u64;
+        self.bytes[category.index()] += bytes;
+        self.counts[category.index()] += 1;
+        self.entries.insert(
+            id,
+            Resident {
+                category,
+                bytes,
+                last_used: self.tick,
+            },
+        );
+    }
+
+    pub(crate) fn remove(&mut self, id: AssetId) {
+        if let Some(r) = self.entries.remove(&id) {
+            self.bytes[r.category.index()] -= r.bytes;
+            self.counts[r.category.index()] -= 1;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn touch(&mut self, id: AssetId) {
+        self.tick += 1;
+        if let Some(r) = self.entries.get_mut(&id) {
+            r.last_used = self.tick;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn resident_bytes(&self, category: AssetCategory) -> u64 {
+        self.bytes[category.index()]
+    }
+
+    #[inline]
+    pub(crate) fn record_eviction(&mut self, category: AssetCategory) {
+        self.evictions[category.index()] += 1;
+    }
+
+    /// Assets of `category`, least recently used first.
+    pub(crate) fn lru_order(&self, category: AssetCategory) -> Vec<(AssetId, u64)> {
+        let mut v: Vec<(AssetId, u64, u64)> = self
+            .entries
+            .iter()
+            .filter(|(_, r)| r.category == category)
+            .map(|(id, r)| (*id, r.bytes, r.last_used))
+            .collect();
+        v.sort_by_key(|e| e.2);
+        v.into_iter().map(|(id, bytes, _)| (id, bytes)).collect()
+    }
+
+    pub(crate) fn usage(&self, budget: &MemoryBudget) -> Vec<CategoryUsage> {
+        AssetCategory::ALL
+            .iter()
+            .map(|&category| CategoryUsage {
+                category,
+                resident_bytes: self.bytes[category.index()],
+                assets: self.counts[category.index()],
+                limit: budget.limit(category),
+                evictions: self.evictions[category.index()],
+            })
+            .collect()
+    }
+}
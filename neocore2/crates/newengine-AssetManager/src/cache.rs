@@ -0,0 +1,319 @@
+use crate::meta::ImportSettings;
+use crate::source::AssetSource;
+use crate::stream::AssetReadStream;
+use crate::types::{AssetBlob, AssetDependency, AssetError, AssetKey};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const ENTRY_MAGIC: &[u8; 4] = b"NEAC";
+const ENTRY_VERSION: u32 = 1;
+const ENTRY_EXT: &str = "neac";
+
+/// Content-addressed key of one import: source bytes + logical path + settings + importer identity.
+///
+/// `.meta` sidecar settings are part of the key; assets without a sidecar keep the key they
+/// had before sidecars existed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CacheKey([u8; 32]);
+
+impl CacheKey {
+    pub fn compute(
+        bytes: &[u8],
+        key: &AssetKey,
+        settings: Option<&ImportSettings>,
+        importer_id: &str,
+        importer_version: &str,
+    ) -> Self {
+        let mut h = Self::hasher(key, settings, importer_id, importer_version);
+        h.update(bytes);
+        Self(*h.finalize().as_bytes())
+    }
+
+    /// Same key as `compute`, hashing the source in chunks as it is read.
+    pub fn compute_stream(
+        stream: &mut AssetReadStream,
+        key: &AssetKey,
+        settings: Option<&ImportSettings>,
+        importer_id: &str,
+        importer_version: &str,
+    ) -> Result<Self, AssetError> {
+        let mut h = Self::hasher(key, settings, importer_id, importer_version);
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let n = stream.read_chunk(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            h.update(&buf[..n]);
+        }
+        Ok(Self(*h.finalize().as_bytes()))
+    }
+
+    fn hasher(
+        key: &AssetKey,
+        settings: Option<&ImportSettings>,
+        importer_id: &str,
+        importer_version: &str,
+    ) -> blake3::Hasher {
+        let mut h = blake3::Hasher::new();
+        h.update(b"ne.asset-cache.v1\0");
+        h.update(key.logical_path.to_string_lossy().as_bytes());
+        h.update(b"\0");
+        h.update(&key.settings_hash.to_le_bytes());
+        h.update(importer_id.as_bytes());
+        h.update(b"\0");
+        h.update(importer_version.as_bytes());
+        h.update(b"\0");
+        if let Some(s) = settings {
+            h.update(b"meta\0");
+            h.update(s.as_json().as_bytes());
+            h.update(b"\0");
+        }
+        h
+    }
+
+    /// Parses the 64-character form produced by `to_hex`.
+    pub fn from_hex(s: &str) -> Option<Self> {
+        if s.len() != 64 || !s.is_ascii() {
+            return None;
+        }
+        let mut out = [0u8; 32];
+        for (i, b) in out.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).ok()?;
+        }
+        Some(Self(out))
+    }
+
+    #[inline]
+    pub fn to_hex(&self) -> String {
+        let mut s = String::with_capacity(64);
+        for b in self.0 {
+            s.push_str(&format!("{b:02x}"));
+        }
+        s
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryHeader {
+    type_id: String,
+    format: String,
+    meta_json: String,
+    #[serde(default)]
+    dependencies: Vec<EntryDependency>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryDependency {
+    path: String,
+    settings_hash: u64,
+    type_hint: String,
+    usage: String,
+    /// blake3 of the dependency source bytes at import time; `None` if it was unreadable.
+    #[serde(default)]
+    content_hash: Option<String>,
+}
+
+/// On-disk cache of imported blobs (`<dir>/<2 hex>/<64 hex>.neac`).
+///
+/// Entries are immutable and written atomically (temp file + rename). Importers that read
+/// other source files (reported as blob dependencies) are covered too: dependency content
+/// hashes are stored with the entry and re-checked on lookup.
+#[derive(Debug, Clone)]
+pub struct AssetCache {
+    dir: PathBuf,
+}
+
+impl AssetCache {
+    #[inline]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    #[inline]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    #[inline]
+    fn entry_path(&self, ck: &CacheKey) -> PathBuf {
+        let hex = ck.to_hex();
+        self.dir.join(&hex[0..2]).join(format!("{hex}.{ENTRY_EXT}"))
+    }
+
+    /// Returns the cached blob if present and all recorded dependencies are unchanged.
+    pub fn lookup(&self, ck: &CacheKey, sources: &[Arc<dyn AssetSource>]) -> Option<AssetBlob> {
+        let path = self.entry_path(ck);
+        let bytes = std::fs::read(&path).ok()?;
+
+        match decode_entry_checked(&bytes, sources) {
+            Ok(blob) => Some(blob),
+            Err(EntryError::Corrupt) => {
+                warn!(target: "assets::cache", "cache.corrupt file='{}'", path.display());
+                let _ = std::fs::remove_file(&path);
+                None
+            }
+            Err(EntryError::StaleDependency(dep)) => {
+                debug!(
+                    target: "assets::cache",
+                    "cache.stale key={} dependency='{}'",
+                    ck.to_hex(),
+                    dep
+                );
+                None
+            }
+        }
+    }
+
+    /// Writes an entry. Failures are logged and otherwise ignored (the cache is best-effort).
+    pub fn store(&self, ck: &CacheKey, blob: &AssetBlob, sources: &[Arc<dyn AssetSource>]) {
+        let Some(out) = encode_entry(blob, sources) else {
+            return;
+        };
+
+        let path = self.entry_path(ck);
+        if let Err(e) = write_atomic(&path, &out) {
+            warn!(
+                target: "assets::cache",
+                "cache.write failed file='{}' err='{}'",
+                path.display(),
+                e
+            );
+        }
+    }
+
+    /// Removes every cache entry. Returns the number of deleted files.
+    pub fn clear(&self) -> std::io::Result<usize> {
+        let mut removed = 0usize;
+        let Ok(shards) = std::fs::read_dir(&self.dir) else {
+            return Ok(0);
+        };
+
+        for shard in shards.flatten() {
+            let shard_path = shard.path();
+            if !shard_path.is_dir() {
+                continue;
+            }
+            for e in std::fs::read_dir(&shard_path)?.flatten() {
+                let p = e.path();
+                if p.extension().and_then(|x| x.to_str()) == Some(ENTRY_EXT) {
+                    std::fs::remove_file(&p)?;
+                    removed += 1;
+                }
+            }
+            let _ = std::fs::remove_dir(&shard_path);
+        }
+
+        Ok(removed)
+    }
+}
+
+/// Why an encoded entry could not be used.
+#[derive(Debug)]
+pub(crate) enum EntryError {
+    Corrupt,
+    /// A dependency's current source bytes differ from the ones the entry was imported with.
+    StaleDependency(String),
+}
+
+/// Serializes `blob` in the cache entry format, hashing its dependencies through `sources`.
+/// Also the wire format of the asset server.
+pub(crate) fn encode_entry(blob: &AssetBlob, sources: &[Arc<dyn AssetSource>]) -> Option<Vec<u8>> {
+    let header = EntryHeader {
+        type_id: blob.type_id.to_string(),
+        format: blob.format.to_string(),
+        meta_json: blob.meta_json.to_string(),
+        dependencies: blob
+            .dependencies
+            .iter()
+            .map(|d| EntryDependency {
+                path: d.logical_path.to_string_lossy().into_owned(),
+                settings_hash: d.settings_hash,
+                type_hint: d.type_hint.to_string(),
+                usage: d.usage.to_string(),
+                content_hash: hash_source(sources, &d.logical_path),
+            })
+            .collect(),
+    };
+
+    let header_json = serde_json::to_vec(&header).ok()?;
+
+    let mut out = Vec::with_capacity(12 + header_json.len() + blob.payload.len());
+    out.extend_from_slice(ENTRY_MAGIC);
+    out.extend_from_slice(&ENTRY_VERSION.to_le_bytes());
+    out.extend_from_slice(&(header_json.len() as u32).to_le_bytes());
+    out.extend_from_slice(&header_json);
+    out.extend_from_slice(&blob.payload);
+    Some(out)
+}
+
+/// Decodes an entry and re-checks its dependency hashes against `sources`.
+pub(crate) fn decode_entry_checked(
+    bytes: &[u8],
+    sources: &[Arc<dyn AssetSource>],
+) -> Result<AssetBlob, EntryError> {
+    let (header, payload) = decode_entry(bytes).ok_or(EntryError::Corrupt)?;
+
+    for d in header.dependencies.iter() {
+        if hash_source(sources, Path::new(&d.path)) != d.content_hash {
+            return Err(EntryError::StaleDependency(d.path.clone()));
+        }
+    }
+
+    let dependencies = header
+        .dependencies
+        .into_iter()
+        .map(|d| AssetDependency {
+            logical_path: PathBuf::from(d.path),
+            settings_hash: d.settings_hash,
+            type_hint: Arc::from(d.type_hint),
+            usage: Arc::from(d.usage),
+        })
+        .collect();
+
+    Ok(AssetBlob {
+        type_id: Arc::from(header.type_id),
+        format: Arc::from(header.format),
+        payload: payload.to_vec(),
+        meta_json: Arc::from(header.meta_json),
+        dependencies,
+    })
+}
+
+fn decode_entry(bytes: &[u8]) -> Option<(EntryHeader, &[u8])> {
+    if bytes.len() < 12 || &bytes[0..4] != ENTRY_MAGIC {
+        return None;
+    }
+    let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    if version != ENTRY_VERSION {
+        return None;
+    }
+    let header_len = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize;
+    let header_end = 12usize.checked_add(header_len)?;
+    if header_end > bytes.len() {
+        return None;
+    }
+
+    let header: EntryHeader = serde_json::from_slice(&bytes[12..header_end]).ok()?;
+    Some((header, &bytes[header_end..]))
+}
+
+fn hash_source(sources: &[Arc<dyn AssetSource>], logical_path: &Path) -> Option<String> {
+    let s = sources.iter().find(|s| s.exists(logical_path))?;
+    let bytes = s.read(logical_path).ok()?;
+    Some(blake3::hash(&bytes).to_hex().to_string())
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension(format!("{ENTRY_EXT}.tmp{}", std::process::id()));
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp);
+    })
+}
@@ -0,0 +1,408 @@
+//! Web content served from the asset store.
+//!
+//! HTML UI (CEF views, in-engine browsers) loads its pages, scripts and images through
+//! [`EngineContent`], so those files ship through the same sources, archives and paks as every
+//! other asset. Views that support custom schemes resolve `engine://<logical path>` URLs
+//! directly; others point at a [`ContentServer`], a minimal HTTP/1.1 static server that
+//! answers `GET`/`HEAD` from the same resolver.
+//!
+//! Content types come from the importers registered for the file's extension (the text and
+//! image importers advertise them), with a built-in table for web-only formats.
+
+use crate::store::AssetStore;
+
+use log::{debug, info, warn};
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Scheme handled by [`EngineContent::resolve`].
+pub const CONTENT_SCHEME: &str = "engine";
+
+/// Page served for directory URLs.
+const INDEX_FILE: &str = "index.html";
+const DEFAULT_MIME: &str = "application/octet-stream";
+/// Request heads larger than this are rejected.
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+/// Connections served at once; further ones get a 503 until one finishes.
+const MAX_CONNECTIONS: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct ContentResponse {
+    /// HTTP status: 200, 400, 403, 404, 405 or 503.
+    pub status: u16,
+    pub mime: Arc<str>,
+    pub bytes: Vec<u8>,
+}
+
+impl ContentResponse {
+    fn error(status: u16, msg: &str) -> Self {
+        Self {
+            status,
+            mime: Arc::from("text/plain"),
+            bytes: msg.as_bytes().to_vec(),
+        }
+    }
+
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.status == 200
+    }
+}
+
+/// Resolves `engine://` URLs and HTTP paths against an [`AssetStore`].
+#[derive(Clone)]
+pub struct EngineContent {
+    store: Arc<AssetStore>,
+    /// Logical directory prepended to every request (e.g. `ui/web`).
+    root: Option<PathBuf>,
+}
+
+impl EngineContent {
+    #[inline]
+    pub fn new(store: Arc<AssetStore>) -> Self {
+        Self { store, root: None }
+    }
+
+    /// Serves only files below `dir`; `engine://menu.html` then reads `<dir>/menu.html`.
+    #[inline]
+    pub fn with_root(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.root = Some(dir.into());
+        self
+    }
+
+    /// Resolves an `engine://` URL. Query strings and fragments are ignored.
+    pub fn resolve(&self, url: &str) -> ContentResponse {
+        let prefix = format!("{CONTENT_SCHEME}://");
+        match url.get(..prefix.len()) {
+            Some(p) if p.eq_ignore_ascii_case(&prefix) => self.resolve_path(&url[prefix.len()..]),
+            _ => ContentResponse::error(400, "unsupported scheme"),
+        }
+    }
+
+    /// Resolves a URL path (`/ui/index.html`) relative to the content root.
+    pub fn resolve_path(&self, path: &str) -> ContentResponse {
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let Some(decoded) = percent_decode(path) else {
+            return ContentResponse::error(400, "malformed path");
+        };
+        let Some(mut logical) = logical_path(&decoded) else {
+            return ContentResponse::error(400, "invalid path");
+        };
+        if decoded.is_empty() || decoded.ends_with('/') {
+            logical.push(INDEX_FILE);
+        }
+        if let Some(root) = &self.root {
+            logical = root.join(logical);
+        }
+
+        match self.store.read_source(&logical) {
+            Ok(bytes) => ContentResponse {
+                status: 200,
+                mime: self.mime_type(&logical),
+                bytes,
+            },
+            Err(e) => {
+                debug!(
+                    target: "assets::content",
+                    "content.miss path='{}' err='{}'",
+                    logical.display(),
+                    e.msg()
+                );
+                ContentResponse::error(404, "not found")
+            }
+        }
+    }
+
+    fn mime_type(&self, logical: &std::path::Path) -> Arc<str> {
+        if let Some(mime) = self.store.mime_type(logical) {
+            return mime;
+        }
+        let ext = logical
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        Arc::from(web_mime(&ext).unwrap_or(DEFAULT_MIME))
+    }
+}
+
+/// Formats browsers need that no importer covers.
+fn web_mime(ext: &str) -> Option<&'static str> {
+    Some(match ext {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" | "map" => "application/json",
+        "wasm" => "application/wasm",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "txt" => "text/plain",
+        _ => return None,
+    })
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Splits on `/`, drops empty and `.` segments and rejects `..`.
+fn logical_path(path: &str) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for seg in path.split(['/', '\\']) {
+        match seg {
+            "" | "." => {}
+            ".." => return None,
+            s if s.contains(':') => return None,
+            s => out.push(s),
+        }
+    }
+    Some(out)
+}
+
+/// Serves [`EngineContent`] over HTTP until shut down or dropped.
+///
+/// Meant for loopback use by embedded browsers; it has no TLS and no authentication.
+/// Requests whose `Host` is neither the bound address nor localhost are refused, so pages
+/// from other origins cannot reach it through DNS rebinding.
+pub struct ContentServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ContentServer {
+    /// Binds `addr` (e.g. `127.0.0.1:0`) and serves on a background thread.
+    pub fn spawn(addr: impl ToSocketAddrs, content: EngineContent) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+
+        info!(target: "assets::content", "content.listen addr={addr}");
+
+        let stop_flag = stop.clone();
+        let thread = std::thread::Builder::new()
+            .name("content-server".into())
+            .spawn(move || accept_loop(listener, content, stop_flag))?;
+
+        Ok(Self {
+            addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    #[inline]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Base URL to load pages from, e.g. `http://127.0.0.1:5173/`.
+    #[inline]
+    pub fn base_url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    pub fn shutdown(&mut self) {
+        if self.stop.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let _ = TcpStream::connect(self.addr);
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+        info!(target: "assets::content", "content.stopped addr={}", self.addr);
+    }
+}
+
+impl Drop for ContentServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Counts a connection as active until dropped.
+struct ConnSlot(Arc<AtomicUsize>);
+
+impl ConnSlot {
+    fn acquire(active: &Arc<AtomicUsize>) -> Option<Self> {
+        active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < MAX_CONNECTIONS).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Self(active.clone()))
+    }
+}
+
+impl Drop for ConnSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+fn accept_loop(listener: TcpListener, content: EngineContent, stop: Arc<AtomicBool>) {
+    let addr = listener.local_addr().ok();
+    let active = Arc::new(AtomicUsize::new(0));
+    for conn in listener.incoming() {
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        let stream = match conn {
+            Ok(s) => s,
+            Err(e) => {
+                warn!(target: "assets::content", "content.accept failed err='{e}'");
+                continue;
+            }
+        };
+
+        let Some(slot) = ConnSlot::acquire(&active) else {
+            warn!(target: "assets::content", "content.busy limit={MAX_CONNECTIONS}");
+            let resp = ContentResponse::error(503, "too many connections");
+            let _ = write_response(stream, "GET", &resp);
+            continue;
+        };
+
+        let content = content.clone();
+        let spawned = std::thread::Builder::new()
+            .name("content-server-conn".into())
+            .spawn(move || {
+                let _slot = slot;
+                if let Err(e) = serve_http(stream, &content, addr) {
+                    debug!(target: "assets::content", "content.conn err='{e}'");
+                }
+            });
+        if let Err(e) = spawned {
+            warn!(target: "assets::content", "content.spawn failed err='{e}'");
+        }
+    }
+}
+
+/// Reads up to the end of the request head.
+fn read_head(stream: &mut TcpStream) -> io::Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "request head too large"));
+        }
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    String::from_utf8(head).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// One request per connection (`Connection: close`).
+fn serve_http(
+    mut stream: TcpStream,
+    content: &EngineContent,
+    addr: Option<SocketAddr>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let head = read_head(&mut stream)?;
+
+    let mut parts = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+
+    let host = head.lines().skip(1).find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("host")
+            .then(|| value.trim())
+    });
+
+    let resp = match method {
+        _ if !host.is_some_and(|h| host_allowed(h, addr)) => {
+            ContentResponse::error(403, "host not allowed")
+        }
+        "GET" | "HEAD" => content.resolve_path(target),
+        _ => ContentResponse::error(405, "method not allowed"),
+    };
+    debug!(
+        target: "assets::content",
+        "content.request method={method} path='{target}' status={} bytes={}",
+        resp.status,
+        resp.bytes.len()
+    );
+    write_response(stream, method, &resp)
+}
+
+/// `Host` must name the bound address or localhost, with the bound port if one is given.
+fn host_allowed(host: &str, addr: Option<SocketAddr>) -> bool {
+    let Some(addr) = addr else {
+        return false;
+    };
+    if host.eq_ignore_ascii_case(&addr.to_string()) {
+        return true;
+    }
+    let (name, port) = match host.rsplit_once(':') {
+        Some((name, port)) if !port.contains(']') => (name, Some(port)),
+        _ => (host, None),
+    };
+    if port.is_some_and(|p| p.parse::<u16>().ok() != Some(addr.port())) {
+        return false;
+    }
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "localhost" | "127.0.0.1" | "[::1]"
+    )
+}
+
+fn write_response(mut stream: TcpStream, method: &str, resp: &ContentResponse) -> io::Result<()> {
+    let reason = match resp.status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        503 => "Service Unavailable",
+        _ => "Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        resp.status,
+        resp.mime,
+        resp.bytes.len()
+    )?;
+    if method != "HEAD" {
+        stream.write_all(&resp.bytes)?;
+    }
+    stream.flush()?;
+    let _ = stream.shutdown(Shutdown::Both);
+    Ok(())
+}
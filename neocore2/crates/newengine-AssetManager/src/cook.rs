@@ -0,0 +1,772 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Incremental cooking for `nepak serve`.
+//!
+//! [`Cooker`] keeps a record per source file (modification stamp, size, blake3, references)
+//! next to the pack. A pass re-hashes only files whose stamp moved; a source whose content
+//! changed is recooked together with everything that references it, found through a
+//! [`DependencyGraph`]. The pack is rewritten with unchanged entries copied as stored (no
+//! re-read, no recompression) and the manifest is rebuilt from the recorded hashes.
+//!
+//! Cooking a source means reading its references (material shaders and textures), checking
+//! they resolve, and evaluating the content rules; payloads are packed as authored. Error
+//! issues keep the previous pack in place until they are fixed.
+//!
+//! [`CookServer`] pushes a [`CookNotice`] to connected editors after every written pass so
+//! they can remount the pack; [`CookClient`] is the receiving end. Frames use the
+//! [`remote`](crate::remote) wire format.
+
+use crate::deps::DependencyGraph;
+use crate::id::AssetId;
+use crate::material::{MaterialAsset, MATERIAL_EXTENSION};
+use crate::pak::{normalize_entry_path, PakOptions, PakReader, PakStats, PakWriter, PAK_EXTENSION};
+use crate::patch::{ContentManifest, ManifestEntry};
+use crate::remote::{read_json, write_json};
+use crate::types::{AssetError, AssetKey};
+use crate::validate::{
+    AssetFacts, ValidationIssue, ValidationRules, ValidationSeverity, VALIDATION_RULES_PATH,
+};
+
+use log::{debug, info, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Instant, UNIX_EPOCH};
+
+const STATE_VERSION: u32 = 1;
+const NOTICE_VERSION: u32 = 1;
+
+/// Issue rule id for a reference that names no source file.
+const RULE_REFERENCE: &str = "cook.reference";
+/// Issue rule id for a source the cooker could not parse.
+const RULE_PARSE: &str = "cook.parse";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SourceRecord {
+    mtime_ns: u64,
+    size: u64,
+    /// blake3, hex.
+    hash: String,
+    /// Logical paths this source references.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    refs: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CookState {
+    v: u32,
+    /// Content version of the last written pack.
+    version: u64,
+    sources: BTreeMap<String, SourceRecord>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CookOptions {
+    pub src: PathBuf,
+    pub out: PathBuf,
+    /// Manifest rewritten with every pack; `None` skips it.
+    pub manifest: Option<PathBuf>,
+    pub pak: PakOptions,
+    /// Rules file; defaults to [`VALIDATION_RULES_PATH`] inside `src` when present.
+    pub rules: Option<PathBuf>,
+}
+
+impl CookOptions {
+    #[inline]
+    pub fn new(src: impl Into<PathBuf>, out: impl Into<PathBuf>) -> Self {
+        Self {
+            src: src.into(),
+            out: out.into(),
+            manifest: None,
+            pak: PakOptions::default(),
+            rules: None,
+        }
+    }
+
+    #[inline]
+    pub fn with_manifest(mut self, manifest: Option<PathBuf>) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+    #[inline]
+    pub fn with_pak_options(mut self, pak: PakOptions) -> Self {
+        self.pak = pak;
+        self
+    }
+
+    #[inline]
+    pub fn with_rules(mut self, rules: Option<PathBuf>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Per-source records kept between runs (`<out>.cook.json`).
+    #[inline]
+    pub fn state_file(&self) -> PathBuf {
+        self.out
+            .with_extension(format!("{PAK_EXTENSION}.cook.json"))
+    }
+
+    fn rules_file(&self) -> Option<PathBuf> {
+        self.rules.clone().or_else(|| {
+            let p = self.src.join(VALIDATION_RULES_PATH);
+            p.is_file().then_some(p)
+        })
+    }
+}
+
+/// Outcome of one [`Cooker::cook`] pass that found changes.
+#[derive(Debug, Clone, Default)]
+pub struct CookReport {
+    pub version: u64,
+    /// Sources that changed or were added, then their dependents (sorted).
+    pub cooked: Vec<String>,
+    pub removed: Vec<String>,
+    /// Pack entries copied from the previous pack.
+    pub reused: usize,
+    /// Every outstanding issue, not only those of this pass.
+    pub issues: Vec<ValidationIssue>,
+    /// `None` when the pack was not rewritten: error issues kept the previous one, or only
+    /// the rules changed.
+    pub stats: Option<PakStats>,
+}
+
+impl CookReport {
+    #[inline]
+    pub fn errors(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == ValidationSeverity::Error)
+            .count()
+    }
+}
+
+/// Incremental cooker over one assets tree; see the module docs.
+pub struct Cooker {
+    options: CookOptions,
+    state: CookState,
+    graph: DependencyGraph,
+    paths: HashMap<AssetId, String>,
+    issues: BTreeMap<String, Vec<ValidationIssue>>,
+    rules: ValidationRules,
+    rules_hash: Option<blake3::Hash>,
+    /// Set until the first pass, which checks every source since issues are not persisted.
+    fresh: bool,
+}
+
+impl Cooker {
+    /// Loads the records of a previous run; a missing or unreadable state file starts over.
+    pub fn new(options: CookOptions) -> Self {
+        let state_file = options.state_file();
+        let state = match std::fs::read(&state_file) {
+            Ok(bytes) => match serde_json::from_slice::<CookState>(&bytes) {
+                Ok(s) if s.v == STATE_VERSION => s,
+                Ok(_) => CookState::default(),
+                Err(e) => {
+                    warn!(
+                        target: "assets::cook",
+                        "cook.state unreadable file='{}' err='{}'",
+                        state_file.display(),
+                        e
+                    );
+                    CookState::default()
+                }
+            },
+            Err(_) => CookState::default(),
+        };
+
+        let mut cooker = Self {
+            options,
+            state,
+            graph: DependencyGraph::new(),
+            paths: HashMap::new(),
+            issues: BTreeMap::new(),
+            rules: ValidationRules::default(),
+            rules_hash: None,
+            fresh: true,
+        };
+        let records: Vec<(String, Vec<String>)> = cooker
+            .state
+            .sources
+            .iter()
+            .map(|(k, r)| (k.clone(), r.refs.clone()))
+            .collect();
+        for (key, refs) in records {
+            cooker.link(&key, &refs);
+        }
+        cooker
+    }
+
+    #[inline]
+    pub fn options(&self) -> &CookOptions {
+        &self.options
+    }
+
+    /// Content version of the last written pack.
+    #[inline]
+    pub fn version(&self) -> u64 {
+        self.state.version
+    }
+
+    /// Scans the tree and, if anything changed (or on the first pass), recooks the affected
+    /// sources and rewrites the pack. Returns `None` when the tree is unchanged.
+    pub fn cook(&mut self) -> Result<Option<CookReport>, AssetError> {
+        let t0 = Instant::now();
+        let mut loaded: HashMap<String, Vec<u8>> = HashMap::new();
+        let mut changed: BTreeSet<String> = BTreeSet::new();
+        let mut touched = false;
+        let mut sources: BTreeMap<String, SourceRecord> = BTreeMap::new();
+
+        for (key, path) in self.scan()? {
+            let meta = std::fs::metadata(&path)
+                .map_err(|e| AssetError::new(format!("cook: stat '{}': {}", path.display(), e)))?;
+            let mtime_ns = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_nanos() as u64);
+
+            let prev = self.state.sources.get(&key);
+            if let Some(prev) = prev.filter(|p| p.mtime_ns == mtime_ns && p.size == meta.len()) {
+                sources.insert(key, prev.clone());
+                continue;
+            }
+
+            let bytes = std::fs::read(&path)
+                .map_err(|e| AssetError::new(format!("cook: read '{}': {}", path.display(), e)))?;
+            let hash = blake3::hash(&bytes).to_hex().to_string();
+            let record = match prev {
+                Some(prev) if prev.hash == hash => {
+                    touched = true;
+                    SourceRecord {
+                        mtime_ns,
+                        size: bytes.len() as u64,
+                        ..prev.clone()
+                    }
+                }
+                _ => {
+                    changed.insert(key.clone());
+                    SourceRecord {
+                        mtime_ns,
+                        size: bytes.len() as u64,
+                        hash,
+                        refs: scan_refs(&key, &bytes),
+                    }
+                }
+            };
+            sources.insert(key.clone(), record);
+            loaded.insert(key, bytes);
+        }
+
+        let removed: Vec<String> = self
+            .state
+            .sources
+            .keys()
+            .filter(|k| !sources.contains_key(*k))
+            .cloned()
+            .collect();
+
+        let rules_changed = self.reload_rules()?;
+        let repack = !changed.is_empty() || !removed.is_empty() || !self.options.out.is_file();
+
+        if !repack && !rules_changed && !self.fresh {
+            if touched {
+                self.state.sources = sources;
+                self.save_state()?;
+            }
+            return Ok(None);
+        }
+
+        for key in changed.iter() {
+            let refs = sources[key].refs.clone();
+            self.link(key, &refs);
+        }
+        for key in removed.iter() {
+            self.graph.clear_dependencies(asset_id(key));
+            self.issues.remove(key);
+        }
+
+        // Changed and removed sources invalidate everything that (transitively) references them.
+        let mut dirty: BTreeSet<String> = changed.clone();
+        for key in changed.iter().chain(removed.iter()) {
+            for id in self.graph.dependents_transitive(asset_id(key)) {
+                if let Some(p) = self.paths.get(&id).filter(|p| sources.contains_key(*p)) {
+                    dirty.insert(p.clone());
+                }
+            }
+        }
+        if rules_changed || self.fresh {
+            dirty.extend(sources.keys().cloned());
+        }
+
+        for key in dirty.iter() {
+            if !loaded.contains_key(key) {
+                let path = self.options.src.join(key);
+                let b = std::fs::read(&path).map_err(|e| {
+                    AssetError::new(format!("cook: read '{}': {}", path.display(), e))
+                })?;
+                loaded.insert(key.clone(), b);
+            }
+            let issues = self.check(key, &loaded[key], &sources);
+            if issues.is_empty() {
+                self.issues.remove(key);
+            } else {
+                self.issues.insert(key.clone(), issues);
+            }
+        }
+
+        self.fresh = false;
+        self.state.sources = sources;
+
+        let mut report = CookReport {
+            cooked: dirty.into_iter().collect(),
+            removed,
+            issues: self.issues.values().flatten().cloned().collect(),
+            ..CookReport::default()
+        };
+
+        let errors = report.errors();
+        if errors > 0 {
+            self.save_state()?;
+            report.version = self.state.version;
+            warn!(
+                target: "assets::cook",
+                "cook.blocked errors={} cooked={} dt_ms={}",
+                errors,
+                report.cooked.len(),
+                t0.elapsed().as_millis()
+            );
+            return Ok(Some(report));
+        }
+        if !repack {
+            self.save_state()?;
+            report.version = self.state.version;
+            return Ok(Some(report));
+        }
+
+        let (stats, reused) = self.write_pak(&mut loaded)?;
+        self.state.version += 1;
+        if let Some(path) = self.options.manifest.clone() {
+            self.write_manifest(&path)?;
+        }
+        self.save_state()?;
+
+        report.version = self.state.version;
+        report.reused = reused;
+        report.stats = Some(stats);
+
+        info!(
+            target: "assets::cook",
+            "cook.pass version={} cooked={} removed={} reused={} warnings={} dt_ms={}",
+            report.version,
+            report.cooked.len(),
+            report.removed.len(),
+            reused,
+            report.issues.len(),
+            t0.elapsed().as_millis()
+        );
+        Ok(Some(report))
+    }
+
+    /// Files under `src`, keyed by entry path. Hidden entries are skipped like
+    /// [`PakWriter::add_dir`] does, as are the cooker's own outputs.
+    fn scan(&self) -> Result<Vec<(String, PathBuf)>, AssetError> {
+        let root = &self.options.src;
+        let outputs = [
+            Some(self.options.out.clone()),
+            Some(self.options.state_file()),
+            self.options.manifest.clone(),
+        ];
+
+        let mut out = Vec::new();
+        let mut stack = vec![root.to_path_buf()];
+        while let Some(dir) = stack.pop() {
+            let rd = std::fs::read_dir(&dir).map_err(|e| {
+                AssetError::new(format!("cook: read_dir '{}': {}", dir.display(), e))
+            })?;
+
+            for e in rd.flatten() {
+                let p = e.path();
+                let hidden = p
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with('.'));
+                if hidden || outputs.iter().flatten().any(|o| *o == p) {
+                    continue;
+                }
+                if p.is_dir() {
+                    stack.push(p);
+                    continue;
+                }
+                let Ok(rel) = p.strip_prefix(root) else {
+                    continue;
+                };
+                out.push((normalize_entry_path(&rel.to_string_lossy()), p));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Re-reads the rules file; `true` if its content differs from the last pass.
+    fn reload_rules(&mut self) -> Result<bool, AssetError> {
+        let bytes =
+            match self.options.rules_file() {
+                Some(path) => Some(std::fs::read(&path).map_err(|e| {
+                    AssetError::new(format!("cook: read '{}': {}", path.display(), e))
+                })?),
+                None => None,
+            };
+        let hash = bytes.as_deref().map(blake3::hash);
+        if hash == self.rules_hash {
+            return Ok(false);
+        }
+
+        self.rules = match bytes {
+            Some(b) => ValidationRules::from_json(&String::from_utf8_lossy(&b))?,
+            None => ValidationRules::default(),
+        };
+        self.rules_hash = hash;
+        Ok(true)
+    }
+
+    fn check(
+        &self,
+        key: &str,
+        bytes: &[u8],
+        sources: &BTreeMap<String, SourceRecord>,
+    ) -> Vec<ValidationIssue> {
+        let issue = |rule: &str, severity: ValidationSeverity, message: String| ValidationIssue {
+            rule: rule.to_owned(),
+            path: key.to_owned(),
+            severity,
+            message,
+        };
+
+        let mut out = Vec::new();
+        if is_material(key) {
+            if let Err(e) = MaterialAsset::from_json(bytes).and_then(|m| m.validate()) {
+                out.push(issue(RULE_PARSE, ValidationSeverity::Error, e.to_string()));
+            }
+        }
+        if let Some(record) = sources.get(key) {
+            for r in record.refs.iter().filter(|r| !sources.contains_key(*r)) {
+                out.push(issue(
+                    RULE_REFERENCE,
+                    ValidationSeverity::Warning,
+                    format!("references missing '{r}'"),
+                ));
+            }
+        }
+        out.extend(
+            self.rules
+                .evaluate(key, &AssetFacts::from_source(key, bytes)),
+        );
+        out
+    }
+
+    #[inline]
+    fn link(&mut self, key: &str, refs: &[String]) {
+        self.paths.insert(asset_id(key), key.to_owned());
+        for r in refs {
+            self.paths.insert(asset_id(r), r.clone());
+        }
+        self.graph
+            .set_dependencies(asset_id(key), refs.iter().map(|r| asset_id(r)));
+    }
+
+    /// Rewrites the pack, copying entries whose hash is unchanged from the previous one.
+    fn write_pak(
+        &self,
+        loaded: &mut HashMap<String, Vec<u8>>,
+    ) -> Result<(PakStats, usize), AssetError> {
+        let out = &self.options.out;
+        let mut w = PakWriter::new(self.options.pak.clone());
+        let mut reused = 0usize;
+
+        // The previous pack is copied out and closed before the new one replaces it.
+        let prev = out.is_file().then(|| PakReader::open(out)).and_then(|r| {
+            r.map_err(|e| warn!(target: "assets::cook", "cook.pak rebuilding: {e}"))
+                .ok()
+        });
+
+        for (key, record) in self.state.sources.iter() {
+            let same = prev
+                .as_ref()
+                .and_then(|r| r.entry(key))
+                .is_some_and(|e| blake3::Hash::from(e.hash).to_hex().as_str() == record.hash);
+            if same && w.add_from_pak(prev.as_ref().unwrap(), key) {
+                reused += 1;
+                continue;
+            }
+
+            let bytes = match loaded.remove(key) {
+                Some(b) => b,
+                None => {
+                    let path = self.options.src.join(key);
+                    std::fs::read(&path).map_err(|e| {
+                        AssetError::new(format!("cook: read '{}': {}", path.display(), e))
+                    })?
+                }
+            };
+            w.add_bytes(key, bytes);
+        }
+        drop(prev);
+
+        for c in w.case_collisions() {
+            warn!(target: "assets::cook", "cook.case_collision {c}");
+        }
+        let stats = w.write_to(out)?;
+        Ok((stats, reused))
+    }
+
+    /// Rebuilds the manifest entries from the records; published patches of entries that
+    /// still exist are kept.
+    fn write_manifest(&self, path: &Path) -> Result<(), AssetError> {
+        let mut m = std::fs::read(path)
+            .ok()
+            .and_then(|b| serde_json::from_slice::<ContentManifest>(&b).ok())
+            .unwrap_or_default();
+
+        m.version = self.state.version;
+        m.entries = self
+            .state
+            .sources
+            .iter()
+            .map(|(k, r)| {
+                (
+                    k.clone(),
+                    ManifestEntry {
+                        hash: r.hash.clone(),
+                        size: r.size,
+                    },
+                )
+            })
+            .collect();
+        let entries = &m.entries;
+        m.patches.retain(|p| entries.contains_key(&p.path));
+
+        let json = serde_json::to_string_pretty(&m)
+            .map_err(|e| AssetError::new(format!("cook: manifest: {e}")))?;
+        write_atomic(path, json.as_bytes())
+    }
+
+    fn save_state(&self) -> Result<(), AssetError> {
+        let json = serde_json::to_vec(&CookState {
+            v: STATE_VERSION,
+            version: self.state.version,
+            sources: self.state.sources.clone(),
+        })
+        .map_err(|e| AssetError::new(format!("cook: state: {e}")))?;
+        write_atomic(&self.options.state_file(), &json)
+    }
+}
+
+#[inline]
+fn asset_id(key: &str) -> AssetId {
+    AssetKey::new(key, 0).id()
+}
+
+#[inline]
+fn is_material(key: &str) -> bool {
+    key.rsplit_once('.')
+        .is_some_and(|(_, ext)| ext.eq_ignore_ascii_case(MATERIAL_EXTENSION))
+}
+
+/// Logical paths a source references; only materials reference other files today.
+fn scan_refs(key: &str, bytes: &[u8]) -> Vec<String> {
+    if !is_material(key) {
+        return Vec::new();
+    }
+    let Ok(m) = MaterialAsset::from_json(bytes) else {
+        return Vec::new();
+    };
+    let mut refs: Vec<String> = m
+        .dependencies()
+        .iter()
+        .map(|d| normalize_entry_path(&d.logical_path.to_string_lossy()))
+        .collect();
+    refs.sort();
+    refs.dedup();
+    refs
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), AssetError> {
+    let io = |e: io::Error| AssetError::new(format!("cook: write '{}': {}", path.display(), e));
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(io)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, bytes).map_err(io)?;
+    std::fs::rename(&tmp, path).map_err(io)
+}
+
+/// Sent to clients after every written pass, and once on connect with the current state.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CookNotice {
+    pub v: u32,
+    pub version: u64,
+    /// The pack path as given to the cooker.
+    pub pak: String,
+    #[serde(default)]
+    pub cooked: Vec<String>,
+    #[serde(default)]
+    pub removed: Vec<String>,
+}
+
+impl CookNotice {
+    pub fn from_report(options: &CookOptions, report: &CookReport) -> Self {
+        Self {
+            v: NOTICE_VERSION,
+            version: report.version,
+            pak: options.out.to_string_lossy().into_owned(),
+            cooked: report.cooked.clone(),
+            removed: report.removed.clone(),
+        }
+    }
+}
+
+/// Pushes [`CookNotice`]s to connected clients until shut down or dropped.
+pub struct CookServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+    last: Arc<Mutex<Option<CookNotice>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CookServer {
+    /// Binds `addr` and starts accepting clients on a background thread.
+    pub fn spawn(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let clients: Arc<Mutex<Vec<TcpStream>>> = Arc::new(Mutex::new(Vec::new()));
+        let last: Arc<Mutex<Option<CookNotice>>> = Arc::new(Mutex::new(None));
+
+        info!(target: "assets::cook", "cook.server listen addr={addr}");
+
+        let (stop_flag, clients_in, last_in) = (stop.clone(), clients.clone(), last.clone());
+        let thread = std::thread::Builder::new()
+            .name("cook-server".into())
+            .spawn(move || {
+                for conn in listener.incoming() {
+                    if stop_flag.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let mut stream = match conn {
+                        Ok(s) => s,
+                        Err(e) => {
+                            warn!(target: "assets::cook", "cook.server accept failed err='{e}'");
+                            continue;
+                        }
+                    };
+                    let _ = stream.set_nodelay(true);
+                    // Late joiners learn the current version right away.
+                    let greeted = match last_in.lock().as_ref() {
+                        Some(n) => write_json(&mut stream, n).is_ok(),
+                        None => true,
+                    };
+                    if greeted {
+                        debug!(
+                            target: "assets::cook",
+                            "cook.server client connected peer={:?}",
+                            stream.peer_addr().ok()
+                        );
+                        clients_in.lock().push(stream);
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            addr,
+            stop,
+            clients,
+            last,
+            thread: Some(thread),
+        })
+    }
+
+    #[inline]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    #[inline]
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().len()
+    }
+
+    /// Sends `notice` to every client; clients whose connection fails are dropped.
+    pub fn broadcast(&self, notice: &CookNotice) {
+        *self.last.lock() = Some(notice.clone());
+        self.clients
+            .lock()
+            .retain_mut(|s| match write_json(s, notice) {
+                Ok(()) => true,
+                Err(e) => {
+                    debug!(target: "assets::cook", "cook.server client dropped err='{e}'");
+                    let _ = s.shutdown(Shutdown::Both);
+                    false
+                }
+            });
+    }
+
+    pub fn shutdown(&mut self) {
+        if self.stop.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // Wake the blocking accept.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+        for s in self.clients.lock().drain(..) {
+            let _ = s.shutdown(Shutdown::Both);
+        }
+        info!(target: "assets::cook", "cook.server stopped addr={}", self.addr);
+    }
+}
+
+impl Drop for CookServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Editor-side connection to a [`CookServer`].
+pub struct CookClient {
+    stream: TcpStream,
+}
+
+impl CookClient {
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        let _ = stream.set_nodelay(true);
+        Ok(Self { stream })
+    }
+
+    /// Blocks until the next notice. Notices of a newer protocol version are rejected.
+    pub fn recv(&mut self) -> io::Result<CookNotice> {
+        let n: CookNotice = read_json(&mut self.stream)?;
+        if n.v != NOTICE_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("cook notice version {} not supported", n.v),
+            ));
+        }
+        Ok(n)
+    }
+
+    #[inline]
+    pub fn set_read_timeout(&self, timeout: Option<std::time::Duration>) -> io::Result<()> {
+        self.stream.set_read_timeout(timeout)
+    }
+}
@@ -0,0 +1,282 @@
+use crate::id::AssetId;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+/// Directed asset dependency graph.
+///
+/// Edges point from a dependent to its dependency (`material -> texture`).
+/// The reverse map is kept in sync so cascades are O(dependents).
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    forward: HashMap<AssetId, Vec<AssetId>>,
+    reverse: HashMap<AssetId, HashSet<AssetId>>,
+}
+
+impl DependencyGraph {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the outgoing edges of `id`. Self-edges are ignored.
+    pub fn set_dependencies(&mut self, id: AssetId, deps: impl IntoIterator<Item = AssetId>) {
+        self.clear_dependencies(id);
+
+        let mut list: Vec<AssetId> = deps.into_iter().filter(|d| *d != id).collect();
+        list.sort();
+        list.dedup();
+
+        if list.is_empty() {
+            return;
+        }
+
+        for d in list.iter() {
+            self.reverse.entry(*d).or_default().insert(id);
+        }
+        self.forward.insert(id, list);
+    }
+
+    /// Removes the outgoing edges of `id`. Incoming edges (who depends on `id`) are kept:
+    /// dependents still reference the asset even if it is unloaded.
+    pub fn clear_dependencies(&mut self, id: AssetId) {
+        let Some(old) = self.forward.remove(&id) else {
+            return;
+        };
+        for d in old {
+            if let Some(set) = self.reverse.get_mut(&d) {
+                set.remove(&id);
+                if set.is_empty() {
+                    self.reverse.remove(&d);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub fn dependencies(&self, id: AssetId) -> &[AssetId] {
+        self.forward.get(&id).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Direct dependents (sorted for determinism).
+    pub fn dependents(&self, id: AssetId) -> Vec<AssetId> {
+        let mut out: Vec<AssetId> = self
+            .reverse
+            .get(&id)
+            .map(|s| s.iter().copied().collect())
+            .unwrap_or_default();
+        out.sort();
+        out
+    }
+
+    /// Transitive dependents in breadth-first order (nearest first), excluding `id`.
+    /// Cycles are tolerated: every asset is visited at most once.
+    pub fn dependents_transitive(&self, id: AssetId) -> Vec<AssetId> {
+        let mut seen: HashSet<AssetId> = HashSet::new();
+        let mut out = Vec::new();
+        let mut q: VecDeque<AssetId> = VecDeque::new();
+
+        seen.insert(id);
+        q.push_back(id);
+
+        while let Some(cur) = q.pop_front() {
+            for d in self.dependents(cur) {
+                if seen.insert(d) {
+                    out.push(d);
+                    q.push_back(d);
+                }
+            }
+        }
+
+        out
+    }
+
+    #[inline]
+    pub fn edge_count(&self) -> usize {
+        self.forward.values().map(|v| v.len()).sum()
+    }
+
+    /// All `(dependent, dependency)` edges, sorted.
+    pub fn edges(&self) -> Vec<(AssetId, AssetId)> {
+        let mut out: Vec<(AssetId, AssetId)> = self
+            .forward
+            .iter()
+            .flat_map(|(from, to)| to.iter().map(move |t| (*from, *t)))
+            .collect();
+        out.sort();
+        out
+    }
+}
+
+/// Asset as seen by [`DependencyGraphExport`].
+#[derive(Debug, Clone)]
+pub struct DependencyGraphNode {
+    pub id: AssetId,
+    pub path: String,
+    /// `unloaded`, `loading`, `ready`, `failed` or `unknown` (referenced, never requested).
+    pub state: &'static str,
+    pub type_id: Option<String>,
+    /// Imported payload size, when ready.
+    pub bytes: Option<u64>,
+    pub dirty: bool,
+}
+
+/// Snapshot of the dependency graph with per-asset state and size, for DOT/JSON export.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraphExport {
+    /// Sorted by id.
+    pub nodes: Vec<DependencyGraphNode>,
+    /// `(dependent, dependency)`, sorted.
+    pub edges: Vec<(AssetId, AssetId)>,
+}
+
+impl DependencyGraphExport {
+    #[inline]
+    pub fn node(&self, id: AssetId) -> Option<&DependencyGraphNode> {
+        self.nodes
+            .binary_search_by_key(&id, |n| n.id)
+            .ok()
+            .map(|i| &self.nodes[i])
+    }
+
+    /// Assets within `depth` edges of `root`, following edges in both directions.
+    pub fn subgraph(&self, root: AssetId, depth: usize) -> Self {
+        let mut adj: HashMap<AssetId, Vec<AssetId>> = HashMap::new();
+        for (a, b) in self.edges.iter() {
+            adj.entry(*a).or_default().push(*b);
+            adj.entry(*b).or_default().push(*a);
+        }
+
+        let mut keep: HashSet<AssetId> = HashSet::new();
+        let mut q: VecDeque<(AssetId, usize)> = VecDeque::new();
+        keep.insert(root);
+        q.push_back((root, 0));
+
+        while let Some((cur, d)) = q.pop_front() {
+            if d >= depth {
+                continue;
+            }
+            for n in adj.get(&cur).map(|v| v.as_slice()).unwrap_or(&[]) {
+                if keep.insert(*n) {
+                    q.push_back((*n, d + 1));
+                }
+            }
+        }
+
+        Self {
+            nodes: self
+                .nodes
+                .iter()
+                .filter(|n| keep.contains(&n.id))
+                .cloned()
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter(|(a, b)| keep.contains(a) && keep.contains(b))
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Payload bytes of `id` plus everything it transitively depends on (each asset counted once).
+    #[inline]
+    pub fn transitive_bytes(&self, id: AssetId) -> u64 {
+        self.transitive_bytes_in(&self.forward_map(), id)
+    }
+
+    fn forward_map(&self) -> HashMap<AssetId, Vec<AssetId>> {
+        let mut fwd: HashMap<AssetId, Vec<AssetId>> = HashMap::new();
+        for (a, b) in self.edges.iter() {
+            fwd.entry(*a).or_default().push(*b);
+        }
+        fwd
+    }
+
+    fn transitive_bytes_in(&self, fwd: &HashMap<AssetId, Vec<AssetId>>, id: AssetId) -> u64 {
+        let mut seen: HashSet<AssetId> = HashSet::new();
+        let mut stack = vec![id];
+        let mut total = 0u64;
+        while let Some(cur) = stack.pop() {
+            if !seen.insert(cur) {
+                continue;
+            }
+            total += self.node(cur).and_then(|n| n.bytes).unwrap_or(0);
+            if let Some(next) = fwd.get(&cur) {
+                stack.extend(next.iter().copied());
+            }
+        }
+        total
+    }
+
+    /// Graphviz DOT. Edges point from dependent to dependency; failed assets are red,
+    /// not-ready ones dashed.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph assets {\n  rankdir=LR;\n  node [shape=box, fontname=\"monospace\"];\n");
+
+        for n in self.nodes.iter() {
+            let label = match n.bytes {
+                Some(b) => format!("{}\\n{} {}", dot_escape(&n.path), n.state, format_bytes(b)),
+                None => format!("{}\\n{}", dot_escape(&n.path), n.state),
+            };
+            let style = match n.state {
+                "failed" => ", color=red, fontcolor=red",
+                "ready" if n.dirty => ", color=orange",
+                "ready" => "",
+                _ => ", style=dashed",
+            };
+            let _ = writeln!(out, "  \"{:032x}\" [label=\"{label}\"{style}];", n.id.to_u128());
+        }
+
+        for (a, b) in self.edges.iter() {
+            let _ = writeln!(out, "  \"{:032x}\" -> \"{:032x}\";", a.to_u128(), b.to_u128());
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_json(&self) -> Value {
+        let index: BTreeMap<AssetId, usize> =
+            self.nodes.iter().enumerate().map(|(i, n)| (n.id, i)).collect();
+        let fwd = self.forward_map();
+
+        let nodes: Vec<Value> = self
+            .nodes
+            .iter()
+            .map(|n| {
+                json!({
+                    "id_u128": format!("{:032x}", n.id.to_u128()),
+                    "path": n.path,
+                    "state": n.state,
+                    "type_id": n.type_id,
+                    "bytes": n.bytes,
+                    "transitive_bytes": self.transitive_bytes_in(&fwd, n.id),
+                    "dirty": n.dirty,
+                })
+            })
+            .collect();
+
+        let edges: Vec<Value> = self
+            .edges
+            .iter()
+            .filter_map(|(a, b)| Some(json!([index.get(a)?, index.get(b)?])))
+            .collect();
+
+        json!({ "nodes": nodes, "edges": edges })
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn format_bytes(b: u64) -> String {
+    if b >= 1 << 20 {
+        format!("{:.1} MiB", b as f64 / (1u64 << 20) as f64)
+    } else if b >= 1 << 10 {
+        format!("{:.1} KiB", b as f64 / 1024.0)
+    } else {
+        format!("{b} B")
+    }
+}
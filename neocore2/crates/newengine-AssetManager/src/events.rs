@@ -13,4 +13,37 @@ pub enum AssetEvent {
         type_id: Arc<str>,
         error: Arc<str>,
     },
-}
\ No newline at end of file
+    /// The asset was explicitly unloaded (blob dropped).
+    Unloaded {
+        id: AssetId,
+    },
+    /// The blob was dropped to keep its category within the store's memory budget. The
+    /// asset is `Unloaded`; loading it again re-imports it.
+    Evicted {
+        id: AssetId,
+    },
+    /// A queued import was dropped because every requester's cancel token fired.
+    Cancelled {
+        id: AssetId,
+    },
+    /// A (transitive) dependency was reloaded or unloaded; `id` is now dirty and will be
+    /// re-imported once `dependency` becomes ready again.
+    DependencyChanged {
+        id: AssetId,
+        dependency: AssetId,
+    },
+}
+
+impl AssetEvent {
+    #[inline]
+    pub fn id(&self) -> AssetId {
+        match self {
+            AssetEvent::Ready { id, .. }
+            | AssetEvent::Failed { id, .. }
+            | AssetEvent::Unloaded { id }
+            | AssetEvent::Evicted { id }
+            | AssetEvent::Cancelled { id }
+            | AssetEvent::DependencyChanged { id, .. } => *id,
+        }
+    }
+}
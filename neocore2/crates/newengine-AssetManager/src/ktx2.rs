@@ -0,0 +1,411 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! KTX2 containers as produced by the image importer (`container: "ktx2"`, payload passed
+//! through untouched).
+//!
+//! Plain KTX2 files (BCn/ETC2/ASTC/RGBA8 with no supercompression) are sliced straight into
+//! [`TextureAsset`] mips. Basis Universal data (ETC1S/BasisLZ or UASTC) stays supercompressed
+//! on disk and is transcoded at load time into whatever block format the active render
+//! backend samples, picked by [`TranscodeTarget::select`].
+
+use crate::texture::{
+    TextureAsset, TextureDesc, TextureFormat, TextureKind, TextureMip, TextureSubresource,
+};
+use crate::types::AssetError;
+
+pub const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+
+const HEADER_LEN: usize = 80;
+const LEVEL_INDEX_ENTRY: usize = 24;
+
+const KHR_DF_MODEL_ETC1S: u8 = 163;
+const KHR_DF_MODEL_UASTC: u8 = 166;
+
+const KHR_DF_CHANNEL_UASTC_RGBA: u8 = 3;
+const KHR_DF_CHANNEL_UASTC_RRRG: u8 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ktx2Supercompression {
+    None,
+    BasisLz,
+    Zstd,
+    Zlib,
+    Other(u32),
+}
+
+impl Ktx2Supercompression {
+    #[inline]
+    fn from_u32(v: u32) -> Self {
+        match v {
+            0 => Self::None,
+            1 => Self::BasisLz,
+            2 => Self::Zstd,
+            3 => Self::Zlib,
+            v => Self::Other(v),
+        }
+    }
+}
+
+/// Basis Universal codec stored in the container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BasisCodec {
+    Etc1s,
+    Uastc,
+}
+
+/// One entry of the KTX2 level index. Offsets are absolute within the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ktx2Level {
+    pub offset: u64,
+    pub length: u64,
+    pub uncompressed_length: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ktx2Header {
+    pub vk_format: u32,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub layers: u32,
+    pub faces: u32,
+    pub supercompression: Ktx2Supercompression,
+    /// Level 0 (largest) first.
+    pub levels: Vec<Ktx2Level>,
+    pub basis: Option<BasisCodec>,
+    pub has_alpha: bool,
+}
+
+#[inline]
+fn u32_at(b: &[u8], off: usize) -> Option<u32> {
+    b.get(off..off + 4)
+        .map(|s| u32::from_le_bytes([s[0], s[1], s[2], s[3]]))
+}
+
+#[inline]
+fn u64_at(b: &[u8], off: usize) -> Option<u64> {
+    b.get(off..off + 8).map(|s| {
+        let mut a = [0u8; 8];
+        a.copy_from_slice(s);
+        u64::from_le_bytes(a)
+    })
+}
+
+#[inline]
+fn trunc() -> AssetError {
+    AssetError::new("ktx2: truncated header")
+}
+
+impl Ktx2Header {
+    #[inline]
+    pub fn sniff(bytes: &[u8]) -> bool {
+        bytes.len() >= KTX2_IDENTIFIER.len() && bytes[..KTX2_IDENTIFIER.len()] == KTX2_IDENTIFIER
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, AssetError> {
+        if !Self::sniff(bytes) {
+            return Err(AssetError::new("ktx2: bad identifier"));
+        }
+        if bytes.len() < HEADER_LEN {
+            return Err(trunc());
+        }
+
+        let vk_format = u32_at(bytes, 12).ok_or_else(trunc)?;
+        let width = u32_at(bytes, 20).ok_or_else(trunc)?;
+        let height = u32_at(bytes, 24).ok_or_else(trunc)?;
+        let depth = u32_at(bytes, 28).ok_or_else(trunc)?;
+        let layers = u32_at(bytes, 32).ok_or_else(trunc)?;
+        let faces = u32_at(bytes, 36).ok_or_else(trunc)?;
+        let level_count = u32_at(bytes, 40).ok_or_else(trunc)?.max(1);
+        let supercompression = Ktx2Supercompression::from_u32(u32_at(bytes, 44).ok_or_else(trunc)?);
+        let dfd_offset = u32_at(bytes, 48).ok_or_else(trunc)? as usize;
+
+        if width == 0 || (faces != 1 && faces != 6) {
+            return Err(AssetError::new(format!(
+                "ktx2: unsupported shape width={width} faces={faces}"
+            )));
+        }
+
+        let mut levels = Vec::with_capacity(level_count as usize);
+        for i in 0..level_count as usize {
+            let at = HEADER_LEN + i * LEVEL_INDEX_ENTRY;
+            let level = Ktx2Level {
+                offset: u64_at(bytes, at).ok_or_else(trunc)?,
+                length: u64_at(bytes, at + 8).ok_or_else(trunc)?,
+                uncompressed_length: u64_at(bytes, at + 16).ok_or_else(trunc)?,
+            };
+            if level.offset.saturating_add(level.length) > bytes.len() as u64 {
+                return Err(AssetError::new(format!("ktx2: level {i} out of bounds")));
+            }
+            levels.push(level);
+        }
+
+        let (basis, has_alpha) = Self::parse_dfd(bytes, dfd_offset, vk_format, supercompression);
+
+        Ok(Self {
+            vk_format,
+            width,
+            height: height.max(1),
+            depth: depth.max(1),
+            layers: layers.max(1),
+            faces,
+            supercompression,
+            levels,
+            basis,
+            has_alpha,
+        })
+    }
+
+    /// Reads the color model and sample channels of the basic data format descriptor.
+    fn parse_dfd(
+        bytes: &[u8],
+        dfd_offset: usize,
+        vk_format: u32,
+        supercompression: Ktx2Supercompression,
+    ) -> (Option<BasisCodec>, bool) {
+        // Block starts after the u32 total size.
+        let block = dfd_offset + 4;
+        let model = bytes.get(block + 8).copied();
+        let block_size = u32_at(bytes, block + 4).map(|v| (v >> 16) as usize).unwrap_or(0);
+        let samples = block_size.saturating_sub(24) / 16;
+        let channel = |i: usize| u32_at(bytes, block + 24 + i * 16).map(|w| ((w >> 24) & 0x0F) as u8);
+
+        let basis = match (vk_format, model, supercompression) {
+            (0, Some(KHR_DF_MODEL_UASTC), _) => Some(BasisCodec::Uastc),
+            (0, Some(KHR_DF_MODEL_ETC1S), _) | (0, _, Ktx2Supercompression::BasisLz) => {
+                Some(BasisCodec::Etc1s)
+            }
+            _ => None,
+        };
+
+        let has_alpha = match basis {
+            // ETC1S stores alpha as a second (AAA) slice.
+            Some(BasisCodec::Etc1s) => samples >= 2,
+            Some(BasisCodec::Uastc) => matches!(
+                channel(0),
+                Some(KHR_DF_CHANNEL_UASTC_RGBA) | Some(KHR_DF_CHANNEL_UASTC_RRRG)
+            ),
+            None => !matches!(native_format(vk_format), Some(TextureFormat::Bc1RgbUnorm)),
+        };
+
+        (basis, has_alpha)
+    }
+
+    /// Extent of mip `level` (clamped to 1).
+    #[inline]
+    pub fn level_extent(&self, level: u32) -> (u32, u32, u32) {
+        (
+            (self.width >> level).max(1),
+            (self.height >> level).max(1),
+            (self.depth >> level).max(1),
+        )
+    }
+
+    /// Array layers times cube faces.
+    #[inline]
+    pub fn subresources(&self) -> u32 {
+        self.layers * self.faces
+    }
+
+    /// Describes the texture in the format it will have after [`ktx2_to_texture_asset`].
+    pub fn texture_desc(&self, format: TextureFormat) -> TextureDesc {
+        TextureDesc {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            layers: self.subresources(),
+            mip_count: self.levels.len() as u32,
+            format,
+            kind: if self.faces == 6 {
+                TextureKind::Cube
+            } else if self.depth > 1 {
+                TextureKind::Tex3D
+            } else {
+                TextureKind::Tex2D
+            },
+        }
+    }
+
+    /// Format as stored in the file (`BasisEtc1s`/`BasisUastc` for Basis data).
+    #[inline]
+    pub fn stored_format(&self) -> Option<TextureFormat> {
+        match self.basis {
+            Some(BasisCodec::Etc1s) => Some(TextureFormat::BasisEtc1s),
+            Some(BasisCodec::Uastc) => Some(TextureFormat::BasisUastc),
+            None => native_format(self.vk_format),
+        }
+    }
+}
+
+/// `VkFormat` values we can upload without conversion.
+fn native_format(vk_format: u32) -> Option<TextureFormat> {
+    Some(match vk_format {
+        37 | 43 => TextureFormat::Rgba8Unorm,
+        131 | 132 => TextureFormat::Bc1RgbUnorm,
+        133 | 134 => TextureFormat::Bc1RgbaUnorm,
+        135 | 136 => TextureFormat::Bc2Unorm,
+        137 | 138 => TextureFormat::Bc3Unorm,
+        139 => TextureFormat::Bc4Unorm,
+        141 => TextureFormat::Bc5Unorm,
+        145 | 146 => TextureFormat::Bc7Unorm,
+        151 | 152 => TextureFormat::Etc2Rgba8Unorm,
+        157 | 158 => TextureFormat::Astc4x4Unorm,
+        _ => return None,
+    })
+}
+
+/// Compressed families the GPU can sample; filled from the render backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextureCompressionSupport {
+    pub bc: bool,
+    pub etc2: bool,
+    pub astc_ldr: bool,
+}
+
+/// GPU format Basis data is transcoded into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscodeTarget {
+    Bc7,
+    Bc3,
+    Bc1,
+    Etc2,
+    Astc4x4,
+    /// Uncompressed fallback when the device has no usable block format.
+    Rgba8,
+}
+
+impl TranscodeTarget {
+    /// UASTC keeps its quality best in BC7/ASTC; ETC1S maps almost losslessly onto BC1/BC3
+    /// and ETC2, so those are preferred for it.
+    pub fn select(support: TextureCompressionSupport, codec: BasisCodec, has_alpha: bool) -> Self {
+        match codec {
+            BasisCodec::Uastc => {
+                if support.bc {
+                    Self::Bc7
+                } else if support.astc_ldr {
+                    Self::Astc4x4
+                } else if support.etc2 {
+                    Self::Etc2
+                } else {
+                    Self::Rgba8
+                }
+            }
+            BasisCodec::Etc1s => {
+                if support.bc {
+                    if has_alpha {
+                        Self::Bc3
+                    } else {
+                        Self::Bc1
+                    }
+                } else if support.etc2 {
+                    Self::Etc2
+                } else if support.astc_ldr {
+                    Self::Astc4x4
+                } else {
+                    Self::Rgba8
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub fn texture_format(self) -> TextureFormat {
+        match self {
+            Self::Bc7 => TextureFormat::Bc7Unorm,
+            Self::Bc3 => TextureFormat::Bc3Unorm,
+            Self::Bc1 => TextureFormat::Bc1RgbUnorm,
+            Self::Etc2 => TextureFormat::Etc2Rgba8Unorm,
+            Self::Astc4x4 => TextureFormat::Astc4x4Unorm,
+            Self::Rgba8 => TextureFormat::Rgba8Unorm,
+        }
+    }
+}
+
+/// Basis Universal transcoder backend (e.g. bindings to `basisu_transcoder`).
+pub trait BasisTranscoder: Send + Sync {
+    /// Transcodes mip `level` of the KTX2 file in `bytes` into `target`. Returns every
+    /// layer/face of the level concatenated in file order.
+    fn transcode_level(
+        &self,
+        bytes: &[u8],
+        header: &Ktx2Header,
+        level: u32,
+        target: TranscodeTarget,
+    ) -> Result<Vec<u8>, AssetError>;
+}
+
+/// Builds a GPU-ready mip chain from a KTX2 file.
+///
+/// Basis data requires `transcoder`; plain block-compressed data is copied as is.
+pub fn ktx2_to_texture_asset(
+    bytes: &[u8],
+    support: TextureCompressionSupport,
+    transcoder: Option<&dyn BasisTranscoder>,
+) -> Result<TextureAsset, AssetError> {
+    let header = Ktx2Header::parse(bytes)?;
+
+    let (format, target) = match header.basis {
+        Some(codec) => {
+            let target = TranscodeTarget::select(support, codec, header.has_alpha);
+            (target.texture_format(), Some(target))
+        }
+        None => {
+            if header.supercompression != Ktx2Supercompression::None {
+                return Err(AssetError::new(format!(
+                    "ktx2: supercompression {:?} is not supported",
+                    header.supercompression
+                )));
+            }
+            let format = native_format(header.vk_format).ok_or_else(|| {
+                AssetError::new(format!("ktx2: unsupported vkFormat {}", header.vk_format))
+            })?;
+            (format, None)
+        }
+    };
+
+    if let (Some(t), None) = (target, transcoder) {
+        return Err(AssetError::new(format!(
+            "ktx2: basis data needs a transcoder (target {t:?})"
+        )));
+    }
+
+    let subs = header.subresources() as usize;
+    let mut mips = Vec::with_capacity(header.levels.len());
+
+    for (i, level) in header.levels.iter().enumerate() {
+        let data = match (target, transcoder) {
+            (Some(t), Some(tr)) => tr.transcode_level(bytes, &header, i as u32, t)?,
+            _ => bytes[level.offset as usize..(level.offset + level.length) as usize].to_vec(),
+        };
+
+        if data.is_empty() || data.len() % subs != 0 {
+            return Err(AssetError::new(format!(
+                "ktx2: level {i} size {} does not split into {subs} subresources",
+                data.len()
+            )));
+        }
+
+        let (width, height, depth) = header.level_extent(i as u32);
+        let slice = data.len() / subs;
+        mips.push(TextureMip {
+            width,
+            height,
+            depth,
+            subresources: data
+                .chunks_exact(slice)
+                .enumerate()
+                .map(|(layer, d)| TextureSubresource {
+                    layer: layer as u32,
+                    data: d.to_vec(),
+                })
+                .collect(),
+        });
+    }
+
+    Ok(TextureAsset {
+        desc: header.texture_desc(format),
+        mips,
+    })
+}
@@ -1,25 +1,78 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+pub mod archive;
+pub mod budget;
+pub mod cache;
+pub mod content;
+pub mod cook;
+pub mod deps;
 pub mod events;
 pub mod id;
 pub mod importers;
+pub mod ktx2;
+pub mod material;
+pub mod mesh;
+pub mod meta;
+pub mod pak;
+pub mod patch;
+pub mod path;
+pub mod procedural;
+pub mod query;
+pub mod remote;
+pub mod shader;
 pub mod source;
 pub mod store;
+pub mod stream;
 pub mod texture;
 pub mod types;
+pub mod validate;
+mod workers;
 
 pub mod text_reader;
 pub mod audio;
 pub mod model3d;
 
+pub use archive::ArchiveSource;
+pub use budget::{AssetCategory, AssetHandle, CategoryUsage, MemoryBudget};
+pub use cache::{AssetCache, CacheKey};
+pub use content::{ContentResponse, ContentServer, EngineContent, CONTENT_SCHEME};
+pub use cook::{CookClient, CookNotice, CookOptions, CookReport, CookServer, Cooker};
+pub use deps::{DependencyGraph, DependencyGraphExport, DependencyGraphNode};
 pub use events::AssetEvent;
 pub use id::AssetId;
 pub use importers::Importer;
+pub use ktx2::{
+    ktx2_to_texture_asset, BasisCodec, BasisTranscoder, Ktx2Header, Ktx2Level, Ktx2Supercompression,
+    TextureCompressionSupport, TranscodeTarget,
+};
+pub use material::{MaterialAsset, MaterialImporter, MATERIAL_TYPE_ID};
+pub use mesh::{MeshAsset, MeshReadError, MESH_VERTEX_STRIDE};
+pub use meta::{sidecar_path, ImportSettings, META_EXTENSION};
+pub use pak::{pack_directory, PakCompression, PakEntry, PakOptions, PakReader, PakStats, PakWriter};
+pub use patch::{
+    apply_patch, apply_patch_expecting, make_patch, ContentManifest, ContentUpdater, ManifestEntry, ManifestPatch, SignedManifest,
+    UpdateAction, UpdatePlan, UpdateStats,
+};
+pub use path::{find_case_collisions, fold_case, normalize_path, CaseCollision, CaseIndex, PathCase};
+pub use procedural::{ProceduralRecipe, ProceduralTextureImporter};
+pub use query::{AssetQuery, AssetQueryEntry, AssetQueryResult, AssetStateKind};
+pub use remote::{AssetServer, RemoteImportSource};
+pub use shader::{ShaderAsset, SpirvShaderImporter, SHADER_TYPE_ID};
 pub use source::{AssetSource, FileSystemSource};
-pub use store::{AssetStore, BlobImporterDispatch, PumpBudget};
+pub use store::{
+    AssetEventObserver, AssetFailure, AssetFailureObserver, AssetStore, BlobImporterDispatch,
+    ImportProvider, LoadCancel, PumpBudget,
+};
+pub use stream::{AssetChunks, AssetReadStream, ChunkPoll, DEFAULT_STREAM_CHUNK};
 
 pub use texture::{
     TextureAsset, TextureDesc, TextureFormat, TextureKind, TextureMip, TextureSubresource,
+    TEXTURE_RGBA8_FORMAT, TEXTURE_TYPE_ID,
+};
+
+pub use validate::{
+    AssetFacts, RuleCheck, ValidationIssue, ValidationRule, ValidationRules, ValidationSeverity,
+    VALIDATION_RULES_PATH,
 };
 
 pub use types::{
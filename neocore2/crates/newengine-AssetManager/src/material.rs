@@ -0,0 +1,193 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::shader::SHADER_TYPE_ID;
+use crate::store::BlobImporterDispatch;
+use crate::texture::TEXTURE_TYPE_ID;
+use crate::types::{Asset, AssetBlob, AssetDependency, AssetError, AssetKey, ImporterPriority};
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub const MATERIAL_TYPE_ID: &str = "kalitech.asset.material";
+pub const MATERIAL_JSON_FORMAT: &str = "ne.material.json.v1";
+pub const MATERIAL_EXTENSION: &str = "nemat";
+pub const MATERIAL_VERSION: &str = "material.v1";
+
+/// Size of the per-draw transform at the start of the uniform block (one `mat4`).
+pub const MATERIAL_TRANSFORM_BYTES: usize = 64;
+
+/// `.nemat` material: shader pair, named textures and uniform defaults.
+///
+/// ```json
+/// {
+///   "vertex_shader": "shaders/lit.vert.spv",
+///   "fragment_shader": "shaders/lit.frag.spv",
+///   "textures": { "albedo": "textures/crate.png" },
+///   "uniforms": { "tint": [1, 0.9, 0.8, 1], "roughness": [0.5] }
+/// }
+/// ```
+///
+/// Uniform block layout: a `mat4` transform followed by one `vec4` per uniform in name
+/// order; shorter values are zero-padded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaterialAsset {
+    pub vertex_shader: String,
+    pub fragment_shader: String,
+    #[serde(default)]
+    pub textures: BTreeMap<String, String>,
+    #[serde(default)]
+    pub uniforms: BTreeMap<String, Vec<f32>>,
+    #[serde(default = "default_true")]
+    pub depth_test: bool,
+}
+
+#[inline]
+fn default_true() -> bool {
+    true
+}
+
+impl Asset for MaterialAsset {
+    #[inline]
+    fn type_name() -> &'static str {
+        "MaterialAsset"
+    }
+}
+
+impl MaterialAsset {
+    /// Material without textures or uniforms.
+    pub fn new(vertex_shader: impl Into<String>, fragment_shader: impl Into<String>) -> Self {
+        Self {
+            vertex_shader: vertex_shader.into(),
+            fragment_shader: fragment_shader.into(),
+            textures: BTreeMap::new(),
+            uniforms: BTreeMap::new(),
+            depth_test: true,
+        }
+    }
+
+    pub fn from_json(bytes: &[u8]) -> Result<Self, AssetError> {
+        let m: Self = serde_json::from_slice(bytes)
+            .map_err(|e| AssetError::new(format!("material: json: {e}")))?;
+        m.validate()?;
+        Ok(m)
+    }
+
+    #[inline]
+    pub fn to_json(&self) -> Result<String, AssetError> {
+        serde_json::to_string(self).map_err(|e| AssetError::new(format!("material: json: {e}")))
+    }
+
+    pub fn validate(&self) -> Result<(), AssetError> {
+        if self.vertex_shader.trim().is_empty() || self.fragment_shader.trim().is_empty() {
+            return Err(AssetError::new("material: vertex_shader and fragment_shader are required"));
+        }
+        for (name, v) in &self.uniforms {
+            if v.is_empty() || v.len() > 4 {
+                return Err(AssetError::new(format!(
+                    "material: uniform '{name}' has {} components (1..=4)",
+                    v.len()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn uniform_size(&self) -> usize {
+        MATERIAL_TRANSFORM_BYTES + self.uniforms.len() * 16
+    }
+
+    /// Uniform block with an identity transform and the default values.
+    pub fn uniform_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.uniform_size());
+        for c in 0..4 {
+            for r in 0..4 {
+                let v: f32 = if c == r { 1.0 } else { 0.0 };
+                out.extend_from_slice(&v.to_ne_bytes());
+            }
+        }
+        for v in self.uniforms.values() {
+            for i in 0..4 {
+                out.extend_from_slice(&v.get(i).copied().unwrap_or(0.0).to_ne_bytes());
+            }
+        }
+        out
+    }
+
+    /// Shaders and textures as importer dependencies.
+    pub fn dependencies(&self) -> Vec<AssetDependency> {
+        let dep = |path: &str, type_hint: &str, usage: &str| AssetDependency {
+            logical_path: PathBuf::from(path),
+            settings_hash: 0,
+            type_hint: Arc::from(type_hint),
+            usage: Arc::from(usage),
+        };
+
+        let mut out = vec![
+            dep(&self.vertex_shader, SHADER_TYPE_ID, "material.shader"),
+            dep(&self.fragment_shader, SHADER_TYPE_ID, "material.shader"),
+        ];
+        out.extend(
+            self.textures
+                .values()
+                .map(|p| dep(p, TEXTURE_TYPE_ID, "material.texture")),
+        );
+        out
+    }
+}
+
+/// In-process importer for `.nemat` materials.
+///
+/// The payload is the normalized JSON; shader and texture references are reported as
+/// dependencies so edits to them invalidate the material.
+#[derive(Debug, Default)]
+pub struct MaterialImporter;
+
+impl BlobImporterDispatch for MaterialImporter {
+    fn import_blob(&self, bytes: &[u8], key: &AssetKey) -> Result<AssetBlob, AssetError> {
+        let m = MaterialAsset::from_json(bytes)?;
+
+        log::info!(
+            target: "assets",
+            "material.import path='{}' textures={} uniforms={}",
+            key.logical_path.display(),
+            m.textures.len(),
+            m.uniforms.len()
+        );
+
+        Ok(AssetBlob {
+            type_id: Arc::from(MATERIAL_TYPE_ID),
+            format: Arc::from(MATERIAL_JSON_FORMAT),
+            payload: m.to_json()?.into_bytes(),
+            meta_json: Arc::from("{\"schema\":\"kalitech.material.meta.v1\"}"),
+            dependencies: m.dependencies(),
+        })
+    }
+
+    #[inline]
+    fn output_type_id(&self) -> Arc<str> {
+        Arc::from(MATERIAL_TYPE_ID)
+    }
+
+    #[inline]
+    fn extensions(&self) -> Vec<String> {
+        vec![MATERIAL_EXTENSION.to_string()]
+    }
+
+    #[inline]
+    fn priority(&self) -> ImporterPriority {
+        ImporterPriority::new(100)
+    }
+
+    #[inline]
+    fn stable_id(&self) -> Arc<str> {
+        Arc::from("material_importer@newengine-assets")
+    }
+
+    #[inline]
+    fn version(&self) -> Arc<str> {
+        Arc::from(MATERIAL_VERSION)
+    }
+}
@@ -0,0 +1,231 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::model3d::{Model3dAsset, Model3dFormat};
+use crate::types::{Asset, AssetError};
+
+/// Magic of the NE3D mesh payload written by the 3D importers.
+pub const NE3D_MAGIC: &[u8; 4] = b"NE3D";
+pub const NE3D_VERSION: u32 = 1;
+
+const NE3D_FLAG_NORMALS: u32 = 0x1;
+const NE3D_FLAG_UVS: u32 = 0x2;
+
+/// Bytes per vertex in `MeshAsset::vertex_bytes`: position, normal, uv (all f32).
+pub const MESH_VERTEX_STRIDE: u32 = 8 * 4;
+
+/// CPU-side indexed triangle mesh.
+///
+/// Missing normals default to +Y and missing UVs to zero, so every mesh has the same
+/// vertex layout (`MESH_VERTEX_STRIDE`).
+#[derive(Debug, Clone, Default)]
+pub struct MeshAsset {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+    pub bbox_min: [f32; 3],
+    pub bbox_max: [f32; 3],
+}
+
+impl Asset for MeshAsset {
+    #[inline]
+    fn type_name() -> &'static str {
+        "MeshAsset"
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MeshReadError {
+    #[error("ne3d: too short")]
+    TooShort,
+    #[error("ne3d: bad magic")]
+    BadMagic,
+    #[error("ne3d: unsupported version {0}")]
+    UnsupportedVersion(u32),
+    #[error("ne3d: truncated while reading {0}")]
+    Truncated(&'static str),
+    #[error("ne3d: index {index} out of range ({vertices} vertices)")]
+    IndexOutOfRange { index: u32, vertices: usize },
+    #[error("mesh: unsupported model format {0:?}")]
+    UnsupportedFormat(Model3dFormat),
+    #[error("mesh: empty geometry")]
+    Empty,
+}
+
+impl From<MeshReadError> for AssetError {
+    #[inline]
+    fn from(e: MeshReadError) -> Self {
+        AssetError::new(e.to_string())
+    }
+}
+
+impl MeshAsset {
+    /// Decodes the mesh carried by an imported 3D model.
+    ///
+    /// The payload is sniffed rather than trusted from metadata: the OBJ importer reports its
+    /// source container while already emitting NE3D.
+    pub fn from_model3d(model: &Model3dAsset) -> Result<Self, MeshReadError> {
+        if model.payload.starts_with(NE3D_MAGIC) {
+            return Self::decode_ne3d(&model.payload);
+        }
+        Err(MeshReadError::UnsupportedFormat(model.format))
+    }
+
+    /// Decodes an NE3D v1 payload:
+    /// `"NE3D"`, version, vertex_count, index_count, flags (u32 LE), then positions,
+    /// optional normals (flag 0x1), optional UVs (flag 0x2) and u32 indices.
+    pub fn decode_ne3d(bytes: &[u8]) -> Result<Self, MeshReadError> {
+        if bytes.len() < 4 + 4 * 4 {
+            return Err(MeshReadError::TooShort);
+        }
+        if &bytes[0..4] != NE3D_MAGIC {
+            return Err(MeshReadError::BadMagic);
+        }
+
+        let mut r = Reader { bytes, at: 4 };
+        let ver = r.u32("version")?;
+        if ver != NE3D_VERSION {
+            return Err(MeshReadError::UnsupportedVersion(ver));
+        }
+
+        let vtx_count = r.u32("vertex_count")? as usize;
+        let idx_count = r.u32("index_count")? as usize;
+        let flags = r.u32("flags")?;
+
+        let positions = r.vec3s(vtx_count, "positions")?;
+        let normals = if flags & NE3D_FLAG_NORMALS != 0 {
+            r.vec3s(vtx_count, "normals")?
+        } else {
+            vec![[0.0, 1.0, 0.0]; vtx_count]
+        };
+        let uvs = if flags & NE3D_FLAG_UVS != 0 {
+            (0..vtx_count)
+                .map(|_| Ok([r.f32("uvs")?, r.f32("uvs")?]))
+                .collect::<Result<Vec<_>, MeshReadError>>()?
+        } else {
+            vec![[0.0, 0.0]; vtx_count]
+        };
+
+        let indices = (0..idx_count)
+            .map(|_| r.u32("indices"))
+            .collect::<Result<Vec<_>, MeshReadError>>()?;
+
+        if let Some(&index) = indices.iter().find(|&&i| i as usize >= vtx_count) {
+            return Err(MeshReadError::IndexOutOfRange {
+                index,
+                vertices: vtx_count,
+            });
+        }
+
+        Self::new(positions, normals, uvs, indices)
+    }
+
+    /// Builds a mesh and computes its bounds. Attribute arrays must match `positions` in length.
+    pub fn new(
+        positions: Vec<[f32; 3]>,
+        normals: Vec<[f32; 3]>,
+        uvs: Vec<[f32; 2]>,
+        indices: Vec<u32>,
+    ) -> Result<Self, MeshReadError> {
+        if positions.is_empty() || indices.is_empty() {
+            return Err(MeshReadError::Empty);
+        }
+
+        let mut bbox_min = [f32::INFINITY; 3];
+        let mut bbox_max = [f32::NEG_INFINITY; 3];
+        for p in &positions {
+            for a in 0..3 {
+                bbox_min[a] = bbox_min[a].min(p[a]);
+                bbox_max[a] = bbox_max[a].max(p[a]);
+            }
+        }
+
+        Ok(Self {
+            positions,
+            normals,
+            uvs,
+            indices,
+            bbox_min,
+            bbox_max,
+        })
+    }
+
+    #[inline]
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    #[inline]
+    pub fn index_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Bounding box center and half of its largest extent.
+    pub fn bounding_sphere(&self) -> ([f32; 3], f32) {
+        let center = [
+            (self.bbox_min[0] + self.bbox_max[0]) * 0.5,
+            (self.bbox_min[1] + self.bbox_max[1]) * 0.5,
+            (self.bbox_min[2] + self.bbox_max[2]) * 0.5,
+        ];
+        let ext = (0..3)
+            .map(|a| (self.bbox_max[a] - self.bbox_min[a]).abs())
+            .fold(0.0f32, f32::max);
+        (center, 0.5 * ext)
+    }
+
+    /// Interleaved `position, normal, uv` vertices in native endianness.
+    pub fn vertex_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.positions.len() * MESH_VERTEX_STRIDE as usize);
+        for i in 0..self.positions.len() {
+            let n = self.normals.get(i).copied().unwrap_or([0.0, 1.0, 0.0]);
+            let uv = self.uvs.get(i).copied().unwrap_or([0.0, 0.0]);
+            for f in self.positions[i].iter().chain(n.iter()).chain(uv.iter()) {
+                out.extend_from_slice(&f.to_ne_bytes());
+            }
+        }
+        out
+    }
+
+    /// u32 indices in native endianness.
+    pub fn index_bytes(&self) -> Vec<u8> {
+        self.indices.iter().flat_map(|i| i.to_ne_bytes()).collect()
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    #[inline]
+    fn take4(&mut self, what: &'static str) -> Result<[u8; 4], MeshReadError> {
+        let end = self.at.saturating_add(4);
+        let b = self
+            .bytes
+            .get(self.at..end)
+            .ok_or(MeshReadError::Truncated(what))?;
+        self.at = end;
+        Ok([b[0], b[1], b[2], b[3]])
+    }
+
+    #[inline]
+    fn u32(&mut self, what: &'static str) -> Result<u32, MeshReadError> {
+        self.take4(what).map(u32::from_le_bytes)
+    }
+
+    #[inline]
+    fn f32(&mut self, what: &'static str) -> Result<f32, MeshReadError> {
+        self.take4(what).map(f32::from_le_bytes)
+    }
+
+    fn vec3s(&mut self, n: usize, what: &'static str) -> Result<Vec<[f32; 3]>, MeshReadError> {
+        let need = n.checked_mul(12).ok_or(MeshReadError::Truncated(what))?;
+        if self.bytes.len().saturating_sub(self.at) < need {
+            return Err(MeshReadError::Truncated(what));
+        }
+        (0..n)
+            .map(|_| Ok([self.f32(what)?, self.f32(what)?, self.f32(what)?]))
+            .collect()
+    }
+}
@@ -0,0 +1,163 @@
+//! Per-asset import settings from `.meta` sidecars.
+//!
+//! `textures/wall.png.meta` next to `textures/wall.png` holds a JSON object of import
+//! options. The store reads it with the source, hands it to the importer and folds it into
+//! the cache key, so editing a sidecar re-imports the asset. Well-known keys:
+//!
+//! ```json
+//! { "srgb": true, "mips": false, "loop_start": 4410, "loop_end": 88200 }
+//! ```
+//!
+//! Importers may read any other key; unknown keys are ignored by the ones that do not.
+//!
+//! `"tags": ["env", "brick"]` labels the asset for [`AssetStore::query`](crate::AssetStore::query).
+//! Tags are not import settings: they are left out of the importer JSON and the cache key, so
+//! retagging never re-imports.
+
+use crate::types::AssetError;
+
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Extension appended to the asset's own file name (`foo.png` -> `foo.png.meta`).
+pub const META_EXTENSION: &str = "meta";
+
+/// Sidecar key holding the asset browser tags.
+pub const TAGS_KEY: &str = "tags";
+
+/// Sidecar path for `logical_path`.
+#[inline]
+pub fn sidecar_path(logical_path: &Path) -> PathBuf {
+    let mut name = logical_path.as_os_str().to_owned();
+    name.push(".");
+    name.push(META_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Parsed contents of a `.meta` sidecar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportSettings {
+    values: Map<String, Value>,
+    /// Compact JSON with sorted keys: stable across formatting-only edits of the sidecar.
+    canonical: Arc<str>,
+}
+
+impl ImportSettings {
+    pub fn parse(json: &[u8]) -> Result<Self, AssetError> {
+        let value: Value = serde_json::from_slice(json)
+            .map_err(|e| AssetError::new(format!("meta: invalid json: {e}")))?;
+        let Value::Object(values) = value else {
+            return Err(AssetError::new("meta: expected a json object"));
+        };
+        let canonical = Arc::from(canonical_json(&values));
+        Ok(Self { values, canonical })
+    }
+
+    /// No import settings, e.g. a sidecar holding only tags.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.keys().all(|k| k == TAGS_KEY)
+    }
+
+    /// Settings JSON as passed to plugin importers.
+    #[inline]
+    pub fn as_json(&self) -> &str {
+        &self.canonical
+    }
+
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    #[inline]
+    pub fn bool(&self, key: &str) -> Option<bool> {
+        self.get(key).and_then(Value::as_bool)
+    }
+
+    #[inline]
+    pub fn u64(&self, key: &str) -> Option<u64> {
+        self.get(key).and_then(Value::as_u64)
+    }
+
+    #[inline]
+    pub fn str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Value::as_str)
+    }
+
+    /// Color data in sRGB (albedo, UI) rather than linear (normals, masks).
+    #[inline]
+    pub fn srgb(&self) -> Option<bool> {
+        self.bool("srgb")
+    }
+
+    /// Generate the mip chain on import.
+    #[inline]
+    pub fn mips(&self) -> Option<bool> {
+        self.bool("mips")
+    }
+
+    /// Audio loop region in sample frames; `loop_end` defaults to the end of the clip.
+    #[inline]
+    pub fn loop_points(&self) -> Option<(u64, Option<u64>)> {
+        self.u64("loop_start").map(|s| (s, self.u64("loop_end")))
+    }
+
+    /// Asset browser tags; non-string entries are skipped.
+    pub fn tags(&self) -> Vec<String> {
+        match self.get(TAGS_KEY) {
+            Some(Value::Array(a)) => a
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Sidecar JSON `existing` (if any) with its tags replaced; other keys are kept as written.
+/// An empty `tags` removes the key.
+pub fn sidecar_with_tags(existing: Option<&[u8]>, tags: &[String]) -> Result<Vec<u8>, AssetError> {
+    let mut values = match existing {
+        Some(bytes) => match serde_json::from_slice(bytes) {
+            Ok(Value::Object(m)) => m,
+            Ok(_) => return Err(AssetError::new("meta: expected a json object")),
+            Err(e) => return Err(AssetError::new(format!("meta: invalid json: {e}"))),
+        },
+        None => Map::new(),
+    };
+    if tags.is_empty() {
+        values.remove(TAGS_KEY);
+    } else {
+        values.insert(TAGS_KEY.to_string(), Value::from(tags.to_vec()));
+    }
+    serde_json::to_vec_pretty(&Value::Object(values))
+        .map_err(|e| AssetError::new(format!("meta: failed to encode: {e}")))
+}
+
+/// Import settings without the tags.
+///
+/// `serde_json` maps are sorted unless `preserve_order` is enabled, which another crate in
+/// the graph may turn on; sort explicitly.
+fn canonical_json(values: &Map<String, Value>) -> String {
+    fn sorted(v: &Value) -> Value {
+        match v {
+            Value::Object(m) => {
+                let mut keys: Vec<&String> = m.keys().collect();
+                keys.sort();
+                Value::Object(
+                    keys.into_iter()
+                        .map(|k| (k.clone(), sorted(&m[k])))
+                        .collect(),
+                )
+            }
+            Value::Array(a) => Value::Array(a.iter().map(sorted).collect()),
+            other => other.clone(),
+        }
+    }
+    let mut values = values.clone();
+    values.remove(TAGS_KEY);
+    sorted(&Value::Object(values)).to_string()
+}
@@ -0,0 +1,665 @@
+use crate::path::{find_case_collisions, normalize_path, CaseCollision};
+use crate::stream::AssetReadStream;
+use crate::types::AssetError;
+use crate::validate::{AssetFacts, ValidationIssue, ValidationRules};
+
+use log::{debug, info, warn};
+use memmap2::Mmap;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub const PAK_EXTENSION: &str = "nepak";
+
+const PAK_MAGIC: &[u8; 4] = b"NEPK";
+const PAK_VERSION: u32 = 1;
+const HEADER_SIZE: u64 = 64;
+/// Smallest index record: path length, offset, stored and raw size, compression, hash.
+const MIN_INDEX_ENTRY_SIZE: u64 = 2 + 8 + 8 + 8 + 1 + 32;
+/// Largest entry stored compressed; bigger files are stored as-is, so a reader never
+/// allocates more than this for one decompression.
+const MAX_LZ4_RAW_SIZE: u64 = 1 << 30;
+/// lz4 block compression cannot shrink data by more than ~255x.
+const MAX_LZ4_RATIO: u64 = 255;
+
+/// Extensions whose payload is already compressed; stored as-is.
+const PRECOMPRESSED: &[&str] = &[
+    "png", "jpg", "jpeg", "webp", "ktx2", "dds", "ogg", "mp3", "flac", "zip", "nepak", "glb",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PakCompression {
+    None = 0,
+    Lz4 = 1,
+}
+
+impl PakCompression {
+    #[inline]
+    fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::None),
+            1 => Some(Self::Lz4),
+            _ => None,
+        }
+    }
+}
+
+/// One file inside a `.nepak`.
+#[derive(Debug, Clone)]
+pub struct PakEntry {
+    pub path: String,
+    pub offset: u64,
+    pub stored_size: u64,
+    pub raw_size: u64,
+    pub compression: PakCompression,
+    /// blake3 of the uncompressed bytes.
+    pub hash: [u8; 32],
+}
+
+/// `.nepak` layout (little-endian):
+///
+/// ```text
+/// header (64 bytes): magic "NEPK" | version u32 | entry_count u32 | alignment u32
+///                    | index_offset u64 | index_size u64 | reserved
+/// data:              entries, each starting at a multiple of `alignment`
+/// index:             per entry: path_len u16 | path utf8 | offset u64 | stored_size u64
+///                    | raw_size u64 | compression u8 | blake3 [32]
+/// ```
+///
+/// Uncompressed entries are aligned so they can be consumed straight from the mapping.
+#[derive(Debug)]
+pub struct PakReader {
+    map: Arc<Mmap>,
+    entries: HashMap<String, PakEntry>,
+    alignment: u32,
+}
+
+impl PakReader {
+    pub fn open(path: &Path) -> Result<Self, AssetError> {
+        let file = File::open(path).map_err(|e| {
+            AssetError::new(format!("pak: failed to open '{}': {}", path.display(), e))
+        })?;
+
+        // SAFETY: the mapping is read-only; packs are immutable build outputs and are not
+        // expected to be modified while mounted.
+        let map = unsafe { Mmap::map(&file) }.map_err(|e| {
+            AssetError::new(format!("pak: failed to map '{}': {}", path.display(), e))
+        })?;
+
+        let (alignment, entries) = parse_index(&map)
+            .map_err(|e| AssetError::new(format!("pak: '{}': {}", path.display(), e)))?;
+
+        debug!(
+            target: "assets::pak",
+            "pak.open file='{}' entries={} align={}",
+            path.display(),
+            entries.len(),
+            alignment
+        );
+
+        Ok(Self {
+            map: Arc::new(map),
+            entries,
+            alignment,
+        })
+    }
+
+    #[inline]
+    pub fn alignment(&self) -> u32 {
+        self.alignment
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    pub fn entry(&self, path: &str) -> Option<&PakEntry> {
+        self.entries.get(path)
+    }
+
+    #[inline]
+    pub fn entries(&self) -> impl Iterator<Item = &PakEntry> {
+        self.entries.values()
+    }
+
+    /// Zero-copy view of an uncompressed entry.
+    pub fn raw_slice(&self, path: &str) -> Option<&[u8]> {
+        let e = self.entries.get(path)?;
+        if e.compression != PakCompression::None {
+            return None;
+        }
+        Some(&self.map[e.offset as usize..(e.offset + e.stored_size) as usize])
+    }
+
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, AssetError> {
+        let e = self
+            .entries
+            .get(path)
+            .ok_or_else(|| AssetError::new(format!("pak: entry not found '{path}'")))?;
+
+        decode_entry(e, self.stored_slice(e))
+    }
+
+    /// Streams an entry. Uncompressed entries are read straight from the mapping and their
+    /// checksum is verified when the stream reaches the end; lz4 entries are decoded whole.
+    pub fn open_stream(&self, path: &str) -> Result<AssetReadStream, AssetError> {
+        let e = self
+            .entries
+            .get(path)
+            .ok_or_else(|| AssetError::new(format!("pak: entry not found '{path}'")))?;
+
+        if e.compression != PakCompression::None {
+            return Ok(AssetReadStream::from_bytes(self.read(path)?, path));
+        }
+
+        let reader = MappedEntryReader {
+            map: self.map.clone(),
+            pos: e.offset as usize,
+            end: (e.offset + e.stored_size) as usize,
+            hasher: blake3::Hasher::new(),
+            hash: e.hash,
+        };
+        Ok(AssetReadStream::new(reader, Some(e.raw_size), path))
+    }
+
+    #[inline]
+    fn stored_slice(&self, e: &PakEntry) -> &[u8] {
+        &self.map[e.offset as usize..(e.offset + e.stored_size) as usize]
+    }
+}
+
+/// Decompresses and checks one entry's stored bytes.
+/// Uncompressed pak entry read from the shared mapping, checksummed on the fly.
+struct MappedEntryReader {
+    map: Arc<Mmap>,
+    pos: usize,
+    end: usize,
+    hasher: blake3::Hasher,
+    hash: [u8; 32],
+}
+
+impl Read for MappedEntryReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.end - self.pos);
+        if n == 0 {
+            if !buf.is_empty() && self.hasher.finalize().as_bytes() != &self.hash {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "pak: checksum mismatch",
+                ));
+            }
+            return Ok(0);
+        }
+        let chunk = &self.map[self.pos..self.pos + n];
+        buf[..n].copy_from_slice(chunk);
+        self.hasher.update(chunk);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+fn decode_entry(e: &PakEntry, stored: &[u8]) -> Result<Vec<u8>, AssetError> {
+    let path = &e.path;
+    let bytes = match e.compression {
+        PakCompression::None => stored.to_vec(),
+        PakCompression::Lz4 => lz4_flex::block::decompress(stored, e.raw_size as usize)
+            .map_err(|err| AssetError::new(format!("pak: lz4 '{path}': {err}")))?,
+    };
+
+    if blake3::hash(&bytes).as_bytes() != &e.hash {
+        return Err(AssetError::new(format!("pak: checksum mismatch '{path}'")));
+    }
+
+    Ok(bytes)
+}
+
+fn parse_index(map: &[u8]) -> Result<(u32, HashMap<String, PakEntry>), String> {
+    if (map.len() as u64) < HEADER_SIZE || &map[0..4] != PAK_MAGIC {
+        return Err("not a nepak file".to_string());
+    }
+
+    let mut h = Cursor::new(&map[4..HEADER_SIZE as usize]);
+    let version = h.u32()?;
+    if version != PAK_VERSION {
+        return Err(format!("unsupported version {version}"));
+    }
+    let count = h.u32()? as usize;
+    let alignment = h.u32()?;
+    let index_offset = h.u64()?;
+    let index_size = h.u64()?;
+
+    let end = index_offset
+        .checked_add(index_size)
+        .filter(|e| *e <= map.len() as u64)
+        .ok_or("index out of bounds")?;
+
+    if count as u64 > index_size / MIN_INDEX_ENTRY_SIZE {
+        return Err(format!(
+            "{count} entries do not fit a {index_size} byte index"
+        ));
+    }
+
+    let mut c = Cursor::new(&map[index_offset as usize..end as usize]);
+    let mut entries = HashMap::with_capacity(count);
+
+    for _ in 0..count {
+        let path_len = c.u16()? as usize;
+        let path = std::str::from_utf8(c.bytes(path_len)?)
+            .map_err(|_| "entry path is not utf-8")?
+            .to_string();
+        let offset = c.u64()?;
+        let stored_size = c.u64()?;
+        let raw_size = c.u64()?;
+        let compression = PakCompression::from_u8(c.u8()?).ok_or("unknown compression")?;
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(c.bytes(32)?);
+
+        if offset < HEADER_SIZE
+            || offset
+                .checked_add(stored_size)
+                .is_none_or(|e| e > index_offset)
+        {
+            return Err(format!("entry '{path}' out of bounds"));
+        }
+        let raw_ok = match compression {
+            PakCompression::None => raw_size == stored_size,
+            PakCompression::Lz4 => {
+                raw_size <= MAX_LZ4_RAW_SIZE
+                    && raw_size <= stored_size.saturating_mul(MAX_LZ4_RATIO).saturating_add(16)
+            }
+        };
+        if !raw_ok {
+            return Err(format!("entry '{path}' has an implausible size {raw_size}"));
+        }
+
+        entries.insert(
+            path.clone(),
+            PakEntry {
+                path,
+                offset,
+                stored_size,
+                raw_size,
+                compression,
+                hash,
+            },
+        );
+    }
+
+    Ok((alignment, entries))
+}
+
+struct Cursor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    #[inline]
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    #[inline]
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).filter(|e| *e <= self.buf.len());
+        let end = end.ok_or("truncated index")?;
+        let s = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(s)
+    }
+
+    #[inline]
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    #[inline]
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    #[inline]
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    #[inline]
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PakOptions {
+    /// Data alignment in bytes (power of two). 4096 makes entries page-aligned.
+    pub alignment: u32,
+    pub compression: PakCompression,
+    /// Compressed output is kept only if `stored <= raw * max_ratio`.
+    pub max_ratio: f32,
+    /// Fail instead of warning when entries differ only by case.
+    pub strict_case: bool,
+}
+
+impl Default for PakOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            alignment: 16,
+            compression: PakCompression::Lz4,
+            max_ratio: 0.9,
+            strict_case: false,
+        }
+    }
+}
+
+impl PakOptions {
+    #[inline]
+    pub fn with_alignment(mut self, alignment: u32) -> Self {
+        self.alignment = alignment.max(1).next_power_of_two();
+        self
+    }
+
+    #[inline]
+    pub fn with_compression(mut self, compression: PakCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    #[inline]
+    pub fn with_strict_case(mut self, strict: bool) -> Self {
+        self.strict_case = strict;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PakStats {
+    pub entries: usize,
+    pub raw_bytes: u64,
+    pub stored_bytes: u64,
+    pub file_bytes: u64,
+    /// Groups of entries that differ only by case; see [`PakWriter::case_collisions`].
+    pub case_collisions: usize,
+}
+
+/// Builds a `.nepak` from in-memory files or a directory tree.
+#[derive(Debug, Default)]
+pub struct PakWriter {
+    options: PakOptions,
+    files: Vec<(String, PakInput)>,
+}
+
+#[derive(Debug)]
+enum PakInput {
+    Bytes(Vec<u8>),
+    File(PathBuf),
+    /// Already encoded bytes copied from another pack, written as-is.
+    Stored(PakEntry, Vec<u8>),
+}
+
+impl PakWriter {
+    #[inline]
+    pub fn new(options: PakOptions) -> Self {
+        Self {
+            options,
+            files: Vec::new(),
+        }
+    }
+
+    /// Adds (or replaces) one entry under a logical path (`/` separated).
+    pub fn add_bytes(&mut self, logical_path: &str, bytes: Vec<u8>) {
+        self.insert(normalize_entry_path(logical_path), PakInput::Bytes(bytes));
+    }
+
+    /// Copies `path` from an existing pack without decompressing or re-encoding it, keeping
+    /// its compression even if this writer's options differ. Returns `false` if `reader` has
+    /// no such entry.
+    pub fn add_from_pak(&mut self, reader: &PakReader, path: &str) -> bool {
+        let Some(e) = reader.entry(path) else {
+            return false;
+        };
+        let stored = reader.stored_slice(e).to_vec();
+        self.insert(e.path.clone(), PakInput::Stored(e.clone(), stored));
+        true
+    }
+
+    /// Adds every file under `root` (recursively), keyed by its root-relative path.
+    /// Hidden files and directories (leading `.`) are skipped.
+    pub fn add_dir(&mut self, root: &Path) -> Result<usize, AssetError> {
+        let mut stack = vec![root.to_path_buf()];
+        let mut added = 0usize;
+
+        while let Some(dir) = stack.pop() {
+            let rd = std::fs::read_dir(&dir).map_err(|e| {
+                AssetError::new(format!("pak: read_dir '{}': {}", dir.display(), e))
+            })?;
+
+            for e in rd.flatten() {
+                let p = e.path();
+                let hidden = p
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with('.'));
+                if hidden {
+                    continue;
+                }
+                if p.is_dir() {
+                    stack.push(p);
+                    continue;
+                }
+
+                let Ok(rel) = p.strip_prefix(root) else {
+                    continue;
+                };
+                let key = normalize_entry_path(&rel.to_string_lossy());
+                self.insert(key, PakInput::File(p));
+                added += 1;
+            }
+        }
+
+        Ok(added)
+    }
+
+    /// Entries whose paths differ only by case. They pack fine but break on case-insensitive
+    /// file systems and are ambiguous under `PathCase::Insensitive` lookups.
+    pub fn case_collisions(&self) -> Vec<CaseCollision> {
+        find_case_collisions(self.files.iter().map(|(k, _)| k.as_str()))
+    }
+
+    /// Checks every entry against `rules`, with facts read from the entry bytes (see
+    /// [`AssetFacts::from_source`]). Entries no rule applies to are not read.
+    pub fn validate(&self, rules: &ValidationRules) -> Result<Vec<ValidationIssue>, AssetError> {
+        let mut out = Vec::new();
+        for (key, input) in self.files.iter() {
+            if !rules.rules.iter().any(|r| r.applies_to(key)) {
+                continue;
+            }
+            let bytes = match input {
+                PakInput::Bytes(b) => std::borrow::Cow::Borrowed(b.as_slice()),
+                PakInput::File(p) => {
+                    std::borrow::Cow::Owned(std::fs::read(p).map_err(|e| {
+                        AssetError::new(format!("pak: read '{}': {}", p.display(), e))
+                    })?)
+                }
+                PakInput::Stored(e, stored) => std::borrow::Cow::Owned(decode_entry(e, stored)?),
+            };
+            out.extend(rules.evaluate(key, &AssetFacts::from_source(key, &bytes)));
+        }
+        Ok(out)
+    }
+
+    #[inline]
+    fn insert(&mut self, key: String, input: PakInput) {
+        match self.files.iter_mut().find(|(k, _)| *k == key) {
+            Some(slot) => slot.1 = input,
+            None => self.files.push((key, input)),
+        }
+    }
+
+    pub fn write_to(mut self, out: &Path) -> Result<PakStats, AssetError> {
+        let io = |e: std::io::Error| {
+            AssetError::new(format!("pak: write '{}': {}", out.display(), e))
+        };
+
+        let collisions = self.case_collisions();
+        for c in collisions.iter() {
+            warn!(target: "assets::pak", "pak.case_collision {}", c);
+        }
+        if self.options.strict_case && !collisions.is_empty() {
+            return Err(AssetError::new(format!(
+                "pak: {} case collision(s), first: {}",
+                collisions.len(),
+                collisions[0]
+            )));
+        }
+
+        if let Some(parent) = out.parent() {
+            std::fs::create_dir_all(parent).map_err(io)?;
+        }
+
+        self.files.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let align = self.options.alignment.max(1) as u64;
+        let tmp = out.with_extension(format!("{PAK_EXTENSION}.tmp"));
+        let mut w = BufWriter::new(File::create(&tmp).map_err(io)?);
+        w.write_all(&[0u8; HEADER_SIZE as usize]).map_err(io)?;
+
+        let mut pos = HEADER_SIZE;
+        let mut stats = PakStats {
+            case_collisions: collisions.len(),
+            ..PakStats::default()
+        };
+        let mut index: Vec<PakEntry> = Vec::with_capacity(self.files.len());
+
+        for (key, input) in self.files.iter() {
+            let raw = match input {
+                PakInput::Bytes(b) => std::borrow::Cow::Borrowed(b.as_slice()),
+                PakInput::File(p) => std::borrow::Cow::Owned(std::fs::read(p).map_err(|e| {
+                    AssetError::new(format!("pak: read '{}': {}", p.display(), e))
+                })?),
+                // Already encoded; passed through below.
+                PakInput::Stored(_, stored) => std::borrow::Cow::Borrowed(stored.as_slice()),
+            };
+
+            let (stored, compression, raw_size, hash) = match input {
+                PakInput::Stored(e, _) => (
+                    std::borrow::Cow::Borrowed(raw.as_ref()),
+                    e.compression,
+                    e.raw_size,
+                    e.hash,
+                ),
+                _ => {
+                    let (stored, compression) = self.encode(key, &raw);
+                    (stored, compression, raw.len() as u64, *blake3::hash(&raw).as_bytes())
+                }
+            };
+
+            let pad = (align - pos % align) % align;
+            w.write_all(&vec![0u8; pad as usize]).map_err(io)?;
+            pos += pad;
+
+            w.write_all(&stored).map_err(io)?;
+
+            index.push(PakEntry {
+                path: key.clone(),
+                offset: pos,
+                stored_size: stored.len() as u64,
+                raw_size,
+                compression,
+                hash,
+            });
+
+            pos += stored.len() as u64;
+            stats.raw_bytes += raw_size;
+            stats.stored_bytes += stored.len() as u64;
+        }
+
+        let index_offset = pos;
+        let mut idx = Vec::new();
+        for e in index.iter() {
+            let path_len = u16::try_from(e.path.len())
+                .map_err(|_| AssetError::new(format!("pak: path too long '{}'", e.path)))?;
+            idx.extend_from_slice(&path_len.to_le_bytes());
+            idx.extend_from_slice(e.path.as_bytes());
+            idx.extend_from_slice(&e.offset.to_le_bytes());
+            idx.extend_from_slice(&e.stored_size.to_le_bytes());
+            idx.extend_from_slice(&e.raw_size.to_le_bytes());
+            idx.push(e.compression as u8);
+            idx.extend_from_slice(&e.hash);
+        }
+        w.write_all(&idx).map_err(io)?;
+
+        let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+        header.extend_from_slice(PAK_MAGIC);
+        header.extend_from_slice(&PAK_VERSION.to_le_bytes());
+        header.extend_from_slice(&(index.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(align as u32).to_le_bytes());
+        header.extend_from_slice(&index_offset.to_le_bytes());
+        header.extend_from_slice(&(idx.len() as u64).to_le_bytes());
+        header.resize(HEADER_SIZE as usize, 0);
+
+        w.seek(SeekFrom::Start(0)).map_err(io)?;
+        w.write_all(&header).map_err(io)?;
+        w.flush().map_err(io)?;
+        drop(w);
+
+        std::fs::rename(&tmp, out).map_err(io)?;
+
+        stats.entries = index.len();
+        stats.file_bytes = index_offset + idx.len() as u64;
+
+        info!(
+            target: "assets::pak",
+            "pak.write file='{}' entries={} raw={} stored={} size={}",
+            out.display(),
+            stats.entries,
+            stats.raw_bytes,
+            stats.stored_bytes,
+            stats.file_bytes
+        );
+
+        Ok(stats)
+    }
+
+    fn encode<'b>(&self, key: &str, raw: &'b [u8]) -> (std::borrow::Cow<'b, [u8]>, PakCompression) {
+        let ext = key.rsplit_once('.').map(|(_, e)| e.to_ascii_lowercase());
+        let skip = ext.as_deref().is_some_and(|e| PRECOMPRESSED.contains(&e));
+
+        let fits = raw.len() as u64 <= MAX_LZ4_RAW_SIZE;
+        if self.options.compression == PakCompression::Lz4 && !skip && fits && !raw.is_empty() {
+            let c = lz4_flex::block::compress(raw);
+            if (c.len() as f32) <= raw.len() as f32 * self.options.max_ratio {
+                return (std::borrow::Cow::Owned(c), PakCompression::Lz4);
+            }
+        }
+
+        (std::borrow::Cow::Borrowed(raw), PakCompression::None)
+    }
+}
+
+/// Packs an assets directory into a `.nepak`.
+pub fn pack_directory(
+    src_dir: &Path,
+    out: &Path,
+    options: PakOptions,
+) -> Result<PakStats, AssetError> {
+    let mut w = PakWriter::new(options);
+    w.add_dir(src_dir)?;
+    w.write_to(out)
+}
+
+/// Archive-internal path form: [`normalize_path`] without a leading `/`.
+pub(crate) fn normalize_entry_path(p: &str) -> String {
+    let s = normalize_path(p);
+    s.strip_prefix('/').unwrap_or(&s).to_string()
+}
@@ -0,0 +1,643 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Content versioning for live updates: binary deltas, signed manifests and an updater that
+//! brings a local content directory from one manifest version to the next.
+//!
+//! Transport is left to the caller (`ContentUpdater::apply` takes a fetch callback), so the
+//! same code serves HTTP downloads, a cook server or a local patch folder.
+
+use crate::pak::normalize_entry_path;
+use crate::types::AssetError;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const PATCH_EXTENSION: &str = "nedelta";
+/// File name of the local manifest inside an updatable content root.
+pub const LOCAL_MANIFEST_FILE: &str = ".content.manifest.json";
+
+const PATCH_MAGIC: &[u8; 4] = b"NEDP";
+const PATCH_VERSION: u32 = 1;
+const PATCH_HEADER_SIZE: usize = 4 + 4 + 32 + 32 + 8;
+
+/// A delta is only worth shipping if it is clearly smaller than the full file.
+const MAX_PATCH_RATIO: f64 = 0.75;
+
+/// Binary delta between two versions of one file.
+///
+/// Layout (little-endian):
+///
+/// ```text
+/// magic "NEDP" | version u32 | from blake3 [32] | to blake3 [32] | to_size u64
+/// | lz4 (size-prepended) bsdiff stream
+/// ```
+///
+/// Both hashes are checked on apply, so a delta can never be applied to the wrong base or
+/// produce a silently corrupted file.
+pub fn make_patch(old: &[u8], new: &[u8]) -> Result<Vec<u8>, AssetError> {
+    let mut raw = Vec::new();
+    bsdiff::diff(old, new, &mut raw).map_err(|e| AssetError::new(format!("patch: diff: {e}")))?;
+
+    let body = lz4_flex::compress_prepend_size(&raw);
+    let mut out = Vec::with_capacity(PATCH_HEADER_SIZE + body.len());
+    out.extend_from_slice(PATCH_MAGIC);
+    out.extend_from_slice(&PATCH_VERSION.to_le_bytes());
+    out.extend_from_slice(blake3::hash(old).as_bytes());
+    out.extend_from_slice(blake3::hash(new).as_bytes());
+    out.extend_from_slice(&(new.len() as u64).to_le_bytes());
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// Applies a `make_patch` delta. Fails if `old` is not the base the delta was made from.
+pub fn apply_patch(old: &[u8], patch: &[u8]) -> Result<Vec<u8>, AssetError> {
+    let header = PatchHeader::parse(patch)?;
+    apply_parsed(old, patch, &header)
+}
+
+/// [`apply_patch`] for a result that must be `expected_size` bytes: a delta claiming another
+/// size is rejected before anything is decompressed.
+pub fn apply_patch_expecting(
+    old: &[u8],
+    patch: &[u8],
+    expected_size: u64,
+) -> Result<Vec<u8>, AssetError> {
+    let header = PatchHeader::parse(patch)?;
+    if header.to_size != expected_size {
+        return Err(AssetError::new(format!(
+            "patch: delta produces {} bytes, expected {expected_size}",
+            header.to_size
+        )));
+    }
+    apply_parsed(old, patch, &header)
+}
+
+fn apply_parsed(old: &[u8], patch: &[u8], header: &PatchHeader) -> Result<Vec<u8>, AssetError> {
+    if blake3::hash(old).as_bytes() != &header.from {
+        return Err(AssetError::new("patch: base file does not match the delta"));
+    }
+
+    // The bsdiff stream holds the result's bytes plus 24 bytes of control per run, and lz4
+    // cannot expand its input by more than ~255x; sizes beyond either are forged.
+    let body = &patch[PATCH_HEADER_SIZE..];
+    if body.len() < 4 {
+        return Err(AssetError::new("patch: truncated delta"));
+    }
+    let raw_size = u32::from_le_bytes(body[0..4].try_into().expect("4 bytes")) as u64;
+    let max_raw = header.to_size.saturating_mul(25).saturating_add(24);
+    let max_lz4 = (body.len() as u64 - 4).saturating_mul(255).saturating_add(16);
+    if raw_size > max_raw || raw_size > max_lz4 {
+        return Err(AssetError::new(format!(
+            "patch: implausible stream size {raw_size} for a {} byte result",
+            header.to_size
+        )));
+    }
+
+    let raw = lz4_flex::decompress(&body[4..], raw_size as usize)
+        .map_err(|e| AssetError::new(format!("patch: lz4: {e}")))?;
+
+    // The result is made of bytes taken from the stream, so it is never larger than it.
+    let mut new = Vec::with_capacity(header.to_size.min(raw.len() as u64) as usize);
+    bsdiff::patch(old, &mut raw.as_slice(), &mut new)
+        .map_err(|e| AssetError::new(format!("patch: apply: {e}")))?;
+
+    if new.len() as u64 != header.to_size || blake3::hash(&new).as_bytes() != &header.to {
+        return Err(AssetError::new("patch: result hash mismatch"));
+    }
+    Ok(new)
+}
+
+struct PatchHeader {
+    from: [u8; 32],
+    to: [u8; 32],
+    to_size: u64,
+}
+
+impl PatchHeader {
+    fn parse(b: &[u8]) -> Result<Self, AssetError> {
+        if b.len() < PATCH_HEADER_SIZE || &b[0..4] != PATCH_MAGIC {
+            return Err(AssetError::new("patch: not a NEDP delta"));
+        }
+        let version = u32::from_le_bytes(b[4..8].try_into().expect("4 bytes"));
+        if version != PATCH_VERSION {
+            return Err(AssetError::new(format!("patch: unsupported version {version}")));
+        }
+        Ok(Self {
+            from: b[8..40].try_into().expect("32 bytes"),
+            to: b[40..72].try_into().expect("32 bytes"),
+            to_size: u64::from_le_bytes(b[72..80].try_into().expect("8 bytes")),
+        })
+    }
+}
+
+/// One file of a content version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// blake3, hex.
+    pub hash: String,
+    pub size: u64,
+}
+
+/// Delta available on the server for upgrading `path` from `from_hash` to the manifest version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestPatch {
+    pub path: String,
+    pub from_hash: String,
+    /// Location of the `.nedelta`, passed verbatim to the fetch callback.
+    pub file: String,
+    pub size: u64,
+    /// blake3 of the `.nedelta`, hex. Patches without one are never used.
+    #[serde(default)]
+    pub hash: String,
+}
+
+/// Content listing of one published version.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentManifest {
+    pub version: u64,
+    pub entries: BTreeMap<String, ManifestEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patches: Vec<ManifestPatch>,
+}
+
+impl ContentManifest {
+    #[inline]
+    pub fn new(version: u64) -> Self {
+        Self {
+            version,
+            ..Self::default()
+        }
+    }
+
+    /// Hashes every file under `root`. Hidden files and directories are skipped, which also
+    /// keeps `LOCAL_MANIFEST_FILE` out of the listing.
+    pub fn from_directory(root: &Path, version: u64) -> Result<Self, AssetError> {
+        let mut m = Self::new(version);
+        let mut stack = vec![root.to_path_buf()];
+
+        while let Some(dir) = stack.pop() {
+            let rd = std::fs::read_dir(&dir)
+                .map_err(|e| AssetError::new(format!("manifest: read_dir '{}': {}", dir.display(), e)))?;
+
+            for e in rd.flatten() {
+                let p = e.path();
+                let hidden = p
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with('.'));
+                if hidden {
+                    continue;
+                }
+                if p.is_dir() {
+                    stack.push(p);
+                    continue;
+                }
+                let Ok(rel) = p.strip_prefix(root) else {
+                    continue;
+                };
+
+                let bytes = std::fs::read(&p)
+                    .map_err(|e| AssetError::new(format!("manifest: read '{}': {}", p.display(), e)))?;
+                m.entries.insert(
+                    normalize_entry_path(&rel.to_string_lossy()),
+                    ManifestEntry {
+                        hash: blake3::hash(&bytes).to_hex().to_string(),
+                        size: bytes.len() as u64,
+                    },
+                );
+            }
+        }
+
+        Ok(m)
+    }
+
+    /// Writes `.nedelta` files for every entry that changed between `old_root` and
+    /// `new_root` into `out_dir` and records them in `patches`. Deltas that do not save at
+    /// least a quarter of the full size are dropped; clients download the file instead.
+    pub fn build_patches(
+        &mut self,
+        previous: &ContentManifest,
+        old_root: &Path,
+        new_root: &Path,
+        out_dir: &Path,
+    ) -> Result<usize, AssetError> {
+        let io = |p: &Path, e: std::io::Error| AssetError::new(format!("patch: '{}': {}", p.display(), e));
+        let mut written = 0usize;
+
+        for (path, entry) in self.entries.iter() {
+            let Some(prev) = previous.entries.get(path) else {
+                continue;
+            };
+            if prev.hash == entry.hash {
+                continue;
+            }
+
+            let old_path = old_root.join(path);
+            let new_path = new_root.join(path);
+            let old = std::fs::read(&old_path).map_err(|e| io(&old_path, e))?;
+            let new = std::fs::read(&new_path).map_err(|e| io(&new_path, e))?;
+            let delta = make_patch(&old, &new)?;
+
+            if delta.len() as f64 > new.len() as f64 * MAX_PATCH_RATIO {
+                continue;
+            }
+
+            // The prefix names a file, so it must be hex even if the manifest was edited.
+            let Some(tag) = prev
+                .hash
+                .get(..16)
+                .filter(|t| t.bytes().all(|b| b.is_ascii_hexdigit()))
+            else {
+                return Err(AssetError::new(format!(
+                    "patch: previous manifest has an invalid hash for '{path}'"
+                )));
+            };
+            let file = format!("{}.{}.{}", path, tag, PATCH_EXTENSION);
+            let out = out_dir.join(&file);
+            if let Some(parent) = out.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| io(parent, e))?;
+            }
+            std::fs::write(&out, &delta).map_err(|e| io(&out, e))?;
+
+            self.patches.retain(|p| !(p.path == *path && p.from_hash == prev.hash));
+            self.patches.push(ManifestPatch {
+                path: path.clone(),
+                from_hash: prev.hash.clone(),
+                file,
+                size: delta.len() as u64,
+                hash: blake3::hash(&delta).to_hex().to_string(),
+            });
+            written += 1;
+        }
+
+        Ok(written)
+    }
+
+    #[inline]
+    fn patch_for(&self, path: &str, from_hash: &str) -> Option<&ManifestPatch> {
+        self.patches
+            .iter()
+            .find(|p| p.path == path && p.from_hash == from_hash && !p.hash.is_empty())
+    }
+}
+
+/// Manifest plus detached ed25519 signature over the exact `body` bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedManifest {
+    pub key_id: String,
+    /// Serialized `ContentManifest`, kept verbatim so verification never depends on
+    /// re-serialization.
+    pub body: String,
+    /// Signature, hex.
+    pub signature: String,
+}
+
+impl SignedManifest {
+    pub fn sign(manifest: &ContentManifest, key_id: impl Into<String>, key: &SigningKey) -> Result<Self, AssetError> {
+        let body = serde_json::to_string(manifest)
+            .map_err(|e| AssetError::new(format!("manifest: serialize: {e}")))?;
+        let signature = key.sign(body.as_bytes());
+        Ok(Self {
+            key_id: key_id.into(),
+            body,
+            signature: hex_encode(&signature.to_bytes()),
+        })
+    }
+
+    /// Checks the signature against `keys` (`key_id` -> public key) and returns the manifest.
+    pub fn verify(&self, keys: &BTreeMap<String, VerifyingKey>) -> Result<ContentManifest, AssetError> {
+        let key = keys
+            .get(&self.key_id)
+            .ok_or_else(|| AssetError::new(format!("manifest: unknown signing key '{}'", self.key_id)))?;
+
+        let sig: [u8; 64] = hex_decode(&self.signature)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| AssetError::new("manifest: malformed signature"))?;
+
+        key.verify(self.body.as_bytes(), &Signature::from_bytes(&sig))
+            .map_err(|_| AssetError::new("manifest: signature verification failed"))?;
+
+        serde_json::from_str(&self.body).map_err(|e| AssetError::new(format!("manifest: parse: {e}")))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("SignedManifest serializes")
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, AssetError> {
+        serde_json::from_str(s).map_err(|e| AssetError::new(format!("manifest: parse: {e}")))
+    }
+}
+
+/// Parses a hex ed25519 public key.
+pub fn verifying_key_from_hex(s: &str) -> Result<VerifyingKey, AssetError> {
+    let bytes: [u8; 32] = hex_decode(s)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| AssetError::new("manifest: public key must be 32 hex-encoded bytes"))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| AssetError::new(format!("manifest: public key: {e}")))
+}
+
+/// Parses a hex ed25519 secret key (32-byte seed).
+pub fn signing_key_from_hex(s: &str) -> Result<SigningKey, AssetError> {
+    let bytes: [u8; 32] = hex_decode(s)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| AssetError::new("manifest: secret key must be 32 hex-encoded bytes"))?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateAction {
+    /// Fetch `file`, check it against `hash` and apply it to the local copy.
+    Patch {
+        path: String,
+        file: String,
+        size: u64,
+        hash: String,
+    },
+    /// Fetch the full file.
+    Download { path: String, size: u64 },
+    Remove { path: String },
+}
+
+/// Steps to go from a local manifest to a remote one.
+#[derive(Debug, Clone, Default)]
+pub struct UpdatePlan {
+    pub actions: Vec<UpdateAction>,
+}
+
+impl UpdatePlan {
+    pub fn between(local: &ContentManifest, remote: &ContentManifest) -> Self {
+        let mut actions = Vec::new();
+
+        for (path, entry) in remote.entries.iter() {
+            match local.entries.get(path) {
+                Some(l) if l.hash == entry.hash => {}
+                Some(l) => match remote.patch_for(path, &l.hash) {
+                    Some(p) => actions.push(UpdateAction::Patch {
+                        path: path.clone(),
+                        file: p.file.clone(),
+                        size: p.size,
+                        hash: p.hash.clone(),
+                    }),
+                    None => actions.push(UpdateAction::Download {
+                        path: path.clone(),
+                        size: entry.size,
+                    }),
+                },
+                None => actions.push(UpdateAction::Download {
+                    path: path.clone(),
+                    size: entry.size,
+                }),
+            }
+        }
+
+        for path in local.entries.keys() {
+            if !remote.entries.contains_key(path) {
+                actions.push(UpdateAction::Remove { path: path.clone() });
+            }
+        }
+
+        Self { actions }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Bytes to transfer.
+    pub fn download_bytes(&self) -> u64 {
+        self.actions
+            .iter()
+            .map(|a| match a {
+                UpdateAction::Patch { size, .. } | UpdateAction::Download { size, .. } => *size,
+                UpdateAction::Remove { .. } => 0,
+            })
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UpdateStats {
+    pub from_version: u64,
+    pub to_version: u64,
+    pub patched: u32,
+    pub downloaded: u32,
+    pub removed: u32,
+    pub transferred_bytes: u64,
+    /// Patches that failed to apply and were replaced by a full download.
+    pub patch_fallbacks: u32,
+}
+
+/// Applies signed manifests to a local content directory (usually the assets root of a
+/// shipped game).
+///
+/// Every file is verified against the manifest hash before it replaces the local copy, and
+/// the local manifest is only rewritten once all files are in place, so an interrupted update
+/// resumes from where it stopped.
+pub struct ContentUpdater {
+    root: PathBuf,
+    trusted_keys: BTreeMap<String, VerifyingKey>,
+}
+
+impl ContentUpdater {
+    #[inline]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            trusted_keys: BTreeMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn with_trusted_key(mut self, key_id: impl Into<String>, key: VerifyingKey) -> Self {
+        self.trusted_keys.insert(key_id.into(), key);
+        self
+    }
+
+    #[inline]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Local manifest, or one computed from disk if none was written yet.
+    pub fn local_manifest(&self) -> Result<ContentManifest, AssetError> {
+        let path = self.root.join(LOCAL_MANIFEST_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(s) => serde_json::from_str(&s)
+                .map_err(|e| AssetError::new(format!("manifest: '{}': {}", path.display(), e))),
+            Err(_) => ContentManifest::from_directory(&self.root, 0),
+        }
+    }
+
+    /// Verifies `remote`, then fetches and applies everything needed to match it.
+    ///
+    /// `fetch` receives either a manifest path (full download) or a `ManifestPatch::file`.
+    pub fn apply(
+        &self,
+        remote: &SignedManifest,
+        fetch: &mut dyn FnMut(&str) -> Result<Vec<u8>, AssetError>,
+    ) -> Result<UpdateStats, AssetError> {
+        let target = remote.verify(&self.trusted_keys)?;
+        let local = self.local_manifest()?;
+
+        let mut stats = UpdateStats {
+            from_version: local.version,
+            to_version: target.version,
+            ..UpdateStats::default()
+        };
+
+        if target.version < local.version {
+            return Err(AssetError::new(format!(
+                "update: remote version {} is older than local {}",
+                target.version, local.version
+            )));
+        }
+
+        let plan = UpdatePlan::between(&local, &target);
+        info!(
+            target: "assets::update",
+            "update.plan from={} to={} actions={} bytes={}",
+            local.version,
+            target.version,
+            plan.actions.len(),
+            plan.download_bytes()
+        );
+
+        for action in plan.actions.iter() {
+            match action {
+                UpdateAction::Patch { path, file, hash, .. } => {
+                    let expected = &target.entries[path];
+                    let delta = fetch(file)?;
+                    stats.transferred_bytes += delta.len() as u64;
+
+                    // The delta is fetched separately; only the signed manifest vouches for it.
+                    let patched = if blake3::hash(&delta).to_hex().as_str() != hash.as_str() {
+                        Err(AssetError::new(format!("update: hash mismatch for delta '{file}'")))
+                    } else {
+                        self.read_local(path).and_then(|old| {
+                            let b = apply_patch_expecting(&old, &delta, expected.size)?;
+                            check_hash(path, &b, expected).map(|_| b)
+                        })
+                    };
+                    match patched {
+                        Ok(bytes) => {
+                            self.write_local(path, &bytes)?;
+                            stats.patched += 1;
+                        }
+                        Err(e) => {
+                            warn!(target: "assets::update", "update.patch failed path='{}' err='{}' fallback=download", path, e);
+                            stats.patch_fallbacks += 1;
+                            self.download(path, expected, fetch, &mut stats)?;
+                        }
+                    }
+                }
+                UpdateAction::Download { path, .. } => {
+                    self.download(path, &target.entries[path], fetch, &mut stats)?;
+                }
+                UpdateAction::Remove { path } => {
+                    let p = self.local_path(path)?;
+                    if p.exists() {
+                        std::fs::remove_file(&p)
+                            .map_err(|e| AssetError::new(format!("update: remove '{}': {}", p.display(), e)))?;
+                    }
+                    stats.removed += 1;
+                }
+            }
+        }
+
+        let mut written = target.clone();
+        written.patches.clear();
+        let json = serde_json::to_string_pretty(&written)
+            .map_err(|e| AssetError::new(format!("manifest: serialize: {e}")))?;
+        self.write_local(LOCAL_MANIFEST_FILE, json.as_bytes())?;
+
+        info!(
+            target: "assets::update",
+            "update.done version={} patched={} downloaded={} removed={} bytes={} fallbacks={}",
+            stats.to_version,
+            stats.patched,
+            stats.downloaded,
+            stats.removed,
+            stats.transferred_bytes,
+            stats.patch_fallbacks
+        );
+        Ok(stats)
+    }
+
+    fn download(
+        &self,
+        path: &str,
+        expected: &ManifestEntry,
+        fetch: &mut dyn FnMut(&str) -> Result<Vec<u8>, AssetError>,
+        stats: &mut UpdateStats,
+    ) -> Result<(), AssetError> {
+        let bytes = fetch(path)?;
+        stats.transferred_bytes += bytes.len() as u64;
+        check_hash(path, &bytes, expected)?;
+        self.write_local(path, &bytes)?;
+        stats.downloaded += 1;
+        Ok(())
+    }
+
+    /// Resolves a manifest path under the root, rejecting traversal.
+    fn local_path(&self, path: &str) -> Result<PathBuf, AssetError> {
+        let rel = Path::new(path);
+        let ok = rel
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)));
+        if !ok {
+            return Err(AssetError::new(format!("update: invalid manifest path '{path}'")));
+        }
+        Ok(self.root.join(rel))
+    }
+
+    fn read_local(&self, path: &str) -> Result<Vec<u8>, AssetError> {
+        let p = self.local_path(path)?;
+        std::fs::read(&p).map_err(|e| AssetError::new(format!("update: read '{}': {}", p.display(), e)))
+    }
+
+    /// Writes through a temp file + rename so readers never observe a partial file.
+    fn write_local(&self, path: &str, bytes: &[u8]) -> Result<(), AssetError> {
+        let p = self.local_path(path)?;
+        let io = |e: std::io::Error| AssetError::new(format!("update: write '{}': {}", p.display(), e));
+        if let Some(parent) = p.parent() {
+            std::fs::create_dir_all(parent).map_err(io)?;
+        }
+        let tmp = p.with_extension("partial");
+        std::fs::write(&tmp, bytes).map_err(io)?;
+        std::fs::rename(&tmp, &p).map_err(io)
+    }
+}
+
+fn check_hash(path: &str, bytes: &[u8], expected: &ManifestEntry) -> Result<(), AssetError> {
+    let hash = blake3::hash(bytes).to_hex();
+    if hash.as_str() != expected.hash {
+        return Err(AssetError::new(format!("update: hash mismatch for '{path}'")));
+    }
+    Ok(())
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        s.push(DIGITS[(b >> 4) as usize] as char);
+        s.push(DIGITS[(b & 0x0f) as usize] as char);
+    }
+    s
+}
+
+fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim();
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
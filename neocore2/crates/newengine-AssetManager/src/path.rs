@@ -0,0 +1,127 @@
+use crate::types::AssetError;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+/// How logical paths are matched against source entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathCase {
+    /// The requested spelling must match the entry exactly.
+    #[default]
+    Sensitive,
+    /// Falls back to a match ignoring case when the exact entry is missing, so content
+    /// authored on a case-insensitive file system loads the same everywhere. Several entries
+    /// differing only by case make the lookup fail instead of picking one.
+    Insensitive,
+}
+
+/// Canonical spelling of a logical path: `/` separators, no empty or `.` segments, no
+/// trailing `/`, Unicode NFC. A leading `/` and `..` segments are kept for the caller to
+/// reject.
+pub fn normalize_path(p: &str) -> Cow<'_, str> {
+    let clean = !p.contains('\\')
+        && !p.contains("//")
+        && !p.starts_with("./")
+        && !p.contains("/./")
+        && !p.ends_with('/')
+        && !p.ends_with("/.")
+        && p != "."
+        && is_nfc_quick(p.chars()) == IsNormalized::Yes;
+    if clean {
+        return Cow::Borrowed(p);
+    }
+
+    let nfc: String = p.nfc().collect();
+    let mut out = String::with_capacity(nfc.len());
+    if nfc.starts_with(['/', '\\']) {
+        out.push('/');
+    }
+    for seg in nfc.split(['/', '\\']) {
+        if seg.is_empty() || seg == "." {
+            continue;
+        }
+        if !out.is_empty() && !out.ends_with('/') {
+            out.push('/');
+        }
+        out.push_str(seg);
+    }
+    Cow::Owned(out)
+}
+
+/// Key used for case-insensitive comparison: [`normalize_path`] followed by Unicode lowercase.
+#[inline]
+pub fn fold_case(p: &str) -> String {
+    normalize_path(p).to_lowercase()
+}
+
+/// Entries that differ only by case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseCollision {
+    pub folded: String,
+    /// Sorted spellings.
+    pub paths: Vec<String>,
+}
+
+impl std::fmt::Display for CaseCollision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "paths differ only by case: {}", self.paths.join(", "))
+    }
+}
+
+/// Case-folded lookup over a fixed set of entry paths.
+#[derive(Debug, Clone, Default)]
+pub struct CaseIndex {
+    by_folded: HashMap<String, Vec<String>>,
+}
+
+impl CaseIndex {
+    pub fn new<'a>(paths: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut by_folded: HashMap<String, Vec<String>> = HashMap::new();
+        for p in paths {
+            let spellings = by_folded.entry(fold_case(p)).or_default();
+            if !spellings.iter().any(|s| s == p) {
+                spellings.push(p.to_string());
+            }
+        }
+        Self { by_folded }
+    }
+
+    /// Entry matching `path` ignoring case; an error if the match is ambiguous.
+    pub fn resolve(&self, path: &str) -> Result<Option<&str>, AssetError> {
+        match self.by_folded.get(&fold_case(path)).map(Vec::as_slice) {
+            None | Some([]) => Ok(None),
+            Some([only]) => Ok(Some(only.as_str())),
+            Some(many) => Err(AssetError::new(format!(
+                "ambiguous path '{}': matches {}",
+                path,
+                many.join(", ")
+            ))),
+        }
+    }
+
+    /// All groups of entries that differ only by case, sorted by folded path.
+    pub fn collisions(&self) -> Vec<CaseCollision> {
+        let mut out: Vec<CaseCollision> = self
+            .by_folded
+            .iter()
+            .filter(|(_, v)| v.len() > 1)
+            .map(|(k, v)| {
+                let mut paths = v.clone();
+                paths.sort();
+                CaseCollision {
+                    folded: k.clone(),
+                    paths,
+                }
+            })
+            .collect();
+        out.sort_by(|a, b| a.folded.cmp(&b.folded));
+        out
+    }
+}
+
+/// Groups of `paths` that differ only by case (after normalization).
+#[inline]
+pub fn find_case_collisions<'a>(paths: impl IntoIterator<Item = &'a str>) -> Vec<CaseCollision> {
+    CaseIndex::new(paths).collisions()
+}
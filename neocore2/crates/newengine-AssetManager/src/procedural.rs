@@ -0,0 +1,493 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::store::BlobImporterDispatch;
+use crate::texture::{TextureAsset, TEXTURE_RGBA8_FORMAT, TEXTURE_TYPE_ID};
+use crate::types::{AssetBlob, AssetError, AssetKey, ImporterPriority};
+
+use serde::Deserialize;
+use std::sync::Arc;
+
+pub const PROCEDURAL_TEXTURE_EXTENSION: &str = "proctex";
+pub const PROCEDURAL_TEXTURE_VERSION: &str = "proctex.v1";
+
+const MAX_SIZE: u32 = 8192;
+
+pub type Rgba = [f32; 4];
+
+/// `.proctex` recipe: layers are evaluated bottom-up and composited into an RGBA8 texture.
+///
+/// ```json
+/// {
+///   "width": 256, "height": 256, "seed": 7,
+///   "layers": [
+///     { "type": "gradient", "kind": "linear", "angle": 90,
+///       "stops": [{ "at": 0, "color": [0.1, 0.2, 0.4, 1] }, { "at": 1, "color": [0.6, 0.8, 1, 1] }] },
+///     { "type": "noise", "kind": "simplex", "scale": 6, "octaves": 5,
+///       "blend": "multiply", "opacity": 0.5 }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProceduralRecipe {
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub seed: u32,
+    #[serde(default = "default_true")]
+    pub mips: bool,
+    pub layers: Vec<ProceduralLayer>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProceduralLayer {
+    #[serde(flatten)]
+    pub source: LayerSource,
+    #[serde(default)]
+    pub blend: BlendMode,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LayerSource {
+    Solid {
+        color: Rgba,
+    },
+    Gradient {
+        #[serde(default)]
+        kind: GradientKind,
+        /// Degrees, linear only (0 = left to right, 90 = top to bottom).
+        #[serde(default)]
+        angle: f32,
+        stops: Vec<ColorStop>,
+    },
+    Noise {
+        #[serde(default)]
+        kind: NoiseKind,
+        /// Base frequency in cells across the texture.
+        #[serde(default = "default_scale")]
+        scale: f32,
+        #[serde(default = "default_octaves")]
+        octaves: u32,
+        #[serde(default = "default_lacunarity")]
+        lacunarity: f32,
+        #[serde(default = "default_gain")]
+        gain: f32,
+        /// `1 - |n|` per octave (ridges/veins).
+        #[serde(default)]
+        ridged: bool,
+        /// Added to the recipe seed so layers can decorrelate.
+        #[serde(default)]
+        seed: u32,
+        /// Color ramp for the `0..1` noise value; grayscale when empty.
+        #[serde(default)]
+        stops: Vec<ColorStop>,
+    },
+    Checker {
+        #[serde(default = "default_scale")]
+        scale: f32,
+        a: Rgba,
+        b: Rgba,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GradientKind {
+    #[default]
+    Linear,
+    Radial,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseKind {
+    #[default]
+    Perlin,
+    Simplex,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Add,
+    Multiply,
+    Screen,
+    Overlay,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ColorStop {
+    pub at: f32,
+    pub color: Rgba,
+}
+
+#[inline]
+fn default_true() -> bool {
+    true
+}
+
+#[inline]
+fn default_opacity() -> f32 {
+    1.0
+}
+
+#[inline]
+fn default_scale() -> f32 {
+    4.0
+}
+
+#[inline]
+fn default_octaves() -> u32 {
+    4
+}
+
+#[inline]
+fn default_lacunarity() -> f32 {
+    2.0
+}
+
+#[inline]
+fn default_gain() -> f32 {
+    0.5
+}
+
+impl ProceduralRecipe {
+    #[inline]
+    pub fn from_json(bytes: &[u8]) -> Result<Self, AssetError> {
+        serde_json::from_slice(bytes)
+            .map_err(|e| AssetError::new(format!("proctex: recipe json: {e}")))
+    }
+
+    /// Evaluates the recipe on the CPU.
+    pub fn evaluate(&self) -> Result<TextureAsset, AssetError> {
+        let (w, h) = (self.width, self.height);
+        if w == 0 || h == 0 || w > MAX_SIZE || h > MAX_SIZE {
+            return Err(AssetError::new(format!(
+                "proctex: invalid size {w}x{h} (1..={MAX_SIZE})"
+            )));
+        }
+
+        let mut px: Vec<Rgba> = vec![[0.0, 0.0, 0.0, 0.0]; w as usize * h as usize];
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            let noise = match &layer.source {
+                LayerSource::Noise { seed, .. } => Some(Permutation::new(
+                    self.seed.wrapping_add(*seed).wrapping_add(i as u32),
+                )),
+                _ => None,
+            };
+            let opacity = layer.opacity.clamp(0.0, 1.0);
+
+            for y in 0..h {
+                for x in 0..w {
+                    let u = (x as f32 + 0.5) / w as f32;
+                    let v = (y as f32 + 0.5) / h as f32;
+                    let src = sample(&layer.source, noise.as_ref(), u, v);
+                    let dst = &mut px[(y * w + x) as usize];
+                    *dst = composite(*dst, src, layer.blend, opacity);
+                }
+            }
+        }
+
+        let mut base = Vec::with_capacity(px.len() * 4);
+        for p in px.iter() {
+            for c in p.iter() {
+                base.push((c.clamp(0.0, 1.0) * 255.0 + 0.5) as u8);
+            }
+        }
+
+        Ok(TextureAsset::rgba8(w, h, base, self.mips))
+    }
+}
+
+fn sample(src: &LayerSource, perm: Option<&Permutation>, u: f32, v: f32) -> Rgba {
+    match src {
+        LayerSource::Solid { color } => *color,
+        LayerSource::Gradient { kind, angle, stops } => {
+            let t = match kind {
+                GradientKind::Linear => {
+                    let (s, c) = angle.to_radians().sin_cos();
+                    // Project onto the direction, remapped so the texture spans 0..1.
+                    let d = (u - 0.5) * c + (v - 0.5) * s;
+                    let half = 0.5 * (c.abs() + s.abs());
+                    0.5 + d / (2.0 * half.max(1e-6))
+                }
+                GradientKind::Radial => {
+                    let (dx, dy) = (u - 0.5, v - 0.5);
+                    (dx * dx + dy * dy).sqrt() * 2.0
+                }
+            };
+            ramp(stops, t)
+        }
+        LayerSource::Noise {
+            kind,
+            scale,
+            octaves,
+            lacunarity,
+            gain,
+            ridged,
+            stops,
+            ..
+        } => {
+            let Some(perm) = perm else {
+                return [0.0, 0.0, 0.0, 1.0];
+            };
+
+            let mut freq = *scale;
+            let mut amp = 1.0f32;
+            let mut sum = 0.0f32;
+            let mut norm = 0.0f32;
+
+            for _ in 0..(*octaves).clamp(1, 12) {
+                let n = match kind {
+                    NoiseKind::Perlin => perm.perlin(u * freq, v * freq),
+                    NoiseKind::Simplex => perm.simplex(u * freq, v * freq),
+                };
+                let n = if *ridged { 1.0 - n.abs() } else { 0.5 + 0.5 * n };
+                sum += n * amp;
+                norm += amp;
+                freq *= lacunarity;
+                amp *= gain;
+            }
+
+            let t = if norm > 0.0 { sum / norm } else { 0.0 };
+            if stops.is_empty() {
+                [t, t, t, 1.0]
+            } else {
+                ramp(stops, t)
+            }
+        }
+        LayerSource::Checker { scale, a, b } => {
+            let cx = (u * scale).floor() as i32;
+            let cy = (v * scale).floor() as i32;
+            if (cx + cy) & 1 == 0 {
+                *a
+            } else {
+                *b
+            }
+        }
+    }
+}
+
+fn ramp(stops: &[ColorStop], t: f32) -> Rgba {
+    let t = t.clamp(0.0, 1.0);
+    match stops {
+        [] => [t, t, t, 1.0],
+        [only] => only.color,
+        _ => {
+            if t <= stops[0].at {
+                return stops[0].color;
+            }
+            for pair in stops.windows(2) {
+                let (a, b) = (pair[0], pair[1]);
+                if t <= b.at {
+                    let span = (b.at - a.at).max(1e-6);
+                    return lerp4(a.color, b.color, (t - a.at) / span);
+                }
+            }
+            stops[stops.len() - 1].color
+        }
+    }
+}
+
+#[inline]
+fn lerp4(a: Rgba, b: Rgba, t: f32) -> Rgba {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+        a[3] + (b[3] - a[3]) * t,
+    ]
+}
+
+fn composite(dst: Rgba, src: Rgba, mode: BlendMode, opacity: f32) -> Rgba {
+    let blend = |d: f32, s: f32| match mode {
+        BlendMode::Normal => s,
+        BlendMode::Add => d + s,
+        BlendMode::Multiply => d * s,
+        BlendMode::Screen => 1.0 - (1.0 - d) * (1.0 - s),
+        BlendMode::Overlay => {
+            if d < 0.5 {
+                2.0 * d * s
+            } else {
+                1.0 - 2.0 * (1.0 - d) * (1.0 - s)
+            }
+        }
+    };
+
+    let a = src[3] * opacity;
+    let mut out = [0.0; 4];
+    for c in 0..3 {
+        let b = blend(dst[c], src[c]).clamp(0.0, 1.0);
+        out[c] = dst[c] + (b - dst[c]) * a;
+    }
+    out[3] = a + dst[3] * (1.0 - a);
+    out
+}
+
+/// Seeded permutation table shared by Perlin and simplex noise.
+struct Permutation {
+    p: [u8; 512],
+}
+
+impl Permutation {
+    fn new(seed: u32) -> Self {
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+
+        // xorshift32 Fisher-Yates; seed 0 is remapped so the state never sticks at zero.
+        let mut s = seed ^ 0x9E37_79B9;
+        if s == 0 {
+            s = 1;
+        }
+        for i in (1..256usize).rev() {
+            s ^= s << 13;
+            s ^= s >> 17;
+            s ^= s << 5;
+            let j = (s as usize) % (i + 1);
+            table.swap(i, j);
+        }
+
+        Self {
+            p: std::array::from_fn(|i| table[i & 255]),
+        }
+    }
+
+    #[inline]
+    fn hash(&self, x: i32, y: i32) -> u8 {
+        let xi = (x & 255) as usize;
+        let yi = (y & 255) as usize;
+        self.p[self.p[xi] as usize + yi]
+    }
+
+    /// Classic gradient noise, roughly `-1..1`.
+    fn perlin(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (xi, yi) = (x0 as i32, y0 as i32);
+
+        let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let (u, v) = (fade(fx), fade(fy));
+
+        let n00 = grad(self.hash(xi, yi), fx, fy);
+        let n10 = grad(self.hash(xi + 1, yi), fx - 1.0, fy);
+        let n01 = grad(self.hash(xi, yi + 1), fx, fy - 1.0);
+        let n11 = grad(self.hash(xi + 1, yi + 1), fx - 1.0, fy - 1.0);
+
+        let nx0 = n00 + (n10 - n00) * u;
+        let nx1 = n01 + (n11 - n01) * u;
+        (nx0 + (nx1 - nx0) * v) * std::f32::consts::SQRT_2
+    }
+
+    /// 2D simplex noise, roughly `-1..1`.
+    fn simplex(&self, x: f32, y: f32) -> f32 {
+        const F2: f32 = 0.366_025_42; // (sqrt(3) - 1) / 2
+        const G2: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+
+        let s = (x + y) * F2;
+        let (i, j) = ((x + s).floor(), (y + s).floor());
+        let t = (i + j) * G2;
+        let (x0, y0) = (x - (i - t), y - (j - t));
+
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let (x1, y1) = (x0 - i1 as f32 + G2, y0 - j1 as f32 + G2);
+        let (x2, y2) = (x0 - 1.0 + 2.0 * G2, y0 - 1.0 + 2.0 * G2);
+        let (ii, jj) = (i as i32, j as i32);
+
+        let corner = |h: u8, dx: f32, dy: f32| {
+            let t = 0.5 - dx * dx - dy * dy;
+            if t <= 0.0 {
+                0.0
+            } else {
+                let t2 = t * t;
+                t2 * t2 * grad(h, dx, dy)
+            }
+        };
+
+        let n0 = corner(self.hash(ii, jj), x0, y0);
+        let n1 = corner(self.hash(ii + i1, jj + j1), x1, y1);
+        let n2 = corner(self.hash(ii + 1, jj + 1), x2, y2);
+
+        70.0 * (n0 + n1 + n2)
+    }
+}
+
+#[inline]
+fn grad(h: u8, x: f32, y: f32) -> f32 {
+    match h & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+/// In-process importer for `.proctex` recipes.
+///
+/// Output is a regular `kalitech.asset.texture` blob in the `ne.texture.rgba8.v1` format
+/// (full mip chain unless the recipe disables it), so consumers treat it like any other
+/// texture. Results are deterministic for a given recipe and land in the persistent cache.
+#[derive(Debug, Default)]
+pub struct ProceduralTextureImporter;
+
+impl BlobImporterDispatch for ProceduralTextureImporter {
+    fn import_blob(&self, bytes: &[u8], key: &AssetKey) -> Result<AssetBlob, AssetError> {
+        let recipe = ProceduralRecipe::from_json(bytes)?;
+        let tex = recipe.evaluate()?;
+
+        log::info!(
+            target: "assets",
+            "proctex.import path='{}' size={}x{} layers={} mips={}",
+            key.logical_path.display(),
+            tex.desc.width,
+            tex.desc.height,
+            recipe.layers.len(),
+            tex.desc.mip_count
+        );
+
+        let meta_json = format!(
+            "{{\"schema\":\"kalitech.texture.meta.v1\",\"container\":\"procedural\",\"width\":{},\"height\":{},\"depth\":1,\"mips\":{},\"is_cube\":false,\"format\":\"RGBA8\"}}",
+            tex.desc.width, tex.desc.height, tex.desc.mip_count
+        );
+
+        Ok(AssetBlob {
+            type_id: Arc::from(TEXTURE_TYPE_ID),
+            format: Arc::from(TEXTURE_RGBA8_FORMAT),
+            payload: tex.to_rgba8_payload(),
+            meta_json: Arc::from(meta_json),
+            dependencies: Vec::new(),
+        })
+    }
+
+    #[inline]
+    fn output_type_id(&self) -> Arc<str> {
+        Arc::from(TEXTURE_TYPE_ID)
+    }
+
+    #[inline]
+    fn extensions(&self) -> Vec<String> {
+        vec![PROCEDURAL_TEXTURE_EXTENSION.to_string()]
+    }
+
+    #[inline]
+    fn priority(&self) -> ImporterPriority {
+        ImporterPriority::new(100)
+    }
+
+    #[inline]
+    fn stable_id(&self) -> Arc<str> {
+        Arc::from("proctex_importer@newengine-assets")
+    }
+
+    #[inline]
+    fn version(&self) -> Arc<str> {
+        Arc::from(PROCEDURAL_TEXTURE_VERSION)
+    }
+}
@@ -0,0 +1,215 @@
+//! Asset browser queries: list assets by folder, extension, import state and tag.
+//!
+//! [`AssetStore::query`](crate::AssetStore::query) merges the entries of every source with
+//! the assets the store already knows, so editor panels never walk the filesystem
+//! themselves. Tags live in the asset's `.meta` sidecar (see [`crate::meta`]).
+
+use crate::id::AssetId;
+use crate::path::fold_case;
+use crate::types::AssetState;
+
+use std::path::Path;
+use std::sync::Arc;
+
+/// Import state bucket of a query entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetStateKind {
+    Unloaded,
+    Loading,
+    Ready,
+    Failed,
+}
+
+impl AssetStateKind {
+    #[inline]
+    pub fn of(state: &AssetState) -> Self {
+        match state {
+            AssetState::Unloaded => Self::Unloaded,
+            AssetState::Loading => Self::Loading,
+            AssetState::Ready => Self::Ready,
+            AssetState::Failed(_) => Self::Failed,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unloaded => "unloaded",
+            Self::Loading => "loading",
+            Self::Ready => "ready",
+            Self::Failed => "failed",
+        }
+    }
+
+    #[inline]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "unloaded" => Some(Self::Unloaded),
+            "loading" => Some(Self::Loading),
+            "ready" => Some(Self::Ready),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Filter for [`AssetStore::query`](crate::AssetStore::query); the default matches every
+/// asset. Filters combine with AND; several extensions or states match any of them, several
+/// tags must all be present.
+#[derive(Debug, Clone, Default)]
+pub struct AssetQuery {
+    /// Logical folder (`/`-separated, no trailing slash); `None` is the whole tree.
+    pub folder: Option<String>,
+    /// Include assets in subfolders of `folder`.
+    pub recursive: bool,
+    /// Extensions without the dot, compared ignoring ASCII case.
+    pub extensions: Vec<String>,
+    pub states: Vec<AssetStateKind>,
+    pub tags: Vec<String>,
+    /// Case-insensitive substring of the logical path.
+    pub text: Option<String>,
+    /// Maximum entries returned; `total` in the result still counts every match.
+    pub limit: Option<usize>,
+}
+
+impl AssetQuery {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assets directly in `folder`.
+    #[inline]
+    pub fn in_folder(mut self, folder: impl AsRef<str>) -> Self {
+        self.folder = Some(normalize_folder(folder.as_ref()));
+        self.recursive = false;
+        self
+    }
+
+    /// Assets in `folder` and its subfolders.
+    #[inline]
+    pub fn under_folder(mut self, folder: impl AsRef<str>) -> Self {
+        self.folder = Some(normalize_folder(folder.as_ref()));
+        self.recursive = true;
+        self
+    }
+
+    #[inline]
+    pub fn with_extension(mut self, ext: impl AsRef<str>) -> Self {
+        self.extensions
+            .push(ext.as_ref().trim_start_matches('.').to_ascii_lowercase());
+        self
+    }
+
+    #[inline]
+    pub fn with_state(mut self, state: AssetStateKind) -> Self {
+        self.states.push(state);
+        self
+    }
+
+    #[inline]
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    #[inline]
+    pub fn matching(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    #[inline]
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Folder, extension and text filters, which need nothing but the path.
+    pub(crate) fn matches_path(&self, path: &str) -> bool {
+        if let Some(folder) = self.folder.as_deref().filter(|f| !f.is_empty()) {
+            let Some(rest) = path.strip_prefix(folder).and_then(|r| r.strip_prefix('/')) else {
+                return false;
+            };
+            if !self.recursive && rest.contains('/') {
+                return false;
+            }
+        } else if self.folder.is_some() && !self.recursive && path.contains('/') {
+            return false;
+        }
+
+        if !self.extensions.is_empty() {
+            let ext = extension(path).unwrap_or("");
+            if !self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)) {
+                return false;
+            }
+        }
+
+        if let Some(text) = self.text.as_deref().filter(|t| !t.is_empty()) {
+            if !fold_case(path).contains(&fold_case(text)) {
+                return false;
+            }
+        }
+        true
+    }
+
+    #[inline]
+    pub(crate) fn matches_state(&self, state: &AssetState) -> bool {
+        self.states.is_empty() || self.states.contains(&AssetStateKind::of(state))
+    }
+
+    #[inline]
+    pub(crate) fn matches_tags(&self, tags: &[String]) -> bool {
+        self.tags
+            .iter()
+            .all(|want| tags.iter().any(|t| t.eq_ignore_ascii_case(want)))
+    }
+}
+
+/// One asset of a query result.
+#[derive(Debug, Clone)]
+pub struct AssetQueryEntry {
+    /// Logical path, `/`-separated.
+    pub path: String,
+    pub id: AssetId,
+    pub state: AssetState,
+    pub tags: Vec<String>,
+    /// Type and payload size of the resident blob; `None` unless the asset is ready.
+    pub type_id: Option<Arc<str>>,
+    pub bytes: Option<u64>,
+}
+
+impl AssetQueryEntry {
+    /// Extension without the dot.
+    #[inline]
+    pub fn extension(&self) -> Option<&str> {
+        extension(&self.path)
+    }
+
+    /// Containing folder; empty at the root.
+    #[inline]
+    pub fn folder(&self) -> &str {
+        self.path.rsplit_once('/').map_or("", |(dir, _)| dir)
+    }
+}
+
+/// Entries sorted by path.
+#[derive(Debug, Clone, Default)]
+pub struct AssetQueryResult {
+    /// [`AssetStore::query_revision`](crate::AssetStore::query_revision) the result was taken
+    /// at; a view is stale once the store's revision moves past it.
+    pub revision: u64,
+    /// Matches before `limit` was applied.
+    pub total: usize,
+    pub entries: Vec<AssetQueryEntry>,
+}
+
+#[inline]
+fn normalize_folder(folder: &str) -> String {
+    folder.replace('\\', "/").trim_matches('/').to_string()
+}
+
+#[inline]
+fn extension(path: &str) -> Option<&str> {
+    Path::new(path).extension().and_then(|e| e.to_str())
+}
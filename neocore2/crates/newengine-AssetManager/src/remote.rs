@@ -0,0 +1,355 @@
+//! Shared import server for team workflows.
+//!
+//! One machine runs [`AssetServer`] next to a store that has the full importer set; editor
+//! instances install a [`RemoteImportSource`] as their store's import provider. On a local
+//! cache miss the client sends the import's content hash ([`CacheKey`]) and the server
+//! answers from its cache, importing on demand, so each source version is imported once
+//! per team instead of once per workstation.
+//!
+//! Wire format: length-prefixed frames (`u32` LE) over TCP. A request is one JSON frame; a
+//! response is a JSON status frame followed, on success, by the blob in cache entry format.
+
+use crate::cache::{decode_entry_checked, CacheKey, EntryError};
+use crate::source::AssetSource;
+use crate::store::{AssetStore, ImportProvider};
+use crate::types::{AssetBlob, AssetKey};
+
+use log::{debug, info, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const PROTOCOL_VERSION: u32 = 1;
+/// Upper bound for a single frame; larger blobs are imported locally.
+const MAX_FRAME: usize = 1 << 30;
+/// After a connection failure the client imports locally for this long before retrying.
+const RETRY_AFTER: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ImportRequest {
+    v: u32,
+    key: String,
+    path: String,
+    settings_hash: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "status")]
+enum ImportResponse {
+    Ok,
+    /// The server's source differs from the client's (or is missing).
+    Miss,
+    Error { error: String },
+}
+
+fn write_frame(w: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    w.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    w.write_all(bytes)
+}
+
+fn read_frame(r: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too large"));
+    }
+    let mut buf = vec![0u8; len];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+pub(crate) fn write_json(w: &mut impl Write, v: &impl Serialize) -> io::Result<()> {
+    let bytes = serde_json::to_vec(v).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    write_frame(w, &bytes)
+}
+
+pub(crate) fn read_json<T: for<'de> Deserialize<'de>>(r: &mut impl Read) -> io::Result<T> {
+    let bytes = read_frame(r)?;
+    serde_json::from_slice(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Serves imports from `store` until the handle is shut down or dropped.
+pub struct AssetServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AssetServer {
+    /// Binds `addr` and starts accepting clients on a background thread.
+    pub fn spawn(addr: impl ToSocketAddrs, store: Arc<AssetStore>) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+
+        info!(target: "assets::remote", "server.listen addr={addr}");
+
+        let stop_flag = stop.clone();
+        let thread = std::thread::Builder::new()
+            .name("asset-server".into())
+            .spawn(move || accept_loop(listener, store, stop_flag))?;
+
+        Ok(Self {
+            addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    #[inline]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stops accepting clients. Connections already being served finish their request.
+    pub fn shutdown(&mut self) {
+        if self.stop.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // Wake the blocking accept.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+        info!(target: "assets::remote", "server.stopped addr={}", self.addr);
+    }
+}
+
+impl Drop for AssetServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn accept_loop(listener: TcpListener, store: Arc<AssetStore>, stop: Arc<AtomicBool>) {
+    for conn in listener.incoming() {
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        let stream = match conn {
+            Ok(s) => s,
+            Err(e) => {
+                warn!(target: "assets::remote", "server.accept failed err='{e}'");
+                continue;
+            }
+        };
+
+        let store = store.clone();
+        let spawned = std::thread::Builder::new()
+            .name("asset-server-conn".into())
+            .spawn(move || serve_client(stream, &store));
+        if let Err(e) = spawned {
+            warn!(target: "assets::remote", "server.spawn failed err='{e}'");
+        }
+    }
+}
+
+fn serve_client(mut stream: TcpStream, store: &AssetStore) {
+    let peer = stream
+        .peer_addr()
+        .map_or_else(|_| "?".to_owned(), |a| a.to_string());
+    debug!(target: "assets::remote", "server.client connected peer={peer}");
+
+    loop {
+        let req: ImportRequest = match read_json(&mut stream) {
+            Ok(r) => r,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => {
+                warn!(target: "assets::remote", "server.read failed peer={peer} err='{e}'");
+                break;
+            }
+        };
+
+        let t0 = Instant::now();
+        let (resp, entry) = handle_request(store, &req);
+        info!(
+            target: "assets::remote",
+            "server.request peer={peer} path='{}' key={} result={:?} bytes={} dt_us={}",
+            req.path,
+            req.key,
+            resp,
+            entry.as_ref().map_or(0, Vec::len),
+            t0.elapsed().as_micros()
+        );
+
+        let sent = write_json(&mut stream, &resp).and_then(|_| match &entry {
+            Some(bytes) => write_frame(&mut stream, bytes),
+            None => Ok(()),
+        });
+        if let Err(e) = sent {
+            warn!(target: "assets::remote", "server.write failed peer={peer} err='{e}'");
+            break;
+        }
+    }
+
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+fn handle_request(store: &AssetStore, req: &ImportRequest) -> (ImportResponse, Option<Vec<u8>>) {
+    if req.v != PROTOCOL_VERSION {
+        let error = format!("protocol version {} not supported", req.v);
+        return (ImportResponse::Error { error }, None);
+    }
+    let Some(expected) = CacheKey::from_hex(&req.key) else {
+        let error = "malformed key".to_owned();
+        return (ImportResponse::Error { error }, None);
+    };
+
+    let key = AssetKey::new(PathBuf::from(&req.path), req.settings_hash);
+    match store.import_for_remote(&key, &expected) {
+        Ok(Some(entry)) if entry.len() <= MAX_FRAME => (ImportResponse::Ok, Some(entry)),
+        Ok(_) => (ImportResponse::Miss, None),
+        Err(e) => (
+            ImportResponse::Error {
+                error: e.msg().to_owned(),
+            },
+            None,
+        ),
+    }
+}
+
+/// Client side: fetches imports from an [`AssetServer`] by content hash.
+///
+/// Any failure (server down, miss, stale dependency) makes the store import locally, so a
+/// missing server only costs the local import time.
+pub struct RemoteImportSource {
+    addr: String,
+    timeout: Duration,
+    conn: Mutex<Option<TcpStream>>,
+    offline_until: Mutex<Option<Instant>>,
+}
+
+impl RemoteImportSource {
+    #[inline]
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            timeout: Duration::from_secs(30),
+            conn: Mutex::new(None),
+            offline_until: Mutex::new(None),
+        }
+    }
+
+    /// Read/write timeout per request; imports of large sources may need more.
+    #[inline]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn connect(&self) -> io::Result<TcpStream> {
+        let mut last = io::Error::new(io::ErrorKind::NotFound, "address did not resolve");
+        for addr in self.addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, Duration::from_secs(2)) {
+                Ok(s) => {
+                    s.set_read_timeout(Some(self.timeout))?;
+                    s.set_write_timeout(Some(self.timeout))?;
+                    s.set_nodelay(true)?;
+                    info!(target: "assets::remote", "client.connected server={}", self.addr);
+                    return Ok(s);
+                }
+                Err(e) => last = e,
+            }
+        }
+        Err(last)
+    }
+
+    fn request(&self, req: &ImportRequest) -> io::Result<Option<Vec<u8>>> {
+        let mut conn = self.conn.lock();
+        let stream = match conn.as_mut() {
+            Some(s) => s,
+            None => conn.insert(self.connect()?),
+        };
+
+        let res = (|| {
+            write_json(stream, req)?;
+            match read_json::<ImportResponse>(stream)? {
+                ImportResponse::Ok => read_frame(stream).map(Some),
+                ImportResponse::Miss => Ok(None),
+                ImportResponse::Error { error } => {
+                    debug!(target: "assets::remote", "client.server_error path='{}' err='{error}'", req.path);
+                    Ok(None)
+                }
+            }
+        })();
+
+        if res.is_err() {
+            *conn = None;
+        }
+        res
+    }
+}
+
+impl ImportProvider for RemoteImportSource {
+    fn fetch(
+        &self,
+        key: &CacheKey,
+        asset: &AssetKey,
+        sources: &[Arc<dyn AssetSource>],
+    ) -> Option<AssetBlob> {
+        {
+            let mut until = self.offline_until.lock();
+            match *until {
+                Some(t) if Instant::now() < t => return None,
+                Some(_) => *until = None,
+                None => {}
+            }
+        }
+
+        let req = ImportRequest {
+            v: PROTOCOL_VERSION,
+            key: key.to_hex(),
+            path: asset.logical_path.to_string_lossy().into_owned(),
+            settings_hash: asset.settings_hash,
+        };
+
+        let entry = match self.request(&req) {
+            Ok(Some(entry)) => entry,
+            Ok(None) => {
+                debug!(target: "assets::remote", "client.miss path='{}'", req.path);
+                return None;
+            }
+            Err(e) => {
+                warn!(
+                    target: "assets::remote",
+                    "client.unreachable server={} err='{e}'; importing locally for {}s",
+                    self.addr,
+                    RETRY_AFTER.as_secs()
+                );
+                *self.offline_until.lock() = Some(Instant::now() + RETRY_AFTER);
+                return None;
+            }
+        };
+
+        match decode_entry_checked(&entry, sources) {
+            Ok(blob) => {
+                debug!(
+                    target: "assets::remote",
+                    "client.hit path='{}' bytes={}",
+                    req.path,
+                    entry.len()
+                );
+                Some(blob)
+            }
+            Err(EntryError::Corrupt) => {
+                warn!(target: "assets::remote", "client.corrupt path='{}'", req.path);
+                None
+            }
+            Err(EntryError::StaleDependency(dep)) => {
+                debug!(
+                    target: "assets::remote",
+                    "client.stale path='{}' dependency='{dep}'",
+                    req.path
+                );
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::store::BlobImporterDispatch;
+use crate::types::{Asset, AssetBlob, AssetError, AssetKey, ImporterPriority};
+
+use std::sync::Arc;
+
+pub const SHADER_TYPE_ID: &str = "kalitech.asset.shader";
+pub const SHADER_SPIRV_FORMAT: &str = "ne.shader.spirv.v1";
+pub const SHADER_SPIRV_EXTENSION: &str = "spv";
+pub const SHADER_SPIRV_VERSION: &str = "spirv.v1";
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+/// Precompiled SPIR-V module.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderAsset {
+    pub spirv: Vec<u32>,
+}
+
+impl Asset for ShaderAsset {
+    #[inline]
+    fn type_name() -> &'static str {
+        "ShaderAsset"
+    }
+}
+
+impl ShaderAsset {
+    /// Parses little-endian SPIR-V words and checks the magic number.
+    pub fn from_spirv_bytes(bytes: &[u8]) -> Result<Self, AssetError> {
+        if bytes.len() < 20 || bytes.len() % 4 != 0 {
+            return Err(AssetError::new(format!(
+                "shader: spirv size {} is not a valid module",
+                bytes.len()
+            )));
+        }
+
+        let spirv: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+
+        if spirv[0] != SPIRV_MAGIC {
+            return Err(AssetError::new("shader: bad spirv magic"));
+        }
+        Ok(Self { spirv })
+    }
+
+    #[inline]
+    pub fn from_blob(blob: &AssetBlob) -> Result<Self, AssetError> {
+        Self::from_spirv_bytes(&blob.payload)
+    }
+}
+
+/// In-process importer for precompiled `.spv` shaders. The payload is passed through after
+/// validation.
+#[derive(Debug, Default)]
+pub struct SpirvShaderImporter;
+
+impl BlobImporterDispatch for SpirvShaderImporter {
+    fn import_blob(&self, bytes: &[u8], key: &AssetKey) -> Result<AssetBlob, AssetError> {
+        let shader = ShaderAsset::from_spirv_bytes(bytes)?;
+
+        log::info!(
+            target: "assets",
+            "shader.import path='{}' words={}",
+            key.logical_path.display(),
+            shader.spirv.len()
+        );
+
+        Ok(AssetBlob {
+            type_id: Arc::from(SHADER_TYPE_ID),
+            format: Arc::from(SHADER_SPIRV_FORMAT),
+            payload: bytes.to_vec(),
+            meta_json: Arc::from("{\"schema\":\"kalitech.shader.meta.v1\",\"container\":\"spirv\"}"),
+            dependencies: Vec::new(),
+        })
+    }
+
+    #[inline]
+    fn output_type_id(&self) -> Arc<str> {
+        Arc::from(SHADER_TYPE_ID)
+    }
+
+    #[inline]
+    fn extensions(&self) -> Vec<String> {
+        vec![SHADER_SPIRV_EXTENSION.to_string()]
+    }
+
+    #[inline]
+    fn priority(&self) -> ImporterPriority {
+        ImporterPriority::new(100)
+    }
+
+    #[inline]
+    fn stable_id(&self) -> Arc<str> {
+        Arc::from("spirv_importer@newengine-assets")
+    }
+
+    #[inline]
+    fn version(&self) -> Arc<str> {
+        Arc::from(SHADER_SPIRV_VERSION)
+    }
+}
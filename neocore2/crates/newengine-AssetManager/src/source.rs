@@ -1,9 +1,43 @@
+use crate::path::fold_case;
+use crate::stream::AssetReadStream;
 use crate::types::AssetError;
-use std::path::{Path, PathBuf};
+use std::fs::File;
+use std::path::{Component, Path, PathBuf};
 
 pub trait AssetSource: Send + Sync + 'static {
     fn exists(&self, logical_path: &Path) -> bool;
     fn read(&self, logical_path: &Path) -> Result<Vec<u8>, AssetError>;
+
+    /// Entry spelled like `logical_path` ignoring case, for `PathCase::Insensitive` lookups.
+    /// Errors if several entries match. Sources that cannot enumerate entries return `None`.
+    fn find_ignore_case(&self, _logical_path: &Path) -> Result<Option<PathBuf>, AssetError> {
+        Ok(None)
+    }
+
+    /// Opens the entry for chunked reads, so large files never sit in memory whole. The
+    /// default reads the entry up front; sources that can read incrementally override it.
+    fn open_stream(&self, logical_path: &Path) -> Result<AssetReadStream, AssetError> {
+        let bytes = self.read(logical_path)?;
+        Ok(AssetReadStream::from_bytes(
+            bytes,
+            logical_path.to_string_lossy(),
+        ))
+    }
+
+    /// Every entry of the source as logical paths, for asset browsing. Sources that cannot
+    /// enumerate entries return none; their assets still show up in queries once loaded.
+    fn list(&self) -> Result<Vec<PathBuf>, AssetError> {
+        Ok(Vec::new())
+    }
+
+    /// Creates or replaces an entry (editor-written `.meta` sidecars). Read-only sources
+    /// refuse.
+    fn write(&self, logical_path: &Path, _bytes: &[u8]) -> Result<(), AssetError> {
+        Err(AssetError::new(format!(
+            "source is read-only: '{}'",
+            logical_path.to_string_lossy()
+        )))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -41,4 +75,107 @@ impl AssetSource for FileSystemSource {
             ))
         })
     }
+
+    fn open_stream(&self, logical_path: &Path) -> Result<AssetReadStream, AssetError> {
+        let p = self.resolve(logical_path);
+        let open_err = |e: std::io::Error| {
+            AssetError::new(format!(
+                "FileSystemSource: failed to open '{}': {}",
+                p.to_string_lossy(),
+                e
+            ))
+        };
+        let file = File::open(&p).map_err(open_err)?;
+        let len = file.metadata().map_err(open_err)?.len();
+        Ok(AssetReadStream::new(file, Some(len), p.to_string_lossy()))
+    }
+
+    fn list(&self) -> Result<Vec<PathBuf>, AssetError> {
+        let mut out = Vec::new();
+        let mut stack = vec![self.root.clone()];
+        while let Some(dir) = stack.pop() {
+            let rd = std::fs::read_dir(&dir).map_err(|e| {
+                AssetError::new(format!(
+                    "FileSystemSource: failed to list '{}': {}",
+                    dir.to_string_lossy(),
+                    e
+                ))
+            })?;
+            for de in rd.flatten() {
+                let p = de.path();
+                if p.is_dir() {
+                    stack.push(p);
+                } else if let Ok(rel) = p.strip_prefix(&self.root) {
+                    out.push(rel.to_path_buf());
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn write(&self, logical_path: &Path, bytes: &[u8]) -> Result<(), AssetError> {
+        let p = self.resolve(logical_path);
+        let write_err = |e: std::io::Error| {
+            AssetError::new(format!(
+                "FileSystemSource: failed to write '{}': {}",
+                p.to_string_lossy(),
+                e
+            ))
+        };
+        if let Some(parent) = p.parent() {
+            std::fs::create_dir_all(parent).map_err(write_err)?;
+        }
+        std::fs::write(&p, bytes).map_err(write_err)
+    }
+
+    /// Walks the path one directory at a time, listing a directory only where the exact
+    /// component is missing.
+    fn find_ignore_case(&self, logical_path: &Path) -> Result<Option<PathBuf>, AssetError> {
+        let mut dir = self.root.clone();
+        let mut out = PathBuf::new();
+
+        for comp in logical_path.components() {
+            let Component::Normal(name) = comp else {
+                return Ok(None);
+            };
+
+            let exact = dir.join(name);
+            if exact.exists() {
+                dir = exact;
+                out.push(name);
+                continue;
+            }
+
+            let want = fold_case(&name.to_string_lossy());
+            let Ok(rd) = std::fs::read_dir(&dir) else {
+                return Ok(None);
+            };
+            let mut found: Vec<_> = rd
+                .flatten()
+                .map(|e| e.file_name())
+                .filter(|n| fold_case(&n.to_string_lossy()) == want)
+                .collect();
+
+            match found.len() {
+                0 => return Ok(None),
+                1 => {
+                    let name = found.remove(0);
+                    dir.push(&name);
+                    out.push(&name);
+                }
+                _ => {
+                    found.sort();
+                    let names: Vec<_> = found.iter().map(|n| n.to_string_lossy()).collect();
+                    return Err(AssetError::new(format!(
+                        "FileSystemSource: ambiguous path '{}' in '{}': {}",
+                        logical_path.to_string_lossy(),
+                        dir.to_string_lossy(),
+                        names.join(", ")
+                    )));
+                }
+            }
+        }
+
+        Ok(dir.is_file().then_some(out))
+    }
 }
\ No newline at end of file
@@ -1,16 +1,28 @@
+use crate::budget::{AssetCategory, AssetHandle, CategoryUsage, MemoryBudget, ResidentSet};
+use crate::cache::{encode_entry, AssetCache, CacheKey};
+use crate::deps::{DependencyGraph, DependencyGraphExport, DependencyGraphNode};
 use crate::events::AssetEvent;
 use crate::id::AssetId;
+use crate::meta::{self, ImportSettings};
+use crate::path::PathCase;
+use crate::query::{AssetQuery, AssetQueryEntry, AssetQueryResult};
 use crate::source::AssetSource;
+use crate::stream::AssetReadStream;
 use crate::types::{AssetBlob, AssetError, AssetKey, AssetState, ImporterPriority};
+use crate::validate::{AssetFacts, ValidationIssue, ValidationRules, ValidationSeverity};
+use crate::workers::ImportWorkers;
 use log::{debug, info, warn};
 use parking_lot::Mutex;
-use std::collections::{HashMap, VecDeque};
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 
 #[derive(Debug, Clone, Copy)]
 pub struct PumpBudget {
+    /// Imports started per pump. With import workers it also caps the imports in flight,
+    /// bounding the I/O and CPU they take while the frame runs.
     pub steps: u32,
 }
 
@@ -37,14 +49,114 @@ pub trait BlobImporterDispatch: Send + Sync + 'static {
 
     /// Stable identifier for tie-break and diagnostics (e.g. "dds_importer@plugin:render").
     fn stable_id(&self) -> Arc<str>;
+
+    /// Importer output version. Part of the persistent cache key: bump it whenever the
+    /// produced blob changes for the same input.
+    fn version(&self) -> Arc<str> {
+        Arc::from("0")
+    }
+
+    /// Content type of source files with extension `ext` (lowercase, no dot), if the
+    /// importer knows it. Used when serving raw sources to web views.
+    fn mime_type(&self, _ext: &str) -> Option<Arc<str>> {
+        None
+    }
+
+    /// Whether the store should hand this importer a stream (`import_stream`) instead of
+    /// the whole source (`import_blob`). Set by importers of large media (audio, video).
+    fn streams_source(&self) -> bool {
+        false
+    }
+
+    /// Imports with the options from the asset's `.meta` sidecar; only called when one
+    /// exists. The default ignores the settings.
+    fn import_blob_with_settings(
+        &self,
+        bytes: &[u8],
+        key: &AssetKey,
+        _settings: &ImportSettings,
+    ) -> Result<AssetBlob, AssetError> {
+        self.import_blob(bytes, key)
+    }
+
+    /// Imports from a chunked reader over the source. Only called when `streams_source`
+    /// is set; the default reads the whole stream and imports it like any other source.
+    fn import_stream(
+        &self,
+        stream: &mut AssetReadStream,
+        key: &AssetKey,
+        settings: Option<&ImportSettings>,
+    ) -> Result<AssetBlob, AssetError> {
+        let bytes = stream.read_all()?;
+        match settings {
+            Some(s) => self.import_blob_with_settings(&bytes, key, s),
+            None => self.import_blob(&bytes, key),
+        }
+    }
+}
+
+/// Cancellation signal attached to a queued import (core implements it for `CancelToken`).
+pub trait LoadCancel: Send + Sync + 'static {
+    fn is_cancelled(&self) -> bool;
+}
+
+/// Supplies imported blobs by content hash before the local importer runs; see
+/// [`RemoteImportSource`](crate::remote::RemoteImportSource).
+pub trait ImportProvider: Send + Sync + 'static {
+    /// Returns the blob imported from the bytes `key` was computed from, or `None` to import
+    /// locally. `sources` lets the provider re-check dependency hashes.
+    fn fetch(
+        &self,
+        key: &CacheKey,
+        asset: &AssetKey,
+        sources: &[Arc<dyn AssetSource>],
+    ) -> Option<AssetBlob>;
 }
 
+/// A failed import, as reported to the failure observer.
+#[derive(Debug, Clone)]
+pub struct AssetFailure {
+    pub id: AssetId,
+    pub type_id: Arc<str>,
+    /// Logical path the asset was requested under, when still known.
+    pub logical_path: Option<String>,
+    pub error: Arc<str>,
+}
+
+/// Called on the pumping thread after an import fails, with the store unlocked.
+pub type AssetFailureObserver = Arc<dyn Fn(&AssetFailure) + Send + Sync>;
+
+/// Called for every [`AssetEvent`] as it is queued, with the logical path when known. Runs with
+/// the store locked: it must not call back into the store.
+pub type AssetEventObserver = Arc<dyn Fn(&AssetEvent, Option<&Path>) + Send + Sync>;
+
 struct PendingRequest {
     id: AssetId,
     key: AssetKey,
     type_id: Arc<str>,
     importer: Arc<dyn BlobImporterDispatch>,
     importer_id: Arc<str>,
+    /// Tokens of every requester; `None` once any requester asked without one.
+    cancel: Option<Vec<Arc<dyn LoadCancel>>>,
+}
+
+impl PendingRequest {
+    /// A shared request is dropped only after every requester gave up.
+    #[inline]
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|list| !list.is_empty() && list.iter().all(|c| c.is_cancelled()))
+    }
+
+    #[inline]
+    fn join(&mut self, cancel: Option<Arc<dyn LoadCancel>>) {
+        match (self.cancel.as_mut(), cancel) {
+            (Some(list), Some(c)) => list.push(c),
+            (_, None) => self.cancel = None,
+            (None, Some(_)) => {}
+        }
+    }
 }
 
 impl std::fmt::Debug for PendingRequest {
@@ -74,6 +186,8 @@ struct AssetDiagnostics {
     bytes_read: u64,
     io_time_us: u64,
     import_time_us: u64,
+    cache_hits: u64,
+    cache_misses: u64,
 }
 
 impl AssetDiagnostics {
@@ -86,6 +200,15 @@ impl AssetDiagnostics {
         self.io_time_us = 0;
         self.import_time_us = 0;
     }
+
+    #[inline]
+    fn record_cache(&mut self, hit: bool) {
+        if hit {
+            self.cache_hits += 1;
+        } else {
+            self.cache_misses += 1;
+        }
+    }
 }
 
 #[derive(Default)]
@@ -94,14 +217,107 @@ struct StoreInner {
     importers_by_ext: HashMap<String, Vec<Arc<dyn BlobImporterDispatch>>>,
     state: HashMap<AssetId, AssetState>,
     blobs: HashMap<AssetId, Arc<AssetBlob>>,
+    keys: HashMap<AssetId, AssetKey>,
+    deps: DependencyGraph,
+    dirty: HashSet<AssetId>,
     queue: VecDeque<PendingRequest>,
     events: VecDeque<AssetEvent>,
     diag: AssetDiagnostics,
+    cache: Option<Arc<AssetCache>>,
+    import_provider: Option<Arc<dyn ImportProvider>>,
+    failure_observer: Option<AssetFailureObserver>,
+    event_observer: Option<AssetEventObserver>,
+    path_case: PathCase,
+    validation: Option<Arc<ValidationRules>>,
+    /// Rule violations of the last import, per asset; assets without issues are absent.
+    issues: HashMap<AssetId, Vec<ValidationIssue>>,
+    /// Imports handed to the workers and not finished by `pump` yet.
+    in_flight: usize,
+    budget: MemoryBudget,
+    /// Accounting of `blobs` for the memory budget.
+    resident: ResidentSet,
+    /// Bumped whenever a query result may change; see `AssetStore::query_revision`.
+    query_revision: u64,
+}
+
+impl StoreInner {
+    /// Queues `ev` for `drain_events` and reports it to the event observer.
+    fn push_event(&mut self, ev: AssetEvent) {
+        if let Some(o) = &self.event_observer {
+            let path = self.keys.get(&ev.id()).map(|k| k.logical_path.as_path());
+            o(&ev, path);
+        }
+        self.query_revision += 1;
+        self.events.push_back(ev);
+    }
+
+    #[inline]
+    fn insert_blob(&mut self, id: AssetId, blob: Arc<AssetBlob>) {
+        self.resident.insert(id, &blob);
+        self.blobs.insert(id, blob);
+    }
+
+    #[inline]
+    fn remove_blob(&mut self, id: AssetId) {
+        self.resident.remove(id);
+        self.blobs.remove(&id);
+    }
+
+    /// Evicts least recently used `Ready` assets of `category` until it fits its budget.
+    /// Skips `keep` and blobs referenced outside the store. Returns the evicted assets.
+    fn enforce_budget(&mut self, category: AssetCategory, keep: Option<AssetId>) -> Vec<AssetId> {
+        let Some(limit) = self.budget.limit(category) else {
+            return Vec::new();
+        };
+        let mut resident = self.resident.resident_bytes(category);
+        if resident <= limit {
+            return Vec::new();
+        }
+
+        let mut evicted = Vec::new();
+        for (id, bytes) in self.resident.lru_order(category) {
+            if resident <= limit {
+                break;
+            }
+            if Some(id) == keep {
+                continue;
+            }
+            let pinned = self
+                .blobs
+                .get(&id)
+                .is_some_and(|b| Arc::strong_count(b) > 1);
+            if pinned || !matches!(self.state.get(&id), Some(AssetState::Ready)) {
+                continue;
+            }
+
+            self.remove_blob(id);
+            self.state.insert(id, AssetState::Unloaded);
+            self.resident.record_eviction(category);
+            self.push_event(AssetEvent::Evicted { id });
+            resident -= bytes;
+            evicted.push(id);
+        }
+
+        if resident > limit {
+            debug!(
+                target: "assets::budget",
+                "budget.over category={} resident={} limit={}",
+                category.as_str(),
+                resident,
+                limit
+            );
+        }
+        evicted
+    }
 }
 
 #[derive(Default)]
 pub struct AssetStore {
     inner: Mutex<StoreInner>,
+    /// `None` imports on the pumping thread.
+    workers: Mutex<Option<ImportWorkers>>,
+    /// Runs the workers finished, waiting for the next `pump`.
+    completed: Arc<Mutex<VecDeque<ImportOutcome>>>,
 }
 
 impl AssetStore {
@@ -110,10 +326,130 @@ impl AssetStore {
         Self::default()
     }
 
+    /// Enables (or disables with `None`) the persistent import cache.
+    pub fn set_cache(&self, cache: Option<AssetCache>) {
+        let mut g = self.inner.lock();
+        match &cache {
+            Some(c) => info!(target: "assets::cache", "cache.enabled dir='{}'", c.dir().display()),
+            None => info!(target: "assets::cache", "cache.disabled"),
+        }
+        g.cache = cache.map(Arc::new);
+    }
+
+    /// Installs (or clears with `None`) a provider consulted on local cache misses.
+    pub fn set_import_provider(&self, provider: Option<Arc<dyn ImportProvider>>) {
+        info!(
+            target: "assets::remote",
+            "import_provider.{}",
+            if provider.is_some() { "enabled" } else { "disabled" }
+        );
+        self.inner.lock().import_provider = provider;
+    }
+
+    /// Installs (or clears with `None`) a callback for import failures. Unlike
+    /// `drain_events`, this does not consume anything other listeners rely on.
+    pub fn set_failure_observer(&self, observer: Option<AssetFailureObserver>) {
+        self.inner.lock().failure_observer = observer;
+    }
+
+    /// Installs (or clears with `None`) a callback that sees every asset event without
+    /// consuming it (tracing, diagnostics).
+    pub fn set_event_observer(&self, observer: Option<AssetEventObserver>) {
+        self.inner.lock().event_observer = observer;
+    }
+
+    /// How logical paths are matched against sources. `PathCase::Insensitive` lets content
+    /// authored on Windows or macOS load on Linux when references differ in case.
+    pub fn set_path_case(&self, case: PathCase) {
+        info!(target: "assets", "path_case={:?}", case);
+        self.inner.lock().path_case = case;
+    }
+
+    #[inline]
+    pub fn path_case(&self) -> PathCase {
+        self.inner.lock().path_case
+    }
+
+    /// Installs (or clears with `None`) the rules checked after every import. Assets already
+    /// loaded are re-checked against the new rules right away.
+    pub fn set_validation_rules(&self, rules: Option<ValidationRules>) {
+        let mut g = self.inner.lock();
+        info!(
+            target: "assets::validate",
+            "validation.rules count={}",
+            rules.as_ref().map_or(0, |r| r.rules.len())
+        );
+        g.validation = rules.filter(|r| !r.is_empty()).map(Arc::new);
+        g.issues.clear();
+
+        let Some(rules) = g.validation.clone() else {
+            return;
+        };
+        let checked: Vec<(AssetId, Vec<ValidationIssue>)> = g
+            .blobs
+            .iter()
+            .filter_map(|(id, blob)| {
+                let path = g.keys.get(id)?.logical_path.to_string_lossy();
+                let issues = rules.evaluate(&path, &AssetFacts::from_blob(blob));
+                (!issues.is_empty()).then_some((*id, issues))
+            })
+            .collect();
+        g.issues.extend(checked);
+    }
+
+    /// Reads the rules asset at `logical_path` through the sources and installs it. A missing
+    /// asset clears the rules and returns `Ok(false)`.
+    pub fn load_validation_rules(&self, logical_path: &Path) -> Result<bool, AssetError> {
+        let bytes = match self.read_source(logical_path) {
+            Ok(b) => b,
+            Err(_) => {
+                self.set_validation_rules(None);
+                return Ok(false);
+            }
+        };
+        let text = std::str::from_utf8(&bytes)
+            .map_err(|e| AssetError::new(format!("validation rules: {e}")))?;
+        self.set_validation_rules(Some(ValidationRules::from_json(text)?));
+        Ok(true)
+    }
+
+    #[inline]
+    pub fn validation_rules(&self) -> Option<Arc<ValidationRules>> {
+        self.inner.lock().validation.clone()
+    }
+
+    /// Rule violations found when `id` was last imported.
+    #[inline]
+    pub fn validation_issues(&self, id: AssetId) -> Vec<ValidationIssue> {
+        let g = self.inner.lock();
+        g.issues.get(&id).cloned().unwrap_or_default()
+    }
+
+    /// Every current rule violation, errors first, then by path.
+    pub fn all_validation_issues(&self) -> Vec<ValidationIssue> {
+        let mut out: Vec<ValidationIssue> = {
+            let g = self.inner.lock();
+            g.issues.values().flatten().cloned().collect()
+        };
+        out.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.rule.cmp(&b.rule))
+        });
+        out
+    }
+
+    #[inline]
+    pub fn cache(&self) -> Option<Arc<AssetCache>> {
+        self.inner.lock().cache.clone()
+    }
+
     #[inline]
     pub fn add_source(&self, source: Arc<dyn AssetSource>) {
         let mut g = self.inner.lock();
         g.sources.push(source);
+        g.query_revision += 1;
     }
 
     pub fn add_importer(&self, importer: Arc<dyn BlobImporterDispatch>) {
@@ -179,19 +515,79 @@ impl AssetStore {
         g.state.get(&id).cloned().unwrap_or(AssetState::Unloaded)
     }
 
+    /// The blob of a ready asset. Counts as a use for budget eviction, and the asset stays
+    /// resident while the returned `Arc` is alive.
     #[inline]
     pub fn get_blob(&self, id: AssetId) -> Option<Arc<AssetBlob>> {
-        let g = self.inner.lock();
+        let mut g = self.inner.lock();
+        g.resident.touch(id);
         g.blobs.get(&id).cloned()
     }
 
+    /// Pins a ready asset against budget eviction for as long as the handle lives.
+    #[inline]
+    pub fn acquire(&self, id: AssetId) -> Option<AssetHandle> {
+        self.get_blob(id).map(|blob| AssetHandle::new(id, blob))
+    }
+
+    /// Replaces the per-category memory limits and evicts down to them right away.
+    pub fn set_memory_budget(&self, budget: MemoryBudget) {
+        let evicted = {
+            let mut g = self.inner.lock();
+            g.budget = budget;
+            AssetCategory::ALL
+                .iter()
+                .map(|&c| g.enforce_budget(c, None).len())
+                .sum::<usize>()
+        };
+        info!(
+            target: "assets::budget",
+            "budget.set textures={:?} audio={:?} blobs={:?} evicted={}",
+            budget.textures,
+            budget.audio,
+            budget.blobs,
+            evicted
+        );
+    }
+
+    #[inline]
+    pub fn memory_budget(&self) -> MemoryBudget {
+        self.inner.lock().budget
+    }
+
+    /// Resident bytes, asset count, limit and evictions per category.
+    pub fn memory_usage(&self) -> Vec<CategoryUsage> {
+        let g = self.inner.lock();
+        g.resident.usage(&g.budget)
+    }
+
     #[inline]
     pub fn drain_events(&self) -> Vec<AssetEvent> {
         let mut g = self.inner.lock();
         g.events.drain(..).collect()
     }
 
+    #[inline]
     pub fn load(&self, key: AssetKey) -> Result<AssetId, AssetError> {
+        self.load_inner(key, None)
+    }
+
+    /// Like `load`, but the queued import is dropped (`AssetEvent::Cancelled`) if `cancel`
+    /// fires before it runs. Has no effect on assets that are already ready.
+    #[inline]
+    pub fn load_cancellable(
+        &self,
+        key: AssetKey,
+        cancel: Arc<dyn LoadCancel>,
+    ) -> Result<AssetId, AssetError> {
+        self.load_inner(key, Some(cancel))
+    }
+
+    fn load_inner(
+        &self,
+        key: AssetKey,
+        cancel: Option<Arc<dyn LoadCancel>>,
+    ) -> Result<AssetId, AssetError> {
         let id = key.id();
 
         info!(
@@ -203,42 +599,25 @@ impl AssetStore {
 
         let mut g = self.inner.lock();
         match g.state.get(&id) {
-            Some(AssetState::Ready) | Some(AssetState::Loading) | Some(AssetState::Failed(_)) => {
-                return Ok(id)
+            Some(AssetState::Ready) | Some(AssetState::Failed(_)) => return Ok(id),
+            Some(AssetState::Loading) => {
+                if let Some(req) = g.queue.iter_mut().find(|r| r.id == id) {
+                    req.join(cancel);
+                }
+                return Ok(id);
             }
             _ => {}
         }
 
-        let ext = extension_ascii_lower(&key.logical_path)
-            .ok_or_else(|| AssetError::new("AssetStore: asset path has no extension"))?;
-
-        let Some(list) = g.importers_by_ext.get(&ext) else {
+        let (ext, importer) = select_importer(&g, &key).inspect_err(|e| {
             warn!(
                 target: "assets",
-                "asset.load rejected id={:032x} path='{}' reason='no_importer' ext='{}'",
+                "asset.load rejected id={:032x} path='{}' reason='{}'",
                 id.to_u128(),
                 key.logical_path.display(),
-                ext
+                e
             );
-            return Err(AssetError::new(format!(
-                "AssetStore: no importer registered for extension '.{}'",
-                ext
-            )));
-        };
-
-        let Some(importer) = list.first().cloned() else {
-            warn!(
-                target: "assets",
-                "asset.load rejected id={:032x} path='{}' reason='no_importer' ext='{}'",
-                id.to_u128(),
-                key.logical_path.display(),
-                ext
-            );
-            return Err(AssetError::new(format!(
-                "AssetStore: no importer registered for extension '.{}'",
-                ext
-            )));
-        };
+        })?;
 
         let type_id = importer.output_type_id();
 
@@ -251,6 +630,8 @@ impl AssetStore {
         );
 
         g.state.insert(id, AssetState::Loading);
+        g.query_revision += 1;
+        g.keys.insert(id, key.clone());
         let importer_id = importer.stable_id();
         g.queue.push_back(PendingRequest {
             id,
@@ -258,11 +639,17 @@ impl AssetStore {
             type_id,
             importer,
             importer_id,
+            cancel: cancel.map(|c| vec![c]),
         });
 
         Ok(id)
     }
 
+    /// Runs queued imports within `budget`.
+    ///
+    /// With import workers (`set_import_workers`) the imports run on the workers and this
+    /// only starts them and finishes the ones that completed since the last call; events,
+    /// state changes and dependency loads always happen on the pumping thread.
     pub fn pump(&self, budget: PumpBudget) {
         {
             let mut g = self.inner.lock();
@@ -270,47 +657,57 @@ impl AssetStore {
         }
 
         let pump_t0 = Instant::now();
+        let workers = self.workers.lock().as_ref().map(ImportWorkers::queue);
+
+        let done: Vec<ImportOutcome> = self.completed.lock().drain(..).collect();
+        for out in done {
+            self.inner.lock().in_flight -= 1;
+            self.finish_import(out);
+        }
+
         let mut steps_left = budget.steps;
 
         while steps_left > 0 {
             steps_left -= 1;
 
-            let req = {
+            let (req, env) = {
                 let mut g = self.inner.lock();
-                g.queue.pop_front()
+                // Imports in flight count against the budget until they are finished here.
+                if workers.is_some() && g.in_flight >= budget.steps as usize {
+                    break;
+                }
+                (g.queue.pop_front(), ImportEnv::snapshot(&g))
             };
 
             let Some(req) = req else { break; };
 
-            {
-                let mut g = self.inner.lock();
-                g.diag.pump_total += 1;
+            // Cancelled requests are dropped without spending budget.
+            if req.is_cancelled() {
+                steps_left += 1;
+                self.drop_cancelled(&req);
+                continue;
             }
 
-            if let Err(err) = self.process_one(req) {
-                {
-                    let mut g = self.inner.lock();
-                    g.diag.pump_failed += 1;
-                    g.state.insert(err.id, AssetState::Failed(err.error.clone()));
-                    g.events.push_back(AssetEvent::Failed {
-                        id: err.id,
-                        type_id: err.type_id.clone(),
-                        error: err.error.clone(),
-                    });
+            match &workers {
+                Some(queue) => {
+                    self.inner.lock().in_flight += 1;
+                    let completed = self.completed.clone();
+                    queue.push(Box::new(move || {
+                        // A panicking importer fails its asset instead of taking the worker.
+                        let run = panic::catch_unwind(AssertUnwindSafe(|| env.run(&req)))
+                            .unwrap_or_else(|_| ImportRun::failed("importer panicked"));
+                        completed.lock().push_back(ImportOutcome { req, run });
+                    }));
+                }
+                None => {
+                    let run = env.run(&req);
+                    self.finish_import(ImportOutcome { req, run });
                 }
-
-                warn!(
-                    target: "assets::events",
-                    "asset.failed id={:032x} type='{}' error='{}'",
-                    err.id.to_u128(),
-                    err.type_id,
-                    err.error
-                );
             }
         }
 
         let dt = pump_t0.elapsed();
-        let (total, ok, fail, bytes, io_us, imp_us) = {
+        let (total, ok, fail, bytes, io_us, imp_us, in_flight) = {
             let g = self.inner.lock();
             (
                 g.diag.pump_total,
@@ -319,107 +716,527 @@ impl AssetStore {
                 g.diag.bytes_read,
                 g.diag.io_time_us,
                 g.diag.import_time_us,
+                g.in_flight,
             )
         };
 
         if total > 0 {
             info!(
                 target: "assets",
-                "pump.summary total={} ok={} fail={} bytes={} io_us={} import_us={} frame_ms={:.3}",
+                "pump.summary total={} ok={} fail={} bytes={} io_us={} import_us={} in_flight={} frame_ms={:.3}",
                 total,
                 ok,
                 fail,
                 bytes,
                 io_us,
                 imp_us,
+                in_flight,
                 dt.as_secs_f64() * 1000.0
             );
         }
     }
 
-    fn process_one(&self, req: PendingRequest) -> Result<(), ProcessError> {
-        let sources = {
-            let g = self.inner.lock();
-            g.sources.clone()
+    /// Runs imports on `count` worker threads; `0` runs them inside `pump` on the pumping
+    /// thread. Replacing the workers waits for the imports they already started.
+    pub fn set_import_workers(&self, count: usize) {
+        let workers = match count {
+            0 => None,
+            n => match ImportWorkers::new(n) {
+                Ok(w) => Some(w),
+                Err(e) => {
+                    warn!(
+                        target: "assets",
+                        "import_workers.spawn failed count={} err='{}'; importing on the pumping thread",
+                        n,
+                        e
+                    );
+                    None
+                }
+            },
         };
+        info!(
+            target: "assets",
+            "import_workers count={}",
+            workers.as_ref().map_or(0, ImportWorkers::len)
+        );
+        let old = std::mem::replace(&mut *self.workers.lock(), workers);
+        drop(old);
+    }
+
+    fn drop_cancelled(&self, req: &PendingRequest) {
+        {
+            let mut g = self.inner.lock();
+            if matches!(g.state.get(&req.id), Some(AssetState::Loading)) {
+                g.state.insert(req.id, AssetState::Unloaded);
+            }
+            g.push_event(AssetEvent::Cancelled { id: req.id });
+        }
+
+        info!(
+            target: "assets::events",
+            "asset.cancelled id={:032x} path='{}'",
+            req.id.to_u128(),
+            req.key.logical_path.display()
+        );
+    }
 
-        let importer = req.importer;
+    /// Applies a finished import to the store: diagnostics, state, events and dependencies.
+    fn finish_import(&self, out: ImportOutcome) {
+        let ImportOutcome { req, run } = out;
 
-        let io_t0 = Instant::now();
-        let bytes = read_from_any_source_list(&sources, &req.key.logical_path).map_err(|e| {
-            ProcessError {
+        {
+            let mut g = self.inner.lock();
+            g.diag.bytes_read += run.bytes_read;
+            g.diag.io_time_us += run.io_us;
+            g.diag.import_time_us += run.import_us;
+            if let Some(hit) = run.cache_hit {
+                g.diag.record_cache(hit);
+            }
+
+            // Unloaded while a worker imported it; a result now would resurrect it.
+            if !matches!(g.state.get(&req.id), Some(AssetState::Loading)) {
+                debug!(
+                    target: "assets",
+                    "asset.discarded id={:032x} path='{}'",
+                    req.id.to_u128(),
+                    req.key.logical_path.display()
+                );
+                return;
+            }
+            g.diag.pump_total += 1;
+        }
+
+        let (blob, issues, rejected) = match run.result {
+            ImportResult::Cancelled => {
+                self.drop_cancelled(&req);
+                return;
+            }
+            ImportResult::Failed(error) => {
+                self.report_failure(ProcessError {
+                    id: req.id,
+                    type_id: req.type_id.clone(),
+                    error,
+                });
+                return;
+            }
+            ImportResult::Imported {
+                blob,
+                issues,
+                rejected,
+            } => (blob, issues, rejected),
+        };
+
+        {
+            let mut g = self.inner.lock();
+            if issues.is_empty() {
+                g.issues.remove(&req.id);
+            } else {
+                g.issues.insert(req.id, issues);
+            }
+        }
+        if let Some(error) = rejected {
+            self.report_failure(ProcessError {
                 id: req.id,
                 type_id: req.type_id.clone(),
-                error: Arc::from(e.msg().to_string()),
+                error: Arc::from(error),
+            });
+            return;
+        }
+
+        let format = blob.format.clone();
+        let dep_keys: Vec<AssetKey> = blob
+            .dependencies
+            .iter()
+            .map(|d| AssetKey::new(d.logical_path.clone(), d.settings_hash))
+            .collect();
+        let blob = Arc::new(blob);
+
+        let (stale_dependents, evicted) = {
+            let mut g = self.inner.lock();
+            g.diag.pump_success += 1;
+            g.deps
+                .set_dependencies(req.id, dep_keys.iter().map(|k| k.id()));
+            g.dirty.remove(&req.id);
+            let category = AssetCategory::of(&blob.type_id);
+            g.insert_blob(req.id, blob);
+            g.state.insert(req.id, AssetState::Ready);
+            g.push_event(AssetEvent::Ready {
+                id: req.id,
+                type_id: req.type_id.clone(),
+                format: format.clone(),
+            });
+
+            let mut stale = Vec::new();
+            for d in g.deps.dependents(req.id) {
+                if g.dirty.contains(&d) {
+                    if let Some(k) = g.keys.get(&d) {
+                        stale.push(k.clone());
+                    }
+                }
             }
-        })?;
-        let io_dt = io_t0.elapsed();
+            let evicted = g.enforce_budget(category, Some(req.id));
+            (stale, evicted)
+        };
 
-        {
+        info!(
+            target: "assets::events",
+            "asset.ready id={:032x} type='{}' format='{}' path='{}' deps={}",
+            req.id.to_u128(),
+            req.type_id,
+            format,
+            req.key.logical_path.display(),
+            dep_keys.len()
+        );
+        for id in evicted {
+            info!(
+                target: "assets::budget",
+                "asset.evicted id={:032x} for={:032x}",
+                id.to_u128(),
+                req.id.to_u128()
+            );
+        }
+
+        // Dependencies are loaded on demand; failures surface through their own events.
+        for k in dep_keys {
+            if let Err(e) = self.load(k.clone()) {
+                warn!(
+                    target: "assets::deps",
+                    "deps.load failed owner={:032x} dep='{}' err='{}'",
+                    req.id.to_u128(),
+                    k.logical_path.display(),
+                    e
+                );
+            }
+        }
+
+        // Cascade: dependents invalidated by an earlier reload of this asset.
+        for k in stale_dependents {
+            info!(
+                target: "assets::deps",
+                "deps.cascade_reload dep={:032x} dependent='{}'",
+                req.id.to_u128(),
+                k.logical_path.display()
+            );
+            let _ = self.reload_key(k);
+        }
+    }
+
+    fn report_failure(&self, err: ProcessError) {
+        let notify = {
             let mut g = self.inner.lock();
-            g.diag.bytes_read += bytes.len() as u64;
-            g.diag.io_time_us += io_dt.as_micros() as u64;
+            g.diag.pump_failed += 1;
+            g.state.insert(err.id, AssetState::Failed(err.error.clone()));
+            g.push_event(AssetEvent::Failed {
+                id: err.id,
+                type_id: err.type_id.clone(),
+                error: err.error.clone(),
+            });
+            g.failure_observer.clone().map(|o| {
+                let logical_path = g
+                    .keys
+                    .get(&err.id)
+                    .map(|k| k.logical_path.to_string_lossy().into_owned());
+                (o, logical_path)
+            })
+        };
+
+        warn!(
+            target: "assets::events",
+            "asset.failed id={:032x} type='{}' error='{}'",
+            err.id.to_u128(),
+            err.type_id,
+            err.error
+        );
+
+        if let Some((observer, logical_path)) = notify {
+            observer(&AssetFailure {
+                id: err.id,
+                type_id: err.type_id,
+                logical_path,
+                error: err.error,
+            });
+        }
+    }
+}
+
+/// What an import needs from the store, copied out so it runs without the store lock.
+struct ImportEnv {
+    sources: Vec<Arc<dyn AssetSource>>,
+    cache: Option<Arc<AssetCache>>,
+    provider: Option<Arc<dyn ImportProvider>>,
+    case: PathCase,
+    rules: Option<Arc<ValidationRules>>,
+}
+
+impl ImportEnv {
+    fn snapshot(g: &StoreInner) -> Self {
+        Self {
+            sources: g.sources.clone(),
+            cache: g.cache.clone(),
+            provider: g.import_provider.clone(),
+            case: g.path_case,
+            rules: g.validation.clone(),
+        }
+    }
+
+    /// Reads, imports (or fetches from the cache) and validates `req`. Touches no store
+    /// state, so it may run on an import worker.
+    fn run(&self, req: &PendingRequest) -> ImportRun {
+        let importer = &req.importer;
+        // Streaming importers read the source themselves; only the cache key needs a pass
+        // over the bytes up front.
+        let streamed = importer.streams_source();
+
+        let io_t0 = Instant::now();
+        let path = &req.key.logical_path;
+        let bytes = if streamed {
+            Vec::new()
+        } else {
+            match read_from_any_source_list(&self.sources, path, self.case) {
+                Ok(b) => b,
+                Err(e) => return ImportRun::failed(e.msg()),
+            }
+        };
+        let settings = match read_settings(&self.sources, path, self.case) {
+            Ok(s) => s,
+            Err(e) => return ImportRun::failed(e.msg()),
+        };
+        let settings = settings.as_ref();
+        let wants_key = self.cache.is_some() || self.provider.is_some();
+        let (cache_key, hashed) = match (wants_key, streamed) {
+            (false, _) => (None, 0),
+            (true, false) => {
+                let version = importer.version();
+                let ck = CacheKey::compute(&bytes, &req.key, settings, &req.importer_id, &version);
+                (Some(ck), 0)
+            }
+            (true, true) => match self.stream_cache_key(req, settings) {
+                Ok((ck, n)) => (Some(ck), n),
+                Err(e) => return ImportRun::failed(e.msg()),
+            },
+        };
+        let io_dt = io_t0.elapsed();
+        let mut run = ImportRun {
+            result: ImportResult::Cancelled,
+            bytes_read: bytes.len() as u64 + hashed,
+            io_us: io_dt.as_micros() as u64,
+            import_us: 0,
+            cache_hit: None,
+        };
+
+        // Reads can be slow (archives, network mounts); skip the import if cancelled meanwhile.
+        if req.is_cancelled() {
+            return run;
         }
 
         debug!(
             target: "assets::io",
-            "io.read id={:032x} path='{}' bytes={} dt_us={}",
+            "io.read id={:032x} path='{}' bytes={} streamed={} meta={} dt_us={}",
             req.id.to_u128(),
             req.key.logical_path.display(),
-            bytes.len(),
+            run.bytes_read,
+            streamed,
+            settings.is_some(),
             io_dt.as_micros()
         );
 
         let imp_t0 = Instant::now();
-        let blob = importer.import_blob(&bytes, &req.key).map_err(|e| ProcessError {
-            id: req.id,
-            type_id: req.type_id.clone(),
-            error: Arc::from(e.msg().to_string()),
-        })?;
-        let imp_dt = imp_t0.elapsed();
+        let cached = match (&self.cache, &cache_key) {
+            (Some(c), Some(ck)) => c.lookup(ck, &self.sources),
+            _ => None,
+        };
 
-        {
-            let mut g = self.inner.lock();
-            g.diag.import_time_us += imp_dt.as_micros() as u64;
-        }
+        let from_cache = cached.is_some();
+        let remote = match (&cached, &self.provider, &cache_key) {
+            (None, Some(p), Some(ck)) => p.fetch(ck, &req.key, &self.sources),
+            _ => None,
+        };
+        let from_remote = remote.is_some();
+
+        let blob = match (cached, remote) {
+            (Some(blob), _) => {
+                debug!(
+                    target: "assets::cache",
+                    "cache.hit id={:032x} path='{}'",
+                    req.id.to_u128(),
+                    req.key.logical_path.display()
+                );
+                blob
+            }
+            (None, Some(blob)) => {
+                if let (Some(c), Some(ck)) = (&self.cache, &cache_key) {
+                    c.store(ck, &blob, &self.sources);
+                }
+                blob
+            }
+            (None, None) => {
+                let imported = if streamed {
+                    self.import_streamed(req, settings, &mut run)
+                } else {
+                    import_with(importer.as_ref(), &bytes, &req.key, settings)
+                };
+                match imported {
+                    Ok(blob) => {
+                        if let (Some(c), Some(ck)) = (&self.cache, &cache_key) {
+                            c.store(ck, &blob, &self.sources);
+                        }
+                        blob
+                    }
+                    Err(e) => {
+                        run.result = ImportResult::Failed(Arc::from(e.msg()));
+                        return run;
+                    }
+                }
+            }
+        };
+        let imp_dt = imp_t0.elapsed();
+        run.import_us = imp_dt.as_micros() as u64;
+        run.cache_hit = self.cache.is_some().then_some(from_cache);
 
         debug!(
             target: "assets::import",
-            "import.done id={:032x} importer='{}' type='{}' format='{}' payload={} dt_us={}",
+            "import.done id={:032x} importer='{}' type='{}' format='{}' payload={} cached={} remote={} dt_us={}",
             req.id.to_u128(),
             importer.stable_id(),
             blob.type_id,
             blob.format,
             blob.payload.len(),
+            from_cache,
+            from_remote,
             imp_dt.as_micros()
         );
 
-        let format = blob.format.clone();
-        let blob = Arc::new(blob);
+        let issues = match &self.rules {
+            Some(r) => {
+                let path = req.key.logical_path.to_string_lossy();
+                r.evaluate(&path, &AssetFacts::from_blob(&blob))
+            }
+            None => Vec::new(),
+        };
+        for i in issues.iter() {
+            let level = match i.severity {
+                ValidationSeverity::Warning => log::Level::Warn,
+                ValidationSeverity::Error => log::Level::Error,
+            };
+            log::log!(
+                target: "assets::validate",
+                level,
+                "validation.{} rule='{}' path='{}' msg='{}'",
+                i.severity,
+                i.rule,
+                i.path,
+                i.message
+            );
+        }
+        let is_error = |i: &&ValidationIssue| i.severity == ValidationSeverity::Error;
+        let rejected = self
+            .rules
+            .as_ref()
+            .filter(|r| r.fail_on_error)
+            .and_then(|_| issues.iter().find(is_error))
+            .map(|i| format!("validation: [{}] {}", i.rule, i.message));
+
+        run.result = ImportResult::Imported {
+            blob,
+            issues,
+            rejected,
+        };
+        run
+    }
 
-        {
-            let mut g = self.inner.lock();
-            g.diag.pump_success += 1;
-            g.blobs.insert(req.id, blob);
-            g.state.insert(req.id, AssetState::Ready);
-            g.events.push_back(AssetEvent::Ready {
-                id: req.id,
-                type_id: req.type_id.clone(),
-                format: format.clone(),
-            });
+    /// Hashes the source in chunks for the cache key of a streamed import; returns the key
+    /// and the bytes hashed.
+    fn stream_cache_key(
+        &self,
+        req: &PendingRequest,
+        settings: Option<&ImportSettings>,
+    ) -> Result<(CacheKey, u64), AssetError> {
+        let mut stream =
+            stream_from_any_source_list(&self.sources, &req.key.logical_path, self.case)?;
+        let version = req.importer.version();
+        let ck =
+            CacheKey::compute_stream(&mut stream, &req.key, settings, &req.importer_id, &version)?;
+        Ok((ck, stream.position()))
+    }
+
+    fn import_streamed(
+        &self,
+        req: &PendingRequest,
+        settings: Option<&ImportSettings>,
+        run: &mut ImportRun,
+    ) -> Result<AssetBlob, AssetError> {
+        let mut stream =
+            stream_from_any_source_list(&self.sources, &req.key.logical_path, self.case)?;
+        let blob = req.importer.import_stream(&mut stream, &req.key, settings);
+        run.bytes_read += stream.position();
+        blob
+    }
+}
+
+enum ImportResult {
+    Imported {
+        blob: AssetBlob,
+        issues: Vec<ValidationIssue>,
+        /// Set when an error-level rule rejects the blob.
+        rejected: Option<String>,
+    },
+    Cancelled,
+    Failed(Arc<str>),
+}
+
+/// One executed import and its I/O and CPU cost.
+struct ImportRun {
+    result: ImportResult,
+    bytes_read: u64,
+    io_us: u64,
+    import_us: u64,
+    /// Whether the persistent cache had the blob; `None` without a cache.
+    cache_hit: Option<bool>,
+}
+
+impl ImportRun {
+    #[inline]
+    fn failed(error: &str) -> Self {
+        Self {
+            result: ImportResult::Failed(Arc::from(error)),
+            bytes_read: 0,
+            io_us: 0,
+            import_us: 0,
+            cache_hit: None,
         }
+    }
+}
 
-        info!(
-            target: "assets::events",
-            "asset.ready id={:032x} type='{}' format='{}' path='{}'",
-            req.id.to_u128(),
-            req.type_id,
-            format,
-            req.key.logical_path.display()
-        );
+/// A run waiting to be finished by the pumping thread.
+struct ImportOutcome {
+    req: PendingRequest,
+    run: ImportRun,
+}
 
-        Ok(())
+/// Picks the highest-priority importer for `key`. The longest extension suffix wins so
+/// `.scene.json` can bind separately from `.json`.
+fn select_importer(
+    g: &StoreInner,
+    key: &AssetKey,
+) -> Result<(String, Arc<dyn BlobImporterDispatch>), AssetError> {
+    let exts = extension_candidates(&key.logical_path);
+    if exts.is_empty() {
+        return Err(AssetError::new("AssetStore: asset path has no extension"));
     }
+
+    let found = exts.iter().find_map(|ext| {
+        g.importers_by_ext
+            .get(ext)
+            .and_then(|list| list.first().cloned())
+            .map(|importer| (ext.clone(), importer))
+    });
+
+    found.ok_or_else(|| {
+        AssetError::new(format!(
+            "AssetStore: no importer registered for extension '.{}'",
+            exts.last().cloned().unwrap_or_default()
+        ))
+    })
 }
 
 #[derive(Debug)]
@@ -429,13 +1246,21 @@ struct ProcessError {
     error: Arc<str>,
 }
 
-#[inline]
-fn extension_ascii_lower(p: &Path) -> Option<String> {
-    let ext = p.extension()?.to_string_lossy();
-    if ext.is_empty() {
-        return None;
-    }
-    Some(ext.to_ascii_lowercase())
+/// Lowercased extension suffixes of the file name, longest first
+/// (`a.scene.json` -> `["scene.json", "json"]`). Leading dots (hidden files) are ignored.
+fn extension_candidates(p: &Path) -> Vec<String> {
+    let Some(name) = p.file_name() else {
+        return Vec::new();
+    };
+    let name = name.to_string_lossy().to_ascii_lowercase();
+    let name = name.trim_start_matches('.');
+
+    name.char_indices()
+        .filter(|&(_, c)| c == '.')
+        .map(|(i, _)| &name[i + 1..])
+        .filter(|ext| !ext.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 
 #[inline]
@@ -447,21 +1272,120 @@ fn normalize_ext(ext: &str) -> String {
 fn read_from_any_source_list(
     sources: &[Arc<dyn AssetSource>],
     logical_path: &Path,
+    case: PathCase,
 ) -> Result<Vec<u8>, AssetError> {
+    with_any_source(sources, logical_path, case, |s, p| s.read(p))
+}
+
+fn stream_from_any_source_list(
+    sources: &[Arc<dyn AssetSource>],
+    logical_path: &Path,
+    case: PathCase,
+) -> Result<AssetReadStream, AssetError> {
+    with_any_source(sources, logical_path, case, |s, p| s.open_stream(p))
+}
+
+/// Runs `f` on the first source holding `logical_path` (ignoring case if `case` says so).
+fn with_any_source<T>(
+    sources: &[Arc<dyn AssetSource>],
+    logical_path: &Path,
+    case: PathCase,
+    f: impl FnOnce(&dyn AssetSource, &Path) -> Result<T, AssetError>,
+) -> Result<T, AssetError> {
     if sources.is_empty() {
         return Err(AssetError::new("AssetStore: no sources registered"));
     }
 
+    match find_source(sources, logical_path, case)? {
+        Some((s, actual)) => f(s, &actual),
+        None => Err(AssetError::new(format!(
+            "AssetStore: asset not found in any source: '{}'",
+            logical_path.to_string_lossy()
+        ))),
+    }
+}
+
+/// First source holding `logical_path`, with the spelling it holds it under.
+fn find_source<'a>(
+    sources: &'a [Arc<dyn AssetSource>],
+    logical_path: &Path,
+    case: PathCase,
+) -> Result<Option<(&'a dyn AssetSource, PathBuf)>, AssetError> {
     for s in sources {
         if s.exists(logical_path) {
-            return s.read(logical_path);
+            return Ok(Some((s.as_ref(), logical_path.to_path_buf())));
+        }
+    }
+
+    if case == PathCase::Insensitive {
+        for s in sources {
+            if let Some(actual) = s.find_ignore_case(logical_path)? {
+                warn!(
+                    target: "assets",
+                    "asset.path_case requested='{}' actual='{}'",
+                    logical_path.display(),
+                    actual.display()
+                );
+                return Ok(Some((s.as_ref(), actual)));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Import settings from the `.meta` sidecar of `logical_path`; `None` without a sidecar or
+/// when it only holds tags.
+fn read_settings(
+    sources: &[Arc<dyn AssetSource>],
+    logical_path: &Path,
+    case: PathCase,
+) -> Result<Option<ImportSettings>, AssetError> {
+    let path = meta::sidecar_path(logical_path);
+    let Some((s, actual)) = find_source(sources, &path, case)? else {
+        return Ok(None);
+    };
+    let bytes = s.read(&actual)?;
+    ImportSettings::parse(&bytes)
+        .map(|s| (!s.is_empty()).then_some(s))
+        .map_err(|e| AssetError::new(format!("'{}': {}", actual.display(), e)))
+}
+
+/// Tags from the sidecar of `logical_path` in `source`; an unreadable sidecar has none.
+fn read_tags(source: &dyn AssetSource, logical_path: &str) -> Vec<String> {
+    let path = meta::sidecar_path(Path::new(logical_path));
+    match source.read(&path).and_then(|b| ImportSettings::parse(&b)) {
+        Ok(s) => s.tags(),
+        Err(e) => {
+            warn!(
+                target: "assets",
+                "asset.query meta_failed path='{}' reason='{}'",
+                path.display(),
+                e
+            );
+            Vec::new()
         }
     }
+}
 
-    Err(AssetError::new(format!(
-        "AssetStore: asset not found in any source: '{}'",
-        logical_path.to_string_lossy()
-    )))
+/// `/`-separated form of a logical path, as shown to browsers and queries.
+#[inline]
+fn logical_string(p: &Path) -> String {
+    p.to_string_lossy().replace('\\', "/")
+}
+
+/// Imports `bytes` with the sidecar settings when there are any.
+#[inline]
+fn import_with(
+    importer: &dyn BlobImporterDispatch,
+    bytes: &[u8],
+    key: &AssetKey,
+    settings: Option<&ImportSettings>,
+) -> Result<AssetBlob, AssetError> {
+    match settings {
+        Some(s) => importer.import_blob_with_settings(bytes, key, s),
+        None => importer.import_blob(bytes, key),
+    }
 }
 
 /// Utility: stable single-line preview for logs/UI.
@@ -508,6 +1432,13 @@ pub struct AssetStoreStats {
     pub blobs_ready: usize,
     pub blobs_bytes: u64,
     pub queue_len: usize,
+    /// Imports running on the import workers.
+    pub in_flight: usize,
+    pub cache_enabled: bool,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// Resident bytes and budget per category.
+    pub memory: Vec<CategoryUsage>,
 }
 
 #[derive(Debug, Clone)]
@@ -551,6 +1482,11 @@ impl AssetStore {
             blobs_ready,
             blobs_bytes,
             queue_len,
+            in_flight: g.in_flight,
+            cache_enabled: g.cache.is_some(),
+            cache_hits: g.diag.cache_hits,
+            cache_misses: g.diag.cache_misses,
+            memory: g.resident.usage(&g.budget),
         }
     }
 
@@ -581,12 +1517,15 @@ impl AssetStore {
                 crate::types::AssetState::Failed(e) => format!("failed: {}", e),
             };
 
-            // Path is not stored in the state map. We can only show id-based entry here.
-            // The canonical path is available at load-time via AssetKey, but not retained.
-            // For console use, we support list by id and state; path is available via commands like asset.load/reload.
+            let path = g
+                .keys
+                .get(id)
+                .map(|k| k.logical_path.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_default();
+
             out.push(AssetEntrySnapshot {
                 id_u128,
-                path: String::new(),
+                path,
                 state: state_str,
                 type_id,
                 format,
@@ -597,28 +1536,378 @@ impl AssetStore {
         out
     }
 
+    /// Lists the assets of every source together with the ones the store already knows,
+    /// filtered by `query` and sorted by path. Tags come from the `.meta` sidecars, which are
+    /// not listed themselves.
+    ///
+    /// Sources are listed on every call; compare the result's `revision` with
+    /// [`query_revision`](Self::query_revision) to know when to call again.
+    pub fn query(&self, query: &AssetQuery) -> AssetQueryResult {
+        let (sources, revision) = {
+            let g = self.inner.lock();
+            (g.sources.clone(), g.query_revision)
+        };
+
+        let mut listed = Vec::new();
+        // Sidecar path -> first source listing it, matching `read_settings`.
+        let mut sidecars = HashMap::<String, usize>::new();
+        for (i, s) in sources.iter().enumerate() {
+            let entries = match s.list() {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!(target: "assets", "asset.query list_failed source={} reason='{}'", i, e);
+                    continue;
+                }
+            };
+            for p in entries {
+                let p = logical_string(&p);
+                match p
+                    .strip_suffix(meta::META_EXTENSION)
+                    .and_then(|base| base.strip_suffix('.'))
+                {
+                    Some(base) => {
+                        sidecars.entry(base.to_string()).or_insert(i);
+                    }
+                    None => listed.push(p),
+                }
+            }
+        }
+
+        let candidates: Vec<AssetQueryEntry> = {
+            let g = self.inner.lock();
+            let mut ids: BTreeMap<String, AssetId> = listed
+                .into_iter()
+                .filter(|p| query.matches_path(p))
+                .map(|p| {
+                    let id = AssetKey::new(p.as_str(), 0).id();
+                    (p, id)
+                })
+                .collect();
+            for (id, key) in g.keys.iter() {
+                let p = logical_string(&key.logical_path);
+                if query.matches_path(&p) {
+                    ids.entry(p).or_insert(*id);
+                }
+            }
+
+            ids.into_iter()
+                .filter_map(|(path, id)| {
+                    let state = g.state.get(&id).cloned().unwrap_or(AssetState::Unloaded);
+                    if !query.matches_state(&state) {
+                        return None;
+                    }
+                    let blob = g.blobs.get(&id);
+                    Some(AssetQueryEntry {
+                        path,
+                        id,
+                        state,
+                        tags: Vec::new(),
+                        type_id: blob.map(|b| b.type_id.clone()),
+                        bytes: blob.map(|b| b.payload.len() as u64),
+                    })
+                })
+                .collect()
+        };
+
+        let mut entries = Vec::new();
+        for mut e in candidates {
+            if let Some(&i) = sidecars.get(&e.path) {
+                e.tags = read_tags(sources[i].as_ref(), &e.path);
+            }
+            if query.matches_tags(&e.tags) {
+                entries.push(e);
+            }
+        }
+
+        let total = entries.len();
+        if let Some(limit) = query.limit {
+            entries.truncate(limit);
+        }
+        AssetQueryResult {
+            revision,
+            total,
+            entries,
+        }
+    }
+
+    /// Bumped whenever a query result may have changed: asset state changes, tag edits and
+    /// added sources. Files changed on disk behind the store's back do not bump it.
+    #[inline]
+    pub fn query_revision(&self) -> u64 {
+        self.inner.lock().query_revision
+    }
+
+    /// Replaces the tags of `logical_path` in its `.meta` sidecar, creating the sidecar if
+    /// needed; empty `tags` clears them. The sidecar is written next to the asset, through
+    /// the first source holding it. Tags never trigger a re-import.
+    pub fn set_tags(&self, logical_path: &str, tags: &[String]) -> Result<(), AssetError> {
+        let key = AssetKey::new(logical_path, 0);
+        let (sources, case) = {
+            let g = self.inner.lock();
+            (g.sources.clone(), g.path_case)
+        };
+
+        let mut clean: Vec<String> = Vec::with_capacity(tags.len());
+        for t in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            if !clean.iter().any(|c| c.eq_ignore_ascii_case(t)) {
+                clean.push(t.to_string());
+            }
+        }
+
+        let sidecar = with_any_source(&sources, &key.logical_path, case, |s, actual| {
+            let sidecar = meta::sidecar_path(actual);
+            let existing = if s.exists(&sidecar) {
+                Some(s.read(&sidecar)?)
+            } else {
+                None
+            };
+            let bytes = meta::sidecar_with_tags(existing.as_deref(), &clean)
+                .map_err(|e| AssetError::new(format!("'{}': {}", sidecar.display(), e)))?;
+            s.write(&sidecar, &bytes)?;
+            Ok(sidecar)
+        })?;
+
+        self.inner.lock().query_revision += 1;
+        info!(
+            target: "assets",
+            "asset.tags meta='{}' tags='{}'",
+            sidecar.display(),
+            clean.join(",")
+        );
+        Ok(())
+    }
+
+    /// Imports `key` for a remote client, outside the queue and without touching asset state.
+    ///
+    /// Returns the encoded cache entry, or `None` when this store's source does not hash to
+    /// `expected` (the client has different content or importer version) or is missing.
+    pub fn import_for_remote(
+        &self,
+        key: &AssetKey,
+        expected: &CacheKey,
+    ) -> Result<Option<Vec<u8>>, AssetError> {
+        let (sources, cache, importer, case) = {
+            let g = self.inner.lock();
+            let (_, importer) = select_importer(&g, key)?;
+            (g.sources.clone(), g.cache.clone(), importer, g.path_case)
+        };
+
+        let Ok(bytes) = read_from_any_source_list(&sources, &key.logical_path, case) else {
+            return Ok(None);
+        };
+        let settings = read_settings(&sources, &key.logical_path, case)?;
+        let settings = settings.as_ref();
+        let ck = CacheKey::compute(
+            &bytes,
+            key,
+            settings,
+            &importer.stable_id(),
+            &importer.version(),
+        );
+        if ck != *expected {
+            return Ok(None);
+        }
+
+        let blob = match cache.as_ref().and_then(|c| c.lookup(&ck, &sources)) {
+            Some(blob) => blob,
+            None => {
+                let blob = import_with(importer.as_ref(), &bytes, key, settings)?;
+                if let Some(c) = &cache {
+                    c.store(&ck, &blob, &sources);
+                }
+                blob
+            }
+        };
+
+        encode_entry(&blob, &sources)
+            .map(Some)
+            .ok_or_else(|| AssetError::new("AssetStore: failed to encode imported blob"))
+    }
+
+    /// Reads raw bytes through the registered sources without importing.
+    ///
+    /// Intended for importers that assemble an asset from several source files
+    /// (e.g. a terrain descriptor referencing its heightmap).
+    pub fn read_source(&self, logical_path: &Path) -> Result<Vec<u8>, AssetError> {
+        let (sources, case) = {
+            let g = self.inner.lock();
+            (g.sources.clone(), g.path_case)
+        };
+        read_from_any_source_list(&sources, logical_path, case)
+    }
+
+    /// Opens a source for chunked reads without importing; see [`AssetReadStream`].
+    ///
+    /// For runtime consumers of large files (streamed audio, video) and importers that
+    /// pull companion files too big to read whole.
+    pub fn open_source_stream(&self, logical_path: &Path) -> Result<AssetReadStream, AssetError> {
+        let (sources, case) = {
+            let g = self.inner.lock();
+            (g.sources.clone(), g.path_case)
+        };
+        stream_from_any_source_list(&sources, logical_path, case)
+    }
+
+    /// Content type of `logical_path` as advertised by the importers bound to its extension,
+    /// longest suffix and highest priority first.
+    pub fn mime_type(&self, logical_path: &Path) -> Option<Arc<str>> {
+        let g = self.inner.lock();
+        extension_candidates(logical_path).iter().find_map(|ext| {
+            g.importers_by_ext
+                .get(ext)?
+                .iter()
+                .find_map(|imp| imp.mime_type(ext))
+        })
+    }
+
     /// Convenience: enqueue load by logical path with settings_hash=0.
     pub fn load_path(&self, logical_path: &str) -> Result<crate::id::AssetId, crate::types::AssetError> {
         let key = AssetKey::new(logical_path, 0);
         self.load(key)
     }
 
+    /// Convenience: `load_cancellable` by logical path with settings_hash=0.
+    pub fn load_path_cancellable(
+        &self,
+        logical_path: &str,
+        cancel: Arc<dyn LoadCancel>,
+    ) -> Result<AssetId, AssetError> {
+        self.load_cancellable(AssetKey::new(logical_path, 0), cancel)
+    }
+
     /// Convenience: attempt "reload" semantics:
     /// - mark asset Unloaded and drop cached blob (if any)
     /// - enqueue new load
+    ///
+    /// Transitive dependents are marked dirty and re-imported once the asset is ready again.
     pub fn reload_path(&self, logical_path: &str) -> Result<crate::id::AssetId, crate::types::AssetError> {
-        let key = AssetKey::new(logical_path, 0);
+        self.reload_key(AssetKey::new(logical_path, 0))
+    }
+
+    /// Reload by key (see `reload_path`).
+    pub fn reload_key(&self, key: AssetKey) -> Result<AssetId, AssetError> {
         let id = key.id();
 
         {
             let mut g = self.inner.lock();
-            g.blobs.remove(&id);
-            g.state.insert(id, crate::types::AssetState::Unloaded);
+            g.remove_blob(id);
+            g.state.insert(id, AssetState::Unloaded);
+            Self::invalidate_dependents(&mut g, id);
         }
 
         self.load(key)
     }
 
+    /// Drops the cached blob and marks the asset unloaded.
+    ///
+    /// Dependents keep their edge to this asset, are marked dirty and receive
+    /// `AssetEvent::DependencyChanged`; they are re-imported when it is loaded again.
+    pub fn unload(&self, id: AssetId) -> bool {
+        let mut g = self.inner.lock();
+
+        let known = g.state.contains_key(&id);
+        if !known {
+            return false;
+        }
+
+        g.remove_blob(id);
+        g.state.insert(id, AssetState::Unloaded);
+        // A still-queued import would only resurrect the asset.
+        g.queue.retain(|r| r.id != id);
+        g.deps.clear_dependencies(id);
+        g.dirty.remove(&id);
+        g.issues.remove(&id);
+        g.push_event(AssetEvent::Unloaded { id });
+        Self::invalidate_dependents(&mut g, id);
+
+        info!(target: "assets::events", "asset.unloaded id={:032x}", id.to_u128());
+        true
+    }
+
+    /// Direct dependencies declared by the importer of `id`.
+    pub fn dependencies_of(&self, id: AssetId) -> Vec<AssetId> {
+        let g = self.inner.lock();
+        g.deps.dependencies(id).to_vec()
+    }
+
+    /// Adds edges discovered by a consumer after import (e.g. UI markup includes).
+    ///
+    /// Merged with the importer-declared dependencies; replaced again when `id` is re-imported.
+    pub fn link_dependencies(&self, id: AssetId, deps: impl IntoIterator<Item = AssetId>) {
+        let mut g = self.inner.lock();
+        let mut list = g.deps.dependencies(id).to_vec();
+        list.extend(deps);
+        g.deps.set_dependencies(id, list);
+    }
+
+    /// Direct dependents of `id`.
+    pub fn dependents_of(&self, id: AssetId) -> Vec<AssetId> {
+        let g = self.inner.lock();
+        g.deps.dependents(id)
+    }
+
+    /// Snapshot of every known asset and dependency edge with states and payload sizes.
+    /// Use `DependencyGraphExport::subgraph` to focus on one asset.
+    pub fn export_dependency_graph(&self) -> DependencyGraphExport {
+        let g = self.inner.lock();
+
+        let mut ids: Vec<AssetId> = g.state.keys().copied().collect();
+        let edges = g.deps.edges();
+        ids.extend(edges.iter().flat_map(|(a, b)| [*a, *b]));
+        ids.sort();
+        ids.dedup();
+
+        let nodes = ids
+            .into_iter()
+            .map(|id| {
+                let state = match g.state.get(&id) {
+                    Some(AssetState::Unloaded) => "unloaded",
+                    Some(AssetState::Loading) => "loading",
+                    Some(AssetState::Ready) => "ready",
+                    Some(AssetState::Failed(_)) => "failed",
+                    None => "unknown",
+                };
+                let blob = g.blobs.get(&id);
+                DependencyGraphNode {
+                    id,
+                    path: g
+                        .keys
+                        .get(&id)
+                        .map(|k| k.logical_path.to_string_lossy().replace('\\', "/"))
+                        .unwrap_or_else(|| format!("{:032x}", id.to_u128())),
+                    state,
+                    type_id: blob.map(|b| b.type_id.to_string()),
+                    bytes: blob.map(|b| b.payload.len() as u64),
+                    dirty: g.dirty.contains(&id),
+                }
+            })
+            .collect();
+
+        DependencyGraphExport { nodes, edges }
+    }
+
+    /// True if a dependency changed since `id` was last imported.
+    pub fn is_dirty(&self, id: AssetId) -> bool {
+        let g = self.inner.lock();
+        g.dirty.contains(&id)
+    }
+
+    fn invalidate_dependents(g: &mut StoreInner, id: AssetId) {
+        let dependents = g.deps.dependents_transitive(id);
+        for d in dependents {
+            if !g.dirty.insert(d) {
+                continue;
+            }
+            g.push_event(AssetEvent::DependencyChanged { id: d, dependency: id });
+            debug!(
+                target: "assets::deps",
+                "deps.dirty dependent={:032x} dependency={:032x}",
+                d.to_u128(),
+                id.to_u128()
+            );
+        }
+    }
+
     /// Returns the current queue length (for console/UI).
     #[inline]
     pub fn queue_len(&self) -> usize {
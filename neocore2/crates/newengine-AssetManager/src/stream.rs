@@ -0,0 +1,240 @@
+//! Chunked reads of source entries too large to hold in memory (audio, video, packed media).
+//!
+//! [`AssetSource::open_stream`](crate::source::AssetSource::open_stream) hands out an
+//! [`AssetReadStream`]; importers either pull chunks from it directly or call
+//! [`AssetReadStream::prefetch`] to keep reading on a background thread while they decode.
+
+use crate::types::AssetError;
+
+use std::io::{self, Read};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+
+/// Default chunk size for [`AssetReadStream::prefetch`].
+pub const DEFAULT_STREAM_CHUNK: usize = 1 << 20;
+
+/// Sequential reader over one source entry.
+pub struct AssetReadStream {
+    inner: Box<dyn Read + Send>,
+    len: Option<u64>,
+    pos: u64,
+    /// Entry name for error messages.
+    label: Arc<str>,
+}
+
+impl AssetReadStream {
+    /// Wraps a reader; `len` is the entry size when the source knows it.
+    #[inline]
+    pub fn new(
+        inner: impl Read + Send + 'static,
+        len: Option<u64>,
+        label: impl Into<Arc<str>>,
+    ) -> Self {
+        Self {
+            inner: Box::new(inner),
+            len,
+            pos: 0,
+            label: label.into(),
+        }
+    }
+
+    /// Stream over bytes already in memory (sources that cannot read incrementally).
+    #[inline]
+    pub fn from_bytes(bytes: Vec<u8>, label: impl Into<Arc<str>>) -> Self {
+        let len = bytes.len() as u64;
+        Self::new(io::Cursor::new(bytes), Some(len), label)
+    }
+
+    /// Total entry size, if known up front.
+    #[inline]
+    pub fn size(&self) -> Option<u64> {
+        self.len
+    }
+
+    /// Bytes read so far.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    #[inline]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Reads up to `buf.len()` bytes; `Ok(0)` at the end of the entry.
+    pub fn read_chunk(&mut self, buf: &mut [u8]) -> Result<usize, AssetError> {
+        loop {
+            match self.inner.read(buf) {
+                Ok(n) => {
+                    self.pos += n as u64;
+                    return Ok(n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    return Err(AssetError::new(format!(
+                        "stream: failed to read '{}' at {}: {}",
+                        self.label, self.pos, e
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Reads the rest of the entry into memory.
+    pub fn read_all(&mut self) -> Result<Vec<u8>, AssetError> {
+        let rest = self.len.map_or(0, |l| l.saturating_sub(self.pos));
+        let mut out = Vec::with_capacity(rest as usize);
+        self.inner.read_to_end(&mut out).map_err(|e| {
+            AssetError::new(format!(
+                "stream: failed to read '{}' at {}: {}",
+                self.label, self.pos, e
+            ))
+        })?;
+        self.pos += out.len() as u64;
+        Ok(out)
+    }
+
+    /// Moves the reads to a background thread that stays up to `depth` chunks of
+    /// `chunk_size` bytes ahead of the consumer.
+    pub fn prefetch(self, chunk_size: usize, depth: usize) -> Result<AssetChunks, AssetError> {
+        let chunk_size = chunk_size.max(1);
+        let (tx, rx) = mpsc::sync_channel(depth.max(1));
+        let label = self.label.clone();
+        let len = self.len;
+
+        let mut stream = self;
+        std::thread::Builder::new()
+            .name("asset-stream".to_string())
+            .spawn(move || loop {
+                let mut buf = vec![0u8; chunk_size];
+                let msg = match stream.read_chunk(&mut buf) {
+                    Ok(0) => return,
+                    Ok(n) => {
+                        buf.truncate(n);
+                        Ok(buf)
+                    }
+                    Err(e) => Err(e),
+                };
+                let failed = msg.is_err();
+                // The consumer dropped the chunks: stop reading.
+                if tx.send(msg).is_err() || failed {
+                    return;
+                }
+            })
+            .map_err(|e| {
+                AssetError::new(format!("stream: failed to spawn reader for '{label}': {e}"))
+            })?;
+
+        Ok(AssetChunks {
+            rx,
+            len,
+            received: 0,
+            done: false,
+        })
+    }
+}
+
+impl Read for AssetReadStream {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl std::fmt::Debug for AssetReadStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssetReadStream")
+            .field("label", &self.label)
+            .field("len", &self.len)
+            .field("pos", &self.pos)
+            .finish()
+    }
+}
+
+/// Result of [`AssetChunks::poll_chunk`].
+#[derive(Debug)]
+pub enum ChunkPoll {
+    Ready(Vec<u8>),
+    /// The reader has not produced the next chunk yet.
+    Pending,
+    End,
+}
+
+/// Chunks read ahead by [`AssetReadStream::prefetch`], in entry order.
+///
+/// Dropping it stops the reader thread after its current chunk.
+pub struct AssetChunks {
+    rx: Receiver<Result<Vec<u8>, AssetError>>,
+    len: Option<u64>,
+    received: u64,
+    done: bool,
+}
+
+impl AssetChunks {
+    /// Total entry size, if known up front.
+    #[inline]
+    pub fn size(&self) -> Option<u64> {
+        self.len
+    }
+
+    /// Bytes handed out so far.
+    #[inline]
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    /// Blocks for the next chunk; `None` at the end of the entry.
+    pub fn next_chunk(&mut self) -> Option<Result<Vec<u8>, AssetError>> {
+        if self.done {
+            return None;
+        }
+        match self.rx.recv() {
+            Ok(msg) => Some(self.take(msg)),
+            Err(_) => {
+                self.done = true;
+                None
+            }
+        }
+    }
+
+    /// Returns the next chunk if it is already read, without blocking.
+    pub fn poll_chunk(&mut self) -> Result<ChunkPoll, AssetError> {
+        if self.done {
+            return Ok(ChunkPoll::End);
+        }
+        match self.rx.try_recv() {
+            Ok(msg) => self.take(msg).map(ChunkPoll::Ready),
+            Err(TryRecvError::Empty) => Ok(ChunkPoll::Pending),
+            Err(TryRecvError::Disconnected) => {
+                self.done = true;
+                Ok(ChunkPoll::End)
+            }
+        }
+    }
+
+    #[inline]
+    fn take(&mut self, msg: Result<Vec<u8>, AssetError>) -> Result<Vec<u8>, AssetError> {
+        match msg {
+            Ok(chunk) => {
+                self.received += chunk.len() as u64;
+                Ok(chunk)
+            }
+            Err(e) => {
+                self.done = true;
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Iterator for AssetChunks {
+    type Item = Result<Vec<u8>, AssetError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk()
+    }
+}
@@ -1,4 +1,9 @@
-use crate::types::Asset;
+use crate::types::{Asset, AssetError};
+
+/// Blob type id shared by every texture importer.
+pub const TEXTURE_TYPE_ID: &str = "kalitech.asset.texture";
+/// Blob format for decoded RGBA8 mip chains (`payload` = mips concatenated, largest first).
+pub const TEXTURE_RGBA8_FORMAT: &str = "ne.texture.rgba8.v1";
 
 /// CPU-side texture payload.
 ///
@@ -44,6 +49,12 @@ pub enum TextureFormat {
     Bc4Unorm,
     Bc5Unorm,
     Bc7Unorm,
+    Etc2Rgba8Unorm,
+    Astc4x4Unorm,
+    /// Basis Universal ETC1S (BasisLZ) payload; must be transcoded before upload.
+    BasisEtc1s,
+    /// Basis Universal UASTC payload; must be transcoded before upload.
+    BasisUastc,
 }
 
 /// One mip level for a single layer.
@@ -69,4 +80,128 @@ impl Asset for TextureAsset {
     fn type_name() -> &'static str {
         "TextureAsset"
     }
-}
\ No newline at end of file
+}
+impl TextureAsset {
+    /// Single-layer RGBA8 texture. With `mips` the full chain is generated by 2x2 box filtering.
+    pub fn rgba8(width: u32, height: u32, base: Vec<u8>, mips: bool) -> Self {
+        let mut levels = vec![TextureMip {
+            width,
+            height,
+            depth: 1,
+            subresources: vec![TextureSubresource {
+                layer: 0,
+                data: base,
+            }],
+        }];
+
+        while mips {
+            let prev = levels.last().expect("mip 0");
+            if prev.width == 1 && prev.height == 1 {
+                break;
+            }
+            let next = downsample_rgba8(prev);
+            levels.push(next);
+        }
+
+        Self {
+            desc: TextureDesc {
+                width,
+                height,
+                depth: 1,
+                layers: 1,
+                mip_count: levels.len() as u32,
+                format: TextureFormat::Rgba8Unorm,
+                kind: TextureKind::Tex2D,
+            },
+            mips: levels,
+        }
+    }
+
+    /// Concatenated mip data in the `ne.texture.rgba8.v1` layout.
+    pub fn to_rgba8_payload(&self) -> Vec<u8> {
+        let len = self
+            .mips
+            .iter()
+            .flat_map(|m| m.subresources.iter())
+            .map(|s| s.data.len())
+            .sum();
+        let mut out = Vec::with_capacity(len);
+        for sub in self.mips.iter().flat_map(|m| m.subresources.iter()) {
+            out.extend_from_slice(&sub.data);
+        }
+        out
+    }
+
+    /// Inverse of [`TextureAsset::to_rgba8_payload`] for a single-layer 2D texture.
+    pub fn from_rgba8_payload(
+        width: u32,
+        height: u32,
+        mip_count: u32,
+        payload: &[u8],
+    ) -> Result<Self, AssetError> {
+        let mut mips = Vec::with_capacity(mip_count as usize);
+        let (mut w, mut h, mut off) = (width.max(1), height.max(1), 0usize);
+
+        for _ in 0..mip_count.max(1) {
+            let len = w as usize * h as usize * 4;
+            let data = payload
+                .get(off..off + len)
+                .ok_or_else(|| AssetError::new("texture: rgba8 payload truncated"))?;
+            mips.push(TextureMip {
+                width: w,
+                height: h,
+                depth: 1,
+                subresources: vec![TextureSubresource {
+                    layer: 0,
+                    data: data.to_vec(),
+                }],
+            });
+            off += len;
+            w = (w / 2).max(1);
+            h = (h / 2).max(1);
+        }
+
+        Ok(Self {
+            desc: TextureDesc {
+                width,
+                height,
+                depth: 1,
+                layers: 1,
+                mip_count: mips.len() as u32,
+                format: TextureFormat::Rgba8Unorm,
+                kind: TextureKind::Tex2D,
+            },
+            mips,
+        })
+    }
+}
+
+fn downsample_rgba8(src: &TextureMip) -> TextureMip {
+    let (sw, sh) = (src.width as usize, src.height as usize);
+    let (dw, dh) = ((sw / 2).max(1), (sh / 2).max(1));
+    let data = &src.subresources[0].data;
+    let mut out = vec![0u8; dw * dh * 4];
+
+    for y in 0..dh {
+        for x in 0..dw {
+            let x0 = (x * 2).min(sw - 1);
+            let x1 = (x * 2 + 1).min(sw - 1);
+            let y0 = (y * 2).min(sh - 1);
+            let y1 = (y * 2 + 1).min(sh - 1);
+            for c in 0..4 {
+                let sum = data[(y0 * sw + x0) * 4 + c] as u32
+                    + data[(y0 * sw + x1) * 4 + c] as u32
+                    + data[(y1 * sw + x0) * 4 + c] as u32
+                    + data[(y1 * sw + x1) * 4 + c] as u32;
+                out[(y * dw + x) * 4 + c] = ((sum + 2) / 4) as u8;
+            }
+        }
+    }
+
+    TextureMip {
+        width: dw as u32,
+        height: dh as u32,
+        depth: 1,
+        subresources: vec![TextureSubresource { layer: 0, data: out }],
+    }
+}
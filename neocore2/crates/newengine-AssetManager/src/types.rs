@@ -1,4 +1,5 @@
 use crate::id::AssetId;
+use crate::path::normalize_path;
 use std::path::{Component, PathBuf};
 use std::sync::Arc;
 
@@ -101,9 +102,14 @@ enum NormalizePathError {
 /// - relative
 /// - no root/prefix
 /// - no '.' or '..'
+/// - `\` and `/` both separate components, names are Unicode NFC
 /// - platform-stable via components joining
 #[inline]
 fn normalize_logical_path(p: PathBuf) -> Result<PathBuf, NormalizePathError> {
+    let p = match p.to_str() {
+        Some(s) => PathBuf::from(normalize_path(s).as_ref()),
+        None => p,
+    };
     let mut out = PathBuf::new();
 
     for c in p.components() {
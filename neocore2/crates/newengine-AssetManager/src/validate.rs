@@ -0,0 +1,404 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Content standards checked after import.
+//!
+//! Rules live in a JSON asset (`validation.rules.json` at the assets root by default):
+//!
+//! ```json
+//! {
+//!   "rules": [
+//!     { "id": "tex-pot", "check": "power_of_two", "severity": "error" },
+//!     { "id": "prop-budget", "paths": ["props/**"], "check": "max_triangles", "max": 5000 },
+//!     { "id": "sfx-level", "paths": ["audio/sfx/**"], "check": "loudness", "min_db": -24, "max_db": -6 }
+//!   ]
+//! }
+//! ```
+//!
+//! The store evaluates them on every imported blob; the cooker evaluates them on source files.
+//! A rule only applies to assets that expose the measured property (a texture rule ignores
+//! meshes), and `paths` globs (`*`, `?`, `**`) narrow it to a category of content.
+
+use crate::ktx2::Ktx2Header;
+use crate::texture::TEXTURE_TYPE_ID;
+use crate::types::{AssetBlob, AssetError};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fmt;
+use std::path::Path;
+
+/// Logical path of the rules asset loaded by default.
+pub const VALIDATION_RULES_PATH: &str = "validation.rules.json";
+
+const MODEL3D_TYPE_ID: &str = "kalitech.asset.model3d";
+pub(crate) const AUDIO_TYPE_ID: &str = "kalitech.asset.audio";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationSeverity {
+    #[default]
+    Warning,
+    Error,
+}
+
+impl fmt::Display for ValidationSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValidationSeverity::Warning => "warning",
+            ValidationSeverity::Error => "error",
+        })
+    }
+}
+
+/// What a rule measures.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum RuleCheck {
+    /// Texture width and height are powers of two.
+    PowerOfTwo,
+    /// Neither texture side exceeds `max` pixels.
+    MaxTextureSize { max: u32 },
+    /// Mesh triangle count is at most `max`.
+    MaxTriangles { max: u64 },
+    /// Unweighted RMS level of the clip, in dBFS, lies in `min_db..=max_db`.
+    Loudness { min_db: f32, max_db: f32 },
+    /// Sample peak, in dBFS, is at most `max_db`.
+    MaxPeak { max_db: f32 },
+    /// Audio sample rate is one of `allowed`.
+    SampleRate { allowed: Vec<u32> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationRule {
+    pub id: String,
+    /// Globs over logical paths; empty applies the rule everywhere.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    #[serde(flatten)]
+    pub check: RuleCheck,
+    #[serde(default)]
+    pub severity: ValidationSeverity,
+    /// Replaces the generated message.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl ValidationRule {
+    #[inline]
+    pub fn applies_to(&self, path: &str) -> bool {
+        self.paths.is_empty() || self.paths.iter().any(|p| glob_match(p, path))
+    }
+
+    /// Failure description, or `None` if `facts` pass (or lack the measured property).
+    fn check(&self, facts: &AssetFacts) -> Option<String> {
+        let failure = match &self.check {
+            RuleCheck::PowerOfTwo => {
+                let (w, h) = facts.texture_size?;
+                (!w.is_power_of_two() || !h.is_power_of_two())
+                    .then(|| format!("{w}x{h} is not a power of two"))
+            }
+            RuleCheck::MaxTextureSize { max } => {
+                let (w, h) = facts.texture_size?;
+                (w > *max || h > *max).then(|| format!("{w}x{h} exceeds {max}px"))
+            }
+            RuleCheck::MaxTriangles { max } => {
+                let n = facts.triangles?;
+                (n > *max).then(|| format!("{n} triangles exceeds {max}"))
+            }
+            RuleCheck::Loudness { min_db, max_db } => {
+                let db = facts.rms_db?;
+                (db < *min_db || db > *max_db)
+                    .then(|| format!("loudness {db:.1} dBFS outside {min_db}..{max_db}"))
+            }
+            RuleCheck::MaxPeak { max_db } => {
+                let db = facts.peak_db?;
+                (db > *max_db).then(|| format!("peak {db:.1} dBFS exceeds {max_db}"))
+            }
+            RuleCheck::SampleRate { allowed } => {
+                let rate = facts.sample_rate?;
+                (!allowed.contains(&rate))
+                    .then(|| format!("sample rate {rate} Hz not in {allowed:?}"))
+            }
+        }?;
+        Some(self.message.clone().unwrap_or(failure))
+    }
+}
+
+/// Parsed rules asset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationRules {
+    #[serde(default)]
+    pub rules: Vec<ValidationRule>,
+    /// Fail imports that break an `error` rule instead of only reporting them.
+    #[serde(default)]
+    pub fail_on_error: bool,
+}
+
+impl ValidationRules {
+    pub fn from_json(s: &str) -> Result<Self, AssetError> {
+        serde_json::from_str(s).map_err(|e| AssetError::new(format!("validation rules: {e}")))
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, AssetError> {
+        let s = std::fs::read_to_string(path).map_err(|e| {
+            AssetError::new(format!("validation rules '{}': {}", path.display(), e))
+        })?;
+        Self::from_json(&s)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Issues raised by the rules that apply to `path`.
+    pub fn evaluate(&self, path: &str, facts: &AssetFacts) -> Vec<ValidationIssue> {
+        self.rules
+            .iter()
+            .filter(|r| r.applies_to(path))
+            .filter_map(|r| {
+                r.check(facts).map(|message| ValidationIssue {
+                    rule: r.id.clone(),
+                    path: path.to_string(),
+                    severity: r.severity,
+                    message,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    pub rule: String,
+    pub path: String,
+    pub severity: ValidationSeverity,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] {}: {}",
+            self.severity, self.rule, self.path, self.message
+        )
+    }
+}
+
+/// Measured properties of one asset; `None` where the asset has no such property.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AssetFacts {
+    pub texture_size: Option<(u32, u32)>,
+    pub triangles: Option<u64>,
+    pub sample_rate: Option<u32>,
+    pub rms_db: Option<f32>,
+    pub peak_db: Option<f32>,
+}
+
+impl AssetFacts {
+    /// Facts of an imported blob, from its metadata (and the payload for WAV levels).
+    pub fn from_blob(blob: &AssetBlob) -> Self {
+        let meta: JsonValue = serde_json::from_str(&blob.meta_json).unwrap_or(JsonValue::Null);
+        let u64_at = |ptr: &str| meta.pointer(ptr).and_then(JsonValue::as_u64);
+        let mut facts = AssetFacts::default();
+
+        match &*blob.type_id {
+            TEXTURE_TYPE_ID => {
+                if let (Some(w), Some(h)) = (u64_at("/width"), u64_at("/height")) {
+                    facts.texture_size = Some((w as u32, h as u32));
+                }
+            }
+            MODEL3D_TYPE_ID => {
+                facts.triangles = u64_at("/mesh/index_count").map(|n| n / 3);
+            }
+            AUDIO_TYPE_ID => {
+                facts.sample_rate = u64_at("/sample_rate").map(|r| r as u32).filter(|r| *r > 0);
+                facts.add_wav_levels(&blob.payload);
+            }
+            _ => {}
+        }
+
+        facts
+    }
+
+    /// Facts read straight from a source file, for tools that do not run importers. Covers
+    /// PNG, JPEG, KTX2, OBJ and PCM/float WAV; other files yield no facts.
+    pub fn from_source(path: &str, bytes: &[u8]) -> Self {
+        let ext = path
+            .rsplit_once('.')
+            .map(|(_, e)| e.to_ascii_lowercase())
+            .unwrap_or_default();
+        let mut facts = AssetFacts::default();
+
+        match ext.as_str() {
+            "png" => facts.texture_size = png_size(bytes),
+            "jpg" | "jpeg" => facts.texture_size = jpeg_size(bytes),
+            "ktx2" => {
+                facts.texture_size = Ktx2Header::parse(bytes).ok().map(|h| (h.width, h.height));
+            }
+            "obj" => facts.triangles = Some(obj_triangles(bytes)),
+            "wav" => facts.add_wav_levels(bytes),
+            _ => {}
+        }
+
+        facts
+    }
+
+    fn add_wav_levels(&mut self, bytes: &[u8]) {
+        if let Some(levels) = wav_levels(bytes) {
+            self.sample_rate = Some(levels.sample_rate);
+            self.rms_db = Some(levels.rms_db);
+            self.peak_db = Some(levels.peak_db);
+        }
+    }
+}
+
+/// Matches `path` against a glob: `*` and `?` stay within one segment, `**` spans segments.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    if let Some(rest) = pattern.strip_prefix("**") {
+        let rest = rest.strip_prefix('/').unwrap_or(rest);
+        if rest.is_empty() {
+            return true;
+        }
+        return glob_match(rest, path)
+            || path
+                .char_indices()
+                .any(|(i, c)| c == '/' && glob_match(rest, &path[i + 1..]));
+    }
+
+    let mut chars = pattern.chars();
+    match chars.next() {
+        None => path.is_empty(),
+        Some('*') => {
+            let rest = chars.as_str();
+            let segment_end = path.find('/').unwrap_or(path.len());
+            (0..=segment_end)
+                .filter(|i| path.is_char_boundary(*i))
+                .any(|i| glob_match(rest, &path[i..]))
+        }
+        Some('?') => {
+            let mut p = path.chars();
+            matches!(p.next(), Some(c) if c != '/') && glob_match(chars.as_str(), p.as_str())
+        }
+        Some(c) => path
+            .strip_prefix(c)
+            .is_some_and(|rest| glob_match(chars.as_str(), rest)),
+    }
+}
+
+fn png_size(b: &[u8]) -> Option<(u32, u32)> {
+    if b.len() < 24 || &b[..8] != b"\x89PNG\r\n\x1a\n" || &b[12..16] != b"IHDR" {
+        return None;
+    }
+    let be = |at: usize| u32::from_be_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]]);
+    Some((be(16), be(20)))
+}
+
+fn jpeg_size(b: &[u8]) -> Option<(u32, u32)> {
+    if !b.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut at = 2usize;
+    while at + 4 <= b.len() {
+        if b[at] != 0xFF {
+            return None;
+        }
+        let marker = b[at + 1];
+        if marker == 0xFF {
+            at += 1;
+            continue;
+        }
+        let len = u16::from_be_bytes([b[at + 2], b[at + 3]]) as usize;
+        let is_sof = matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+        if is_sof {
+            let s = b.get(at + 5..at + 9)?;
+            let h = u16::from_be_bytes([s[0], s[1]]) as u32;
+            let w = u16::from_be_bytes([s[2], s[3]]) as u32;
+            return Some((w, h));
+        }
+        at += 2 + len;
+    }
+    None
+}
+
+/// Triangles after fan-triangulating every `f` line.
+fn obj_triangles(b: &[u8]) -> u64 {
+    String::from_utf8_lossy(b)
+        .lines()
+        .filter_map(|l| l.trim_start().strip_prefix("f "))
+        .map(|f| f.split_whitespace().count().saturating_sub(2) as u64)
+        .sum()
+}
+
+struct WavLevels {
+    sample_rate: u32,
+    rms_db: f32,
+    peak_db: f32,
+}
+
+/// Levels of a PCM (8/16/24/32-bit) or 32-bit float WAV.
+fn wav_levels(b: &[u8]) -> Option<WavLevels> {
+    if b.len() < 12 || &b[..4] != b"RIFF" || &b[8..12] != b"WAVE" {
+        return None;
+    }
+
+    let mut fmt: Option<(u16, u32, u16)> = None;
+    let mut data: Option<&[u8]> = None;
+    let mut at = 12usize;
+    while at + 8 <= b.len() {
+        let id = &b[at..at + 4];
+        let len = u32::from_le_bytes([b[at + 4], b[at + 5], b[at + 6], b[at + 7]]) as usize;
+        let body = &b[at + 8..(at + 8).saturating_add(len).min(b.len())];
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let format = u16::from_le_bytes([body[0], body[1]]);
+                let rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                let bits = u16::from_le_bytes([body[14], body[15]]);
+                fmt = Some((format, rate, bits));
+            }
+            b"data" => data = Some(body),
+            _ => {}
+        }
+        at += 8 + len + (len & 1);
+    }
+
+    // 0xFFFE (extensible) carries PCM or float; the sample width tells them apart well enough.
+    let (format, sample_rate, bits) = fmt?;
+    let data = data?;
+    let sample: fn(&[u8]) -> f32 = match (format, bits) {
+        (1 | 0xFFFE, 8) => |s| (s[0] as f32 - 128.0) / 128.0,
+        (1 | 0xFFFE, 16) => |s| i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0,
+        (1 | 0xFFFE, 24) => {
+            |s| (i32::from_le_bytes([0, s[0], s[1], s[2]]) >> 8) as f32 / 8_388_608.0
+        }
+        (1, 32) => |s| i32::from_le_bytes([s[0], s[1], s[2], s[3]]) as f32 / 2_147_483_648.0,
+        (3 | 0xFFFE, 32) => |s| f32::from_le_bytes([s[0], s[1], s[2], s[3]]),
+        _ => return None,
+    };
+
+    let width = bits as usize / 8;
+    let (mut peak, mut sum_sq, mut n) = (0.0f32, 0.0f64, 0u64);
+    for s in data.chunks_exact(width) {
+        let v = sample(s);
+        peak = peak.max(v.abs());
+        sum_sq += (v as f64) * (v as f64);
+        n += 1;
+    }
+    if n == 0 {
+        return None;
+    }
+
+    let db = |x: f64| {
+        if x > 0.0 {
+            20.0 * x.log10() as f32
+        } else {
+            f32::NEG_INFINITY
+        }
+    };
+    Some(WavLevels {
+        sample_rate,
+        rms_db: db((sum_sq / n as f64).sqrt()),
+        peak_db: db(peak as f64),
+    })
+}
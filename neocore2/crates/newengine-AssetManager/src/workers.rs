@@ -0,0 +1,90 @@
+use parking_lot::{Condvar, Mutex};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+pub(crate) type Job = Box<dyn FnOnce() + Send + 'static>;
+
+#[derive(Default)]
+struct QueueState {
+    jobs: VecDeque<Job>,
+    shutdown: bool,
+}
+
+/// Jobs waiting for an import worker, oldest first.
+#[derive(Default)]
+pub(crate) struct JobQueue {
+    state: Mutex<QueueState>,
+    ready: Condvar,
+}
+
+impl JobQueue {
+    pub(crate) fn push(&self, job: Job) {
+        self.state.lock().jobs.push_back(job);
+        self.ready.notify_one();
+    }
+
+    /// Blocks until a job is queued; `None` once the pool shuts down and the queue is empty.
+    fn next(&self) -> Option<Job> {
+        let mut q = self.state.lock();
+        loop {
+            if let Some(job) = q.jobs.pop_front() {
+                return Some(job);
+            }
+            if q.shutdown {
+                return None;
+            }
+            self.ready.wait(&mut q);
+        }
+    }
+}
+
+/// Fixed set of threads running import jobs off the pumping thread.
+///
+/// Dropping the pool runs the jobs still queued, then joins the threads.
+pub(crate) struct ImportWorkers {
+    queue: Arc<JobQueue>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl ImportWorkers {
+    pub(crate) fn new(count: usize) -> std::io::Result<Self> {
+        let mut pool = Self {
+            queue: Arc::new(JobQueue::default()),
+            threads: Vec::with_capacity(count),
+        };
+        for i in 0..count {
+            let queue = pool.queue.clone();
+            let thread = std::thread::Builder::new()
+                .name(format!("asset-import-{i}"))
+                .spawn(move || {
+                    while let Some(job) = queue.next() {
+                        job();
+                    }
+                })?;
+            pool.threads.push(thread);
+        }
+        Ok(pool)
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.threads.len()
+    }
+
+    /// Queue feeding the workers; lets callers submit without holding the pool.
+    #[inline]
+    pub(crate) fn queue(&self) -> Arc<JobQueue> {
+        self.queue.clone()
+    }
+}
+
+impl Drop for ImportWorkers {
+    fn drop(&mut self) {
+        self.queue.state.lock().shutdown = true;
+        self.queue.ready.notify_all();
+        for t in self.threads.drain(..) {
+            let _ = t.join();
+        }
+    }
+}
@@ -17,7 +17,7 @@ pub mod protocol;
 
 pub mod audio_api;
 pub mod capability;
-mod occlusion;
+pub mod occlusion;
 
 pub mod prelude {
     pub use crate::ambience::*;
@@ -28,6 +28,7 @@ pub mod prelude {
     pub use crate::math::*;
     pub use crate::mixer::*;
     pub use crate::music::*;
+    pub use crate::occlusion::*;
     pub use crate::system::*;
     pub use crate::types::*;
     pub use crate::vehicle::*;
@@ -25,4 +25,13 @@ pub struct AudioEntityDesc {
     pub bus: crate::ids::AudioBusId,
     pub pos: crate::math::Vec3f,
     pub vel: crate::math::Vec3f,
-}
\ No newline at end of file
+}
+#[repr(C)]
+#[cfg_attr(feature = "abi", derive(StableAbi))]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Zeroable, Pod)]
+pub struct AudioListenerDesc {
+    pub pos: crate::math::Vec3f,
+    pub vel: crate::math::Vec3f,
+    pub forward: crate::math::Vec3f,
+    pub up: crate::math::Vec3f,
+}
@@ -0,0 +1,95 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use glam::Vec3;
+
+use crate::{CameraInput, CameraMatrices, CameraState, CameraUniform, Frustum};
+
+/// The camera renderers draw the world with. Lives in `Resources` (see `CameraModule`).
+///
+/// Gameplay/editor code moves it through `state_mut` or `push_input`; the module refreshes
+/// the matrices once per frame and renderers read `matrices`/`frustum` in their render stage.
+#[derive(Clone, Debug)]
+pub struct ActiveCamera {
+    state: CameraState,
+    input: Option<CameraInput>,
+    matrices: CameraMatrices,
+    frustum: Frustum,
+}
+
+impl Default for ActiveCamera {
+    #[inline]
+    fn default() -> Self {
+        Self::new(CameraState::default())
+    }
+}
+
+impl ActiveCamera {
+    pub fn new(mut state: CameraState) -> Self {
+        let (matrices, frustum) = state.update(None, 0.0);
+        Self {
+            state,
+            input: None,
+            matrices,
+            frustum,
+        }
+    }
+
+    #[inline]
+    pub fn state(&self) -> &CameraState {
+        &self.state
+    }
+
+    /// Direct access; changes are visible after the next `refresh`.
+    #[inline]
+    pub fn state_mut(&mut self) -> &mut CameraState {
+        &mut self.state
+    }
+
+    /// Accumulates controller input for the next `refresh`.
+    pub fn push_input(&mut self, input: CameraInput) {
+        let acc = self.input.get_or_insert_with(CameraInput::default);
+        acc.look_delta += input.look_delta;
+        acc.move_axis = input.move_axis;
+        acc.speed_mul = input.speed_mul;
+    }
+
+    #[inline]
+    pub fn look_at(&mut self, eye: Vec3, target: Vec3, up: Vec3) {
+        self.state.look_at(eye, target, up);
+        self.refresh(0.0);
+    }
+
+    /// Matches the projection to the render target; recomputes matrices if the size changed.
+    pub fn set_viewport(&mut self, width: u32, height: u32) {
+        let wh = glam::Vec2::new(width.max(1) as f32, height.max(1) as f32);
+        if self.state.viewport_wh == wh {
+            return;
+        }
+        self.state.set_viewport(width, height);
+        self.refresh(0.0);
+    }
+
+    /// Applies pending input and recomputes matrices and frustum.
+    pub fn refresh(&mut self, dt: f32) {
+        let input = self.input.take();
+        let (matrices, frustum) = self.state.update(input, dt);
+        self.matrices = matrices;
+        self.frustum = frustum;
+    }
+
+    #[inline]
+    pub fn matrices(&self) -> &CameraMatrices {
+        &self.matrices
+    }
+
+    #[inline]
+    pub fn frustum(&self) -> &Frustum {
+        &self.frustum
+    }
+
+    #[inline]
+    pub fn uniform(&self) -> CameraUniform {
+        let (near, far) = self.state.near_far();
+        self.matrices.to_uniform().with_near_far(near, far)
+    }
+}
@@ -35,6 +35,13 @@ impl Default for FreeFlyController {
 }
 
 impl FreeFlyController {
+    /// Takes yaw/pitch from the rig so the next `apply` continues from its orientation.
+    pub fn sync_from_rig(&mut self, rig: &CameraRig) {
+        let f = rig.forward();
+        self.yaw = (-f.x).atan2(-f.z);
+        self.pitch = f.y.clamp(-1.0, 1.0).asin().clamp(-self.pitch_limit, self.pitch_limit);
+    }
+
     #[inline]
     pub fn apply(&mut self, rig: &mut CameraRig, input: CameraInput, dt: f32) {
         let speed_mul = if input.speed_mul.is_finite() && input.speed_mul > 0.0 {
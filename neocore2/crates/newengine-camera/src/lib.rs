@@ -1,15 +1,21 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+pub mod active;
 pub mod controller;
 pub mod frustum;
+pub mod module;
 pub mod projection;
 pub mod rig;
 pub mod state;
 pub mod types;
 
+pub use active::*;
 pub use controller::*;
 pub use frustum::*;
+pub use module::*;
 pub use projection::*;
 pub use rig::*;
 pub use state::*;
 pub use types::*;
+
+pub use glam;
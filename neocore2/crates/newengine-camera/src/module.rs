@@ -0,0 +1,51 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_core::{EngineResult, Module, ModuleCtx};
+
+use crate::{ActiveCamera, CameraState};
+
+pub const CAMERA_MODULE_ID: &str = "camera";
+
+/// Registers the `ActiveCamera` resource and refreshes it every frame.
+///
+/// Register it before render controllers; they only set the viewport and read matrices.
+#[derive(Debug, Default)]
+pub struct CameraModule {
+    initial: Option<CameraState>,
+}
+
+impl CameraModule {
+    #[inline]
+    pub fn new(initial: CameraState) -> Self {
+        Self {
+            initial: Some(initial),
+        }
+    }
+}
+
+impl<E: Send + 'static> Module<E> for CameraModule {
+    fn id(&self) -> &'static str {
+        CAMERA_MODULE_ID
+    }
+
+    fn init(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if ctx.resources().get::<ActiveCamera>().is_none() {
+            let state = self.initial.take().unwrap_or_default();
+            ctx.resources_mut().insert(ActiveCamera::new(state));
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let dt = ctx.frame.map_or(0.0, |f| f.dt);
+        if let Some(cam) = ctx.resources_mut().get_mut::<ActiveCamera>() {
+            cam.refresh(dt);
+        }
+        Ok(())
+    }
+
+    fn shutdown(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let _ = ctx.resources_mut().remove::<ActiveCamera>();
+        Ok(())
+    }
+}
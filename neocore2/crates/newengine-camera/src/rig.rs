@@ -60,6 +60,14 @@ impl CameraRig {
         Mat4::from_translation(self.position) * Mat4::from_quat(self.rotation)
     }
 
+    /// Rig at `eye` looking at `target` (no roll).
+    #[inline]
+    pub fn looking_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let view = Mat4::look_at_rh(eye, target, up);
+        let (_, rotation, _) = view.inverse().to_scale_rotation_translation();
+        Self::new(eye, rotation)
+    }
+
     /// Adds a local-space translation (relative to the current rotation).
     #[inline]
     pub fn translate_local(&mut self, delta_local: Vec3) {
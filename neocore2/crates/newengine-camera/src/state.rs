@@ -1,6 +1,6 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
-use glam::{Mat4, Vec2};
+use glam::{Mat4, Vec2, Vec3};
 
 use crate::{CameraInput, CameraMatrices, CameraRig, FreeFlyController, Frustum, Projection};
 
@@ -16,6 +16,9 @@ pub struct CameraState {
 
     pub jitter: Vec2,
     pub viewport_wh: Vec2,
+
+    /// Vulkan clip space (Y down): flips the projection's Y axis.
+    pub flip_y: bool,
 }
 
 impl Default for CameraState {
@@ -31,6 +34,7 @@ impl Default for CameraState {
             controller: FreeFlyController::default(),
             jitter: Vec2::ZERO,
             viewport_wh: Vec2::new(1920.0, 1080.0),
+            flip_y: true,
         }
     }
 }
@@ -56,12 +60,24 @@ impl CameraState {
 
         // IMPORTANT: jitter is applied to projection (TAA-ready). For now we offset NDC.
         let proj = apply_jitter(proj, self.jitter, self.viewport_wh);
+        let proj = if self.flip_y {
+            Mat4::from_scale(Vec3::new(1.0, -1.0, 1.0)) * proj
+        } else {
+            proj
+        };
 
         let mats = CameraMatrices::new(view, proj, self.rig.position, self.viewport_wh, self.jitter);
         let frustum = Frustum::from_view_proj(mats.view_proj);
         (mats, frustum)
     }
 
+    /// Places the rig at `eye` facing `target` and re-syncs the controller.
+    #[inline]
+    pub fn look_at(&mut self, eye: Vec3, target: Vec3, up: Vec3) {
+        self.rig = CameraRig::looking_at(eye, target, up);
+        self.controller.sync_from_rig(&self.rig);
+    }
+
     #[inline]
     pub fn near_far(&self) -> (f32, f32) {
         self.projection.near_far()
@@ -80,5 +96,5 @@ fn apply_jitter(proj: Mat4, jitter: Vec2, viewport_wh: Vec2) -> Mat4 {
     // In clip space, translation lives in the 3rd column for RH perspective in typical conventions.
     // For robustness, we post-multiply by a translation in NDC.
     // This works for both perspective and ortho in practice.
-    proj * Mat4::from_translation(glam::Vec3::new(dx, dy, 0.0))
+    proj * Mat4::from_translation(Vec3::new(dx, dy, 0.0))
 }
\ No newline at end of file
@@ -0,0 +1,59 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Set to `1` to copy the generated header over the checked-in one.
+const WRITE_HEADER_ENV: &str = "NEWENGINE_CAPI_WRITE_HEADER";
+
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR"));
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR"));
+    let checked_in = crate_dir.join("include").join("newengine.h");
+    let generated = out_dir.join("newengine.h");
+
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=include/newengine.h");
+    println!("cargo:rerun-if-env-changed={WRITE_HEADER_ENV}");
+
+    // Generate into OUT_DIR so a build never writes to the source tree; the checked-in header
+    // is only replaced on request. A failure keeps the build going with the old header.
+    let config = match cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")) {
+        Ok(c) => c,
+        Err(e) => {
+            println!("cargo:warning=newengine-capi: cbindgen.toml: {e}");
+            return;
+        }
+    };
+    match cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+    {
+        Ok(bindings) => {
+            bindings.write_to_file(&generated);
+        }
+        Err(e) => {
+            println!("cargo:warning=newengine-capi: header not generated: {e}");
+            return;
+        }
+    }
+
+    let fresh = fs::read(&generated).unwrap_or_default();
+    if fs::read(&checked_in).ok().as_deref() == Some(fresh.as_slice()) {
+        return;
+    }
+    if env::var(WRITE_HEADER_ENV).is_ok_and(|v| v == "1") {
+        if let Err(e) = fs::write(&checked_in, &fresh) {
+            println!(
+                "cargo:warning=newengine-capi: {}: {e}",
+                checked_in.display()
+            );
+        }
+    } else {
+        println!(
+            "cargo:warning=newengine-capi: include/newengine.h is out of date; rebuild with \
+             {WRITE_HEADER_ENV}=1 to update it"
+        );
+    }
+}
@@ -0,0 +1,204 @@
+#ifndef NEWENGINE_H
+#define NEWENGINE_H
+
+/* Generated by cbindgen from crates/newengine-capi; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Version of this ABI; bumped when functions are added.
+#define NE_API_VERSION 1
+
+typedef enum NeLogLevel {
+  NE_LOG_LEVEL_ERROR = 1,
+  NE_LOG_LEVEL_WARN = 2,
+  NE_LOG_LEVEL_INFO = 3,
+  NE_LOG_LEVEL_DEBUG = 4,
+  NE_LOG_LEVEL_TRACE = 5,
+} NeLogLevel;
+
+// Result of every fallible call. Details are in [`ne_last_error`].
+typedef enum NeStatus {
+  NE_STATUS_OK = 0,
+  // Null handle or pointer, or a string that is not UTF-8.
+  NE_STATUS_INVALID_ARGUMENT = 1,
+  // The config could not be read or parsed.
+  NE_STATUS_CONFIG = 2,
+  // The engine returned an error (module init, plugin load, ...).
+  NE_STATUS_ENGINE = 3,
+  // The engine is shutting down; stop stepping and call `ne_engine_shutdown`.
+  NE_STATUS_EXIT_REQUESTED = 4,
+  // A call made in the wrong state (e.g. stepping before `ne_engine_start`).
+  NE_STATUS_INVALID_STATE = 5,
+  // A panic was caught at the boundary.
+  NE_STATUS_PANIC = 6,
+} NeStatus;
+
+// Startup configuration (the same settings as `config.json`). Opaque to C.
+typedef struct NeConfig NeConfig;
+
+// An engine instance. Opaque to C.
+typedef struct NeEngine NeEngine;
+
+// Receives every log record: level, target (module path) and message. The strings are
+// only valid during the call. May be called from any engine thread.
+typedef void (*NeLogCallback)(void *user, NeLogLevel level, const char *target, const char *msg);
+
+// Handles a console command. `args` is the text after the command name. Write the reply
+// (NUL-terminated, truncated to `out_cap` bytes) into `out` and return 0, or write an error
+// message and return non-zero.
+typedef int32_t (*NeCommandCallback)(void *user, const char *args, char *out, size_t out_cap);
+
+// Timing of the frame produced by [`ne_engine_step`].
+typedef struct NeFrame {
+  uint64_t frame_index;
+  // Seconds since the previous frame (clamped).
+  float dt;
+  float fixed_dt;
+  // Remainder of the fixed-step accumulator in `[0, 1)`, for render interpolation.
+  float fixed_alpha;
+  // Fixed steps run during this frame.
+  uint32_t fixed_step_count;
+  uint64_t fixed_tick;
+} NeFrame;
+
+// Called after every [`ne_engine_step`] with the frame it produced.
+typedef void (*NeFrameCallback)(void *user, const NeFrame *frame);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Routes engine logs to `cb`, or back to stderr with a null `cb`. Can be called before any
+// engine exists to capture startup logs.
+//
+// # Safety
+// `cb` stays callable until it is replaced; `user` is passed back as is.
+NeStatus ne_set_log_callback(NeLogCallback cb, void *user);
+
+// Adds a console command handled by the host; it shows up in `help` and completions like
+// module commands. `help` and `usage` may be null.
+//
+// # Safety
+// `name`, `help` and `usage` are NUL-terminated strings (or null where allowed); `cb`
+// stays callable until the command is unregistered.
+NeStatus ne_console_register_command(const char *name,
+                                     const char *help,
+                                     const char *usage,
+                                     NeCommandCallback cb,
+                                     void *user);
+
+// Removes a command added with [`ne_console_register_command`].
+//
+// # Safety
+// `name` is a NUL-terminated string.
+NeStatus ne_console_unregister_command(const char *name);
+
+// Runs a console line (as typed in the in-game console) and writes its output into `out`.
+// `out_len`, if non-null, receives the full output length; retry with a larger buffer when
+// it is `>= out_cap`. A failing command returns `Engine` with its message in `out`.
+//
+// # Safety
+// `line` is a NUL-terminated string; `out` is null or valid for `out_cap` bytes; `out_len`
+// is null or valid for a write.
+NeStatus ne_console_exec(const char *line, char *out, size_t out_cap, size_t *out_len);
+
+// New config with engine defaults. Free with [`ne_config_free`].
+NeConfig *ne_config_new(void);
+
+// Replaces `config` with the settings parsed from `json` (the `config.json` format).
+//
+// # Safety
+// `config` comes from [`ne_config_new`]; `json` is a NUL-terminated string.
+NeStatus ne_config_load_json(NeConfig *config, const char *json);
+
+// Replaces `config` with a config file; a missing file leaves the defaults.
+//
+// # Safety
+// `config` comes from [`ne_config_new`]; `path` is a NUL-terminated string.
+NeStatus ne_config_load_file(NeConfig *config, const char *path);
+
+// Directory plugins (`.dll` / `.so` modules) are loaded from.
+//
+// # Safety
+// `config` comes from [`ne_config_new`]; `path` is a NUL-terminated string.
+NeStatus ne_config_set_modules_dir(NeConfig *config, const char *path);
+
+// Root directory of the asset sources.
+//
+// # Safety
+// `config` comes from [`ne_config_new`]; `path` is a NUL-terminated string.
+NeStatus ne_config_set_assets_root(NeConfig *config, const char *path);
+
+// Frees a config. Null is ignored.
+//
+// # Safety
+// `config` comes from [`ne_config_new`] and is not used afterwards.
+void ne_config_free(NeConfig *config);
+
+// Creates an engine from `config`. `config` is only read; free it afterwards as usual.
+//
+// # Safety
+// `config` comes from `ne_config_new`; `out` is valid for a pointer write.
+NeStatus ne_engine_create(const NeConfig *config, NeEngine **out);
+
+// Loads plugins from the modules dir and starts every module.
+//
+// # Safety
+// `engine` comes from [`ne_engine_create`].
+NeStatus ne_engine_start(NeEngine *engine);
+
+// Runs one frame. Returns [`NeStatus::ExitRequested`] once the engine wants to stop (a
+// `quit` command, [`ne_engine_request_exit`], a module request). `out_frame` may be null.
+//
+// # Safety
+// `engine` comes from [`ne_engine_create`]; `out_frame` is null or valid for a write.
+NeStatus ne_engine_step(NeEngine *engine, NeFrame *out_frame);
+
+// Asks the engine to stop; the next [`ne_engine_step`] returns `ExitRequested`.
+//
+// # Safety
+// `engine` comes from [`ne_engine_create`].
+NeStatus ne_engine_request_exit(NeEngine *engine);
+
+// Posts `len` bytes under `topic` on the engine's type-erased bus, where modules pick it
+// up as a `BusMessage` with a bytes payload. `data` may be null when `len` is 0.
+//
+// # Safety
+// `engine` comes from [`ne_engine_create`]; `topic` is a NUL-terminated string; `data` is
+// valid for reads of `len` bytes.
+NeStatus ne_engine_send_event(NeEngine *engine, const char *topic, const uint8_t *data, size_t len);
+
+// Sets (or clears, with a null `cb`) the per-frame callback. `user` is passed back as is.
+//
+// # Safety
+// `engine` comes from [`ne_engine_create`].
+NeStatus ne_engine_set_frame_callback(NeEngine *engine, NeFrameCallback cb, void *user);
+
+// Shuts down modules and plugins and saves persistent cvars. Safe to call twice.
+//
+// # Safety
+// `engine` comes from [`ne_engine_create`].
+NeStatus ne_engine_shutdown(NeEngine *engine);
+
+// Destroys an engine, shutting it down first if needed. Null is ignored.
+//
+// # Safety
+// `engine` comes from [`ne_engine_create`] and is not used afterwards.
+void ne_engine_destroy(NeEngine *engine);
+
+// Message of the last failed call on this thread; empty if none. Valid until the next
+// failing call on the same thread.
+const char *ne_last_error(void);
+
+// [`NE_API_VERSION`] of the loaded library.
+uint32_t ne_api_version(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* NEWENGINE_H */
@@ -0,0 +1,249 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::status::{arg_str, fail, guard, write_out, NeStatus};
+
+use newengine_core::console::{method, ConsoleCommands, COMMAND_SERVICE_ID};
+use std::ffi::{c_char, c_void, CString};
+use std::sync::{Once, RwLock};
+
+/// Opaque host pointer handed back to callbacks.
+///
+/// The library never dereferences it; the host is responsible for it being usable from
+/// whatever thread invokes the callback.
+#[derive(Clone, Copy)]
+pub(crate) struct UserData(pub(crate) *mut c_void);
+
+// SAFETY: only passed back to host callbacks, never dereferenced here (see above).
+unsafe impl Send for UserData {}
+// SAFETY: as above.
+unsafe impl Sync for UserData {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NeLogLevel {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl From<log::Level> for NeLogLevel {
+    #[inline]
+    fn from(l: log::Level) -> Self {
+        match l {
+            log::Level::Error => NeLogLevel::Error,
+            log::Level::Warn => NeLogLevel::Warn,
+            log::Level::Info => NeLogLevel::Info,
+            log::Level::Debug => NeLogLevel::Debug,
+            log::Level::Trace => NeLogLevel::Trace,
+        }
+    }
+}
+
+/// Receives every log record: level, target (module path) and message. The strings are
+/// only valid during the call. May be called from any engine thread.
+pub type NeLogCallback =
+    extern "C" fn(user: *mut c_void, level: NeLogLevel, target: *const c_char, msg: *const c_char);
+
+/// Handles a console command. `args` is the text after the command name. Write the reply
+/// (NUL-terminated, truncated to `out_cap` bytes) into `out` and return 0, or write an error
+/// message and return non-zero.
+pub type NeCommandCallback =
+    extern "C" fn(user: *mut c_void, args: *const c_char, out: *mut c_char, out_cap: usize) -> i32;
+
+/// Reply buffer handed to [`NeCommandCallback`].
+const COMMAND_OUT_CAP: usize = 4096;
+
+static LOG_CALLBACK: RwLock<Option<(NeLogCallback, UserData)>> = RwLock::new(None);
+static LOGGER_INIT: Once = Once::new();
+
+struct CapiLogger;
+
+static LOGGER: CapiLogger = CapiLogger;
+
+impl log::Log for CapiLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let cb = LOG_CALLBACK.read().ok().and_then(|g| *g);
+        match cb {
+            Some((cb, user)) => {
+                let target = CString::new(record.target().replace('\0', " ")).unwrap_or_default();
+                let msg =
+                    CString::new(record.args().to_string().replace('\0', " ")).unwrap_or_default();
+                cb(user.0, record.level().into(), target.as_ptr(), msg.as_ptr());
+            }
+            None => eprintln!(
+                "[{}] {}: {}",
+                record.level(),
+                record.target(),
+                record.args()
+            ),
+        }
+        newengine_core::log_tap::dispatch(record);
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the forwarding logger once per process; later calls only adjust the level.
+/// Leaves an already installed logger (a Rust host's own) alone.
+pub(crate) fn install_logger(level: &str) {
+    LOGGER_INIT.call_once(|| {
+        if log::set_logger(&LOGGER).is_err() {
+            log::debug!("capi: a logger is already installed; log callback not used");
+        }
+    });
+    let filter = level
+        .trim()
+        .parse::<log::LevelFilter>()
+        .unwrap_or(log::LevelFilter::Info);
+    log::set_max_level(filter);
+}
+
+/// Routes engine logs to `cb`, or back to stderr with a null `cb`. Can be called before any
+/// engine exists to capture startup logs.
+///
+/// # Safety
+/// `cb` stays callable until it is replaced; `user` is passed back as is.
+#[no_mangle]
+pub unsafe extern "C" fn ne_set_log_callback(
+    cb: Option<NeLogCallback>,
+    user: *mut c_void,
+) -> NeStatus {
+    guard(|| {
+        install_logger(&log::max_level().to_string());
+        match LOG_CALLBACK.write() {
+            Ok(mut g) => {
+                *g = cb.map(|cb| (cb, UserData(user)));
+                Ok(())
+            }
+            Err(_) => fail(NeStatus::InvalidState, "log callback lock poisoned"),
+        }
+    })
+}
+
+/// Adds a console command handled by the host; it shows up in `help` and completions like
+/// module commands. `help` and `usage` may be null.
+///
+/// # Safety
+/// `name`, `help` and `usage` are NUL-terminated strings (or null where allowed); `cb`
+/// stays callable until the command is unregistered.
+#[no_mangle]
+pub unsafe extern "C" fn ne_console_register_command(
+    name: *const c_char,
+    help: *const c_char,
+    usage: *const c_char,
+    cb: NeCommandCallback,
+    user: *mut c_void,
+) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let name = unsafe { arg_str(name, "name")? };
+        // SAFETY: caller contract.
+        let help = if help.is_null() {
+            ""
+        } else {
+            unsafe { arg_str(help, "help")? }
+        };
+        // SAFETY: caller contract.
+        let usage = if usage.is_null() {
+            format!("{name} [args]")
+        } else {
+            unsafe { arg_str(usage, "usage")? }.to_owned()
+        };
+
+        let user = UserData(user);
+        let handler = move |args: &str| -> Result<String, String> {
+            let args = CString::new(args.replace('\0', " ")).unwrap_or_default();
+            // Capture the whole wrapper, not the raw pointer field, so the closure stays Send.
+            let user = user;
+            let mut out = vec![0 as c_char; COMMAND_OUT_CAP];
+            let rc = cb(user.0, args.as_ptr(), out.as_mut_ptr(), out.len());
+            // Guarantee termination even if the host filled the buffer.
+            out[COMMAND_OUT_CAP - 1] = 0;
+            let bytes: Vec<u8> = out
+                .iter()
+                .take_while(|&&c| c != 0)
+                .map(|&c| c as u8)
+                .collect();
+            let text = String::from_utf8_lossy(&bytes).into_owned();
+            if rc == 0 {
+                Ok(text)
+            } else {
+                Err(text)
+            }
+        };
+
+        ConsoleCommands
+            .register_command_with_usage(name, &usage, handler, help)
+            .or_else(|e| fail(NeStatus::InvalidArgument, e.to_string()))
+    })
+}
+
+/// Removes a command added with [`ne_console_register_command`].
+///
+/// # Safety
+/// `name` is a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ne_console_unregister_command(name: *const c_char) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let name = unsafe { arg_str(name, "name")? };
+        if ConsoleCommands.unregister_command(name) {
+            Ok(())
+        } else {
+            fail(NeStatus::InvalidArgument, format!("not registered: {name}"))
+        }
+    })
+}
+
+/// Runs a console line (as typed in the in-game console) and writes its output into `out`.
+/// `out_len`, if non-null, receives the full output length; retry with a larger buffer when
+/// it is `>= out_cap`. A failing command returns `Engine` with its message in `out`.
+///
+/// # Safety
+/// `line` is a NUL-terminated string; `out` is null or valid for `out_cap` bytes; `out_len`
+/// is null or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn ne_console_exec(
+    line: *const c_char,
+    out: *mut c_char,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let line = unsafe { arg_str(line, "line")? };
+        let reply =
+            newengine_core::call_service_v1(COMMAND_SERVICE_ID, method::EXEC, line.as_bytes())
+                .or_else(|e| fail(NeStatus::Engine, e))?;
+        let v: serde_json::Value = serde_json::from_slice(&reply)
+            .or_else(|e| fail(NeStatus::Engine, format!("bad console reply: {e}")))?;
+
+        let ok = v.get("ok").and_then(|x| x.as_bool()).unwrap_or(false);
+        let text = v
+            .get(if ok { "output" } else { "error" })
+            .and_then(|x| x.as_str())
+            .unwrap_or_default();
+
+        // SAFETY: caller contract.
+        let n = unsafe { write_out(text, out, out_cap) };
+        if !out_len.is_null() {
+            // SAFETY: non-null and writable per the caller contract.
+            unsafe { *out_len = n };
+        }
+        if ok {
+            Ok(())
+        } else {
+            fail(NeStatus::Engine, text.to_owned())
+        }
+    })
+}
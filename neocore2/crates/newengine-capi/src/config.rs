@@ -0,0 +1,127 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::status::{arg_str, fail, guard, NeStatus};
+
+use newengine_core::{ConfigPaths, StartupConfig, StartupLoader};
+use std::ffi::c_char;
+use std::path::PathBuf;
+
+/// Startup configuration (the same settings as `config.json`). Opaque to C.
+pub struct NeConfig {
+    pub(crate) startup: StartupConfig,
+}
+
+/// New config with engine defaults. Free with [`ne_config_free`].
+#[no_mangle]
+pub extern "C" fn ne_config_new() -> *mut NeConfig {
+    Box::into_raw(Box::new(NeConfig {
+        startup: StartupConfig::default(),
+    }))
+}
+
+/// Replaces `config` with the settings parsed from `json` (the `config.json` format).
+///
+/// # Safety
+/// `config` comes from [`ne_config_new`]; `json` is a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ne_config_load_json(
+    config: *mut NeConfig,
+    json: *const c_char,
+) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let config = unsafe { config_mut(config)? };
+        // SAFETY: caller contract.
+        let json = unsafe { arg_str(json, "json")? };
+        let (startup, report) = StartupLoader::load_json_str(json)
+            .or_else(|e| fail(NeStatus::Config, e.to_string()))?;
+        for w in report.warnings.iter() {
+            log::warn!("capi.config: {w}");
+        }
+        config.startup = startup;
+        Ok(())
+    })
+}
+
+/// Replaces `config` with a config file; a missing file leaves the defaults.
+///
+/// # Safety
+/// `config` comes from [`ne_config_new`]; `path` is a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ne_config_load_file(
+    config: *mut NeConfig,
+    path: *const c_char,
+) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let config = unsafe { config_mut(config)? };
+        // SAFETY: caller contract.
+        let path = unsafe { arg_str(path, "path")? };
+        let (startup, report) = StartupLoader::load_json(&ConfigPaths::from_startup_str(path))
+            .or_else(|e| fail(NeStatus::Config, e.to_string()))?;
+        for w in report.warnings.iter() {
+            log::warn!("capi.config: {w}");
+        }
+        config.startup = startup;
+        Ok(())
+    })
+}
+
+/// Directory plugins (`.dll` / `.so` modules) are loaded from.
+///
+/// # Safety
+/// `config` comes from [`ne_config_new`]; `path` is a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ne_config_set_modules_dir(
+    config: *mut NeConfig,
+    path: *const c_char,
+) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let config = unsafe { config_mut(config)? };
+        // SAFETY: caller contract.
+        config.startup.modules_dir = PathBuf::from(unsafe { arg_str(path, "path")? });
+        Ok(())
+    })
+}
+
+/// Root directory of the asset sources.
+///
+/// # Safety
+/// `config` comes from [`ne_config_new`]; `path` is a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ne_config_set_assets_root(
+    config: *mut NeConfig,
+    path: *const c_char,
+) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let config = unsafe { config_mut(config)? };
+        // SAFETY: caller contract.
+        config.startup.assets_root = PathBuf::from(unsafe { arg_str(path, "path")? });
+        Ok(())
+    })
+}
+
+/// Frees a config. Null is ignored.
+///
+/// # Safety
+/// `config` comes from [`ne_config_new`] and is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ne_config_free(config: *mut NeConfig) {
+    if !config.is_null() {
+        // SAFETY: allocated by `ne_config_new`, freed once per the caller contract.
+        drop(unsafe { Box::from_raw(config) });
+    }
+}
+
+/// # Safety
+/// `config` is null or a live pointer from [`ne_config_new`].
+#[inline]
+unsafe fn config_mut<'a>(config: *mut NeConfig) -> Result<&'a mut NeConfig, NeStatus> {
+    // SAFETY: caller contract.
+    match unsafe { config.as_mut() } {
+        Some(c) => Ok(c),
+        None => fail(NeStatus::InvalidArgument, "config is null"),
+    }
+}
@@ -0,0 +1,290 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::callbacks::{install_logger, UserData};
+use crate::config::NeConfig;
+use crate::status::{arg_str, fail, guard, NeStatus};
+
+use crossbeam_channel::unbounded;
+use newengine_core::render::NullRenderModule;
+use newengine_core::{
+    Bus, BusMessage, Engine, EngineConfig, EngineError, Frame, Services, ShutdownToken,
+    StartupConfig,
+};
+use std::ffi::{c_char, c_void};
+
+const FIXED_DT_MS: u32 = 16;
+
+/// Timing of the frame produced by [`ne_engine_step`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NeFrame {
+    pub frame_index: u64,
+    /// Seconds since the previous frame (clamped).
+    pub dt: f32,
+    pub fixed_dt: f32,
+    /// Remainder of the fixed-step accumulator in `[0, 1)`, for render interpolation.
+    pub fixed_alpha: f32,
+    /// Fixed steps run during this frame.
+    pub fixed_step_count: u32,
+    pub fixed_tick: u64,
+}
+
+impl From<Frame> for NeFrame {
+    #[inline]
+    fn from(f: Frame) -> Self {
+        Self {
+            frame_index: f.frame_index,
+            dt: f.dt,
+            fixed_dt: f.fixed_dt,
+            fixed_alpha: f.fixed_alpha,
+            fixed_step_count: f.fixed_step_count,
+            fixed_tick: f.fixed_tick,
+        }
+    }
+}
+
+/// Called after every [`ne_engine_step`] with the frame it produced.
+pub type NeFrameCallback = extern "C" fn(user: *mut c_void, frame: *const NeFrame);
+
+struct HostServices;
+
+impl Services for HostServices {
+    #[inline]
+    fn logger(&self) -> &dyn log::Log {
+        log::logger()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Created,
+    Started,
+    ShutDown,
+}
+
+/// An engine instance. Opaque to C.
+pub struct NeEngine {
+    engine: Engine<()>,
+    state: State,
+    frame_cb: Option<(NeFrameCallback, UserData)>,
+}
+
+fn build_engine(startup: &StartupConfig) -> Result<Engine<()>, EngineError> {
+    let (tx, rx) = unbounded::<()>();
+    let bus: Bus<()> = Bus::new(tx, rx);
+
+    let config = EngineConfig::from_startup(FIXED_DT_MS, startup);
+    let mut engine: Engine<()> =
+        Engine::new_with_config(config, Box::new(HostServices), bus, ShutdownToken::new())?;
+
+    // Embedded engines have no window; only the headless backend can be created here.
+    if startup.render_backend.trim().eq_ignore_ascii_case("null") {
+        engine.register_module(Box::new(NullRenderModule::new()))?;
+    } else {
+        log::info!(
+            "capi: render backend '{}' needs a window; running without a renderer",
+            startup.render_backend
+        );
+    }
+
+    Ok(engine)
+}
+
+/// Creates an engine from `config`. `config` is only read; free it afterwards as usual.
+///
+/// # Safety
+/// `config` comes from `ne_config_new`; `out` is valid for a pointer write.
+#[no_mangle]
+pub unsafe extern "C" fn ne_engine_create(
+    config: *const NeConfig,
+    out: *mut *mut NeEngine,
+) -> NeStatus {
+    guard(|| {
+        if out.is_null() {
+            return fail(NeStatus::InvalidArgument, "out is null");
+        }
+        // SAFETY: caller contract.
+        let Some(config) = (unsafe { config.as_ref() }) else {
+            return fail(NeStatus::InvalidArgument, "config is null");
+        };
+
+        install_logger(&config.startup.log_level);
+        newengine_core::cvars::apply_startup_cvars(&config.startup);
+
+        let engine =
+            build_engine(&config.startup).or_else(|e| fail(NeStatus::Engine, e.to_string()))?;
+        let handle = Box::new(NeEngine {
+            engine,
+            state: State::Created,
+            frame_cb: None,
+        });
+        // SAFETY: checked non-null above.
+        unsafe { *out = Box::into_raw(handle) };
+        Ok(())
+    })
+}
+
+/// Loads plugins from the modules dir and starts every module.
+///
+/// # Safety
+/// `engine` comes from [`ne_engine_create`].
+#[no_mangle]
+pub unsafe extern "C" fn ne_engine_start(engine: *mut NeEngine) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let e = unsafe { engine_mut(engine)? };
+        if e.state != State::Created {
+            return fail(NeStatus::InvalidState, "engine already started");
+        }
+        e.engine.load_plugins_once().or_else(map_engine_err)?;
+        e.engine.start().or_else(map_engine_err)?;
+        e.state = State::Started;
+        Ok(())
+    })
+}
+
+/// Runs one frame. Returns [`NeStatus::ExitRequested`] once the engine wants to stop (a
+/// `quit` command, [`ne_engine_request_exit`], a module request). `out_frame` may be null.
+///
+/// # Safety
+/// `engine` comes from [`ne_engine_create`]; `out_frame` is null or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn ne_engine_step(
+    engine: *mut NeEngine,
+    out_frame: *mut NeFrame,
+) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let e = unsafe { engine_mut(engine)? };
+        if e.state != State::Started {
+            return fail(NeStatus::InvalidState, "engine is not running");
+        }
+        let frame = NeFrame::from(e.engine.step_frame().or_else(map_engine_err)?);
+        if !out_frame.is_null() {
+            // SAFETY: non-null and writable per the caller contract.
+            unsafe { *out_frame = frame };
+        }
+        if let Some((cb, user)) = e.frame_cb {
+            cb(user.0, &frame);
+        }
+        Ok(())
+    })
+}
+
+/// Asks the engine to stop; the next [`ne_engine_step`] returns `ExitRequested`.
+///
+/// # Safety
+/// `engine` comes from [`ne_engine_create`].
+#[no_mangle]
+pub unsafe extern "C" fn ne_engine_request_exit(engine: *mut NeEngine) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let e = unsafe { engine_mut(engine)? };
+        e.engine.request_exit().or_else(map_engine_err)
+    })
+}
+
+/// Posts `len` bytes under `topic` on the engine's type-erased bus, where modules pick it
+/// up as a `BusMessage` with a bytes payload. `data` may be null when `len` is 0.
+///
+/// # Safety
+/// `engine` comes from [`ne_engine_create`]; `topic` is a NUL-terminated string; `data` is
+/// valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ne_engine_send_event(
+    engine: *mut NeEngine,
+    topic: *const c_char,
+    data: *const u8,
+    len: usize,
+) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let e = unsafe { engine_mut(engine)? };
+        // SAFETY: caller contract.
+        let topic = unsafe { arg_str(topic, "topic")? };
+        let bytes = if len == 0 {
+            Vec::new()
+        } else if data.is_null() {
+            return fail(NeStatus::InvalidArgument, "data is null");
+        } else {
+            // SAFETY: non-null and readable for `len` bytes per the caller contract.
+            unsafe { std::slice::from_raw_parts(data, len) }.to_vec()
+        };
+        e.engine
+            .any_bus()
+            .send(BusMessage::bytes(topic.to_owned(), bytes))
+            .or_else(|_| fail(NeStatus::Engine, "event bus closed"))
+    })
+}
+
+/// Sets (or clears, with a null `cb`) the per-frame callback. `user` is passed back as is.
+///
+/// # Safety
+/// `engine` comes from [`ne_engine_create`].
+#[no_mangle]
+pub unsafe extern "C" fn ne_engine_set_frame_callback(
+    engine: *mut NeEngine,
+    cb: Option<NeFrameCallback>,
+    user: *mut c_void,
+) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let e = unsafe { engine_mut(engine)? };
+        e.frame_cb = cb.map(|cb| (cb, UserData(user)));
+        Ok(())
+    })
+}
+
+/// Shuts down modules and plugins and saves persistent cvars. Safe to call twice.
+///
+/// # Safety
+/// `engine` comes from [`ne_engine_create`].
+#[no_mangle]
+pub unsafe extern "C" fn ne_engine_shutdown(engine: *mut NeEngine) -> NeStatus {
+    guard(|| {
+        // SAFETY: caller contract.
+        let e = unsafe { engine_mut(engine)? };
+        shutdown(e)
+    })
+}
+
+/// Destroys an engine, shutting it down first if needed. Null is ignored.
+///
+/// # Safety
+/// `engine` comes from [`ne_engine_create`] and is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ne_engine_destroy(engine: *mut NeEngine) {
+    if engine.is_null() {
+        return;
+    }
+    // SAFETY: allocated by `ne_engine_create`, freed once per the caller contract.
+    let mut e = unsafe { Box::from_raw(engine) };
+    let _ = guard(move || shutdown(&mut e));
+}
+
+fn shutdown(e: &mut NeEngine) -> Result<(), NeStatus> {
+    if e.state != State::Started {
+        return Ok(());
+    }
+    e.state = State::ShutDown;
+    e.engine.shutdown().or_else(map_engine_err)
+}
+
+#[inline]
+fn map_engine_err<T>(e: EngineError) -> Result<T, NeStatus> {
+    match e {
+        EngineError::ExitRequested => fail(NeStatus::ExitRequested, "exit requested"),
+        e => fail(NeStatus::Engine, e.to_string()),
+    }
+}
+
+/// # Safety
+/// `engine` is null or a live pointer from [`ne_engine_create`].
+#[inline]
+unsafe fn engine_mut<'a>(engine: *mut NeEngine) -> Result<&'a mut NeEngine, NeStatus> {
+    // SAFETY: caller contract.
+    match unsafe { engine.as_mut() } {
+        Some(e) => Ok(e),
+        None => fail(NeStatus::InvalidArgument, "engine is null"),
+    }
+}
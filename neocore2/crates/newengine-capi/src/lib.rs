@@ -0,0 +1,48 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! C ABI for embedding the engine in C, C++ and C# hosts.
+//!
+//! The header is `include/newengine.h`. The build script generates it into `OUT_DIR`, warns
+//! when the checked-in copy is stale and replaces it only with `NEWENGINE_CAPI_WRITE_HEADER=1`.
+//! A host builds a config ([`ne_config_new`], [`ne_config_load_json`]), creates an engine
+//! ([`ne_engine_create`]), starts it and calls [`ne_engine_step`] until it reports
+//! [`NeStatus::ExitRequested`], then shuts it down and destroys it.
+//!
+//! Rules for every function:
+//! - strings are NUL-terminated UTF-8; returned strings are owned by the library;
+//! - a non-`Ok` [`NeStatus`] leaves a message for [`ne_last_error`] on the calling thread;
+//! - panics never cross the boundary, they become [`NeStatus::Panic`];
+//! - an engine handle is used from one thread at a time.
+//!
+//! The ABI only grows: functions and enum values are added, never changed. Check
+//! [`ne_api_version`] against [`NE_API_VERSION`] from the header when loading the library
+//! dynamically.
+
+mod callbacks;
+mod config;
+mod engine;
+mod status;
+
+pub use callbacks::{
+    ne_console_exec, ne_console_register_command, ne_console_unregister_command,
+    ne_set_log_callback, NeCommandCallback, NeLogCallback, NeLogLevel,
+};
+pub use config::{
+    ne_config_free, ne_config_load_file, ne_config_load_json, ne_config_new,
+    ne_config_set_assets_root, ne_config_set_modules_dir, NeConfig,
+};
+pub use engine::{
+    ne_engine_create, ne_engine_destroy, ne_engine_request_exit, ne_engine_send_event,
+    ne_engine_set_frame_callback, ne_engine_shutdown, ne_engine_start, ne_engine_step, NeEngine,
+    NeFrame, NeFrameCallback,
+};
+pub use status::{ne_last_error, NeStatus};
+
+/// Version of this ABI; bumped when functions are added.
+pub const NE_API_VERSION: u32 = 1;
+
+/// [`NE_API_VERSION`] of the loaded library.
+#[no_mangle]
+pub extern "C" fn ne_api_version() -> u32 {
+    NE_API_VERSION
+}
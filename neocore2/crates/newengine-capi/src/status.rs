@@ -0,0 +1,99 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Result of every fallible call. Details are in [`ne_last_error`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NeStatus {
+    Ok = 0,
+    /// Null handle or pointer, or a string that is not UTF-8.
+    InvalidArgument = 1,
+    /// The config could not be read or parsed.
+    Config = 2,
+    /// The engine returned an error (module init, plugin load, ...).
+    Engine = 3,
+    /// The engine is shutting down; stop stepping and call `ne_engine_shutdown`.
+    ExitRequested = 4,
+    /// A call made in the wrong state (e.g. stepping before `ne_engine_start`).
+    InvalidState = 5,
+    /// A panic was caught at the boundary.
+    Panic = 6,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+pub(crate) fn set_last_error(msg: impl Into<String>) {
+    let msg = msg.into().replace('\0', " ");
+    LAST_ERROR.with(|e| *e.borrow_mut() = CString::new(msg).unwrap_or_default());
+}
+
+/// Message of the last failed call on this thread; empty if none. Valid until the next
+/// failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn ne_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+/// Records `msg` and returns `status`, for `?` in the entry points.
+#[inline]
+pub(crate) fn fail<T>(status: NeStatus, msg: impl Into<String>) -> Result<T, NeStatus> {
+    set_last_error(msg);
+    Err(status)
+}
+
+/// Runs an entry point body, turning errors and panics into a status.
+pub(crate) fn guard(f: impl FnOnce() -> Result<(), NeStatus>) -> NeStatus {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => NeStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(p) => {
+            let msg = p
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| p.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_owned());
+            set_last_error(format!("panic: {msg}"));
+            NeStatus::Panic
+        }
+    }
+}
+
+/// Borrows a C string argument.
+///
+/// # Safety
+/// `p` is null or points to a NUL-terminated string that outlives the call.
+pub(crate) unsafe fn arg_str<'a>(p: *const c_char, what: &str) -> Result<&'a str, NeStatus> {
+    if p.is_null() {
+        return fail(NeStatus::InvalidArgument, format!("{what} is null"));
+    }
+    // SAFETY: non-null and NUL-terminated per the caller contract.
+    let s = unsafe { CStr::from_ptr(p) };
+    s.to_str()
+        .or_else(|_| fail(NeStatus::InvalidArgument, format!("{what} is not utf-8")))
+}
+
+/// Copies `s` into a caller buffer of `cap` bytes, truncating at a char boundary and always
+/// NUL-terminating. Returns the full length of `s` so callers can retry with a bigger buffer.
+///
+/// # Safety
+/// `out` is null or valid for writes of `cap` bytes.
+pub(crate) unsafe fn write_out(s: &str, out: *mut c_char, cap: usize) -> usize {
+    if out.is_null() || cap == 0 {
+        return s.len();
+    }
+    let mut n = s.len().min(cap - 1);
+    while !s.is_char_boundary(n) {
+        n -= 1;
+    }
+    // SAFETY: `n + 1 <= cap` bytes fit in `out`; the ranges cannot overlap.
+    unsafe {
+        std::ptr::copy_nonoverlapping(s.as_ptr(), out.cast::<u8>(), n);
+        *out.add(n) = 0;
+    }
+    s.len()
+}
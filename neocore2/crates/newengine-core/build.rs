@@ -0,0 +1,73 @@
+use std::env;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let target = env::var("TARGET").unwrap_or_else(|_| "unknown".to_owned());
+    let profile = env::var("PROFILE").unwrap_or_else(|_| "debug".to_owned());
+
+    println!("cargo:rustc-env=NEWENGINE_BUILD_TARGET={target}");
+    println!("cargo:rustc-env=NEWENGINE_BUILD_PROFILE={profile}");
+    println!("cargo:rustc-env=NEWENGINE_BUILD_GIT_HASH={}", git_hash());
+    println!("cargo:rustc-env=NEWENGINE_BUILD_DATE={}", build_date());
+
+    // Re-stamp on commit/checkout. Packaged builds without a .git directory keep the env override.
+    if let Some(head) = git(&["rev-parse", "--git-path", "HEAD"]) {
+        println!("cargo:rerun-if-changed={head}");
+    }
+    if let Some(refs) = git(&["rev-parse", "--git-path", "refs"]) {
+        println!("cargo:rerun-if-changed={refs}");
+    }
+    println!("cargo:rerun-if-env-changed=NEWENGINE_GIT_HASH");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let out = Command::new("git").args(args).output().ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let s = String::from_utf8(out.stdout).ok()?.trim().to_owned();
+    (!s.is_empty()).then_some(s)
+}
+
+fn git_hash() -> String {
+    if let Ok(h) = env::var("NEWENGINE_GIT_HASH") {
+        return h;
+    }
+    let Some(hash) = git(&["rev-parse", "--short=12", "HEAD"]) else {
+        return "unknown".to_owned();
+    };
+    let dirty = git(&["status", "--porcelain", "--untracked-files=no"]).is_some();
+    if dirty {
+        format!("{hash}-dirty")
+    } else {
+        hash
+    }
+}
+
+/// UTC `YYYY-MM-DD`. Honors `SOURCE_DATE_EPOCH` for reproducible builds.
+fn build_date() -> String {
+    let secs = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+
+    // Civil-from-days (Howard Hinnant), days since 1970-01-01.
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+
+    format!("{y:04}-{m:02}-{d:02}")
+}
@@ -1,16 +1,36 @@
 use log::info;
 use newengine_assets::{
-    AssetBlob, AssetError, AssetEvent, AssetId, AssetKey, AssetSource, AssetState, AssetStore,
-    BlobImporterDispatch, FileSystemSource, PumpBudget,
+    ArchiveSource, AssetBlob, AssetCache, AssetError, AssetEvent, AssetHandle, AssetId, AssetKey, AssetQuery,
+    AssetQueryResult, AssetServer, AssetSource, AssetState, AssetStore, BlobImporterDispatch, ContentServer, EngineContent, FileSystemSource, LoadCancel,
+    MaterialImporter, MemoryBudget, PathCase,
+    ProceduralTextureImporter, PumpBudget, RemoteImportSource, SpirvShaderImporter,
+    VALIDATION_RULES_PATH,
 };
-use std::path::PathBuf;
+use crate::sync::CancelToken;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct AssetManagerConfig {
     pub root: PathBuf,
     pub pump_steps: u32,
+    /// Threads running imports off the pumping thread (0 imports inline in `pump`).
+    pub import_workers: usize,
+    /// Resident blob limits per category (see `MemoryBudget`).
+    pub memory_budget: MemoryBudget,
     pub enable_filesystem_source: bool,
+    /// Persistent import cache directory (`None` disables the cache).
+    pub cache_dir: Option<PathBuf>,
+    /// `.zip` / `.nepak` archives mounted after the filesystem source (loose files win).
+    pub archives: Vec<PathBuf>,
+    /// Logical path matching against sources.
+    pub path_case: PathCase,
+    /// Serve imports to other workstations on this address (asset server mode).
+    pub serve_imports: Option<String>,
+    /// Fetch imports from the asset server at this address before importing locally.
+    pub remote_imports: Option<String>,
+    /// Serve raw sources over HTTP to embedded web views on this address.
+    pub content_server: Option<String>,
 }
 
 impl AssetManagerConfig {
@@ -19,7 +39,15 @@ impl AssetManagerConfig {
         Self {
             root,
             pump_steps: 8,
+            import_workers: 2,
+            memory_budget: MemoryBudget::unlimited(),
             enable_filesystem_source: true,
+            cache_dir: None,
+            archives: Vec::new(),
+            path_case: PathCase::Sensitive,
+            serve_imports: None,
+            remote_imports: None,
+            content_server: None,
         }
     }
 
@@ -29,17 +57,77 @@ impl AssetManagerConfig {
         self
     }
 
+    #[inline]
+    pub fn with_import_workers(mut self, count: usize) -> Self {
+        self.import_workers = count;
+        self
+    }
+
+    #[inline]
+    pub fn with_memory_budget(mut self, budget: MemoryBudget) -> Self {
+        self.memory_budget = budget;
+        self
+    }
+
     #[inline]
     pub fn with_filesystem_source(mut self, enabled: bool) -> Self {
         self.enable_filesystem_source = enabled;
         self
     }
+
+    /// Enables the persistent import cache (e.g. `.cache/assets`). Unchanged sources
+    /// imported by the same importer version are served from disk instead of re-imported.
+    #[inline]
+    pub fn with_cache_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.cache_dir = dir;
+        self
+    }
+
+    /// Mounts a packed archive as an additional source.
+    #[inline]
+    pub fn with_archive(mut self, path: impl Into<PathBuf>) -> Self {
+        self.archives.push(path.into());
+        self
+    }
+
+    /// Falls back to case-insensitive lookups when a path is missing as spelled.
+    #[inline]
+    pub fn with_path_case(mut self, case: PathCase) -> Self {
+        self.path_case = case;
+        self
+    }
+
+    /// Runs an asset server on `addr` (e.g. `0.0.0.0:7878`), answering import requests from
+    /// editors configured with `with_remote_imports`. Imports it serves land in the cache.
+    #[inline]
+    pub fn with_asset_server(mut self, addr: Option<String>) -> Self {
+        self.serve_imports = addr;
+        self
+    }
+
+    /// Asks the asset server at `addr` for imports missing from the local cache.
+    #[inline]
+    pub fn with_remote_imports(mut self, addr: Option<String>) -> Self {
+        self.remote_imports = addr;
+        self
+    }
+
+    /// Runs a static HTTP server on `addr` (e.g. `127.0.0.1:0`) that serves source files to
+    /// HTML UI views which cannot use the `engine://` scheme directly.
+    #[inline]
+    pub fn with_content_server(mut self, addr: Option<String>) -> Self {
+        self.content_server = addr;
+        self
+    }
 }
 
 pub struct AssetManager {
     store: Arc<AssetStore>,
+    root: PathBuf,
     budget: PumpBudget,
     importers_dir: PathBuf,
+    server: Option<AssetServer>,
+    content_server: Option<ContentServer>,
 }
 
 impl AssetManager {
@@ -69,6 +157,12 @@ impl AssetManager {
         }
 
         let store = Arc::new(AssetStore::new());
+        let root = config.root.clone();
+        store.set_path_case(config.path_case);
+        store.set_import_workers(config.import_workers);
+        if config.memory_budget != MemoryBudget::unlimited() {
+            store.set_memory_budget(config.memory_budget);
+        }
 
         if config.enable_filesystem_source {
             info!(
@@ -79,17 +173,127 @@ impl AssetManager {
             store.add_source(Arc::new(FileSystemSource::new(config.root)));
         }
 
+        for path in config.archives.iter() {
+            match ArchiveSource::open(path.clone()) {
+                Ok(src) => store.add_source(Arc::new(src)),
+                Err(e) => log::warn!(
+                    target: "assets",
+                    "manager.source.archive failed file='{}' err='{}'",
+                    path.display(),
+                    e
+                ),
+            }
+        }
+
+        match store.load_validation_rules(Path::new(VALIDATION_RULES_PATH)) {
+            Ok(true) => info!(target: "assets", "manager.validation rules='{}'", VALIDATION_RULES_PATH),
+            Ok(false) => {}
+            Err(e) => log::warn!(
+                target: "assets",
+                "manager.validation failed rules='{}' err='{}'",
+                VALIDATION_RULES_PATH,
+                e
+            ),
+        }
+
+        info!(target: "assets", "manager.importer.register builtin='proctex'");
+        store.add_importer(Arc::new(ProceduralTextureImporter));
+        info!(target: "assets", "manager.importer.register builtin='material'");
+        store.add_importer(Arc::new(MaterialImporter));
+        info!(target: "assets", "manager.importer.register builtin='spirv'");
+        store.add_importer(Arc::new(SpirvShaderImporter));
+
+        if let Some(dir) = config.cache_dir {
+            info!(target: "assets", "manager.cache dir='{}'", dir.display());
+            store.set_cache(Some(AssetCache::new(dir)));
+        }
+
+        if let Some(addr) = config.remote_imports {
+            info!(target: "assets", "manager.remote_imports server='{}'", addr);
+            store.set_import_provider(Some(Arc::new(RemoteImportSource::new(addr))));
+        }
+
+        let server = config.serve_imports.and_then(|addr| {
+            AssetServer::spawn(addr.as_str(), store.clone())
+                .inspect_err(|e| {
+                    log::warn!(
+                        target: "assets",
+                        "manager.asset_server failed addr='{}' err='{}'",
+                        addr,
+                        e
+                    )
+                })
+                .ok()
+        });
+
+        let content_server = config.content_server.and_then(|addr| {
+            ContentServer::spawn(addr.as_str(), EngineContent::new(store.clone()))
+                .inspect_err(|e| {
+                    log::warn!(
+                        target: "assets",
+                        "manager.content_server failed addr='{}' err='{}'",
+                        addr,
+                        e
+                    )
+                })
+                .ok()
+        });
+
         let steps = config.pump_steps.max(1);
         let budget = PumpBudget::steps(steps);
         info!(target: "assets", "manager.budget steps={}", budget.steps);
 
         Self {
             store,
+            root,
             budget,
             importers_dir,
+            server,
+            content_server,
         }
     }
 
+    /// Filesystem assets root.
+    #[inline]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Queues an import of an arbitrary file (e.g. dropped onto the window). Files outside
+    /// the assets root are copied to `<root>/<dest_dir>/` first, renamed if the name is taken
+    /// by a different file. Returns the logical path and the asset id.
+    pub fn import_file(&self, path: &Path, dest_dir: &Path) -> Result<(PathBuf, AssetId), AssetError> {
+        let logical = match logical_under_root(&self.root, path) {
+            Some(rel) => rel,
+            None => {
+                let name = path
+                    .file_name()
+                    .ok_or_else(|| AssetError::new(format!("import_file: '{}' has no file name", path.display())))?;
+                let dir = self.root.join(dest_dir);
+                std::fs::create_dir_all(&dir)
+                    .map_err(|e| AssetError::new(format!("import_file: create '{}': {e}", dir.display())))?;
+
+                let dest = free_destination(&dir, Path::new(name), path);
+                if !dest.exists() {
+                    std::fs::copy(path, &dest).map_err(|e| {
+                        AssetError::new(format!("import_file: copy '{}': {e}", path.display()))
+                    })?;
+                }
+                info!(
+                    target: "assets",
+                    "manager.import_file copied src='{}' dst='{}'",
+                    path.display(),
+                    dest.display()
+                );
+                logical_under_root(&self.root, &dest)
+                    .ok_or_else(|| AssetError::new("import_file: destination escapes the assets root"))?
+            }
+        };
+
+        let id = self.store.load(AssetKey::new(logical.clone(), 0))?;
+        Ok((logical, id))
+    }
+
     /// Directory where asset importer dynamic libraries are discovered.
     ///
     /// By default this is `<exe_dir>/importers`.
@@ -98,12 +302,47 @@ impl AssetManager {
         &self.importers_dir
     }
 
+    /// Address the asset server listens on, when running in asset server mode.
+    #[inline]
+    pub fn asset_server_addr(&self) -> Option<std::net::SocketAddr> {
+        self.server.as_ref().map(AssetServer::local_addr)
+    }
+
+    /// Address the content server listens on, when enabled.
+    #[inline]
+    pub fn content_server_addr(&self) -> Option<std::net::SocketAddr> {
+        self.content_server.as_ref().map(ContentServer::local_addr)
+    }
+
+    /// Resolver for `engine://` URLs, for web views that register a custom scheme handler.
+    #[inline]
+    pub fn content(&self) -> EngineContent {
+        EngineContent::new(self.store.clone())
+    }
+
     /// Returns a shared handle to the underlying store.
     #[inline]
     pub fn store(&self) -> &Arc<AssetStore> {
         &self.store
     }
 
+    /// Deletes every persistent cache entry. Returns the number of removed entries.
+    pub fn clear_cache(&self) -> usize {
+        let Some(cache) = self.store.cache() else {
+            return 0;
+        };
+        match cache.clear() {
+            Ok(n) => {
+                info!(target: "assets", "manager.cache.clear removed={}", n);
+                n
+            }
+            Err(e) => {
+                log::warn!(target: "assets", "manager.cache.clear failed err='{}'", e);
+                0
+            }
+        }
+    }
+
     /// Registers an additional asset source.
     #[inline]
     pub fn add_source(&self, source: Arc<dyn AssetSource>) {
@@ -122,6 +361,12 @@ impl AssetManager {
         self.store.load(key)
     }
 
+    /// Enqueues an import request that is dropped if `cancel` fires before it runs.
+    #[inline]
+    pub fn load_cancellable(&self, key: AssetKey, cancel: &CancelToken) -> Result<AssetId, AssetError> {
+        self.store.load_cancellable(key, Arc::new(cancel.clone()))
+    }
+
     #[inline]
     pub fn state(&self, id: AssetId) -> AssetState {
         self.store.state(id)
@@ -132,6 +377,28 @@ impl AssetManager {
         self.store.get_blob(id)
     }
 
+    /// Pins a ready asset against memory-budget eviction while the handle lives.
+    #[inline]
+    pub fn acquire(&self, id: AssetId) -> Option<AssetHandle> {
+        self.store.acquire(id)
+    }
+
+    #[inline]
+    pub fn set_memory_budget(&self, budget: MemoryBudget) {
+        self.store.set_memory_budget(budget);
+    }
+
+    /// Asset browser listing; see `AssetStore::query`.
+    #[inline]
+    pub fn query(&self, query: &AssetQuery) -> AssetQueryResult {
+        self.store.query(query)
+    }
+
+    #[inline]
+    pub fn set_tags(&self, logical_path: &str, tags: &[String]) -> Result<(), AssetError> {
+        self.store.set_tags(logical_path, tags)
+    }
+
     #[inline]
     pub fn drain_events(&self) -> Vec<AssetEvent> {
         self.store.drain_events()
@@ -164,4 +431,42 @@ fn default_importers_dir() -> PathBuf {
         .map(|p| p.to_path_buf())
         .unwrap_or_else(|| PathBuf::from("."));
     base.join("importers")
-}
\ No newline at end of file
+}
+
+impl LoadCancel for CancelToken {
+    #[inline]
+    fn is_cancelled(&self) -> bool {
+        CancelToken::is_cancelled(self)
+    }
+}
+
+/// `path` relative to `root`, if it lies inside it.
+fn logical_under_root(root: &Path, path: &Path) -> Option<PathBuf> {
+    let root = root.canonicalize().ok()?;
+    let path = path.canonicalize().ok()?;
+    path.strip_prefix(&root).ok().map(Path::to_path_buf)
+}
+
+/// `dir/name`, or `dir/stem-N.ext` when that name holds a different file. An identical
+/// existing file is reused.
+fn free_destination(dir: &Path, name: &Path, src: &Path) -> PathBuf {
+    let same = |dst: &Path| match (std::fs::read(dst), std::fs::read(src)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    };
+
+    let first = dir.join(name);
+    if !first.exists() || same(&first) {
+        return first;
+    }
+
+    let stem = name.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = name.extension().map(|e| e.to_string_lossy().into_owned());
+    (1u32..)
+        .map(|n| match &ext {
+            Some(ext) => dir.join(format!("{stem}-{n}.{ext}")),
+            None => dir.join(format!("{stem}-{n}")),
+        })
+        .find(|p| !p.exists() || same(p))
+        .unwrap_or(first)
+}
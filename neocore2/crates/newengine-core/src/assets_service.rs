@@ -4,10 +4,13 @@ use crate::plugins::host_api;
 use abi_stable::std_types::{RResult, RString};
 use newengine_assets::store::ImporterBindingInfo;
 use newengine_assets::types::{AssetKey, AssetState};
-use newengine_assets::AssetStore;
+use newengine_assets::{
+    AssetQuery, AssetStateKind, AssetStore, ValidationIssue, ValidationSeverity, VALIDATION_RULES_PATH,
+};
 use newengine_plugin_api::{Blob, CapabilityId, MethodName, ServiceV1, ServiceV1Dyn};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::path::Path;
 use std::sync::Arc;
 
 pub const ASSET_SERVICE_ID: &str = "asset.manager";
@@ -19,8 +22,18 @@ pub mod method {
     pub const INFO_JSON: &str = "asset.info_json";
     pub const LOAD: &str = "asset.load";
     pub const RELOAD: &str = "asset.reload";
+    pub const UNLOAD: &str = "asset.unload";
+    pub const DEPS_JSON: &str = "asset.deps_json";
+    pub const DEP_GRAPH: &str = "asset.dep_graph";
+    pub const VALIDATION_JSON: &str = "asset.validation_json";
+    pub const QUERY_JSON: &str = "asset.query_json";
+    pub const QUERY_REVISION: &str = "asset.query_revision";
+    pub const TAG: &str = "asset.tag";
 }
 
+/// Neighbourhood depth used by `asset.dep_graph <path>` when none is given.
+const DEP_GRAPH_DEFAULT_DEPTH: usize = 2;
+
 #[derive(Debug, Serialize)]
 struct AssetStatsResp {
     sources: usize,
@@ -30,6 +43,21 @@ struct AssetStatsResp {
     blobs_ready: usize,
     blobs_bytes: u64,
     queue_len: usize,
+    in_flight: usize,
+    cache_enabled: bool,
+    cache_hits: u64,
+    cache_misses: u64,
+    memory: Vec<MemoryUsageResp>,
+}
+
+#[derive(Debug, Serialize)]
+struct MemoryUsageResp {
+    category: &'static str,
+    resident_bytes: u64,
+    assets: usize,
+    /// `None` when the category has no budget.
+    limit_bytes: Option<u64>,
+    evictions: u64,
 }
 
 #[derive(Debug, Serialize)]
@@ -43,6 +71,7 @@ struct ImporterBindingResp {
 #[derive(Debug, Serialize)]
 struct AssetListItem {
     id_u128: String,
+    path: String,
     state: String,
     type_id: Option<String>,
     format: Option<String>,
@@ -64,6 +93,56 @@ struct AssetInfoResp {
     error: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+struct AssetDepsResp {
+    ok: bool,
+    id_u128: String,
+    dirty: bool,
+    dependencies: Vec<String>,
+    dependents: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ValidationResp {
+    rules: usize,
+    errors: usize,
+    warnings: usize,
+    issues: Vec<ValidationIssue>,
+    error: Option<String>,
+}
+
+/// `asset.query_json` filter; every field is optional (see `AssetQuery`).
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct QueryReq {
+    folder: Option<String>,
+    recursive: bool,
+    extensions: Vec<String>,
+    states: Vec<String>,
+    tags: Vec<String>,
+    text: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct QueryItemResp {
+    id_u128: String,
+    path: String,
+    state: &'static str,
+    tags: Vec<String>,
+    type_id: Option<String>,
+    bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct QueryResp {
+    ok: bool,
+    revision: u64,
+    total: usize,
+    entries: Vec<QueryItemResp>,
+    error: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 struct LoadResp {
     ok: bool,
@@ -89,20 +168,28 @@ impl ServiceV1 for AssetManagerService {
     fn describe(&self) -> RString {
         let d = json!({
           "id": ASSET_SERVICE_ID,
-          "version": 1,
+          "version": 2,
+          "permissions": ["filesystem"],
           "methods": [
             { "name": method::STATS_JSON, "payload": "empty", "returns": "json AssetStatsResp" },
             { "name": method::IMPORTERS_JSON, "payload": "empty", "returns": "json [ImporterBindingResp]" },
             { "name": method::LIST_JSON, "payload": "empty", "returns": "json [AssetListItem]" },
             { "name": method::INFO_JSON, "payload": "utf8 logical_path", "returns": "json AssetInfoResp" },
             { "name": method::LOAD, "payload": "utf8 logical_path", "returns": "json LoadResp" },
-            { "name": method::RELOAD, "payload": "utf8 logical_path", "returns": "json LoadResp" }
+            { "name": method::RELOAD, "payload": "utf8 logical_path", "returns": "json LoadResp" },
+            { "name": method::UNLOAD, "payload": "utf8 logical_path", "returns": "json LoadResp" },
+            { "name": method::DEPS_JSON, "payload": "utf8 logical_path", "returns": "json AssetDepsResp" },
+            { "name": method::DEP_GRAPH, "payload": "utf8 \"[logical_path] [depth] [dot|json]\"", "returns": "json {nodes, edges} or text/vnd.graphviz" },
+            { "name": method::VALIDATION_JSON, "payload": "empty | \"reload\"", "returns": "json ValidationResp" },
+            { "name": method::QUERY_JSON, "payload": "empty | json QueryReq | utf8 search text", "returns": "json QueryResp" },
+            { "name": method::QUERY_REVISION, "payload": "empty", "returns": "json {revision}" },
+            { "name": method::TAG, "payload": "utf8 \"logical_path [tag...]\"", "returns": "json LoadResp" }
           ],
           "console": {
             "commands": [
               {
                 "name": "asset.stats",
-                "help": "Asset store stats",
+                "help": "Asset store stats, including resident memory and budget per category",
                 "kind": "service_call",
                 "service_id": ASSET_SERVICE_ID,
                 "method": method::STATS_JSON,
@@ -150,6 +237,60 @@ impl ServiceV1 for AssetManagerService {
                 "service_id": ASSET_SERVICE_ID,
                 "method": method::RELOAD,
                 "payload": "raw"
+              },
+              {
+                "name": "asset.unload",
+                "help": "Unload asset (dependents become dirty): asset.unload <logical_path>",
+                "usage": "asset.unload <logical_path>",
+                "kind": "service_call",
+                "service_id": ASSET_SERVICE_ID,
+                "method": method::UNLOAD,
+                "payload": "raw"
+              },
+              {
+                "name": "asset.deps",
+                "help": "Show dependency edges: asset.deps <logical_path>",
+                "usage": "asset.deps <logical_path>",
+                "kind": "service_call",
+                "service_id": ASSET_SERVICE_ID,
+                "method": method::DEPS_JSON,
+                "payload": "raw"
+              },
+              {
+                "name": "asset.graph",
+                "help": "Export the dependency graph (whole store or around one asset) as JSON or DOT",
+                "usage": "asset.graph [logical_path] [depth] [dot|json]",
+                "kind": "service_call",
+                "service_id": ASSET_SERVICE_ID,
+                "method": method::DEP_GRAPH,
+                "payload": "raw"
+              },
+              {
+                "name": "asset.validate",
+                "help": "List content rule violations; 'reload' re-reads validation.rules.json first",
+                "usage": "asset.validate [reload]",
+                "kind": "service_call",
+                "service_id": ASSET_SERVICE_ID,
+                "method": method::VALIDATION_JSON,
+                "payload": "raw"
+              },
+              {
+                "name": "asset.find",
+                "help": "Search assets in every source by path, or filter with a json query",
+                "usage": "asset.find <text | {\"folder\",\"recursive\",\"extensions\",\"states\",\"tags\",\"text\",\"limit\"}>",
+                "kind": "service_call",
+                "service_id": ASSET_SERVICE_ID,
+                "method": method::QUERY_JSON,
+                "payload": "raw"
+              },
+              {
+                "name": "asset.tag",
+                "help": "Set an asset's tags in its .meta sidecar (no tags clears them)",
+                "usage": "asset.tag <logical_path> [tag...]",
+                "kind": "service_call",
+                "service_id": ASSET_SERVICE_ID,
+                "method": method::TAG,
+                "payload": "raw"
               }
             ]
           }
@@ -172,6 +313,21 @@ impl ServiceV1 for AssetManagerService {
                     blobs_ready: s.blobs_ready,
                     blobs_bytes: s.blobs_bytes,
                     queue_len: s.queue_len,
+                    in_flight: s.in_flight,
+                    cache_enabled: s.cache_enabled,
+                    cache_hits: s.cache_hits,
+                    cache_misses: s.cache_misses,
+                    memory: s
+                        .memory
+                        .iter()
+                        .map(|m| MemoryUsageResp {
+                            category: m.category.as_str(),
+                            resident_bytes: m.resident_bytes,
+                            assets: m.assets,
+                            limit_bytes: m.limit,
+                            evictions: m.evictions,
+                        })
+                        .collect(),
                 };
                 let bytes = serde_json::to_vec(&resp).unwrap_or_default();
                 RResult::ROk(Blob::from(bytes))
@@ -196,6 +352,7 @@ impl ServiceV1 for AssetManagerService {
                     .into_iter()
                     .map(|x| AssetListItem {
                         id_u128: format!("{:032x}", x.id_u128),
+                        path: x.path,
                         state: x.state,
                         type_id: x.type_id,
                         format: x.format,
@@ -355,11 +512,206 @@ impl ServiceV1 for AssetManagerService {
                     }
                 }
             }
+            method::UNLOAD => {
+                let path = String::from_utf8_lossy(payload.as_slice()).trim().to_string();
+                let id = AssetKey::new(&path, 0).id();
+                let ok = !path.is_empty() && self.store.unload(id);
+                let bytes = serde_json::to_vec(&LoadResp {
+                    ok,
+                    id_u128: ok.then(|| format!("{:032x}", id.to_u128())),
+                    error: (!ok).then(|| format!("asset not known: '{path}'")),
+                })
+                    .unwrap_or_default();
+                RResult::ROk(Blob::from(bytes))
+            }
+            method::DEPS_JSON => {
+                let path = String::from_utf8_lossy(payload.as_slice()).trim().to_string();
+                let id = AssetKey::new(&path, 0).id();
+                let fmt = |v: Vec<newengine_assets::AssetId>| {
+                    v.into_iter()
+                        .map(|d| format!("{:032x}", d.to_u128()))
+                        .collect::<Vec<_>>()
+                };
+                let resp = AssetDepsResp {
+                    ok: !path.is_empty(),
+                    id_u128: format!("{:032x}", id.to_u128()),
+                    dirty: self.store.is_dirty(id),
+                    dependencies: fmt(self.store.dependencies_of(id)),
+                    dependents: fmt(self.store.dependents_of(id)),
+                };
+                let bytes = serde_json::to_vec(&resp).unwrap_or_default();
+                RResult::ROk(Blob::from(bytes))
+            }
+            method::DEP_GRAPH => {
+                let args = String::from_utf8_lossy(payload.as_slice()).to_string();
+                RResult::ROk(Blob::from(self.dep_graph(&args)))
+            }
+            method::VALIDATION_JSON => {
+                let reload = String::from_utf8_lossy(payload.as_slice()).trim() == "reload";
+                RResult::ROk(Blob::from(self.validation(reload)))
+            }
+            method::QUERY_JSON => {
+                let req = String::from_utf8_lossy(payload.as_slice()).to_string();
+                RResult::ROk(Blob::from(self.query(req.trim())))
+            }
+            method::QUERY_REVISION => {
+                let resp = json!({ "revision": self.store.query_revision() });
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+            method::TAG => {
+                let args = String::from_utf8_lossy(payload.as_slice()).to_string();
+                let mut parts = args.split_whitespace();
+                let path = parts.next().unwrap_or_default();
+                let tags: Vec<String> = parts.map(str::to_string).collect();
+                let result = if path.is_empty() {
+                    Err("empty path".to_string())
+                } else {
+                    self.store.set_tags(path, &tags).map_err(|e| e.to_string())
+                };
+                let bytes = serde_json::to_vec(&LoadResp {
+                    ok: result.is_ok(),
+                    id_u128: result
+                        .is_ok()
+                        .then(|| format!("{:032x}", AssetKey::new(path, 0).id().to_u128())),
+                    error: result.err(),
+                })
+                    .unwrap_or_default();
+                RResult::ROk(Blob::from(bytes))
+            }
             _ => RResult::RErr(RString::from(format!("unknown method: {m}"))),
         }
     }
 }
 
+impl AssetManagerService {
+    fn validation(&self, reload: bool) -> Vec<u8> {
+        let error = reload
+            .then(|| self.store.load_validation_rules(Path::new(VALIDATION_RULES_PATH)))
+            .and_then(|r| r.err())
+            .map(|e| e.to_string());
+
+        let issues = self.store.all_validation_issues();
+        let errors = issues
+            .iter()
+            .filter(|i| i.severity == ValidationSeverity::Error)
+            .count();
+        let resp = ValidationResp {
+            rules: self.store.validation_rules().map_or(0, |r| r.rules.len()),
+            errors,
+            warnings: issues.len() - errors,
+            issues,
+            error,
+        };
+        serde_json::to_vec(&resp).unwrap_or_default()
+    }
+
+    /// Empty lists everything; a payload starting with `{` is a `QueryReq`, anything else is
+    /// searched for in the paths.
+    fn query(&self, payload: &str) -> Vec<u8> {
+        let req = if payload.starts_with('{') {
+            match serde_json::from_str::<QueryReq>(payload) {
+                Ok(r) => r,
+                Err(e) => return query_error(format!("bad query: {e}")),
+            }
+        } else {
+            QueryReq {
+                text: (!payload.is_empty()).then(|| payload.to_string()),
+                ..QueryReq::default()
+            }
+        };
+
+        let mut q = AssetQuery::new();
+        if let Some(folder) = &req.folder {
+            q = if req.recursive {
+                q.under_folder(folder)
+            } else {
+                q.in_folder(folder)
+            };
+        }
+        for ext in &req.extensions {
+            q = q.with_extension(ext);
+        }
+        for s in &req.states {
+            match AssetStateKind::parse(s) {
+                Some(k) => q = q.with_state(k),
+                None => return query_error(format!("unknown state '{s}'")),
+            }
+        }
+        for tag in req.tags {
+            q = q.with_tag(tag);
+        }
+        if let Some(text) = req.text {
+            q = q.matching(text);
+        }
+        if let Some(limit) = req.limit {
+            q = q.with_limit(limit);
+        }
+
+        let res = self.store.query(&q);
+        let resp = QueryResp {
+            ok: true,
+            revision: res.revision,
+            total: res.total,
+            entries: res
+                .entries
+                .into_iter()
+                .map(|e| QueryItemResp {
+                    id_u128: format!("{:032x}", e.id.to_u128()),
+                    state: AssetStateKind::of(&e.state).as_str(),
+                    path: e.path,
+                    tags: e.tags,
+                    type_id: e.type_id.map(|t| t.to_string()),
+                    bytes: e.bytes,
+                })
+                .collect(),
+            error: None,
+        };
+        serde_json::to_vec(&resp).unwrap_or_default()
+    }
+
+    /// `[logical_path] [depth] [dot|json]` in any order; without a path the whole graph is exported.
+    fn dep_graph(&self, args: &str) -> Vec<u8> {
+        let mut path: Option<&str> = None;
+        let mut depth = DEP_GRAPH_DEFAULT_DEPTH;
+        let mut dot = false;
+
+        for tok in args.split_whitespace() {
+            if tok.eq_ignore_ascii_case("dot") {
+                dot = true;
+            } else if tok.eq_ignore_ascii_case("json") {
+                dot = false;
+            } else if let Ok(d) = tok.parse::<usize>() {
+                depth = d;
+            } else {
+                path = Some(tok);
+            }
+        }
+
+        let mut graph = self.store.export_dependency_graph();
+        if let Some(p) = path {
+            graph = graph.subgraph(AssetKey::new(p, 0).id(), depth);
+        }
+
+        if dot {
+            graph.to_dot().into_bytes()
+        } else {
+            graph.to_json().to_string().into_bytes()
+        }
+    }
+}
+
+#[inline]
+fn query_error(error: String) -> Vec<u8> {
+    serde_json::to_vec(&QueryResp {
+        ok: false,
+        revision: 0,
+        total: 0,
+        entries: Vec::new(),
+        error: Some(error),
+    })
+    .unwrap_or_default()
+}
+
 /// Register asset manager service into host services.
 pub fn register_asset_manager_service(asset_store: Arc<AssetStore>) {
     let svc = AssetManagerService::new(asset_store);
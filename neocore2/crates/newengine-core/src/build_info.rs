@@ -0,0 +1,86 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use serde_json::{json, Value};
+
+/// Compile-time build stamp of the engine core. Inserted as a resource by `Engine`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    /// Short commit hash, `-dirty` suffixed for uncommitted changes, or `unknown`.
+    pub git_hash: &'static str,
+    /// UTC `YYYY-MM-DD`.
+    pub build_date: &'static str,
+    pub target: &'static str,
+    /// Cargo profile (`debug`, `release`, ...).
+    pub profile: &'static str,
+    /// Enabled `newengine-core` cargo features.
+    pub features: &'static [&'static str],
+}
+
+const FEATURES: &[&str] = &[
+    #[cfg(feature = "runtime")]
+    "runtime",
+];
+
+static BUILD_INFO: BuildInfo = BuildInfo {
+    name: "NewEngine",
+    version: env!("CARGO_PKG_VERSION"),
+    git_hash: env!("NEWENGINE_BUILD_GIT_HASH"),
+    build_date: env!("NEWENGINE_BUILD_DATE"),
+    target: env!("NEWENGINE_BUILD_TARGET"),
+    profile: env!("NEWENGINE_BUILD_PROFILE"),
+    features: FEATURES,
+};
+
+impl BuildInfo {
+    #[inline]
+    pub fn get() -> &'static BuildInfo {
+        &BUILD_INFO
+    }
+
+    /// `0.1.0+abc123def456`.
+    pub fn version_string(&self) -> String {
+        if self.git_hash == "unknown" {
+            self.version.to_owned()
+        } else {
+            format!("{}+{}", self.version, self.git_hash)
+        }
+    }
+
+    /// One line for logs, the console and crash reports.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} {} ({} {} {}, features=[{}])",
+            self.name,
+            self.version_string(),
+            self.build_date,
+            self.target,
+            self.profile,
+            self.features.join(",")
+        )
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "version": self.version,
+            "git_hash": self.git_hash,
+            "build_date": self.build_date,
+            "target": self.target,
+            "profile": self.profile,
+            "features": self.features,
+        })
+    }
+
+    /// Replaces `%version%` in window titles and similar user-facing strings.
+    #[inline]
+    pub fn expand(&self, s: &str) -> String {
+        s.replace("%version%", &self.version_string())
+    }
+
+    #[inline]
+    pub fn log(&self) {
+        log::info!("build: {}", self.summary());
+    }
+}
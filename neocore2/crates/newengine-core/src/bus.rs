@@ -1,4 +1,12 @@
+use crate::trace::{self, TraceKind};
+
 use crossbeam_channel::{Receiver, Sender};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::fmt;
 
 pub struct Bus<E: Send + 'static> {
     tx: Sender<E>,
@@ -11,20 +19,38 @@ impl<E: Send + 'static> Bus<E> {
         Self { tx, rx }
     }
 
+    /// Bus over a fresh unbounded channel.
+    #[inline]
+    pub fn unbounded() -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        Self { tx, rx }
+    }
+
     /// Sends an event to the bus.
     ///
     /// Returns an error if all receivers are disconnected.
     #[inline]
     pub fn send(&self, ev: E) -> Result<(), crossbeam_channel::SendError<E>> {
+        Self::trace_send();
         self.tx.send(ev)
     }
 
     /// Lossy send variant for non-critical fire-and-forget signals.
     #[inline]
     pub fn send_lossy(&self, ev: E) {
+        Self::trace_send();
         let _ = self.tx.send(ev);
     }
 
+    #[inline]
+    fn trace_send() {
+        trace::record(
+            TraceKind::Bus,
+            trace::short_type_name(std::any::type_name::<E>()),
+            String::new,
+        );
+    }
+
     #[inline]
     pub fn try_recv(&self) -> Option<E> {
         self.rx.try_recv().ok()
@@ -37,3 +63,104 @@ impl<E: Send + 'static> Bus<E> {
         }
     }
 }
+
+/// Payload of a [`BusMessage`].
+pub enum BusPayload {
+    /// In-process value; receivers downcast it.
+    Value(Box<dyn Any + Send>),
+    /// Serialized value (JSON by convention) for receivers that do not share the Rust type,
+    /// e.g. across a plugin boundary.
+    Bytes(Vec<u8>),
+}
+
+impl fmt::Debug for BusPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BusPayload::Value(_) => f.write_str("Value(..)"),
+            BusPayload::Bytes(b) => write!(f, "Bytes({} bytes)", b.len()),
+        }
+    }
+}
+
+/// Type-erased bus event: a topic and a boxed or serialized payload.
+///
+/// Modules written against [`AnyBus`] do not depend on the app's event type, so the same
+/// module runs in any `Engine<E>`.
+#[derive(Debug)]
+pub struct BusMessage {
+    pub topic: Cow<'static, str>,
+    pub payload: BusPayload,
+}
+
+impl BusMessage {
+    #[inline]
+    pub fn new<T: Any + Send>(topic: impl Into<Cow<'static, str>>, value: T) -> Self {
+        Self {
+            topic: topic.into(),
+            payload: BusPayload::Value(Box::new(value)),
+        }
+    }
+
+    #[inline]
+    pub fn bytes(topic: impl Into<Cow<'static, str>>, bytes: Vec<u8>) -> Self {
+        Self {
+            topic: topic.into(),
+            payload: BusPayload::Bytes(bytes),
+        }
+    }
+
+    /// Serializes `value` as JSON.
+    pub fn json<T: Serialize>(
+        topic: impl Into<Cow<'static, str>>,
+        value: &T,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self::bytes(topic, serde_json::to_vec(value)?))
+    }
+
+    #[inline]
+    pub fn is(&self, topic: &str) -> bool {
+        self.topic == topic
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match &self.payload {
+            BusPayload::Value(v) => v.downcast_ref::<T>(),
+            BusPayload::Bytes(_) => None,
+        }
+    }
+
+    /// Takes the value out; gives the message back if the payload is not a `T`.
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        match self.payload {
+            BusPayload::Value(v) => match v.downcast::<T>() {
+                Ok(v) => Ok(*v),
+                Err(v) => Err(Self {
+                    topic: self.topic,
+                    payload: BusPayload::Value(v),
+                }),
+            },
+            payload => Err(Self {
+                topic: self.topic,
+                payload,
+            }),
+        }
+    }
+
+    /// Raw bytes of a serialized payload.
+    #[inline]
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match &self.payload {
+            BusPayload::Bytes(b) => Some(b),
+            BusPayload::Value(_) => None,
+        }
+    }
+
+    /// Deserializes a JSON payload. `None` if the payload is an in-process value.
+    pub fn decode_json<T: DeserializeOwned>(&self) -> Option<Result<T, serde_json::Error>> {
+        self.as_bytes().map(serde_json::from_slice)
+    }
+}
+
+/// Bus carrying [`BusMessage`]s; every engine owns one next to its typed `Bus<E>`.
+pub type AnyBus = Bus<BusMessage>;
@@ -7,4 +7,8 @@ pub mod method {
     pub const COMPLETE: &str = "command.complete";
     pub const SUGGEST: &str = "command.suggest";
     pub const REFRESH: &str = "command.refresh";
+    pub const BUILD_INFO: &str = "engine.build_info";
+    pub const REMOTE_EXEC: &str = "command.remote_exec";
+    pub const REMOTE_AUDIT: &str = "command.remote_audit";
+    pub const REMOTE_AUTH: &str = "command.remote_auth";
 }
\ No newline at end of file
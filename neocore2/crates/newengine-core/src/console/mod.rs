@@ -1,9 +1,15 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 mod method;
+mod overlay;
+pub(crate) mod registry;
+mod remote;
 mod runtime;
 mod service;
 mod types;
 
-pub use method::COMMAND_SERVICE_ID;
-pub use service::{init_console_service, take_exit_requested};
+pub use method::{method, COMMAND_SERVICE_ID};
+pub use overlay::DevConsoleModule;
+pub use registry::{CommandHandler, ConsoleCommands};
+pub use remote::{AuditEntry, AuditOutcome, PermissionLevel, RemoteConsolePolicy};
+pub use service::{init_console_service, set_remote_console_policy, take_exit_requested};
@@ -0,0 +1,84 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use super::method::{method, COMMAND_SERVICE_ID};
+
+use crate::error::EngineResult;
+use crate::host_services::call_service_v1;
+use crate::module::{Module, ModuleCtx};
+
+use newengine_ui::console::{ConsoleApi, ConsoleBackend, ConsoleSuggestions};
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+struct ExecResponse {
+    ok: bool,
+    #[serde(default)]
+    output: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Runs overlay lines through `engine.command` and completes from its command metadata.
+struct CommandServiceBackend;
+
+impl ConsoleBackend for CommandServiceBackend {
+    fn exec(&self, line: &str) -> Result<String, String> {
+        let bytes = call_service_v1(COMMAND_SERVICE_ID, method::EXEC, line.as_bytes())?;
+        let r: ExecResponse =
+            serde_json::from_slice(&bytes).map_err(|e| format!("bad response json: {e}"))?;
+        if r.ok {
+            Ok(r.output.unwrap_or_default())
+        } else {
+            Err(r.error.unwrap_or_else(|| "command failed".to_string()))
+        }
+    }
+
+    fn suggest(&self, input: &str) -> ConsoleSuggestions {
+        call_service_v1(COMMAND_SERVICE_ID, method::SUGGEST, input.as_bytes())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Drop-down developer console drawn by the UI provider (toggled with `~`). Lines go to the
+/// command service; Tab completes from the registered command and cvar metadata.
+#[derive(Default)]
+pub struct DevConsoleModule {
+    open_on_start: bool,
+}
+
+impl DevConsoleModule {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows the console as soon as the engine starts.
+    #[inline]
+    pub fn with_open(mut self, open: bool) -> Self {
+        self.open_on_start = open;
+        self
+    }
+}
+
+impl<E: Send + 'static> Module<E> for DevConsoleModule {
+    fn id(&self) -> &'static str {
+        "ui.console"
+    }
+
+    fn init(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        ConsoleApi::set_backend(Some(Arc::new(CommandServiceBackend)));
+        if self.open_on_start {
+            ConsoleApi::set_open(true);
+        }
+        Ok(())
+    }
+
+    fn shutdown(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        ConsoleApi::set_backend(None);
+        ConsoleApi::set_open(false);
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::error::{EngineError, EngineResult};
+use crate::plugins::host_context;
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Handler of a registered console command. Receives the arguments after the command name.
+pub type CommandHandler = Arc<dyn Fn(&str) -> Result<String, String> + Send + Sync>;
+
+#[derive(Clone)]
+pub(super) struct RegisteredCommand {
+    pub help: String,
+    pub usage: String,
+    /// Plugin that registered the command; its commands go away when it is unloaded.
+    pub owner: Option<String>,
+    pub handler: CommandHandler,
+}
+
+static COMMANDS: OnceLock<Mutex<BTreeMap<String, RegisteredCommand>>> = OnceLock::new();
+
+#[inline]
+fn commands() -> &'static Mutex<BTreeMap<String, RegisteredCommand>> {
+    COMMANDS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+pub(crate) fn register(
+    name: &str,
+    help: &str,
+    usage: &str,
+    handler: CommandHandler,
+) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("invalid console command name: '{name}'"));
+    }
+    if super::service::is_builtin_command(name) {
+        return Err(format!("console command is built in: {name}"));
+    }
+
+    let mut g = commands()
+        .lock()
+        .map_err(|_| "console commands mutex poisoned".to_string())?;
+    if g.contains_key(name) {
+        return Err(format!("console command already registered: {name}"));
+    }
+
+    let usage = if usage.trim().is_empty() {
+        name.to_string()
+    } else {
+        usage.to_string()
+    };
+    g.insert(
+        name.to_string(),
+        RegisteredCommand {
+            help: help.to_string(),
+            usage,
+            owner: host_context::current_plugin_id(),
+            handler,
+        },
+    );
+    Ok(())
+}
+
+pub(crate) fn unregister(name: &str) -> bool {
+    commands()
+        .lock()
+        .map(|mut g| g.remove(name).is_some())
+        .unwrap_or(false)
+}
+
+/// Drops every command registered by `plugin_id`.
+pub(crate) fn unregister_by_owner(plugin_id: &str) {
+    if let Ok(mut g) = commands().lock() {
+        g.retain(|_, c| c.owner.as_deref() != Some(plugin_id));
+    }
+}
+
+pub(super) fn get(name: &str) -> Option<RegisteredCommand> {
+    commands().lock().ok()?.get(name).cloned()
+}
+
+/// Registered commands sorted by name.
+pub(super) fn snapshot() -> Vec<(String, RegisteredCommand)> {
+    commands()
+        .lock()
+        .map(|g| g.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default()
+}
+
+/// Console commands added by modules, returned by
+/// [`ModuleCtx::console`](crate::module::ModuleCtx::console).
+///
+/// Registered commands run through the `engine.command` service like the built-in ones and
+/// are listed by `help` and in completions. They are local-only unless the remote console
+/// policy whitelists them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleCommands;
+
+impl ConsoleCommands {
+    /// Adds `name` (e.g. `"physics.gravity"`); `handler` gets the text after the name.
+    pub fn register_command<F>(&self, name: &str, handler: F, help: &str) -> EngineResult<()>
+    where
+        F: Fn(&str) -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.register_command_with_usage(name, &format!("{name} [args]"), handler, help)
+    }
+
+    /// Like [`register_command`](Self::register_command), with the signature shown while the
+    /// command is being typed (e.g. `"physics.gravity [x y z]"`).
+    pub fn register_command_with_usage<F>(
+        &self,
+        name: &str,
+        usage: &str,
+        handler: F,
+        help: &str,
+    ) -> EngineResult<()>
+    where
+        F: Fn(&str) -> Result<String, String> + Send + Sync + 'static,
+    {
+        register(name, help, usage, Arc::new(handler)).map_err(EngineError::Other)
+    }
+
+    /// Removes a command added with `register_command`. Returns `false` if it was not
+    /// registered.
+    #[inline]
+    pub fn unregister_command(&self, name: &str) -> bool {
+        unregister(name)
+    }
+
+    #[inline]
+    pub fn is_registered(&self, name: &str) -> bool {
+        get(name).is_some()
+    }
+}
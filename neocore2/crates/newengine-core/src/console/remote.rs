@@ -0,0 +1,399 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Authorized console execution for remote tools (editor, admin clients, dedicated servers).
+//!
+//! Remote callers present a token that maps to a [`PermissionLevel`]. Only whitelisted
+//! commands run, each gated by its own minimum level; every attempt lands in a bounded
+//! audit log. Services opt their console commands in with a `"remote"` level in describe.
+
+use super::runtime::ConsoleRuntime;
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Audit entries retained in memory; older entries are only in the log output.
+const AUDIT_CAPACITY: usize = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionLevel {
+    /// Read-only inspection (help, version, describe).
+    Observer,
+    /// Gameplay/tuning changes (cvars, service commands that opt in).
+    Operator,
+    /// Everything whitelisted, including shutdown and the audit log.
+    Admin,
+}
+
+impl PermissionLevel {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "observer" => Some(Self::Observer),
+            "operator" => Some(Self::Operator),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Observer => "observer",
+            Self::Operator => "operator",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RemoteClient {
+    name: String,
+    level: PermissionLevel,
+}
+
+/// Who may run what remotely. The default policy has no tokens, so remote execution is off
+/// until the host registers at least one.
+#[derive(Debug, Clone)]
+pub struct RemoteConsolePolicy {
+    tokens: BTreeMap<String, RemoteClient>,
+    commands: BTreeMap<String, PermissionLevel>,
+    denied: BTreeSet<String>,
+}
+
+impl Default for RemoteConsolePolicy {
+    fn default() -> Self {
+        let mut commands = BTreeMap::new();
+        commands.insert("help".to_owned(), PermissionLevel::Observer);
+        commands.insert("version".to_owned(), PermissionLevel::Observer);
+        commands.insert("describe".to_owned(), PermissionLevel::Observer);
+        commands.insert("services".to_owned(), PermissionLevel::Observer);
+        commands.insert("services reset".to_owned(), PermissionLevel::Operator);
+        commands.insert("services.describe".to_owned(), PermissionLevel::Observer);
+        commands.insert("cvars".to_owned(), PermissionLevel::Observer);
+        commands.insert("set".to_owned(), PermissionLevel::Operator);
+        commands.insert("reset".to_owned(), PermissionLevel::Operator);
+        commands.insert("refresh".to_owned(), PermissionLevel::Operator);
+        commands.insert("quit".to_owned(), PermissionLevel::Admin);
+
+        // `call` reaches any service method and bypasses the whitelist; hosts must allow it
+        // explicitly.
+        Self {
+            tokens: BTreeMap::new(),
+            commands,
+            denied: BTreeSet::new(),
+        }
+    }
+}
+
+impl RemoteConsolePolicy {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `token` for a client; `name` is what the audit log records.
+    pub fn with_token(
+        mut self,
+        token: impl Into<String>,
+        name: impl Into<String>,
+        level: PermissionLevel,
+    ) -> Self {
+        self.tokens.insert(
+            token.into(),
+            RemoteClient {
+                name: name.into(),
+                level,
+            },
+        );
+        self
+    }
+
+    /// Whitelists `command` for clients at `level` or above. Overrides a service's own level.
+    /// `"<command> <subcommand>"` sets the level of one subcommand, ahead of the command's.
+    pub fn allow(mut self, command: impl Into<String>, level: PermissionLevel) -> Self {
+        let command = command.into();
+        self.denied.remove(&command);
+        self.commands.insert(command, level);
+        self
+    }
+
+    /// Blocks `command` remotely, including service commands that opted in.
+    pub fn deny(mut self, command: impl Into<String>) -> Self {
+        let command = command.into();
+        self.commands.remove(&command);
+        self.denied.insert(command);
+        self
+    }
+
+    #[inline]
+    pub fn enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    fn authenticate(&self, token: &str) -> Option<&RemoteClient> {
+        // Compare against every token so timing does not reveal how close a guess was.
+        let mut found = None;
+        for (k, c) in self.tokens.iter() {
+            if ct_eq(k.as_bytes(), token.as_bytes()) {
+                found = Some(c);
+            }
+        }
+        found
+    }
+
+    fn required_level(
+        &self,
+        rt: &ConsoleRuntime,
+        head: &str,
+        sub: Option<&str>,
+    ) -> Option<PermissionLevel> {
+        if self.denied.contains(head) {
+            return None;
+        }
+        if let Some(sub) = sub {
+            let key = format!("{head} {sub}");
+            if self.denied.contains(&key) {
+                return None;
+            }
+            if let Some(level) = self.commands.get(&key) {
+                return Some(*level);
+            }
+        }
+        self.commands
+            .get(head)
+            .copied()
+            .or_else(|| rt.dyn_remote_level(head))
+    }
+}
+
+fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Ok,
+    Failed,
+    Denied,
+    Unauthorized,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub unix_ms: u64,
+    /// Client name from the policy, or `None` when the token was rejected.
+    pub client: Option<String>,
+    pub level: Option<PermissionLevel>,
+    pub line: String,
+    pub outcome: AuditOutcome,
+    pub message: String,
+}
+
+#[derive(Default)]
+struct AuditLog {
+    seq: u64,
+    entries: VecDeque<AuditEntry>,
+}
+
+pub(super) struct RemoteConsole {
+    policy: Mutex<RemoteConsolePolicy>,
+    audit: Mutex<AuditLog>,
+}
+
+impl RemoteConsole {
+    pub(super) fn new() -> Self {
+        Self {
+            policy: Mutex::new(RemoteConsolePolicy::default()),
+            audit: Mutex::new(AuditLog::default()),
+        }
+    }
+
+    pub(super) fn set_policy(&self, policy: RemoteConsolePolicy) {
+        if let Ok(mut g) = self.policy.lock() {
+            log::info!(
+                target: "console.remote",
+                "console.remote policy set clients={} whitelisted={}",
+                policy.tokens.len(),
+                policy.commands.len()
+            );
+            *g = policy;
+        }
+    }
+
+    /// Authenticates `token`, checks the command's level and runs `line` on `rt`.
+    pub(super) fn exec(
+        &self,
+        rt: &ConsoleRuntime,
+        token: &str,
+        line: &str,
+    ) -> Result<String, String> {
+        let line = line.trim();
+
+        let (client, head, required) = {
+            let policy = self
+                .policy
+                .lock()
+                .map_err(|_| "remote policy mutex poisoned".to_string())?;
+
+            if !policy.enabled() {
+                drop(policy);
+                return self.reject(
+                    None,
+                    line,
+                    AuditOutcome::Unauthorized,
+                    "remote console disabled",
+                );
+            }
+
+            let Some(client) = policy.authenticate(token).cloned() else {
+                drop(policy);
+                return self.reject(None, line, AuditOutcome::Unauthorized, "invalid token");
+            };
+
+            let mut words = line.split_whitespace();
+            let head = words.next().unwrap_or("");
+            let required = policy.required_level(rt, head, words.next());
+            (client, head.to_owned(), required)
+        };
+
+        if line.is_empty() || line.chars().any(char::is_control) {
+            return self.reject(
+                Some(&client),
+                line,
+                AuditOutcome::Denied,
+                "malformed command line",
+            );
+        }
+
+        let Some(required) = required else {
+            let msg = format!("command '{head}' is not allowed remotely");
+            return self.reject(Some(&client), line, AuditOutcome::Denied, &msg);
+        };
+
+        if client.level < required {
+            let msg = format!(
+                "command '{head}' requires {} (client has {})",
+                required.as_str(),
+                client.level.as_str()
+            );
+            return self.reject(Some(&client), line, AuditOutcome::Denied, &msg);
+        }
+
+        let out = rt.exec(line);
+        match &out {
+            Ok(_) => self.record(Some(&client), line, AuditOutcome::Ok, ""),
+            Err(e) => self.record(Some(&client), line, AuditOutcome::Failed, e),
+        }
+        out
+    }
+
+    /// Checks `token` without running anything, for sessions that authenticate once up front.
+    /// Rejections are audited like failed executions.
+    pub(super) fn authorize(&self, token: &str) -> Result<(String, PermissionLevel), String> {
+        let client = {
+            let policy = self
+                .policy
+                .lock()
+                .map_err(|_| "remote policy mutex poisoned".to_string())?;
+            if !policy.enabled() {
+                None
+            } else {
+                policy.authenticate(token).cloned()
+            }
+        };
+
+        match client {
+            Some(c) => {
+                self.record(Some(&c), "<auth>", AuditOutcome::Ok, "");
+                Ok((c.name, c.level))
+            }
+            None => {
+                self.record(None, "<auth>", AuditOutcome::Unauthorized, "invalid token");
+                Err("invalid token".to_owned())
+            }
+        }
+    }
+
+    /// Newest-last audit entries; only admins may read them.
+    pub(super) fn audit(&self, token: &str, limit: usize) -> Result<Vec<AuditEntry>, String> {
+        let client = self
+            .policy
+            .lock()
+            .map_err(|_| "remote policy mutex poisoned".to_string())?
+            .authenticate(token)
+            .cloned();
+
+        match client {
+            Some(c) if c.level >= PermissionLevel::Admin => {}
+            _ => return Err("audit log requires admin".into()),
+        }
+
+        let g = self
+            .audit
+            .lock()
+            .map_err(|_| "audit mutex poisoned".to_string())?;
+        let skip = g.entries.len().saturating_sub(limit);
+        Ok(g.entries.iter().skip(skip).cloned().collect())
+    }
+
+    fn reject(
+        &self,
+        client: Option<&RemoteClient>,
+        line: &str,
+        outcome: AuditOutcome,
+        message: &str,
+    ) -> Result<String, String> {
+        self.record(client, line, outcome, message);
+        Err(message.to_owned())
+    }
+
+    fn record(
+        &self,
+        client: Option<&RemoteClient>,
+        line: &str,
+        outcome: AuditOutcome,
+        message: &str,
+    ) {
+        let who = client.map(|c| c.name.as_str()).unwrap_or("<unauthenticated>");
+        match outcome {
+            AuditOutcome::Ok | AuditOutcome::Failed => log::info!(
+                target: "console.remote",
+                "console.remote exec client='{who}' outcome={outcome:?} line='{line}' msg='{message}'"
+            ),
+            AuditOutcome::Denied | AuditOutcome::Unauthorized => log::warn!(
+                target: "console.remote",
+                "console.remote reject client='{who}' outcome={outcome:?} line='{line}' msg='{message}'"
+            ),
+        }
+
+        let unix_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let Ok(mut g) = self.audit.lock() else {
+            return;
+        };
+        g.seq += 1;
+        let entry = AuditEntry {
+            seq: g.seq,
+            unix_ms,
+            client: client.map(|c| c.name.clone()),
+            level: client.map(|c| c.level),
+            line: line.to_owned(),
+            outcome,
+            message: message.to_owned(),
+        };
+        if g.entries.len() >= AUDIT_CAPACITY {
+            g.entries.pop_front();
+        }
+        g.entries.push_back(entry);
+    }
+}
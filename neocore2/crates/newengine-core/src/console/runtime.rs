@@ -1,7 +1,11 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+use crate::build_info::BuildInfo;
+use crate::cvars::CVars;
 use crate::plugins::host_context;
 
+use super::registry;
+use super::remote::PermissionLevel;
 use super::types::{ConsoleCmdEntry, DynCommand, DynPayload, SuggestItem, SuggestResponse};
 
 use std::collections::BTreeMap;
@@ -43,16 +47,18 @@ impl ConsoleRuntime {
         cmds.insert(
             "services",
             Cmd {
-                help: "List services",
-                usage: "services",
-                f: |_, _| {
-                    let c = host_context::ctx();
-                    let g = c
-                        .services
-                        .lock()
-                        .map_err(|_| "services mutex poisoned".to_string())?;
-                    Ok(g.keys().cloned().collect::<Vec<_>>().join("\n"))
-                },
+                help: "List services (or call stats)",
+                usage: "services [stats|reset]",
+                f: |rt, line| rt.services_cmd(line),
+            },
+        );
+
+        cmds.insert(
+            "services.describe",
+            Cmd {
+                help: "Show a service's methods and payload schemas",
+                usage: "services.describe <service_id> [json]",
+                f: |rt, line| rt.services_describe_cmd(line),
             },
         );
 
@@ -86,6 +92,65 @@ impl ConsoleRuntime {
             },
         );
 
+        cmds.insert(
+            "version",
+            Cmd {
+                help: "Show engine build info",
+                usage: "version [json]",
+                f: |_, line| {
+                    let info = BuildInfo::get();
+                    match line.split_whitespace().nth(1) {
+                        None => Ok(info.summary()),
+                        Some("json") => Ok(info.to_json().to_string()),
+                        Some(other) => Err(format!("version: unknown option '{other}'")),
+                    }
+                },
+            },
+        );
+
+        cmds.insert(
+            "cvars",
+            Cmd {
+                help: "List configuration variables",
+                usage: "cvars [prefix]",
+                f: |rt, line| rt.cvars_cmd(line),
+            },
+        );
+
+        cmds.insert(
+            "set",
+            Cmd {
+                help: "Set a configuration variable",
+                usage: "set <cvar> <value>",
+                f: |_, line| {
+                    let rest = line["set".len()..].trim_start();
+                    let name = rest.split_whitespace().next().unwrap_or("");
+                    let value = rest[name.len()..].trim();
+                    if name.is_empty() || value.is_empty() {
+                        return Err("usage: set <cvar> <value>".into());
+                    }
+                    CVars::global()
+                        .set_text(name, value)
+                        .map(|v| format!("{name} = {v}"))
+                },
+            },
+        );
+
+        cmds.insert(
+            "reset",
+            Cmd {
+                help: "Restore a configuration variable's default",
+                usage: "reset <cvar>",
+                f: |_, line| {
+                    let name = line.split_whitespace().nth(1).unwrap_or("");
+                    if name.is_empty() {
+                        return Err("usage: reset <cvar>".into());
+                    }
+                    CVars::global().reset(name).map(|v| format!("{name} = {v}"))
+                },
+            },
+        );
+
         cmds.insert(
             "quit",
             Cmd {
@@ -141,18 +206,51 @@ impl ConsoleRuntime {
             return (c.f)(self, line);
         }
 
+        if let Some(c) = registry::get(head) {
+            let args = line[head.len()..].trim_start();
+            return (c.handler)(args);
+        }
+
+        // `<cvar>` prints the value, `<cvar> <value>` sets it.
+        let cvars = CVars::global();
+        if let Some(v) = cvars.get(head) {
+            let value = line[head.len()..].trim();
+            if value.is_empty() {
+                return Ok(format!("{head} = {v}"));
+            }
+            return cvars.set_text(head, value).map(|v| format!("{head} = {v}"));
+        }
+
         Err(format!("unknown command: {head}"))
     }
 
+    #[inline]
+    pub(super) fn has_builtin(&self, name: &str) -> bool {
+        self.cmds.contains_key(name)
+    }
+
+    /// Remote permission a service declared for one of its console commands.
+    pub(super) fn dyn_remote_level(&self, head: &str) -> Option<PermissionLevel> {
+        self.refresh_if_services_changed();
+        self.dyn_cmds.lock().ok()?.get(head).and_then(|d| d.remote)
+    }
+
     pub fn complete(&self, input: &str) -> Vec<String> {
         self.refresh_if_services_changed();
 
         let s = input.trim_start();
 
-        if let Some(rest) = s.strip_prefix("describe ") {
+        if let Some(rest) = s
+            .strip_prefix("describe ")
+            .or_else(|| s.strip_prefix("services.describe "))
+        {
             return self.complete_service_id(rest.trim());
         }
 
+        if let Some(rest) = s.strip_prefix("set ").or_else(|| s.strip_prefix("reset ")) {
+            return complete_cvar(rest.trim());
+        }
+
         if let Some(rest) = s.strip_prefix("call ") {
             let mut parts = rest.split_whitespace();
             let sid = parts.next().unwrap_or("");
@@ -183,6 +281,14 @@ impl ConsoleRuntime {
             }
         }
 
+        for (k, _) in registry::snapshot() {
+            if k.starts_with(head) {
+                out.push(k);
+            }
+        }
+
+        out.extend(complete_cvar(head));
+
         out.sort();
         out.dedup();
         out
@@ -217,28 +323,53 @@ impl ConsoleRuntime {
             };
         }
 
-        if head == "describe" {
+        if head == "describe" || head == "services.describe" {
             let prefix = if tokens.len() >= 2 { tokens[1] } else { "" };
             let signature = self
                 .cmds
-                .get("describe")
+                .get(head)
                 .map(|c| c.usage.to_string())
                 .unwrap_or_default();
 
             for sid in self.complete_service_id(prefix) {
-                let insert = format!("describe {} ", sid);
+                let insert = format!("{} {} ", head, sid);
                 items.push(SuggestItem {
                     kind: "service".into(),
                     display: sid.clone(),
                     insert,
                     help: "service id".into(),
-                    usage: "describe <service_id>".into(),
+                    usage: signature.clone(),
                 });
             }
 
             return SuggestResponse { signature, items };
         }
 
+        if head == "set" || head == "reset" {
+            let prefix = if tokens.len() >= 2 { tokens[1] } else { "" };
+            let signature = self
+                .cmds
+                .get(head)
+                .map(|c| c.usage.to_string())
+                .unwrap_or_default();
+
+            if tokens.len() <= 2 && !(ends_with_space && tokens.len() == 2) {
+                for c in CVars::global().list() {
+                    if c.name.starts_with(prefix) {
+                        items.push(SuggestItem {
+                            kind: "cvar".into(),
+                            display: c.name.clone(),
+                            insert: format!("{} {} ", head, c.name),
+                            help: c.help,
+                            usage: format!("{} {}", c.name, c.value.kind().as_str()),
+                        });
+                    }
+                }
+            }
+
+            return SuggestResponse { signature, items };
+        }
+
         if head == "call" {
             let signature = self
                 .cmds
@@ -291,6 +422,20 @@ impl ConsoleRuntime {
             }
         }
 
+        if let Some(c) = registry::get(head) {
+            return SuggestResponse {
+                signature: c.usage,
+                items,
+            };
+        }
+
+        if let Some(v) = CVars::global().get(head) {
+            return SuggestResponse {
+                signature: format!("{head} <{}> (= {v})", v.kind().as_str()),
+                items,
+            };
+        }
+
         SuggestResponse {
             signature: String::new(),
             items,
@@ -333,6 +478,61 @@ impl ConsoleRuntime {
                 }
             }
         }
+
+        for (name, c) in registry::snapshot() {
+            if name.starts_with(prefix) {
+                let insert = if c.usage.contains('<') {
+                    format!("{} ", name)
+                } else {
+                    name.clone()
+                };
+                out.push(SuggestItem {
+                    kind: "command".into(),
+                    display: name,
+                    insert,
+                    help: c.help,
+                    usage: c.usage,
+                });
+            }
+        }
+
+        for c in CVars::global().list() {
+            if c.name.starts_with(prefix) {
+                out.push(SuggestItem {
+                    kind: "cvar".into(),
+                    insert: format!("{} ", c.name),
+                    usage: format!("{} <{}> (= {})", c.name, c.value.kind().as_str(), c.value),
+                    display: c.name,
+                    help: c.help,
+                });
+            }
+        }
+    }
+
+    fn cvars_cmd(&self, line: &str) -> Result<String, String> {
+        let prefix = line.split_whitespace().nth(1).unwrap_or("");
+        let mut out = String::new();
+        for c in CVars::global().list() {
+            if !c.name.starts_with(prefix) {
+                continue;
+            }
+            out.push_str(&format!("{} = {}", c.name, c.value));
+            if c.value != c.default {
+                out.push_str(&format!(" (default {})", c.default));
+            }
+            if c.persist {
+                out.push_str(" [persist]");
+            }
+            if !c.help.is_empty() {
+                out.push_str("  - ");
+                out.push_str(&c.help);
+            }
+            out.push('\n');
+        }
+        if out.is_empty() {
+            return Ok(format!("no cvars match '{prefix}'"));
+        }
+        Ok(out.trim_end().to_string())
     }
 
     fn complete_service_id(&self, prefix: &str) -> Vec<String> {
@@ -511,6 +711,7 @@ impl ConsoleRuntime {
                         service_id: sid,
                         method,
                         payload,
+                        remote: entry_cmd.remote.as_deref().and_then(PermissionLevel::parse),
                     },
                 );
             }
@@ -558,6 +759,73 @@ impl ConsoleRuntime {
         Ok(raw)
     }
 
+    fn services_describe_cmd(&self, line: &str) -> Result<String, String> {
+        let mut it = line.split_whitespace();
+        let _ = it.next();
+
+        let sid = it.next().unwrap_or("").trim();
+        if sid.is_empty() {
+            return Err("usage: services.describe <service_id> [json]".into());
+        }
+
+        let d = crate::host_services::service_descriptor(sid)
+            .ok_or_else(|| format!("unknown service: {sid}"))?;
+        match it.next() {
+            None => Ok(d.pretty()),
+            Some("json") => serde_json::to_string_pretty(&d).map_err(|e| e.to_string()),
+            Some(other) => Err(format!("services.describe: unknown option '{other}'")),
+        }
+    }
+
+    fn services_cmd(&self, line: &str) -> Result<String, String> {
+        let mut it = line.split_whitespace();
+        let _ = it.next();
+
+        match it.next().unwrap_or("") {
+            "" => {
+                let c = host_context::ctx();
+                let g = c
+                    .services
+                    .lock()
+                    .map_err(|_| "services mutex poisoned".to_string())?;
+                let mut ids = g.keys().cloned().collect::<Vec<_>>();
+                ids.sort();
+                Ok(ids.join("\n"))
+            }
+            "stats" => Ok(self.services_stats_text()),
+            "reset" => {
+                crate::host_services::reset_service_call_stats();
+                Ok("service stats reset".into())
+            }
+            other => Err(format!(
+                "unknown services subcommand: {other} (usage: services [stats|reset])"
+            )),
+        }
+    }
+
+    fn services_stats_text(&self) -> String {
+        let stats = crate::host_services::service_call_stats();
+        if stats.is_empty() {
+            return "no service calls recorded".into();
+        }
+
+        let mut out = String::new();
+        out.push_str("service  calls  errors  err%  throttled  mean_us  max_us\n");
+        for (id, s) in stats.iter() {
+            out.push_str(&format!(
+                "{}  {}  {}  {:.1}  {}  {}  {}\n",
+                id,
+                s.calls,
+                s.errors,
+                s.error_rate() * 100.0,
+                s.throttled,
+                s.mean_latency().as_micros(),
+                s.max_latency.as_micros()
+            ));
+        }
+        out.trim_end().to_string()
+    }
+
     fn call_service_cmd(&self, line: &str) -> Result<String, String> {
         let mut it = line.split_whitespace();
         let _ = it.next();
@@ -579,33 +847,14 @@ impl ConsoleRuntime {
         method: &str,
         payload: &[u8],
     ) -> Result<String, String> {
-        let c = host_context::ctx();
-        let g = c
-            .services
-            .lock()
-            .map_err(|_| "services mutex poisoned".to_string())?;
-
-        let entry = g
-            .get(service_id)
-            .ok_or_else(|| format!("unknown service: {service_id}"))?;
-
-        let res = entry.service.call(
-            abi_stable::std_types::RString::from(method),
-            newengine_plugin_api::Blob::from(payload.to_vec()),
-        );
+        // Routed through the host entrypoint so console calls show up in service metrics.
+        let bytes = crate::host_services::call_service_v1(service_id, method, payload)?;
 
-        match res.into_result() {
-            Ok(b) => {
-                let bytes = b.into_vec();
-                if let Ok(v) = serde_json::from_slice::<serde_json::Value>(&bytes) {
-                    return Ok(serde_json::to_string_pretty(&v).unwrap_or_else(|_| {
-                        String::from_utf8_lossy(&bytes).to_string()
-                    }));
-                }
-                Ok(String::from_utf8_lossy(&bytes).to_string())
-            }
-            Err(e) => Err(e.to_string()),
+        if let Ok(v) = serde_json::from_slice::<serde_json::Value>(&bytes) {
+            return Ok(serde_json::to_string_pretty(&v)
+                .unwrap_or_else(|_| String::from_utf8_lossy(&bytes).to_string()));
         }
+        Ok(String::from_utf8_lossy(&bytes).to_string())
     }
 
     pub fn help_text(&self) -> Result<String, String> {
@@ -635,10 +884,32 @@ impl ConsoleRuntime {
             }
         }
 
+        let registered = registry::snapshot();
+        if !registered.is_empty() {
+            out.push('\n');
+            out.push_str("From modules:\n");
+            for (name, c) in registered.iter() {
+                out.push_str("  ");
+                out.push_str(name);
+                out.push_str("  - ");
+                out.push_str(&c.help);
+                out.push('\n');
+            }
+        }
+
         Ok(out.trim_end().to_string())
     }
 }
 
+fn complete_cvar(prefix: &str) -> Vec<String> {
+    CVars::global()
+        .list()
+        .into_iter()
+        .map(|c| c.name)
+        .filter(|n| n.starts_with(prefix))
+        .collect()
+}
+
 impl ConsoleRuntime {
     pub fn shared() -> Arc<Self> {
         Arc::new(Self::new())
@@ -1,18 +1,45 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 use super::method::{method, COMMAND_SERVICE_ID};
+use super::remote::{RemoteConsole, RemoteConsolePolicy};
 use super::runtime::ConsoleRuntime;
 use super::types::SuggestResponse;
 
+use crate::build_info::BuildInfo;
 use crate::plugins::host_api;
 
 use abi_stable::std_types::{RResult, RString};
 use newengine_plugin_api::{Blob, CapabilityId, MethodName, ServiceV1, ServiceV1Dyn};
+use serde::Deserialize;
 use serde_json::json;
 use std::sync::{Arc, OnceLock};
 
 struct CommandService {
     rt: Arc<ConsoleRuntime>,
+    remote: Arc<RemoteConsole>,
+}
+
+#[derive(Deserialize)]
+struct RemoteExecRequest {
+    token: String,
+    line: String,
+}
+
+#[derive(Deserialize)]
+struct RemoteAuthRequest {
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct RemoteAuditRequest {
+    token: String,
+    #[serde(default = "default_audit_limit")]
+    limit: usize,
+}
+
+#[inline]
+fn default_audit_limit() -> usize {
+    100
 }
 
 impl ServiceV1 for CommandService {
@@ -24,20 +51,30 @@ impl ServiceV1 for CommandService {
         RString::from(
             json!({
                 "id": COMMAND_SERVICE_ID,
-                "version": 2,
+                "version": 6,
+                "permissions": ["process"],
                 "methods": [
                     { "name": method::EXEC, "payload": "utf8 line", "returns": "json {ok, output?, error?}" },
                     { "name": method::COMPLETE, "payload": "utf8 prefix", "returns": "json {items:[string]}" },
                     { "name": method::SUGGEST, "payload": "utf8 input", "returns": "json SuggestResponse" },
-                    { "name": method::REFRESH, "payload": "empty", "returns": "json {ok:true}" }
+                    { "name": method::REFRESH, "payload": "empty", "returns": "json {ok:true}" },
+                    { "name": method::BUILD_INFO, "payload": "empty", "returns": "json BuildInfo" },
+                    { "name": method::REMOTE_EXEC, "payload": "json {token, line}", "returns": "json {ok, output?, error?}" },
+                    { "name": method::REMOTE_AUDIT, "payload": "json {token, limit?}", "returns": "json {ok, entries?, error?}" },
+                    { "name": method::REMOTE_AUTH, "payload": "json {token}", "returns": "json {ok, client?, level?, error?}" }
                 ],
                 "console": {
                     "commands": [
                         { "name": "help", "help": "List commands", "usage": "help" },
-                        { "name": "services", "help": "List services", "usage": "services" },
+                        { "name": "services", "help": "List services (or call stats)", "usage": "services [stats|reset]" },
+                        { "name": "services.describe", "help": "Show a service's methods and payload schemas", "usage": "services.describe <service_id> [json]" },
                         { "name": "refresh", "help": "Refresh console commands", "usage": "refresh" },
                         { "name": "describe", "help": "Describe a service", "usage": "describe <service_id>" },
                         { "name": "call", "help": "Call a service method", "usage": "call <service_id> <method> [payload]" },
+                        { "name": "version", "help": "Show engine build info", "usage": "version [json]" },
+                        { "name": "cvars", "help": "List configuration variables", "usage": "cvars [prefix]" },
+                        { "name": "set", "help": "Set a configuration variable", "usage": "set <cvar> <value>" },
+                        { "name": "reset", "help": "Restore a configuration variable's default", "usage": "reset <cvar>" },
                         { "name": "quit", "help": "Exit engine", "usage": "quit" }
                     ]
                 }
@@ -78,12 +115,62 @@ impl ServiceV1 for CommandService {
                 RResult::ROk(Blob::from(json!({ "ok": true }).to_string().into_bytes()))
             }
 
+            method::BUILD_INFO => {
+                let bytes = BuildInfo::get().to_json().to_string().into_bytes();
+                RResult::ROk(Blob::from(bytes))
+            }
+
+            method::REMOTE_EXEC => {
+                let out = serde_json::from_slice::<RemoteExecRequest>(payload.as_slice())
+                    .map_err(|e| format!("bad remote_exec json: {e}"))
+                    .and_then(|r| self.remote.exec(&self.rt, &r.token, &r.line));
+
+                let resp = match out {
+                    Ok(v) => json!({ "ok": true, "output": v }),
+                    Err(e) => json!({ "ok": false, "error": e }),
+                };
+
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+
+            method::REMOTE_AUTH => {
+                let out = serde_json::from_slice::<RemoteAuthRequest>(payload.as_slice())
+                    .map_err(|e| format!("bad remote_auth json: {e}"))
+                    .and_then(|r| self.remote.authorize(&r.token));
+
+                let resp = match out {
+                    Ok((client, level)) => json!({ "ok": true, "client": client, "level": level }),
+                    Err(e) => json!({ "ok": false, "error": e }),
+                };
+
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+
+            method::REMOTE_AUDIT => {
+                let out = serde_json::from_slice::<RemoteAuditRequest>(payload.as_slice())
+                    .map_err(|e| format!("bad remote_audit json: {e}"))
+                    .and_then(|r| self.remote.audit(&r.token, r.limit));
+
+                let resp = match out {
+                    Ok(v) => json!({ "ok": true, "entries": v }),
+                    Err(e) => json!({ "ok": false, "error": e }),
+                };
+
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+
             _ => RResult::RErr(RString::from("unknown method")),
         }
     }
 }
 
 static RT: OnceLock<Arc<ConsoleRuntime>> = OnceLock::new();
+static REMOTE: OnceLock<Arc<RemoteConsole>> = OnceLock::new();
+
+#[inline]
+fn remote() -> &'static Arc<RemoteConsole> {
+    REMOTE.get_or_init(|| Arc::new(RemoteConsole::new()))
+}
 
 pub fn init_console_service() {
     let rt = RT.get_or_init(|| Arc::new(ConsoleRuntime::new())).clone();
@@ -91,12 +178,26 @@ pub fn init_console_service() {
     // Prebuild caches once at boot.
     rt.refresh_dyn_commands();
 
-    let svc = CommandService { rt };
+    let svc = CommandService {
+        rt,
+        remote: remote().clone(),
+    };
     let dyn_svc = ServiceV1Dyn::from_value(svc, abi_stable::sabi_trait::TD_Opaque);
 
     let _ = host_api::host_register_service_impl(dyn_svc, false);
 }
 
+/// Whether `name` is one of the runtime's own commands (`help`, `call`, ...).
+pub(super) fn is_builtin_command(name: &str) -> bool {
+    RT.get().is_some_and(|r| r.has_builtin(name))
+}
+
 pub fn take_exit_requested() -> bool {
     RT.get().map(|r| r.take_exit_requested()).unwrap_or(false)
-}
\ No newline at end of file
+}
+
+/// Replaces the remote execution policy. Safe to call before or after the service is
+/// registered; remote calls are rejected until a policy with at least one token is set.
+pub fn set_remote_console_policy(policy: RemoteConsolePolicy) {
+    remote().set_policy(policy);
+}
@@ -1,5 +1,7 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+use super::remote::PermissionLevel;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Deserialize)]
@@ -17,6 +19,10 @@ pub struct ConsoleCmdEntry {
     pub method: Option<String>,
     #[serde(default)]
     pub payload: Option<String>,
+    /// Minimum remote permission level ("observer" | "operator" | "admin"); absent means
+    /// the command is local-only unless the remote policy whitelists it.
+    #[serde(default)]
+    pub remote: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -26,6 +32,7 @@ pub struct DynCommand {
     pub service_id: String,
     pub method: String,
     pub payload: DynPayload,
+    pub remote: Option<PermissionLevel>,
 }
 
 #[derive(Debug, Clone, Copy)]
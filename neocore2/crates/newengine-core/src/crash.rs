@@ -0,0 +1,320 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Crash reports for panics and native crashes.
+//!
+//! [`install`] sets a process-wide panic hook that writes a text report (panic message and
+//! location, the module and [`ModuleStage`] running on the panicking thread, the plugin if a
+//! plugin callback was running, build and system info, loaded plugins, backtrace) into the
+//! configured directory, flushes the logger, then runs the optional callback and the
+//! previously installed hook.
+//!
+//! Every panic produces a report, including those the engine recovers from by quarantining
+//! a module or disabling a plugin. With [`CrashConfig::abort`] the process aborts after the
+//! report instead, so nothing unwinds past a panic. The backtrace is symbolized in-process
+//! when debug info is available.
+//!
+//! With the `minidump` feature, [`install`] also starts a monitor process that writes a
+//! minidump into the same directory when the process crashes natively (access violation,
+//! illegal instruction, abort, including inside plugins). Hosts must call
+//! [`run_monitor_if_requested`] at the top of `main` so the monitor can reuse their
+//! executable.
+
+use crate::build_info::BuildInfo;
+use crate::error::ModuleStage;
+use crate::module::{current_module_id, current_module_stage, panic_message};
+use crate::plugins::host_context::current_plugin_id;
+use crate::system_info::SystemInfo;
+
+use std::backtrace::Backtrace;
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::panic::Location;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Once, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, fs, panic, process, thread};
+
+#[cfg(feature = "minidump")]
+mod minidump;
+
+#[cfg(feature = "minidump")]
+pub use minidump::{run_monitor_if_requested, MONITOR_ARG};
+
+/// Without the `minidump` feature there is no monitor process; returns immediately.
+#[cfg(not(feature = "minidump"))]
+#[inline]
+pub fn run_monitor_if_requested() {}
+
+pub type CrashCallback = Arc<dyn Fn(&CrashReport) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct CrashConfig {
+    /// Directory reports are written to; created on first crash.
+    pub dir: PathBuf,
+    /// Abort the process after reporting instead of letting the panic unwind.
+    pub abort: bool,
+    /// Write a minidump on native crashes (needs the `minidump` feature).
+    pub minidump: bool,
+    /// Runs after the report is written (and before aborting). Keep it short: it runs on the
+    /// panicking thread, possibly with engine state half-updated.
+    pub callback: Option<CrashCallback>,
+}
+
+impl Default for CrashConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("crashes"),
+            abort: false,
+            minidump: true,
+            callback: None,
+        }
+    }
+}
+
+impl CrashConfig {
+    #[inline]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Defaults overridden by `NEWENGINE_CRASH_DIR`, `NEWENGINE_CRASH_ABORT` and
+    /// `NEWENGINE_CRASH_MINIDUMP` (`1`/`true`).
+    pub fn from_env() -> Self {
+        let mut cfg = Self::default();
+        if let Some(dir) = env::var_os("NEWENGINE_CRASH_DIR").filter(|v| !v.is_empty()) {
+            cfg.dir = PathBuf::from(dir);
+        }
+        if let Ok(v) = env::var("NEWENGINE_CRASH_ABORT") {
+            cfg.abort = matches!(v.trim(), "1" | "true");
+        }
+        if let Ok(v) = env::var("NEWENGINE_CRASH_MINIDUMP") {
+            cfg.minidump = matches!(v.trim(), "1" | "true");
+        }
+        cfg
+    }
+
+    #[inline]
+    pub fn with_abort(mut self, abort: bool) -> Self {
+        self.abort = abort;
+        self
+    }
+
+    #[inline]
+    pub fn with_minidump(mut self, minidump: bool) -> Self {
+        self.minidump = minidump;
+        self
+    }
+
+    #[inline]
+    pub fn with_callback(mut self, f: impl Fn(&CrashReport) + Send + Sync + 'static) -> Self {
+        self.callback = Some(Arc::new(f));
+        self
+    }
+}
+
+/// What the hook knows about a panic.
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    pub message: String,
+    /// `file:line:column` of the panic.
+    pub location: Option<String>,
+    pub thread: String,
+    /// Engine module whose callback was running on the panicking thread.
+    pub module_id: Option<&'static str>,
+    pub stage: Option<ModuleStage>,
+    /// Plugin whose callback was running on the panicking thread.
+    pub plugin_id: Option<String>,
+    pub backtrace: String,
+    pub unix_ms: u64,
+    /// `(id, version)` of the plugins loaded at the time.
+    pub plugins: Vec<(String, String)>,
+    /// Report file; `None` if it could not be written.
+    pub path: Option<PathBuf>,
+}
+
+impl CrashReport {
+    /// The report file contents.
+    pub fn render(&self) -> String {
+        let mut s = String::with_capacity(4096);
+        let _ = writeln!(s, "NewEngine crash report");
+        let _ = writeln!(s, "time_unix_ms: {}", self.unix_ms);
+        let _ = writeln!(s, "thread: {}", self.thread);
+        let _ = writeln!(s, "message: {}", self.message);
+        let _ = writeln!(
+            s,
+            "location: {}",
+            self.location.as_deref().unwrap_or("<unknown>")
+        );
+        match (self.module_id, self.stage) {
+            (Some(id), Some(stage)) => {
+                let _ = writeln!(s, "module: {id} (stage={})", stage.as_str());
+            }
+            (Some(id), None) => {
+                let _ = writeln!(s, "module: {id}");
+            }
+            _ => {
+                let _ = writeln!(s, "module: <none>");
+            }
+        }
+        let _ = writeln!(
+            s,
+            "plugin: {}",
+            self.plugin_id.as_deref().unwrap_or("<none>")
+        );
+
+        let _ = writeln!(s, "\n[build]\n{}", BuildInfo::get().summary());
+
+        let sys = SystemInfo::collect();
+        let _ = writeln!(
+            s,
+            "\n[system]\nos={} arch={} family={} pid={} logical_cpus={}",
+            sys.os,
+            sys.arch,
+            sys.family,
+            sys.pid,
+            sys.logical_cpus
+                .map_or_else(|| "?".to_owned(), |n| n.to_string())
+        );
+        if let Some(exe) = &sys.exe {
+            let _ = writeln!(s, "exe: {}", exe.display());
+        }
+        if let Some(cwd) = &sys.cwd {
+            let _ = writeln!(s, "cwd: {}", cwd.display());
+        }
+
+        let _ = writeln!(s, "\n[plugins]");
+        if self.plugins.is_empty() {
+            let _ = writeln!(s, "<none>");
+        }
+        for (id, version) in self.plugins.iter() {
+            let _ = writeln!(s, "{id} {version}");
+        }
+
+        let _ = writeln!(s, "\n[backtrace]\n{}", self.backtrace);
+        s
+    }
+}
+
+static CONFIG: RwLock<Option<CrashConfig>> = RwLock::new(None);
+static HOOK: Once = Once::new();
+#[cfg(feature = "minidump")]
+static NATIVE: Once = Once::new();
+static PLUGINS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+thread_local! {
+    /// Set while the hook runs, so a panic inside it does not recurse.
+    static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Installs the crash hook, or replaces the config of an installed one. The hook that was
+/// installed before the first call keeps running after each report. The minidump monitor is
+/// started by the first call that enables it and keeps that call's directory.
+pub fn install(config: CrashConfig) {
+    #[cfg(feature = "minidump")]
+    if config.minidump {
+        NATIVE.call_once(|| match minidump::attach(&config.dir) {
+            Ok(()) => log::info!(target: "crash", "crash.minidump dir='{}'", config.dir.display()),
+            Err(e) => log::warn!(target: "crash", "crash.minidump unavailable: {e}"),
+        });
+    }
+
+    if let Ok(mut g) = CONFIG.write() {
+        *g = Some(config);
+    }
+
+    HOOK.call_once(|| {
+        let prev = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if IN_HOOK.with(|f| f.replace(true)) {
+                prev(info);
+                return;
+            }
+            let abort = report_panic(panic_message(info.payload()), info.location());
+            prev(info);
+            IN_HOOK.with(|f| f.set(false));
+            if abort {
+                process::abort();
+            }
+        }));
+    });
+}
+
+/// Whether [`install`] was called.
+#[inline]
+pub fn is_installed() -> bool {
+    HOOK.is_completed()
+}
+
+pub(crate) fn note_plugin_loaded(id: &str, version: &str) {
+    if let Ok(mut g) = PLUGINS.lock() {
+        g.push((id.to_owned(), version.to_owned()));
+    }
+}
+
+pub(crate) fn note_plugins_unloaded() {
+    if let Ok(mut g) = PLUGINS.lock() {
+        g.clear();
+    }
+}
+
+/// Builds and writes the report; returns whether to abort.
+fn report_panic(message: String, location: Option<&Location<'_>>) -> bool {
+    let Some(config) = CONFIG.read().ok().and_then(|g| g.clone()) else {
+        return false;
+    };
+
+    let mut report = CrashReport {
+        message,
+        location: location.map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+        thread: thread::current().name().unwrap_or("<unnamed>").to_owned(),
+        module_id: current_module_id(),
+        stage: current_module_stage(),
+        plugin_id: current_plugin_id(),
+        backtrace: Backtrace::force_capture().to_string(),
+        unix_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        // `try_lock`: the panic may have happened while the list was being updated.
+        plugins: PLUGINS.try_lock().map(|g| g.clone()).unwrap_or_default(),
+        path: None,
+    };
+
+    match write_report(&config.dir, &report) {
+        Ok(path) => {
+            log::error!(
+                target: "crash",
+                "crash.report path='{}' module={} stage={} msg='{}'",
+                path.display(),
+                report.module_id.unwrap_or("-"),
+                report.stage.map_or("-", ModuleStage::as_str),
+                report.message
+            );
+            report.path = Some(path);
+        }
+        Err(e) => {
+            log::error!(
+                target: "crash",
+                "crash.report_failed dir='{}' err='{e}' msg='{}'",
+                config.dir.display(),
+                report.message
+            );
+        }
+    }
+    log::logger().flush();
+
+    if let Some(cb) = config.callback.as_ref() {
+        cb(&report);
+    }
+    config.abort
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("crash-{}-{}.txt", report.unix_ms, process::id()));
+    fs::write(&path, report.render())?;
+    Ok(path)
+}
@@ -0,0 +1,121 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Native crash minidumps.
+//!
+//! A process cannot reliably dump itself after an access violation or an abort, so
+//! [`attach`] starts a monitor: the same executable, run with [`MONITOR_ARG`]. The engine
+//! process installs signal/exception handlers that ask the monitor for a dump over a local
+//! socket; the monitor writes `crash-<unix_ms>-<pid>.dmp` next to the text reports and exits
+//! with the engine. Hosts call [`run_monitor_if_requested`] first thing in `main`.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, process, thread};
+
+/// First argument of a monitor process: `<exe> --newengine-crash-monitor <socket> <pid> <dir>`.
+pub const MONITOR_ARG: &str = "--newengine-crash-monitor";
+
+/// How long [`attach`] waits for the monitor to listen.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Starts the monitor process and installs the native crash handlers.
+pub(super) fn attach(dir: &Path) -> Result<(), String> {
+    let pid = process::id();
+    let socket = format!("newengine-crash-{pid}");
+    let exe = env::current_exe().map_err(|e| format!("current exe: {e}"))?;
+
+    Command::new(exe)
+        .arg(MONITOR_ARG)
+        .arg(&socket)
+        .arg(pid.to_string())
+        .arg(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("spawn monitor: {e}"))?;
+
+    let started = std::time::Instant::now();
+    let client = loop {
+        match minidumper::Client::with_name(socket.as_str()) {
+            Ok(c) => break c,
+            Err(e) if started.elapsed() >= CONNECT_TIMEOUT => {
+                return Err(format!("connect to monitor: {e}"));
+            }
+            Err(_) => thread::sleep(Duration::from_millis(10)),
+        }
+    };
+
+    // SAFETY: the closure only sends the crash context to the monitor and waits for the dump;
+    // it does not allocate or take locks held by the crashed thread.
+    let event = unsafe {
+        crash_handler::make_crash_event(move |ctx: &crash_handler::CrashContext| {
+            crash_handler::CrashEventResult::Handled(client.request_dump(ctx).is_ok())
+        })
+    };
+    let handler =
+        crash_handler::CrashHandler::attach(event).map_err(|e| format!("attach handler: {e}"))?;
+    // Stays attached for the life of the process.
+    std::mem::forget(handler);
+    Ok(())
+}
+
+/// Runs the monitor and exits if this process was started as one by [`attach`]; returns
+/// otherwise.
+pub fn run_monitor_if_requested() {
+    let mut args = env::args().skip(1);
+    if args.next().as_deref() != Some(MONITOR_ARG) {
+        return;
+    }
+    let (Some(socket), Some(pid), Some(dir)) = (args.next(), args.next(), args.next()) else {
+        process::exit(2);
+    };
+
+    let server = match minidumper::Server::with_name(socket.as_str()) {
+        Ok(s) => s,
+        Err(_) => process::exit(1),
+    };
+    let handler = DumpWriter {
+        dir: PathBuf::from(dir),
+        pid,
+    };
+    let shutdown = AtomicBool::new(false);
+    let code = match server.run(Box::new(handler), &shutdown, None) {
+        Ok(()) => 0,
+        Err(_) => 1,
+    };
+    process::exit(code);
+}
+
+struct DumpWriter {
+    dir: PathBuf,
+    /// Engine process id, for the file name.
+    pid: String,
+}
+
+impl minidumper::ServerHandler for DumpWriter {
+    fn create_minidump_file(&self) -> Result<(File, PathBuf), std::io::Error> {
+        fs::create_dir_all(&self.dir)?;
+        let unix_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let path = self.dir.join(format!("crash-{unix_ms}-{}.dmp", self.pid));
+        Ok((File::create(&path)?, path))
+    }
+
+    fn on_minidump_created(
+        &self,
+        result: Result<minidumper::MinidumpBinary, minidumper::Error>,
+    ) -> minidumper::LoopAction {
+        if let Ok(mut dump) = result {
+            use std::io::Write as _;
+            let _ = dump.file.flush();
+        }
+        minidumper::LoopAction::Exit
+    }
+
+    fn on_message(&self, _kind: u32, _buffer: Vec<u8>) {}
+}
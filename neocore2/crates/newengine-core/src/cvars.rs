@@ -0,0 +1,595 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Configuration variables: typed, named values registered by modules and tweaked at runtime.
+//!
+//! Values come from the `cvars` section of `config.json`, from the file persisted at the
+//! previous shutdown, and from the console (`set r.vsync false`, or just `r.vsync false`).
+//! A value set before its variable is registered is kept and applied on registration.
+//! Variables declared with [`CVarDef::persist`] are written back by [`CVars::save`], which the
+//! engine calls on shutdown.
+
+use crate::startup::StartupConfig;
+
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CVarKind {
+    Bool,
+    Int,
+    Float,
+    Str,
+}
+
+impl CVarKind {
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CVarKind::Bool => "bool",
+            CVarKind::Int => "int",
+            CVarKind::Float => "float",
+            CVarKind::Str => "string",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CVarValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl CVarValue {
+    #[inline]
+    pub fn kind(&self) -> CVarKind {
+        match self {
+            CVarValue::Bool(_) => CVarKind::Bool,
+            CVarValue::Int(_) => CVarKind::Int,
+            CVarValue::Float(_) => CVarKind::Float,
+            CVarValue::Str(_) => CVarKind::Str,
+        }
+    }
+
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            CVarValue::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            CVarValue::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Float value; ints widen.
+    #[inline]
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            CVarValue::Float(v) => Some(*v),
+            CVarValue::Int(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            CVarValue::Str(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Parses console text as a value of `kind`. Bools accept `1/0`, `true/false`, `on/off`
+    /// and `yes/no`; strings may be quoted.
+    pub fn parse(kind: CVarKind, text: &str) -> Result<Self, String> {
+        let t = text.trim();
+        match kind {
+            CVarKind::Bool => match t.to_ascii_lowercase().as_str() {
+                "1" | "true" | "on" | "yes" => Ok(CVarValue::Bool(true)),
+                "0" | "false" | "off" | "no" => Ok(CVarValue::Bool(false)),
+                _ => Err(format!("expected bool, got '{t}'")),
+            },
+            CVarKind::Int => t
+                .parse::<i64>()
+                .map(CVarValue::Int)
+                .map_err(|_| format!("expected int, got '{t}'")),
+            CVarKind::Float => t
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .map(CVarValue::Float)
+                .ok_or_else(|| format!("expected float, got '{t}'")),
+            CVarKind::Str => {
+                let unquoted = t
+                    .strip_prefix('"')
+                    .and_then(|s| s.strip_suffix('"'))
+                    .unwrap_or(t);
+                Ok(CVarValue::Str(unquoted.to_owned()))
+            }
+        }
+    }
+
+    /// Converts a `config.json` value; strings are parsed so `"1"` works for an int.
+    fn from_json(kind: CVarKind, v: &Value) -> Result<Self, String> {
+        match (kind, v) {
+            (CVarKind::Bool, Value::Bool(b)) => Ok(CVarValue::Bool(*b)),
+            (CVarKind::Int, Value::Number(n)) => n
+                .as_i64()
+                .map(CVarValue::Int)
+                .ok_or_else(|| format!("expected int, got {n}")),
+            (CVarKind::Float, Value::Number(n)) => n
+                .as_f64()
+                .map(CVarValue::Float)
+                .ok_or_else(|| format!("expected float, got {n}")),
+            (CVarKind::Str, Value::String(s)) => Ok(CVarValue::Str(s.clone())),
+            (_, Value::String(s)) => Self::parse(kind, s),
+            (_, other) => Err(format!("expected {}, got {other}", kind.as_str())),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            CVarValue::Bool(v) => Value::from(*v),
+            CVarValue::Int(v) => Value::from(*v),
+            CVarValue::Float(v) => Value::from(*v),
+            CVarValue::Str(v) => Value::from(v.as_str()),
+        }
+    }
+
+    /// `self` as a value of `kind`; ints widen to floats, nothing else converts.
+    fn coerce(self, kind: CVarKind) -> Result<Self, String> {
+        match (self, kind) {
+            (CVarValue::Int(v), CVarKind::Float) => Ok(CVarValue::Float(v as f64)),
+            (v, k) if v.kind() == k => Ok(v),
+            (v, k) => Err(format!(
+                "expected {}, got {}",
+                k.as_str(),
+                v.kind().as_str()
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CVarValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CVarValue::Bool(v) => write!(f, "{v}"),
+            CVarValue::Int(v) => write!(f, "{v}"),
+            CVarValue::Float(v) => write!(f, "{v}"),
+            CVarValue::Str(v) => write!(f, "\"{v}\""),
+        }
+    }
+}
+
+impl From<bool> for CVarValue {
+    #[inline]
+    fn from(v: bool) -> Self {
+        CVarValue::Bool(v)
+    }
+}
+
+impl From<i32> for CVarValue {
+    #[inline]
+    fn from(v: i32) -> Self {
+        CVarValue::Int(v.into())
+    }
+}
+
+impl From<i64> for CVarValue {
+    #[inline]
+    fn from(v: i64) -> Self {
+        CVarValue::Int(v)
+    }
+}
+
+impl From<u32> for CVarValue {
+    #[inline]
+    fn from(v: u32) -> Self {
+        CVarValue::Int(v.into())
+    }
+}
+
+impl From<f32> for CVarValue {
+    #[inline]
+    fn from(v: f32) -> Self {
+        CVarValue::Float(v.into())
+    }
+}
+
+impl From<f64> for CVarValue {
+    #[inline]
+    fn from(v: f64) -> Self {
+        CVarValue::Float(v)
+    }
+}
+
+impl From<&str> for CVarValue {
+    #[inline]
+    fn from(v: &str) -> Self {
+        CVarValue::Str(v.to_owned())
+    }
+}
+
+impl From<String> for CVarValue {
+    #[inline]
+    fn from(v: String) -> Self {
+        CVarValue::Str(v)
+    }
+}
+
+/// Called with the variable name and its new value after every change. Runs on the thread
+/// that made the change, outside the variable lock.
+pub type CVarCallback = Arc<dyn Fn(&str, &CVarValue) + Send + Sync>;
+
+/// Declaration of a variable, e.g.
+/// `CVarDef::new("r.vsync", true).help("Wait for vblank").persist()`.
+#[derive(Debug, Clone)]
+pub struct CVarDef {
+    name: String,
+    default: CVarValue,
+    help: String,
+    persist: bool,
+}
+
+impl CVarDef {
+    #[inline]
+    pub fn new(name: impl Into<String>, default: impl Into<CVarValue>) -> Self {
+        Self {
+            name: name.into(),
+            default: default.into(),
+            help: String::new(),
+            persist: false,
+        }
+    }
+
+    #[inline]
+    pub fn help(mut self, help: impl Into<String>) -> Self {
+        self.help = help.into();
+        self
+    }
+
+    /// Write the value back to the cvars file on shutdown.
+    #[inline]
+    pub fn persist(mut self) -> Self {
+        self.persist = true;
+        self
+    }
+}
+
+/// Snapshot of one variable, as listed by [`CVars::list`].
+#[derive(Debug, Clone)]
+pub struct CVarInfo {
+    pub name: String,
+    pub value: CVarValue,
+    pub default: CVarValue,
+    pub help: String,
+    pub persist: bool,
+}
+
+struct CVarEntry {
+    value: CVarValue,
+    default: CVarValue,
+    help: String,
+    persist: bool,
+    callbacks: Vec<CVarCallback>,
+}
+
+/// Value for a variable that is not registered yet.
+struct Pending {
+    value: Value,
+    /// Read from the cvars file; written back so variables of modules that did not load this
+    /// session keep their value.
+    persisted: bool,
+}
+
+#[derive(Default)]
+struct Store {
+    vars: BTreeMap<String, CVarEntry>,
+    pending: BTreeMap<String, Pending>,
+    file: Option<PathBuf>,
+}
+
+/// Handle to the engine's configuration variables. Clones share the same variables; the
+/// engine inserts one into its resources and the console reads the same set.
+#[derive(Clone)]
+pub struct CVars {
+    store: Arc<Mutex<Store>>,
+}
+
+static GLOBAL: OnceLock<CVars> = OnceLock::new();
+
+impl CVars {
+    /// The process-wide set used by the engine and the console.
+    #[inline]
+    pub fn global() -> CVars {
+        GLOBAL
+            .get_or_init(|| CVars {
+                store: Arc::new(Mutex::new(Store::default())),
+            })
+            .clone()
+    }
+
+    #[inline]
+    fn lock(&self) -> Result<MutexGuard<'_, Store>, String> {
+        self.store
+            .lock()
+            .map_err(|_| "cvars mutex poisoned".to_string())
+    }
+
+    /// Registers a variable and returns its initial value: the pending value from config or
+    /// the cvars file if there is one that fits the type, the default otherwise.
+    pub fn register(&self, def: CVarDef) -> Result<CVarValue, String> {
+        let name = def.name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(format!("invalid cvar name: '{name}'"));
+        }
+
+        let mut g = self.lock()?;
+        if g.vars.contains_key(name) {
+            return Err(format!("cvar already registered: {name}"));
+        }
+
+        let kind = def.default.kind();
+        let value = match g.pending.remove(name) {
+            Some(p) => CVarValue::from_json(kind, &p.value).unwrap_or_else(|e| {
+                log::warn!("cvars: '{name}' keeps its default: {e}");
+                def.default.clone()
+            }),
+            None => def.default.clone(),
+        };
+
+        g.vars.insert(
+            name.to_string(),
+            CVarEntry {
+                value: value.clone(),
+                default: def.default,
+                help: def.help,
+                persist: def.persist,
+                callbacks: Vec::new(),
+            },
+        );
+        Ok(value)
+    }
+
+    /// Removes a variable. Its value is kept as pending, so registering it again restores it.
+    pub fn unregister(&self, name: &str) -> bool {
+        let Ok(mut g) = self.lock() else {
+            return false;
+        };
+        let Some(e) = g.vars.remove(name) else {
+            return false;
+        };
+        g.pending.insert(
+            name.to_string(),
+            Pending {
+                value: e.value.to_json(),
+                persisted: e.persist,
+            },
+        );
+        true
+    }
+
+    #[inline]
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.lock().is_ok_and(|g| g.vars.contains_key(name))
+    }
+
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<CVarValue> {
+        self.lock().ok()?.vars.get(name).map(|e| e.value.clone())
+    }
+
+    #[inline]
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.get(name)?.as_bool()
+    }
+
+    #[inline]
+    pub fn get_int(&self, name: &str) -> Option<i64> {
+        self.get(name)?.as_int()
+    }
+
+    #[inline]
+    pub fn get_float(&self, name: &str) -> Option<f64> {
+        self.get(name)?.as_float()
+    }
+
+    #[inline]
+    pub fn get_str(&self, name: &str) -> Option<String> {
+        match self.get(name)? {
+            CVarValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Sets a registered variable and runs its change callbacks. The value must have the
+    /// variable's type (ints are accepted for floats). Returns the stored value.
+    pub fn set(&self, name: &str, value: impl Into<CVarValue>) -> Result<CVarValue, String> {
+        self.update(name, |kind| value.into().coerce(kind))
+    }
+
+    /// Sets a registered variable from console text, parsed according to its type.
+    pub fn set_text(&self, name: &str, text: &str) -> Result<CVarValue, String> {
+        self.update(name, |kind| CVarValue::parse(kind, text))
+    }
+
+    /// Restores the default value.
+    pub fn reset(&self, name: &str) -> Result<CVarValue, String> {
+        let default = self
+            .lock()?
+            .vars
+            .get(name)
+            .map(|e| e.default.clone())
+            .ok_or_else(|| format!("unknown cvar: {name}"))?;
+        self.set(name, default)
+    }
+
+    fn update(
+        &self,
+        name: &str,
+        make: impl FnOnce(CVarKind) -> Result<CVarValue, String>,
+    ) -> Result<CVarValue, String> {
+        let (value, callbacks) = {
+            let mut g = self.lock()?;
+            let e = g
+                .vars
+                .get_mut(name)
+                .ok_or_else(|| format!("unknown cvar: {name}"))?;
+            let value = make(e.value.kind()).map_err(|err| format!("{name}: {err}"))?;
+            if value == e.value {
+                return Ok(value);
+            }
+            e.value = value.clone();
+            (value, e.callbacks.clone())
+        };
+
+        log::debug!("cvars: {name} = {value}");
+        for cb in callbacks.iter() {
+            cb(name, &value);
+        }
+        Ok(value)
+    }
+
+    /// Runs `f` after every change of `name`.
+    pub fn on_change<F>(&self, name: &str, f: F) -> Result<(), String>
+    where
+        F: Fn(&str, &CVarValue) + Send + Sync + 'static,
+    {
+        let mut g = self.lock()?;
+        let e = g
+            .vars
+            .get_mut(name)
+            .ok_or_else(|| format!("unknown cvar: {name}"))?;
+        e.callbacks.push(Arc::new(f));
+        Ok(())
+    }
+
+    /// Registered variables, sorted by name.
+    pub fn list(&self) -> Vec<CVarInfo> {
+        let Ok(g) = self.lock() else {
+            return Vec::new();
+        };
+        g.vars
+            .iter()
+            .map(|(name, e)| CVarInfo {
+                name: name.clone(),
+                value: e.value.clone(),
+                default: e.default.clone(),
+                help: e.help.clone(),
+                persist: e.persist,
+            })
+            .collect()
+    }
+
+    /// Applies `values` (a `config.json` `cvars` section or a cvars file). Registered
+    /// variables change now; the others are applied when they register.
+    pub fn seed(&self, values: &BTreeMap<String, Value>) {
+        self.seed_from(values, false);
+    }
+
+    fn seed_from(&self, values: &BTreeMap<String, Value>, persisted: bool) {
+        for (name, v) in values.iter() {
+            let kind = match self.lock() {
+                Ok(g) => g.vars.get(name).map(|e| e.value.kind()),
+                Err(_) => return,
+            };
+            match kind {
+                Some(kind) => {
+                    if let Err(e) = CVarValue::from_json(kind, v).and_then(|v| self.set(name, v)) {
+                        log::warn!("cvars: {e}");
+                    }
+                }
+                None => {
+                    if let Ok(mut g) = self.lock() {
+                        g.pending.insert(
+                            name.clone(),
+                            Pending {
+                                value: v.clone(),
+                                persisted,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// File [`save`](Self::save) writes to; `None` turns persistence off.
+    pub fn set_persist_file(&self, path: Option<PathBuf>) {
+        if let Ok(mut g) = self.lock() {
+            g.file = path;
+        }
+    }
+
+    #[inline]
+    pub fn persist_file(&self) -> Option<PathBuf> {
+        self.lock().ok()?.file.clone()
+    }
+
+    /// Applies a cvars file written by [`save`](Self::save). A missing file is not an error.
+    pub fn load(&self, path: &Path) -> Result<usize, String> {
+        let data = match std::fs::read_to_string(path) {
+            Ok(d) => d,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("cvars: read {} failed: {e}", path.display())),
+        };
+        let values: BTreeMap<String, Value> = serde_json::from_str(&data)
+            .map_err(|e| format!("cvars: parse {} failed: {e}", path.display()))?;
+        self.seed_from(&values, true);
+        Ok(values.len())
+    }
+
+    /// Writes persistent variables to the persist file, if one is set. Returns how many
+    /// values were written.
+    pub fn save(&self) -> Result<usize, String> {
+        let (path, out) = {
+            let g = self.lock()?;
+            let Some(path) = g.file.clone() else {
+                return Ok(0);
+            };
+            let mut out = Map::new();
+            for (name, p) in g.pending.iter().filter(|(_, p)| p.persisted) {
+                out.insert(name.clone(), p.value.clone());
+            }
+            for (name, e) in g.vars.iter().filter(|(_, e)| e.persist) {
+                out.insert(name.clone(), e.value.to_json());
+            }
+            (path, out)
+        };
+
+        let n = out.len();
+        let text = serde_json::to_string_pretty(&Value::Object(out)).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("cvars: create {} failed: {e}", dir.display()))?;
+        }
+        std::fs::write(&path, text)
+            .map_err(|e| format!("cvars: write {} failed: {e}", path.display()))?;
+        Ok(n)
+    }
+}
+
+/// Seeds the global variables from the startup config: the `cvars` section first, then the
+/// persisted file, so values changed in a previous session win.
+pub fn apply_startup_cvars(startup: &StartupConfig) {
+    let cvars = CVars::global();
+    cvars.seed(&startup.cvars);
+    cvars.set_persist_file(startup.cvars_file.clone());
+    if let Some(path) = &startup.cvars_file {
+        match cvars.load(path) {
+            Ok(n) if n > 0 => log::info!("cvars: loaded {n} from {}", path.display()),
+            Ok(_) => {}
+            Err(e) => log::warn!("{e}"),
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! Report of [`Engine::validate_only`](crate::Engine::validate_only): a dry run that brings
+//! the engine up to the point of rendering the first frame, checks what it loaded and shuts
+//! down again. Serialized as JSON for CI gates on content and configuration changes.
+
+use serde::Serialize;
+use std::time::Instant;
+
+#[cfg(feature = "runtime")]
+use crate::assets::AssetManager;
+#[cfg(feature = "runtime")]
+use newengine_assets::{AssetState, ValidationIssue, ValidationSeverity, VALIDATION_RULES_PATH};
+#[cfg(feature = "runtime")]
+use std::collections::HashSet;
+#[cfg(feature = "runtime")]
+use std::path::Path;
+#[cfg(feature = "runtime")]
+use std::time::Duration;
+
+/// Upper bound for importing the whole assets root.
+#[cfg(feature = "runtime")]
+const IMPORT_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightCheck {
+    /// Dotted area, e.g. `modules.init` or `assets.import`.
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginEntry {
+    pub id: String,
+    pub version: String,
+}
+
+/// One importer binding: which importer handles which source extension.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportManifestEntry {
+    pub ext: String,
+    pub importer: String,
+    pub output_type: String,
+    pub priority: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DryRunReport {
+    /// No check reported an error.
+    pub ok: bool,
+    pub version: String,
+    pub elapsed_ms: u64,
+    pub checks: Vec<PreflightCheck>,
+    /// Registered modules, in registration order.
+    pub modules: Vec<String>,
+    pub plugins: Vec<PluginEntry>,
+    pub import_manifest: Vec<ImportManifestEntry>,
+    /// Source files under the assets root that were imported.
+    pub assets_checked: usize,
+    pub asset_failures: Vec<AssetFailure>,
+    #[cfg(feature = "runtime")]
+    pub validation_issues: Vec<ValidationIssue>,
+}
+
+impl DryRunReport {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            version: crate::BuildInfo::get().version_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn check(
+        &mut self,
+        name: impl Into<String>,
+        status: CheckStatus,
+        detail: impl Into<String>,
+    ) {
+        let check = PreflightCheck {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        };
+        match status {
+            CheckStatus::Ok => log::info!("dry_run: ok {} {}", check.name, check.detail),
+            CheckStatus::Warning => log::warn!("dry_run: warning {} {}", check.name, check.detail),
+            CheckStatus::Error => log::error!("dry_run: error {} {}", check.name, check.detail),
+        }
+        self.checks.push(check);
+        self.ok = self.errors() == 0;
+    }
+
+    #[inline]
+    pub fn errors(&self) -> usize {
+        self.count(CheckStatus::Error)
+    }
+
+    #[inline]
+    pub fn warnings(&self) -> usize {
+        self.count(CheckStatus::Warning)
+    }
+
+    /// Process exit code for CI: 0 when no check failed, 1 otherwise.
+    #[inline]
+    pub fn exit_code(&self) -> i32 {
+        if self.ok {
+            0
+        } else {
+            1
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|e| {
+            format!("{{\"ok\":false,\"error\":\"report serialization failed: {e}\"}}")
+        })
+    }
+
+    #[inline]
+    fn count(&self, status: CheckStatus) -> usize {
+        self.checks.iter().filter(|c| c.status == status).count()
+    }
+
+    pub(crate) fn finish(&mut self, started: Instant) {
+        self.ok = self.errors() == 0;
+        self.elapsed_ms = started.elapsed().as_millis() as u64;
+        log::info!(
+            "dry_run: done ok={} errors={} warnings={} elapsed_ms={}",
+            self.ok,
+            self.errors(),
+            self.warnings(),
+            self.elapsed_ms
+        );
+    }
+}
+
+/// Mounted sources, importer manifest, validation rules, a full import of the assets root and
+/// the content rule violations it produced.
+#[cfg(feature = "runtime")]
+pub(crate) fn check_assets(am: &AssetManager, report: &mut DryRunReport) {
+    let store = am.store();
+
+    let root = am.root();
+    if root.is_dir() {
+        report.check("assets.root", CheckStatus::Ok, root.display().to_string());
+    } else {
+        report.check(
+            "assets.root",
+            CheckStatus::Error,
+            format!("'{}' is not a directory", root.display()),
+        );
+    }
+
+    let mut bindings = store.importer_bindings();
+    bindings.sort_by(|a, b| a.ext.cmp(&b.ext).then(b.priority.0.cmp(&a.priority.0)));
+    report.import_manifest = bindings
+        .iter()
+        .map(|b| ImportManifestEntry {
+            ext: b.ext.trim_start_matches('.').to_ascii_lowercase(),
+            importer: b.stable_id.to_string(),
+            output_type: b.output_type_id.to_string(),
+            priority: b.priority.0,
+        })
+        .collect();
+    if report.import_manifest.is_empty() {
+        report.check(
+            "assets.importers",
+            CheckStatus::Warning,
+            "no importers registered",
+        );
+    } else {
+        report.check(
+            "assets.importers",
+            CheckStatus::Ok,
+            format!("{} binding(s)", report.import_manifest.len()),
+        );
+    }
+
+    match store.load_validation_rules(Path::new(VALIDATION_RULES_PATH)) {
+        Ok(true) => {
+            let n = store.validation_rules().map_or(0, |r| r.rules.len());
+            report.check("assets.rules", CheckStatus::Ok, format!("{n} rule(s)"));
+        }
+        Ok(false) => report.check(
+            "assets.rules",
+            CheckStatus::Ok,
+            format!("no {VALIDATION_RULES_PATH}"),
+        ),
+        Err(e) => report.check("assets.rules", CheckStatus::Error, e.to_string()),
+    }
+
+    let exts: HashSet<&str> = report
+        .import_manifest
+        .iter()
+        .map(|e| e.ext.as_str())
+        .collect();
+    let paths = importable_files(root, &exts);
+
+    let mut pending = Vec::with_capacity(paths.len());
+    let mut failures = Vec::new();
+    for path in paths {
+        match store.load_path(&path) {
+            Ok(id) => pending.push((path, id)),
+            Err(e) => failures.push(AssetFailure {
+                path,
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    let t0 = Instant::now();
+    let mut checked = 0usize;
+    while !pending.is_empty() {
+        am.pump();
+        pending.retain(|(path, id)| match store.state(*id) {
+            AssetState::Ready => {
+                checked += 1;
+                false
+            }
+            AssetState::Failed(e) => {
+                failures.push(AssetFailure {
+                    path: path.clone(),
+                    error: e.to_string(),
+                });
+                false
+            }
+            AssetState::Loading | AssetState::Unloaded => true,
+        });
+        if t0.elapsed() >= IMPORT_TIMEOUT {
+            failures.extend(pending.drain(..).map(|(path, _)| AssetFailure {
+                path,
+                error: format!("import timed out after {}s", IMPORT_TIMEOUT.as_secs()),
+            }));
+            break;
+        }
+        if !pending.is_empty() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    report.assets_checked = checked;
+    if failures.is_empty() {
+        report.check(
+            "assets.import",
+            CheckStatus::Ok,
+            format!("{checked} asset(s) imported"),
+        );
+    } else {
+        report.check(
+            "assets.import",
+            CheckStatus::Error,
+            format!(
+                "{} of {} asset(s) failed",
+                failures.len(),
+                checked + failures.len()
+            ),
+        );
+    }
+    report.asset_failures = failures;
+
+    let issues = store.all_validation_issues();
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == ValidationSeverity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+    let status = if errors > 0 {
+        CheckStatus::Error
+    } else if warnings > 0 {
+        CheckStatus::Warning
+    } else {
+        CheckStatus::Ok
+    };
+    report.check(
+        "assets.validation",
+        status,
+        format!("{errors} error(s), {warnings} warning(s)"),
+    );
+    report.validation_issues = issues;
+}
+
+/// Logical paths of files under `root` with an importer for their extension; dot-files and
+/// dot-directories (caches, manifests) are skipped.
+#[cfg(feature = "runtime")]
+fn importable_files(root: &Path, exts: &HashSet<&str>) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(rd) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for de in rd.flatten() {
+            if de.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let p = de.path();
+            if p.is_dir() {
+                stack.push(p);
+                continue;
+            }
+            let importable = p
+                .extension()
+                .map(|e| e.to_string_lossy().to_ascii_lowercase())
+                .is_some_and(|e| exts.contains(e.as_str()));
+            if !importable {
+                continue;
+            }
+            if let Ok(rel) = p.strip_prefix(root) {
+                out.push(rel.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    out.sort();
+    out
+}
@@ -1,16 +1,37 @@
+use crate::build_info::BuildInfo;
+use crate::bus::{AnyBus, BusMessage};
+use crate::cvars::CVars;
+use crate::dry_run::{CheckStatus, DryRunReport, PluginEntry};
 use crate::error::{EngineError, EngineResult, ModuleStage};
-use crate::events::EventHub;
+use crate::events::{EventHub, EventSub};
 use crate::frame::Frame;
-use crate::module::{ApiVersion, Bus, Module, ModuleCtx, Resources, Services};
+use crate::host_events::{HostEvent, WindowHostEvent};
+use crate::lifecycle::{SuspendPolicy, SuspendReason};
+use crate::module::{
+    catch_module_panic, ApiVersion, Bus, ErasedModule, Module, ModuleCtx, ModuleErrorPolicy,
+    ModuleQuarantined, ModuleScope, Resources, Services,
+};
 #[cfg(feature = "runtime")]
 use crate::plugins::importers_host_api;
-use crate::plugins::{default_host_api, init_host_context, PluginManager};
+use crate::plugins::host_api_v2::{forward_host_event, publish_frame_timing};
+use crate::plugins::host_context::{set_plugin_event_feed, PluginEventFeed};
+use crate::plugins::{
+    default_host_api, init_host_context, EventBridge, PluginManager, PluginPermissionPolicy,
+};
 use crate::sched::Scheduler;
-use crate::sync::ShutdownToken;
+use crate::sync::{CancelToken, ShutdownToken};
 use crate::system_info::SystemInfo;
+use crate::replay::ReplaySystem;
+use crate::save::SaveApi;
+use crate::time_control::{TimeControl, TimeSource};
+use crate::trace::TraceKind;
+#[cfg(feature = "runtime")]
+use crate::startup::StartupConfig;
 #[cfg(feature = "runtime")]
 use crate::AssetManagerConfig;
 
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::any::Any;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
@@ -23,6 +44,10 @@ pub struct EngineConfig {
     #[cfg(feature = "runtime")]
     pub assets: AssetManagerConfig,
     pub plugins_dir: Option<PathBuf>,
+    /// Handling of errors returned by non-critical modules.
+    pub on_module_error: ModuleErrorPolicy,
+    /// Permissions granted to plugins from their manifests.
+    pub plugin_permissions: PluginPermissionPolicy,
 }
 
 impl EngineConfig {
@@ -33,15 +58,53 @@ impl EngineConfig {
             fixed_dt_ms,
             assets,
             plugins_dir: None,
+            on_module_error: ModuleErrorPolicy::Abort,
+            plugin_permissions: PluginPermissionPolicy::default(),
         }
     }
 
+    /// Engine settings from the startup config: asset sources, budgets, cache and servers,
+    /// the plugins directory and the plugin permission policy. Shared by every host so no
+    /// startup key is honoured by one and ignored by another.
+    #[cfg(feature = "runtime")]
+    pub fn from_startup(fixed_dt_ms: u32, startup: &StartupConfig) -> Self {
+        use newengine_assets::{MemoryBudget, PathCase};
+
+        let mut assets = AssetManagerConfig::new(startup.assets_root.clone())
+            .with_pump_steps(startup.asset_pump_steps)
+            .with_import_workers(startup.asset_import_workers as usize)
+            .with_memory_budget(MemoryBudget::from_mib(
+                startup.asset_budget_textures_mb,
+                startup.asset_budget_audio_mb,
+                startup.asset_budget_blobs_mb,
+            ))
+            .with_filesystem_source(startup.asset_filesystem_source)
+            .with_path_case(if startup.asset_case_insensitive {
+                PathCase::Insensitive
+            } else {
+                PathCase::Sensitive
+            })
+            .with_cache_dir(startup.asset_cache.then(|| startup.asset_cache_dir.clone()))
+            .with_asset_server(startup.asset_server_listen.clone())
+            .with_remote_imports(startup.asset_server.clone())
+            .with_content_server(startup.content_server_listen.clone());
+        for archive in startup.asset_archives.iter() {
+            assets = assets.with_archive(archive.clone());
+        }
+
+        Self::new(fixed_dt_ms, assets)
+            .with_plugins_dir(Some(startup.modules_dir.clone()))
+            .with_plugin_permissions(startup.plugin_permissions.clone())
+    }
+
     #[cfg(not(feature = "runtime"))]
     #[inline]
     pub fn new(fixed_dt_ms: u32) -> Self {
         Self {
             fixed_dt_ms,
             plugins_dir: None,
+            on_module_error: ModuleErrorPolicy::Abort,
+            plugin_permissions: PluginPermissionPolicy::default(),
         }
     }
 
@@ -50,6 +113,18 @@ impl EngineConfig {
         self.plugins_dir = dir;
         self
     }
+
+    #[inline]
+    pub fn with_on_module_error(mut self, policy: ModuleErrorPolicy) -> Self {
+        self.on_module_error = policy;
+        self
+    }
+
+    #[inline]
+    pub fn with_plugin_permissions(mut self, policy: PluginPermissionPolicy) -> Self {
+        self.plugin_permissions = policy;
+        self
+    }
 }
 
 pub struct Engine<E: Send + 'static> {
@@ -57,12 +132,33 @@ pub struct Engine<E: Send + 'static> {
     services: Box<dyn Services>,
     modules: Vec<Box<dyn Module<E>>>,
     module_ids: HashSet<&'static str>,
+    /// Non-critical modules that panicked or exhausted `on_module_error`; skipped by every
+    /// stage except shutdown.
+    quarantined: HashSet<&'static str>,
+    /// Quarantined modules whose `init` never succeeded; they get no shutdown either.
+    init_failed: HashSet<&'static str>,
+    /// Modules paused with [`Engine::set_module_enabled`]; skipped like quarantined ones.
+    disabled: HashSet<&'static str>,
+    on_module_error: ModuleErrorPolicy,
+    /// Consecutive failures per module under [`ModuleErrorPolicy::Retry`].
+    module_failures: HashMap<&'static str, u32>,
+    /// Call order of the stages whose `OrderHint::BeforeIn`/`AfterIn` hints differ from the
+    /// order of `modules`, as indices into it.
+    stage_orders: Vec<(ModuleStage, Vec<usize>)>,
 
     pub resources: Resources,
     bus: Bus<E>,
+    /// Topic-based bus shared by every module regardless of `E`; see [`ErasedModule`].
+    any_bus: AnyBus,
 
     events: EventHub,
+    /// Window events mirrored into `HostApiV2` state and typed plugin events.
+    plugin_host_events: EventSub<HostEvent>,
+    /// Hub events exported to plugins and plugin events imported into the hub.
+    event_bridge: EventBridge,
     scheduler: Scheduler,
+    time: TimeControl,
+    time_source: Option<Box<dyn TimeSource>>,
 
     plugins: PluginManager,
     plugins_loaded: bool,
@@ -76,6 +172,9 @@ pub struct Engine<E: Send + 'static> {
     started: bool,
     last: Instant,
     acc: f32,
+
+    suspended: Option<SuspendReason>,
+    suspend_policy: SuspendPolicy,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -128,6 +227,16 @@ impl<E: Send + 'static> Engine<E> {
         self.shutdown.clone()
     }
 
+    /// Engine-wide cancellation root (also available as a `CancelToken` resource).
+    /// Cancelled on shutdown so in-flight loads and tasks stop early.
+    #[inline]
+    pub fn cancel_token(&self) -> CancelToken {
+        self.resources
+            .get::<CancelToken>()
+            .cloned()
+            .unwrap_or_else(|| self.shutdown.cancel_token())
+    }
+
     #[inline]
     pub fn events(&self) -> &EventHub {
         &self.events
@@ -140,6 +249,29 @@ impl<E: Send + 'static> Engine<E> {
         self.events.publish(event)
     }
 
+    /// Forwards every `T` published on the event hub to plugins as a JSON event on `topic`
+    /// (under `engine.`), tagged with `schema`. Plugins see the events of a frame at the
+    /// start of the next one.
+    pub fn export_event_to_plugins<T>(&mut self, topic: &str, schema: &str) -> EngineResult<()>
+    where
+        T: Serialize + Any + Send + Sync + 'static,
+    {
+        self.event_bridge
+            .export::<T>(&self.events, topic, schema)
+            .map_err(EngineError::Other)
+    }
+
+    /// Publishes the JSON payload of plugin events on `topic` as `T` on the event hub. Every
+    /// plugin event is published as a [`PluginEvent`](crate::PluginEvent) regardless.
+    pub fn import_plugin_event<T>(&mut self, topic: &str) -> EngineResult<()>
+    where
+        T: DeserializeOwned + Any + Send + Sync + 'static,
+    {
+        self.event_bridge
+            .import::<T>(topic)
+            .map_err(EngineError::Other)
+    }
+
     pub fn new(
         fixed_dt_ms: u32,
         services: Box<dyn Services>,
@@ -168,7 +300,19 @@ impl<E: Send + 'static> Engine<E> {
     ) -> EngineResult<Self> {
         let fixed_dt = (config.fixed_dt_ms as f32 / 1000.0).max(0.001);
 
+        BuildInfo::get().log();
+
         let mut resources = Resources::default();
+        resources.insert(SuspendPolicy::default());
+        resources.insert(*BuildInfo::get());
+        // Root of all engine-level cancellation; modules derive children via `child()`.
+        resources.insert(shutdown.cancel_token());
+        // Same set the console edits; see `crate::cvars`.
+        resources.insert(CVars::global());
+        let time = TimeControl::new();
+        resources.insert(time.clone());
+        let save = SaveApi::new();
+        resources.insert(save.clone());
 
         #[cfg(feature = "runtime")]
         {
@@ -185,6 +329,14 @@ impl<E: Send + 'static> Engine<E> {
             init_host_context(asset_store.clone());
             crate::assets_service::register_asset_manager_service(asset_store.clone());
             crate::console::init_console_service();
+            crate::ui_remote::register_ui_remote_service();
+            crate::settings::register_settings_service();
+            crate::notify::register_notify_service();
+            crate::notify::install_asset_failure_toasts(&asset_store);
+            crate::trace::register_trace_service();
+            crate::trace::install_asset_trace(&asset_store);
+            crate::time_control::register_time_commands(&time);
+            crate::save::register_save_commands(&save);
         }
 
         #[cfg(not(feature = "runtime"))]
@@ -192,18 +344,33 @@ impl<E: Send + 'static> Engine<E> {
             init_host_context();
         }
 
+        let events = EventHub::new();
+        let plugin_host_events =
+            events.subscribe_filtered(|e: &HostEvent| matches!(e, HostEvent::Window(_)));
+
         Ok(Self {
             fixed_dt,
             services,
             modules: Vec::new(),
             module_ids: HashSet::new(),
+            quarantined: HashSet::new(),
+            init_failed: HashSet::new(),
+            disabled: HashSet::new(),
+            on_module_error: config.on_module_error,
+            module_failures: HashMap::new(),
+            stage_orders: Vec::new(),
 
             resources,
             bus,
-            events: EventHub::new(),
+            any_bus: AnyBus::unbounded(),
+            events,
+            plugin_host_events,
+            event_bridge: EventBridge::new(),
             scheduler: Scheduler::new(),
+            time,
+            time_source: None,
 
-            plugins: PluginManager::new(),
+            plugins: PluginManager::new().with_permission_policy(config.plugin_permissions),
             plugins_loaded: false,
             plugins_dir: config.plugins_dir,
 
@@ -215,9 +382,18 @@ impl<E: Send + 'static> Engine<E> {
             started: false,
             last: Instant::now(),
             acc: 0.0,
+
+            suspended: None,
+            suspend_policy: SuspendPolicy::default(),
         })
     }
 
+    /// Version, commit and target the engine core was built from.
+    #[inline]
+    pub fn build_info(&self) -> &'static BuildInfo {
+        BuildInfo::get()
+    }
+
     #[inline]
     pub fn resources_mut(&mut self) -> &mut Resources {
         &mut self.resources
@@ -228,6 +404,13 @@ impl<E: Send + 'static> Engine<E> {
         &self.bus
     }
 
+    /// Type-erased bus, independent of the engine's event type. Modules reach it through
+    /// [`ModuleCtx::any_bus`]; the host drains what they publish here.
+    #[inline]
+    pub fn any_bus(&self) -> &AnyBus {
+        &self.any_bus
+    }
+
     pub fn register_module(&mut self, module: Box<dyn Module<E>>) -> EngineResult<()> {
         self.sync_shutdown_state();
 
@@ -243,6 +426,132 @@ impl<E: Send + 'static> Engine<E> {
         Ok(())
     }
 
+    /// Registers a module written against the type-erased bus; see [`ErasedModule`].
+    #[inline]
+    pub fn register_erased_module(&mut self, module: Box<dyn Module<BusMessage>>) -> EngineResult<()> {
+        self.register_module(Box::new(ErasedModule::new(module)))
+    }
+
+    /// Adds a module to a running engine and initializes it. Before `start()` this is
+    /// [`Engine::register_module`].
+    ///
+    /// Its dependencies must already be registered. Modules are re-sorted as `start()` would
+    /// have with the new one included; since nothing registered can depend on it, only its
+    /// dependencies and order hints decide where it lands. API contracts are checked against
+    /// the running set. If `init` fails the module is dropped and the error returned.
+    pub fn add_module_runtime(&mut self, mut module: Box<dyn Module<E>>) -> EngineResult<()> {
+        if !self.started {
+            return self.register_module(module);
+        }
+        self.sync_shutdown_state();
+        if self.is_exit_requested() {
+            return Err(EngineError::ExitRequested);
+        }
+
+        let id = module.id();
+        if self.module_ids.contains(id) {
+            return Err(EngineError::Other(format!(
+                "module already registered: {id}"
+            )));
+        }
+
+        self.modules.push(module);
+        let checked = check_api_contracts(self.modules.iter().map(|m| m.as_ref()))
+            .and_then(|()| plan_module_order(&self.modules));
+        module = self.modules.pop().expect("module pushed above");
+        let plan = checked?;
+
+        let init_result = {
+            let _scope = ModuleScope::enter(id, ModuleStage::Init);
+            let mut ctx = ModuleCtx::new(
+                self.services.as_ref(),
+                &mut self.resources,
+                &self.bus,
+                &self.any_bus,
+                &self.events,
+                &mut self.scheduler,
+                &mut self.exit_requested,
+            );
+            catch_module_panic(id, || module.init(&mut ctx))
+        };
+        init_result.map_err(|e| EngineError::with_module_stage(id, ModuleStage::Init, e))?;
+
+        self.modules.push(module);
+        self.modules = reorder(std::mem::take(&mut self.modules), &plan.global);
+        self.stage_orders = plan.stages;
+        self.module_ids.insert(id);
+
+        let pos = self.modules.iter().position(|m| m.id() == id).unwrap_or(0);
+        log::info!("engine.module added id='{id}' pos={pos}");
+        Ok(())
+    }
+
+    /// Shuts down a module and removes it from a running engine (before `start()` it is only
+    /// unregistered).
+    ///
+    /// Fails without changes if another module depends on it or requires an API only it
+    /// provides. The module is removed even if its `shutdown` fails; that error is returned.
+    pub fn remove_module(&mut self, id: &str) -> EngineResult<()> {
+        let Some(idx) = self.modules.iter().position(|m| m.id() == id) else {
+            return Err(EngineError::Other(format!("module not registered: {id}")));
+        };
+
+        if let Some(dependent) = self
+            .modules
+            .iter()
+            .find(|m| m.dependencies().iter().any(|d| *d == id))
+        {
+            return Err(EngineError::Other(format!(
+                "module '{id}' is a dependency of '{}'",
+                dependent.id()
+            )));
+        }
+
+        check_api_contracts(
+            self.modules
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != idx)
+                .map(|(_, m)| m.as_ref()),
+        )?;
+
+        let mut module = self.modules.remove(idx);
+        let module_id = module.id();
+        self.module_ids.remove(module_id);
+        let quarantined = self.quarantined.remove(module_id);
+        let init_failed = self.init_failed.remove(module_id);
+        self.disabled.remove(module_id);
+        self.module_failures.remove(module_id);
+
+        // Removing a module cannot create a cycle; only stage orders need rebuilding, and
+        // their indices follow the re-sorted `modules`.
+        if let Ok(plan) = plan_module_order(&self.modules) {
+            self.modules = reorder(std::mem::take(&mut self.modules), &plan.global);
+            self.stage_orders = plan.stages;
+        }
+
+        if !self.started || init_failed {
+            return Ok(());
+        }
+
+        let result = {
+            let _scope = ModuleScope::enter(module_id, ModuleStage::Shutdown);
+            let mut ctx = ModuleCtx::new(
+                self.services.as_ref(),
+                &mut self.resources,
+                &self.bus,
+                &self.any_bus,
+                &self.events,
+                &mut self.scheduler,
+                &mut self.exit_requested,
+            );
+            catch_module_panic(module_id, || module.shutdown(&mut ctx))
+        };
+        log::info!("engine.module removed id='{module_id}' quarantined={quarantined}");
+
+        result.map_err(|e| EngineError::with_module_stage(module_id, ModuleStage::Shutdown, e))
+    }
+
     #[inline]
     fn elapsed_since(t0: Instant) -> Elapsed {
         Elapsed::from_duration(t0.elapsed())
@@ -439,6 +748,27 @@ impl<E: Send + 'static> Engine<E> {
         }
     }
 
+    /// Hands pending window events, exported hub events and the frame's timing to plugins
+    /// before they update.
+    fn publish_plugin_frame(&self, frame: &Frame) {
+        self.plugin_host_events.drain(|ev| forward_host_event(&ev));
+        self.event_bridge.export_pending();
+        publish_frame_timing(frame, self.time.scale(), self.time.is_paused());
+    }
+
+    /// Publishes the events plugins emitted since the last frame, before modules update.
+    #[inline]
+    fn publish_plugin_events(&self) {
+        self.event_bridge.import_pending(&self.events);
+    }
+
+    /// Publishes plugin faults (panics, failed calls) collected since the last frame.
+    fn publish_plugin_faults(&mut self) {
+        for fault in self.plugins.take_faults() {
+            let _ = self.events.publish(fault);
+        }
+    }
+
     pub fn start(&mut self) -> EngineResult<()> {
         self.started = true;
         self.last = Instant::now();
@@ -450,84 +780,31 @@ impl<E: Send + 'static> Engine<E> {
 
         self.validate_api_contracts()?;
 
-        let n = self.modules.len();
-
-        let mut id_to_index: HashMap<&'static str, usize> = HashMap::with_capacity(n);
-        for (i, m) in self.modules.iter().enumerate() {
-            let id = m.id();
-            if id_to_index.insert(id, i).is_some() {
-                return Err(EngineError::Other(format!("duplicate module id: {id}")));
-            }
-        }
-
-        let mut indegree = vec![0usize; n];
-        let mut rev_edges: Vec<Vec<usize>> = vec![Vec::new(); n];
-
-        for (i, m) in self.modules.iter().enumerate() {
-            for &dep in m.dependencies() {
-                let Some(&dep_i) = id_to_index.get(dep) else {
-                    return Err(EngineError::Other(format!(
-                        "module dependency missing: {} -> {dep}",
-                        m.id()
-                    )));
-                };
-                indegree[i] += 1;
-                rev_edges[dep_i].push(i);
-            }
-        }
-
-        let mut q: VecDeque<usize> = VecDeque::new();
-        for i in 0..n {
-            if indegree[i] == 0 {
-                q.push_back(i);
-            }
-        }
-
-        let mut order: Vec<usize> = Vec::with_capacity(n);
-        while let Some(i) = q.pop_front() {
-            order.push(i);
-            for &to in rev_edges[i].iter() {
-                indegree[to] = indegree[to].saturating_sub(1);
-                if indegree[to] == 0 {
-                    q.push_back(to);
-                }
-            }
-        }
-
-        if order.len() != n {
-            let mut cyclic = Vec::new();
-            for (i, deg) in indegree.iter().enumerate() {
-                if *deg != 0 {
-                    cyclic.push(self.modules[i].id());
-                }
-            }
-            return Err(EngineError::Other(format!(
-                "module dependency cycle detected among: {:?}",
-                cyclic
-            )));
-        }
-
-        let mut sorted: Vec<Box<dyn Module<E>>> = Vec::with_capacity(n);
-        let mut old = std::mem::take(&mut self.modules);
-        let mut slots: Vec<Option<Box<dyn Module<E>>>> = old.drain(..).map(Some).collect();
-
-        for idx in order {
-            let m = slots[idx].take().expect("module slot already moved");
-            sorted.push(m);
-        }
+        let plan = plan_module_order(&self.modules)?;
+        let mut sorted = reorder(std::mem::take(&mut self.modules), &plan.global);
 
         #[inline]
         fn shutdown_modules<E: Send + 'static>(engine: &mut Engine<E>, modules: &mut [Box<dyn Module<E>>]) {
             for m in modules.iter_mut().rev() {
+                let module_id = m.id();
+                if engine.init_failed.contains(module_id) {
+                    continue;
+                }
+                let _scope = ModuleScope::enter(module_id, ModuleStage::Shutdown);
                 let mut ctx = ModuleCtx::new(
                     engine.services.as_ref(),
                     &mut engine.resources,
                     &engine.bus,
+                    &engine.any_bus,
                     &engine.events,
                     &mut engine.scheduler,
                     &mut engine.exit_requested,
                 );
-                let _ = m.shutdown(&mut ctx);
+                if let Err(e) = catch_module_panic(module_id, || m.shutdown(&mut ctx)) {
+                    if e.is_panic() {
+                        log::error!("engine.shutdown {e}");
+                    }
+                }
             }
         }
 
@@ -536,17 +813,59 @@ impl<E: Send + 'static> Engine<E> {
         for i in 0..sorted.len() {
             self.sync_shutdown_state();
 
-            let init_result = {
+            let critical = sorted[i].is_critical();
+            let mut attempt = 0u32;
+            let init_result = loop {
                 let m = &mut sorted[i];
+                let module_id = m.id();
+                let _scope = ModuleScope::enter(module_id, ModuleStage::Init);
                 let mut ctx = ModuleCtx::new(
                     self.services.as_ref(),
                     &mut self.resources,
                     &self.bus,
+                    &self.any_bus,
                     &self.events,
                     &mut self.scheduler,
                     &mut self.exit_requested,
                 );
-                m.init(&mut ctx)
+                match catch_module_panic(module_id, || m.init(&mut ctx)) {
+                    Err(e)
+                        if !critical
+                            && attempt < self.on_module_error.retries()
+                            && !e.is_panic()
+                            && !matches!(e, EngineError::ExitRequested) =>
+                    {
+                        attempt += 1;
+                        log::warn!(
+                            "engine.module init failed id='{}' attempt={} err='{}'; retrying",
+                            module_id,
+                            attempt,
+                            e
+                        );
+                    }
+                    res => break res,
+                }
+            };
+
+            let init_result = match init_result {
+                // Retries were spent above; a failing init cannot be retried next frame.
+                Err(err)
+                    if !critical
+                        && (err.is_panic() || self.on_module_error != ModuleErrorPolicy::Abort)
+                        && !matches!(err, EngineError::ExitRequested) =>
+                {
+                    let module_id = sorted[i].id();
+                    quarantine_module(
+                        &mut self.quarantined,
+                        &self.events,
+                        module_id,
+                        ModuleStage::Init,
+                        err,
+                    );
+                    self.init_failed.insert(module_id);
+                    Ok(())
+                }
+                other => other,
             };
 
             if let Err(err) = init_result {
@@ -568,6 +887,7 @@ impl<E: Send + 'static> Engine<E> {
         }
 
         self.modules = sorted;
+        self.stage_orders = plan.stages;
 
         self.try_load_plugins_once()?;
         self.log_plugins_diagnostics("after module init");
@@ -575,6 +895,7 @@ impl<E: Send + 'static> Engine<E> {
         if let Err(e) = self.plugins.start_all() {
             return Err(EngineError::Other(format!("plugins: start failed: {e}")));
         }
+        self.publish_plugin_faults();
 
         Ok(())
     }
@@ -597,9 +918,18 @@ impl<E: Send + 'static> Engine<E> {
 
         dt = dt.clamp(0.0, 0.2);
 
+        if self.suspended.is_some() {
+            return self.idle_frame(dt);
+        }
+
+        let real_dt = dt;
+        let mut dt = self.time.advance(real_dt, self.fixed_dt);
+        if let Some(source) = self.time_source.as_mut() {
+            dt = source.next_dt(dt, self.fixed_dt);
+        }
         self.acc = (self.acc + dt).min(1.0);
 
-        self.scheduler.begin_frame(Duration::from_secs_f32(dt));
+        self.scheduler.begin_frame(Duration::from_secs_f32(real_dt));
 
         let mut steps_to_run = (self.acc / self.fixed_dt).floor() as u32;
         steps_to_run = steps_to_run.min(8);
@@ -616,6 +946,7 @@ impl<E: Send + 'static> Engine<E> {
             let fixed_frame = Frame {
                 frame_index: self.frame_index,
                 dt: self.fixed_dt,
+                real_dt: self.fixed_dt,
                 fixed_dt: self.fixed_dt,
                 fixed_alpha: 0.0,
                 fixed_step_count: steps_to_run,
@@ -633,6 +964,7 @@ impl<E: Send + 'static> Engine<E> {
         let frame = Frame {
             frame_index: self.frame_index,
             dt,
+            real_dt,
             fixed_dt: self.fixed_dt,
             fixed_alpha: (self.acc / self.fixed_dt).clamp(0.0, 0.999_999),
             fixed_step_count: steps_to_run,
@@ -640,15 +972,60 @@ impl<E: Send + 'static> Engine<E> {
             fixed_tick: self.fixed_tick,
         };
 
+        self.publish_plugin_frame(&frame);
         if let Err(e) = self.plugins.update_all(dt) {
             return Err(EngineError::Other(format!("plugins: update failed: {e}")));
         }
+        self.publish_plugin_events();
         self.run_stage(&frame, ModuleStage::Update, |m, ctx| m.update(ctx))?;
 
         if let Err(e) = self.plugins.render_all(dt) {
             return Err(EngineError::Other(format!("plugins: render failed: {e}")));
         }
         self.run_stage(&frame, ModuleStage::Render, |m, ctx| m.render(ctx))?;
+        self.publish_plugin_faults();
+
+        self.scheduler.end_frame(Duration::from_secs_f32(real_dt));
+        self.frame_index = self.frame_index.wrapping_add(1);
+
+        #[cfg(feature = "runtime")]
+        {
+            if let Some(am) = self.resources.get::<crate::assets::AssetManager>() {
+                am.pump();
+            }
+            crate::notify::run_toast_actions();
+            crate::ui_actions::run_pending(&self.events);
+            if crate::console::take_exit_requested() {
+                self.exit_requested = true;
+            }
+        }
+
+        crate::trace::end_frame(frame.frame_index);
+        Ok(frame)
+    }
+
+    /// Suspended tick: `Update` only, no fixed steps, no rendering.
+    fn idle_frame(&mut self, dt: f32) -> EngineResult<Frame> {
+        self.scheduler.begin_frame(Duration::from_secs_f32(dt));
+
+        let frame = Frame {
+            frame_index: self.frame_index,
+            dt,
+            real_dt: dt,
+            fixed_dt: self.fixed_dt,
+            fixed_alpha: 0.0,
+            fixed_step_count: 0,
+            fixed_step_index: 0,
+            fixed_tick: self.fixed_tick,
+        };
+
+        self.publish_plugin_frame(&frame);
+        if let Err(e) = self.plugins.update_all(dt) {
+            return Err(EngineError::Other(format!("plugins: update failed: {e}")));
+        }
+        self.publish_plugin_events();
+        self.run_stage(&frame, ModuleStage::Update, |m, ctx| m.update(ctx))?;
+        self.publish_plugin_faults();
 
         self.scheduler.end_frame(Duration::from_secs_f32(dt));
         self.frame_index = self.frame_index.wrapping_add(1);
@@ -658,14 +1035,306 @@ impl<E: Send + 'static> Engine<E> {
             if let Some(am) = self.resources.get::<crate::assets::AssetManager>() {
                 am.pump();
             }
+            crate::notify::run_toast_actions();
+            crate::ui_actions::run_pending(&self.events);
             if crate::console::take_exit_requested() {
                 self.exit_requested = true;
             }
         }
 
+        crate::trace::end_frame(frame.frame_index);
         Ok(frame)
     }
 
+    /// Game clock shared with the `time.*` console commands; also in the resources.
+    #[inline]
+    pub fn time_control(&self) -> &TimeControl {
+        &self.time
+    }
+
+    /// Multiplies the delta fed to fixed updates and delivered as [`Frame::dt`].
+    #[inline]
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.time.set_scale(scale);
+    }
+
+    #[inline]
+    pub fn time_scale(&self) -> f32 {
+        self.time.scale()
+    }
+
+    /// Stops game time: no fixed steps and `dt == 0.0` until [`Engine::resume_time`].
+    /// Unlike [`Engine::suspend`], modules keep getting `update()` and `render()`.
+    #[inline]
+    pub fn pause_time(&mut self) {
+        self.time.pause();
+    }
+
+    #[inline]
+    pub fn resume_time(&mut self) {
+        self.time.resume();
+    }
+
+    #[inline]
+    pub fn is_time_paused(&self) -> bool {
+        self.time.is_paused()
+    }
+
+    /// Pauses game time if needed and advances the next frame by exactly one fixed step.
+    #[inline]
+    pub fn step_single_frame(&mut self) {
+        self.time.step(1);
+    }
+
+    /// Substitutes the game delta of every frame (`None` restores the engine's clock).
+    #[inline]
+    pub fn set_time_source(&mut self, source: Option<Box<dyn TimeSource>>) {
+        self.time_source = source;
+    }
+
+    /// Installs a filter in front of every plugin event (`None` removes it). Plugin events
+    /// are process-wide, so this affects every engine in the process.
+    #[inline]
+    pub fn set_event_feed(&mut self, feed: Option<PluginEventFeed>) {
+        set_plugin_event_feed(feed);
+    }
+
+    /// Makes `replay` this engine's time source and event feed, and inserts it into the
+    /// resources so modules can draw seeds and control recording.
+    pub fn attach_replay(&mut self, replay: &ReplaySystem) {
+        self.set_time_source(Some(Box::new(replay.clone())));
+        self.set_event_feed(Some(replay.event_feed()));
+        self.resources.insert(replay.clone());
+    }
+
+    #[inline]
+    pub fn suspend_policy(&self) -> SuspendPolicy {
+        self.suspend_policy
+    }
+
+    /// Replaces the suspend policy (also published to `Resources`).
+    pub fn set_suspend_policy(&mut self, policy: SuspendPolicy) {
+        self.suspend_policy = policy;
+        self.resources.insert(policy);
+    }
+
+    #[inline]
+    pub fn is_suspended(&self) -> bool {
+        self.suspended.is_some()
+    }
+
+    /// True if `module_id` panicked and was quarantined.
+    #[inline]
+    pub fn is_quarantined(&self, module_id: &str) -> bool {
+        self.quarantined.contains(module_id)
+    }
+
+    /// Ids of quarantined modules.
+    #[inline]
+    pub fn quarantined_modules(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.quarantined.iter().copied()
+    }
+
+    /// Pauses (`false`) or resumes (`true`) a module without unregistering it, calling its
+    /// `on_pause` / `on_unpause` hook. A paused module gets no stage, suspend or resume
+    /// callbacks but keeps its state; shutdown still reaches it. Modules depending on it keep
+    /// running, so only pause what they can do without.
+    ///
+    /// Before `start()` only the flag is set. If the hook fails the state is unchanged and
+    /// the error returned; if a non-critical module panics it is quarantined.
+    pub fn set_module_enabled(&mut self, id: &str, enabled: bool) -> EngineResult<()> {
+        let Some(m) = self.modules.iter_mut().find(|m| m.id() == id) else {
+            return Err(EngineError::Other(format!("module not registered: {id}")));
+        };
+        let module_id = m.id();
+        let is_enabled = !self.disabled.contains(module_id);
+        if is_enabled == enabled {
+            return Ok(());
+        }
+
+        if self.started && !self.quarantined.contains(module_id) {
+            let stage = if enabled {
+                ModuleStage::Unpause
+            } else {
+                ModuleStage::Pause
+            };
+            let _scope = ModuleScope::enter(module_id, stage);
+            let mut ctx = ModuleCtx::new(
+                self.services.as_ref(),
+                &mut self.resources,
+                &self.bus,
+                &self.any_bus,
+                &self.events,
+                &mut self.scheduler,
+                &mut self.exit_requested,
+            );
+
+            let critical = m.is_critical();
+            let res = catch_module_panic(module_id, || {
+                if enabled {
+                    m.on_unpause(&mut ctx)
+                } else {
+                    m.on_pause(&mut ctx)
+                }
+            });
+            match res {
+                Ok(()) => {}
+                Err(e @ EngineError::ModulePanicked(..)) if !critical => {
+                    quarantine_module(&mut self.quarantined, &self.events, module_id, stage, e);
+                }
+                Err(e) => return Err(EngineError::with_module_stage(module_id, stage, e)),
+            }
+        }
+
+        if enabled {
+            self.disabled.remove(module_id);
+        } else {
+            self.disabled.insert(module_id);
+        }
+        log::info!("engine.module enabled={enabled} id='{module_id}'");
+        Ok(())
+    }
+
+    /// False while the module is paused with [`Engine::set_module_enabled`].
+    #[inline]
+    pub fn is_module_enabled(&self, module_id: &str) -> bool {
+        !self.disabled.contains(module_id)
+    }
+
+    #[inline]
+    pub fn suspend_reason(&self) -> Option<SuspendReason> {
+        self.suspended
+    }
+
+    /// Enters the suspended state and notifies modules (reverse order). Idempotent.
+    ///
+    /// A module error is logged and does not keep the engine running at full rate.
+    pub fn suspend(&mut self, reason: SuspendReason) -> EngineResult<()> {
+        if self.suspended.is_some() || !self.started {
+            return Ok(());
+        }
+        self.suspended = Some(reason);
+
+        log::info!("engine.suspend reason={:?}", reason);
+
+        let mut first_err = None;
+        for m in self.modules.iter_mut().rev() {
+            let module_id = m.id();
+            if self.quarantined.contains(module_id) || self.disabled.contains(module_id) {
+                continue;
+            }
+            let _scope = ModuleScope::enter(module_id, ModuleStage::Suspend);
+            let mut ctx = ModuleCtx::new(
+                self.services.as_ref(),
+                &mut self.resources,
+                &self.bus,
+                &self.any_bus,
+                &self.events,
+                &mut self.scheduler,
+                &mut self.exit_requested,
+            );
+
+            let critical = m.is_critical();
+            let res = catch_module_panic(module_id, || m.on_suspend(&mut ctx, reason));
+            match res {
+                Ok(()) => {}
+                Err(e @ EngineError::ModulePanicked(..)) if !critical => {
+                    quarantine_module(&mut self.quarantined, &self.events, module_id, ModuleStage::Suspend, e);
+                }
+                Err(e) if !critical => {
+                    let err = apply_error_policy(
+                        self.on_module_error,
+                        &mut self.module_failures,
+                        &mut self.quarantined,
+                        &self.events,
+                        module_id,
+                        ModuleStage::Suspend,
+                        e,
+                    );
+                    if let Some(e) = err {
+                        let e = EngineError::with_module_stage(module_id, ModuleStage::Suspend, e);
+                        log::error!("engine.suspend module failed: {e}");
+                        first_err.get_or_insert(e);
+                    }
+                }
+                Err(e) => {
+                    let e = EngineError::with_module_stage(module_id, ModuleStage::Suspend, e);
+                    log::error!("engine.suspend module failed: {e}");
+                    first_err.get_or_insert(e);
+                }
+            }
+        }
+
+        let _ = self.emit(HostEvent::Window(WindowHostEvent::Suspended));
+
+        first_err.map_or(Ok(()), Err)
+    }
+
+    /// Leaves the suspended state and notifies modules (init order). Idempotent.
+    pub fn resume(&mut self) -> EngineResult<()> {
+        let Some(reason) = self.suspended.take() else {
+            return Ok(());
+        };
+
+        log::info!("engine.resume after={:?}", reason);
+
+        // Do not let the suspended wall time turn into a catch-up burst.
+        self.last = Instant::now();
+        self.acc = 0.0;
+
+        let mut first_err = None;
+        for m in self.modules.iter_mut() {
+            let module_id = m.id();
+            if self.quarantined.contains(module_id) || self.disabled.contains(module_id) {
+                continue;
+            }
+            let _scope = ModuleScope::enter(module_id, ModuleStage::Resume);
+            let mut ctx = ModuleCtx::new(
+                self.services.as_ref(),
+                &mut self.resources,
+                &self.bus,
+                &self.any_bus,
+                &self.events,
+                &mut self.scheduler,
+                &mut self.exit_requested,
+            );
+
+            let critical = m.is_critical();
+            let res = catch_module_panic(module_id, || m.on_resume(&mut ctx));
+            match res {
+                Ok(()) => {}
+                Err(e @ EngineError::ModulePanicked(..)) if !critical => {
+                    quarantine_module(&mut self.quarantined, &self.events, module_id, ModuleStage::Resume, e);
+                }
+                Err(e) if !critical => {
+                    let err = apply_error_policy(
+                        self.on_module_error,
+                        &mut self.module_failures,
+                        &mut self.quarantined,
+                        &self.events,
+                        module_id,
+                        ModuleStage::Resume,
+                        e,
+                    );
+                    if let Some(e) = err {
+                        let e = EngineError::with_module_stage(module_id, ModuleStage::Resume, e);
+                        log::error!("engine.resume module failed: {e}");
+                        first_err.get_or_insert(e);
+                    }
+                }
+                Err(e) => {
+                    let e = EngineError::with_module_stage(module_id, ModuleStage::Resume, e);
+                    log::error!("engine.resume module failed: {e}");
+                    first_err.get_or_insert(e);
+                }
+            }
+        }
+
+        let _ = self.emit(HostEvent::Window(WindowHostEvent::Resumed));
+
+        first_err.map_or(Ok(()), Err)
+    }
+
     /// Single engine tick (compat facade).
     ///
     /// Keeps external runners stable. Internally delegates to `begin_frame()`.
@@ -696,6 +1365,7 @@ impl<E: Send + 'static> Engine<E> {
 
         let services = self.services.as_ref();
         let bus = &self.bus;
+        let any_bus = &self.any_bus;
         let events = &self.events;
         let shutdown = &self.shutdown;
 
@@ -713,11 +1383,54 @@ impl<E: Send + 'static> Engine<E> {
             }
 
             let module_id = m.id();
-            let mut ctx = ModuleCtx::new(services, resources, bus, events, scheduler, exit_requested);
+            if self.quarantined.contains(module_id) || self.disabled.contains(module_id) {
+                continue;
+            }
+            let _scope = ModuleScope::enter(module_id, ModuleStage::ExternalEvent);
+            let mut ctx = ModuleCtx::new(
+                services,
+                resources,
+                bus,
+                any_bus,
+                events,
+                scheduler,
+                exit_requested,
+            );
 
+            let critical = m.is_critical();
             #[allow(deprecated)]
-            m.on_external_event(&mut ctx, event)
-                .map_err(|e| EngineError::with_module_stage(module_id, ModuleStage::ExternalEvent, e))?;
+            let res = catch_module_panic(module_id, || m.on_external_event(&mut ctx, event));
+            match res {
+                Ok(()) => {}
+                Err(e @ EngineError::ModulePanicked(..)) if !critical => {
+                    quarantine_module(
+                        &mut self.quarantined,
+                        events,
+                        module_id,
+                        ModuleStage::ExternalEvent,
+                        e,
+                    );
+                }
+                Err(e) if !critical => {
+                    let err = apply_error_policy(
+                        self.on_module_error,
+                        &mut self.module_failures,
+                        &mut self.quarantined,
+                        events,
+                        module_id,
+                        ModuleStage::ExternalEvent,
+                        e,
+                    );
+                    if let Some(e) = err {
+                        let stage = ModuleStage::ExternalEvent;
+                        return Err(EngineError::with_module_stage(module_id, stage, e));
+                    }
+                }
+                Err(e) => {
+                    let stage = ModuleStage::ExternalEvent;
+                    return Err(EngineError::with_module_stage(module_id, stage, e));
+                }
+            }
 
             if *exit_requested {
                 shutdown.request();
@@ -730,29 +1443,136 @@ impl<E: Send + 'static> Engine<E> {
 
     pub fn shutdown(&mut self) -> EngineResult<()> {
         self.sync_shutdown_state();
+        if let Some(cancel) = self.resources.get::<CancelToken>() {
+            cancel.cancel();
+        }
 
         self.plugins.shutdown();
 
         for m in self.modules.iter_mut().rev() {
             let module_id = m.id();
+            if self.init_failed.contains(module_id) {
+                continue;
+            }
+            let _scope = ModuleScope::enter(module_id, ModuleStage::Shutdown);
 
             let mut ctx = ModuleCtx::new(
                 self.services.as_ref(),
                 &mut self.resources,
                 &self.bus,
+                &self.any_bus,
                 &self.events,
                 &mut self.scheduler,
                 &mut self.exit_requested,
             );
 
-            let _ = m
-                .shutdown(&mut ctx)
-                .map_err(|e| EngineError::with_module_stage(module_id, ModuleStage::Shutdown, e));
+            if let Err(e) = catch_module_panic(module_id, || m.shutdown(&mut ctx)) {
+                if e.is_panic() {
+                    log::error!("engine.shutdown {e}");
+                }
+            }
+        }
+
+        if let Some(cvars) = self.resources.get::<CVars>() {
+            match cvars.save() {
+                Ok(0) => {}
+                Ok(n) => log::info!("engine.shutdown cvars saved={n}"),
+                Err(e) => log::warn!("engine.shutdown {e}"),
+            }
         }
 
         Ok(())
     }
 
+    /// Dry run for CI: initializes modules, loads and starts plugins, imports every asset under
+    /// the assets root with the validation rules applied, then shuts down without running a
+    /// frame. Register a headless render backend ([`crate::render::NullRenderModule`]) instead
+    /// of a GPU one.
+    ///
+    /// Failures are recorded in the report rather than returned; `Err` only means the report
+    /// could not be produced.
+    #[inline]
+    pub fn validate_only(&mut self) -> EngineResult<DryRunReport> {
+        self.validate_only_with(|_, _| {})
+    }
+
+    /// [`Self::validate_only`] with app-specific checks, run after the asset import and before
+    /// shutdown while plugins and importers are still loaded.
+    pub fn validate_only_with<F>(&mut self, extra: F) -> EngineResult<DryRunReport>
+    where
+        F: FnOnce(&Self, &mut DryRunReport),
+    {
+        let t0 = Instant::now();
+        let mut report = DryRunReport::new();
+        report.modules = self.modules.iter().map(|m| m.id().to_string()).collect();
+
+        match self.start() {
+            Ok(()) => report.check(
+                "modules.init",
+                CheckStatus::Ok,
+                format!(
+                    "{} of {} module(s)",
+                    report.modules.len() - self.init_failed.len(),
+                    report.modules.len()
+                ),
+            ),
+            Err(e) => report.check("modules.init", CheckStatus::Error, e.to_string()),
+        }
+
+        if !self.quarantined.is_empty() {
+            let mut ids: Vec<&str> = self.quarantined.iter().copied().collect();
+            ids.sort_unstable();
+            report.check("modules.quarantined", CheckStatus::Error, ids.join(", "));
+        }
+
+        // `start` stops before plugins when module init fails; load them anyway so their
+        // failures are reported too.
+        let errors_before = report.errors();
+        if let Err(e) = self.try_load_plugins_once() {
+            report.check("plugins.load", CheckStatus::Error, e.to_string());
+        }
+        report.plugins = self
+            .plugins
+            .iter()
+            .map(|p| {
+                let info = p.info();
+                PluginEntry {
+                    id: info.id.to_string(),
+                    version: info.version.to_string(),
+                }
+            })
+            .collect();
+        report.plugins.sort_by(|a, b| a.id.cmp(&b.id));
+        for e in self.plugins.load_failures() {
+            report.check("plugins.load", CheckStatus::Error, e.to_string());
+        }
+        for (id, reason) in self.plugins.disabled() {
+            report.check("plugins.start", CheckStatus::Error, format!("{id}: {reason}"));
+        }
+        if report.errors() == errors_before {
+            report.check(
+                "plugins.load",
+                CheckStatus::Ok,
+                format!("{} plugin(s)", report.plugins.len()),
+            );
+        }
+
+        #[cfg(feature = "runtime")]
+        match self.resources.get::<crate::assets::AssetManager>() {
+            Some(am) => crate::dry_run::check_assets(am, &mut report),
+            None => report.check("assets.root", CheckStatus::Error, "AssetManager missing"),
+        }
+
+        extra(self, &mut report);
+
+        if let Err(e) = self.shutdown() {
+            report.check("engine.shutdown", CheckStatus::Error, e.to_string());
+        }
+
+        report.finish(t0);
+        Ok(report)
+    }
+
     #[inline]
     fn run_stage<F>(&mut self, frame: &Frame, stage: ModuleStage, mut call: F) -> EngineResult<()>
     where
@@ -765,14 +1585,26 @@ impl<E: Send + 'static> Engine<E> {
 
         let services = self.services.as_ref();
         let bus = &self.bus;
+        let any_bus = &self.any_bus;
         let events = &self.events;
         let shutdown = &self.shutdown;
 
         let resources = &mut self.resources;
         let scheduler = &mut self.scheduler;
         let exit_requested = &mut self.exit_requested;
-
-        for m in self.modules.iter_mut() {
+        let quarantined = &mut self.quarantined;
+        let disabled = &self.disabled;
+        let policy = self.on_module_error;
+        let failures = &mut self.module_failures;
+        let order = self
+            .stage_orders
+            .iter()
+            .find(|(s, _)| *s == stage)
+            .map(|(_, order)| order.as_slice());
+        let modules = &mut self.modules;
+
+        for k in 0..modules.len() {
+            let m = &mut modules[order.map_or(k, |order| order[k])];
             if shutdown.is_requested() {
                 *exit_requested = true;
             }
@@ -782,11 +1614,54 @@ impl<E: Send + 'static> Engine<E> {
             }
 
             let module_id = m.id();
+            if quarantined.contains(module_id) || disabled.contains(module_id) {
+                continue;
+            }
+            let _scope = ModuleScope::enter(module_id, stage);
 
-            let mut ctx = ModuleCtx::new(services, resources, bus, events, scheduler, exit_requested);
+            let mut ctx = ModuleCtx::new(
+                services,
+                resources,
+                bus,
+                any_bus,
+                events,
+                scheduler,
+                exit_requested,
+            );
             ctx.set_frame(frame);
 
-            call(m.as_mut(), &mut ctx).map_err(|e| EngineError::with_module_stage(module_id, stage, e))?;
+            let critical = m.is_critical();
+            let t0 = crate::trace::is_enabled().then(Instant::now);
+            let res = catch_module_panic(module_id, || call(m.as_mut(), &mut ctx));
+            if let Some(t0) = t0 {
+                let dt = t0.elapsed();
+                crate::trace::record_span(TraceKind::Stage, Some(module_id), stage.as_str(), t0, dt);
+            }
+            match res {
+                Ok(()) => {
+                    if !failures.is_empty() {
+                        failures.remove(module_id);
+                    }
+                }
+                Err(e @ EngineError::ModulePanicked(..)) if !critical => {
+                    quarantine_module(quarantined, events, module_id, stage, e);
+                }
+                Err(e) if !critical => {
+                    let err = apply_error_policy(
+                        policy,
+                        failures,
+                        quarantined,
+                        events,
+                        module_id,
+                        stage,
+                        e,
+                    );
+                    if let Some(e) = err {
+                        return Err(EngineError::with_module_stage(module_id, stage, e));
+                    }
+                }
+                Err(e) => return Err(EngineError::with_module_stage(module_id, stage, e)),
+            }
 
             if *exit_requested {
                 shutdown.request();
@@ -816,53 +1691,254 @@ impl<E: Send + 'static> Engine<E> {
         }
     }
 
+    #[inline]
     fn validate_api_contracts(&self) -> EngineResult<()> {
-        let mut provided: HashMap<&'static str, ApiVersion> = HashMap::new();
-        let mut provider: HashMap<&'static str, &'static str> = HashMap::new();
-
-        for m in self.modules.iter() {
-            for p in m.provides().iter() {
-                match provided.get(p.id) {
-                    Some(v) if *v >= p.version => {}
-                    _ => {
-                        provided.insert(p.id, p.version);
-                        provider.insert(p.id, m.id());
-                    }
+        check_api_contracts(self.modules.iter().map(|m| m.as_ref()))
+    }
+}
+
+/// Result of [`plan_module_order`].
+struct ModuleOrder {
+    /// Indices into the planned modules in init order: dependencies and stage-less hints.
+    global: Vec<usize>,
+    /// Stages whose scoped hints change that order, as positions in `global`.
+    stages: Vec<(ModuleStage, Vec<usize>)>,
+}
+
+/// Stages that `OrderHint::BeforeIn`/`AfterIn` can reorder.
+const ORDERED_STAGES: [ModuleStage; 3] = [
+    ModuleStage::FixedUpdate,
+    ModuleStage::Update,
+    ModuleStage::Render,
+];
+
+/// Topologically sorts `modules` by `dependencies()` and `order_hints()`. Modules that are
+/// not constrained keep their relative order.
+fn plan_module_order<E: Send + 'static>(modules: &[Box<dyn Module<E>>]) -> EngineResult<ModuleOrder> {
+    let mut id_to_index: HashMap<&'static str, usize> = HashMap::with_capacity(modules.len());
+    for (i, m) in modules.iter().enumerate() {
+        let id = m.id();
+        if id_to_index.insert(id, i).is_some() {
+            return Err(EngineError::Other(format!("duplicate module id: {id}")));
+        }
+    }
+
+    // (first, then)
+    let mut dep_edges: Vec<(usize, usize)> = Vec::new();
+    for (i, m) in modules.iter().enumerate() {
+        for &dep in m.dependencies() {
+            let Some(&dep_i) = id_to_index.get(dep) else {
+                return Err(EngineError::Other(format!(
+                    "module dependency missing: {} -> {dep}",
+                    m.id()
+                )));
+            };
+            dep_edges.push((dep_i, i));
+        }
+    }
+
+    let sort = |stage: Option<ModuleStage>| -> EngineResult<Vec<usize>> {
+        let mut edges = dep_edges.clone();
+        for (i, m) in modules.iter().enumerate() {
+            for hint in m.order_hints() {
+                if hint.stage().is_some() && hint.stage() != stage {
+                    continue;
+                }
+                let Some(&other) = id_to_index.get(hint.target()) else {
+                    continue;
+                };
+                if other != i {
+                    edges.push(if hint.is_before() { (i, other) } else { (other, i) });
                 }
             }
         }
 
-        for m in self.modules.iter() {
-            for r in m.requires().iter() {
-                let Some(have) = provided.get(r.id) else {
-                    return Err(EngineError::Other(format!(
-                        "module '{}' requires API '{}' >= {}.{}.{} but it is not provided",
-                        m.id(),
-                        r.id,
-                        r.min_version.major,
-                        r.min_version.minor,
-                        r.min_version.patch,
-                    )));
-                };
+        topo_sort(modules.len(), &edges).map_err(|cyclic| {
+            let ids: Vec<&'static str> = cyclic.iter().map(|&i| modules[i].id()).collect();
+            if topo_sort(modules.len(), &dep_edges).is_err() {
+                EngineError::Other(format!("module dependency cycle detected among: {ids:?}"))
+            } else {
+                EngineError::Other(format!(
+                    "module order hints conflict (stage={}) among: {ids:?}",
+                    stage.map_or("all", ModuleStage::as_str)
+                ))
+            }
+        })
+    };
+
+    let global = sort(None)?;
+
+    let mut position = vec![0usize; modules.len()];
+    for (pos, &i) in global.iter().enumerate() {
+        position[i] = pos;
+    }
+
+    let mut stages = Vec::new();
+    for stage in ORDERED_STAGES {
+        let scoped = modules
+            .iter()
+            .any(|m| m.order_hints().iter().any(|h| h.stage() == Some(stage)));
+        if !scoped {
+            continue;
+        }
+        let order: Vec<usize> = sort(Some(stage))?.into_iter().map(|i| position[i]).collect();
+        if order.iter().enumerate().any(|(k, &pos)| k != pos) {
+            stages.push((stage, order));
+        }
+    }
+
+    Ok(ModuleOrder { global, stages })
+}
+
+/// Kahn's algorithm over `0..n`, taking ready nodes in index order. On a cycle returns the
+/// nodes that could not be ordered.
+fn topo_sort(n: usize, edges: &[(usize, usize)]) -> Result<Vec<usize>, Vec<usize>> {
+    let mut indegree = vec![0usize; n];
+    let mut rev_edges: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for &(from, to) in edges {
+        indegree[to] += 1;
+        rev_edges[from].push(to);
+    }
 
-                if *have < r.min_version {
-                    let prov = provider.get(r.id).copied().unwrap_or("<unknown>");
-                    return Err(EngineError::Other(format!(
-                        "module '{}' requires API '{}' >= {}.{}.{} but provider '{}' offers {}.{}.{}",
-                        m.id(),
-                        r.id,
-                        r.min_version.major,
-                        r.min_version.minor,
-                        r.min_version.patch,
-                        prov,
-                        have.major,
-                        have.minor,
-                        have.patch,
-                    )));
+    let mut q: VecDeque<usize> = (0..n).filter(|&i| indegree[i] == 0).collect();
+    let mut order: Vec<usize> = Vec::with_capacity(n);
+    while let Some(i) = q.pop_front() {
+        order.push(i);
+        for &to in rev_edges[i].iter() {
+            indegree[to] = indegree[to].saturating_sub(1);
+            if indegree[to] == 0 {
+                q.push_back(to);
+            }
+        }
+    }
+
+    if order.len() != n {
+        return Err((0..n).filter(|&i| indegree[i] != 0).collect());
+    }
+    Ok(order)
+}
+
+#[inline]
+fn reorder<T>(items: Vec<T>, order: &[usize]) -> Vec<T> {
+    let mut slots: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    order
+        .iter()
+        .map(|&i| slots[i].take().expect("module slot already moved"))
+        .collect()
+}
+
+/// Checks that every `requires()` in `modules` is met by some module's `provides()`.
+fn check_api_contracts<'a, E: Send + 'static>(
+    modules: impl Iterator<Item = &'a dyn Module<E>> + Clone,
+) -> EngineResult<()> {
+    let mut provided: HashMap<&'static str, ApiVersion> = HashMap::new();
+    let mut provider: HashMap<&'static str, &'static str> = HashMap::new();
+
+    for m in modules.clone() {
+        for p in m.provides().iter() {
+            match provided.get(p.id) {
+                Some(v) if *v >= p.version => {}
+                _ => {
+                    provided.insert(p.id, p.version);
+                    provider.insert(p.id, m.id());
                 }
             }
         }
+    }
 
-        Ok(())
+    for m in modules {
+        for r in m.requires().iter() {
+            let Some(have) = provided.get(r.id) else {
+                return Err(EngineError::Other(format!(
+                    "module '{}' requires API '{}' >= {}.{}.{} but it is not provided",
+                    m.id(),
+                    r.id,
+                    r.min_version.major,
+                    r.min_version.minor,
+                    r.min_version.patch,
+                )));
+            };
+
+            if *have < r.min_version {
+                let prov = provider.get(r.id).copied().unwrap_or("<unknown>");
+                return Err(EngineError::Other(format!(
+                    "module '{}' requires API '{}' >= {}.{}.{} but provider '{}' offers {}.{}.{}",
+                    m.id(),
+                    r.id,
+                    r.min_version.major,
+                    r.min_version.minor,
+                    r.min_version.patch,
+                    prov,
+                    have.major,
+                    have.minor,
+                    have.patch,
+                )));
+            }
+        }
     }
-}
\ No newline at end of file
+
+    Ok(())
+}
+
+/// Marks a non-critical module as quarantined after a panic and reports it on the event hub.
+fn quarantine_module(
+    quarantined: &mut HashSet<&'static str>,
+    events: &EventHub,
+    module_id: &'static str,
+    stage: ModuleStage,
+    err: EngineError,
+) {
+    let message = match err {
+        EngineError::ModulePanicked(_, msg) => msg,
+        other => other.to_string(),
+    };
+
+    log::error!(
+        "engine.module quarantined id='{}' stage={:?} cause='{}' build='{}'",
+        module_id,
+        stage,
+        message,
+        BuildInfo::get().version_string()
+    );
+
+    quarantined.insert(module_id);
+    let _ = events.publish(ModuleQuarantined {
+        module_id,
+        stage,
+        message,
+    });
+}
+
+/// Applies `policy` to an error returned by a non-critical module. Returns the error if the
+/// engine has to stop.
+fn apply_error_policy(
+    policy: ModuleErrorPolicy,
+    failures: &mut HashMap<&'static str, u32>,
+    quarantined: &mut HashSet<&'static str>,
+    events: &EventHub,
+    module_id: &'static str,
+    stage: ModuleStage,
+    err: EngineError,
+) -> Option<EngineError> {
+    if matches!(err, EngineError::ExitRequested) || policy == ModuleErrorPolicy::Abort {
+        return Some(err);
+    }
+
+    let count = failures.entry(module_id).or_insert(0);
+    *count += 1;
+    if *count <= policy.retries() {
+        log::warn!(
+            "engine.module failed id='{}' stage={:?} failures={}/{} err='{}'",
+            module_id,
+            stage,
+            count,
+            policy.retries() + 1,
+            err
+        );
+        return None;
+    }
+
+    failures.remove(module_id);
+    quarantine_module(quarantined, events, module_id, stage, err);
+    None
+}
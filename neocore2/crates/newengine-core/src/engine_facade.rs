@@ -0,0 +1,115 @@
+use crate::build_info::BuildInfo;
+use crate::bus::{AnyBus, BusMessage};
+use crate::engine::Engine;
+use crate::error::EngineResult;
+use crate::events::EventHub;
+use crate::frame::Frame;
+use crate::lifecycle::SuspendReason;
+use crate::module::{Module, Resources};
+use crate::sync::{CancelToken, ShutdownToken};
+
+/// Object-safe view of an [`Engine`] that does not name its event type.
+///
+/// Runners, tools and plugin hosts take `&mut dyn EngineFacade` so they can drive any
+/// `Engine<E>`; modules they add go through the type-erased bus.
+pub trait EngineFacade {
+    fn build_info(&self) -> &'static BuildInfo;
+
+    fn events(&self) -> &EventHub;
+
+    fn any_bus(&self) -> &AnyBus;
+
+    fn resources_mut(&mut self) -> &mut Resources;
+
+    fn register_erased_module(&mut self, module: Box<dyn Module<BusMessage>>) -> EngineResult<()>;
+
+    fn start(&mut self) -> EngineResult<()>;
+
+    fn step_frame(&mut self) -> EngineResult<Frame>;
+
+    fn suspend(&mut self, reason: SuspendReason) -> EngineResult<()>;
+
+    fn resume(&mut self) -> EngineResult<()>;
+
+    fn is_suspended(&self) -> bool;
+
+    fn request_exit(&mut self) -> EngineResult<()>;
+
+    fn shutdown_token(&self) -> ShutdownToken;
+
+    fn cancel_token(&self) -> CancelToken;
+
+    fn shutdown(&mut self) -> EngineResult<()>;
+}
+
+impl<E: Send + 'static> EngineFacade for Engine<E> {
+    #[inline]
+    fn build_info(&self) -> &'static BuildInfo {
+        Engine::build_info(self)
+    }
+
+    #[inline]
+    fn events(&self) -> &EventHub {
+        Engine::events(self)
+    }
+
+    #[inline]
+    fn any_bus(&self) -> &AnyBus {
+        Engine::any_bus(self)
+    }
+
+    #[inline]
+    fn resources_mut(&mut self) -> &mut Resources {
+        Engine::resources_mut(self)
+    }
+
+    #[inline]
+    fn register_erased_module(&mut self, module: Box<dyn Module<BusMessage>>) -> EngineResult<()> {
+        Engine::register_erased_module(self, module)
+    }
+
+    #[inline]
+    fn start(&mut self) -> EngineResult<()> {
+        Engine::start(self)
+    }
+
+    #[inline]
+    fn step_frame(&mut self) -> EngineResult<Frame> {
+        Engine::step_frame(self)
+    }
+
+    #[inline]
+    fn suspend(&mut self, reason: SuspendReason) -> EngineResult<()> {
+        Engine::suspend(self, reason)
+    }
+
+    #[inline]
+    fn resume(&mut self) -> EngineResult<()> {
+        Engine::resume(self)
+    }
+
+    #[inline]
+    fn is_suspended(&self) -> bool {
+        Engine::is_suspended(self)
+    }
+
+    #[inline]
+    fn request_exit(&mut self) -> EngineResult<()> {
+        Engine::request_exit(self)
+    }
+
+    #[inline]
+    fn shutdown_token(&self) -> ShutdownToken {
+        Engine::shutdown_token(self)
+    }
+
+    #[inline]
+    fn cancel_token(&self) -> CancelToken {
+        Engine::cancel_token(self)
+    }
+
+    #[inline]
+    fn shutdown(&mut self) -> EngineResult<()> {
+        Engine::shutdown(self)
+    }
+}
@@ -16,12 +16,15 @@ pub enum EngineError {
         cause: Box<EngineError>,
     },
 
+    /// A module callback panicked: `(module_id, panic message)`.
+    ModulePanicked(&'static str, String),
+
     /// Generic error (fallback).
     Other(String),
 }
 
 /// Module lifecycle stage used for error attribution.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ModuleStage {
     Init,
     Start,
@@ -29,15 +32,48 @@ pub enum ModuleStage {
     Update,
     Render,
     ExternalEvent,
+    Suspend,
+    Resume,
+    Pause,
+    Unpause,
     Shutdown,
 }
 
+impl ModuleStage {
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModuleStage::Init => "init",
+            ModuleStage::Start => "start",
+            ModuleStage::FixedUpdate => "fixed_update",
+            ModuleStage::Update => "update",
+            ModuleStage::Render => "render",
+            ModuleStage::ExternalEvent => "external_event",
+            ModuleStage::Suspend => "suspend",
+            ModuleStage::Resume => "resume",
+            ModuleStage::Pause => "pause",
+            ModuleStage::Unpause => "unpause",
+            ModuleStage::Shutdown => "shutdown",
+        }
+    }
+}
+
 impl EngineError {
     #[inline]
     pub fn other(msg: impl Into<String>) -> Self {
         Self::Other(msg.into())
     }
 
+    /// True for `ModulePanicked`, also when wrapped in `Module { .. }`.
+    #[inline]
+    pub fn is_panic(&self) -> bool {
+        match self {
+            EngineError::ModulePanicked(..) => true,
+            EngineError::Module { cause, .. } => cause.is_panic(),
+            _ => false,
+        }
+    }
+
     #[inline]
     pub fn with_module_stage(
         module_id: &'static str,
@@ -60,6 +96,9 @@ impl fmt::Display for EngineError {
         match self {
             EngineError::ExitRequested => write!(f, "exit requested"),
             EngineError::Other(s) => write!(f, "{s}"),
+            EngineError::ModulePanicked(module_id, msg) => {
+                write!(f, "module '{module_id}' panicked: {msg}")
+            }
             EngineError::Module {
                 module_id,
                 stage,
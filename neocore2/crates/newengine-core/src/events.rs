@@ -1,4 +1,5 @@
 use crate::error::EngineResult;
+use crate::trace::{self, TraceKind};
 
 use crossbeam_channel::{Receiver, Sender, TrySendError};
 use std::any::{Any, TypeId};
@@ -62,6 +63,11 @@ impl EventHub {
     where
         T: Any + Send + Sync + 'static,
     {
+        trace::record(
+            TraceKind::Event,
+            trace::short_type_name(std::any::type_name::<T>()),
+            String::new,
+        );
         let arc: Arc<dyn Any + Send + Sync> = Arc::new(event);
         self.inner.publish_typed(TypeId::of::<T>(), arc)
     }
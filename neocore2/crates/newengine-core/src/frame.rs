@@ -3,7 +3,8 @@
 /// The engine emits two kinds of frames:
 ///
 /// - **Variable frame**: used for `update()` and `render()`.
-///   `dt` is the real (clamped) wall-clock delta.
+///   `dt` is the (clamped) wall-clock delta scaled by the engine's
+///   [`TimeControl`](crate::time_control::TimeControl); `0.0` while paused.
 ///
 /// - **Fixed subframe**: emitted for each `fixed_update()` step.
 ///   `dt == fixed_dt`, `fixed_alpha == 0.0`, and `fixed_step_index` indicates
@@ -19,6 +20,10 @@ pub struct Frame {
     /// Delta time for this frame. For fixed subframes this equals `fixed_dt`.
     pub dt: f32,
 
+    /// Unscaled wall-clock delta, for things that keep moving while game time is paused or
+    /// slowed (editor cameras, UI). For fixed subframes this equals `fixed_dt`.
+    pub real_dt: f32,
+
     /// Fixed timestep size.
     pub fixed_dt: f32,
 
@@ -1,10 +1,14 @@
+use newengine_assets::AssetId;
 use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
+use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub enum HostEvent {
     Window(WindowHostEvent),
     Input(InputHostEvent),
     Text(TextHostEvent),
+    Gamepad(GamepadHostEvent),
+    FileDrop(FileDropEvent),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -19,9 +23,63 @@ pub enum WindowHostEvent {
         height: u32,
     },
     Focused(bool),
+    /// Engine entered the suspended state (minimized / OS suspend).
+    Suspended,
+    /// Engine left the suspended state.
+    Resumed,
     CloseRequested,
+    /// Display scale of a window changed (moved to another monitor, OS setting). `window` is
+    /// `None` for the main window; `width`/`height` are the new inner size.
+    ScaleFactorChanged {
+        window: Option<WindowId>,
+        scale_factor: f64,
+        width: u32,
+        height: u32,
+    },
+    /// Secondary window created through the host window API.
+    Opened {
+        window: WindowId,
+        width: u32,
+        height: u32,
+    },
+    SecondaryResized {
+        window: WindowId,
+        width: u32,
+        height: u32,
+    },
+    /// The user asked to close a secondary window; it stays open until the app closes it.
+    SecondaryCloseRequested(WindowId),
+    /// Secondary window destroyed; its render surface must already be detached.
+    Closed(WindowId),
+}
+
+/// Files dragged onto a window from the OS. `window` is `None` for the main window; paths
+/// of one drag gesture arrive together.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileDropEvent {
+    Hovered {
+        window: Option<WindowId>,
+        paths: Vec<PathBuf>,
+    },
+    /// The drag left the window or was aborted.
+    HoverCancelled { window: Option<WindowId> },
+    Dropped {
+        window: Option<WindowId>,
+        paths: Vec<PathBuf>,
+    },
+    /// A dropped file was placed under the assets root and queued for import (host drop
+    /// import enabled).
+    Imported {
+        source: PathBuf,
+        logical_path: PathBuf,
+        asset: AssetId,
+    },
 }
 
+/// Host-assigned id of a secondary window. The main window has no id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WindowId(pub u64);
+
 #[derive(Debug, Clone, Copy)]
 pub enum InputHostEvent {
     Key {
@@ -54,6 +112,68 @@ pub enum TextHostEvent {
     ImeCommit(String),
 }
 
+/// Host-assigned gamepad id (stable while the device stays connected).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GamepadId(pub u32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GamepadHostEvent {
+    Connected {
+        id: GamepadId,
+        name: String,
+        rumble: bool,
+    },
+    Disconnected {
+        id: GamepadId,
+    },
+    /// `value` is 0..=1 (analog for triggers, 0/1 for digital buttons).
+    Button {
+        id: GamepadId,
+        button: GamepadButton,
+        value: f32,
+    },
+    /// `value` is -1..=1 for sticks, 0..=1 for triggers.
+    Axis {
+        id: GamepadId,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+/// Standard (Xbox-style) button layout; `South` is A / Cross.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Other(u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+    Other(u16),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum KeyState {
     Pressed,
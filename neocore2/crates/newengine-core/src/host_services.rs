@@ -1,10 +1,13 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 use abi_stable::std_types::{RResult, RString};
-use newengine_plugin_api::{Blob, CapabilityId, MethodName};
+use newengine_plugin_api::{schemas, Blob, CapabilityId, MethodName, ServiceV1Dyn};
 
+use crate::plugins::descriptor::{lookup_descriptor, register_descriptor};
 use crate::plugins::host_api;
-use crate::plugins::host_context;
+use crate::plugins::host_context::{self, with_current_plugin_id};
+use crate::plugins::permissions::{grant_permissions, revoke_permissions};
+use crate::plugins::{PluginPermissions, ServiceCallStats, ServiceDescriptor, ServiceRateLimit};
 
 #[inline]
 pub fn call_service_v1(capability_id: &str, method: &str, payload: &[u8]) -> Result<Vec<u8>, String> {
@@ -37,4 +40,92 @@ pub fn describe_service(service_id: &str) -> Option<String> {
     let g = c.services.lock().ok()?;
     let svc = g.get(service_id)?.clone();
     Some(svc.describe_json.to_string())
-}
\ No newline at end of file
+}
+/// Structured descriptor of `service_id`: the registered one, or one derived from its
+/// describe JSON.
+#[inline]
+pub fn service_descriptor(service_id: &str) -> Option<ServiceDescriptor> {
+    lookup_descriptor(service_id)
+}
+
+/// Registers `descriptor` for a service owned by the engine (outside any plugin call) or by
+/// the plugin currently calling into the host.
+#[inline]
+pub fn register_service_descriptor(descriptor: ServiceDescriptor) -> Result<(), String> {
+    register_descriptor(descriptor)
+}
+
+/// Per-service call metrics collected by the host (sorted by service id).
+#[inline]
+pub fn service_call_stats() -> Vec<(String, ServiceCallStats)> {
+    let c = host_context::ctx();
+    let g = match c.service_metrics.lock() {
+        Ok(v) => v,
+        Err(_) => return Vec::new(),
+    };
+    g.snapshot()
+}
+
+#[inline]
+pub fn reset_service_call_stats() {
+    let c = host_context::ctx();
+    if let Ok(mut g) = c.service_metrics.lock() {
+        g.reset();
+    }
+}
+
+/// Installs (or clears with `None`) a call quota for services invoked by `plugin_id`.
+#[inline]
+pub fn set_plugin_service_rate_limit(plugin_id: &str, limit: Option<ServiceRateLimit>) {
+    let c = host_context::ctx();
+    if let Ok(mut g) = c.service_metrics.lock() {
+        g.set_limit(plugin_id, limit);
+    }
+}
+
+/* Plugin runtimes other than the DLL loader (WASM) go through these to act as a plugin. */
+
+/// Registers `svc` as owned by `plugin_id`. Permission checks and importer auto-registration
+/// apply as for a DLL plugin's `register_service_v1`.
+pub fn register_plugin_service(plugin_id: &str, svc: ServiceV1Dyn<'static>) -> Result<(), String> {
+    with_current_plugin_id(plugin_id, || host_api::host_register_service_impl(svc, true))
+        .into_result()
+        .map_err(|e| e.to_string())
+}
+
+/// [`call_service_v1`] on behalf of `plugin_id`: its permissions and rate limit apply.
+#[inline]
+pub fn call_service_as(
+    plugin_id: &str,
+    capability_id: &str,
+    method: &str,
+    payload: &[u8],
+) -> Result<Vec<u8>, String> {
+    with_current_plugin_id(plugin_id, || call_service_v1(capability_id, method, payload))
+}
+
+/// Emits a plugin event on behalf of `plugin_id`, for plugin runtimes outside the native ABI:
+/// the sinks receive it and the engine publishes it as a [`PluginEvent`](crate::PluginEvent).
+#[inline]
+pub fn emit_plugin_event_as(plugin_id: &str, topic: &str, payload: &[u8]) -> Result<(), String> {
+    with_current_plugin_id(plugin_id, || {
+        host_context::emit_event_from_plugin(
+            RString::from(topic),
+            schemas::UNTYPED,
+            Blob::from(payload.to_vec()),
+        )
+    })
+}
+
+/// Sets the permissions checked for `plugin_id`'s host calls. The plugin is sandboxed: it
+/// cannot call services that declare no permissions, other than its own.
+#[inline]
+pub fn grant_plugin_permissions(plugin_id: &str, permissions: PluginPermissions) {
+    grant_permissions(plugin_id, permissions, true);
+}
+
+/// Removes the services, event sinks and commands `plugin_id` registered, and its permissions.
+pub fn unregister_plugin(plugin_id: &str) {
+    host_context::unregister_by_owner(plugin_id);
+    revoke_permissions(plugin_id);
+}
@@ -1,35 +1,74 @@
+pub mod build_info;
 pub mod bus;
 pub mod core_invariants;
+pub mod crash;
+pub mod cvars;
+pub mod dry_run;
 pub mod engine;
+pub mod engine_facade;
 pub mod error;
 pub mod events;
 pub mod frame;
 pub mod host_events;
+pub mod lifecycle;
+pub mod log_filter;
+pub mod log_tap;
 pub mod module;
 pub mod plugins;
+pub mod replay;
+pub mod replication;
+pub mod save;
 pub mod sched;
 pub mod sync;
+pub mod time_control;
 mod system_info;
+mod wire;
 pub mod render;
 pub mod startup;
 pub mod assets;
 pub mod assets_service;
 pub mod console;
 pub mod host_services;
+pub mod notify;
+pub mod settings;
+pub mod trace;
+pub mod ui_actions;
+pub mod ui_remote;
 
-pub use host_services::{call_service_v1, describe_service, list_service_ids};
+pub use host_services::{
+    call_service_as, call_service_v1, describe_service, emit_plugin_event_as,
+    grant_plugin_permissions, list_service_ids, register_plugin_service,
+    register_service_descriptor, reset_service_call_stats, service_call_stats,
+    service_descriptor, set_plugin_service_rate_limit, unregister_plugin,
+};
+pub use plugins::{
+    EventBridge, MethodDescriptor, PluginCallSite, PluginEvent, PluginFault, PluginPermission, PluginPermissionPolicy,
+    PluginPermissions, ServiceCallStats, ServiceDescriptor, ServiceRateLimit,
+};
 
 pub use assets::{AssetManager, AssetManagerConfig};
 
-pub use bus::Bus;
+pub use build_info::BuildInfo;
+pub use bus::{AnyBus, Bus, BusMessage, BusPayload};
+pub use cvars::{CVarDef, CVarKind, CVarValue, CVars};
+pub use dry_run::{CheckStatus, DryRunReport};
 pub use engine::{Engine, EngineConfig};
+pub use engine_facade::EngineFacade;
 pub use error::{EngineError, EngineResult, ModuleStage};
 pub use events::{EventHub, EventSub};
 pub use frame::Frame;
-pub use host_events::WindowHostEvent;
-pub use module::{ApiProvide, ApiRequire, ApiVersion, Module, ModuleCtx, Resources, Services};
+pub use host_events::{FileDropEvent, WindowHostEvent, WindowId};
+pub use lifecycle::{SuspendPolicy, SuspendReason};
+pub use module::{
+    ApiProvide, ApiRequire, ApiVersion, DynModule, ErasedModule, Module, ModuleCtx,
+    ModuleErrorPolicy, ModuleQuarantined, OrderHint, Resources, Services,
+};
 pub use sched::Scheduler;
-pub use sync::ShutdownToken;
+pub use sync::{CancelToken, ShutdownToken};
+pub use replay::{Replay, ReplaySystem};
+pub use replication::{ReplicationClient, ReplicationServer};
+pub use save::{SaveApi, SaveLoad};
+pub use time_control::{TimeControl, TimeSource};
 
 pub use render::{
     BeginFrameDesc, Color4, RenderApi, RenderApiRef, RENDER_API_ID, RENDER_API_PROVIDE,
@@ -44,5 +83,6 @@ pub use startup::{
     StartupLoader,
     StartupOverride,
     StartupResolvedFrom,
+    WindowFullscreen,
     WindowPlacement,
 };
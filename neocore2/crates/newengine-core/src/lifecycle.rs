@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+/// Why the engine was suspended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SuspendReason {
+    /// Window minimized / fully occluded.
+    Minimized,
+    /// OS sleep or application backgrounding (winit `suspended`).
+    OsSuspend,
+    /// Requested explicitly by the application.
+    Host,
+}
+
+/// What the engine and host do while suspended.
+///
+/// Stored in `Resources` so modules can read it from `on_suspend`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SuspendPolicy {
+    /// Loop rate while suspended. Only `Update` runs; `FixedUpdate` and `Render` are skipped.
+    pub idle_tick_hz: u32,
+    /// Ask the render backend to drop its swapchain/surface resources.
+    pub release_gpu_surface: bool,
+    /// Suspend on minimize/occlusion (not only on OS suspend).
+    pub suspend_on_minimize: bool,
+}
+
+impl Default for SuspendPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            idle_tick_hz: 10,
+            release_gpu_surface: false,
+            suspend_on_minimize: true,
+        }
+    }
+}
+
+impl SuspendPolicy {
+    #[inline]
+    pub fn with_idle_tick_hz(mut self, hz: u32) -> Self {
+        self.idle_tick_hz = hz;
+        self
+    }
+
+    #[inline]
+    pub fn with_release_gpu_surface(mut self, release: bool) -> Self {
+        self.release_gpu_surface = release;
+        self
+    }
+
+    #[inline]
+    pub fn with_suspend_on_minimize(mut self, enabled: bool) -> Self {
+        self.suspend_on_minimize = enabled;
+        self
+    }
+
+    /// Interval between idle ticks (`idle_tick_hz` of 0 is treated as 1 Hz).
+    #[inline]
+    pub fn idle_interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.idle_tick_hz.max(1) as f64)
+    }
+}
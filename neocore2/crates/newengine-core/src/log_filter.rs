@@ -0,0 +1,188 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Log verbosity per engine module id, on top of the usual per-target filter.
+//!
+//! A record belongs to the plugin whose callback is running on the logging thread (plugin
+//! logs arrive through `HostApiV1`, so their Rust target is the host's), otherwise to the
+//! engine module whose lifecycle callback is running (see [`current_module_id`]). Records
+//! logged outside any callback, e.g. from worker threads, have no owner and only see the
+//! target filter.
+//!
+//! ```text
+//! NEWENGINE_LOG_MODULES="render.vulkan.ash=debug,cef=warn,import=off"
+//! ```
+//!
+//! A rule applies to its id and to ids below it (`import` covers `import.image`); the most
+//! specific rule wins. A rule replaces the target filter for the records it matches, so it
+//! can make a module both quieter and more verbose than the global level.
+
+use crate::module::current_module_id;
+use crate::plugins::host_context::peek_current_plugin_id;
+
+use log::LevelFilter;
+use std::cmp::Reverse;
+use std::env;
+
+/// Parsed `id=level` rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleLogLevels {
+    /// Sorted by descending id length so the first match is the most specific.
+    rules: Vec<(String, LevelFilter)>,
+}
+
+impl ModuleLogLevels {
+    /// Parses `id=level[,id=level...]`. Whitespace and empty entries are ignored.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut out = Self::default();
+        for part in spec.split(',') {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            let (id, level) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected 'module=level', got '{part}'"))?;
+            let id = id.trim();
+            if id.is_empty() {
+                return Err(format!("missing module id in '{part}'"));
+            }
+            let level = level
+                .trim()
+                .parse::<LevelFilter>()
+                .map_err(|_| format!("unknown log level '{}' for '{id}'", level.trim()))?;
+            out.set(id, level);
+        }
+        Ok(out)
+    }
+
+    /// Reads `NEWENGINE_LOG_MODULES`. Invalid specs are reported on stderr (there may be no
+    /// logger yet) and ignored.
+    pub fn from_env() -> Self {
+        let Ok(spec) = env::var("NEWENGINE_LOG_MODULES") else {
+            return Self::default();
+        };
+        match Self::parse(&spec) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("NEWENGINE_LOG_MODULES ignored: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    /// Adds or replaces the rule for `id`.
+    pub fn set(&mut self, id: impl Into<String>, level: LevelFilter) {
+        let id = id.into();
+        self.rules.retain(|(i, _)| *i != id);
+        self.rules.push((id, level));
+        self.rules.sort_by_key(|(id, _)| Reverse(id.len()));
+    }
+
+    #[inline]
+    pub fn with(mut self, id: impl Into<String>, level: LevelFilter) -> Self {
+        self.set(id, level);
+        self
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Level of the most specific rule covering `id`.
+    pub fn level_for(&self, id: &str) -> Option<LevelFilter> {
+        self.rules
+            .iter()
+            .find(|(rule, _)| {
+                id.strip_prefix(rule.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+            .map(|(_, level)| *level)
+    }
+
+    /// Most verbose level any rule asks for.
+    pub fn max_level(&self) -> LevelFilter {
+        self.rules
+            .iter()
+            .map(|(_, level)| *level)
+            .max()
+            .unwrap_or(LevelFilter::Off)
+    }
+
+    /// Level for the record being logged on this thread, if a rule covers its owner.
+    #[inline]
+    pub fn current_level(&self) -> Option<LevelFilter> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        with_log_owner(|owner| owner.and_then(|id| self.level_for(id)))
+    }
+}
+
+/// Calls `f` with the id that owns records logged on this thread right now: the running
+/// plugin, else the running engine module.
+pub fn with_log_owner<R>(f: impl FnOnce(Option<&str>) -> R) -> R {
+    peek_current_plugin_id(|plugin| match plugin {
+        Some(id) => f(Some(id)),
+        None => f(current_module_id()),
+    })
+}
+
+/// Applies [`ModuleLogLevels`] in front of a logger.
+///
+/// `inner` handles records without a matching rule and keeps its own filter. Records with a
+/// rule go to `verbose`, which should format like `inner` but accept everything; without
+/// it they go to `inner` and can only be made quieter.
+pub struct ModuleFilterLogger<L> {
+    inner: L,
+    verbose: Option<L>,
+    levels: ModuleLogLevels,
+}
+
+impl<L: log::Log> ModuleFilterLogger<L> {
+    #[inline]
+    pub fn new(inner: L, verbose: Option<L>, levels: ModuleLogLevels) -> Self {
+        Self {
+            inner,
+            verbose,
+            levels,
+        }
+    }
+
+    /// Max level to pass to [`log::set_max_level`] given the inner logger's own max.
+    #[inline]
+    pub fn max_level(&self, inner_max: LevelFilter) -> LevelFilter {
+        match self.verbose {
+            Some(_) => inner_max.max(self.levels.max_level()),
+            None => inner_max,
+        }
+    }
+}
+
+impl<L: log::Log> log::Log for ModuleFilterLogger<L> {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        match self.levels.current_level() {
+            Some(level) if self.verbose.is_some() => metadata.level() <= level,
+            Some(level) => metadata.level() <= level && self.inner.enabled(metadata),
+            None => self.inner.enabled(metadata),
+        }
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        match self.levels.current_level() {
+            Some(level) if record.level() > level => {}
+            Some(_) => match self.verbose.as_ref() {
+                Some(verbose) => verbose.log(record),
+                None => self.inner.log(record),
+            },
+            None => self.inner.log(record),
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+        if let Some(verbose) = self.verbose.as_ref() {
+            verbose.flush();
+        }
+    }
+}
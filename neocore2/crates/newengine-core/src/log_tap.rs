@@ -0,0 +1,125 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Fan-out of log records to in-process listeners (remote consoles, log viewers).
+//!
+//! The global logger stays whatever the host installed. Loggers that want their records
+//! observable wrap it in [`TeeLogger`] (the logging module does) or call [`dispatch`]
+//! themselves. Formatting only happens while at least one listener is registered.
+
+use crate::log_filter::with_log_owner;
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One formatted log record.
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub level: log::Level,
+    pub target: String,
+    /// Engine module or plugin that logged it (see [`crate::log_filter::with_log_owner`]).
+    pub module: Option<String>,
+    pub message: String,
+    pub unix_ms: u64,
+}
+
+pub type LogListener = Arc<dyn Fn(&LogLine) + Send + Sync>;
+
+static LISTENERS: RwLock<Vec<(u64, LogListener)>> = RwLock::new(Vec::new());
+static LISTENER_COUNT: AtomicUsize = AtomicUsize::new(0);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// Set while listeners run, so records they log themselves are not fed back to them.
+    static IN_DISPATCH: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Registers `f` for every record passed to [`dispatch`]. It runs on the logging thread and
+/// must not block; hand records to a channel for anything slow. Returns an id for
+/// [`remove_log_listener`].
+pub fn add_log_listener(f: impl Fn(&LogLine) + Send + Sync + 'static) -> u64 {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    if let Ok(mut g) = LISTENERS.write() {
+        g.push((id, Arc::new(f)));
+        LISTENER_COUNT.store(g.len(), Ordering::Release);
+    }
+    id
+}
+
+pub fn remove_log_listener(id: u64) -> bool {
+    let Ok(mut g) = LISTENERS.write() else {
+        return false;
+    };
+    let before = g.len();
+    g.retain(|(i, _)| *i != id);
+    LISTENER_COUNT.store(g.len(), Ordering::Release);
+    g.len() != before
+}
+
+#[inline]
+pub fn has_log_listeners() -> bool {
+    LISTENER_COUNT.load(Ordering::Acquire) > 0
+}
+
+/// Hands `record` to every listener.
+pub fn dispatch(record: &log::Record<'_>) {
+    if !has_log_listeners() || IN_DISPATCH.with(Cell::get) {
+        return;
+    }
+
+    // Listeners are called outside the lock so they may (un)register listeners.
+    let listeners: Vec<LogListener> = match LISTENERS.read() {
+        Ok(g) => g.iter().map(|(_, f)| f.clone()).collect(),
+        Err(_) => return,
+    };
+
+    let line = LogLine {
+        level: record.level(),
+        target: record.target().to_owned(),
+        module: with_log_owner(|owner| owner.map(str::to_owned)),
+        message: record.args().to_string(),
+        unix_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    };
+
+    IN_DISPATCH.with(|f| f.set(true));
+    for f in listeners.iter() {
+        f(&line);
+    }
+    IN_DISPATCH.with(|f| f.set(false));
+}
+
+/// Wraps a logger so every record it accepts also reaches the listeners.
+pub struct TeeLogger<L> {
+    inner: L,
+}
+
+impl<L: log::Log> TeeLogger<L> {
+    #[inline]
+    pub fn new(inner: L) -> Self {
+        Self { inner }
+    }
+}
+
+impl<L: log::Log> log::Log for TeeLogger<L> {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.inner.enabled(record.metadata()) {
+            return;
+        }
+        self.inner.log(record);
+        dispatch(record);
+    }
+
+    #[inline]
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
@@ -1,3 +1,6 @@
+use crate::bus::{AnyBus, BusMessage};
+use crate::console::ConsoleCommands;
+use crate::cvars::CVars;
 use crate::events::EventHub;
 use crate::frame::Frame;
 use crate::module::{Bus, Resources, Services};
@@ -10,6 +13,7 @@ pub struct ModuleCtx<'a, E: Send + 'static> {
     services: &'a dyn Services,
     resources: &'a mut Resources,
     bus: &'a Bus<E>,
+    any_bus: &'a AnyBus,
     events: &'a EventHub,
     scheduler: &'a mut Scheduler,
     exit: &'a mut bool,
@@ -22,6 +26,7 @@ impl<'a, E: Send + 'static> ModuleCtx<'a, E> {
         services: &'a dyn Services,
         resources: &'a mut Resources,
         bus: &'a Bus<E>,
+        any_bus: &'a AnyBus,
         events: &'a EventHub,
         scheduler: &'a mut Scheduler,
         exit: &'a mut bool,
@@ -30,6 +35,7 @@ impl<'a, E: Send + 'static> ModuleCtx<'a, E> {
             services,
             resources,
             bus,
+            any_bus,
             events,
             scheduler,
             exit,
@@ -103,11 +109,50 @@ impl<'a, E: Send + 'static> ModuleCtx<'a, E> {
         self.bus
     }
 
+    /// Type-erased bus, shared by all modules whatever the engine's event type is.
+    #[inline]
+    pub fn any_bus(&self) -> &AnyBus {
+        self.any_bus
+    }
+
+    /// Reborrows this context as the one an [`ErasedModule`](crate::module::ErasedModule)
+    /// sees: same resources and services, with [`any_bus`](Self::any_bus) as its bus.
+    #[inline]
+    pub fn erased(&mut self) -> ModuleCtx<'_, BusMessage> {
+        ModuleCtx {
+            services: self.services,
+            resources: self.resources,
+            bus: self.any_bus,
+            any_bus: self.any_bus,
+            events: self.events,
+            scheduler: self.scheduler,
+            exit: self.exit,
+            frame: self.frame,
+        }
+    }
+
     #[inline]
     pub fn events(&self) -> &EventHub {
         self.events
     }
 
+    /// Registers console commands, e.g.
+    /// `ctx.console().register_command("physics.gravity", handler, "Get or set gravity")`.
+    #[inline]
+    pub fn console(&self) -> ConsoleCommands {
+        ConsoleCommands
+    }
+
+    /// Configuration variables, e.g.
+    /// `ctx.cvars().register(CVarDef::new("snd.volume", 0.8).persist())`.
+    #[inline]
+    pub fn cvars(&self) -> CVars {
+        self.resources
+            .get::<CVars>()
+            .cloned()
+            .unwrap_or_else(CVars::global)
+    }
+
     #[inline]
     pub fn scheduler(&mut self) -> &mut Scheduler {
         self.scheduler
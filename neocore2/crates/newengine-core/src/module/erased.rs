@@ -0,0 +1,114 @@
+use crate::bus::BusMessage;
+use crate::error::EngineResult;
+use crate::lifecycle::SuspendReason;
+use crate::module::{ApiProvide, ApiRequire, Module, ModuleCtx, OrderHint};
+
+use std::any::Any;
+
+/// A module written once against the type-erased bus.
+///
+/// Implement `Module<BusMessage>` (`ctx.bus()` is then the engine's [`AnyBus`](crate::bus::AnyBus))
+/// and register it in any `Engine<E>` with `Engine::register_erased_module`, or wrap it
+/// yourself with [`ErasedModule::new`].
+pub type DynModule = dyn Module<BusMessage>;
+
+/// Adapts a `Module<BusMessage>` to `Module<E>` for every `E`.
+pub struct ErasedModule {
+    inner: Box<DynModule>,
+}
+
+impl ErasedModule {
+    #[inline]
+    pub fn new(inner: Box<DynModule>) -> Self {
+        Self { inner }
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &DynModule {
+        self.inner.as_ref()
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Box<DynModule> {
+        self.inner
+    }
+}
+
+impl<E: Send + 'static> Module<E> for ErasedModule {
+    fn id(&self) -> &'static str {
+        self.inner.id()
+    }
+
+    fn is_critical(&self) -> bool {
+        self.inner.is_critical()
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        self.inner.dependencies()
+    }
+
+    fn order_hints(&self) -> &'static [OrderHint] {
+        self.inner.order_hints()
+    }
+
+    fn provides(&self) -> &'static [ApiProvide] {
+        self.inner.provides()
+    }
+
+    fn requires(&self) -> &'static [ApiRequire] {
+        self.inner.requires()
+    }
+
+    fn init(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        self.inner.init(&mut ctx.erased())
+    }
+
+    fn start(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        self.inner.start(&mut ctx.erased())
+    }
+
+    fn fixed_update(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        self.inner.fixed_update(&mut ctx.erased())
+    }
+
+    fn update(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        self.inner.update(&mut ctx.erased())
+    }
+
+    fn render(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        self.inner.render(&mut ctx.erased())
+    }
+
+    fn on_suspend(
+        &mut self,
+        ctx: &mut ModuleCtx<'_, E>,
+        reason: SuspendReason,
+    ) -> EngineResult<()> {
+        self.inner.on_suspend(&mut ctx.erased(), reason)
+    }
+
+    fn on_resume(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        self.inner.on_resume(&mut ctx.erased())
+    }
+
+    fn on_pause(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        self.inner.on_pause(&mut ctx.erased())
+    }
+
+    fn on_unpause(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        self.inner.on_unpause(&mut ctx.erased())
+    }
+
+    #[allow(deprecated)]
+    fn on_external_event(
+        &mut self,
+        ctx: &mut ModuleCtx<'_, E>,
+        event: &dyn Any,
+    ) -> EngineResult<()> {
+        self.inner.on_external_event(&mut ctx.erased(), event)
+    }
+
+    fn shutdown(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        self.inner.shutdown(&mut ctx.erased())
+    }
+}
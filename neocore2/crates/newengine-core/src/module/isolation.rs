@@ -0,0 +1,69 @@
+use crate::error::{EngineError, EngineResult, ModuleStage};
+
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Published on the `EventHub` when a non-critical module is quarantined after a panic.
+///
+/// A quarantined module receives no further stage calls; `shutdown` is still attempted.
+#[derive(Debug, Clone)]
+pub struct ModuleQuarantined {
+    pub module_id: &'static str,
+    pub stage: ModuleStage,
+    pub message: String,
+}
+
+/// What the engine does when a non-critical module returns an error from a stage callback.
+///
+/// Critical modules always abort the engine, and panics always quarantine a non-critical
+/// module (its state may be inconsistent); the policy covers returned errors only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModuleErrorPolicy {
+    /// Stop the engine with the error.
+    #[default]
+    Abort,
+    /// Quarantine the module and keep running.
+    DisableModule,
+    /// Log the error and keep calling the module; quarantine it after `n` consecutive
+    /// failures beyond the first. A failing `init` is retried right away instead.
+    Retry { n: u32 },
+}
+
+impl ModuleErrorPolicy {
+    /// Failures tolerated before quarantine.
+    #[inline]
+    pub fn retries(self) -> u32 {
+        match self {
+            ModuleErrorPolicy::Retry { n } => n,
+            _ => 0,
+        }
+    }
+}
+
+/// Runs a module callback, converting a panic into `EngineError::ModulePanicked`.
+///
+/// Only effective when the build unwinds (`panic = "unwind"`); with `abort` the process ends
+/// before this returns.
+#[inline]
+pub(crate) fn catch_module_panic<R>(
+    module_id: &'static str,
+    f: impl FnOnce() -> EngineResult<R>,
+) -> EngineResult<R> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(r) => r,
+        Err(payload) => Err(EngineError::ModulePanicked(
+            module_id,
+            panic_message(payload.as_ref()),
+        )),
+    }
+}
+
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&'static str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
@@ -1,13 +1,21 @@
 pub mod ctx;
+mod erased;
+mod isolation;
 pub mod module;
 pub mod resources;
+mod scope;
 pub mod services;
 
 pub use ctx::ModuleCtx;
-pub use module::{ApiProvide, ApiRequire, ApiVersion, Module};
+pub use erased::{DynModule, ErasedModule};
+pub(crate) use isolation::{catch_module_panic, panic_message};
+pub use isolation::{ModuleErrorPolicy, ModuleQuarantined};
+pub use module::{ApiProvide, ApiRequire, ApiVersion, Module, OrderHint};
 pub use resources::Resources;
+pub use scope::{current_module_id, current_module_stage};
+pub(crate) use scope::ModuleScope;
 pub use services::Services;
 
 /// Re-export the engine bus as a part of `crate::module` facade.
-pub use crate::bus::Bus;
+pub use crate::bus::{AnyBus, Bus, BusMessage};
 
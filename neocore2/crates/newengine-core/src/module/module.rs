@@ -1,7 +1,9 @@
-use crate::error::EngineResult;
+use crate::error::{EngineResult, ModuleStage};
+use crate::lifecycle::SuspendReason;
 use crate::module::ModuleCtx;
 
 use std::any::Any;
+use std::fmt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ApiVersion {
@@ -19,6 +21,31 @@ impl ApiVersion {
             patch,
         }
     }
+
+    /// Parses `major[.minor[.patch]]`; missing parts are zero.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.trim().split('.');
+        let major = parts.next()?.trim().parse().ok()?;
+        let minor = match parts.next() {
+            Some(p) => p.trim().parse().ok()?,
+            None => 0,
+        };
+        let patch = match parts.next() {
+            Some(p) => p.trim().parse().ok()?,
+            None => 0,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -47,15 +74,71 @@ impl ApiRequire {
     }
 }
 
+/// Soft ordering against another module, see [`Module::order_hints`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderHint {
+    /// Run before the module with this id, in every stage.
+    Before(&'static str),
+    /// Run after the module with this id, in every stage.
+    After(&'static str),
+    /// `Before`, in one stage only.
+    BeforeIn(ModuleStage, &'static str),
+    /// `After`, in one stage only.
+    AfterIn(ModuleStage, &'static str),
+}
+
+impl OrderHint {
+    /// Id of the other module.
+    #[inline]
+    pub const fn target(self) -> &'static str {
+        match self {
+            Self::Before(id)
+            | Self::After(id)
+            | Self::BeforeIn(_, id)
+            | Self::AfterIn(_, id) => id,
+        }
+    }
+
+    /// Stage the hint is limited to; `None` for every stage.
+    #[inline]
+    pub const fn stage(self) -> Option<ModuleStage> {
+        match self {
+            Self::Before(_) | Self::After(_) => None,
+            Self::BeforeIn(stage, _) | Self::AfterIn(stage, _) => Some(stage),
+        }
+    }
+
+    #[inline]
+    pub const fn is_before(self) -> bool {
+        matches!(self, Self::Before(_) | Self::BeforeIn(..))
+    }
+}
+
 pub trait Module<E: Send + 'static>: Send {
     fn id(&self) -> &'static str {
         "module"
     }
 
+    /// A panic in a critical module fails the engine (`EngineError::ModulePanicked`); a
+    /// non-critical module (overlay, analytics, ...) is quarantined and the engine keeps running.
+    fn is_critical(&self) -> bool {
+        true
+    }
+
     fn dependencies(&self) -> &'static [&'static str] {
         &[]
     }
 
+    /// Ordering constraints that are not requirements: the engine's topological sort honors
+    /// them next to `dependencies()`, but a hint naming a module that is not registered is
+    /// ignored. Hints that contradict each other or a dependency fail `start()`.
+    ///
+    /// Hints without a stage also order init and shutdown. `BeforeIn`/`AfterIn` only reorder
+    /// the calls of their stage (`FixedUpdate`, `Update` or `Render`).
+    fn order_hints(&self) -> &'static [OrderHint] {
+        &[]
+    }
+
     fn provides(&self) -> &'static [ApiProvide] {
         &[]
     }
@@ -84,6 +167,31 @@ pub trait Module<E: Send + 'static>: Send {
         Ok(())
     }
 
+    /// Called (in reverse init order) when the engine is suspended. Pause audio, stop
+    /// streaming, release transient GPU resources. While suspended only `update` runs,
+    /// at the policy's idle rate.
+    fn on_suspend(&mut self, _ctx: &mut ModuleCtx<'_, E>, _reason: SuspendReason) -> EngineResult<()> {
+        Ok(())
+    }
+
+    /// Called (in init order) when the engine resumes. Frame time restarts from zero.
+    fn on_resume(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        Ok(())
+    }
+
+    /// Called when the module is disabled with `Engine::set_module_enabled(id, false)`. No
+    /// stage callbacks run until it is enabled again, but its state is kept; stop what costs
+    /// time while idle (browser processes, audio streams, background loads).
+    fn on_pause(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        Ok(())
+    }
+
+    /// Called when a disabled module is enabled again, before its next stage callback.
+    /// (`on_resume` is the counterpart of `on_suspend`, for the whole engine.)
+    fn on_unpause(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        Ok(())
+    }
+
     #[deprecated(note = "Use Engine::emit(...) + EventHub subscriptions instead")]
     fn on_external_event(
         &mut self,
@@ -0,0 +1,45 @@
+use crate::error::ModuleStage;
+
+use std::cell::Cell;
+
+thread_local! {
+    static CURRENT_MODULE_ID: Cell<Option<&'static str>> = const { Cell::new(None) };
+    static CURRENT_STAGE: Cell<Option<ModuleStage>> = const { Cell::new(None) };
+}
+
+/// Id of the module whose lifecycle callback is currently running on this thread.
+///
+/// Used for diagnostics only (e.g. attributing render handles to their creator).
+#[inline]
+pub fn current_module_id() -> Option<&'static str> {
+    CURRENT_MODULE_ID.with(|c| c.get())
+}
+
+/// Lifecycle stage of the callback reported by [`current_module_id`].
+#[inline]
+pub fn current_module_stage() -> Option<ModuleStage> {
+    CURRENT_STAGE.with(|c| c.get())
+}
+
+/// RAII guard that marks a module as current for the duration of a callback.
+pub(crate) struct ModuleScope {
+    prev: Option<&'static str>,
+    prev_stage: Option<ModuleStage>,
+}
+
+impl ModuleScope {
+    #[inline]
+    pub(crate) fn enter(module_id: &'static str, stage: ModuleStage) -> Self {
+        let prev = CURRENT_MODULE_ID.with(|c| c.replace(Some(module_id)));
+        let prev_stage = CURRENT_STAGE.with(|c| c.replace(Some(stage)));
+        Self { prev, prev_stage }
+    }
+}
+
+impl Drop for ModuleScope {
+    #[inline]
+    fn drop(&mut self) {
+        CURRENT_MODULE_ID.with(|c| c.set(self.prev));
+        CURRENT_STAGE.with(|c| c.set(self.prev_stage));
+    }
+}
@@ -0,0 +1,142 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Toast notifications exposed through the `engine.notify` service.
+//!
+//! Toasts live in `newengine_ui::notify` and are drawn by the UI provider. The engine wires
+//! asset import failures into them and runs clicked toast actions as console commands.
+
+use crate::console::COMMAND_SERVICE_ID;
+use crate::plugins::host_api;
+use abi_stable::std_types::{RResult, RString};
+use newengine_assets::{AssetFailure, AssetStore};
+use newengine_plugin_api::{Blob, CapabilityId, MethodName, ServiceV1, ServiceV1Dyn};
+use newengine_ui::notify::{NotifyApi, ToastAction, ToastLevel};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+pub const NOTIFY_SERVICE_ID: &str = "engine.notify";
+
+pub mod method {
+    pub const TOAST: &str = "notify.toast";
+    pub const LIST_JSON: &str = "notify.list_json";
+}
+
+#[derive(Deserialize)]
+struct ToastRequest {
+    level: ToastLevel,
+    message: String,
+    #[serde(default)]
+    action: Option<ToastAction>,
+}
+
+struct NotifyService;
+
+impl ServiceV1 for NotifyService {
+    fn id(&self) -> CapabilityId {
+        RString::from(NOTIFY_SERVICE_ID)
+    }
+
+    fn describe(&self) -> RString {
+        RString::from(
+            json!({
+                "id": NOTIFY_SERVICE_ID,
+                "version": 1,
+                "permissions": [],
+                "methods": [
+                    { "name": method::TOAST, "payload": "json {level: info|warning|error, message, action?: {label, command}}", "returns": "json {ok, id?, error?}" },
+                    { "name": method::LIST_JSON, "payload": "empty", "returns": "json [{id, level, message, action?, count}]" }
+                ]
+            })
+            .to_string(),
+        )
+    }
+
+    fn call(&self, method: MethodName, payload: Blob) -> RResult<Blob, RString> {
+        match method.to_string().as_str() {
+            method::TOAST => {
+                let resp = match serde_json::from_slice::<ToastRequest>(payload.as_slice()) {
+                    Ok(r) => {
+                        let id = NotifyApi::toast(r.level, r.message, r.action);
+                        json!({ "ok": true, "id": id })
+                    }
+                    Err(e) => json!({ "ok": false, "error": format!("bad toast json: {e}") }),
+                };
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+
+            method::LIST_JSON => {
+                let list: Vec<_> = NotifyApi::active()
+                    .into_iter()
+                    .map(|t| {
+                        json!({
+                            "id": t.id,
+                            "level": t.level,
+                            "message": t.message,
+                            "action": t.action,
+                            "count": t.count,
+                        })
+                    })
+                    .collect();
+                RResult::ROk(Blob::from(json!(list).to_string().into_bytes()))
+            }
+
+            _ => RResult::RErr(RString::from("unknown method")),
+        }
+    }
+}
+
+pub fn register_notify_service() {
+    let dyn_svc: ServiceV1Dyn<'static> =
+        ServiceV1Dyn::from_value(NotifyService, abi_stable::sabi_trait::TD_Opaque);
+
+    let _ = host_api::host_register_service_impl(dyn_svc, false);
+}
+
+/// Turns import failures into error toasts with a "Retry" action.
+pub fn install_asset_failure_toasts(store: &AssetStore) {
+    store.set_failure_observer(Some(Arc::new(|f: &AssetFailure| {
+        let (what, action) = match f.logical_path.as_deref() {
+            Some(path) => (
+                format!("'{path}'"),
+                Some(ToastAction::new("Retry", format!("asset.reload {path}"))),
+            ),
+            None => (format!("asset {:032x}", f.id.to_u128()), None),
+        };
+        NotifyApi::toast(
+            ToastLevel::Error,
+            format!("Import of {what} ({}) failed: {}", f.type_id, f.error),
+            action,
+        );
+    })));
+}
+
+/// Runs console commands of toast actions clicked since the last frame.
+pub(crate) fn run_toast_actions() {
+    for a in NotifyApi::take_activated() {
+        log::info!("notify: running toast action '{}'", a.command);
+
+        let out = crate::host_services::call_service_v1(
+            COMMAND_SERVICE_ID,
+            crate::console::method::EXEC,
+            a.command.as_bytes(),
+        );
+
+        let error = match out {
+            Err(e) => Some(e),
+            Ok(bytes) => serde_json::from_slice::<serde_json::Value>(&bytes)
+                .ok()
+                .filter(|v| v.get("ok").and_then(|ok| ok.as_bool()) == Some(false))
+                .map(|v| {
+                    v.get("error")
+                        .and_then(|e| e.as_str())
+                        .unwrap_or("command failed")
+                        .to_owned()
+                }),
+        };
+
+        if let Some(e) = error {
+            NotifyApi::toast(ToastLevel::Error, format!("{}: {e}", a.label), None);
+        }
+    }
+}
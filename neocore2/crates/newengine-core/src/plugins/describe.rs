@@ -1,6 +1,7 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 use serde::Deserialize;
+use std::collections::HashMap;
 
 #[derive(Debug, Deserialize)]
 pub(crate) struct ServiceDescribe {
@@ -8,6 +9,15 @@ pub(crate) struct ServiceDescribe {
     pub kind: Option<String>,
     #[serde(default)]
     pub asset_importer: Option<AssetImporterDesc>,
+    /// Single-container importers (text) describe their provider at the top level.
+    #[serde(default)]
+    pub provider: Option<ProviderDesc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ProviderDesc {
+    #[serde(default)]
+    pub mime: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,6 +30,17 @@ pub(crate) struct AssetImporterDesc {
     pub priority: Option<i32>,
     #[serde(default)]
     pub wire: Option<String>,
+    /// Method taking `.meta` sidecar settings: `[u32 settings_len_le][settings json][bytes]`.
+    /// Without it, assets with sidecars are imported through `method` and the settings only
+    /// key the cache.
+    #[serde(default)]
+    pub settings_method: Option<String>,
+    /// Output version; bump to invalidate persistent cache entries produced by this importer.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Content type per extension, for serving sources to web views.
+    #[serde(default)]
+    pub mime_types: HashMap<String, String>,
 }
 
 #[inline]
@@ -0,0 +1,274 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Structured service descriptors.
+//!
+//! A plugin registers a [`ServiceDescriptorV1`] next to its `ServiceV1`; services without
+//! one get a descriptor derived from their describe JSON (`version`, `doc` and the
+//! `methods` array, whose entries may carry `request_schema`/`response_schema`).
+
+use crate::module::ApiVersion;
+use crate::plugins::host_context::{ctx, current_plugin_id};
+
+use newengine_plugin_api::{MethodDescriptorV1, ServiceDescriptorV1};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DescriptorSource {
+    /// Registered through `register_service_descriptor_v2` or
+    /// [`register_service_descriptor`](crate::register_service_descriptor).
+    Registered,
+    /// Derived from the service's describe JSON.
+    Describe,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MethodDescriptor {
+    pub name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub doc: String,
+    /// JSON Schema of the request payload; `None` when it is not JSON or not described.
+    pub request_schema: Option<Value>,
+    pub response_schema: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServiceDescriptor {
+    pub service_id: String,
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub doc: String,
+    pub methods: Vec<MethodDescriptor>,
+    pub source: DescriptorSource,
+}
+
+#[derive(Clone)]
+pub(crate) struct DescriptorEntry {
+    pub owner_plugin_id: Option<String>,
+    pub descriptor: ServiceDescriptor,
+}
+
+impl ServiceDescriptor {
+    /// Validates a descriptor received over the plugin ABI.
+    pub fn from_abi(d: ServiceDescriptorV1) -> Result<Self, String> {
+        let service_id = d.service_id.into_string();
+        if service_id.trim().is_empty() {
+            return Err("service descriptor: empty service id".to_string());
+        }
+        let version = match d.version.trim() {
+            "" => None,
+            v if ApiVersion::parse(v).is_some() => Some(v.to_string()),
+            v => {
+                return Err(format!(
+                    "service descriptor '{service_id}': version '{v}' is not major[.minor[.patch]]"
+                ))
+            }
+        };
+
+        let mut seen = HashSet::new();
+        let mut methods = Vec::with_capacity(d.methods.len());
+        for m in d.methods.into_iter() {
+            let m = method_from_abi(&service_id, m)?;
+            if !seen.insert(m.name.clone()) {
+                return Err(format!(
+                    "service descriptor '{service_id}': method '{}' listed twice",
+                    m.name
+                ));
+            }
+            methods.push(m);
+        }
+
+        Ok(Self {
+            service_id,
+            version,
+            doc: d.doc.into_string(),
+            methods,
+            source: DescriptorSource::Registered,
+        })
+    }
+
+    /// Best-effort descriptor from a describe JSON; unknown keys are ignored.
+    pub fn from_describe(service_id: &str, describe_json: &str) -> Self {
+        let v = serde_json::from_str::<Value>(describe_json).unwrap_or(Value::Null);
+        let text = |v: &Value, keys: &[&str]| {
+            keys.iter()
+                .find_map(|k| v.get(*k).and_then(Value::as_str))
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let methods = v
+            .get("methods")
+            .and_then(Value::as_array)
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|m| {
+                        let name = m.get("name")?.as_str()?.to_string();
+                        let mut doc = text(m, &["doc", "help"]);
+                        // Free-form payload notes stand in for a schema.
+                        for key in ["payload", "returns"] {
+                            if let Some(note) = m.get(key).and_then(Value::as_str) {
+                                if !doc.is_empty() {
+                                    doc.push_str("; ");
+                                }
+                                let _ = write!(doc, "{key}: {note}");
+                            }
+                        }
+                        Some(MethodDescriptor {
+                            name,
+                            doc,
+                            request_schema: m.get("request_schema").filter(is_schema).cloned(),
+                            response_schema: m.get("response_schema").filter(is_schema).cloned(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            service_id: service_id.to_string(),
+            version: v.get("version").and_then(Value::as_str).map(str::to_string),
+            doc: text(&v, &["doc", "description"]),
+            methods,
+            source: DescriptorSource::Describe,
+        }
+    }
+
+    #[inline]
+    pub fn method(&self, name: &str) -> Option<&MethodDescriptor> {
+        self.methods.iter().find(|m| m.name == name)
+    }
+
+    /// Multi-line listing for the console.
+    pub fn pretty(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(out, "{}", self.service_id);
+        if let Some(v) = self.version.as_deref() {
+            let _ = write!(out, " v{v}");
+        }
+        if self.source == DescriptorSource::Describe {
+            out.push_str("  (from describe json)");
+        }
+        out.push('\n');
+        if !self.doc.is_empty() {
+            let _ = writeln!(out, "  {}", self.doc);
+        }
+
+        if self.methods.is_empty() {
+            out.push_str("  no methods described");
+            return out;
+        }
+        for m in self.methods.iter() {
+            let _ = write!(out, "\n  {}", m.name);
+            if !m.doc.is_empty() {
+                let _ = write!(out, "  - {}", m.doc);
+            }
+            out.push('\n');
+            write_schema(&mut out, "request", m.request_schema.as_ref());
+            write_schema(&mut out, "response", m.response_schema.as_ref());
+        }
+        out.trim_end().to_string()
+    }
+}
+
+fn method_from_abi(service_id: &str, m: MethodDescriptorV1) -> Result<MethodDescriptor, String> {
+    let name = m.name.into_string();
+    if name.trim().is_empty() {
+        return Err(format!(
+            "service descriptor '{service_id}': method with an empty name"
+        ));
+    }
+    let schema = |which: &str, text: &str| -> Result<Option<Value>, String> {
+        if text.trim().is_empty() {
+            return Ok(None);
+        }
+        let v = serde_json::from_str::<Value>(text).map_err(|e| {
+            format!("service descriptor '{service_id}': {name}.{which}_schema is not JSON: {e}")
+        })?;
+        if !is_schema(&&v) {
+            return Err(format!(
+                "service descriptor '{service_id}': {name}.{which}_schema must be a JSON \
+                 Schema object or boolean"
+            ));
+        }
+        Ok(Some(v))
+    };
+    Ok(MethodDescriptor {
+        request_schema: schema("request", m.request_schema.as_str())?,
+        response_schema: schema("response", m.response_schema.as_str())?,
+        doc: m.doc.into_string(),
+        name,
+    })
+}
+
+#[inline]
+fn is_schema(v: &&Value) -> bool {
+    v.is_object() || v.is_boolean()
+}
+
+fn write_schema(out: &mut String, label: &str, schema: Option<&Value>) {
+    let Some(schema) = schema else {
+        return;
+    };
+    let text = serde_json::to_string_pretty(schema).unwrap_or_default();
+    let _ = write!(out, "    {label}: ");
+    for (i, line) in text.lines().enumerate() {
+        if i > 0 {
+            out.push_str("\n      ");
+        }
+        out.push_str(line);
+    }
+    out.push('\n');
+}
+
+/// Stores `descriptor` for the current plugin. Fails when another plugin owns the service.
+pub(crate) fn register_descriptor(descriptor: ServiceDescriptor) -> Result<(), String> {
+    let owner = current_plugin_id();
+    let c = ctx();
+
+    if let Ok(services) = c.services.lock() {
+        if let Some(svc) = services.get(&descriptor.service_id) {
+            if svc.owner_plugin_id != owner {
+                return Err(format!(
+                    "service '{}' belongs to {}; only its owner can describe it",
+                    descriptor.service_id,
+                    svc.owner_plugin_id.as_deref().unwrap_or("the engine")
+                ));
+            }
+        }
+    }
+
+    let mut g = c
+        .descriptors
+        .lock()
+        .map_err(|_| "descriptors mutex poisoned".to_string())?;
+    g.insert(
+        descriptor.service_id.clone(),
+        DescriptorEntry {
+            owner_plugin_id: owner,
+            descriptor,
+        },
+    );
+    Ok(())
+}
+
+/// Registered descriptor of `service_id` if its owner registered one, else the one derived
+/// from the describe JSON. `None` for unknown services.
+pub(crate) fn lookup_descriptor(service_id: &str) -> Option<ServiceDescriptor> {
+    let c = ctx();
+    let (owner, describe_json) = {
+        let g = c.services.lock().ok()?;
+        let e = g.get(service_id)?;
+        (e.owner_plugin_id.clone(), e.describe_json.clone())
+    };
+
+    let registered = c.descriptors.lock().ok().and_then(|g| {
+        g.get(service_id)
+            .filter(|d| d.owner_plugin_id == owner)
+            .map(|d| d.descriptor.clone())
+    });
+    Some(registered.unwrap_or_else(|| ServiceDescriptor::from_describe(service_id, &describe_json)))
+}
@@ -0,0 +1,188 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Bridge between the engine's [`EventHub`] and plugin events.
+//!
+//! Engine to plugins: [`EventBridge::export`] forwards every `T` published on the hub to the
+//! plugin event sinks, JSON-encoded, under a topic in the reserved `engine.` namespace
+//! (window events are always forwarded, see [`newengine_plugin_api::schemas`]).
+//!
+//! Plugins to engine: every event a plugin emits is published on the hub as a
+//! [`PluginEvent`] after the plugins' `update`. [`EventBridge::import`] also decodes the JSON
+//! payload of one topic into `T` and publishes that. Plugin events in the `engine.`
+//! namespace are delivered to other plugins but never reach the hub.
+
+use crate::events::{EventHub, EventSub};
+use crate::plugins::host_context::{ctx, current_plugin_id, emit_typed_plugin_event};
+
+use abi_stable::std_types::RString;
+use newengine_plugin_api::{schemas, Blob};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Plugin events waiting for the engine; further events are dropped until it catches up.
+const MAX_QUEUED_EVENTS: usize = 4096;
+
+static QUEUE_FULL_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Event emitted by a plugin, as published on the engine's [`EventHub`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginEvent {
+    /// Emitting plugin; `None` when it was emitted outside a plugin callback.
+    pub plugin_id: Option<String>,
+    pub topic: String,
+    /// Schema id from `emit_event_v2`; [`schemas::UNTYPED`] for `emit_event_v1`.
+    pub schema: String,
+    pub payload: Vec<u8>,
+}
+
+impl PluginEvent {
+    /// Decodes the payload as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, String> {
+        serde_json::from_slice(&self.payload).map_err(|e| {
+            format!(
+                "plugin event '{}': payload is not the expected JSON: {e}",
+                self.topic
+            )
+        })
+    }
+}
+
+type Export = Box<dyn Fn() + Send>;
+type Import = Box<dyn Fn(&PluginEvent, &EventHub) -> Result<(), String> + Send>;
+
+/// Topic mappings of one engine; see the module docs.
+#[derive(Default)]
+pub struct EventBridge {
+    exports: Vec<Export>,
+    imports: Vec<(String, Import)>,
+}
+
+impl EventBridge {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forwards every `T` published on `hub` to plugins as `topic`, tagged with `schema`.
+    /// `topic` must start with [`schemas::ENGINE_TOPIC_PREFIX`].
+    pub fn export<T>(&mut self, hub: &EventHub, topic: &str, schema: &str) -> Result<(), String>
+    where
+        T: Serialize + Any + Send + Sync + 'static,
+    {
+        let name = topic.strip_prefix(schemas::ENGINE_TOPIC_PREFIX);
+        if name.unwrap_or_default().is_empty() {
+            return Err(format!(
+                "event bridge: exported topic '{topic}' must be '{}<name>'",
+                schemas::ENGINE_TOPIC_PREFIX
+            ));
+        }
+
+        let sub: EventSub<T> = hub.subscribe();
+        let topic = topic.to_string();
+        let schema = schema.to_string();
+        self.exports.push(Box::new(move || {
+            sub.drain(|ev| {
+                let bytes = match serde_json::to_vec(&*ev) {
+                    Ok(b) => b,
+                    Err(e) => {
+                        log::warn!("plugins: event '{}' not serializable: {}", topic, e);
+                        return;
+                    }
+                };
+                if let Err(e) = emit_typed_plugin_event(
+                    RString::from(topic.as_str()),
+                    &schema,
+                    Blob::from(bytes),
+                ) {
+                    log::warn!("plugins: event '{}' not delivered: {}", topic, e);
+                }
+            });
+        }));
+        Ok(())
+    }
+
+    /// Publishes the JSON payload of every plugin event on `topic` as a `T`, next to its
+    /// [`PluginEvent`]. Events whose payload does not decode are logged and skipped.
+    pub fn import<T>(&mut self, topic: &str) -> Result<(), String>
+    where
+        T: DeserializeOwned + Any + Send + Sync + 'static,
+    {
+        if topic.is_empty() || topic.starts_with(schemas::ENGINE_TOPIC_PREFIX) {
+            return Err(format!(
+                "event bridge: imported topic '{topic}' must be non-empty and outside '{}'",
+                schemas::ENGINE_TOPIC_PREFIX
+            ));
+        }
+
+        self.imports.push((
+            topic.to_string(),
+            Box::new(|ev: &PluginEvent, hub: &EventHub| {
+                let value = ev.json::<T>()?;
+                hub.publish(value).map_err(|e| e.to_string())
+            }),
+        ));
+        Ok(())
+    }
+
+    /// Hands the exported hub events of the last frame to plugins.
+    pub(crate) fn export_pending(&self) {
+        for export in self.exports.iter() {
+            export();
+        }
+    }
+
+    /// Publishes the plugin events emitted since the last call on `hub`.
+    pub(crate) fn import_pending(&self, hub: &EventHub) {
+        for ev in take_plugin_events() {
+            for (topic, import) in self.imports.iter() {
+                if *topic != ev.topic {
+                    continue;
+                }
+                if let Err(e) = import(&ev, hub) {
+                    log::warn!(
+                        "plugins: event '{}' from '{}' not imported: {}",
+                        ev.topic,
+                        ev.plugin_id.as_deref().unwrap_or("?"),
+                        e
+                    );
+                }
+            }
+            let _ = hub.publish(ev);
+        }
+    }
+}
+
+/// Queues an event a plugin emitted for the engine's hub.
+pub(crate) fn queue_plugin_event(topic: &str, schema: &str, payload: &[u8]) {
+    if topic.starts_with(schemas::ENGINE_TOPIC_PREFIX) {
+        return;
+    }
+    let Ok(mut g) = ctx().plugin_events.lock() else {
+        return;
+    };
+    if g.len() >= MAX_QUEUED_EVENTS {
+        if !QUEUE_FULL_WARNED.swap(true, Ordering::Relaxed) {
+            log::warn!(
+                "plugins: {} plugin events queued, dropping new ones until the engine runs a frame",
+                MAX_QUEUED_EVENTS
+            );
+        }
+        return;
+    }
+    g.push(PluginEvent {
+        plugin_id: current_plugin_id(),
+        topic: topic.to_string(),
+        schema: schema.to_string(),
+        payload: payload.to_vec(),
+    });
+}
+
+fn take_plugin_events() -> Vec<PluginEvent> {
+    let Ok(mut g) = ctx().plugin_events.lock() else {
+        return Vec::new();
+    };
+    QUEUE_FULL_WARNED.store(false, Ordering::Relaxed);
+    std::mem::take(&mut *g)
+}
@@ -0,0 +1,59 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::plugins::host_context::ctx;
+
+/// Host call into a plugin that faulted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginCallSite {
+    Start,
+    FixedUpdate,
+    Update,
+    Render,
+    /// `call_service_v1` into a service the plugin registered.
+    Service,
+    /// Asset import through a plugin importer service.
+    Importer,
+}
+
+impl PluginCallSite {
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PluginCallSite::Start => "start",
+            PluginCallSite::FixedUpdate => "fixed_update",
+            PluginCallSite::Update => "update",
+            PluginCallSite::Render => "render",
+            PluginCallSite::Service => "service",
+            PluginCallSite::Importer => "importer",
+        }
+    }
+}
+
+/// Published on the `EventHub` when a plugin panics or fails at the ABI boundary. The plugin
+/// is disabled: it gets no further calls and its services, sinks and commands are removed.
+#[derive(Debug, Clone)]
+pub struct PluginFault {
+    pub plugin_id: String,
+    pub site: PluginCallSite,
+    /// `service_id::method` for service and importer faults.
+    pub target: Option<String>,
+    pub message: String,
+    /// `false` when the plugin returned an error instead of panicking.
+    pub panicked: bool,
+}
+
+/// Queues a fault raised outside the [`PluginManager`](super::PluginManager) (service calls,
+/// possibly on an import worker) for it to disable the plugin on its next tick.
+pub(crate) fn report_fault(fault: PluginFault) {
+    if let Ok(mut g) = ctx().faults.lock() {
+        g.push(fault);
+    }
+}
+
+#[inline]
+pub(crate) fn take_reported_faults() -> Vec<PluginFault> {
+    match ctx().faults.lock() {
+        Ok(mut g) => std::mem::take(&mut *g),
+        Err(_) => Vec::new(),
+    }
+}
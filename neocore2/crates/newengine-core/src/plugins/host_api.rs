@@ -1,15 +1,27 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+use crate::module::panic_message;
 use crate::plugins::describe::is_asset_importer;
-use crate::plugins::host_context::{ctx, ServiceEntry};
+use crate::plugins::fault::{report_fault, PluginCallSite, PluginFault};
+use crate::plugins::host_api_v2::host_api_v2;
+use crate::plugins::host_context::{
+    ctx, emit_event_from_plugin, unregister_by_owner, ServiceEntry,
+};
+use crate::plugins::permissions::{
+    check_current, check_service_call, required_by_describe, PluginPermission,
+    PluginPermissions,
+};
 #[cfg(feature = "runtime")]
 use crate::plugins::importer::try_auto_register_importer;
 use abi_stable::std_types::{RResult, RString};
 use newengine_plugin_api::{
-    Blob, CapabilityId, EventSinkV1Dyn, HostApiV1, MethodName, ServiceV1Dyn,
+    schemas, Blob, CapabilityId, ConsoleCommandV1Dyn, EventSinkV1Dyn, HostApiV1, MethodName,
+    ServiceV1Dyn,
 };
 use std::cell::Cell;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::sync::Arc;
+use std::time::Instant;
 
 pub(crate) struct ImporterLoadState {
     pub saw_importer: bool,
@@ -53,6 +65,14 @@ pub(crate) fn host_register_service_impl(
     let describe_json = svc.describe().to_string();
     let owner = crate::plugins::host_context::current_plugin_id();
 
+    if is_asset_importer(&describe_json) {
+        let need = PluginPermissions::NONE.with(PluginPermission::Importer);
+        if let Err(e) = check_current(need, &format!("importer '{service_id}'")) {
+            return RResult::RErr(RString::from(e));
+        }
+    }
+    let required_permissions = required_by_describe(&service_id, &describe_json);
+
     let c = ctx();
 
     {
@@ -74,6 +94,7 @@ pub(crate) fn host_register_service_impl(
                 owner_plugin_id: owner,
                 service: Arc::from(svc),
                 describe_json: describe_json.clone(),
+                required_permissions,
             },
         );
         crate::plugins::host_context::bump_services_generation();
@@ -118,27 +139,101 @@ pub(crate) extern "C" fn call_service_v1(
     cap_id: CapabilityId,
     method: MethodName,
     payload: Blob,
+) -> RResult<Blob, RString> {
+    call_service_at(PluginCallSite::Service, cap_id, method, payload)
+}
+
+/// `call_service_v1` with the call site reported if the service's plugin panics. A panic
+/// becomes an error for the caller; the owning plugin's services are removed at once and the
+/// `PluginManager` disables the plugin on its next tick.
+pub(crate) fn call_service_at(
+    site: PluginCallSite,
+    cap_id: CapabilityId,
+    method: MethodName,
+    payload: Blob,
 ) -> RResult<Blob, RString> {
     let id = cap_id.to_string();
     let c = ctx();
 
-    let svc = {
+    let (svc, owner, required) = {
         let g = match c.services.lock() {
             Ok(v) => v,
             Err(_) => return RResult::RErr(RString::from("services mutex poisoned")),
         };
 
         match g.get(&id) {
-            Some(v) => v.service.clone(),
+            Some(v) => (
+                v.service.clone(),
+                v.owner_plugin_id.clone(),
+                v.required_permissions,
+            ),
             None => return RResult::RErr(RString::from(format!("service not found: {id}"))),
         }
     };
 
-    svc.call(method, payload)
+    if let Err(e) = check_service_call(required, &id, owner.as_deref()) {
+        return RResult::RErr(RString::from(e));
+    }
+
+    let caller = crate::plugins::host_context::current_plugin_id();
+    if let Ok(mut m) = c.service_metrics.lock() {
+        if let Err(e) = m.admit(caller.as_deref(), &id, Instant::now()) {
+            log::warn!("services: {}", e);
+            return RResult::RErr(RString::from(e));
+        }
+    }
+
+    let t0 = Instant::now();
+    let method_name = method.clone();
+    let out = match catch_unwind(AssertUnwindSafe(|| svc.call(method, payload))) {
+        Ok(out) => out,
+        Err(p) => service_panicked(site, &id, &method_name, owner, panic_message(p.as_ref())),
+    };
+    let failed = matches!(out, RResult::RErr(_));
+
+    if let Ok(mut m) = c.service_metrics.lock() {
+        m.record(&id, t0.elapsed(), failed);
+    }
+
+    out
+}
+
+#[cold]
+fn service_panicked(
+    site: PluginCallSite,
+    service_id: &str,
+    method: &str,
+    owner: Option<String>,
+    message: String,
+) -> RResult<Blob, RString> {
+    let target = format!("{service_id}::{method}");
+    log::error!(
+        "plugins: panic in {} call target='{}' owner='{}': {}",
+        site.as_str(),
+        target,
+        owner.as_deref().unwrap_or("host"),
+        message
+    );
+
+    // Host services have no plugin to disable; the caller still gets an error.
+    if let Some(plugin_id) = owner {
+        unregister_by_owner(&plugin_id);
+        report_fault(PluginFault {
+            plugin_id,
+            site,
+            target: Some(target.clone()),
+            message: message.clone(),
+            panicked: true,
+        });
+    }
+
+    RResult::RErr(RString::from(format!(
+        "service '{target}' panicked: {message}"
+    )))
 }
 
 extern "C" fn host_emit_event_v1(topic: RString, payload: Blob) -> RResult<(), RString> {
-    match crate::plugins::host_context::emit_plugin_event(topic, payload) {
+    match emit_event_from_plugin(topic, schemas::UNTYPED, payload) {
         Ok(()) => RResult::ROk(()),
         Err(e) => RResult::RErr(RString::from(e)),
     }
@@ -151,6 +246,22 @@ extern "C" fn host_subscribe_events_v1(sink: EventSinkV1Dyn<'static>) -> RResult
     }
 }
 
+extern "C" fn host_register_command_v1(
+    name: RString,
+    help: RString,
+    usage: RString,
+    cmd: ConsoleCommandV1Dyn<'static>,
+) -> RResult<(), RString> {
+    let handler = move |args: &str| match cmd.exec(RString::from(args)) {
+        RResult::ROk(v) => Ok(v.into_string()),
+        RResult::RErr(e) => Err(e.into_string()),
+    };
+    match crate::console::registry::register(&name, &help, &usage, Arc::new(handler)) {
+        Ok(()) => RResult::ROk(()),
+        Err(e) => RResult::RErr(RString::from(e)),
+    }
+}
+
 pub fn default_host_api() -> HostApiV1 {
     HostApiV1 {
         log_info: host_log_info,
@@ -162,6 +273,10 @@ pub fn default_host_api() -> HostApiV1 {
 
         emit_event_v1: host_emit_event_v1,
         subscribe_events_v1: host_subscribe_events_v1,
+
+        register_command_v1: host_register_command_v1,
+
+        host_api_v2,
     }
 }
 
@@ -176,5 +291,9 @@ pub fn importers_host_api() -> HostApiV1 {
 
         emit_event_v1: host_emit_event_v1,
         subscribe_events_v1: host_subscribe_events_v1,
+
+        register_command_v1: host_register_command_v1,
+
+        host_api_v2,
     }
 }
\ No newline at end of file
@@ -0,0 +1,174 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! `HostApiV2`: typed events, engine state and service descriptors for plugins.
+//!
+//! The engine feeds it once per frame ([`publish_frame_timing`]) and for every window
+//! [`HostEvent`] ([`forward_host_event`]), which also becomes a typed event under the topics
+//! in [`newengine_plugin_api::schemas`].
+
+use crate::frame::Frame;
+use crate::host_events::{HostEvent, WindowHostEvent};
+use crate::plugins::descriptor::{register_descriptor, ServiceDescriptor};
+use crate::plugins::host_context::{
+    emit_event_from_plugin, emit_typed_plugin_event, subscribe_typed_event_sink,
+};
+
+use abi_stable::prefix_type::PrefixTypeTrait;
+use abi_stable::std_types::{RResult, RString};
+use newengine_plugin_api::{
+    schemas, Blob, FrameTimingV2, HostApiV2, HostApiV2Ref, ServiceDescriptorV1,
+    TypedEventSinkV2Dyn, WindowStateV2,
+};
+use std::sync::{OnceLock, RwLock};
+
+static WINDOW_STATE: RwLock<WindowStateV2> = RwLock::new(WindowStateV2 {
+    width: 0,
+    height: 0,
+    scale_factor: 1.0,
+    focused: false,
+});
+
+static FRAME_TIMING: RwLock<FrameTimingV2> = RwLock::new(FrameTimingV2 {
+    frame_index: 0,
+    dt: 0.0,
+    real_dt: 0.0,
+    fixed_dt: 0.0,
+    time_scale: 1.0,
+    paused: false,
+});
+
+extern "C" fn host_emit_event_v2(
+    topic: RString,
+    schema: RString,
+    payload: Blob,
+) -> RResult<(), RString> {
+    match emit_event_from_plugin(topic, schema.as_str(), payload) {
+        Ok(()) => RResult::ROk(()),
+        Err(e) => RResult::RErr(RString::from(e)),
+    }
+}
+
+extern "C" fn host_subscribe_events_v2(
+    topic_prefix: RString,
+    sink: TypedEventSinkV2Dyn<'static>,
+) -> RResult<(), RString> {
+    match subscribe_typed_event_sink(topic_prefix.into_string(), sink) {
+        Ok(()) => RResult::ROk(()),
+        Err(e) => RResult::RErr(RString::from(e)),
+    }
+}
+
+extern "C" fn host_window_state_v2() -> WindowStateV2 {
+    WINDOW_STATE.read().map(|g| *g).unwrap_or_default()
+}
+
+extern "C" fn host_frame_timing_v2() -> FrameTimingV2 {
+    FRAME_TIMING.read().map(|g| *g).unwrap_or_default()
+}
+
+extern "C" fn host_register_service_descriptor_v2(
+    descriptor: ServiceDescriptorV1,
+) -> RResult<(), RString> {
+    match ServiceDescriptor::from_abi(descriptor).and_then(register_descriptor) {
+        Ok(()) => RResult::ROk(()),
+        Err(e) => RResult::RErr(RString::from(e)),
+    }
+}
+
+pub(crate) extern "C" fn host_api_v2() -> HostApiV2Ref {
+    static API: OnceLock<HostApiV2Ref> = OnceLock::new();
+    *API.get_or_init(|| {
+        HostApiV2 {
+            emit_event_v2: host_emit_event_v2,
+            subscribe_events_v2: host_subscribe_events_v2,
+            window_state_v2: host_window_state_v2,
+            frame_timing_v2: host_frame_timing_v2,
+            register_service_descriptor_v2: host_register_service_descriptor_v2,
+        }
+        .leak_into_prefix()
+    })
+}
+
+/// Records the timing of the variable frame about to run `update`.
+pub(crate) fn publish_frame_timing(frame: &Frame, time_scale: f32, paused: bool) {
+    if let Ok(mut g) = FRAME_TIMING.write() {
+        *g = FrameTimingV2 {
+            frame_index: frame.frame_index,
+            dt: frame.dt,
+            real_dt: frame.real_dt,
+            fixed_dt: frame.fixed_dt,
+            time_scale,
+            paused,
+        };
+    }
+}
+
+/// Updates the window state from a main-window event and emits its typed event.
+pub(crate) fn forward_host_event(event: &HostEvent) {
+    let HostEvent::Window(ev) = event else {
+        return;
+    };
+
+    let (topic, schema, payload) = {
+        let Ok(mut w) = WINDOW_STATE.write() else {
+            return;
+        };
+        match *ev {
+            WindowHostEvent::Ready { width, height }
+            | WindowHostEvent::Resized { width, height } => {
+                w.width = width;
+                w.height = height;
+                (
+                    schemas::WINDOW_RESIZED_TOPIC,
+                    schemas::WINDOW_RESIZED_V1,
+                    serde_json::json!({ "width": width, "height": height }),
+                )
+            }
+            WindowHostEvent::Focused(focused) => {
+                w.focused = focused;
+                (
+                    schemas::WINDOW_FOCUS_TOPIC,
+                    schemas::WINDOW_FOCUS_V1,
+                    serde_json::json!({ "focused": focused }),
+                )
+            }
+            WindowHostEvent::ScaleFactorChanged {
+                window: None,
+                scale_factor,
+                width,
+                height,
+            } => {
+                w.scale_factor = scale_factor;
+                w.width = width;
+                w.height = height;
+                (
+                    schemas::WINDOW_SCALE_FACTOR_TOPIC,
+                    schemas::WINDOW_SCALE_FACTOR_V1,
+                    serde_json::json!({
+                        "scale_factor": scale_factor,
+                        "width": width,
+                        "height": height
+                    }),
+                )
+            }
+            WindowHostEvent::Suspended => (
+                schemas::LIFECYCLE_TOPIC,
+                schemas::LIFECYCLE_V1,
+                serde_json::json!({ "state": "suspended" }),
+            ),
+            WindowHostEvent::Resumed => (
+                schemas::LIFECYCLE_TOPIC,
+                schemas::LIFECYCLE_V1,
+                serde_json::json!({ "state": "resumed" }),
+            ),
+            _ => return,
+        }
+    };
+
+    let Ok(bytes) = serde_json::to_vec(&payload) else {
+        return;
+    };
+    if let Err(e) = emit_typed_plugin_event(RString::from(topic), schema, Blob::from(bytes)) {
+        log::warn!("plugins: typed event '{}' not delivered: {}", topic, e);
+    }
+}
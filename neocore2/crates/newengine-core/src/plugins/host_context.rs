@@ -3,18 +3,27 @@
 use abi_stable::std_types::RString;
 #[cfg(feature = "runtime")]
 use newengine_assets::AssetStore;
-use newengine_plugin_api::{Blob, EventSinkV1Dyn, ServiceV1Dyn};
+use newengine_plugin_api::{schemas, Blob, EventSinkV1Dyn, ServiceV1Dyn, TypedEventSinkV2Dyn};
+
+use crate::plugins::descriptor::DescriptorEntry;
+use crate::plugins::event_bridge::{queue_plugin_event, PluginEvent};
+use crate::plugins::fault::PluginFault;
+use crate::plugins::permissions::{PermissionTable, PluginPermissions};
+use crate::plugins::service_metrics::ServiceMetrics;
 
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 
 #[derive(Clone)]
 pub struct ServiceEntry {
     pub owner_plugin_id: Option<String>,
     pub service: Arc<ServiceV1Dyn<'static>>,
     pub describe_json: String,
+    /// Caller permissions from the describe JSON, checked on every call. `None` when the
+    /// service declares none; sandboxed plugins cannot call it then.
+    pub required_permissions: Option<PluginPermissions>,
 }
 
 #[derive(Clone)]
@@ -23,6 +32,14 @@ pub struct EventSinkEntry {
     pub sink: Arc<Mutex<EventSinkV1Dyn<'static>>>,
 }
 
+#[derive(Clone)]
+pub struct TypedEventSinkEntry {
+    pub owner_plugin_id: Option<String>,
+    /// Only topics starting with it are delivered; empty matches all.
+    pub topic_prefix: String,
+    pub sink: Arc<Mutex<TypedEventSinkV2Dyn<'static>>>,
+}
+
 thread_local! {
     static CURRENT_PLUGIN_ID: RefCell<Option<String>> = const { RefCell::new(None) };
 }
@@ -41,13 +58,28 @@ pub(crate) fn current_plugin_id() -> Option<String> {
     CURRENT_PLUGIN_ID.with(|slot| slot.borrow().clone())
 }
 
+/// Like [`current_plugin_id`] without the allocation; `f` must not enter another plugin.
+#[inline]
+pub(crate) fn peek_current_plugin_id<R>(f: impl FnOnce(Option<&str>) -> R) -> R {
+    CURRENT_PLUGIN_ID.with(|slot| f(slot.borrow().as_deref()))
+}
+
 pub struct HostContext {
     pub services: Mutex<HashMap<String, ServiceEntry>>,
     #[cfg(feature = "runtime")]
     pub(crate) asset_store: Arc<AssetStore>,
     services_generation: AtomicU64,
+    pub(crate) service_metrics: Mutex<ServiceMetrics>,
+    /// Registered service descriptors by service id.
+    pub(crate) descriptors: Mutex<HashMap<String, DescriptorEntry>>,
 
     pub(crate) event_sinks: Mutex<Vec<EventSinkEntry>>,
+    pub(crate) typed_event_sinks: Mutex<Vec<TypedEventSinkEntry>>,
+    /// Events emitted by plugins, waiting for the engine to publish them on its `EventHub`.
+    pub(crate) plugin_events: Mutex<Vec<PluginEvent>>,
+    /// Faults from service calls, waiting for the `PluginManager` to disable their plugins.
+    pub(crate) faults: Mutex<Vec<PluginFault>>,
+    pub(crate) permissions: RwLock<PermissionTable>,
 }
 
 static HOST_CTX: OnceLock<Arc<HostContext>> = OnceLock::new();
@@ -58,7 +90,13 @@ pub fn init_host_context(asset_store: Arc<AssetStore>) {
         services: Mutex::new(HashMap::new()),
         asset_store,
         services_generation: AtomicU64::new(1),
+        service_metrics: Mutex::new(ServiceMetrics::default()),
+        descriptors: Mutex::new(HashMap::new()),
         event_sinks: Mutex::new(Vec::new()),
+        typed_event_sinks: Mutex::new(Vec::new()),
+        plugin_events: Mutex::new(Vec::new()),
+        faults: Mutex::new(Vec::new()),
+        permissions: RwLock::new(PermissionTable::default()),
     });
     let _ = HOST_CTX.set(ctx);
 }
@@ -68,7 +106,13 @@ pub fn init_host_context() {
     let ctx = Arc::new(HostContext {
         services: Mutex::new(HashMap::new()),
         services_generation: AtomicU64::new(1),
+        service_metrics: Mutex::new(ServiceMetrics::default()),
+        descriptors: Mutex::new(HashMap::new()),
         event_sinks: Mutex::new(Vec::new()),
+        typed_event_sinks: Mutex::new(Vec::new()),
+        plugin_events: Mutex::new(Vec::new()),
+        faults: Mutex::new(Vec::new()),
+        permissions: RwLock::new(PermissionTable::default()),
     });
     let _ = HOST_CTX.set(ctx);
 }
@@ -101,7 +145,79 @@ pub fn subscribe_event_sink(sink: EventSinkV1Dyn<'static>) -> Result<(), String>
     Ok(())
 }
 
+pub fn subscribe_typed_event_sink(
+    topic_prefix: String,
+    sink: TypedEventSinkV2Dyn<'static>,
+) -> Result<(), String> {
+    let c = ctx();
+    let mut g = c
+        .typed_event_sinks
+        .lock()
+        .map_err(|_| "typed_event_sinks mutex poisoned".to_string())?;
+    g.push(TypedEventSinkEntry {
+        owner_plugin_id: current_plugin_id(),
+        topic_prefix,
+        sink: Arc::new(Mutex::new(sink)),
+    });
+    Ok(())
+}
+
+/// Sees every plugin event before the sinks do; returning `false` drops it. Replay uses it
+/// to record input and to keep live input out of a playback.
+pub type PluginEventFeed = Arc<dyn Fn(&str, &[u8]) -> bool + Send + Sync>;
+
+static EVENT_FEED: RwLock<Option<PluginEventFeed>> = RwLock::new(None);
+
+/// Installs (or with `None` removes) the process-wide [`PluginEventFeed`].
+pub fn set_plugin_event_feed(feed: Option<PluginEventFeed>) {
+    if let Ok(mut g) = EVENT_FEED.write() {
+        *g = feed;
+    }
+}
+
 pub fn emit_plugin_event(topic: RString, payload: Blob) -> Result<(), String> {
+    emit_typed_plugin_event(topic, schemas::UNTYPED, payload)
+}
+
+/// Emits an event tagged with a schema id; untyped sinks receive it without the schema.
+pub fn emit_typed_plugin_event(topic: RString, schema: &str, payload: Blob) -> Result<(), String> {
+    if !passes_feed(&topic, &payload) {
+        return Ok(());
+    }
+    deliver_typed_plugin_event(topic, schema, payload)
+}
+
+/// Emits an event raised by a plugin: besides the sinks, the engine publishes it on its
+/// `EventHub` as a [`PluginEvent`].
+pub(crate) fn emit_event_from_plugin(
+    topic: RString,
+    schema: &str,
+    payload: Blob,
+) -> Result<(), String> {
+    if !passes_feed(&topic, &payload) {
+        return Ok(());
+    }
+    queue_plugin_event(topic.as_str(), schema, payload.as_slice());
+    deliver_typed_plugin_event(topic, schema, payload)
+}
+
+fn passes_feed(topic: &RString, payload: &Blob) -> bool {
+    match EVENT_FEED.read().ok().and_then(|g| g.clone()) {
+        Some(feed) => feed(topic.as_str(), payload.as_slice()),
+        None => true,
+    }
+}
+
+/// Sends an event to the sinks without passing it through the [`PluginEventFeed`].
+pub(crate) fn deliver_plugin_event(topic: RString, payload: Blob) -> Result<(), String> {
+    deliver_typed_plugin_event(topic, schemas::UNTYPED, payload)
+}
+
+fn deliver_typed_plugin_event(topic: RString, schema: &str, payload: Blob) -> Result<(), String> {
+    crate::trace::record(crate::trace::TraceKind::Event, topic.to_string(), || {
+        String::from_utf8_lossy(payload.as_slice()).into_owned()
+    });
+
     let c = ctx();
     let sinks = {
         let g = c
@@ -119,6 +235,25 @@ pub fn emit_plugin_event(topic: RString, payload: Blob) -> Result<(), String> {
         guard.on_event(topic.clone(), payload.clone());
     }
 
+    let typed = {
+        let g = c
+            .typed_event_sinks
+            .lock()
+            .map_err(|_| "typed_event_sinks mutex poisoned".to_string())?;
+        g.iter()
+            .filter(|s| topic.as_str().starts_with(s.topic_prefix.as_str()))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    for s in typed {
+        let mut guard = s
+            .sink
+            .lock()
+            .map_err(|_| "typed event sink mutex poisoned".to_string())?;
+        guard.on_event(topic.clone(), RString::from(schema), payload.clone());
+    }
+
     Ok(())
 }
 
@@ -137,6 +272,10 @@ pub fn unregister_by_owner(plugin_id: &str) {
         }
     }
 
+    if let Ok(mut g) = c.descriptors.lock() {
+        g.retain(|_, e| e.owner_plugin_id.as_deref() != Some(plugin_id));
+    }
+
     {
         let mut g = match c.event_sinks.lock() {
             Ok(v) => v,
@@ -144,4 +283,18 @@ pub fn unregister_by_owner(plugin_id: &str) {
         };
         g.retain(|e| e.owner_plugin_id.as_deref() != Some(plugin_id));
     }
+
+    {
+        let mut g = match c.typed_event_sinks.lock() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        g.retain(|e| e.owner_plugin_id.as_deref() != Some(plugin_id));
+    }
+
+    if let Ok(mut m) = c.service_metrics.lock() {
+        m.forget_caller(plugin_id);
+    }
+
+    crate::console::registry::unregister_by_owner(plugin_id);
 }
\ No newline at end of file
@@ -1,31 +1,65 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 use abi_stable::std_types::{RResult, RString};
-use newengine_assets::{AssetBlob, AssetError, AssetKey, BlobImporterDispatch, ImporterPriority};
+use newengine_assets::{
+    AssetBlob, AssetDependency, AssetError, AssetKey, BlobImporterDispatch, ImportSettings,
+    ImporterPriority,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use newengine_plugin_api::{Blob, CapabilityId, MethodName};
 use std::sync::Arc;
 
 use crate::plugins::describe::parse_describe;
-use crate::plugins::host_api::call_service_v1;
+use crate::plugins::fault::PluginCallSite;
+use crate::plugins::host_api::call_service_at;
 use crate::plugins::host_context::ctx;
 
+/// Optional dependency list an importer may place in its wire meta:
+/// `{"dependencies":[{"path":"textures/a.png","usage":"albedo"}]}`.
+/// With `"relative":true` the path is resolved against the imported asset's directory.
+#[derive(Debug, Default, Deserialize)]
+struct WireMetaDeps {
+    #[serde(default)]
+    dependencies: Vec<WireDependency>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WireDependency {
+    path: String,
+    #[serde(default)]
+    settings_hash: u64,
+    #[serde(default)]
+    type_hint: String,
+    #[serde(default)]
+    usage: String,
+    #[serde(default)]
+    relative: bool,
+}
+
 pub(crate) struct ServiceBlobImporter {
     stable_id: Arc<str>,
     exts: Vec<String>,
     output_type_id: Arc<str>,
     format: Arc<str>,
     method: Arc<str>,
+    settings_method: Option<Arc<str>>,
     service_id: Arc<str>,
     priority: ImporterPriority,
+    version: Arc<str>,
+    /// Lowercase extension -> content type.
+    mime_types: HashMap<String, Arc<str>>,
 }
 
 impl ServiceBlobImporter {
     #[inline]
-    fn call_import(&self, bytes: &[u8]) -> Result<Vec<u8>, AssetError> {
-        let out: RResult<Blob, RString> = call_service_v1(
+    fn call_import(&self, method: &str, payload: Vec<u8>) -> Result<Vec<u8>, AssetError> {
+        let out: RResult<Blob, RString> = call_service_at(
+            PluginCallSite::Importer,
             CapabilityId::from(self.service_id.as_ref()),
-            MethodName::from(self.method.as_ref()),
-            Blob::from(bytes.to_vec()),
+            MethodName::from(method),
+            Blob::from(payload),
         );
 
         out.into_result()
@@ -55,21 +89,84 @@ impl ServiceBlobImporter {
 
         Ok((Arc::from(meta_json), payload))
     }
-}
 
-impl BlobImporterDispatch for ServiceBlobImporter {
-    fn import_blob(&self, bytes: &[u8], _key: &AssetKey) -> Result<AssetBlob, AssetError> {
-        let frame = self.call_import(bytes)?;
-        let (meta_json, payload) = Self::unpack_wire_v1(&frame)?;
+    #[inline]
+    fn parse_dependencies(meta_json: &str, key: &AssetKey) -> Vec<AssetDependency> {
+        let Ok(m) = serde_json::from_str::<WireMetaDeps>(meta_json) else {
+            return Vec::new();
+        };
+
+        m.dependencies
+            .into_iter()
+            .filter(|d| !d.path.trim().is_empty())
+            .filter_map(|d| {
+                let logical_path = if d.relative {
+                    let resolved = resolve_relative(&key.logical_path, &d.path);
+                    if resolved.is_none() {
+                        log::warn!(
+                            target: "assets",
+                            "importer.dependency escapes assets root: asset='{}' path='{}'",
+                            key.logical_path.display(),
+                            d.path
+                        );
+                    }
+                    resolved?
+                } else {
+                    PathBuf::from(&d.path)
+                };
+                Some((logical_path, d))
+            })
+            .map(|(logical_path, d)| AssetDependency {
+                logical_path,
+                settings_hash: d.settings_hash,
+                type_hint: Arc::from(d.type_hint),
+                usage: Arc::from(d.usage),
+            })
+            .collect()
+    }
+
+    #[inline]
+    fn pack_settings(settings: &ImportSettings, bytes: &[u8]) -> Vec<u8> {
+        let json = settings.as_json().as_bytes();
+        let mut out = Vec::with_capacity(4 + json.len() + bytes.len());
+        out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        out.extend_from_slice(json);
+        out.extend_from_slice(bytes);
+        out
+    }
+
+    fn blob_from_frame(&self, frame: &[u8], key: &AssetKey) -> Result<AssetBlob, AssetError> {
+        let (meta_json, payload) = Self::unpack_wire_v1(frame)?;
+        let dependencies = Self::parse_dependencies(&meta_json, key);
 
         Ok(AssetBlob {
             type_id: self.output_type_id.clone(),
             format: self.format.clone(),
             payload,
             meta_json,
-            dependencies: Vec::new(),
+            dependencies,
         })
     }
+}
+
+impl BlobImporterDispatch for ServiceBlobImporter {
+    fn import_blob(&self, bytes: &[u8], key: &AssetKey) -> Result<AssetBlob, AssetError> {
+        let frame = self.call_import(&self.method, bytes.to_vec())?;
+        self.blob_from_frame(&frame, key)
+    }
+
+    fn import_blob_with_settings(
+        &self,
+        bytes: &[u8],
+        key: &AssetKey,
+        settings: &ImportSettings,
+    ) -> Result<AssetBlob, AssetError> {
+        let Some(method) = &self.settings_method else {
+            return self.import_blob(bytes, key);
+        };
+        let frame = self.call_import(method, Self::pack_settings(settings, bytes))?;
+        self.blob_from_frame(&frame, key)
+    }
 
     fn output_type_id(&self) -> Arc<str> {
         self.output_type_id.clone()
@@ -86,6 +183,27 @@ impl BlobImporterDispatch for ServiceBlobImporter {
     fn stable_id(&self) -> Arc<str> {
         self.stable_id.clone()
     }
+
+    fn version(&self) -> Arc<str> {
+        self.version.clone()
+    }
+
+    fn mime_type(&self, ext: &str) -> Option<Arc<str>> {
+        self.mime_types.get(ext).cloned()
+    }
+}
+
+/// Importers without an explicit version are keyed by their describe document,
+/// so any change in the advertised contract invalidates cached imports.
+#[inline]
+fn describe_fingerprint(describe_json: &str) -> String {
+    // FNV-1a 64: stable across toolchains (unlike `DefaultHasher`).
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in describe_json.as_bytes() {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("describe:{h:016x}")
 }
 
 pub(crate) fn try_auto_register_importer(service_id: &str, describe_json: &str) {
@@ -101,6 +219,25 @@ pub(crate) fn try_auto_register_importer(service_id: &str, describe_json: &str)
     };
 
     let _wire = imp.wire;
+    let version = imp
+        .version
+        .unwrap_or_else(|| describe_fingerprint(describe_json));
+
+    // Explicit per-extension types win; a provider-level type covers all extensions.
+    let provider_mime = d.provider.and_then(|p| p.mime);
+    let mut mime_types: HashMap<String, Arc<str>> = HashMap::new();
+    for ext in &imp.extensions {
+        let ext = ext.trim().trim_start_matches('.').to_ascii_lowercase();
+        let mime = imp
+            .mime_types
+            .iter()
+            .find(|(k, _)| k.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+            .map(|(_, v)| v.as_str())
+            .or(provider_mime.as_deref());
+        if let Some(mime) = mime {
+            mime_types.insert(ext, Arc::from(mime));
+        }
+    }
 
     let importer = ServiceBlobImporter {
         stable_id: Arc::from(service_id.to_string()),
@@ -108,10 +245,33 @@ pub(crate) fn try_auto_register_importer(service_id: &str, describe_json: &str)
         output_type_id: Arc::from(imp.output_type_id),
         format: Arc::from(imp.format),
         method: Arc::from(imp.method),
+        settings_method: imp.settings_method.map(Arc::from),
         service_id: Arc::from(service_id.to_string()),
         priority: ImporterPriority::new(imp.priority.unwrap_or(0)),
+        version: Arc::from(version),
+        mime_types,
     };
 
     ctx().asset_store.add_importer(Arc::new(importer));
     log::info!(target: "assets", "importer.auto_registered service_id='{}'", service_id);
 }
+
+/// Joins `rel` onto the directory of `asset`, folding `.`/`..`. `None` if it leaves the root.
+fn resolve_relative(asset: &Path, rel: &str) -> Option<PathBuf> {
+    let mut parts: Vec<&str> = asset
+        .parent()
+        .map(|p| p.iter().filter_map(|c| c.to_str()).collect())
+        .unwrap_or_default();
+
+    for seg in rel.split(['/', '\\']) {
+        match seg {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            s => parts.push(s),
+        }
+    }
+
+    (!parts.is_empty()).then(|| parts.iter().collect())
+}
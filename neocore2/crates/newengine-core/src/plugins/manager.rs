@@ -1,15 +1,22 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 use libloading::Library;
+#[cfg(feature = "runtime")]
+use newengine_ui::notify::{NotifyApi, ToastLevel};
 use newengine_plugin_api::{HostApiV1, PluginInfo, PluginModuleDyn, PluginRootV1Ref, ServiceV1Dyn};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+use crate::module::{panic_message, ApiVersion};
+use crate::plugins::fault::{take_reported_faults, PluginCallSite, PluginFault};
 use crate::plugins::host_api::{
     host_register_service_impl, with_importer_load_state, ImporterLoadState,
 };
 use crate::plugins::host_context::{unregister_by_owner, with_current_plugin_id};
+use crate::plugins::manifest::{parse_reported_version, PluginManifest};
 use crate::plugins::paths::{default_plugins_dir, is_dynamic_lib, resolve_plugins_dir};
+use crate::plugins::permissions::{grant_permissions, revoke_permissions, PluginPermissionPolicy};
+use crate::plugins::resolve::{resolve_load_order, PluginCandidate};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum PluginState {
@@ -44,6 +51,11 @@ struct LoadedPlugin {
 pub struct PluginManager {
     loaded: Vec<LoadedPlugin>,
     loaded_ids: HashSet<String>,
+    /// Plugins and importers that failed to load; loading continues past them.
+    failures: Vec<PluginLoadError>,
+    /// Faults not yet taken by `take_faults`.
+    faults: Vec<PluginFault>,
+    permission_policy: PluginPermissionPolicy,
 }
 
 impl PluginManager {
@@ -52,14 +64,45 @@ impl PluginManager {
         Self {
             loaded: Vec::new(),
             loaded_ids: HashSet::new(),
+            failures: Vec::new(),
+            faults: Vec::new(),
+            permission_policy: PluginPermissionPolicy::default(),
         }
     }
 
+    /// Policy for the permissions granted to plugins loaded from now on.
+    #[inline]
+    pub fn with_permission_policy(mut self, policy: PluginPermissionPolicy) -> Self {
+        self.permission_policy = policy;
+        self
+    }
+
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = &PluginModuleDyn<'static>> {
         self.loaded.iter().map(|p| &p.module)
     }
 
+    /// Candidates that failed to load, in scan order.
+    #[inline]
+    pub fn load_failures(&self) -> &[PluginLoadError] {
+        &self.failures
+    }
+
+    /// `(id, reason)` of plugins disabled after a failing or panicking call.
+    pub fn disabled(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.loaded.iter().filter_map(|p| {
+            let reason = p.disabled_reason.as_deref()?;
+            Some((p.info.id.as_str(), reason))
+        })
+    }
+
+    /// Faults since the last call, oldest first; the engine publishes them on its `EventHub`.
+    /// Also disables plugins whose services panicked since the last tick.
+    pub fn take_faults(&mut self) -> Vec<PluginFault> {
+        self.collect_service_faults();
+        std::mem::take(&mut self.faults)
+    }
+
     pub fn load_default(&mut self, host: HostApiV1) -> Result<(), PluginLoadError> {
         let dir = default_plugins_dir()?;
         self.load_from_dir(&dir, host)
@@ -73,33 +116,7 @@ impl PluginManager {
         let dir = resolve_plugins_dir(dir)?;
         log::info!(target: "assets", "importers: scanning directory '{}'", dir.display());
 
-        if let Err(e) = std::fs::create_dir_all(&dir) {
-            return Err(PluginLoadError {
-                path: dir.clone(),
-                message: format!("create_dir_all failed: {e}"),
-            });
-        }
-
-        let mut candidates = Vec::new();
-        let rd = std::fs::read_dir(&dir).map_err(|e| PluginLoadError {
-            path: dir.clone(),
-            message: format!("read_dir failed: {e}"),
-        })?;
-
-        for ent in rd {
-            let ent = ent.map_err(|e| PluginLoadError {
-                path: dir.clone(),
-                message: format!("read_dir entry failed: {e}"),
-            })?;
-
-            let p = ent.path();
-            if !is_dynamic_lib(&p) {
-                continue;
-            }
-            candidates.push(p);
-        }
-
-        candidates.sort();
+        let candidates = scan_dir(&dir)?;
 
         log::info!(
             target: "assets",
@@ -108,8 +125,21 @@ impl PluginManager {
             dir.display()
         );
 
-        for path in candidates {
-            match self.load_one_importer(&path, host.clone()) {
+        let resolved = resolve_load_order(candidates, &self.loaded_versions());
+        for e in resolved.failures {
+            log::warn!(target: "assets", "importers: not loaded: {}", e);
+            #[cfg(feature = "runtime")]
+            NotifyApi::toast(
+                ToastLevel::Warning,
+                format!("Importer not loaded: {e}"),
+                None,
+            );
+            self.failures.push(e);
+        }
+
+        for candidate in resolved.order {
+            let path = candidate.path.clone();
+            match self.load_one_importer(candidate, host.clone()) {
                 Ok(ImporterLoadOutcome::Loaded(info)) => {
                     log::info!(
                         target: "assets",
@@ -133,6 +163,13 @@ impl PluginManager {
                         path.display(),
                         e
                     );
+                    #[cfg(feature = "runtime")]
+                    NotifyApi::toast(
+                        ToastLevel::Warning,
+                        format!("Importer '{}' failed to load: {e}", path.display()),
+                        None,
+                    );
+                    self.failures.push(e);
                 }
             }
         }
@@ -144,33 +181,7 @@ impl PluginManager {
         let dir = resolve_plugins_dir(dir)?;
         log::info!("plugins: scanning directory '{}'", dir.display());
 
-        if let Err(e) = std::fs::create_dir_all(&dir) {
-            return Err(PluginLoadError {
-                path: dir.clone(),
-                message: format!("create_dir_all failed: {e}"),
-            });
-        }
-
-        let mut candidates = Vec::new();
-        let rd = std::fs::read_dir(&dir).map_err(|e| PluginLoadError {
-            path: dir.clone(),
-            message: format!("read_dir failed: {e}"),
-        })?;
-
-        for ent in rd {
-            let ent = ent.map_err(|e| PluginLoadError {
-                path: dir.clone(),
-                message: format!("read_dir entry failed: {e}"),
-            })?;
-
-            let p = ent.path();
-            if !is_dynamic_lib(&p) {
-                continue;
-            }
-            candidates.push(p);
-        }
-
-        candidates.sort();
+        let candidates = scan_dir(&dir)?;
 
         log::info!(
             "plugins: found {} candidate(s) in '{}'",
@@ -178,18 +189,68 @@ impl PluginManager {
             dir.display()
         );
 
-        for path in candidates {
-            match self.load_one(&path, host.clone()) {
-                Ok(()) => {}
-                Err(e) => {
-                    log::warn!("plugins: failed to load '{}': {}", path.display(), e);
-                }
+        let resolved = resolve_load_order(candidates, &self.loaded_versions());
+        for e in resolved.failures {
+            self.report_load_failure(e);
+        }
+
+        for candidate in resolved.order {
+            if let Err(e) = self.load_one(candidate, host.clone()) {
+                self.report_load_failure(e);
             }
         }
 
         Ok(())
     }
 
+    fn report_load_failure(&mut self, e: PluginLoadError) {
+        log::warn!("plugins: failed to load {}", e);
+        #[cfg(feature = "runtime")]
+        NotifyApi::toast(
+            ToastLevel::Error,
+            format!("Plugin failed to load: {e}"),
+            None,
+        );
+        self.failures.push(e);
+    }
+
+    /// Reported versions of the loaded plugins, for dependency checks.
+    fn loaded_versions(&self) -> HashMap<String, Option<ApiVersion>> {
+        self.loaded
+            .iter()
+            .map(|p| {
+                (
+                    p.info.id.to_string(),
+                    parse_reported_version(p.info.version.as_str()),
+                )
+            })
+            .collect()
+    }
+
+    /// Fails when a dependency of `candidate` was ordered before it but did not load.
+    fn check_dependencies_loaded(
+        &self,
+        candidate: &PluginCandidate,
+    ) -> Result<(), PluginLoadError> {
+        let Some(m) = candidate.manifest.as_ref() else {
+            return Ok(());
+        };
+        match m
+            .dependencies
+            .iter()
+            .find(|d| !self.loaded_ids.contains(&d.id))
+        {
+            Some(dep) => Err(PluginLoadError {
+                path: candidate.path.clone(),
+                message: format!(
+                    "dependency '{}' is not loaded (it failed or was skipped, see the log)",
+                    dep.id
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+
     #[inline]
     fn rresult_to_string(
         r: abi_stable::std_types::RResult<(), abi_stable::std_types::RString>,
@@ -202,17 +263,20 @@ impl PluginManager {
             if self.loaded[i].state != PluginState::Registered {
                 continue;
             }
-            self.call_plugin(i, "start", |m| Self::rresult_to_string(m.start()));
+            self.call_plugin(i, PluginCallSite::Start, |m| {
+                Self::rresult_to_string(m.start())
+            });
         }
         Ok(())
     }
 
     pub fn fixed_update_all(&mut self, dt: f32) -> Result<(), String> {
+        self.collect_service_faults();
         for i in 0..self.loaded.len() {
             if self.loaded[i].state != PluginState::Running {
                 continue;
             }
-            self.call_plugin(i, "fixed_update", |m| {
+            self.call_plugin(i, PluginCallSite::FixedUpdate, |m| {
                 Self::rresult_to_string(m.fixed_update(dt))
             });
         }
@@ -220,21 +284,27 @@ impl PluginManager {
     }
 
     pub fn update_all(&mut self, dt: f32) -> Result<(), String> {
+        self.collect_service_faults();
         for i in 0..self.loaded.len() {
             if self.loaded[i].state != PluginState::Running {
                 continue;
             }
-            self.call_plugin(i, "update", |m| Self::rresult_to_string(m.update(dt)));
+            self.call_plugin(i, PluginCallSite::Update, |m| {
+                Self::rresult_to_string(m.update(dt))
+            });
         }
         Ok(())
     }
 
     pub fn render_all(&mut self, dt: f32) -> Result<(), String> {
+        self.collect_service_faults();
         for i in 0..self.loaded.len() {
             if self.loaded[i].state != PluginState::Running {
                 continue;
             }
-            self.call_plugin(i, "render", |m| Self::rresult_to_string(m.render(dt)));
+            self.call_plugin(i, PluginCallSite::Render, |m| {
+                Self::rresult_to_string(m.render(dt))
+            });
         }
         Ok(())
     }
@@ -245,15 +315,17 @@ impl PluginManager {
             self.safe_shutdown_one(i);
             self.loaded[i].state = PluginState::Stopped;
             unregister_by_owner(&id);
+            revoke_permissions(&id);
         }
         self.loaded.clear();
         self.loaded_ids.clear();
+        crate::crash::note_plugins_unloaded();
     }
 
     fn call_plugin(
         &mut self,
         idx: usize,
-        op: &str,
+        site: PluginCallSite,
         f: impl FnOnce(&mut PluginModuleDyn<'static>) -> Result<(), String>,
     ) {
         if idx >= self.loaded.len() {
@@ -270,35 +342,77 @@ impl PluginManager {
             with_current_plugin_id(&id, || f(&mut self.loaded[idx].module))
         }));
 
-        match result {
-            Ok(Ok(())) => {}
+        let op = site.as_str();
+        let (message, panicked) = match result {
+            Ok(Ok(())) => (None, false),
             Ok(Err(e)) => {
                 log::error!("plugins: op '{}' failed for id='{}': {}", op, id, e);
                 self.disable_plugin(idx, &id, format!("op '{op}' failed: {e}"));
+                (Some(e), false)
             }
-            Err(_) => {
+            Err(p) => {
+                let msg = panic_message(p.as_ref());
                 log::error!(
-                    "plugins: panic during op '{}' for id='{}' (plugin disabled)",
+                    "plugins: panic during op '{}' for id='{}' (plugin disabled): {}",
                     op,
-                    id
+                    id,
+                    msg
                 );
-                self.disable_plugin(idx, &id, format!("panic during op '{op}'"));
+                self.disable_plugin(idx, &id, format!("panic during op '{op}': {msg}"));
+                (Some(msg), true)
             }
+        };
+        if let Some(message) = message {
+            self.faults.push(PluginFault {
+                plugin_id: id,
+                site,
+                target: None,
+                message,
+                panicked,
+            });
         }
 
         if idx < self.loaded.len() {
-            if op == "start" && self.loaded[idx].state == PluginState::Registered {
+            if site == PluginCallSite::Start && self.loaded[idx].state == PluginState::Registered {
                 self.loaded[idx].state = PluginState::Running;
             }
         }
     }
 
+    /// Disables the plugins whose services panicked (see `call_service_at`) and keeps their
+    /// faults for `take_faults`.
+    fn collect_service_faults(&mut self) {
+        for fault in take_reported_faults() {
+            if let Some(idx) = self
+                .loaded
+                .iter()
+                .position(|p| p.info.id.as_str() == fault.plugin_id)
+            {
+                let target = fault.target.as_deref().unwrap_or("?");
+                let reason = format!(
+                    "panic in {} '{}': {}",
+                    fault.site.as_str(),
+                    target,
+                    fault.message
+                );
+                self.disable_plugin(idx, &fault.plugin_id, reason);
+            }
+            self.faults.push(fault);
+        }
+    }
+
     fn disable_plugin(&mut self, idx: usize, id: &str, reason: String) {
         if idx >= self.loaded.len() || self.loaded[idx].state == PluginState::Disabled {
             return;
         }
 
         self.loaded[idx].state = PluginState::Disabled;
+        #[cfg(feature = "runtime")]
+        NotifyApi::toast(
+            ToastLevel::Error,
+            format!("Plugin '{id}' was disabled: {reason}"),
+            None,
+        );
         self.loaded[idx].disabled_reason = Some(reason);
 
         self.safe_shutdown_one(idx);
@@ -318,9 +432,61 @@ impl PluginManager {
         }));
     }
 
-    fn load_one(&mut self, path: &Path, host: HostApiV1) -> Result<(), PluginLoadError> {
+    /// Grants `id` the permissions of its manifest allowed by the policy, before `init`.
+    fn grant_permissions_for(
+        &self,
+        path: &Path,
+        id: &str,
+        manifest: Option<&PluginManifest>,
+    ) -> Result<(), PluginLoadError> {
+        let err = |message: String| PluginLoadError {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        if let Some(want) = manifest.and_then(|m| m.id.as_deref()) {
+            if want != id {
+                return Err(err(format!(
+                    "manifest is for plugin '{want}', library reports '{id}'"
+                )));
+            }
+        }
+
+        let granted = self.permission_policy.resolve(id, manifest).map_err(err)?;
+        if let Some(m) = manifest {
+            let denied = granted.missing(m.permissions);
+            if !denied.is_empty() {
+                log::warn!(
+                    "plugins: permissions not allowed by policy id='{}' denied={}",
+                    id,
+                    denied
+                );
+            }
+        } else if !self.permission_policy.strict {
+            log::warn!(
+                "plugins: no manifest for id='{}' (all permissions granted)",
+                id
+            );
+        }
+        log::info!("plugins: permissions id='{}' granted={}", id, granted);
+
+        grant_permissions(id, granted, self.permission_policy.strict);
+        Ok(())
+    }
+
+    fn load_one(
+        &mut self,
+        candidate: PluginCandidate,
+        host: HostApiV1,
+    ) -> Result<(), PluginLoadError> {
+        let path = candidate.path.as_path();
         log::info!("plugins: loading '{}'", path.display());
 
+        // Manifests are read and dependencies checked before the library, so a rejected
+        // plugin never runs any code.
+        self.check_dependencies_loaded(&candidate)?;
+        let manifest = candidate.manifest.as_ref();
+
         let lib = unsafe { Library::new(path) }.map_err(|e| PluginLoadError {
             path: path.to_path_buf(),
             message: format!("Library::new failed: {e}"),
@@ -372,6 +538,22 @@ impl PluginManager {
             return Ok(());
         }
 
+        if let Some(want) = manifest.and_then(|m| m.version) {
+            if parse_reported_version(info.version.as_str()) != Some(want) {
+                log::warn!(
+                    "plugins: id='{}' reports version '{}' but its manifest says {}",
+                    id_str,
+                    info.version,
+                    want
+                );
+            }
+        }
+
+        if let Err(e) = self.grant_permissions_for(path, &id_str, manifest) {
+            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| module.shutdown()));
+            return Err(e);
+        }
+
         let init_res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
             with_current_plugin_id(&id_str, || module.init(host).into_result())
         }));
@@ -380,6 +562,7 @@ impl PluginManager {
             Ok(Ok(())) => {}
             Ok(Err(e)) => {
                 unregister_by_owner(&id_str);
+                revoke_permissions(&id_str);
                 let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                     with_current_plugin_id(&id_str, || module.shutdown());
                 }));
@@ -390,6 +573,7 @@ impl PluginManager {
             }
             Err(_) => {
                 unregister_by_owner(&id_str);
+                revoke_permissions(&id_str);
                 let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                     with_current_plugin_id(&id_str, || module.shutdown());
                 }));
@@ -407,6 +591,7 @@ impl PluginManager {
             path.display()
         );
 
+        crate::crash::note_plugin_loaded(&id_str, info.version.as_str());
         self.loaded_ids.insert(id_str);
         self.loaded.push(LoadedPlugin {
             _lib: lib,
@@ -421,11 +606,15 @@ impl PluginManager {
 
     fn load_one_importer(
         &mut self,
-        path: &Path,
+        candidate: PluginCandidate,
         host: HostApiV1,
     ) -> Result<ImporterLoadOutcome, PluginLoadError> {
+        let path = candidate.path.as_path();
         log::info!(target: "assets", "importers: loading '{}'", path.display());
 
+        self.check_dependencies_loaded(&candidate)?;
+        let manifest = candidate.manifest.as_ref();
+
         let lib = unsafe { Library::new(path) }.map_err(|e| PluginLoadError {
             path: path.to_path_buf(),
             message: format!("Library::new failed: {e}"),
@@ -443,6 +632,21 @@ impl PluginManager {
         let info_pre = module.info();
         let id_pre = info_pre.id.to_string();
 
+        // A duplicate is rejected after `init`; it must not replace the loaded plugin's grants.
+        let duplicate = self.loaded_ids.contains(&id_pre);
+        if !duplicate {
+            if let Err(e) = self.grant_permissions_for(path, &id_pre, manifest) {
+                let _ =
+                    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| module.shutdown()));
+                return Err(e);
+            }
+        }
+        let revoke = |id: &str| {
+            if !duplicate {
+                revoke_permissions(id);
+            }
+        };
+
         let mut state = ImporterLoadState {
             saw_importer: false,
             staged: Vec::<ServiceV1Dyn<'static>>::new(),
@@ -462,6 +666,7 @@ impl PluginManager {
 
         if let Err(e) = init_outcome {
             unregister_by_owner(&id_pre);
+            revoke(&id_pre);
             let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                 with_current_plugin_id(&id_pre, || module.shutdown());
             }));
@@ -473,6 +678,7 @@ impl PluginManager {
 
         if !state.saw_importer {
             unregister_by_owner(&id_pre);
+            revoke(&id_pre);
             let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                 with_current_plugin_id(&id_pre, || module.shutdown());
             }));
@@ -492,6 +698,7 @@ impl PluginManager {
                 Ok(Ok(())) => {}
                 Ok(Err(e)) => {
                     unregister_by_owner(&id_pre);
+                    revoke(&id_pre);
                     let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                         with_current_plugin_id(&id_pre, || module.shutdown());
                     }));
@@ -502,6 +709,7 @@ impl PluginManager {
                 }
                 Err(_) => {
                     unregister_by_owner(&id_pre);
+                    revoke(&id_pre);
                     let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                         with_current_plugin_id(&id_pre, || module.shutdown());
                     }));
@@ -530,6 +738,7 @@ impl PluginManager {
             return Ok(ImporterLoadOutcome::SkippedNotImporter);
         }
 
+        crate::crash::note_plugin_loaded(&id_str, info.version.as_str());
         self.loaded_ids.insert(id_str);
 
         self.loaded.push(LoadedPlugin {
@@ -547,4 +756,35 @@ impl PluginManager {
 enum ImporterLoadOutcome {
     Loaded(PluginInfo),
     SkippedNotImporter,
-}
\ No newline at end of file
+}
+/// Dynamic libraries in `dir` (created if missing), sorted by path.
+fn scan_dir(dir: &Path) -> Result<Vec<PathBuf>, PluginLoadError> {
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        return Err(PluginLoadError {
+            path: dir.to_path_buf(),
+            message: format!("create_dir_all failed: {e}"),
+        });
+    }
+
+    let mut candidates = Vec::new();
+    let rd = std::fs::read_dir(dir).map_err(|e| PluginLoadError {
+        path: dir.to_path_buf(),
+        message: format!("read_dir failed: {e}"),
+    })?;
+
+    for ent in rd {
+        let ent = ent.map_err(|e| PluginLoadError {
+            path: dir.to_path_buf(),
+            message: format!("read_dir entry failed: {e}"),
+        })?;
+
+        let p = ent.path();
+        if !is_dynamic_lib(&p) {
+            continue;
+        }
+        candidates.push(p);
+    }
+
+    candidates.sort();
+    Ok(candidates)
+}
@@ -0,0 +1,180 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Plugin manifests.
+//!
+//! A plugin ships a manifest next to its library (`foo.dll` -> `foo.plugin.toml`):
+//!
+//! ```toml
+//! id = "acme.exporter"
+//! version = "1.2.0"
+//! engine_api = "2.0"            # minimum PLUGIN_HOST_API_VERSION
+//! permissions = ["filesystem"]
+//!
+//! [dependencies]
+//! "acme.core" = "1.1"           # acme.core >= 1.1.0
+//! "acme.log" = "*"              # any version
+//! ```
+//!
+//! Versions are `major[.minor[.patch]]`. A `foo.plugin.json` with the same keys is still
+//! read when there is no TOML manifest. The plugin manager loads a directory in dependency
+//! order and rejects plugins whose dependencies are missing, too old or circular before any
+//! of their code runs.
+
+use crate::module::ApiVersion;
+use crate::plugins::permissions::PluginPermissions;
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Version of the host API handed to plugins (`HostApiV1` and its `HostApiV2` extension).
+/// Plugins that need `HostApiV2` declare `engine_api = "2.0"`.
+pub const PLUGIN_HOST_API_VERSION: ApiVersion = ApiVersion::new(2, 0, 0);
+
+/// Suffix replacing the library extension for the manifest file name.
+pub const MANIFEST_SUFFIX: &str = "plugin.toml";
+
+/// Suffix of the JSON manifest read when there is no TOML one.
+pub const JSON_MANIFEST_SUFFIX: &str = "plugin.json";
+
+#[derive(Deserialize)]
+struct ManifestFile {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    engine_api: Option<String>,
+    #[serde(default)]
+    permissions: Vec<String>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, String>,
+}
+
+/// Another plugin that must be loaded first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginDependency {
+    pub id: String,
+    /// `0.0.0` for `"*"`.
+    pub min_version: ApiVersion,
+}
+
+/// Parsed `<library>.plugin.toml` (or `.plugin.json`).
+#[derive(Debug, Clone, Default)]
+pub struct PluginManifest {
+    /// Plugin id the manifest was written for; a library reporting another id fails to load.
+    /// Other plugins can only depend on plugins whose manifest declares an id.
+    pub id: Option<String>,
+    pub version: Option<ApiVersion>,
+    /// Minimum [`PLUGIN_HOST_API_VERSION`] the plugin was built for.
+    pub engine_api: Option<ApiVersion>,
+    pub dependencies: Vec<PluginDependency>,
+    pub permissions: PluginPermissions,
+    /// File the manifest was read from; empty when parsed from memory.
+    pub path: PathBuf,
+}
+
+impl PluginManifest {
+    pub fn parse_toml(text: &str) -> Result<Self, String> {
+        let m: ManifestFile =
+            toml::from_str(text).map_err(|e| format!("invalid manifest toml: {e}"))?;
+        Self::from_file(m)
+    }
+
+    pub fn parse_json(json: &[u8]) -> Result<Self, String> {
+        let m: ManifestFile =
+            serde_json::from_slice(json).map_err(|e| format!("invalid manifest json: {e}"))?;
+        Self::from_file(m)
+    }
+
+    fn from_file(m: ManifestFile) -> Result<Self, String> {
+        let permissions =
+            PluginPermissions::parse_list(m.permissions.iter().map(String::as_str))
+                .map_err(|unknown| format!("unknown permission(s): {}", unknown.join(", ")))?;
+        let version = m
+            .version
+            .as_deref()
+            .map(|v| parse_version("version", v))
+            .transpose()?;
+        let engine_api = m
+            .engine_api
+            .as_deref()
+            .map(|v| parse_version("engine_api", v))
+            .transpose()?;
+
+        let mut dependencies = Vec::with_capacity(m.dependencies.len());
+        for (id, req) in m.dependencies {
+            let id = id.trim().to_string();
+            if id.is_empty() {
+                return Err("dependency with an empty plugin id".to_string());
+            }
+            let min_version = match req.trim() {
+                "" | "*" => ApiVersion::new(0, 0, 0),
+                v => parse_version(&format!("dependencies.\"{id}\""), v)?,
+            };
+            dependencies.push(PluginDependency { id, min_version });
+        }
+
+        Ok(Self {
+            id: m.id.filter(|id| !id.trim().is_empty()),
+            version,
+            engine_api,
+            dependencies,
+            permissions,
+            path: PathBuf::new(),
+        })
+    }
+
+    /// Reads the manifest of the library at `lib_path`; `Ok(None)` when there is none.
+    pub fn load_for(lib_path: &Path) -> Result<Option<Self>, String> {
+        let toml_path = manifest_path(lib_path);
+        let json_path = lib_path.with_extension(JSON_MANIFEST_SUFFIX);
+
+        let (path, parsed) = if let Some(bytes) = read_if_exists(&toml_path)? {
+            let parsed = std::str::from_utf8(&bytes)
+                .map_err(|_| "manifest is not valid UTF-8".to_string())
+                .and_then(Self::parse_toml);
+            (toml_path, parsed)
+        } else if let Some(bytes) = read_if_exists(&json_path)? {
+            (json_path, Self::parse_json(&bytes))
+        } else {
+            return Ok(None);
+        };
+
+        parsed
+            .map(|m| Some(m.with_path(path.clone())))
+            .map_err(|e| format!("manifest '{}': {e}", path.display()))
+    }
+
+    #[inline]
+    fn with_path(mut self, path: PathBuf) -> Self {
+        self.path = path;
+        self
+    }
+}
+
+/// Manifest path for the library at `lib_path`.
+#[inline]
+pub fn manifest_path(lib_path: &Path) -> PathBuf {
+    lib_path.with_extension(MANIFEST_SUFFIX)
+}
+
+fn read_if_exists(path: &Path) -> Result<Option<Vec<u8>>, String> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("manifest '{}': read failed: {e}", path.display())),
+    }
+}
+
+/// Version reported by a loaded plugin's `PluginInfo`; pre-release and build suffixes are
+/// ignored.
+pub(crate) fn parse_reported_version(s: &str) -> Option<ApiVersion> {
+    ApiVersion::parse(s.split(['-', '+']).next().unwrap_or(s))
+}
+
+fn parse_version(key: &str, s: &str) -> Result<ApiVersion, String> {
+    let v = s.trim().trim_start_matches(">=").trim_start_matches('v');
+    ApiVersion::parse(v)
+        .ok_or_else(|| format!("{key}: '{s}' is not a version (expected major[.minor[.patch]])"))
+}
@@ -1,13 +1,32 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 mod describe;
+pub(crate) mod descriptor;
+mod event_bridge;
+mod fault;
 pub(crate) mod host_api;
+pub(crate) mod host_api_v2;
 pub mod host_context;
 #[cfg(feature = "runtime")]
 mod importer;
 mod manager;
+mod manifest;
 mod paths;
+pub(crate) mod permissions;
+mod resolve;
+mod service_metrics;
 
+pub use descriptor::{DescriptorSource, MethodDescriptor, ServiceDescriptor};
+pub use event_bridge::{EventBridge, PluginEvent};
+pub use fault::{PluginCallSite, PluginFault};
 pub use host_api::{default_host_api, importers_host_api};
 pub use host_context::init_host_context;
-pub use manager::PluginManager;
+pub use manager::{PluginLoadError, PluginManager};
+pub use manifest::{
+    manifest_path, PluginDependency, PluginManifest, JSON_MANIFEST_SUFFIX, MANIFEST_SUFFIX,
+    PLUGIN_HOST_API_VERSION,
+};
+pub use permissions::{
+    plugin_permissions, PluginPermission, PluginPermissionPolicy, PluginPermissions,
+};
+pub use service_metrics::{ServiceCallStats, ServiceRateLimit};
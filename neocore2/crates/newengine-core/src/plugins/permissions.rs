@@ -0,0 +1,368 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Capability permissions for plugins.
+//!
+//! A plugin declares what it needs from the host in its manifest (see
+//! [`PluginManifest`](super::manifest::PluginManifest)):
+//!
+//! ```toml
+//! id = "acme.exporter"
+//! permissions = ["filesystem", "network"]
+//! ```
+//!
+//! The host grants the declared permissions that the [`PluginPermissionPolicy`] allows for
+//! the plugin id and checks them on every `HostApiV1` call that needs one: registering an
+//! asset importer needs `importer`, calling a service needs whatever the service lists under
+//! `"permissions"` in its describe JSON (the host services all declare theirs). Sandboxed
+//! plugins (WASM plugins, and native ones under a strict policy) cannot call services that
+//! declare no `"permissions"` at all, except their own. Native plugins still run in-process,
+//! so this gates what they reach through the host API, not what their own code can do.
+
+use crate::plugins::host_context::{ctx, peek_current_plugin_id};
+use crate::plugins::manifest::PluginManifest;
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluginPermission {
+    /// Services that read or write files (assets, sidecars, settings).
+    Filesystem,
+    /// Services that open connections or listen on sockets.
+    Network,
+    /// Services that control the host process or spawn others (console execution).
+    Process,
+    /// Registering asset importer services.
+    Importer,
+}
+
+impl PluginPermission {
+    pub const ALL: [PluginPermission; 4] = [
+        Self::Filesystem,
+        Self::Network,
+        Self::Process,
+        Self::Importer,
+    ];
+
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Filesystem => "filesystem",
+            Self::Network => "network",
+            Self::Process => "process",
+            Self::Importer => "importer",
+        }
+    }
+
+    /// Accepts the `as_str` names and the short forms `fs` and `net`.
+    #[inline]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "filesystem" | "fs" => Some(Self::Filesystem),
+            "network" | "net" => Some(Self::Network),
+            "process" => Some(Self::Process),
+            "importer" => Some(Self::Importer),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn bit(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+impl fmt::Display for PluginPermission {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Set of [`PluginPermission`]s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PluginPermissions(u8);
+
+impl PluginPermissions {
+    pub const NONE: Self = Self(0);
+    pub const ALL: Self = Self(0b1111);
+
+    #[inline]
+    pub fn contains(self, p: PluginPermission) -> bool {
+        self.0 & p.bit() != 0
+    }
+
+    #[inline]
+    pub fn with(self, p: PluginPermission) -> Self {
+        Self(self.0 | p.bit())
+    }
+
+    #[inline]
+    pub fn intersect(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Permissions in `required` that are not in `self`.
+    #[inline]
+    pub fn missing(self, required: Self) -> Self {
+        Self(required.0 & !self.0)
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline]
+    pub fn iter(self) -> impl Iterator<Item = PluginPermission> {
+        PluginPermission::ALL
+            .into_iter()
+            .filter(move |p| self.contains(*p))
+    }
+
+    /// Parses permission names; unknown names are returned as the error.
+    pub fn parse_list<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<Self, Vec<String>> {
+        let mut out = Self::NONE;
+        let mut unknown = Vec::new();
+        for name in names {
+            match PluginPermission::parse(name) {
+                Some(p) => out = out.with(p),
+                None => unknown.push(name.to_string()),
+            }
+        }
+        if unknown.is_empty() {
+            Ok(out)
+        } else {
+            Err(unknown)
+        }
+    }
+}
+
+impl FromIterator<PluginPermission> for PluginPermissions {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = PluginPermission>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::NONE, Self::with)
+    }
+}
+
+impl fmt::Display for PluginPermissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("none");
+        }
+        for (i, p) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            f.write_str(p.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// Which manifest permissions each plugin is granted.
+///
+/// The default trusts manifests as written and grants every permission to plugins without
+/// one, so builds whose plugins predate manifests keep working. Distributions loading
+/// third-party plugins turn on `strict`: a manifest is required, only permissions also
+/// listed in `allow` for the plugin id are granted, and plugins are sandboxed.
+#[derive(Debug, Clone, Default)]
+pub struct PluginPermissionPolicy {
+    pub strict: bool,
+    /// Per plugin id; a listed plugin is never granted more than its entry, strict or not.
+    pub allow: HashMap<String, PluginPermissions>,
+}
+
+impl PluginPermissionPolicy {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    #[inline]
+    pub fn with_allow(
+        mut self,
+        plugin_id: impl Into<String>,
+        permissions: PluginPermissions,
+    ) -> Self {
+        self.allow.insert(plugin_id.into(), permissions);
+        self
+    }
+
+    /// Permissions granted to `plugin_id`, or the reason it may not load.
+    pub fn resolve(
+        &self,
+        plugin_id: &str,
+        manifest: Option<&PluginManifest>,
+    ) -> Result<PluginPermissions, String> {
+        let declared = match manifest {
+            Some(m) => m.permissions,
+            None if self.strict => {
+                return Err("no plugin manifest (required by strict permissions)".to_string())
+            }
+            None => PluginPermissions::ALL,
+        };
+        match self.allow.get(plugin_id) {
+            Some(allowed) => Ok(declared.intersect(*allowed)),
+            None if self.strict => Ok(PluginPermissions::NONE),
+            None => Ok(declared),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Grant {
+    permissions: PluginPermissions,
+    sandboxed: bool,
+}
+
+/// Permission table checked by the host API, keyed by plugin id.
+#[derive(Default)]
+pub(crate) struct PermissionTable {
+    grants: HashMap<String, Grant>,
+}
+
+impl PermissionTable {
+    #[inline]
+    pub(crate) fn get(&self, plugin_id: &str) -> Option<PluginPermissions> {
+        self.grants.get(plugin_id).map(|g| g.permissions)
+    }
+
+    /// Unknown plugin ids count as sandboxed.
+    #[inline]
+    fn is_sandboxed(&self, plugin_id: &str) -> bool {
+        self.grants
+            .get(plugin_id)
+            .map(|g| g.sandboxed)
+            .unwrap_or(true)
+    }
+}
+
+/// Records `plugin_id`'s permissions. A sandboxed plugin is also denied services that
+/// declare no permissions.
+pub(crate) fn grant_permissions(plugin_id: &str, permissions: PluginPermissions, sandboxed: bool) {
+    if let Ok(mut g) = ctx().permissions.write() {
+        g.grants.insert(
+            plugin_id.to_string(),
+            Grant {
+                permissions,
+                sandboxed,
+            },
+        );
+    }
+}
+
+pub(crate) fn revoke_permissions(plugin_id: &str) {
+    if let Ok(mut g) = ctx().permissions.write() {
+        g.grants.remove(plugin_id);
+    }
+}
+
+/// Permissions granted to `plugin_id`; `None` if no plugin with that id was loaded.
+#[inline]
+pub fn plugin_permissions(plugin_id: &str) -> Option<PluginPermissions> {
+    ctx().permissions.read().ok()?.get(plugin_id)
+}
+
+/// Checks `required` against the current plugin's grants. Host calls (no current plugin)
+/// always pass; an unknown plugin has no permissions.
+pub(crate) fn check_current(required: PluginPermissions, target: &str) -> Result<(), String> {
+    if required.is_empty() {
+        return Ok(());
+    }
+    peek_current_plugin_id(|caller| {
+        let Some(caller) = caller else {
+            return Ok(());
+        };
+        let granted = ctx()
+            .permissions
+            .read()
+            .ok()
+            .and_then(|g| g.get(caller))
+            .unwrap_or_default();
+        let missing = granted.missing(required);
+        if missing.is_empty() {
+            return Ok(());
+        }
+        log::warn!(
+            "plugins: permission denied id='{}' target='{}' missing={}",
+            caller,
+            target,
+            missing
+        );
+        Err(format!(
+            "permission denied: plugin '{caller}' lacks '{missing}' for {target}"
+        ))
+    })
+}
+
+/// Checks the current plugin may call `service_id`, owned by `owner`. `required` is `None`
+/// for services that declare no permissions: open to trusted plugins, closed to sandboxed
+/// ones unless they own the service.
+pub(crate) fn check_service_call(
+    required: Option<PluginPermissions>,
+    service_id: &str,
+    owner: Option<&str>,
+) -> Result<(), String> {
+    let target = format!("service '{service_id}'");
+    if let Some(required) = required {
+        return check_current(required, &target);
+    }
+    peek_current_plugin_id(|caller| {
+        let Some(caller) = caller else {
+            return Ok(());
+        };
+        if owner == Some(caller) {
+            return Ok(());
+        }
+        let sandboxed = ctx()
+            .permissions
+            .read()
+            .map(|g| g.is_sandboxed(caller))
+            .unwrap_or(true);
+        if !sandboxed {
+            return Ok(());
+        }
+        log::warn!(
+            "plugins: permission denied id='{}' target='{}' (no declared permissions)",
+            caller,
+            target
+        );
+        Err(format!(
+            "permission denied: {target} declares no \"permissions\", so sandboxed plugin \
+             '{caller}' cannot call it"
+        ))
+    })
+}
+
+/// Permissions a service requires of its callers: the `"permissions"` array of its describe
+/// JSON, `None` when it has none. Unknown names are ignored with a warning.
+pub(crate) fn required_by_describe(
+    service_id: &str,
+    describe_json: &str,
+) -> Option<PluginPermissions> {
+    let v = serde_json::from_str::<serde_json::Value>(describe_json).ok()?;
+    let list = v.get("permissions")?.as_array()?;
+    let names = list.iter().filter_map(|p| p.as_str());
+    match PluginPermissions::parse_list(names) {
+        Ok(p) => Some(p),
+        Err(unknown) => {
+            log::warn!(
+                "services: unknown permission(s) ignored service='{}': {}",
+                service_id,
+                unknown.join(", ")
+            );
+            Some(
+                list.iter()
+                    .filter_map(|p| p.as_str().and_then(PluginPermission::parse))
+                    .collect(),
+            )
+        }
+    }
+}
@@ -0,0 +1,287 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Load order of the plugins of one directory, from their manifests' dependencies.
+
+use crate::module::ApiVersion;
+use crate::plugins::manager::PluginLoadError;
+use crate::plugins::manifest::{PluginManifest, MANIFEST_SUFFIX, PLUGIN_HOST_API_VERSION};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Library to load, with its manifest.
+pub(crate) struct PluginCandidate {
+    pub path: PathBuf,
+    pub manifest: Option<PluginManifest>,
+}
+
+impl PluginCandidate {
+    #[inline]
+    fn id(&self) -> Option<&str> {
+        self.manifest.as_ref().and_then(|m| m.id.as_deref())
+    }
+}
+
+pub(crate) struct Resolution {
+    /// Candidates in load order: every one after the candidates it depends on.
+    pub order: Vec<PluginCandidate>,
+    /// Candidates rejected before loading.
+    pub failures: Vec<PluginLoadError>,
+}
+
+/// Reads the manifests of `paths` (in scan order) and orders them by dependency.
+///
+/// `loaded` maps the ids of plugins already loaded to their reported version; they satisfy
+/// dependencies without being loaded again. Candidates with a broken manifest, an engine API
+/// newer than this host, a duplicate manifest id, a missing or too old dependency or a
+/// dependency cycle are rejected, along with everything depending on them. Candidates
+/// without a manifest keep their scan position relative to each other.
+pub(crate) fn resolve_load_order(
+    paths: Vec<PathBuf>,
+    loaded: &HashMap<String, Option<ApiVersion>>,
+) -> Resolution {
+    let mut failures = Vec::new();
+    let mut candidates = Vec::with_capacity(paths.len());
+
+    for path in paths {
+        match PluginManifest::load_for(&path) {
+            Ok(manifest) => candidates.push(PluginCandidate { path, manifest }),
+            Err(message) => failures.push(PluginLoadError { path, message }),
+        }
+    }
+
+    // Per-candidate rejection reason; `None` while still loadable.
+    let mut rejected: Vec<Option<String>> = candidates.iter().map(check_engine_api).collect();
+
+    let mut by_id: HashMap<&str, usize> = HashMap::new();
+    for (i, c) in candidates.iter().enumerate() {
+        let Some(id) = c.id() else {
+            continue;
+        };
+        if let Some(&first) = by_id.get(id) {
+            rejected[i].get_or_insert_with(|| {
+                format!(
+                    "plugin id '{id}' is also declared by '{}'; remove one of the two libraries",
+                    candidates[first].path.display()
+                )
+            });
+            continue;
+        }
+        by_id.insert(id, i);
+    }
+
+    for (i, c) in candidates.iter().enumerate() {
+        if rejected[i].is_some() {
+            continue;
+        }
+        rejected[i] = check_dependencies(c, &candidates, &by_id, loaded);
+    }
+
+    // A plugin whose dependency was rejected is rejected too; repeat until nothing changes.
+    loop {
+        let mut changed = false;
+        for (i, c) in candidates.iter().enumerate() {
+            if rejected[i].is_some() {
+                continue;
+            }
+            let failed_dep = dependency_ids(c).find(|dep| {
+                by_id
+                    .get(dep)
+                    .is_some_and(|&d| rejected[d].is_some() && !loaded.contains_key(*dep))
+            });
+            if let Some(dep) = failed_dep {
+                rejected[i] = Some(format!(
+                    "dependency '{dep}' cannot be loaded (see its error); fix or remove it first"
+                ));
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    // Kahn's algorithm, always taking the earliest ready candidate so the order stays close
+    // to the scan order.
+    let mut placed = vec![false; candidates.len()];
+    let mut order_idx = Vec::with_capacity(candidates.len());
+    loop {
+        let next = (0..candidates.len()).find(|&i| {
+            !placed[i]
+                && rejected[i].is_none()
+                && dependency_ids(&candidates[i]).all(|dep| match by_id.get(dep) {
+                    Some(&d) => placed[d] || loaded.contains_key(dep),
+                    None => true,
+                })
+        });
+        let Some(i) = next else {
+            break;
+        };
+        placed[i] = true;
+        order_idx.push(i);
+    }
+
+    let blocked: Vec<usize> = (0..candidates.len())
+        .filter(|&i| !placed[i] && rejected[i].is_none())
+        .collect();
+    // Each blocked candidate is in a cycle or depends on one; there may be several cycles,
+    // so every candidate is reported against the cycle its own dependencies lead to.
+    let mut cycles: Vec<Vec<&str>> = Vec::new();
+    let mut reasons = Vec::with_capacity(blocked.len());
+    for &i in blocked.iter() {
+        let id = candidates[i].id().unwrap_or("?");
+        let known = cycles.iter().position(|c| c.contains(&id));
+        let (k, in_cycle) = match known {
+            Some(k) => (k, true),
+            None => {
+                let cycle = find_cycle(&candidates, &by_id, &placed, &rejected, i);
+                let in_cycle = cycle.contains(&id);
+                let k = cycles
+                    .iter()
+                    .position(|c| cycle.first().is_some_and(|first| c.contains(first)))
+                    .unwrap_or_else(|| {
+                        cycles.push(cycle);
+                        cycles.len() - 1
+                    });
+                (k, in_cycle)
+            }
+        };
+        let cycle = cycles[k].join(" -> ");
+        reasons.push(if in_cycle {
+            format!("dependency cycle {cycle}; remove one of these dependencies")
+        } else {
+            format!("depends on plugins in the dependency cycle {cycle}")
+        });
+    }
+    for (i, reason) in blocked.into_iter().zip(reasons) {
+        rejected[i] = Some(reason);
+    }
+
+    let mut slots: Vec<Option<PluginCandidate>> = candidates.into_iter().map(Some).collect();
+    for (i, reason) in rejected.into_iter().enumerate() {
+        if let Some(message) = reason {
+            let c = slots[i].take().expect("candidate rejected twice");
+            failures.push(PluginLoadError {
+                path: c.path,
+                message,
+            });
+        }
+    }
+    let order = order_idx
+        .into_iter()
+        .map(|i| slots[i].take().expect("candidate ordered twice"))
+        .collect();
+
+    Resolution { order, failures }
+}
+
+#[inline]
+fn dependency_ids(c: &PluginCandidate) -> impl Iterator<Item = &str> {
+    c.manifest
+        .iter()
+        .flat_map(|m| m.dependencies.iter())
+        .map(|d| d.id.as_str())
+}
+
+fn check_engine_api(c: &PluginCandidate) -> Option<String> {
+    let want = c.manifest.as_ref()?.engine_api?;
+    (want > PLUGIN_HOST_API_VERSION).then(|| {
+        format!(
+            "requires engine plugin API >= {want} but this engine provides \
+             {PLUGIN_HOST_API_VERSION}; update the engine or install a build of the plugin \
+             for API {PLUGIN_HOST_API_VERSION}"
+        )
+    })
+}
+
+fn check_dependencies(
+    c: &PluginCandidate,
+    candidates: &[PluginCandidate],
+    by_id: &HashMap<&str, usize>,
+    loaded: &HashMap<String, Option<ApiVersion>>,
+) -> Option<String> {
+    let m = c.manifest.as_ref()?;
+    for dep in m.dependencies.iter() {
+        let want = dep.min_version;
+        if m.id.as_deref() == Some(dep.id.as_str()) {
+            return Some(format!(
+                "depends on itself; remove '{}' from [dependencies]",
+                dep.id
+            ));
+        }
+
+        let (found, source) = if let Some(v) = loaded.get(&dep.id) {
+            (*v, "the loaded plugin")
+        } else if let Some(&d) = by_id.get(dep.id.as_str()) {
+            let version = candidates[d].manifest.as_ref().and_then(|m| m.version);
+            if version.is_none() && want > ApiVersion::new(0, 0, 0) {
+                return Some(format!(
+                    "requires '{}' >= {want} but '{}' declares no version; add \
+                     `version = \"...\"` to it",
+                    dep.id,
+                    candidates[d]
+                        .manifest
+                        .as_ref()
+                        .map(|m| m.path.display().to_string())
+                        .unwrap_or_default()
+                ));
+            }
+            (version, "its manifest")
+        } else {
+            return Some(format!(
+                "requires plugin '{}' >= {want}, which is not installed; copy its library and \
+                 its .{MANIFEST_SUFFIX} (declaring id = \"{}\") into '{}'",
+                dep.id,
+                dep.id,
+                parent_dir(&c.path).display()
+            ));
+        };
+
+        if let Some(have) = found {
+            if have < want {
+                return Some(format!(
+                    "requires plugin '{}' >= {want} but {source} is version {have}; update '{}'",
+                    dep.id, dep.id
+                ));
+            }
+        }
+    }
+    None
+}
+
+/// Ids of a cycle reachable from `start` through unplaced candidates, first id repeated.
+fn find_cycle<'a>(
+    candidates: &'a [PluginCandidate],
+    by_id: &HashMap<&str, usize>,
+    placed: &[bool],
+    rejected: &[Option<String>],
+    start: usize,
+) -> Vec<&'a str> {
+    let mut path: Vec<usize> = vec![start];
+    loop {
+        let cur = *path.last().expect("cycle path is never empty");
+        let next = dependency_ids(&candidates[cur])
+            .filter_map(|dep| by_id.get(dep).copied())
+            .find(|&d| !placed[d] && rejected[d].is_none());
+        let Some(next) = next else {
+            break;
+        };
+        if let Some(pos) = path.iter().position(|&p| p == next) {
+            let mut ids: Vec<&str> = path[pos..]
+                .iter()
+                .map(|&i| candidates[i].id().unwrap_or("?"))
+                .collect();
+            ids.push(candidates[next].id().unwrap_or("?"));
+            return ids;
+        }
+        path.push(next);
+    }
+    path.iter()
+        .map(|&i| candidates[i].id().unwrap_or("?"))
+        .collect()
+}
+
+#[inline]
+fn parent_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new("."))
+}
@@ -0,0 +1,157 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Aggregated call statistics for a single service id.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ServiceCallStats {
+    pub calls: u64,
+    pub errors: u64,
+    /// Calls rejected by a caller rate limit (not forwarded to the service).
+    pub throttled: u64,
+    pub total_latency: Duration,
+    pub max_latency: Duration,
+}
+
+impl ServiceCallStats {
+    #[inline]
+    pub fn mean_latency(&self) -> Duration {
+        if self.calls == 0 {
+            return Duration::ZERO;
+        }
+        let nanos = self.total_latency.as_nanos() / u128::from(self.calls);
+        Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+    }
+
+    /// Fraction of forwarded calls that returned an error, in `[0..1]`.
+    #[inline]
+    pub fn error_rate(&self) -> f32 {
+        if self.calls == 0 {
+            return 0.0;
+        }
+        self.errors as f32 / self.calls as f32
+    }
+}
+
+/// Per-caller quota: at most `max_calls` service calls per `window`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServiceRateLimit {
+    pub max_calls: u32,
+    pub window: Duration,
+}
+
+impl ServiceRateLimit {
+    #[inline]
+    pub const fn new(max_calls: u32, window: Duration) -> Self {
+        Self { max_calls, window }
+    }
+
+    #[inline]
+    pub const fn per_second(max_calls: u32) -> Self {
+        Self::new(max_calls, Duration::from_secs(1))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CallerWindow {
+    started: Instant,
+    used: u32,
+}
+
+/// Host-side bookkeeping for `call_service_v1`.
+///
+/// Limits are keyed by the *calling* plugin id. Calls issued by the host itself
+/// (no current plugin) are never throttled.
+#[derive(Default)]
+pub(crate) struct ServiceMetrics {
+    per_service: HashMap<String, ServiceCallStats>,
+    limits: HashMap<String, ServiceRateLimit>,
+    windows: HashMap<String, CallerWindow>,
+}
+
+impl ServiceMetrics {
+    pub(crate) fn set_limit(&mut self, caller_plugin_id: &str, limit: Option<ServiceRateLimit>) {
+        match limit {
+            Some(l) => {
+                self.limits.insert(caller_plugin_id.to_string(), l);
+            }
+            None => {
+                self.limits.remove(caller_plugin_id);
+            }
+        }
+        self.windows.remove(caller_plugin_id);
+    }
+
+    /// Consumes one unit of the caller quota. Returns an error if the quota is exhausted.
+    pub(crate) fn admit(
+        &mut self,
+        caller_plugin_id: Option<&str>,
+        service_id: &str,
+        now: Instant,
+    ) -> Result<(), String> {
+        let Some(caller) = caller_plugin_id else {
+            return Ok(());
+        };
+        let Some(limit) = self.limits.get(caller).copied() else {
+            return Ok(());
+        };
+
+        let w = self
+            .windows
+            .entry(caller.to_string())
+            .or_insert(CallerWindow { started: now, used: 0 });
+
+        if now.duration_since(w.started) >= limit.window {
+            w.started = now;
+            w.used = 0;
+        }
+
+        if w.used >= limit.max_calls {
+            self.per_service
+                .entry(service_id.to_string())
+                .or_default()
+                .throttled += 1;
+            return Err(format!(
+                "rate limit exceeded: caller='{caller}' service='{service_id}' limit={}/{}ms",
+                limit.max_calls,
+                limit.window.as_millis()
+            ));
+        }
+
+        w.used += 1;
+        Ok(())
+    }
+
+    pub(crate) fn record(&mut self, service_id: &str, latency: Duration, failed: bool) {
+        let s = self.per_service.entry(service_id.to_string()).or_default();
+        s.calls = s.calls.saturating_add(1);
+        if failed {
+            s.errors = s.errors.saturating_add(1);
+        }
+        s.total_latency = s.total_latency.saturating_add(latency);
+        s.max_latency = s.max_latency.max(latency);
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<(String, ServiceCallStats)> {
+        let mut out: Vec<(String, ServiceCallStats)> = self
+            .per_service
+            .iter()
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
+
+    #[inline]
+    pub(crate) fn reset(&mut self) {
+        self.per_service.clear();
+        self.windows.clear();
+    }
+
+    /// Drops quota state owned by an unloaded/disabled plugin.
+    #[inline]
+    pub(crate) fn forget_caller(&mut self, plugin_id: &str) {
+        self.windows.remove(plugin_id);
+    }
+}
@@ -0,0 +1,399 @@
+use super::{
+    BindGroupDesc, BindGroupId, BindGroupLayoutDesc, BindGroupLayoutId, BindingKind, BufferBinding,
+    BufferDesc, BufferId, BufferUsage, MemoryHint, PipelineDesc, PipelineId, PrimitiveTopology,
+    RenderApi, ShaderDesc, ShaderId, ShaderStage, TextureCompression, TextureFormat,
+    VertexAttribute, VertexFormat, VertexLayout,
+};
+use crate::error::{EngineError, EngineResult};
+
+use newengine_assets::material::MATERIAL_TRANSFORM_BYTES;
+use newengine_assets::{
+    AssetBlob, AssetId, AssetState, AssetStore, MaterialAsset, MeshAsset, Model3dReader, ShaderAsset,
+    TextureCompressionSupport, MESH_VERTEX_STRIDE,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Vertex layout of `MeshAsset::vertex_bytes`: position (0), normal (1), uv (2).
+pub fn mesh_vertex_layout() -> VertexLayout {
+    VertexLayout::new(
+        MESH_VERTEX_STRIDE,
+        vec![
+            VertexAttribute::new(0, 0, VertexFormat::Float32x3),
+            VertexAttribute::new(1, 12, VertexFormat::Float32x3),
+            VertexAttribute::new(2, 24, VertexFormat::Float32x2),
+        ],
+    )
+}
+
+/// Compressed formats of the active backend in asset-layer terms; pass to
+/// `newengine_assets::ktx2_to_texture_asset` so Basis textures transcode to what it samples.
+pub fn texture_compression_support(r: &dyn RenderApi) -> TextureCompressionSupport {
+    let TextureCompression { bc, etc2, astc_ldr } = r.texture_compression();
+    TextureCompressionSupport { bc, etc2, astc_ldr }
+}
+
+/// Uploaded mesh. Bounds are in mesh space.
+#[derive(Debug, Clone, Copy)]
+pub struct GpuMesh {
+    pub vb: BufferId,
+    pub ib: BufferId,
+    pub vertex_count: u32,
+    pub index_count: u32,
+    pub center: [f32; 3],
+    pub radius: f32,
+}
+
+/// Uploaded material: pipeline plus a uniform buffer laid out as described on `MaterialAsset`.
+#[derive(Debug, Clone, Copy)]
+pub struct GpuMaterial {
+    pub pipeline: PipelineId,
+    pub bgl: BindGroupLayoutId,
+    pub bg: BindGroupId,
+    pub ubo: BufferId,
+    pub vs: ShaderId,
+    pub fs: ShaderId,
+    pub uniform_size: u64,
+}
+
+impl GpuMaterial {
+    /// Writes the per-draw transform (column-major `mat4`) at the start of the uniform block.
+    #[inline]
+    pub fn write_transform(&self, r: &mut dyn RenderApi, m: &[f32; 16]) -> EngineResult<()> {
+        let bytes: Vec<u8> = m.iter().flat_map(|f| f.to_ne_bytes()).collect();
+        r.write_buffer(self.ubo, 0, &bytes)
+    }
+}
+
+struct Entry<T> {
+    gpu: T,
+    /// Blobs the entry was built from; a new `Arc` in the store means the asset was reloaded.
+    sources: Vec<Arc<AssetBlob>>,
+}
+
+/// GPU copies of mesh and material assets, uploaded on first use and reused across frames.
+///
+/// Entries built from the asset store are rebuilt when one of their source blobs is replaced
+/// (hot reload). Entries uploaded directly are keyed by the caller-provided id.
+pub struct RenderAssetCache {
+    color_format: TextureFormat,
+    depth_format: Option<TextureFormat>,
+    /// Second bind group layout of material pipelines, for instanced drawing.
+    instance_layout: Option<BindGroupLayoutId>,
+    meshes: HashMap<AssetId, Entry<GpuMesh>>,
+    materials: HashMap<AssetId, Entry<GpuMaterial>>,
+}
+
+impl RenderAssetCache {
+    #[inline]
+    pub fn new(color_format: TextureFormat, depth_format: Option<TextureFormat>) -> Self {
+        Self {
+            color_format,
+            depth_format,
+            instance_layout: None,
+            meshes: HashMap::new(),
+            materials: HashMap::new(),
+        }
+    }
+
+    /// Adds `layout` at `INSTANCE_SET` to the pipelines of materials uploaded from now on,
+    /// so they can be drawn by an `InstanceBatcher` (pass its `layout()`).
+    #[inline]
+    pub fn set_instance_layout(&mut self, layout: Option<BindGroupLayoutId>) {
+        self.instance_layout = layout;
+    }
+
+    #[inline]
+    pub fn get_mesh(&self, id: AssetId) -> Option<GpuMesh> {
+        self.meshes.get(&id).map(|e| e.gpu)
+    }
+
+    #[inline]
+    pub fn get_material(&self, id: AssetId) -> Option<GpuMaterial> {
+        self.materials.get(&id).map(|e| e.gpu)
+    }
+
+    /// Mesh for a `kalitech.asset.model3d` asset. `None` while the asset is still loading.
+    pub fn mesh(
+        &mut self,
+        r: &mut dyn RenderApi,
+        store: &AssetStore,
+        id: AssetId,
+    ) -> EngineResult<Option<GpuMesh>> {
+        let Some(blob) = ready_blob(store, id)? else {
+            return Ok(self.get_mesh(id));
+        };
+        if let Some(e) = self.meshes.get(&id) {
+            if Arc::ptr_eq(&e.sources[0], &blob) {
+                return Ok(Some(e.gpu));
+            }
+        }
+
+        let model = Model3dReader::from_blob_parts(&blob.meta_json, &blob.payload)
+            .map_err(|e| EngineError::other(format!("render.cache: model decode: {e}")))?;
+        let mesh = MeshAsset::from_model3d(&model)
+            .map_err(|e| EngineError::other(format!("render.cache: {e}")))?;
+
+        let gpu = self.upload_mesh(r, id, &mesh)?;
+        if let Some(e) = self.meshes.get_mut(&id) {
+            e.sources = vec![blob];
+        }
+        Ok(Some(gpu))
+    }
+
+    /// Material for a `kalitech.asset.material` asset. Its shaders are requested from the store
+    /// on demand; `None` until the material and both shaders are ready.
+    pub fn material(
+        &mut self,
+        r: &mut dyn RenderApi,
+        store: &AssetStore,
+        id: AssetId,
+    ) -> EngineResult<Option<GpuMaterial>> {
+        let Some(blob) = ready_blob(store, id)? else {
+            return Ok(self.get_material(id));
+        };
+        let mat = MaterialAsset::from_json(&blob.payload)
+            .map_err(|e| EngineError::other(format!("render.cache: {e}")))?;
+
+        let load = |path: &str| -> EngineResult<Option<(AssetId, Arc<AssetBlob>)>> {
+            let sid = store
+                .load_path(path)
+                .map_err(|e| EngineError::other(format!("render.cache: shader '{path}': {e}")))?;
+            Ok(ready_blob(store, sid)?.map(|b| (sid, b)))
+        };
+        let (Some((vs_id, vs)), Some((fs_id, fs))) =
+            (load(&mat.vertex_shader)?, load(&mat.fragment_shader)?)
+        else {
+            return Ok(self.get_material(id));
+        };
+
+        let sources = vec![blob, vs, fs];
+        if let Some(e) = self.materials.get(&id) {
+            // With backend hot reload, shader edits are applied in place; only a new
+            // material blob needs a new entry.
+            let watched = if r.shader_hot_reload() { 1 } else { sources.len() };
+            if e.sources
+                .iter()
+                .zip(&sources)
+                .take(watched)
+                .all(|(a, b)| Arc::ptr_eq(a, b))
+            {
+                return Ok(Some(e.gpu));
+            }
+        }
+
+        let to_spirv = |b: &AssetBlob| {
+            ShaderAsset::from_blob(b)
+                .map(|s| s.spirv)
+                .map_err(|e| EngineError::other(format!("render.cache: {e}")))
+        };
+        let vs_spirv = to_spirv(&sources[1])?;
+        let fs_spirv = to_spirv(&sources[2])?;
+
+        let gpu =
+            self.upload_material_inner(r, id, &mat, vs_spirv, fs_spirv, Some((vs_id, fs_id)))?;
+        if let Some(e) = self.materials.get_mut(&id) {
+            e.sources = sources;
+        }
+        Ok(Some(gpu))
+    }
+
+    /// Uploads `mesh` under `id`, replacing any previous entry.
+    pub fn upload_mesh(
+        &mut self,
+        r: &mut dyn RenderApi,
+        id: AssetId,
+        mesh: &MeshAsset,
+    ) -> EngineResult<GpuMesh> {
+        let vbytes = mesh.vertex_bytes();
+        let ibytes = mesh.index_bytes();
+
+        let vb = r.create_buffer(
+            BufferDesc::new(vbytes.len() as u64, BufferUsage::Vertex, MemoryHint::GpuOnly)
+                .with_label("asset_mesh_vb"),
+        )?;
+        r.write_buffer(vb, 0, &vbytes)?;
+
+        let ib = r.create_buffer(
+            BufferDesc::new(ibytes.len() as u64, BufferUsage::Index, MemoryHint::GpuOnly)
+                .with_label("asset_mesh_ib"),
+        )?;
+        r.write_buffer(ib, 0, &ibytes)?;
+
+        let (center, radius) = mesh.bounding_sphere();
+        let gpu = GpuMesh {
+            vb,
+            ib,
+            vertex_count: mesh.vertex_count() as u32,
+            index_count: mesh.index_count() as u32,
+            center,
+            radius,
+        };
+
+        log::info!(
+            target: "render",
+            "render.cache.mesh id={id:?} vertices={} indices={}",
+            gpu.vertex_count,
+            gpu.index_count
+        );
+
+        if let Some(old) = self.meshes.insert(
+            id,
+            Entry {
+                gpu,
+                sources: Vec::new(),
+            },
+        ) {
+            destroy_mesh(r, old.gpu);
+        }
+        Ok(gpu)
+    }
+
+    /// Creates the pipeline and uniform buffer for `mat` under `id`, replacing any previous
+    /// entry. The uniform buffer starts with an identity transform and the material defaults.
+    #[inline]
+    pub fn upload_material(
+        &mut self,
+        r: &mut dyn RenderApi,
+        id: AssetId,
+        mat: &MaterialAsset,
+        vs_spirv: Vec<u32>,
+        fs_spirv: Vec<u32>,
+    ) -> EngineResult<GpuMaterial> {
+        self.upload_material_inner(r, id, mat, vs_spirv, fs_spirv, None)
+    }
+
+    fn upload_material_inner(
+        &mut self,
+        r: &mut dyn RenderApi,
+        id: AssetId,
+        mat: &MaterialAsset,
+        vs_spirv: Vec<u32>,
+        fs_spirv: Vec<u32>,
+        shader_sources: Option<(AssetId, AssetId)>,
+    ) -> EngineResult<GpuMaterial> {
+        let uniforms = mat.uniform_bytes();
+        debug_assert!(uniforms.len() >= MATERIAL_TRANSFORM_BYTES);
+        let uniform_size = uniforms.len() as u64;
+
+        let ubo = r.create_buffer(
+            BufferDesc::new(uniform_size, BufferUsage::Uniform, MemoryHint::CpuToGpu)
+                .with_label("asset_material_ubo"),
+        )?;
+        r.write_buffer(ubo, 0, &uniforms)?;
+
+        let bgl = r.create_bind_group_layout(
+            BindGroupLayoutDesc::new(vec![BindingKind::UniformBuffer])
+                .with_label("asset_material_bgl"),
+        )?;
+        let bg = r.create_bind_group(
+            BindGroupDesc::new(bgl)
+                .with_label("asset_material_bg")
+                .with_uniform0(BufferBinding::new(ubo, 0, uniform_size)),
+        )?;
+
+        let mut vs_desc =
+            ShaderDesc::new(ShaderStage::Vertex, "main", vs_spirv).with_label("asset_material_vs");
+        let mut fs_desc = ShaderDesc::new(ShaderStage::Fragment, "main", fs_spirv)
+            .with_label("asset_material_fs");
+        if let Some((vs_src, fs_src)) = shader_sources {
+            vs_desc = vs_desc.with_source(vs_src);
+            fs_desc = fs_desc.with_source(fs_src);
+        }
+        let vs = r.create_shader(vs_desc)?;
+        let fs = r.create_shader(fs_desc)?;
+
+        let mut desc = PipelineDesc::new(vs, fs, self.color_format)
+            .with_label("asset_material_pipeline")
+            .with_topology(PrimitiveTopology::TriangleList)
+            .with_vertex_layouts(vec![mesh_vertex_layout()])
+            .with_bind_group_layouts(vec![bgl]);
+        if let Some(instances) = self.instance_layout {
+            desc = desc.push_bind_group_layout(instances);
+        }
+        if let (true, Some(depth)) = (mat.depth_test, self.depth_format) {
+            desc = desc.with_depth(depth);
+        }
+        let pipeline = r.create_pipeline(desc)?;
+
+        let gpu = GpuMaterial {
+            pipeline,
+            bgl,
+            bg,
+            ubo,
+            vs,
+            fs,
+            uniform_size,
+        };
+
+        log::info!(
+            target: "render",
+            "render.cache.material id={id:?} uniforms={} textures={}",
+            mat.uniforms.len(),
+            mat.textures.len()
+        );
+
+        if let Some(old) = self.materials.insert(
+            id,
+            Entry {
+                gpu,
+                sources: Vec::new(),
+            },
+        ) {
+            destroy_material(r, old.gpu);
+        }
+        Ok(gpu)
+    }
+
+    /// Destroys the GPU resources cached for `id`.
+    pub fn evict(&mut self, r: &mut dyn RenderApi, id: AssetId) {
+        if let Some(e) = self.meshes.remove(&id) {
+            destroy_mesh(r, e.gpu);
+        }
+        if let Some(e) = self.materials.remove(&id) {
+            destroy_material(r, e.gpu);
+        }
+    }
+
+    /// Drops every entry without destroying it, for use after `RenderDeviceReset` when the
+    /// handles are already invalid. Assets from the store are uploaded again on their next
+    /// request; entries uploaded directly and the instance layout must be set up again.
+    pub fn forget_all(&mut self) {
+        self.meshes.clear();
+        self.materials.clear();
+        self.instance_layout = None;
+    }
+
+    pub fn clear(&mut self, r: &mut dyn RenderApi) {
+        for (_, e) in self.meshes.drain() {
+            destroy_mesh(r, e.gpu);
+        }
+        for (_, e) in self.materials.drain() {
+            destroy_material(r, e.gpu);
+        }
+    }
+}
+
+fn ready_blob(store: &AssetStore, id: AssetId) -> EngineResult<Option<Arc<AssetBlob>>> {
+    match store.state(id) {
+        AssetState::Ready => Ok(store.get_blob(id)),
+        AssetState::Failed(e) => Err(EngineError::other(format!(
+            "render.cache: asset {id:?} failed: {e}"
+        ))),
+        _ => Ok(None),
+    }
+}
+
+fn destroy_mesh(r: &mut dyn RenderApi, m: GpuMesh) {
+    r.destroy_buffer(m.vb);
+    r.destroy_buffer(m.ib);
+}
+
+fn destroy_material(r: &mut dyn RenderApi, m: GpuMaterial) {
+    r.destroy_pipeline(m.pipeline);
+    r.destroy_shader(m.vs);
+    r.destroy_shader(m.fs);
+    r.destroy_bind_group(m.bg);
+    r.destroy_bind_group_layout(m.bgl);
+    r.destroy_buffer(m.ubo);
+}
@@ -0,0 +1,36 @@
+/// CPU copy of a rendered color target (screenshots, visual regression tests).
+///
+/// Pixels are tightly packed RGBA8, top-left origin, regardless of the backend's
+/// swapchain format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameCapture {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl FrameCapture {
+    #[inline]
+    pub fn new(width: u32, height: u32, rgba: Vec<u8>) -> Self {
+        debug_assert_eq!(rgba.len(), width as usize * height as usize * 4);
+        Self {
+            width,
+            height,
+            rgba,
+        }
+    }
+
+    /// Converts BGRA8 readback data in place.
+    pub fn from_bgra(width: u32, height: u32, mut bgra: Vec<u8>) -> Self {
+        for px in bgra.chunks_exact_mut(4) {
+            px.swap(0, 2);
+        }
+        Self::new(width, height, bgra)
+    }
+
+    #[inline]
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [self.rgba[i], self.rgba[i + 1], self.rgba[i + 2], self.rgba[i + 3]]
+    }
+}
@@ -0,0 +1,240 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::render::Color4;
+
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Segments of each circle of [`DebugDraw::sphere`].
+const SPHERE_SEGMENTS: usize = 24;
+
+/// Vertices kept per frame; lines beyond it are dropped until the next flush.
+pub const DEBUG_DRAW_MAX_VERTICES: usize = 1 << 20;
+
+/// Line vertex as uploaded by backends: world position and RGBA8 color (little-endian, as the
+/// UI vertices).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugVertex {
+    pub pos: [f32; 3],
+    pub color: u32,
+}
+
+/// Whether lines are hidden behind scene geometry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DebugDepth {
+    /// Occluded by the scene (where the main pass has a depth buffer).
+    #[default]
+    Tested,
+    /// Always visible, drawn after the depth-tested lines.
+    Always,
+}
+
+/// One frame of debug lines, as handed to the backend.
+#[derive(Debug, Clone, Default)]
+pub struct DebugDrawList {
+    /// Column-major world to clip transform of the main camera.
+    pub view_proj: [f32; 16],
+    /// Line list (two vertices per line) drawn with depth test.
+    pub tested: Vec<DebugVertex>,
+    /// Line list drawn without depth test.
+    pub always: Vec<DebugVertex>,
+    /// Lines dropped because the frame hit [`DEBUG_DRAW_MAX_VERTICES`].
+    pub dropped: u32,
+}
+
+impl DebugDrawList {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tested.is_empty() && self.always.is_empty()
+    }
+
+    /// Lines of both variants.
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        (self.tested.len() + self.always.len()) / 2
+    }
+
+    #[inline]
+    fn push(&mut self, depth: DebugDepth, a: [f32; 3], b: [f32; 3], color: u32) {
+        if self.tested.len() + self.always.len() + 2 > DEBUG_DRAW_MAX_VERTICES {
+            self.dropped = self.dropped.saturating_add(1);
+            return;
+        }
+        let out = match depth {
+            DebugDepth::Tested => &mut self.tested,
+            DebugDepth::Always => &mut self.always,
+        };
+        out.push(DebugVertex { pos: a, color });
+        out.push(DebugVertex { pos: b, color });
+    }
+}
+
+/// Immediate-mode debug lines (gizmos), as a resource.
+///
+/// Any module calls `line`/`ray`/`aabb`/`sphere`/`axes` during the frame; the render module
+/// takes the accumulated lines once per frame and the backend draws them after the main pass,
+/// before the UI. Cheap to clone: clones share the same frame, so systems can keep one.
+/// Calls go to the depth-tested list unless the handle came from
+/// [`DebugDraw::with_depth`]`(DebugDepth::Always)`.
+#[derive(Clone, Default)]
+pub struct DebugDraw {
+    frame: Arc<Mutex<DebugDrawList>>,
+    depth: DebugDepth,
+}
+
+impl std::fmt::Debug for DebugDraw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DebugDraw")
+            .field("lines", &self.frame.lock().line_count())
+            .field("depth", &self.depth)
+            .finish()
+    }
+}
+
+impl DebugDraw {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle onto the same frame that draws with `depth`.
+    #[inline]
+    pub fn with_depth(&self, depth: DebugDepth) -> Self {
+        Self {
+            frame: self.frame.clone(),
+            depth,
+        }
+    }
+
+    #[inline]
+    pub fn depth(&self) -> DebugDepth {
+        self.depth
+    }
+
+    pub fn line(&self, a: [f32; 3], b: [f32; 3], color: Color4) {
+        self.frame.lock().push(self.depth, a, b, pack(color));
+    }
+
+    /// Segment from `origin` to `origin + dir`.
+    pub fn ray(&self, origin: [f32; 3], dir: [f32; 3], color: Color4) {
+        self.line(origin, add(origin, dir), color);
+    }
+
+    /// The 12 edges of an axis-aligned box.
+    pub fn aabb(&self, min: [f32; 3], max: [f32; 3], color: Color4) {
+        let c = |i: usize| {
+            [
+                if i & 1 == 0 { min[0] } else { max[0] },
+                if i & 2 == 0 { min[1] } else { max[1] },
+                if i & 4 == 0 { min[2] } else { max[2] },
+            ]
+        };
+        const EDGES: [(usize, usize); 12] = [
+            (0, 1),
+            (2, 3),
+            (4, 5),
+            (6, 7),
+            (0, 2),
+            (1, 3),
+            (4, 6),
+            (5, 7),
+            (0, 4),
+            (1, 5),
+            (2, 6),
+            (3, 7),
+        ];
+
+        let color = pack(color);
+        let mut frame = self.frame.lock();
+        for (a, b) in EDGES {
+            frame.push(self.depth, c(a), c(b), color);
+        }
+    }
+
+    /// Three orthogonal circles (around X, Y and Z).
+    pub fn sphere(&self, center: [f32; 3], radius: f32, color: Color4) {
+        let color = pack(color);
+        let point = |axis: usize, i: usize| {
+            let t = i as f32 / SPHERE_SEGMENTS as f32 * std::f32::consts::TAU;
+            let (s, c) = t.sin_cos();
+            let mut p = center;
+            p[(axis + 1) % 3] += c * radius;
+            p[(axis + 2) % 3] += s * radius;
+            p
+        };
+
+        let mut frame = self.frame.lock();
+        for axis in 0..3 {
+            for i in 0..SPHERE_SEGMENTS {
+                frame.push(self.depth, point(axis, i), point(axis, i + 1), color);
+            }
+        }
+    }
+
+    /// X (red), Y (green) and Z (blue) axes of length `size`, rotated by the unit quaternion
+    /// `rotation` (`[x, y, z, w]`).
+    pub fn axes(&self, origin: [f32; 3], rotation: [f32; 4], size: f32) {
+        const COLORS: [Color4; 3] = [
+            [1.0, 0.2, 0.2, 1.0],
+            [0.2, 1.0, 0.2, 1.0],
+            [0.3, 0.5, 1.0, 1.0],
+        ];
+
+        let mut frame = self.frame.lock();
+        for (i, color) in COLORS.into_iter().enumerate() {
+            let mut axis = [0.0; 3];
+            axis[i] = size;
+            let tip = add(origin, rotate(rotation, axis));
+            frame.push(self.depth, origin, tip, pack(color));
+        }
+    }
+
+    /// Takes the lines of the frame, leaving it empty. Called by the render module once per
+    /// frame with the camera the lines are seen through.
+    pub fn take(&self, view_proj: [f32; 16]) -> DebugDrawList {
+        let mut frame = self.frame.lock();
+        DebugDrawList {
+            view_proj,
+            tested: std::mem::take(&mut frame.tested),
+            always: std::mem::take(&mut frame.always),
+            dropped: std::mem::take(&mut frame.dropped),
+        }
+    }
+
+    /// Drops the lines of the frame, e.g. when nothing renders them.
+    #[inline]
+    pub fn clear(&self) {
+        let mut frame = self.frame.lock();
+        frame.tested.clear();
+        frame.always.clear();
+        frame.dropped = 0;
+    }
+}
+
+#[inline]
+fn pack(c: Color4) -> u32 {
+    u32::from_le_bytes(c.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8))
+}
+
+#[inline]
+fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+#[inline]
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// `v` rotated by the unit quaternion `q` (`[x, y, z, w]`).
+#[inline]
+fn rotate(q: [f32; 4], v: [f32; 3]) -> [f32; 3] {
+    let u = [q[0], q[1], q[2]];
+    let t = cross(u, v).map(|c| c * 2.0);
+    add(add(v, t.map(|c| c * q[3])), cross(u, t))
+}
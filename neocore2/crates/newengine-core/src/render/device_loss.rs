@@ -0,0 +1,31 @@
+/// What a backend lost while rendering (see `RenderApi::device_lost`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderLoss {
+    /// The main window surface is gone (e.g. the platform recreated the native window).
+    /// Resources survive; only the surface and swapchain are rebuilt.
+    Surface,
+    /// The GPU device was lost (driver reset, TDR, removed adapter). Everything created
+    /// through the `RenderApi` is gone with it.
+    Device,
+}
+
+impl RenderLoss {
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RenderLoss::Surface => "surface",
+            RenderLoss::Device => "device",
+        }
+    }
+}
+
+/// Published on the `EventHub` after the render backend recreated a lost device.
+///
+/// Every render handle created before the reset is invalid. Modules holding GPU resources
+/// drop them without destroying (e.g. `RenderAssetCache::forget_all`) and create them again.
+/// Textures sent with UI draw lists survive; ids from `RenderApi::ui_texture` do not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderDeviceReset {
+    /// Number of device resets so far, starting at 1.
+    pub generation: u32,
+}
@@ -0,0 +1,42 @@
+use super::TransientAliasReport;
+
+/// GPU time spent in one pass of a frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuPassTiming {
+    pub name: &'static str,
+    pub gpu_ms: f32,
+}
+
+/// GPU timings of the most recently resolved frame, measured with timestamp queries.
+///
+/// Backends read queries back once the frame's work has completed, so the stats trail the
+/// frame being recorded by up to the number of frames in flight. The render module inserts
+/// the latest value as a resource every frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GpuFrameStats {
+    /// Backend frame counter of the measured frame.
+    pub frame: u64,
+    /// From the start of the first pass to the end of the last one, gaps included.
+    pub gpu_ms: f32,
+    /// Passes in submission order. Offscreen passes recorded before the frame come first.
+    pub passes: Vec<GpuPassTiming>,
+}
+
+impl GpuFrameStats {
+    /// Sum of all passes named `name`, or `None` if the frame had no such pass.
+    pub fn pass_ms(&self, name: &str) -> Option<f32> {
+        self.passes
+            .iter()
+            .filter(|p| p.name == name)
+            .map(|p| p.gpu_ms)
+            .reduce(|a, b| a + b)
+    }
+}
+
+/// GPU memory telemetry of the render backend. The render module inserts the latest value as
+/// a resource every frame, next to [`GpuFrameStats`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GpuMemoryStats {
+    /// Live aliased render targets, from the backend's memory requirements.
+    pub transient: TransientAliasReport,
+}
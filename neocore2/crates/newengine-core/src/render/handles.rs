@@ -0,0 +1,271 @@
+use crate::error::{EngineError, EngineResult};
+
+use std::fmt;
+use std::hash::Hash;
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Every registry instance (backend instance / backend reset) gets its own realm.
+/// Handles carry the realm they were minted in, so a handle that outlives its backend
+/// can never alias an object of the next one.
+static NEXT_REALM: AtomicU32 = AtomicU32::new(1);
+
+/// Opaque generation-tagged handle payload shared by all render resource ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawHandle {
+    index: NonZeroU32,
+    generation: u32,
+    realm: u32,
+}
+
+impl RawHandle {
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index.get()
+    }
+
+    #[inline]
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    #[inline]
+    pub fn realm(&self) -> u32 {
+        self.realm
+    }
+}
+
+impl fmt::Display for RawHandle {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}v{}@r{}", self.index, self.generation, self.realm)
+    }
+}
+
+/// Typed render handle. Implemented by `BufferId`, `PipelineId`, etc.
+pub trait RenderHandle: Copy + Eq + Hash + fmt::Debug {
+    const KIND: &'static str;
+
+    fn from_raw(raw: RawHandle) -> Self;
+    fn raw(&self) -> RawHandle;
+}
+
+/// Handle validation policy of a render backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleValidation {
+    /// Only the implicit map lookup protects against stale handles (generic errors).
+    Off,
+    /// Every handle passed to the backend is checked; stale/foreign/mistyped handles are
+    /// rejected with a descriptive error naming the creating module.
+    Strict,
+}
+
+impl Default for HandleValidation {
+    #[inline]
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            HandleValidation::Strict
+        } else {
+            HandleValidation::Off
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    generation: u32,
+    alive: bool,
+    kind: &'static str,
+    label: Option<&'static str>,
+    owner: Option<&'static str>,
+}
+
+/// Generation-counting allocator for render handles.
+///
+/// Backends keep one registry for all resource kinds. Freed slots are reused with a bumped
+/// generation, so a handle to a destroyed object never resolves to its successor.
+pub struct HandleRegistry {
+    realm: u32,
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    mode: HandleValidation,
+}
+
+impl Default for HandleRegistry {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HandleRegistry {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            realm: NEXT_REALM.fetch_add(1, Ordering::Relaxed),
+            slots: Vec::new(),
+            free: Vec::new(),
+            mode: HandleValidation::default(),
+        }
+    }
+
+    #[inline]
+    pub fn mode(&self) -> HandleValidation {
+        self.mode
+    }
+
+    #[inline]
+    pub fn set_mode(&mut self, mode: HandleValidation) {
+        self.mode = mode;
+    }
+
+    #[inline]
+    pub fn realm(&self) -> u32 {
+        self.realm
+    }
+
+    /// Number of live handles.
+    #[inline]
+    pub fn live_count(&self) -> usize {
+        self.slots.iter().filter(|s| s.alive).count()
+    }
+
+    /// Mints a new handle. The creating module is taken from the current module scope.
+    pub fn alloc<H: RenderHandle>(&mut self, label: Option<&'static str>) -> H {
+        let owner = crate::module::current_module_id();
+
+        let slot_idx = match self.free.pop() {
+            Some(i) => {
+                let s = &mut self.slots[i as usize];
+                s.alive = true;
+                s.kind = H::KIND;
+                s.label = label;
+                s.owner = owner;
+                i
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 1,
+                    alive: true,
+                    kind: H::KIND,
+                    label,
+                    owner,
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+
+        let generation = self.slots[slot_idx as usize].generation;
+        H::from_raw(RawHandle {
+            index: NonZeroU32::new(slot_idx + 1).expect("render handle index overflow"),
+            generation,
+            realm: self.realm,
+        })
+    }
+
+    /// Retires a handle. Always checked (regardless of mode): releasing a stale handle
+    /// must never free the slot of a live successor.
+    pub fn release<H: RenderHandle>(&mut self, h: H) -> EngineResult<()> {
+        self.check(h, "destroy")?;
+
+        let i = h.raw().index() - 1;
+        let s = &mut self.slots[i as usize];
+        s.alive = false;
+        s.generation = s.generation.wrapping_add(1).max(1);
+        self.free.push(i);
+        Ok(())
+    }
+
+    /// Validates a handle according to the current mode.
+    #[inline]
+    pub fn validate<H: RenderHandle>(&self, h: H, op: &'static str) -> EngineResult<()> {
+        match self.mode {
+            HandleValidation::Off => Ok(()),
+            HandleValidation::Strict => self.check(h, op),
+        }
+    }
+
+    /// Module id that created the handle (if it is still live).
+    pub fn owner<H: RenderHandle>(&self, h: H) -> Option<&'static str> {
+        self.live_slot(h).and_then(|s| s.owner)
+    }
+
+    /// Human-readable description for diagnostics.
+    pub fn describe<H: RenderHandle>(&self, h: H) -> String {
+        let raw = h.raw();
+        match self.slots.get((raw.index() - 1) as usize) {
+            Some(s) => format!(
+                "{} {} (label={} owner={} alive={} current_gen={})",
+                H::KIND,
+                raw,
+                s.label.unwrap_or("<none>"),
+                s.owner.unwrap_or("<host>"),
+                s.alive,
+                s.generation
+            ),
+            None => format!("{} {} (unknown slot)", H::KIND, raw),
+        }
+    }
+
+    /// Invalidates every outstanding handle (e.g. after a device reset).
+    pub fn reset(&mut self) {
+        self.realm = NEXT_REALM.fetch_add(1, Ordering::Relaxed);
+        self.slots.clear();
+        self.free.clear();
+    }
+
+    #[inline]
+    fn live_slot<H: RenderHandle>(&self, h: H) -> Option<&Slot> {
+        let raw = h.raw();
+        if raw.realm != self.realm {
+            return None;
+        }
+        let s = self.slots.get((raw.index() - 1) as usize)?;
+        (s.alive && s.generation == raw.generation && s.kind == H::KIND).then_some(s)
+    }
+
+    fn check<H: RenderHandle>(&self, h: H, op: &'static str) -> EngineResult<()> {
+        let raw = h.raw();
+
+        if raw.realm != self.realm {
+            return Err(EngineError::other(format!(
+                "{op}: foreign {} {} (minted by another backend instance, current realm r{})",
+                H::KIND,
+                raw,
+                self.realm
+            )));
+        }
+
+        let Some(s) = self.slots.get((raw.index() - 1) as usize) else {
+            return Err(EngineError::other(format!(
+                "{op}: unknown {} {} (never allocated)",
+                H::KIND,
+                raw
+            )));
+        };
+
+        if s.kind != H::KIND {
+            return Err(EngineError::other(format!(
+                "{op}: {} {} refers to a {} slot (owner={})",
+                H::KIND,
+                raw,
+                s.kind,
+                s.owner.unwrap_or("<host>")
+            )));
+        }
+
+        if !s.alive || s.generation != raw.generation {
+            return Err(EngineError::other(format!(
+                "{op}: stale {} {} (current_gen={} alive={} last_label={} last_owner={})",
+                H::KIND,
+                raw,
+                s.generation,
+                s.alive,
+                s.label.unwrap_or("<none>"),
+                s.owner.unwrap_or("<host>")
+            )));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,426 @@
+use super::{
+    BindGroupDesc, BindGroupId, BindGroupLayoutDesc, BindGroupLayoutId, BindingKind, BufferBinding,
+    BufferDesc, BufferId, BufferSlice, BufferUsage, DrawIndexedArgs, GpuMaterial, GpuMesh,
+    IndexFormat, MemoryHint, PipelineId, RenderApi,
+};
+use crate::error::EngineResult;
+use crate::trace::{self, TraceKind};
+
+use std::collections::{HashMap, HashSet};
+
+/// Bind group index of the instance buffer in instanced pipelines.
+pub const INSTANCE_SET: u32 = 1;
+
+/// Size of one [`InstanceData`] record in the instance buffer.
+pub const INSTANCE_DATA_BYTES: u64 = 96;
+
+/// Smallest instance buffer the batcher allocates.
+const MIN_INSTANCE_CAPACITY: u64 = 256 * INSTANCE_DATA_BYTES;
+
+/// Per-instance record, laid out for GLSL `std430`:
+///
+/// ```glsl
+/// struct Instance { mat4 model; vec4 custom; uvec4 skin; };
+/// layout(std430, set = 1, binding = 0) readonly buffer Instances { Instance instances[]; };
+/// // instances[gl_InstanceIndex]
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InstanceData {
+    /// Column-major model matrix.
+    pub model: [f32; 16],
+    /// Free per-instance parameters (tint, animation phase, ...).
+    pub custom: [f32; 4],
+    /// Skinned meshes: first joint matrix in the material's joint palette and the joint
+    /// count. Zero for static meshes.
+    pub skin: [u32; 4],
+}
+
+impl InstanceData {
+    #[inline]
+    pub fn new(model: [f32; 16]) -> Self {
+        Self {
+            model,
+            custom: [0.0; 4],
+            skin: [0; 4],
+        }
+    }
+
+    #[inline]
+    pub fn with_custom(mut self, custom: [f32; 4]) -> Self {
+        self.custom = custom;
+        self
+    }
+
+    #[inline]
+    pub fn with_skin(mut self, joint_offset: u32, joint_count: u32) -> Self {
+        self.skin = [joint_offset, joint_count, 0, 0];
+        self
+    }
+
+    #[inline]
+    pub fn is_skinned(&self) -> bool {
+        self.skin[1] > 0
+    }
+
+    fn write_to(&self, out: &mut Vec<u8>) {
+        out.extend(self.model.iter().flat_map(|f| f.to_ne_bytes()));
+        out.extend(self.custom.iter().flat_map(|f| f.to_ne_bytes()));
+        out.extend(self.skin.iter().flat_map(|u| u.to_ne_bytes()));
+    }
+}
+
+/// One mesh drawn with one material.
+#[derive(Debug, Clone, Copy)]
+pub struct Renderable {
+    pub mesh: GpuMesh,
+    pub material: GpuMaterial,
+    pub instance: InstanceData,
+}
+
+/// Renderables of a pass, in submission order. [`InstanceBatcher`] groups the ones sharing a
+/// mesh and material into one instanced draw.
+#[derive(Debug, Clone, Default)]
+pub struct RenderList {
+    items: Vec<Renderable>,
+}
+
+impl RenderList {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn push(&mut self, mesh: GpuMesh, material: GpuMaterial, instance: InstanceData) {
+        self.items.push(Renderable {
+            mesh,
+            material,
+            instance,
+        });
+    }
+
+    #[inline]
+    pub fn push_static(&mut self, mesh: GpuMesh, material: GpuMaterial, model: [f32; 16]) {
+        self.push(mesh, material, InstanceData::new(model));
+    }
+
+    /// Skinned mesh whose joints start at `joint_offset` in the material's joint palette.
+    #[inline]
+    pub fn push_skinned(
+        &mut self,
+        mesh: GpuMesh,
+        material: GpuMaterial,
+        model: [f32; 16],
+        joint_offset: u32,
+        joint_count: u32,
+    ) {
+        self.push(
+            mesh,
+            material,
+            InstanceData::new(model).with_skin(joint_offset, joint_count),
+        );
+    }
+
+    #[inline]
+    pub fn items(&self) -> &[Renderable] {
+        &self.items
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+/// How well the renderables of a frame collapsed into instanced draws.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InstancingStats {
+    pub renderables: u32,
+    pub draw_calls: u32,
+    pub skinned_instances: u32,
+    /// Instances in the largest draw.
+    pub largest_batch: u32,
+    /// Instance data written this frame.
+    pub instance_bytes: u64,
+}
+
+impl InstancingStats {
+    /// Draw calls avoided by instancing.
+    #[inline]
+    pub fn draws_saved(&self) -> u32 {
+        self.renderables.saturating_sub(self.draw_calls)
+    }
+
+    /// Average instances per draw; `1.0` means nothing was batched.
+    #[inline]
+    pub fn instances_per_draw(&self) -> f32 {
+        if self.draw_calls == 0 {
+            0.0
+        } else {
+            self.renderables as f32 / self.draw_calls as f32
+        }
+    }
+
+    #[inline]
+    fn add(&mut self, other: &Self) {
+        self.renderables += other.renderables;
+        self.draw_calls += other.draw_calls;
+        self.skinned_instances += other.skinned_instances;
+        self.largest_batch = self.largest_batch.max(other.largest_batch);
+        self.instance_bytes += other.instance_bytes;
+    }
+}
+
+type BatchKey = (PipelineId, BindGroupId, BufferId, BufferId);
+
+struct Batch {
+    mesh: GpuMesh,
+    material: GpuMaterial,
+    items: Vec<u32>,
+}
+
+#[derive(Clone, Copy)]
+struct InstanceBuffer {
+    buffer: BufferId,
+    bg: BindGroupId,
+    capacity: u64,
+}
+
+/// Draws [`RenderList`]s with automatic instancing.
+///
+/// Renderables sharing a mesh and material become one `draw_indexed` whose instances read
+/// their [`InstanceData`] from a storage buffer bound at [`INSTANCE_SET`]. The material's
+/// uniform transform receives the view-projection matrix instead of a per-draw MVP, so
+/// instanced materials must be created with [`InstanceBatcher::layout`] as their second bind
+/// group layout (see `RenderAssetCache::set_instance_layout`).
+///
+/// Lists drawn within one frame are appended to the same buffer; call
+/// [`InstanceBatcher::begin_frame`] once per frame before the first draw.
+#[derive(Default)]
+pub struct InstanceBatcher {
+    layout: Option<BindGroupLayoutId>,
+    buffer: Option<InstanceBuffer>,
+    /// Buffers outgrown this frame; earlier draws may still reference them.
+    retired: Vec<InstanceBuffer>,
+    /// Bytes already written this frame.
+    cursor: u64,
+    scratch: Vec<u8>,
+    batches: Vec<Batch>,
+    lookup: HashMap<BatchKey, usize>,
+    stats: InstancingStats,
+}
+
+impl InstanceBatcher {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Layout of the instance bind group (one storage buffer), created on first use.
+    pub fn layout(&mut self, r: &mut dyn RenderApi) -> EngineResult<BindGroupLayoutId> {
+        if let Some(l) = self.layout {
+            return Ok(l);
+        }
+        let l = r.create_bind_group_layout(
+            BindGroupLayoutDesc::new(vec![BindingKind::StorageBuffer])
+                .with_label("instance_data_bgl"),
+        )?;
+        self.layout = Some(l);
+        Ok(l)
+    }
+
+    /// Totals of the draws since the last `begin_frame`.
+    #[inline]
+    pub fn stats(&self) -> InstancingStats {
+        self.stats
+    }
+
+    /// Starts a new frame: rewinds the instance buffer and resets the stats.
+    pub fn begin_frame(&mut self, r: &mut dyn RenderApi) {
+        for b in self.retired.drain(..) {
+            destroy_buffer(r, b);
+        }
+        self.cursor = 0;
+        self.stats = InstancingStats::default();
+    }
+
+    /// Draws `list` into the current pass. `view_proj` is column-major.
+    pub fn draw(
+        &mut self,
+        r: &mut dyn RenderApi,
+        list: &RenderList,
+        view_proj: &[f32; 16],
+    ) -> EngineResult<InstancingStats> {
+        if list.is_empty() {
+            return Ok(InstancingStats::default());
+        }
+
+        self.group(list);
+
+        self.scratch.clear();
+        let mut stats = InstancingStats {
+            renderables: list.len() as u32,
+            ..InstancingStats::default()
+        };
+        for batch in &self.batches {
+            for &i in &batch.items {
+                let inst = &list.items[i as usize].instance;
+                inst.write_to(&mut self.scratch);
+                stats.skinned_instances += inst.is_skinned() as u32;
+            }
+        }
+        let bytes = self.scratch.len() as u64;
+        let base = self.cursor;
+        let buf = self.reserve(r, base + bytes)?;
+        r.write_buffer(buf.buffer, base, &self.scratch)?;
+        self.cursor = base + bytes;
+
+        let mut first_instance = (base / INSTANCE_DATA_BYTES) as u32;
+        let mut transform_written: HashSet<BufferId> = HashSet::new();
+        for batch in &self.batches {
+            let count = batch.items.len() as u32;
+            let material = batch.material;
+            let mesh = batch.mesh;
+
+            if transform_written.insert(material.ubo) {
+                material.write_transform(r, view_proj)?;
+            }
+
+            r.set_pipeline(material.pipeline)?;
+            r.set_bind_group(0, material.bg)?;
+            r.set_bind_group(INSTANCE_SET, buf.bg)?;
+            r.set_vertex_buffer(0, BufferSlice::new(mesh.vb, 0))?;
+            r.set_index_buffer(BufferSlice::new(mesh.ib, 0), IndexFormat::U32)?;
+            r.draw_indexed(DrawIndexedArgs {
+                instance_count: count,
+                first_instance,
+                ..DrawIndexedArgs::new(mesh.index_count)
+            })?;
+
+            first_instance += count;
+            stats.draw_calls += 1;
+            stats.largest_batch = stats.largest_batch.max(count);
+        }
+        stats.instance_bytes = bytes;
+
+        trace::record(TraceKind::Marker, "render.instancing", || {
+            format!(
+                "renderables={} draws={} largest={} bytes={}",
+                stats.renderables, stats.draw_calls, stats.largest_batch, stats.instance_bytes
+            )
+        });
+
+        self.stats.add(&stats);
+        Ok(stats)
+    }
+
+    /// Destroys the instance buffer and layout.
+    pub fn destroy(&mut self, r: &mut dyn RenderApi) {
+        for b in self.retired.drain(..).chain(self.buffer.take()) {
+            destroy_buffer(r, b);
+        }
+        if let Some(l) = self.layout.take() {
+            r.destroy_bind_group_layout(l);
+        }
+        self.cursor = 0;
+    }
+
+    /// Drops the instance buffer and layout without destroying them, after a
+    /// `RenderDeviceReset` invalidated their handles.
+    pub fn forget(&mut self) {
+        self.layout = None;
+        self.buffer = None;
+        self.retired.clear();
+        self.cursor = 0;
+    }
+
+    /// Groups renderables by mesh and material, keeping first-appearance order. Batch
+    /// allocations are reused across lists.
+    fn group(&mut self, list: &RenderList) {
+        self.lookup.clear();
+        let mut used = 0usize;
+        for (i, it) in list.items.iter().enumerate() {
+            let key = (it.material.pipeline, it.material.bg, it.mesh.vb, it.mesh.ib);
+            let slot = match self.lookup.get(&key) {
+                Some(&slot) => slot,
+                None => {
+                    let slot = used;
+                    used += 1;
+                    self.lookup.insert(key, slot);
+                    if slot == self.batches.len() {
+                        self.batches.push(Batch {
+                            mesh: it.mesh,
+                            material: it.material,
+                            items: Vec::new(),
+                        });
+                    } else {
+                        let b = &mut self.batches[slot];
+                        b.mesh = it.mesh;
+                        b.material = it.material;
+                        b.items.clear();
+                    }
+                    slot
+                }
+            };
+            self.batches[slot].items.push(i as u32);
+        }
+        self.batches.truncate(used);
+    }
+
+    /// Instance buffer holding at least `needed` bytes. A buffer that is too small is retired
+    /// (not destroyed) since draws recorded earlier this frame still read from it.
+    fn reserve(&mut self, r: &mut dyn RenderApi, needed: u64) -> EngineResult<InstanceBuffer> {
+        if let Some(b) = self.buffer {
+            if b.capacity >= needed {
+                return Ok(b);
+            }
+        }
+
+        let capacity = needed
+            .max(self.buffer.map_or(0, |b| b.capacity * 2))
+            .max(MIN_INSTANCE_CAPACITY)
+            .next_multiple_of(INSTANCE_DATA_BYTES);
+        let layout = self.layout(r)?;
+        let buffer = r.create_buffer(
+            BufferDesc::new(capacity, BufferUsage::Storage, MemoryHint::CpuToGpu)
+                .with_label("instance_data"),
+        )?;
+        let bg = match r.create_bind_group(
+            BindGroupDesc::new(layout)
+                .with_label("instance_data_bg")
+                .with_storage0(BufferBinding::new(buffer, 0, capacity)),
+        ) {
+            Ok(bg) => bg,
+            Err(e) => {
+                r.destroy_buffer(buffer);
+                return Err(e);
+            }
+        };
+
+        let grown = InstanceBuffer {
+            buffer,
+            bg,
+            capacity,
+        };
+        if let Some(old) = self.buffer.replace(grown) {
+            self.retired.push(old);
+        }
+        log::debug!(target: "render", "render.instancing buffer grown to {capacity} bytes");
+        Ok(grown)
+    }
+}
+
+fn destroy_buffer(r: &mut dyn RenderApi, b: InstanceBuffer) {
+    r.destroy_bind_group(b.bg);
+    r.destroy_buffer(b.buffer);
+}
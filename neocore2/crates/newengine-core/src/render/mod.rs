@@ -1,13 +1,57 @@
 use crate::error::{EngineError, EngineResult};
+use crate::host_events::{WindowHandles, WindowId};
 use crate::module::{ApiProvide, ApiVersion};
 
-use newengine_ui::draw::UiDrawList;
+use newengine_assets::AssetId;
+use newengine_ui::draw::{UiDrawList, UiTexId};
 use parking_lot::{Mutex, MutexGuard};
 use std::num::NonZeroU32;
 use std::sync::Arc;
 
+mod asset_cache;
+mod capture;
+mod debug_draw;
+mod device_loss;
+mod gpu_stats;
+mod handles;
+mod instancing;
+mod null;
+mod present;
+mod sprite;
+mod transient;
+mod virtual_texture;
+
+pub use asset_cache::{
+    mesh_vertex_layout, texture_compression_support, GpuMaterial, GpuMesh, RenderAssetCache,
+};
+pub use capture::FrameCapture;
+pub use debug_draw::{DebugDepth, DebugDraw, DebugDrawList, DebugVertex, DEBUG_DRAW_MAX_VERTICES};
+pub use device_loss::{RenderDeviceReset, RenderLoss};
+pub use gpu_stats::{GpuFrameStats, GpuMemoryStats, GpuPassTiming};
+pub use handles::{HandleRegistry, HandleValidation, RawHandle, RenderHandle};
+pub use instancing::{
+    InstanceBatcher, InstanceData, InstancingStats, RenderList, Renderable, INSTANCE_DATA_BYTES,
+    INSTANCE_SET,
+};
+pub use null::{NullRenderApi, NullRenderModule};
+pub use present::{
+    active_latency_mode, active_present_mode, active_swapchain_images, publish_latency_mode,
+    publish_present_mode, publish_swapchain_images, request_latency_mode, request_present_mode,
+    request_swapchain_images, take_latency_mode_request, take_present_mode_request,
+    take_swapchain_images_request, LatencyMode, PresentMode,
+};
+pub use sprite::{Sprite, SpriteBatch, SpriteStats, SpriteTexture, SPRITE_VERTEX_BYTES};
+pub use transient::{
+    AliasedTargets, TransientAliasReport, TransientDesc, TransientGraph, TransientId,
+    TransientPass, TransientPlan, TransientTargets,
+};
+pub use virtual_texture::{
+    PageCoord, PageUpdate, VirtualTexture, VirtualTextureDesc, VirtualTextureLayout,
+    VirtualTextureMode, VirtualTextureStats,
+};
+
 pub const RENDER_API_ID: &str = "render.api";
-pub const RENDER_API_VERSION: ApiVersion = ApiVersion::new(0, 2, 0);
+pub const RENDER_API_VERSION: ApiVersion = ApiVersion::new(0, 3, 0);
 pub const RENDER_API_PROVIDE: ApiProvide = ApiProvide::new(RENDER_API_ID, RENDER_API_VERSION);
 
 pub type Color4 = [f32; 4];
@@ -190,6 +234,9 @@ pub struct ShaderDesc {
     pub stage: ShaderStage,
     pub entry: &'static str,
     pub spirv: Vec<u32>,
+    /// Shader asset the SPIR-V came from. Backends with hot reload watch it and rebuild
+    /// the shader and its pipelines in place when the asset is re-imported.
+    pub source: Option<AssetId>,
 }
 
 impl ShaderDesc {
@@ -200,6 +247,7 @@ impl ShaderDesc {
             stage,
             entry,
             spirv,
+            source: None,
         }
     }
 
@@ -208,6 +256,12 @@ impl ShaderDesc {
         self.label = Some(label);
         self
     }
+
+    #[inline]
+    pub fn with_source(mut self, asset: AssetId) -> Self {
+        self.source = Some(asset);
+        self
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -230,6 +284,8 @@ pub enum VertexFormat {
     Float32x3,
     Float32x4,
     Unorm8x4,
+    /// Read as `uint` in the shader, e.g. a texture array index.
+    Uint32,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -263,6 +319,22 @@ impl VertexLayout {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    #[default]
+    Opaque,
+    /// Straight (non-premultiplied) alpha: `src * a + dst * (1 - a)`.
+    Alpha,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CullMode {
+    None,
+    /// Culls clockwise triangles (counter-clockwise is front-facing).
+    #[default]
+    Back,
+}
+
 #[derive(Debug, Clone)]
 pub struct PipelineDesc {
     pub label: Option<&'static str>,
@@ -273,6 +345,8 @@ pub struct PipelineDesc {
     pub bind_group_layouts: Vec<BindGroupLayoutId>,
     pub color_format: TextureFormat,
     pub depth_format: Option<TextureFormat>,
+    pub blend: BlendMode,
+    pub cull: CullMode,
 }
 
 impl PipelineDesc {
@@ -287,6 +361,8 @@ impl PipelineDesc {
             bind_group_layouts: Vec::new(),
             color_format,
             depth_format: None,
+            blend: BlendMode::Opaque,
+            cull: CullMode::Back,
         }
     }
 
@@ -325,6 +401,55 @@ impl PipelineDesc {
         self.depth_format = Some(depth_format);
         self
     }
+
+    #[inline]
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    #[inline]
+    pub fn with_cull(mut self, cull: CullMode) -> Self {
+        self.cull = cull;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ComputePipelineDesc {
+    pub label: Option<&'static str>,
+    /// `ShaderStage::Compute` shader.
+    pub cs: ShaderId,
+    pub bind_group_layouts: Vec<BindGroupLayoutId>,
+}
+
+impl ComputePipelineDesc {
+    #[inline]
+    pub fn new(cs: ShaderId) -> Self {
+        Self {
+            label: None,
+            cs,
+            bind_group_layouts: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn with_label(mut self, label: &'static str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    #[inline]
+    pub fn with_bind_group_layouts(mut self, layouts: Vec<BindGroupLayoutId>) -> Self {
+        self.bind_group_layouts = layouts;
+        self
+    }
+
+    #[inline]
+    pub fn push_bind_group_layout(mut self, layout: BindGroupLayoutId) -> Self {
+        self.bind_group_layouts.push(layout);
+        self
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -366,82 +491,34 @@ impl RectI32 {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct BufferId(NonZeroU32);
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct TextureId(NonZeroU32);
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct SamplerId(NonZeroU32);
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct ShaderId(NonZeroU32);
+macro_rules! render_handle {
+    ($name:ident, $kind:literal) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name(RawHandle);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct PipelineId(NonZeroU32);
+        impl RenderHandle for $name {
+            const KIND: &'static str = $kind;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct BindGroupLayoutId(NonZeroU32);
+            #[inline]
+            fn from_raw(raw: RawHandle) -> Self {
+                Self(raw)
+            }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct BindGroupId(NonZeroU32);
-
-#[allow(dead_code)]
-impl BufferId {
-    #[inline]
-    pub fn new(v: u32) -> Self {
-        Self(NonZeroU32::new(v).expect("BufferId must be non-zero"))
-    }
-}
-
-#[allow(dead_code)]
-impl TextureId {
-    #[inline]
-    pub(crate) fn new(v: u32) -> Self {
-        Self(NonZeroU32::new(v).expect("TextureId must be non-zero"))
-    }
-}
-
-#[allow(dead_code)]
-impl SamplerId {
-    #[inline]
-    pub(crate) fn new(v: u32) -> Self {
-        Self(NonZeroU32::new(v).expect("SamplerId must be non-zero"))
-    }
-}
-
-#[allow(dead_code)]
-impl ShaderId {
-    #[inline]
-    pub fn new(v: u32) -> Self {
-        Self(NonZeroU32::new(v).expect("ShaderId must be non-zero"))
-    }
-}
-
-#[allow(dead_code)]
-impl PipelineId {
-    #[inline]
-    pub fn new(v: u32) -> Self {
-        Self(NonZeroU32::new(v).expect("PipelineId must be non-zero"))
-    }
-}
-
-#[allow(dead_code)]
-impl BindGroupLayoutId {
-    #[inline]
-    pub fn new(v: u32) -> Self {
-        Self(NonZeroU32::new(v).expect("BindGroupLayoutId must be non-zero"))
-    }
+            #[inline]
+            fn raw(&self) -> RawHandle {
+                self.0
+            }
+        }
+    };
 }
 
-#[allow(dead_code)]
-impl BindGroupId {
-    #[inline]
-    pub fn new(v: u32) -> Self {
-        Self(NonZeroU32::new(v).expect("BindGroupId must be non-zero"))
-    }
-}
+render_handle!(BufferId, "buffer");
+render_handle!(TextureId, "texture");
+render_handle!(SamplerId, "sampler");
+render_handle!(ShaderId, "shader");
+render_handle!(PipelineId, "pipeline");
+render_handle!(BindGroupLayoutId, "bind_group_layout");
+render_handle!(BindGroupId, "bind_group");
 
 #[derive(Debug, Clone, Copy)]
 pub struct BufferSlice {
@@ -503,7 +580,13 @@ pub enum BindingKind {
     Texture2D,
     Sampler,
     UniformBuffer,
+    /// Read-write in compute shaders, readable from vertex and fragment shaders. The n-th
+    /// storage binding of a layout takes `BindGroupDesc::storage{n}`.
     StorageBuffer,
+    /// Array of this many sampled textures, indexed in the shader. Elements start unbound and
+    /// are filled with `RenderApi::write_bind_group_texture`, also while the group is in use.
+    /// Needs `RenderApi::bindless_texture_capacity` of at least the length.
+    TextureArray(u32),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -551,6 +634,9 @@ pub struct BindGroupDesc {
     pub sampler0: Option<SamplerId>,
     pub uniform0: Option<BufferBinding>,
     pub storage0: Option<BufferBinding>,
+    pub storage1: Option<BufferBinding>,
+    pub storage2: Option<BufferBinding>,
+    pub storage3: Option<BufferBinding>,
 }
 
 impl BindGroupDesc {
@@ -563,6 +649,21 @@ impl BindGroupDesc {
             sampler0: None,
             uniform0: None,
             storage0: None,
+            storage1: None,
+            storage2: None,
+            storage3: None,
+        }
+    }
+
+    /// Buffer of the `n`-th `StorageBuffer` binding of the layout.
+    #[inline]
+    pub fn storage(&self, n: usize) -> Option<BufferBinding> {
+        match n {
+            0 => self.storage0,
+            1 => self.storage1,
+            2 => self.storage2,
+            3 => self.storage3,
+            _ => None,
         }
     }
 
@@ -595,11 +696,53 @@ impl BindGroupDesc {
         self.storage0 = Some(b);
         self
     }
+
+    #[inline]
+    pub fn with_storage1(mut self, b: BufferBinding) -> Self {
+        self.storage1 = Some(b);
+        self
+    }
+
+    #[inline]
+    pub fn with_storage2(mut self, b: BufferBinding) -> Self {
+        self.storage2 = Some(b);
+        self
+    }
+
+    #[inline]
+    pub fn with_storage3(mut self, b: BufferBinding) -> Self {
+        self.storage3 = Some(b);
+        self
+    }
+}
+
+/// Block-compressed texture families the active backend can sample.
+/// Drives the transcode target for supercompressed (Basis) textures.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextureCompression {
+    pub bc: bool,
+    pub etc2: bool,
+    pub astc_ldr: bool,
+}
+
+/// Shaders a backend ships precompiled for the engine's own render helpers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuiltinShader {
+    /// [`SpriteBatch`] vertex stage.
+    SpriteVertex,
+    /// [`SpriteBatch`] fragment stage.
+    SpriteFragment,
+    /// [`SpriteBatch`] fragment stage sampling a `BindingKind::TextureArray` at the index
+    /// each vertex carries.
+    SpriteFragmentBindless,
 }
 
 pub trait RenderApi: Send {
     fn begin_frame(&mut self, desc: BeginFrameDesc) -> EngineResult<()>;
     fn set_ui_draw_list(&mut self, ui: UiDrawList);
+    /// Debug lines drawn by the next `end_frame` after the main pass, before the UI. Backends
+    /// without line support drop them.
+    fn set_debug_draw_list(&mut self, _lines: DebugDrawList) {}
     fn end_frame(&mut self) -> EngineResult<()>;
     fn resize(&mut self, width: u32, height: u32) -> EngineResult<()>;
 
@@ -610,6 +753,11 @@ pub trait RenderApi: Send {
     fn create_texture(&mut self, desc: TextureDesc) -> EngineResult<TextureId>;
     fn destroy_texture(&mut self, id: TextureId);
 
+    /// Replaces the texels of one mip of a `Sampled` texture; `data` holds tightly packed rows.
+    fn write_texture(&mut self, _id: TextureId, _mip: u32, _data: &[u8]) -> EngineResult<()> {
+        Err(EngineError::other("texture uploads are not supported by this backend"))
+    }
+
     fn create_sampler(&mut self, desc: SamplerDesc) -> EngineResult<SamplerId>;
     fn destroy_sampler(&mut self, id: SamplerId);
 
@@ -626,6 +774,23 @@ pub trait RenderApi: Send {
     fn create_bind_group(&mut self, desc: BindGroupDesc) -> EngineResult<BindGroupId>;
     fn destroy_bind_group(&mut self, id: BindGroupId);
 
+    /// Longest `BindingKind::TextureArray` the backend supports; 0 without descriptor
+    /// indexing.
+    fn bindless_texture_capacity(&self) -> u32 {
+        0
+    }
+
+    /// Points element `index` of the texture array in `group` at `texture`. The element must
+    /// not be sampled by a frame still in flight.
+    fn write_bind_group_texture(
+        &mut self,
+        _group: BindGroupId,
+        _index: u32,
+        _texture: TextureId,
+    ) -> EngineResult<()> {
+        Err(EngineError::other("texture arrays are not supported by this backend"))
+    }
+
     fn set_viewport(&mut self, vp: Viewport) -> EngineResult<()>;
     fn set_scissor(&mut self, rect: RectI32) -> EngineResult<()>;
 
@@ -637,6 +802,215 @@ pub trait RenderApi: Send {
 
     fn draw(&mut self, args: DrawArgs) -> EngineResult<()>;
     fn draw_indexed(&mut self, args: DrawIndexedArgs) -> EngineResult<()>;
+
+    /// Creates a pipeline around a `ShaderStage::Compute` shader. It is destroyed with
+    /// `destroy_pipeline` and cannot be passed to `set_pipeline`.
+    fn create_compute_pipeline(&mut self, _desc: ComputePipelineDesc) -> EngineResult<PipelineId> {
+        Err(EngineError::other("compute pipelines are not supported by this backend"))
+    }
+
+    /// Selects the pipeline of the following `dispatch` calls. Their bind groups are the ones
+    /// set with `set_bind_group`.
+    fn set_compute_pipeline(&mut self, _pipeline: PipelineId) -> EngineResult<()> {
+        Err(EngineError::other("compute pipelines are not supported by this backend"))
+    }
+
+    /// Runs `x * y * z` workgroups of the compute pipeline. Its storage writes are visible to
+    /// every draw and dispatch issued afterwards, including as vertex or index input.
+    fn dispatch(&mut self, _x: u32, _y: u32, _z: u32) -> EngineResult<()> {
+        Err(EngineError::other("compute pipelines are not supported by this backend"))
+    }
+
+    /// Handle validation policy. Backends without a handle registry report `Off`.
+    fn handle_validation(&self) -> HandleValidation {
+        HandleValidation::Off
+    }
+
+    fn set_handle_validation(&mut self, _mode: HandleValidation) {}
+
+    /// Drops swapchain/surface-sized resources while the engine is suspended.
+    /// They are recreated lazily by the next `begin_frame`.
+    fn release_surface(&mut self) {}
+
+    /// Loss hit by an earlier frame and not repaired yet. Frames are skipped while it is set.
+    fn device_lost(&self) -> Option<RenderLoss> {
+        None
+    }
+
+    /// Repairs what `device_lost` reports, creating the main window surface from `handles`.
+    /// After a device loss every handle created before is invalid; the render module then
+    /// publishes `RenderDeviceReset`. Must be called on the thread that owns the window.
+    fn recover_device(&mut self, _handles: WindowHandles) -> EngineResult<()> {
+        Err(EngineError::other("device recovery is not supported by this backend"))
+    }
+
+    /// Asks the backend to read back the color target of the next completed frame.
+    /// Returns false if the backend cannot capture.
+    fn request_frame_capture(&mut self) -> bool {
+        false
+    }
+
+    /// Takes the capture produced by the last `end_frame` after a request, if any.
+    fn take_frame_capture(&mut self) -> Option<FrameCapture> {
+        None
+    }
+
+    /// Compressed formats enabled on the device. Backends that do not report support
+    /// receive uncompressed RGBA8 textures.
+    fn texture_compression(&self) -> TextureCompression {
+        TextureCompression::default()
+    }
+
+    /// SPIR-V of a built-in shader, for `ShaderDesc::new`. `None` if the backend does not
+    /// ship it.
+    fn builtin_shader(&self, _shader: BuiltinShader) -> Option<Vec<u32>> {
+        None
+    }
+
+    /// True if shaders created with `ShaderDesc::source` are reloaded by the backend,
+    /// keeping their `ShaderId` and every dependent `PipelineId` valid.
+    fn shader_hot_reload(&self) -> bool {
+        false
+    }
+
+    /// Switches the swapchain present mode; the swapchain is recreated before the next frame.
+    /// Unsupported modes degrade along `PresentMode::fallback`.
+    fn set_present_mode(&mut self, _mode: PresentMode) -> EngineResult<()> {
+        Err(EngineError::other("present mode control is not supported by this backend"))
+    }
+
+    /// Present mode of the current swapchain, if the backend reports it.
+    fn present_mode(&self) -> Option<PresentMode> {
+        None
+    }
+
+    /// Requests at least `count` swapchain images (`None`: backend default), clamped to the
+    /// surface limits. The swapchain is recreated before the next frame.
+    fn set_swapchain_image_count(&mut self, _count: Option<u32>) -> EngineResult<()> {
+        Err(EngineError::other("swapchain image count control is not supported by this backend"))
+    }
+
+    /// Image count of the current swapchain, if the backend reports it.
+    fn swapchain_image_count(&self) -> Option<u32> {
+        None
+    }
+
+    /// Trades throughput for input latency; takes effect on the next frame.
+    fn set_latency_mode(&mut self, _mode: LatencyMode) -> EngineResult<()> {
+        Err(EngineError::other("latency mode control is not supported by this backend"))
+    }
+
+    fn latency_mode(&self) -> Option<LatencyMode> {
+        None
+    }
+
+    /// GPU pass timings of the latest frame whose queries have resolved. `None` if the
+    /// device has no timestamp support or no frame has completed yet.
+    fn gpu_frame_stats(&self) -> Option<GpuFrameStats> {
+        None
+    }
+
+    /// GPU memory held by the backend's aliased allocations. `None` if the backend does not
+    /// track it.
+    fn gpu_memory_stats(&self) -> Option<GpuMemoryStats> {
+        None
+    }
+
+    /// Creates a color texture that can be drawn into (see `begin_render_target`) and then
+    /// bound as a `Texture2D` or shown in the UI via `ui_texture`.
+    fn create_render_target(
+        &mut self,
+        _extent: Extent2D,
+        _format: TextureFormat,
+    ) -> EngineResult<TextureId> {
+        Err(EngineError::other("render targets are not supported by this backend"))
+    }
+
+    /// Creates one color render target per `(extent, format)`, all bound to a single memory
+    /// allocation sized for the largest. Drawing into one of them discards the contents of
+    /// the others, so only targets with disjoint lifetimes may share a call (see
+    /// [`TransientGraph`]). Each target is destroyed with `destroy_texture`; the memory goes
+    /// with the last one.
+    fn create_aliased_render_targets(
+        &mut self,
+        _targets: &[(Extent2D, TextureFormat)],
+    ) -> EngineResult<AliasedTargets> {
+        Err(EngineError::other("aliased render targets are not supported by this backend"))
+    }
+
+    /// Redirects subsequent draws into `target`, cleared to `clear_color`, until
+    /// `end_render_target`. Pipelines must be created with the target's color format.
+    fn begin_render_target(&mut self, _target: TextureId, _clear_color: Color4) -> EngineResult<()> {
+        Err(EngineError::other("render targets are not supported by this backend"))
+    }
+
+    /// Finishes the offscreen pass. The target is ready for sampling once this returns.
+    fn end_render_target(&mut self) -> EngineResult<()> {
+        Err(EngineError::other("render targets are not supported by this backend"))
+    }
+
+    /// UI texture showing `target` (egui: `TextureId::User(id.0 as u64)`).
+    /// Valid until the texture is destroyed.
+    fn ui_texture(&mut self, _target: TextureId) -> EngineResult<UiTexId> {
+        Err(EngineError::other("render targets are not supported by this backend"))
+    }
+
+    /// Creates a presentation surface for a secondary OS window. Must be called on the
+    /// thread that owns the window.
+    fn attach_window(
+        &mut self,
+        _window: WindowId,
+        _handles: WindowHandles,
+        _extent: Extent2D,
+    ) -> EngineResult<()> {
+        Err(EngineError::other("secondary windows are not supported by this backend"))
+    }
+
+    /// Destroys the window's surface; call before the OS window is closed.
+    fn detach_window(&mut self, _window: WindowId) {}
+
+    fn resize_window(&mut self, _window: WindowId, _extent: Extent2D) -> EngineResult<()> {
+        Err(EngineError::other("secondary windows are not supported by this backend"))
+    }
+
+    /// Redirects subsequent draws into `window`, cleared to `clear_color`, until
+    /// `end_window`. Pipelines must be created with the window's color format.
+    fn begin_window(&mut self, _window: WindowId, _clear_color: Color4) -> EngineResult<()> {
+        Err(EngineError::other("secondary windows are not supported by this backend"))
+    }
+
+    /// Submits and presents the window pass.
+    fn end_window(&mut self) -> EngineResult<()> {
+        Err(EngineError::other("secondary windows are not supported by this backend"))
+    }
+
+    /// Swapchain color format of an attached window.
+    fn window_format(&self, _window: WindowId) -> Option<TextureFormat> {
+        None
+    }
+
+    /// How `create_virtual_texture` backs its textures on this device, if at all.
+    fn virtual_texture_mode(&self) -> Option<VirtualTextureMode> {
+        None
+    }
+
+    /// Creates a partially resident texture; no page is resident until
+    /// `update_virtual_texture` uploads it. Destroy it with `destroy_texture`.
+    fn create_virtual_texture(
+        &mut self,
+        _desc: VirtualTextureDesc,
+    ) -> EngineResult<(TextureId, VirtualTextureLayout)> {
+        Err(EngineError::other("virtual textures are not supported by this backend"))
+    }
+
+    /// Applies page residency changes, evictions first. Usually driven by [`VirtualTexture`].
+    fn update_virtual_texture(
+        &mut self,
+        _texture: TextureId,
+        _updates: &[PageUpdate<'_>],
+    ) -> EngineResult<()> {
+        Err(EngineError::other("virtual textures are not supported by this backend"))
+    }
 }
 
 #[derive(Clone)]
@@ -0,0 +1,401 @@
+use crate::error::{EngineError, EngineResult};
+use crate::module::{ApiProvide, Module, ModuleCtx};
+use crate::render::{
+    AliasedTargets, BeginFrameDesc, BindGroupDesc, BindGroupId, BindGroupLayoutDesc,
+    BindGroupLayoutId, BufferDesc, BufferId, BufferSlice, BuiltinShader, Color4,
+    ComputePipelineDesc, DebugDraw, DrawArgs, DrawIndexedArgs, Extent2D, HandleRegistry,
+    HandleValidation, IndexFormat, PipelineDesc, PipelineId, RectI32, RenderApi, RenderApiRef,
+    RenderHandle, SamplerDesc, SamplerId, ShaderDesc, ShaderId, TextureDesc, TextureFormat,
+    TextureId, TransientDesc, Viewport, RENDER_API_ID, RENDER_API_PROVIDE,
+};
+
+use newengine_ui::draw::UiDrawList;
+use std::collections::HashMap;
+
+/// Render backend that records nothing. Handles are minted and validated like a real backend
+/// (strict by default), so misuse still surfaces; used for dry runs and headless tooling.
+pub struct NullRenderApi {
+    handles: HandleRegistry,
+    /// Buffer sizes, for bounds checks on writes.
+    buffers: HashMap<BufferId, u64>,
+    extent: Extent2D,
+    in_frame: bool,
+    offscreen: bool,
+    pipeline: Option<PipelineId>,
+    compute_pipeline: Option<PipelineId>,
+    frames: u64,
+}
+
+impl Default for NullRenderApi {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Extent2D::new(1, 1))
+    }
+}
+
+impl NullRenderApi {
+    pub fn new(extent: Extent2D) -> Self {
+        let mut handles = HandleRegistry::new();
+        handles.set_mode(HandleValidation::Strict);
+        Self {
+            handles,
+            buffers: HashMap::new(),
+            extent,
+            in_frame: false,
+            offscreen: false,
+            pipeline: None,
+            compute_pipeline: None,
+            frames: 0,
+        }
+    }
+
+    #[inline]
+    pub fn extent(&self) -> Extent2D {
+        self.extent
+    }
+
+    /// Frames completed with `end_frame`.
+    #[inline]
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Resources created and not yet destroyed.
+    #[inline]
+    pub fn live_resources(&self) -> usize {
+        self.handles.live_count()
+    }
+
+    #[inline]
+    fn check<H: RenderHandle>(&self, h: H, op: &'static str) -> EngineResult<()> {
+        self.handles.validate(h, op)
+    }
+
+    #[inline]
+    fn retire<H: RenderHandle>(&mut self, h: H) -> bool {
+        match self.handles.release(h) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("render.null: {}", e);
+                false
+            }
+        }
+    }
+}
+
+impl RenderApi for NullRenderApi {
+    fn begin_frame(&mut self, _desc: BeginFrameDesc) -> EngineResult<()> {
+        if self.in_frame {
+            return Err(EngineError::other("begin_frame: frame already begun"));
+        }
+        self.in_frame = true;
+        Ok(())
+    }
+
+    fn set_ui_draw_list(&mut self, _ui: UiDrawList) {}
+
+    fn end_frame(&mut self) -> EngineResult<()> {
+        if !self.in_frame {
+            return Err(EngineError::other("end_frame: no frame was begun"));
+        }
+        self.in_frame = false;
+        self.pipeline = None;
+        self.compute_pipeline = None;
+        self.frames += 1;
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> EngineResult<()> {
+        self.extent = Extent2D::new(width, height);
+        Ok(())
+    }
+
+    fn create_buffer(&mut self, desc: BufferDesc) -> EngineResult<BufferId> {
+        if desc.size == 0 {
+            return Err(EngineError::other("create_buffer: size must be > 0"));
+        }
+        let id = self.handles.alloc(desc.label);
+        self.buffers.insert(id, desc.size);
+        Ok(id)
+    }
+
+    fn destroy_buffer(&mut self, id: BufferId) {
+        if self.retire(id) {
+            self.buffers.remove(&id);
+        }
+    }
+
+    fn write_buffer(&mut self, id: BufferId, offset: u64, data: &[u8]) -> EngineResult<()> {
+        self.check(id, "write_buffer")?;
+        let size = self
+            .buffers
+            .get(&id)
+            .copied()
+            .ok_or_else(|| EngineError::other("write_buffer: invalid BufferId"))?;
+        if (offset as u128) + (data.len() as u128) > (size as u128) {
+            return Err(EngineError::other("write_buffer: out of bounds"));
+        }
+        Ok(())
+    }
+
+    fn create_texture(&mut self, desc: TextureDesc) -> EngineResult<TextureId> {
+        if desc.extent.width == 0 || desc.extent.height == 0 {
+            return Err(EngineError::other(
+                "create_texture: extent must be non-zero",
+            ));
+        }
+        Ok(self.handles.alloc(desc.label))
+    }
+
+    fn destroy_texture(&mut self, id: TextureId) {
+        self.retire(id);
+    }
+
+    fn write_texture(&mut self, id: TextureId, _mip: u32, data: &[u8]) -> EngineResult<()> {
+        self.check(id, "write_texture")?;
+        if data.is_empty() {
+            return Err(EngineError::other("write_texture: empty data"));
+        }
+        Ok(())
+    }
+
+    fn create_sampler(&mut self, desc: SamplerDesc) -> EngineResult<SamplerId> {
+        Ok(self.handles.alloc(desc.label))
+    }
+
+    fn destroy_sampler(&mut self, id: SamplerId) {
+        self.retire(id);
+    }
+
+    fn create_shader(&mut self, desc: ShaderDesc) -> EngineResult<ShaderId> {
+        if desc.spirv.is_empty() {
+            return Err(EngineError::other("create_shader: empty SPIR-V"));
+        }
+        Ok(self.handles.alloc(desc.label))
+    }
+
+    fn destroy_shader(&mut self, id: ShaderId) {
+        self.retire(id);
+    }
+
+    fn create_pipeline(&mut self, desc: PipelineDesc) -> EngineResult<PipelineId> {
+        self.check(desc.vs, "create_pipeline")?;
+        self.check(desc.fs, "create_pipeline")?;
+        for layout in desc.bind_group_layouts.iter() {
+            self.check(*layout, "create_pipeline")?;
+        }
+        Ok(self.handles.alloc(desc.label))
+    }
+
+    fn destroy_pipeline(&mut self, id: PipelineId) {
+        self.retire(id);
+    }
+
+    fn create_bind_group_layout(
+        &mut self,
+        desc: BindGroupLayoutDesc,
+    ) -> EngineResult<BindGroupLayoutId> {
+        Ok(self.handles.alloc(desc.label))
+    }
+
+    fn destroy_bind_group_layout(&mut self, id: BindGroupLayoutId) {
+        self.retire(id);
+    }
+
+    fn create_bind_group(&mut self, desc: BindGroupDesc) -> EngineResult<BindGroupId> {
+        self.check(desc.layout, "create_bind_group")?;
+        if let Some(t) = desc.texture0 {
+            self.check(t, "create_bind_group")?;
+        }
+        if let Some(s) = desc.sampler0 {
+            self.check(s, "create_bind_group")?;
+        }
+        let storage = [desc.storage0, desc.storage1, desc.storage2, desc.storage3];
+        for b in std::iter::once(desc.uniform0).chain(storage).flatten() {
+            self.check(b.buffer, "create_bind_group")?;
+        }
+        Ok(self.handles.alloc(desc.label))
+    }
+
+    fn destroy_bind_group(&mut self, id: BindGroupId) {
+        self.retire(id);
+    }
+
+    fn set_viewport(&mut self, _vp: Viewport) -> EngineResult<()> {
+        Ok(())
+    }
+
+    fn set_scissor(&mut self, _rect: RectI32) -> EngineResult<()> {
+        Ok(())
+    }
+
+    fn set_pipeline(&mut self, pipeline: PipelineId) -> EngineResult<()> {
+        self.check(pipeline, "set_pipeline")?;
+        self.pipeline = Some(pipeline);
+        Ok(())
+    }
+
+    fn set_bind_group(&mut self, _index: u32, group: BindGroupId) -> EngineResult<()> {
+        self.check(group, "set_bind_group")
+    }
+
+    fn set_vertex_buffer(&mut self, _slot: u32, slice: BufferSlice) -> EngineResult<()> {
+        self.check(slice.buffer, "set_vertex_buffer")
+    }
+
+    fn set_index_buffer(&mut self, slice: BufferSlice, _format: IndexFormat) -> EngineResult<()> {
+        self.check(slice.buffer, "set_index_buffer")
+    }
+
+    fn draw(&mut self, _args: DrawArgs) -> EngineResult<()> {
+        match self.pipeline {
+            Some(p) => self.check(p, "draw"),
+            None => Err(EngineError::other("draw: no pipeline bound")),
+        }
+    }
+
+    fn draw_indexed(&mut self, _args: DrawIndexedArgs) -> EngineResult<()> {
+        match self.pipeline {
+            Some(p) => self.check(p, "draw_indexed"),
+            None => Err(EngineError::other("draw_indexed: no pipeline bound")),
+        }
+    }
+
+    fn create_compute_pipeline(&mut self, desc: ComputePipelineDesc) -> EngineResult<PipelineId> {
+        self.check(desc.cs, "create_compute_pipeline")?;
+        for layout in desc.bind_group_layouts.iter() {
+            self.check(*layout, "create_compute_pipeline")?;
+        }
+        Ok(self.handles.alloc(desc.label))
+    }
+
+    fn set_compute_pipeline(&mut self, pipeline: PipelineId) -> EngineResult<()> {
+        self.check(pipeline, "set_compute_pipeline")?;
+        self.compute_pipeline = Some(pipeline);
+        Ok(())
+    }
+
+    fn dispatch(&mut self, _x: u32, _y: u32, _z: u32) -> EngineResult<()> {
+        match self.compute_pipeline {
+            Some(p) => self.check(p, "dispatch"),
+            None => Err(EngineError::other("dispatch: no compute pipeline bound")),
+        }
+    }
+
+    #[inline]
+    fn handle_validation(&self) -> HandleValidation {
+        self.handles.mode()
+    }
+
+    #[inline]
+    fn set_handle_validation(&mut self, mode: HandleValidation) {
+        self.handles.set_mode(mode);
+    }
+
+    /// A bare SPIR-V header: enough for `create_shader`, which never compiles anything here.
+    fn builtin_shader(&self, _shader: BuiltinShader) -> Option<Vec<u32>> {
+        Some(vec![0x0723_0203, 0x0001_0000, 0, 1, 0])
+    }
+
+    fn create_render_target(
+        &mut self,
+        extent: Extent2D,
+        _format: TextureFormat,
+    ) -> EngineResult<TextureId> {
+        if extent.width == 0 || extent.height == 0 {
+            return Err(EngineError::other(
+                "create_render_target: extent must be non-zero",
+            ));
+        }
+        Ok(self.handles.alloc(Some("render_target")))
+    }
+
+    /// Separate handles; the sizes are estimates of what an aliasing backend would allocate.
+    fn create_aliased_render_targets(
+        &mut self,
+        targets: &[(Extent2D, TextureFormat)],
+    ) -> EngineResult<AliasedTargets> {
+        let mut out = AliasedTargets::default();
+        for &(extent, format) in targets {
+            out.textures.push(self.create_render_target(extent, format)?);
+            let bytes = TransientDesc::new(extent, format).size_bytes();
+            out.requested_bytes += bytes;
+            out.memory_bytes = out.memory_bytes.max(bytes);
+        }
+        Ok(out)
+    }
+
+    fn begin_render_target(&mut self, target: TextureId, _clear_color: Color4) -> EngineResult<()> {
+        self.check(target, "begin_render_target")?;
+        if self.offscreen {
+            return Err(EngineError::other(
+                "begin_render_target: a render target pass is already open",
+            ));
+        }
+        self.offscreen = true;
+        self.pipeline = None;
+        Ok(())
+    }
+
+    fn end_render_target(&mut self) -> EngineResult<()> {
+        if !self.offscreen {
+            return Err(EngineError::other(
+                "end_render_target: no render target pass is open",
+            ));
+        }
+        self.offscreen = false;
+        self.pipeline = None;
+        Ok(())
+    }
+}
+
+/// Provides a [`NullRenderApi`] as the render API, in place of a GPU backend module.
+#[derive(Default)]
+pub struct NullRenderModule {
+    api: Option<RenderApiRef>,
+}
+
+impl NullRenderModule {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<E: Send + 'static> Module<E> for NullRenderModule {
+    fn id(&self) -> &'static str {
+        "render.null"
+    }
+
+    fn provides(&self) -> &'static [ApiProvide] {
+        &[RENDER_API_PROVIDE]
+    }
+
+    fn init(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let api = RenderApiRef::new(NullRenderApi::default());
+        ctx.resources_mut()
+            .register_api(RENDER_API_ID, api.clone())?;
+        if ctx.resources().get::<DebugDraw>().is_none() {
+            ctx.resources_mut().insert(DebugDraw::new());
+        }
+        log::info!("render.null: ready (no GPU work is performed)");
+        self.api = Some(api);
+        Ok(())
+    }
+
+    fn render(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        // Nothing draws debug lines; keep them from piling up.
+        if let Some(dd) = ctx.resources().get::<DebugDraw>() {
+            dd.clear();
+        }
+        Ok(())
+    }
+
+    fn shutdown(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let _ = ctx
+            .resources_mut()
+            .unregister_api::<RenderApiRef>(RENDER_API_ID);
+        self.api = None;
+        Ok(())
+    }
+}
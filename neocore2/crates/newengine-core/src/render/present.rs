@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, OnceLock};
+
+/// How finished frames reach the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresentMode {
+    /// No vsync; lowest latency, may tear.
+    Immediate,
+    /// Latest frame replaces the queued one; no tearing, frame rate is not capped.
+    Mailbox,
+    /// Classic vsync; always supported.
+    Fifo,
+}
+
+impl PresentMode {
+    /// `true` maps to `Fifo`, `false` to `Immediate`.
+    #[inline]
+    pub fn from_vsync(vsync: bool) -> Self {
+        if vsync {
+            Self::Fifo
+        } else {
+            Self::Immediate
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "immediate" => Some(Self::Immediate),
+            "mailbox" => Some(Self::Mailbox),
+            "fifo" => Some(Self::Fifo),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Immediate => "immediate",
+            Self::Mailbox => "mailbox",
+            Self::Fifo => "fifo",
+        }
+    }
+
+    /// Next mode to try when this one is unsupported; `Fifo` has none.
+    #[inline]
+    pub fn fallback(self) -> Option<Self> {
+        match self {
+            Self::Immediate => Some(Self::Mailbox),
+            Self::Mailbox => Some(Self::Fifo),
+            Self::Fifo => None,
+        }
+    }
+}
+
+/// Frame pacing: throughput (CPU may run a frame ahead of the GPU) or latency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyMode {
+    /// Frames overlap: the CPU records the next frame while the GPU renders the last one.
+    Throughput,
+    /// The swapchain image is acquired only after the frame is recorded, and the CPU waits
+    /// for the GPU after presenting, so input for the next frame is sampled as late as
+    /// possible. Costs throughput when GPU-bound.
+    Low,
+}
+
+impl LatencyMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "throughput" | "normal" => Some(Self::Throughput),
+            "low" | "low_latency" => Some(Self::Low),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Throughput => "throughput",
+            Self::Low => "low",
+        }
+    }
+}
+
+impl Default for LatencyMode {
+    #[inline]
+    fn default() -> Self {
+        Self::Throughput
+    }
+}
+
+#[derive(Default)]
+struct PresentState {
+    active: Option<PresentMode>,
+    pending: Option<PresentMode>,
+    latency_active: Option<LatencyMode>,
+    latency_pending: Option<LatencyMode>,
+    /// Image count of the live swapchain.
+    images_active: Option<u32>,
+    /// `Some(None)` asks for the backend default.
+    images_pending: Option<Option<u32>>,
+}
+
+static PRESENT: OnceLock<Mutex<PresentState>> = OnceLock::new();
+
+#[inline]
+fn present() -> &'static Mutex<PresentState> {
+    PRESENT.get_or_init(|| Mutex::new(PresentState::default()))
+}
+
+/// Asks the render module to switch present mode on its next frame (settings, console).
+pub fn request_present_mode(mode: PresentMode) {
+    if let Ok(mut g) = present().lock() {
+        g.pending = Some(mode);
+    }
+}
+
+/// Taken by the render module once per frame.
+pub fn take_present_mode_request() -> Option<PresentMode> {
+    present().lock().ok().and_then(|mut g| g.pending.take())
+}
+
+/// Mode of the live swapchain, as last published by the render module.
+pub fn active_present_mode() -> Option<PresentMode> {
+    present().lock().ok().and_then(|g| g.active)
+}
+
+pub fn publish_present_mode(mode: Option<PresentMode>) {
+    if let Ok(mut g) = present().lock() {
+        g.active = mode;
+    }
+}
+
+/// Asks the render module to switch latency mode on its next frame.
+pub fn request_latency_mode(mode: LatencyMode) {
+    if let Ok(mut g) = present().lock() {
+        g.latency_pending = Some(mode);
+    }
+}
+
+pub fn take_latency_mode_request() -> Option<LatencyMode> {
+    present().lock().ok().and_then(|mut g| g.latency_pending.take())
+}
+
+pub fn active_latency_mode() -> Option<LatencyMode> {
+    present().lock().ok().and_then(|g| g.latency_active)
+}
+
+pub fn publish_latency_mode(mode: Option<LatencyMode>) {
+    if let Ok(mut g) = present().lock() {
+        g.latency_active = mode;
+    }
+}
+
+/// Asks for a minimum swapchain image count (`None`: backend default). Clamped to what the
+/// surface supports; the swapchain is rebuilt on the next frame.
+pub fn request_swapchain_images(count: Option<u32>) {
+    if let Ok(mut g) = present().lock() {
+        g.images_pending = Some(count);
+    }
+}
+
+pub fn take_swapchain_images_request() -> Option<Option<u32>> {
+    present().lock().ok().and_then(|mut g| g.images_pending.take())
+}
+
+/// Image count of the live swapchain, as last published by the render module.
+pub fn active_swapchain_images() -> Option<u32> {
+    present().lock().ok().and_then(|g| g.images_active)
+}
+
+pub fn publish_swapchain_images(count: Option<u32>) {
+    if let Ok(mut g) = present().lock() {
+        g.images_active = count;
+    }
+}
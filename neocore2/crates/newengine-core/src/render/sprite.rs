@@ -0,0 +1,663 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use super::{
+    BindGroupDesc, BindGroupId, BindGroupLayoutDesc, BindGroupLayoutId, BindingKind, BlendMode,
+    BufferDesc, BufferId, BufferSlice, BufferUsage, BuiltinShader, Color4, CullMode, DrawArgs,
+    Extent2D, FilterMode, MemoryHint, PipelineDesc, PipelineId, RenderApi, SamplerDesc, SamplerId,
+    ShaderDesc, ShaderId, ShaderStage, TextureDesc, TextureFormat, TextureId, TextureUsage,
+    VertexAttribute, VertexFormat, VertexLayout,
+};
+use crate::error::{EngineError, EngineResult};
+use crate::trace::{self, TraceKind};
+
+use newengine_assets::{TextureAsset, TextureKind};
+use std::collections::VecDeque;
+use std::num::NonZeroU32;
+
+/// Size of one sprite vertex: clip-space position, uv, RGBA8 tint, texture table slot.
+pub const SPRITE_VERTEX_BYTES: u64 = 24;
+
+/// Upper bound of the texture table, whatever the backend allows.
+const MAX_TABLE_TEXTURES: u32 = 4096;
+
+const VERTICES_PER_SPRITE: u64 = 6;
+
+/// Smallest vertex buffer the batch allocates.
+const MIN_VERTEX_CAPACITY: u64 = 1024 * VERTICES_PER_SPRITE * SPRITE_VERTEX_BYTES;
+
+/// Texture uploaded by [`SpriteBatch::upload_texture`], ready to be drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpriteTexture {
+    pub texture: TextureId,
+    /// Shared by every texture of the batch when it draws from a texture table.
+    pub bind_group: BindGroupId,
+    /// Index into the texture table; 0 without one.
+    pub slot: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// One textured quad.
+///
+/// Coordinates are in the space of the `view_proj` passed to [`SpriteBatch::draw`]; with
+/// [`SpriteBatch::screen_projection`] that is pixels, origin top-left, y down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite {
+    pub texture: SpriteTexture,
+    /// Texel rectangle `[x, y, w, h]` of the texture to draw.
+    pub region: [u32; 4],
+    pub position: [f32; 2],
+    /// Size of the quad; `None` uses the region size.
+    pub size: Option<[f32; 2]>,
+    /// Point of the quad placed at `position` and rotated around, `[0, 0]` is the top-left
+    /// corner and `[1, 1]` the bottom-right one.
+    pub anchor: [f32; 2],
+    /// Radians, clockwise on screen.
+    pub rotation: f32,
+    /// Multiplied with the texel color.
+    pub tint: Color4,
+    /// Sprites on lower layers are drawn first; equal layers keep submission order.
+    pub layer: i32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl Sprite {
+    #[inline]
+    pub fn new(texture: SpriteTexture, position: [f32; 2]) -> Self {
+        Self {
+            texture,
+            region: [0, 0, texture.width, texture.height],
+            position,
+            size: None,
+            anchor: [0.5, 0.5],
+            rotation: 0.0,
+            tint: [1.0; 4],
+            layer: 0,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    #[inline]
+    pub fn with_region(mut self, x: u32, y: u32, w: u32, h: u32) -> Self {
+        self.region = [x, y, w, h];
+        self
+    }
+
+    #[inline]
+    pub fn with_size(mut self, size: [f32; 2]) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    #[inline]
+    pub fn with_anchor(mut self, anchor: [f32; 2]) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    #[inline]
+    pub fn with_rotation(mut self, radians: f32) -> Self {
+        self.rotation = radians;
+        self
+    }
+
+    #[inline]
+    pub fn with_tint(mut self, tint: Color4) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    #[inline]
+    pub fn with_layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    #[inline]
+    pub fn with_flip(mut self, flip_x: bool, flip_y: bool) -> Self {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+
+    /// Appends the two triangles of the quad, transformed by `view_proj`.
+    fn write_to(&self, view_proj: &[f32; 16], out: &mut Vec<u8>) {
+        let [rx, ry, rw, rh] = self.region.map(|v| v as f32);
+        let [w, h] = self.size.unwrap_or([rw, rh]);
+        let (sin, cos) = self.rotation.sin_cos();
+
+        let tw = self.texture.width.max(1) as f32;
+        let th = self.texture.height.max(1) as f32;
+        let (mut u0, mut u1) = (rx / tw, (rx + rw) / tw);
+        let (mut v0, mut v1) = (ry / th, (ry + rh) / th);
+        if self.flip_x {
+            std::mem::swap(&mut u0, &mut u1);
+        }
+        if self.flip_y {
+            std::mem::swap(&mut v0, &mut v1);
+        }
+
+        let color = self.tint.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        let corner = |fx: f32, fy: f32, u: f32, v: f32| {
+            let lx = (fx - self.anchor[0]) * w;
+            let ly = (fy - self.anchor[1]) * h;
+            let x = self.position[0] + lx * cos - ly * sin;
+            let y = self.position[1] + lx * sin + ly * cos;
+            (transform_point(view_proj, x, y), [u, v])
+        };
+        let tl = corner(0.0, 0.0, u0, v0);
+        let tr = corner(1.0, 0.0, u1, v0);
+        let br = corner(1.0, 1.0, u1, v1);
+        let bl = corner(0.0, 1.0, u0, v1);
+
+        for (pos, uv) in [tl, tr, br, tl, br, bl] {
+            out.extend(pos.iter().chain(uv.iter()).flat_map(|f| f.to_ne_bytes()));
+            out.extend_from_slice(&color);
+            out.extend_from_slice(&self.texture.slot.to_ne_bytes());
+        }
+    }
+}
+
+/// Counters of the sprite draws since the last `begin_frame`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpriteStats {
+    pub sprites: u32,
+    pub draw_calls: u32,
+    /// Vertex data written this frame.
+    pub vertex_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SpritePipeline {
+    vs: ShaderId,
+    fs: ShaderId,
+    layout: BindGroupLayoutId,
+    sampler: SamplerId,
+    pipeline: PipelineId,
+}
+
+/// Texture array bound once for all sprites, on backends with bindless textures.
+#[derive(Debug)]
+struct TextureTable {
+    bind_group: BindGroupId,
+    capacity: u32,
+    /// Slots below `next` have been handed out at least once.
+    next: u32,
+    /// Released slots, oldest first. Reusing the oldest one gives frames still in flight the
+    /// most time to finish with the texture it held.
+    free: VecDeque<u32>,
+}
+
+impl TextureTable {
+    fn alloc(&mut self) -> Option<u32> {
+        if self.next < self.capacity {
+            self.next += 1;
+            return Some(self.next - 1);
+        }
+        self.free.pop_front()
+    }
+
+    #[inline]
+    fn release(&mut self, slot: u32) {
+        self.free.push_back(slot);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct VertexBuffer {
+    buffer: BufferId,
+    capacity: u64,
+}
+
+/// Batched 2D sprite renderer on top of [`RenderApi`].
+///
+/// Sprites pushed during a frame are sorted by layer, turned into quads on the CPU and drawn
+/// from a single dynamic vertex buffer, one draw per run of sprites sharing a texture. When the
+/// backend supports bindless textures ([`RenderApi::bindless_texture_capacity`]) all textures
+/// live in one texture table indexed per vertex, so a frame of sprites is a single draw. The
+/// pipeline uses straight alpha blending and no depth, so call [`SpriteBatch::draw`] inside the
+/// pass the sprites belong to, after opaque geometry.
+///
+/// Shaders come from [`RenderApi::builtin_shader`]; GPU objects are created on first use.
+/// Call [`SpriteBatch::begin_frame`] once per frame before the first draw.
+pub struct SpriteBatch {
+    color_format: TextureFormat,
+    filter: FilterMode,
+    pipeline: Option<SpritePipeline>,
+    table: Option<TextureTable>,
+    buffer: Option<VertexBuffer>,
+    /// Buffers outgrown this frame; earlier draws may still reference them.
+    retired: Vec<VertexBuffer>,
+    /// Bytes already written this frame.
+    cursor: u64,
+    sprites: Vec<Sprite>,
+    scratch: Vec<u8>,
+    stats: SpriteStats,
+}
+
+impl SpriteBatch {
+    /// Batch drawing into `color_format` targets with linear filtering.
+    #[inline]
+    pub fn new(color_format: TextureFormat) -> Self {
+        Self {
+            color_format,
+            filter: FilterMode::Linear,
+            pipeline: None,
+            table: None,
+            buffer: None,
+            retired: Vec::new(),
+            cursor: 0,
+            sprites: Vec::new(),
+            scratch: Vec::new(),
+            stats: SpriteStats::default(),
+        }
+    }
+
+    /// Texture filtering, e.g. `Nearest` for pixel art. Must be set before first use.
+    #[inline]
+    pub fn with_filter(mut self, filter: FilterMode) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Projection mapping pixels of a `extent` target to clip space: origin top-left, y down.
+    pub fn screen_projection(extent: Extent2D) -> [f32; 16] {
+        let w = extent.width.max(1) as f32;
+        let h = extent.height.max(1) as f32;
+        [
+            2.0 / w,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            2.0 / h,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+            0.0,
+            -1.0,
+            -1.0,
+            0.0,
+            1.0,
+        ]
+    }
+
+    /// Totals of the draws since the last `begin_frame`.
+    #[inline]
+    pub fn stats(&self) -> SpriteStats {
+        self.stats
+    }
+
+    /// Sprites pushed since the last draw.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    /// Uploads an RGBA8 2D texture with all of its mips.
+    pub fn upload_texture(
+        &mut self,
+        r: &mut dyn RenderApi,
+        asset: &TextureAsset,
+    ) -> EngineResult<SpriteTexture> {
+        let desc = asset.desc;
+        if desc.kind != TextureKind::Tex2D
+            || desc.layers != 1
+            || desc.format != newengine_assets::TextureFormat::Rgba8Unorm
+        {
+            return Err(EngineError::other(format!(
+                "sprite textures must be single-layer RGBA8 2D textures (got {:?} {:?} layers={})",
+                desc.kind, desc.format, desc.layers
+            )));
+        }
+        let mips = NonZeroU32::new(asset.mips.len() as u32)
+            .ok_or_else(|| EngineError::other("sprite texture has no mips"))?;
+        let pipe = self.pipeline(r)?;
+
+        let texture = r.create_texture(
+            TextureDesc::new(
+                Extent2D::new(desc.width, desc.height),
+                TextureFormat::Rgba8Unorm,
+                TextureUsage::Sampled,
+            )
+            .with_label("sprite_texture")
+            .with_mips(mips),
+        )?;
+        let uploaded = asset.mips.iter().enumerate().try_for_each(|(i, mip)| {
+            let data = mip
+                .subresources
+                .first()
+                .ok_or_else(|| EngineError::other("sprite texture mip has no data"))?;
+            r.write_texture(texture, i as u32, &data.data)
+        });
+        let bound = uploaded.and_then(|()| match self.table.as_mut() {
+            Some(table) => {
+                let slot = table
+                    .alloc()
+                    .ok_or_else(|| EngineError::other("sprite texture table is full"))?;
+                match r.write_bind_group_texture(table.bind_group, slot, texture) {
+                    Ok(()) => Ok((table.bind_group, slot)),
+                    Err(e) => {
+                        table.release(slot);
+                        Err(e)
+                    }
+                }
+            }
+            None => r
+                .create_bind_group(
+                    BindGroupDesc::new(pipe.layout)
+                        .with_label("sprite_texture_bg")
+                        .with_texture0(texture)
+                        .with_sampler0(pipe.sampler),
+                )
+                .map(|bg| (bg, 0)),
+        });
+        let (bind_group, slot) = match bound {
+            Ok(b) => b,
+            Err(e) => {
+                r.destroy_texture(texture);
+                return Err(e);
+            }
+        };
+
+        log::debug!(
+            target: "render",
+            "render.sprite texture={texture:?} size={}x{} mips={} slot={slot}",
+            desc.width,
+            desc.height,
+            mips
+        );
+        Ok(SpriteTexture {
+            texture,
+            bind_group,
+            slot,
+            width: desc.width,
+            height: desc.height,
+        })
+    }
+
+    /// Destroys a texture from [`SpriteBatch::upload_texture`]. Sprites still using it must not
+    /// be drawn afterwards.
+    pub fn destroy_texture(&mut self, r: &mut dyn RenderApi, texture: SpriteTexture) {
+        self.sprites.retain(|s| s.texture != texture);
+        match self.table.as_mut() {
+            Some(table) if table.bind_group == texture.bind_group => table.release(texture.slot),
+            _ => r.destroy_bind_group(texture.bind_group),
+        }
+        r.destroy_texture(texture.texture);
+    }
+
+    /// Starts a new frame: rewinds the vertex buffer and resets the stats.
+    pub fn begin_frame(&mut self, r: &mut dyn RenderApi) {
+        for b in self.retired.drain(..) {
+            r.destroy_buffer(b.buffer);
+        }
+        self.cursor = 0;
+        self.stats = SpriteStats::default();
+    }
+
+    #[inline]
+    pub fn push(&mut self, sprite: Sprite) {
+        self.sprites.push(sprite);
+    }
+
+    /// Draws the pushed sprites into the current pass and clears them. `view_proj` is
+    /// column-major.
+    pub fn draw(
+        &mut self,
+        r: &mut dyn RenderApi,
+        view_proj: &[f32; 16],
+    ) -> EngineResult<SpriteStats> {
+        if self.sprites.is_empty() {
+            return Ok(SpriteStats::default());
+        }
+        let pipe = self.pipeline(r)?;
+
+        self.sprites.sort_by_key(|s| s.layer);
+        self.scratch.clear();
+        for s in &self.sprites {
+            s.write_to(view_proj, &mut self.scratch);
+        }
+
+        let bytes = self.scratch.len() as u64;
+        let base = self.cursor;
+        let buf = self.reserve(r, base + bytes)?;
+        r.write_buffer(buf.buffer, base, &self.scratch)?;
+        self.cursor = base + bytes;
+
+        r.set_pipeline(pipe.pipeline)?;
+        r.set_vertex_buffer(0, BufferSlice::new(buf.buffer, 0))?;
+
+        let mut stats = SpriteStats {
+            sprites: self.sprites.len() as u32,
+            vertex_bytes: bytes,
+            ..SpriteStats::default()
+        };
+        let mut first_vertex = (base / SPRITE_VERTEX_BYTES) as u32;
+        for run in self
+            .sprites
+            .chunk_by(|a, b| a.texture.bind_group == b.texture.bind_group)
+        {
+            let vertex_count = run.len() as u32 * VERTICES_PER_SPRITE as u32;
+            r.set_bind_group(0, run[0].texture.bind_group)?;
+            r.draw(DrawArgs {
+                first_vertex,
+                ..DrawArgs::new(vertex_count)
+            })?;
+            first_vertex += vertex_count;
+            stats.draw_calls += 1;
+        }
+        self.sprites.clear();
+
+        trace::record(TraceKind::Marker, "render.sprites", || {
+            format!(
+                "sprites={} draws={} bytes={}",
+                stats.sprites, stats.draw_calls, stats.vertex_bytes
+            )
+        });
+
+        self.stats.sprites += stats.sprites;
+        self.stats.draw_calls += stats.draw_calls;
+        self.stats.vertex_bytes += stats.vertex_bytes;
+        Ok(stats)
+    }
+
+    /// Destroys the vertex buffers, texture table and pipeline. Uploaded textures are left to
+    /// the caller.
+    pub fn destroy(&mut self, r: &mut dyn RenderApi) {
+        for b in self.retired.drain(..).chain(self.buffer.take()) {
+            r.destroy_buffer(b.buffer);
+        }
+        if let Some(t) = self.table.take() {
+            r.destroy_bind_group(t.bind_group);
+        }
+        if let Some(p) = self.pipeline.take() {
+            r.destroy_pipeline(p.pipeline);
+            r.destroy_sampler(p.sampler);
+            r.destroy_bind_group_layout(p.layout);
+            r.destroy_shader(p.fs);
+            r.destroy_shader(p.vs);
+        }
+        self.sprites.clear();
+        self.cursor = 0;
+    }
+
+    fn pipeline(&mut self, r: &mut dyn RenderApi) -> EngineResult<SpritePipeline> {
+        if let Some(p) = self.pipeline {
+            return Ok(p);
+        }
+        let capacity = r.bindless_texture_capacity().min(MAX_TABLE_TEXTURES);
+        let table_spv = (capacity > 0)
+            .then(|| r.builtin_shader(BuiltinShader::SpriteFragmentBindless))
+            .flatten();
+        let bindless = table_spv.is_some();
+        let (Some(vs_spv), Some(fs_spv)) = (
+            r.builtin_shader(BuiltinShader::SpriteVertex),
+            table_spv.or_else(|| r.builtin_shader(BuiltinShader::SpriteFragment)),
+        ) else {
+            return Err(EngineError::other(
+                "sprite shaders are not provided by this backend",
+            ));
+        };
+
+        let vs = r.create_shader(
+            ShaderDesc::new(ShaderStage::Vertex, "main", vs_spv).with_label("sprite_vs"),
+        )?;
+        let fs = match r.create_shader(
+            ShaderDesc::new(ShaderStage::Fragment, "main", fs_spv).with_label("sprite_fs"),
+        ) {
+            Ok(fs) => fs,
+            Err(e) => {
+                r.destroy_shader(vs);
+                return Err(e);
+            }
+        };
+        let texture_binding = if bindless {
+            BindingKind::TextureArray(capacity)
+        } else {
+            BindingKind::Texture2D
+        };
+        let layout = match r.create_bind_group_layout(
+            BindGroupLayoutDesc::new(vec![texture_binding, BindingKind::Sampler])
+                .with_label("sprite_bgl"),
+        ) {
+            Ok(l) => l,
+            Err(e) => {
+                r.destroy_shader(fs);
+                r.destroy_shader(vs);
+                return Err(e);
+            }
+        };
+        let sampler = match r.create_sampler(
+            SamplerDesc {
+                min_filter: self.filter,
+                mag_filter: self.filter,
+                ..SamplerDesc::default()
+            }
+            .with_label("sprite_sampler"),
+        ) {
+            Ok(s) => s,
+            Err(e) => {
+                r.destroy_bind_group_layout(layout);
+                r.destroy_shader(fs);
+                r.destroy_shader(vs);
+                return Err(e);
+            }
+        };
+        let pipeline = match r.create_pipeline(
+            PipelineDesc::new(vs, fs, self.color_format)
+                .with_label("sprite_pipeline")
+                .with_vertex_layouts(vec![sprite_vertex_layout()])
+                .push_bind_group_layout(layout)
+                .with_blend(BlendMode::Alpha)
+                .with_cull(CullMode::None),
+        ) {
+            Ok(p) => p,
+            Err(e) => {
+                r.destroy_sampler(sampler);
+                r.destroy_bind_group_layout(layout);
+                r.destroy_shader(fs);
+                r.destroy_shader(vs);
+                return Err(e);
+            }
+        };
+
+        if bindless {
+            let table = r.create_bind_group(
+                BindGroupDesc::new(layout)
+                    .with_label("sprite_texture_table")
+                    .with_sampler0(sampler),
+            );
+            match table {
+                Ok(bind_group) => {
+                    self.table = Some(TextureTable {
+                        bind_group,
+                        capacity,
+                        next: 0,
+                        free: VecDeque::new(),
+                    });
+                }
+                Err(e) => {
+                    r.destroy_pipeline(pipeline);
+                    r.destroy_sampler(sampler);
+                    r.destroy_bind_group_layout(layout);
+                    r.destroy_shader(fs);
+                    r.destroy_shader(vs);
+                    return Err(e);
+                }
+            }
+            log::debug!(target: "render", "render.sprite texture_table capacity={capacity}");
+        }
+
+        let p = SpritePipeline {
+            vs,
+            fs,
+            layout,
+            sampler,
+            pipeline,
+        };
+        self.pipeline = Some(p);
+        Ok(p)
+    }
+
+    /// Vertex buffer holding at least `needed` bytes. A buffer that is too small is retired
+    /// (not destroyed) since draws recorded earlier this frame still read from it.
+    fn reserve(&mut self, r: &mut dyn RenderApi, needed: u64) -> EngineResult<VertexBuffer> {
+        if let Some(b) = self.buffer {
+            if b.capacity >= needed {
+                return Ok(b);
+            }
+        }
+
+        let capacity = needed
+            .max(self.buffer.map_or(0, |b| b.capacity * 2))
+            .max(MIN_VERTEX_CAPACITY)
+            .next_multiple_of(VERTICES_PER_SPRITE * SPRITE_VERTEX_BYTES);
+        let buffer = r.create_buffer(
+            BufferDesc::new(capacity, BufferUsage::Vertex, MemoryHint::CpuToGpu)
+                .with_label("sprite_vertices"),
+        )?;
+
+        let grown = VertexBuffer { buffer, capacity };
+        if let Some(old) = self.buffer.replace(grown) {
+            self.retired.push(old);
+        }
+        log::debug!(target: "render", "render.sprite buffer grown to {capacity} bytes");
+        Ok(grown)
+    }
+}
+
+/// Vertex layout of sprite quads: position (0), uv (1), tint (2), texture table slot (3).
+fn sprite_vertex_layout() -> VertexLayout {
+    VertexLayout::new(
+        SPRITE_VERTEX_BYTES as u32,
+        vec![
+            VertexAttribute::new(0, 0, VertexFormat::Float32x2),
+            VertexAttribute::new(1, 8, VertexFormat::Float32x2),
+            VertexAttribute::new(2, 16, VertexFormat::Unorm8x4),
+            VertexAttribute::new(3, 20, VertexFormat::Uint32),
+        ],
+    )
+}
+
+/// `view_proj * (x, y, 0, 1)`, perspective-divided, as clip-space xy.
+#[inline]
+fn transform_point(m: &[f32; 16], x: f32, y: f32) -> [f32; 2] {
+    let cx = m[0] * x + m[4] * y + m[12];
+    let cy = m[1] * x + m[5] * y + m[13];
+    let w = m[3] * x + m[7] * y + m[15];
+    if w != 0.0 && w != 1.0 {
+        [cx / w, cy / w]
+    } else {
+        [cx, cy]
+    }
+}
@@ -0,0 +1,353 @@
+//! Transient render targets with aliased memory.
+//!
+//! A frame declares its intermediate targets (shadow maps, post-process ping-pong buffers)
+//! and the passes that draw into and sample them, in execution order. [`TransientGraph::compile`]
+//! finds each target's first and last pass and packs targets whose lifetimes do not overlap
+//! into alias groups. [`TransientTargets`] backs every group with one memory allocation
+//! (see [`RenderApi::create_aliased_render_targets`]), keeps it across frames and runs the
+//! graph's passes, so the pass list that decided the lifetimes is the one executed.
+//!
+//! Targets of one group share memory: a target's contents are defined only from its first to
+//! its last pass, and are discarded once another target of the group is drawn into.
+
+use super::{Color4, Extent2D, RenderApi, TextureFormat, TextureId};
+use crate::error::{EngineError, EngineResult};
+
+#[derive(Debug, Clone, Copy)]
+pub struct TransientDesc {
+    pub extent: Extent2D,
+    /// Color format; transient targets are render targets, so depth formats are rejected.
+    pub format: TextureFormat,
+}
+
+impl TransientDesc {
+    #[inline]
+    pub const fn new(extent: Extent2D, format: TextureFormat) -> Self {
+        Self { extent, format }
+    }
+
+    #[inline]
+    fn same(&self, other: &Self) -> bool {
+        self.format == other.format
+            && self.extent.width == other.extent.width
+            && self.extent.height == other.extent.height
+    }
+
+    /// Estimated footprint, without backend padding. Backends report the real sizes.
+    pub fn size_bytes(&self) -> u64 {
+        let texel = match self.format {
+            TextureFormat::Rgba16Float => 8,
+            TextureFormat::Rgba8Unorm
+            | TextureFormat::Bgra8Unorm
+            | TextureFormat::Depth24Stencil8
+            | TextureFormat::Depth32Float => 4,
+        };
+        self.extent.width as u64 * self.extent.height as u64 * texel
+    }
+}
+
+/// Target declared in a [`TransientGraph`]; valid only for that graph and its plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TransientId(u32);
+
+#[derive(Debug, Clone)]
+struct TransientResource {
+    label: &'static str,
+    desc: TransientDesc,
+    /// First and last pass that touch the target; `None` if no pass does.
+    lifetime: Option<(u32, u32)>,
+}
+
+/// Pass of a [`TransientGraph`]: draws into `target` and samples `reads`.
+#[derive(Debug, Clone)]
+pub struct TransientPass {
+    pub name: &'static str,
+    pub target: TransientId,
+    pub clear_color: Color4,
+    pub reads: Vec<TransientId>,
+}
+
+/// Per-frame declaration of transient targets and the passes using them, in execution order.
+#[derive(Debug, Clone, Default)]
+pub struct TransientGraph {
+    resources: Vec<TransientResource>,
+    passes: Vec<TransientPass>,
+}
+
+impl TransientGraph {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create(&mut self, label: &'static str, desc: TransientDesc) -> TransientId {
+        self.resources.push(TransientResource {
+            label,
+            desc,
+            lifetime: None,
+        });
+        TransientId(self.resources.len() as u32 - 1)
+    }
+
+    /// Appends a pass drawing into `target` (cleared to `clear_color`) that samples `reads`;
+    /// returns its index.
+    pub fn add_pass(
+        &mut self,
+        name: &'static str,
+        target: TransientId,
+        clear_color: Color4,
+        reads: &[TransientId],
+    ) -> u32 {
+        let pass = self.passes.len() as u32;
+        for id in std::iter::once(&target).chain(reads) {
+            if let Some(r) = self.resources.get_mut(id.0 as usize) {
+                r.lifetime = Some(match r.lifetime {
+                    Some((first, _)) => (first, pass),
+                    None => (pass, pass),
+                });
+            }
+        }
+        self.passes.push(TransientPass {
+            name,
+            target,
+            clear_color,
+            reads: reads.to_vec(),
+        });
+        pass
+    }
+
+    #[inline]
+    pub fn passes(&self) -> &[TransientPass] {
+        &self.passes
+    }
+
+    /// Assigns every used target to an alias group. A group takes a target once the previous
+    /// member's last pass is behind the new target's first pass; descriptions may differ,
+    /// the group's memory is sized for its largest member.
+    pub fn compile(&self) -> TransientPlan {
+        let mut order: Vec<usize> = (0..self.resources.len())
+            .filter(|&i| self.resources[i].lifetime.is_some())
+            .collect();
+        order.sort_by_key(|&i| self.resources[i].lifetime.map(|(first, _)| first));
+
+        let mut groups: Vec<Vec<TransientDesc>> = Vec::new();
+        let mut group_free_after: Vec<u32> = Vec::new();
+        let mut slot_of = vec![None; self.resources.len()];
+        let mut report = TransientAliasReport::default();
+
+        for i in order {
+            let r = &self.resources[i];
+            let Some((first, last)) = r.lifetime else {
+                continue;
+            };
+
+            let group = match (0..groups.len()).find(|&g| group_free_after[g] < first) {
+                Some(g) => {
+                    group_free_after[g] = last;
+                    groups[g].push(r.desc);
+                    g
+                }
+                None => {
+                    groups.push(vec![r.desc]);
+                    group_free_after.push(last);
+                    groups.len() - 1
+                }
+            };
+
+            slot_of[i] = Some((group as u32, groups[group].len() as u32 - 1));
+            report.resources += 1;
+            report.requested_bytes += r.desc.size_bytes();
+            log::trace!(
+                "render.transient '{}' passes {first}..={last} -> group {group}",
+                r.label
+            );
+        }
+
+        report.groups = groups.len() as u32;
+        report.allocated_bytes = groups
+            .iter()
+            .map(|g| g.iter().map(TransientDesc::size_bytes).max().unwrap_or(0))
+            .sum();
+
+        TransientPlan {
+            slot_of,
+            groups,
+            passes: self.passes.clone(),
+            report,
+        }
+    }
+}
+
+/// Aliasing of transient targets: how many share memory and what it saved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransientAliasReport {
+    /// Targets used by at least one pass.
+    pub resources: u32,
+    /// Alias groups, one memory allocation each.
+    pub groups: u32,
+    /// Memory the targets would take with one allocation each.
+    pub requested_bytes: u64,
+    /// Memory of the alias groups.
+    pub allocated_bytes: u64,
+}
+
+impl TransientAliasReport {
+    /// Memory aliasing avoided allocating.
+    #[inline]
+    pub fn saved_bytes(&self) -> u64 {
+        self.requested_bytes.saturating_sub(self.allocated_bytes)
+    }
+}
+
+/// Render targets created by [`RenderApi::create_aliased_render_targets`].
+#[derive(Debug, Clone, Default)]
+pub struct AliasedTargets {
+    /// One render target per requested description, in order.
+    pub textures: Vec<TextureId>,
+    /// Size of the shared allocation, from the backend's memory requirements.
+    pub memory_bytes: u64,
+    /// Sum of the targets' own memory requirements.
+    pub requested_bytes: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransientPlan {
+    /// `(group, index in group)` per target; `None` if no pass uses it.
+    slot_of: Vec<Option<(u32, u32)>>,
+    groups: Vec<Vec<TransientDesc>>,
+    passes: Vec<TransientPass>,
+    /// Estimate from the descriptions; [`TransientTargets::report`] has the backend's sizes.
+    pub report: TransientAliasReport,
+}
+
+impl TransientPlan {
+    /// Alias group and position in it of `id`, or `None` if no pass uses it.
+    #[inline]
+    pub fn slot(&self, id: TransientId) -> Option<(u32, u32)> {
+        self.slot_of.get(id.0 as usize).copied().flatten()
+    }
+
+    #[inline]
+    pub fn groups(&self) -> &[Vec<TransientDesc>] {
+        &self.groups
+    }
+
+    #[inline]
+    pub fn passes(&self) -> &[TransientPass] {
+        &self.passes
+    }
+}
+
+/// Backing memory and targets for compiled plans, kept alive between frames and recreated
+/// only when the group layout changes (e.g. on resize).
+#[derive(Default)]
+pub struct TransientTargets {
+    groups: Vec<(Vec<TransientDesc>, AliasedTargets)>,
+    report: TransientAliasReport,
+}
+
+impl TransientTargets {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the targets match `plan`, one shared allocation per alias group.
+    pub fn realize(&mut self, api: &mut dyn RenderApi, plan: &TransientPlan) -> EngineResult<()> {
+        // Keep the longest prefix of groups that still matches; everything after is rebuilt.
+        let keep = self
+            .groups
+            .iter()
+            .zip(&plan.groups)
+            .take_while(|((have, _), want)| {
+                have.len() == want.len() && have.iter().zip(want.iter()).all(|(a, b)| a.same(b))
+            })
+            .count();
+        let changed = keep != self.groups.len() || keep != plan.groups.len();
+
+        for (_, targets) in self.groups.drain(keep..) {
+            for tex in targets.textures {
+                api.destroy_texture(tex);
+            }
+        }
+
+        for group in &plan.groups[keep..] {
+            let descs: Vec<(Extent2D, TextureFormat)> =
+                group.iter().map(|d| (d.extent, d.format)).collect();
+            let targets = api.create_aliased_render_targets(&descs)?;
+            if targets.textures.len() != descs.len() {
+                return Err(EngineError::other(
+                    "transient targets: backend returned the wrong number of targets",
+                ));
+            }
+            self.groups.push((group.clone(), targets));
+        }
+
+        self.report = TransientAliasReport {
+            resources: plan.report.resources,
+            groups: self.groups.len() as u32,
+            requested_bytes: self.groups.iter().map(|(_, t)| t.requested_bytes).sum(),
+            allocated_bytes: self.groups.iter().map(|(_, t)| t.memory_bytes).sum(),
+        };
+        if changed {
+            log::debug!(
+                "render.transient aliased {} targets into {} allocations: {} of {} bytes",
+                self.report.resources,
+                self.report.groups,
+                self.report.allocated_bytes,
+                self.report.requested_bytes
+            );
+        }
+        Ok(())
+    }
+
+    /// Texture backing `id` after `realize` was called with `plan`.
+    #[inline]
+    pub fn texture(&self, plan: &TransientPlan, id: TransientId) -> Option<TextureId> {
+        let (group, index) = plan.slot(id)?;
+        let (_, targets) = self.groups.get(group as usize)?;
+        targets.textures.get(index as usize).copied()
+    }
+
+    /// Runs the plan's passes in order: each one is opened on its target, recorded by
+    /// `record` (which looks up the textures it samples with [`Self::texture`]) and closed.
+    pub fn execute<F>(
+        &self,
+        api: &mut dyn RenderApi,
+        plan: &TransientPlan,
+        mut record: F,
+    ) -> EngineResult<()>
+    where
+        F: FnMut(&TransientPass, &mut dyn RenderApi) -> EngineResult<()>,
+    {
+        for pass in plan.passes.iter() {
+            let target = self.texture(plan, pass.target).ok_or_else(|| {
+                EngineError::other(format!(
+                    "transient pass '{}': target is not realized for this plan",
+                    pass.name
+                ))
+            })?;
+            api.begin_render_target(target, pass.clear_color)?;
+            let recorded = record(pass, api);
+            let ended = api.end_render_target();
+            recorded?;
+            ended?;
+        }
+        Ok(())
+    }
+
+    /// Aliasing of the realized targets, with the backend's memory sizes.
+    #[inline]
+    pub fn report(&self) -> TransientAliasReport {
+        self.report
+    }
+
+    pub fn release(&mut self, api: &mut dyn RenderApi) {
+        for (_, targets) in self.groups.drain(..) {
+            for tex in targets.textures {
+                api.destroy_texture(tex);
+            }
+        }
+        self.report = TransientAliasReport::default();
+    }
+}
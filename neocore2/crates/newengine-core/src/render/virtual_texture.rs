@@ -0,0 +1,460 @@
+//! Partially resident textures for very large terrain and lightmap images.
+//!
+//! Only the pages the camera needs are kept in GPU memory. Backends with sparse residency
+//! bind device memory page by page into one huge image ([`VirtualTextureMode::Sparse`]);
+//! elsewhere the pages are packed into a fixed-size atlas and the shader goes through the
+//! page table ([`VirtualTextureMode::Atlas`]). [`VirtualTexture`] is the streaming side:
+//! it collects page requests, loads missing pages within a per-frame budget and evicts the
+//! least recently used ones when the residency budget is full.
+
+use super::{Extent2D, RenderApi, TextureFormat, TextureId};
+use crate::error::EngineResult;
+
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU32;
+
+/// Page of a virtual texture, in page units of its mip level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PageCoord {
+    pub mip: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl PageCoord {
+    #[inline]
+    pub const fn new(mip: u32, x: u32, y: u32) -> Self {
+        Self { mip, x, y }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VirtualTextureMode {
+    /// Sparse image; pages are bound in place and sampled with regular UVs.
+    Sparse,
+    /// Pages live in slots of a CPU-managed atlas; sampling goes through the page table.
+    Atlas,
+}
+
+impl VirtualTextureMode {
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VirtualTextureMode::Sparse => "sparse",
+            VirtualTextureMode::Atlas => "atlas",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VirtualTextureDesc {
+    pub label: Option<&'static str>,
+    pub extent: Extent2D,
+    pub format: TextureFormat,
+    pub mip_levels: NonZeroU32,
+    /// Residency budget in pages; sizes the atlas in `Atlas` mode.
+    pub max_resident_pages: u32,
+}
+
+impl VirtualTextureDesc {
+    #[inline]
+    pub fn new(extent: Extent2D, format: TextureFormat, max_resident_pages: u32) -> Self {
+        Self {
+            label: None,
+            extent,
+            format,
+            mip_levels: NonZeroU32::new(1).unwrap(),
+            max_resident_pages,
+        }
+    }
+
+    #[inline]
+    pub fn with_label(mut self, label: &'static str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    #[inline]
+    pub fn with_mips(mut self, mip_levels: NonZeroU32) -> Self {
+        self.mip_levels = mip_levels;
+        self
+    }
+}
+
+/// Page geometry chosen by the backend for a virtual texture.
+#[derive(Debug, Clone, Copy)]
+pub struct VirtualTextureLayout {
+    pub mode: VirtualTextureMode,
+    pub extent: Extent2D,
+    pub mip_levels: u32,
+    /// Page size in texels (the sparse block shape, or the atlas slot size).
+    pub page_extent: Extent2D,
+    pub bytes_per_texel: u32,
+    /// Mips from this level down are one always-resident block (the sparse mip tail) and
+    /// never use a residency slot. Equal to `mip_levels` when there is no tail.
+    pub tail_mip: u32,
+    /// Atlas size in slots; zero in `Sparse` mode.
+    pub atlas_slots: Extent2D,
+    /// Number of residency slots (pages that can be resident outside the mip tail).
+    pub slots: u32,
+}
+
+impl VirtualTextureLayout {
+    #[inline]
+    pub fn mip_extent(&self, mip: u32) -> Extent2D {
+        Extent2D::new(
+            (self.extent.width >> mip).max(1),
+            (self.extent.height >> mip).max(1),
+        )
+    }
+
+    /// Page grid of `mip`.
+    #[inline]
+    pub fn pages(&self, mip: u32) -> Extent2D {
+        let e = self.mip_extent(mip);
+        Extent2D::new(
+            e.width.div_ceil(self.page_extent.width),
+            e.height.div_ceil(self.page_extent.height),
+        )
+    }
+
+    #[inline]
+    pub fn contains(&self, page: PageCoord) -> bool {
+        let p = self.pages(page.mip);
+        page.mip < self.mip_levels && page.x < p.width && page.y < p.height
+    }
+
+    #[inline]
+    pub fn in_tail(&self, page: PageCoord) -> bool {
+        page.mip >= self.tail_mip
+    }
+
+    /// Texel rectangle `(x, y, w, h)` of `page` within its mip; edge pages are clipped.
+    /// A mip-tail page covers its whole mip.
+    pub fn page_rect(&self, page: PageCoord) -> (u32, u32, u32, u32) {
+        let e = self.mip_extent(page.mip);
+        if self.in_tail(page) {
+            return (0, 0, e.width, e.height);
+        }
+        let x = page.x * self.page_extent.width;
+        let y = page.y * self.page_extent.height;
+        (
+            x,
+            y,
+            self.page_extent.width.min(e.width.saturating_sub(x)),
+            self.page_extent.height.min(e.height.saturating_sub(y)),
+        )
+    }
+
+    /// Size of the tightly packed texel data expected for `page`.
+    #[inline]
+    pub fn page_bytes(&self, page: PageCoord) -> usize {
+        let (_, _, w, h) = self.page_rect(page);
+        w as usize * h as usize * self.bytes_per_texel as usize
+    }
+
+    /// Top-left texel of an atlas slot.
+    #[inline]
+    pub fn slot_origin(&self, slot: u32) -> (u32, u32) {
+        let cols = self.atlas_slots.width.max(1);
+        (
+            (slot % cols) * self.page_extent.width,
+            (slot / cols) * self.page_extent.height,
+        )
+    }
+}
+
+/// One residency change sent to the backend. `slot` is `None` for mip-tail pages.
+#[derive(Debug, Clone, Copy)]
+pub enum PageUpdate<'a> {
+    /// Makes the page resident and fills it with tightly packed texels
+    /// (`VirtualTextureLayout::page_bytes`).
+    Upload {
+        page: PageCoord,
+        slot: Option<u32>,
+        texels: &'a [u8],
+    },
+    /// Releases the page; its contents become undefined.
+    Evict { page: PageCoord, slot: Option<u32> },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VirtualTextureStats {
+    pub resident: u32,
+    pub requested: u32,
+    pub uploaded: u32,
+    pub evicted: u32,
+    /// Requested pages still missing after this update (budget or loader not ready).
+    pub missing: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Resident {
+    slot: Option<u32>,
+    last_used: u64,
+}
+
+/// Streaming manager of one virtual texture.
+///
+/// Each frame: `request` the pages the view needs (from a feedback pass or the terrain
+/// LOD selection), then `update` with a loader. Coarser mips are loaded first so a
+/// fallback is always resident, at most `upload_budget` pages per update.
+pub struct VirtualTexture {
+    texture: TextureId,
+    layout: VirtualTextureLayout,
+    resident: HashMap<PageCoord, Resident>,
+    free_slots: Vec<u32>,
+    requested: HashSet<PageCoord>,
+    frame: u64,
+    upload_budget: usize,
+    last_stats: VirtualTextureStats,
+}
+
+impl VirtualTexture {
+    pub const DEFAULT_UPLOAD_BUDGET: usize = 16;
+
+    pub fn create(api: &mut dyn RenderApi, desc: VirtualTextureDesc) -> EngineResult<Self> {
+        let (texture, layout) = api.create_virtual_texture(desc)?;
+        log::info!(
+            "render.virtual_texture mode={} extent={}x{} mips={} page={}x{} slots={} tail_mip={}",
+            layout.mode.as_str(),
+            layout.extent.width,
+            layout.extent.height,
+            layout.mip_levels,
+            layout.page_extent.width,
+            layout.page_extent.height,
+            layout.slots,
+            layout.tail_mip
+        );
+
+        Ok(Self {
+            texture,
+            layout,
+            resident: HashMap::new(),
+            free_slots: (0..layout.slots).rev().collect(),
+            requested: HashSet::new(),
+            frame: 0,
+            upload_budget: Self::DEFAULT_UPLOAD_BUDGET,
+            last_stats: VirtualTextureStats::default(),
+        })
+    }
+
+    #[inline]
+    pub fn texture(&self) -> TextureId {
+        self.texture
+    }
+
+    #[inline]
+    pub fn layout(&self) -> &VirtualTextureLayout {
+        &self.layout
+    }
+
+    #[inline]
+    pub fn mode(&self) -> VirtualTextureMode {
+        self.layout.mode
+    }
+
+    #[inline]
+    pub fn set_upload_budget(&mut self, pages_per_update: usize) {
+        self.upload_budget = pages_per_update.max(1);
+    }
+
+    #[inline]
+    pub fn is_resident(&self, page: PageCoord) -> bool {
+        self.resident.contains_key(&page)
+    }
+
+    #[inline]
+    pub fn stats(&self) -> VirtualTextureStats {
+        self.last_stats
+    }
+
+    /// Marks `page` as needed this frame. Returns true if it is already resident.
+    pub fn request(&mut self, page: PageCoord) -> bool {
+        if !self.layout.contains(page) {
+            return false;
+        }
+        let page = self.canonical(page);
+        self.requested.insert(page);
+        match self.resident.get_mut(&page) {
+            Some(r) => {
+                r.last_used = self.frame;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Requests every page of `mip` overlapping the texel rectangle (in mip 0 texels).
+    pub fn request_region(&mut self, mip: u32, x: u32, y: u32, w: u32, h: u32) {
+        if mip >= self.layout.mip_levels || w == 0 || h == 0 {
+            return;
+        }
+        let pw = self.layout.page_extent.width << mip;
+        let ph = self.layout.page_extent.height << mip;
+        let pages = self.layout.pages(mip);
+        let x1 = ((x + w - 1) / pw).min(pages.width.saturating_sub(1));
+        let y1 = ((y + h - 1) / ph).min(pages.height.saturating_sub(1));
+        for py in (y / ph)..=y1 {
+            for px in (x / pw)..=x1 {
+                self.request(PageCoord::new(mip, px, py));
+            }
+        }
+    }
+
+    /// Loads missing requested pages and evicts stale ones. `load` returns the page texels,
+    /// or `None` if they are not available yet (the page is retried next update).
+    pub fn update(
+        &mut self,
+        api: &mut dyn RenderApi,
+        mut load: impl FnMut(PageCoord) -> Option<Vec<u8>>,
+    ) -> EngineResult<VirtualTextureStats> {
+        let mut missing: Vec<PageCoord> = self
+            .requested
+            .iter()
+            .copied()
+            .filter(|p| !self.resident.contains_key(p))
+            .collect();
+        // Coarse mips first; they are the fallback for everything finer.
+        missing.sort_unstable_by(|a, b| b.mip.cmp(&a.mip).then(a.cmp(b)));
+
+        let mut stats = VirtualTextureStats {
+            requested: self.requested.len() as u32,
+            ..VirtualTextureStats::default()
+        };
+
+        let mut evicted: Vec<(PageCoord, Option<u32>)> = Vec::new();
+        let mut loaded: Vec<(PageCoord, Option<u32>, Vec<u8>)> = Vec::new();
+
+        for page in missing {
+            if loaded.len() >= self.upload_budget {
+                stats.missing += 1;
+                continue;
+            }
+
+            let slot = if self.layout.in_tail(page) {
+                None
+            } else {
+                match self
+                    .free_slots
+                    .pop()
+                    .or_else(|| self.evict_lru(&mut evicted))
+                {
+                    Some(slot) => Some(slot),
+                    None => {
+                        stats.missing += 1;
+                        continue;
+                    }
+                }
+            };
+
+            match load(page) {
+                Some(texels) if texels.len() == self.layout.page_bytes(page) => {
+                    loaded.push((page, slot, texels));
+                }
+                other => {
+                    if let Some(texels) = other {
+                        log::warn!(
+                            "render.virtual_texture page {:?}: {} bytes, expected {}",
+                            page,
+                            texels.len(),
+                            self.layout.page_bytes(page)
+                        );
+                    }
+                    if let Some(slot) = slot {
+                        self.free_slots.push(slot);
+                    }
+                    stats.missing += 1;
+                }
+            }
+        }
+
+        if !evicted.is_empty() || !loaded.is_empty() {
+            let updates: Vec<PageUpdate<'_>> = evicted
+                .iter()
+                .map(|&(page, slot)| PageUpdate::Evict { page, slot })
+                .chain(
+                    loaded
+                        .iter()
+                        .map(|(page, slot, texels)| PageUpdate::Upload {
+                            page: *page,
+                            slot: *slot,
+                            texels,
+                        }),
+                )
+                .collect();
+
+            if let Err(e) = api.update_virtual_texture(self.texture, &updates) {
+                // Treat every touched page as gone; the slots are reused on the next update.
+                let slots = evicted.iter().map(|(_, s)| *s);
+                let slots = slots.chain(loaded.iter().map(|(_, s, _)| *s));
+                self.free_slots.extend(slots.flatten());
+                self.requested.clear();
+                return Err(e);
+            }
+        }
+
+        stats.evicted = evicted.len() as u32;
+        stats.uploaded = loaded.len() as u32;
+        for (page, slot, _) in loaded {
+            self.resident.insert(
+                page,
+                Resident {
+                    slot,
+                    last_used: self.frame,
+                },
+            );
+        }
+
+        stats.resident = self.resident.len() as u32;
+        self.requested.clear();
+        self.frame += 1;
+        self.last_stats = stats;
+        Ok(stats)
+    }
+
+    /// Residency table of `mip`, row-major over its page grid: `slot + 1` for resident
+    /// pages (`u32::MAX` for mip-tail pages), `0` for missing ones. Upload it for
+    /// atlas-mode sampling or to clamp the sampled mip in sparse mode.
+    pub fn page_table(&self, mip: u32) -> Vec<u32> {
+        let pages = self.layout.pages(mip);
+        let mut table = vec![0u32; pages.width as usize * pages.height as usize];
+        for (page, r) in &self.resident {
+            if page.mip != mip {
+                continue;
+            }
+            let i = page.y as usize * pages.width as usize + page.x as usize;
+            if let Some(e) = table.get_mut(i) {
+                *e = r.slot.map_or(u32::MAX, |s| s + 1);
+            }
+        }
+        table
+    }
+
+    /// Destroys the backing texture.
+    pub fn release(self, api: &mut dyn RenderApi) {
+        api.destroy_texture(self.texture);
+    }
+
+    /// Tail mips are a single block regardless of the requested page.
+    #[inline]
+    fn canonical(&self, page: PageCoord) -> PageCoord {
+        if self.layout.in_tail(page) {
+            PageCoord::new(page.mip, 0, 0)
+        } else {
+            page
+        }
+    }
+
+    /// Frees the least recently used page not requested this frame.
+    fn evict_lru(&mut self, evicted: &mut Vec<(PageCoord, Option<u32>)>) -> Option<u32> {
+        let (&page, _) = self
+            .resident
+            .iter()
+            .filter(|(p, r)| r.slot.is_some() && !self.requested.contains(p))
+            .min_by_key(|(p, r)| (r.last_used, std::cmp::Reverse(p.mip)))?;
+        let r = self.resident.remove(&page)?;
+        evicted.push((page, r.slot));
+        r.slot
+    }
+}
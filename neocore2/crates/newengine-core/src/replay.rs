@@ -0,0 +1,397 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Deterministic replay of frame inputs.
+//!
+//! A [`ReplaySystem`] attached with [`Engine::attach_replay`](crate::Engine::attach_replay)
+//! records, per frame, the game delta fed to the fixed-step accumulator, the plugin input
+//! events (topics starting with one of its prefixes, `winit.` by default) delivered before
+//! the frame, and the RNG seeds drawn through [`ReplaySystem::seed`]. Playing a [`Replay`]
+//! back substitutes the recorded deltas and events for the live ones (live events on the
+//! recorded topics are dropped), which reproduces the session as long as gameplay only
+//! depends on those inputs and runs on the fixed timestep.
+//!
+//! Typed `HostEvent`s published on the `EventHub` are not recorded.
+
+use crate::plugins::host_context::{deliver_plugin_event, PluginEventFeed};
+use crate::time_control::TimeSource;
+use crate::wire::{write_bytes, write_varint, Reader};
+
+use abi_stable::std_types::RString;
+use newengine_plugin_api::Blob;
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+const MAGIC: [u8; 4] = *b"NERP";
+const VERSION: u16 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayEvent {
+    pub topic: String,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayFrame {
+    /// Game delta of the frame.
+    pub dt: f32,
+    /// Seeds handed out by [`ReplaySystem::seed`] during the frame, in order.
+    pub seeds: Vec<u64>,
+    /// Plugin events delivered before the frame ran.
+    pub events: Vec<ReplayEvent>,
+}
+
+/// A recorded session.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    /// Fixed timestep of the recording engine; playback requires the same one.
+    pub fixed_dt: f32,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    /// Binary stream, little-endian, counts and lengths as LEB128:
+    ///
+    /// ```text
+    /// "NERP" version:u16 fixed_dt:f32 topic_count (len utf8)* frame_count
+    /// frame: dt:f32 seed_count seed:u64* event_count (topic_index payload_len payload)*
+    /// ```
+    pub fn encode(&self) -> Vec<u8> {
+        let mut topics: Vec<&str> = Vec::new();
+        for e in self.frames.iter().flat_map(|f| f.events.iter()) {
+            if !topics.contains(&e.topic.as_str()) {
+                topics.push(&e.topic);
+            }
+        }
+
+        let mut out = Vec::with_capacity(64 + self.frames.len() * 8);
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&self.fixed_dt.to_le_bytes());
+
+        write_varint(&mut out, topics.len() as u64);
+        for t in topics.iter() {
+            write_bytes(&mut out, t.as_bytes());
+        }
+
+        write_varint(&mut out, self.frames.len() as u64);
+        for f in self.frames.iter() {
+            out.extend_from_slice(&f.dt.to_le_bytes());
+            write_varint(&mut out, f.seeds.len() as u64);
+            for s in f.seeds.iter() {
+                out.extend_from_slice(&s.to_le_bytes());
+            }
+            write_varint(&mut out, f.events.len() as u64);
+            for e in f.events.iter() {
+                let idx = topics.iter().position(|t| *t == e.topic).unwrap_or(0);
+                write_varint(&mut out, idx as u64);
+                write_bytes(&mut out, &e.payload);
+            }
+        }
+        out
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(data, "replay");
+        if r.take(4)? != MAGIC {
+            return Err("replay: bad magic".into());
+        }
+        let version = u16::from_le_bytes(r.array()?);
+        if version != VERSION {
+            return Err(format!("replay: unsupported version {version}"));
+        }
+        let fixed_dt = f32::from_le_bytes(r.array()?);
+
+        let topic_count = r.count()?;
+        let mut topics = Vec::with_capacity(topic_count);
+        for _ in 0..topic_count {
+            let bytes = r.bytes()?;
+            let topic =
+                std::str::from_utf8(bytes).map_err(|_| "replay: topic is not utf-8".to_string())?;
+            topics.push(topic.to_owned());
+        }
+
+        let frame_count = r.count()?;
+        let mut frames = Vec::with_capacity(frame_count);
+        for _ in 0..frame_count {
+            let dt = f32::from_le_bytes(r.array()?);
+            let seed_count = r.count()?;
+            let mut seeds = Vec::with_capacity(seed_count);
+            for _ in 0..seed_count {
+                seeds.push(u64::from_le_bytes(r.array()?));
+            }
+            let event_count = r.count()?;
+            let mut events = Vec::with_capacity(event_count);
+            for _ in 0..event_count {
+                let idx = r.varint()?;
+                let topic = topics
+                    .get(idx as usize)
+                    .ok_or_else(|| format!("replay: topic index {idx} out of range"))?
+                    .clone();
+                let payload = r.bytes()?.to_vec();
+                events.push(ReplayEvent { topic, payload });
+            }
+            frames.push(ReplayFrame { dt, seeds, events });
+        }
+
+        if !r.is_empty() {
+            return Err("replay: trailing bytes".into());
+        }
+        Ok(Self { fixed_dt, frames })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.encode())
+            .map_err(|e| format!("replay: write '{}': {e}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data =
+            std::fs::read(path).map_err(|e| format!("replay: read '{}': {e}", path.display()))?;
+        Self::decode(&data)
+    }
+}
+
+struct Recorder {
+    fixed_dt: Option<f32>,
+    frames: Vec<ReplayFrame>,
+    /// Frame that is running; seeds drawn now belong to it.
+    current: Option<ReplayFrame>,
+    /// Events for the next frame.
+    pending: Vec<ReplayEvent>,
+}
+
+struct Player {
+    replay: Replay,
+    next: usize,
+    seeds: VecDeque<u64>,
+    checked: bool,
+}
+
+#[derive(Default)]
+enum Mode {
+    #[default]
+    Idle,
+    Recording(Recorder),
+    Playing(Player),
+}
+
+struct Shared {
+    prefixes: Vec<String>,
+    mode: Mutex<Mode>,
+}
+
+/// Records and plays back frame inputs. Cheap to clone; clones share the recording.
+#[derive(Clone)]
+pub struct ReplaySystem {
+    shared: Arc<Shared>,
+}
+
+impl Default for ReplaySystem {
+    fn default() -> Self {
+        Self::with_topic_prefixes(["winit."])
+    }
+}
+
+impl ReplaySystem {
+    /// Records the host's `winit.*` events.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records plugin events whose topic starts with one of `prefixes`.
+    pub fn with_topic_prefixes<S: Into<String>>(prefixes: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                prefixes: prefixes.into_iter().map(Into::into).collect(),
+                mode: Mutex::new(Mode::Idle),
+            }),
+        }
+    }
+
+    /// Starts a new recording, ending any recording or playback in progress. The first
+    /// recorded frame is the next one the engine begins.
+    pub fn start_recording(&self) {
+        *self.mode() = Mode::Recording(Recorder {
+            fixed_dt: None,
+            frames: Vec::new(),
+            current: None,
+            pending: Vec::new(),
+        });
+        log::info!("replay.record start");
+    }
+
+    /// Ends the recording; `None` if none was running.
+    pub fn stop_recording(&self) -> Option<Replay> {
+        let mut mode = self.mode();
+        let Mode::Recording(r) = &mut *mode else {
+            return None;
+        };
+        let mut frames = std::mem::take(&mut r.frames);
+        frames.extend(r.current.take());
+        let fixed_dt = r.fixed_dt.unwrap_or(0.0);
+        *mode = Mode::Idle;
+
+        log::info!("replay.record stop frames={}", frames.len());
+        Some(Replay { fixed_dt, frames })
+    }
+
+    /// Plays `replay` from the next frame on, ending any recording or playback in progress.
+    /// Playback stops by itself after the last frame, or right away if the engine's fixed
+    /// timestep differs from the recording's.
+    pub fn start_playback(&self, replay: Replay) {
+        log::info!("replay.play start frames={}", replay.frames.len());
+        *self.mode() = Mode::Playing(Player {
+            replay,
+            next: 0,
+            seeds: VecDeque::new(),
+            checked: false,
+        });
+    }
+
+    pub fn stop_playback(&self) {
+        let mut mode = self.mode();
+        if matches!(*mode, Mode::Playing(_)) {
+            *mode = Mode::Idle;
+            log::info!("replay.play stop");
+        }
+    }
+
+    #[inline]
+    pub fn is_recording(&self) -> bool {
+        matches!(*self.mode(), Mode::Recording(_))
+    }
+
+    #[inline]
+    pub fn is_playing(&self) -> bool {
+        matches!(*self.mode(), Mode::Playing(_))
+    }
+
+    /// `(frames played, total frames)` while playing.
+    pub fn playback_progress(&self) -> Option<(usize, usize)> {
+        match &*self.mode() {
+            Mode::Playing(p) => Some((p.next, p.replay.frames.len())),
+            _ => None,
+        }
+    }
+
+    /// Seed for an RNG created during the current frame. `fresh` makes a new one; it is
+    /// recorded while recording, and replaced by the recorded seed while playing.
+    pub fn seed(&self, fresh: impl FnOnce() -> u64) -> u64 {
+        let mut mode = self.mode();
+        match &mut *mode {
+            Mode::Recording(r) => {
+                let seed = fresh();
+                if let Some(frame) = r.current.as_mut() {
+                    frame.seeds.push(seed);
+                }
+                seed
+            }
+            Mode::Playing(p) => match p.seeds.pop_front() {
+                Some(seed) => seed,
+                None => {
+                    log::warn!("replay.desync frame={} extra seed requested", p.next);
+                    fresh()
+                }
+            },
+            Mode::Idle => fresh(),
+        }
+    }
+
+    /// Feed that records (or, during playback, drops) plugin events on the recorded topics.
+    pub fn event_feed(&self) -> PluginEventFeed {
+        let shared = self.shared.clone();
+        Arc::new(move |topic, payload| {
+            if !shared
+                .prefixes
+                .iter()
+                .any(|p| topic.starts_with(p.as_str()))
+            {
+                return true;
+            }
+            let Ok(mut mode) = shared.mode.lock() else {
+                return true;
+            };
+            match &mut *mode {
+                Mode::Recording(r) => {
+                    r.pending.push(ReplayEvent {
+                        topic: topic.to_owned(),
+                        payload: payload.to_vec(),
+                    });
+                    true
+                }
+                Mode::Playing(_) => false,
+                Mode::Idle => true,
+            }
+        })
+    }
+
+    #[inline]
+    fn mode(&self) -> MutexGuard<'_, Mode> {
+        self.shared
+            .mode
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl TimeSource for ReplaySystem {
+    fn next_dt(&mut self, dt: f32, fixed_dt: f32) -> f32 {
+        let mut inject = Vec::new();
+        let mut stop = false;
+
+        let dt = match &mut *self.mode() {
+            Mode::Recording(r) => {
+                r.fixed_dt.get_or_insert(fixed_dt);
+                r.frames.extend(r.current.take());
+                r.current = Some(ReplayFrame {
+                    dt,
+                    seeds: Vec::new(),
+                    events: std::mem::take(&mut r.pending),
+                });
+                dt
+            }
+            Mode::Playing(p) => {
+                if !p.checked && p.replay.fixed_dt != fixed_dt {
+                    log::warn!(
+                        "replay.play aborted: fixed_dt {} differs from the recording's {}",
+                        fixed_dt,
+                        p.replay.fixed_dt
+                    );
+                    stop = true;
+                    dt
+                } else if let Some(frame) = p.replay.frames.get(p.next) {
+                    if !p.seeds.is_empty() {
+                        log::warn!(
+                            "replay.desync frame={} unused_seeds={}",
+                            p.next.saturating_sub(1),
+                            p.seeds.len()
+                        );
+                    }
+                    p.checked = true;
+                    p.next += 1;
+                    p.seeds = frame.seeds.iter().copied().collect();
+                    inject = frame.events.clone();
+                    frame.dt
+                } else {
+                    log::info!("replay.play finished frames={}", p.next);
+                    stop = true;
+                    dt
+                }
+            }
+            Mode::Idle => dt,
+        };
+        if stop {
+            *self.mode() = Mode::Idle;
+        }
+
+        for e in inject {
+            if let Err(err) = deliver_plugin_event(RString::from(e.topic), Blob::from(e.payload)) {
+                log::warn!("replay.play event dropped: {err}");
+            }
+        }
+        dt
+    }
+}
@@ -0,0 +1,577 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Snapshot replication of game state for multiplayer prototypes.
+//!
+//! [`ReplicationServer`] holds the authoritative state of every replicated object. Every
+//! `send_interval` fixed ticks it builds one packet per peer with the objects relevant to
+//! that peer (interest management), delta-compressed against the last snapshot the peer
+//! acknowledged. [`ReplicationClient`] rebuilds the snapshot from such packets, reports
+//! spawns, updates and despawns, and returns the ack to send back.
+//!
+//! The layer is transport-agnostic: packets are byte buffers the app moves over whatever
+//! connection it has, since the engine has no network API yet. Packets may be lost,
+//! duplicated or reordered; deltas are always against an acknowledged snapshot, so a lost
+//! packet only delays a peer until the next one arrives.
+
+use crate::frame::Frame;
+use crate::wire::{write_bytes, write_varint, Reader};
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::sync::Arc;
+
+const PACKET_SNAPSHOT: u8 = b'S';
+const PACKET_ACK: u8 = b'A';
+
+const ENTRY_FULL: u8 = 0;
+const ENTRY_DELTA: u8 = 1;
+const ENTRY_REMOVE: u8 = 2;
+
+/// Id of a replicated object, assigned by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NetId(pub u64);
+
+/// App-assigned id of a connected peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PeerId(pub u32);
+
+/// A type whose values can be replicated.
+///
+/// Delta compression works on the encoded bytes, so encodings that keep unchanged fields at
+/// the same offsets (fixed-size fields) compress best.
+pub trait Replicate: Sized + 'static {
+    /// Name on the wire; must match between server and client builds.
+    const TYPE_NAME: &'static str;
+
+    fn encode(&self, out: &mut Vec<u8>);
+
+    fn decode(data: &[u8]) -> Result<Self, String>;
+}
+
+/// Whether an object (with its position, if it has one) is sent to a peer. Replaces the
+/// view-radius check.
+pub type InterestFn = Box<dyn Fn(PeerId, NetId, Option<[f32; 3]>) -> bool + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplicationConfig {
+    /// Send a snapshot every N fixed ticks.
+    pub send_interval: u32,
+    /// Snapshots kept as delta baselines, per peer on the server and in total on the client.
+    /// A peer whose last ack is older gets full states.
+    pub history: usize,
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        Self {
+            send_interval: 1,
+            history: 32,
+        }
+    }
+}
+
+type State = Arc<[u8]>;
+
+struct ServerObject {
+    type_name: &'static str,
+    state: State,
+    position: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct View {
+    center: [f32; 3],
+    radius: f32,
+}
+
+#[derive(Default)]
+struct Peer {
+    view: Option<View>,
+    acked: Option<u64>,
+    /// What each recent packet left the peer with, oldest first.
+    sent: VecDeque<(u64, BTreeMap<NetId, State>)>,
+}
+
+/// Authoritative side of replication.
+pub struct ReplicationServer {
+    config: ReplicationConfig,
+    types: BTreeSet<&'static str>,
+    next_id: u64,
+    objects: BTreeMap<NetId, ServerObject>,
+    peers: BTreeMap<PeerId, Peer>,
+    interest: Option<InterestFn>,
+    last_tick: Option<u64>,
+}
+
+impl Default for ReplicationServer {
+    fn default() -> Self {
+        Self::new(ReplicationConfig::default())
+    }
+}
+
+impl ReplicationServer {
+    pub fn new(config: ReplicationConfig) -> Self {
+        Self {
+            config: ReplicationConfig {
+                send_interval: config.send_interval.max(1),
+                history: config.history.max(1),
+            },
+            types: BTreeSet::new(),
+            next_id: 0,
+            objects: BTreeMap::new(),
+            peers: BTreeMap::new(),
+            interest: None,
+            last_tick: None,
+        }
+    }
+
+    #[inline]
+    pub fn config(&self) -> ReplicationConfig {
+        self.config
+    }
+
+    /// Allows spawning objects of type `T`.
+    #[inline]
+    pub fn register<T: Replicate>(&mut self) {
+        self.types.insert(T::TYPE_NAME);
+    }
+
+    pub fn spawn<T: Replicate>(&mut self, value: &T) -> Result<NetId, String> {
+        if !self.types.contains(T::TYPE_NAME) {
+            return Err(format!(
+                "replication: type not registered: {}",
+                T::TYPE_NAME
+            ));
+        }
+        self.next_id += 1;
+        let id = NetId(self.next_id);
+        self.objects.insert(
+            id,
+            ServerObject {
+                type_name: T::TYPE_NAME,
+                state: encode(value),
+                position: None,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Replaces the state of `id`; sent with the next snapshot.
+    pub fn update<T: Replicate>(&mut self, id: NetId, value: &T) -> Result<(), String> {
+        let obj = self
+            .objects
+            .get_mut(&id)
+            .ok_or_else(|| format!("replication: unknown object {}", id.0))?;
+        if obj.type_name != T::TYPE_NAME {
+            return Err(format!(
+                "replication: object {} is a {}, not a {}",
+                id.0,
+                obj.type_name,
+                T::TYPE_NAME
+            ));
+        }
+        obj.state = encode(value);
+        Ok(())
+    }
+
+    /// Position used by the view-radius interest check; objects without one go to every
+    /// peer.
+    pub fn set_position(&mut self, id: NetId, position: Option<[f32; 3]>) -> bool {
+        match self.objects.get_mut(&id) {
+            Some(obj) => {
+                obj.position = position;
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[inline]
+    pub fn despawn(&mut self, id: NetId) -> bool {
+        self.objects.remove(&id).is_some()
+    }
+
+    #[inline]
+    pub fn contains(&self, id: NetId) -> bool {
+        self.objects.contains_key(&id)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Adds a peer; its first snapshot carries full states.
+    #[inline]
+    pub fn add_peer(&mut self, peer: PeerId) {
+        self.peers.entry(peer).or_default();
+    }
+
+    #[inline]
+    pub fn remove_peer(&mut self, peer: PeerId) -> bool {
+        self.peers.remove(&peer).is_some()
+    }
+
+    /// Limits `peer` to positioned objects within `radius` of `center` (objects without a
+    /// position are always sent).
+    pub fn set_peer_view(&mut self, peer: PeerId, center: [f32; 3], radius: f32) -> bool {
+        match self.peers.get_mut(&peer) {
+            Some(p) => {
+                p.view = Some(View { center, radius });
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear_peer_view(&mut self, peer: PeerId) -> bool {
+        match self.peers.get_mut(&peer) {
+            Some(p) => {
+                p.view = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the view-radius check with `interest` (`None` restores it).
+    #[inline]
+    pub fn set_interest(&mut self, interest: Option<InterestFn>) {
+        self.interest = interest;
+    }
+
+    /// Handles a packet from `peer` (its acks).
+    pub fn receive(&mut self, peer: PeerId, packet: &[u8]) -> Result<(), String> {
+        let mut r = Reader::new(packet, "replication");
+        if r.u8()? != PACKET_ACK {
+            return Err("replication: expected an ack packet".into());
+        }
+        let tick = r.varint()?;
+        let Some(p) = self.peers.get_mut(&peer) else {
+            return Err(format!("replication: unknown peer {}", peer.0));
+        };
+        if p.acked.is_some_and(|t| t >= tick) || !p.sent.iter().any(|(t, _)| *t == tick) {
+            return Ok(());
+        }
+        p.acked = Some(tick);
+        while p.sent.front().is_some_and(|(t, _)| *t < tick) {
+            p.sent.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Snapshot packets for `fixed_tick`, one per peer; empty between send intervals.
+    /// Ticks must increase.
+    pub fn tick(&mut self, fixed_tick: u64) -> Vec<(PeerId, Vec<u8>)> {
+        if fixed_tick % u64::from(self.config.send_interval) != 0
+            || self.last_tick.is_some_and(|t| fixed_tick <= t)
+        {
+            return Vec::new();
+        }
+        self.last_tick = Some(fixed_tick);
+
+        let objects = &self.objects;
+        let interest = self.interest.as_ref();
+        let history = self.config.history;
+        let mut out = Vec::with_capacity(self.peers.len());
+
+        for (&peer_id, peer) in self.peers.iter_mut() {
+            let baseline = peer
+                .acked
+                .and_then(|t| peer.sent.iter().find(|(tick, _)| *tick == t));
+
+            let mut packet = vec![PACKET_SNAPSHOT];
+            write_varint(&mut packet, fixed_tick);
+            match baseline {
+                Some((t, _)) => {
+                    packet.push(1);
+                    write_varint(&mut packet, *t);
+                }
+                None => packet.push(0),
+            }
+
+            let mut entries = Vec::new();
+            let mut count = 0u64;
+            let mut current = BTreeMap::new();
+            for (&id, obj) in objects.iter() {
+                if !is_relevant(interest, peer.view, peer_id, id, obj) {
+                    continue;
+                }
+                current.insert(id, obj.state.clone());
+                match baseline.and_then(|(_, b)| b.get(&id)) {
+                    Some(base) if *base == obj.state => continue,
+                    Some(base) if base.len() == obj.state.len() => {
+                        write_varint(&mut entries, id.0);
+                        entries.push(ENTRY_DELTA);
+                        write_bytes(&mut entries, &xor_delta(base, &obj.state));
+                    }
+                    _ => {
+                        write_varint(&mut entries, id.0);
+                        entries.push(ENTRY_FULL);
+                        write_bytes(&mut entries, obj.type_name.as_bytes());
+                        write_bytes(&mut entries, &obj.state);
+                    }
+                }
+                count += 1;
+            }
+            if let Some((_, base)) = baseline {
+                for id in base.keys().filter(|id| !current.contains_key(*id)) {
+                    write_varint(&mut entries, id.0);
+                    entries.push(ENTRY_REMOVE);
+                    count += 1;
+                }
+            }
+
+            write_varint(&mut packet, count);
+            packet.extend_from_slice(&entries);
+            out.push((peer_id, packet));
+
+            peer.sent.push_back((fixed_tick, current));
+            while peer.sent.len() > history {
+                peer.sent.pop_front();
+            }
+        }
+        out
+    }
+
+    /// [`tick`](Self::tick) for fixed subframes; empty for variable frames.
+    #[inline]
+    pub fn tick_frame(&mut self, frame: &Frame) -> Vec<(PeerId, Vec<u8>)> {
+        if !frame.is_fixed() {
+            return Vec::new();
+        }
+        self.tick(frame.fixed_tick)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplicationEvent {
+    Spawned(NetId),
+    Updated(NetId),
+    Despawned(NetId),
+}
+
+/// Result of [`ReplicationClient::receive`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Received {
+    pub tick: u64,
+    /// Changes against the previous snapshot.
+    pub events: Vec<ReplicationEvent>,
+    /// Packet to send back to the server.
+    pub ack: Vec<u8>,
+}
+
+#[derive(Clone)]
+struct ClientObject {
+    type_name: Arc<str>,
+    state: State,
+}
+
+/// Receiving side of replication.
+pub struct ReplicationClient {
+    history: usize,
+    /// Received snapshots, oldest first; the last one is current.
+    snapshots: VecDeque<(u64, BTreeMap<NetId, ClientObject>)>,
+}
+
+impl Default for ReplicationClient {
+    fn default() -> Self {
+        Self::new(ReplicationConfig::default())
+    }
+}
+
+impl ReplicationClient {
+    /// Only `history` is used; it should be at least the server's.
+    pub fn new(config: ReplicationConfig) -> Self {
+        Self {
+            history: config.history.max(1),
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    /// Applies a snapshot packet. `Ok(None)` for packets older than the current snapshot.
+    pub fn receive(&mut self, packet: &[u8]) -> Result<Option<Received>, String> {
+        let mut r = Reader::new(packet, "replication");
+        if r.u8()? != PACKET_SNAPSHOT {
+            return Err("replication: expected a snapshot packet".into());
+        }
+        let tick = r.varint()?;
+        if self.tick().is_some_and(|t| t >= tick) {
+            return Ok(None);
+        }
+
+        let empty = BTreeMap::new();
+        let base = match r.u8()? {
+            0 => &empty,
+            _ => {
+                let t = r.varint()?;
+                self.snapshots
+                    .iter()
+                    .find(|(tick, _)| *tick == t)
+                    .map(|(_, s)| s)
+                    .ok_or_else(|| format!("replication: baseline {t} not available"))?
+            }
+        };
+
+        let mut next = base.clone();
+        for _ in 0..r.count()? {
+            let id = NetId(r.varint()?);
+            match r.u8()? {
+                ENTRY_FULL => {
+                    let type_name = std::str::from_utf8(r.bytes()?)
+                        .map_err(|_| "replication: type name is not utf-8".to_string())?;
+                    let state = State::from(r.bytes()?);
+                    next.insert(
+                        id,
+                        ClientObject {
+                            type_name: Arc::from(type_name),
+                            state,
+                        },
+                    );
+                }
+                ENTRY_DELTA => {
+                    let obj = base
+                        .get(&id)
+                        .ok_or_else(|| format!("replication: delta for unknown object {}", id.0))?;
+                    let state = apply_xor_delta(&obj.state, r.bytes()?)?;
+                    next.insert(
+                        id,
+                        ClientObject {
+                            type_name: obj.type_name.clone(),
+                            state,
+                        },
+                    );
+                }
+                ENTRY_REMOVE => {
+                    next.remove(&id);
+                }
+                other => return Err(format!("replication: unknown entry kind {other}")),
+            }
+        }
+        if !r.is_empty() {
+            return Err("replication: trailing bytes".into());
+        }
+
+        let prev = self.snapshots.back().map(|(_, s)| s).unwrap_or(&empty);
+        let mut events = Vec::new();
+        for (id, obj) in next.iter() {
+            match prev.get(id) {
+                None => events.push(ReplicationEvent::Spawned(*id)),
+                Some(p) if p.state != obj.state => events.push(ReplicationEvent::Updated(*id)),
+                Some(_) => {}
+            }
+        }
+        for id in prev.keys().filter(|id| !next.contains_key(*id)) {
+            events.push(ReplicationEvent::Despawned(*id));
+        }
+
+        self.snapshots.push_back((tick, next));
+        while self.snapshots.len() > self.history {
+            self.snapshots.pop_front();
+        }
+
+        let mut ack = vec![PACKET_ACK];
+        write_varint(&mut ack, tick);
+        Ok(Some(Received { tick, events, ack }))
+    }
+
+    /// Tick of the current snapshot.
+    #[inline]
+    pub fn tick(&self) -> Option<u64> {
+        self.snapshots.back().map(|(t, _)| *t)
+    }
+
+    /// Current state of `id`; `Some(Err)` if it is not a `T` or fails to decode.
+    pub fn get<T: Replicate>(&self, id: NetId) -> Option<Result<T, String>> {
+        let obj = self.snapshots.back()?.1.get(&id)?;
+        if &*obj.type_name != T::TYPE_NAME {
+            return Some(Err(format!(
+                "replication: object {} is a {}, not a {}",
+                id.0,
+                obj.type_name,
+                T::TYPE_NAME
+            )));
+        }
+        Some(T::decode(&obj.state))
+    }
+
+    /// Objects of the current snapshot with their type names.
+    pub fn objects(&self) -> impl Iterator<Item = (NetId, &str)> + '_ {
+        self.snapshots
+            .back()
+            .into_iter()
+            .flat_map(|(_, s)| s.iter().map(|(id, o)| (*id, &*o.type_name)))
+    }
+}
+
+#[inline]
+fn encode<T: Replicate>(value: &T) -> State {
+    let mut out = Vec::new();
+    value.encode(&mut out);
+    State::from(out)
+}
+
+fn is_relevant(
+    interest: Option<&InterestFn>,
+    view: Option<View>,
+    peer: PeerId,
+    id: NetId,
+    obj: &ServerObject,
+) -> bool {
+    if let Some(f) = interest {
+        return f(peer, id, obj.position);
+    }
+    match (view, obj.position) {
+        (Some(v), Some(p)) => {
+            let d: f32 = (0..3).map(|i| (p[i] - v.center[i]).powi(2)).sum();
+            d <= v.radius * v.radius
+        }
+        _ => true,
+    }
+}
+
+/// XOR of two equal-length states as `(zero_run, literal_len, literal bytes)*`; unchanged
+/// bytes cost nothing but the run lengths.
+fn xor_delta(base: &[u8], next: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < next.len() {
+        let start = i;
+        while i < next.len() && base[i] == next[i] {
+            i += 1;
+        }
+        let zeros = i - start;
+        let lit_start = i;
+        while i < next.len() && base[i] != next[i] {
+            i += 1;
+        }
+        write_varint(&mut out, zeros as u64);
+        write_varint(&mut out, (i - lit_start) as u64);
+        out.extend((lit_start..i).map(|k| base[k] ^ next[k]));
+    }
+    out
+}
+
+fn apply_xor_delta(base: &[u8], delta: &[u8]) -> Result<State, String> {
+    let mut out = base.to_vec();
+    let mut r = Reader::new(delta, "replication");
+    let mut pos = 0usize;
+    while !r.is_empty() {
+        pos = pos.saturating_add(r.varint()? as usize);
+        let lit = r.count()?;
+        let bytes = r.take(lit)?;
+        let end = pos
+            .checked_add(lit)
+            .filter(|end| *end <= out.len())
+            .ok_or_else(|| "replication: delta runs past the state".to_string())?;
+        for (o, b) in out[pos..end].iter_mut().zip(bytes) {
+            *o ^= b;
+        }
+        pos = end;
+    }
+    Ok(State::from(out))
+}
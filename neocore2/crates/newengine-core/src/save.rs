@@ -0,0 +1,381 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Savegames: named, versioned snapshot channels aggregated into one file.
+//!
+//! Modules register a [`SaveLoad`] handler per channel (usually their module id) with the
+//! engine's [`SaveApi`] resource. Saving asks every handler for a blob and writes them into
+//! one file with a header and a CRC-32; loading verifies the file and hands each blob, with
+//! the version it was written with, back to the handler registered under its id.
+//!
+//! ```text
+//! "NESV" format:u16 created_unix_ms:u64 channel_count:u32
+//! channel: id_len:u16 id:utf8 version:u32 data_len:u32 data
+//! crc32:u32 (IEEE, over everything before it)
+//! ```
+//!
+//! All integers are little-endian. The engine also registers the `save.write <path>` and
+//! `save.read <path>` console commands.
+
+use crate::wire::Reader;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MAGIC: [u8; 4] = *b"NESV";
+const FORMAT_VERSION: u16 = 1;
+
+/// State that can be written into and restored from a savegame.
+///
+/// Handlers are shared with the [`SaveApi`], so they take `&self`; keep the state behind a
+/// `Mutex` (or an `Arc` the module also holds).
+pub trait SaveLoad: Send + Sync {
+    /// Version stored next to the blob and passed back to [`load`](Self::load), so newer code
+    /// can migrate older saves.
+    fn save_version(&self) -> u32 {
+        1
+    }
+
+    fn save(&self) -> Result<Vec<u8>, String>;
+
+    fn load(&self, version: u32, data: &[u8]) -> Result<(), String>;
+}
+
+/// One channel of a [`SaveFile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveChannel {
+    pub id: String,
+    pub version: u32,
+    pub data: Vec<u8>,
+}
+
+/// Decoded savegame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SaveFile {
+    pub created_unix_ms: u64,
+    pub channels: Vec<SaveChannel>,
+}
+
+impl SaveFile {
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        let size: usize = self
+            .channels
+            .iter()
+            .map(|c| 10 + c.id.len() + c.data.len())
+            .sum();
+        let mut out = Vec::with_capacity(22 + size);
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&self.created_unix_ms.to_le_bytes());
+        out.extend_from_slice(&(self.channels.len() as u32).to_le_bytes());
+
+        for c in self.channels.iter() {
+            let id_len = u16::try_from(c.id.len())
+                .map_err(|_| format!("save: channel id too long: '{}'", c.id))?;
+            out.extend_from_slice(&id_len.to_le_bytes());
+            out.extend_from_slice(c.id.as_bytes());
+            out.extend_from_slice(&c.version.to_le_bytes());
+            let data_len = u32::try_from(c.data.len())
+                .map_err(|_| format!("save: channel '{}' is larger than 4 GiB", c.id))?;
+            out.extend_from_slice(&data_len.to_le_bytes());
+            out.extend_from_slice(&c.data);
+        }
+
+        let crc = crc32(&out);
+        out.extend_from_slice(&crc.to_le_bytes());
+        Ok(out)
+    }
+
+    /// Decodes and verifies a savegame.
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        if data.len() < 4 {
+            return Err("save: file too short".into());
+        }
+        let (body, crc) = data.split_at(data.len() - 4);
+        if crc32(body).to_le_bytes() != crc {
+            return Err("save: checksum mismatch (file is corrupt or truncated)".into());
+        }
+
+        let mut r = Reader::new(body, "save");
+        if r.take(4)? != MAGIC {
+            return Err("save: not a savegame".into());
+        }
+        let format = u16::from_le_bytes(r.array()?);
+        if format != FORMAT_VERSION {
+            return Err(format!("save: unsupported format version {format}"));
+        }
+        let created_unix_ms = u64::from_le_bytes(r.array()?);
+
+        let count = u32::from_le_bytes(r.array()?) as usize;
+        let mut channels = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            let id_len = u16::from_le_bytes(r.array()?) as usize;
+            let id = std::str::from_utf8(r.take(id_len)?)
+                .map_err(|_| "save: channel id is not utf-8".to_string())?
+                .to_owned();
+            let version = u32::from_le_bytes(r.array()?);
+            let len = u32::from_le_bytes(r.array()?) as usize;
+            let data = r.take(len)?.to_vec();
+            channels.push(SaveChannel { id, version, data });
+        }
+
+        if !r.is_empty() {
+            return Err("save: trailing bytes".into());
+        }
+        Ok(Self {
+            created_unix_ms,
+            channels,
+        })
+    }
+
+    pub fn channel(&self, id: &str) -> Option<&SaveChannel> {
+        self.channels.iter().find(|c| c.id == id)
+    }
+}
+
+/// What [`SaveApi::load_bytes`] did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    /// Channels handed to their handler.
+    pub loaded: Vec<String>,
+    /// Registered channels the file has no blob for; their handlers were not called.
+    pub missing: Vec<String>,
+    /// Blobs without a registered handler; skipped.
+    pub unknown: Vec<String>,
+}
+
+type Handlers = BTreeMap<String, Arc<dyn SaveLoad>>;
+
+/// Savegame service. Cheap to clone; clones share the registered channels.
+///
+/// The engine inserts one into its resources; modules usually clone it in `init` and
+/// register their channel there.
+#[derive(Clone, Default)]
+pub struct SaveApi {
+    handlers: Arc<Mutex<Handlers>>,
+}
+
+impl SaveApi {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the handler of channel `id`.
+    pub fn register(
+        &self,
+        id: impl Into<String>,
+        handler: Arc<dyn SaveLoad>,
+    ) -> Result<(), String> {
+        let id = id.into();
+        if id.is_empty() || id.len() > usize::from(u16::MAX) {
+            return Err(format!("save: invalid channel id '{id}'"));
+        }
+        let mut g = self.lock();
+        if g.contains_key(&id) {
+            return Err(format!("save: channel already registered: {id}"));
+        }
+        g.insert(id, handler);
+        Ok(())
+    }
+
+    /// Removes the handler of channel `id`. Returns `false` if it was not registered.
+    #[inline]
+    pub fn unregister(&self, id: &str) -> bool {
+        self.lock().remove(id).is_some()
+    }
+
+    /// Registered channel ids, sorted.
+    pub fn channels(&self) -> Vec<String> {
+        self.lock().keys().cloned().collect()
+    }
+
+    /// Snapshot of every registered channel, in id order.
+    pub fn snapshot(&self) -> Result<SaveFile, String> {
+        let mut channels = Vec::new();
+        for (id, handler) in self.handlers_snapshot() {
+            let data = handler
+                .save()
+                .map_err(|e| format!("save: channel '{id}' failed: {e}"))?;
+            channels.push(SaveChannel {
+                version: handler.save_version(),
+                id,
+                data,
+            });
+        }
+        Ok(SaveFile {
+            created_unix_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            channels,
+        })
+    }
+
+    #[inline]
+    pub fn save_bytes(&self) -> Result<Vec<u8>, String> {
+        self.snapshot()?.encode()
+    }
+
+    /// Writes a savegame to `path` through a temporary file, so a crash mid-write leaves the
+    /// previous save intact.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let bytes = self.save_bytes()?;
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("save: create '{}': {e}", dir.display()))?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, &bytes)
+            .map_err(|e| format!("save: write '{}': {e}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .map_err(|e| format!("save: rename to '{}': {e}", path.display()))?;
+        log::info!(
+            "save.write path='{}' bytes={} channels={}",
+            path.display(),
+            bytes.len(),
+            self.lock().len()
+        );
+        Ok(())
+    }
+
+    /// Restores every channel of `file` that has a handler. Stops at the first handler that
+    /// fails; channels before it are already loaded.
+    pub fn load_file(&self, file: &SaveFile) -> Result<LoadReport, String> {
+        let handlers = self.handlers_snapshot();
+        let mut report = LoadReport::default();
+
+        for c in file.channels.iter() {
+            let Some((_, handler)) = handlers.iter().find(|(id, _)| *id == c.id) else {
+                report.unknown.push(c.id.clone());
+                continue;
+            };
+            handler
+                .load(c.version, &c.data)
+                .map_err(|e| format!("save: load channel '{}' failed: {e}", c.id))?;
+            report.loaded.push(c.id.clone());
+        }
+        for (id, _) in handlers.iter() {
+            if file.channel(id).is_none() {
+                report.missing.push(id.clone());
+            }
+        }
+        Ok(report)
+    }
+
+    #[inline]
+    pub fn load_bytes(&self, data: &[u8]) -> Result<LoadReport, String> {
+        self.load_file(&SaveFile::decode(data)?)
+    }
+
+    pub fn load(&self, path: &Path) -> Result<LoadReport, String> {
+        let data =
+            std::fs::read(path).map_err(|e| format!("save: read '{}': {e}", path.display()))?;
+        let report = self.load_bytes(&data)?;
+        log::info!(
+            "save.read path='{}' loaded={} missing={:?} unknown={:?}",
+            path.display(),
+            report.loaded.len(),
+            report.missing,
+            report.unknown
+        );
+        Ok(report)
+    }
+
+    /// Handlers are called without the lock held, so they may use the `SaveApi` themselves.
+    fn handlers_snapshot(&self) -> Vec<(String, Arc<dyn SaveLoad>)> {
+        self.lock()
+            .iter()
+            .map(|(id, h)| (id.clone(), h.clone()))
+            .collect()
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, Handlers> {
+        self.handlers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Registers the `save.*` console commands for `api`. A later engine replaces them.
+#[cfg(feature = "runtime")]
+pub(crate) fn register_save_commands(api: &SaveApi) {
+    use crate::console::ConsoleCommands;
+
+    let console = ConsoleCommands;
+    for name in ["save.write", "save.read"] {
+        console.unregister_command(name);
+    }
+
+    let a = api.clone();
+    let write = console.register_command_with_usage(
+        "save.write",
+        "save.write <path>",
+        move |args| {
+            let path = args.trim();
+            if path.is_empty() {
+                return Err("usage: save.write <path>".into());
+            }
+            a.save(Path::new(path)).map(|()| format!("saved to {path}"))
+        },
+        "Write a savegame",
+    );
+
+    let a = api.clone();
+    let read = console.register_command_with_usage(
+        "save.read",
+        "save.read <path>",
+        move |args| {
+            let path = args.trim();
+            if path.is_empty() {
+                return Err("usage: save.read <path>".into());
+            }
+            a.load(Path::new(path)).map(|r| {
+                format!(
+                    "loaded {} channel(s); missing={:?} unknown={:?}",
+                    r.loaded.len(),
+                    r.missing,
+                    r.unknown
+                )
+            })
+        },
+        "Load a savegame",
+    );
+
+    for res in [write, read] {
+        if let Err(e) = res {
+            log::warn!("save.commands register failed: {e}");
+        }
+    }
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32 (IEEE 802.3), as used by zip and PNG.
+fn crc32(data: &[u8]) -> u32 {
+    let mut c = 0xFFFF_FFFFu32;
+    for &b in data {
+        c = CRC_TABLE[((c ^ u32::from(b)) & 0xFF) as usize] ^ (c >> 8);
+    }
+    c ^ 0xFFFF_FFFF
+}
@@ -1,6 +1,8 @@
 use std::collections::VecDeque;
 use std::time::Duration;
 
+use crate::sync::CancelToken;
+
 /// Scheduler phase within a frame.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SchedulePhase {
@@ -49,6 +51,19 @@ impl Scheduler {
         }
     }
 
+    /// Like `schedule`, but the task is skipped if `cancel` fires before its phase runs.
+    #[inline]
+    pub fn schedule_cancellable<F>(&mut self, phase: SchedulePhase, cancel: CancelToken, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.schedule(phase, move || {
+            if !cancel.is_cancelled() {
+                f();
+            }
+        });
+    }
+
     /// Called by the engine at the very beginning of a frame.
     #[inline]
     pub fn begin_frame(&mut self, dt: Duration) {
@@ -0,0 +1,214 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Runtime user settings exposed through the `engine.settings` service.
+//!
+//! Covers UI accessibility (scale, high contrast, reduced motion, screen reader) and the
+//! swapchain present mode (vsync), image count and latency mode. Accessibility values live in
+//! `newengine_ui::accessibility`; swapchain changes are queued in `render` and applied by the
+//! render module. Both take effect on the next frame.
+
+use crate::plugins::host_api;
+use crate::render::{
+    active_latency_mode, active_present_mode, active_swapchain_images, request_latency_mode,
+    request_present_mode, request_swapchain_images, LatencyMode, PresentMode,
+};
+use crate::startup::StartupConfig;
+use abi_stable::std_types::{RResult, RString};
+use newengine_plugin_api::{Blob, CapabilityId, MethodName, ServiceV1, ServiceV1Dyn};
+use newengine_ui::text::{set_fonts, UiFonts};
+use newengine_ui::{accessibility, set_accessibility, UiAccessibility};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+pub const SETTINGS_SERVICE_ID: &str = "engine.settings";
+
+pub mod method {
+    pub const ACCESSIBILITY_GET: &str = "ui.accessibility.get";
+    pub const ACCESSIBILITY_SET: &str = "ui.accessibility.set";
+    pub const PRESENT_MODE_GET: &str = "render.present_mode.get";
+    pub const PRESENT_MODE_SET: &str = "render.present_mode.set";
+    pub const LATENCY_GET: &str = "render.latency.get";
+    pub const LATENCY_SET: &str = "render.latency.set";
+}
+
+/// Seeds accessibility options and UI fonts from the startup config. Call before the window
+/// is created so the screen-reader adapter can attach.
+pub fn apply_startup_ui_settings(startup: &StartupConfig) {
+    set_accessibility(UiAccessibility {
+        ui_scale: startup.ui_scale,
+        high_contrast: startup.ui_high_contrast,
+        reduced_motion: startup.ui_reduced_motion,
+        screen_reader: startup.ui_screen_reader,
+    });
+    if let Some(fonts) = &startup.ui_fonts {
+        set_fonts(UiFonts::from_config(fonts, &startup.assets_root));
+    }
+}
+
+/// Overlays the keys present in `patch` onto the current options.
+fn merge_accessibility(patch: &[u8]) -> Result<UiAccessibility, String> {
+    let patch: Value =
+        serde_json::from_slice(patch).map_err(|e| format!("bad settings json: {e}"))?;
+    let Value::Object(patch) = patch else {
+        return Err("settings payload must be a json object".to_owned());
+    };
+
+    let mut cur = serde_json::to_value(accessibility()).map_err(|e| e.to_string())?;
+    if let Value::Object(cur) = &mut cur {
+        for (k, v) in patch {
+            if !cur.contains_key(&k) {
+                return Err(format!("unknown accessibility setting '{k}'"));
+            }
+            cur.insert(k, v);
+        }
+    }
+
+    serde_json::from_value::<UiAccessibility>(cur).map_err(|e| format!("bad settings value: {e}"))
+}
+
+#[derive(Deserialize)]
+struct PresentModeRequest {
+    #[serde(default)]
+    mode: Option<PresentMode>,
+    #[serde(default)]
+    vsync: Option<bool>,
+}
+
+fn parse_present_mode(payload: &[u8]) -> Result<PresentMode, String> {
+    let req: PresentModeRequest =
+        serde_json::from_slice(payload).map_err(|e| format!("bad settings json: {e}"))?;
+    match (req.mode, req.vsync) {
+        (Some(mode), None) => Ok(mode),
+        (None, Some(vsync)) => Ok(PresentMode::from_vsync(vsync)),
+        _ => Err("expected exactly one of 'mode' or 'vsync'".to_owned()),
+    }
+}
+
+fn present_mode_json() -> Value {
+    let active = active_present_mode();
+    json!({
+        "mode": active,
+        "vsync": active.map(|m| m != PresentMode::Immediate),
+    })
+}
+
+#[derive(Deserialize)]
+struct LatencyRequest {
+    #[serde(default)]
+    mode: Option<LatencyMode>,
+    /// `0` restores the backend default.
+    #[serde(default)]
+    swapchain_images: Option<u32>,
+}
+
+fn apply_latency(payload: &[u8]) -> Result<Value, String> {
+    let req: LatencyRequest =
+        serde_json::from_slice(payload).map_err(|e| format!("bad settings json: {e}"))?;
+    if req.mode.is_none() && req.swapchain_images.is_none() {
+        return Err("expected 'mode' and/or 'swapchain_images'".to_owned());
+    }
+    if let Some(mode) = req.mode {
+        request_latency_mode(mode);
+    }
+    if let Some(n) = req.swapchain_images {
+        request_swapchain_images((n > 0).then_some(n));
+    }
+    Ok(json!({ "mode": req.mode, "swapchain_images": req.swapchain_images }))
+}
+
+fn latency_json() -> Value {
+    json!({
+        "mode": active_latency_mode(),
+        "swapchain_images": active_swapchain_images(),
+    })
+}
+
+struct SettingsService;
+
+impl ServiceV1 for SettingsService {
+    fn id(&self) -> CapabilityId {
+        RString::from(SETTINGS_SERVICE_ID)
+    }
+
+    fn describe(&self) -> RString {
+        RString::from(
+            json!({
+                "id": SETTINGS_SERVICE_ID,
+                "version": 3,
+                "permissions": [],
+                "methods": [
+                    { "name": method::ACCESSIBILITY_GET, "payload": "empty", "returns": "json UiAccessibility" },
+                    { "name": method::ACCESSIBILITY_SET, "payload": "json partial UiAccessibility {ui_scale?, high_contrast?, reduced_motion?, screen_reader?}", "returns": "json {ok, settings?, error?}" },
+                    { "name": method::PRESENT_MODE_GET, "payload": "empty", "returns": "json {mode: immediate|mailbox|fifo|null, vsync: bool|null}" },
+                    { "name": method::PRESENT_MODE_SET, "payload": "json {mode: immediate|mailbox|fifo} | {vsync: bool}", "returns": "json {ok, requested?, error?}" },
+                    { "name": method::LATENCY_GET, "payload": "empty", "returns": "json {mode: throughput|low|null, swapchain_images: u32|null}" },
+                    { "name": method::LATENCY_SET, "payload": "json {mode?: throughput|low, swapchain_images?: u32 (0 = default)}", "returns": "json {ok, requested?, error?}" }
+                ]
+            })
+            .to_string(),
+        )
+    }
+
+    fn call(&self, method: MethodName, payload: Blob) -> RResult<Blob, RString> {
+        match method.to_string().as_str() {
+            method::ACCESSIBILITY_GET => {
+                let resp = json!(accessibility());
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+
+            method::ACCESSIBILITY_SET => {
+                let resp = match merge_accessibility(payload.as_slice()) {
+                    Ok(next) => {
+                        let prev = accessibility();
+                        set_accessibility(next);
+                        let applied = accessibility();
+                        if prev.screen_reader != applied.screen_reader {
+                            log::info!(
+                                "settings: screen_reader change takes effect on next window creation"
+                            );
+                        }
+                        json!({ "ok": true, "settings": applied })
+                    }
+                    Err(e) => json!({ "ok": false, "error": e }),
+                };
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+
+            method::PRESENT_MODE_GET => {
+                RResult::ROk(Blob::from(present_mode_json().to_string().into_bytes()))
+            }
+
+            method::PRESENT_MODE_SET => {
+                let resp = match parse_present_mode(payload.as_slice()) {
+                    Ok(mode) => {
+                        request_present_mode(mode);
+                        json!({ "ok": true, "requested": mode })
+                    }
+                    Err(e) => json!({ "ok": false, "error": e }),
+                };
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+
+            method::LATENCY_GET => {
+                RResult::ROk(Blob::from(latency_json().to_string().into_bytes()))
+            }
+
+            method::LATENCY_SET => {
+                let resp = match apply_latency(payload.as_slice()) {
+                    Ok(requested) => json!({ "ok": true, "requested": requested }),
+                    Err(e) => json!({ "ok": false, "error": e }),
+                };
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+
+            _ => RResult::RErr(RString::from("unknown method")),
+        }
+    }
+}
+
+pub fn register_settings_service() {
+    let dyn_svc: ServiceV1Dyn<'static> =
+        ServiceV1Dyn::from_value(SettingsService, abi_stable::sabi_trait::TD_Opaque);
+
+    let _ = host_api::host_register_service_impl(dyn_svc, false);
+}
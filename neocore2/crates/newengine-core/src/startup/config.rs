@@ -1,12 +1,17 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
-use std::collections::HashMap;
+use crate::plugins::PluginPermissionPolicy;
+use newengine_ui::text::UiFontConfig;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub enum StartupConfigSource {
     Defaults,
     File { path: PathBuf },
+    /// JSON passed in memory (`StartupLoader::load_json_str`).
+    Inline,
 }
 
 impl Default for StartupConfigSource {
@@ -43,30 +48,111 @@ impl Default for WindowPlacement {
     }
 }
 
+/// Initial fullscreen mode. Monitors are indexed in OS enumeration order; `None` means
+/// the monitor the window opens on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowFullscreen {
+    Windowed,
+    Borderless {
+        monitor: Option<usize>,
+    },
+    /// Exclusive video mode. Missing size/refresh pick the monitor's largest/fastest mode.
+    Exclusive {
+        monitor: Option<usize>,
+        size: Option<(u32, u32)>,
+        refresh_hz: Option<u32>,
+    },
+}
+
+impl Default for WindowFullscreen {
+    #[inline]
+    fn default() -> Self {
+        Self::Windowed
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StartupConfig {
     pub source: StartupConfigSource,
 
     pub log_level: String,
+    /// `%version%` is replaced with `BuildInfo::version_string` when loaded.
     pub window_title: String,
     pub window_size: (u32, u32),
     pub window_placement: WindowPlacement,
+    pub window_fullscreen: WindowFullscreen,
+    /// `window_size` is in logical pixels and scaled by the monitor's DPI factor.
+    pub window_dpi_aware: bool,
 
     /// Path inside assets root, resolved via AssetManager + existing importers.
     /// Example: "ui/icon.png".
     pub window_icon_path: Option<String>,
 
     pub modules_dir: PathBuf,
+    /// Plugin permission policy: `plugins.strict` and the per-id `plugins.allow` lists.
+    pub plugin_permissions: PluginPermissionPolicy,
 
     pub assets_root: PathBuf,
     pub asset_pump_steps: u32,
+    /// Asset import worker threads; 0 imports on the main thread.
+    pub asset_import_workers: u32,
+    /// Resident CPU memory budgets per asset category in MiB; 0 is unlimited. Over budget
+    /// the least recently used assets nobody holds are evicted.
+    pub asset_budget_textures_mb: u32,
+    pub asset_budget_audio_mb: u32,
+    pub asset_budget_blobs_mb: u32,
     pub asset_filesystem_source: bool,
+    pub asset_cache: bool,
+    pub asset_cache_dir: PathBuf,
+    /// Packed archives (`.zip` / `.nepak`) mounted as asset sources.
+    pub asset_archives: Vec<PathBuf>,
+    /// Resolve asset paths ignoring case when the exact spelling is missing.
+    pub asset_case_insensitive: bool,
+    /// Asset server mode: serve imports to other workstations on this address.
+    pub asset_server_listen: Option<String>,
+    /// Address of a team asset server to fetch imports from before importing locally.
+    pub asset_server: Option<String>,
+    /// Serve asset sources over HTTP to embedded web views on this address.
+    pub content_server_listen: Option<String>,
 
     pub render_backend: String,
     pub render_clear_color: [f32; 4],
     pub render_debug_text: String,
+    /// Staging-free uploads into host-visible VRAM when the GPU supports it; off for
+    /// benchmarking the staging path.
+    pub render_direct_upload: bool,
+    /// FIFO presentation when true, immediate (tearing allowed) when false. Toggled at runtime
+    /// through the `engine.settings` service.
+    pub render_vsync: bool,
+    /// Minimum swapchain image count; `0` keeps the backend default (surface minimum + 1).
+    pub render_swapchain_images: u32,
+    /// Acquire late and wait for the GPU after present, trading throughput for input latency.
+    pub render_low_latency: bool,
+    /// Back virtual textures with sparse residency when the device supports it; off forces
+    /// the tiled atlas fallback.
+    pub render_sparse_textures: bool,
+    /// Descriptor-indexed texture arrays for the UI and sprites when the device supports
+    /// them; off keeps one descriptor set per texture.
+    pub render_bindless_textures: bool,
+    /// Frames recorded ahead of the GPU; the backend clamps it to its supported range.
+    pub render_frames_in_flight: u32,
 
     pub ui_backend: UiBackend,
+    /// Accessibility defaults; live changes go through the `engine.settings` service.
+    pub ui_scale: f32,
+    pub ui_high_contrast: bool,
+    pub ui_reduced_motion: bool,
+    pub ui_screen_reader: bool,
+    /// Font faces and per-script fallback chains for localized builds; face paths are
+    /// relative to `assets_root`. `None` keeps the provider's built-in fonts.
+    pub ui_fonts: Option<UiFontConfig>,
+
+    /// Initial values of configuration variables (`crate::cvars`), applied when each one is
+    /// registered.
+    pub cvars: BTreeMap<String, Value>,
+    /// Where persistent cvars are written on shutdown and read back at startup; `None`
+    /// disables persistence.
+    pub cvars_file: Option<PathBuf>,
 
     pub extra: HashMap<String, String>,
 
@@ -84,20 +170,49 @@ impl Default for StartupConfig {
             window_title: "NewEngine".to_owned(),
             window_size: (1600, 900),
             window_placement: WindowPlacement::Default,
+            window_fullscreen: WindowFullscreen::Windowed,
+            window_dpi_aware: false,
 
             window_icon_path: None,
 
             modules_dir: PathBuf::from("./"),
+            plugin_permissions: PluginPermissionPolicy::default(),
 
             assets_root: PathBuf::from("assets"),
             asset_pump_steps: 8,
+            asset_import_workers: 2,
+            asset_budget_textures_mb: 0,
+            asset_budget_audio_mb: 0,
+            asset_budget_blobs_mb: 0,
             asset_filesystem_source: true,
+            asset_cache: true,
+            asset_cache_dir: PathBuf::from(".cache/assets"),
+            asset_archives: Vec::new(),
+            asset_case_insensitive: false,
+            asset_server_listen: None,
+            asset_server: None,
+            content_server_listen: None,
 
             render_backend: "vulkan".to_owned(),
             render_clear_color: [0.02, 0.02, 0.03, 1.0],
             render_debug_text: "NewEngine".to_owned(),
+            render_direct_upload: true,
+            render_vsync: true,
+            render_swapchain_images: 0,
+            render_low_latency: false,
+            render_sparse_textures: true,
+            render_bindless_textures: true,
+            render_frames_in_flight: 2,
 
             ui_backend: UiBackend::default(),
+            ui_scale: 1.0,
+            ui_high_contrast: false,
+            ui_reduced_motion: false,
+            ui_screen_reader: true,
+            ui_fonts: None,
+
+            cvars: BTreeMap::new(),
+            cvars_file: Some(PathBuf::from("cvars.json")),
 
             extra: HashMap::new(),
 
@@ -136,6 +251,8 @@ pub struct StartupLoadReport {
     pub file: Option<PathBuf>,
     pub resolved_from: StartupResolvedFrom,
     pub overrides: Vec<StartupOverride>,
+    /// Config entries that were present but could not be applied.
+    pub warnings: Vec<String>,
 }
 
 impl StartupLoadReport {
@@ -146,6 +263,7 @@ impl StartupLoadReport {
             file: None,
             resolved_from: StartupResolvedFrom::NotProvided,
             overrides: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 }
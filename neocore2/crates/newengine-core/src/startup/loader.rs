@@ -1,12 +1,17 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+use crate::build_info::BuildInfo;
 use crate::error::{EngineError, EngineResult};
+use crate::plugins::{PluginPermission, PluginPermissions};
 use crate::startup::config::UiBackend;
 use crate::startup::{
     ConfigPaths, StartupConfig, StartupConfigSource, StartupLoadReport, StartupOverride,
-    StartupResolvedFrom, WindowPlacement,
+    StartupResolvedFrom, WindowFullscreen, WindowPlacement,
 };
+use newengine_ui::text::UiFontConfig;
 use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -53,6 +58,26 @@ impl StartupLoader {
             Err(e) => return Err(e),
         }
 
+        cfg.window_title = BuildInfo::get().expand(&cfg.window_title);
+
+        Ok((cfg, report))
+    }
+
+    /// Parses a config from memory, e.g. JSON handed over by an embedding host. Relative
+    /// paths stay relative to the working directory.
+    pub fn load_json_str(json: &str) -> EngineResult<(StartupConfig, StartupLoadReport)> {
+        let mut cfg = StartupConfig::default();
+        let mut report = StartupLoadReport::new();
+
+        let parsed: RootJson = serde_json::from_str(json).map_err(|e| {
+            EngineError::Other(format!("startup config parse failed (json): err={e}"))
+        })?;
+        apply_root(&mut cfg, &mut report, parsed);
+
+        cfg.source = StartupConfigSource::Inline;
+        report.source = cfg.source.clone();
+        cfg.window_title = BuildInfo::get().expand(&cfg.window_title);
+
         Ok((cfg, report))
     }
 }
@@ -64,6 +89,8 @@ struct RootJson {
     engine: Option<EngineJson>,
     render: Option<RenderJson>,
     ui: Option<UiJson>,
+    plugins: Option<PluginsJson>,
+    cvars: Option<BTreeMap<String, Value>>,
 }
 
 #[derive(Deserialize)]
@@ -84,6 +111,8 @@ struct WindowJson {
     height: Option<u32>,
 
     placement: Option<WindowPlacementJson>,
+    fullscreen: Option<WindowFullscreenJson>,
+    dpi_aware: Option<bool>,
 
     /// Logical path inside assets, e.g. "ui/icon.png"
     icon: Option<String>,
@@ -96,12 +125,34 @@ struct WindowPlacementJson {
     offset: Option<[i32; 2]>,
 }
 
+#[derive(Deserialize)]
+struct WindowFullscreenJson {
+    /// "windowed" | "borderless" | "exclusive"
+    mode: Option<String>,
+    monitor: Option<usize>,
+    size: Option<[u32; 2]>,
+    refresh_hz: Option<u32>,
+}
+
 #[derive(Deserialize)]
 struct EngineJson {
     assets_root: Option<String>,
     asset_pump_steps: Option<u32>,
+    asset_import_workers: Option<u32>,
+    asset_budget_textures_mb: Option<u32>,
+    asset_budget_audio_mb: Option<u32>,
+    asset_budget_blobs_mb: Option<u32>,
     asset_filesystem_source: Option<bool>,
+    asset_cache: Option<bool>,
+    asset_cache_dir: Option<String>,
+    asset_archives: Option<Vec<String>>,
+    asset_case_insensitive: Option<bool>,
+    asset_server_listen: Option<String>,
+    asset_server: Option<String>,
+    content_server_listen: Option<String>,
     modules_dir: Option<String>,
+    /// Empty string disables cvar persistence.
+    cvars_file: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -109,11 +160,31 @@ struct RenderJson {
     backend: Option<String>,
     clear_color: Option<[f32; 4]>,
     debug_text: Option<String>,
+    direct_upload: Option<bool>,
+    vsync: Option<bool>,
+    swapchain_images: Option<u32>,
+    low_latency: Option<bool>,
+    sparse_textures: Option<bool>,
+    bindless_textures: Option<bool>,
+    frames_in_flight: Option<u32>,
 }
 
 #[derive(Deserialize)]
 struct UiJson {
     backend: Option<String>,
+    scale: Option<f32>,
+    high_contrast: Option<bool>,
+    reduced_motion: Option<bool>,
+    screen_reader: Option<bool>,
+    fonts: Option<UiFontConfig>,
+}
+
+#[derive(Deserialize)]
+struct PluginsJson {
+    /// Require manifests and grant only permissions listed in `allow`.
+    strict: Option<bool>,
+    /// Plugin id -> permission names, e.g. `{ "acme.exporter": ["filesystem"] }`.
+    allow: Option<BTreeMap<String, Vec<String>>>,
 }
 
 fn apply_root(cfg: &mut StartupConfig, report: &mut StartupLoadReport, src: RootJson) {
@@ -135,21 +206,48 @@ fn apply_root(cfg: &mut StartupConfig, report: &mut StartupLoadReport, src: Root
                 (Some(ww), Some(hh)) => {
                     apply_size(report, "window_size", &mut cfg.window_size, (ww, hh));
                 }
-                (Some(_), None) | (None, Some(_)) => report.overrides.push(StartupOverride {
-                    key: "window_size",
-                    from: format_size(cfg.window_size),
-                    to: "ignored (width/height must both be present)".to_owned(),
-                }),
+                (Some(_), None) | (None, Some(_)) => {
+                    report.overrides.push(StartupOverride {
+                        key: "window_size",
+                        from: format_size(cfg.window_size),
+                        to: "ignored (width/height must both be present)".to_owned(),
+                    });
+                    report
+                        .warnings
+                        .push("window: width/height must both be present; size ignored".to_owned());
+                }
                 (None, None) => {}
             }
         }
 
         if let Some(p) = w.placement {
-            if let Some(pl) = parse_placement(p) {
-                apply_placement(report, "window_placement", &mut cfg.window_placement, pl);
+            let kind = p.kind.clone().unwrap_or_default();
+            match parse_placement(p) {
+                Some(pl) => {
+                    apply_placement(report, "window_placement", &mut cfg.window_placement, pl)
+                }
+                None => report
+                    .warnings
+                    .push(format!("window.placement: unknown type '{kind}'; ignored")),
+            }
+        }
+
+        if let Some(f) = w.fullscreen {
+            let mode = f.mode.clone().unwrap_or_default();
+            match parse_fullscreen(f) {
+                Some(fs) => {
+                    apply_fullscreen(report, "window_fullscreen", &mut cfg.window_fullscreen, fs)
+                }
+                None => report
+                    .warnings
+                    .push(format!("window.fullscreen: unknown mode '{mode}'; ignored")),
             }
         }
 
+        if let Some(v) = w.dpi_aware {
+            apply_bool(report, "window_dpi_aware", &mut cfg.window_dpi_aware, v);
+        }
+
         if let Some(icon) = w.icon {
             apply_opt_string(report, "window_icon", &mut cfg.window_icon_path, icon);
         }
@@ -162,6 +260,18 @@ fn apply_root(cfg: &mut StartupConfig, report: &mut StartupLoadReport, src: Root
         if let Some(steps) = engine.asset_pump_steps {
             apply_u32(report, "asset_pump_steps", &mut cfg.asset_pump_steps, steps);
         }
+        if let Some(count) = engine.asset_import_workers {
+            apply_u32(report, "asset_import_workers", &mut cfg.asset_import_workers, count);
+        }
+        if let Some(mb) = engine.asset_budget_textures_mb {
+            apply_u32(report, "asset_budget_textures_mb", &mut cfg.asset_budget_textures_mb, mb);
+        }
+        if let Some(mb) = engine.asset_budget_audio_mb {
+            apply_u32(report, "asset_budget_audio_mb", &mut cfg.asset_budget_audio_mb, mb);
+        }
+        if let Some(mb) = engine.asset_budget_blobs_mb {
+            apply_u32(report, "asset_budget_blobs_mb", &mut cfg.asset_budget_blobs_mb, mb);
+        }
         if let Some(enabled) = engine.asset_filesystem_source {
             apply_bool(
                 report,
@@ -170,9 +280,54 @@ fn apply_root(cfg: &mut StartupConfig, report: &mut StartupLoadReport, src: Root
                 enabled,
             );
         }
+        if let Some(enabled) = engine.asset_cache {
+            apply_bool(report, "asset_cache", &mut cfg.asset_cache, enabled);
+        }
+        if let Some(dir) = engine.asset_cache_dir {
+            apply_path(report, "asset_cache_dir", &mut cfg.asset_cache_dir, dir);
+        }
+        if let Some(enabled) = engine.asset_case_insensitive {
+            apply_bool(
+                report,
+                "asset_case_insensitive",
+                &mut cfg.asset_case_insensitive,
+                enabled,
+            );
+        }
+        if let Some(list) = engine.asset_archives {
+            let next: Vec<PathBuf> = list.into_iter().map(PathBuf::from).collect();
+            if next != cfg.asset_archives {
+                report.overrides.push(StartupOverride {
+                    key: "asset_archives",
+                    from: format!("{:?}", cfg.asset_archives),
+                    to: format!("{next:?}"),
+                });
+                cfg.asset_archives = next;
+            }
+        }
+        if let Some(addr) = engine.asset_server_listen {
+            apply_opt_string(report, "asset_server_listen", &mut cfg.asset_server_listen, addr);
+        }
+        if let Some(addr) = engine.asset_server {
+            apply_opt_string(report, "asset_server", &mut cfg.asset_server, addr);
+        }
+        if let Some(addr) = engine.content_server_listen {
+            apply_opt_string(report, "content_server_listen", &mut cfg.content_server_listen, addr);
+        }
         if let Some(dir) = engine.modules_dir {
             apply_path(report, "modules_dir", &mut cfg.modules_dir, dir);
         }
+        if let Some(file) = engine.cvars_file {
+            let next = (!file.trim().is_empty()).then(|| PathBuf::from(file.trim()));
+            if next != cfg.cvars_file {
+                report.overrides.push(StartupOverride {
+                    key: "cvars_file",
+                    from: format!("{:?}", cfg.cvars_file),
+                    to: format!("{next:?}"),
+                });
+                cfg.cvars_file = next;
+            }
+        }
     }
 
     if let Some(render) = src.render {
@@ -185,6 +340,27 @@ fn apply_root(cfg: &mut StartupConfig, report: &mut StartupLoadReport, src: Root
         if let Some(text) = render.debug_text {
             apply_string(report, "render_debug_text", &mut cfg.render_debug_text, text);
         }
+        if let Some(v) = render.direct_upload {
+            apply_bool(report, "render_direct_upload", &mut cfg.render_direct_upload, v);
+        }
+        if let Some(v) = render.vsync {
+            apply_bool(report, "render_vsync", &mut cfg.render_vsync, v);
+        }
+        if let Some(v) = render.swapchain_images {
+            apply_u32(report, "render_swapchain_images", &mut cfg.render_swapchain_images, v);
+        }
+        if let Some(v) = render.low_latency {
+            apply_bool(report, "render_low_latency", &mut cfg.render_low_latency, v);
+        }
+        if let Some(v) = render.sparse_textures {
+            apply_bool(report, "render_sparse_textures", &mut cfg.render_sparse_textures, v);
+        }
+        if let Some(v) = render.bindless_textures {
+            apply_bool(report, "render_bindless_textures", &mut cfg.render_bindless_textures, v);
+        }
+        if let Some(v) = render.frames_in_flight {
+            apply_u32(report, "render_frames_in_flight", &mut cfg.render_frames_in_flight, v);
+        }
     }
 
     if let Some(ui) = src.ui {
@@ -192,6 +368,59 @@ fn apply_root(cfg: &mut StartupConfig, report: &mut StartupLoadReport, src: Root
             let parsed = parse_ui_backend(&backend);
             apply_ui_backend(report, "ui_backend", &mut cfg.ui_backend, parsed);
         }
+        if let Some(scale) = ui.scale {
+            apply_f32(report, "ui_scale", &mut cfg.ui_scale, scale.clamp(0.5, 4.0));
+        }
+        if let Some(v) = ui.high_contrast {
+            apply_bool(report, "ui_high_contrast", &mut cfg.ui_high_contrast, v);
+        }
+        if let Some(v) = ui.reduced_motion {
+            apply_bool(report, "ui_reduced_motion", &mut cfg.ui_reduced_motion, v);
+        }
+        if let Some(v) = ui.screen_reader {
+            apply_bool(report, "ui_screen_reader", &mut cfg.ui_screen_reader, v);
+        }
+        if let Some(fonts) = ui.fonts {
+            report.overrides.push(StartupOverride {
+                key: "ui_fonts",
+                from: format!("{:?}", cfg.ui_fonts.as_ref().map(|f| f.faces.len())),
+                to: format!("{} faces", fonts.faces.len()),
+            });
+            cfg.ui_fonts = Some(fonts);
+        }
+    }
+
+    if let Some(plugins) = src.plugins {
+        if let Some(v) = plugins.strict {
+            apply_bool(report, "plugins_strict", &mut cfg.plugin_permissions.strict, v);
+        }
+        if let Some(allow) = plugins.allow {
+            for (id, names) in allow {
+                let parsed = PluginPermissions::parse_list(names.iter().map(String::as_str));
+                let permissions = parsed.unwrap_or_else(|unknown| {
+                    report.warnings.push(format!(
+                        "plugins.allow.{id}: unknown permission(s) ignored: {}",
+                        unknown.join(", ")
+                    ));
+                    names.iter().filter_map(|n| PluginPermission::parse(n)).collect()
+                });
+                report.overrides.push(StartupOverride {
+                    key: "plugins_allow",
+                    from: format!("{id}=default"),
+                    to: format!("{id}={permissions}"),
+                });
+                cfg.plugin_permissions.allow.insert(id, permissions);
+            }
+        }
+    }
+
+    if let Some(cvars) = src.cvars {
+        report.overrides.push(StartupOverride {
+            key: "cvars",
+            from: format!("{} values", cfg.cvars.len()),
+            to: format!("{} values", cvars.len()),
+        });
+        cfg.cvars.extend(cvars);
     }
 }
 
@@ -213,6 +442,24 @@ fn parse_placement(p: WindowPlacementJson) -> Option<WindowPlacement> {
     }
 }
 
+fn parse_fullscreen(f: WindowFullscreenJson) -> Option<WindowFullscreen> {
+    let mode = f
+        .mode
+        .unwrap_or_else(|| "windowed".to_owned())
+        .to_ascii_lowercase();
+
+    match mode.as_str() {
+        "windowed" | "none" | "off" => Some(WindowFullscreen::Windowed),
+        "borderless" => Some(WindowFullscreen::Borderless { monitor: f.monitor }),
+        "exclusive" => Some(WindowFullscreen::Exclusive {
+            monitor: f.monitor,
+            size: f.size.map(|[w, h]| (w, h)),
+            refresh_hz: f.refresh_hz,
+        }),
+        _ => None,
+    }
+}
+
 fn parse_ui_backend(s: &str) -> UiBackend {
     let v = s.trim().to_ascii_lowercase();
     match v.as_str() {
@@ -276,6 +523,16 @@ fn apply_bool(report: &mut StartupLoadReport, key: &'static str, dst: &mut bool,
     }
 }
 
+#[inline]
+fn apply_f32(report: &mut StartupLoadReport, key: &'static str, dst: &mut f32, v: f32) {
+    let from = format!("{:.3}", dst);
+    let to = format!("{:.3}", v);
+    if *dst != v {
+        *dst = v;
+        report.overrides.push(StartupOverride { key, from, to });
+    }
+}
+
 #[inline]
 fn apply_size(
     report: &mut StartupLoadReport,
@@ -306,6 +563,21 @@ fn apply_placement(
     }
 }
 
+#[inline]
+fn apply_fullscreen(
+    report: &mut StartupLoadReport,
+    key: &'static str,
+    dst: &mut WindowFullscreen,
+    v: WindowFullscreen,
+) {
+    let from = format!("{:?}", dst);
+    let to = format!("{:?}", v);
+    if *dst != v {
+        *dst = v;
+        report.overrides.push(StartupOverride { key, from, to });
+    }
+}
+
 #[inline]
 fn apply_ui_backend(report: &mut StartupLoadReport, key: &'static str, dst: &mut UiBackend, v: UiBackend) {
     let from = format!("{:?}", dst);
@@ -3,7 +3,7 @@ mod loader;
 
 pub use config::{
     ConfigPaths, StartupConfig, StartupConfigSource, StartupLoadReport, StartupOverride,
-    StartupResolvedFrom, UiBackend, WindowPlacement,
+    StartupResolvedFrom, UiBackend, WindowFullscreen, WindowPlacement,
 };
 
 pub use loader::StartupLoader;
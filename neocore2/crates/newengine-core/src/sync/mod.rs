@@ -1,3 +1,3 @@
 mod sync;
 
-pub use sync::ShutdownToken;
+pub use sync::{CancelToken, ShutdownToken};
@@ -35,3 +35,83 @@ impl ShutdownToken {
         GLOBAL_SHUTDOWN.store(true, Ordering::Relaxed);
     }
 }
+
+    /// Root cancellation token that fires together with this shutdown token.
+    #[inline]
+    pub fn cancel_token(&self) -> CancelToken {
+        CancelToken::with_parent(None, Some(self.clone()))
+    }
+}
+
+struct CancelInner {
+    flag: AtomicBool,
+    parent: Option<CancelToken>,
+    shutdown: Option<ShutdownToken>,
+}
+
+/// Hierarchical cooperative cancellation.
+///
+/// A token reads as cancelled once it, any ancestor, or the `ShutdownToken` at the root is
+/// cancelled. Clones share state; `child` creates a token that can be cancelled on its own
+/// (e.g. per scene or per request) while still following its parent.
+#[derive(Clone)]
+pub struct CancelToken {
+    inner: Arc<CancelInner>,
+}
+
+impl CancelToken {
+    /// Detached root; only cancelled explicitly.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_parent(None, None)
+    }
+
+    #[inline]
+    fn with_parent(parent: Option<CancelToken>, shutdown: Option<ShutdownToken>) -> Self {
+        Self {
+            inner: Arc::new(CancelInner {
+                flag: AtomicBool::new(false),
+                parent,
+                shutdown,
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn child(&self) -> Self {
+        Self::with_parent(Some(self.clone()), None)
+    }
+
+    #[inline]
+    pub fn cancel(&self) {
+        self.inner.flag.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        let mut cur = Some(self);
+        while let Some(t) = cur {
+            if t.inner.flag.load(Ordering::Acquire)
+                || t.inner.shutdown.as_ref().is_some_and(|s| s.is_requested())
+            {
+                return true;
+            }
+            cur = t.inner.parent.as_ref();
+        }
+        false
+    }
+}
+
+impl Default for CancelToken {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
@@ -0,0 +1,210 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Game-time controls: time scale, pause and single-frame stepping.
+//!
+//! The engine scales the wall-clock delta before feeding the fixed-update accumulator and
+//! before handing [`Frame::dt`](crate::frame::Frame::dt) to modules and plugins. While paused
+//! game time stands still: no fixed steps run and `dt` is `0.0`, but `update()` and
+//! `render()` keep running so editors and menus stay responsive ([`Frame::real_dt`] still
+//! carries the wall-clock delta). A step advances a paused engine by exactly one fixed step.
+//!
+//! The engine inserts its [`TimeControl`] into the resources and drives it from the
+//! `time.scale`, `time.pause`, `time.resume` and `time.step` console commands.
+//!
+//! [`Frame::real_dt`]: crate::frame::Frame::real_dt
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Largest accepted time scale; the fixed-step cap makes anything above it pointless.
+pub const MAX_TIME_SCALE: f32 = 64.0;
+
+struct Inner {
+    /// `f32` bits.
+    scale: AtomicU32,
+    paused: AtomicBool,
+    pending_steps: AtomicU32,
+}
+
+/// Shared handle to the engine's game clock. Cheap to clone; clones control the same clock.
+#[derive(Clone)]
+pub struct TimeControl {
+    inner: Arc<Inner>,
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                scale: AtomicU32::new(1.0f32.to_bits()),
+                paused: AtomicBool::new(false),
+                pending_steps: AtomicU32::new(0),
+            }),
+        }
+    }
+}
+
+impl TimeControl {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn scale(&self) -> f32 {
+        f32::from_bits(self.inner.scale.load(Ordering::Acquire))
+    }
+
+    /// Sets the game-time multiplier (`0.5` is half speed). Clamped to
+    /// `0.0..=MAX_TIME_SCALE`; NaN resets it to `1.0`.
+    pub fn set_scale(&self, scale: f32) {
+        let scale = if scale.is_nan() {
+            1.0
+        } else {
+            scale.clamp(0.0, MAX_TIME_SCALE)
+        };
+        self.inner.scale.store(scale.to_bits(), Ordering::Release);
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.inner.paused.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn pause(&self) {
+        self.inner.paused.store(true, Ordering::Release);
+    }
+
+    /// Unpauses and drops steps that were queued but not run yet.
+    #[inline]
+    pub fn resume(&self) {
+        self.inner.pending_steps.store(0, Ordering::Release);
+        self.inner.paused.store(false, Ordering::Release);
+    }
+
+    /// Pauses if running, then queues `frames` single-step frames.
+    pub fn step(&self, frames: u32) {
+        self.pause();
+        let _ = self
+            .inner
+            .pending_steps
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                Some(n.saturating_add(frames))
+            });
+    }
+
+    /// Steps queued with [`step`](Self::step) that have not run yet.
+    #[inline]
+    pub fn pending_steps(&self) -> u32 {
+        self.inner.pending_steps.load(Ordering::Acquire)
+    }
+
+    /// Game-time delta for a frame whose wall-clock delta is `real_dt`: scaled while running,
+    /// `fixed_dt` for a queued step, otherwise `0.0` while paused.
+    pub(crate) fn advance(&self, real_dt: f32, fixed_dt: f32) -> f32 {
+        if !self.is_paused() {
+            return real_dt * self.scale();
+        }
+        let stepped = self
+            .inner
+            .pending_steps
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+            .is_ok();
+        if stepped {
+            fixed_dt
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Replaces the engine's frame clock; see [`Engine::set_time_source`](crate::Engine::set_time_source).
+pub trait TimeSource: Send {
+    /// Called at the start of every frame that is not suspended. `dt` is what the engine's
+    /// clock produced (the clamped wall-clock delta run through the [`TimeControl`]); the
+    /// result feeds the fixed-step accumulator and becomes [`Frame::dt`].
+    ///
+    /// [`Frame::dt`]: crate::frame::Frame::dt
+    fn next_dt(&mut self, dt: f32, fixed_dt: f32) -> f32;
+}
+
+/// Registers the `time.*` console commands for `time`. A later engine replaces them.
+#[cfg(feature = "runtime")]
+pub(crate) fn register_time_commands(time: &TimeControl) {
+    use crate::console::ConsoleCommands;
+
+    let console = ConsoleCommands;
+    for name in ["time.scale", "time.pause", "time.resume", "time.step"] {
+        console.unregister_command(name);
+    }
+
+    let t = time.clone();
+    let scale = console.register_command_with_usage(
+        "time.scale",
+        "time.scale [factor]",
+        move |args| {
+            let args = args.trim();
+            if args.is_empty() {
+                return Ok(format!("time.scale = {}", t.scale()));
+            }
+            let v: f32 = args
+                .parse()
+                .map_err(|_| format!("time.scale: not a number: '{args}'"))?;
+            if !v.is_finite() || v < 0.0 {
+                return Err(format!(
+                    "time.scale: expected 0..={MAX_TIME_SCALE}, got {v}"
+                ));
+            }
+            t.set_scale(v);
+            Ok(format!("time.scale = {}", t.scale()))
+        },
+        "Get or set the game time scale",
+    );
+
+    let t = time.clone();
+    let pause = console.register_command_with_usage(
+        "time.pause",
+        "time.pause",
+        move |_| {
+            t.pause();
+            Ok("paused".into())
+        },
+        "Pause game time",
+    );
+
+    let t = time.clone();
+    let resume = console.register_command_with_usage(
+        "time.resume",
+        "time.resume",
+        move |_| {
+            t.resume();
+            Ok("resumed".into())
+        },
+        "Resume game time",
+    );
+
+    let t = time.clone();
+    let step = console.register_command_with_usage(
+        "time.step",
+        "time.step [frames]",
+        move |args| {
+            let args = args.trim();
+            let frames: u32 = if args.is_empty() {
+                1
+            } else {
+                args.parse()
+                    .map_err(|_| format!("time.step: not a frame count: '{args}'"))?
+            };
+            t.step(frames);
+            Ok(format!("stepping {frames} frame(s)"))
+        },
+        "Pause and advance game time by fixed steps",
+    );
+
+    for res in [scale, pause, resume, step] {
+        if let Err(e) = res {
+            log::warn!("time.commands register failed: {e}");
+        }
+    }
+}
@@ -0,0 +1,391 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Per-frame activity trace exposed through the `engine.trace` service.
+//!
+//! While enabled, the engine records `EventHub` publishes, `Bus` sends, plugin events, module
+//! stage timings and asset state transitions, and hosts or backends add markers for work that is
+//! not otherwise visible (e.g. `render.swapchain_rebuild`). Records are grouped by frame into a
+//! ring of recent frames, so a slow frame can be read together with what led up to it instead
+//! of being reconstructed from logs.
+//!
+//! Recording is off by default; a disabled trace costs one atomic load per hook.
+
+use crate::module::current_module_id;
+use crate::plugins::host_api;
+use abi_stable::std_types::{RResult, RString};
+#[cfg(feature = "runtime")]
+use newengine_assets::{AssetEvent, AssetStore};
+use newengine_plugin_api::{Blob, CapabilityId, MethodName, ServiceV1, ServiceV1Dyn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+#[cfg(feature = "runtime")]
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "runtime")]
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+pub const TRACE_SERVICE_ID: &str = "engine.trace";
+
+pub mod method {
+    pub const ENABLE: &str = "trace.enable";
+    pub const FRAMES_JSON: &str = "trace.frames_json";
+    pub const CLEAR: &str = "trace.clear";
+}
+
+/// Frames kept by default (a few seconds at 60 Hz).
+pub const DEFAULT_CAPACITY: usize = 300;
+/// Records beyond this within one frame are counted in [`TraceFrame::dropped`] instead.
+const MAX_RECORDS_PER_FRAME: usize = 4096;
+/// Longer details are cut; the trace is for correlation, not payload inspection.
+const MAX_DETAIL_LEN: usize = 160;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceKind {
+    /// Typed `EventHub` publish or plugin event.
+    Event,
+    /// `Bus` message.
+    Bus,
+    /// Module stage call; carries a duration.
+    Stage,
+    /// Asset state transition.
+    Asset,
+    /// Host or backend work (resize, swapchain rebuild, ...).
+    Marker,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceRecord {
+    /// Microseconds since the frame opened.
+    pub at_us: u64,
+    /// Zero for instantaneous records.
+    pub dur_us: u64,
+    pub kind: TraceKind,
+    /// Module whose callback was running, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<&'static str>,
+    pub topic: Cow<'static, str>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+}
+
+/// Everything recorded between two engine frames, host events included.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceFrame {
+    /// Engine frame index.
+    pub frame: u64,
+    /// Wall time from the end of the previous frame to the end of this one.
+    pub dur_us: u64,
+    pub dropped: u32,
+    pub records: Vec<TraceRecord>,
+}
+
+/// Selects records for [`frames`]. Empty lists match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TraceFilter {
+    /// Most recent frames to return; 0 returns all retained frames.
+    pub frames: usize,
+    /// Exact module ids.
+    pub modules: Vec<String>,
+    /// Topic substrings.
+    pub topics: Vec<String>,
+    pub kinds: Vec<TraceKind>,
+}
+
+impl TraceFilter {
+    fn matches(&self, r: &TraceRecord) -> bool {
+        (self.kinds.is_empty() || self.kinds.contains(&r.kind))
+            && (self.modules.is_empty()
+                || r.module.is_some_and(|m| self.modules.iter().any(|x| x == m)))
+            && (self.topics.is_empty() || self.topics.iter().any(|t| r.topic.contains(t.as_str())))
+    }
+}
+
+struct OpenFrame {
+    start: Instant,
+    dropped: u32,
+    records: Vec<TraceRecord>,
+}
+
+struct Trace {
+    capacity: usize,
+    frames: VecDeque<TraceFrame>,
+    open: Option<OpenFrame>,
+}
+
+impl Trace {
+    fn open(&mut self) -> &mut OpenFrame {
+        self.open.get_or_insert_with(|| OpenFrame {
+            start: Instant::now(),
+            dropped: 0,
+            records: Vec::new(),
+        })
+    }
+
+    fn push(
+        &mut self,
+        at: Instant,
+        dur: Duration,
+        kind: TraceKind,
+        module: Option<&'static str>,
+        topic: Cow<'static, str>,
+        detail: String,
+    ) {
+        let f = self.open();
+        if f.records.len() >= MAX_RECORDS_PER_FRAME {
+            f.dropped = f.dropped.saturating_add(1);
+            return;
+        }
+        f.records.push(TraceRecord {
+            at_us: at.saturating_duration_since(f.start).as_micros() as u64,
+            dur_us: dur.as_micros() as u64,
+            kind,
+            module,
+            topic,
+            detail,
+        });
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TRACE: OnceLock<Mutex<Trace>> = OnceLock::new();
+
+#[inline]
+fn trace() -> &'static Mutex<Trace> {
+    TRACE.get_or_init(|| {
+        Mutex::new(Trace {
+            capacity: DEFAULT_CAPACITY,
+            frames: VecDeque::new(),
+            open: None,
+        })
+    })
+}
+
+#[inline]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Starts or stops recording. Retained frames are kept until [`clear`].
+pub fn set_enabled(enabled: bool) {
+    if ENABLED.swap(enabled, Ordering::Relaxed) != enabled {
+        log::info!("trace: {}", if enabled { "enabled" } else { "disabled" });
+        if !enabled {
+            trace().lock().open = None;
+        }
+    }
+}
+
+/// Number of frames retained; older frames are dropped first.
+pub fn set_capacity(frames: usize) {
+    let mut g = trace().lock();
+    g.capacity = frames.max(1);
+    while g.frames.len() > g.capacity {
+        g.frames.pop_front();
+    }
+}
+
+pub fn clear() {
+    let mut g = trace().lock();
+    g.frames.clear();
+    g.open = None;
+}
+
+/// Records an instantaneous entry attributed to the current module. `detail` is only
+/// evaluated while recording.
+#[inline]
+pub fn record(kind: TraceKind, topic: impl Into<Cow<'static, str>>, detail: impl FnOnce() -> String) {
+    if !is_enabled() {
+        return;
+    }
+    let detail = truncate(detail());
+    trace()
+        .lock()
+        .push(Instant::now(), Duration::ZERO, kind, current_module_id(), topic.into(), detail);
+}
+
+/// Records work of `module` that started at `start` and took `dur`.
+#[inline]
+pub fn record_span(
+    kind: TraceKind,
+    module: Option<&'static str>,
+    topic: impl Into<Cow<'static, str>>,
+    start: Instant,
+    dur: Duration,
+) {
+    if !is_enabled() {
+        return;
+    }
+    trace().lock().push(start, dur, kind, module, topic.into(), String::new());
+}
+
+/// Closes the frame `frame` and opens the next one. Called by the engine after each tick.
+pub(crate) fn end_frame(frame: u64) {
+    if !is_enabled() {
+        return;
+    }
+    let now = Instant::now();
+    let mut g = trace().lock();
+    let open = g.open.take().unwrap_or_else(|| OpenFrame {
+        start: now,
+        dropped: 0,
+        records: Vec::new(),
+    });
+    g.frames.push_back(TraceFrame {
+        frame,
+        dur_us: now.saturating_duration_since(open.start).as_micros() as u64,
+        dropped: open.dropped,
+        records: open.records,
+    });
+    while g.frames.len() > g.capacity {
+        g.frames.pop_front();
+    }
+    g.open = Some(OpenFrame {
+        start: now,
+        dropped: 0,
+        records: Vec::new(),
+    });
+}
+
+/// Closed frames matching `filter`, oldest first. Frames whose records are all filtered out
+/// are still returned so the timeline keeps its frame axis.
+pub fn frames(filter: &TraceFilter) -> Vec<TraceFrame> {
+    let g = trace().lock();
+    let skip = match filter.frames {
+        0 => 0,
+        n => g.frames.len().saturating_sub(n),
+    };
+    g.frames
+        .iter()
+        .skip(skip)
+        .map(|f| TraceFrame {
+            frame: f.frame,
+            dur_us: f.dur_us,
+            dropped: f.dropped,
+            records: f.records.iter().filter(|r| filter.matches(r)).cloned().collect(),
+        })
+        .collect()
+}
+
+/// Records asset state transitions as [`TraceKind::Asset`].
+#[cfg(feature = "runtime")]
+pub fn install_asset_trace(store: &AssetStore) {
+    store.set_event_observer(Some(Arc::new(|ev: &AssetEvent, path: Option<&Path>| {
+        if !is_enabled() {
+            return;
+        }
+        let topic = match ev {
+            AssetEvent::Ready { .. } => "asset.ready",
+            AssetEvent::Failed { .. } => "asset.failed",
+            AssetEvent::Unloaded { .. } => "asset.unloaded",
+            AssetEvent::Evicted { .. } => "asset.evicted",
+            AssetEvent::Cancelled { .. } => "asset.cancelled",
+            AssetEvent::DependencyChanged { .. } => "asset.dependency_changed",
+        };
+        record(TraceKind::Asset, topic, || match path {
+            Some(p) => p.display().to_string(),
+            None => format!("{:032x}", ev.id().to_u128()),
+        });
+    })));
+}
+
+/// `a::b::Event<c::D>` -> `Event<c::D>`.
+#[inline]
+pub fn short_type_name(full: &'static str) -> &'static str {
+    let head = full.split('<').next().unwrap_or(full);
+    match head.rfind("::") {
+        Some(i) => &full[i + 2..],
+        None => full,
+    }
+}
+
+fn truncate(mut s: String) -> String {
+    if s.len() > MAX_DETAIL_LEN {
+        let mut end = MAX_DETAIL_LEN;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.truncate(end);
+        s.push('…');
+    }
+    s
+}
+
+#[derive(Deserialize)]
+struct EnableRequest {
+    enabled: bool,
+    #[serde(default)]
+    capacity: Option<usize>,
+}
+
+struct TraceService;
+
+impl ServiceV1 for TraceService {
+    fn id(&self) -> CapabilityId {
+        RString::from(TRACE_SERVICE_ID)
+    }
+
+    fn describe(&self) -> RString {
+        RString::from(
+            json!({
+                "id": TRACE_SERVICE_ID,
+                "version": 1,
+                "permissions": [],
+                "methods": [
+                    { "name": method::ENABLE, "payload": "json {enabled, capacity?}", "returns": "json {ok, enabled, error?}" },
+                    { "name": method::FRAMES_JSON, "payload": "json {frames?, modules?, topics?, kinds?} or empty", "returns": "json {enabled, frames:[{frame, dur_us, dropped, records:[{at_us, dur_us, kind, module?, topic, detail?}]}]}" },
+                    { "name": method::CLEAR, "payload": "empty", "returns": "json {ok}" }
+                ]
+            })
+            .to_string(),
+        )
+    }
+
+    fn call(&self, method: MethodName, payload: Blob) -> RResult<Blob, RString> {
+        let resp = match method.to_string().as_str() {
+            method::ENABLE => match serde_json::from_slice::<EnableRequest>(payload.as_slice()) {
+                Ok(r) => {
+                    if let Some(n) = r.capacity {
+                        set_capacity(n);
+                    }
+                    set_enabled(r.enabled);
+                    json!({ "ok": true, "enabled": is_enabled() })
+                }
+                Err(e) => json!({ "ok": false, "enabled": is_enabled(), "error": format!("bad trace json: {e}") }),
+            },
+
+            method::FRAMES_JSON => {
+                let filter = if payload.as_slice().is_empty() {
+                    TraceFilter::default()
+                } else {
+                    match serde_json::from_slice::<TraceFilter>(payload.as_slice()) {
+                        Ok(f) => f,
+                        Err(e) => return RResult::RErr(RString::from(format!("bad filter json: {e}"))),
+                    }
+                };
+                json!({ "enabled": is_enabled(), "frames": frames(&filter) })
+            }
+
+            method::CLEAR => {
+                clear();
+                json!({ "ok": true })
+            }
+
+            _ => return RResult::RErr(RString::from("unknown method")),
+        };
+        RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+    }
+}
+
+pub fn register_trace_service() {
+    let dyn_svc: ServiceV1Dyn<'static> =
+        ServiceV1Dyn::from_value(TraceService, abi_stable::sabi_trait::TD_Opaque);
+
+    let _ = host_api::host_register_service_impl(dyn_svc, false);
+}
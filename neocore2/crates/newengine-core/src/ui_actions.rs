@@ -0,0 +1,136 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Declarative markup actions: `on_click="console:render.wireframe on"` and
+//! `on_click="emit:MyEvent"` are run by the engine instead of app code matching widget ids.
+//!
+//! The UI owner hands drained events to [`dispatch`] (any thread); the engine runs the queued
+//! console commands and publishes the events on its `EventHub` at the end of the next frame.
+//! Named events are published as [`UiEmit`] unless a typed constructor was registered with
+//! [`register_ui_event`].
+
+use crate::console::COMMAND_SERVICE_ID;
+use crate::error::EngineResult;
+use crate::events::EventHub;
+use newengine_ui::markup::{UiAction, UiEvent};
+use parking_lot::Mutex;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+/// Queued actions beyond this are dropped (engine not ticking).
+const MAX_PENDING: usize = 1024;
+
+/// A named event raised by `emit:<name> [arg]` in markup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UiEmit {
+    pub name: String,
+    pub arg: Option<String>,
+    /// Id of the widget that fired.
+    pub target_id: String,
+    /// Widget value for change/submit events.
+    pub value: Option<String>,
+}
+
+type Publisher = Arc<dyn Fn(&UiEmit, &EventHub) -> EngineResult<()> + Send + Sync>;
+
+enum Pending {
+    Console(String),
+    Emit(UiEmit),
+}
+
+#[derive(Default)]
+struct Registry {
+    publishers: HashMap<String, Publisher>,
+    pending: Vec<Pending>,
+}
+
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+#[inline]
+fn registry() -> &'static Mutex<Registry> {
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+/// Publishes `emit:<name>` as the typed event built by `make` instead of [`UiEmit`].
+/// `make` returning `None` (e.g. an unparsable argument) drops the event.
+pub fn register_ui_event<T, F>(name: impl Into<String>, make: F)
+where
+    T: Any + Send + Sync + 'static,
+    F: Fn(&UiEmit) -> Option<T> + Send + Sync + 'static,
+{
+    let publisher: Publisher = Arc::new(move |emit, hub| match make(emit) {
+        Some(ev) => hub.publish(ev),
+        None => {
+            log::warn!("ui.actions: '{}' rejected by its constructor", emit.name);
+            Ok(())
+        }
+    });
+    registry().lock().publishers.insert(name.into(), publisher);
+}
+
+pub fn unregister_ui_event(name: &str) -> bool {
+    registry().lock().publishers.remove(name).is_some()
+}
+
+/// Queues the console and emit actions of `events`; other actions are left to app code.
+pub fn dispatch(events: &[UiEvent]) {
+    let mut g = registry().lock();
+    for ev in events {
+        for action in ev.resolved_actions() {
+            let pending = match action {
+                UiAction::Console(line) => Pending::Console(line),
+                UiAction::Emit { name, arg } => Pending::Emit(UiEmit {
+                    name,
+                    arg,
+                    target_id: ev.target_id.clone(),
+                    value: ev.value.clone(),
+                }),
+                UiAction::Custom(_) => continue,
+            };
+            if g.pending.len() >= MAX_PENDING {
+                log::warn!(
+                    "ui.actions: queue full, dropping action of '{}'",
+                    ev.target_id
+                );
+                continue;
+            }
+            g.pending.push(pending);
+        }
+    }
+}
+
+/// Runs the actions queued since the last frame.
+pub(crate) fn run_pending(events: &EventHub) {
+    let (pending, publishers) = {
+        let mut g = registry().lock();
+        if g.pending.is_empty() {
+            return;
+        }
+        (std::mem::take(&mut g.pending), g.publishers.clone())
+    };
+
+    for p in pending {
+        match p {
+            Pending::Console(line) => {
+                log::debug!("ui.actions: console '{line}'");
+                let out = crate::host_services::call_service_v1(
+                    COMMAND_SERVICE_ID,
+                    crate::console::method::EXEC,
+                    line.as_bytes(),
+                );
+                if let Err(e) = out {
+                    log::warn!("ui.actions: console '{line}' failed: {e}");
+                }
+            }
+            Pending::Emit(emit) => {
+                let res = match publishers.get(&emit.name) {
+                    Some(publish) => publish(&emit, events),
+                    None => events.publish(emit.clone()),
+                };
+                if let Err(e) = res {
+                    log::warn!("ui.actions: emit '{}' failed: {e}", emit.name);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,226 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Remote UI mirroring: the UI owner publishes its markup document and per-frame state diffs,
+//! remote tools poll them through the `engine.ui.remote` service and send synthetic events
+//! back, which the UI owner applies on its next frame.
+
+use crate::plugins::host_api;
+use abi_stable::std_types::{RResult, RString};
+use newengine_plugin_api::{Blob, CapabilityId, MethodName, ServiceV1, ServiceV1Dyn};
+use newengine_ui::markup::{UiRemoteEvent, UiStateDiff};
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::OnceLock;
+
+pub const UI_REMOTE_SERVICE_ID: &str = "engine.ui.remote";
+
+pub mod method {
+    pub const DOC_JSON: &str = "ui.doc_json";
+    pub const DIFF_JSON: &str = "ui.diff_json";
+    pub const EVENT: &str = "ui.event";
+}
+
+/// Diffs retained for pollers; a remote that falls further behind receives a full snapshot.
+const DIFF_HISTORY: usize = 256;
+/// Pending remote events beyond this are dropped (UI owner not draining).
+const MAX_PENDING_EVENTS: usize = 1024;
+
+#[derive(Default)]
+struct Hub {
+    doc: Option<Value>,
+    /// Bumped on every document publish; remotes rebuild their tree when it changes.
+    doc_gen: u64,
+
+    seq: u64,
+    strings: BTreeMap<String, String>,
+    vars: BTreeMap<String, String>,
+    bindings: BTreeMap<String, String>,
+    diffs: VecDeque<UiStateDiff>,
+
+    events: Vec<UiRemoteEvent>,
+}
+
+impl Hub {
+    fn snapshot(&self) -> UiStateDiff {
+        UiStateDiff {
+            seq: self.seq,
+            full: true,
+            strings: self
+                .strings
+                .iter()
+                .map(|(k, v)| (k.clone(), Some(v.clone())))
+                .collect(),
+            vars: self
+                .vars
+                .iter()
+                .map(|(k, v)| (k.clone(), Some(v.clone())))
+                .collect(),
+            bindings: self
+                .bindings
+                .iter()
+                .map(|(k, v)| (k.clone(), Some(v.clone())))
+                .collect(),
+            events: Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, diff: &UiStateDiff) {
+        if diff.full {
+            self.strings.clear();
+            self.vars.clear();
+            self.bindings.clear();
+        }
+        merge(&mut self.strings, &diff.strings);
+        merge(&mut self.vars, &diff.vars);
+        merge(&mut self.bindings, &diff.bindings);
+        self.seq = diff.seq;
+    }
+}
+
+fn merge(dst: &mut BTreeMap<String, String>, src: &BTreeMap<String, Option<String>>) {
+    for (k, v) in src.iter() {
+        match v {
+            Some(v) => {
+                dst.insert(k.clone(), v.clone());
+            }
+            None => {
+                dst.remove(k);
+            }
+        }
+    }
+}
+
+static HUB: OnceLock<Mutex<Hub>> = OnceLock::new();
+
+#[inline]
+fn hub() -> &'static Mutex<Hub> {
+    HUB.get_or_init(|| Mutex::new(Hub::default()))
+}
+
+/// Publishes the (re)loaded markup document (`UiMarkupDoc::to_remote_json`).
+pub fn publish_doc(doc: Value) {
+    let mut g = hub().lock();
+    g.doc = Some(doc);
+    g.doc_gen += 1;
+}
+
+/// Publishes a state diff produced by `UiStateSync`.
+pub fn publish_diff(diff: UiStateDiff) {
+    let mut g = hub().lock();
+    g.apply(&diff);
+    g.diffs.push_back(diff);
+    while g.diffs.len() > DIFF_HISTORY {
+        g.diffs.pop_front();
+    }
+}
+
+/// Events sent by remote tools since the last call, oldest first.
+pub fn take_events() -> Vec<UiRemoteEvent> {
+    std::mem::take(&mut hub().lock().events)
+}
+
+#[derive(Debug, Deserialize)]
+struct DiffRequest {
+    #[serde(default)]
+    since: u64,
+    #[serde(default)]
+    doc_gen: u64,
+}
+
+struct UiRemoteService;
+
+impl UiRemoteService {
+    fn diff_json(&self, payload: &[u8]) -> Value {
+        let req = serde_json::from_slice::<DiffRequest>(payload).unwrap_or(DiffRequest {
+            since: 0,
+            doc_gen: 0,
+        });
+
+        let g = hub().lock();
+
+        // Remote is on an old document or behind the retained history: resync.
+        let oldest = g.diffs.front().map(|d| d.seq).unwrap_or(g.seq + 1);
+        if req.doc_gen != g.doc_gen || req.since + 1 < oldest {
+            return json!({
+                "resync": true,
+                "doc_gen": g.doc_gen,
+                "diffs": [g.snapshot()],
+            });
+        }
+
+        let diffs: Vec<&UiStateDiff> = g.diffs.iter().filter(|d| d.seq > req.since).collect();
+        json!({
+            "resync": false,
+            "doc_gen": g.doc_gen,
+            "diffs": diffs,
+        })
+    }
+}
+
+impl ServiceV1 for UiRemoteService {
+    fn id(&self) -> CapabilityId {
+        RString::from(UI_REMOTE_SERVICE_ID)
+    }
+
+    fn describe(&self) -> RString {
+        RString::from(
+            json!({
+                "id": UI_REMOTE_SERVICE_ID,
+                "version": 1,
+                "permissions": ["process"],
+                "methods": [
+                    { "name": method::DOC_JSON, "payload": "empty", "returns": "json {doc_gen, doc, state: UiStateDiff}" },
+                    { "name": method::DIFF_JSON, "payload": "json {since, doc_gen}", "returns": "json {resync, doc_gen, diffs:[UiStateDiff]}" },
+                    { "name": method::EVENT, "payload": "json UiRemoteEvent", "returns": "json {ok, error?}" }
+                ]
+            })
+            .to_string(),
+        )
+    }
+
+    fn call(&self, method: MethodName, payload: Blob) -> RResult<Blob, RString> {
+        match method.to_string().as_str() {
+            method::DOC_JSON => {
+                let g = hub().lock();
+                let resp = json!({
+                    "doc_gen": g.doc_gen,
+                    "doc": g.doc,
+                    "state": g.snapshot(),
+                });
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+
+            method::DIFF_JSON => {
+                let resp = self.diff_json(payload.as_slice());
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+
+            method::EVENT => {
+                let resp = match serde_json::from_slice::<UiRemoteEvent>(payload.as_slice()) {
+                    Ok(ev) => {
+                        let mut g = hub().lock();
+                        if g.events.len() < MAX_PENDING_EVENTS {
+                            g.events.push(ev);
+                            json!({ "ok": true })
+                        } else {
+                            json!({ "ok": false, "error": "event queue full" })
+                        }
+                    }
+                    Err(e) => json!({ "ok": false, "error": format!("bad event json: {e}") }),
+                };
+                RResult::ROk(Blob::from(resp.to_string().into_bytes()))
+            }
+
+            _ => RResult::RErr(RString::from("unknown method")),
+        }
+    }
+}
+
+pub fn register_ui_remote_service() {
+    let dyn_svc: ServiceV1Dyn<'static> =
+        ServiceV1Dyn::from_value(UiRemoteService, abi_stable::sabi_trait::TD_Opaque);
+
+    let _ = host_api::host_register_service_impl(dyn_svc, false);
+}
@@ -0,0 +1,132 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use ahash::AHashMap;
+use std::any::TypeId;
+
+use crate::component::{Column, Component, ComponentInfo, TypedColumn};
+use crate::entity::Entity;
+
+/// All entities with exactly the same component set, stored column-wise.
+pub struct Archetype {
+    infos: Vec<ComponentInfo>,
+    columns: Vec<Box<dyn Column>>,
+    index: AHashMap<TypeId, usize>,
+    pub(crate) entities: Vec<Entity>,
+}
+
+impl Archetype {
+    /// `infos` must be sorted by type id and free of duplicates.
+    pub(crate) fn new(infos: Vec<ComponentInfo>) -> Self {
+        let columns = infos.iter().map(|i| (i.new_column)()).collect();
+        let index = infos
+            .iter()
+            .enumerate()
+            .map(|(i, info)| (info.type_id, i))
+            .collect();
+        Self {
+            infos,
+            columns,
+            index,
+            entities: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    #[inline]
+    pub fn has(&self, t: TypeId) -> bool {
+        self.index.contains_key(&t)
+    }
+
+    #[inline]
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// Component type names, for diagnostics.
+    pub fn component_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.infos.iter().map(|i| i.name)
+    }
+
+    #[inline]
+    pub(crate) fn infos(&self) -> &[ComponentInfo] {
+        &self.infos
+    }
+
+    #[inline]
+    pub(crate) fn type_ids(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.infos.iter().map(|i| i.type_id)
+    }
+
+    #[inline]
+    pub(crate) fn column<T: Component>(&self) -> Option<&TypedColumn<T>> {
+        let i = *self.index.get(&TypeId::of::<T>())?;
+        self.columns[i].as_any().downcast_ref::<TypedColumn<T>>()
+    }
+
+    #[inline]
+    pub(crate) fn column_mut<T: Component>(&mut self) -> Option<&mut TypedColumn<T>> {
+        let i = *self.index.get(&TypeId::of::<T>())?;
+        self.columns[i].as_any_mut().downcast_mut::<TypedColumn<T>>()
+    }
+
+    /// Removes `row`, dropping its components. Returns the entity swapped into `row`, if any.
+    pub(crate) fn swap_remove(&mut self, row: usize) -> Option<Entity> {
+        for c in self.columns.iter_mut() {
+            c.swap_remove_drop(row);
+        }
+        self.entities.swap_remove(row);
+        self.entities.get(row).copied()
+    }
+
+    /// Moves `row` into `dst`. Components `dst` lacks are dropped; components `dst` has but
+    /// this archetype lacks must be pushed by the caller afterwards. The `taken` column has
+    /// already been swap-removed by the caller and is skipped.
+    ///
+    /// Returns `(new row in dst, entity swapped into row here)`.
+    pub(crate) fn move_row(
+        &mut self,
+        row: usize,
+        dst: &mut Archetype,
+        taken: Option<TypeId>,
+    ) -> (usize, Option<Entity>) {
+        let e = self.entities.swap_remove(row);
+        let dst_row = dst.entities.len();
+        dst.entities.push(e);
+
+        for (i, info) in self.infos.iter().enumerate() {
+            if Some(info.type_id) == taken {
+                continue;
+            }
+            match dst.index.get(&info.type_id) {
+                Some(&j) => self.columns[i].swap_remove_into(row, dst.columns[j].as_mut()),
+                None => self.columns[i].swap_remove_drop(row),
+            }
+        }
+
+        (dst_row, self.entities.get(row).copied())
+    }
+
+    /// Debug check that every column has one row per entity.
+    #[inline]
+    pub(crate) fn debug_validate(&self) {
+        debug_assert!(self.columns.iter().all(|c| c.len() == self.entities.len()));
+    }
+}
+
+impl std::fmt::Debug for Archetype {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Archetype")
+            .field("components", &self.infos)
+            .field("len", &self.entities.len())
+            .finish()
+    }
+}
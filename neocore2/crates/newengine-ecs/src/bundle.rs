@@ -0,0 +1,50 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::archetype::Archetype;
+use crate::component::{Component, ComponentInfo};
+
+/// A tuple of components spawned or inserted together: `(Transform, Velocity)`, `(Name,)`.
+pub trait Bundle: Send + Sync + 'static {
+    /// Component infos in declaration order (unsorted).
+    fn infos(out: &mut Vec<ComponentInfo>);
+
+    /// Writes the components into `row` of `arch`. Columns that already hold the row (the
+    /// entity had the component before) are overwritten and marked changed; others are pushed.
+    fn write(self, arch: &mut Archetype, row: usize, tick: u32);
+}
+
+macro_rules! tuple_bundle {
+    ($($t:ident),+) => {
+        impl<$($t: Component),+> Bundle for ($($t,)+) {
+            #[inline]
+            fn infos(out: &mut Vec<ComponentInfo>) {
+                $(out.push(ComponentInfo::of::<$t>());)+
+            }
+
+            #[allow(non_snake_case)]
+            fn write(self, arch: &mut Archetype, row: usize, tick: u32) {
+                let ($($t,)+) = self;
+                $(
+                    let col = arch
+                        .column_mut::<$t>()
+                        .expect("bundle component missing from target archetype");
+                    if col.data.len() > row {
+                        col.data[row] = $t;
+                        col.changed[row] = tick;
+                    } else {
+                        col.push($t, tick);
+                    }
+                )+
+            }
+        }
+    };
+}
+
+tuple_bundle!(A);
+tuple_bundle!(A, B);
+tuple_bundle!(A, B, C);
+tuple_bundle!(A, B, C, D);
+tuple_bundle!(A, B, C, D, E);
+tuple_bundle!(A, B, C, D, E, F);
+tuple_bundle!(A, B, C, D, E, F, G);
+tuple_bundle!(A, B, C, D, E, F, G, H);
@@ -0,0 +1,98 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::bundle::Bundle;
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::world::World;
+
+type WorldFn = Box<dyn FnOnce(&mut World) + Send>;
+
+/// Deferred structural changes, recorded while the world is borrowed (e.g. during a query)
+/// and applied in order by `apply`.
+#[derive(Default)]
+pub struct Commands {
+    queue: Vec<WorldFn>,
+}
+
+impl Commands {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Reserves the entity now so it can be referenced by later commands.
+    pub fn spawn<B: Bundle>(&mut self, world: &World, bundle: B) -> Entity {
+        let e = world.reserve_entity();
+        self.queue.push(Box::new(move |w| {
+            if let Err(err) = w.insert(e, bundle) {
+                log::warn!(target: "ecs", "commands.spawn failed err='{}'", err);
+            }
+        }));
+        e
+    }
+
+    pub fn despawn(&mut self, e: Entity) {
+        self.queue.push(Box::new(move |w| {
+            w.despawn(e);
+        }));
+    }
+
+    pub fn insert<B: Bundle>(&mut self, e: Entity, bundle: B) {
+        self.queue.push(Box::new(move |w| {
+            if let Err(err) = w.insert(e, bundle) {
+                log::warn!(target: "ecs", "commands.insert failed err='{}'", err);
+            }
+        }));
+    }
+
+    /// Removes `T`; missing entities or components are ignored.
+    pub fn remove<T: Component>(&mut self, e: Entity) {
+        self.queue.push(Box::new(move |w| {
+            let _ = w.remove::<T>(e);
+        }));
+    }
+
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) {
+        self.queue.push(Box::new(move |w| {
+            if let Err(err) = w.set_parent(child, parent) {
+                log::warn!(target: "ecs", "commands.set_parent failed err='{}'", err);
+            }
+        }));
+    }
+
+    pub fn despawn_recursive(&mut self, e: Entity) {
+        self.queue.push(Box::new(move |w| {
+            w.despawn_recursive(e);
+        }));
+    }
+
+    /// Arbitrary deferred world access.
+    pub fn add(&mut self, f: impl FnOnce(&mut World) + Send + 'static) {
+        self.queue.push(Box::new(f));
+    }
+
+    pub fn apply(&mut self, world: &mut World) {
+        world.flush();
+        for f in self.queue.drain(..) {
+            f(world);
+        }
+    }
+}
+
+impl std::fmt::Debug for Commands {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Commands")
+            .field("len", &self.queue.len())
+            .finish()
+    }
+}
@@ -0,0 +1,109 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use std::any::{Any, TypeId};
+
+/// Anything `Send + Sync + 'static` can be a component.
+pub trait Component: Send + Sync + 'static {}
+
+impl<T: Send + Sync + 'static> Component for T {}
+
+/// Type-erased column operations needed when entities move between archetypes.
+pub(crate) trait Column: Send + Sync {
+    fn len(&self) -> usize;
+    fn swap_remove_drop(&mut self, row: usize);
+    /// Moves `row` (value and ticks) to the end of `dst`, which must hold the same type.
+    fn swap_remove_into(&mut self, row: usize, dst: &mut dyn Column);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// Dense storage for one component type inside one archetype, with per-row change ticks.
+pub(crate) struct TypedColumn<T: Component> {
+    pub(crate) data: Vec<T>,
+    pub(crate) added: Vec<u32>,
+    pub(crate) changed: Vec<u32>,
+}
+
+impl<T: Component> Default for TypedColumn<T> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            data: Vec::new(),
+            added: Vec::new(),
+            changed: Vec::new(),
+        }
+    }
+}
+
+impl<T: Component> TypedColumn<T> {
+    #[inline]
+    pub(crate) fn push(&mut self, value: T, tick: u32) {
+        self.data.push(value);
+        self.added.push(tick);
+        self.changed.push(tick);
+    }
+
+    #[inline]
+    pub(crate) fn swap_remove(&mut self, row: usize) -> T {
+        self.added.swap_remove(row);
+        self.changed.swap_remove(row);
+        self.data.swap_remove(row)
+    }
+}
+
+impl<T: Component> Column for TypedColumn<T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    #[inline]
+    fn swap_remove_drop(&mut self, row: usize) {
+        drop(self.swap_remove(row));
+    }
+
+    fn swap_remove_into(&mut self, row: usize, dst: &mut dyn Column) {
+        let dst = dst
+            .as_any_mut()
+            .downcast_mut::<TypedColumn<T>>()
+            .expect("column type mismatch");
+        dst.added.push(self.added.swap_remove(row));
+        dst.changed.push(self.changed.swap_remove(row));
+        dst.data.push(self.data.swap_remove(row));
+    }
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline]
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Static description of a component type; enough to build an empty column for it.
+#[derive(Clone, Copy)]
+pub(crate) struct ComponentInfo {
+    pub(crate) type_id: TypeId,
+    pub(crate) name: &'static str,
+    pub(crate) new_column: fn() -> Box<dyn Column>,
+}
+
+impl ComponentInfo {
+    #[inline]
+    pub(crate) fn of<T: Component>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            name: std::any::type_name::<T>(),
+            new_column: || Box::new(TypedColumn::<T>::default()),
+        }
+    }
+}
+
+impl std::fmt::Debug for ComponentInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)
+    }
+}
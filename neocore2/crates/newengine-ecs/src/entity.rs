@@ -0,0 +1,163 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Generational entity handle. Stale handles (despawned, slot reused) never resolve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    #[inline]
+    pub(crate) const fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    #[inline]
+    pub const fn index(self) -> u32 {
+        self.index
+    }
+
+    #[inline]
+    pub const fn generation(self) -> u32 {
+        self.generation
+    }
+
+    /// Packs into one integer (generation high, index low), e.g. for scripting or the wire.
+    #[inline]
+    pub const fn to_bits(self) -> u64 {
+        ((self.generation as u64) << 32) | self.index as u64
+    }
+
+    #[inline]
+    pub const fn from_bits(bits: u64) -> Self {
+        Self {
+            index: bits as u32,
+            generation: (bits >> 32) as u32,
+        }
+    }
+}
+
+impl std::fmt::Display for Entity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}v{}", self.index, self.generation)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Location {
+    pub(crate) archetype: usize,
+    pub(crate) row: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Meta {
+    generation: u32,
+    location: Option<Location>,
+}
+
+/// Entity allocator. Indices are recycled with a bumped generation.
+///
+/// `reserve` works through `&self` so command buffers can hand out ids while the world is
+/// borrowed; reserved ids always use fresh indices and become live on `flush`.
+#[derive(Debug, Default)]
+pub(crate) struct Entities {
+    metas: Vec<Meta>,
+    free: Vec<u32>,
+    reserved: AtomicU32,
+    alive: usize,
+}
+
+impl Entities {
+    pub(crate) fn alloc(&mut self) -> Entity {
+        debug_assert_eq!(*self.reserved.get_mut(), 0, "flush reserved entities first");
+        self.alive += 1;
+
+        if let Some(index) = self.free.pop() {
+            let m = &self.metas[index as usize];
+            return Entity {
+                index,
+                generation: m.generation,
+            };
+        }
+
+        let index = self.metas.len() as u32;
+        self.metas.push(Meta {
+            generation: 0,
+            location: None,
+        });
+        Entity {
+            index,
+            generation: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn reserve(&self) -> Entity {
+        let n = self.reserved.fetch_add(1, Ordering::Relaxed);
+        Entity {
+            index: self.metas.len() as u32 + n,
+            generation: 0,
+        }
+    }
+
+    /// Materializes reserved ids; the caller must give each of them a location.
+    pub(crate) fn flush(&mut self) -> Vec<Entity> {
+        let n = std::mem::take(self.reserved.get_mut());
+        let start = self.metas.len() as u32;
+        for _ in 0..n {
+            self.metas.push(Meta {
+                generation: 0,
+                location: None,
+            });
+        }
+        self.alive += n as usize;
+        (start..start + n).map(|i| Entity::new(i, 0)).collect()
+    }
+
+    #[inline]
+    pub(crate) fn has_reserved(&self) -> bool {
+        self.reserved.load(Ordering::Relaxed) != 0
+    }
+
+    /// Frees the slot; returns the last location if `e` was live.
+    pub(crate) fn free(&mut self, e: Entity) -> Option<Location> {
+        let m = self.metas.get_mut(e.index as usize)?;
+        if m.generation != e.generation {
+            return None;
+        }
+        m.generation = m.generation.wrapping_add(1);
+        let loc = m.location.take();
+        self.free.push(e.index);
+        self.alive -= 1;
+        loc
+    }
+
+    #[inline]
+    pub(crate) fn contains(&self, e: Entity) -> bool {
+        self.metas
+            .get(e.index as usize)
+            .is_some_and(|m| m.generation == e.generation)
+    }
+
+    #[inline]
+    pub(crate) fn location(&self, e: Entity) -> Option<Location> {
+        let m = self.metas.get(e.index as usize)?;
+        if m.generation != e.generation {
+            return None;
+        }
+        m.location
+    }
+
+    #[inline]
+    pub(crate) fn set_location(&mut self, index: u32, loc: Location) {
+        self.metas[index as usize].location = Some(loc);
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.alive
+    }
+}
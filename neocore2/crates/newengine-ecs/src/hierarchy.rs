@@ -0,0 +1,143 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::entity::Entity;
+use crate::transform::Transform;
+use crate::world::{EcsError, World};
+
+/// Parent link. Maintained together with `Children` by `World::set_parent`; do not insert
+/// it directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parent(pub Entity);
+
+impl Parent {
+    #[inline]
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+}
+
+/// Child list, in attach order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Children(Vec<Entity>);
+
+impl Children {
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, Entity> {
+        self.0.iter()
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[Entity] {
+        &self.0
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl World {
+    /// Attaches `child` under `parent`, detaching it from its previous parent.
+    ///
+    /// The child's `Transform` stays local, so its world position changes with the new
+    /// parent. Rejects links that would form a cycle.
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> Result<(), EcsError> {
+        if !self.contains(child) {
+            return Err(EcsError::NoSuchEntity(child));
+        }
+        if !self.contains(parent) {
+            return Err(EcsError::NoSuchEntity(parent));
+        }
+        if child == parent || self.is_ancestor_of(child, parent) {
+            return Err(EcsError::HierarchyCycle { child, parent });
+        }
+        if self.parent(child) == Some(parent) {
+            return Ok(());
+        }
+
+        self.detach(child);
+
+        match self.get_mut::<Children>(parent) {
+            Some(c) => c.0.push(child),
+            None => self.insert(parent, (Children(vec![child]),))?,
+        }
+        self.insert(child, (Parent(parent),))
+    }
+
+    /// Makes `child` a root. Returns `false` if it had no parent.
+    pub fn remove_parent(&mut self, child: Entity) -> bool {
+        if !self.detach(child) {
+            return false;
+        }
+        // Removing `Parent` leaves no change stamp; mark the transform so the subtree is
+        // re-propagated from the root.
+        let _ = self.get_mut::<Transform>(child);
+        true
+    }
+
+    #[inline]
+    pub fn parent(&self, e: Entity) -> Option<Entity> {
+        self.get::<Parent>(e).map(|p| p.0)
+    }
+
+    #[inline]
+    pub fn children(&self, e: Entity) -> &[Entity] {
+        self.get::<Children>(e).map_or(&[], |c| c.as_slice())
+    }
+
+    /// True if `ancestor` is a (transitive) parent of `e`.
+    pub fn is_ancestor_of(&self, ancestor: Entity, e: Entity) -> bool {
+        let mut cur = self.parent(e);
+        while let Some(p) = cur {
+            if p == ancestor {
+                return true;
+            }
+            cur = self.parent(p);
+        }
+        false
+    }
+
+    /// Despawns `e` and all its descendants, and unlinks it from its parent.
+    /// Returns the number of despawned entities.
+    pub fn despawn_recursive(&mut self, e: Entity) -> usize {
+        if !self.contains(e) {
+            return 0;
+        }
+        self.detach(e);
+
+        let mut stack = vec![e];
+        let mut count = 0usize;
+        while let Some(cur) = stack.pop() {
+            stack.extend_from_slice(self.children(cur));
+            if self.despawn(cur) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Removes the `Parent`/`Children` link of `child`. Returns `false` if it had no parent.
+    fn detach(&mut self, child: Entity) -> bool {
+        let Ok(Parent(old)) = self.remove::<Parent>(child) else {
+            return false;
+        };
+
+        let now_empty = match self.get_mut::<Children>(old) {
+            Some(c) => {
+                c.0.retain(|&x| x != child);
+                c.0.is_empty()
+            }
+            None => false,
+        };
+        if now_empty {
+            let _ = self.remove::<Children>(old);
+        }
+        true
+    }
+}
@@ -0,0 +1,24 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+pub mod archetype;
+pub mod bundle;
+pub mod commands;
+pub mod component;
+pub mod entity;
+pub mod hierarchy;
+pub mod module;
+pub mod query;
+pub mod transform;
+pub mod world;
+
+pub use bundle::Bundle;
+pub use commands::Commands;
+pub use component::Component;
+pub use entity::Entity;
+pub use hierarchy::{Children, Parent};
+pub use module::{EcsModule, ECS_MODULE_ID};
+pub use query::{Added, Changed, QueryData, QueryFilter, QueryIter, ReadOnlyQueryData, With, Without};
+pub use transform::{GlobalTransform, Transform, TransformPropagator};
+pub use world::{EcsError, World};
+
+pub use glam;
@@ -0,0 +1,66 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_core::{EngineResult, Module, ModuleCtx};
+
+use crate::transform::TransformPropagator;
+use crate::world::World;
+
+pub const ECS_MODULE_ID: &str = "ecs";
+
+/// Owns the `World` resource, advances its change tick once per frame and propagates
+/// `GlobalTransform`s at the start of the render stage.
+///
+/// Register it before gameplay modules so each frame's `Changed`/`Added` window opens before
+/// they run, and before renderers so they see this frame's transforms.
+#[derive(Debug, Default)]
+pub struct EcsModule {
+    transforms: TransformPropagator,
+}
+
+impl EcsModule {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<E: Send + 'static> Module<E> for EcsModule {
+    fn id(&self) -> &'static str {
+        ECS_MODULE_ID
+    }
+
+    fn init(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if ctx.resources().get::<World>().is_none() {
+            ctx.resources_mut().insert(World::new());
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if let Some(world) = ctx.resources_mut().get_mut::<World>() {
+            world.flush();
+            world.advance_tick();
+        }
+        Ok(())
+    }
+
+    fn render(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if let Some(world) = ctx.resources_mut().get_mut::<World>() {
+            self.transforms.run(world);
+        }
+        Ok(())
+    }
+
+    fn shutdown(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if let Some(world) = ctx.resources_mut().get_mut::<World>() {
+            log::info!(
+                target: "ecs",
+                "world.shutdown entities={} archetypes={}",
+                world.len(),
+                world.archetypes().len()
+            );
+            world.clear();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,448 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+use crate::archetype::Archetype;
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::world::World;
+
+/// Component types a query reads and writes; validated once when the query is created.
+#[derive(Debug, Default)]
+pub struct Access {
+    reads: Vec<(TypeId, &'static str)>,
+    writes: Vec<(TypeId, &'static str)>,
+}
+
+impl Access {
+    #[inline]
+    fn read<T: Component>(&mut self) {
+        self.reads.push((TypeId::of::<T>(), std::any::type_name::<T>()));
+    }
+
+    #[inline]
+    fn write<T: Component>(&mut self) {
+        self.writes.push((TypeId::of::<T>(), std::any::type_name::<T>()));
+    }
+
+    /// Panics if a component is written twice, or both read and written, by one query.
+    pub(crate) fn validate(&self) {
+        for (i, (t, name)) in self.writes.iter().enumerate() {
+            let aliased = self.writes[i + 1..].iter().any(|(o, _)| o == t)
+                || self.reads.iter().any(|(o, _)| o == t);
+            assert!(!aliased, "query accesses `{name}` mutably more than once");
+        }
+    }
+}
+
+/// What a query yields per entity: `Entity`, `&T`, `&mut T`, `Option<&T>` and tuples of those.
+///
+/// # Safety
+/// Implementations must report every component they touch in `access`, and `fetch` must only
+/// touch rows of the archetype passed to `prepare`.
+pub unsafe trait QueryData {
+    type Item<'w>;
+    #[doc(hidden)]
+    type Fetch: Copy;
+
+    fn access(access: &mut Access);
+    fn matches(arch: &Archetype) -> bool;
+
+    #[doc(hidden)]
+    /// # Safety
+    /// `arch` must be valid and not otherwise borrowed for the lifetime of the fetch.
+    unsafe fn prepare(arch: *mut Archetype, tick: u32) -> Self::Fetch;
+
+    #[doc(hidden)]
+    /// # Safety
+    /// `row` must be in bounds and each row fetched at most once per iteration.
+    unsafe fn fetch<'w>(fetch: Self::Fetch, row: usize) -> Self::Item<'w>;
+}
+
+/// Query data that never writes; usable through `&World`.
+pub trait ReadOnlyQueryData: QueryData {}
+
+unsafe impl QueryData for Entity {
+    type Item<'w> = Entity;
+    type Fetch = *const Entity;
+
+    #[inline]
+    fn access(_: &mut Access) {}
+
+    #[inline]
+    fn matches(_: &Archetype) -> bool {
+        true
+    }
+
+    #[inline]
+    unsafe fn prepare(arch: *mut Archetype, _: u32) -> Self::Fetch {
+        unsafe { (*arch).entities.as_ptr() }
+    }
+
+    #[inline]
+    unsafe fn fetch<'w>(fetch: Self::Fetch, row: usize) -> Entity {
+        unsafe { *fetch.add(row) }
+    }
+}
+
+impl ReadOnlyQueryData for Entity {}
+
+unsafe impl<T: Component> QueryData for &T {
+    type Item<'w> = &'w T;
+    type Fetch = *const T;
+
+    #[inline]
+    fn access(access: &mut Access) {
+        access.read::<T>();
+    }
+
+    #[inline]
+    fn matches(arch: &Archetype) -> bool {
+        arch.has(TypeId::of::<T>())
+    }
+
+    #[inline]
+    unsafe fn prepare(arch: *mut Archetype, _: u32) -> Self::Fetch {
+        let arch = unsafe { &*arch };
+        arch.column::<T>().expect("matched column").data.as_ptr()
+    }
+
+    #[inline]
+    unsafe fn fetch<'w>(fetch: Self::Fetch, row: usize) -> &'w T {
+        unsafe { &*fetch.add(row) }
+    }
+}
+
+impl<T: Component> ReadOnlyQueryData for &T {}
+
+/// Fetching `&mut T` stamps the row as changed, whether or not it is written.
+unsafe impl<T: Component> QueryData for &mut T {
+    type Item<'w> = &'w mut T;
+    type Fetch = (*mut T, *mut u32, u32);
+
+    #[inline]
+    fn access(access: &mut Access) {
+        access.write::<T>();
+    }
+
+    #[inline]
+    fn matches(arch: &Archetype) -> bool {
+        arch.has(TypeId::of::<T>())
+    }
+
+    #[inline]
+    unsafe fn prepare(arch: *mut Archetype, tick: u32) -> Self::Fetch {
+        let arch = unsafe { &mut *arch };
+        let col = arch.column_mut::<T>().expect("matched column");
+        (col.data.as_mut_ptr(), col.changed.as_mut_ptr(), tick)
+    }
+
+    #[inline]
+    unsafe fn fetch<'w>(fetch: Self::Fetch, row: usize) -> &'w mut T {
+        let (data, changed, tick) = fetch;
+        unsafe {
+            *changed.add(row) = tick;
+            &mut *data.add(row)
+        }
+    }
+}
+
+unsafe impl<T: Component> QueryData for Option<&T> {
+    type Item<'w> = Option<&'w T>;
+    type Fetch = Option<*const T>;
+
+    #[inline]
+    fn access(access: &mut Access) {
+        access.read::<T>();
+    }
+
+    #[inline]
+    fn matches(_: &Archetype) -> bool {
+        true
+    }
+
+    #[inline]
+    unsafe fn prepare(arch: *mut Archetype, _: u32) -> Self::Fetch {
+        let arch = unsafe { &*arch };
+        arch.column::<T>().map(|c| c.data.as_ptr())
+    }
+
+    #[inline]
+    unsafe fn fetch<'w>(fetch: Self::Fetch, row: usize) -> Option<&'w T> {
+        fetch.map(|p| unsafe { &*p.add(row) })
+    }
+}
+
+impl<T: Component> ReadOnlyQueryData for Option<&T> {}
+
+macro_rules! tuple_query {
+    ($($q:ident),+) => {
+        unsafe impl<$($q: QueryData),+> QueryData for ($($q,)+) {
+            type Item<'w> = ($($q::Item<'w>,)+);
+            type Fetch = ($($q::Fetch,)+);
+
+            #[inline]
+            fn access(access: &mut Access) {
+                $($q::access(access);)+
+            }
+
+            #[inline]
+            fn matches(arch: &Archetype) -> bool {
+                true $(&& $q::matches(arch))+
+            }
+
+            #[inline]
+            unsafe fn prepare(arch: *mut Archetype, tick: u32) -> Self::Fetch {
+                unsafe { ($($q::prepare(arch, tick),)+) }
+            }
+
+            #[allow(non_snake_case)]
+            #[inline]
+            unsafe fn fetch<'w>(fetch: Self::Fetch, row: usize) -> Self::Item<'w> {
+                let ($($q,)+) = fetch;
+                unsafe { ($($q::fetch($q, row),)+) }
+            }
+        }
+
+        impl<$($q: ReadOnlyQueryData),+> ReadOnlyQueryData for ($($q,)+) {}
+    };
+}
+
+tuple_query!(A);
+tuple_query!(A, B);
+tuple_query!(A, B, C);
+tuple_query!(A, B, C, D);
+tuple_query!(A, B, C, D, E);
+tuple_query!(A, B, C, D, E, F);
+tuple_query!(A, B, C, D, E, F, G);
+tuple_query!(A, B, C, D, E, F, G, H);
+
+/// Restricts which entities a query visits without fetching data.
+pub trait QueryFilter {
+    #[doc(hidden)]
+    type Fetch: Copy;
+
+    fn matches(arch: &Archetype) -> bool;
+
+    #[doc(hidden)]
+    /// # Safety
+    /// `arch` must be valid for the lifetime of the fetch.
+    unsafe fn prepare(arch: *const Archetype, since: u32) -> Self::Fetch;
+
+    #[doc(hidden)]
+    /// # Safety
+    /// `row` must be in bounds.
+    unsafe fn row_ok(fetch: Self::Fetch, row: usize) -> bool;
+}
+
+impl QueryFilter for () {
+    type Fetch = ();
+
+    #[inline]
+    fn matches(_: &Archetype) -> bool {
+        true
+    }
+
+    #[inline]
+    unsafe fn prepare(_: *const Archetype, _: u32) {}
+
+    #[inline]
+    unsafe fn row_ok(_: (), _: usize) -> bool {
+        true
+    }
+}
+
+/// Entity has `T` (not fetched).
+pub struct With<T>(PhantomData<fn() -> T>);
+
+/// Entity lacks `T`.
+pub struct Without<T>(PhantomData<fn() -> T>);
+
+/// `T` was added at or after the query's `since` tick.
+pub struct Added<T>(PhantomData<fn() -> T>);
+
+/// `T` was added or fetched mutably at or after the query's `since` tick.
+pub struct Changed<T>(PhantomData<fn() -> T>);
+
+impl<T: Component> QueryFilter for With<T> {
+    type Fetch = ();
+
+    #[inline]
+    fn matches(arch: &Archetype) -> bool {
+        arch.has(TypeId::of::<T>())
+    }
+
+    #[inline]
+    unsafe fn prepare(_: *const Archetype, _: u32) {}
+
+    #[inline]
+    unsafe fn row_ok(_: (), _: usize) -> bool {
+        true
+    }
+}
+
+impl<T: Component> QueryFilter for Without<T> {
+    type Fetch = ();
+
+    #[inline]
+    fn matches(arch: &Archetype) -> bool {
+        !arch.has(TypeId::of::<T>())
+    }
+
+    #[inline]
+    unsafe fn prepare(_: *const Archetype, _: u32) {}
+
+    #[inline]
+    unsafe fn row_ok(_: (), _: usize) -> bool {
+        true
+    }
+}
+
+impl<T: Component> QueryFilter for Added<T> {
+    type Fetch = (*const u32, u32);
+
+    #[inline]
+    fn matches(arch: &Archetype) -> bool {
+        arch.has(TypeId::of::<T>())
+    }
+
+    #[inline]
+    unsafe fn prepare(arch: *const Archetype, since: u32) -> Self::Fetch {
+        let arch = unsafe { &*arch };
+        (arch.column::<T>().expect("matched column").added.as_ptr(), since)
+    }
+
+    #[inline]
+    unsafe fn row_ok(fetch: Self::Fetch, row: usize) -> bool {
+        unsafe { *fetch.0.add(row) >= fetch.1 }
+    }
+}
+
+impl<T: Component> QueryFilter for Changed<T> {
+    type Fetch = (*const u32, u32);
+
+    #[inline]
+    fn matches(arch: &Archetype) -> bool {
+        arch.has(TypeId::of::<T>())
+    }
+
+    #[inline]
+    unsafe fn prepare(arch: *const Archetype, since: u32) -> Self::Fetch {
+        let arch = unsafe { &*arch };
+        (arch.column::<T>().expect("matched column").changed.as_ptr(), since)
+    }
+
+    #[inline]
+    unsafe fn row_ok(fetch: Self::Fetch, row: usize) -> bool {
+        unsafe { *fetch.0.add(row) >= fetch.1 }
+    }
+}
+
+macro_rules! tuple_filter {
+    ($($f:ident),+) => {
+        impl<$($f: QueryFilter),+> QueryFilter for ($($f,)+) {
+            type Fetch = ($($f::Fetch,)+);
+
+            #[inline]
+            fn matches(arch: &Archetype) -> bool {
+                true $(&& $f::matches(arch))+
+            }
+
+            #[inline]
+            unsafe fn prepare(arch: *const Archetype, since: u32) -> Self::Fetch {
+                unsafe { ($($f::prepare(arch, since),)+) }
+            }
+
+            #[allow(non_snake_case)]
+            #[inline]
+            unsafe fn row_ok(fetch: Self::Fetch, row: usize) -> bool {
+                let ($($f,)+) = fetch;
+                unsafe { true $(&& $f::row_ok($f, row))+ }
+            }
+        }
+    };
+}
+
+tuple_filter!(A);
+tuple_filter!(A, B);
+tuple_filter!(A, B, C);
+tuple_filter!(A, B, C, D);
+
+/// Iterator over all entities matching `Q` and `F`, archetype by archetype.
+pub struct QueryIter<'w, Q: QueryData, F: QueryFilter = ()> {
+    archetypes: *mut Archetype,
+    archetype_count: usize,
+    next_archetype: usize,
+
+    current: Option<(Q::Fetch, F::Fetch)>,
+    row: usize,
+    len: usize,
+
+    tick: u32,
+    since: u32,
+    _world: PhantomData<&'w World>,
+}
+
+impl<'w, Q: QueryData, F: QueryFilter> QueryIter<'w, Q, F> {
+    /// # Safety
+    /// `archetypes..archetypes + count` must stay valid for `'w`; if `Q` writes, the pointer
+    /// must come from an exclusive borrow of the world held for `'w`.
+    pub(crate) unsafe fn new(archetypes: *mut Archetype, count: usize, tick: u32, since: u32) -> Self {
+        let mut access = Access::default();
+        Q::access(&mut access);
+        access.validate();
+
+        Self {
+            archetypes,
+            archetype_count: count,
+            next_archetype: 0,
+            current: None,
+            row: 0,
+            len: 0,
+            tick,
+            since,
+            _world: PhantomData,
+        }
+    }
+}
+
+impl<'w, Q: QueryData, F: QueryFilter> Iterator for QueryIter<'w, Q, F> {
+    type Item = Q::Item<'w>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((q, f)) = self.current {
+                while self.row < self.len {
+                    let row = self.row;
+                    self.row += 1;
+                    // SAFETY: row < archetype length; each row is visited once.
+                    unsafe {
+                        if F::row_ok(f, row) {
+                            return Some(Q::fetch(q, row));
+                        }
+                    }
+                }
+                self.current = None;
+            }
+
+            if self.next_archetype >= self.archetype_count {
+                return None;
+            }
+
+            // SAFETY: index is in bounds of the slice captured in `new`.
+            let arch = unsafe { self.archetypes.add(self.next_archetype) };
+            self.next_archetype += 1;
+
+            let a = unsafe { &*arch };
+            if a.is_empty() || !Q::matches(a) || !F::matches(a) {
+                continue;
+            }
+
+            self.len = a.len();
+            self.row = 0;
+            // SAFETY: the iterator holds the borrow the caller guaranteed in `new`.
+            self.current = unsafe { Some((Q::prepare(arch, self.tick), F::prepare(arch, self.since))) };
+        }
+    }
+}
@@ -0,0 +1,353 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use glam::{Affine3A, Mat3, Mat4, Quat, Vec3};
+use std::collections::HashSet;
+
+use crate::entity::Entity;
+use crate::hierarchy::{Children, Parent};
+use crate::query::Changed;
+use crate::world::World;
+
+/// Local transform, relative to the `Parent` (or the world for roots).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Default for Transform {
+    #[inline]
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    #[inline]
+    pub const fn from_xyz(x: f32, y: f32, z: f32) -> Self {
+        Self::from_translation(Vec3::new(x, y, z))
+    }
+
+    #[inline]
+    pub const fn from_translation(translation: Vec3) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    #[inline]
+    pub const fn from_rotation(rotation: Quat) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    #[inline]
+    pub const fn from_scale(scale: Vec3) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Decomposes an affine matrix; shear is lost.
+    #[inline]
+    pub fn from_matrix(m: Mat4) -> Self {
+        let (scale, rotation, translation) = m.to_scale_rotation_translation();
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    #[inline]
+    pub fn with_translation(mut self, translation: Vec3) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    #[inline]
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    #[inline]
+    pub fn with_scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Rotates so that local `-Z` points at `target` (right-handed, `up` as the hint).
+    pub fn looking_at(mut self, target: Vec3, up: Vec3) -> Self {
+        let forward = (target - self.translation).normalize_or_zero();
+        if forward == Vec3::ZERO {
+            return self;
+        }
+        let right = forward.cross(up).normalize_or_zero();
+        let right = if right == Vec3::ZERO { forward.any_orthonormal_vector() } else { right };
+        let up = right.cross(forward);
+        self.rotation = Quat::from_mat3(&Mat3::from_cols(right, up, -forward));
+        self
+    }
+
+    #[inline]
+    pub fn rotate(&mut self, rotation: Quat) {
+        self.rotation = rotation * self.rotation;
+    }
+
+    #[inline]
+    pub fn rotate_y(&mut self, angle: f32) {
+        self.rotate(Quat::from_rotation_y(angle));
+    }
+
+    #[inline]
+    pub fn forward(&self) -> Vec3 {
+        self.rotation * Vec3::NEG_Z
+    }
+
+    #[inline]
+    pub fn right(&self) -> Vec3 {
+        self.rotation * Vec3::X
+    }
+
+    #[inline]
+    pub fn up(&self) -> Vec3 {
+        self.rotation * Vec3::Y
+    }
+
+    #[inline]
+    pub fn compute_affine(&self) -> Affine3A {
+        Affine3A::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    #[inline]
+    pub fn compute_matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    /// `self` applied after `child` (i.e. `child` expressed in `self`'s parent space).
+    #[inline]
+    pub fn mul_transform(&self, child: &Transform) -> Transform {
+        Transform {
+            translation: self.transform_point(child.translation),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+
+    #[inline]
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        self.rotation * (self.scale * p) + self.translation
+    }
+}
+
+/// World-space transform. Written by `TransformPropagator`; do not edit directly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlobalTransform(pub Affine3A);
+
+impl Default for GlobalTransform {
+    #[inline]
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl From<Transform> for GlobalTransform {
+    #[inline]
+    fn from(t: Transform) -> Self {
+        Self(t.compute_affine())
+    }
+}
+
+impl GlobalTransform {
+    pub const IDENTITY: Self = Self(Affine3A::IDENTITY);
+
+    #[inline]
+    pub fn affine(&self) -> Affine3A {
+        self.0
+    }
+
+    #[inline]
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from(self.0)
+    }
+
+    /// Column-major, ready for a uniform buffer.
+    #[inline]
+    pub fn to_cols_array(&self) -> [f32; 16] {
+        self.matrix().to_cols_array()
+    }
+
+    #[inline]
+    pub fn translation(&self) -> Vec3 {
+        self.0.translation.into()
+    }
+
+    #[inline]
+    pub fn to_transform(&self) -> Transform {
+        let (scale, rotation, translation) = self.0.to_scale_rotation_translation();
+        Transform {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    #[inline]
+    pub fn mul_transform(&self, local: &Transform) -> GlobalTransform {
+        GlobalTransform(self.0 * local.compute_affine())
+    }
+
+    #[inline]
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        self.0.transform_point3(p)
+    }
+
+    #[inline]
+    pub fn forward(&self) -> Vec3 {
+        self.0.transform_vector3(Vec3::NEG_Z).normalize_or_zero()
+    }
+}
+
+/// Below this many dirty subtrees propagation stays on the calling thread.
+const PARALLEL_MIN_ROOTS: usize = 64;
+
+/// Recomputes `GlobalTransform` for entities whose `Transform` or `Parent` changed, plus
+/// their descendants. Clean subtrees are not touched.
+///
+/// Dirty subtrees are independent, so their matrices are computed in parallel from a shared
+/// borrow of the world; results are written back afterwards. Entities with a `Transform` but
+/// no `GlobalTransform` get one inserted.
+#[derive(Debug)]
+pub struct TransformPropagator {
+    last_run: u32,
+    threads: usize,
+}
+
+impl Default for TransformPropagator {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransformPropagator {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            last_run: 0,
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    /// Caps worker threads (1 = always single-threaded).
+    #[inline]
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Forces a full recompute on the next `run` (e.g. after loading a world snapshot).
+    #[inline]
+    pub fn mark_all_dirty(&mut self) {
+        self.last_run = 0;
+    }
+
+    /// Returns the number of updated `GlobalTransform`s.
+    pub fn run(&mut self, world: &mut World) -> usize {
+        let since = self.last_run;
+        self.last_run = world.change_tick();
+
+        let mut dirty: HashSet<Entity> = world.query_since::<Entity, Changed<Transform>>(since).collect();
+        dirty.extend(world.query_since::<Entity, Changed<Parent>>(since));
+        if dirty.is_empty() {
+            return 0;
+        }
+
+        // Keep only the top-most dirty entity of every chain; the walk covers the rest.
+        let roots: Vec<Entity> = dirty
+            .iter()
+            .copied()
+            .filter(|&e| !has_dirty_ancestor(world, e, &dirty))
+            .collect();
+
+        let results = if roots.len() >= PARALLEL_MIN_ROOTS && self.threads > 1 {
+            let world = &*world;
+            let chunk = roots.len().div_ceil(self.threads);
+            std::thread::scope(|s| {
+                let jobs: Vec<_> = roots
+                    .chunks(chunk)
+                    .map(|part| s.spawn(move || compute_subtrees(world, part)))
+                    .collect();
+                jobs.into_iter()
+                    .flat_map(|j| j.join().expect("transform propagation worker panicked"))
+                    .collect::<Vec<_>>()
+            })
+        } else {
+            compute_subtrees(world, &roots)
+        };
+
+        let updated = results.len();
+        for (e, global) in results {
+            match world.get_mut::<GlobalTransform>(e) {
+                Some(g) => *g = global,
+                None => {
+                    let _ = world.insert(e, (global,));
+                }
+            }
+        }
+        updated
+    }
+}
+
+fn has_dirty_ancestor(world: &World, e: Entity, dirty: &HashSet<Entity>) -> bool {
+    let mut cur = world.get::<Parent>(e).map(|p| p.0);
+    while let Some(p) = cur {
+        if dirty.contains(&p) {
+            return true;
+        }
+        cur = world.get::<Parent>(p).map(|p| p.0);
+    }
+    false
+}
+
+/// Globals for every entity in the subtrees under `roots`, parent-first.
+fn compute_subtrees(world: &World, roots: &[Entity]) -> Vec<(Entity, GlobalTransform)> {
+    let mut out = Vec::new();
+    let mut stack: Vec<(Entity, GlobalTransform)> = Vec::new();
+
+    for &root in roots {
+        let parent_global = world
+            .get::<Parent>(root)
+            .and_then(|p| world.get::<GlobalTransform>(p.0))
+            .copied()
+            .unwrap_or_default();
+        stack.push((root, parent_global));
+
+        while let Some((e, parent_global)) = stack.pop() {
+            // Entities without a `Transform` cut the chain; their subtree is left as is.
+            let Some(local) = world.get::<Transform>(e) else {
+                continue;
+            };
+            let global = parent_global.mul_transform(local);
+            out.push((e, global));
+
+            if let Some(children) = world.get::<Children>(e) {
+                stack.extend(children.iter().map(|&c| (c, global)));
+            }
+        }
+    }
+
+    out
+}
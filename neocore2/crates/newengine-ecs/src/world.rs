@@ -0,0 +1,414 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use ahash::AHashMap;
+use std::any::TypeId;
+
+use crate::archetype::Archetype;
+use crate::bundle::Bundle;
+use crate::component::{Component, ComponentInfo};
+use crate::entity::{Entities, Entity, Location};
+use crate::query::{QueryData, QueryFilter, QueryIter, ReadOnlyQueryData};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EcsError {
+    NoSuchEntity(Entity),
+    MissingComponent {
+        entity: Entity,
+        component: &'static str,
+    },
+    /// `set_parent` would make an entity its own ancestor.
+    HierarchyCycle {
+        child: Entity,
+        parent: Entity,
+    },
+}
+
+impl std::fmt::Display for EcsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EcsError::NoSuchEntity(e) => write!(f, "ecs: entity {e} does not exist"),
+            EcsError::MissingComponent { entity, component } => {
+                write!(f, "ecs: entity {entity} has no `{component}`")
+            }
+            EcsError::HierarchyCycle { child, parent } => {
+                write!(f, "ecs: parenting {child} under {parent} would create a cycle")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EcsError {}
+
+/// Entity/component storage. Insert into `Resources` (see `EcsModule`) to share it between
+/// modules.
+///
+/// Change detection is tick based: every add and every mutable access stamps the component
+/// with the current tick, and `Added`/`Changed` filters match stamps at or after the query's
+/// `since` tick. By default that is the current tick (changes made this frame); modules that
+/// must not miss changes made before they ran record `change_tick()` and pass it to
+/// `query_since` on their next run.
+pub struct World {
+    entities: Entities,
+    archetypes: Vec<Archetype>,
+    archetype_index: AHashMap<Vec<TypeId>, usize>,
+    tick: u32,
+}
+
+const EMPTY_ARCHETYPE: usize = 0;
+
+impl Default for World {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        let mut archetype_index = AHashMap::new();
+        archetype_index.insert(Vec::new(), EMPTY_ARCHETYPE);
+
+        Self {
+            entities: Entities::default(),
+            archetypes: vec![Archetype::new(Vec::new())],
+            archetype_index,
+            tick: 1,
+        }
+    }
+
+    /* ============================
+    Entities
+    ============================ */
+
+    /// Spawns an entity with `bundle`.
+    ///
+    /// Panics if the bundle names the same component type twice.
+    pub fn spawn<B: Bundle>(&mut self, bundle: B) -> Entity {
+        self.flush();
+
+        let e = self.entities.alloc();
+        let arch = self.archetype_for_bundle::<B>(&[]);
+        let a = &mut self.archetypes[arch];
+        let row = a.entities.len();
+        a.entities.push(e);
+        bundle.write(a, row, self.tick);
+        a.debug_validate();
+
+        self.entities.set_location(e.index(), Location { archetype: arch, row });
+        e
+    }
+
+    /// Spawns an entity without components.
+    #[inline]
+    pub fn spawn_empty(&mut self) -> Entity {
+        self.flush();
+        let e = self.entities.alloc();
+        self.place_empty(e);
+        e
+    }
+
+    /// Hands out an id usable immediately; the entity becomes live (without components) on the
+    /// next mutating call or `flush`.
+    #[inline]
+    pub fn reserve_entity(&self) -> Entity {
+        self.entities.reserve()
+    }
+
+    /// Makes reserved entities live.
+    pub fn flush(&mut self) {
+        if !self.entities.has_reserved() {
+            return;
+        }
+        for e in self.entities.flush() {
+            self.place_empty(e);
+        }
+    }
+
+    pub fn despawn(&mut self, e: Entity) -> bool {
+        self.flush();
+
+        let Some(loc) = self.entities.free(e) else {
+            return false;
+        };
+        if let Some(moved) = self.archetypes[loc.archetype].swap_remove(loc.row) {
+            self.entities.set_location(moved.index(), loc);
+        }
+        true
+    }
+
+    #[inline]
+    pub fn contains(&self, e: Entity) -> bool {
+        self.entities.contains(e)
+    }
+
+    /// Live entities.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entities.len() == 0
+    }
+
+    /// Despawns everything; archetypes are kept for reuse.
+    pub fn clear(&mut self) {
+        self.flush();
+        let all: Vec<Entity> = self
+            .archetypes
+            .iter()
+            .flat_map(|a| a.entities.iter().copied())
+            .collect();
+        for e in all {
+            self.despawn(e);
+        }
+    }
+
+    /* ============================
+    Components
+    ============================ */
+
+    /// Adds (or overwrites) the components of `bundle` on `e`.
+    pub fn insert<B: Bundle>(&mut self, e: Entity, bundle: B) -> Result<(), EcsError> {
+        self.flush();
+
+        let loc = self.entities.location(e).ok_or(EcsError::NoSuchEntity(e))?;
+        let src_types: Vec<TypeId> = self.archetypes[loc.archetype].type_ids().collect();
+        let dst = self.archetype_for_bundle::<B>(&src_types);
+
+        let tick = self.tick;
+        if dst == loc.archetype {
+            bundle.write(&mut self.archetypes[dst], loc.row, tick);
+            return Ok(());
+        }
+
+        let (src_arch, dst_arch) = pair_mut(&mut self.archetypes, loc.archetype, dst);
+        let (row, swapped) = src_arch.move_row(loc.row, dst_arch, None);
+        bundle.write(dst_arch, row, tick);
+        dst_arch.debug_validate();
+
+        if let Some(s) = swapped {
+            self.entities.set_location(s.index(), loc);
+        }
+        self.entities.set_location(e.index(), Location { archetype: dst, row });
+        Ok(())
+    }
+
+    /// Removes `T` from `e` and returns it.
+    pub fn remove<T: Component>(&mut self, e: Entity) -> Result<T, EcsError> {
+        self.flush();
+
+        let loc = self.entities.location(e).ok_or(EcsError::NoSuchEntity(e))?;
+        let missing = EcsError::MissingComponent {
+            entity: e,
+            component: std::any::type_name::<T>(),
+        };
+        if !self.archetypes[loc.archetype].has(TypeId::of::<T>()) {
+            return Err(missing);
+        }
+
+        let infos: Vec<ComponentInfo> = self.archetypes[loc.archetype]
+            .infos()
+            .iter()
+            .copied()
+            .filter(|i| i.type_id != TypeId::of::<T>())
+            .collect();
+        let dst = self.archetype_for_infos(infos);
+
+        let (src_arch, dst_arch) = pair_mut(&mut self.archetypes, loc.archetype, dst);
+        let value = src_arch
+            .column_mut::<T>()
+            .ok_or(missing)?
+            .swap_remove(loc.row);
+        let (row, swapped) = src_arch.move_row(loc.row, dst_arch, Some(TypeId::of::<T>()));
+        src_arch.debug_validate();
+
+        if let Some(s) = swapped {
+            self.entities.set_location(s.index(), loc);
+        }
+        self.entities.set_location(e.index(), Location { archetype: dst, row });
+        Ok(value)
+    }
+
+    #[inline]
+    pub fn has<T: Component>(&self, e: Entity) -> bool {
+        self.entities
+            .location(e)
+            .is_some_and(|l| self.archetypes[l.archetype].has(TypeId::of::<T>()))
+    }
+
+    #[inline]
+    pub fn get<T: Component>(&self, e: Entity) -> Option<&T> {
+        let loc = self.entities.location(e)?;
+        self.archetypes[loc.archetype]
+            .column::<T>()
+            .map(|c| &c.data[loc.row])
+    }
+
+    /// Mutable access; stamps the component as changed.
+    #[inline]
+    pub fn get_mut<T: Component>(&mut self, e: Entity) -> Option<&mut T> {
+        let loc = self.entities.location(e)?;
+        let tick = self.tick;
+        let col = self.archetypes[loc.archetype].column_mut::<T>()?;
+        col.changed[loc.row] = tick;
+        Some(&mut col.data[loc.row])
+    }
+
+    /* ============================
+    Queries
+    ============================ */
+
+    #[inline]
+    pub fn query<Q: QueryData>(&mut self) -> QueryIter<'_, Q> {
+        self.query_since::<Q, ()>(self.tick)
+    }
+
+    #[inline]
+    pub fn query_filtered<Q: QueryData, F: QueryFilter>(&mut self) -> QueryIter<'_, Q, F> {
+        self.query_since::<Q, F>(self.tick)
+    }
+
+    /// Like `query_filtered`, with `Added`/`Changed` matching stamps at or after `since`.
+    pub fn query_since<Q: QueryData, F: QueryFilter>(&mut self, since: u32) -> QueryIter<'_, Q, F> {
+        self.flush();
+        let count = self.archetypes.len();
+        // SAFETY: `&mut self` is held for the iterator lifetime.
+        unsafe { QueryIter::new(self.archetypes.as_mut_ptr(), count, self.tick, since) }
+    }
+
+    /// Read-only query through a shared borrow.
+    pub fn query_ref<Q: ReadOnlyQueryData, F: QueryFilter>(&self) -> QueryIter<'_, Q, F> {
+        let count = self.archetypes.len();
+        // SAFETY: read-only fetches never write through the pointer.
+        unsafe {
+            QueryIter::new(self.archetypes.as_ptr() as *mut Archetype, count, self.tick, self.tick)
+        }
+    }
+
+    /// Fetches `Q` for one entity; `None` if it is gone or does not match.
+    pub fn query_one<Q: QueryData>(&mut self, e: Entity) -> Option<Q::Item<'_>> {
+        let loc = self.entities.location(e)?;
+        let arch = &mut self.archetypes[loc.archetype];
+        if !Q::matches(arch) {
+            return None;
+        }
+
+        let mut access = crate::query::Access::default();
+        Q::access(&mut access);
+        access.validate();
+
+        // SAFETY: `&mut self` is held for the item lifetime and `row` is in bounds.
+        unsafe {
+            let f = Q::prepare(arch as *mut Archetype, self.tick);
+            Some(Q::fetch(f, loc.row))
+        }
+    }
+
+    /* ============================
+    Change ticks
+    ============================ */
+
+    #[inline]
+    pub fn change_tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Starts a new change-detection window (once per frame, see `EcsModule`).
+    #[inline]
+    pub fn advance_tick(&mut self) {
+        self.tick = self.tick.wrapping_add(1).max(1);
+    }
+
+    /* ============================
+    Introspection
+    ============================ */
+
+    #[inline]
+    pub fn archetypes(&self) -> &[Archetype] {
+        &self.archetypes
+    }
+
+    /* ============================
+    Internals
+    ============================ */
+
+    fn place_empty(&mut self, e: Entity) {
+        let a = &mut self.archetypes[EMPTY_ARCHETYPE];
+        let row = a.entities.len();
+        a.entities.push(e);
+        self.entities.set_location(
+            e.index(),
+            Location {
+                archetype: EMPTY_ARCHETYPE,
+                row,
+            },
+        );
+    }
+
+    /// Archetype holding `base` plus the bundle's components.
+    fn archetype_for_bundle<B: Bundle>(&mut self, base: &[TypeId]) -> usize {
+        let mut added = Vec::new();
+        B::infos(&mut added);
+
+        let mut ids: Vec<TypeId> = added.iter().map(|i| i.type_id).collect();
+        ids.sort();
+        let before = ids.len();
+        ids.dedup();
+        assert_eq!(before, ids.len(), "bundle contains a component type twice");
+
+        let mut infos: Vec<ComponentInfo> = Vec::new();
+        if let Some(&i) = self.archetype_index.get(base) {
+            infos.extend(self.archetypes[i].infos().iter().copied());
+        }
+        for info in added {
+            if !infos.iter().any(|i| i.type_id == info.type_id) {
+                infos.push(info);
+            }
+        }
+        self.archetype_for_infos(infos)
+    }
+
+    fn archetype_for_infos(&mut self, mut infos: Vec<ComponentInfo>) -> usize {
+        infos.sort_by_key(|i| i.type_id);
+        let key: Vec<TypeId> = infos.iter().map(|i| i.type_id).collect();
+
+        if let Some(&i) = self.archetype_index.get(&key) {
+            return i;
+        }
+
+        let i = self.archetypes.len();
+        log::debug!(
+            target: "ecs",
+            "archetype.create index={} components={:?}",
+            i,
+            infos
+        );
+        self.archetypes.push(Archetype::new(infos));
+        self.archetype_index.insert(key, i);
+        i
+    }
+}
+
+impl std::fmt::Debug for World {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("World")
+            .field("entities", &self.entities.len())
+            .field("archetypes", &self.archetypes.len())
+            .field("tick", &self.tick)
+            .finish()
+    }
+}
+
+/// Two distinct elements mutably.
+fn pair_mut<T>(v: &mut [T], a: usize, b: usize) -> (&mut T, &mut T) {
+    assert_ne!(a, b);
+    if a < b {
+        let (lo, hi) = v.split_at_mut(b);
+        (&mut lo[a], &mut hi[0])
+    } else {
+        let (lo, hi) = v.split_at_mut(a);
+        (&mut hi[0], &mut lo[b])
+    }
+}
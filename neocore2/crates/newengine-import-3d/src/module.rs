@@ -103,6 +103,7 @@ impl ThreeDImporterService {
   "id":"kalitech.import.3d.v1",
   "kind":"asset_importer",
   "asset_importer":{{
+    "version":"1",
     "priority":120,
     "extensions":{exts_json},
     "output_type_id":"kalitech.asset.model3d",
@@ -152,7 +153,7 @@ impl PluginModule for ThreeDImporterPlugin {
     fn info(&self) -> PluginInfo {
         PluginInfo {
             id: RString::from("import.3d"),
-            name: RString::from("3D Importer (.obj/.fbx/.glb/.gltf)"),
+            name: RString::from("3D Importer (.obj/.fbx)"),
             version: RString::from(env!("CARGO_PKG_VERSION")),
         }
     }
@@ -3,7 +3,6 @@
 use abi_stable::std_types::{RResult, RString, RVec};
 
 mod obj;
-mod gltf;
 mod fbx;
 
 pub(crate) trait Provider: Sync {
@@ -19,12 +18,10 @@ pub(crate) trait Provider: Sync {
 
 pub(crate) fn iter_providers() -> impl Iterator<Item=&'static dyn Provider> {
     static OBJ: obj::ObjProvider = obj::ObjProvider;
-    static GLTF: gltf::GltfProvider = gltf::GltfProvider;
     static FBX: fbx::FbxProvider = fbx::FbxProvider;
 
     [
         &OBJ as &dyn Provider,
-        &GLTF as &dyn Provider,
         &FBX as &dyn Provider,
     ]
         .into_iter()
@@ -4,6 +4,7 @@ use abi_stable::sabi_trait::TD_Opaque;
 use abi_stable::std_types::{RResult, RString, RVec};
 use abi_stable::StableAbi;
 
+use newengine_plugin_api::importer::{split_settings, with_import_settings};
 use newengine_plugin_api::{
     Blob, HostApiV1, MethodName, PluginInfo, PluginModule, ServiceV1, ServiceV1Dyn, ServiceV1_TO,
 };
@@ -156,16 +157,19 @@ impl AudioImporterService {
   "id":"kalitech.import.audio.v1",
   "kind":"asset_importer",
   "asset_importer":{{
+    "version":"1",
     "priority":100,
     "extensions":{exts_json},
     "output_type_id":"kalitech.asset.audio",
     "format":"audio",
     "method":"import_audio_v1",
+    "settings_method":"import_audio_settings_v1",
     "wire":"u32_meta_len_le + meta_utf8 + payload",
     "formats":{formats_json}
   }},
   "methods":{{
-    "import_audio_v1":{{"in":"audio bytes","out":"[u32 meta_len_le][meta_json utf8][original bytes]"}}
+    "import_audio_v1":{{"in":"audio bytes","out":"[u32 meta_len_le][meta_json utf8][original bytes]"}},
+    "import_audio_settings_v1":{{"in":"[u32 settings_len_le][settings_json][audio bytes]","out":"as import_audio_v1, settings (loop_start, loop_end) in meta.import_settings"}}
   }},
   "meta_schema":"kalitech.audio.meta.v1"
 }}"#
@@ -189,6 +193,13 @@ impl ServiceV1 for AudioImporterService {
 
         match method.as_str() {
             "import_audio_v1" => import_audio(&bytes, None).map(|v| v),
+            "import_audio_settings_v1" => match split_settings(&bytes) {
+                Some((settings, audio)) => match import_audio(audio, None) {
+                    RResult::ROk(frame) => with_import_settings(frame, settings, "audio"),
+                    e => e,
+                },
+                None => err("audio: malformed settings payload"),
+            },
 
             _ => {
                 if let Some((base, ext)) = method.as_str().split_once(':') {
@@ -0,0 +1,378 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! glTF 2.0 -> NE3D conversion.
+//!
+//! The default scene is flattened into one indexed mesh in model space (node transforms
+//! applied). Primitive ranges and their materials are described in the meta as `submeshes`.
+//!
+//! Payload: `[NE3D mesh][embedded image bytes...]`. Embedded images (GLB buffer views and
+//! `data:` URIs) are appended unmodified after the mesh and addressed by `images[].offset/size`
+//! in the meta; images referenced by relative URI become asset dependencies instead.
+
+use base64::Engine as _;
+use gltf::image::Source as ImageSource;
+use gltf::mesh::Mode;
+use serde_json::{json, Value};
+
+const NE3D_VERSION: u32 = 1;
+const NE3D_FLAG_NORMALS: u32 = 0x1;
+const NE3D_FLAG_UVS: u32 = 0x2;
+
+type Mat4 = [[f32; 4]; 4];
+
+const IDENTITY: Mat4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+#[derive(Default)]
+struct MeshBuilder {
+    pos: Vec<[f32; 3]>,
+    nrm: Vec<[f32; 3]>,
+    uv: Vec<[f32; 2]>,
+    idx: Vec<u32>,
+    has_normals: bool,
+    has_uvs: bool,
+    submeshes: Vec<Value>,
+    skipped_primitives: u32,
+}
+
+pub fn convert(bytes: &[u8], container: &'static str) -> Result<(String, Vec<u8>), String> {
+    let gltf::Gltf { document, blob } =
+        gltf::Gltf::from_slice(bytes).map_err(|e| format!("gltf: parse failed: {e}"))?;
+
+    // The importer only sees this file's bytes; sibling .bin files cannot be resolved.
+    for b in document.buffers() {
+        if let gltf::buffer::Source::Uri(uri) = b.source() {
+            if !uri.starts_with("data:") {
+                return Err(format!(
+                    "gltf: external buffer '{uri}' is not supported (use .glb or embed data: URIs)"
+                ));
+            }
+        }
+    }
+
+    let buffers = gltf::import_buffers(&document, None, blob)
+        .map_err(|e| format!("gltf: buffers: {e}"))?;
+
+    let mut mb = MeshBuilder::default();
+    let roots: Vec<gltf::Node> = match document.default_scene().or_else(|| document.scenes().next()) {
+        Some(scene) => scene.nodes().collect(),
+        None => document.nodes().collect(),
+    };
+
+    let mut stack: Vec<(gltf::Node, Mat4)> = roots.into_iter().map(|n| (n, IDENTITY)).collect();
+    while let Some((node, parent)) = stack.pop() {
+        let world = mul(&parent, &node.transform().matrix());
+        if let Some(mesh) = node.mesh() {
+            append_mesh(&mut mb, &mesh, &buffers, &world)?;
+        }
+        stack.extend(node.children().map(|c| (c, world)));
+    }
+
+    if mb.pos.is_empty() || mb.idx.is_empty() {
+        return Err("gltf: no triangle geometry in scene".to_owned());
+    }
+
+    let mut payload = encode_ne3d(&mb);
+    let mut dependencies = Vec::new();
+    let mut images = Vec::new();
+
+    for img in document.images() {
+        match img.source() {
+            ImageSource::View { view, mime_type } => {
+                let data = &buffers[view.buffer().index()];
+                let start = view.offset();
+                let end = start + view.length();
+                let slice = data
+                    .get(start..end)
+                    .ok_or_else(|| format!("gltf: image {} view out of bounds", img.index()))?;
+                images.push(embed(&mut payload, img.index(), mime_type, slice));
+            }
+            ImageSource::Uri { uri, mime_type } => {
+                if let Some(rest) = uri.strip_prefix("data:") {
+                    let (mime, data) = decode_data_uri(rest)
+                        .ok_or_else(|| format!("gltf: image {} has a malformed data uri", img.index()))?;
+                    let mime = mime_type.map(str::to_owned).unwrap_or(mime);
+                    images.push(embed(&mut payload, img.index(), &mime, &data));
+                } else {
+                    let path = percent_decode(uri);
+                    images.push(json!({ "index": img.index(), "uri": path }));
+                    dependencies.push(json!({
+                        "path": path,
+                        "type_hint": "kalitech.asset.texture",
+                        "usage": "gltf.image",
+                        "relative": true,
+                    }));
+                }
+            }
+        }
+    }
+
+    let materials: Vec<Value> = document.materials().map(|m| material_json(&m)).collect();
+
+    let (bb_min, bb_max) = bounds(&mb.pos);
+    let meta = json!({
+        "schema": "kalitech.model3d.meta.v1",
+        "source": "gltf",
+        "container": container,
+        "payload_format": "ne3d",
+        "meshes": document.meshes().len(),
+        "vertices": mb.pos.len(),
+        "indices": mb.idx.len(),
+        "bbox_min": bb_min,
+        "bbox_max": bb_max,
+        "mesh": {
+            "vertex_count": mb.pos.len(),
+            "index_count": mb.idx.len(),
+            "has_normals": mb.has_normals,
+            "has_uvs": mb.has_uvs,
+            "skipped_primitives": mb.skipped_primitives,
+        },
+        "submeshes": mb.submeshes,
+        "materials": materials,
+        "images": images,
+        "dependencies": dependencies,
+    });
+
+    Ok((meta.to_string(), payload))
+}
+
+fn append_mesh(
+    mb: &mut MeshBuilder,
+    mesh: &gltf::Mesh,
+    buffers: &[gltf::buffer::Data],
+    world: &Mat4,
+) -> Result<(), String> {
+    let normal_m = normal_matrix(world);
+    let flip = det3(world) < 0.0;
+
+    for prim in mesh.primitives() {
+        if prim.mode() != Mode::Triangles {
+            mb.skipped_primitives += 1;
+            continue;
+        }
+
+        let reader = prim.reader(|b| buffers.get(b.index()).map(|d| &d.0[..]));
+        let Some(positions) = reader.read_positions() else {
+            mb.skipped_primitives += 1;
+            continue;
+        };
+
+        let base = mb.pos.len() as u32;
+        mb.pos.extend(positions.map(|p| transform_point(world, p)));
+        let count = mb.pos.len() - base as usize;
+
+        match reader.read_normals() {
+            Some(n) => {
+                mb.has_normals = true;
+                mb.nrm.extend(n.map(|n| normalize(transform_dir(&normal_m, n))));
+            }
+            None => mb.nrm.extend(std::iter::repeat([0.0, 1.0, 0.0]).take(count)),
+        }
+        mb.nrm.resize(mb.pos.len(), [0.0, 1.0, 0.0]);
+
+        match reader.read_tex_coords(0) {
+            Some(t) => {
+                mb.has_uvs = true;
+                mb.uv.extend(t.into_f32());
+            }
+            None => mb.uv.extend(std::iter::repeat([0.0, 0.0]).take(count)),
+        }
+        mb.uv.resize(mb.pos.len(), [0.0, 0.0]);
+
+        let first_index = mb.idx.len();
+        let local: Vec<u32> = match reader.read_indices() {
+            Some(i) => i.into_u32().collect(),
+            None => (0..count as u32).collect(),
+        };
+        if let Some(&bad) = local.iter().find(|&&i| i as usize >= count) {
+            return Err(format!(
+                "gltf: mesh '{}' index {bad} out of range ({count} vertices)",
+                mesh.name().unwrap_or("")
+            ));
+        }
+        for tri in local.chunks_exact(3) {
+            let (a, b, c) = if flip {
+                (tri[0], tri[2], tri[1])
+            } else {
+                (tri[0], tri[1], tri[2])
+            };
+            mb.idx.extend_from_slice(&[base + a, base + b, base + c]);
+        }
+
+        mb.submeshes.push(json!({
+            "mesh": mesh.name().unwrap_or(""),
+            "first_index": first_index,
+            "index_count": mb.idx.len() - first_index,
+            "material": prim.material().index(),
+        }));
+    }
+    Ok(())
+}
+
+fn material_json(m: &gltf::Material) -> Value {
+    let pbr = m.pbr_metallic_roughness();
+    let tex = |t: Option<gltf::texture::Texture>| t.map(|t| t.source().index());
+
+    json!({
+        "name": m.name().unwrap_or(""),
+        "base_color_factor": pbr.base_color_factor(),
+        "metallic_factor": pbr.metallic_factor(),
+        "roughness_factor": pbr.roughness_factor(),
+        "emissive_factor": m.emissive_factor(),
+        "alpha_mode": format!("{:?}", m.alpha_mode()).to_ascii_lowercase(),
+        "alpha_cutoff": m.alpha_cutoff(),
+        "double_sided": m.double_sided(),
+        "textures": {
+            "base_color": tex(pbr.base_color_texture().map(|i| i.texture())),
+            "metallic_roughness": tex(pbr.metallic_roughness_texture().map(|i| i.texture())),
+            "normal": tex(m.normal_texture().map(|i| i.texture())),
+            "occlusion": tex(m.occlusion_texture().map(|i| i.texture())),
+            "emissive": tex(m.emissive_texture().map(|i| i.texture())),
+        },
+    })
+}
+
+fn embed(payload: &mut Vec<u8>, index: usize, mime: &str, data: &[u8]) -> Value {
+    let offset = payload.len();
+    payload.extend_from_slice(data);
+    json!({ "index": index, "mime": mime, "offset": offset, "size": data.len() })
+}
+
+/// `image/png;base64,....` -> (mime, bytes)
+fn decode_data_uri(rest: &str) -> Option<(String, Vec<u8>)> {
+    let (header, data) = rest.split_once(',')?;
+    let mime = header.strip_suffix(";base64")?;
+    let bytes = base64::engine::general_purpose::STANDARD.decode(data).ok()?;
+    Some((mime.to_owned(), bytes))
+}
+
+fn percent_decode(s: &str) -> String {
+    let b = s.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'%' && i + 2 < b.len() {
+            let hex = std::str::from_utf8(&b[i + 1..i + 3]).ok();
+            if let Some(v) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(v);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(b[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn encode_ne3d(mb: &MeshBuilder) -> Vec<u8> {
+    let mut flags = 0u32;
+    if mb.has_normals {
+        flags |= NE3D_FLAG_NORMALS;
+    }
+    if mb.has_uvs {
+        flags |= NE3D_FLAG_UVS;
+    }
+
+    let mut out = Vec::with_capacity(20 + mb.pos.len() * 32 + mb.idx.len() * 4);
+    out.extend_from_slice(b"NE3D");
+    out.extend_from_slice(&NE3D_VERSION.to_le_bytes());
+    out.extend_from_slice(&(mb.pos.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(mb.idx.len() as u32).to_le_bytes());
+    out.extend_from_slice(&flags.to_le_bytes());
+
+    let mut put = |v: &[f32]| {
+        for f in v {
+            out.extend_from_slice(&f.to_le_bytes());
+        }
+    };
+    mb.pos.iter().for_each(|p| put(p));
+    if mb.has_normals {
+        mb.nrm.iter().for_each(|n| put(n));
+    }
+    if mb.has_uvs {
+        mb.uv.iter().for_each(|t| put(t));
+    }
+    for i in &mb.idx {
+        out.extend_from_slice(&i.to_le_bytes());
+    }
+    out
+}
+
+fn bounds(pos: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    let mut lo = [f32::INFINITY; 3];
+    let mut hi = [f32::NEG_INFINITY; 3];
+    for p in pos {
+        for a in 0..3 {
+            lo[a] = lo[a].min(p[a]);
+            hi[a] = hi[a].max(p[a]);
+        }
+    }
+    (lo, hi)
+}
+
+/* ---- column-major math (gltf matrices are `m[col][row]`) ---- */
+
+fn mul(a: &Mat4, b: &Mat4) -> Mat4 {
+    let mut r = [[0.0f32; 4]; 4];
+    for (c, col) in r.iter_mut().enumerate() {
+        for (row, v) in col.iter_mut().enumerate() {
+            *v = (0..4).map(|k| a[k][row] * b[c][k]).sum();
+        }
+    }
+    r
+}
+
+#[inline]
+fn transform_point(m: &Mat4, p: [f32; 3]) -> [f32; 3] {
+    [
+        m[0][0] * p[0] + m[1][0] * p[1] + m[2][0] * p[2] + m[3][0],
+        m[0][1] * p[0] + m[1][1] * p[1] + m[2][1] * p[2] + m[3][1],
+        m[0][2] * p[0] + m[1][2] * p[1] + m[2][2] * p[2] + m[3][2],
+    ]
+}
+
+#[inline]
+fn transform_dir(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    [
+        m[0][0] * v[0] + m[1][0] * v[1] + m[2][0] * v[2],
+        m[0][1] * v[0] + m[1][1] * v[1] + m[2][1] * v[2],
+        m[0][2] * v[0] + m[1][2] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+#[inline]
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let len = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if len > 1e-12 {
+        [v[0] / len, v[1] / len, v[2] / len]
+    } else {
+        [0.0, 1.0, 0.0]
+    }
+}
+
+#[inline]
+fn det3(m: &Mat4) -> f32 {
+    m[0][0] * (m[1][1] * m[2][2] - m[2][1] * m[1][2]) - m[1][0] * (m[0][1] * m[2][2] - m[2][1] * m[0][2])
+        + m[2][0] * (m[0][1] * m[1][2] - m[1][1] * m[0][2])
+}
+
+/// Inverse-transpose of the upper 3x3 up to a positive scale (cofactor matrix times the sign
+/// of the determinant); normals are renormalized afterwards.
+fn normal_matrix(m: &Mat4) -> [[f32; 3]; 3] {
+    let sign = if det3(m) < 0.0 { -1.0 } else { 1.0 };
+    let a = |c: usize, r: usize| m[c][r];
+    let mut out = [[0.0f32; 3]; 3];
+    for (c, col) in out.iter_mut().enumerate() {
+        for (r, v) in col.iter_mut().enumerate() {
+            let (c1, c2) = ((c + 1) % 3, (c + 2) % 3);
+            let (r1, r2) = ((r + 1) % 3, (r + 2) % 3);
+            *v = sign * (a(c1, r1) * a(c2, r2) - a(c1, r2) * a(c2, r1));
+        }
+    }
+    out
+}
@@ -0,0 +1,6 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+pub mod convert;
+pub mod module;
+pub mod plugin;
+pub mod providers;
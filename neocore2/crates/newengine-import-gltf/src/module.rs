@@ -0,0 +1,172 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use abi_stable::sabi_trait::TD_Opaque;
+use abi_stable::std_types::{RResult, RString, RVec};
+use abi_stable::StableAbi;
+
+use newengine_plugin_api::{
+    Blob, HostApiV1, MethodName, PluginInfo, PluginModule, ServiceV1, ServiceV1Dyn, ServiceV1_TO,
+};
+
+use std::sync::OnceLock;
+
+use crate::providers;
+
+/* =============================================================================================
+Wire: [u32 meta_len_le][meta_json utf8][payload bytes]
+============================================================================================= */
+
+#[inline]
+pub(crate) fn pack_wire(meta_json: &str, payload: &[u8]) -> Vec<u8> {
+    let meta = meta_json.as_bytes();
+    let meta_len: u32 = meta.len().min(u32::MAX as usize) as u32;
+
+    let mut out = Vec::with_capacity(4 + meta.len() + payload.len());
+    out.extend_from_slice(&meta_len.to_le_bytes());
+    out.extend_from_slice(meta);
+    out.extend_from_slice(payload);
+    out
+}
+
+#[inline]
+fn err(msg: impl Into<String>) -> RResult<RVec<u8>, RString> {
+    RResult::RErr(RString::from(msg.into()))
+}
+
+#[derive(StableAbi)]
+#[repr(C)]
+struct GltfImporterService;
+
+impl GltfImporterService {
+    fn import_auto(bytes: &[u8]) -> RResult<RVec<u8>, RString> {
+        for p in providers::iter_providers() {
+            if p.sniff(bytes) {
+                return p.import(bytes);
+            }
+        }
+
+        // .gltf files may start with a large embedded buffer before "asset".
+        if bytes.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{') {
+            return providers::import_as(bytes, "gltf");
+        }
+
+        err("gltf: unsupported container")
+    }
+
+    fn describe_cached() -> &'static str {
+        static CACHED: OnceLock<String> = OnceLock::new();
+        CACHED
+            .get_or_init(|| {
+                let mut exts: Vec<&'static str> = Vec::new();
+                let mut formats: Vec<&'static str> = Vec::new();
+
+                for p in providers::iter_providers() {
+                    for &e in p.extensions() {
+                        if !exts.iter().any(|&x| x == e) {
+                            exts.push(e);
+                        }
+                    }
+                    formats.push(p.describe_json());
+                }
+
+                exts.sort_unstable();
+
+                let exts_json = format!(
+                    "[{}]",
+                    exts.iter().map(|e| format!("\"{e}\"")).collect::<Vec<_>>().join(",")
+                );
+                let formats_json = format!("[{}]", formats.join(","));
+
+                // Above the generic 3D importer (120) so .gltf/.glb resolve here.
+                format!(
+                    r#"{{
+  "id":"kalitech.import.gltf.v1",
+  "kind":"asset_importer",
+  "asset_importer":{{
+    "version":"1",
+    "priority":130,
+    "extensions":{exts_json},
+    "output_type_id":"kalitech.asset.model3d",
+    "format":"ne3d",
+    "method":"import_gltf_v1",
+    "wire":"u32_meta_len_le + meta_utf8 + payload",
+    "formats":{formats_json}
+  }},
+  "methods":{{
+    "import_gltf_v1":{{"in":"gltf/glb bytes (auto sniff)","out":"[u32 meta_len_le][meta_json][NE3D + embedded images]"}}
+  }},
+  "meta_schema":"kalitech.model3d.meta.v1"
+}}"#
+                )
+            })
+            .as_str()
+    }
+}
+
+impl ServiceV1 for GltfImporterService {
+    fn id(&self) -> RString {
+        RString::from("kalitech.import.gltf.v1")
+    }
+
+    fn describe(&self) -> RString {
+        RString::from(Self::describe_cached())
+    }
+
+    fn call(&self, method: MethodName, payload: Blob) -> RResult<Blob, RString> {
+        let bytes: Vec<u8> = payload.into_vec();
+        match method.as_str() {
+            "import_gltf_v1" => Self::import_auto(&bytes),
+            _ => RResult::RErr(RString::from(format!(
+                "gltf-importer: unknown method '{}'",
+                method
+            ))),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct GltfImporterPlugin;
+
+impl PluginModule for GltfImporterPlugin {
+    fn info(&self) -> PluginInfo {
+        PluginInfo {
+            id: RString::from("import.gltf"),
+            name: RString::from("glTF 2.0 Importer (.gltf/.glb)"),
+            version: RString::from(env!("CARGO_PKG_VERSION")),
+        }
+    }
+
+    fn init(&mut self, host: HostApiV1) -> RResult<(), RString> {
+        let svc: ServiceV1Dyn<'static> = ServiceV1_TO::from_value(GltfImporterService, TD_Opaque);
+
+        let r = (host.register_service_v1)(svc);
+        if let Err(e) = r.clone().into_result() {
+            (host.log_warn)(RString::from(format!(
+                "gltf-importer: register service failed: {}",
+                e
+            )));
+            return r;
+        }
+
+        (host.log_info)(RString::from("gltf-importer: service registered (kalitech.import.gltf.v1)"));
+        RResult::ROk(())
+    }
+
+    fn start(&mut self) -> RResult<(), RString> {
+        RResult::ROk(())
+    }
+
+    fn fixed_update(&mut self, _dt: f32) -> RResult<(), RString> {
+        RResult::ROk(())
+    }
+
+    fn update(&mut self, _dt: f32) -> RResult<(), RString> {
+        RResult::ROk(())
+    }
+
+    fn render(&mut self, _dt: f32) -> RResult<(), RString> {
+        RResult::ROk(())
+    }
+
+    fn shutdown(&mut self) {}
+}
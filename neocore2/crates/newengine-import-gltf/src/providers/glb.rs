@@ -0,0 +1,33 @@
+use abi_stable::std_types::{RResult, RString, RVec};
+
+use crate::providers::{import_as, GltfProviderV1, ProviderEntry};
+
+pub struct GlbProvider;
+
+impl GltfProviderV1 for GlbProvider {
+    fn container(&self) -> &'static str {
+        "glb"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["glb"]
+    }
+
+    fn sniff(&self, bytes: &[u8]) -> bool {
+        bytes.len() >= 12 && &bytes[0..4] == b"glTF"
+    }
+
+    fn import(&self, bytes: &[u8]) -> RResult<RVec<u8>, RString> {
+        import_as(bytes, "glb")
+    }
+
+    fn describe_json(&self) -> &'static str {
+        r#"{"container":"glb","extensions":["glb"],"sniff":"magic: glTF","method":"import_gltf_v1"}"#
+    }
+}
+
+static PROVIDER: GlbProvider = GlbProvider;
+
+inventory::submit!(ProviderEntry {
+    provider: &PROVIDER
+});
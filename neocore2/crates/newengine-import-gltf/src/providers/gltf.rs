@@ -0,0 +1,38 @@
+use abi_stable::std_types::{RResult, RString, RVec};
+
+use crate::providers::{import_as, GltfProviderV1, ProviderEntry};
+
+pub struct GltfJsonProvider;
+
+impl GltfProviderV1 for GltfJsonProvider {
+    fn container(&self) -> &'static str {
+        "gltf"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["gltf"]
+    }
+
+    fn sniff(&self, bytes: &[u8]) -> bool {
+        let prefix = &bytes[..bytes.len().min(4096)];
+        let Ok(s) = std::str::from_utf8(prefix) else {
+            return false;
+        };
+        let t = s.trim_start_matches('\u{feff}').trim_start();
+        t.starts_with('{') && t.contains("\"asset\"")
+    }
+
+    fn import(&self, bytes: &[u8]) -> RResult<RVec<u8>, RString> {
+        import_as(bytes, "gltf")
+    }
+
+    fn describe_json(&self) -> &'static str {
+        r#"{"container":"gltf","extensions":["gltf"],"sniff":"json with \"asset\"","method":"import_gltf_v1","notes":"Buffers must be data: URIs; relative image URIs become dependencies."}"#
+    }
+}
+
+static PROVIDER: GltfJsonProvider = GltfJsonProvider;
+
+inventory::submit!(ProviderEntry {
+    provider: &PROVIDER
+});
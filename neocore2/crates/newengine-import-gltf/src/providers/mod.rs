@@ -0,0 +1,33 @@
+use abi_stable::std_types::{RResult, RString, RVec};
+
+pub trait GltfProviderV1: Sync + Send + 'static {
+    fn container(&self) -> &'static str;
+    fn extensions(&self) -> &'static [&'static str];
+    fn sniff(&self, bytes: &[u8]) -> bool;
+    fn import(&self, bytes: &[u8]) -> RResult<RVec<u8>, RString>;
+    fn describe_json(&self) -> &'static str;
+}
+
+pub struct ProviderEntry {
+    pub provider: &'static dyn GltfProviderV1,
+}
+
+inventory::collect!(ProviderEntry);
+
+#[inline]
+pub fn iter_providers() -> impl Iterator<Item = &'static dyn GltfProviderV1> {
+    inventory::iter::<ProviderEntry>
+        .into_iter()
+        .map(|e| e.provider)
+}
+
+#[inline]
+pub(crate) fn import_as(bytes: &[u8], container: &'static str) -> RResult<RVec<u8>, RString> {
+    match crate::convert::convert(bytes, container) {
+        Ok((meta, payload)) => RResult::ROk(RVec::from(crate::module::pack_wire(&meta, &payload))),
+        Err(e) => RResult::RErr(RString::from(e)),
+    }
+}
+
+pub mod glb;
+pub mod gltf;
@@ -4,6 +4,7 @@ use abi_stable::sabi_trait::TD_Opaque;
 use abi_stable::std_types::{RResult, RString, RVec};
 use abi_stable::StableAbi;
 
+use newengine_plugin_api::importer::{split_settings, with_import_settings};
 use newengine_plugin_api::{
     Blob, HostApiV1, MethodName, PluginInfo, PluginModule, ServiceV1, ServiceV1Dyn, ServiceV1_TO,
 };
@@ -37,11 +38,13 @@ impl ImageImporterService {
         CACHED.get_or_init(|| {
             let mut exts: Vec<&'static str> = Vec::new();
             let mut formats: Vec<&'static str> = Vec::new();
+            let mut mimes: Vec<(&'static str, &'static str)> = Vec::new();
 
             for p in providers::iter_providers() {
                 for &e in p.extensions() {
                     if !exts.iter().any(|&x| x == e) {
                         exts.push(e);
+                        mimes.push((e, p.mime()));
                     }
                 }
                 formats.push(p.describe_json());
@@ -69,20 +72,34 @@ impl ImageImporterService {
             }
             formats_json.push(']');
 
+            let mut mimes_json = String::new();
+            mimes_json.push('{');
+            for (i, (e, m)) in mimes.iter().enumerate() {
+                if i != 0 {
+                    mimes_json.push(',');
+                }
+                mimes_json.push_str(&format!("\"{e}\":\"{m}\""));
+            }
+            mimes_json.push('}');
+
             format!(
                 r#"{{
   "id":"kalitech.import.image.v1",
   "kind":"asset_importer",
   "asset_importer":{{
+    "version":"1",
     "extensions":{exts_json},
     "output_type_id":"kalitech.asset.texture",
     "format":"image",
     "method":"import_image_v1",
+    "settings_method":"import_image_settings_v1",
     "wire":"u32_meta_len_le + meta_utf8 + payload",
-    "formats":{formats_json}
+    "formats":{formats_json},
+    "mime_types":{mimes_json}
   }},
   "methods":{{
-    "import_image_v1":{{"in":"image bytes (auto sniff)","out":"[u32 meta_len_le][meta_json][payload]"}}
+    "import_image_v1":{{"in":"image bytes (auto sniff)","out":"[u32 meta_len_le][meta_json][payload]"}},
+    "import_image_settings_v1":{{"in":"[u32 settings_len_le][settings_json][image bytes]","out":"as import_image_v1, settings in meta.import_settings"}}
   }},
   "meta_schema":"kalitech.texture.meta.v1"
 }}"#
@@ -105,6 +122,13 @@ impl ServiceV1 for ImageImporterService {
         let bytes: Vec<u8> = payload.into_vec();
         match method.as_str() {
             "import_image_v1" => Self::import_auto(&bytes).map(|v| v),
+            "import_image_settings_v1" => match split_settings(&bytes) {
+                Some((settings, image)) => match Self::import_auto(image) {
+                    RResult::ROk(frame) => with_import_settings(frame, settings, "image"),
+                    e => e,
+                },
+                None => err("image: malformed settings payload"),
+            },
             _ => RResult::RErr(RString::from(format!(
                 "image-importer: unknown method '{}'",
                 method
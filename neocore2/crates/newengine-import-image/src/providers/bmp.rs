@@ -112,6 +112,10 @@ impl ImageProviderV1 for BmpProvider {
         &["bmp"]
     }
 
+    fn mime(&self) -> &'static str {
+        "image/bmp"
+    }
+
     fn sniff(&self, bytes: &[u8]) -> bool {
         bytes.len() >= 2 && bytes[0] == b'B' && bytes[1] == b'M'
     }
@@ -62,6 +62,10 @@ impl ImageProviderV1 for DdsProvider {
         &["dds"]
     }
 
+    fn mime(&self) -> &'static str {
+        "image/vnd-ms.dds"
+    }
+
     fn sniff(&self, bytes: &[u8]) -> bool {
         bytes.len() >= 4
             && bytes[0] == b'D'
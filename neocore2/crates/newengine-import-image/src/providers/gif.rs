@@ -70,6 +70,10 @@ impl ImageProviderV1 for GifProvider {
         &["gif"]
     }
 
+    fn mime(&self) -> &'static str {
+        "image/gif"
+    }
+
     fn sniff(&self, bytes: &[u8]) -> bool {
         bytes.len() >= 6 && (&bytes[..6] == b"GIF87a" || &bytes[..6] == b"GIF89a")
     }
@@ -122,6 +122,10 @@ impl ImageProviderV1 for JpegProvider {
         &["jpg", "jpeg"]
     }
 
+    fn mime(&self) -> &'static str {
+        "image/jpeg"
+    }
+
     fn sniff(&self, bytes: &[u8]) -> bool {
         bytes.len() >= 3 && bytes[0] == 0xFF && bytes[1] == 0xD8 && bytes[2] == 0xFF
     }
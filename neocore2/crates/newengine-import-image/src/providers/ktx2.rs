@@ -0,0 +1,224 @@
+use abi_stable::std_types::{RResult, RString, RVec};
+
+use crate::providers::{ImageProviderV1, ProviderEntry};
+
+#[inline]
+fn pack(meta_json: &str, payload: &[u8]) -> RVec<u8> {
+    let meta = meta_json.as_bytes();
+    let meta_len: u32 = meta.len().min(u32::MAX as usize) as u32;
+
+    let mut out = Vec::with_capacity(4 + meta.len() + payload.len());
+    out.extend_from_slice(&meta_len.to_le_bytes());
+    out.extend_from_slice(meta);
+    out.extend_from_slice(payload);
+    RVec::from(out)
+}
+
+#[inline]
+fn err(msg: impl Into<String>) -> RResult<RVec<u8>, RString> {
+    RResult::RErr(RString::from(msg.into()))
+}
+
+#[inline]
+fn ok(v: RVec<u8>) -> RResult<RVec<u8>, RString> {
+    RResult::ROk(v)
+}
+
+const IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+
+const HEADER_LEN: usize = 80;
+const LEVEL_INDEX_ENTRY: usize = 24;
+
+const KHR_DF_MODEL_ETC1S: u8 = 163;
+const KHR_DF_MODEL_UASTC: u8 = 166;
+
+#[inline]
+fn u32_at(b: &[u8], off: usize) -> Option<u32> {
+    b.get(off..off + 4)
+        .map(|s| u32::from_le_bytes([s[0], s[1], s[2], s[3]]))
+}
+
+#[inline]
+fn u64_at(b: &[u8], off: usize) -> Option<u64> {
+    b.get(off..off + 8).map(|s| {
+        let mut a = [0u8; 8];
+        a.copy_from_slice(s);
+        u64::from_le_bytes(a)
+    })
+}
+
+struct Level {
+    width: u32,
+    height: u32,
+    offset: u64,
+    length: u64,
+}
+
+struct Header {
+    vk_format: u32,
+    width: u32,
+    height: u32,
+    depth: u32,
+    layers: u32,
+    faces: u32,
+    supercompression: u32,
+    model: Option<u8>,
+    levels: Vec<Level>,
+}
+
+/// KTX2 container. The file is passed through untouched: Basis Universal data stays
+/// supercompressed and is transcoded by the host for the active render backend.
+pub struct Ktx2Provider;
+
+impl Ktx2Provider {
+    fn parse(bytes: &[u8]) -> Result<Header, String> {
+        if bytes.len() < HEADER_LEN {
+            return Err("ktx2: truncated header".to_string());
+        }
+        let field = |off: usize| u32_at(bytes, off).ok_or_else(|| "ktx2: truncated header".to_string());
+
+        let vk_format = field(12)?;
+        let width = field(20)?;
+        let height = field(24)?.max(1);
+        let depth = field(28)?.max(1);
+        let layers = field(32)?.max(1);
+        let faces = field(36)?;
+        let level_count = field(40)?.max(1);
+        let supercompression = field(44)?;
+        let dfd_offset = field(48)? as usize;
+
+        if width == 0 || (faces != 1 && faces != 6) {
+            return Err(format!("ktx2: unsupported shape width={width} faces={faces}"));
+        }
+
+        let mut levels = Vec::with_capacity(level_count as usize);
+        for i in 0..level_count as usize {
+            let at = HEADER_LEN + i * LEVEL_INDEX_ENTRY;
+            let (Some(offset), Some(length)) = (u64_at(bytes, at), u64_at(bytes, at + 8)) else {
+                return Err("ktx2: truncated level index".to_string());
+            };
+            if offset.saturating_add(length) > bytes.len() as u64 {
+                return Err(format!("ktx2: level {i} out of bounds"));
+            }
+            levels.push(Level {
+                width: (width >> i).max(1),
+                height: (height >> i).max(1),
+                offset,
+                length,
+            });
+        }
+
+        // Color model of the basic DFD block (after the u32 total size and two header words).
+        let model = bytes.get(dfd_offset + 4 + 8).copied();
+
+        Ok(Header {
+            vk_format,
+            width,
+            height,
+            depth,
+            layers,
+            faces,
+            supercompression,
+            model,
+            levels,
+        })
+    }
+
+    #[inline]
+    fn format_name(h: &Header) -> String {
+        match (h.vk_format, h.model, h.supercompression) {
+            (0, Some(KHR_DF_MODEL_UASTC), _) => "BASIS_UASTC".to_string(),
+            (0, Some(KHR_DF_MODEL_ETC1S), _) | (0, _, 1) => "BASIS_ETC1S".to_string(),
+            (37 | 43, ..) => "RGBA8".to_string(),
+            (131..=134, ..) => "BC1".to_string(),
+            (135 | 136, ..) => "BC2".to_string(),
+            (137 | 138, ..) => "BC3".to_string(),
+            (139, ..) => "BC4".to_string(),
+            (141, ..) => "BC5".to_string(),
+            (145 | 146, ..) => "BC7".to_string(),
+            (151 | 152, ..) => "ETC2_RGBA8".to_string(),
+            (157 | 158, ..) => "ASTC_4x4".to_string(),
+            (v, ..) => format!("VK_FORMAT_{v}"),
+        }
+    }
+
+    #[inline]
+    fn supercompression_name(v: u32) -> &'static str {
+        match v {
+            0 => "none",
+            1 => "basis_lz",
+            2 => "zstd",
+            3 => "zlib",
+            _ => "unknown",
+        }
+    }
+
+    fn build_meta_json(h: &Header) -> String {
+        let fmt = Self::format_name(h);
+        let transcode = fmt.starts_with("BASIS_");
+
+        let mut levels = String::from("[");
+        for (i, l) in h.levels.iter().enumerate() {
+            if i != 0 {
+                levels.push(',');
+            }
+            levels.push_str(&format!(
+                "{{\"width\":{},\"height\":{},\"offset\":{},\"length\":{}}}",
+                l.width, l.height, l.offset, l.length
+            ));
+        }
+        levels.push(']');
+
+        format!(
+            "{{\"schema\":\"kalitech.texture.meta.v1\",\"container\":\"ktx2\",\"width\":{},\"height\":{},\"depth\":{},\"layers\":{},\"mips\":{},\"is_cube\":{},\"format\":\"{fmt}\",\"vk_format\":{},\"supercompression\":\"{}\",\"transcode\":{transcode},\"levels\":{levels}}}",
+            h.width,
+            h.height,
+            h.depth,
+            h.layers,
+            h.levels.len(),
+            h.faces == 6,
+            h.vk_format,
+            Self::supercompression_name(h.supercompression),
+        )
+    }
+}
+
+impl ImageProviderV1 for Ktx2Provider {
+    fn container(&self) -> &'static str {
+        "ktx2"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["ktx2"]
+    }
+
+    fn mime(&self) -> &'static str {
+        "image/ktx2"
+    }
+
+    fn sniff(&self, bytes: &[u8]) -> bool {
+        bytes.len() >= IDENTIFIER.len() && bytes[..IDENTIFIER.len()] == IDENTIFIER
+    }
+
+    fn import(&self, bytes: &[u8]) -> RResult<RVec<u8>, RString> {
+        let header = match Self::parse(bytes) {
+            Ok(h) => h,
+            Err(e) => return err(e),
+        };
+
+        let meta = Self::build_meta_json(&header);
+        ok(pack(&meta, bytes))
+    }
+
+    fn describe_json(&self) -> &'static str {
+        r#"{"container":"ktx2","extensions":["ktx2"],"sniff":"magic: AB 4B 54 58 20 32 30 BB","method":"import_image_v1","passthrough":true,"transcode":"basis_etc1s|basis_uastc"}"#
+    }
+}
+
+static PROVIDER: Ktx2Provider = Ktx2Provider;
+
+inventory::submit!(ProviderEntry {
+    provider: &PROVIDER
+});
@@ -3,6 +3,8 @@ use abi_stable::std_types::{RResult, RString, RVec};
 pub trait ImageProviderV1: Sync + Send + 'static {
     fn container(&self) -> &'static str;
     fn extensions(&self) -> &'static [&'static str];
+    /// Content type of the container, shared by all of its extensions.
+    fn mime(&self) -> &'static str;
     fn sniff(&self, bytes: &[u8]) -> bool;
     fn import(&self, bytes: &[u8]) -> RResult<RVec<u8>, RString>;
     fn describe_json(&self) -> &'static str;
@@ -22,6 +24,7 @@ pub fn iter_providers() -> impl Iterator<Item = &'static dyn ImageProviderV1> {
 }
 
 pub mod dds;
+pub mod ktx2;
 pub mod png;
 
 pub mod bmp;
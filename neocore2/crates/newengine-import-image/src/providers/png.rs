@@ -64,6 +64,10 @@ impl ImageProviderV1 for PngProvider {
         &["png"]
     }
 
+    fn mime(&self) -> &'static str {
+        "image/png"
+    }
+
     fn sniff(&self, bytes: &[u8]) -> bool {
         bytes.len() >= 8
             && bytes[0] == 0x89
@@ -95,6 +95,10 @@ impl ImageProviderV1 for TgaProvider {
         &["tga"]
     }
 
+    fn mime(&self) -> &'static str {
+        "image/x-tga"
+    }
+
     fn sniff(&self, bytes: &[u8]) -> bool {
         plausible_tga_header(bytes).is_some()
     }
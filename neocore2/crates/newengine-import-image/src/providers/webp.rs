@@ -122,6 +122,10 @@ impl ImageProviderV1 for WebpProvider {
         &["webp"]
     }
 
+    fn mime(&self) -> &'static str {
+        "image/webp"
+    }
+
     fn sniff(&self, bytes: &[u8]) -> bool {
         bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP"
     }
@@ -84,6 +84,7 @@ impl TextService {
   "id":"{id}",
   "kind":"asset_importer",
   "asset_importer":{{
+    "version":"1",
     "priority":100,
     "extensions":{exts},
     "output_type_id":"kalitech.asset.text",
@@ -0,0 +1,777 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::bus::{MASTER_BUS, MUSIC_BUS, SFX_BUS, VOICE_BUS};
+use crate::config::AudioModuleConfig;
+use crate::decode::{decode_all, AudioStream};
+use crate::error::AudioError;
+use crate::mixer::{Mixer, MixerStats, Voice, VoiceId, VoiceSource};
+use crate::occlusion::{
+    cross, dot, length, normalize, sub, OcclusionConfig, OcclusionGeometry, OcclusionRequest,
+    OcclusionWorker,
+};
+use crate::output::OutputInfo;
+
+use newengine_assets::{AssetBlob, AudioAsset, AudioFormat, AudioReader};
+use newengine_audio_api::prelude::*;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+pub const AUDIO_API_ID: &str = "audio.api";
+
+/// How a clip is decoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StreamMode {
+    /// Stream OGG/MP3 longer than `AudioModuleConfig::stream_threshold_sec`.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Playable sound: decoded samples or the encoded bytes of a streamed clip.
+#[derive(Clone)]
+pub struct AudioClip {
+    kind: ClipKind,
+    duration_sec: f64,
+}
+
+#[derive(Clone)]
+enum ClipKind {
+    Static(Arc<[f32]>),
+    Stream {
+        bytes: Arc<[u8]>,
+        container: String,
+        sample_rate: u32,
+        channels: u16,
+    },
+}
+
+impl AudioClip {
+    #[inline]
+    pub fn is_streaming(&self) -> bool {
+        matches!(self.kind, ClipKind::Stream { .. })
+    }
+
+    #[inline]
+    pub fn duration_sec(&self) -> f64 {
+        self.duration_sec
+    }
+}
+
+/// Per-voice playback parameters.
+#[derive(Debug, Clone, Copy)]
+pub struct PlayParams {
+    pub bus: AudioBusId,
+    pub volume: f32,
+    pub pan: f32,
+    pub pitch: f32,
+    pub looping: bool,
+}
+
+impl Default for PlayParams {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            bus: SFX_BUS,
+            volume: 1.0,
+            pan: 0.0,
+            pitch: 1.0,
+            looping: false,
+        }
+    }
+}
+
+impl PlayParams {
+    #[inline]
+    pub fn with_bus(mut self, bus: AudioBusId) -> Self {
+        self.bus = bus;
+        self
+    }
+
+    #[inline]
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume.max(0.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_pan(mut self, pan: f32) -> Self {
+        self.pan = pan.clamp(-1.0, 1.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_pitch(mut self, pitch: f32) -> Self {
+        self.pitch = pitch.clamp(0.01, 8.0);
+        self
+    }
+
+    #[inline]
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entity {
+    desc: AudioEntityDesc,
+    voices: [Option<VoiceId>; 4],
+    /// Smoothed occlusion applied to the entity's voices.
+    occlusion: OcclusionResult,
+}
+
+#[derive(Default)]
+struct EntityState {
+    next: u64,
+    entities: HashMap<AudioEntityId, Entity>,
+    listener: AudioListenerDesc,
+    spatial: SpatializationDesc,
+    events: HashMap<AudioEventId, (AudioClip, PlayParams)>,
+    voice_lines: HashMap<String, AudioClip>,
+    occlusion_timer: f32,
+}
+
+struct Shared {
+    config: AudioModuleConfig,
+    output: OutputInfo,
+    mixer: Arc<Mutex<Mixer>>,
+    next_voice: AtomicU64,
+    state: Mutex<EntityState>,
+    occlusion: OcclusionWorker,
+    /// Voice started by `preview`; a new preview replaces it.
+    preview: Mutex<Option<VoiceId>>,
+}
+
+/// Audio output API (registered as `AUDIO_API_ID`).
+///
+/// Also implements the `newengine-audio-api` traits (`AudioApiV1` and its sub-systems) so
+/// gameplay code can stay backend-agnostic. Ambience, environment and vehicle calls are
+/// accepted and ignored by this backend.
+#[derive(Clone)]
+pub struct AudioApi {
+    shared: Arc<Shared>,
+}
+
+impl AudioApi {
+    pub(crate) fn new(config: AudioModuleConfig, output: OutputInfo, mixer: Arc<Mutex<Mixer>>) -> Self {
+        {
+            let mut m = mixer.lock();
+            for (name, parent) in config.buses.iter() {
+                let parent = parent.as_deref().and_then(|p| m.buses.find(p)).or(Some(MASTER_BUS));
+                m.buses.push(name, parent);
+            }
+        }
+
+        let state = EntityState {
+            next: 1,
+            spatial: SpatializationDesc {
+                min_distance: 1.0,
+                max_distance: 50.0,
+                rolloff: 1.0,
+                doppler: 0.0,
+                flags: 0,
+            },
+            ..Default::default()
+        };
+
+        Self {
+            shared: Arc::new(Shared {
+                output,
+                mixer,
+                next_voice: AtomicU64::new(1),
+                state: Mutex::new(state),
+                occlusion: OcclusionWorker::new(config.occlusion),
+                preview: Mutex::new(None),
+                config,
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn output(&self) -> &OutputInfo {
+        &self.shared.output
+    }
+
+    #[inline]
+    pub fn stats(&self) -> MixerStats {
+        self.shared.mixer.lock().stats()
+    }
+
+    /// Prepares a clip from an imported `kalitech.asset.audio` blob.
+    pub fn load_blob(&self, blob: &AssetBlob, mode: StreamMode) -> Result<AudioClip, AudioError> {
+        let asset = AudioReader::from_blob_parts(&blob.meta_json, &blob.payload)
+            .map_err(|e| AudioError::Decode(e.to_string()))?;
+        self.load(&asset, mode)
+    }
+
+    /// Prepares a clip. Importer metadata decides streaming and feeds the decoder when the
+    /// codec parameters are incomplete.
+    pub fn load(&self, asset: &AudioAsset, mode: StreamMode) -> Result<AudioClip, AudioError> {
+        let meta = &asset.meta;
+        let streamable = matches!(asset.format, AudioFormat::Ogg | AudioFormat::Mp3);
+        let stream = match mode {
+            StreamMode::Always => true,
+            StreamMode::Never => false,
+            StreamMode::Auto => {
+                streamable && meta.duration_sec > self.shared.config.stream_threshold_sec as f64
+            }
+        };
+
+        let bytes: Arc<[u8]> = Arc::from(asset.payload.as_slice());
+        let rate = self.shared.output.sample_rate;
+
+        let kind = if stream {
+            ClipKind::Stream {
+                bytes,
+                container: meta.container.clone(),
+                sample_rate: meta.sample_rate,
+                channels: meta.channels,
+            }
+        } else {
+            let samples = decode_all(bytes, &meta.container, meta.sample_rate, meta.channels, rate)?;
+            ClipKind::Static(Arc::from(samples))
+        };
+
+        log::info!(
+            target: "audio",
+            "clip.load container='{}' rate={} channels={} duration={:.2}s stream={}",
+            meta.container,
+            meta.sample_rate,
+            meta.channels,
+            meta.duration_sec,
+            stream
+        );
+
+        Ok(AudioClip {
+            kind,
+            duration_sec: meta.duration_sec,
+        })
+    }
+
+    /// Starts a voice.
+    pub fn play(&self, clip: &AudioClip, params: PlayParams) -> Result<VoiceId, AudioError> {
+        let cfg = &self.shared.config;
+        let source = match &clip.kind {
+            ClipKind::Static(samples) => VoiceSource::Static {
+                samples: samples.clone(),
+                pos: 0.0,
+            },
+            ClipKind::Stream {
+                bytes,
+                container,
+                sample_rate,
+                channels,
+            } => VoiceSource::Stream(AudioStream::spawn(
+                bytes.clone(),
+                container.clone(),
+                *sample_rate,
+                *channels,
+                self.shared.output.sample_rate,
+                cfg.stream_buffer_sec,
+                params.looping,
+            )?),
+        };
+
+        let id = VoiceId(self.shared.next_voice.fetch_add(1, Ordering::Relaxed));
+        let mut m = self.shared.mixer.lock();
+        if m.buses.get(params.bus).is_none() {
+            return Err(AudioError::UnknownBus(params.bus));
+        }
+        if m.voices.len() >= cfg.max_voices {
+            return Err(AudioError::VoiceLimit(cfg.max_voices));
+        }
+
+        let mut v = Voice::new(id, source, params.bus);
+        v.volume = params.volume;
+        v.pan = params.pan;
+        v.pitch = params.pitch;
+        v.looping = params.looping;
+        m.voices.push(v);
+        Ok(id)
+    }
+
+    /// Stops a voice, optionally fading out.
+    pub fn stop(&self, id: VoiceId, fade_out_sec: f32) {
+        let mut m = self.shared.mixer.lock();
+        let rate = m.sample_rate;
+        if let Some(v) = m.voice_mut(id) {
+            v.fade_out(fade_out_sec, rate);
+        }
+    }
+
+    /// Auditions an asset (e.g. from the content browser) on the master bus, so game bus
+    /// mutes and ducking do not apply. Stops the previous preview.
+    pub fn preview(&self, asset: &AudioAsset) -> Result<VoiceId, AudioError> {
+        self.stop_preview();
+        let clip = self.load(asset, StreamMode::Auto)?;
+        let id = self.play(&clip, PlayParams::default().with_bus(MASTER_BUS))?;
+        *self.shared.preview.lock() = Some(id);
+        Ok(id)
+    }
+
+    pub fn stop_preview(&self) {
+        if let Some(id) = self.shared.preview.lock().take() {
+            self.stop(id, 0.05);
+        }
+    }
+
+    /// The preview voice, while it is still playing.
+    #[inline]
+    pub fn preview_voice(&self) -> Option<VoiceId> {
+        let id = (*self.shared.preview.lock())?;
+        self.is_playing(id).then_some(id)
+    }
+
+    pub fn stop_bus(&self, bus: AudioBusId, fade_out_sec: f32) {
+        let mut m = self.shared.mixer.lock();
+        let rate = m.sample_rate;
+        for v in m.voices.iter_mut().filter(|v| v.bus == bus) {
+            v.fade_out(fade_out_sec, rate);
+        }
+    }
+
+    #[inline]
+    pub fn is_playing(&self, id: VoiceId) -> bool {
+        self.shared.mixer.lock().voices.iter().any(|v| v.id == id)
+    }
+
+    #[inline]
+    pub fn set_paused(&self, id: VoiceId, paused: bool) {
+        if let Some(v) = self.shared.mixer.lock().voice_mut(id) {
+            v.paused = paused;
+        }
+    }
+
+    #[inline]
+    pub fn set_volume(&self, id: VoiceId, volume: f32) {
+        if let Some(v) = self.shared.mixer.lock().voice_mut(id) {
+            v.volume = volume.max(0.0);
+        }
+    }
+
+    #[inline]
+    pub fn set_pan(&self, id: VoiceId, pan: f32) {
+        if let Some(v) = self.shared.mixer.lock().voice_mut(id) {
+            v.pan = pan.clamp(-1.0, 1.0);
+        }
+    }
+
+    #[inline]
+    pub fn set_pitch(&self, id: VoiceId, pitch: f32) {
+        if let Some(v) = self.shared.mixer.lock().voice_mut(id) {
+            v.pitch = pitch.clamp(0.01, 8.0);
+        }
+    }
+
+    /// Creates a mixer group under `parent` (master when `None`).
+    pub fn create_bus(&self, name: &str, parent: Option<AudioBusId>) -> Result<AudioBusId, AudioError> {
+        let mut m = self.shared.mixer.lock();
+        let parent = parent.unwrap_or(MASTER_BUS);
+        if m.buses.get(parent).is_none() {
+            return Err(AudioError::UnknownBus(parent));
+        }
+        Ok(m.buses.push(name, Some(parent)))
+    }
+
+    #[inline]
+    pub fn find_bus(&self, name: &str) -> Option<AudioBusId> {
+        self.shared.mixer.lock().buses.find(name)
+    }
+
+    /// `(id, name, gain, muted)` for every bus.
+    pub fn buses(&self) -> Vec<(AudioBusId, String, f32, bool)> {
+        self.shared
+            .mixer
+            .lock()
+            .buses
+            .iter()
+            .map(|(id, b)| (id, b.name.clone(), b.gain, b.muted))
+            .collect()
+    }
+
+    #[inline]
+    pub fn set_bus_muted(&self, bus: AudioBusId, muted: bool) {
+        if let Some(b) = self.shared.mixer.lock().buses.get_mut(bus) {
+            b.muted = muted;
+        }
+    }
+
+    /// Binds a clip to an event id for `AudioSystemV1::post_event`.
+    pub fn register_event(&self, event: AudioEventId, clip: AudioClip, params: PlayParams) {
+        self.shared.state.lock().events.insert(event, (clip, params));
+    }
+
+    /// Binds a clip to a key for `VoiceSystemV1::play_voice_line`.
+    pub fn register_voice_line(&self, key: impl Into<String>, clip: AudioClip) {
+        self.shared.state.lock().voice_lines.insert(key.into(), clip);
+    }
+
+    /// Adds scene geometry used for occlusion raycasts (terrain, physics, level boxes).
+    #[inline]
+    pub fn add_occlusion_geometry(&self, geometry: Arc<dyn OcclusionGeometry>) {
+        self.shared.occlusion.add_geometry(geometry);
+    }
+
+    #[inline]
+    pub fn clear_occlusion_geometry(&self) {
+        self.shared.occlusion.clear_geometry();
+    }
+
+    #[inline]
+    pub fn occlusion_config(&self) -> OcclusionConfig {
+        self.shared.occlusion.config()
+    }
+
+    #[inline]
+    pub fn set_occlusion_config(&self, config: OcclusionConfig) {
+        self.shared.occlusion.set_config(config);
+    }
+
+    /// Current (smoothed) occlusion of an entity.
+    #[inline]
+    pub fn entity_occlusion(&self, id: AudioEntityId) -> Option<OcclusionResult> {
+        self.shared.state.lock().entities.get(&id).map(|e| e.occlusion)
+    }
+
+    /// Posts a raycast request at the configured rate and eases entities toward the
+    /// latest results.
+    fn update_occlusion(&self, dt: f32) {
+        let cfg = self.shared.occlusion.config();
+        let worker = &self.shared.occlusion;
+        let mut st = self.shared.state.lock();
+
+        if cfg.enabled && worker.has_geometry() {
+            st.occlusion_timer += dt;
+            let interval = 1.0 / cfg.rate_hz.max(0.5);
+            if st.occlusion_timer >= interval {
+                st.occlusion_timer %= interval;
+                let emitters = st
+                    .entities
+                    .iter()
+                    .filter(|(_, e)| e.voices.iter().any(Option::is_some))
+                    .map(|(id, e)| (*id, e.desc.pos))
+                    .collect::<Vec<_>>();
+                if !emitters.is_empty() {
+                    worker.post(OcclusionRequest {
+                        listener: st.listener.pos,
+                        emitters,
+                    });
+                }
+            }
+        }
+
+        let k = if cfg.smoothing_sec <= 0.0 {
+            1.0
+        } else {
+            1.0 - (-dt / cfg.smoothing_sec).exp()
+        };
+        for (id, e) in st.entities.iter_mut() {
+            let target = if cfg.enabled {
+                worker.result(*id).unwrap_or_default()
+            } else {
+                OcclusionResult::default()
+            };
+            e.occlusion.occlusion += (target.occlusion - e.occlusion.occlusion) * k;
+            e.occlusion.obstruction += (target.obstruction - e.occlusion.obstruction) * k;
+        }
+    }
+
+    /// Recomputes distance attenuation, listener-relative pan and occlusion filtering of
+    /// entity voices.
+    fn update_spatial(&self) {
+        let st = self.shared.state.lock();
+        let l = st.listener;
+        let right = normalize(cross(l.forward, l.up));
+        let sp = st.spatial;
+        let occ = self.shared.occlusion.config();
+
+        let mut m = self.shared.mixer.lock();
+        for e in st.entities.values() {
+            let d = sub(e.desc.pos, l.pos);
+            let dist = length(d);
+            let (occ_gain, lowpass_hz) = occ.apply(e.occlusion);
+            let gain = attenuation(dist, &sp) * e.desc.gain * occ_gain;
+            let pan = if dist > 1e-4 { dot(d, right) / dist } else { 0.0 };
+
+            for id in e.voices.iter().flatten() {
+                if let Some(v) = m.voice_mut(*id) {
+                    v.spatial_gain = gain;
+                    v.spatial_pan = pan;
+                    v.lowpass_hz = lowpass_hz;
+                    if e.desc.pitch > 0.0 {
+                        v.pitch = e.desc.pitch;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn attenuation(dist: f32, sp: &SpatializationDesc) -> f32 {
+    let min = sp.min_distance.max(0.01);
+    let max = sp.max_distance.max(min);
+    if dist <= min {
+        return 1.0;
+    }
+    if dist >= max {
+        return 0.0;
+    }
+    // Inverse-distance rolloff, faded to zero at max_distance.
+    let inv = min / (min + sp.rolloff.max(0.0) * (dist - min));
+    let edge = 1.0 - (dist - min) / (max - min);
+    inv * edge.sqrt()
+}
+
+impl AudioApiV1 for AudioApi {
+    #[inline]
+    fn capabilities(&self) -> AudioCapabilityMask {
+        AudioCapabilityMask::MIXER
+            .union(AudioCapabilityMask::MUSIC)
+            .union(AudioCapabilityMask::VOICE)
+    }
+
+    fn system(&self) -> AudioSystemV1Dyn<'_> {
+        self
+    }
+
+    fn ambience(&self) -> AmbienceSystemV1Dyn<'_> {
+        self
+    }
+
+    fn environment(&self) -> AudioEnvironmentV1Dyn<'_> {
+        self
+    }
+
+    fn occlusion(&self) -> AudioOcclusionV1Dyn<'_> {
+        self
+    }
+
+    fn mixer(&self) -> MixerSystemV1Dyn<'_> {
+        self
+    }
+
+    fn music(&self) -> MusicSystemV1Dyn<'_> {
+        self
+    }
+
+    fn voice(&self) -> VoiceSystemV1Dyn<'_> {
+        self
+    }
+
+    fn vehicle(&self) -> VehicleAudioV1Dyn<'_> {
+        self
+    }
+}
+
+impl AudioSystemV1 for AudioApi {
+    fn create_entity(&self, desc: AudioEntityDesc) -> AudioEntityId {
+        let mut st = self.shared.state.lock();
+        let id = AudioEntityId(st.next);
+        st.next += 1;
+        st.entities.insert(
+            id,
+            Entity {
+                desc,
+                voices: [None; 4],
+                occlusion: OcclusionResult::default(),
+            },
+        );
+        id
+    }
+
+    fn destroy_entity(&self, id: AudioEntityId) {
+        self.shared.occlusion.forget(id);
+        let Some(e) = self.shared.state.lock().entities.remove(&id) else {
+            return;
+        };
+        for v in e.voices.iter().flatten() {
+            self.stop(*v, 0.05);
+        }
+    }
+
+    fn set_entity_desc(&self, id: AudioEntityId, desc: AudioEntityDesc) {
+        if let Some(e) = self.shared.state.lock().entities.get_mut(&id) {
+            e.desc = desc;
+        }
+    }
+
+    fn set_listener(&self, listener: AudioListenerDesc) {
+        self.shared.state.lock().listener = listener;
+    }
+
+    fn set_spatialization_defaults(&self, desc: SpatializationDesc) {
+        self.shared.state.lock().spatial = desc;
+    }
+
+    fn update(&self, dt_sec: f32) {
+        {
+            // Lock order is always state -> mixer.
+            let mut st = self.shared.state.lock();
+            let m = self.shared.mixer.lock();
+            for e in st.entities.values_mut() {
+                for slot in e.voices.iter_mut() {
+                    if slot.is_some_and(|id| !m.voices.iter().any(|v| v.id == id)) {
+                        *slot = None;
+                    }
+                }
+            }
+        }
+        self.update_occlusion(dt_sec);
+        self.update_spatial();
+    }
+
+    fn post_event(&self, event: AudioEventId, target: AudioEntityId) -> u64 {
+        let binding = self.shared.state.lock().events.get(&event).cloned();
+        let Some((clip, mut params)) = binding else {
+            log::warn!(target: "audio", "event.post unknown event={}", event.0);
+            return 0;
+        };
+
+        // A non-master entity bus overrides the event's default bus.
+        let bus = self.shared.state.lock().entities.get(&target).map(|e| e.desc.bus);
+        if let Some(bus) = bus.filter(|b| *b != MASTER_BUS) {
+            params.bus = bus;
+        }
+
+        let id = match self.play(&clip, params) {
+            Ok(id) => id,
+            Err(e) => {
+                log::warn!(target: "audio", "event.post failed event={} err='{}'", event.0, e);
+                return 0;
+            }
+        };
+
+        if let Some(e) = self.shared.state.lock().entities.get_mut(&target) {
+            // Oldest slot is recycled when an entity has more concurrent voices than slots.
+            let slot = e.voices.iter().position(Option::is_none).unwrap_or(0);
+            e.voices[slot] = Some(id);
+        }
+        self.update_spatial();
+        id.0
+    }
+
+    fn stop_event_instance(&self, instance_id: u64) {
+        self.stop(VoiceId(instance_id), 0.05);
+    }
+
+    fn set_bus_gain(&self, bus: AudioBusId, gain: f32) {
+        if let Some(b) = self.shared.mixer.lock().buses.get_mut(bus) {
+            b.gain = gain.max(0.0);
+        }
+    }
+
+    fn set_snapshot(&self, snapshot: AudioSnapshotId, intensity: f32) {
+        log::debug!(
+            target: "audio",
+            "snapshot.set id={} intensity={} (snapshots are not supported by this backend)",
+            snapshot.0,
+            intensity
+        );
+    }
+}
+
+impl MixerSystemV1 for AudioApi {
+    fn set_master_gain(&self, gain: f32) {
+        AudioSystemV1::set_bus_gain(self, MASTER_BUS, gain);
+    }
+
+    fn set_ducking(&self, desc: DuckingDesc) {
+        let mut m = self.shared.mixer.lock();
+        if desc.amount <= 0.0 {
+            m.ducking = None;
+            if let Some(b) = m.buses.get_mut(desc.bus) {
+                b.duck = 1.0;
+            }
+            return;
+        }
+        m.ducking = Some(desc);
+    }
+}
+
+impl MusicSystemV1 for AudioApi {
+    fn set_state(&self, state: MusicStateDesc) {
+        let gain = 0.5 + 0.5 * state.intensity.clamp(0.0, 1.0);
+        AudioSystemV1::set_bus_gain(self, MUSIC_BUS, gain);
+    }
+
+    fn stop_all(&self, fade_out_sec: f32) {
+        self.stop_bus(MUSIC_BUS, fade_out_sec);
+    }
+}
+
+impl VoiceSystemV1 for AudioApi {
+    fn play_voice_line(&self, speaker: AudioEntityId, line: VoiceLineDesc) -> u64 {
+        let clip = self.shared.state.lock().voice_lines.get(line.key.as_str()).cloned();
+        let Some(clip) = clip else {
+            log::warn!(target: "audio", "voice.play unknown line='{}'", line.key);
+            return 0;
+        };
+
+        if matches!(line.priority, VoicePriority::High | VoicePriority::Critical) {
+            self.stop_bus(VOICE_BUS, 0.1);
+        }
+
+        let id = match self.play(&clip, PlayParams::default().with_bus(VOICE_BUS)) {
+            Ok(id) => id,
+            Err(e) => {
+                log::warn!(target: "audio", "voice.play failed line='{}' err='{}'", line.key, e);
+                return 0;
+            }
+        };
+
+        if let Some(e) = self.shared.state.lock().entities.get_mut(&speaker) {
+            let slot = e.voices.iter().position(Option::is_none).unwrap_or(0);
+            e.voices[slot] = Some(id);
+        }
+        self.update_spatial();
+        id.0
+    }
+
+    fn stop_voice_instance(&self, instance_id: u64) {
+        self.stop(VoiceId(instance_id), 0.1);
+    }
+}
+
+impl AmbienceSystemV1 for AudioApi {
+    fn set_zone(&self, _zone: AmbienceZoneDesc) {}
+
+    fn set_directional(&self, _desc: DirectionalAmbienceDesc) {}
+}
+
+impl AudioEnvironmentV1 for AudioApi {
+    fn set_reverb_zone(&self, _zone: ReverbZoneDesc) {}
+}
+
+impl AudioOcclusionV1 for AudioApi {
+    /// Externally traced result; applied to the entity nearest to the ray's end point.
+    fn submit_occlusion_result(&self, ray: OcclusionRayDesc, result: OcclusionResult) {
+        let end = Vec3f::new(
+            ray.origin.x + ray.direction.x * ray.max_distance,
+            ray.origin.y + ray.direction.y * ray.max_distance,
+            ray.origin.z + ray.direction.z * ray.max_distance,
+        );
+
+        let st = self.shared.state.lock();
+        let nearest = st
+            .entities
+            .iter()
+            .map(|(id, e)| (*id, length(sub(e.desc.pos, end))))
+            .filter(|(_, d)| *d <= 1.0)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((id, _)) = nearest {
+            self.shared.occlusion.submit(id, result);
+        }
+    }
+
+    fn set_portal(&self, portal: AudioPortalDesc) {
+        self.shared.occlusion.set_portal(portal);
+    }
+}
+
+impl VehicleAudioV1 for AudioApi {
+    fn set_vehicle_state(&self, _vehicle: AudioEntityId, _desc: VehicleAudioDesc) {}
+}
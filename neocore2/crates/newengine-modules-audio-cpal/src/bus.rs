@@ -0,0 +1,83 @@
+use newengine_audio_api::ids::AudioBusId;
+
+pub const MASTER_BUS: AudioBusId = AudioBusId(0);
+pub const SFX_BUS: AudioBusId = AudioBusId(1);
+pub const MUSIC_BUS: AudioBusId = AudioBusId(2);
+pub const VOICE_BUS: AudioBusId = AudioBusId(3);
+
+/// Mixer group. Effective gain is the product of the gains along the parent chain.
+#[derive(Debug, Clone)]
+pub struct Bus {
+    pub name: String,
+    pub parent: Option<AudioBusId>,
+    pub gain: f32,
+    pub muted: bool,
+    /// Multiplier applied by ducking (1 = not ducked).
+    pub(crate) duck: f32,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct BusTable {
+    buses: Vec<Bus>,
+}
+
+impl Default for BusTable {
+    fn default() -> Self {
+        let mut t = Self { buses: Vec::new() };
+        t.push("master", None);
+        t.push("sfx", Some(MASTER_BUS));
+        t.push("music", Some(MASTER_BUS));
+        t.push("voice", Some(MASTER_BUS));
+        t
+    }
+}
+
+impl BusTable {
+    pub(crate) fn push(&mut self, name: &str, parent: Option<AudioBusId>) -> AudioBusId {
+        let id = AudioBusId(self.buses.len() as u32);
+        self.buses.push(Bus {
+            name: name.to_string(),
+            parent,
+            gain: 1.0,
+            muted: false,
+            duck: 1.0,
+        });
+        id
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, id: AudioBusId) -> Option<&Bus> {
+        self.buses.get(id.0 as usize)
+    }
+
+    #[inline]
+    pub(crate) fn get_mut(&mut self, id: AudioBusId) -> Option<&mut Bus> {
+        self.buses.get_mut(id.0 as usize)
+    }
+
+    #[inline]
+    pub(crate) fn find(&self, name: &str) -> Option<AudioBusId> {
+        self.buses
+            .iter()
+            .position(|b| b.name == name)
+            .map(|i| AudioBusId(i as u32))
+    }
+
+    #[inline]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (AudioBusId, &Bus)> {
+        self.buses
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (AudioBusId(i as u32), b))
+    }
+
+    /// Effective gain per bus index. Parents always precede children, so one pass suffices.
+    pub(crate) fn resolve(&self, out: &mut Vec<f32>) {
+        out.clear();
+        for b in self.buses.iter() {
+            let own = if b.muted { 0.0 } else { b.gain * b.duck };
+            let parent = b.parent.and_then(|p| out.get(p.0 as usize)).copied().unwrap_or(1.0);
+            out.push(own * parent);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::occlusion::OcclusionConfig;
+
+/// Audio output module configuration.
+#[derive(Debug, Clone)]
+pub struct AudioModuleConfig {
+    /// Output device by name; `None` uses the host default.
+    pub device_name: Option<String>,
+    /// Preferred device sample rate; `None` keeps the device default.
+    pub sample_rate: Option<u32>,
+    /// OGG/MP3 clips longer than this (per importer metadata) are decoded on a streaming
+    /// thread instead of up front.
+    pub stream_threshold_sec: f32,
+    /// Decoded audio buffered ahead of the mixer per streaming voice.
+    pub stream_buffer_sec: f32,
+    /// Voices beyond this are refused.
+    pub max_voices: usize,
+    /// Extra buses created on init: `(name, parent name)`; parent `None` means master.
+    pub buses: Vec<(String, Option<String>)>,
+    pub occlusion: OcclusionConfig,
+}
+
+impl Default for AudioModuleConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            device_name: None,
+            sample_rate: None,
+            stream_threshold_sec: 8.0,
+            stream_buffer_sec: 0.5,
+            max_voices: 128,
+            buses: Vec::new(),
+            occlusion: OcclusionConfig::default(),
+        }
+    }
+}
+
+impl AudioModuleConfig {
+    #[inline]
+    pub fn with_device(mut self, name: impl Into<String>) -> Self {
+        self.device_name = Some(name.into());
+        self
+    }
+
+    #[inline]
+    pub fn with_sample_rate(mut self, rate: u32) -> Self {
+        self.sample_rate = Some(rate);
+        self
+    }
+
+    #[inline]
+    pub fn with_stream_threshold(mut self, seconds: f32) -> Self {
+        self.stream_threshold_sec = seconds.max(0.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_stream_buffer(mut self, seconds: f32) -> Self {
+        self.stream_buffer_sec = seconds.clamp(0.05, 10.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_max_voices(mut self, count: usize) -> Self {
+        self.max_voices = count.max(1);
+        self
+    }
+
+    #[inline]
+    pub fn with_occlusion(mut self, occlusion: OcclusionConfig) -> Self {
+        self.occlusion = occlusion;
+        self
+    }
+
+    #[inline]
+    pub fn with_bus(mut self, name: impl Into<String>, parent: Option<&str>) -> Self {
+        self.buses.push((name.into(), parent.map(str::to_string)));
+        self
+    }
+}
@@ -0,0 +1,338 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::error::AudioError;
+
+use parking_lot::{Condvar, Mutex};
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::{MediaSourceStream, MediaSourceStreamOptions};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Packet-by-packet decoder producing interleaved stereo at the output rate.
+pub(crate) struct PacketDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    channels: usize,
+    resampler: LinearResampler,
+    scratch: Option<SampleBuffer<f32>>,
+}
+
+impl PacketDecoder {
+    /// `src_rate`/`channels` come from importer metadata and are only used when the codec
+    /// parameters omit them.
+    pub(crate) fn open(
+        bytes: Arc<[u8]>,
+        container: &str,
+        src_rate: u32,
+        channels: u16,
+        dst_rate: u32,
+    ) -> Result<Self, AudioError> {
+        let mut hint = Hint::new();
+        if !container.is_empty() {
+            hint.with_extension(container);
+        }
+
+        let mss = MediaSourceStream::new(
+            Box::new(Cursor::new(bytes)),
+            MediaSourceStreamOptions::default(),
+        );
+        let probed = symphonia::default::get_probe()
+            .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+            .map_err(|e| AudioError::Decode(format!("probe: {e}")))?;
+
+        let format = probed.format;
+        let track = format
+            .default_track()
+            .ok_or_else(|| AudioError::Decode("no default track".to_string()))?;
+
+        let params = &track.codec_params;
+        let rate = params.sample_rate.unwrap_or(src_rate).max(1);
+        let channels = params
+            .channels
+            .map(|c| c.count())
+            .unwrap_or(channels as usize)
+            .max(1);
+
+        let decoder = symphonia::default::get_codecs()
+            .make(params, &DecoderOptions::default())
+            .map_err(|e| AudioError::Decode(format!("codec: {e}")))?;
+
+        Ok(Self {
+            track_id: track.id,
+            format,
+            decoder,
+            channels,
+            resampler: LinearResampler::new(rate, dst_rate),
+            scratch: None,
+        })
+    }
+
+    /// Appends the next packet's frames to `out`. Returns false at end of stream.
+    pub(crate) fn next_into(&mut self, out: &mut Vec<f32>) -> Result<bool, AudioError> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(p) => p,
+                Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    self.resampler.flush(out);
+                    return Ok(false);
+                }
+                Err(e) => return Err(AudioError::Decode(format!("packet: {e}"))),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(d) => d,
+                // Corrupt packets are skipped rather than ending playback.
+                Err(SymphoniaError::DecodeError(_)) => continue,
+                Err(e) => return Err(AudioError::Decode(format!("decode: {e}"))),
+            };
+
+            let spec = *decoded.spec();
+            let frames = decoded.capacity();
+            let fits = self
+                .scratch
+                .as_ref()
+                .is_some_and(|b| b.capacity() >= frames * spec.channels.count());
+            if !fits {
+                self.scratch = Some(SampleBuffer::new(frames as u64, spec));
+            }
+            let Some(buf) = self.scratch.as_mut() else {
+                continue;
+            };
+            buf.copy_interleaved_ref(decoded);
+
+            let stereo = to_stereo(buf.samples(), self.channels);
+            self.resampler.process(&stereo, out);
+            return Ok(true);
+        }
+    }
+}
+
+/// Decodes a whole clip up front.
+pub(crate) fn decode_all(
+    bytes: Arc<[u8]>,
+    container: &str,
+    src_rate: u32,
+    channels: u16,
+    dst_rate: u32,
+) -> Result<Vec<f32>, AudioError> {
+    let mut dec = PacketDecoder::open(bytes, container, src_rate, channels, dst_rate)?;
+    let mut out = Vec::new();
+    while dec.next_into(&mut out)? {}
+    Ok(out)
+}
+
+fn to_stereo(samples: &[f32], channels: usize) -> Vec<f32> {
+    match channels {
+        1 => samples.iter().flat_map(|s| [*s, *s]).collect(),
+        2 => samples.to_vec(),
+        n => samples
+            .chunks_exact(n)
+            .flat_map(|f| [f[0], f[1]])
+            .collect(),
+    }
+}
+
+/// Linear-interpolating stereo resampler that carries state across packets.
+pub(crate) struct LinearResampler {
+    step: f64,
+    pos: f64,
+    pending: Vec<f32>,
+}
+
+impl LinearResampler {
+    #[inline]
+    pub(crate) fn new(src_rate: u32, dst_rate: u32) -> Self {
+        Self {
+            step: src_rate as f64 / dst_rate.max(1) as f64,
+            pos: 0.0,
+            pending: Vec::new(),
+        }
+    }
+
+    pub(crate) fn process(&mut self, input: &[f32], out: &mut Vec<f32>) {
+        if (self.step - 1.0).abs() < 1e-9 && self.pending.is_empty() {
+            out.extend_from_slice(input);
+            return;
+        }
+
+        self.pending.extend_from_slice(input);
+        let frames = self.pending.len() / 2;
+
+        while self.pos + 1.0 < frames as f64 {
+            let i = self.pos as usize;
+            let t = (self.pos - i as f64) as f32;
+            for c in 0..2 {
+                let a = self.pending[i * 2 + c];
+                let b = self.pending[(i + 1) * 2 + c];
+                out.push(a + (b - a) * t);
+            }
+            self.pos += self.step;
+        }
+
+        let consumed = (self.pos as usize).min(frames);
+        self.pending.drain(..consumed * 2);
+        self.pos -= consumed as f64;
+    }
+
+    /// Emits the held-back last frame.
+    pub(crate) fn flush(&mut self, out: &mut Vec<f32>) {
+        if self.pending.len() >= 2 {
+            out.extend_from_slice(&self.pending[self.pending.len() - 2..]);
+        }
+        self.pending.clear();
+        self.pos = 0.0;
+    }
+}
+
+/// Bounded sample queue between a streaming decoder thread and the mixer.
+pub(crate) struct StreamBuffer {
+    queue: Mutex<VecDeque<f32>>,
+    space: Condvar,
+    capacity: usize,
+    finished: AtomicBool,
+    stop: AtomicBool,
+    underruns: AtomicU64,
+}
+
+impl StreamBuffer {
+    /// Pops up to `out.len()` samples; returns how many were written.
+    pub(crate) fn read(&self, out: &mut [f32]) -> usize {
+        let n = {
+            let mut q = self.queue.lock();
+            let n = out.len().min(q.len());
+            for (dst, src) in out.iter_mut().zip(q.drain(..n)) {
+                *dst = src;
+            }
+            n
+        };
+        self.space.notify_one();
+
+        if n < out.len() && !self.is_finished() {
+            self.underruns.fetch_add(1, Ordering::Relaxed);
+        }
+        n
+    }
+
+    #[inline]
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    /// True once the decoder finished and everything was consumed.
+    #[inline]
+    pub(crate) fn is_drained(&self) -> bool {
+        self.is_finished() && self.queue.lock().is_empty()
+    }
+
+    #[inline]
+    pub(crate) fn underruns(&self) -> u64 {
+        self.underruns.load(Ordering::Relaxed)
+    }
+
+    fn push(&self, samples: &[f32]) -> bool {
+        let mut q = self.queue.lock();
+        while q.len() + samples.len() > self.capacity && !q.is_empty() {
+            if self.stop.load(Ordering::Acquire) {
+                return false;
+            }
+            self.space.wait_for(&mut q, Duration::from_millis(50));
+        }
+        q.extend(samples.iter().copied());
+        !self.stop.load(Ordering::Acquire)
+    }
+}
+
+/// Handle to the decoder thread of one streaming voice; dropping it stops decoding.
+///
+/// The thread is detached: voices are dropped on the audio callback, which must not block.
+pub(crate) struct AudioStream {
+    buffer: Arc<StreamBuffer>,
+}
+
+impl AudioStream {
+    pub(crate) fn spawn(
+        bytes: Arc<[u8]>,
+        container: String,
+        src_rate: u32,
+        channels: u16,
+        dst_rate: u32,
+        buffer_sec: f32,
+        looping: bool,
+    ) -> Result<Self, AudioError> {
+        // Open once on the caller's thread so probe errors are reported synchronously.
+        let first = PacketDecoder::open(bytes.clone(), &container, src_rate, channels, dst_rate)?;
+
+        let capacity = ((dst_rate as f32 * buffer_sec) as usize * 2).max(4096);
+        let buffer = Arc::new(StreamBuffer {
+            queue: Mutex::new(VecDeque::with_capacity(capacity)),
+            space: Condvar::new(),
+            capacity,
+            finished: AtomicBool::new(false),
+            stop: AtomicBool::new(false),
+            underruns: AtomicU64::new(0),
+        });
+
+        let buf = buffer.clone();
+        std::thread::Builder::new()
+            .name("audio-stream".to_string())
+            .spawn(move || {
+                let mut dec = first;
+                let mut chunk = Vec::new();
+                loop {
+                    chunk.clear();
+                    match dec.next_into(&mut chunk) {
+                        Ok(more) => {
+                            if !chunk.is_empty() && !buf.push(&chunk) {
+                                break;
+                            }
+                            if more {
+                                continue;
+                            }
+                            if !looping {
+                                break;
+                            }
+                            match PacketDecoder::open(bytes.clone(), &container, src_rate, channels, dst_rate) {
+                                Ok(d) => dec = d,
+                                Err(e) => {
+                                    log::warn!(target: "audio", "stream.rewind failed err='{}'", e);
+                                    break;
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            log::warn!(target: "audio", "stream.decode failed err='{}'", e);
+                            break;
+                        }
+                    }
+                }
+                buf.finished.store(true, Ordering::Release);
+            })
+            .map_err(|e| AudioError::Device(format!("stream thread: {e}")))?;
+
+        Ok(Self { buffer })
+    }
+
+    #[inline]
+    pub(crate) fn buffer(&self) -> &StreamBuffer {
+        &self.buffer
+    }
+}
+
+impl Drop for AudioStream {
+    fn drop(&mut self) {
+        self.buffer.stop.store(true, Ordering::Release);
+        self.buffer.space.notify_all();
+    }
+}
@@ -0,0 +1,22 @@
+use newengine_audio_api::ids::AudioBusId;
+
+#[derive(Debug, Clone)]
+pub enum AudioError {
+    Device(String),
+    Decode(String),
+    UnknownBus(AudioBusId),
+    VoiceLimit(usize),
+}
+
+impl std::fmt::Display for AudioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AudioError::Device(e) => write!(f, "audio device: {e}"),
+            AudioError::Decode(e) => write!(f, "audio decode: {e}"),
+            AudioError::UnknownBus(b) => write!(f, "audio: unknown bus {}", b.0),
+            AudioError::VoiceLimit(n) => write!(f, "audio: voice limit reached ({n})"),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
@@ -0,0 +1,22 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+pub mod api;
+pub mod bus;
+pub mod config;
+mod decode;
+pub mod error;
+pub mod mixer;
+pub mod module;
+pub mod occlusion;
+pub mod output;
+pub mod thumbnail;
+
+pub use api::*;
+pub use bus::*;
+pub use config::*;
+pub use error::*;
+pub use mixer::{MixerStats, VoiceId};
+pub use module::*;
+pub use occlusion::{BoxOccluders, OcclusionConfig, OcclusionGeometry, OPEN_CUTOFF_HZ};
+pub use output::OutputInfo;
+pub use thumbnail::*;
@@ -0,0 +1,297 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::bus::{BusTable, VOICE_BUS};
+use crate::decode::AudioStream;
+use crate::occlusion::OPEN_CUTOFF_HZ;
+
+use newengine_audio_api::ids::AudioBusId;
+use newengine_audio_api::mixer::DuckingDesc;
+use std::sync::Arc;
+
+/// Handle to a playing sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VoiceId(pub u64);
+
+pub(crate) enum VoiceSource {
+    /// Fully decoded stereo frames at the output rate.
+    Static { samples: Arc<[f32]>, pos: f64 },
+    Stream(AudioStream),
+}
+
+pub(crate) struct Voice {
+    pub(crate) id: VoiceId,
+    pub(crate) source: VoiceSource,
+    pub(crate) bus: AudioBusId,
+    pub(crate) volume: f32,
+    /// -1 (left) ..= 1 (right).
+    pub(crate) pan: f32,
+    /// Playback rate; streaming voices always play at 1.
+    pub(crate) pitch: f32,
+    pub(crate) looping: bool,
+    pub(crate) paused: bool,
+    /// Distance attenuation / listener-relative pan from the entity system.
+    pub(crate) spatial_gain: f32,
+    pub(crate) spatial_pan: f32,
+    /// One-pole low-pass cutoff driven by occlusion; `OPEN_CUTOFF_HZ` bypasses the filter.
+    pub(crate) lowpass_hz: f32,
+    lowpass_state: [f32; 2],
+    /// Gains applied at the end of the previous block; ramped to avoid zipper noise.
+    applied: [f32; 2],
+    fade: Option<Fade>,
+    done: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    gain: f32,
+    step: f32,
+}
+
+impl Voice {
+    #[inline]
+    pub(crate) fn new(id: VoiceId, source: VoiceSource, bus: AudioBusId) -> Self {
+        Self {
+            id,
+            source,
+            bus,
+            volume: 1.0,
+            pan: 0.0,
+            pitch: 1.0,
+            looping: false,
+            paused: false,
+            spatial_gain: 1.0,
+            spatial_pan: 0.0,
+            lowpass_hz: OPEN_CUTOFF_HZ,
+            lowpass_state: [0.0, 0.0],
+            applied: [0.0, 0.0],
+            fade: None,
+            done: false,
+        }
+    }
+
+    /// Fades to silence over `seconds` and stops; zero stops immediately.
+    pub(crate) fn fade_out(&mut self, seconds: f32, sample_rate: u32) {
+        if seconds <= 0.0 {
+            self.done = true;
+            return;
+        }
+        let start = self.fade.map_or(1.0, |f| f.gain);
+        self.fade = Some(Fade {
+            gain: start,
+            step: start / (seconds * sample_rate as f32).max(1.0),
+        });
+    }
+
+    /// Balance law: the opposite channel is attenuated linearly, center keeps unity gain.
+    fn target_gains(&self, bus_gain: f32) -> [f32; 2] {
+        let g = self.volume * self.spatial_gain * bus_gain;
+        let pan = (self.pan + self.spatial_pan).clamp(-1.0, 1.0);
+        [g * (1.0 - pan).min(1.0), g * (1.0 + pan).min(1.0)]
+    }
+
+    /// Reads `tmp.len() / 2` frames into `tmp`; returns frames produced.
+    fn read(&mut self, tmp: &mut [f32]) -> usize {
+        match &mut self.source {
+            VoiceSource::Static { samples, pos } => {
+                let frames = samples.len() / 2;
+                if frames == 0 {
+                    self.done = true;
+                    return 0;
+                }
+                let step = self.pitch.max(0.01) as f64;
+                let mut produced = 0;
+
+                for out in tmp.chunks_exact_mut(2) {
+                    if *pos >= frames as f64 {
+                        if !self.looping {
+                            self.done = true;
+                            break;
+                        }
+                        *pos -= frames as f64;
+                    }
+
+                    let i = *pos as usize;
+                    let j = if i + 1 < frames {
+                        i + 1
+                    } else if self.looping {
+                        0
+                    } else {
+                        i
+                    };
+                    let t = (*pos - i as f64) as f32;
+                    for c in 0..2 {
+                        let a = samples[i * 2 + c];
+                        let b = samples[j * 2 + c];
+                        out[c] = a + (b - a) * t;
+                    }
+                    *pos += step;
+                    produced += 1;
+                }
+                produced
+            }
+            VoiceSource::Stream(stream) => {
+                let n = stream.buffer().read(tmp) / 2;
+                if n * 2 < tmp.len() && stream.buffer().is_drained() {
+                    self.done = true;
+                }
+                n
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MixerStats {
+    pub voices: usize,
+    pub streaming_voices: usize,
+    pub stream_underruns: u64,
+    pub peak: f32,
+}
+
+/// Voice list and bus graph; rendered on the audio callback.
+pub(crate) struct Mixer {
+    pub(crate) voices: Vec<Voice>,
+    pub(crate) buses: BusTable,
+    pub(crate) ducking: Option<DuckingDesc>,
+    pub(crate) sample_rate: u32,
+    bus_gains: Vec<f32>,
+    mix: Vec<f32>,
+    tmp: Vec<f32>,
+    peak: f32,
+}
+
+impl Mixer {
+    #[inline]
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        Self {
+            voices: Vec::new(),
+            buses: BusTable::default(),
+            ducking: None,
+            sample_rate,
+            bus_gains: Vec::new(),
+            mix: Vec::new(),
+            tmp: Vec::new(),
+            peak: 0.0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn voice_mut(&mut self, id: VoiceId) -> Option<&mut Voice> {
+        self.voices.iter_mut().find(|v| v.id == id)
+    }
+
+    pub(crate) fn stats(&self) -> MixerStats {
+        let mut s = MixerStats {
+            voices: self.voices.len(),
+            peak: self.peak,
+            ..Default::default()
+        };
+        for v in self.voices.iter() {
+            if let VoiceSource::Stream(st) = &v.source {
+                s.streaming_voices += 1;
+                s.stream_underruns += st.buffer().underruns();
+            }
+        }
+        s
+    }
+
+    /// Fills an interleaved device buffer with `channels` channels.
+    pub(crate) fn render(&mut self, out: &mut [f32], channels: usize) {
+        let channels = channels.max(1);
+        let frames = out.len() / channels;
+
+        self.update_ducking(frames);
+        self.buses.resolve(&mut self.bus_gains);
+
+        self.mix.clear();
+        self.mix.resize(frames * 2, 0.0);
+        self.tmp.resize(frames * 2, 0.0);
+
+        for v in self.voices.iter_mut() {
+            if v.paused || v.done {
+                continue;
+            }
+
+            let bus_gain = self.bus_gains.get(v.bus.0 as usize).copied().unwrap_or(0.0);
+            let target = v.target_gains(bus_gain);
+            let produced = v.read(&mut self.tmp[..frames * 2]);
+            if produced == 0 {
+                continue;
+            }
+
+            if v.lowpass_hz < OPEN_CUTOFF_HZ {
+                let fs = self.sample_rate.max(1) as f32;
+                let a = 1.0 - (-std::f32::consts::TAU * v.lowpass_hz / fs).exp();
+                for frame in self.tmp[..produced * 2].chunks_exact_mut(2) {
+                    for c in 0..2 {
+                        v.lowpass_state[c] += a * (frame[c] - v.lowpass_state[c]);
+                        frame[c] = v.lowpass_state[c];
+                    }
+                }
+            } else {
+                // Keep the filter primed so engaging it later does not click.
+                let n = produced * 2;
+                v.lowpass_state = [self.tmp[n - 2], self.tmp[n - 1]];
+            }
+
+            let inv = 1.0 / produced as f32;
+            for f in 0..produced {
+                let t = (f + 1) as f32 * inv;
+                let mut fade = 1.0;
+                if let Some(fd) = v.fade.as_mut() {
+                    fd.gain -= fd.step;
+                    if fd.gain <= 0.0 {
+                        v.done = true;
+                        break;
+                    }
+                    fade = fd.gain;
+                }
+                for c in 0..2 {
+                    let g = v.applied[c] + (target[c] - v.applied[c]) * t;
+                    self.mix[f * 2 + c] += self.tmp[f * 2 + c] * g * fade;
+                }
+            }
+            v.applied = target;
+        }
+
+        self.voices.retain(|v| !v.done);
+
+        let mut peak = 0.0f32;
+        for (f, frame) in out.chunks_exact_mut(channels).enumerate() {
+            let l = self.mix[f * 2].clamp(-1.0, 1.0);
+            let r = self.mix[f * 2 + 1].clamp(-1.0, 1.0);
+            peak = peak.max(l.abs()).max(r.abs());
+
+            if channels == 1 {
+                frame[0] = 0.5 * (l + r);
+                continue;
+            }
+            frame[0] = l;
+            frame[1] = r;
+            for s in frame.iter_mut().skip(2) {
+                *s = 0.0;
+            }
+        }
+        self.peak = peak;
+    }
+
+    /// Ducks `DuckingDesc::bus` while anything plays on the voice bus.
+    fn update_ducking(&mut self, frames: usize) {
+        let Some(d) = self.ducking else {
+            return;
+        };
+        let active = self
+            .voices
+            .iter()
+            .any(|v| v.bus == VOICE_BUS && !v.paused && !v.done);
+
+        let dt = frames as f32 / self.sample_rate.max(1) as f32;
+        let target = if active { 1.0 - d.amount.clamp(0.0, 1.0) } else { 1.0 };
+        let time = if active { d.attack_sec } else { d.release_sec };
+
+        if let Some(bus) = self.buses.get_mut(d.bus) {
+            let k = if time <= 0.0 { 1.0 } else { (dt / time).min(1.0) };
+            bus.duck += (target - bus.duck) * k;
+        }
+    }
+}
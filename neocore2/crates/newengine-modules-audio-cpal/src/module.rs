@@ -0,0 +1,116 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::api::{AudioApi, AUDIO_API_ID};
+use crate::config::AudioModuleConfig;
+use crate::error::AudioError;
+use crate::mixer::Mixer;
+use crate::output::OutputThread;
+
+use newengine_audio_api::system::AudioSystemV1;
+use newengine_core::{
+    ApiProvide, ApiVersion, EngineError, EngineResult, Module, ModuleCtx, SuspendReason,
+};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+pub const AUDIO_API_VERSION: ApiVersion = ApiVersion::new(0, 1, 0);
+pub const AUDIO_API_PROVIDE: ApiProvide = ApiProvide::new(AUDIO_API_ID, AUDIO_API_VERSION);
+
+/// Opens the output device and exposes `AudioApi`.
+///
+/// The cpal stream lives on its own thread; the mixer renders on the device callback.
+/// Entity spatialization is refreshed every update; occlusion raycasts run on a worker
+/// thread at `OcclusionConfig::rate_hz`.
+pub struct AudioModule {
+    config: Option<AudioModuleConfig>,
+    output: Option<OutputThread>,
+    api: Option<AudioApi>,
+}
+
+impl Default for AudioModule {
+    fn default() -> Self {
+        Self::new(AudioModuleConfig::default())
+    }
+}
+
+impl AudioModule {
+    #[inline]
+    pub fn new(config: AudioModuleConfig) -> Self {
+        Self {
+            config: Some(config),
+            output: None,
+            api: None,
+        }
+    }
+}
+
+impl<E: Send + 'static> Module<E> for AudioModule {
+    fn id(&self) -> &'static str {
+        "audio.cpal"
+    }
+
+    fn provides(&self) -> &'static [ApiProvide] {
+        &[AUDIO_API_PROVIDE]
+    }
+
+    fn init(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let config = self.config.take().unwrap_or_default();
+
+        let mixer = Arc::new(Mutex::new(Mixer::new(config.sample_rate.unwrap_or(48_000))));
+        let output = OutputThread::start(config.device_name.clone(), config.sample_rate, mixer.clone())
+            .map_err(|e: AudioError| EngineError::other(e.to_string()))?;
+
+        let api = AudioApi::new(config, output.info().clone(), mixer);
+        ctx.resources_mut().register_api(AUDIO_API_ID, api.clone())?;
+
+        self.output = Some(output);
+        self.api = Some(api);
+        Ok(())
+    }
+
+    fn update(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if let Some(api) = self.api.as_ref() {
+            let dt = ctx.frame().map_or(0.0, |f| f.dt);
+            AudioSystemV1::update(api, dt);
+        }
+        Ok(())
+    }
+
+    fn on_suspend(&mut self, _ctx: &mut ModuleCtx<'_, E>, reason: SuspendReason) -> EngineResult<()> {
+        log::info!(target: "audio", "output.pause reason={:?}", reason);
+        if let Some(out) = self.output.as_ref() {
+            out.pause();
+        }
+        Ok(())
+    }
+
+    fn on_resume(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if let Some(out) = self.output.as_ref() {
+            out.play();
+        }
+        Ok(())
+    }
+
+    fn on_pause(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        log::info!(target: "audio", "output.pause reason=disabled");
+        if let Some(out) = self.output.as_ref() {
+            out.pause();
+        }
+        Ok(())
+    }
+
+    fn on_unpause(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if let Some(out) = self.output.as_ref() {
+            out.play();
+        }
+        Ok(())
+    }
+
+    fn shutdown(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let _ = ctx.resources_mut().unregister_api::<AudioApi>(AUDIO_API_ID);
+        self.api = None;
+        // Joins the output thread and closes the device.
+        self.output = None;
+        Ok(())
+    }
+}
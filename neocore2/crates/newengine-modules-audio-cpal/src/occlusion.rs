@@ -0,0 +1,435 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_audio_api::ids::AudioEntityId;
+use newengine_audio_api::math::Vec3f;
+use newengine_audio_api::occlusion::{AudioPortalDesc, OcclusionResult};
+use parking_lot::{Condvar, Mutex, RwLock};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Scene geometry queried for occlusion rays.
+///
+/// Implemented for closures, so any raycaster can be plugged in, e.g. terrain:
+/// `api.add_occlusion_geometry(Arc::new(move |o, d, max| terrain.lock().raycast(..)))`.
+pub trait OcclusionGeometry: Send + Sync + 'static {
+    /// Distance to the first hit along normalized `dir`, if closer than `max_distance`.
+    fn raycast(&self, origin: Vec3f, dir: Vec3f, max_distance: f32) -> Option<f32>;
+}
+
+impl<F> OcclusionGeometry for F
+where
+    F: Fn(Vec3f, Vec3f, f32) -> Option<f32> + Send + Sync + 'static,
+{
+    #[inline]
+    fn raycast(&self, origin: Vec3f, dir: Vec3f, max_distance: f32) -> Option<f32> {
+        self(origin, dir, max_distance)
+    }
+}
+
+/// Axis-aligned boxes; enough for blockout levels and tests without a physics scene.
+#[derive(Debug, Clone, Default)]
+pub struct BoxOccluders {
+    pub boxes: Vec<(Vec3f, Vec3f)>,
+}
+
+impl BoxOccluders {
+    #[inline]
+    pub fn with_box(mut self, min: Vec3f, max: Vec3f) -> Self {
+        self.boxes.push((min, max));
+        self
+    }
+}
+
+impl OcclusionGeometry for BoxOccluders {
+    fn raycast(&self, origin: Vec3f, dir: Vec3f, max_distance: f32) -> Option<f32> {
+        self.boxes
+            .iter()
+            .filter_map(|(min, max)| ray_aabb(origin, dir, *min, *max))
+            .filter(|t| *t <= max_distance)
+            .min_by(|a, b| a.total_cmp(b))
+    }
+}
+
+/// How occlusion is sampled and mapped to filter/gain parameters.
+#[derive(Debug, Clone, Copy)]
+pub struct OcclusionConfig {
+    pub enabled: bool,
+    /// Raycast passes per second.
+    pub rate_hz: f32,
+    /// Extra rays aimed around the emitter to tell obstruction from occlusion.
+    pub offset_rays: u32,
+    /// Radius of the offset ray targets around the emitter (world units).
+    pub spread: f32,
+    /// Gain at full occlusion / obstruction.
+    pub occlusion_gain: f32,
+    pub obstruction_gain: f32,
+    /// Low-pass cutoff at full occlusion / obstruction.
+    pub occlusion_cutoff_hz: f32,
+    pub obstruction_cutoff_hz: f32,
+    /// Time constant used to smooth factor changes.
+    pub smoothing_sec: f32,
+}
+
+impl Default for OcclusionConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rate_hz: 15.0,
+            offset_rays: 4,
+            spread: 0.75,
+            occlusion_gain: 0.25,
+            obstruction_gain: 0.6,
+            occlusion_cutoff_hz: 700.0,
+            obstruction_cutoff_hz: 2500.0,
+            smoothing_sec: 0.15,
+        }
+    }
+}
+
+impl OcclusionConfig {
+    #[inline]
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    #[inline]
+    pub fn with_rate(mut self, hz: f32) -> Self {
+        self.rate_hz = hz.clamp(0.5, 240.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_offset_rays(mut self, rays: u32, spread: f32) -> Self {
+        self.offset_rays = rays.min(16);
+        self.spread = spread.max(0.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_smoothing(mut self, seconds: f32) -> Self {
+        self.smoothing_sec = seconds.max(0.0);
+        self
+    }
+
+    /// `(gain, lowpass_hz)` for a result.
+    pub fn apply(&self, r: OcclusionResult) -> (f32, f32) {
+        let occ = r.occlusion.clamp(0.0, 1.0);
+        let obs = r.obstruction.clamp(0.0, 1.0);
+
+        let gain = (1.0 - occ * (1.0 - self.occlusion_gain)) * (1.0 - obs * (1.0 - self.obstruction_gain));
+
+        // Interpolate in log-frequency so the sweep sounds even.
+        let open = OPEN_CUTOFF_HZ.ln();
+        let hz = (open
+            + occ * (self.occlusion_cutoff_hz.max(20.0).ln() - open)
+            + obs * (self.obstruction_cutoff_hz.max(20.0).ln() - open))
+            .exp()
+            .clamp(20.0, OPEN_CUTOFF_HZ);
+
+        (gain, hz)
+    }
+}
+
+/// Cutoff treated as "no filter".
+pub const OPEN_CUTOFF_HZ: f32 = 20_000.0;
+
+#[derive(Debug, Clone)]
+pub(crate) struct OcclusionRequest {
+    pub(crate) listener: Vec3f,
+    pub(crate) emitters: Vec<(AudioEntityId, Vec3f)>,
+}
+
+#[derive(Default)]
+struct Inbox {
+    request: Option<OcclusionRequest>,
+}
+
+struct WorkerShared {
+    geometry: RwLock<Vec<Arc<dyn OcclusionGeometry>>>,
+    portals: RwLock<HashMap<u32, AudioPortalDesc>>,
+    inbox: Mutex<Inbox>,
+    wake: Condvar,
+    results: Mutex<HashMap<AudioEntityId, OcclusionResult>>,
+    config: RwLock<OcclusionConfig>,
+    stop: AtomicBool,
+}
+
+/// Background raycast worker. The engine thread posts the latest listener/emitter
+/// snapshot at `rate_hz`; the worker keeps only the newest request.
+pub(crate) struct OcclusionWorker {
+    shared: Arc<WorkerShared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl OcclusionWorker {
+    pub(crate) fn new(config: OcclusionConfig) -> Self {
+        let shared = Arc::new(WorkerShared {
+            geometry: RwLock::new(Vec::new()),
+            portals: RwLock::new(HashMap::new()),
+            inbox: Mutex::new(Inbox::default()),
+            wake: Condvar::new(),
+            results: Mutex::new(HashMap::new()),
+            config: RwLock::new(config),
+            stop: AtomicBool::new(false),
+        });
+
+        let s = shared.clone();
+        let thread = std::thread::Builder::new()
+            .name("audio-occlusion".to_string())
+            .spawn(move || worker_loop(&s))
+            .map_err(|e| log::warn!(target: "audio", "occlusion.worker spawn failed err='{}'", e))
+            .ok();
+
+        Self { shared, thread }
+    }
+
+    #[inline]
+    pub(crate) fn config(&self) -> OcclusionConfig {
+        *self.shared.config.read()
+    }
+
+    #[inline]
+    pub(crate) fn set_config(&self, config: OcclusionConfig) {
+        *self.shared.config.write() = config;
+    }
+
+    #[inline]
+    pub(crate) fn has_geometry(&self) -> bool {
+        !self.shared.geometry.read().is_empty()
+    }
+
+    #[inline]
+    pub(crate) fn add_geometry(&self, geometry: Arc<dyn OcclusionGeometry>) {
+        self.shared.geometry.write().push(geometry);
+    }
+
+    #[inline]
+    pub(crate) fn clear_geometry(&self) {
+        self.shared.geometry.write().clear();
+    }
+
+    #[inline]
+    pub(crate) fn set_portal(&self, portal: AudioPortalDesc) {
+        self.shared.portals.write().insert(portal.id, portal);
+    }
+
+    pub(crate) fn post(&self, request: OcclusionRequest) {
+        self.shared.inbox.lock().request = Some(request);
+        self.shared.wake.notify_one();
+    }
+
+    /// Overrides the computed result for one entity (externally traced rays).
+    #[inline]
+    pub(crate) fn submit(&self, id: AudioEntityId, result: OcclusionResult) {
+        self.shared.results.lock().insert(id, result);
+    }
+
+    #[inline]
+    pub(crate) fn result(&self, id: AudioEntityId) -> Option<OcclusionResult> {
+        self.shared.results.lock().get(&id).copied()
+    }
+
+    #[inline]
+    pub(crate) fn forget(&self, id: AudioEntityId) {
+        self.shared.results.lock().remove(&id);
+    }
+}
+
+impl Drop for OcclusionWorker {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Release);
+        self.shared.wake.notify_all();
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+    }
+}
+
+fn worker_loop(s: &WorkerShared) {
+    loop {
+        let request = {
+            let mut inbox = s.inbox.lock();
+            while inbox.request.is_none() && !s.stop.load(Ordering::Acquire) {
+                s.wake.wait_for(&mut inbox, Duration::from_millis(250));
+            }
+            if s.stop.load(Ordering::Acquire) {
+                return;
+            }
+            inbox.request.take()
+        };
+        let Some(req) = request else {
+            continue;
+        };
+
+        let cfg = *s.config.read();
+        let geometry = s.geometry.read().clone();
+        let portals: Vec<AudioPortalDesc> = s.portals.read().values().copied().collect();
+
+        let mut out = Vec::with_capacity(req.emitters.len());
+        for (id, pos) in req.emitters.iter() {
+            out.push((*id, trace(&geometry, &portals, req.listener, *pos, &cfg)));
+        }
+
+        let mut results = s.results.lock();
+        for (id, r) in out {
+            results.insert(id, r);
+        }
+    }
+}
+
+/// Direct ray plus `offset_rays` rays to points around the emitter.
+///
+/// Direct path blocked and every offset blocked -> occlusion; direct blocked with some
+/// offsets clear (sound bends around an edge) -> obstruction. Open portals on the direct
+/// path scale both down by their openness.
+fn trace(
+    geometry: &[Arc<dyn OcclusionGeometry>],
+    portals: &[AudioPortalDesc],
+    listener: Vec3f,
+    emitter: Vec3f,
+    cfg: &OcclusionConfig,
+) -> OcclusionResult {
+    if geometry.is_empty() || !blocked(geometry, listener, emitter) {
+        return OcclusionResult::default();
+    }
+
+    let n = cfg.offset_rays;
+    let blocked_offsets = if n == 0 || cfg.spread <= 0.0 {
+        0
+    } else {
+        let axis = sub(emitter, listener);
+        let (u, v) = basis(axis);
+        (0..n)
+            .filter(|k| {
+                let a = std::f32::consts::TAU * (*k as f32) / n as f32;
+                let (s, c) = a.sin_cos();
+                let target = add(emitter, add(scale(u, c * cfg.spread), scale(v, s * cfg.spread)));
+                blocked(geometry, listener, target)
+            })
+            .count() as u32
+    };
+
+    let f = if n == 0 { 1.0 } else { blocked_offsets as f32 / n as f32 };
+    let mut result = OcclusionResult {
+        occlusion: f,
+        obstruction: 1.0 - f,
+    };
+
+    let open = portals
+        .iter()
+        .filter(|p| segment_hits_portal(listener, emitter, p))
+        .map(|p| p.openness.clamp(0.0, 1.0))
+        .fold(0.0f32, f32::max);
+    if open > 0.0 {
+        result.occlusion *= 1.0 - open;
+        result.obstruction *= 1.0 - open;
+    }
+    result
+}
+
+fn blocked(geometry: &[Arc<dyn OcclusionGeometry>], from: Vec3f, to: Vec3f) -> bool {
+    let d = sub(to, from);
+    let len = length(d);
+    if len <= 1e-3 {
+        return false;
+    }
+    let dir = scale(d, 1.0 / len);
+    // Ignore hits right at the emitter (its own collider).
+    let max = (len - 0.05).max(0.0);
+    geometry.iter().any(|g| g.raycast(from, dir, max).is_some_and(|t| t < max))
+}
+
+fn segment_hits_portal(a: Vec3f, b: Vec3f, p: &AudioPortalDesc) -> bool {
+    let n = p.normal;
+    let d = sub(b, a);
+    let denom = dot(n, d);
+    if denom.abs() < 1e-6 {
+        return false;
+    }
+    let t = dot(n, sub(p.position, a)) / denom;
+    if !(0.0..=1.0).contains(&t) {
+        return false;
+    }
+    let hit = add(a, scale(d, t));
+    let (u, v) = basis(n);
+    let local = sub(hit, p.position);
+    dot(local, u).abs() <= p.width * 0.5 && dot(local, v).abs() <= p.height * 0.5
+}
+
+fn ray_aabb(o: Vec3f, d: Vec3f, min: Vec3f, max: Vec3f) -> Option<f32> {
+    let mut t0 = 0.0f32;
+    let mut t1 = f32::INFINITY;
+    for (o, d, lo, hi) in [(o.x, d.x, min.x, max.x), (o.y, d.y, min.y, max.y), (o.z, d.z, min.z, max.z)] {
+        if d.abs() < 1e-8 {
+            if o < lo || o > hi {
+                return None;
+            }
+            continue;
+        }
+        let inv = 1.0 / d;
+        let (a, b) = ((lo - o) * inv, (hi - o) * inv);
+        t0 = t0.max(a.min(b));
+        t1 = t1.min(a.max(b));
+        if t0 > t1 {
+            return None;
+        }
+    }
+    Some(t0)
+}
+
+/// Two unit vectors perpendicular to `n` (and each other).
+fn basis(n: Vec3f) -> (Vec3f, Vec3f) {
+    let n = normalize(n);
+    let up = if n.y.abs() < 0.99 {
+        Vec3f::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3f::new(1.0, 0.0, 0.0)
+    };
+    let u = normalize(cross(up, n));
+    let v = cross(n, u);
+    (u, v)
+}
+
+#[inline]
+pub(crate) fn add(a: Vec3f, b: Vec3f) -> Vec3f {
+    Vec3f::new(a.x + b.x, a.y + b.y, a.z + b.z)
+}
+
+#[inline]
+pub(crate) fn sub(a: Vec3f, b: Vec3f) -> Vec3f {
+    Vec3f::new(a.x - b.x, a.y - b.y, a.z - b.z)
+}
+
+#[inline]
+pub(crate) fn scale(a: Vec3f, s: f32) -> Vec3f {
+    Vec3f::new(a.x * s, a.y * s, a.z * s)
+}
+
+#[inline]
+pub(crate) fn dot(a: Vec3f, b: Vec3f) -> f32 {
+    a.x * b.x + a.y * b.y + a.z * b.z
+}
+
+#[inline]
+pub(crate) fn cross(a: Vec3f, b: Vec3f) -> Vec3f {
+    Vec3f::new(a.y * b.z - a.z * b.y, a.z * b.x - a.x * b.z, a.x * b.y - a.y * b.x)
+}
+
+#[inline]
+pub(crate) fn length(a: Vec3f) -> f32 {
+    dot(a, a).sqrt()
+}
+
+#[inline]
+pub(crate) fn normalize(a: Vec3f) -> Vec3f {
+    let l = length(a);
+    if l <= 1e-6 {
+        return Vec3f::new(1.0, 0.0, 0.0);
+    }
+    scale(a, 1.0 / l)
+}
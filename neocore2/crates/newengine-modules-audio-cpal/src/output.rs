@@ -0,0 +1,219 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::error::AudioError;
+use crate::mixer::Mixer;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+use parking_lot::Mutex;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Negotiated device format.
+#[derive(Debug, Clone)]
+pub struct OutputInfo {
+    pub device_name: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+enum OutputCmd {
+    Play,
+    Pause,
+    Stop,
+}
+
+/// Owns the cpal stream on a dedicated thread (streams are not `Send` on every backend).
+pub(crate) struct OutputThread {
+    tx: mpsc::Sender<OutputCmd>,
+    thread: Option<JoinHandle<()>>,
+    info: OutputInfo,
+}
+
+impl OutputThread {
+    /// Opens the device and starts the stream; blocks until the device format is known.
+    pub(crate) fn start(
+        device_name: Option<String>,
+        sample_rate: Option<u32>,
+        mixer: Arc<Mutex<Mixer>>,
+    ) -> Result<Self, AudioError> {
+        let (tx, rx) = mpsc::channel::<OutputCmd>();
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<OutputInfo, AudioError>>();
+
+        let thread = std::thread::Builder::new()
+            .name("audio-output".to_string())
+            .spawn(move || {
+                let stream = match open_stream(device_name.as_deref(), sample_rate, mixer) {
+                    Ok((stream, info)) => {
+                        let _ = ready_tx.send(Ok(info));
+                        stream
+                    }
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+
+                while let Ok(cmd) = rx.recv() {
+                    let res = match cmd {
+                        OutputCmd::Play => stream.play().map_err(|e| e.to_string()),
+                        OutputCmd::Pause => stream.pause().map_err(|e| e.to_string()),
+                        OutputCmd::Stop => break,
+                    };
+                    if let Err(e) = res {
+                        log::warn!(target: "audio", "output.command failed err='{}'", e);
+                    }
+                }
+            })
+            .map_err(|e| AudioError::Device(format!("output thread: {e}")))?;
+
+        let info = ready_rx
+            .recv()
+            .map_err(|_| AudioError::Device("output thread exited".to_string()))??;
+
+        Ok(Self {
+            tx,
+            thread: Some(thread),
+            info,
+        })
+    }
+
+    #[inline]
+    pub(crate) fn info(&self) -> &OutputInfo {
+        &self.info
+    }
+
+    #[inline]
+    pub(crate) fn play(&self) {
+        let _ = self.tx.send(OutputCmd::Play);
+    }
+
+    #[inline]
+    pub(crate) fn pause(&self) {
+        let _ = self.tx.send(OutputCmd::Pause);
+    }
+}
+
+impl Drop for OutputThread {
+    fn drop(&mut self) {
+        let _ = self.tx.send(OutputCmd::Stop);
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+    }
+}
+
+fn select_device(
+    device_name: Option<&str>,
+    sample_rate: Option<u32>,
+) -> Result<(cpal::Device, cpal::SupportedStreamConfig), AudioError> {
+    let host = cpal::default_host();
+
+    let device = match device_name {
+        Some(name) => host
+            .output_devices()
+            .map_err(|e| AudioError::Device(e.to_string()))?
+            .find(|d| d.name().is_ok_and(|n| n == name))
+            .ok_or_else(|| AudioError::Device(format!("output device '{name}' not found")))?,
+        None => host
+            .default_output_device()
+            .ok_or_else(|| AudioError::Device("no default output device".to_string()))?,
+    };
+
+    let default = device
+        .default_output_config()
+        .map_err(|e| AudioError::Device(e.to_string()))?;
+
+    let config = match sample_rate {
+        Some(rate) if rate != default.sample_rate().0 => device
+            .supported_output_configs()
+            .map_err(|e| AudioError::Device(e.to_string()))?
+            .find(|c| {
+                c.channels() == default.channels()
+                    && c.sample_format() == default.sample_format()
+                    && c.min_sample_rate().0 <= rate
+                    && rate <= c.max_sample_rate().0
+            })
+            .map(|c| c.with_sample_rate(cpal::SampleRate(rate)))
+            .unwrap_or_else(|| {
+                log::warn!(
+                    target: "audio",
+                    "output.rate unsupported requested={} using={}",
+                    rate,
+                    default.sample_rate().0
+                );
+                default
+            }),
+        _ => default,
+    };
+
+    Ok((device, config))
+}
+
+fn open_stream(
+    device_name: Option<&str>,
+    sample_rate: Option<u32>,
+    mixer: Arc<Mutex<Mixer>>,
+) -> Result<(cpal::Stream, OutputInfo), AudioError> {
+    let (device, supported) = select_device(device_name, sample_rate)?;
+    let format = supported.sample_format();
+    let config: cpal::StreamConfig = supported.into();
+
+    let info = OutputInfo {
+        device_name: device.name().unwrap_or_else(|_| "<unknown>".to_string()),
+        sample_rate: config.sample_rate.0,
+        channels: config.channels,
+    };
+    mixer.lock().sample_rate = info.sample_rate;
+
+    let stream = match format {
+        cpal::SampleFormat::F32 => build::<f32>(&device, &config, mixer),
+        cpal::SampleFormat::I16 => build::<i16>(&device, &config, mixer),
+        cpal::SampleFormat::U16 => build::<u16>(&device, &config, mixer),
+        cpal::SampleFormat::I32 => build::<i32>(&device, &config, mixer),
+        other => Err(AudioError::Device(format!("unsupported sample format {other:?}"))),
+    }?;
+
+    stream
+        .play()
+        .map_err(|e| AudioError::Device(format!("play: {e}")))?;
+
+    log::info!(
+        target: "audio",
+        "output.open device='{}' rate={} channels={} format={:?}",
+        info.device_name,
+        info.sample_rate,
+        info.channels,
+        format
+    );
+
+    Ok((stream, info))
+}
+
+fn build<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mixer: Arc<Mutex<Mixer>>,
+) -> Result<cpal::Stream, AudioError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let mut scratch: Vec<f32> = Vec::new();
+
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                scratch.resize(data.len(), 0.0);
+                mixer.lock().render(&mut scratch, channels);
+                for (dst, src) in data.iter_mut().zip(scratch.iter()) {
+                    *dst = T::from_sample(*src);
+                }
+            },
+            |e| log::warn!(target: "audio", "output.stream error='{}'", e),
+            None,
+        )
+        .map_err(|e| AudioError::Device(format!("build stream: {e}")))
+}
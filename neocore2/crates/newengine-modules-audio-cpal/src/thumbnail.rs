@@ -0,0 +1,168 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::decode::{decode_all, PacketDecoder};
+use crate::error::AudioError;
+
+use newengine_assets::{AssetBlob, AudioAsset, AudioMeta, AudioReader};
+use std::sync::Arc;
+
+/// Default number of peak columns; enough for a browser tile at 2x scale.
+pub const DEFAULT_WAVEFORM_COLUMNS: usize = 256;
+
+/// Short labels for an audio tile, derived from importer metadata only (no decoding).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioBadges {
+    /// `m:ss`, or `h:mm:ss` for clips of an hour or more.
+    pub duration: String,
+    /// Upper-case codec, falling back to the container.
+    pub codec: String,
+    /// e.g. `44.1 kHz · stereo · 16-bit`.
+    pub details: String,
+}
+
+impl AudioBadges {
+    pub fn from_meta(meta: &AudioMeta) -> Self {
+        let codec = if meta.codec.trim().is_empty() {
+            meta.container.as_str()
+        } else {
+            meta.codec.as_str()
+        };
+
+        let mut details = Vec::new();
+        if meta.sample_rate > 0 {
+            let khz = meta.sample_rate as f64 / 1000.0;
+            details.push(if meta.sample_rate % 1000 == 0 {
+                format!("{khz:.0} kHz")
+            } else {
+                format!("{khz:.1} kHz")
+            });
+        }
+        match meta.channels {
+            0 => {}
+            1 => details.push("mono".to_string()),
+            2 => details.push("stereo".to_string()),
+            n => details.push(format!("{n} ch")),
+        }
+        if meta.bits_per_sample > 0 {
+            details.push(format!("{}-bit", meta.bits_per_sample));
+        }
+
+        Self {
+            duration: format_duration(meta.duration_sec),
+            codec: codec.trim().to_ascii_uppercase(),
+            details: details.join(" · "),
+        }
+    }
+}
+
+/// Waveform preview of an audio asset: per-column min/max sample peaks over both channels.
+#[derive(Debug, Clone)]
+pub struct AudioThumbnail {
+    pub peaks: Vec<[f32; 2]>,
+    pub duration_sec: f64,
+    pub badges: AudioBadges,
+}
+
+impl AudioThumbnail {
+    /// Builds a thumbnail from an imported `kalitech.asset.audio` blob.
+    pub fn from_blob(blob: &AssetBlob, columns: usize) -> Result<Self, AudioError> {
+        let asset = AudioReader::from_blob_parts(&blob.meta_json, &blob.payload)
+            .map_err(|e| AudioError::Decode(e.to_string()))?;
+        Self::build(&asset, columns)
+    }
+
+    /// Decodes the clip at its source rate and reduces it to `columns` peaks.
+    ///
+    /// When the importer reported a frame count the clip is reduced packet by packet;
+    /// otherwise it is decoded whole first.
+    pub fn build(asset: &AudioAsset, columns: usize) -> Result<Self, AudioError> {
+        let meta = &asset.meta;
+        let columns = columns.max(1);
+        let rate = meta.sample_rate.max(1);
+        let bytes: Arc<[u8]> = Arc::from(asset.payload.as_slice());
+
+        let total = if meta.frames > 0 {
+            meta.frames
+        } else {
+            (meta.duration_sec * rate as f64) as u64
+        };
+
+        let mut peaks = vec![[0.0f32; 2]; columns];
+        if total == 0 {
+            let samples = decode_all(bytes, &meta.container, rate, meta.channels, rate)?;
+            let frames = (samples.len() / 2) as u64;
+            accumulate(&mut peaks, &samples, 0, frames);
+        } else {
+            let mut dec = PacketDecoder::open(bytes, &meta.container, rate, meta.channels, rate)?;
+            let mut chunk = Vec::new();
+            let mut frame = 0u64;
+            loop {
+                chunk.clear();
+                let more = dec.next_into(&mut chunk)?;
+                accumulate(&mut peaks, &chunk, frame, total);
+                frame += (chunk.len() / 2) as u64;
+                if !more {
+                    break;
+                }
+            }
+        }
+
+        log::debug!(
+            target: "audio",
+            "thumbnail.build container='{}' columns={} duration={:.2}s",
+            meta.container,
+            columns,
+            meta.duration_sec
+        );
+
+        Ok(Self {
+            peaks,
+            duration_sec: meta.duration_sec,
+            badges: AudioBadges::from_meta(meta),
+        })
+    }
+
+    /// Rasterizes the waveform into tightly packed RGBA8, ready for a UI texture.
+    pub fn render_rgba(&self, width: u32, height: u32, fg: [u8; 4], bg: [u8; 4]) -> Vec<u8> {
+        let (w, h) = (width.max(1) as usize, height.max(1) as usize);
+        let mut px: Vec<u8> = bg.iter().copied().cycle().take(w * h * 4).collect();
+
+        let mid = (h - 1) as f32 * 0.5;
+        let to_row = |s: f32| (mid - s.clamp(-1.0, 1.0) * mid).round() as usize;
+
+        for x in 0..w {
+            let [lo, hi] = self.peaks[x * self.peaks.len() / w];
+            let (top, bottom) = (to_row(hi), to_row(lo));
+            // Silence still draws the center line.
+            for y in top.min(bottom)..=bottom.max(top) {
+                let i = (y * w + x) * 4;
+                px[i..i + 4].copy_from_slice(&fg);
+            }
+        }
+        px
+    }
+}
+
+fn accumulate(peaks: &mut [[f32; 2]], stereo: &[f32], first_frame: u64, total: u64) {
+    let n = peaks.len() as u64;
+    for (i, f) in stereo.chunks_exact(2).enumerate() {
+        let col = (((first_frame + i as u64) * n) / total.max(1)).min(n - 1) as usize;
+        let p = &mut peaks[col];
+        p[0] = p[0].min(f[0]).min(f[1]);
+        p[1] = p[1].max(f[0]).max(f[1]);
+    }
+}
+
+fn format_duration(sec: f64) -> String {
+    let total = if sec.is_finite() && sec > 0.0 {
+        sec.round() as u64
+    } else {
+        0
+    };
+    let (h, m, s) = (total / 3600, (total / 60) % 60, total % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
@@ -9,7 +9,6 @@ use newengine_plugin_api::{
     PluginModule, ServiceV1, ServiceV1Dyn, ServiceV1_TO,
 };
 
-use gilrs::{EventType, Gilrs};
 use parking_lot::Mutex;
 use serde::Deserialize;
 use serde_json::{json, Value};
@@ -43,12 +42,14 @@ struct MouseState {
 #[derive(Default)]
 struct TextState {
     text: String,
+    ime_enabled: bool,
     ime_preedit: String,
     ime_commit: String,
 }
 
 #[derive(Default)]
 struct GamepadState {
+    name: String,
     connected: bool,
     buttons: BTreeMap<String, f32>,
     axes: BTreeMap<String, f32>,
@@ -143,6 +144,27 @@ struct MouseButtonJson {
     state: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct GamepadConnectedJson {
+    id: u32,
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GamepadButtonJson {
+    id: u32,
+    button: String,
+    value: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct GamepadAxisJson {
+    id: u32,
+    axis: String,
+    value: f32,
+}
+
 /* =============================================================================================
    Event sink
    ============================================================================================= */
@@ -272,6 +294,19 @@ impl EventSinkV1 for InputEventSink {
                 }
             }
 
+            "winit.ime_enabled" => {
+                if let Some(enabled) = v.get("enabled").and_then(|x| x.as_bool()) {
+                    let mut g = state().lock();
+                    if g.text.ime_enabled != enabled {
+                        g.text.ime_enabled = enabled;
+                        if !enabled {
+                            g.text.ime_preedit.clear();
+                        }
+                        g.bump_epoch();
+                    }
+                }
+            }
+
             "winit.ime_commit" => {
                 if let Some(s) = v.get("text").and_then(|x| x.as_str()) {
                     let mut g = state().lock();
@@ -281,6 +316,52 @@ impl EventSinkV1 for InputEventSink {
                 }
             }
 
+            "winit.gamepad_connected" => {
+                let Ok(ev) = serde_json::from_value::<GamepadConnectedJson>(v) else { return; };
+
+                let mut g = state().lock();
+                let st = g.gamepads.entry(ev.id.to_string()).or_default();
+                st.connected = true;
+                st.name = ev.name;
+                g.bump_epoch();
+            }
+
+            "winit.gamepad_disconnected" => {
+                let Some(id) = v.get("id").and_then(|x| x.as_u64()) else { return; };
+
+                let mut g = state().lock();
+                if let Some(st) = g.gamepads.get_mut(&id.to_string()) {
+                    st.connected = false;
+                    st.buttons.clear();
+                    st.axes.clear();
+                }
+                g.bump_epoch();
+            }
+
+            "winit.gamepad_button" => {
+                let Ok(ev) = serde_json::from_value::<GamepadButtonJson>(v) else { return; };
+
+                let mut g = state().lock();
+                g.gamepads
+                    .entry(ev.id.to_string())
+                    .or_default()
+                    .buttons
+                    .insert(ev.button, ev.value);
+                g.bump_epoch();
+            }
+
+            "winit.gamepad_axis" => {
+                let Ok(ev) = serde_json::from_value::<GamepadAxisJson>(v) else { return; };
+
+                let mut g = state().lock();
+                g.gamepads
+                    .entry(ev.id.to_string())
+                    .or_default()
+                    .axes
+                    .insert(ev.axis, ev.value);
+                g.bump_epoch();
+            }
+
             _ => {}
         }
     }
@@ -319,6 +400,7 @@ impl InputService {
                 (
                     id.clone(),
                     json!({
+                        "name": st.name,
                         "connected": st.connected,
                         "buttons": st.buttons,
                         "axes": st.axes,
@@ -344,6 +426,7 @@ impl InputService {
             },
             "text": {
                 "buffer": g.text.text,
+                "ime_enabled": g.text.ime_enabled,
                 "ime_preedit": g.text.ime_preedit,
                 "ime_commit": g.text.ime_commit
             },
@@ -388,6 +471,7 @@ impl ServiceV1 for InputService {
         RString::from(
             r#"{
   "id":"kalitech.input.v1",
+  "permissions":[],
   "methods":{
     "state_json":{"in":"{}","out":"input state snapshot as JSON (edge-safe cached per epoch)"},
     "text_take_json":{"in":"{}","out":"{text:string} and clears internal text buffer"},
@@ -428,8 +512,13 @@ impl ServiceV1 for InputService {
     "winit.mouse_button":"{button:u32,state:'pressed'|'released'}",
     "winit.mouse_wheel":"{dx:f32,dy:f32}",
     "winit.text_char":"{cp:u32}",
+    "winit.ime_enabled":"{enabled:bool}",
     "winit.ime_preedit":"{text:string}",
-    "winit.ime_commit":"{text:string}"
+    "winit.ime_commit":"{text:string}",
+    "winit.gamepad_connected":"{id:u32,name:string,rumble:bool}",
+    "winit.gamepad_disconnected":"{id:u32}",
+    "winit.gamepad_button":"{id:u32,button:string,value:f32}",
+    "winit.gamepad_axis":"{id:u32,axis:string,value:f32}"
   }
 }"#,
         )
@@ -454,59 +543,9 @@ impl ServiceV1 for InputService {
    Plugin module
    ============================================================================================= */
 
-pub struct InputPlugin {
-    gilrs: Mutex<Option<Gilrs>>,
-}
-
-impl Default for InputPlugin {
-    fn default() -> Self {
-        let g = Gilrs::new().ok();
-        Self {
-            gilrs: Mutex::new(g),
-        }
-    }
-}
-
-impl InputPlugin {
-    fn poll_gilrs(&self) {
-        let mut lock = self.gilrs.lock();
-        let Some(gilrs) = lock.as_mut() else { return; };
-
-        while let Some(ev) = gilrs.next_event() {
-            let id = format!("{:?}", ev.id);
-
-            let mut g = state().lock();
-            let st = g.gamepads.entry(id).or_default();
-
-            match ev.event {
-                EventType::Connected => {
-                    st.connected = true;
-                }
-                EventType::Disconnected => {
-                    st.connected = false;
-                }
-
-                EventType::ButtonPressed(b, _) => {
-                    st.buttons.insert(format!("{:?}", b), 1.0);
-                }
-                EventType::ButtonReleased(b, _) => {
-                    st.buttons.insert(format!("{:?}", b), 0.0);
-                }
-                EventType::ButtonChanged(b, v, _) => {
-                    st.buttons.insert(format!("{:?}", b), v);
-                }
-
-                EventType::AxisChanged(a, v, _) => {
-                    st.axes.insert(format!("{:?}", a), v);
-                }
-
-                _ => {}
-            }
-
-            g.bump_epoch();
-        }
-    }
-}
+/// Gamepads are polled by the platform host (gilrs) and arrive as `winit.gamepad_*` events.
+#[derive(Default)]
+pub struct InputPlugin;
 
 impl PluginModule for InputPlugin {
     fn info(&self) -> PluginInfo {
@@ -534,7 +573,7 @@ impl PluginModule for InputPlugin {
             )));
         }
 
-        (host.log_info)(RString::from("input: initialized (events + gilrs)"));
+        (host.log_info)(RString::from("input: initialized (events)"));
         RResult::ROk(())
     }
 
@@ -547,7 +586,6 @@ impl PluginModule for InputPlugin {
     }
 
     fn update(&mut self, _dt: f32) -> RResult<(), RString> {
-        self.poll_gilrs();
         RResult::ROk(())
     }
 
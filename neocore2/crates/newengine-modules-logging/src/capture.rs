@@ -0,0 +1,172 @@
+//! In-memory capture of recent log records for in-app log views.
+//!
+//! [`LogCapture`] listens on the engine log tap, so it sees every record that passes the
+//! installed filters (whatever the output format) and keeps the newest `capacity` of them.
+//! Records carry a sequence number: a panel can keep the last one it showed and ask for
+//! [`LogCapture::since`] each frame instead of copying the whole buffer.
+
+use newengine_core::log_tap::{add_log_listener, remove_log_listener, LogLine};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex, Weak};
+
+/// A captured record.
+#[derive(Debug, Clone)]
+pub struct CapturedLog {
+    /// Increases by one per captured record, starting at 1.
+    pub seq: u64,
+    pub line: LogLine,
+}
+
+impl fmt::Display for CapturedLog {
+    /// `HH:MM:SS.mmm LEVEL target: message`, time in UTC.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = self.line.unix_ms % 86_400_000;
+        write!(
+            f,
+            "{:02}:{:02}:{:02}.{:03} {:<5} {}: {}",
+            ms / 3_600_000,
+            ms / 60_000 % 60,
+            ms / 1000 % 60,
+            ms % 1000,
+            self.line.level.as_str(),
+            self.line.target,
+            self.line.message
+        )
+    }
+}
+
+struct Ring {
+    records: VecDeque<CapturedLog>,
+    capacity: usize,
+    next_seq: u64,
+    evicted: u64,
+}
+
+struct Shared {
+    ring: Mutex<Ring>,
+    listener: u64,
+}
+
+impl Shared {
+    fn push(&self, line: &LogLine) {
+        let Ok(mut ring) = self.ring.lock() else {
+            return;
+        };
+        if ring.records.len() == ring.capacity {
+            ring.records.pop_front();
+            ring.evicted += 1;
+        }
+        let seq = ring.next_seq;
+        ring.next_seq += 1;
+        ring.records.push_back(CapturedLog {
+            seq,
+            line: line.clone(),
+        });
+    }
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        remove_log_listener(self.listener);
+    }
+}
+
+/// Ring buffer of the last N log records. Cheap to clone; clones share the buffer, and
+/// capturing stops when the last clone is dropped.
+///
+/// The logging module inserts one into the engine resources when
+/// [`ConsoleLoggerConfig::capture`](crate::ConsoleLoggerConfig::capture) is set.
+#[derive(Clone)]
+pub struct LogCapture {
+    shared: Arc<Shared>,
+}
+
+impl LogCapture {
+    /// Starts capturing. Records logged before this call are not available.
+    pub fn install(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let shared = Arc::new_cyclic(|weak: &Weak<Shared>| {
+            let weak = weak.clone();
+            let listener = add_log_listener(move |line| {
+                if let Some(shared) = weak.upgrade() {
+                    shared.push(line);
+                }
+            });
+            Shared {
+                ring: Mutex::new(Ring {
+                    records: VecDeque::with_capacity(capacity),
+                    capacity,
+                    next_seq: 1,
+                    evicted: 0,
+                }),
+                listener,
+            }
+        });
+        Self { shared }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.with_ring(|r| r.capacity).unwrap_or(0)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.with_ring(|r| r.records.len()).unwrap_or(0)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sequence number of the newest record ever captured (0 if none), including records
+    /// already evicted or drained.
+    #[inline]
+    pub fn last_seq(&self) -> u64 {
+        self.with_ring(|r| r.next_seq - 1).unwrap_or(0)
+    }
+
+    /// Records pushed out of the full buffer before anyone drained them.
+    #[inline]
+    pub fn evicted(&self) -> u64 {
+        self.with_ring(|r| r.evicted).unwrap_or(0)
+    }
+
+    /// Copy of the buffered records, oldest first.
+    pub fn snapshot(&self) -> Vec<CapturedLog> {
+        self.with_ring(|r| r.records.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Buffered records newer than `seq`, oldest first.
+    pub fn since(&self, seq: u64) -> Vec<CapturedLog> {
+        self.with_ring(|r| {
+            // Sequence numbers are contiguous within the buffer.
+            let skip = r
+                .records
+                .front()
+                .map_or(0, |c| (seq + 1).saturating_sub(c.seq) as usize);
+            r.records.iter().skip(skip).cloned().collect()
+        })
+        .unwrap_or_default()
+    }
+
+    /// Takes the buffered records, oldest first, leaving the buffer empty.
+    pub fn drain(&self) -> Vec<CapturedLog> {
+        self.with_ring(|r| r.records.drain(..).collect())
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn clear(&self) {
+        self.with_ring(|r| r.records.clear());
+    }
+
+    #[inline]
+    fn with_ring<R>(&self, f: impl FnOnce(&mut Ring) -> R) -> Option<R> {
+        self.shared.ring.lock().ok().map(|mut g| f(&mut g))
+    }
+}
@@ -0,0 +1,103 @@
+//! One-JSON-object-per-line log format for log shippers (ELK, Datadog, Loki).
+//!
+//! ```text
+//! {"timestamp":"2026-01-02T03:04:05.678Z","level":"INFO","target":"assets::cook","module":"assets","message":"cook.done files=3","fields":{"files":3}}
+//! ```
+//!
+//! `module` is the engine module or plugin whose callback emitted the record and is omitted
+//! outside callbacks. `fields` holds the record's structured key-values (`log::info!(files = 3;
+//! "...")`) and is omitted when there are none.
+
+use env_logger::fmt::{Formatter, TimestampPrecision};
+use env_logger::Builder;
+use log::kv::{self, Key, Value, VisitSource};
+use log::Record;
+use newengine_core::log_filter::with_log_owner;
+use serde_json::{Map, Value as Json};
+
+use std::io::{self, Write};
+
+/// Switches `builder` to the JSON line format. `timestamp` picks the precision of the RFC 3339
+/// `timestamp` field; `None` omits it.
+pub fn format_json(builder: &mut Builder, timestamp: Option<TimestampPrecision>) {
+    builder.write_style(env_logger::WriteStyle::Never);
+    builder.format(move |buf, record| write_json(buf, record, timestamp));
+}
+
+fn write_json(
+    buf: &mut Formatter,
+    record: &Record<'_>,
+    timestamp: Option<TimestampPrecision>,
+) -> io::Result<()> {
+    let ts = match timestamp {
+        Some(TimestampPrecision::Seconds) => Some(buf.timestamp_seconds()),
+        Some(TimestampPrecision::Millis) => Some(buf.timestamp_millis()),
+        Some(TimestampPrecision::Micros) => Some(buf.timestamp_micros()),
+        Some(TimestampPrecision::Nanos) => Some(buf.timestamp_nanos()),
+        None => None,
+    };
+
+    // Written by hand to keep a stable, readable key order.
+    buf.write_all(b"{")?;
+    if let Some(ts) = ts {
+        write_str_field(buf, "timestamp", &ts.to_string())?;
+        buf.write_all(b",")?;
+    }
+    write_str_field(buf, "level", record.level().as_str())?;
+    buf.write_all(b",")?;
+    write_str_field(buf, "target", record.target())?;
+    with_log_owner(|owner| match owner {
+        Some(id) => {
+            buf.write_all(b",")?;
+            write_str_field(buf, "module", id)
+        }
+        None => Ok(()),
+    })?;
+    buf.write_all(b",")?;
+    write_str_field(buf, "message", &record.args().to_string())?;
+
+    let mut fields = FieldCollector(Map::new());
+    // Visiting only fails if the collector does, and it never does.
+    let _ = record.key_values().visit(&mut fields);
+    if !fields.0.is_empty() {
+        buf.write_all(b",\"fields\":")?;
+        serde_json::to_writer(&mut *buf, &fields.0)?;
+    }
+
+    buf.write_all(b"}\n")
+}
+
+fn write_str_field(buf: &mut Formatter, key: &str, value: &str) -> io::Result<()> {
+    serde_json::to_writer(&mut *buf, key)?;
+    buf.write_all(b":")?;
+    serde_json::to_writer(&mut *buf, value)?;
+    Ok(())
+}
+
+struct FieldCollector(Map<String, Json>);
+
+impl<'kvs> VisitSource<'kvs> for FieldCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.insert(key.as_str().to_owned(), field_value(&value));
+        Ok(())
+    }
+}
+
+/// Keeps numbers and booleans typed so shippers can aggregate on them.
+fn field_value(value: &Value<'_>) -> Json {
+    if let Some(b) = value.to_bool() {
+        return Json::Bool(b);
+    }
+    if let Some(i) = value.to_i64() {
+        return Json::from(i);
+    }
+    if let Some(u) = value.to_u64() {
+        return Json::from(u);
+    }
+    if let Some(f) = value.to_f64() {
+        if let Some(n) = serde_json::Number::from_f64(f) {
+            return Json::Number(n);
+        }
+    }
+    Json::String(value.to_string())
+}
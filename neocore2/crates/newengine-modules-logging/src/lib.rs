@@ -1,6 +1,14 @@
+mod capture;
+mod json;
+
+pub use capture::{CapturedLog, LogCapture};
+pub use json::format_json;
+
 use env_logger::fmt::{Target, TimestampPrecision, WriteStyle};
 use env_logger::Builder;
 use log::LevelFilter;
+use newengine_core::log_filter::{ModuleFilterLogger, ModuleLogLevels};
+use newengine_core::log_tap::TeeLogger;
 use newengine_core::{EngineResult, Module, ModuleCtx};
 
 use std::env;
@@ -22,6 +30,35 @@ impl LogOutput {
     }
 }
 
+/// Shape of each log line.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum LogFormat {
+    /// Human-readable `env_logger` lines.
+    #[default]
+    Text,
+    /// One JSON object per line (see [`format_json`]).
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" | "plain" | "pretty" => Some(LogFormat::Text),
+            "json" | "jsonl" | "ndjson" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// `NEWENGINE_LOG_FORMAT` (`text` or `json`); `Text` when unset or unknown.
+    pub fn from_env() -> Self {
+        env::var("NEWENGINE_LOG_FORMAT")
+            .ok()
+            .as_deref()
+            .and_then(Self::parse)
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ConsoleLoggerConfig {
     pub filter: Option<String>,
@@ -36,6 +73,14 @@ pub struct ConsoleLoggerConfig {
     pub indent: Option<usize>,
     /// Destination for log output. When `None` defaults to `stderr`.
     pub output: Option<LogOutput>,
+    /// With [`LogFormat::Json`] the style, colour and field toggles above are ignored; every
+    /// line carries the same fields.
+    pub format: LogFormat,
+    /// Per engine module id levels (`NEWENGINE_LOG_MODULES`), applied before the filter.
+    pub modules: ModuleLogLevels,
+    /// Records kept in a [`LogCapture`] resource for in-app log views
+    /// (`NEWENGINE_LOG_CAPTURE`); `None` or `0` disables it.
+    pub capture: Option<usize>,
 }
 
 impl ConsoleLoggerConfig {
@@ -137,6 +182,11 @@ impl ConsoleLoggerConfig {
             timestamp,
             indent,
             output,
+            format: LogFormat::from_env(),
+            modules: ModuleLogLevels::from_env(),
+            capture: env::var("NEWENGINE_LOG_CAPTURE")
+                .ok()
+                .and_then(|v| v.trim().parse::<usize>().ok()),
         }
     }
 }
@@ -156,56 +206,46 @@ impl ConsoleLoggerModule {
     }
 }
 
-impl Default for ConsoleLoggerConfig {
-    fn default() -> Self {
-        Self::from_env()
-    }
-}
-
-impl<E: Send + 'static> Module<E> for ConsoleLoggerModule {
-    fn id(&self) -> &'static str {
-        "console-logger"
-    }
-
-    fn init(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
-        if self.initialized {
-            return Ok(());
-        }
-
+impl ConsoleLoggerConfig {
+    /// Builder for this config. Unfiltered builders accept every record; they back
+    /// [`ModuleLogLevels`] rules that are more verbose than the target filter.
+    fn builder(&self, filtered: bool) -> Builder {
         let mut builder = Builder::new();
 
-        if let Some(ref filters) = self.config.filter {
+        if !filtered {
+            builder.filter_level(LevelFilter::Trace);
+        } else if let Some(ref filters) = self.filter {
             builder.parse_filters(filters);
         } else {
-            builder.filter_level(self.config.level);
+            builder.filter_level(self.level);
         }
 
-        if let Some(out) = self.config.output {
+        if let Some(out) = self.output {
             builder.target(out.to_env_target());
         }
 
-        if let Some(style) = self.config.write_style {
+        if let Some(style) = self.write_style {
             builder.write_style(style);
-        } else if !self.config.colors {
+        } else if !self.colors {
             builder.write_style(WriteStyle::Never);
         } else {
             builder.write_style(WriteStyle::Auto);
         }
 
         builder
-            .format_module_path(self.config.include_module_path)
-            .format_target(self.config.include_target);
+            .format_module_path(self.include_module_path)
+            .format_target(self.include_target);
 
-        if self.config.include_file && self.config.include_line_number {
+        if self.include_file && self.include_line_number {
             builder.format_source_path(true);
         } else {
-            builder.format_file(self.config.include_file);
-            builder.format_line_number(self.config.include_line_number);
+            builder.format_file(self.include_file);
+            builder.format_line_number(self.include_line_number);
         }
 
-        builder.format_indent(self.config.indent);
+        builder.format_indent(self.indent);
 
-        match self.config.timestamp {
+        match self.timestamp {
             Some(TimestampPrecision::Seconds) => builder.format_timestamp_secs(),
             Some(TimestampPrecision::Millis) => builder.format_timestamp_millis(),
             Some(TimestampPrecision::Micros) => builder.format_timestamp_micros(),
@@ -213,14 +253,72 @@ impl<E: Send + 'static> Module<E> for ConsoleLoggerModule {
             None => builder.format_timestamp(None::<TimestampPrecision>),
         };
 
-        match builder.try_init() {
-            Ok(()) => {}
-            Err(_e) => {
-                // Most likely "logger already initialized". Treat as non-fatal.
+        if self.format == LogFormat::Json {
+            format_json(&mut builder, self.timestamp);
+        }
+
+        builder
+    }
+}
+
+impl Default for ConsoleLoggerConfig {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+impl<E: Send + 'static> Module<E> for ConsoleLoggerModule {
+    fn id(&self) -> &'static str {
+        "console-logger"
+    }
+
+    fn init(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if self.initialized {
+            return Ok(());
+        }
+
+        if let Some(capacity) = self.config.capture.filter(|&n| n > 0) {
+            if ctx.resources().get::<LogCapture>().is_none() {
+                ctx.resources_mut().insert(LogCapture::install(capacity));
             }
         }
 
+        // Most likely "logger already initialized". Treat as non-fatal.
+        let _ = try_init_with(&self.config);
+
         self.initialized = true;
         Ok(())
     }
 }
+
+/// Installs the logger described by `config` as the global logger, with its per-module
+/// levels. Returns `false` if a logger is already installed.
+pub fn try_init_with(config: &ConsoleLoggerConfig) -> bool {
+    let inner = config.builder(true).build();
+    let verbose = (!config.modules.is_empty()).then(|| config.builder(false).build());
+    install(inner, verbose, config.modules.clone())
+}
+
+/// Installs the logger built by `builder` as the global logger. `NEWENGINE_LOG_MODULES`
+/// rules apply but cannot go below the builder's filter; prefer [`try_init_with`].
+/// Returns `false` if a logger is already installed.
+pub fn try_init(mut builder: Builder) -> bool {
+    install(builder.build(), None, ModuleLogLevels::from_env())
+}
+
+/// Wraps the logger in a [`ModuleFilterLogger`] and a [`TeeLogger`] so log listeners (remote
+/// console, ...) see the records that pass.
+fn install(
+    inner: env_logger::Logger,
+    verbose: Option<env_logger::Logger>,
+    levels: ModuleLogLevels,
+) -> bool {
+    let inner_max = inner.filter();
+    let logger = ModuleFilterLogger::new(inner, verbose, levels);
+    let max = logger.max_level(inner_max);
+    if log::set_boxed_logger(Box::new(TeeLogger::new(logger))).is_err() {
+        return false;
+    }
+    log::set_max_level(max);
+    true
+}
@@ -0,0 +1,489 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::desc::{BodyDesc, BodyKind, ColliderDesc, ColliderShape};
+
+use glam::{Quat, Vec3};
+use newengine_ecs::Entity;
+use parking_lot::Mutex;
+use rapier3d::na::{Quaternion, Translation3, UnitQuaternion};
+use rapier3d::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub use rapier3d::prelude::{ColliderHandle, RigidBodyHandle};
+
+/// Contact events buffered beyond this are dropped (nobody is draining them).
+const MAX_PENDING_CONTACTS: usize = 4096;
+
+/// Two colliders started or stopped touching. Only colliders created with
+/// `ColliderDesc::contact_events` (or as sensors) report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContactEvent {
+    pub collider1: ColliderHandle,
+    pub collider2: ColliderHandle,
+    pub body1: Option<RigidBodyHandle>,
+    pub body2: Option<RigidBodyHandle>,
+    pub entity1: Option<Entity>,
+    pub entity2: Option<Entity>,
+    /// `false` when the pair separated.
+    pub started: bool,
+    /// One of the colliders is a sensor.
+    pub sensor: bool,
+}
+
+/// Closest hit of a raycast.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub collider: ColliderHandle,
+    pub body: Option<RigidBodyHandle>,
+    pub entity: Option<Entity>,
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+/// Collects collision events during a step.
+#[derive(Default)]
+struct EventCollector {
+    events: std::sync::Mutex<Vec<CollisionEvent>>,
+}
+
+impl EventHandler for EventCollector {
+    fn handle_collision_event(
+        &self,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        event: CollisionEvent,
+        _contact_pair: Option<&ContactPair>,
+    ) {
+        if let Ok(mut g) = self.events.lock() {
+            g.push(event);
+        }
+    }
+
+    fn handle_contact_force_event(
+        &self,
+        _dt: Real,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _contact_pair: &ContactPair,
+        _total_force_magnitude: Real,
+    ) {
+    }
+}
+
+pub(crate) struct PhysicsState {
+    gravity: Vector<Real>,
+    params: IntegrationParameters,
+    pipeline: PhysicsPipeline,
+    islands: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    pub(crate) bodies: RigidBodySet,
+    colliders: ColliderSet,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd: CCDSolver,
+    query: QueryPipeline,
+    /// Body poses before the last step, for render interpolation.
+    previous: HashMap<RigidBodyHandle, Isometry<Real>>,
+    entities: HashMap<RigidBodyHandle, Entity>,
+    contacts: Vec<ContactEvent>,
+    steps: u64,
+}
+
+impl PhysicsState {
+    fn new(gravity: Vec3) -> Self {
+        Self {
+            gravity: to_vector(gravity),
+            params: IntegrationParameters::default(),
+            pipeline: PhysicsPipeline::new(),
+            islands: IslandManager::new(),
+            broad_phase: DefaultBroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd: CCDSolver::new(),
+            query: QueryPipeline::new(),
+            previous: HashMap::new(),
+            entities: HashMap::new(),
+            contacts: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    pub(crate) fn step(&mut self, dt: f32) {
+        self.params.dt = dt;
+        self.previous.clear();
+        self.previous
+            .extend(self.bodies.iter().map(|(h, b)| (h, *b.position())));
+
+        let collector = EventCollector::default();
+        self.pipeline.step(
+            &self.gravity,
+            &self.params,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd,
+            Some(&mut self.query),
+            &(),
+            &collector,
+        );
+        self.steps += 1;
+
+        let events = collector.events.into_inner().unwrap_or_default();
+        for event in events {
+            if self.contacts.len() >= MAX_PENDING_CONTACTS {
+                log::warn!(
+                    target: "physics",
+                    "contacts.dropped pending={} (drain_contacts not called)",
+                    self.contacts.len()
+                );
+                break;
+            }
+            let (c1, c2) = (event.collider1(), event.collider2());
+            let body1 = self.colliders.get(c1).and_then(|c| c.parent());
+            let body2 = self.colliders.get(c2).and_then(|c| c.parent());
+            self.contacts.push(ContactEvent {
+                collider1: c1,
+                collider2: c2,
+                body1,
+                body2,
+                entity1: body1.and_then(|b| self.entities.get(&b).copied()),
+                entity2: body2.and_then(|b| self.entities.get(&b).copied()),
+                started: event.started(),
+                sensor: event.sensor(),
+            });
+        }
+    }
+
+    /// Pose between the previous and the current step.
+    pub(crate) fn interpolated(&self, body: RigidBodyHandle, alpha: f32) -> Option<(Vec3, Quat)> {
+        let current = self.bodies.get(body)?.position();
+        let (t1, r1) = from_isometry(current);
+        let Some(prev) = self.previous.get(&body) else {
+            return Some((t1, r1));
+        };
+        let (t0, r0) = from_isometry(prev);
+        let alpha = alpha.clamp(0.0, 1.0);
+        Some((t0.lerp(t1, alpha), r0.slerp(r1, alpha)))
+    }
+}
+
+/// Shared handle to the physics world. Cheap to clone; clones use the same world.
+///
+/// Changes take effect in the simulation on the next fixed step; raycasts see the state
+/// after the last step.
+#[derive(Clone)]
+pub struct PhysicsApi {
+    state: Arc<Mutex<PhysicsState>>,
+}
+
+impl PhysicsApi {
+    pub(crate) fn new(gravity: Vec3) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(PhysicsState::new(gravity))),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn state(&self) -> parking_lot::MutexGuard<'_, PhysicsState> {
+        self.state.lock()
+    }
+
+    #[inline]
+    pub fn gravity(&self) -> Vec3 {
+        from_vector(&self.state.lock().gravity)
+    }
+
+    #[inline]
+    pub fn set_gravity(&self, gravity: Vec3) {
+        self.state.lock().gravity = to_vector(gravity);
+    }
+
+    /// Fixed steps simulated so far.
+    #[inline]
+    pub fn steps(&self) -> u64 {
+        self.state.lock().steps
+    }
+
+    #[inline]
+    pub fn body_count(&self) -> usize {
+        self.state.lock().bodies.len()
+    }
+
+    /* ============================
+    Bodies and colliders
+    ============================ */
+
+    pub fn add_body(&self, desc: &BodyDesc) -> RigidBodyHandle {
+        let builder = match desc.kind {
+            BodyKind::Dynamic => RigidBodyBuilder::dynamic(),
+            BodyKind::Fixed => RigidBodyBuilder::fixed(),
+            BodyKind::Kinematic => RigidBodyBuilder::kinematic_position_based(),
+        };
+        let mut builder = builder
+            .position(to_isometry(desc.position, desc.rotation))
+            .linvel(to_vector(desc.linear_velocity))
+            .gravity_scale(desc.gravity_scale)
+            .linear_damping(desc.linear_damping)
+            .angular_damping(desc.angular_damping)
+            .ccd_enabled(desc.ccd);
+        if desc.lock_rotations {
+            builder = builder.lock_rotations();
+        }
+        self.state.lock().bodies.insert(builder.build())
+    }
+
+    /// Same as [`add_body`](Self::add_body), linked to `entity`: the body follows or drives
+    /// the entity's `Transform` and contact events and raycast hits name the entity. The
+    /// entity still needs a [`PhysicsBody`](crate::PhysicsBody) component for the
+    /// `Transform` sync.
+    pub fn add_entity_body(&self, entity: Entity, desc: &BodyDesc) -> RigidBodyHandle {
+        let body = self.add_body(desc);
+        self.state.lock().entities.insert(body, entity);
+        body
+    }
+
+    /// Attaches a collider; `None` if the body does not exist.
+    pub fn add_collider(
+        &self,
+        body: RigidBodyHandle,
+        desc: &ColliderDesc,
+    ) -> Option<ColliderHandle> {
+        let builder = match desc.shape {
+            ColliderShape::Ball { radius } => ColliderBuilder::ball(radius),
+            ColliderShape::Cuboid { half_extents: h } => ColliderBuilder::cuboid(h.x, h.y, h.z),
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => ColliderBuilder::capsule_y(half_height, radius),
+            ColliderShape::Cylinder {
+                half_height,
+                radius,
+            } => ColliderBuilder::cylinder(half_height, radius),
+        };
+        let mut builder = builder
+            .translation(to_vector(desc.offset))
+            .friction(desc.friction)
+            .restitution(desc.restitution)
+            .density(desc.density)
+            .sensor(desc.sensor);
+        if desc.contact_events {
+            builder = builder.active_events(ActiveEvents::COLLISION_EVENTS);
+        }
+
+        let mut g = self.state.lock();
+        let s = &mut *g;
+        if !s.bodies.contains(body) {
+            return None;
+        }
+        Some(
+            s.colliders
+                .insert_with_parent(builder.build(), body, &mut s.bodies),
+        )
+    }
+
+    /// Removes the body with its colliders.
+    pub fn remove_body(&self, body: RigidBodyHandle) -> bool {
+        let mut g = self.state.lock();
+        let s = &mut *g;
+        s.previous.remove(&body);
+        s.entities.remove(&body);
+        s.bodies
+            .remove(
+                body,
+                &mut s.islands,
+                &mut s.colliders,
+                &mut s.impulse_joints,
+                &mut s.multibody_joints,
+                true,
+            )
+            .is_some()
+    }
+
+    pub fn remove_collider(&self, collider: ColliderHandle) -> bool {
+        let mut g = self.state.lock();
+        let s = &mut *g;
+        s.colliders
+            .remove(collider, &mut s.islands, &mut s.bodies, true)
+            .is_some()
+    }
+
+    #[inline]
+    pub fn entity_of(&self, body: RigidBodyHandle) -> Option<Entity> {
+        self.state.lock().entities.get(&body).copied()
+    }
+
+    /* ============================
+    Body state
+    ============================ */
+
+    /// Pose after the last step.
+    pub fn position(&self, body: RigidBodyHandle) -> Option<(Vec3, Quat)> {
+        self.state
+            .lock()
+            .bodies
+            .get(body)
+            .map(|b| from_isometry(b.position()))
+    }
+
+    /// Pose `alpha` of the way from the previous to the last step; pass
+    /// [`Frame::fixed_alpha`](newengine_core::Frame::fixed_alpha) when rendering.
+    #[inline]
+    pub fn interpolated(&self, body: RigidBodyHandle, alpha: f32) -> Option<(Vec3, Quat)> {
+        self.state.lock().interpolated(body, alpha)
+    }
+
+    /// Teleports the body; the next render does not interpolate from the old pose.
+    pub fn set_position(&self, body: RigidBodyHandle, position: Vec3, rotation: Quat) -> bool {
+        let mut s = self.state.lock();
+        let Some(b) = s.bodies.get_mut(body) else {
+            return false;
+        };
+        let iso = to_isometry(position, rotation);
+        b.set_position(iso, true);
+        s.previous.insert(body, iso);
+        true
+    }
+
+    pub fn linear_velocity(&self, body: RigidBodyHandle) -> Option<Vec3> {
+        self.state
+            .lock()
+            .bodies
+            .get(body)
+            .map(|b| from_vector(b.linvel()))
+    }
+
+    pub fn set_linear_velocity(&self, body: RigidBodyHandle, velocity: Vec3) -> bool {
+        self.with_body(body, |b| b.set_linvel(to_vector(velocity), true))
+    }
+
+    pub fn apply_impulse(&self, body: RigidBodyHandle, impulse: Vec3) -> bool {
+        self.with_body(body, |b| b.apply_impulse(to_vector(impulse), true))
+    }
+
+    /// Force applied on every step until [`reset_forces`](Self::reset_forces).
+    pub fn add_force(&self, body: RigidBodyHandle, force: Vec3) -> bool {
+        self.with_body(body, |b| b.add_force(to_vector(force), true))
+    }
+
+    pub fn reset_forces(&self, body: RigidBodyHandle) -> bool {
+        self.with_body(body, |b| b.reset_forces(true))
+    }
+
+    fn with_body(&self, body: RigidBodyHandle, f: impl FnOnce(&mut RigidBody)) -> bool {
+        match self.state.lock().bodies.get_mut(body) {
+            Some(b) => {
+                f(b);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /* ============================
+    Queries and events
+    ============================ */
+
+    /// Closest hit along `dir` within `max_distance`.
+    #[inline]
+    pub fn raycast(&self, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<RayHit> {
+        self.raycast_excluding(origin, dir, max_distance, None)
+    }
+
+    /// Like [`raycast`](Self::raycast), ignoring the colliders of `exclude` (e.g. the caster).
+    pub fn raycast_excluding(
+        &self,
+        origin: Vec3,
+        dir: Vec3,
+        max_distance: f32,
+        exclude: Option<RigidBodyHandle>,
+    ) -> Option<RayHit> {
+        let dir = dir.normalize_or_zero();
+        if dir == Vec3::ZERO || max_distance.is_nan() || max_distance <= 0.0 {
+            return None;
+        }
+
+        let s = self.state.lock();
+        let ray = Ray::new(to_vector(origin).into(), to_vector(dir));
+        let mut filter = QueryFilter::default();
+        if let Some(body) = exclude {
+            filter = filter.exclude_rigid_body(body);
+        }
+        let (collider, hit) = s.query.cast_ray_and_get_normal(
+            &s.bodies,
+            &s.colliders,
+            &ray,
+            max_distance,
+            true,
+            filter,
+        )?;
+
+        let body = s.colliders.get(collider).and_then(|c| c.parent());
+        Some(RayHit {
+            collider,
+            body,
+            entity: body.and_then(|b| s.entities.get(&b).copied()),
+            point: origin + dir * hit.time_of_impact,
+            normal: from_vector(&hit.normal),
+            distance: hit.time_of_impact,
+        })
+    }
+
+    /// Contact events since the last call, oldest first.
+    #[inline]
+    pub fn drain_contacts(&self) -> Vec<ContactEvent> {
+        std::mem::take(&mut self.state.lock().contacts)
+    }
+}
+
+impl std::fmt::Debug for PhysicsApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = self.state.lock();
+        f.debug_struct("PhysicsApi")
+            .field("bodies", &s.bodies.len())
+            .field("colliders", &s.colliders.len())
+            .field("steps", &s.steps)
+            .finish()
+    }
+}
+
+/* ============================
+glam <-> nalgebra
+============================ */
+
+#[inline]
+fn to_vector(v: Vec3) -> Vector<Real> {
+    vector![v.x, v.y, v.z]
+}
+
+#[inline]
+fn from_vector(v: &Vector<Real>) -> Vec3 {
+    Vec3::new(v.x, v.y, v.z)
+}
+
+#[inline]
+pub(crate) fn to_isometry(position: Vec3, rotation: Quat) -> Isometry<Real> {
+    let q = Quaternion::new(rotation.w, rotation.x, rotation.y, rotation.z);
+    Isometry::from_parts(
+        Translation3::new(position.x, position.y, position.z),
+        UnitQuaternion::from_quaternion(q),
+    )
+}
+
+#[inline]
+pub(crate) fn from_isometry(iso: &Isometry<Real>) -> (Vec3, Quat) {
+    let c = iso.rotation.coords;
+    (
+        from_vector(&iso.translation.vector),
+        Quat::from_xyzw(c.x, c.y, c.z, c.w),
+    )
+}
@@ -0,0 +1,228 @@
+use glam::{Quat, Vec3};
+
+/// How a body moves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BodyKind {
+    /// Moved by the simulation; its pose is written back to the ECS.
+    #[default]
+    Dynamic,
+    /// Never moves.
+    Fixed,
+    /// Moved by the game: follows its entity's `Transform` each fixed step and pushes
+    /// dynamic bodies out of the way.
+    Kinematic,
+}
+
+/// Rigid body creation parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyDesc {
+    pub kind: BodyKind,
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub linear_velocity: Vec3,
+    pub gravity_scale: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    /// Continuous collision detection, for small fast bodies that would tunnel.
+    pub ccd: bool,
+    /// Keeps the body upright (character controllers).
+    pub lock_rotations: bool,
+}
+
+impl Default for BodyDesc {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            kind: BodyKind::Dynamic,
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            linear_velocity: Vec3::ZERO,
+            gravity_scale: 1.0,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            ccd: false,
+            lock_rotations: false,
+        }
+    }
+}
+
+impl BodyDesc {
+    #[inline]
+    pub fn dynamic() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn fixed() -> Self {
+        Self {
+            kind: BodyKind::Fixed,
+            ..Self::default()
+        }
+    }
+
+    #[inline]
+    pub fn kinematic() -> Self {
+        Self {
+            kind: BodyKind::Kinematic,
+            ..Self::default()
+        }
+    }
+
+    #[inline]
+    pub fn with_position(mut self, position: Vec3) -> Self {
+        self.position = position;
+        self
+    }
+
+    #[inline]
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    #[inline]
+    pub fn with_linear_velocity(mut self, velocity: Vec3) -> Self {
+        self.linear_velocity = velocity;
+        self
+    }
+
+    #[inline]
+    pub fn with_gravity_scale(mut self, scale: f32) -> Self {
+        self.gravity_scale = scale;
+        self
+    }
+
+    #[inline]
+    pub fn with_damping(mut self, linear: f32, angular: f32) -> Self {
+        self.linear_damping = linear.max(0.0);
+        self.angular_damping = angular.max(0.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_ccd(mut self, ccd: bool) -> Self {
+        self.ccd = ccd;
+        self
+    }
+
+    #[inline]
+    pub fn with_locked_rotations(mut self, locked: bool) -> Self {
+        self.lock_rotations = locked;
+        self
+    }
+}
+
+/// Collider geometry, in the body's local space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColliderShape {
+    Ball {
+        radius: f32,
+    },
+    Cuboid {
+        half_extents: Vec3,
+    },
+    /// Along the local Y axis.
+    Capsule {
+        half_height: f32,
+        radius: f32,
+    },
+    /// Along the local Y axis.
+    Cylinder {
+        half_height: f32,
+        radius: f32,
+    },
+}
+
+/// Collider creation parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColliderDesc {
+    pub shape: ColliderShape,
+    /// Offset from the body origin.
+    pub offset: Vec3,
+    pub friction: f32,
+    pub restitution: f32,
+    pub density: f32,
+    /// Reports overlaps as contact events but generates no contact forces.
+    pub sensor: bool,
+    /// Emit [`ContactEvent`](crate::ContactEvent)s for this collider.
+    pub contact_events: bool,
+}
+
+impl ColliderDesc {
+    #[inline]
+    pub fn new(shape: ColliderShape) -> Self {
+        Self {
+            shape,
+            offset: Vec3::ZERO,
+            friction: 0.5,
+            restitution: 0.0,
+            density: 1.0,
+            sensor: false,
+            contact_events: false,
+        }
+    }
+
+    #[inline]
+    pub fn ball(radius: f32) -> Self {
+        Self::new(ColliderShape::Ball { radius })
+    }
+
+    #[inline]
+    pub fn cuboid(half_extents: Vec3) -> Self {
+        Self::new(ColliderShape::Cuboid { half_extents })
+    }
+
+    #[inline]
+    pub fn capsule(half_height: f32, radius: f32) -> Self {
+        Self::new(ColliderShape::Capsule {
+            half_height,
+            radius,
+        })
+    }
+
+    #[inline]
+    pub fn cylinder(half_height: f32, radius: f32) -> Self {
+        Self::new(ColliderShape::Cylinder {
+            half_height,
+            radius,
+        })
+    }
+
+    #[inline]
+    pub fn with_offset(mut self, offset: Vec3) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    #[inline]
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction.max(0.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution.clamp(0.0, 1.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density.max(0.0);
+        self
+    }
+
+    /// A sensor always reports contact events.
+    #[inline]
+    pub fn sensor(mut self) -> Self {
+        self.sensor = true;
+        self.contact_events = true;
+        self
+    }
+
+    #[inline]
+    pub fn with_contact_events(mut self, enabled: bool) -> Self {
+        self.contact_events = enabled;
+        self
+    }
+}
@@ -0,0 +1,13 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+pub mod api;
+pub mod desc;
+pub mod module;
+pub mod sync;
+
+pub use api::{ColliderHandle, ContactEvent, PhysicsApi, RayHit, RigidBodyHandle};
+pub use desc::*;
+pub use module::*;
+pub use sync::PhysicsBody;
+
+pub use rapier3d;
@@ -0,0 +1,128 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::api::PhysicsApi;
+use crate::sync::{pull_dynamic, push_kinematic};
+
+use glam::Vec3;
+use newengine_core::{EngineResult, Module, ModuleCtx, ModuleStage, OrderHint};
+use newengine_ecs::{World, ECS_MODULE_ID};
+
+pub const PHYSICS_MODULE_ID: &str = "physics.rapier";
+
+/// Physics module configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsConfig {
+    pub gravity: Vec3,
+    /// Sync `PhysicsBody` entities with the `World` resource, if there is one.
+    pub sync_ecs: bool,
+}
+
+impl Default for PhysicsConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            sync_ecs: true,
+        }
+    }
+}
+
+impl PhysicsConfig {
+    #[inline]
+    pub fn with_gravity(mut self, gravity: Vec3) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    #[inline]
+    pub fn with_ecs_sync(mut self, enabled: bool) -> Self {
+        self.sync_ecs = enabled;
+        self
+    }
+}
+
+/// Registers the `PhysicsApi` resource and steps the simulation once per fixed update.
+///
+/// Kinematic bodies pick up their entities' `Transform`s before each step; in `render`
+/// dynamic bodies write their pose, interpolated by `Frame::fixed_alpha`, back before the
+/// ECS propagates transforms. Register it after gameplay modules that push bodies in
+/// `fixed_update`.
+#[derive(Debug, Default)]
+pub struct PhysicsModule {
+    config: PhysicsConfig,
+    api: Option<PhysicsApi>,
+}
+
+impl PhysicsModule {
+    #[inline]
+    pub fn new(config: PhysicsConfig) -> Self {
+        Self { config, api: None }
+    }
+}
+
+impl<E: Send + 'static> Module<E> for PhysicsModule {
+    fn id(&self) -> &'static str {
+        PHYSICS_MODULE_ID
+    }
+
+    fn order_hints(&self) -> &'static [OrderHint] {
+        &[OrderHint::BeforeIn(ModuleStage::Render, ECS_MODULE_ID)]
+    }
+
+    fn init(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let api = PhysicsApi::new(self.config.gravity);
+        ctx.resources_mut().insert(api.clone());
+        log::info!(
+            target: "physics",
+            "physics.init gravity={:?} sync_ecs={}",
+            self.config.gravity,
+            self.config.sync_ecs
+        );
+        self.api = Some(api);
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let (Some(api), Some(frame)) = (self.api.as_ref(), ctx.frame()) else {
+            return Ok(());
+        };
+        let dt = frame.fixed_dt;
+
+        let mut state = api.state();
+        if self.config.sync_ecs {
+            if let Some(world) = ctx.resources().get::<World>() {
+                push_kinematic(world, &mut state);
+            }
+        }
+        state.step(dt);
+        Ok(())
+    }
+
+    fn render(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if !self.config.sync_ecs {
+            return Ok(());
+        }
+        let (Some(api), Some(frame)) = (self.api.as_ref(), ctx.frame()) else {
+            return Ok(());
+        };
+        let alpha = frame.fixed_alpha;
+
+        if let Some(world) = ctx.resources_mut().get_mut::<World>() {
+            pull_dynamic(world, &api.state(), alpha);
+        }
+        Ok(())
+    }
+
+    fn shutdown(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if let Some(api) = self.api.take() {
+            log::info!(
+                target: "physics",
+                "physics.shutdown bodies={} steps={}",
+                api.body_count(),
+                api.steps()
+            );
+        }
+        let _ = ctx.resources_mut().remove::<PhysicsApi>();
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::api::{to_isometry, PhysicsState, RigidBodyHandle};
+
+use newengine_ecs::{Entity, Transform, World};
+
+/// Links an entity to a rigid body (see `PhysicsApi::add_entity_body`).
+///
+/// Kinematic bodies follow the entity's `Transform`; dynamic bodies write their interpolated
+/// pose into it before transforms are propagated. The `Transform` is treated as world space,
+/// so physics entities should not have a `Parent`. Scale is left alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhysicsBody(pub RigidBodyHandle);
+
+/// Moves kinematic bodies to their entities' `Transform`s for the coming step.
+pub(crate) fn push_kinematic(world: &World, state: &mut PhysicsState) {
+    for (body, t) in world.query_ref::<(&PhysicsBody, &Transform), ()>() {
+        let Some(b) = state.bodies.get_mut(body.0) else {
+            continue;
+        };
+        if b.is_kinematic() {
+            b.set_next_kinematic_position(to_isometry(t.translation, t.rotation));
+        }
+    }
+}
+
+/// Writes interpolated poses of dynamic bodies into `Transform`s. Only poses that moved are
+/// written, so resting bodies do not show up in `Changed<Transform>`.
+pub(crate) fn pull_dynamic(world: &mut World, state: &PhysicsState, alpha: f32) -> usize {
+    let moved: Vec<(Entity, glam::Vec3, glam::Quat)> = world
+        .query_ref::<(Entity, &PhysicsBody, &Transform), ()>()
+        .filter(|(_, body, _)| state.bodies.get(body.0).is_some_and(|b| b.is_dynamic()))
+        .filter_map(|(e, body, t)| {
+            let (translation, rotation) = state.interpolated(body.0, alpha)?;
+            (translation != t.translation || rotation != t.rotation).then_some((
+                e,
+                translation,
+                rotation,
+            ))
+        })
+        .collect();
+
+    for &(e, translation, rotation) in moved.iter() {
+        if let Some(t) = world.get_mut::<Transform>(e) {
+            t.translation = translation;
+            t.rotation = rotation;
+        }
+    }
+    moved.len()
+}
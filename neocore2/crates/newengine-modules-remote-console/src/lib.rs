@@ -0,0 +1,198 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Remote console: inspect and drive headless servers and on-device builds over the network.
+//!
+//! [`RemoteConsoleModule`] listens on a local port for plain TCP (`nc`, scripts) and
+//! WebSocket (browser tools) clients on the same port. A session authenticates once with a
+//! token from the engine's [`RemoteConsolePolicy`]; every other line is forwarded to the
+//! console service's `remote_exec`, so permission levels, the command whitelist and the
+//! audit log apply exactly as for other remote callers. Log records are streamed back.
+//!
+//! Protocol: the client sends text lines (one per WebSocket text message); the server sends
+//! one JSON object per line (or message) with a `type` of `hello`, `auth`, `reply`, `logs`,
+//! `log`, `dropped` or `error`. Lines starting with `.` are handled by the session itself:
+//!
+//! ```text
+//! .auth <token>        authenticate (required before anything else)
+//! .logs <level|off>    set the streamed log level (default from the config)
+//! .quit                close the session
+//! ```
+//!
+//! Commands run on the engine thread during [`Module::update`], never on network threads.
+
+mod server;
+
+use newengine_core::console::{set_remote_console_policy, PermissionLevel, RemoteConsolePolicy};
+use newengine_core::{EngineError, EngineResult, Module, ModuleCtx};
+
+use crossbeam_channel::{unbounded, Receiver};
+use log::LevelFilter;
+use std::env;
+
+use server::{Request, Server};
+
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:7370";
+
+/// Requests run per frame; the rest wait for the next frame.
+const MAX_REQUESTS_PER_FRAME: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct RemoteConsoleConfig {
+    /// Bind address. Keep it on loopback unless the network is trusted; traffic is not
+    /// encrypted.
+    pub listen: String,
+    /// Installed on init when set; otherwise the policy the host configured applies.
+    pub policy: Option<RemoteConsolePolicy>,
+    pub max_clients: usize,
+    /// Level streamed to new sessions until they pick their own with `.logs`.
+    pub log_level: LevelFilter,
+}
+
+impl Default for RemoteConsoleConfig {
+    fn default() -> Self {
+        Self {
+            listen: DEFAULT_LISTEN.to_owned(),
+            policy: None,
+            max_clients: 8,
+            log_level: LevelFilter::Info,
+        }
+    }
+}
+
+impl RemoteConsoleConfig {
+    #[inline]
+    pub fn new(listen: impl Into<String>) -> Self {
+        Self {
+            listen: listen.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Reads `NEWENGINE_REMOTE_CONSOLE` (listen address), `NEWENGINE_REMOTE_CONSOLE_TOKEN`
+    /// and `NEWENGINE_REMOTE_CONSOLE_LEVEL` (`observer`, `operator` or `admin`; default
+    /// `operator`). Returns `None` without a token: there is nothing to authenticate against.
+    pub fn from_env() -> Option<Self> {
+        let token = env::var("NEWENGINE_REMOTE_CONSOLE_TOKEN")
+            .ok()
+            .filter(|t| !t.trim().is_empty())?;
+        let level = env::var("NEWENGINE_REMOTE_CONSOLE_LEVEL")
+            .ok()
+            .and_then(|v| PermissionLevel::parse(&v))
+            .unwrap_or(PermissionLevel::Operator);
+        let listen = env::var("NEWENGINE_REMOTE_CONSOLE")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_LISTEN.to_owned());
+
+        Some(Self::new(listen).with_token(token.trim(), "env", level))
+    }
+
+    /// Adds a client token to the policy this module installs.
+    pub fn with_token(
+        mut self,
+        token: impl Into<String>,
+        name: impl Into<String>,
+        level: PermissionLevel,
+    ) -> Self {
+        let policy = self.policy.take().unwrap_or_default();
+        self.policy = Some(policy.with_token(token, name, level));
+        self
+    }
+
+    #[inline]
+    pub fn with_policy(mut self, policy: RemoteConsolePolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    #[inline]
+    pub fn with_max_clients(mut self, max_clients: usize) -> Self {
+        self.max_clients = max_clients.max(1);
+        self
+    }
+
+    #[inline]
+    pub fn with_log_level(mut self, level: LevelFilter) -> Self {
+        self.log_level = level;
+        self
+    }
+}
+
+pub struct RemoteConsoleModule {
+    config: RemoteConsoleConfig,
+    server: Option<Server>,
+    requests: Option<Receiver<Request>>,
+}
+
+impl RemoteConsoleModule {
+    #[inline]
+    pub fn new(config: RemoteConsoleConfig) -> Self {
+        Self {
+            config,
+            server: None,
+            requests: None,
+        }
+    }
+
+    /// Address actually bound (useful with port 0); `None` before init.
+    #[inline]
+    pub fn local_addr(&self) -> Option<std::net::SocketAddr> {
+        self.server.as_ref().map(Server::local_addr)
+    }
+}
+
+impl<E: Send + 'static> Module<E> for RemoteConsoleModule {
+    fn id(&self) -> &'static str {
+        "remote-console"
+    }
+
+    fn is_critical(&self) -> bool {
+        false
+    }
+
+    fn init(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if self.server.is_some() {
+            return Ok(());
+        }
+        if let Some(policy) = self.config.policy.clone() {
+            set_remote_console_policy(policy);
+        }
+
+        let (tx, rx) = unbounded::<Request>();
+        let server = Server::spawn(&self.config, tx).map_err(|e| {
+            EngineError::other(format!(
+                "remote console: bind '{}': {e}",
+                self.config.listen
+            ))
+        })?;
+        log::info!(
+            target: "console.remote",
+            "remote_console.listen addr={} max_clients={}",
+            server.local_addr(),
+            self.config.max_clients
+        );
+
+        self.server = Some(server);
+        self.requests = Some(rx);
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let Some(rx) = self.requests.as_ref() else {
+            return Ok(());
+        };
+        for req in rx.try_iter().take(MAX_REQUESTS_PER_FRAME) {
+            req.run();
+        }
+        Ok(())
+    }
+
+    fn shutdown(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if let Some(mut server) = self.server.take() {
+            server.shutdown();
+        }
+        // Sessions still waiting for a reply see the channel close.
+        self.requests = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,527 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::RemoteConsoleConfig;
+
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender, TrySendError};
+use log::LevelFilter;
+use newengine_core::console::{method, COMMAND_SERVICE_ID};
+use newengine_core::log_tap::{add_log_listener, remove_log_listener, LogLine};
+use serde_json::{json, Value};
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+const PROTOCOL_VERSION: u32 = 1;
+/// How long a session blocks on its socket before flushing queued log lines.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// WebSocket clients send their upgrade request right away; silent clients are plain TCP.
+const SNIFF_TIMEOUT: Duration = Duration::from_millis(300);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Commands wait this long for the engine thread (a paused engine still runs `update`).
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_LINE: usize = 4096;
+/// Log lines queued per session; a slow client loses lines instead of stalling logging.
+const LOG_QUEUE: usize = 1024;
+const MAX_AUTH_FAILURES: u32 = 3;
+const AUTH_FAILURE_DELAY: Duration = Duration::from_millis(500);
+
+/// A console service call made on the engine thread.
+pub(crate) struct Request {
+    method: &'static str,
+    payload: Vec<u8>,
+    reply: Sender<Result<Vec<u8>, String>>,
+}
+
+impl Request {
+    pub(crate) fn run(self) {
+        let out = newengine_core::call_service_v1(COMMAND_SERVICE_ID, self.method, &self.payload);
+        let _ = self.reply.send(out);
+    }
+}
+
+/// Log subscription of one session.
+struct Tap {
+    session: u64,
+    /// `LevelFilter as usize`; `Off` until the session authenticates.
+    level: Arc<AtomicUsize>,
+    tx: Sender<String>,
+    dropped: Arc<AtomicU64>,
+}
+
+struct Shared {
+    stop: AtomicBool,
+    active: AtomicUsize,
+    next_session: AtomicU64,
+    max_clients: usize,
+    log_level: LevelFilter,
+    requests: Sender<Request>,
+    taps: Mutex<Vec<Tap>>,
+}
+
+impl Shared {
+    fn on_log(&self, line: &LogLine) {
+        let Ok(taps) = self.taps.lock() else {
+            return;
+        };
+        let mut json: Option<String> = None;
+        for t in taps.iter() {
+            if line.level as usize > t.level.load(Ordering::Relaxed) {
+                continue;
+            }
+            let msg = json
+                .get_or_insert_with(|| {
+                    json!({
+                        "type": "log",
+                        "level": line.level.as_str(),
+                        "target": line.target,
+                        "module": line.module,
+                        "msg": line.message,
+                        "unix_ms": line.unix_ms,
+                    })
+                    .to_string()
+                })
+                .clone();
+            if let Err(TrySendError::Full(_)) = t.tx.try_send(msg) {
+                t.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+pub(crate) struct Server {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    listener_id: u64,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    pub(crate) fn spawn(
+        config: &RemoteConsoleConfig,
+        requests: Sender<Request>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(config.listen.as_str())?;
+        let addr = listener.local_addr()?;
+
+        let shared = Arc::new(Shared {
+            stop: AtomicBool::new(false),
+            active: AtomicUsize::new(0),
+            next_session: AtomicU64::new(1),
+            max_clients: config.max_clients,
+            log_level: config.log_level,
+            requests,
+            taps: Mutex::new(Vec::new()),
+        });
+
+        let tap = shared.clone();
+        let listener_id = add_log_listener(move |line| tap.on_log(line));
+
+        let accept = shared.clone();
+        let thread = std::thread::Builder::new()
+            .name("remote-console".into())
+            .spawn(move || accept_loop(listener, accept));
+        let thread = match thread {
+            Ok(t) => t,
+            Err(e) => {
+                remove_log_listener(listener_id);
+                return Err(e);
+            }
+        };
+
+        Ok(Self {
+            addr,
+            shared,
+            listener_id,
+            thread: Some(thread),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stops accepting; sessions notice within one poll interval and close.
+    pub(crate) fn shutdown(&mut self) {
+        if self.shared.stop.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        remove_log_listener(self.listener_id);
+        // Wake the blocking accept.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+        log::info!(target: "console.remote", "remote_console.stopped addr={}", self.addr);
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn accept_loop(listener: TcpListener, shared: Arc<Shared>) {
+    for conn in listener.incoming() {
+        if shared.stop.load(Ordering::SeqCst) {
+            break;
+        }
+        let mut stream = match conn {
+            Ok(s) => s,
+            Err(e) => {
+                log::warn!(target: "console.remote", "remote_console.accept failed err='{e}'");
+                continue;
+            }
+        };
+
+        if shared.active.load(Ordering::SeqCst) >= shared.max_clients {
+            let msg = json!({ "type": "error", "error": "too many remote console clients" });
+            let _ = writeln!(stream, "{msg}");
+            let _ = stream.shutdown(Shutdown::Both);
+            continue;
+        }
+
+        shared.active.fetch_add(1, Ordering::SeqCst);
+        let session = shared.clone();
+        let spawned = std::thread::Builder::new()
+            .name("remote-console-session".into())
+            .spawn(move || {
+                run_session(stream, &session);
+                session.active.fetch_sub(1, Ordering::SeqCst);
+            });
+        if let Err(e) = spawned {
+            shared.active.fetch_sub(1, Ordering::SeqCst);
+            log::warn!(target: "console.remote", "remote_console.spawn failed err='{e}'");
+        }
+    }
+}
+
+fn run_session(stream: TcpStream, shared: &Arc<Shared>) {
+    let peer = stream
+        .peer_addr()
+        .map_or_else(|_| "?".to_owned(), |a| a.to_string());
+    let transport = match Transport::open(stream) {
+        Ok(t) => t,
+        Err(e) => {
+            log::debug!(target: "console.remote", "remote_console.open failed peer={peer} err='{e}'");
+            return;
+        }
+    };
+
+    let id = shared.next_session.fetch_add(1, Ordering::Relaxed);
+    let (tx, rx) = bounded::<String>(LOG_QUEUE);
+    let level = Arc::new(AtomicUsize::new(LevelFilter::Off as usize));
+    let dropped = Arc::new(AtomicU64::new(0));
+    if let Ok(mut taps) = shared.taps.lock() {
+        taps.push(Tap {
+            session: id,
+            level: level.clone(),
+            tx,
+            dropped: dropped.clone(),
+        });
+    }
+
+    log::debug!(
+        target: "console.remote",
+        "remote_console.session open id={id} peer={peer} websocket={}",
+        transport.is_websocket()
+    );
+
+    let mut session = Session {
+        shared,
+        transport,
+        token: None,
+        failures: 0,
+        level,
+        dropped,
+        logs: rx,
+    };
+    let res = session.run();
+    session.transport.close();
+
+    if let Ok(mut taps) = shared.taps.lock() {
+        taps.retain(|t| t.session != id);
+    }
+    match res {
+        Ok(()) => log::debug!(target: "console.remote", "remote_console.session closed id={id}"),
+        Err(e) => log::debug!(
+            target: "console.remote",
+            "remote_console.session closed id={id} err='{e}'"
+        ),
+    }
+}
+
+struct Session<'a> {
+    shared: &'a Shared,
+    transport: Transport,
+    /// Set once `.auth` succeeds; sent with every command so the console re-checks it.
+    token: Option<String>,
+    failures: u32,
+    level: Arc<AtomicUsize>,
+    dropped: Arc<AtomicU64>,
+    logs: Receiver<String>,
+}
+
+impl Session<'_> {
+    fn run(&mut self) -> io::Result<()> {
+        self.send(json!({
+            "type": "hello",
+            "v": PROTOCOL_VERSION,
+            "auth": ".auth <token>",
+        }))?;
+
+        loop {
+            if self.shared.stop.load(Ordering::SeqCst) {
+                return self.send(json!({ "type": "error", "error": "server shutting down" }));
+            }
+            self.flush_logs()?;
+
+            let Some(line) = self.transport.recv()? else {
+                continue;
+            };
+            if !self.handle(line.trim())? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Returns `false` when the session should close.
+    fn handle(&mut self, line: &str) -> io::Result<bool> {
+        if line.is_empty() {
+            return Ok(true);
+        }
+
+        if let Some(cmd) = line.strip_prefix('.') {
+            let (head, arg) = cmd.split_once(' ').unwrap_or((cmd, ""));
+            return match head {
+                "auth" => self.auth(arg.trim()),
+                "logs" => self.set_logs(arg.trim()).map(|_| true),
+                "quit" | "exit" => Ok(false),
+                _ => self
+                    .error(&format!(
+                        "unknown session command '.{head}' (.auth, .logs, .quit)"
+                    ))
+                    .map(|_| true),
+            };
+        }
+
+        let Some(token) = self.token.clone() else {
+            self.error("authenticate first: .auth <token>")?;
+            return Ok(true);
+        };
+
+        let payload = json!({ "token": token, "line": line })
+            .to_string()
+            .into_bytes();
+        let mut reply = self.call_json(method::REMOTE_EXEC, payload);
+        reply["type"] = "reply".into();
+        reply["line"] = line.into();
+        self.send(reply)?;
+        Ok(true)
+    }
+
+    fn auth(&mut self, token: &str) -> io::Result<bool> {
+        let payload = json!({ "token": token }).to_string().into_bytes();
+        let mut reply = self.call_json(method::REMOTE_AUTH, payload);
+
+        if reply["ok"].as_bool() == Some(true) {
+            self.token = Some(token.to_owned());
+            self.failures = 0;
+            self.level
+                .store(self.shared.log_level as usize, Ordering::Relaxed);
+        } else {
+            self.token = None;
+            self.level
+                .store(LevelFilter::Off as usize, Ordering::Relaxed);
+            self.failures += 1;
+            std::thread::sleep(AUTH_FAILURE_DELAY);
+        }
+
+        reply["type"] = "auth".into();
+        self.send(reply)?;
+        Ok(self.failures < MAX_AUTH_FAILURES)
+    }
+
+    fn set_logs(&mut self, arg: &str) -> io::Result<()> {
+        if self.token.is_none() {
+            return self.error("authenticate first: .auth <token>");
+        }
+        let Ok(level) = arg.parse::<LevelFilter>() else {
+            return self.error("usage: .logs <off|error|warn|info|debug|trace>");
+        };
+        self.level.store(level as usize, Ordering::Relaxed);
+        self.send(json!({ "type": "logs", "level": level.as_str() }))
+    }
+
+    /// Runs a console service call on the engine thread; failures become `{ok:false, error}`.
+    fn call_json(&self, method: &'static str, payload: Vec<u8>) -> Value {
+        let out = self.call(method, payload).and_then(|bytes| {
+            serde_json::from_slice::<Value>(&bytes).map_err(|e| format!("bad console reply: {e}"))
+        });
+        match out {
+            Ok(v) if v.is_object() => v,
+            Ok(_) => json!({ "ok": false, "error": "bad console reply" }),
+            Err(e) => json!({ "ok": false, "error": e }),
+        }
+    }
+
+    fn call(&self, method: &'static str, payload: Vec<u8>) -> Result<Vec<u8>, String> {
+        let (tx, rx) = bounded(1);
+        self.shared
+            .requests
+            .send(Request {
+                method,
+                payload,
+                reply: tx,
+            })
+            .map_err(|_| "engine is shutting down".to_string())?;
+
+        match rx.recv_timeout(REPLY_TIMEOUT) {
+            Ok(out) => out,
+            Err(RecvTimeoutError::Timeout) => Err("engine did not answer in time".into()),
+            Err(RecvTimeoutError::Disconnected) => Err("engine is shutting down".into()),
+        }
+    }
+
+    fn flush_logs(&mut self) -> io::Result<()> {
+        let n = self.dropped.swap(0, Ordering::Relaxed);
+        if n > 0 {
+            self.send(json!({ "type": "dropped", "count": n }))?;
+        }
+        while let Ok(line) = self.logs.try_recv() {
+            self.transport.send(&line)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn error(&mut self, msg: &str) -> io::Result<()> {
+        self.send(json!({ "type": "error", "error": msg }))
+    }
+
+    #[inline]
+    fn send(&mut self, v: Value) -> io::Result<()> {
+        self.transport.send(&v.to_string())
+    }
+}
+
+enum Transport {
+    Tcp { stream: TcpStream, buf: Vec<u8> },
+    Ws(Box<WebSocket<TcpStream>>),
+}
+
+impl Transport {
+    /// Tells WebSocket upgrades from plain TCP by the first bytes the client sends.
+    fn open(stream: TcpStream) -> io::Result<Self> {
+        let _ = stream.set_nodelay(true);
+        stream.set_read_timeout(Some(SNIFF_TIMEOUT))?;
+
+        let mut head = [0u8; 4];
+        let websocket = match stream.peek(&mut head) {
+            Ok(n) => head[..n].starts_with(b"GET"),
+            Err(e) if is_timeout(&e) => false,
+            Err(e) => return Err(e),
+        };
+
+        if !websocket {
+            stream.set_read_timeout(Some(POLL_INTERVAL))?;
+            return Ok(Self::Tcp {
+                stream,
+                buf: Vec::new(),
+            });
+        }
+
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let ws = tungstenite::accept(stream)
+            .map_err(|e| io::Error::other(format!("websocket handshake: {e}")))?;
+        ws.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+        Ok(Self::Ws(Box::new(ws)))
+    }
+
+    #[inline]
+    fn is_websocket(&self) -> bool {
+        matches!(self, Self::Ws(_))
+    }
+
+    /// Next line from the client, or `None` if nothing complete arrived within the poll
+    /// interval.
+    fn recv(&mut self) -> io::Result<Option<String>> {
+        match self {
+            Self::Tcp { stream, buf } => loop {
+                if let Some(pos) = buf.iter().position(|&b| b == b'\n') {
+                    let line: Vec<u8> = buf.drain(..=pos).collect();
+                    let line = String::from_utf8_lossy(&line);
+                    return Ok(Some(line.trim_end_matches(['\r', '\n']).to_owned()));
+                }
+                if buf.len() > MAX_LINE {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "line too long"));
+                }
+
+                let mut chunk = [0u8; 1024];
+                match stream.read(&mut chunk) {
+                    Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                    Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                    Err(e) if is_timeout(&e) => return Ok(None),
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            },
+            Self::Ws(ws) => {
+                let text = match ws.read() {
+                    Ok(Message::Text(t)) => t.to_string(),
+                    Ok(Message::Binary(b)) => String::from_utf8_lossy(&b).into_owned(),
+                    Ok(Message::Close(_)) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                    // Pings are answered by tungstenite on the next read or write.
+                    Ok(_) => return Ok(None),
+                    Err(tungstenite::Error::Io(e)) if is_timeout(&e) => return Ok(None),
+                    Err(
+                        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed,
+                    ) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                    Err(e) => return Err(io::Error::other(e)),
+                };
+                if text.len() > MAX_LINE {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "line too long"));
+                }
+                Ok(Some(text))
+            }
+        }
+    }
+
+    fn send(&mut self, text: &str) -> io::Result<()> {
+        match self {
+            Self::Tcp { stream, .. } => {
+                let mut line = String::with_capacity(text.len() + 1);
+                line.push_str(text);
+                line.push('\n');
+                stream.write_all(line.as_bytes())
+            }
+            Self::Ws(ws) => ws.send(Message::text(text)).map_err(io::Error::other),
+        }
+    }
+
+    fn close(&mut self) {
+        match self {
+            Self::Tcp { stream, .. } => {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+            Self::Ws(ws) => {
+                let _ = ws.close(None);
+                let _ = ws.flush();
+                let _ = ws.get_ref().shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+#[inline]
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
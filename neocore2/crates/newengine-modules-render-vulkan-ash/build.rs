@@ -9,6 +9,12 @@ fn main() {
     println!("cargo:rerun-if-changed=shaders/text.frag");
     println!("cargo:rerun-if-changed=shaders/ui.vert");
     println!("cargo:rerun-if-changed=shaders/ui.frag");
+    println!("cargo:rerun-if-changed=shaders/ui_bindless.frag");
+    println!("cargo:rerun-if-changed=shaders/sprite.vert");
+    println!("cargo:rerun-if-changed=shaders/sprite.frag");
+    println!("cargo:rerun-if-changed=shaders/sprite_bindless.frag");
+    println!("cargo:rerun-if-changed=shaders/debug_line.vert");
+    println!("cargo:rerun-if-changed=shaders/debug_line.frag");
 
     let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR"));
     let compiler = shaderc::Compiler::new().expect("shaderc compiler");
@@ -58,6 +64,52 @@ fn main() {
         &out_dir,
         "ui.frag.spv",
     );
+    compile(
+        &compiler,
+        "shaders/ui_bindless.frag",
+        shaderc::ShaderKind::Fragment,
+        &out_dir,
+        "ui_bindless.frag.spv",
+    );
+
+    // Built-in shaders exposed through `RenderApi::builtin_shader`
+    compile(
+        &compiler,
+        "shaders/sprite.vert",
+        shaderc::ShaderKind::Vertex,
+        &out_dir,
+        "sprite.vert.spv",
+    );
+    compile(
+        &compiler,
+        "shaders/sprite.frag",
+        shaderc::ShaderKind::Fragment,
+        &out_dir,
+        "sprite.frag.spv",
+    );
+    compile(
+        &compiler,
+        "shaders/sprite_bindless.frag",
+        shaderc::ShaderKind::Fragment,
+        &out_dir,
+        "sprite_bindless.frag.spv",
+    );
+
+    // Debug draw lines
+    compile(
+        &compiler,
+        "shaders/debug_line.vert",
+        shaderc::ShaderKind::Vertex,
+        &out_dir,
+        "debug_line.vert.spv",
+    );
+    compile(
+        &compiler,
+        "shaders/debug_line.frag",
+        shaderc::ShaderKind::Fragment,
+        &out_dir,
+        "debug_line.frag.spv",
+    );
 }
 
 fn compile(
@@ -0,0 +1,19 @@
+#version 450
+
+// Positions arrive in clip space; SpriteBatch applies the view-projection on the CPU.
+layout(location = 0) in vec2 a_pos;
+layout(location = 1) in vec2 a_uv;
+layout(location = 2) in vec4 a_color;
+// Texture table element; only read by the bindless fragment stage.
+layout(location = 3) in uint a_texture;
+
+layout(location = 0) out vec2 v_uv;
+layout(location = 1) out vec4 v_color;
+layout(location = 2) flat out uint v_texture;
+
+void main() {
+    gl_Position = vec4(a_pos, 0.0, 1.0);
+    v_uv = a_uv;
+    v_color = a_color;
+    v_texture = a_texture;
+}
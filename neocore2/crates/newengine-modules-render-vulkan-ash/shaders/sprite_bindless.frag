@@ -0,0 +1,17 @@
+#version 450
+#extension GL_EXT_nonuniform_qualifier : require
+
+// Every sprite texture lives in one array; each vertex carries its element, so a batch
+// mixing textures is still one draw.
+layout(set = 0, binding = 0) uniform texture2D u_textures[];
+layout(set = 0, binding = 1) uniform sampler u_sampler;
+
+layout(location = 0) in vec2 v_uv;
+layout(location = 1) in vec4 v_color;
+layout(location = 2) flat in uint v_texture;
+
+layout(location = 0) out vec4 o_color;
+
+void main() {
+    o_color = texture(sampler2D(u_textures[nonuniformEXT(v_texture)], u_sampler), v_uv) * v_color;
+}
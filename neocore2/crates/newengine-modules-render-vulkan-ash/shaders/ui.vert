@@ -6,7 +6,8 @@ layout(location = 2) in vec4 a_color;
 
 layout(push_constant) uniform Pc {
     vec2 screen_size;
-    vec2 _pad;
+    uint texture_index;
+    uint _pad;
 } pc;
 
 layout(location = 0) out vec2 v_uv;
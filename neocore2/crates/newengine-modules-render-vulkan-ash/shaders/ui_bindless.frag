@@ -0,0 +1,19 @@
+#version 450
+#extension GL_EXT_nonuniform_qualifier : require
+
+// Every UI texture lives in one array; the draw selects its element by push constant.
+layout(set = 0, binding = 0) uniform sampler2D u_textures[];
+
+layout(push_constant) uniform Pc {
+    layout(offset = 8) uint texture_index;
+} pc;
+
+layout(location = 0) in vec2 v_uv;
+layout(location = 1) in vec4 v_color;
+
+layout(location = 0) out vec4 o_color;
+
+void main() {
+    vec4 t = texture(u_textures[pc.texture_index], v_uv);
+    o_color = t * v_color;
+}
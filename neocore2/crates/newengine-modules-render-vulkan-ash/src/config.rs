@@ -0,0 +1,42 @@
+use newengine_core::render::{LatencyMode, PresentMode};
+
+/// Backend options fixed at device creation.
+#[derive(Debug, Clone, Copy)]
+pub struct VulkanRenderConfig {
+    /// Write UI geometry, small uniforms and texture staging straight into host-visible
+    /// VRAM (resizable BAR) when the device exposes it. Disable to benchmark the staging path.
+    pub direct_upload: bool,
+    /// Initial swapchain present mode; changeable at runtime via `RenderApi::set_present_mode`.
+    pub present_mode: PresentMode,
+    /// Minimum swapchain image count, clamped to the surface limits; `None` uses the surface
+    /// minimum + 1. Changeable at runtime via `RenderApi::set_swapchain_image_count`.
+    pub swapchain_images: Option<u32>,
+    /// Initial frame pacing; changeable at runtime via `RenderApi::set_latency_mode`.
+    pub latency_mode: LatencyMode,
+    /// Back virtual textures with sparse residency when the device and its graphics queue
+    /// support it. Otherwise (or when disabled) they use a tiled atlas.
+    pub sparse_textures: bool,
+    /// Keep UI textures (and `RenderApi` texture arrays) in one descriptor array indexed per
+    /// draw when the device supports descriptor indexing. Disable to compare against one
+    /// descriptor set per texture.
+    pub bindless_textures: bool,
+    /// Frames the CPU may record while the GPU still renders earlier ones, clamped to 1..=4.
+    /// Each has its own command buffer, semaphores and UI/debug vertex buffers; more frames
+    /// raise throughput at the cost of latency.
+    pub frames_in_flight: u32,
+}
+
+impl Default for VulkanRenderConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            direct_upload: true,
+            present_mode: PresentMode::Mailbox,
+            swapchain_images: None,
+            latency_mode: LatencyMode::Throughput,
+            sparse_textures: true,
+            bindless_textures: true,
+            frames_in_flight: 2,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use ash::vk;
+use newengine_core::render::RenderLoss;
 use thiserror::Error;
 
 pub type VkResult<T> = Result<T, VkRenderError>;
@@ -19,3 +21,14 @@ pub enum VkRenderError {
     #[error("Vulkan error: {0}")]
     Vk(#[from] ash::vk::Result),
 }
+
+impl VkRenderError {
+    /// Device or surface loss, which `VulkanRenderApi` repairs instead of failing every frame.
+    pub fn loss(&self) -> Option<RenderLoss> {
+        match self {
+            Self::Vk(vk::Result::ERROR_DEVICE_LOST) => Some(RenderLoss::Device),
+            Self::Vk(vk::Result::ERROR_SURFACE_LOST_KHR) => Some(RenderLoss::Surface),
+            _ => None,
+        }
+    }
+}
@@ -1,16 +1,36 @@
+mod config;
 mod error;
+mod pipeline_cache;
 mod render_api;
 mod vulkan;
 
-use newengine_core::render::{RenderApiRef, RENDER_API_ID, RENDER_API_PROVIDE};
-use newengine_core::{EngineError, EngineResult, Module, ModuleCtx};
-use newengine_platform_winit::{WinitWindowHandles, WinitWindowInitSize};
+use newengine_camera::ActiveCamera;
+use newengine_core::host_events::{WindowHandles, WindowId};
+use newengine_core::render::{
+    publish_latency_mode, publish_present_mode, publish_swapchain_images,
+    take_latency_mode_request, take_present_mode_request, take_swapchain_images_request, DebugDraw,
+    Extent2D, RenderApi, RenderApiRef, RenderDeviceReset, RenderLoss, RENDER_API_ID,
+    RENDER_API_PROVIDE,
+};
+use newengine_core::{
+    AssetManager, EngineError, EngineResult, Module, ModuleCtx, SuspendPolicy, SuspendReason,
+};
+use newengine_platform_winit::{WindowApi, WinitWindowHandles, WinitWindowInitSize};
+use std::collections::HashMap;
+
+pub use crate::config::VulkanRenderConfig;
 
 use crate::error::VkRenderError;
 use crate::render_api::VulkanRenderApi;
 
 pub struct VulkanAshRenderModule {
+    config: VulkanRenderConfig,
     api: Option<RenderApiRef>,
+    /// Secondary windows seen so far and the size last passed to the backend;
+    /// `None` if attaching the surface failed.
+    windows: HashMap<WindowId, Option<(u32, u32)>>,
+    /// Device resets so far; the generation of the next `RenderDeviceReset`.
+    resets: u32,
 }
 
 impl Default for VulkanAshRenderModule {
@@ -42,20 +62,100 @@ impl<E: Send + 'static> Module<E> for VulkanAshRenderModule {
             (handles.display, handles.window, size.width, size.height)
         };
 
-        let renderer = unsafe { vulkan::VulkanRenderer::new(display, window, w, h) }
+        let renderer = unsafe { vulkan::VulkanRenderer::new(display, window, w, h, self.config) }
             .map_err(|e| EngineError::other(e.to_string()))?;
 
-        let api = RenderApiRef::new(VulkanRenderApi::new(renderer, w, h));
+        // Shader hot reload follows the asset store when one is installed.
+        let mut backend = VulkanRenderApi::new(renderer, w, h);
+        if let Some(am) = ctx.resources().get::<AssetManager>() {
+            backend = backend.with_asset_store(am.store().clone());
+        }
+        let api = RenderApiRef::new(backend);
 
         ctx.resources_mut()
             .register_api(RENDER_API_ID, api.clone())?;
+        if ctx.resources().get::<DebugDraw>().is_none() {
+            ctx.resources_mut().insert(DebugDraw::new());
+        }
 
         self.api = Some(api);
         Ok(())
     }
 
-    fn render(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
-        // Backend is a pure provider of RenderApi. All policy lives in an app-side controller.
+    fn render(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        // Backend is a pure provider of RenderApi. All policy lives in an app-side controller;
+        // the module only applies settings requests, keeps window surfaces in sync with the
+        // host, recovers a lost device and publishes GPU timings.
+        let Some(api) = self.api.clone() else {
+            return Ok(());
+        };
+        let host_windows = ctx.resources().get::<WindowApi>().cloned();
+        // Lines are seen through the active camera; without one there is nothing to place
+        // them with.
+        let debug_lines = ctx.resources().get::<DebugDraw>().and_then(|dd| {
+            match ctx.resources().get::<ActiveCamera>() {
+                Some(cam) => Some(dd.take(bytemuck::cast(cam.uniform().view_proj))),
+                None => {
+                    dd.clear();
+                    None
+                }
+            }
+        });
+        // While the device is lost the settings and windows wait for the recovered backend.
+        if !self.recover(ctx, &api) {
+            return Ok(());
+        }
+
+        let (stats, memory) = {
+            let mut api = api.lock();
+            if let Some(host_windows) = host_windows.as_ref() {
+                sync_windows(&mut **api, host_windows, &mut self.windows);
+            }
+            if let Some(lines) = debug_lines {
+                if lines.dropped > 0 {
+                    log::warn!("render.vulkan: debug draw dropped {} lines", lines.dropped);
+                }
+                api.set_debug_draw_list(lines);
+            }
+            if let Some(mode) = take_present_mode_request() {
+                if let Err(e) = api.set_present_mode(mode) {
+                    log::warn!("render.vulkan: set_present_mode failed: {e}");
+                }
+            }
+            if let Some(count) = take_swapchain_images_request() {
+                if let Err(e) = api.set_swapchain_image_count(count) {
+                    log::warn!("render.vulkan: set_swapchain_image_count failed: {e}");
+                }
+            }
+            if let Some(mode) = take_latency_mode_request() {
+                if let Err(e) = api.set_latency_mode(mode) {
+                    log::warn!("render.vulkan: set_latency_mode failed: {e}");
+                }
+            }
+            publish_present_mode(api.present_mode());
+            publish_swapchain_images(api.swapchain_image_count());
+            publish_latency_mode(api.latency_mode());
+            (api.gpu_frame_stats(), api.gpu_memory_stats())
+        };
+
+        if let Some(stats) = stats {
+            ctx.resources_mut().insert(stats);
+        }
+        if let Some(memory) = memory {
+            ctx.resources_mut().insert(memory);
+        }
+        Ok(())
+    }
+
+    fn on_suspend(&mut self, ctx: &mut ModuleCtx<'_, E>, _reason: SuspendReason) -> EngineResult<()> {
+        let release = ctx
+            .resources()
+            .get::<SuspendPolicy>()
+            .is_some_and(|p| p.release_gpu_surface);
+
+        if let (true, Some(api)) = (release, self.api.as_ref()) {
+            api.lock().release_surface();
+        }
         Ok(())
     }
 
@@ -64,13 +164,109 @@ impl<E: Send + 'static> Module<E> for VulkanAshRenderModule {
             .resources_mut()
             .unregister_api::<RenderApiRef>(RENDER_API_ID);
         self.api = None;
+        self.windows.clear();
         Ok(())
     }
 }
 
+impl VulkanAshRenderModule {
+    /// Repairs a lost device or surface reported by the backend. Returns false while the
+    /// backend is still lost; recovery is retried every frame.
+    fn recover<E: Send + 'static>(
+        &mut self,
+        ctx: &mut ModuleCtx<'_, E>,
+        api: &RenderApiRef,
+    ) -> bool {
+        let Some(loss) = api.lock().device_lost() else {
+            return true;
+        };
+        let Some(handles) = ctx.resources().get::<WinitWindowHandles>().map(|h| WindowHandles {
+            window: h.window,
+            display: h.display,
+        }) else {
+            return false;
+        };
+
+        if let Err(e) = api.lock().recover_device(handles) {
+            log::warn!("render.vulkan: {} recovery failed: {e}", loss.as_str());
+            return false;
+        }
+        if loss == RenderLoss::Device {
+            // Secondary window surfaces went with the device; `sync_windows` attaches them again.
+            self.windows.clear();
+            self.resets += 1;
+            log::info!("render.vulkan: device reset generation={}", self.resets);
+            let _ = ctx.events().publish(RenderDeviceReset {
+                generation: self.resets,
+            });
+        }
+        true
+    }
+}
+
+/// Attaches surfaces for newly opened windows, forwards resizes and detaches windows the
+/// host is about to close.
+fn sync_windows(
+    api: &mut dyn RenderApi,
+    host: &WindowApi,
+    attached: &mut HashMap<WindowId, Option<(u32, u32)>>,
+) {
+    let open = host.windows();
+
+    attached.retain(|id, _| {
+        let keep = open.contains(id);
+        if !keep {
+            api.detach_window(*id);
+        }
+        keep
+    });
+
+    for id in open {
+        let Some((w, h)) = host.size(id) else {
+            continue;
+        };
+        match attached.get_mut(&id) {
+            Some(Some(size)) => {
+                if *size != (w, h) {
+                    *size = (w, h);
+                    if let Err(e) = api.resize_window(id, Extent2D::new(w, h)) {
+                        log::warn!("render.vulkan: window {} resize failed: {e}", id.0);
+                    }
+                }
+            }
+            Some(None) => {}
+            None => {
+                let Some(handles) = host.handles(id) else {
+                    continue;
+                };
+                let handles = WindowHandles {
+                    window: handles.window,
+                    display: handles.display,
+                };
+                let res = api.attach_window(id, handles, Extent2D::new(w, h));
+                if let Err(e) = &res {
+                    log::warn!("render.vulkan: window {} attach failed: {e}", id.0);
+                }
+                attached.insert(id, res.ok().map(|_| (w, h)));
+            }
+        }
+    }
+}
+
 impl VulkanAshRenderModule {
     #[inline]
     pub fn new() -> Self {
-        Self { api: None }
+        Self {
+            config: VulkanRenderConfig::default(),
+            api: None,
+            windows: HashMap::new(),
+            resets: 0,
+        }
+    }
+
+    #[inline]
+    pub fn with_config(mut self, config: VulkanRenderConfig) -> Self {
+        self.config = config;
+        self
     }
 }
\ No newline at end of file
@@ -0,0 +1,152 @@
+use newengine_assets::{AssetBlob, AssetId, AssetState, AssetStore, ShaderAsset};
+use newengine_core::render::{ComputePipelineDesc, PipelineDesc, PipelineId, ShaderId};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+struct WatchedShader {
+    asset: AssetId,
+    /// Blob the current module was built from; a different `Arc` means a re-import.
+    blob: Option<Arc<AssetBlob>>,
+}
+
+/// Re-imported SPIR-V for a watched shader.
+pub(crate) struct ShaderReload {
+    pub(crate) shader: ShaderId,
+    pub(crate) asset: AssetId,
+    pub(crate) spirv: Vec<u32>,
+}
+
+/// Shader -> pipeline dependency tracking for in-place rebuilds.
+///
+/// Every pipeline keeps the descriptor it was created from. When a watched shader asset is
+/// re-imported, the backend swaps the shader module and recreates the dependent pipelines
+/// under their existing ids.
+#[derive(Default)]
+pub(crate) struct PipelineCache {
+    store: Option<Arc<AssetStore>>,
+    descs: HashMap<PipelineId, PipelineDesc>,
+    compute: HashMap<PipelineId, ComputePipelineDesc>,
+    dependents: HashMap<ShaderId, Vec<PipelineId>>,
+    watched: HashMap<ShaderId, WatchedShader>,
+}
+
+impl PipelineCache {
+    #[inline]
+    pub(crate) fn set_store(&mut self, store: Arc<AssetStore>) {
+        self.store = Some(store);
+    }
+
+    #[inline]
+    pub(crate) fn hot_reload(&self) -> bool {
+        self.store.is_some()
+    }
+
+    /// Forgets every pipeline and watched shader (their ids died with the device); hot reload
+    /// stays enabled.
+    pub(crate) fn reset(&mut self) {
+        self.descs.clear();
+        self.compute.clear();
+        self.dependents.clear();
+        self.watched.clear();
+    }
+
+    pub(crate) fn insert_pipeline(&mut self, id: PipelineId, desc: PipelineDesc) {
+        for s in [desc.vs, desc.fs] {
+            let list = self.dependents.entry(s).or_default();
+            if !list.contains(&id) {
+                list.push(id);
+            }
+        }
+        self.descs.insert(id, desc);
+    }
+
+    pub(crate) fn insert_compute_pipeline(&mut self, id: PipelineId, desc: ComputePipelineDesc) {
+        let list = self.dependents.entry(desc.cs).or_default();
+        if !list.contains(&id) {
+            list.push(id);
+        }
+        self.compute.insert(id, desc);
+    }
+
+    pub(crate) fn remove_pipeline(&mut self, id: PipelineId) {
+        let shaders = match (self.descs.remove(&id), self.compute.remove(&id)) {
+            (Some(desc), _) => vec![desc.vs, desc.fs],
+            (None, Some(desc)) => vec![desc.cs],
+            (None, None) => return,
+        };
+        for s in shaders {
+            if let Some(list) = self.dependents.get_mut(&s) {
+                list.retain(|p| *p != id);
+                if list.is_empty() {
+                    self.dependents.remove(&s);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn desc(&self, id: PipelineId) -> Option<&PipelineDesc> {
+        self.descs.get(&id)
+    }
+
+    #[inline]
+    pub(crate) fn compute_desc(&self, id: PipelineId) -> Option<&ComputePipelineDesc> {
+        self.compute.get(&id)
+    }
+
+    #[inline]
+    pub(crate) fn dependents(&self, shader: ShaderId) -> Vec<PipelineId> {
+        self.dependents.get(&shader).cloned().unwrap_or_default()
+    }
+
+    /// Starts watching `asset` for `shader`. No-op without an asset store.
+    pub(crate) fn watch_shader(&mut self, shader: ShaderId, asset: AssetId) {
+        let Some(store) = self.store.as_ref() else {
+            return;
+        };
+        let blob = store.get_blob(asset);
+        self.watched.insert(shader, WatchedShader { asset, blob });
+    }
+
+    #[inline]
+    pub(crate) fn forget_shader(&mut self, shader: ShaderId) {
+        self.watched.remove(&shader);
+    }
+
+    /// Collects watched shaders whose asset was re-imported since the last poll.
+    /// Undecodable blobs are reported and skipped; the old module stays active.
+    pub(crate) fn poll_reloads(&mut self) -> Vec<ShaderReload> {
+        let Some(store) = self.store.as_ref() else {
+            return Vec::new();
+        };
+
+        let mut out = Vec::new();
+        for (shader, w) in self.watched.iter_mut() {
+            if !matches!(store.state(w.asset), AssetState::Ready) {
+                continue;
+            }
+            let Some(blob) = store.get_blob(w.asset) else {
+                continue;
+            };
+            if w.blob.as_ref().is_some_and(|b| Arc::ptr_eq(b, &blob)) {
+                continue;
+            }
+
+            match ShaderAsset::from_blob(&blob) {
+                Ok(s) => out.push(ShaderReload {
+                    shader: *shader,
+                    asset: w.asset,
+                    spirv: s.spirv,
+                }),
+                Err(e) => log::warn!(
+                    target: "render",
+                    "render.vulkan.shader_reload decode failed asset={:?} err='{e}'",
+                    w.asset
+                ),
+            }
+            w.blob = Some(blob);
+        }
+        out
+    }
+}
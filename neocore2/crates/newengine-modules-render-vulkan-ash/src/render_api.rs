@@ -1,15 +1,28 @@
+use crate::error::VkRenderError;
+use crate::pipeline_cache::{PipelineCache, ShaderReload};
 use crate::vulkan::pipeline::create_shader_module;
-use crate::vulkan::util::immediate_submit;
+use crate::vulkan::renderer::{
+    AliasBlock, ColorTarget, SampledImage, VirtualImage, VirtualImageDesc, WindowSurface,
+};
+use crate::vulkan::sync::BufferAcquire;
 use crate::vulkan::VulkanRenderer;
 
 use ash::vk;
 
+use newengine_assets::AssetStore;
+use newengine_core::host_events::{WindowHandles, WindowId};
 use newengine_core::render::*;
 use newengine_core::{EngineError, EngineResult};
-use newengine_ui::draw::UiDrawList;
+use newengine_ui::draw::{UiDrawList, UiTexId};
+use newengine_ui::texture::reserved;
 
 use std::collections::HashMap;
 use std::ffi::CString;
+use std::sync::Arc;
+
+/// GPU-only uniform buffers up to this size live in host-visible VRAM when the device has
+/// it, so `write_buffer` maps them instead of staging a copy.
+const DIRECT_UNIFORM_MAX: u64 = 64 * 1024;
 
 #[derive(Clone, Copy)]
 struct VkBuffer {
@@ -38,12 +51,48 @@ struct VkBindGroup {
     set: vk::DescriptorSet,
     pool: vk::DescriptorPool,
     layout: vk::DescriptorSetLayout,
+    /// `(binding, len)` of the layout's `TextureArray`, filled by `write_bind_group_texture`.
+    texture_array: Option<(u32, u32)>,
 }
 
 #[derive(Clone, Copy)]
 struct VkPipeline {
     pipeline: vk::Pipeline,
     layout: vk::PipelineLayout,
+    bind_point: vk::PipelineBindPoint,
+}
+
+#[derive(Clone, Copy)]
+struct VkRenderTarget {
+    color: ColorTarget,
+    ui: Option<UiTexId>,
+    /// Key in `alias_blocks` when the target shares its memory.
+    alias: Option<u32>,
+}
+
+/// Memory shared by the targets of one `create_aliased_render_targets` call.
+struct VkAliasBlock {
+    block: AliasBlock,
+    /// Targets still bound to the block; it is freed with the last one.
+    users: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PassTarget {
+    Texture(TextureId),
+    Window(WindowId),
+}
+
+/// Offscreen or secondary window pass being recorded; the swapchain recording is parked
+/// until it ends.
+struct OffscreenPass {
+    target: PassTarget,
+    clear_color: Color4,
+    saved: Vec<RecordedCmd>,
+    saved_pipeline: Option<PipelineId>,
+    saved_vertex: [Option<BufferSlice>; 4],
+    saved_index: Option<(BufferSlice, IndexFormat)>,
+    saved_bind_groups: [Option<BindGroupId>; 4],
 }
 
 enum RecordedCmd {
@@ -75,20 +124,38 @@ pub struct VulkanRenderApi {
     renderer: VulkanRenderer,
     target: Extent2D,
 
-    next_id: u32,
+    handles: HandleRegistry,
 
     buffers: HashMap<BufferId, VkBuffer>,
     shaders: HashMap<ShaderId, VkShader>,
     bg_layouts: HashMap<BindGroupLayoutId, VkBgLayout>,
     bind_groups: HashMap<BindGroupId, VkBindGroup>,
     pipelines: HashMap<PipelineId, VkPipeline>,
+    pipeline_cache: PipelineCache,
+    samplers: HashMap<SamplerId, vk::Sampler>,
+    targets: HashMap<TextureId, VkRenderTarget>,
+    alias_blocks: HashMap<u32, VkAliasBlock>,
+    next_alias_block: u32,
+    textures: HashMap<TextureId, SampledImage>,
+    virtual_textures: HashMap<TextureId, VirtualImage>,
+    target_passes: HashMap<vk::Format, vk::RenderPass>,
+    offscreen: Option<OffscreenPass>,
+    windows: HashMap<WindowId, WindowSurface>,
+    next_ui_texture: u32,
 
     current_pipeline: Option<PipelineId>,
+    current_compute: Option<PipelineId>,
     current_vertex: [Option<BufferSlice>; 4],
     current_index: Option<(BufferSlice, IndexFormat)>,
     current_bind_groups: [Option<BindGroupId>; 4],
 
     recorded: Vec<RecordedCmd>,
+    /// Clear color of a frame whose swapchain image is acquired in `end_frame`
+    /// (`LatencyMode::Low`).
+    late_begin: Option<[f32; 4]>,
+    /// Set when the device or main surface is lost; frames are skipped until
+    /// `recover_device` succeeds.
+    lost: Option<RenderLoss>,
 }
 
 impl VulkanRenderApi {
@@ -97,30 +164,62 @@ impl VulkanRenderApi {
         Self {
             renderer,
             target: Extent2D::new(width, height),
-            next_id: 1,
+            handles: HandleRegistry::new(),
             buffers: HashMap::new(),
             shaders: HashMap::new(),
             bg_layouts: HashMap::new(),
             bind_groups: HashMap::new(),
             pipelines: HashMap::new(),
+            pipeline_cache: PipelineCache::default(),
+            samplers: HashMap::new(),
+            targets: HashMap::new(),
+            alias_blocks: HashMap::new(),
+            next_alias_block: 0,
+            textures: HashMap::new(),
+            virtual_textures: HashMap::new(),
+            target_passes: HashMap::new(),
+            offscreen: None,
+            windows: HashMap::new(),
+            next_ui_texture: 0,
             current_pipeline: None,
+            current_compute: None,
             current_vertex: [None, None, None, None],
             current_index: None,
             current_bind_groups: [None, None, None, None],
             recorded: Vec::new(),
+            late_begin: None,
+            lost: None,
         }
     }
 
+    /// Enables shader hot reload: shaders created with `ShaderDesc::source` follow re-imports
+    /// of their asset, and dependent pipelines are rebuilt under the same ids.
+    #[inline]
+    pub fn with_asset_store(mut self, store: Arc<AssetStore>) -> Self {
+        self.pipeline_cache.set_store(store);
+        self
+    }
+
     #[inline]
     pub fn set_ui_draw_list(&mut self, ui: UiDrawList) {
         self.renderer.set_ui_draw_list(ui);
     }
 
     #[inline]
-    fn alloc_u32(&mut self) -> u32 {
-        let v = self.next_id;
-        self.next_id = self.next_id.wrapping_add(1).max(1);
-        v
+    fn check<H: RenderHandle>(&self, h: H, op: &'static str) -> EngineResult<()> {
+        self.handles.validate(h, op)
+    }
+
+    /// Retires a handle; stale/foreign destroys are reported and ignored.
+    #[inline]
+    fn retire<H: RenderHandle>(&mut self, h: H) -> bool {
+        match self.handles.release(h) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("render.vulkan: {}", e);
+                false
+            }
+        }
     }
 
     #[inline]
@@ -128,6 +227,322 @@ impl VulkanRenderApi {
         Err(EngineError::other(msg.into()))
     }
 
+    #[inline]
+    unsafe fn destroy_vk_pipeline(&self, p: VkPipeline) {
+        let device = &self.renderer.core.device;
+        if p.pipeline != vk::Pipeline::null() {
+            device.destroy_pipeline(p.pipeline, None);
+        }
+        if p.layout != vk::PipelineLayout::null() {
+            device.destroy_pipeline_layout(p.layout, None);
+        }
+    }
+
+    /// Builds the Vulkan objects for `desc`. Shared by `create_pipeline` and hot-reload rebuilds.
+    fn build_pipeline(&self, desc: &PipelineDesc) -> EngineResult<VkPipeline> {
+        let vs = self.shaders.get(&desc.vs).ok_or_else(|| EngineError::other("create_pipeline: invalid vs"))?.clone();
+        let fs = self.shaders.get(&desc.fs).ok_or_else(|| EngineError::other("create_pipeline: invalid fs"))?.clone();
+
+        let mut set_layouts: Vec<vk::DescriptorSetLayout> = Vec::with_capacity(desc.bind_group_layouts.len());
+        for l_id in &desc.bind_group_layouts {
+            self.check(*l_id, "create_pipeline.layout")?;
+            let l = self.bg_layouts.get(l_id).ok_or_else(|| EngineError::other("create_pipeline: invalid bind group layout"))?;
+            set_layouts.push(l.layout);
+        }
+
+        unsafe {
+            let device = &self.renderer.core.device;
+
+            let layout_ci = vk::PipelineLayoutCreateInfo::default().set_layouts(&set_layouts);
+            let layout = device.create_pipeline_layout(&layout_ci, None).map_err(|e| EngineError::other(e.to_string()))?;
+
+            let stages = [
+                vk::PipelineShaderStageCreateInfo::default().stage(vs.stage).module(vs.module).name(&vs.entry),
+                vk::PipelineShaderStageCreateInfo::default().stage(fs.stage).module(fs.module).name(&fs.entry),
+            ];
+
+            let mut binding_descs: Vec<vk::VertexInputBindingDescription> = Vec::new();
+            let mut attr_descs: Vec<vk::VertexInputAttributeDescription> = Vec::new();
+
+            for (i, l) in desc.vertex_layouts.iter().enumerate() {
+                binding_descs.push(
+                    vk::VertexInputBindingDescription::default()
+                        .binding(i as u32)
+                        .stride(l.stride)
+                        .input_rate(vk::VertexInputRate::VERTEX),
+                );
+
+                for a in &l.attributes {
+                    attr_descs.push(
+                        vk::VertexInputAttributeDescription::default()
+                            .binding(i as u32)
+                            .location(a.location)
+                            .format(Self::map_vertex_format(a.format))
+                            .offset(a.offset),
+                    );
+                }
+            }
+
+            let vi = vk::PipelineVertexInputStateCreateInfo::default()
+                .vertex_binding_descriptions(&binding_descs)
+                .vertex_attribute_descriptions(&attr_descs);
+
+            let ia = vk::PipelineInputAssemblyStateCreateInfo::default().topology(Self::map_topology(desc.topology));
+            let vp = vk::PipelineViewportStateCreateInfo::default().viewport_count(1).scissor_count(1);
+
+            let rs = vk::PipelineRasterizationStateCreateInfo::default()
+                .polygon_mode(vk::PolygonMode::FILL)
+                .cull_mode(match desc.cull {
+                    CullMode::None => vk::CullModeFlags::NONE,
+                    CullMode::Back => vk::CullModeFlags::BACK,
+                })
+                .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+                .line_width(1.0);
+
+            let ms = vk::PipelineMultisampleStateCreateInfo::default().rasterization_samples(vk::SampleCountFlags::TYPE_1);
+
+            let ca = match desc.blend {
+                BlendMode::Opaque => vk::PipelineColorBlendAttachmentState::default().blend_enable(false),
+                BlendMode::Alpha => vk::PipelineColorBlendAttachmentState::default()
+                    .blend_enable(true)
+                    .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
+                    .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+                    .color_blend_op(vk::BlendOp::ADD)
+                    .src_alpha_blend_factor(vk::BlendFactor::ONE)
+                    .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+                    .alpha_blend_op(vk::BlendOp::ADD),
+            }
+            .color_write_mask(
+                    vk::ColorComponentFlags::R
+                        | vk::ColorComponentFlags::G
+                        | vk::ColorComponentFlags::B
+                        | vk::ColorComponentFlags::A,
+                );
+
+            let cb = vk::PipelineColorBlendStateCreateInfo::default().attachments(std::slice::from_ref(&ca));
+
+            let dyn_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+            let ds = vk::PipelineDynamicStateCreateInfo::default().dynamic_states(&dyn_states);
+
+            let gp = vk::GraphicsPipelineCreateInfo::default()
+                .stages(&stages)
+                .vertex_input_state(&vi)
+                .input_assembly_state(&ia)
+                .viewport_state(&vp)
+                .rasterization_state(&rs)
+                .multisample_state(&ms)
+                .color_blend_state(&cb)
+                .dynamic_state(&ds)
+                .layout(layout)
+                .render_pass(self.render_pass_for(desc.color_format))
+                .subpass(0);
+
+            let pipelines = device.create_graphics_pipelines(vk::PipelineCache::null(), &[gp], None);
+            let pipeline = match pipelines {
+                Ok(v) => v[0],
+                Err((_, e)) => {
+                    device.destroy_pipeline_layout(layout, None);
+                    return Err(EngineError::other(e.to_string()));
+                }
+            };
+
+            Ok(VkPipeline { pipeline, layout, bind_point: vk::PipelineBindPoint::GRAPHICS })
+        }
+    }
+
+    /// Builds the Vulkan objects for `desc`. Shared by `create_compute_pipeline` and hot-reload
+    /// rebuilds.
+    fn build_compute_pipeline(&self, desc: &ComputePipelineDesc) -> EngineResult<VkPipeline> {
+        let cs = self.shaders.get(&desc.cs).ok_or_else(|| EngineError::other("create_compute_pipeline: invalid cs"))?.clone();
+        if cs.stage != vk::ShaderStageFlags::COMPUTE {
+            return self.err("create_compute_pipeline: cs is not a compute shader");
+        }
+
+        let mut set_layouts: Vec<vk::DescriptorSetLayout> = Vec::with_capacity(desc.bind_group_layouts.len());
+        for l_id in &desc.bind_group_layouts {
+            self.check(*l_id, "create_compute_pipeline.layout")?;
+            let l = self
+                .bg_layouts
+                .get(l_id)
+                .ok_or_else(|| EngineError::other("create_compute_pipeline: invalid bind group layout"))?;
+            set_layouts.push(l.layout);
+        }
+
+        unsafe {
+            let device = &self.renderer.core.device;
+
+            let layout_ci = vk::PipelineLayoutCreateInfo::default().set_layouts(&set_layouts);
+            let layout = device.create_pipeline_layout(&layout_ci, None).map_err(|e| EngineError::other(e.to_string()))?;
+
+            let stage = vk::PipelineShaderStageCreateInfo::default().stage(cs.stage).module(cs.module).name(&cs.entry);
+            let ci = vk::ComputePipelineCreateInfo::default().stage(stage).layout(layout);
+
+            let pipelines = device.create_compute_pipelines(vk::PipelineCache::null(), &[ci], None);
+            let pipeline = match pipelines {
+                Ok(v) => v[0],
+                Err((_, e)) => {
+                    device.destroy_pipeline_layout(layout, None);
+                    return Err(EngineError::other(e.to_string()));
+                }
+            };
+
+            Ok(VkPipeline { pipeline, layout, bind_point: vk::PipelineBindPoint::COMPUTE })
+        }
+    }
+
+    /// Descriptor sets of the groups set with `set_bind_group`, and how many leading slots
+    /// they span.
+    fn bound_sets(&self, op: &'static str) -> EngineResult<([vk::DescriptorSet; 4], u32)> {
+        let mut sets = [vk::DescriptorSet::null(); 4];
+        let mut set_count = 0u32;
+        for (i, bg_id) in self.current_bind_groups.iter().enumerate() {
+            if let Some(bg_id) = bg_id {
+                let bg = *self.bind_groups.get(bg_id).ok_or_else(|| EngineError::other(format!("{op}: invalid bind group")))?;
+                sets[i] = bg.set;
+                set_count = (i as u32) + 1;
+            }
+        }
+        Ok((sets, set_count))
+    }
+
+    /// Applies re-imported shaders. Runs at frame start, before anything is recorded.
+    fn apply_shader_reloads(&mut self) {
+        let reloads = self.pipeline_cache.poll_reloads();
+        if reloads.is_empty() {
+            return;
+        }
+
+        // Old modules/pipelines may still be referenced by frames in flight.
+        if let Err(e) = unsafe { self.renderer.core.device.device_wait_idle() } {
+            log::warn!(target: "render", "render.vulkan.shader_reload wait_idle failed: {e}");
+            return;
+        }
+
+        for r in reloads {
+            match self.reload_shader(&r) {
+                Ok(rebuilt) => log::info!(
+                    target: "render",
+                    "render.vulkan.shader_reload asset={:?} pipelines={rebuilt}",
+                    r.asset
+                ),
+                Err(e) => log::warn!(
+                    target: "render",
+                    "render.vulkan.shader_reload failed asset={:?} err='{e}' (keeping previous)",
+                    r.asset
+                ),
+            }
+        }
+    }
+
+    /// Swaps the module behind `r.shader` and rebuilds every dependent pipeline in place.
+    /// All-or-nothing: on any failure the previous module and pipelines are kept.
+    fn reload_shader(&mut self, r: &ShaderReload) -> EngineResult<usize> {
+        let Some(old) = self.shaders.get(&r.shader).cloned() else {
+            return Ok(0);
+        };
+
+        let module = unsafe {
+            create_shader_module(&self.renderer.core.device, bytemuck::cast_slice(&r.spirv))
+                .map_err(|e| EngineError::other(e.to_string()))?
+        };
+        self.shaders.insert(
+            r.shader,
+            VkShader {
+                module,
+                ..old.clone()
+            },
+        );
+
+        let mut rebuilt: Vec<(PipelineId, VkPipeline)> = Vec::new();
+        for pid in self.pipeline_cache.dependents(r.shader) {
+            let built = if let Some(desc) = self.pipeline_cache.desc(pid) {
+                self.build_pipeline(desc)
+            } else if let Some(desc) = self.pipeline_cache.compute_desc(pid) {
+                self.build_compute_pipeline(desc)
+            } else {
+                continue;
+            };
+            match built {
+                Ok(p) => rebuilt.push((pid, p)),
+                Err(e) => {
+                    unsafe {
+                        for (_, p) in rebuilt {
+                            self.destroy_vk_pipeline(p);
+                        }
+                        self.renderer.core.device.destroy_shader_module(module, None);
+                    }
+                    self.shaders.insert(r.shader, old);
+                    return Err(e);
+                }
+            }
+        }
+
+        let count = rebuilt.len();
+        for (pid, p) in rebuilt {
+            if let Some(prev) = self.pipelines.insert(pid, p) {
+                unsafe { self.destroy_vk_pipeline(prev) };
+            }
+        }
+        unsafe { self.renderer.core.device.destroy_shader_module(old.module, None) };
+        Ok(count)
+    }
+
+    #[inline]
+    fn map_color_format(f: TextureFormat) -> Option<vk::Format> {
+        match f {
+            TextureFormat::Rgba8Unorm => Some(vk::Format::R8G8B8A8_UNORM),
+            TextureFormat::Bgra8Unorm => Some(vk::Format::B8G8R8A8_UNORM),
+            TextureFormat::Rgba16Float => Some(vk::Format::R16G16B16A16_SFLOAT),
+            TextureFormat::Depth24Stencil8 | TextureFormat::Depth32Float => None,
+        }
+    }
+
+    #[inline]
+    fn unmap_color_format(f: vk::Format) -> Option<TextureFormat> {
+        match f {
+            vk::Format::R8G8B8A8_UNORM => Some(TextureFormat::Rgba8Unorm),
+            vk::Format::B8G8R8A8_UNORM => Some(TextureFormat::Bgra8Unorm),
+            vk::Format::R16G16B16A16_SFLOAT => Some(TextureFormat::Rgba16Float),
+            _ => None,
+        }
+    }
+
+    /// Pass a pipeline for `color_format` is built against: the swapchain pass, or the
+    /// offscreen pass of that format when it differs from the swapchain.
+    fn render_pass_for(&self, color_format: TextureFormat) -> vk::RenderPass {
+        Self::map_color_format(color_format)
+            .filter(|f| *f != self.renderer.swapchain.format)
+            .and_then(|f| self.target_passes.get(&f).copied())
+            .unwrap_or(self.renderer.pipelines.render_pass)
+    }
+
+    fn ensure_target_pass(&mut self, format: vk::Format) -> EngineResult<vk::RenderPass> {
+        if let Some(p) = self.target_passes.get(&format) {
+            return Ok(*p);
+        }
+        let pass = unsafe { self.renderer.create_target_pass(format) }
+            .map_err(|e| EngineError::other(e.to_string()))?;
+        self.target_passes.insert(format, pass);
+        Ok(pass)
+    }
+
+    #[inline]
+    fn map_filter(f: FilterMode) -> vk::Filter {
+        match f {
+            FilterMode::Nearest => vk::Filter::NEAREST,
+            FilterMode::Linear => vk::Filter::LINEAR,
+        }
+    }
+
+    #[inline]
+    fn map_address_mode(m: AddressMode) -> vk::SamplerAddressMode {
+        match m {
+            AddressMode::ClampToEdge => vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            AddressMode::Repeat => vk::SamplerAddressMode::REPEAT,
+            AddressMode::MirroredRepeat => vk::SamplerAddressMode::MIRRORED_REPEAT,
+        }
+    }
+
     #[inline]
     fn map_stage(stage: ShaderStage) -> vk::ShaderStageFlags {
         match stage {
@@ -162,6 +577,18 @@ impl VulkanRenderApi {
             VertexFormat::Float32x3 => vk::Format::R32G32B32_SFLOAT,
             VertexFormat::Float32x4 => vk::Format::R32G32B32A32_SFLOAT,
             VertexFormat::Unorm8x4 => vk::Format::R8G8B8A8_UNORM,
+            VertexFormat::Uint32 => vk::Format::R32_UINT,
+        }
+    }
+
+    /// View of a sampled texture, render target or virtual texture.
+    fn texture_view(&self, tex: TextureId) -> Option<vk::ImageView> {
+        if let Some(t) = self.textures.get(&tex) {
+            Some(t.alloc.view)
+        } else if let Some(t) = self.targets.get(&tex) {
+            Some(t.color.alloc.view)
+        } else {
+            self.virtual_textures.get(&tex).map(|v| v.alloc.view)
         }
     }
 
@@ -170,7 +597,9 @@ impl VulkanRenderApi {
             BufferUsage::Vertex => vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
             BufferUsage::Index => vk::BufferUsageFlags::INDEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
             BufferUsage::Uniform => vk::BufferUsageFlags::UNIFORM_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
-            BufferUsage::Storage => vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
+            BufferUsage::Storage => {
+                vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST
+            }
             BufferUsage::Staging => vk::BufferUsageFlags::TRANSFER_SRC,
         }
     }
@@ -218,25 +647,38 @@ impl VulkanRenderApi {
 
         let req = device.get_buffer_memory_requirements(buffer);
 
-        let mem_type = Self::find_memory_type(
-            &self.renderer.core.instance,
-            self.renderer.core.physical_device,
-            req.memory_type_bits,
-            props,
-        )
-            .ok_or_else(|| EngineError::other("No compatible Vulkan memory type"))?;
+        // Direct-upload callers retry with other memory, so a failed allocation must not leak.
+        let memory = (|| -> EngineResult<vk::DeviceMemory> {
+            let mem_type = Self::find_memory_type(
+                &self.renderer.core.instance,
+                self.renderer.core.physical_device,
+                req.memory_type_bits,
+                props,
+            )
+                .ok_or_else(|| EngineError::other("No compatible Vulkan memory type"))?;
 
-        let alloc = vk::MemoryAllocateInfo::default()
-            .allocation_size(req.size)
-            .memory_type_index(mem_type);
+            let alloc = vk::MemoryAllocateInfo::default()
+                .allocation_size(req.size)
+                .memory_type_index(mem_type);
 
-        let memory = device
-            .allocate_memory(&alloc, None)
-            .map_err(|e| EngineError::other(e.to_string()))?;
+            let memory = device
+                .allocate_memory(&alloc, None)
+                .map_err(|e| EngineError::other(e.to_string()))?;
 
-        device
-            .bind_buffer_memory(buffer, memory, 0)
-            .map_err(|e| EngineError::other(e.to_string()))?;
+            if let Err(e) = device.bind_buffer_memory(buffer, memory, 0) {
+                device.free_memory(memory, None);
+                return Err(EngineError::other(e.to_string()));
+            }
+            Ok(memory)
+        })();
+
+        let memory = match memory {
+            Ok(m) => m,
+            Err(e) => {
+                device.destroy_buffer(buffer, None);
+                return Err(e);
+            }
+        };
 
         Ok(VkBuffer {
             buffer,
@@ -251,15 +693,45 @@ impl VulkanRenderApi {
         if !self.renderer.debug.in_frame {
             return None;
         }
-        let idx = self.renderer.debug.current_swapchain_idx;
-        Some(self.renderer.frames.command_buffers[idx])
+        Some(self.renderer.frames.command_buffers[self.renderer.frames.frame_index])
+    }
+
+    fn open_pass(&mut self, target: PassTarget, clear_color: Color4) {
+        self.offscreen = Some(OffscreenPass {
+            target,
+            clear_color,
+            saved: std::mem::take(&mut self.recorded),
+            saved_pipeline: self.current_pipeline.take(),
+            saved_vertex: std::mem::take(&mut self.current_vertex),
+            saved_index: self.current_index.take(),
+            saved_bind_groups: std::mem::take(&mut self.current_bind_groups),
+        });
+    }
+
+    /// Ends the open pass, restores the parked swapchain recording and returns the pass
+    /// commands.
+    fn close_pass(&mut self) -> Option<(PassTarget, Color4, Vec<RecordedCmd>)> {
+        let pass = self.offscreen.take()?;
+        let cmds = std::mem::replace(&mut self.recorded, pass.saved);
+        self.current_pipeline = pass.saved_pipeline;
+        self.current_vertex = pass.saved_vertex;
+        self.current_index = pass.saved_index;
+        self.current_bind_groups = pass.saved_bind_groups;
+        Some((pass.target, pass.clear_color, cmds))
     }
 
     unsafe fn flush_recorded(&mut self) -> EngineResult<()> {
         let Some(cmd) = self.current_cmd() else { return Ok(()); };
-        let device = &self.renderer.core.device;
+        Self::replay(&self.renderer.core.device, cmd, self.recorded.drain(..));
+        Ok(())
+    }
 
-        for c in self.recorded.drain(..) {
+    unsafe fn replay(
+        device: &ash::Device,
+        cmd: vk::CommandBuffer,
+        cmds: impl IntoIterator<Item = RecordedCmd>,
+    ) {
+        for c in cmds {
             match c {
                 RecordedCmd::SetViewport(vp) => device.cmd_set_viewport(cmd, 0, std::slice::from_ref(&vp)),
                 RecordedCmd::SetScissor(sc) => device.cmd_set_scissor(cmd, 0, std::slice::from_ref(&sc)),
@@ -296,66 +768,167 @@ impl VulkanRenderApi {
                 ),
             }
         }
+    }
+}
+
+impl VulkanRenderApi {
+    /// Records a device or surface loss behind `e`. A device loss supersedes a surface loss.
+    fn note_loss(&mut self, e: &VkRenderError) -> bool {
+        let Some(loss) = e.loss() else { return false; };
+        if self.lost != Some(RenderLoss::Device) {
+            if self.lost.is_none() {
+                log::error!("render.vulkan: {} lost: {e}", loss.as_str());
+            }
+            self.lost = Some(loss);
+        }
+        true
+    }
 
+    /// Maps a renderer error to the API error, swallowing losses (see `note_loss`).
+    fn frame_result(&mut self, r: Result<(), VkRenderError>) -> EngineResult<()> {
+        match r {
+            Ok(()) => Ok(()),
+            Err(e) if self.note_loss(&e) => Ok(()),
+            Err(e) => Err(EngineError::other(e.to_string())),
+        }
+    }
+
+    /// Rebuilds the renderer on a new device for the same window. Every object created
+    /// through the API is dropped and its handle invalidated.
+    unsafe fn recreate_device(&mut self, handles: WindowHandles) -> EngineResult<()> {
+        self.destroy_resources();
+        self.renderer.destroy_surface();
+
+        let old = &self.renderer;
+        let mut renderer = VulkanRenderer::new(handles.display, handles.window, self.target.width, self.target.height, old.core.config)
+            .map_err(|e| EngineError::other(e.to_string()))?;
+        renderer.set_present_mode(old.swapchain.requested_present_mode);
+        renderer.set_swapchain_image_count(old.swapchain.requested_image_count);
+        renderer.set_latency_mode(old.swapchain.latency_mode);
+        // UI providers send a texture once, so the copies kept by the old renderer move over.
+        let ui = self.renderer.ui_take_textures();
+        renderer.ui_restore(ui).map_err(|e| EngineError::other(e.to_string()))?;
+        self.renderer = renderer;
+
+        self.handles.reset();
+        self.pipeline_cache.reset();
+        self.offscreen = None;
+        self.next_ui_texture = 0;
+        self.current_pipeline = None;
+        self.current_compute = None;
+        self.current_vertex = [None, None, None, None];
+        self.current_index = None;
+        self.current_bind_groups = [None, None, None, None];
+        self.recorded.clear();
+        self.late_begin = None;
         Ok(())
     }
-}
 
-impl Drop for VulkanRenderApi {
-    fn drop(&mut self) {
-        unsafe {
-            let device = &self.renderer.core.device;
+    /// Destroys every object created through the API. The maps are drained, so calling it
+    /// again is a no-op.
+    unsafe fn destroy_resources(&mut self) {
+        let device = &self.renderer.core.device;
 
-            for (_, p) in self.pipelines.drain() {
-                if p.pipeline != vk::Pipeline::null() {
-                    device.destroy_pipeline(p.pipeline, None);
-                }
-                if p.layout != vk::PipelineLayout::null() {
-                    device.destroy_pipeline_layout(p.layout, None);
-                }
+        for (_, p) in self.pipelines.drain() {
+            if p.pipeline != vk::Pipeline::null() {
+                device.destroy_pipeline(p.pipeline, None);
+            }
+            if p.layout != vk::PipelineLayout::null() {
+                device.destroy_pipeline_layout(p.layout, None);
             }
+        }
 
-            for (_, bg) in self.bind_groups.drain() {
-                if bg.pool != vk::DescriptorPool::null() {
-                    device.destroy_descriptor_pool(bg.pool, None);
-                }
-                let _ = bg.layout;
+        for (_, bg) in self.bind_groups.drain() {
+            if bg.pool != vk::DescriptorPool::null() {
+                device.destroy_descriptor_pool(bg.pool, None);
             }
+            let _ = bg.layout;
+        }
 
-            for (_, l) in self.bg_layouts.drain() {
-                if l.layout != vk::DescriptorSetLayout::null() {
-                    device.destroy_descriptor_set_layout(l.layout, None);
-                }
+        for (_, l) in self.bg_layouts.drain() {
+            if l.layout != vk::DescriptorSetLayout::null() {
+                device.destroy_descriptor_set_layout(l.layout, None);
             }
+        }
 
-            for (_, s) in self.shaders.drain() {
-                if s.module != vk::ShaderModule::null() {
-                    device.destroy_shader_module(s.module, None);
-                }
+        for (_, s) in self.shaders.drain() {
+            if s.module != vk::ShaderModule::null() {
+                device.destroy_shader_module(s.module, None);
             }
+        }
 
-            for (_, b) in self.buffers.drain() {
-                if b.buffer != vk::Buffer::null() {
-                    device.destroy_buffer(b.buffer, None);
-                }
-                if b.memory != vk::DeviceMemory::null() {
-                    device.free_memory(b.memory, None);
-                }
-                let _ = b.size;
+        for (_, s) in self.samplers.drain() {
+            device.destroy_sampler(s, None);
+        }
+
+        for (_, mut t) in self.targets.drain() {
+            self.renderer.destroy_color_target(&mut t.color);
+        }
+
+        for (_, a) in self.alias_blocks.drain() {
+            device.free_memory(a.block.memory, None);
+        }
+
+        for (_, mut t) in self.textures.drain() {
+            self.renderer.destroy_sampled_image(&mut t);
+        }
+
+        for (_, mut v) in self.virtual_textures.drain() {
+            self.renderer.destroy_virtual_image(&mut v);
+        }
+
+        for (_, mut s) in self.windows.drain() {
+            self.renderer.destroy_window_surface(&mut s);
+        }
+
+        for (_, p) in self.target_passes.drain() {
+            device.destroy_render_pass(p, None);
+        }
+
+        for (_, b) in self.buffers.drain() {
+            if b.buffer != vk::Buffer::null() {
+                device.destroy_buffer(b.buffer, None);
             }
+            if b.memory != vk::DeviceMemory::null() {
+                device.free_memory(b.memory, None);
+            }
+            let _ = b.size;
         }
     }
 }
 
+impl Drop for VulkanRenderApi {
+    fn drop(&mut self) {
+        unsafe { self.destroy_resources() };
+    }
+}
+
 impl RenderApi for VulkanRenderApi {
     fn begin_frame(&mut self, desc: BeginFrameDesc) -> EngineResult<()> {
+        if self.offscreen.take().is_some() {
+            log::warn!("render.vulkan: render target pass left open; discarded");
+        }
         self.recorded.clear();
         self.current_pipeline = None;
+        self.current_compute = None;
         self.current_vertex = [None, None, None, None];
         self.current_index = None;
         self.current_bind_groups = [None, None, None, None];
+        if self.lost.is_some() {
+            return Ok(());
+        }
 
-        self.renderer.begin_frame(desc.clear_color).map_err(|e| EngineError::other(e.to_string()))
+        self.apply_shader_reloads();
+
+        // Main-pass commands are recorded and replayed at `end_frame`, so with low latency the
+        // image acquire (and the wait for its frame slot) moves there too.
+        if self.renderer.latency_mode() == LatencyMode::Low {
+            self.late_begin = Some(desc.clear_color);
+            return Ok(());
+        }
+        self.late_begin = None;
+        let r = self.renderer.begin_frame(desc.clear_color);
+        self.frame_result(r)
     }
 
     #[inline]
@@ -363,9 +936,31 @@ impl RenderApi for VulkanRenderApi {
         self.renderer.set_ui_draw_list(ui);
     }
 
+    #[inline]
+    fn set_debug_draw_list(&mut self, lines: DebugDrawList) {
+        self.renderer.set_debug_draw_list(lines);
+    }
+
     fn end_frame(&mut self) -> EngineResult<()> {
+        if let Some(pass) = self.offscreen.take() {
+            log::warn!("render.vulkan: render target pass left open; discarded");
+            self.recorded = pass.saved;
+        }
+        if self.lost.is_some() {
+            self.recorded.clear();
+            return Ok(());
+        }
+        if let Some(clear) = self.late_begin.take() {
+            let r = self.renderer.begin_frame(clear);
+            self.frame_result(r)?;
+            if self.lost.is_some() {
+                self.recorded.clear();
+                return Ok(());
+            }
+        }
         unsafe { self.flush_recorded()?; }
-        self.renderer.end_frame().map_err(|e| EngineError::other(e.to_string()))
+        let r = self.renderer.end_frame();
+        self.frame_result(r)
     }
 
     fn resize(&mut self, width: u32, height: u32) -> EngineResult<()> {
@@ -374,18 +969,39 @@ impl RenderApi for VulkanRenderApi {
     }
 
     fn create_buffer(&mut self, desc: BufferDesc) -> EngineResult<BufferId> {
-        let id = BufferId::new(self.alloc_u32());
-        unsafe {
+        let direct = desc.usage == BufferUsage::Uniform
+            && desc.memory == MemoryHint::GpuOnly
+            && desc.size <= DIRECT_UNIFORM_MAX
+            && self.renderer.core.direct_upload.is_some();
+
+        let b = unsafe {
             let usage = Self::buffer_usage_flags(desc.usage);
             let props = Self::memory_props(desc.memory);
-            let b = self.create_vk_buffer(desc.size as vk::DeviceSize, usage, props)?;
-            self.buffers.insert(id, b);
-        }
+            let size = desc.size as vk::DeviceSize;
+
+            let direct_props = props
+                | vk::MemoryPropertyFlags::HOST_VISIBLE
+                | vk::MemoryPropertyFlags::HOST_COHERENT;
+            match direct.then(|| self.create_vk_buffer(size, usage, direct_props)) {
+                Some(Ok(b)) => b,
+                Some(Err(e)) => {
+                    log::debug!("render.vulkan: direct uniform fell back to staging: {e}");
+                    self.create_vk_buffer(size, usage, props)?
+                }
+                None => self.create_vk_buffer(size, usage, props)?,
+            }
+        };
+        let id: BufferId = self.handles.alloc(desc.label);
+        self.buffers.insert(id, b);
         Ok(id)
     }
 
     fn destroy_buffer(&mut self, id: BufferId) {
+        if !self.retire(id) {
+            return;
+        }
         if let Some(b) = self.buffers.remove(&id) {
+            self.renderer.forget_buffer(b.buffer);
             unsafe {
                 let device = &self.renderer.core.device;
                 if b.buffer != vk::Buffer::null() {
@@ -399,6 +1015,7 @@ impl RenderApi for VulkanRenderApi {
     }
 
     fn write_buffer(&mut self, id: BufferId, offset: u64, data: &[u8]) -> EngineResult<()> {
+        self.check(id, "write_buffer")?;
         let b = *self
             .buffers
             .get(&id)
@@ -426,283 +1043,331 @@ impl RenderApi for VulkanRenderApi {
                 return Ok(());
             }
 
-            let staging = self.create_vk_buffer(
-                data.len() as vk::DeviceSize,
-                vk::BufferUsageFlags::TRANSFER_SRC,
-                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
-            )?;
-
-            let ptr = device
-                .map_memory(
-                    staging.memory,
-                    0,
-                    data.len() as vk::DeviceSize,
-                    vk::MemoryMapFlags::empty(),
+            let (dst_stage, dst_access) = if b.usage.contains(vk::BufferUsageFlags::STORAGE_BUFFER) {
+                (
+                    vk::PipelineStageFlags::VERTEX_INPUT
+                        | vk::PipelineStageFlags::VERTEX_SHADER
+                        | vk::PipelineStageFlags::FRAGMENT_SHADER
+                        | vk::PipelineStageFlags::COMPUTE_SHADER,
+                    vk::AccessFlags::VERTEX_ATTRIBUTE_READ | vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
                 )
-                .map_err(|e| EngineError::other(e.to_string()))? as *mut u8;
-
-            std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len());
-            device.unmap_memory(staging.memory);
-
-            immediate_submit(
-                device,
-                self.renderer.frames.upload_command_pool,
-                self.renderer.core.queue,
-                |cmd| {
-                    let region = vk::BufferCopy::default()
-                        .src_offset(0)
-                        .dst_offset(offset as vk::DeviceSize)
-                        .size(data.len() as vk::DeviceSize);
-
-                    device.cmd_copy_buffer(cmd, staging.buffer, b.buffer, std::slice::from_ref(&region));
-
-                    let (dst_stage, dst_access) = if b.usage.intersects(
-                        vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::INDEX_BUFFER,
-                    ) {
-                        (
-                            vk::PipelineStageFlags::VERTEX_INPUT,
-                            vk::AccessFlags::VERTEX_ATTRIBUTE_READ | vk::AccessFlags::INDEX_READ,
-                        )
-                    } else if b.usage.contains(vk::BufferUsageFlags::UNIFORM_BUFFER) {
-                        (
-                            vk::PipelineStageFlags::VERTEX_SHADER | vk::PipelineStageFlags::FRAGMENT_SHADER,
-                            vk::AccessFlags::UNIFORM_READ,
-                        )
-                    } else if b.usage.contains(vk::BufferUsageFlags::STORAGE_BUFFER) {
-                        (
-                            vk::PipelineStageFlags::VERTEX_SHADER | vk::PipelineStageFlags::FRAGMENT_SHADER,
-                            vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
-                        )
-                    } else {
-                        (
-                            vk::PipelineStageFlags::ALL_COMMANDS,
-                            vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE,
-                        )
-                    };
-
-                    let barrier = vk::BufferMemoryBarrier::default()
-                        .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
-                        .dst_access_mask(dst_access)
-                        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-                        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-                        .buffer(b.buffer)
-                        .offset(offset as vk::DeviceSize)
-                        .size(data.len() as vk::DeviceSize);
-
-                    device.cmd_pipeline_barrier(
-                        cmd,
-                        vk::PipelineStageFlags::TRANSFER,
-                        dst_stage,
-                        vk::DependencyFlags::empty(),
-                        &[],
-                        std::slice::from_ref(&barrier),
-                        &[],
-                    );
-                },
-            )
-                .map_err(|e| EngineError::other(e.to_string()))?;
+            } else if b.usage.intersects(
+                vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::INDEX_BUFFER,
+            ) {
+                (
+                    vk::PipelineStageFlags::VERTEX_INPUT,
+                    vk::AccessFlags::VERTEX_ATTRIBUTE_READ | vk::AccessFlags::INDEX_READ,
+                )
+            } else if b.usage.contains(vk::BufferUsageFlags::UNIFORM_BUFFER) {
+                (
+                    vk::PipelineStageFlags::VERTEX_SHADER | vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::AccessFlags::UNIFORM_READ,
+                )
+            } else {
+                (
+                    vk::PipelineStageFlags::ALL_COMMANDS,
+                    vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE,
+                )
+            };
 
-            device.destroy_buffer(staging.buffer, None);
-            device.free_memory(staging.memory, None);
+            let target = BufferAcquire {
+                buffer: b.buffer,
+                offset: offset as vk::DeviceSize,
+                size: data.len() as vk::DeviceSize,
+                dst_stage,
+                dst_access,
+            };
+            let whole_buffer = offset == 0 && data.len() as vk::DeviceSize == b.size;
+
+            self.renderer
+                .upload_buffer(data, target, whole_buffer)
+                .map_err(|e| EngineError::other(e.to_string()))?;
         }
 
         Ok(())
     }
 
-    fn create_texture(&mut self, _desc: TextureDesc) -> EngineResult<TextureId> {
-        self.err("VulkanRenderApi: create_texture not implemented (world textures pending)")
-    }
-
-    fn destroy_texture(&mut self, _id: TextureId) {}
+    fn create_texture(&mut self, desc: TextureDesc) -> EngineResult<TextureId> {
+        if desc.usage == TextureUsage::RenderTarget && desc.mip_levels.get() == 1 {
+            return self.create_render_target(desc.extent, desc.format);
+        }
+        if desc.usage != TextureUsage::Sampled {
+            return self.err("create_texture: only Sampled textures and single-mip render targets are supported");
+        }
+        let Some(format) = Self::map_color_format(desc.format) else {
+            return self.err("create_texture: depth formats cannot be sampled textures");
+        };
+        if desc.extent.width == 0 || desc.extent.height == 0 {
+            return self.err("create_texture: zero-sized extent");
+        }
+        let max_mips = 32 - desc.extent.width.max(desc.extent.height).leading_zeros();
+        let mip_levels = desc.mip_levels.get();
+        if mip_levels > max_mips {
+            return self.err(format!("create_texture: {mip_levels} mips exceed the {max_mips} of the extent"));
+        }
+        let bytes_per_texel = match desc.format {
+            TextureFormat::Rgba16Float => 8,
+            _ => 4,
+        };
 
-    fn create_sampler(&mut self, _desc: SamplerDesc) -> EngineResult<SamplerId> {
-        self.err("VulkanRenderApi: create_sampler not implemented (world samplers pending)")
+        let img = unsafe {
+            self.renderer
+                .create_sampled_image(format, bytes_per_texel, desc.extent, mip_levels)
+                .map_err(|e| EngineError::other(e.to_string()))?
+        };
+        let id: TextureId = self.handles.alloc(desc.label.or(Some("texture")));
+        self.textures.insert(id, img);
+        Ok(id)
     }
 
-    fn destroy_sampler(&mut self, _id: SamplerId) {}
-
-    fn create_shader(&mut self, desc: ShaderDesc) -> EngineResult<ShaderId> {
-        let id = ShaderId::new(self.alloc_u32());
+    fn write_texture(&mut self, id: TextureId, mip: u32, data: &[u8]) -> EngineResult<()> {
+        self.check(id, "write_texture")?;
+        let Some(img) = self.textures.get(&id) else {
+            return self.err("write_texture: texture is not a Sampled texture");
+        };
+        let Some(expected) = img.mip_bytes(mip) else {
+            return self.err(format!("write_texture: mip {mip} out of range ({} mips)", img.mip_levels));
+        };
+        if data.len() != expected {
+            return self.err(format!("write_texture: mip {mip} takes {expected} bytes, got {}", data.len()));
+        }
 
         unsafe {
-            let bytes: &[u8] = bytemuck::cast_slice(&desc.spirv);
-
-            let module = create_shader_module(&self.renderer.core.device, bytes)
-                .map_err(|e: crate::error::VkRenderError| EngineError::other(e.to_string()))?;
-
-            let stage = Self::map_stage(desc.stage);
-
-            let entry = CString::new(desc.entry)
-                .map_err(|_| EngineError::other("ShaderDesc.entry must be a valid C string"))?;
-
-            self.shaders.insert(id, VkShader { module, stage, entry });
+            self.renderer
+                .upload_sampled_image(img, mip, data)
+                .map_err(|e| EngineError::other(e.to_string()))
         }
-
-        Ok(id)
     }
 
-    fn destroy_shader(&mut self, id: ShaderId) {
-        if let Some(s) = self.shaders.remove(&id) {
-            unsafe { self.renderer.core.device.destroy_shader_module(s.module, None); }
+    fn destroy_texture(&mut self, id: TextureId) {
+        if !self.retire(id) {
+            return;
         }
-    }
-
-    fn create_pipeline(&mut self, desc: PipelineDesc) -> EngineResult<PipelineId> {
-        let id = PipelineId::new(self.alloc_u32());
-
-        let vs = self.shaders.get(&desc.vs).ok_or_else(|| EngineError::other("create_pipeline: invalid vs"))?.clone();
-        let fs = self.shaders.get(&desc.fs).ok_or_else(|| EngineError::other("create_pipeline: invalid fs"))?.clone();
-
-        let mut set_layouts: Vec<vk::DescriptorSetLayout> = Vec::with_capacity(desc.bind_group_layouts.len());
-        for l_id in &desc.bind_group_layouts {
-            let l = self.bg_layouts.get(l_id).ok_or_else(|| EngineError::other("create_pipeline: invalid bind group layout"))?;
-            set_layouts.push(l.layout);
+        if self.offscreen.as_ref().is_some_and(|p| p.target == id) {
+            log::warn!("render.vulkan: destroying the active render target; pass discarded");
+            self.offscreen = None;
+        }
+        if let Some(mut t) = self.textures.remove(&id) {
+            self.renderer.forget_image(t.alloc.image);
+            // Frames in flight may still sample it.
+            unsafe {
+                if let Err(e) = self.renderer.core.device.device_wait_idle() {
+                    log::warn!("render.vulkan: destroy_texture wait_idle failed: {e}");
+                }
+                self.renderer.destroy_sampled_image(&mut t);
+            }
+            return;
+        }
+        if let Some(mut v) = self.virtual_textures.remove(&id) {
+            unsafe {
+                if let Err(e) = self.renderer.core.device.device_wait_idle() {
+                    log::warn!("render.vulkan: destroy_texture wait_idle failed: {e}");
+                }
+                self.renderer.destroy_virtual_image(&mut v);
+            }
+            return;
         }
+        let Some(mut t) = self.targets.remove(&id) else {
+            return;
+        };
 
+        // Frames in flight may still sample the target (directly or through the UI).
         unsafe {
-            let device = &self.renderer.core.device;
-
-            let layout_ci = vk::PipelineLayoutCreateInfo::default().set_layouts(&set_layouts);
-            let layout = device.create_pipeline_layout(&layout_ci, None).map_err(|e| EngineError::other(e.to_string()))?;
-
-            let stages = [
-                vk::PipelineShaderStageCreateInfo::default().stage(vs.stage).module(vs.module).name(&vs.entry),
-                vk::PipelineShaderStageCreateInfo::default().stage(fs.stage).module(fs.module).name(&fs.entry),
-            ];
-
-            let mut binding_descs: Vec<vk::VertexInputBindingDescription> = Vec::new();
-            let mut attr_descs: Vec<vk::VertexInputAttributeDescription> = Vec::new();
-
-            for (i, l) in desc.vertex_layouts.iter().enumerate() {
-                binding_descs.push(
-                    vk::VertexInputBindingDescription::default()
-                        .binding(i as u32)
-                        .stride(l.stride)
-                        .input_rate(vk::VertexInputRate::VERTEX),
-                );
-
-                for a in &l.attributes {
-                    attr_descs.push(
-                        vk::VertexInputAttributeDescription::default()
-                            .binding(i as u32)
-                            .location(a.location)
-                            .format(Self::map_vertex_format(a.format))
-                            .offset(a.offset),
-                    );
+            if let Err(e) = self.renderer.core.device.device_wait_idle() {
+                log::warn!("render.vulkan: destroy_texture wait_idle failed: {e}");
+            }
+            if let Some(ui) = t.ui {
+                self.renderer.ui_free_texture(ui);
+            }
+            self.renderer.destroy_color_target(&mut t.color);
+
+            if let Some(key) = t.alias {
+                let last = self.alias_blocks.get_mut(&key).is_some_and(|a| {
+                    a.users -= 1;
+                    a.users == 0
+                });
+                if last {
+                    if let Some(a) = self.alias_blocks.remove(&key) {
+                        self.renderer.core.device.free_memory(a.block.memory, None);
+                    }
                 }
             }
+        }
+    }
 
-            let vi = vk::PipelineVertexInputStateCreateInfo::default()
-                .vertex_binding_descriptions(&binding_descs)
-                .vertex_attribute_descriptions(&attr_descs);
-
-            let ia = vk::PipelineInputAssemblyStateCreateInfo::default().topology(Self::map_topology(desc.topology));
-            let vp = vk::PipelineViewportStateCreateInfo::default().viewport_count(1).scissor_count(1);
-
-            let rs = vk::PipelineRasterizationStateCreateInfo::default()
-                .polygon_mode(vk::PolygonMode::FILL)
-                .cull_mode(vk::CullModeFlags::BACK)
-                .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
-                .line_width(1.0);
+    fn create_sampler(&mut self, desc: SamplerDesc) -> EngineResult<SamplerId> {
+        let ci = vk::SamplerCreateInfo::default()
+            .min_filter(Self::map_filter(desc.min_filter))
+            .mag_filter(Self::map_filter(desc.mag_filter))
+            .mipmap_mode(match desc.mip_filter {
+                FilterMode::Nearest => vk::SamplerMipmapMode::NEAREST,
+                FilterMode::Linear => vk::SamplerMipmapMode::LINEAR,
+            })
+            .address_mode_u(Self::map_address_mode(desc.address_u))
+            .address_mode_v(Self::map_address_mode(desc.address_v))
+            .address_mode_w(Self::map_address_mode(desc.address_w))
+            .max_lod(vk::LOD_CLAMP_NONE);
+
+        let sampler = unsafe { self.renderer.core.device.create_sampler(&ci, None) }
+            .map_err(|e| EngineError::other(e.to_string()))?;
 
-            let ms = vk::PipelineMultisampleStateCreateInfo::default().rasterization_samples(vk::SampleCountFlags::TYPE_1);
+        let id: SamplerId = self.handles.alloc(desc.label);
+        self.samplers.insert(id, sampler);
+        Ok(id)
+    }
 
-            let ca = vk::PipelineColorBlendAttachmentState::default()
-                .blend_enable(false)
-                .color_write_mask(
-                    vk::ColorComponentFlags::R
-                        | vk::ColorComponentFlags::G
-                        | vk::ColorComponentFlags::B
-                        | vk::ColorComponentFlags::A,
-                );
+    fn destroy_sampler(&mut self, id: SamplerId) {
+        if !self.retire(id) {
+            return;
+        }
+        if let Some(s) = self.samplers.remove(&id) {
+            unsafe { self.renderer.core.device.destroy_sampler(s, None); }
+        }
+    }
 
-            let cb = vk::PipelineColorBlendStateCreateInfo::default().attachments(std::slice::from_ref(&ca));
+    fn create_shader(&mut self, desc: ShaderDesc) -> EngineResult<ShaderId> {
+        unsafe {
+            let bytes: &[u8] = bytemuck::cast_slice(&desc.spirv);
 
-            let dyn_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
-            let ds = vk::PipelineDynamicStateCreateInfo::default().dynamic_states(&dyn_states);
+            let module = create_shader_module(&self.renderer.core.device, bytes)
+                .map_err(|e: crate::error::VkRenderError| EngineError::other(e.to_string()))?;
 
-            let gp = vk::GraphicsPipelineCreateInfo::default()
-                .stages(&stages)
-                .vertex_input_state(&vi)
-                .input_assembly_state(&ia)
-                .viewport_state(&vp)
-                .rasterization_state(&rs)
-                .multisample_state(&ms)
-                .color_blend_state(&cb)
-                .dynamic_state(&ds)
-                .layout(layout)
-                .render_pass(self.renderer.pipelines.render_pass)
-                .subpass(0);
+            let stage = Self::map_stage(desc.stage);
 
-            let pipelines = device.create_graphics_pipelines(vk::PipelineCache::null(), &[gp], None);
-            let pipeline = match pipelines {
-                Ok(v) => v[0],
-                Err((_, e)) => return Err(EngineError::other(e.to_string())),
-            };
+            let entry = CString::new(desc.entry)
+                .map_err(|_| EngineError::other("ShaderDesc.entry must be a valid C string"))?;
+
+            let id: ShaderId = self.handles.alloc(desc.label);
+            self.shaders.insert(id, VkShader { module, stage, entry });
+            if let Some(asset) = desc.source {
+                self.pipeline_cache.watch_shader(id, asset);
+            }
+            Ok(id)
+        }
+    }
+
+    fn destroy_shader(&mut self, id: ShaderId) {
+        if !self.retire(id) {
+            return;
+        }
+        self.pipeline_cache.forget_shader(id);
+        if let Some(s) = self.shaders.remove(&id) {
+            unsafe { self.renderer.core.device.destroy_shader_module(s.module, None); }
+        }
+    }
+
+    fn create_pipeline(&mut self, desc: PipelineDesc) -> EngineResult<PipelineId> {
+        self.check(desc.vs, "create_pipeline.vs")?;
+        self.check(desc.fs, "create_pipeline.fs")?;
 
-            self.pipelines.insert(id, VkPipeline { pipeline, layout });
+        if let Some(f) = Self::map_color_format(desc.color_format) {
+            if f != self.renderer.swapchain.format {
+                self.ensure_target_pass(f)?;
+            }
         }
 
+        let vk_pipeline = self.build_pipeline(&desc)?;
+        let id: PipelineId = self.handles.alloc(desc.label);
+        self.pipelines.insert(id, vk_pipeline);
+        self.pipeline_cache.insert_pipeline(id, desc);
         Ok(id)
     }
 
     fn destroy_pipeline(&mut self, id: PipelineId) {
+        if !self.retire(id) {
+            return;
+        }
+        self.pipeline_cache.remove_pipeline(id);
         if let Some(p) = self.pipelines.remove(&id) {
-            unsafe {
-                let device = &self.renderer.core.device;
-                if p.pipeline != vk::Pipeline::null() {
-                    device.destroy_pipeline(p.pipeline, None);
-                }
-                if p.layout != vk::PipelineLayout::null() {
-                    device.destroy_pipeline_layout(p.layout, None);
-                }
-            }
+            unsafe { self.destroy_vk_pipeline(p) };
         }
     }
 
     fn create_bind_group_layout(&mut self, desc: BindGroupLayoutDesc) -> EngineResult<BindGroupLayoutId> {
-        let id = BindGroupLayoutId::new(self.alloc_u32());
-
         unsafe {
             let device = &self.renderer.core.device;
 
+            let capacity = self.renderer.core.bindless_textures;
             let mut vk_bindings: Vec<vk::DescriptorSetLayoutBinding> = Vec::with_capacity(desc.bindings.len());
+            let mut binding_flags: Vec<vk::DescriptorBindingFlags> = Vec::with_capacity(desc.bindings.len());
+            let mut has_array = false;
             for (i, k) in desc.bindings.iter().enumerate() {
-                let ty = match k {
-                    BindingKind::Texture2D => vk::DescriptorType::SAMPLED_IMAGE,
-                    BindingKind::Sampler => vk::DescriptorType::SAMPLER,
-                    BindingKind::UniformBuffer => vk::DescriptorType::UNIFORM_BUFFER,
-                    BindingKind::StorageBuffer => vk::DescriptorType::STORAGE_BUFFER,
+                let (ty, count) = match *k {
+                    BindingKind::Texture2D => (vk::DescriptorType::SAMPLED_IMAGE, 1),
+                    BindingKind::Sampler => (vk::DescriptorType::SAMPLER, 1),
+                    BindingKind::UniformBuffer => (vk::DescriptorType::UNIFORM_BUFFER, 1),
+                    BindingKind::StorageBuffer => (vk::DescriptorType::STORAGE_BUFFER, 1),
+                    BindingKind::TextureArray(n) => {
+                        if n == 0 || n > capacity {
+                            return Err(EngineError::other(format!(
+                                "create_bind_group_layout: texture array of {n} exceeds bindless capacity {capacity}"
+                            )));
+                        }
+                        has_array = true;
+                        (vk::DescriptorType::SAMPLED_IMAGE, n)
+                    }
                 };
 
+                binding_flags.push(if matches!(k, BindingKind::TextureArray(_)) {
+                    vk::DescriptorBindingFlags::PARTIALLY_BOUND
+                        | vk::DescriptorBindingFlags::UPDATE_AFTER_BIND
+                        | vk::DescriptorBindingFlags::UPDATE_UNUSED_WHILE_PENDING
+                } else {
+                    vk::DescriptorBindingFlags::empty()
+                });
+
                 vk_bindings.push(
                     vk::DescriptorSetLayoutBinding::default()
                         .binding(i as u32)
                         .descriptor_type(ty)
-                        .descriptor_count(1)
-                        .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT),
+                        .descriptor_count(count)
+                        .stage_flags(
+                            vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT | vk::ShaderStageFlags::COMPUTE,
+                        ),
                 );
             }
 
-            let ci = vk::DescriptorSetLayoutCreateInfo::default().bindings(&vk_bindings);
+            let mut flags_ci = vk::DescriptorSetLayoutBindingFlagsCreateInfo::default().binding_flags(&binding_flags);
+            let mut ci = vk::DescriptorSetLayoutCreateInfo::default().bindings(&vk_bindings);
+            if has_array {
+                ci = ci
+                    .flags(vk::DescriptorSetLayoutCreateFlags::UPDATE_AFTER_BIND_POOL)
+                    .push_next(&mut flags_ci);
+            }
             let layout = device
                 .create_descriptor_set_layout(&ci, None)
                 .map_err(|e| EngineError::other(e.to_string()))?;
 
+            let id: BindGroupLayoutId = self.handles.alloc(desc.label);
             self.bg_layouts.insert(id, VkBgLayout { layout, bindings: desc.bindings });
+            Ok(id)
         }
-
-        Ok(id)
     }
 
     fn destroy_bind_group_layout(&mut self, id: BindGroupLayoutId) {
+        if !self.retire(id) {
+            return;
+        }
         if let Some(l) = self.bg_layouts.remove(&id) {
             unsafe { self.renderer.core.device.destroy_descriptor_set_layout(l.layout, None); }
         }
     }
 
     fn create_bind_group(&mut self, desc: BindGroupDesc) -> EngineResult<BindGroupId> {
-        let id = BindGroupId::new(self.alloc_u32());
+        self.check(desc.layout, "create_bind_group.layout")?;
+        if let Some(bb) = desc.uniform0 {
+            self.check(bb.buffer, "create_bind_group.uniform0")?;
+        }
+        for bb in (0..4).filter_map(|n| desc.storage(n)) {
+            self.check(bb.buffer, "create_bind_group.storage")?;
+        }
+        if let Some(t) = desc.texture0 {
+            self.check(t, "create_bind_group.texture0")?;
+        }
+        if let Some(s) = desc.sampler0 {
+            self.check(s, "create_bind_group.sampler0")?;
+        }
+
         let l = self
             .bg_layouts
             .get(&desc.layout)
@@ -716,13 +1381,18 @@ impl RenderApi for VulkanRenderApi {
             let mut need_samp = 0u32;
             let mut need_ubo = 0u32;
             let mut need_ssbo = 0u32;
+            let mut texture_array = None;
 
-            for k in &l.bindings {
-                match k {
+            for (binding, k) in l.bindings.iter().enumerate() {
+                match *k {
                     BindingKind::Texture2D => need_img += 1,
                     BindingKind::Sampler => need_samp += 1,
                     BindingKind::UniformBuffer => need_ubo += 1,
                     BindingKind::StorageBuffer => need_ssbo += 1,
+                    BindingKind::TextureArray(n) => {
+                        need_img += n;
+                        texture_array = Some((binding as u32, n));
+                    }
                 }
             }
 
@@ -756,9 +1426,12 @@ impl RenderApi for VulkanRenderApi {
                 );
             }
 
-            let pool_ci = vk::DescriptorPoolCreateInfo::default()
+            let mut pool_ci = vk::DescriptorPoolCreateInfo::default()
                 .max_sets(1)
                 .pool_sizes(&pool_sizes);
+            if texture_array.is_some() {
+                pool_ci = pool_ci.flags(vk::DescriptorPoolCreateFlags::UPDATE_AFTER_BIND);
+            }
 
             let pool = device
                 .create_descriptor_pool(&pool_ci, None)
@@ -775,6 +1448,7 @@ impl RenderApi for VulkanRenderApi {
 
             let mut writes: Vec<vk::WriteDescriptorSet> = Vec::new();
             let mut buf_infos: Vec<vk::DescriptorBufferInfo> = Vec::new();
+            let mut img_infos: Vec<vk::DescriptorImageInfo> = Vec::new();
 
             #[derive(Clone, Copy)]
             struct PendingBufWrite {
@@ -783,10 +1457,21 @@ impl RenderApi for VulkanRenderApi {
                 buf_info_index: usize,
             }
 
+            #[derive(Clone, Copy)]
+            struct PendingImgWrite {
+                binding: u32,
+                ty: vk::DescriptorType,
+                img_info_index: usize,
+            }
+
             let mut pending: Vec<PendingBufWrite> = Vec::new();
+            let mut pending_img: Vec<PendingImgWrite> = Vec::new();
+            let mut storage_index = 0usize;
 
             buf_infos.reserve_exact((need_ubo + need_ssbo) as usize);
             pending.reserve_exact((need_ubo + need_ssbo) as usize);
+            img_infos.reserve_exact(l.bindings.len());
+            pending_img.reserve_exact(l.bindings.len());
 
             for (binding, k) in l.bindings.iter().enumerate() {
                 match k {
@@ -811,11 +1496,13 @@ impl RenderApi for VulkanRenderApi {
                         });
                     }
                     BindingKind::StorageBuffer => {
-                        let Some(bb) = desc.storage0 else { continue; };
+                        let n = storage_index;
+                        storage_index += 1;
+                        let Some(bb) = desc.storage(n) else { continue; };
                         let b = *self
                             .buffers
                             .get(&bb.buffer)
-                            .ok_or_else(|| EngineError::other("create_bind_group: invalid storage0 buffer"))?;
+                            .ok_or_else(|| EngineError::other(format!("create_bind_group: invalid storage{n} buffer")))?;
 
                         buf_infos.push(
                             vk::DescriptorBufferInfo::default()
@@ -831,19 +1518,44 @@ impl RenderApi for VulkanRenderApi {
                         });
                     }
                     BindingKind::Texture2D => {
-                        return Err(EngineError::other(
-                            "create_bind_group: Texture2D not implemented (world textures pending)",
-                        ));
+                        let Some(tex) = desc.texture0 else { continue; };
+                        let view = self
+                            .texture_view(tex)
+                            .ok_or_else(|| EngineError::other("create_bind_group: texture0 is not a texture"))?;
+
+                        img_infos.push(
+                            vk::DescriptorImageInfo::default()
+                                .image_view(view)
+                                .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL),
+                        );
+
+                        pending_img.push(PendingImgWrite {
+                            binding: binding as u32,
+                            ty: vk::DescriptorType::SAMPLED_IMAGE,
+                            img_info_index: img_infos.len() - 1,
+                        });
                     }
+                    // Filled slot by slot through `write_bind_group_texture`.
+                    BindingKind::TextureArray(_) => {}
                     BindingKind::Sampler => {
-                        return Err(EngineError::other(
-                            "create_bind_group: Sampler not implemented (world samplers pending)",
-                        ));
+                        let Some(s) = desc.sampler0 else { continue; };
+                        let sampler = *self
+                            .samplers
+                            .get(&s)
+                            .ok_or_else(|| EngineError::other("create_bind_group: invalid sampler0"))?;
+
+                        img_infos.push(vk::DescriptorImageInfo::default().sampler(sampler));
+
+                        pending_img.push(PendingImgWrite {
+                            binding: binding as u32,
+                            ty: vk::DescriptorType::SAMPLER,
+                            img_info_index: img_infos.len() - 1,
+                        });
                     }
                 }
             }
 
-            writes.reserve_exact(pending.len());
+            writes.reserve_exact(pending.len() + pending_img.len());
             for p in pending {
                 let bi_ref = std::slice::from_ref(&buf_infos[p.buf_info_index]);
                 writes.push(
@@ -854,25 +1566,75 @@ impl RenderApi for VulkanRenderApi {
                         .buffer_info(bi_ref),
                 );
             }
+            for p in pending_img {
+                let ii_ref = std::slice::from_ref(&img_infos[p.img_info_index]);
+                writes.push(
+                    vk::WriteDescriptorSet::default()
+                        .dst_set(set)
+                        .dst_binding(p.binding)
+                        .descriptor_type(p.ty)
+                        .image_info(ii_ref),
+                );
+            }
 
             if !writes.is_empty() {
                 device.update_descriptor_sets(&writes, &[]);
             }
 
+            let id: BindGroupId = self.handles.alloc(desc.label);
             self.bind_groups.insert(
                 id,
                 VkBindGroup {
                     set,
                     pool,
                     layout: l.layout,
+                    texture_array,
                 },
             );
+            Ok(id)
         }
+    }
 
-        Ok(id)
+    #[inline]
+    fn bindless_texture_capacity(&self) -> u32 {
+        self.renderer.core.bindless_textures
+    }
+
+    fn write_bind_group_texture(&mut self, group: BindGroupId, index: u32, texture: TextureId) -> EngineResult<()> {
+        self.check(group, "write_bind_group_texture.group")?;
+        self.check(texture, "write_bind_group_texture.texture")?;
+
+        let bg = *self
+            .bind_groups
+            .get(&group)
+            .ok_or_else(|| EngineError::other("write_bind_group_texture: invalid bind group"))?;
+        let Some((binding, len)) = bg.texture_array else {
+            return Err(EngineError::other("write_bind_group_texture: bind group has no texture array"));
+        };
+        if index >= len {
+            return Err(EngineError::other(format!("write_bind_group_texture: index {index} out of range (len={len})")));
+        }
+        let view = self
+            .texture_view(texture)
+            .ok_or_else(|| EngineError::other("write_bind_group_texture: not a texture"))?;
+
+        let info = [vk::DescriptorImageInfo::default()
+            .image_view(view)
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)];
+        let write = vk::WriteDescriptorSet::default()
+            .dst_set(bg.set)
+            .dst_binding(binding)
+            .dst_array_element(index)
+            .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+            .image_info(&info);
+        unsafe { self.renderer.core.device.update_descriptor_sets(&[write], &[]) };
+        Ok(())
     }
 
     fn destroy_bind_group(&mut self, id: BindGroupId) {
+        if !self.retire(id) {
+            return;
+        }
         if let Some(bg) = self.bind_groups.remove(&id) {
             unsafe {
                 if bg.pool != vk::DescriptorPool::null() {
@@ -905,7 +1667,21 @@ impl RenderApi for VulkanRenderApi {
     }
 
     fn set_pipeline(&mut self, pipeline: PipelineId) -> EngineResult<()> {
+        self.check(pipeline, "set_pipeline")?;
         let p = *self.pipelines.get(&pipeline).ok_or_else(|| EngineError::other("set_pipeline: invalid PipelineId"))?;
+        if p.bind_point != vk::PipelineBindPoint::GRAPHICS {
+            return self.err("set_pipeline: compute pipelines are bound with set_compute_pipeline");
+        }
+        if let Some(pass) = &self.offscreen {
+            let want = match pass.target {
+                PassTarget::Texture(t) => self.targets.get(&t).map(|t| t.color.format),
+                PassTarget::Window(w) => self.windows.get(&w).map(|s| s.format),
+            };
+            let have = self.pipeline_cache.desc(pipeline).and_then(|d| Self::map_color_format(d.color_format));
+            if want != have {
+                return self.err("set_pipeline: pipeline color format does not match the pass target");
+            }
+        }
         self.current_pipeline = Some(pipeline);
         self.recorded.push(RecordedCmd::BindPipeline(p.pipeline));
         Ok(())
@@ -915,6 +1691,7 @@ impl RenderApi for VulkanRenderApi {
         if index as usize >= self.current_bind_groups.len() {
             return self.err("set_bind_group: index out of range (max 4)");
         }
+        self.check(group, "set_bind_group")?;
         self.current_bind_groups[index as usize] = Some(group);
         Ok(())
     }
@@ -923,11 +1700,13 @@ impl RenderApi for VulkanRenderApi {
         if slot as usize >= self.current_vertex.len() {
             return self.err("set_vertex_buffer: slot out of range (max 4)");
         }
+        self.check(slice.buffer, "set_vertex_buffer")?;
         self.current_vertex[slot as usize] = Some(slice);
         Ok(())
     }
 
     fn set_index_buffer(&mut self, slice: BufferSlice, format: IndexFormat) -> EngineResult<()> {
+        self.check(slice.buffer, "set_index_buffer")?;
         self.current_index = Some((slice, format));
         Ok(())
     }
@@ -936,15 +1715,7 @@ impl RenderApi for VulkanRenderApi {
         let Some(pipeline_id) = self.current_pipeline else { return self.err("draw: no pipeline bound"); };
         let p = *self.pipelines.get(&pipeline_id).ok_or_else(|| EngineError::other("draw: invalid current pipeline"))?;
 
-        let mut sets = [vk::DescriptorSet::null(); 4];
-        let mut set_count = 0u32;
-        for (i, bg_id) in self.current_bind_groups.iter().enumerate() {
-            if let Some(bg_id) = bg_id {
-                let bg = *self.bind_groups.get(bg_id).ok_or_else(|| EngineError::other("draw: invalid bind group"))?;
-                sets[i] = bg.set;
-                set_count = (i as u32) + 1;
-            }
-        }
+        let (sets, set_count) = self.bound_sets("draw")?;
         if set_count > 0 {
             self.recorded.push(RecordedCmd::BindDescriptorSets { layout: p.layout, first_set: 0, sets, set_count });
         }
@@ -972,15 +1743,7 @@ impl RenderApi for VulkanRenderApi {
         let Some(pipeline_id) = self.current_pipeline else { return self.err("draw_indexed: no pipeline bound"); };
         let p = *self.pipelines.get(&pipeline_id).ok_or_else(|| EngineError::other("draw_indexed: invalid current pipeline"))?;
 
-        let mut sets = [vk::DescriptorSet::null(); 4];
-        let mut set_count = 0u32;
-        for (i, bg_id) in self.current_bind_groups.iter().enumerate() {
-            if let Some(bg_id) = bg_id {
-                let bg = *self.bind_groups.get(bg_id).ok_or_else(|| EngineError::other("draw_indexed: invalid bind group"))?;
-                sets[i] = bg.set;
-                set_count = (i as u32) + 1;
-            }
-        }
+        let (sets, set_count) = self.bound_sets("draw_indexed")?;
         if set_count > 0 {
             self.recorded.push(RecordedCmd::BindDescriptorSets { layout: p.layout, first_set: 0, sets, set_count });
         }
@@ -1012,4 +1775,454 @@ impl RenderApi for VulkanRenderApi {
         self.recorded.push(RecordedCmd::DrawIndexed(args));
         Ok(())
     }
+
+    fn create_compute_pipeline(&mut self, desc: ComputePipelineDesc) -> EngineResult<PipelineId> {
+        self.check(desc.cs, "create_compute_pipeline.cs")?;
+        let vk_pipeline = self.build_compute_pipeline(&desc)?;
+        let id: PipelineId = self.handles.alloc(desc.label);
+        self.pipelines.insert(id, vk_pipeline);
+        self.pipeline_cache.insert_compute_pipeline(id, desc);
+        Ok(id)
+    }
+
+    fn set_compute_pipeline(&mut self, pipeline: PipelineId) -> EngineResult<()> {
+        self.check(pipeline, "set_compute_pipeline")?;
+        let p = *self
+            .pipelines
+            .get(&pipeline)
+            .ok_or_else(|| EngineError::other("set_compute_pipeline: invalid PipelineId"))?;
+        if p.bind_point != vk::PipelineBindPoint::COMPUTE {
+            return self.err("set_compute_pipeline: not a compute pipeline");
+        }
+        self.current_compute = Some(pipeline);
+        Ok(())
+    }
+
+    fn dispatch(&mut self, x: u32, y: u32, z: u32) -> EngineResult<()> {
+        // Submitted right away rather than recorded with the draws, so it also runs ahead of
+        // the draws recorded earlier in the frame.
+        let Some(pipeline_id) = self.current_compute else { return self.err("dispatch: no compute pipeline bound"); };
+        let p = *self.pipelines.get(&pipeline_id).ok_or_else(|| EngineError::other("dispatch: invalid current compute pipeline"))?;
+        let (sets, set_count) = self.bound_sets("dispatch")?;
+        if x == 0 || y == 0 || z == 0 {
+            return Ok(());
+        }
+
+        let r = unsafe {
+            self.renderer
+                .run_compute(|device, cmd| {
+                    device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::COMPUTE, p.pipeline);
+                    if set_count > 0 {
+                        device.cmd_bind_descriptor_sets(
+                            cmd,
+                            vk::PipelineBindPoint::COMPUTE,
+                            p.layout,
+                            0,
+                            &sets[..set_count as usize],
+                            &[],
+                        );
+                    }
+                    device.cmd_dispatch(cmd, x, y, z);
+                })
+        };
+        self.frame_result(r)
+    }
+
+    #[inline]
+    fn handle_validation(&self) -> HandleValidation {
+        self.handles.mode()
+    }
+
+    #[inline]
+    fn set_handle_validation(&mut self, mode: HandleValidation) {
+        self.handles.set_mode(mode);
+    }
+
+    #[inline]
+    fn release_surface(&mut self) {
+        self.renderer.release_swapchain();
+    }
+
+    #[inline]
+    fn device_lost(&self) -> Option<RenderLoss> {
+        self.lost
+    }
+
+    fn recover_device(&mut self, handles: WindowHandles) -> EngineResult<()> {
+        let Some(loss) = self.lost else { return Ok(()); };
+        match loss {
+            RenderLoss::Surface => {
+                if let Err(e) = unsafe { self.renderer.recreate_surface(handles.display, handles.window) } {
+                    // The device may have gone with the surface; the next attempt recreates both.
+                    self.note_loss(&e);
+                    return Err(EngineError::other(e.to_string()));
+                }
+            }
+            RenderLoss::Device => unsafe { self.recreate_device(handles)? },
+        }
+        self.lost = None;
+        log::info!("render.vulkan: recovered from {} loss", loss.as_str());
+        Ok(())
+    }
+
+    #[inline]
+    fn request_frame_capture(&mut self) -> bool {
+        self.renderer.request_capture()
+    }
+
+    #[inline]
+    fn take_frame_capture(&mut self) -> Option<FrameCapture> {
+        self.renderer.take_capture()
+    }
+
+    #[inline]
+    fn texture_compression(&self) -> TextureCompression {
+        self.renderer.texture_compression()
+    }
+
+    fn builtin_shader(&self, shader: BuiltinShader) -> Option<Vec<u32>> {
+        let bytes: &[u8] = match shader {
+            BuiltinShader::SpriteVertex => include_bytes!(concat!(env!("OUT_DIR"), "/sprite.vert.spv")),
+            BuiltinShader::SpriteFragment => include_bytes!(concat!(env!("OUT_DIR"), "/sprite.frag.spv")),
+            BuiltinShader::SpriteFragmentBindless => include_bytes!(concat!(env!("OUT_DIR"), "/sprite_bindless.frag.spv")),
+        };
+        ash::util::read_spv(&mut std::io::Cursor::new(bytes)).ok()
+    }
+
+    #[inline]
+    fn shader_hot_reload(&self) -> bool {
+        self.pipeline_cache.hot_reload()
+    }
+
+    fn set_present_mode(&mut self, mode: PresentMode) -> EngineResult<()> {
+        self.renderer.set_present_mode(mode);
+        Ok(())
+    }
+
+    #[inline]
+    fn present_mode(&self) -> Option<PresentMode> {
+        Some(self.renderer.present_mode())
+    }
+
+    fn set_swapchain_image_count(&mut self, count: Option<u32>) -> EngineResult<()> {
+        self.renderer.set_swapchain_image_count(count);
+        Ok(())
+    }
+
+    #[inline]
+    fn swapchain_image_count(&self) -> Option<u32> {
+        Some(self.renderer.swapchain_image_count()).filter(|&n| n > 0)
+    }
+
+    fn set_latency_mode(&mut self, mode: LatencyMode) -> EngineResult<()> {
+        self.renderer.set_latency_mode(mode);
+        Ok(())
+    }
+
+    #[inline]
+    fn latency_mode(&self) -> Option<LatencyMode> {
+        Some(self.renderer.latency_mode())
+    }
+
+    #[inline]
+    fn gpu_frame_stats(&self) -> Option<GpuFrameStats> {
+        self.renderer.gpu_frame_stats()
+    }
+
+    fn create_render_target(&mut self, extent: Extent2D, format: TextureFormat) -> EngineResult<TextureId> {
+        let Some(vk_format) = Self::map_color_format(format) else {
+            return self.err("create_render_target: depth formats are not supported");
+        };
+        if extent.width == 0 || extent.height == 0 {
+            return self.err("create_render_target: zero-sized extent");
+        }
+
+        let pass = self.ensure_target_pass(vk_format)?;
+        let color = unsafe {
+            self.renderer
+                .create_color_target(
+                    pass,
+                    vk_format,
+                    vk::Extent2D { width: extent.width, height: extent.height },
+                )
+                .map_err(|e| EngineError::other(e.to_string()))?
+        };
+
+        let id: TextureId = self.handles.alloc(Some("render_target"));
+        self.targets.insert(id, VkRenderTarget { color, ui: None, alias: None });
+        Ok(id)
+    }
+
+    fn create_aliased_render_targets(
+        &mut self,
+        targets: &[(Extent2D, TextureFormat)],
+    ) -> EngineResult<AliasedTargets> {
+        if targets.is_empty() {
+            return Ok(AliasedTargets::default());
+        }
+
+        let mut descs = Vec::with_capacity(targets.len());
+        for &(extent, format) in targets {
+            let Some(vk_format) = Self::map_color_format(format) else {
+                return self.err("create_aliased_render_targets: depth formats are not supported");
+            };
+            if extent.width == 0 || extent.height == 0 {
+                return self.err("create_aliased_render_targets: zero-sized extent");
+            }
+            let pass = self.ensure_target_pass(vk_format)?;
+            let extent = vk::Extent2D { width: extent.width, height: extent.height };
+            descs.push((pass, vk_format, extent));
+        }
+
+        let (colors, block) = unsafe {
+            self.renderer
+                .create_aliased_color_targets(&descs)
+                .map_err(|e| EngineError::other(e.to_string()))?
+        };
+
+        let key = self.next_alias_block;
+        self.next_alias_block = self.next_alias_block.wrapping_add(1);
+        self.alias_blocks.insert(key, VkAliasBlock { block, users: colors.len() as u32 });
+
+        let textures = colors
+            .into_iter()
+            .map(|color| {
+                let id: TextureId = self.handles.alloc(Some("render_target"));
+                self.targets.insert(id, VkRenderTarget { color, ui: None, alias: Some(key) });
+                id
+            })
+            .collect();
+
+        Ok(AliasedTargets {
+            textures,
+            memory_bytes: block.size,
+            requested_bytes: block.requested,
+        })
+    }
+
+    fn gpu_memory_stats(&self) -> Option<GpuMemoryStats> {
+        let blocks = self.alias_blocks.values();
+        let transient = blocks.fold(TransientAliasReport::default(), |mut r, a| {
+            r.resources += a.users;
+            r.groups += 1;
+            r.requested_bytes += a.block.requested;
+            r.allocated_bytes += a.block.size;
+            r
+        });
+        Some(GpuMemoryStats { transient })
+    }
+
+    fn begin_render_target(&mut self, target: TextureId, clear_color: Color4) -> EngineResult<()> {
+        self.check(target, "begin_render_target")?;
+        if self.offscreen.is_some() {
+            return self.err("begin_render_target: a render target pass is already open");
+        }
+        if !self.targets.contains_key(&target) {
+            return self.err("begin_render_target: texture is not a render target");
+        }
+
+        self.open_pass(PassTarget::Texture(target), clear_color);
+        Ok(())
+    }
+
+    fn end_render_target(&mut self) -> EngineResult<()> {
+        let target = match self.offscreen.as_ref().map(|p| p.target) {
+            Some(PassTarget::Texture(t)) => t,
+            _ => return self.err("end_render_target: no render target pass is open"),
+        };
+        let Some((_, clear_color, cmds)) = self.close_pass() else {
+            return self.err("end_render_target: no render target pass is open");
+        };
+
+        let t = self
+            .targets
+            .get(&target)
+            .ok_or_else(|| EngineError::other("end_render_target: render target was destroyed"))?
+            .color;
+        let rp = self.ensure_target_pass(t.format)?;
+
+        let r = unsafe {
+            self.renderer
+                .render_offscreen(rp, &t, clear_color, |device, cmd| Self::replay(device, cmd, cmds))
+        };
+        self.frame_result(r)
+    }
+
+    fn ui_texture(&mut self, target: TextureId) -> EngineResult<UiTexId> {
+        self.check(target, "ui_texture")?;
+        let t = *self
+            .targets
+            .get(&target)
+            .ok_or_else(|| EngineError::other("ui_texture: texture is not a render target"))?;
+        if let Some(ui) = t.ui {
+            return Ok(ui);
+        }
+        if self.renderer.ui.desc_pool == vk::DescriptorPool::null() {
+            return self.err("ui_texture: UI overlay is not initialized");
+        }
+
+        let ui = UiTexId::new(reserved::EXTERNAL_BEGIN + self.next_ui_texture);
+        unsafe {
+            self.renderer
+                .ui_register_external(ui, t.color.alloc.view)
+                .map_err(|e| EngineError::other(e.to_string()))?;
+        }
+        self.next_ui_texture += 1;
+
+        if let Some(t) = self.targets.get_mut(&target) {
+            t.ui = Some(ui);
+        }
+        Ok(ui)
+    }
+
+    fn attach_window(
+        &mut self,
+        window: WindowId,
+        handles: WindowHandles,
+        extent: Extent2D,
+    ) -> EngineResult<()> {
+        if self.windows.contains_key(&window) {
+            return self.err("attach_window: window is already attached");
+        }
+        let s = unsafe {
+            self.renderer
+                .create_window_surface(handles.display, handles.window, extent.width, extent.height)
+                .map_err(|e| EngineError::other(e.to_string()))?
+        };
+        log::info!(
+            "render.vulkan: window {} attached {}x{} format={:?}",
+            window.0,
+            extent.width,
+            extent.height,
+            s.format
+        );
+        self.windows.insert(window, s);
+        Ok(())
+    }
+
+    fn detach_window(&mut self, window: WindowId) {
+        if self.offscreen.as_ref().is_some_and(|p| p.target == PassTarget::Window(window)) {
+            log::warn!("render.vulkan: window {} detached with its pass open; discarded", window.0);
+            let _ = self.close_pass();
+        }
+        if let Some(mut s) = self.windows.remove(&window) {
+            unsafe { self.renderer.destroy_window_surface(&mut s) };
+            log::info!("render.vulkan: window {} detached", window.0);
+        }
+    }
+
+    fn resize_window(&mut self, window: WindowId, extent: Extent2D) -> EngineResult<()> {
+        let Some(s) = self.windows.get_mut(&window) else {
+            return self.err("resize_window: window is not attached");
+        };
+        s.set_size(extent.width, extent.height);
+        Ok(())
+    }
+
+    fn begin_window(&mut self, window: WindowId, clear_color: Color4) -> EngineResult<()> {
+        if self.offscreen.is_some() {
+            return self.err("begin_window: a pass is already open");
+        }
+        let Some(s) = self.windows.get_mut(&window) else {
+            return self.err("begin_window: window is not attached");
+        };
+        if s.dirty {
+            unsafe { self.renderer.rebuild_window_swapchain(s) }
+                .map_err(|e| EngineError::other(e.to_string()))?;
+        }
+
+        self.open_pass(PassTarget::Window(window), clear_color);
+        Ok(())
+    }
+
+    fn end_window(&mut self) -> EngineResult<()> {
+        let window = match self.offscreen.as_ref().map(|p| p.target) {
+            Some(PassTarget::Window(w)) => w,
+            _ => return self.err("end_window: no window pass is open"),
+        };
+        let Some((_, clear_color, cmds)) = self.close_pass() else {
+            return self.err("end_window: no window pass is open");
+        };
+
+        let format = self
+            .windows
+            .get(&window)
+            .ok_or_else(|| EngineError::other("end_window: window was detached"))?
+            .format;
+        let rp = self.ensure_target_pass(format)?;
+        let Some(s) = self.windows.get_mut(&window) else {
+            return self.err("end_window: window was detached");
+        };
+
+        let r = unsafe {
+            self.renderer
+                .render_window(s, rp, clear_color, |device, cmd| Self::replay(device, cmd, cmds))
+        };
+        match r {
+            // A lost secondary surface is not the main surface; only a device loss is recovered.
+            Err(e) if e.loss() == Some(RenderLoss::Device) && self.note_loss(&e) => Ok(()),
+            r => r.map_err(|e| EngineError::other(e.to_string())),
+        }
+    }
+
+    #[inline]
+    fn virtual_texture_mode(&self) -> Option<VirtualTextureMode> {
+        Some(self.renderer.virtual_texture_mode())
+    }
+
+    fn create_virtual_texture(
+        &mut self,
+        desc: VirtualTextureDesc,
+    ) -> EngineResult<(TextureId, VirtualTextureLayout)> {
+        let Some(format) = Self::map_color_format(desc.format) else {
+            return self.err("create_virtual_texture: depth formats are not supported");
+        };
+        if desc.extent.width == 0 || desc.extent.height == 0 {
+            return self.err("create_virtual_texture: zero-sized extent");
+        }
+        let bytes_per_texel = match desc.format {
+            TextureFormat::Rgba16Float => 8,
+            _ => 4,
+        };
+
+        let img = unsafe {
+            self.renderer
+                .create_virtual_image(&VirtualImageDesc {
+                    format,
+                    bytes_per_texel,
+                    extent: desc.extent,
+                    mip_levels: desc.mip_levels.get(),
+                    max_resident_pages: desc.max_resident_pages,
+                })
+                .map_err(|e| EngineError::other(e.to_string()))?
+        };
+
+        let layout = img.layout;
+        let id: TextureId = self.handles.alloc(desc.label.or(Some("virtual_texture")));
+        self.virtual_textures.insert(id, img);
+        Ok((id, layout))
+    }
+
+    fn update_virtual_texture(
+        &mut self,
+        texture: TextureId,
+        updates: &[PageUpdate<'_>],
+    ) -> EngineResult<()> {
+        self.check(texture, "update_virtual_texture")?;
+        let Some(img) = self.virtual_textures.get_mut(&texture) else {
+            return self.err("update_virtual_texture: texture is not a virtual texture");
+        };
+
+        unsafe {
+            self.renderer
+                .update_virtual_image(img, updates)
+                .map_err(|e| EngineError::other(e.to_string()))
+        }
+    }
+
+    fn window_format(&self, window: WindowId) -> Option<TextureFormat> {
+        self.windows
+            .get(&window)
+            .and_then(|s| Self::unmap_color_format(s.format))
+    }
 }
@@ -2,6 +2,7 @@ use crate::error::{VkRenderError, VkResult};
 
 use ash::vk;
 use ash::{Device, Instance};
+use newengine_core::render::TextureCompression;
 use std::ffi::CStr;
 
 #[inline]
@@ -78,28 +79,214 @@ pub(super) fn pick_physical_device(
     ))
 }
 
+/// Picks a transfer-only queue family (DMA engine), if the device exposes one.
+pub(super) fn find_transfer_queue_family(
+    instance: &Instance,
+    physical_device: vk::PhysicalDevice,
+    graphics_family: u32,
+) -> Option<u32> {
+    let qprops = unsafe { instance.get_physical_device_queue_family_properties(physical_device) };
+
+    qprops
+        .iter()
+        .enumerate()
+        .find(|(i, q)| {
+            *i as u32 != graphics_family
+                && q.queue_count > 0
+                && q.queue_flags.contains(vk::QueueFlags::TRANSFER)
+                && !q.queue_flags.intersects(vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE)
+        })
+        .map(|(i, _)| i as u32)
+}
+
+/// Timeline semaphores are core in Vulkan 1.2 but still an optional feature bit.
+pub(super) fn supports_timeline_semaphores(
+    instance: &Instance,
+    physical_device: vk::PhysicalDevice,
+) -> bool {
+    let props = unsafe { instance.get_physical_device_properties(physical_device) };
+    let api = props.api_version;
+    if vk::api_version_major(api) == 1 && vk::api_version_minor(api) < 2 {
+        return false;
+    }
+
+    let mut features12 = vk::PhysicalDeviceVulkan12Features::default();
+    let mut features = vk::PhysicalDeviceFeatures2::default().push_next(&mut features12);
+    unsafe { instance.get_physical_device_features2(physical_device, &mut features) };
+
+    features12.timeline_semaphore == vk::TRUE
+}
+
+/// Upper bound for a descriptor-indexed texture array, far above what UI and sprites use.
+const MAX_BINDLESS_TEXTURES: u32 = 16 * 1024;
+
+/// Largest sampled-image array the device can index from a shader and update while bound,
+/// or 0 without the Vulkan 1.2 descriptor indexing features this needs.
+pub(super) fn bindless_texture_capacity(
+    instance: &Instance,
+    physical_device: vk::PhysicalDevice,
+) -> u32 {
+    let props = unsafe { instance.get_physical_device_properties(physical_device) };
+    let api = props.api_version;
+    if vk::api_version_major(api) == 1 && vk::api_version_minor(api) < 2 {
+        return 0;
+    }
+
+    let mut features12 = vk::PhysicalDeviceVulkan12Features::default();
+    let mut features = vk::PhysicalDeviceFeatures2::default().push_next(&mut features12);
+    unsafe { instance.get_physical_device_features2(physical_device, &mut features) };
+    if features12.runtime_descriptor_array != vk::TRUE
+        || features12.descriptor_binding_partially_bound != vk::TRUE
+        || features12.descriptor_binding_sampled_image_update_after_bind != vk::TRUE
+        || features12.descriptor_binding_update_unused_while_pending != vk::TRUE
+        || features12.shader_sampled_image_array_non_uniform_indexing != vk::TRUE
+    {
+        return 0;
+    }
+
+    let mut props12 = vk::PhysicalDeviceVulkan12Properties::default();
+    let mut props2 = vk::PhysicalDeviceProperties2::default().push_next(&mut props12);
+    unsafe { instance.get_physical_device_properties2(physical_device, &mut props2) };
+    props12
+        .max_per_stage_descriptor_update_after_bind_sampled_images
+        .min(props12.max_descriptor_set_update_after_bind_sampled_images)
+        .min(MAX_BINDLESS_TEXTURES)
+}
+
+/// Compressed texture families supported by the device; all of them are enabled at creation.
+pub(super) fn texture_compression_support(
+    instance: &Instance,
+    physical_device: vk::PhysicalDevice,
+) -> TextureCompression {
+    let f = unsafe { instance.get_physical_device_features(physical_device) };
+    TextureCompression {
+        bc: f.texture_compression_bc == vk::TRUE,
+        etc2: f.texture_compression_etc2 == vk::TRUE,
+        astc_ldr: f.texture_compression_astc_ldr == vk::TRUE,
+    }
+}
+
+/// Sparse 2D images with per-page residency, bound through the graphics queue.
+pub(super) fn supports_sparse_textures(
+    instance: &Instance,
+    physical_device: vk::PhysicalDevice,
+    queue_family_index: u32,
+) -> bool {
+    let f = unsafe { instance.get_physical_device_features(physical_device) };
+    if f.sparse_binding != vk::TRUE || f.sparse_residency_image2_d != vk::TRUE {
+        return false;
+    }
+
+    let qprops = unsafe { instance.get_physical_device_queue_family_properties(physical_device) };
+    qprops
+        .get(queue_family_index as usize)
+        .is_some_and(|q| q.queue_flags.contains(vk::QueueFlags::SPARSE_BINDING))
+}
+
+/// Legacy BAR windows expose 256 MiB; a larger host-visible VRAM heap means resizable BAR
+/// (or a UMA device) where the whole heap is CPU-writable.
+const LEGACY_BAR_SIZE: vk::DeviceSize = 256 * 1024 * 1024;
+
+/// Device-local memory the CPU can write directly, used to skip staging copies.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DirectUploadMemory {
+    pub(crate) heap_size: vk::DeviceSize,
+}
+
+impl DirectUploadMemory {
+    /// Large enough for streaming data, not just a handful of small per-frame buffers.
+    #[inline]
+    pub(crate) fn resizable(&self) -> bool {
+        self.heap_size > LEGACY_BAR_SIZE
+    }
+}
+
+/// Finds the largest heap with a `DEVICE_LOCAL | HOST_VISIBLE | HOST_COHERENT` memory type.
+pub(super) fn direct_upload_memory(
+    instance: &Instance,
+    physical_device: vk::PhysicalDevice,
+) -> Option<DirectUploadMemory> {
+    let mem = unsafe { instance.get_physical_device_memory_properties(physical_device) };
+    let want = vk::MemoryPropertyFlags::DEVICE_LOCAL
+        | vk::MemoryPropertyFlags::HOST_VISIBLE
+        | vk::MemoryPropertyFlags::HOST_COHERENT;
+
+    mem.memory_types[..mem.memory_type_count as usize]
+        .iter()
+        .filter(|t| t.property_flags.contains(want))
+        .map(|t| mem.memory_heaps[t.heap_index as usize].size)
+        .max()
+        .map(|heap_size| DirectUploadMemory { heap_size })
+}
+
+pub(super) struct DeviceQueues {
+    pub(super) device: Device,
+    pub(super) graphics: vk::Queue,
+    pub(super) transfer: Option<vk::Queue>,
+}
+
 pub(super) fn create_device(
     instance: &Instance,
     physical_device: vk::PhysicalDevice,
     queue_family_index: u32,
-) -> VkResult<(Device, vk::Queue)> {
+    transfer_family_index: Option<u32>,
+    timeline_semaphores: bool,
+    bindless_textures: bool,
+    compression: TextureCompression,
+    sparse_textures: bool,
+) -> VkResult<DeviceQueues> {
     let queue_priorities = [1.0f32];
 
-    let queue_info = vk::DeviceQueueCreateInfo::default()
+    let mut queue_infos = vec![vk::DeviceQueueCreateInfo::default()
         .queue_family_index(queue_family_index)
-        .queue_priorities(&queue_priorities);
+        .queue_priorities(&queue_priorities)];
+
+    if let Some(family) = transfer_family_index {
+        queue_infos.push(
+            vk::DeviceQueueCreateInfo::default()
+                .queue_family_index(family)
+                .queue_priorities(&queue_priorities),
+        );
+    }
 
     // Enable required device extensions.
     let device_extensions = [ash::khr::swapchain::NAME.as_ptr()];
 
-    let device_info = vk::DeviceCreateInfo::default()
-        .queue_create_infos(std::slice::from_ref(&queue_info))
-        .enabled_extension_names(&device_extensions);
+    let mut features12 = vk::PhysicalDeviceVulkan12Features::default()
+        .timeline_semaphore(timeline_semaphores)
+        .runtime_descriptor_array(bindless_textures)
+        .descriptor_binding_partially_bound(bindless_textures)
+        .descriptor_binding_sampled_image_update_after_bind(bindless_textures)
+        .descriptor_binding_update_unused_while_pending(bindless_textures)
+        .shader_sampled_image_array_non_uniform_indexing(bindless_textures);
+
+    let features = vk::PhysicalDeviceFeatures::default()
+        .texture_compression_bc(compression.bc)
+        .texture_compression_etc2(compression.etc2)
+        .texture_compression_astc_ldr(compression.astc_ldr)
+        .sparse_binding(sparse_textures)
+        .sparse_residency_image2_d(sparse_textures);
+
+    let mut device_info = vk::DeviceCreateInfo::default()
+        .queue_create_infos(&queue_infos)
+        .enabled_extension_names(&device_extensions)
+        .enabled_features(&features);
+
+    // The 1.2 feature struct is only valid on 1.2+ devices.
+    if timeline_semaphores || bindless_textures {
+        device_info = device_info.push_next(&mut features12);
+    }
 
     let device = unsafe { instance.create_device(physical_device, &device_info, None)? };
-    let queue = unsafe { device.get_device_queue(queue_family_index, 0) };
+    let graphics = unsafe { device.get_device_queue(queue_family_index, 0) };
+    let transfer =
+        transfer_family_index.map(|family| unsafe { device.get_device_queue(family, 0) });
 
-    Ok((device, queue))
+    Ok(DeviceQueues {
+        device,
+        graphics,
+        transfer,
+    })
 }
 
 pub(super) fn find_memory_type(
@@ -139,14 +326,27 @@ pub(super) fn create_buffer(
     let buffer = unsafe { device.create_buffer(&info, None)? };
     let req = unsafe { device.get_buffer_memory_requirements(buffer) };
 
-    let mem_type = find_memory_type(instance, physical_device, req.memory_type_bits, props)?;
+    // Callers fall back to other memory on failure, so nothing may leak here.
+    let memory = (|| -> VkResult<vk::DeviceMemory> {
+        let mem_type = find_memory_type(instance, physical_device, req.memory_type_bits, props)?;
 
-    let alloc = vk::MemoryAllocateInfo::default()
-        .allocation_size(req.size)
-        .memory_type_index(mem_type);
+        let alloc = vk::MemoryAllocateInfo::default()
+            .allocation_size(req.size)
+            .memory_type_index(mem_type);
 
-    let memory = unsafe { device.allocate_memory(&alloc, None)? };
-    unsafe { device.bind_buffer_memory(buffer, memory, 0)? };
+        let memory = unsafe { device.allocate_memory(&alloc, None)? };
+        if let Err(e) = unsafe { device.bind_buffer_memory(buffer, memory, 0) } {
+            unsafe { device.free_memory(memory, None) };
+            return Err(e.into());
+        }
+        Ok(memory)
+    })();
 
-    Ok((buffer, memory))
+    match memory {
+        Ok(memory) => Ok((buffer, memory)),
+        Err(e) => {
+            unsafe { device.destroy_buffer(buffer, None) };
+            Err(e)
+        }
+    }
 }
\ No newline at end of file
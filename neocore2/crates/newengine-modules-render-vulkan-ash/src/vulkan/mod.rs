@@ -1,8 +1,9 @@
 mod device;
 mod instance;
 pub(crate) mod pipeline;
-mod resources;
+pub(crate) mod resources;
 mod swapchain;
+pub(crate) mod sync;
 mod text;
 mod ui;
 pub(crate) mod util;
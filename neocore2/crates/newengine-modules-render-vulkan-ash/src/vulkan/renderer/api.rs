@@ -1,8 +1,12 @@
 use crate::error::VkResult;
 use ash::vk;
+use newengine_core::render::{DebugDrawList, LatencyMode, PresentMode, TextureCompression};
 use newengine_ui::draw::UiDrawList;
 
 use super::state::VulkanRenderer;
+use super::types::FrameBuffer;
+use crate::vulkan::device::create_buffer;
+use crate::vulkan::sync::SyncPoint;
 
 impl VulkanRenderer {
     #[inline]
@@ -30,6 +34,59 @@ impl VulkanRenderer {
         self.debug.target_height = height;
     }
 
+    #[inline]
+    pub fn texture_compression(&self) -> TextureCompression {
+        self.core.texture_compression
+    }
+
+    /// Recreates the swapchain with `mode` at the start of the next frame.
+    pub fn set_present_mode(&mut self, mode: PresentMode) {
+        if self.swapchain.requested_present_mode == mode {
+            return;
+        }
+        log::info!("vulkan.swapchain present_mode request={}", mode.as_str());
+        self.swapchain.requested_present_mode = mode;
+        self.debug.swapchain_dirty = true;
+    }
+
+    #[inline]
+    pub fn present_mode(&self) -> PresentMode {
+        self.swapchain.present_mode
+    }
+
+    /// Recreates the swapchain with at least `count` images at the start of the next frame.
+    pub fn set_swapchain_image_count(&mut self, count: Option<u32>) {
+        if self.swapchain.requested_image_count == count {
+            return;
+        }
+        log::info!("vulkan.swapchain image_count request={:?}", count);
+        self.swapchain.requested_image_count = count;
+        self.debug.swapchain_dirty = true;
+    }
+
+    #[inline]
+    pub fn swapchain_image_count(&self) -> u32 {
+        self.swapchain.images.len() as u32
+    }
+
+    pub fn set_latency_mode(&mut self, mode: LatencyMode) {
+        if self.swapchain.latency_mode != mode {
+            log::info!("vulkan.swapchain latency_mode={}", mode.as_str());
+            self.swapchain.latency_mode = mode;
+        }
+    }
+
+    #[inline]
+    pub fn latency_mode(&self) -> LatencyMode {
+        self.swapchain.latency_mode
+    }
+
+    /// Stores debug lines for the next presented frame.
+    #[inline]
+    pub fn set_debug_draw_list(&mut self, lines: DebugDrawList) {
+        self.debug.pending_lines = Some(lines);
+    }
+
     /// Stores UI draw list for the next presented frame.
     #[inline]
     pub fn set_ui_draw_list(&mut self, ui: UiDrawList) {
@@ -39,24 +96,93 @@ impl VulkanRenderer {
     /// Submits a short-lived upload command buffer using a persistent `UploadCtx`.
     ///
     /// This method does NOT call `queue_wait_idle`.
-    /// It returns a fence that will be signaled once the upload work is complete.
+    /// It returns the point at which the upload work is complete. With a dedicated transfer
+    /// queue the work runs there, after every frame submitted so far (write-after-read).
     #[inline]
-    pub unsafe fn submit_upload<F: FnOnce(vk::CommandBuffer)>(&mut self, f: F) -> VkResult<vk::Fence> {
+    pub(crate) unsafe fn submit_upload<F: FnOnce(vk::CommandBuffer)>(&mut self, f: F) -> VkResult<SyncPoint> {
         let idx = self.frames.upload_cursor;
         self.frames.upload_cursor = (self.frames.upload_cursor + 1) % super::state::UPLOAD_CONTEXTS;
 
         let ctx = self.frames.upload_ctxs[idx];
-        ctx.submit_async(&self.core.device, self.core.queue, f)
+
+        let Some(transfer) = self.core.transfer else {
+            return ctx.submit_async(&self.core.device, self.core.queue, None, None, f);
+        };
+
+        let wait = self
+            .frames
+            .frame_timeline
+            .filter(|t| t.last() > 0)
+            .map(|t| (t.semaphore(), t.last()));
+        let signal = self
+            .frames
+            .transfer_timeline
+            .as_mut()
+            .map(|t| (t.semaphore(), t.advance()));
+
+        ctx.submit_async(&self.core.device, transfer.queue, wait, signal, f)
     }
 
-    /// Schedules a staging buffer for destruction after `fence` is signaled.
-    #[inline]
-    pub fn defer_free_staging_buffer(
-        &mut self,
-        fence: vk::Fence,
-        buffer: vk::Buffer,
-        memory: vk::DeviceMemory,
-    ) {
-        self.frames.deferred_free.push_buffer(fence, buffer, memory);
+    /// Creates a CPU-written buffer, placed in host-visible VRAM when `direct` is set and the
+    /// device has it. Falls back to plain host memory when that heap is exhausted.
+    pub(crate) unsafe fn create_host_buffer(
+        &self,
+        size: vk::DeviceSize,
+        usage: vk::BufferUsageFlags,
+        direct: bool,
+    ) -> VkResult<(vk::Buffer, vk::DeviceMemory)> {
+        let host = vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT;
+
+        if direct && self.core.direct_upload.is_some() {
+            match create_buffer(
+                &self.core.instance,
+                self.core.physical_device,
+                &self.core.device,
+                size,
+                usage,
+                host | vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            ) {
+                Ok(v) => return Ok(v),
+                Err(e) => log::debug!(
+                    "vulkan.memory direct upload buffer ({size} bytes) fell back to host memory: {e}"
+                ),
+            }
+        }
+
+        create_buffer(
+            &self.core.instance,
+            self.core.physical_device,
+            &self.core.device,
+            size,
+            usage,
+            host,
+        )
+    }
+
+    /// `b` if it holds `bytes`, otherwise a bigger buffer replacing it. The slot owning `b`
+    /// must have no submit in flight; on error `b` is already destroyed.
+    pub(crate) unsafe fn ensure_frame_buffer(
+        &self,
+        b: FrameBuffer,
+        bytes: vk::DeviceSize,
+        usage: vk::BufferUsageFlags,
+    ) -> VkResult<FrameBuffer> {
+        if b.buf != vk::Buffer::null() && bytes <= b.size {
+            return Ok(b);
+        }
+        self.destroy_frame_buffer(b);
+
+        let size = bytes.max(64 * 1024).next_power_of_two();
+        let (buf, mem) = self.create_host_buffer(size, usage, true)?;
+        Ok(FrameBuffer { buf, mem, size })
+    }
+
+    pub(crate) unsafe fn destroy_frame_buffer(&self, b: FrameBuffer) {
+        if b.buf != vk::Buffer::null() {
+            self.core.device.destroy_buffer(b.buf, None);
+        }
+        if b.mem != vk::DeviceMemory::null() {
+            self.core.device.free_memory(b.mem, None);
+        }
     }
-}
\ No newline at end of file
+}
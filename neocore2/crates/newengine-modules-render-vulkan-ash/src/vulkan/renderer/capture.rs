@@ -0,0 +1,156 @@
+use crate::error::VkResult;
+use crate::vulkan::sync::SyncPoint;
+use crate::vulkan::util::transition_image;
+
+use ash::vk;
+use newengine_core::render::FrameCapture;
+
+use super::super::device::create_buffer;
+use super::state::VulkanRenderer;
+
+/// Host-visible readback buffer filled by the frame's command buffer.
+pub(super) struct PendingCapture {
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    size: vk::DeviceSize,
+    extent: vk::Extent2D,
+    format: vk::Format,
+}
+
+impl VulkanRenderer {
+    /// Arms a readback of the next presented image. Returns false if the surface does not
+    /// allow TRANSFER_SRC on swapchain images or the format is not 8-bit RGBA/BGRA.
+    pub fn request_capture(&mut self) -> bool {
+        if !self.capture_supported() {
+            return false;
+        }
+        self.debug.capture_requested = true;
+        true
+    }
+
+    #[inline]
+    pub fn take_capture(&mut self) -> Option<FrameCapture> {
+        self.debug.captured.take()
+    }
+
+    fn capture_supported(&self) -> bool {
+        let fmt_ok = matches!(
+            self.swapchain.format,
+            vk::Format::B8G8R8A8_UNORM
+                | vk::Format::B8G8R8A8_SRGB
+                | vk::Format::R8G8B8A8_UNORM
+                | vk::Format::R8G8B8A8_SRGB
+        );
+
+        let caps = unsafe {
+            self.core
+                .surface_loader
+                .get_physical_device_surface_capabilities(
+                    self.core.physical_device,
+                    self.core.surface,
+                )
+        };
+
+        fmt_ok
+            && caps.is_ok_and(|c| {
+                c.supported_usage_flags
+                    .contains(vk::ImageUsageFlags::TRANSFER_SRC)
+            })
+    }
+
+    /// Records `COLOR_ATTACHMENT -> TRANSFER_SRC` + image-to-buffer copy. The image is left in
+    /// TRANSFER_SRC_OPTIMAL; the caller transitions it to PRESENT_SRC.
+    pub(super) unsafe fn record_capture(
+        &mut self,
+        cmd: vk::CommandBuffer,
+        image: vk::Image,
+    ) -> VkResult<Option<PendingCapture>> {
+        let extent = self.swapchain.extent;
+        let size = extent.width as vk::DeviceSize * extent.height as vk::DeviceSize * 4;
+        if size == 0 {
+            return Ok(None);
+        }
+
+        let (buffer, memory) = create_buffer(
+            &self.core.instance,
+            self.core.physical_device,
+            &self.core.device,
+            size,
+            vk::BufferUsageFlags::TRANSFER_DST,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+        )?;
+
+        transition_image(
+            &self.core.device,
+            cmd,
+            image,
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        );
+
+        let region = vk::BufferImageCopy::default()
+            .buffer_offset(0)
+            .buffer_row_length(0)
+            .buffer_image_height(0)
+            .image_subresource(vk::ImageSubresourceLayers {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count: 1,
+            })
+            .image_extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            });
+
+        self.core.device.cmd_copy_image_to_buffer(
+            cmd,
+            image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            buffer,
+            std::slice::from_ref(&region),
+        );
+
+        Ok(Some(PendingCapture {
+            buffer,
+            memory,
+            size,
+            extent,
+            format: self.swapchain.format,
+        }))
+    }
+
+    /// Waits for the frame submit and copies the readback into `captured`.
+    ///
+    /// This stalls the CPU for one frame; captures are meant for tests and screenshots.
+    pub(super) unsafe fn finish_capture(
+        &mut self,
+        pending: PendingCapture,
+        submitted: SyncPoint,
+    ) -> VkResult<()> {
+        let device = &self.core.device;
+
+        let res = (|| -> VkResult<Vec<u8>> {
+            submitted.wait(device)?;
+            let ptr = device.map_memory(pending.memory, 0, pending.size, vk::MemoryMapFlags::empty())?;
+            let bytes = std::slice::from_raw_parts(ptr as *const u8, pending.size as usize).to_vec();
+            device.unmap_memory(pending.memory);
+            Ok(bytes)
+        })();
+
+        device.destroy_buffer(pending.buffer, None);
+        device.free_memory(pending.memory, None);
+
+        let bytes = res?;
+        let (w, h) = (pending.extent.width, pending.extent.height);
+        self.debug.captured = Some(match pending.format {
+            vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB => {
+                FrameCapture::from_bgra(w, h, bytes)
+            }
+            _ => FrameCapture::new(w, h, bytes),
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use crate::error::VkResult;
+use crate::vulkan::sync::acquire_buffers;
+use crate::vulkan::util::immediate_submit;
+
+use ash::vk;
+
+use super::state::VulkanRenderer;
+
+/// Graphics stages that read what a dispatch writes, or write what it reads.
+const GRAPHICS_STAGES: vk::PipelineStageFlags = vk::PipelineStageFlags::from_raw(
+    vk::PipelineStageFlags::DRAW_INDIRECT.as_raw()
+        | vk::PipelineStageFlags::VERTEX_INPUT.as_raw()
+        | vk::PipelineStageFlags::VERTEX_SHADER.as_raw()
+        | vk::PipelineStageFlags::FRAGMENT_SHADER.as_raw()
+        | vk::PipelineStageFlags::COMPUTE_SHADER.as_raw(),
+);
+
+const GRAPHICS_READS: vk::AccessFlags = vk::AccessFlags::from_raw(
+    vk::AccessFlags::INDIRECT_COMMAND_READ.as_raw()
+        | vk::AccessFlags::VERTEX_ATTRIBUTE_READ.as_raw()
+        | vk::AccessFlags::INDEX_READ.as_raw()
+        | vk::AccessFlags::UNIFORM_READ.as_raw()
+        | vk::AccessFlags::SHADER_READ.as_raw(),
+);
+
+impl VulkanRenderer {
+    /// Records compute work with `f` and submits it on the graphics queue.
+    ///
+    /// Like `render_offscreen`, the submit completes before this returns. A barrier in front
+    /// keeps the dispatch from overwriting buffers earlier frames still read; one behind makes
+    /// its writes visible to later draws (vertex, index and indirect input included) and
+    /// dispatches. Queued uploads are flushed and transfer-queue buffers acquired first.
+    pub(crate) unsafe fn run_compute<F: FnOnce(&ash::Device, vk::CommandBuffer)>(
+        &mut self,
+        f: F,
+    ) -> VkResult<()> {
+        self.flush_uploads()?;
+        if let Some(t) = self.frames.transfer_timeline.filter(|t| t.last() > 0) {
+            t.point(t.last()).wait(&self.core.device)?;
+        }
+        let acquires = std::mem::take(&mut self.frames.pending_acquires);
+        let families = self
+            .core
+            .transfer
+            .map(|t| (t.family_index, self.core.queue_family_index));
+
+        let timer_pool = self.gpu_offscreen_pool();
+
+        let device = &self.core.device;
+        immediate_submit(device, self.frames.upload_command_pool, self.core.queue, |cmd| {
+            if let Some((src, dst)) = families {
+                acquire_buffers(device, cmd, &acquires, src, dst);
+            }
+
+            if let Some(pool) = timer_pool {
+                device.cmd_reset_query_pool(cmd, pool, 0, 2);
+                device.cmd_write_timestamp(cmd, vk::PipelineStageFlags::TOP_OF_PIPE, pool, 0);
+            }
+
+            let before = vk::MemoryBarrier::default()
+                .src_access_mask(vk::AccessFlags::SHADER_WRITE | vk::AccessFlags::TRANSFER_WRITE)
+                .dst_access_mask(vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE);
+            device.cmd_pipeline_barrier(
+                cmd,
+                GRAPHICS_STAGES | vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::DependencyFlags::empty(),
+                std::slice::from_ref(&before),
+                &[],
+                &[],
+            );
+
+            f(device, cmd);
+
+            let after = vk::MemoryBarrier::default()
+                .src_access_mask(vk::AccessFlags::SHADER_WRITE)
+                .dst_access_mask(GRAPHICS_READS | vk::AccessFlags::SHADER_WRITE);
+            device.cmd_pipeline_barrier(
+                cmd,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                GRAPHICS_STAGES,
+                vk::DependencyFlags::empty(),
+                std::slice::from_ref(&after),
+                &[],
+                &[],
+            );
+
+            if let Some(pool) = timer_pool {
+                device.cmd_write_timestamp(cmd, vk::PipelineStageFlags::BOTTOM_OF_PIPE, pool, 1);
+            }
+        })?;
+
+        if timer_pool.is_some() {
+            self.gpu_offscreen_done("compute");
+        }
+        Ok(())
+    }
+}
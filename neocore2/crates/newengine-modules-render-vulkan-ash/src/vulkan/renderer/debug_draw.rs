@@ -0,0 +1,250 @@
+use crate::error::VkResult;
+use crate::vulkan::pipeline::create_shader_module;
+
+use ash::vk;
+use newengine_core::render::{DebugDrawList, DebugVertex};
+use std::mem;
+use std::ptr;
+
+use super::state::VulkanRenderer;
+use super::types::FrameBuffer;
+
+/// Vertex buffers of the debug lines, one per frame in flight.
+#[derive(Default)]
+pub struct DebugLineResources {
+    pub(crate) buffers: Vec<FrameBuffer>,
+}
+
+unsafe fn create_line_pipeline(
+    device: &ash::Device,
+    render_pass: vk::RenderPass,
+) -> VkResult<(vk::PipelineLayout, vk::Pipeline)> {
+    let vert = create_shader_module(
+        device,
+        include_bytes!(concat!(env!("OUT_DIR"), "/debug_line.vert.spv")),
+    )?;
+    let frag = create_shader_module(
+        device,
+        include_bytes!(concat!(env!("OUT_DIR"), "/debug_line.frag.spv")),
+    )?;
+
+    let entry = std::ffi::CString::new("main").unwrap();
+
+    let stages = [
+        vk::PipelineShaderStageCreateInfo::default()
+            .stage(vk::ShaderStageFlags::VERTEX)
+            .module(vert)
+            .name(&entry),
+        vk::PipelineShaderStageCreateInfo::default()
+            .stage(vk::ShaderStageFlags::FRAGMENT)
+            .module(frag)
+            .name(&entry),
+    ];
+
+    let binding = vk::VertexInputBindingDescription::default()
+        .binding(0)
+        .stride(mem::size_of::<DebugVertex>() as u32)
+        .input_rate(vk::VertexInputRate::VERTEX);
+
+    let attrs = [
+        vk::VertexInputAttributeDescription::default()
+            .location(0)
+            .binding(0)
+            .format(vk::Format::R32G32B32_SFLOAT)
+            .offset(0),
+        vk::VertexInputAttributeDescription::default()
+            .location(1)
+            .binding(0)
+            .format(vk::Format::R8G8B8A8_UNORM)
+            .offset(12),
+    ];
+
+    let vi = vk::PipelineVertexInputStateCreateInfo::default()
+        .vertex_binding_descriptions(std::slice::from_ref(&binding))
+        .vertex_attribute_descriptions(&attrs);
+
+    let ia = vk::PipelineInputAssemblyStateCreateInfo::default()
+        .topology(vk::PrimitiveTopology::LINE_LIST);
+
+    let vp = vk::PipelineViewportStateCreateInfo::default()
+        .viewport_count(1)
+        .scissor_count(1);
+
+    let rs = vk::PipelineRasterizationStateCreateInfo::default()
+        .polygon_mode(vk::PolygonMode::FILL)
+        .cull_mode(vk::CullModeFlags::NONE)
+        .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+        .line_width(1.0);
+
+    let ms = vk::PipelineMultisampleStateCreateInfo::default()
+        .rasterization_samples(vk::SampleCountFlags::TYPE_1);
+
+    let ca = vk::PipelineColorBlendAttachmentState::default()
+        .blend_enable(true)
+        .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
+        .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .color_blend_op(vk::BlendOp::ADD)
+        .src_alpha_blend_factor(vk::BlendFactor::ONE)
+        .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .alpha_blend_op(vk::BlendOp::ADD)
+        .color_write_mask(
+            vk::ColorComponentFlags::R
+                | vk::ColorComponentFlags::G
+                | vk::ColorComponentFlags::B
+                | vk::ColorComponentFlags::A,
+        );
+
+    let cb =
+        vk::PipelineColorBlendStateCreateInfo::default().attachments(std::slice::from_ref(&ca));
+
+    let dyn_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let ds = vk::PipelineDynamicStateCreateInfo::default().dynamic_states(&dyn_states);
+
+    // Push constants: mat4 view_proj
+    let push_ranges = [vk::PushConstantRange::default()
+        .stage_flags(vk::ShaderStageFlags::VERTEX)
+        .offset(0)
+        .size(64)];
+
+    let layout = device.create_pipeline_layout(
+        &vk::PipelineLayoutCreateInfo::default().push_constant_ranges(&push_ranges),
+        None,
+    )?;
+
+    let gp = vk::GraphicsPipelineCreateInfo::default()
+        .stages(&stages)
+        .vertex_input_state(&vi)
+        .input_assembly_state(&ia)
+        .viewport_state(&vp)
+        .rasterization_state(&rs)
+        .multisample_state(&ms)
+        .color_blend_state(&cb)
+        .dynamic_state(&ds)
+        .layout(layout)
+        .render_pass(render_pass)
+        .subpass(0);
+
+    let pipelines = device.create_graphics_pipelines(vk::PipelineCache::null(), &[gp], None);
+    device.destroy_shader_module(vert, None);
+    device.destroy_shader_module(frag, None);
+
+    match pipelines {
+        Ok(v) => Ok((layout, v[0])),
+        Err((_, e)) => {
+            device.destroy_pipeline_layout(layout, None);
+            Err(e.into())
+        }
+    }
+}
+
+impl VulkanRenderer {
+    pub(super) fn init_debug_lines(&mut self) -> VkResult<()> {
+        self.lines.buffers = vec![FrameBuffer::default(); self.frames.frames.len()];
+        unsafe {
+            let (layout, pipeline) =
+                create_line_pipeline(&self.core.device, self.pipelines.render_pass)?;
+            self.pipelines.line_pipeline_layout = layout;
+            self.pipelines.line_pipeline = pipeline;
+        }
+        Ok(())
+    }
+
+    pub(super) unsafe fn destroy_debug_lines(&mut self) {
+        for b in std::mem::take(&mut self.lines.buffers) {
+            self.destroy_frame_buffer(b);
+        }
+
+        if self.pipelines.line_pipeline != vk::Pipeline::null() {
+            self.core
+                .device
+                .destroy_pipeline(self.pipelines.line_pipeline, None);
+            self.pipelines.line_pipeline = vk::Pipeline::null();
+        }
+        if self.pipelines.line_pipeline_layout != vk::PipelineLayout::null() {
+            self.core
+                .device
+                .destroy_pipeline_layout(self.pipelines.line_pipeline_layout, None);
+            self.pipelines.line_pipeline_layout = vk::PipelineLayout::null();
+        }
+    }
+
+    /// Records the lines into the open swapchain pass.
+    ///
+    /// The swapchain pass has no depth attachment, so depth-tested lines are not occluded
+    /// yet; they are drawn first and the `Always` lines on top of them.
+    pub(super) unsafe fn draw_debug_lines(
+        &mut self,
+        cmd: vk::CommandBuffer,
+        list: &DebugDrawList,
+    ) -> VkResult<()> {
+        let count = list.tested.len() + list.always.len();
+        if count == 0 {
+            return Ok(());
+        }
+
+        let bytes = (count * mem::size_of::<DebugVertex>()) as vk::DeviceSize;
+        let slot = self.frames.frame_index;
+        let vb = std::mem::take(&mut self.lines.buffers[slot]);
+        let vb = self.ensure_frame_buffer(vb, bytes, vk::BufferUsageFlags::VERTEX_BUFFER)?;
+        self.lines.buffers[slot] = vb;
+
+        let mapped = self
+            .core
+            .device
+            .map_memory(vb.mem, 0, bytes, vk::MemoryMapFlags::empty())?
+            as *mut DebugVertex;
+        ptr::copy_nonoverlapping(list.tested.as_ptr(), mapped, list.tested.len());
+        ptr::copy_nonoverlapping(
+            list.always.as_ptr(),
+            mapped.add(list.tested.len()),
+            list.always.len(),
+        );
+        self.core.device.unmap_memory(vb.mem);
+
+        // Passes recorded through the RenderApi may have narrowed viewport and scissor.
+        let extent = self.swapchain.extent;
+        let viewport = vk::Viewport {
+            x: 0.0,
+            y: 0.0,
+            width: extent.width as f32,
+            height: extent.height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        };
+        let scissor = vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent,
+        };
+
+        let device = &self.core.device;
+        device.cmd_set_viewport(cmd, 0, std::slice::from_ref(&viewport));
+        device.cmd_set_scissor(cmd, 0, std::slice::from_ref(&scissor));
+        device.cmd_bind_pipeline(
+            cmd,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipelines.line_pipeline,
+        );
+        device.cmd_push_constants(
+            cmd,
+            self.pipelines.line_pipeline_layout,
+            vk::ShaderStageFlags::VERTEX,
+            0,
+            bytemuck::cast_slice(&list.view_proj),
+        );
+        device.cmd_bind_vertex_buffers(cmd, 0, &[vb.buf], &[0]);
+
+        if !list.tested.is_empty() {
+            device.cmd_draw(cmd, list.tested.len() as u32, 1, 0, 0);
+        }
+        if !list.always.is_empty() {
+            device.cmd_draw(
+                cmd,
+                list.always.len() as u32,
+                1,
+                list.tested.len() as u32,
+                0,
+            );
+        }
+        Ok(())
+    }
+}
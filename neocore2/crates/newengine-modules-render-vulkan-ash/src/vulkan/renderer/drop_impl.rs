@@ -7,15 +7,23 @@ impl Drop for VulkanRenderer {
         unsafe {
             let _ = self.core.device.device_wait_idle();
 
+            self.destroy_debug_lines();
             self.destroy_ui_overlay();
             self.destroy_text_overlay();
 
             // Flush deferred frees; device is idle already.
             let _ = self.frames.deferred_free.pump(&self.core.device);
 
+            // Copies that were never flushed are dropped with their one-off staging buffers.
+            self.frames.uploads.discard(&self.core.device);
+            self.frames.staging.destroy(&self.core.device);
+
             for ctx in &mut self.frames.upload_ctxs {
                 ctx.destroy(&self.core.device);
             }
+            for ctx in &mut self.frames.graphics_upload_ctxs {
+                ctx.destroy(&self.core.device);
+            }
 
             if self.frames.upload_command_pool != vk::CommandPool::null() {
                 self.core
@@ -36,6 +44,17 @@ impl Drop for VulkanRenderer {
                 }
             }
 
+            if let Some(t) = self.frames.gpu_timer.as_mut() {
+                t.destroy(&self.core.device);
+            }
+
+            if let Some(t) = self.frames.frame_timeline.as_mut() {
+                t.destroy(&self.core.device);
+            }
+            if let Some(t) = self.frames.transfer_timeline.as_mut() {
+                t.destroy(&self.core.device);
+            }
+
             if self.frames.command_pool != vk::CommandPool::null() {
                 if !self.frames.command_buffers.is_empty() {
                     self.core.device.free_command_buffers(
@@ -1,10 +1,14 @@
 use crate::error::{VkRenderError, VkResult};
+use crate::vulkan::sync::{acquire_buffers, SyncPoint};
 use crate::vulkan::util::transition_image;
 
 use ash::vk;
+use newengine_core::module::current_module_id;
+use newengine_core::render::LatencyMode;
+use newengine_core::trace::{self, TraceKind};
+use std::time::Instant;
 
 use super::state::VulkanRenderer;
-use super::types::FRAMES_IN_FLIGHT;
 
 impl VulkanRenderer {
     pub fn begin_frame(&mut self, clear_rgba: [f32; 4]) -> VkResult<()> {
@@ -17,6 +21,12 @@ impl VulkanRenderer {
             return Err(VkRenderError::InvalidState("begin_frame called while already in frame"));
         }
 
+        // Copies queued since the last frame; the transfer batch must be submitted before its
+        // buffers are acquired below.
+        unsafe {
+            self.flush_uploads()?;
+        }
+
         // If window is minimized or has no drawable area: keep state clean and do nothing.
         if self.debug.target_width == 0 || self.debug.target_height == 0 {
             self.debug.swapchain_dirty = true;
@@ -26,15 +36,22 @@ impl VulkanRenderer {
         // Apply deferred swapchain recreation exactly once at a safe point.
         if self.debug.swapchain_dirty {
             self.debug.swapchain_dirty = false;
+            let t0 = Instant::now();
             unsafe { self.recreate_swapchain()? };
+            trace::record_span(
+                TraceKind::Marker,
+                current_module_id(),
+                "render.swapchain_rebuild",
+                t0,
+                t0.elapsed(),
+            );
         }
 
         let frame = self.frames.frames[self.frames.frame_index];
 
         unsafe {
-            self.core
-                .device
-                .wait_for_fences(&[frame.in_flight], true, u64::MAX)?;
+            frame.submitted.wait(&self.core.device)?;
+            self.gpu_timer_resolve();
         }
 
         let (image_index, _suboptimal) = match unsafe {
@@ -56,17 +73,14 @@ impl VulkanRenderer {
         let idx = image_index as usize;
 
         unsafe {
-            let inflight = self.frames.images_in_flight[idx];
-            if inflight != vk::Fence::null() {
-                self.core
-                    .device
-                    .wait_for_fences(&[inflight], true, u64::MAX)?;
+            self.frames.images_in_flight[idx].wait(&self.core.device)?;
+            if frame.in_flight != vk::Fence::null() {
+                self.core.device.reset_fences(&[frame.in_flight])?;
             }
-            self.frames.images_in_flight[idx] = frame.in_flight;
-            self.core.device.reset_fences(&[frame.in_flight])?;
         }
 
-        let cmd = self.frames.command_buffers[idx];
+        // The slot's previous submit completed above, so its command buffer is free.
+        let cmd = self.frames.command_buffers[self.frames.frame_index];
         let image = self.swapchain.images[idx];
 
         unsafe {
@@ -79,6 +93,20 @@ impl VulkanRenderer {
                 &vk::CommandBufferBeginInfo::default()
                     .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
             )?;
+            self.gpu_timer_begin_frame(cmd);
+
+            // Take ownership of buffers the transfer queue released since the last frame.
+            // The submit waits on the transfer timeline, see `end_frame`.
+            if let Some(transfer) = self.core.transfer {
+                acquire_buffers(
+                    &self.core.device,
+                    cmd,
+                    &self.frames.pending_acquires,
+                    transfer.family_index,
+                    self.core.queue_family_index,
+                );
+                self.frames.pending_acquires.clear();
+            }
 
             let old_layout = self.swapchain.image_layouts[idx];
             transition_image(
@@ -102,6 +130,7 @@ impl VulkanRenderer {
                 })
                 .clear_values(std::slice::from_ref(&clear));
 
+            self.gpu_pass(cmd, "scene");
             self.core
                 .device
                 .cmd_begin_render_pass(cmd, &rp_begin, vk::SubpassContents::INLINE);
@@ -141,15 +170,23 @@ impl VulkanRenderer {
 
         let frame = self.frames.frames[self.frames.frame_index];
         let idx = self.debug.current_swapchain_idx;
-        let cmd = self.frames.command_buffers[idx];
+        let cmd = self.frames.command_buffers[self.frames.frame_index];
         let image = self.swapchain.images[idx];
         let image_index = self.debug.current_image_index;
 
         unsafe {
+            if let Some(lines) = self.debug.pending_lines.take() {
+                if self.pipelines.line_pipeline != vk::Pipeline::null() && !lines.is_empty() {
+                    self.gpu_pass(cmd, "debug_draw");
+                    self.draw_debug_lines(cmd, &lines)?;
+                }
+            }
+
             if self.pipelines.text_pipeline != vk::Pipeline::null()
                 && self.pipelines.text_pipeline_layout != vk::PipelineLayout::null()
                 && !self.debug.debug_text.is_empty()
             {
+                self.gpu_pass(cmd, "debug_text");
                 let debug_text = std::mem::take(&mut self.debug.debug_text);
                 let res = self.draw_text_overlay(cmd, &debug_text);
                 self.debug.debug_text = debug_text;
@@ -163,43 +200,95 @@ impl VulkanRenderer {
                     && self.ui.sampler != vk::Sampler::null();
 
                 if ui_ready {
+                    self.gpu_pass(cmd, "ui");
                     self.ui_upload_and_draw(cmd, &list)?;
                 }
             }
 
             self.core.device.cmd_end_render_pass(cmd);
 
+            let pending_capture = if self.debug.capture_requested {
+                self.debug.capture_requested = false;
+                self.gpu_pass(cmd, "capture");
+                self.record_capture(cmd, image)?
+            } else {
+                None
+            };
+            self.gpu_pass_end(cmd);
+
             transition_image(
                 &self.core.device,
                 cmd,
                 image,
-                vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                if pending_capture.is_some() {
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL
+                } else {
+                    vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL
+                },
                 vk::ImageLayout::PRESENT_SRC_KHR,
             );
             self.swapchain.image_layouts[idx] = vk::ImageLayout::PRESENT_SRC_KHR;
 
             self.core.device.end_command_buffer(cmd)?;
 
-            let wait_stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
-            let wait_sems = [frame.image_available];
-            let signal_sems = [frame.render_finished];
+            // Copies queued while recording (UI textures, in-frame writes) go ahead of the frame.
+            self.flush_uploads()?;
+
+            // Binary semaphores carry no value; their entries in the timeline arrays are ignored.
+            let mut wait_stages = vec![vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
+            let mut wait_sems = vec![frame.image_available];
+            let mut wait_values = vec![0u64];
+            let mut signal_sems = vec![frame.render_finished];
+            let mut signal_values = vec![0u64];
             let cmd_bufs = [cmd];
 
-            let submit_infos = [vk::SubmitInfo::default()
+            if let Some(t) = self.frames.transfer_timeline.filter(|t| t.last() > 0) {
+                wait_stages.push(vk::PipelineStageFlags::ALL_COMMANDS);
+                wait_sems.push(t.semaphore());
+                wait_values.push(t.last());
+            }
+
+            let submitted = match self.frames.frame_timeline.as_mut() {
+                Some(t) => {
+                    let value = t.advance();
+                    signal_sems.push(t.semaphore());
+                    signal_values.push(value);
+                    t.point(value)
+                }
+                None => SyncPoint::Fence(frame.in_flight),
+            };
+
+            let mut timeline_info = vk::TimelineSemaphoreSubmitInfo::default()
+                .wait_semaphore_values(&wait_values)
+                .signal_semaphore_values(&signal_values);
+
+            let mut submit = vk::SubmitInfo::default()
                 .wait_semaphores(&wait_sems)
                 .wait_dst_stage_mask(&wait_stages)
                 .command_buffers(&cmd_bufs)
-                .signal_semaphores(&signal_sems)];
+                .signal_semaphores(&signal_sems);
+            if self.core.timeline_semaphores {
+                submit = submit.push_next(&mut timeline_info);
+            }
 
-            self.core
-                .device
-                .queue_submit(self.core.queue, &submit_infos, frame.in_flight)?;
+            self.core.device.queue_submit(
+                self.core.queue,
+                std::slice::from_ref(&submit),
+                frame.in_flight,
+            )?;
+
+            self.frames.frames[self.frames.frame_index].submitted = submitted;
+            self.frames.images_in_flight[idx] = submitted;
+
+            if let Some(pending) = pending_capture {
+                self.finish_capture(pending, submitted)?;
+            }
 
             let swapchains = [self.swapchain.swapchain];
             let indices = [image_index];
 
             let present_info = vk::PresentInfoKHR::default()
-                .wait_semaphores(&signal_sems)
+                .wait_semaphores(std::slice::from_ref(&frame.render_finished))
                 .swapchains(&swapchains)
                 .image_indices(&indices);
 
@@ -214,9 +303,15 @@ impl VulkanRenderer {
                 }
                 Err(e) => return Err(e.into()),
             }
+
+            // Low latency: no frame is queued behind this one, so the next frame's input is
+            // sampled once the GPU is idle instead of a frame early.
+            if self.swapchain.latency_mode == LatencyMode::Low {
+                submitted.wait(&self.core.device)?;
+            }
         }
 
-        self.frames.frame_index = (self.frames.frame_index + 1) % FRAMES_IN_FLIGHT;
+        self.frames.frame_index = (self.frames.frame_index + 1) % self.frames.frames.len();
         self.debug.in_frame = false;
         Ok(())
     }
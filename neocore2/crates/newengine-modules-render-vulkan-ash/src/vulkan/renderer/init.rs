@@ -1,3 +1,4 @@
+use crate::config::VulkanRenderConfig;
 use crate::error::{VkRenderError, VkResult};
 
 use ash::vk;
@@ -6,13 +7,18 @@ use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
 use std::ffi::CString;
 use std::time::Instant;
 
+use super::debug_draw::DebugLineResources;
+use super::staging::{StagingRing, UploadBatch};
 use super::state::UPLOAD_CONTEXTS;
 use super::state::{
     CoreContext, DebugState, FrameManager, PipelinePack, SwapchainContext, TextOverlayResources,
     UiOverlayResources, VulkanRenderer,
 };
-use super::types::{FrameSync, FRAMES_IN_FLIGHT};
+use super::timing::GpuTimer;
+use super::types::{FrameSync, MAX_FRAMES_IN_FLIGHT};
 use crate::vulkan::resources::{DeferredFree, UploadCtx};
+use crate::vulkan::sync::{SyncPoint, Timeline, TransferQueue};
+use crate::vulkan::text::TextGlyphCache;
 
 use super::super::device::*;
 use super::super::instance::*;
@@ -25,6 +31,7 @@ impl VulkanRenderer {
         window: RawWindowHandle,
         width: u32,
         height: u32,
+        config: VulkanRenderConfig,
     ) -> VkResult<Self> {
         let entry = Entry::load().map_err(|e| VkRenderError::AshWindow(e.to_string()))?;
 
@@ -75,10 +82,85 @@ impl VulkanRenderer {
         let (physical_device, queue_family_index) =
             pick_physical_device(&instance, &surface_loader, surface)?;
 
-        let (device, queue) = create_device(&instance, physical_device, queue_family_index)?;
+        // A dedicated transfer queue needs cross-queue waits, which we only express with
+        // timeline semaphores; without them everything stays on the graphics queue.
+        let timeline_semaphores = supports_timeline_semaphores(&instance, physical_device);
+        let transfer_family_index = if timeline_semaphores {
+            find_transfer_queue_family(&instance, physical_device, queue_family_index)
+        } else {
+            None
+        };
+
+        let texture_compression = texture_compression_support(&instance, physical_device);
+
+        let sparse_supported =
+            supports_sparse_textures(&instance, physical_device, queue_family_index);
+        let sparse_textures = sparse_supported && config.sparse_textures;
+        log::info!(
+            "vulkan.textures sparse_residency={} enabled={}",
+            sparse_supported,
+            sparse_textures
+        );
+
+        let bindless_supported = bindless_texture_capacity(&instance, physical_device);
+        let bindless_textures = if config.bindless_textures {
+            bindless_supported
+        } else {
+            0
+        };
+        log::info!(
+            "vulkan.textures bindless_capacity={} enabled={}",
+            bindless_supported,
+            bindless_textures > 0
+        );
+
+        let direct_upload = direct_upload_memory(&instance, physical_device);
+        log::info!(
+            "vulkan.memory direct_upload={} enabled={} heap_mib={} resizable_bar={}",
+            direct_upload.is_some(),
+            config.direct_upload,
+            direct_upload.map_or(0, |d| d.heap_size >> 20),
+            direct_upload.is_some_and(|d| d.resizable())
+        );
+        let direct_upload = direct_upload.filter(|_| config.direct_upload);
+
+        let DeviceQueues {
+            device,
+            graphics: queue,
+            transfer,
+        } = create_device(
+            &instance,
+            physical_device,
+            queue_family_index,
+            transfer_family_index,
+            timeline_semaphores,
+            bindless_textures > 0,
+            texture_compression,
+            sparse_textures,
+        )?;
+
+        let transfer = transfer_family_index
+            .zip(transfer)
+            .map(|(family_index, queue)| TransferQueue {
+                family_index,
+                queue,
+            });
+
+        log::info!(
+            "vulkan.sync timeline_semaphores={} transfer_queue={:?}",
+            timeline_semaphores,
+            transfer.map(|t| t.family_index)
+        );
+        log::info!(
+            "vulkan.textures compression bc={} etc2={} astc_ldr={}",
+            texture_compression.bc,
+            texture_compression.etc2,
+            texture_compression.astc_ldr
+        );
+
         let swapchain_loader = ash::khr::swapchain::Device::new(&instance, &device);
 
-        let (swapchain, images, format, extent) = create_swapchain(
+        let (swapchain, images, format, extent, present_mode) = create_swapchain(
             &swapchain_loader,
             &surface_loader,
             surface,
@@ -86,6 +168,8 @@ impl VulkanRenderer {
             width,
             height,
             queue_family_index,
+            config.present_mode,
+            config.swapchain_images,
             vk::SwapchainKHR::null(),
         )?;
 
@@ -103,11 +187,14 @@ impl VulkanRenderer {
             None,
         )?;
 
+        let frames_in_flight = config.frames_in_flight.clamp(1, MAX_FRAMES_IN_FLIGHT);
+        log::info!("vulkan.frames in_flight={}", frames_in_flight);
+
         let command_buffers = device.allocate_command_buffers(
             &vk::CommandBufferAllocateInfo::default()
                 .command_pool(command_pool)
                 .level(vk::CommandBufferLevel::PRIMARY)
-                .command_buffer_count(images.len() as u32),
+                .command_buffer_count(frames_in_flight),
         )?;
 
         let upload_command_pool = device.create_command_pool(
@@ -117,35 +204,61 @@ impl VulkanRenderer {
             None,
         )?;
 
-        let mut upload_ctxs = [UploadCtx::default(); UPLOAD_CONTEXTS];
-        for ctx in &mut upload_ctxs {
-            let pool = device.create_command_pool(
-                &vk::CommandPoolCreateInfo::default()
-                    .queue_family_index(queue_family_index)
-                    .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER),
-                None,
-            )?;
-
-            let cmd = device.allocate_command_buffers(
-                &vk::CommandBufferAllocateInfo::default()
-                    .command_pool(pool)
-                    .level(vk::CommandBufferLevel::PRIMARY)
-                    .command_buffer_count(1),
-            )?[0];
-
-            let fence = device.create_fence(
-                &vk::FenceCreateInfo::default().flags(vk::FenceCreateFlags::SIGNALED),
-                None,
-            )?;
+        // Upload contexts submit to the transfer queue when there is one.
+        let upload_family_index = transfer.map_or(queue_family_index, |t| t.family_index);
+
+        let create_upload_ctxs = |family_index: u32| -> VkResult<[UploadCtx; UPLOAD_CONTEXTS]> {
+            let mut ctxs = [UploadCtx::default(); UPLOAD_CONTEXTS];
+            for ctx in &mut ctxs {
+                let pool = device.create_command_pool(
+                    &vk::CommandPoolCreateInfo::default()
+                        .queue_family_index(family_index)
+                        .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER),
+                    None,
+                )?;
+
+                let cmd = device.allocate_command_buffers(
+                    &vk::CommandBufferAllocateInfo::default()
+                        .command_pool(pool)
+                        .level(vk::CommandBufferLevel::PRIMARY)
+                        .command_buffer_count(1),
+                )?[0];
+
+                let fence = device.create_fence(
+                    &vk::FenceCreateInfo::default().flags(vk::FenceCreateFlags::SIGNALED),
+                    None,
+                )?;
+
+                *ctx = UploadCtx { pool, cmd, fence };
+            }
+            Ok(ctxs)
+        };
 
-            *ctx = UploadCtx { pool, cmd, fence };
-        }
+        let upload_ctxs = create_upload_ctxs(upload_family_index)?;
+        // Batches that must stay in order with the frames go to the graphics queue.
+        let graphics_upload_ctxs = if transfer.is_some() {
+            create_upload_ctxs(queue_family_index)?
+        } else {
+            [UploadCtx::default(); UPLOAD_CONTEXTS]
+        };
 
         let make_frame = |device: &Device| -> VkResult<FrameSync> {
             let image_available =
                 device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None)?;
             let render_finished =
                 device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None)?;
+
+            // Acquire/present still need binary semaphores; only the CPU-side frame pacing
+            // moves to the timeline.
+            if timeline_semaphores {
+                return Ok(FrameSync {
+                    image_available,
+                    render_finished,
+                    in_flight: vk::Fence::null(),
+                    submitted: SyncPoint::None,
+                });
+            }
+
             let in_flight = device.create_fence(
                 &vk::FenceCreateInfo::default().flags(vk::FenceCreateFlags::SIGNALED),
                 None,
@@ -154,13 +267,37 @@ impl VulkanRenderer {
                 image_available,
                 render_finished,
                 in_flight,
+                submitted: SyncPoint::Fence(in_flight),
             })
         };
 
-        let frames = [make_frame(&device)?, make_frame(&device)?];
-        let images_in_flight = vec![vk::Fence::null(); images.len()];
+        let frames = (0..frames_in_flight)
+            .map(|_| make_frame(&device))
+            .collect::<VkResult<Vec<_>>>()?;
+        let images_in_flight = vec![SyncPoint::None; images.len()];
+
+        let frame_timeline = if timeline_semaphores {
+            Some(Timeline::new(&device)?)
+        } else {
+            None
+        };
+        let transfer_timeline = if transfer.is_some() {
+            Some(Timeline::new(&device)?)
+        } else {
+            None
+        };
+
+        let gpu_timer = GpuTimer::new(
+            &instance,
+            physical_device,
+            &device,
+            queue_family_index,
+            frames.len(),
+        );
+        log::info!("vulkan.timing gpu_timestamps={}", gpu_timer.is_some());
 
         let core = CoreContext {
+            entry,
             instance,
             surface_loader,
             surface,
@@ -168,7 +305,14 @@ impl VulkanRenderer {
             device,
             queue_family_index,
             queue,
+            transfer,
+            timeline_semaphores,
+            texture_compression,
+            direct_upload,
+            sparse_textures,
+            bindless_textures,
             swapchain_loader,
+            config,
         };
 
         let swapchain = SwapchainContext {
@@ -179,6 +323,10 @@ impl VulkanRenderer {
             extent,
             framebuffers,
             image_layouts,
+            requested_present_mode: config.present_mode,
+            present_mode,
+            requested_image_count: config.swapchain_images,
+            latency_mode: config.latency_mode,
         };
 
         let pipelines = PipelinePack {
@@ -189,6 +337,8 @@ impl VulkanRenderer {
             text_pipeline: vk::Pipeline::null(),
             ui_pipeline_layout: vk::PipelineLayout::null(),
             ui_pipeline: vk::Pipeline::null(),
+            line_pipeline_layout: vk::PipelineLayout::null(),
+            line_pipeline: vk::Pipeline::null(),
         };
 
         let text = TextOverlayResources {
@@ -200,10 +350,9 @@ impl VulkanRenderer {
             font_image_mem: vk::DeviceMemory::null(),
             font_image_view: vk::ImageView::null(),
             font_sampler: vk::Sampler::null(),
+            glyphs: TextGlyphCache::new(),
 
-            vb: vk::Buffer::null(),
-            vb_mem: vk::DeviceMemory::null(),
-            vb_size: 0,
+            vertex_buffers: Vec::new(),
         };
 
         let ui = UiOverlayResources {
@@ -211,24 +360,18 @@ impl VulkanRenderer {
             desc_pool: vk::DescriptorPool::null(),
             sampler: vk::Sampler::null(),
             textures: std::collections::HashMap::new(),
+            shadow: std::collections::HashMap::new(),
+            table: None,
 
-            vb: vk::Buffer::null(),
-            vb_mem: vk::DeviceMemory::null(),
-            vb_size: 0,
-
-            ib: vk::Buffer::null(),
-            ib_mem: vk::DeviceMemory::null(),
-            ib_size: 0,
-
-            staging_buf: vk::Buffer::null(),
-            staging_mem: vk::DeviceMemory::null(),
-            staging_size: 0,
+            vertex_buffers: Vec::new(),
+            index_buffers: Vec::new(),
         };
 
         let debug = DebugState {
             debug_text: String::new(),
             start_time: Instant::now(),
             pending_ui: None,
+            pending_lines: None,
             target_width: width,
             target_height: height,
 
@@ -237,6 +380,9 @@ impl VulkanRenderer {
             in_frame: false,
             current_image_index: 0,
             current_swapchain_idx: 0,
+
+            capture_requested: false,
+            captured: None,
         };
 
         let mut me = Self {
@@ -254,14 +400,26 @@ impl VulkanRenderer {
                 upload_ctxs,
                 upload_cursor: 0,
                 deferred_free: DeferredFree::new(),
+
+                graphics_upload_ctxs,
+                graphics_upload_cursor: 0,
+                staging: StagingRing::default(),
+                uploads: UploadBatch::default(),
+
+                frame_timeline,
+                transfer_timeline,
+                pending_acquires: Vec::new(),
+                gpu_timer,
             },
             text,
             ui,
+            lines: DebugLineResources::default(),
             debug,
         };
 
         me.init_text_overlay()?;
         me.init_ui_overlay()?;
+        me.init_debug_lines()?;
 
         Ok(me)
     }
@@ -1,8 +1,25 @@
 mod api;
+mod capture;
+mod compute;
+mod debug_draw;
 mod frame;
 mod drop_impl;
 mod init;
+mod recovery;
+mod staging;
 mod state;
+mod target;
+mod texture;
+mod timing;
 mod types;
+mod virtual_texture;
+mod window;
+
+pub(crate) use target::{AliasBlock, ColorTarget};
+pub(crate) use staging::ImageUpload;
+pub(crate) use texture::SampledImage;
+pub(crate) use types::FrameBuffer;
+pub(crate) use virtual_texture::{VirtualImage, VirtualImageDesc};
+pub(crate) use window::WindowSurface;
 
 pub use state::VulkanRenderer;
@@ -0,0 +1,96 @@
+use crate::error::{VkRenderError, VkResult};
+use crate::vulkan::sync::SyncPoint;
+
+use ash::vk;
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
+
+use super::state::VulkanRenderer;
+
+impl VulkanRenderer {
+    /// Replaces a lost main-window surface with a new one for `window`. The swapchain is
+    /// rebuilt by the next `begin_frame`; everything else survives.
+    pub(crate) unsafe fn recreate_surface(
+        &mut self,
+        display: RawDisplayHandle,
+        window: RawWindowHandle,
+    ) -> VkResult<()> {
+        self.reset_frame_sync()?;
+        self.destroy_surface();
+
+        let surface = ash_window::create_surface(
+            &self.core.entry,
+            &self.core.instance,
+            display,
+            window,
+            None,
+        )
+        .map_err(|e| VkRenderError::AshWindow(e.to_string()))?;
+
+        let supported = self
+            .core
+            .surface_loader
+            .get_physical_device_surface_support(
+                self.core.physical_device,
+                self.core.queue_family_index,
+                surface,
+            )
+            .unwrap_or(false);
+        if !supported {
+            self.core.surface_loader.destroy_surface(surface, None);
+            return Err(VkRenderError::InvalidState(
+                "recreated surface cannot present from the graphics queue",
+            ));
+        }
+
+        self.core.surface = surface;
+        self.debug.swapchain_dirty = true;
+        log::info!("vulkan.surface recreated");
+        Ok(())
+    }
+
+    /// Destroys the swapchain and the main-window surface, so the window can get a new surface
+    /// (possibly from another device).
+    pub(crate) unsafe fn destroy_surface(&mut self) {
+        // A frame that failed half way never reached `end_frame`'s reset.
+        self.debug.in_frame = false;
+        self.release_swapchain();
+
+        if self.core.surface != vk::SurfaceKHR::null() {
+            self.core
+                .surface_loader
+                .destroy_surface(self.core.surface, None);
+            self.core.surface = vk::SurfaceKHR::null();
+        }
+    }
+
+    /// Forgets the state a failed frame left behind. After the wait nothing is pending, but an
+    /// acquire whose frame never completed leaves its binary semaphore signaled and a failed
+    /// submit leaves its fence reset, so every slot gets new ones.
+    unsafe fn reset_frame_sync(&mut self) -> VkResult<()> {
+        let device = &self.core.device;
+        let _ = device.device_wait_idle();
+
+        for f in &mut self.frames.frames {
+            device.destroy_semaphore(f.image_available, None);
+            device.destroy_semaphore(f.render_finished, None);
+            f.image_available =
+                device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None)?;
+            f.render_finished =
+                device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None)?;
+            f.submitted = SyncPoint::None;
+
+            if f.in_flight != vk::Fence::null() {
+                device.destroy_fence(f.in_flight, None);
+                f.in_flight = device.create_fence(
+                    &vk::FenceCreateInfo::default().flags(vk::FenceCreateFlags::SIGNALED),
+                    None,
+                )?;
+                f.submitted = SyncPoint::Fence(f.in_flight);
+            }
+        }
+        for s in &mut self.frames.images_in_flight {
+            *s = SyncPoint::None;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,484 @@
+use crate::error::{VkRenderError, VkResult};
+use crate::vulkan::sync::{acquire_buffers, release_buffer, BufferAcquire, SyncPoint};
+
+use ash::vk;
+use std::collections::VecDeque;
+
+use super::state::{VulkanRenderer, UPLOAD_CONTEXTS};
+use super::virtual_texture::image_barrier;
+
+/// Capacity of the staging ring. Larger uploads get a one-off staging buffer.
+pub(crate) const STAGING_RING_SIZE: vk::DeviceSize = 32 << 20;
+
+/// Offset alignment of staged data: a multiple of every texel size we upload.
+const STAGING_ALIGN: vk::DeviceSize = 16;
+
+/// Staging memory of the copies submitted by one flush.
+struct StagingRegion {
+    /// Ring position right after the region.
+    end: u64,
+    /// Submits reading the region (transfer, graphics).
+    points: [SyncPoint; 2],
+    /// One-off buffers of oversized uploads in the same flush.
+    oversized: Vec<(vk::Buffer, vk::DeviceMemory)>,
+}
+
+/// Persistent host-visible staging buffer, handed out front to back.
+///
+/// Positions grow monotonically and map onto the buffer modulo its size; an allocation that
+/// would straddle the end starts over at offset 0. Every flush closes the region allocated
+/// since the previous one, and regions free in order once their copies retire.
+#[derive(Default)]
+pub(crate) struct StagingRing {
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    size: vk::DeviceSize,
+    head: u64,
+    tail: u64,
+    regions: VecDeque<StagingRegion>,
+}
+
+impl StagingRing {
+    /// Reserves `len` bytes and returns their offset, or `None` until older regions retire.
+    fn alloc(&mut self, len: vk::DeviceSize) -> Option<vk::DeviceSize> {
+        if self.head == self.tail && self.regions.is_empty() {
+            self.head = 0;
+            self.tail = 0;
+        }
+
+        let mut start = self.head.next_multiple_of(STAGING_ALIGN);
+        if start % self.size + len > self.size {
+            start = start.next_multiple_of(self.size);
+        }
+        if start + len - self.tail > self.size {
+            return None;
+        }
+        self.head = start + len;
+        Some(start % self.size)
+    }
+
+    /// True if bytes were allocated since the last `close`.
+    #[inline]
+    fn has_open_region(&self) -> bool {
+        self.head > self.regions.back().map_or(self.tail, |r| r.end)
+    }
+
+    /// Ends the open region; it frees once every point in `points` completed.
+    fn close(&mut self, points: [SyncPoint; 2], oversized: Vec<(vk::Buffer, vk::DeviceMemory)>) {
+        if !self.has_open_region() && oversized.is_empty() {
+            return;
+        }
+        self.regions.push_back(StagingRegion {
+            end: self.head,
+            points,
+            oversized,
+        });
+    }
+
+    /// Frees the regions whose copies retired.
+    unsafe fn retire(&mut self, device: &ash::Device) -> VkResult<()> {
+        while let Some(r) = self.regions.front() {
+            for p in r.points {
+                if !p.is_complete(device)? {
+                    return Ok(());
+                }
+            }
+            if let Some(r) = self.regions.pop_front() {
+                self.free_region(device, r);
+            }
+        }
+        Ok(())
+    }
+
+    /// Blocks until the oldest region retires. False if no region is in flight.
+    unsafe fn wait_oldest(&mut self, device: &ash::Device) -> VkResult<bool> {
+        let Some(r) = self.regions.pop_front() else {
+            return Ok(false);
+        };
+        for p in r.points {
+            p.wait(device)?;
+        }
+        self.free_region(device, r);
+        Ok(true)
+    }
+
+    unsafe fn free_region(&mut self, device: &ash::Device, r: StagingRegion) {
+        self.tail = self.tail.max(r.end);
+        for (buffer, memory) in r.oversized {
+            device.destroy_buffer(buffer, None);
+            device.free_memory(memory, None);
+        }
+    }
+
+    /// Destroys the ring; the device must be idle.
+    pub(crate) unsafe fn destroy(&mut self, device: &ash::Device) {
+        for r in std::mem::take(&mut self.regions) {
+            self.free_region(device, r);
+        }
+        if self.buffer != vk::Buffer::null() {
+            device.destroy_buffer(self.buffer, None);
+        }
+        if self.memory != vk::DeviceMemory::null() {
+            device.free_memory(self.memory, None);
+        }
+        *self = Self::default();
+    }
+}
+
+/// Buffer copy waiting for the next flush.
+#[derive(Clone, Copy)]
+struct BufferUpload {
+    src: vk::Buffer,
+    src_offset: vk::DeviceSize,
+    target: BufferAcquire,
+}
+
+/// Copy into an image kept in `SHADER_READ_ONLY_OPTIMAL` between uploads. `region` names the
+/// mip and texels; its buffer offset is filled in when the data is staged.
+#[derive(Clone, Copy)]
+pub(crate) struct ImageUpload {
+    pub(crate) image: vk::Image,
+    pub(crate) mip_levels: u32,
+    /// `UNDEFINED` for an image that was never written.
+    pub(crate) old_layout: vk::ImageLayout,
+    pub(crate) region: vk::BufferImageCopy,
+}
+
+/// Copies queued since the last flush. Whole-buffer writes outside a frame go to the transfer
+/// queue (if any); everything else runs on the graphics queue.
+#[derive(Default)]
+pub(crate) struct UploadBatch {
+    transfer: Vec<BufferUpload>,
+    graphics: Vec<BufferUpload>,
+    images: Vec<(vk::Buffer, ImageUpload)>,
+    oversized: Vec<(vk::Buffer, vk::DeviceMemory)>,
+}
+
+impl UploadBatch {
+    /// Frees the one-off staging buffers without submitting; the device must be idle.
+    pub(crate) unsafe fn discard(&mut self, device: &ash::Device) {
+        for (buffer, memory) in self.oversized.drain(..) {
+            device.destroy_buffer(buffer, None);
+            device.free_memory(memory, None);
+        }
+        self.transfer.clear();
+        self.graphics.clear();
+        self.images.clear();
+    }
+}
+
+impl VulkanRenderer {
+    /// Queues a copy of `data` into `target.buffer`, visible to `target.dst_stage` once the
+    /// batch is flushed (at the latest right before the frame is submitted).
+    ///
+    /// Whole-buffer writes outside a frame go through the dedicated transfer queue (if any)
+    /// with a release there and an acquire at the start of the next frame. Partial writes keep
+    /// the rest of the buffer, and writes inside a frame cannot wait for the next acquire, so
+    /// both stay on the graphics queue.
+    pub(crate) unsafe fn upload_buffer(
+        &mut self,
+        data: &[u8],
+        target: BufferAcquire,
+        whole_buffer: bool,
+    ) -> VkResult<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let (src, src_offset) = self.stage(data)?;
+        let upload = BufferUpload {
+            src,
+            src_offset,
+            target,
+        };
+
+        let batch = &mut self.frames.uploads;
+        // A queued graphics copy into the same buffer must not land after this one.
+        let transfer = self.core.transfer.is_some()
+            && whole_buffer
+            && !self.debug.in_frame
+            && !batch
+                .graphics
+                .iter()
+                .any(|u| u.target.buffer == target.buffer);
+        if transfer {
+            batch.transfer.push(upload);
+        } else {
+            batch.graphics.push(upload);
+        }
+        Ok(())
+    }
+
+    /// Queues a copy of `data` into `upload.image`, which is readable by fragment shaders
+    /// again once the batch is flushed.
+    pub(crate) unsafe fn upload_image(
+        &mut self,
+        data: &[u8],
+        mut upload: ImageUpload,
+    ) -> VkResult<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let (src, src_offset) = self.stage(data)?;
+        upload.region = upload.region.buffer_offset(src_offset);
+        self.frames.uploads.images.push((src, upload));
+        Ok(())
+    }
+
+    /// Drops queued copies and a pending ownership acquire for a buffer that is about to be
+    /// destroyed.
+    #[inline]
+    pub(crate) fn forget_buffer(&mut self, buffer: vk::Buffer) {
+        self.frames.pending_acquires.retain(|a| a.buffer != buffer);
+        let batch = &mut self.frames.uploads;
+        batch.transfer.retain(|u| u.target.buffer != buffer);
+        batch.graphics.retain(|u| u.target.buffer != buffer);
+    }
+
+    /// Drops queued copies into an image that is about to be destroyed.
+    #[inline]
+    pub(crate) fn forget_image(&mut self, image: vk::Image) {
+        self.frames.uploads.images.retain(|(_, u)| u.image != image);
+    }
+
+    /// Submits the queued copies: the transfer batch first (the next frame acquires its
+    /// buffers), then the graphics batch. Called at the start and the end of every frame and
+    /// before other submits that may read uploaded data.
+    pub(crate) unsafe fn flush_uploads(&mut self) -> VkResult<()> {
+        let batch = std::mem::take(&mut self.frames.uploads);
+        let mut points = [SyncPoint::None; 2];
+
+        if let Some(transfer) = self.core.transfer.filter(|_| !batch.transfer.is_empty()) {
+            let device = self.core.device.clone();
+            let graphics_family = self.core.queue_family_index;
+            points[0] = self.submit_upload(|cmd| {
+                for u in &batch.transfer {
+                    let t = u.target;
+                    let region = vk::BufferCopy::default()
+                        .src_offset(u.src_offset)
+                        .dst_offset(t.offset)
+                        .size(t.size);
+                    device.cmd_copy_buffer(cmd, u.src, t.buffer, std::slice::from_ref(&region));
+                    release_buffer(
+                        &device,
+                        cmd,
+                        t.buffer,
+                        t.offset,
+                        t.size,
+                        transfer.family_index,
+                        graphics_family,
+                    );
+                }
+            })?;
+
+            for u in &batch.transfer {
+                self.frames
+                    .pending_acquires
+                    .retain(|a| a.buffer != u.target.buffer);
+                self.frames.pending_acquires.push(u.target);
+            }
+        }
+
+        if !batch.graphics.is_empty() || !batch.images.is_empty() {
+            // Buffers still owned by the transfer queue are taken over here, after its copy.
+            let mut acquires = Vec::new();
+            self.frames.pending_acquires.retain(|a| {
+                let hit = batch.graphics.iter().any(|u| u.target.buffer == a.buffer);
+                if hit {
+                    acquires.push(*a);
+                }
+                !hit
+            });
+            if !acquires.is_empty() {
+                if let Some(t) = self.frames.transfer_timeline {
+                    t.point(t.last()).wait(&self.core.device)?;
+                }
+            }
+            let families = self
+                .core
+                .transfer
+                .map(|t| (t.family_index, self.core.queue_family_index));
+
+            let device = self.core.device.clone();
+            points[1] = self.submit_graphics_upload(|cmd| {
+                if let Some((src, dst)) = families {
+                    acquire_buffers(&device, cmd, &acquires, src, dst);
+                }
+                record_graphics_uploads(&device, cmd, &batch.graphics, &batch.images);
+            })?;
+        }
+
+        self.frames.staging.close(points, batch.oversized);
+        Ok(())
+    }
+
+    /// Like `submit_upload`, but always on the graphics queue, behind the frames submitted so
+    /// far.
+    unsafe fn submit_graphics_upload<F: FnOnce(vk::CommandBuffer)>(
+        &mut self,
+        f: F,
+    ) -> VkResult<SyncPoint> {
+        if self.core.transfer.is_none() {
+            return self.submit_upload(f);
+        }
+
+        let idx = self.frames.graphics_upload_cursor;
+        self.frames.graphics_upload_cursor = (idx + 1) % UPLOAD_CONTEXTS;
+        let ctx = self.frames.graphics_upload_ctxs[idx];
+        ctx.submit_async(&self.core.device, self.core.queue, None, None, f)
+    }
+
+    /// Copies `data` into staging memory and returns the buffer and offset holding it.
+    unsafe fn stage(&mut self, data: &[u8]) -> VkResult<(vk::Buffer, vk::DeviceSize)> {
+        let len = data.len() as vk::DeviceSize;
+
+        if len > STAGING_RING_SIZE {
+            let (buffer, memory) =
+                self.create_host_buffer(len, vk::BufferUsageFlags::TRANSFER_SRC, false)?;
+            if let Err(e) = write_staging(&self.core.device, memory, 0, data) {
+                self.core.device.destroy_buffer(buffer, None);
+                self.core.device.free_memory(memory, None);
+                return Err(e);
+            }
+            self.frames.uploads.oversized.push((buffer, memory));
+            return Ok((buffer, 0));
+        }
+
+        if self.frames.staging.buffer == vk::Buffer::null() {
+            // With resizable BAR the copies at least read from VRAM instead of crossing the bus.
+            let direct = self.core.direct_upload.is_some_and(|d| d.resizable());
+            let (buffer, memory) = self.create_host_buffer(
+                STAGING_RING_SIZE,
+                vk::BufferUsageFlags::TRANSFER_SRC,
+                direct,
+            )?;
+            self.frames.staging = StagingRing {
+                buffer,
+                memory,
+                size: STAGING_RING_SIZE,
+                ..StagingRing::default()
+            };
+            log::info!("vulkan.staging ring_bytes={STAGING_RING_SIZE} direct={direct}");
+        }
+
+        self.frames.staging.retire(&self.core.device)?;
+        let offset = loop {
+            if let Some(offset) = self.frames.staging.alloc(len) {
+                break offset;
+            }
+            // Full: submit what is queued so its region can retire, then wait for the oldest.
+            if self.frames.staging.has_open_region() {
+                self.flush_uploads()?;
+                continue;
+            }
+            if !self.frames.staging.wait_oldest(&self.core.device)? {
+                return Err(VkRenderError::InvalidState("staging ring exhausted"));
+            }
+        };
+
+        write_staging(&self.core.device, self.frames.staging.memory, offset, data)?;
+        Ok((self.frames.staging.buffer, offset))
+    }
+}
+
+unsafe fn write_staging(
+    device: &ash::Device,
+    memory: vk::DeviceMemory,
+    offset: vk::DeviceSize,
+    data: &[u8],
+) -> VkResult<()> {
+    let ptr = device.map_memory(
+        memory,
+        offset,
+        data.len() as vk::DeviceSize,
+        vk::MemoryMapFlags::empty(),
+    )? as *mut u8;
+    std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len());
+    device.unmap_memory(memory);
+    Ok(())
+}
+
+unsafe fn record_graphics_uploads(
+    device: &ash::Device,
+    cmd: vk::CommandBuffer,
+    buffers: &[BufferUpload],
+    images: &[(vk::Buffer, ImageUpload)],
+) {
+    // Frames submitted earlier may still read what the copies overwrite.
+    let before = vk::MemoryBarrier::default()
+        .src_access_mask(vk::AccessFlags::MEMORY_WRITE)
+        .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE);
+    device.cmd_pipeline_barrier(
+        cmd,
+        vk::PipelineStageFlags::ALL_COMMANDS,
+        vk::PipelineStageFlags::TRANSFER,
+        vk::DependencyFlags::empty(),
+        std::slice::from_ref(&before),
+        &[],
+        &[],
+    );
+
+    let mut dst_stage = vk::PipelineStageFlags::empty();
+    let mut dst_access = vk::AccessFlags::empty();
+    for u in buffers {
+        let t = u.target;
+        let region = vk::BufferCopy::default()
+            .src_offset(u.src_offset)
+            .dst_offset(t.offset)
+            .size(t.size);
+        device.cmd_copy_buffer(cmd, u.src, t.buffer, std::slice::from_ref(&region));
+        dst_stage |= t.dst_stage;
+        dst_access |= t.dst_access;
+    }
+    if !buffers.is_empty() {
+        let after = vk::MemoryBarrier::default()
+            .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+            .dst_access_mask(dst_access);
+        device.cmd_pipeline_barrier(
+            cmd,
+            vk::PipelineStageFlags::TRANSFER,
+            dst_stage,
+            vk::DependencyFlags::empty(),
+            std::slice::from_ref(&after),
+            &[],
+            &[],
+        );
+    }
+
+    let transfer_dst = (
+        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        vk::PipelineStageFlags::TRANSFER,
+        vk::AccessFlags::TRANSFER_WRITE,
+    );
+    let shader_read = (
+        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        vk::PipelineStageFlags::FRAGMENT_SHADER,
+        vk::AccessFlags::SHADER_READ,
+    );
+    for (src, u) in images {
+        let old = if u.old_layout == vk::ImageLayout::UNDEFINED {
+            (
+                vk::ImageLayout::UNDEFINED,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::AccessFlags::empty(),
+            )
+        } else {
+            (u.old_layout, shader_read.1, shader_read.2)
+        };
+        image_barrier(device, cmd, u.image, u.mip_levels, old, transfer_dst);
+        device.cmd_copy_buffer_to_image(
+            cmd,
+            *src,
+            u.image,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            std::slice::from_ref(&u.region),
+        );
+        image_barrier(
+            device,
+            cmd,
+            u.image,
+            u.mip_levels,
+            transfer_dst,
+            shader_read,
+        );
+    }
+}
@@ -1,15 +1,27 @@
 use ash::vk;
-use newengine_ui::draw::UiDrawList;
+use newengine_core::render::{
+    DebugDrawList, FrameCapture, LatencyMode, PresentMode, TextureCompression,
+};
+use newengine_ui::draw::{UiDrawList, UiTexture};
 use std::collections::HashMap;
 use std::time::Instant;
 
-use super::types::{FrameSync, FRAMES_IN_FLIGHT};
+use super::debug_draw::DebugLineResources;
+use super::staging::{StagingRing, UploadBatch};
+use super::timing::GpuTimer;
+use super::types::{FrameBuffer, FrameSync};
+use crate::config::VulkanRenderConfig;
+use crate::vulkan::device::DirectUploadMemory;
 use crate::vulkan::resources::{DeferredFree, UploadCtx};
-use crate::vulkan::ui::GpuUiTexture;
+use crate::vulkan::sync::{BufferAcquire, SyncPoint, Timeline, TransferQueue};
+use crate::vulkan::text::TextGlyphCache;
+use crate::vulkan::ui::{GpuUiTexture, UiTextureTable};
 
 pub(crate) const UPLOAD_CONTEXTS: usize = 3;
 
 pub struct CoreContext {
+    /// Kept for creating secondary window surfaces.
+    pub(crate) entry: ash::Entry,
     pub(crate) instance: ash::Instance,
 
     pub(crate) surface_loader: ash::khr::surface::Instance,
@@ -21,7 +33,21 @@ pub struct CoreContext {
     pub(crate) queue_family_index: u32,
     pub(crate) queue: vk::Queue,
 
+    /// Dedicated transfer queue; `None` unless the device has one and timeline semaphores.
+    pub(crate) transfer: Option<TransferQueue>,
+    pub(crate) timeline_semaphores: bool,
+    pub(crate) texture_compression: TextureCompression,
+    /// Host-visible VRAM for staging-free uploads; `None` if absent or disabled in config.
+    pub(crate) direct_upload: Option<DirectUploadMemory>,
+    /// Virtual textures use sparse images; `false` means the atlas fallback.
+    pub(crate) sparse_textures: bool,
+    /// Size limit of descriptor-indexed texture arrays; 0 if unsupported or disabled in config.
+    pub(crate) bindless_textures: u32,
+
     pub(crate) swapchain_loader: ash::khr::swapchain::Device,
+
+    /// Settings the renderer was created with; a device lost later is recreated from them.
+    pub(crate) config: VulkanRenderConfig,
 }
 
 pub struct SwapchainContext {
@@ -32,6 +58,13 @@ pub struct SwapchainContext {
     pub(crate) extent: vk::Extent2D,
     pub(crate) framebuffers: Vec<vk::Framebuffer>,
     pub(crate) image_layouts: Vec<vk::ImageLayout>,
+    /// Mode applied on the next recreation; `present_mode` is what the swapchain got.
+    pub(crate) requested_present_mode: PresentMode,
+    pub(crate) present_mode: PresentMode,
+    /// Minimum image count for the next recreation; `None` is the surface minimum + 1.
+    pub(crate) requested_image_count: Option<u32>,
+    /// `Low` waits for the frame's submit right after presenting.
+    pub(crate) latency_mode: LatencyMode,
 }
 
 pub struct PipelinePack {
@@ -45,12 +78,16 @@ pub struct PipelinePack {
 
     pub(crate) ui_pipeline_layout: vk::PipelineLayout,
     pub(crate) ui_pipeline: vk::Pipeline,
+
+    pub(crate) line_pipeline_layout: vk::PipelineLayout,
+    pub(crate) line_pipeline: vk::Pipeline,
 }
 
 pub struct FrameManager {
-    pub(crate) frames: [FrameSync; FRAMES_IN_FLIGHT],
+    /// One per frame in flight; `frame_index` also picks the slot's command buffer.
+    pub(crate) frames: Vec<FrameSync>,
     pub(crate) frame_index: usize,
-    pub(crate) images_in_flight: Vec<vk::Fence>,
+    pub(crate) images_in_flight: Vec<SyncPoint>,
     pub(crate) command_pool: vk::CommandPool,
     pub(crate) command_buffers: Vec<vk::CommandBuffer>,
 
@@ -61,6 +98,26 @@ pub struct FrameManager {
     pub(crate) upload_ctxs: [UploadCtx; UPLOAD_CONTEXTS],
     pub(crate) upload_cursor: usize,
     pub(crate) deferred_free: DeferredFree,
+
+    // Graphics-family upload contexts for batches that stay on the graphics queue; only
+    // created when `upload_ctxs` belong to a dedicated transfer family.
+    pub(crate) graphics_upload_ctxs: [UploadCtx; UPLOAD_CONTEXTS],
+    pub(crate) graphics_upload_cursor: usize,
+
+    // Staging memory of `write_buffer`/texture uploads and the copies queued since the last
+    // flush, see `flush_uploads`.
+    pub(crate) staging: StagingRing,
+    pub(crate) uploads: UploadBatch,
+
+    // Timeline semaphores (when supported): one per queue, signaled by every submit.
+    pub(crate) frame_timeline: Option<Timeline>,
+    pub(crate) transfer_timeline: Option<Timeline>,
+
+    // Buffers released by the transfer queue, acquired at the start of the next frame.
+    pub(crate) pending_acquires: Vec<BufferAcquire>,
+
+    // Per-pass timestamp queries; `None` if the graphics queue has no timestamp support.
+    pub(crate) gpu_timer: Option<GpuTimer>,
 }
 
 pub struct TextOverlayResources {
@@ -72,10 +129,10 @@ pub struct TextOverlayResources {
     pub(crate) font_image_mem: vk::DeviceMemory,
     pub(crate) font_image_view: vk::ImageView,
     pub(crate) font_sampler: vk::Sampler,
+    pub(crate) glyphs: TextGlyphCache,
 
-    pub(crate) vb: vk::Buffer,
-    pub(crate) vb_mem: vk::DeviceMemory,
-    pub(crate) vb_size: vk::DeviceSize,
+    /// Vertex buffer of each frame slot.
+    pub(crate) vertex_buffers: Vec<FrameBuffer>,
 }
 
 pub struct UiOverlayResources {
@@ -84,18 +141,14 @@ pub struct UiOverlayResources {
     pub(crate) sampler: vk::Sampler,
 
     pub(crate) textures: HashMap<u32, GpuUiTexture>,
-
-    pub(crate) vb: vk::Buffer,
-    pub(crate) vb_mem: vk::DeviceMemory,
-    pub(crate) vb_size: vk::DeviceSize,
-
-    pub(crate) ib: vk::Buffer,
-    pub(crate) ib_mem: vk::DeviceMemory,
-    pub(crate) ib_size: vk::DeviceSize,
-
-    pub(crate) staging_buf: vk::Buffer,
-    pub(crate) staging_mem: vk::DeviceMemory,
-    pub(crate) staging_size: vk::DeviceSize,
+    /// CPU copies of the textures the UI sent, uploaded again after a device reset.
+    pub(crate) shadow: HashMap<u32, UiTexture>,
+    /// Descriptor array of every UI texture; `None` uses one set per texture.
+    pub(crate) table: Option<UiTextureTable>,
+
+    /// Vertex and index buffers of each frame slot.
+    pub(crate) vertex_buffers: Vec<FrameBuffer>,
+    pub(crate) index_buffers: Vec<FrameBuffer>,
 }
 
 pub struct DebugState {
@@ -103,6 +156,7 @@ pub struct DebugState {
     pub(crate) start_time: Instant,
 
     pub(crate) pending_ui: Option<UiDrawList>,
+    pub(crate) pending_lines: Option<DebugDrawList>,
 
     pub(crate) target_width: u32,
     pub(crate) target_height: u32,
@@ -114,6 +168,10 @@ pub struct DebugState {
     pub(crate) in_frame: bool,
     pub(crate) current_image_index: u32,
     pub(crate) current_swapchain_idx: usize,
+
+    // Frame capture (readback of the next presented image).
+    pub(crate) capture_requested: bool,
+    pub(crate) captured: Option<FrameCapture>,
 }
 
 pub struct VulkanRenderer {
@@ -123,5 +181,6 @@ pub struct VulkanRenderer {
     pub(crate) frames: FrameManager,
     pub(crate) text: TextOverlayResources,
     pub(crate) ui: UiOverlayResources,
+    pub(crate) lines: DebugLineResources,
     pub(crate) debug: DebugState,
 }
\ No newline at end of file
@@ -0,0 +1,399 @@
+use crate::error::VkResult;
+use crate::vulkan::device::find_memory_type;
+use crate::vulkan::pipeline::create_render_pass;
+use crate::vulkan::resources::ImageAlloc;
+use crate::vulkan::sync::acquire_buffers;
+use crate::vulkan::util::{immediate_submit, transition_image_layout};
+
+use ash::vk;
+
+use super::state::VulkanRenderer;
+
+/// Offscreen color target: an image that is rendered to and then sampled.
+/// Outside of an offscreen pass the image is kept in `SHADER_READ_ONLY_OPTIMAL`.
+#[derive(Clone, Copy)]
+pub(crate) struct ColorTarget {
+    /// `alloc.memory` is null for aliased targets; the block is owned by the API.
+    pub(crate) alloc: ImageAlloc,
+    pub(crate) framebuffer: vk::Framebuffer,
+    pub(crate) format: vk::Format,
+    pub(crate) extent: vk::Extent2D,
+    /// Shares its memory with other targets, so its contents are undefined when a pass opens.
+    pub(crate) aliased: bool,
+}
+
+/// Memory block shared by aliased color targets.
+#[derive(Clone, Copy)]
+pub(crate) struct AliasBlock {
+    pub(crate) memory: vk::DeviceMemory,
+    pub(crate) size: vk::DeviceSize,
+    /// Sum of the bound images' own memory requirements.
+    pub(crate) requested: vk::DeviceSize,
+}
+
+impl VulkanRenderer {
+    /// Render pass for offscreen targets of `format`. It matches the swapchain pass, so a
+    /// pipeline built for either is compatible with both when the formats agree.
+    #[inline]
+    pub(crate) unsafe fn create_target_pass(&self, format: vk::Format) -> VkResult<vk::RenderPass> {
+        create_render_pass(&self.core.device, format)
+    }
+
+    pub(crate) unsafe fn create_color_target(
+        &self,
+        pass: vk::RenderPass,
+        format: vk::Format,
+        extent: vk::Extent2D,
+    ) -> VkResult<ColorTarget> {
+        let device = &self.core.device;
+        let image = self.create_color_image(format, extent)?;
+
+        let memory = (|| -> VkResult<vk::DeviceMemory> {
+            let req = device.get_image_memory_requirements(image);
+            let mem_type = find_memory_type(
+                &self.core.instance,
+                self.core.physical_device,
+                req.memory_type_bits,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+            Ok(device.allocate_memory(
+                &vk::MemoryAllocateInfo::default()
+                    .allocation_size(req.size)
+                    .memory_type_index(mem_type),
+                None,
+            )?)
+        })();
+        let memory = match memory {
+            Ok(m) => m,
+            Err(e) => {
+                device.destroy_image(image, None);
+                return Err(e);
+            }
+        };
+
+        let alloc = ImageAlloc {
+            image,
+            memory,
+            ..ImageAlloc::default()
+        };
+        self.finish_color_target(pass, alloc, format, extent, false)
+    }
+
+    /// Creates one color target per `(pass, format, extent)`, all bound at offset 0 of a
+    /// single block sized and aligned for the largest memory requirement. The caller owns
+    /// the block and frees it after destroying every target.
+    pub(crate) unsafe fn create_aliased_color_targets(
+        &self,
+        targets: &[(vk::RenderPass, vk::Format, vk::Extent2D)],
+    ) -> VkResult<(Vec<ColorTarget>, AliasBlock)> {
+        let device = &self.core.device;
+
+        let mut images = Vec::with_capacity(targets.len());
+        let mut size = 0;
+        let mut requested = 0;
+        let mut type_bits = u32::MAX;
+        let res = (|| -> VkResult<vk::DeviceMemory> {
+            for &(_, format, extent) in targets {
+                let image = self.create_color_image(format, extent)?;
+                images.push(image);
+                let req = device.get_image_memory_requirements(image);
+                // Offset 0 satisfies every alignment; only size and memory type matter.
+                size = size.max(req.size);
+                requested += req.size;
+                type_bits &= req.memory_type_bits;
+            }
+            let mem_type = find_memory_type(
+                &self.core.instance,
+                self.core.physical_device,
+                type_bits,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+            Ok(device.allocate_memory(
+                &vk::MemoryAllocateInfo::default()
+                    .allocation_size(size)
+                    .memory_type_index(mem_type),
+                None,
+            )?)
+        })();
+        let memory = match res {
+            Ok(m) => m,
+            Err(e) => {
+                for image in images {
+                    device.destroy_image(image, None);
+                }
+                return Err(e);
+            }
+        };
+        let block = AliasBlock {
+            memory,
+            size,
+            requested,
+        };
+
+        let mut out: Vec<ColorTarget> = Vec::with_capacity(targets.len());
+        let mut images = images.into_iter();
+        for &(pass, format, extent) in targets {
+            let image = images.next().expect("one image per target");
+            let alloc = ImageAlloc {
+                image,
+                ..ImageAlloc::default()
+            };
+            // `finish_color_target` destroys the image itself when it fails.
+            let res = match device.bind_image_memory(image, memory, 0) {
+                Ok(()) => self.finish_color_target(pass, alloc, format, extent, true),
+                Err(e) => {
+                    device.destroy_image(image, None);
+                    Err(e.into())
+                }
+            };
+            match res {
+                Ok(t) => out.push(t),
+                Err(e) => {
+                    for image in images {
+                        device.destroy_image(image, None);
+                    }
+                    for mut t in out {
+                        self.destroy_color_target(&mut t);
+                    }
+                    device.free_memory(memory, None);
+                    return Err(e);
+                }
+            }
+        }
+        Ok((out, block))
+    }
+
+    unsafe fn create_color_image(
+        &self,
+        format: vk::Format,
+        extent: vk::Extent2D,
+    ) -> VkResult<vk::Image> {
+        let image_info = vk::ImageCreateInfo::default()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(format)
+            .extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(
+                vk::ImageUsageFlags::COLOR_ATTACHMENT
+                    | vk::ImageUsageFlags::SAMPLED
+                    | vk::ImageUsageFlags::TRANSFER_SRC,
+            )
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+        Ok(self.core.device.create_image(&image_info, None)?)
+    }
+
+    /// Creates the view and framebuffer of an image already bound to memory, and moves it to
+    /// `SHADER_READ_ONLY_OPTIMAL`. Destroys `alloc` on failure.
+    unsafe fn finish_color_target(
+        &self,
+        pass: vk::RenderPass,
+        mut alloc: ImageAlloc,
+        format: vk::Format,
+        extent: vk::Extent2D,
+        aliased: bool,
+    ) -> VkResult<ColorTarget> {
+        let device = &self.core.device;
+
+        let res = (|| -> VkResult<vk::Framebuffer> {
+            let view_info = vk::ImageViewCreateInfo::default()
+                .image(alloc.image)
+                .view_type(vk::ImageViewType::TYPE_2D)
+                .format(format)
+                .subresource_range(
+                    vk::ImageSubresourceRange::default()
+                        .aspect_mask(vk::ImageAspectFlags::COLOR)
+                        .base_mip_level(0)
+                        .level_count(1)
+                        .base_array_layer(0)
+                        .layer_count(1),
+                );
+            alloc.view = device.create_image_view(&view_info, None)?;
+
+            let attachments = [alloc.view];
+            let fb_info = vk::FramebufferCreateInfo::default()
+                .render_pass(pass)
+                .attachments(&attachments)
+                .width(extent.width)
+                .height(extent.height)
+                .layers(1);
+            Ok(device.create_framebuffer(&fb_info, None)?)
+        })();
+
+        let framebuffer = match res {
+            Ok(fb) => fb,
+            Err(e) => {
+                alloc.destroy(device);
+                return Err(e);
+            }
+        };
+
+        let target = ColorTarget {
+            alloc,
+            framebuffer,
+            format,
+            extent,
+            aliased,
+        };
+
+        // Sampling a target that was never rendered to must still see a valid layout.
+        let res = immediate_submit(
+            &self.core.device,
+            self.frames.upload_command_pool,
+            self.core.queue,
+            |cmd| {
+                transition_image_layout(
+                    &self.core.device,
+                    cmd,
+                    target.alloc.image,
+                    vk::ImageLayout::UNDEFINED,
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                );
+            },
+        );
+        if let Err(e) = res {
+            let mut target = target;
+            self.destroy_color_target(&mut target);
+            return Err(e);
+        }
+
+        Ok(target)
+    }
+
+    /// Destroys immediately; the caller makes sure no submitted work still uses the target.
+    pub(crate) unsafe fn destroy_color_target(&self, target: &mut ColorTarget) {
+        if target.framebuffer != vk::Framebuffer::null() {
+            self.core.device.destroy_framebuffer(target.framebuffer, None);
+            target.framebuffer = vk::Framebuffer::null();
+        }
+        target.alloc.destroy(&self.core.device);
+    }
+
+    /// Records one offscreen pass into `target` and submits it on the graphics queue.
+    ///
+    /// The submit completes before this returns and leaves the target readable by fragment
+    /// shaders, so later frames (including the UI overlay) can sample it. Queued uploads are
+    /// flushed first; buffers the transfer queue wrote are waited for and acquired.
+    pub(crate) unsafe fn render_offscreen<F: FnOnce(&ash::Device, vk::CommandBuffer)>(
+        &mut self,
+        pass: vk::RenderPass,
+        target: &ColorTarget,
+        clear_rgba: [f32; 4],
+        f: F,
+    ) -> VkResult<()> {
+        self.flush_uploads()?;
+        if let Some(t) = self.frames.transfer_timeline.filter(|t| t.last() > 0) {
+            t.point(t.last()).wait(&self.core.device)?;
+        }
+        let acquires = std::mem::take(&mut self.frames.pending_acquires);
+        let families = self
+            .core
+            .transfer
+            .map(|t| (t.family_index, self.core.queue_family_index));
+
+        let timer_pool = self.gpu_offscreen_pool();
+
+        let device = &self.core.device;
+        immediate_submit(device, self.frames.upload_command_pool, self.core.queue, |cmd| {
+            if let Some((src, dst)) = families {
+                acquire_buffers(device, cmd, &acquires, src, dst);
+            }
+
+            if let Some(pool) = timer_pool {
+                device.cmd_reset_query_pool(cmd, pool, 0, 2);
+                device.cmd_write_timestamp(cmd, vk::PipelineStageFlags::TOP_OF_PIPE, pool, 0);
+            }
+
+            // Earlier frames may still sample the previous contents.
+            if target.aliased {
+                // The memory was last written through another image of the alias group, so the
+                // old contents are discarded; earlier frames may still sample that image.
+                let barrier = vk::ImageMemoryBarrier::default()
+                    .src_access_mask(vk::AccessFlags::SHADER_READ)
+                    .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+                    .old_layout(vk::ImageLayout::UNDEFINED)
+                    .new_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .image(target.alloc.image)
+                    .subresource_range(
+                        vk::ImageSubresourceRange::default()
+                            .aspect_mask(vk::ImageAspectFlags::COLOR)
+                            .level_count(1)
+                            .layer_count(1),
+                    );
+                device.cmd_pipeline_barrier(
+                    cmd,
+                    vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    std::slice::from_ref(&barrier),
+                );
+            } else {
+                transition_image_layout(
+                    device,
+                    cmd,
+                    target.alloc.image,
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                    vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                );
+            }
+
+            let clear = vk::ClearValue {
+                color: vk::ClearColorValue { float32: clear_rgba },
+            };
+            let area = vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent: target.extent,
+            };
+            let rp_begin = vk::RenderPassBeginInfo::default()
+                .render_pass(pass)
+                .framebuffer(target.framebuffer)
+                .render_area(area)
+                .clear_values(std::slice::from_ref(&clear));
+
+            device.cmd_begin_render_pass(cmd, &rp_begin, vk::SubpassContents::INLINE);
+
+            let viewport = vk::Viewport {
+                x: 0.0,
+                y: 0.0,
+                width: target.extent.width as f32,
+                height: target.extent.height as f32,
+                min_depth: 0.0,
+                max_depth: 1.0,
+            };
+            device.cmd_set_viewport(cmd, 0, std::slice::from_ref(&viewport));
+            device.cmd_set_scissor(cmd, 0, std::slice::from_ref(&area));
+
+            f(device, cmd);
+
+            device.cmd_end_render_pass(cmd);
+
+            transition_image_layout(
+                device,
+                cmd,
+                target.alloc.image,
+                vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            );
+
+            if let Some(pool) = timer_pool {
+                device.cmd_write_timestamp(cmd, vk::PipelineStageFlags::BOTTOM_OF_PIPE, pool, 1);
+            }
+        })?;
+
+        if timer_pool.is_some() {
+            self.gpu_offscreen_done("offscreen");
+        }
+        Ok(())
+    }
+}
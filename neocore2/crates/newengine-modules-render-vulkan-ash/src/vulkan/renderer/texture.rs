@@ -0,0 +1,149 @@
+use crate::error::{VkRenderError, VkResult};
+use crate::vulkan::device::find_memory_type;
+use crate::vulkan::resources::ImageAlloc;
+
+use ash::vk;
+use newengine_core::render::Extent2D;
+
+use super::staging::ImageUpload;
+use super::state::VulkanRenderer;
+
+/// Sampled 2D image filled through `write_texture`. Kept in `SHADER_READ_ONLY_OPTIMAL`
+/// between uploads; mips never written read as undefined texels.
+pub(crate) struct SampledImage {
+    pub(crate) alloc: ImageAlloc,
+    pub(crate) extent: Extent2D,
+    pub(crate) mip_levels: u32,
+    pub(crate) bytes_per_texel: u32,
+}
+
+impl SampledImage {
+    /// Tightly packed size of `mip`, or `None` if the image has no such mip.
+    #[inline]
+    pub(crate) fn mip_bytes(&self, mip: u32) -> Option<usize> {
+        if mip >= self.mip_levels {
+            return None;
+        }
+        let (w, h) = self.mip_extent(mip);
+        Some(w as usize * h as usize * self.bytes_per_texel as usize)
+    }
+
+    #[inline]
+    fn mip_extent(&self, mip: u32) -> (u32, u32) {
+        (
+            (self.extent.width >> mip).max(1),
+            (self.extent.height >> mip).max(1),
+        )
+    }
+}
+
+impl VulkanRenderer {
+    pub(crate) unsafe fn create_sampled_image(
+        &self,
+        format: vk::Format,
+        bytes_per_texel: u32,
+        extent: Extent2D,
+        mip_levels: u32,
+    ) -> VkResult<SampledImage> {
+        let device = &self.core.device;
+        let image_info = vk::ImageCreateInfo::default()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(format)
+            .extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            })
+            .mip_levels(mip_levels)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+
+        let mut alloc = ImageAlloc {
+            image: device.create_image(&image_info, None)?,
+            ..ImageAlloc::default()
+        };
+
+        let res = (|| -> VkResult<()> {
+            let req = device.get_image_memory_requirements(alloc.image);
+            let mem_type = find_memory_type(
+                &self.core.instance,
+                self.core.physical_device,
+                req.memory_type_bits,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+            alloc.memory = device.allocate_memory(
+                &vk::MemoryAllocateInfo::default()
+                    .allocation_size(req.size)
+                    .memory_type_index(mem_type),
+                None,
+            )?;
+            device.bind_image_memory(alloc.image, alloc.memory, 0)?;
+
+            alloc.view = self.create_virtual_view(alloc.image, format, mip_levels)?;
+            self.init_virtual_layout(alloc.image, mip_levels)
+        })();
+
+        if let Err(e) = res {
+            alloc.destroy(device);
+            return Err(e);
+        }
+
+        Ok(SampledImage {
+            alloc,
+            extent,
+            mip_levels,
+            bytes_per_texel,
+        })
+    }
+
+    /// Queues a copy replacing the texels of one mip. It is submitted with the next upload
+    /// flush, ahead of any frame or offscreen pass recorded afterwards.
+    pub(crate) unsafe fn upload_sampled_image(
+        &mut self,
+        img: &SampledImage,
+        mip: u32,
+        texels: &[u8],
+    ) -> VkResult<()> {
+        if img.mip_bytes(mip) != Some(texels.len()) {
+            return Err(VkRenderError::InvalidState(
+                "texture upload does not match the mip size",
+            ));
+        }
+
+        let (width, height) = img.mip_extent(mip);
+        let region = vk::BufferImageCopy::default()
+            .image_subresource(
+                vk::ImageSubresourceLayers::default()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .mip_level(mip)
+                    .base_array_layer(0)
+                    .layer_count(1),
+            )
+            .image_extent(vk::Extent3D {
+                width,
+                height,
+                depth: 1,
+            });
+
+        // Earlier frames may still sample the image; the batch orders the copy after them.
+        self.upload_image(
+            texels,
+            ImageUpload {
+                image: img.alloc.image,
+                mip_levels: img.mip_levels,
+                old_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                region,
+            },
+        )
+    }
+
+    /// Destroys immediately; the caller makes sure no submitted work still uses the image.
+    #[inline]
+    pub(crate) unsafe fn destroy_sampled_image(&self, img: &mut SampledImage) {
+        img.alloc.destroy(&self.core.device);
+    }
+}
@@ -0,0 +1,261 @@
+use ash::vk;
+use newengine_core::render::{GpuFrameStats, GpuPassTiming};
+
+use super::state::VulkanRenderer;
+
+/// Timestamp pairs per frame slot; passes beyond this are not timed.
+const MAX_PASSES: u32 = 16;
+
+/// Timestamp queries for the frame passes, one pool per frame slot.
+///
+/// Pass `i` writes query `2 * i` at its start and `2 * i + 1` at its end. A slot is read
+/// back right after its previous submit completes, so results never stall the CPU.
+pub(crate) struct GpuTimer {
+    pools: Vec<vk::QueryPool>,
+    /// Pass names recorded into each slot's command buffer, in query order.
+    passes: Vec<Vec<&'static str>>,
+    /// Offscreen passes submitted before the slot's frame; already resolved.
+    offscreen: Vec<Vec<GpuPassTiming>>,
+    frame_no: Vec<u64>,
+    /// Two queries for synchronous offscreen submits.
+    offscreen_pool: vk::QueryPool,
+    pending_offscreen: Vec<GpuPassTiming>,
+    open_pass: bool,
+    frame_counter: u64,
+    ns_per_tick: f64,
+    valid_mask: u64,
+    latest: Option<GpuFrameStats>,
+}
+
+impl GpuTimer {
+    /// Timer for `frames` frame slots; `None` if the graphics queue does not support
+    /// timestamps.
+    pub(crate) unsafe fn new(
+        instance: &ash::Instance,
+        physical_device: vk::PhysicalDevice,
+        device: &ash::Device,
+        queue_family_index: u32,
+        frames: usize,
+    ) -> Option<Self> {
+        let families = instance.get_physical_device_queue_family_properties(physical_device);
+        let valid_bits = families
+            .get(queue_family_index as usize)
+            .map_or(0, |f| f.timestamp_valid_bits);
+        if valid_bits == 0 {
+            return None;
+        }
+
+        let period = instance
+            .get_physical_device_properties(physical_device)
+            .limits
+            .timestamp_period;
+
+        let mut pools = vec![vk::QueryPool::null(); frames];
+        let mut offscreen_pool = vk::QueryPool::null();
+        let created = (|| -> Result<(), vk::Result> {
+            for pool in &mut pools {
+                *pool = create_pool(device, MAX_PASSES * 2)?;
+            }
+            offscreen_pool = create_pool(device, 2)?;
+            Ok(())
+        })();
+
+        if let Err(e) = created {
+            log::warn!("vulkan.timing query pool creation failed: {e:?}");
+            for p in pools.into_iter().chain([offscreen_pool]) {
+                if p != vk::QueryPool::null() {
+                    device.destroy_query_pool(p, None);
+                }
+            }
+            return None;
+        }
+
+        Some(Self {
+            pools,
+            passes: vec![Vec::new(); frames],
+            offscreen: vec![Vec::new(); frames],
+            frame_no: vec![0; frames],
+            offscreen_pool,
+            pending_offscreen: Vec::new(),
+            open_pass: false,
+            frame_counter: 0,
+            ns_per_tick: period as f64,
+            valid_mask: if valid_bits >= 64 {
+                u64::MAX
+            } else {
+                (1u64 << valid_bits) - 1
+            },
+            latest: None,
+        })
+    }
+
+    pub(crate) unsafe fn destroy(&mut self, device: &ash::Device) {
+        for p in self.pools.iter_mut().chain([&mut self.offscreen_pool]) {
+            if *p != vk::QueryPool::null() {
+                device.destroy_query_pool(*p, None);
+                *p = vk::QueryPool::null();
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn latest(&self) -> Option<GpuFrameStats> {
+        self.latest.clone()
+    }
+
+    #[inline]
+    fn ticks_to_ms(&self, begin: u64, end: u64) -> f32 {
+        let ticks = end.wrapping_sub(begin) & self.valid_mask;
+        (ticks as f64 * self.ns_per_tick / 1_000_000.0) as f32
+    }
+
+    /// Reads back the queries of `slot`. Its last submit must have completed.
+    unsafe fn resolve(&mut self, device: &ash::Device, slot: usize) {
+        let passes = std::mem::take(&mut self.passes[slot]);
+        let offscreen = std::mem::take(&mut self.offscreen[slot]);
+        if passes.is_empty() {
+            return;
+        }
+
+        let mut ticks = vec![0u64; passes.len() * 2];
+        if device
+            .get_query_pool_results(
+                self.pools[slot],
+                0,
+                &mut ticks,
+                vk::QueryResultFlags::TYPE_64,
+            )
+            .is_err()
+        {
+            // The frame was never submitted (recording error); drop its results.
+            return;
+        }
+
+        let mut stats = GpuFrameStats {
+            frame: self.frame_no[slot],
+            gpu_ms: self.ticks_to_ms(ticks[0], ticks[ticks.len() - 1]),
+            passes: offscreen,
+        };
+        stats.passes.extend(passes.iter().enumerate().map(|(i, &name)| GpuPassTiming {
+            name,
+            gpu_ms: self.ticks_to_ms(ticks[2 * i], ticks[2 * i + 1]),
+        }));
+        self.latest = Some(stats);
+    }
+
+    /// Starts the slot's queries; must be recorded outside a render pass.
+    unsafe fn begin_slot(&mut self, device: &ash::Device, cmd: vk::CommandBuffer, slot: usize) {
+        device.cmd_reset_query_pool(cmd, self.pools[slot], 0, MAX_PASSES * 2);
+        self.frame_counter += 1;
+        self.frame_no[slot] = self.frame_counter;
+        self.passes[slot].clear();
+        self.offscreen[slot] = std::mem::take(&mut self.pending_offscreen);
+        self.open_pass = false;
+    }
+
+    unsafe fn begin_pass(
+        &mut self,
+        device: &ash::Device,
+        cmd: vk::CommandBuffer,
+        slot: usize,
+        name: &'static str,
+    ) {
+        let i = self.passes[slot].len() as u32;
+        if self.open_pass || i >= MAX_PASSES {
+            return;
+        }
+        device.cmd_write_timestamp(
+            cmd,
+            vk::PipelineStageFlags::TOP_OF_PIPE,
+            self.pools[slot],
+            2 * i,
+        );
+        self.passes[slot].push(name);
+        self.open_pass = true;
+    }
+
+    unsafe fn end_pass(&mut self, device: &ash::Device, cmd: vk::CommandBuffer, slot: usize) {
+        if !self.open_pass {
+            return;
+        }
+        let i = self.passes[slot].len() as u32 - 1;
+        device.cmd_write_timestamp(
+            cmd,
+            vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+            self.pools[slot],
+            2 * i + 1,
+        );
+        self.open_pass = false;
+    }
+}
+
+unsafe fn create_pool(device: &ash::Device, count: u32) -> Result<vk::QueryPool, vk::Result> {
+    device.create_query_pool(
+        &vk::QueryPoolCreateInfo::default()
+            .query_type(vk::QueryType::TIMESTAMP)
+            .query_count(count),
+        None,
+    )
+}
+
+impl VulkanRenderer {
+    /// Resolves the current slot's previous frame; call after its submit has been waited on.
+    pub(super) unsafe fn gpu_timer_resolve(&mut self) {
+        let slot = self.frames.frame_index;
+        if let Some(t) = self.frames.gpu_timer.as_mut() {
+            t.resolve(&self.core.device, slot);
+        }
+    }
+
+    pub(super) unsafe fn gpu_timer_begin_frame(&mut self, cmd: vk::CommandBuffer) {
+        let slot = self.frames.frame_index;
+        if let Some(t) = self.frames.gpu_timer.as_mut() {
+            t.begin_slot(&self.core.device, cmd, slot);
+        }
+    }
+
+    /// Closes the open pass, if any, and opens `name`.
+    pub(super) unsafe fn gpu_pass(&mut self, cmd: vk::CommandBuffer, name: &'static str) {
+        let slot = self.frames.frame_index;
+        if let Some(t) = self.frames.gpu_timer.as_mut() {
+            t.end_pass(&self.core.device, cmd, slot);
+            t.begin_pass(&self.core.device, cmd, slot, name);
+        }
+    }
+
+    pub(super) unsafe fn gpu_pass_end(&mut self, cmd: vk::CommandBuffer) {
+        let slot = self.frames.frame_index;
+        if let Some(t) = self.frames.gpu_timer.as_mut() {
+            t.end_pass(&self.core.device, cmd, slot);
+        }
+    }
+
+    /// Query pool for timing one synchronous offscreen submit.
+    #[inline]
+    pub(super) fn gpu_offscreen_pool(&self) -> Option<vk::QueryPool> {
+        self.frames.gpu_timer.as_ref().map(|t| t.offscreen_pool)
+    }
+
+    /// Reads the offscreen queries after the submit completed; attached to the next frame.
+    pub(super) unsafe fn gpu_offscreen_done(&mut self, name: &'static str) {
+        let Some(t) = self.frames.gpu_timer.as_mut() else {
+            return;
+        };
+        let mut ticks = [0u64; 2];
+        if self
+            .core
+            .device
+            .get_query_pool_results(t.offscreen_pool, 0, &mut ticks, vk::QueryResultFlags::TYPE_64)
+            .is_ok()
+            && t.pending_offscreen.len() < MAX_PASSES as usize
+        {
+            let gpu_ms = t.ticks_to_ms(ticks[0], ticks[1]);
+            t.pending_offscreen.push(GpuPassTiming { name, gpu_ms });
+        }
+    }
+
+    #[inline]
+    pub fn gpu_frame_stats(&self) -> Option<GpuFrameStats> {
+        self.frames.gpu_timer.as_ref().and_then(GpuTimer::latest)
+    }
+}
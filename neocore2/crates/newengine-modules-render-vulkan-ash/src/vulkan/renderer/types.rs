@@ -1,6 +1,9 @@
 use ash::vk;
 
-pub(super) const FRAMES_IN_FLIGHT: usize = 2;
+use crate::vulkan::sync::SyncPoint;
+
+/// Upper bound of `VulkanRenderConfig::frames_in_flight`.
+pub(super) const MAX_FRAMES_IN_FLIGHT: u32 = 4;
 
 #[derive(Clone, Copy)]
 
@@ -8,5 +11,17 @@ pub(super) struct FrameSync {
     #[warn(private_interfaces)]
     pub(super) image_available: vk::Semaphore,
     pub(super) render_finished: vk::Semaphore,
+    /// Null when the frame timeline is used.
     pub(super) in_flight: vk::Fence,
+    /// Completion of the last submit from this slot.
+    pub(super) submitted: SyncPoint,
+}
+
+/// Host-visible buffer owned by one frame slot, so a frame never overwrites data the previous
+/// one still reads. Grown on demand once the slot's last submit completed.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FrameBuffer {
+    pub(crate) buf: vk::Buffer,
+    pub(crate) mem: vk::DeviceMemory,
+    pub(crate) size: vk::DeviceSize,
 }
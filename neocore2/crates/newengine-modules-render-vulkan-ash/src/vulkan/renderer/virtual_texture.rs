@@ -0,0 +1,674 @@
+use crate::error::{VkRenderError, VkResult};
+use crate::vulkan::device::find_memory_type;
+use crate::vulkan::resources::ImageAlloc;
+use crate::vulkan::util::immediate_submit;
+
+use ash::vk;
+use newengine_core::render::{
+    Extent2D, PageCoord, PageUpdate, VirtualTextureLayout, VirtualTextureMode,
+};
+use std::collections::HashMap;
+
+use super::state::VulkanRenderer;
+
+/// Atlas slot size; the standard sparse block shape of 32-bit formats.
+const ATLAS_PAGE: u32 = 128;
+
+/// Image behind a virtual texture. Kept in `SHADER_READ_ONLY_OPTIMAL` between uploads.
+pub(crate) struct VirtualImage {
+    pub(crate) alloc: ImageAlloc,
+    pub(crate) layout: VirtualTextureLayout,
+    /// `None` in atlas mode, where `alloc.memory` backs the whole image.
+    sparse: Option<SparseBacking>,
+}
+
+/// Device memory bound into a sparse image: one allocation per resident page plus the
+/// mip tail, which stays bound for the lifetime of the image.
+struct SparseBacking {
+    memory_type: u32,
+    page_bytes: vk::DeviceSize,
+    pages: HashMap<PageCoord, vk::DeviceMemory>,
+    tail: vk::DeviceMemory,
+}
+
+pub(crate) struct VirtualImageDesc {
+    pub(crate) format: vk::Format,
+    pub(crate) bytes_per_texel: u32,
+    pub(crate) extent: Extent2D,
+    pub(crate) mip_levels: u32,
+    pub(crate) max_resident_pages: u32,
+}
+
+impl VulkanRenderer {
+    #[inline]
+    pub(crate) fn virtual_texture_mode(&self) -> VirtualTextureMode {
+        if self.core.sparse_textures {
+            VirtualTextureMode::Sparse
+        } else {
+            VirtualTextureMode::Atlas
+        }
+    }
+
+    /// Sparse image when the device allows it for `desc.format`, else a tiled atlas.
+    pub(crate) unsafe fn create_virtual_image(
+        &self,
+        desc: &VirtualImageDesc,
+    ) -> VkResult<VirtualImage> {
+        if self.core.sparse_textures {
+            match self.create_sparse_image(desc) {
+                Ok(Some(img)) => return Ok(img),
+                Ok(None) => log::info!(
+                    "vulkan.textures no sparse residency for {:?}; using the atlas fallback",
+                    desc.format
+                ),
+                Err(e) => log::warn!(
+                    "vulkan.textures sparse image creation failed ({e}); using the atlas fallback"
+                ),
+            }
+        }
+        self.create_atlas_image(desc)
+    }
+
+    unsafe fn create_sparse_image(
+        &self,
+        desc: &VirtualImageDesc,
+    ) -> VkResult<Option<VirtualImage>> {
+        let usage = vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST;
+
+        let formats = self
+            .core
+            .instance
+            .get_physical_device_sparse_image_format_properties(
+                self.core.physical_device,
+                desc.format,
+                vk::ImageType::TYPE_2D,
+                vk::SampleCountFlags::TYPE_1,
+                usage,
+                vk::ImageTiling::OPTIMAL,
+            );
+        if !formats
+            .iter()
+            .any(|p| p.aspect_mask.contains(vk::ImageAspectFlags::COLOR))
+        {
+            return Ok(None);
+        }
+
+        let device = &self.core.device;
+        let image_info = vk::ImageCreateInfo::default()
+            .flags(vk::ImageCreateFlags::SPARSE_BINDING | vk::ImageCreateFlags::SPARSE_RESIDENCY)
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(desc.format)
+            .extent(vk::Extent3D {
+                width: desc.extent.width,
+                height: desc.extent.height,
+                depth: 1,
+            })
+            .mip_levels(desc.mip_levels)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(usage)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+
+        let mut alloc = ImageAlloc {
+            image: device.create_image(&image_info, None)?,
+            ..ImageAlloc::default()
+        };
+        let mut backing = SparseBacking {
+            memory_type: 0,
+            page_bytes: 0,
+            pages: HashMap::new(),
+            tail: vk::DeviceMemory::null(),
+        };
+
+        let res = (|| -> VkResult<VirtualTextureLayout> {
+            let req = device.get_image_memory_requirements(alloc.image);
+            let sparse_req = device.get_image_sparse_memory_requirements(alloc.image);
+            let color = sparse_req
+                .iter()
+                .find(|r| {
+                    r.format_properties
+                        .aspect_mask
+                        .contains(vk::ImageAspectFlags::COLOR)
+                })
+                .ok_or(VkRenderError::InvalidState(
+                    "sparse image reports no color memory requirements",
+                ))?;
+
+            backing.memory_type = find_memory_type(
+                &self.core.instance,
+                self.core.physical_device,
+                req.memory_type_bits,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+            // For sparse resources the alignment is the size of one sparse block.
+            backing.page_bytes = req.alignment;
+
+            let tail_mip = color.image_mip_tail_first_lod.min(desc.mip_levels);
+            if tail_mip < desc.mip_levels && color.image_mip_tail_size > 0 {
+                backing.tail = device.allocate_memory(
+                    &vk::MemoryAllocateInfo::default()
+                        .allocation_size(color.image_mip_tail_size)
+                        .memory_type_index(backing.memory_type),
+                    None,
+                )?;
+                let bind = vk::SparseMemoryBind::default()
+                    .resource_offset(color.image_mip_tail_offset)
+                    .size(color.image_mip_tail_size)
+                    .memory(backing.tail);
+                let opaque = vk::SparseImageOpaqueMemoryBindInfo::default()
+                    .image(alloc.image)
+                    .binds(std::slice::from_ref(&bind));
+                self.bind_sparse(
+                    &vk::BindSparseInfo::default()
+                        .image_opaque_binds(std::slice::from_ref(&opaque)),
+                )?;
+            }
+
+            alloc.view = self.create_virtual_view(alloc.image, desc.format, desc.mip_levels)?;
+            self.init_virtual_layout(alloc.image, desc.mip_levels)?;
+
+            let g = color.format_properties.image_granularity;
+            Ok(VirtualTextureLayout {
+                mode: VirtualTextureMode::Sparse,
+                extent: desc.extent,
+                mip_levels: desc.mip_levels,
+                page_extent: Extent2D::new(g.width, g.height),
+                bytes_per_texel: desc.bytes_per_texel,
+                tail_mip,
+                atlas_slots: Extent2D::new(0, 0),
+                slots: desc.max_resident_pages,
+            })
+        })();
+
+        match res {
+            Ok(layout) => Ok(Some(VirtualImage {
+                alloc,
+                layout,
+                sparse: Some(backing),
+            })),
+            Err(e) => {
+                alloc.destroy(device);
+                if backing.tail != vk::DeviceMemory::null() {
+                    device.free_memory(backing.tail, None);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    unsafe fn create_atlas_image(&self, desc: &VirtualImageDesc) -> VkResult<VirtualImage> {
+        let limits = self
+            .core
+            .instance
+            .get_physical_device_properties(self.core.physical_device)
+            .limits;
+        let max_cols = (limits.max_image_dimension2_d / ATLAS_PAGE).max(1);
+
+        let slots = desc.max_resident_pages.clamp(1, max_cols * max_cols);
+        let cols = ((slots as f64).sqrt().ceil() as u32).min(max_cols);
+        let rows = slots.div_ceil(cols);
+        let extent = vk::Extent3D {
+            width: cols * ATLAS_PAGE,
+            height: rows * ATLAS_PAGE,
+            depth: 1,
+        };
+
+        let device = &self.core.device;
+        let image_info = vk::ImageCreateInfo::default()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(desc.format)
+            .extent(extent)
+            .mip_levels(1)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+
+        let mut alloc = ImageAlloc {
+            image: device.create_image(&image_info, None)?,
+            ..ImageAlloc::default()
+        };
+
+        let res = (|| -> VkResult<()> {
+            let req = device.get_image_memory_requirements(alloc.image);
+            let mem_type = find_memory_type(
+                &self.core.instance,
+                self.core.physical_device,
+                req.memory_type_bits,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+            alloc.memory = device.allocate_memory(
+                &vk::MemoryAllocateInfo::default()
+                    .allocation_size(req.size)
+                    .memory_type_index(mem_type),
+                None,
+            )?;
+            device.bind_image_memory(alloc.image, alloc.memory, 0)?;
+
+            alloc.view = self.create_virtual_view(alloc.image, desc.format, 1)?;
+            self.init_virtual_layout(alloc.image, 1)
+        })();
+
+        if let Err(e) = res {
+            alloc.destroy(device);
+            return Err(e);
+        }
+
+        Ok(VirtualImage {
+            alloc,
+            layout: VirtualTextureLayout {
+                mode: VirtualTextureMode::Atlas,
+                extent: desc.extent,
+                mip_levels: desc.mip_levels,
+                page_extent: Extent2D::new(ATLAS_PAGE, ATLAS_PAGE),
+                bytes_per_texel: desc.bytes_per_texel,
+                tail_mip: desc.mip_levels,
+                atlas_slots: Extent2D::new(cols, rows),
+                slots,
+            },
+            sparse: None,
+        })
+    }
+
+    /// Applies residency changes: sparse pages are bound/unbound first, then uploaded
+    /// texels are copied in with one submit.
+    pub(crate) unsafe fn update_virtual_image(
+        &self,
+        img: &mut VirtualImage,
+        updates: &[PageUpdate<'_>],
+    ) -> VkResult<()> {
+        let layout = img.layout;
+        for u in updates {
+            let (page, slot) = match *u {
+                PageUpdate::Upload { page, slot, texels } => {
+                    if texels.len() != layout.page_bytes(page) {
+                        return Err(VkRenderError::InvalidState(
+                            "virtual texture page data has the wrong size",
+                        ));
+                    }
+                    (page, slot)
+                }
+                PageUpdate::Evict { page, slot } => (page, slot),
+            };
+            if !layout.contains(page) {
+                return Err(VkRenderError::InvalidState(
+                    "virtual texture page out of range",
+                ));
+            }
+            if layout.mode == VirtualTextureMode::Atlas && !slot.is_some_and(|s| s < layout.slots) {
+                return Err(VkRenderError::InvalidState(
+                    "virtual texture atlas page without a valid slot",
+                ));
+            }
+        }
+
+        if let Some(sparse) = img.sparse.as_mut() {
+            self.rebind_sparse_pages(img.alloc.image, &layout, sparse, updates)?;
+        }
+        self.upload_virtual_pages(img.alloc.image, &layout, updates)
+    }
+
+    unsafe fn rebind_sparse_pages(
+        &self,
+        image: vk::Image,
+        layout: &VirtualTextureLayout,
+        sparse: &mut SparseBacking,
+        updates: &[PageUpdate<'_>],
+    ) -> VkResult<()> {
+        let device = &self.core.device;
+
+        let evicted: Vec<PageCoord> = updates
+            .iter()
+            .filter_map(|u| match *u {
+                PageUpdate::Evict { page, .. } if sparse.pages.contains_key(&page) => Some(page),
+                _ => None,
+            })
+            .collect();
+        if !evicted.is_empty() {
+            // Sparse binds are not ordered against earlier submits; drain them once per batch.
+            device.queue_wait_idle(self.core.queue)?;
+        }
+
+        let mut binds = Vec::new();
+        let mut freed = Vec::new();
+        for page in evicted {
+            if let Some(memory) = sparse.pages.remove(&page) {
+                binds.push(page_bind(layout, page, vk::DeviceMemory::null()));
+                freed.push((page, memory));
+            }
+        }
+
+        let mut allocated = Vec::new();
+        for u in updates {
+            let PageUpdate::Upload { page, .. } = *u else {
+                continue;
+            };
+            if layout.in_tail(page)
+                || sparse.pages.contains_key(&page)
+                || allocated.iter().any(|(p, _)| *p == page)
+            {
+                continue;
+            }
+
+            let memory = device.allocate_memory(
+                &vk::MemoryAllocateInfo::default()
+                    .allocation_size(sparse.page_bytes)
+                    .memory_type_index(sparse.memory_type),
+                None,
+            );
+            let memory = match memory {
+                Ok(m) => m,
+                Err(e) => {
+                    for (_, m) in allocated {
+                        device.free_memory(m, None);
+                    }
+                    sparse.pages.extend(freed);
+                    return Err(e.into());
+                }
+            };
+            binds.push(page_bind(layout, page, memory));
+            allocated.push((page, memory));
+        }
+
+        if binds.is_empty() {
+            return Ok(());
+        }
+
+        let info = vk::SparseImageMemoryBindInfo::default()
+            .image(image)
+            .binds(&binds);
+        if let Err(e) = self
+            .bind_sparse(&vk::BindSparseInfo::default().image_binds(std::slice::from_ref(&info)))
+        {
+            for (_, m) in allocated {
+                device.free_memory(m, None);
+            }
+            sparse.pages.extend(freed);
+            return Err(e);
+        }
+
+        for (_, m) in freed {
+            device.free_memory(m, None);
+        }
+        sparse.pages.extend(allocated);
+        Ok(())
+    }
+
+    unsafe fn upload_virtual_pages(
+        &self,
+        image: vk::Image,
+        layout: &VirtualTextureLayout,
+        updates: &[PageUpdate<'_>],
+    ) -> VkResult<()> {
+        let uploads: Vec<(PageCoord, Option<u32>, &[u8])> = updates
+            .iter()
+            .filter_map(|u| match *u {
+                PageUpdate::Upload { page, slot, texels } => Some((page, slot, texels)),
+                PageUpdate::Evict { .. } => None,
+            })
+            .collect();
+        if uploads.is_empty() {
+            return Ok(());
+        }
+
+        let total: usize = uploads.iter().map(|(_, _, t)| t.len()).sum();
+        let (staging, staging_memory) = self.create_host_buffer(
+            total as vk::DeviceSize,
+            vk::BufferUsageFlags::TRANSFER_SRC,
+            true,
+        )?;
+        let device = self.core.device.clone();
+
+        let res = (|| -> VkResult<()> {
+            let ptr = device.map_memory(
+                staging_memory,
+                0,
+                total as vk::DeviceSize,
+                vk::MemoryMapFlags::empty(),
+            )? as *mut u8;
+
+            let mut regions = Vec::with_capacity(uploads.len());
+            let mut offset = 0usize;
+            for &(page, slot, texels) in &uploads {
+                std::ptr::copy_nonoverlapping(texels.as_ptr(), ptr.add(offset), texels.len());
+
+                let (x, y, w, h) = layout.page_rect(page);
+                let (mip, x, y) = match layout.mode {
+                    VirtualTextureMode::Sparse => (page.mip, x, y),
+                    VirtualTextureMode::Atlas => {
+                        let (sx, sy) = layout.slot_origin(slot.unwrap_or(0));
+                        (0, sx, sy)
+                    }
+                };
+                regions.push(
+                    vk::BufferImageCopy::default()
+                        .buffer_offset(offset as vk::DeviceSize)
+                        .image_subresource(
+                            vk::ImageSubresourceLayers::default()
+                                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                                .mip_level(mip)
+                                .base_array_layer(0)
+                                .layer_count(1),
+                        )
+                        .image_offset(vk::Offset3D {
+                            x: x as i32,
+                            y: y as i32,
+                            z: 0,
+                        })
+                        .image_extent(vk::Extent3D {
+                            width: w,
+                            height: h,
+                            depth: 1,
+                        }),
+                );
+                offset += texels.len();
+            }
+            device.unmap_memory(staging_memory);
+
+            let mips = match layout.mode {
+                VirtualTextureMode::Sparse => layout.mip_levels,
+                VirtualTextureMode::Atlas => 1,
+            };
+
+            // Earlier frames may still sample the image; the barriers order the copy after them.
+            immediate_submit(
+                &device,
+                self.frames.upload_command_pool,
+                self.core.queue,
+                |cmd| {
+                    image_barrier(
+                        &device,
+                        cmd,
+                        image,
+                        mips,
+                        (
+                            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                            vk::PipelineStageFlags::FRAGMENT_SHADER,
+                            vk::AccessFlags::SHADER_READ,
+                        ),
+                        (
+                            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                            vk::PipelineStageFlags::TRANSFER,
+                            vk::AccessFlags::TRANSFER_WRITE,
+                        ),
+                    );
+                    device.cmd_copy_buffer_to_image(
+                        cmd,
+                        staging,
+                        image,
+                        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                        &regions,
+                    );
+                    image_barrier(
+                        &device,
+                        cmd,
+                        image,
+                        mips,
+                        (
+                            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                            vk::PipelineStageFlags::TRANSFER,
+                            vk::AccessFlags::TRANSFER_WRITE,
+                        ),
+                        (
+                            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                            vk::PipelineStageFlags::FRAGMENT_SHADER,
+                            vk::AccessFlags::SHADER_READ,
+                        ),
+                    );
+                },
+            )
+        })();
+
+        device.destroy_buffer(staging, None);
+        device.free_memory(staging_memory, None);
+        res
+    }
+
+    /// Destroys immediately; the caller makes sure no submitted work still uses the image.
+    pub(crate) unsafe fn destroy_virtual_image(&self, img: &mut VirtualImage) {
+        let device = &self.core.device;
+        if let Some(sparse) = img.sparse.as_mut() {
+            for (_, m) in sparse.pages.drain() {
+                device.free_memory(m, None);
+            }
+            if sparse.tail != vk::DeviceMemory::null() {
+                device.free_memory(sparse.tail, None);
+                sparse.tail = vk::DeviceMemory::null();
+            }
+        }
+        img.alloc.destroy(device);
+    }
+
+    /// Submits one sparse bind on the graphics queue and waits for it.
+    unsafe fn bind_sparse(&self, info: &vk::BindSparseInfo<'_>) -> VkResult<()> {
+        let device = &self.core.device;
+        let fence = device.create_fence(&vk::FenceCreateInfo::default(), None)?;
+        let res = device
+            .queue_bind_sparse(self.core.queue, std::slice::from_ref(info), fence)
+            .and_then(|_| device.wait_for_fences(&[fence], true, u64::MAX));
+        device.destroy_fence(fence, None);
+        Ok(res?)
+    }
+
+    pub(super) unsafe fn create_virtual_view(
+        &self,
+        image: vk::Image,
+        format: vk::Format,
+        mip_levels: u32,
+    ) -> VkResult<vk::ImageView> {
+        let view_info = vk::ImageViewCreateInfo::default()
+            .image(image)
+            .view_type(vk::ImageViewType::TYPE_2D)
+            .format(format)
+            .subresource_range(
+                vk::ImageSubresourceRange::default()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .base_mip_level(0)
+                    .level_count(mip_levels)
+                    .base_array_layer(0)
+                    .layer_count(1),
+            );
+        Ok(self.core.device.create_image_view(&view_info, None)?)
+    }
+
+    /// Sampling pages that were never uploaded must still see a valid layout.
+    pub(super) unsafe fn init_virtual_layout(
+        &self,
+        image: vk::Image,
+        mip_levels: u32,
+    ) -> VkResult<()> {
+        let device = &self.core.device;
+        immediate_submit(
+            device,
+            self.frames.upload_command_pool,
+            self.core.queue,
+            |cmd| {
+                image_barrier(
+                    device,
+                    cmd,
+                    image,
+                    mip_levels,
+                    (
+                        vk::ImageLayout::UNDEFINED,
+                        vk::PipelineStageFlags::TOP_OF_PIPE,
+                        vk::AccessFlags::empty(),
+                    ),
+                    (
+                        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                        vk::PipelineStageFlags::FRAGMENT_SHADER,
+                        vk::AccessFlags::SHADER_READ,
+                    ),
+                );
+            },
+        )
+    }
+}
+
+/// Binds `memory` (or unbinds, with a null handle) at the texel region of `page`.
+#[inline]
+fn page_bind(
+    layout: &VirtualTextureLayout,
+    page: PageCoord,
+    memory: vk::DeviceMemory,
+) -> vk::SparseImageMemoryBind {
+    let (x, y, w, h) = layout.page_rect(page);
+    vk::SparseImageMemoryBind::default()
+        .subresource(vk::ImageSubresource {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: page.mip,
+            array_layer: 0,
+        })
+        .offset(vk::Offset3D {
+            x: x as i32,
+            y: y as i32,
+            z: 0,
+        })
+        .extent(vk::Extent3D {
+            width: w,
+            height: h,
+            depth: 1,
+        })
+        .memory(memory)
+}
+
+/// Layout transition over all `mip_levels`; `(layout, stage, access)` before and after.
+#[inline]
+pub(super) unsafe fn image_barrier(
+    device: &ash::Device,
+    cmd: vk::CommandBuffer,
+    image: vk::Image,
+    mip_levels: u32,
+    src: (vk::ImageLayout, vk::PipelineStageFlags, vk::AccessFlags),
+    dst: (vk::ImageLayout, vk::PipelineStageFlags, vk::AccessFlags),
+) {
+    let barrier = vk::ImageMemoryBarrier::default()
+        .src_access_mask(src.2)
+        .dst_access_mask(dst.2)
+        .old_layout(src.0)
+        .new_layout(dst.0)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .image(image)
+        .subresource_range(
+            vk::ImageSubresourceRange::default()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .base_mip_level(0)
+                .level_count(mip_levels)
+                .base_array_layer(0)
+                .layer_count(1),
+        );
+
+    device.cmd_pipeline_barrier(
+        cmd,
+        src.1,
+        dst.1,
+        vk::DependencyFlags::empty(),
+        &[],
+        &[],
+        std::slice::from_ref(&barrier),
+    );
+}
@@ -0,0 +1,338 @@
+use crate::error::{VkRenderError, VkResult};
+use crate::vulkan::pipeline::create_framebuffers;
+use crate::vulkan::swapchain::{create_image_views, create_swapchain};
+use crate::vulkan::sync::acquire_buffers;
+use crate::vulkan::util::transition_image;
+
+use ash::vk;
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
+
+use super::state::VulkanRenderer;
+
+/// Surface and swapchain of a secondary OS window.
+///
+/// Window passes are submitted and waited for synchronously, like offscreen passes, so a
+/// single set of sync objects is enough and nothing outlives the call that recorded it.
+pub(crate) struct WindowSurface {
+    surface: vk::SurfaceKHR,
+    swapchain: vk::SwapchainKHR,
+    images: Vec<vk::Image>,
+    views: Vec<vk::ImageView>,
+    framebuffers: Vec<vk::Framebuffer>,
+    /// Pass the framebuffers were created for.
+    pass: vk::RenderPass,
+    pub(crate) format: vk::Format,
+    extent: vk::Extent2D,
+    width: u32,
+    height: u32,
+    /// Swapchain is recreated before the next pass.
+    pub(crate) dirty: bool,
+    cmd: vk::CommandBuffer,
+    image_available: vk::Semaphore,
+    render_finished: vk::Semaphore,
+    fence: vk::Fence,
+}
+
+impl WindowSurface {
+    #[inline]
+    pub(crate) fn set_size(&mut self, width: u32, height: u32) {
+        if (self.width, self.height) != (width, height) {
+            self.width = width;
+            self.height = height;
+            self.dirty = true;
+        }
+    }
+}
+
+impl VulkanRenderer {
+    pub(crate) unsafe fn create_window_surface(
+        &self,
+        display: RawDisplayHandle,
+        window: RawWindowHandle,
+        width: u32,
+        height: u32,
+    ) -> VkResult<WindowSurface> {
+        let surface = ash_window::create_surface(&self.core.entry, &self.core.instance, display, window, None)
+            .map_err(|e| VkRenderError::AshWindow(e.to_string()))?;
+
+        let presentable = self
+            .core
+            .surface_loader
+            .get_physical_device_surface_support(
+                self.core.physical_device,
+                self.core.queue_family_index,
+                surface,
+            )
+            .unwrap_or(false);
+        if !presentable {
+            self.core.surface_loader.destroy_surface(surface, None);
+            return Err(VkRenderError::InvalidState(
+                "window surface cannot be presented from the graphics queue",
+            ));
+        }
+
+        let device = &self.core.device;
+        let mut s = WindowSurface {
+            surface,
+            swapchain: vk::SwapchainKHR::null(),
+            images: Vec::new(),
+            views: Vec::new(),
+            framebuffers: Vec::new(),
+            pass: vk::RenderPass::null(),
+            format: vk::Format::UNDEFINED,
+            extent: vk::Extent2D { width, height },
+            width,
+            height,
+            dirty: true,
+            cmd: vk::CommandBuffer::null(),
+            image_available: vk::Semaphore::null(),
+            render_finished: vk::Semaphore::null(),
+            fence: vk::Fence::null(),
+        };
+
+        let res = (|| -> VkResult<()> {
+            s.cmd = device.allocate_command_buffers(
+                &vk::CommandBufferAllocateInfo::default()
+                    .command_pool(self.frames.upload_command_pool)
+                    .level(vk::CommandBufferLevel::PRIMARY)
+                    .command_buffer_count(1),
+            )?[0];
+            s.image_available = device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None)?;
+            s.render_finished = device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None)?;
+            s.fence = device.create_fence(&vk::FenceCreateInfo::default(), None)?;
+            self.rebuild_window_swapchain(&mut s)
+        })();
+
+        if let Err(e) = res {
+            self.destroy_window_surface(&mut s);
+            return Err(e);
+        }
+        Ok(s)
+    }
+
+    fn destroy_window_swapchain(&self, s: &mut WindowSurface) {
+        unsafe {
+            for fb in s.framebuffers.drain(..) {
+                self.core.device.destroy_framebuffer(fb, None);
+            }
+            for iv in s.views.drain(..) {
+                self.core.device.destroy_image_view(iv, None);
+            }
+            if s.swapchain != vk::SwapchainKHR::null() {
+                self.core.swapchain_loader.destroy_swapchain(s.swapchain, None);
+                s.swapchain = vk::SwapchainKHR::null();
+            }
+        }
+        s.images.clear();
+        s.pass = vk::RenderPass::null();
+    }
+
+    /// Recreates the swapchain at the window's current size. A zero-sized (minimized) window
+    /// keeps no swapchain and stays dirty.
+    pub(crate) unsafe fn rebuild_window_swapchain(&self, s: &mut WindowSurface) -> VkResult<()> {
+        if s.width == 0 || s.height == 0 {
+            return Ok(());
+        }
+
+        // The previous pass was waited for; only presentation may still hold images.
+        let _ = self.core.device.queue_wait_idle(self.core.queue);
+
+        for fb in s.framebuffers.drain(..) {
+            self.core.device.destroy_framebuffer(fb, None);
+        }
+        for iv in s.views.drain(..) {
+            self.core.device.destroy_image_view(iv, None);
+        }
+
+        let old = s.swapchain;
+        let (swapchain, images, format, extent, _) = create_swapchain(
+            &self.core.swapchain_loader,
+            &self.core.surface_loader,
+            s.surface,
+            self.core.physical_device,
+            s.width,
+            s.height,
+            self.core.queue_family_index,
+            self.swapchain.requested_present_mode,
+            self.swapchain.requested_image_count,
+            old,
+        )?;
+        if old != vk::SwapchainKHR::null() {
+            self.core.swapchain_loader.destroy_swapchain(old, None);
+        }
+
+        s.swapchain = swapchain;
+        s.views = create_image_views(&self.core.device, &images, format)?;
+        s.images = images;
+        s.format = format;
+        s.extent = extent;
+        s.pass = vk::RenderPass::null();
+        s.dirty = false;
+        Ok(())
+    }
+
+    pub(crate) unsafe fn destroy_window_surface(&self, s: &mut WindowSurface) {
+        let device = &self.core.device;
+        let _ = device.queue_wait_idle(self.core.queue);
+
+        self.destroy_window_swapchain(s);
+
+        if s.fence != vk::Fence::null() {
+            device.destroy_fence(s.fence, None);
+            s.fence = vk::Fence::null();
+        }
+        for sem in [&mut s.image_available, &mut s.render_finished] {
+            if *sem != vk::Semaphore::null() {
+                device.destroy_semaphore(*sem, None);
+                *sem = vk::Semaphore::null();
+            }
+        }
+        if s.cmd != vk::CommandBuffer::null() {
+            device.free_command_buffers(self.frames.upload_command_pool, &[s.cmd]);
+            s.cmd = vk::CommandBuffer::null();
+        }
+        if s.surface != vk::SurfaceKHR::null() {
+            self.core.surface_loader.destroy_surface(s.surface, None);
+            s.surface = vk::SurfaceKHR::null();
+        }
+    }
+
+    /// Records one pass into the window's next image, submits it and presents.
+    ///
+    /// The submit completes before this returns. Skipped while the window is minimized or its
+    /// swapchain is out of date; the caller recreates dirty swapchains before calling.
+    pub(crate) unsafe fn render_window<F: FnOnce(&ash::Device, vk::CommandBuffer)>(
+        &mut self,
+        s: &mut WindowSurface,
+        pass: vk::RenderPass,
+        clear_rgba: [f32; 4],
+        f: F,
+    ) -> VkResult<()> {
+        if s.swapchain == vk::SwapchainKHR::null() || s.dirty {
+            return Ok(());
+        }
+
+        if s.pass != pass {
+            for fb in s.framebuffers.drain(..) {
+                self.core.device.destroy_framebuffer(fb, None);
+            }
+            s.framebuffers = create_framebuffers(&self.core.device, pass, &s.views, s.extent)?;
+            s.pass = pass;
+        }
+
+        let image_index = match self.core.swapchain_loader.acquire_next_image(
+            s.swapchain,
+            u64::MAX,
+            s.image_available,
+            vk::Fence::null(),
+        ) {
+            Ok((i, suboptimal)) => {
+                s.dirty |= suboptimal;
+                i
+            }
+            Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                s.dirty = true;
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let idx = image_index as usize;
+
+        self.flush_uploads()?;
+        if let Some(t) = self.frames.transfer_timeline.filter(|t| t.last() > 0) {
+            t.point(t.last()).wait(&self.core.device)?;
+        }
+        let acquires = std::mem::take(&mut self.frames.pending_acquires);
+        let families = self
+            .core
+            .transfer
+            .map(|t| (t.family_index, self.core.queue_family_index));
+
+        let device = &self.core.device;
+        let cmd = s.cmd;
+        device.reset_command_buffer(cmd, vk::CommandBufferResetFlags::empty())?;
+        device.begin_command_buffer(
+            cmd,
+            &vk::CommandBufferBeginInfo::default().flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+        )?;
+
+        if let Some((src, dst)) = families {
+            acquire_buffers(device, cmd, &acquires, src, dst);
+        }
+
+        let clear = vk::ClearValue {
+            color: vk::ClearColorValue { float32: clear_rgba },
+        };
+        let area = vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent: s.extent,
+        };
+        let rp_begin = vk::RenderPassBeginInfo::default()
+            .render_pass(pass)
+            .framebuffer(s.framebuffers[idx])
+            .render_area(area)
+            .clear_values(std::slice::from_ref(&clear));
+
+        device.cmd_begin_render_pass(cmd, &rp_begin, vk::SubpassContents::INLINE);
+
+        let viewport = vk::Viewport {
+            x: 0.0,
+            y: 0.0,
+            width: s.extent.width as f32,
+            height: s.extent.height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        };
+        device.cmd_set_viewport(cmd, 0, std::slice::from_ref(&viewport));
+        device.cmd_set_scissor(cmd, 0, std::slice::from_ref(&area));
+
+        f(device, cmd);
+
+        device.cmd_end_render_pass(cmd);
+
+        transition_image(
+            device,
+            cmd,
+            s.images[idx],
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            vk::ImageLayout::PRESENT_SRC_KHR,
+        );
+
+        device.end_command_buffer(cmd)?;
+
+        let wait_stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
+        let submit = vk::SubmitInfo::default()
+            .wait_semaphores(std::slice::from_ref(&s.image_available))
+            .wait_dst_stage_mask(&wait_stages)
+            .command_buffers(std::slice::from_ref(&cmd))
+            .signal_semaphores(std::slice::from_ref(&s.render_finished));
+        device.queue_submit(self.core.queue, std::slice::from_ref(&submit), s.fence)?;
+
+        let swapchains = [s.swapchain];
+        let indices = [image_index];
+        let present_info = vk::PresentInfoKHR::default()
+            .wait_semaphores(std::slice::from_ref(&s.render_finished))
+            .swapchains(&swapchains)
+            .image_indices(&indices);
+
+        let presented = match self
+            .core
+            .swapchain_loader
+            .queue_present(self.core.queue, &present_info)
+        {
+            Ok(suboptimal) => {
+                s.dirty |= suboptimal;
+                Ok(())
+            }
+            Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                s.dirty = true;
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+        };
+
+        device.wait_for_fences(&[s.fence], true, u64::MAX)?;
+        device.reset_fences(&[s.fence])?;
+        presented
+    }
+}
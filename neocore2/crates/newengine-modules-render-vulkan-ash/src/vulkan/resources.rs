@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use crate::error::VkResult;
+use crate::vulkan::sync::SyncPoint;
 use ash::vk;
 
 /// Buffer + device memory bundle.
@@ -144,14 +145,18 @@ impl UploadCtx {
     /// - This method does NOT block.
     /// - The caller must ensure that the context is not in flight (or accept a wait).
     ///
-    /// Returns the fence associated with this submission.
+    /// `wait` / `signal` are timeline semaphore values for cross-queue ordering. Returns the
+    /// point at which the submission retires: the signaled timeline value if any, else the
+    /// context fence.
     #[inline]
     pub unsafe fn submit_async<F: FnOnce(vk::CommandBuffer)>(
         &self,
         device: &ash::Device,
         queue: vk::Queue,
+        wait: Option<(vk::Semaphore, u64)>,
+        signal: Option<(vk::Semaphore, u64)>,
         f: F,
-    ) -> VkResult<vk::Fence> {
+    ) -> VkResult<SyncPoint> {
         debug_assert!(self.is_ready());
 
         // If the context is still in flight, we must wait; otherwise we'd reset in-use resources.
@@ -172,17 +177,38 @@ impl UploadCtx {
 
         device.end_command_buffer(self.cmd)?;
 
-        let submit = vk::SubmitInfo::default().command_buffers(std::slice::from_ref(&self.cmd));
+        let wait_sems: Vec<vk::Semaphore> = wait.iter().map(|w| w.0).collect();
+        let wait_values: Vec<u64> = wait.iter().map(|w| w.1).collect();
+        let wait_stages = vec![vk::PipelineStageFlags::TRANSFER; wait_sems.len()];
+        let signal_sems: Vec<vk::Semaphore> = signal.iter().map(|s| s.0).collect();
+        let signal_values: Vec<u64> = signal.iter().map(|s| s.1).collect();
+
+        let mut timeline_info = vk::TimelineSemaphoreSubmitInfo::default()
+            .wait_semaphore_values(&wait_values)
+            .signal_semaphore_values(&signal_values);
+
+        let mut submit = vk::SubmitInfo::default()
+            .command_buffers(std::slice::from_ref(&self.cmd))
+            .wait_semaphores(&wait_sems)
+            .wait_dst_stage_mask(&wait_stages)
+            .signal_semaphores(&signal_sems);
+        if wait.is_some() || signal.is_some() {
+            submit = submit.push_next(&mut timeline_info);
+        }
+
         device.queue_submit(queue, std::slice::from_ref(&submit), self.fence)?;
 
-        Ok(self.fence)
+        Ok(match signal {
+            Some((semaphore, value)) => SyncPoint::Timeline { semaphore, value },
+            None => SyncPoint::Fence(self.fence),
+        })
     }
 }
 
-/// Deferred destruction queue keyed by a fence.
+/// Deferred destruction queue keyed by a GPU sync point.
 ///
 /// This is the minimal "game-ready" primitive for upload staging cleanup.
-/// Anything pushed here MUST remain valid until the corresponding fence is signaled.
+/// Anything pushed here MUST remain valid until the corresponding point completes.
 pub struct DeferredFree {
     items: Vec<DeferredItem>,
 }
@@ -199,25 +225,25 @@ impl DeferredFree {
     }
 
     #[inline]
-    pub fn push_buffer(&mut self, fence: vk::Fence, buffer: vk::Buffer, memory: vk::DeviceMemory) {
+    pub fn push_buffer(&mut self, point: SyncPoint, buffer: vk::Buffer, memory: vk::DeviceMemory) {
         if buffer == vk::Buffer::null() && memory == vk::DeviceMemory::null() {
             return;
         }
-        self.items.push(DeferredItem::Buffer { fence, buffer, memory });
+        self.items.push(DeferredItem::Buffer { point, buffer, memory });
     }
 
     #[inline]
-    pub fn push_descriptor_pool(&mut self, fence: vk::Fence, pool: vk::DescriptorPool) {
+    pub fn push_descriptor_pool(&mut self, point: SyncPoint, pool: vk::DescriptorPool) {
         if pool == vk::DescriptorPool::null() {
             return;
         }
-        self.items.push(DeferredItem::DescriptorPool { fence, pool });
+        self.items.push(DeferredItem::DescriptorPool { point, pool });
     }
 
     #[inline]
     pub fn push_image(
         &mut self,
-        fence: vk::Fence,
+        point: SyncPoint,
         image: vk::Image,
         view: vk::ImageView,
         memory: vk::DeviceMemory,
@@ -231,7 +257,7 @@ impl DeferredFree {
             return;
         }
         self.items.push(DeferredItem::Image {
-            fence,
+            point,
             image,
             view,
             memory,
@@ -239,18 +265,11 @@ impl DeferredFree {
         });
     }
 
-    /// Destroys everything whose fence is already signaled.
+    /// Destroys everything whose sync point has already completed.
     pub unsafe fn pump(&mut self, device: &ash::Device) -> VkResult<()> {
         let mut i = 0usize;
         while i < self.items.len() {
-            let fence = self.items[i].fence();
-            let signaled = match device.get_fence_status(fence) {
-                Ok(_) => true,
-                Err(vk::Result::NOT_READY) => false,
-                Err(e) => return Err(e.into()),
-            };
-
-            if !signaled {
+            if !self.items[i].point().is_complete(device)? {
                 i += 1;
                 continue;
             }
@@ -264,16 +283,16 @@ impl DeferredFree {
 
 enum DeferredItem {
     Buffer {
-        fence: vk::Fence,
+        point: SyncPoint,
         buffer: vk::Buffer,
         memory: vk::DeviceMemory,
     },
     DescriptorPool {
-        fence: vk::Fence,
+        point: SyncPoint,
         pool: vk::DescriptorPool,
     },
     Image {
-        fence: vk::Fence,
+        point: SyncPoint,
         image: vk::Image,
         view: vk::ImageView,
         memory: vk::DeviceMemory,
@@ -283,11 +302,11 @@ enum DeferredItem {
 
 impl DeferredItem {
     #[inline]
-    fn fence(&self) -> vk::Fence {
+    fn point(&self) -> SyncPoint {
         match *self {
-            DeferredItem::Buffer { fence, .. } => fence,
-            DeferredItem::DescriptorPool { fence, .. } => fence,
-            DeferredItem::Image { fence, .. } => fence,
+            DeferredItem::Buffer { point, .. } => point,
+            DeferredItem::DescriptorPool { point, .. } => point,
+            DeferredItem::Image { point, .. } => point,
         }
     }
 
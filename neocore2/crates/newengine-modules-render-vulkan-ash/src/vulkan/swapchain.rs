@@ -2,8 +2,10 @@ use crate::error::VkResult;
 
 use ash::vk;
 use ash::Device;
+use newengine_core::render::PresentMode;
 
 use super::pipeline::*;
+use super::sync::SyncPoint;
 use super::text::*;
 use super::VulkanRenderer;
 
@@ -16,8 +18,10 @@ pub(super) fn create_swapchain(
     width: u32,
     height: u32,
     queue_family_index: u32,
+    present_mode: PresentMode,
+    min_image_count: Option<u32>,
     old_swapchain: vk::SwapchainKHR,
-) -> VkResult<(vk::SwapchainKHR, Vec<vk::Image>, vk::Format, vk::Extent2D)> {
+) -> VkResult<(vk::SwapchainKHR, Vec<vk::Image>, vk::Format, vk::Extent2D, PresentMode)> {
     let caps = unsafe {
         surface_loader.get_physical_device_surface_capabilities(physical_device, surface)
     }?;
@@ -36,11 +40,21 @@ pub(super) fn create_swapchain(
         .find(|f| f.format == vk::Format::B8G8R8A8_UNORM)
         .unwrap_or(formats[0]);
 
-    let present_mode = present_modes
-        .iter()
-        .cloned()
-        .find(|m| *m == vk::PresentModeKHR::MAILBOX)
-        .unwrap_or(vk::PresentModeKHR::FIFO);
+    // FIFO is mandatory, so the fallback chain always ends in a supported mode.
+    let mut chosen = present_mode;
+    while !present_modes.contains(&vk_present_mode(chosen)) {
+        match chosen.fallback() {
+            Some(next) => chosen = next,
+            None => break,
+        }
+    }
+    if chosen != present_mode {
+        log::info!(
+            "vulkan.swapchain present_mode={} unsupported, using {}",
+            present_mode.as_str(),
+            chosen.as_str()
+        );
+    }
 
     let extent = if caps.current_extent.width != u32::MAX {
         caps.current_extent
@@ -51,14 +65,34 @@ pub(super) fn create_swapchain(
         }
     };
 
-    let image_count = (caps.min_image_count + 1).min(if caps.max_image_count == 0 {
+    let max_images = if caps.max_image_count == 0 {
         u32::MAX
     } else {
         caps.max_image_count
-    });
+    };
+    let wanted = min_image_count.unwrap_or(caps.min_image_count + 1);
+    let image_count = wanted.clamp(caps.min_image_count, max_images);
+    if image_count != wanted {
+        log::info!(
+            "vulkan.swapchain image_count={} outside surface limits [{}, {}], using {}",
+            wanted,
+            caps.min_image_count,
+            caps.max_image_count,
+            image_count
+        );
+    }
 
     let family_indices = [queue_family_index];
 
+    // TRANSFER_SRC enables frame capture (readback) where the surface allows it.
+    let mut image_usage = vk::ImageUsageFlags::COLOR_ATTACHMENT;
+    if caps
+        .supported_usage_flags
+        .contains(vk::ImageUsageFlags::TRANSFER_SRC)
+    {
+        image_usage |= vk::ImageUsageFlags::TRANSFER_SRC;
+    }
+
     let create_info = vk::SwapchainCreateInfoKHR::default()
         .surface(surface)
         .min_image_count(image_count)
@@ -66,19 +100,28 @@ pub(super) fn create_swapchain(
         .image_color_space(surface_format.color_space)
         .image_extent(extent)
         .image_array_layers(1)
-        .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
+        .image_usage(image_usage)
         .image_sharing_mode(vk::SharingMode::EXCLUSIVE)
         .queue_family_indices(&family_indices)
         .pre_transform(caps.current_transform)
         .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
-        .present_mode(present_mode)
+        .present_mode(vk_present_mode(chosen))
         .clipped(true)
         .old_swapchain(old_swapchain);
 
     let swapchain = unsafe { swapchain_loader.create_swapchain(&create_info, None)? };
     let images = unsafe { swapchain_loader.get_swapchain_images(swapchain)? };
 
-    Ok((swapchain, images, surface_format.format, extent))
+    Ok((swapchain, images, surface_format.format, extent, chosen))
+}
+
+#[inline]
+fn vk_present_mode(mode: PresentMode) -> vk::PresentModeKHR {
+    match mode {
+        PresentMode::Immediate => vk::PresentModeKHR::IMMEDIATE,
+        PresentMode::Mailbox => vk::PresentModeKHR::MAILBOX,
+        PresentMode::Fifo => vk::PresentModeKHR::FIFO,
+    }
 }
 
 pub(super) fn create_image_views(
@@ -111,6 +154,38 @@ pub(super) fn create_image_views(
 }
 
 impl VulkanRenderer {
+    /// Destroys the swapchain and its framebuffers/views (suspend). The next `begin_frame`
+    /// recreates them.
+    pub fn release_swapchain(&mut self) {
+        if self.debug.in_frame || self.swapchain.swapchain == vk::SwapchainKHR::null() {
+            return;
+        }
+
+        unsafe {
+            let _ = self.core.device.device_wait_idle();
+
+            for &fb in &self.swapchain.framebuffers {
+                self.core.device.destroy_framebuffer(fb, None);
+            }
+            self.swapchain.framebuffers.clear();
+
+            for &iv in &self.swapchain.image_views {
+                self.core.device.destroy_image_view(iv, None);
+            }
+            self.swapchain.image_views.clear();
+
+            self.core
+                .swapchain_loader
+                .destroy_swapchain(self.swapchain.swapchain, None);
+        }
+
+        self.swapchain.swapchain = vk::SwapchainKHR::null();
+        self.swapchain.images.clear();
+        self.swapchain.image_layouts.clear();
+        self.frames.images_in_flight.clear();
+        self.debug.swapchain_dirty = true;
+    }
+
     /// Recreates swapchain and all swapchain-dependent resources.
     ///
     /// Safety: must be called only when no command buffers are executing that reference old resources.
@@ -133,7 +208,7 @@ impl VulkanRenderer {
 
         let old_swapchain = self.swapchain.swapchain;
 
-        let (new_swapchain, new_images, new_format, new_extent) = create_swapchain(
+        let (new_swapchain, new_images, new_format, new_extent, new_present_mode) = create_swapchain(
             &self.core.swapchain_loader,
             &self.core.surface_loader,
             self.core.surface,
@@ -141,8 +216,11 @@ impl VulkanRenderer {
             self.debug.target_width,
             self.debug.target_height,
             self.core.queue_family_index,
+            self.swapchain.requested_present_mode,
+            self.swapchain.requested_image_count,
             old_swapchain,
         )?;
+        self.swapchain.present_mode = new_present_mode;
 
         if old_swapchain != vk::SwapchainKHR::null() {
             self.core
@@ -217,6 +295,7 @@ impl VulkanRenderer {
                     &self.core.device,
                     self.pipelines.render_pass,
                     self.ui.desc_set_layout,
+                    self.ui.table.is_some(),
                 )?;
                 self.pipelines.ui_pipeline_layout = upl;
                 self.pipelines.ui_pipeline = up;
@@ -232,19 +311,6 @@ impl VulkanRenderer {
             new_extent,
         )?;
 
-        if self.frames.command_pool != vk::CommandPool::null() && !self.frames.command_buffers.is_empty() {
-            self.core
-                .device
-                .free_command_buffers(self.frames.command_pool, &self.frames.command_buffers);
-        }
-
-        self.frames.command_buffers = self.core.device.allocate_command_buffers(
-            &vk::CommandBufferAllocateInfo::default()
-                .command_pool(self.frames.command_pool)
-                .level(vk::CommandBufferLevel::PRIMARY)
-                .command_buffer_count(new_image_count as u32),
-        )?;
-
         self.swapchain.swapchain = new_swapchain;
         self.swapchain.images = new_images;
         self.swapchain.extent = new_extent;
@@ -252,7 +318,7 @@ impl VulkanRenderer {
         self.swapchain.framebuffers = new_framebuffers;
 
         self.swapchain.image_layouts = vec![vk::ImageLayout::UNDEFINED; new_image_count];
-        self.frames.images_in_flight = vec![vk::Fence::null(); new_image_count];
+        self.frames.images_in_flight = vec![SyncPoint::None; new_image_count];
 
         Ok(())
     }
@@ -0,0 +1,210 @@
+#![allow(dead_code)]
+
+use crate::error::VkResult;
+use ash::vk;
+
+/// A point on the GPU timeline that CPU code can poll or wait for.
+///
+/// Fences are only used when the device lacks timeline semaphores; otherwise every submit is
+/// identified by the value it signals on a per-queue `Timeline`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SyncPoint {
+    /// Nothing was submitted; always complete.
+    #[default]
+    None,
+    Fence(vk::Fence),
+    Timeline {
+        semaphore: vk::Semaphore,
+        value: u64,
+    },
+}
+
+impl SyncPoint {
+    pub unsafe fn is_complete(&self, device: &ash::Device) -> VkResult<bool> {
+        match *self {
+            SyncPoint::None => Ok(true),
+            SyncPoint::Fence(fence) => {
+                if fence == vk::Fence::null() {
+                    return Ok(true);
+                }
+                match device.get_fence_status(fence) {
+                    Ok(_) => Ok(true),
+                    Err(vk::Result::NOT_READY) => Ok(false),
+                    Err(e) => Err(e.into()),
+                }
+            }
+            SyncPoint::Timeline { semaphore, value } => {
+                Ok(device.get_semaphore_counter_value(semaphore)? >= value)
+            }
+        }
+    }
+
+    pub unsafe fn wait(&self, device: &ash::Device) -> VkResult<()> {
+        match *self {
+            SyncPoint::None => Ok(()),
+            SyncPoint::Fence(fence) => {
+                if fence != vk::Fence::null() {
+                    device.wait_for_fences(&[fence], true, u64::MAX)?;
+                }
+                Ok(())
+            }
+            SyncPoint::Timeline { semaphore, value } => {
+                let semaphores = [semaphore];
+                let values = [value];
+                let info = vk::SemaphoreWaitInfo::default()
+                    .semaphores(&semaphores)
+                    .values(&values);
+                device.wait_semaphores(&info, u64::MAX)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Monotonic timeline semaphore owned by one queue.
+///
+/// Each submit on the queue signals `advance()`; CPU waits and cross-queue waits refer to
+/// those values instead of juggling fences and binary semaphores.
+#[derive(Clone, Copy, Debug)]
+pub struct Timeline {
+    semaphore: vk::Semaphore,
+    last: u64,
+}
+
+impl Timeline {
+    pub unsafe fn new(device: &ash::Device) -> VkResult<Self> {
+        let mut type_info = vk::SemaphoreTypeCreateInfo::default()
+            .semaphore_type(vk::SemaphoreType::TIMELINE)
+            .initial_value(0);
+        let semaphore = device.create_semaphore(
+            &vk::SemaphoreCreateInfo::default().push_next(&mut type_info),
+            None,
+        )?;
+        Ok(Self { semaphore, last: 0 })
+    }
+
+    #[inline]
+    pub fn semaphore(&self) -> vk::Semaphore {
+        self.semaphore
+    }
+
+    /// Value signaled by the most recent submit (0 if none).
+    #[inline]
+    pub fn last(&self) -> u64 {
+        self.last
+    }
+
+    /// Reserves the value the next submit will signal.
+    #[inline]
+    pub fn advance(&mut self) -> u64 {
+        self.last += 1;
+        self.last
+    }
+
+    #[inline]
+    pub fn point(&self, value: u64) -> SyncPoint {
+        if value == 0 {
+            return SyncPoint::None;
+        }
+        SyncPoint::Timeline {
+            semaphore: self.semaphore,
+            value,
+        }
+    }
+
+    #[inline]
+    pub unsafe fn destroy(&mut self, device: &ash::Device) {
+        if self.semaphore != vk::Semaphore::null() {
+            device.destroy_semaphore(self.semaphore, None);
+            self.semaphore = vk::Semaphore::null();
+        }
+    }
+}
+
+/// Dedicated transfer queue. Only used together with timeline semaphores.
+#[derive(Clone, Copy, Debug)]
+pub struct TransferQueue {
+    pub family_index: u32,
+    pub queue: vk::Queue,
+}
+
+/// Graphics-side half of a queue family ownership transfer, recorded at the start of the next
+/// frame after the transfer queue released the buffer.
+#[derive(Clone, Copy, Debug)]
+pub struct BufferAcquire {
+    pub buffer: vk::Buffer,
+    pub offset: vk::DeviceSize,
+    pub size: vk::DeviceSize,
+    pub dst_stage: vk::PipelineStageFlags,
+    pub dst_access: vk::AccessFlags,
+}
+
+/// Release barrier on the source queue family. `dst_access` is ignored for releases.
+pub unsafe fn release_buffer(
+    device: &ash::Device,
+    cmd: vk::CommandBuffer,
+    buffer: vk::Buffer,
+    offset: vk::DeviceSize,
+    size: vk::DeviceSize,
+    src_family: u32,
+    dst_family: u32,
+) {
+    let barrier = vk::BufferMemoryBarrier::default()
+        .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+        .dst_access_mask(vk::AccessFlags::empty())
+        .src_queue_family_index(src_family)
+        .dst_queue_family_index(dst_family)
+        .buffer(buffer)
+        .offset(offset)
+        .size(size);
+
+    device.cmd_pipeline_barrier(
+        cmd,
+        vk::PipelineStageFlags::TRANSFER,
+        vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+        vk::DependencyFlags::empty(),
+        &[],
+        std::slice::from_ref(&barrier),
+        &[],
+    );
+}
+
+/// Acquire barriers on the destination queue family. Must be recorded outside a render pass,
+/// in a submit that waits for the releasing submit.
+pub unsafe fn acquire_buffers(
+    device: &ash::Device,
+    cmd: vk::CommandBuffer,
+    acquires: &[BufferAcquire],
+    src_family: u32,
+    dst_family: u32,
+) {
+    if acquires.is_empty() {
+        return;
+    }
+
+    let mut dst_stage = vk::PipelineStageFlags::empty();
+    let barriers: Vec<vk::BufferMemoryBarrier> = acquires
+        .iter()
+        .map(|a| {
+            dst_stage |= a.dst_stage;
+            vk::BufferMemoryBarrier::default()
+                .src_access_mask(vk::AccessFlags::empty())
+                .dst_access_mask(a.dst_access)
+                .src_queue_family_index(src_family)
+                .dst_queue_family_index(dst_family)
+                .buffer(a.buffer)
+                .offset(a.offset)
+                .size(a.size)
+        })
+        .collect();
+
+    device.cmd_pipeline_barrier(
+        cmd,
+        vk::PipelineStageFlags::TOP_OF_PIPE,
+        dst_stage,
+        vk::DependencyFlags::empty(),
+        &[],
+        &barriers,
+        &[],
+    );
+}
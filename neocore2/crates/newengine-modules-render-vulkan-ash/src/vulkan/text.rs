@@ -1,11 +1,14 @@
 use crate::error::VkResult;
 
 use ash::vk;
+use newengine_ui::text::{visual_order, GlyphRasterizer, TextDirection};
+use std::collections::HashMap;
 use std::mem;
 use std::ptr;
 
 use super::device::*;
 use super::pipeline::create_shader_module;
+use super::renderer::FrameBuffer;
 use super::util::*;
 use super::VulkanRenderer;
 
@@ -21,6 +24,103 @@ mod font8x8 {
     }
 }
 
+/// Side of the square R8 glyph atlas.
+const ATLAS_SIZE: u32 = 256;
+const CELL: u32 = 8;
+const CELLS_PER_ROW: u32 = ATLAS_SIZE / CELL;
+const CELL_COUNT: u16 = (CELLS_PER_ROW * CELLS_PER_ROW) as u16;
+/// Cells below this hold the built-in ASCII glyphs; the rest are filled on demand.
+const FIRST_DYNAMIC_CELL: u16 = 128;
+/// DEL has no glyph in the ASCII table; its cell holds the "missing glyph" box.
+const MISSING_CELL: u16 = 0x7F;
+
+/// Glyph atlas of the debug text overlay.
+///
+/// ASCII comes from the built-in 8x8 font. Other characters are rasterized on first use from
+/// the UI font fallback chain of their script; characters no face covers, and any beyond the
+/// atlas capacity, draw as a box.
+pub(crate) struct TextGlyphCache {
+    atlas: Vec<u8>,
+    cells: HashMap<char, u16>,
+    next_cell: u16,
+    /// The atlas changed since the last upload.
+    dirty: bool,
+    rasterizer: GlyphRasterizer,
+}
+
+impl TextGlyphCache {
+    pub(crate) fn new() -> Self {
+        Self {
+            atlas: build_font_atlas_r8(),
+            cells: HashMap::new(),
+            next_cell: FIRST_DYNAMIC_CELL,
+            dirty: true,
+            rasterizer: GlyphRasterizer::new(),
+        }
+    }
+
+    /// Drops the rasterized glyphs when the UI fonts changed.
+    fn refresh(&mut self) {
+        if !self.rasterizer.refresh() || self.cells.is_empty() {
+            return;
+        }
+        self.cells.clear();
+        self.next_cell = FIRST_DYNAMIC_CELL;
+        let first_row = (FIRST_DYNAMIC_CELL as u32 / CELLS_PER_ROW * CELL) as usize;
+        self.atlas[first_row * ATLAS_SIZE as usize..].fill(0);
+        self.dirty = true;
+    }
+
+    fn cell_of(&mut self, c: char) -> u16 {
+        if (' '..='~').contains(&c) {
+            return c as u16;
+        }
+        if let Some(&cell) = self.cells.get(&c) {
+            return cell;
+        }
+
+        let cell = if self.next_cell >= CELL_COUNT {
+            MISSING_CELL
+        } else {
+            match self.rasterizer.rasterize(c, CELL, CELL) {
+                Some(px) => {
+                    let cell = self.next_cell;
+                    self.next_cell += 1;
+                    blit_cell(&mut self.atlas, cell, &px);
+                    self.dirty = true;
+                    cell
+                }
+                None => MISSING_CELL,
+            }
+        };
+        self.cells.insert(c, cell);
+        cell
+    }
+
+    #[inline]
+    fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+}
+
+#[inline]
+fn cell_origin(cell: u16) -> (usize, usize) {
+    let cell = cell as u32;
+    (
+        ((cell % CELLS_PER_ROW) * CELL) as usize,
+        ((cell / CELLS_PER_ROW) * CELL) as usize,
+    )
+}
+
+fn blit_cell(atlas: &mut [u8], cell: u16, px: &[u8]) {
+    let (ox, oy) = cell_origin(cell);
+    let cell = CELL as usize;
+    for row in 0..cell {
+        let dst = (oy + row) * ATLAS_SIZE as usize + ox;
+        atlas[dst..dst + cell].copy_from_slice(&px[row * cell..(row + 1) * cell]);
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub(super) struct TextVertex {
@@ -161,10 +261,7 @@ pub(super) unsafe fn create_text_pipeline(
 impl VulkanRenderer {
     pub(super) fn init_text_overlay(&mut self) -> VkResult<()> {
         unsafe {
-            let atlas = build_font_atlas_128_r8();
-            debug_assert_eq!(atlas.len(), 128 * 128);
-
-            self.create_font_resources(&atlas)?;
+            self.create_font_resources()?;
             self.create_text_descriptor()?;
 
             let (tpl, tp) = create_text_pipeline(
@@ -175,17 +272,14 @@ impl VulkanRenderer {
             self.pipelines.text_pipeline_layout = tpl;
             self.pipelines.text_pipeline = tp;
 
-            self.create_text_vertex_buffer(6 * 2048)?;
+            self.create_text_vertex_buffers(6 * 2048)?;
         }
         Ok(())
     }
 
     pub(super) unsafe fn destroy_text_overlay(&mut self) {
-        if self.text.vb != vk::Buffer::null() {
-            self.core.device.destroy_buffer(self.text.vb, None);
-        }
-        if self.text.vb_mem != vk::DeviceMemory::null() {
-            self.core.device.free_memory(self.text.vb_mem, None);
+        for b in std::mem::take(&mut self.text.vertex_buffers) {
+            self.destroy_frame_buffer(b);
         }
 
         if self.pipelines.text_pipeline != vk::Pipeline::null() {
@@ -228,39 +322,95 @@ impl VulkanRenderer {
         }
     }
 
-    unsafe fn create_text_vertex_buffer(&mut self, max_vertices: usize) -> VkResult<()> {
-        self.text.vb_size = (mem::size_of::<TextVertex>() * max_vertices) as vk::DeviceSize;
+    /// One fixed-size vertex buffer per frame slot; longer text is not drawn.
+    unsafe fn create_text_vertex_buffers(&mut self, max_vertices: usize) -> VkResult<()> {
+        let size = (mem::size_of::<TextVertex>() * max_vertices) as vk::DeviceSize;
+        for _ in 0..self.frames.frames.len() {
+            let (buf, mem) =
+                self.create_host_buffer(size, vk::BufferUsageFlags::VERTEX_BUFFER, false)?;
+            self.text
+                .vertex_buffers
+                .push(FrameBuffer { buf, mem, size });
+        }
+        Ok(())
+    }
 
-        let info = vk::BufferCreateInfo::default()
-            .size(self.text.vb_size)
-            .usage(vk::BufferUsageFlags::VERTEX_BUFFER)
-            .sharing_mode(vk::SharingMode::EXCLUSIVE);
+    unsafe fn create_font_resources(&mut self) -> VkResult<()> {
+        let image_info = vk::ImageCreateInfo::default()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(vk::Format::R8_UNORM)
+            .extent(vk::Extent3D {
+                width: ATLAS_SIZE,
+                height: ATLAS_SIZE,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
 
-        self.text.vb = self.core.device.create_buffer(&info, None)?;
+        self.text.font_image = self.core.device.create_image(&image_info, None)?;
         let req = self
             .core
             .device
-            .get_buffer_memory_requirements(self.text.vb);
+            .get_image_memory_requirements(self.text.font_image);
 
         let mem_type = find_memory_type(
             &self.core.instance,
             self.core.physical_device,
             req.memory_type_bits,
-            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
         )?;
 
         let alloc = vk::MemoryAllocateInfo::default()
             .allocation_size(req.size)
             .memory_type_index(mem_type);
 
-        self.text.vb_mem = self.core.device.allocate_memory(&alloc, None)?;
+        self.text.font_image_mem = self.core.device.allocate_memory(&alloc, None)?;
         self.core
             .device
-            .bind_buffer_memory(self.text.vb, self.text.vb_mem, 0)?;
+            .bind_image_memory(self.text.font_image, self.text.font_image_mem, 0)?;
+
+        self.upload_font_atlas()?;
+        self.text.glyphs.take_dirty();
+
+        self.text.font_image_view = self.core.device.create_image_view(
+            &vk::ImageViewCreateInfo::default()
+                .image(self.text.font_image)
+                .view_type(vk::ImageViewType::TYPE_2D)
+                .format(vk::Format::R8_UNORM)
+                .subresource_range(
+                    vk::ImageSubresourceRange::default()
+                        .aspect_mask(vk::ImageAspectFlags::COLOR)
+                        .base_mip_level(0)
+                        .level_count(1)
+                        .base_array_layer(0)
+                        .layer_count(1),
+                ),
+            None,
+        )?;
+
+        self.text.font_sampler = self.core.device.create_sampler(
+            &vk::SamplerCreateInfo::default()
+                .mag_filter(vk::Filter::NEAREST)
+                .min_filter(vk::Filter::NEAREST)
+                .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+                .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE),
+            None,
+        )?;
+
         Ok(())
     }
 
-    unsafe fn create_font_resources(&mut self, atlas_r8: &[u8]) -> VkResult<()> {
+    /// Copies the whole CPU atlas into the font image. The previous contents are discarded,
+    /// so the image must not be in use by the GPU.
+    unsafe fn upload_font_atlas(&self) -> VkResult<()> {
+        let atlas_r8 = &self.text.glyphs.atlas;
         let staging_size = atlas_r8.len() as vk::DeviceSize;
 
         let (staging_buf, staging_mem) = create_buffer(
@@ -282,44 +432,6 @@ impl VulkanRenderer {
         ptr::copy_nonoverlapping(atlas_r8.as_ptr(), ptr_map, atlas_r8.len());
         self.core.device.unmap_memory(staging_mem);
 
-        let image_info = vk::ImageCreateInfo::default()
-            .image_type(vk::ImageType::TYPE_2D)
-            .format(vk::Format::R8_UNORM)
-            .extent(vk::Extent3D {
-                width: 128,
-                height: 128,
-                depth: 1,
-            })
-            .mip_levels(1)
-            .array_layers(1)
-            .samples(vk::SampleCountFlags::TYPE_1)
-            .tiling(vk::ImageTiling::OPTIMAL)
-            .usage(vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED)
-            .sharing_mode(vk::SharingMode::EXCLUSIVE)
-            .initial_layout(vk::ImageLayout::UNDEFINED);
-
-        self.text.font_image = self.core.device.create_image(&image_info, None)?;
-        let req = self
-            .core
-            .device
-            .get_image_memory_requirements(self.text.font_image);
-
-        let mem_type = find_memory_type(
-            &self.core.instance,
-            self.core.physical_device,
-            req.memory_type_bits,
-            vk::MemoryPropertyFlags::DEVICE_LOCAL,
-        )?;
-
-        let alloc = vk::MemoryAllocateInfo::default()
-            .allocation_size(req.size)
-            .memory_type_index(mem_type);
-
-        self.text.font_image_mem = self.core.device.allocate_memory(&alloc, None)?;
-        self.core
-            .device
-            .bind_image_memory(self.text.font_image, self.text.font_image_mem, 0)?;
-
         immediate_submit(
             &self.core.device,
             self.frames.upload_command_pool,
@@ -346,8 +458,8 @@ impl VulkanRenderer {
                     )
                     .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
                     .image_extent(vk::Extent3D {
-                        width: 128,
-                        height: 128,
+                        width: ATLAS_SIZE,
+                        height: ATLAS_SIZE,
                         depth: 1,
                     });
 
@@ -372,33 +484,6 @@ impl VulkanRenderer {
         self.core.device.destroy_buffer(staging_buf, None);
         self.core.device.free_memory(staging_mem, None);
 
-        self.text.font_image_view = self.core.device.create_image_view(
-            &vk::ImageViewCreateInfo::default()
-                .image(self.text.font_image)
-                .view_type(vk::ImageViewType::TYPE_2D)
-                .format(vk::Format::R8_UNORM)
-                .subresource_range(
-                    vk::ImageSubresourceRange::default()
-                        .aspect_mask(vk::ImageAspectFlags::COLOR)
-                        .base_mip_level(0)
-                        .level_count(1)
-                        .base_array_layer(0)
-                        .layer_count(1),
-                ),
-            None,
-        )?;
-
-        self.text.font_sampler = self.core.device.create_sampler(
-            &vk::SamplerCreateInfo::default()
-                .mag_filter(vk::Filter::NEAREST)
-                .min_filter(vk::Filter::NEAREST)
-                .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
-                .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
-                .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
-                .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE),
-            None,
-        )?;
-
         Ok(())
     }
 
@@ -457,24 +542,31 @@ impl VulkanRenderer {
             return Ok(());
         }
 
-        let vertices = build_text_vertices(text, self.swapchain.extent);
+        let vertices = build_text_vertices(text, self.swapchain.extent, &mut self.text.glyphs);
         if vertices.is_empty() {
             return Ok(());
         }
 
+        if self.text.glyphs.take_dirty() {
+            // New glyphs are rare (first use of a character, font change); earlier frames may
+            // still sample the atlas, so let them finish before replacing it.
+            self.core.device.device_wait_idle()?;
+            self.upload_font_atlas()?;
+        }
+
         let bytes = (vertices.len() * mem::size_of::<TextVertex>()) as vk::DeviceSize;
-        if bytes > self.text.vb_size {
+        let vb = self.text.vertex_buffers[self.frames.frame_index];
+        if bytes > vb.size {
             return Ok(());
         }
 
         let dst =
             self.core
                 .device
-                .map_memory(self.text.vb_mem, 0, bytes, vk::MemoryMapFlags::empty())?
-                as *mut u8;
+                .map_memory(vb.mem, 0, bytes, vk::MemoryMapFlags::empty())? as *mut u8;
 
         ptr::copy_nonoverlapping(vertices.as_ptr() as *const u8, dst, bytes as usize);
-        self.core.device.unmap_memory(self.text.vb_mem);
+        self.core.device.unmap_memory(vb.mem);
 
         self.core.device.cmd_bind_pipeline(
             cmd,
@@ -491,11 +583,11 @@ impl VulkanRenderer {
             &[],
         );
 
-        let vb = [self.text.vb];
+        let vbs = [vb.buf];
         let offsets = [0u64];
         self.core
             .device
-            .cmd_bind_vertex_buffers(cmd, 0, &vb, &offsets);
+            .cmd_bind_vertex_buffers(cmd, 0, &vbs, &offsets);
 
         self.core
             .device
@@ -504,7 +596,13 @@ impl VulkanRenderer {
     }
 }
 
-pub(super) fn build_text_vertices(text: &str, extent: vk::Extent2D) -> Vec<TextVertex> {
+/// Quads for `text` (shaped and reordered for right-to-left scripts), one 8x8 cell per
+/// character.
+pub(super) fn build_text_vertices(
+    text: &str,
+    extent: vk::Extent2D,
+    glyphs: &mut TextGlyphCache,
+) -> Vec<TextVertex> {
     let mut out = Vec::new();
     let mut x = 8.0f32;
     let mut y = 8.0f32;
@@ -514,20 +612,25 @@ pub(super) fn build_text_vertices(text: &str, extent: vk::Extent2D) -> Vec<TextV
 
     let color = [1.0, 1.0, 1.0, 1.0];
 
-    for &b in text.as_bytes() {
-        if b == b'\n' {
+    glyphs.refresh();
+    let text = visual_order(text, TextDirection::Auto);
+
+    let cells = CELLS_PER_ROW as f32;
+    for c in text.chars() {
+        if c == '\n' {
             x = 8.0;
             y += 10.0;
             continue;
         }
 
-        let gx = (b & 0x0F) as f32;
-        let gy = (b >> 4) as f32;
+        let cell = glyphs.cell_of(c) as u32;
+        let gx = (cell % CELLS_PER_ROW) as f32;
+        let gy = (cell / CELLS_PER_ROW) as f32;
 
-        let u0 = gx / 16.0;
-        let v0 = gy / 16.0;
-        let u1 = (gx + 1.0) / 16.0;
-        let v1 = (gy + 1.0) / 16.0;
+        let u0 = gx / cells;
+        let v0 = gy / cells;
+        let u1 = (gx + 1.0) / cells;
+        let v1 = (gy + 1.0) / cells;
 
         let p0 = px_to_ndc(x, y, w, h);
         let p1 = px_to_ndc(x + 8.0, y, w, h);
@@ -554,29 +657,33 @@ pub(super) fn px_to_ndc(x_px: f32, y_px: f32, w: f32, h: f32) -> [f32; 2] {
     [x, y]
 }
 
-pub(super) fn build_font_atlas_128_r8() -> Vec<u8> {
-    let mut atlas = vec![0u8; 128 * 128];
+/// Atlas with the ASCII glyphs in the cells of their codes and the missing-glyph box.
+pub(super) fn build_font_atlas_r8() -> Vec<u8> {
+    let mut atlas = vec![0u8; (ATLAS_SIZE * ATLAS_SIZE) as usize];
 
     for ch in 0u8..=127u8 {
-        let glyph = glyph8x8(ch);
-        let gx = (ch & 0x0F) as usize;
-        let gy = (ch >> 4) as usize;
-
-        let ox = gx * 8;
-        let oy = gy * 8;
-
+        let glyph = if ch as u16 == MISSING_CELL {
+            MISSING_GLYPH
+        } else {
+            glyph8x8(ch)
+        };
+        let mut px = [0u8; 64];
         for row in 0..8 {
             let bits = glyph[row];
             for col in 0..8 {
                 let on = (bits & (1u8 << col)) != 0;
-                atlas[(oy + row) * 128 + (ox + col)] = if on { 255 } else { 0 };
+                px[row * 8 + col] = if on { 255 } else { 0 };
             }
         }
+        blit_cell(&mut atlas, ch as u16, &px);
     }
 
     atlas
 }
 
+/// Hollow box drawn for characters no font covers.
+const MISSING_GLYPH: [u8; 8] = [0x00, 0x7E, 0x42, 0x42, 0x42, 0x42, 0x7E, 0x00];
+
 /// Extract glyph from table and fix only vertical orientation.
 /// Do NOT reverse bits: the table defines bit0 as leftmost pixel.
 pub(super) fn glyph8x8(ch: u8) -> [u8; 8] {
@@ -1,5 +1,5 @@
 mod overlay;
 mod pipeline;
 
-pub(super) use overlay::GpuUiTexture;
+pub(super) use overlay::{GpuUiTexture, UiTextureTable};
 pub(super) use pipeline::create_ui_pipeline;
@@ -1,33 +1,115 @@
-use crate::error::VkResult;
+use crate::error::{VkRenderError, VkResult};
 
 use ash::vk;
+use std::collections::{HashMap, VecDeque};
 use std::mem;
 use std::ptr;
 
 use super::super::device::*;
-use super::super::util::*;
+use super::super::renderer::{FrameBuffer, ImageUpload};
 use super::super::VulkanRenderer;
 
-use newengine_ui::draw::{UiDrawCmd, UiDrawList, UiTexId, UiTextureDelta};
+use newengine_ui::draw::{UiDrawCmd, UiDrawList, UiTexId, UiTexture, UiTextureDelta};
+
+use super::pipeline::{create_ui_pipeline, ui_pc_bytes, ui_pc_stages, UI_PC_TEXTURE_OFFSET};
+
+/// Copy of `size` tightly packed RGBA8 texels to `origin` of a UI texture.
+fn ui_upload_region(origin: [u32; 2], size: [u32; 2]) -> vk::BufferImageCopy {
+    vk::BufferImageCopy::default()
+        .image_subresource(
+            vk::ImageSubresourceLayers::default()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .mip_level(0)
+                .base_array_layer(0)
+                .layer_count(1),
+        )
+        .image_offset(vk::Offset3D {
+            x: origin[0] as i32,
+            y: origin[1] as i32,
+            z: 0,
+        })
+        .image_extent(vk::Extent3D {
+            width: size[0],
+            height: size[1],
+            depth: 1,
+        })
+}
+
+/// Applies a texture patch to the CPU copy of a UI texture; out-of-bounds rows are skipped.
+fn ui_patch_shadow(tex: &mut UiTexture, origin: [u32; 2], size: [u32; 2], rgba8: &[u8]) {
+    let row_bytes = size[0] as usize * 4;
+    if origin[0] + size[0] > tex.size[0] {
+        return;
+    }
+    for y in 0..size[1] {
+        let dst_y = origin[1] + y;
+        if dst_y >= tex.size[1] {
+            break;
+        }
+        let dst = (dst_y as usize * tex.size[0] as usize + origin[0] as usize) * 4;
+        let src = y as usize * row_bytes;
+        let (Some(dst), Some(src)) = (
+            tex.rgba8.get_mut(dst..dst + row_bytes),
+            rgba8.get(src..src + row_bytes),
+        ) else {
+            break;
+        };
+        dst.copy_from_slice(src);
+    }
+}
 
-use super::pipeline::{create_ui_pipeline, ui_pc_bytes};
+/// Most UI textures alive at once, in the descriptor pool or the texture table.
+const UI_MAX_TEXTURES: u32 = 1024;
 
 #[derive(Clone, Copy)]
 pub(crate) struct GpuUiTexture {
     pub(crate) image: vk::Image,
     pub(crate) mem: vk::DeviceMemory,
     pub(crate) view: vk::ImageView,
+    /// Own set, or the table set shared by every texture.
     pub(crate) desc_set: vk::DescriptorSet,
+    /// Array element in the texture table; 0 without one.
+    pub(crate) slot: u32,
+}
+
+/// Every UI texture in one descriptor array (descriptor indexing), so the overlay binds a
+/// single set per frame and picks textures with a push constant.
+pub(crate) struct UiTextureTable {
+    pub(crate) set: vk::DescriptorSet,
+    capacity: u32,
+    next: u32,
+    free: VecDeque<u32>,
+}
+
+impl UiTextureTable {
+    /// Fresh slots first, then the longest-freed one, so an element an in-flight frame may
+    /// still sample is rewritten as late as possible.
+    fn alloc(&mut self) -> Option<u32> {
+        if self.next < self.capacity {
+            self.next += 1;
+            return Some(self.next - 1);
+        }
+        self.free.pop_front()
+    }
+
+    #[inline]
+    fn release(&mut self, slot: u32) {
+        self.free.push_back(slot);
+    }
 }
 
 impl VulkanRenderer {
     pub(crate) fn init_ui_overlay(&mut self) -> VkResult<()> {
+        let frames = self.frames.frames.len();
+        self.ui.vertex_buffers = vec![FrameBuffer::default(); frames];
+        self.ui.index_buffers = vec![FrameBuffer::default(); frames];
         unsafe {
             self.create_ui_descriptor()?;
             let (pl, p) = create_ui_pipeline(
                 &self.core.device,
                 self.pipelines.render_pass,
                 self.ui.desc_set_layout,
+                self.ui.table.is_some(),
             )?;
             self.pipelines.ui_pipeline_layout = pl;
             self.pipelines.ui_pipeline = p;
@@ -54,6 +136,7 @@ impl VulkanRenderer {
                 .device
                 .destroy_descriptor_pool(self.ui.desc_pool, None);
         }
+        self.ui.table = None;
         if self.ui.desc_set_layout != vk::DescriptorSetLayout::null() {
             self.core
                 .device
@@ -63,40 +146,16 @@ impl VulkanRenderer {
             self.core.device.destroy_sampler(self.ui.sampler, None);
         }
 
-        if self.ui.vb != vk::Buffer::null() {
-            self.core.device.destroy_buffer(self.ui.vb, None);
-        }
-        if self.ui.vb_mem != vk::DeviceMemory::null() {
-            self.core.device.free_memory(self.ui.vb_mem, None);
-        }
-        if self.ui.ib != vk::Buffer::null() {
-            self.core.device.destroy_buffer(self.ui.ib, None);
-        }
-        if self.ui.ib_mem != vk::DeviceMemory::null() {
-            self.core.device.free_memory(self.ui.ib_mem, None);
-        }
-
-        if self.ui.staging_buf != vk::Buffer::null() {
-            self.core.device.destroy_buffer(self.ui.staging_buf, None);
-            self.ui.staging_buf = vk::Buffer::null();
+        let vertex_buffers = std::mem::take(&mut self.ui.vertex_buffers);
+        let index_buffers = std::mem::take(&mut self.ui.index_buffers);
+        for b in vertex_buffers.into_iter().chain(index_buffers) {
+            self.destroy_frame_buffer(b);
         }
-        if self.ui.staging_mem != vk::DeviceMemory::null() {
-            self.core.device.free_memory(self.ui.staging_mem, None);
-            self.ui.staging_mem = vk::DeviceMemory::null();
-        }
-        self.ui.staging_size = 0;
     }
 
     unsafe fn destroy_ui_resources(&mut self) {
         for (_id, tex) in self.ui.textures.drain() {
-            if tex.desc_set != vk::DescriptorSet::null()
-                && self.ui.desc_pool != vk::DescriptorPool::null()
-            {
-                let _ = self
-                    .core
-                    .device
-                    .free_descriptor_sets(self.ui.desc_pool, &[tex.desc_set]);
-            }
+            self.ui_unbind(&tex);
             if tex.view != vk::ImageView::null() {
                 self.core.device.destroy_image_view(tex.view, None);
             }
@@ -120,6 +179,11 @@ impl VulkanRenderer {
 
         self.ui.sampler = self.core.device.create_sampler(&sampler_info, None)?;
 
+        let capacity = self.core.bindless_textures.min(UI_MAX_TEXTURES);
+        if capacity > 0 {
+            return self.create_ui_texture_table(capacity);
+        }
+
         let binding = vk::DescriptorSetLayoutBinding::default()
             .binding(0)
             .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
@@ -133,12 +197,12 @@ impl VulkanRenderer {
 
         let pool_size = vk::DescriptorPoolSize::default()
             .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
-            .descriptor_count(1024);
+            .descriptor_count(UI_MAX_TEXTURES);
 
         self.ui.desc_pool = self.core.device.create_descriptor_pool(
             &vk::DescriptorPoolCreateInfo::default()
                 .flags(vk::DescriptorPoolCreateFlags::FREE_DESCRIPTOR_SET)
-                .max_sets(1024)
+                .max_sets(UI_MAX_TEXTURES)
                 .pool_sizes(std::slice::from_ref(&pool_size)),
             None,
         )?;
@@ -146,162 +210,147 @@ impl VulkanRenderer {
         Ok(())
     }
 
-    pub(super) unsafe fn ui_apply_delta(&mut self, delta: &UiTextureDelta) -> VkResult<()> {
-        #[derive(Clone, Copy)]
-        enum UploadKind {
-            New,
-            Patch { origin: [u32; 2] },
-        }
+    /// One set with a partially bound array of `capacity` textures, written while in use.
+    unsafe fn create_ui_texture_table(&mut self, capacity: u32) -> VkResult<()> {
+        let binding = vk::DescriptorSetLayoutBinding::default()
+            .binding(0)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .descriptor_count(capacity)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT);
+        let binding_flags = [vk::DescriptorBindingFlags::PARTIALLY_BOUND
+            | vk::DescriptorBindingFlags::UPDATE_AFTER_BIND
+            | vk::DescriptorBindingFlags::UPDATE_UNUSED_WHILE_PENDING];
+        let mut flags_info =
+            vk::DescriptorSetLayoutBindingFlagsCreateInfo::default().binding_flags(&binding_flags);
 
-        #[derive(Clone, Copy)]
-        struct UploadOp {
-            image: vk::Image,
-            old_layout: vk::ImageLayout,
-            offset: vk::DeviceSize,
-            extent: vk::Extent3D,
-            kind: UploadKind,
-        }
+        self.ui.desc_set_layout = self.core.device.create_descriptor_set_layout(
+            &vk::DescriptorSetLayoutCreateInfo::default()
+                .flags(vk::DescriptorSetLayoutCreateFlags::UPDATE_AFTER_BIND_POOL)
+                .bindings(std::slice::from_ref(&binding))
+                .push_next(&mut flags_info),
+            None,
+        )?;
 
-        let mut total_bytes: vk::DeviceSize = 0;
-        for (_id, tex) in &delta.set {
-            total_bytes += tex.rgba8.len() as vk::DeviceSize;
-        }
-        for p in &delta.patches {
-            total_bytes += p.rgba8.len() as vk::DeviceSize;
-        }
+        let pool_size = vk::DescriptorPoolSize::default()
+            .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .descriptor_count(capacity);
 
-        let mut ops: Vec<UploadOp> = Vec::with_capacity(delta.set.len() + delta.patches.len());
+        self.ui.desc_pool = self.core.device.create_descriptor_pool(
+            &vk::DescriptorPoolCreateInfo::default()
+                .flags(vk::DescriptorPoolCreateFlags::UPDATE_AFTER_BIND)
+                .max_sets(1)
+                .pool_sizes(std::slice::from_ref(&pool_size)),
+            None,
+        )?;
 
-        if total_bytes != 0 {
-            self.ui_ensure_staging(total_bytes)?;
+        let layouts = [self.ui.desc_set_layout];
+        let set = self.core.device.allocate_descriptor_sets(
+            &vk::DescriptorSetAllocateInfo::default()
+                .descriptor_pool(self.ui.desc_pool)
+                .set_layouts(&layouts),
+        )?[0];
 
-            let mapped = self.core.device.map_memory(
-                self.ui.staging_mem,
-                0,
-                total_bytes,
-                vk::MemoryMapFlags::empty(),
-            )? as *mut u8;
+        self.ui.table = Some(UiTextureTable {
+            set,
+            capacity,
+            next: 0,
+            free: VecDeque::new(),
+        });
+        log::info!("vulkan.ui texture_table capacity={capacity}");
+        Ok(())
+    }
 
-            let mut cursor: vk::DeviceSize = 0;
+    /// Makes `view` sampleable by UI draws: a table slot when the table exists, otherwise a
+    /// set of its own.
+    unsafe fn ui_bind_view(&mut self, view: vk::ImageView) -> VkResult<(vk::DescriptorSet, u32)> {
+        let (set, slot) = match self.ui.table.as_mut() {
+            Some(table) => {
+                let slot = table
+                    .alloc()
+                    .ok_or(VkRenderError::InvalidState("UI texture table is full"))?;
+                (table.set, slot)
+            }
+            None => {
+                let layouts = [self.ui.desc_set_layout];
+                let set = self.core.device.allocate_descriptor_sets(
+                    &vk::DescriptorSetAllocateInfo::default()
+                        .descriptor_pool(self.ui.desc_pool)
+                        .set_layouts(&layouts),
+                )?[0];
+                (set, 0)
+            }
+        };
 
-            // Creates/replaces textures and schedules their uploads.
-            for (id, tex) in &delta.set {
-                let offset = cursor;
-                let bytes = tex.rgba8.len() as vk::DeviceSize;
-                ptr::copy_nonoverlapping(
-                    tex.rgba8.as_ptr(),
-                    mapped.add(offset as usize),
-                    bytes as usize,
-                );
-                cursor += bytes;
+        let image_info = vk::DescriptorImageInfo::default()
+            .sampler(self.ui.sampler)
+            .image_view(view)
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
 
-                let gpu = self.ui_create_texture_objects(*id, tex.size)?;
+        let write = vk::WriteDescriptorSet::default()
+            .dst_set(set)
+            .dst_binding(0)
+            .dst_array_element(slot)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .image_info(std::slice::from_ref(&image_info));
 
-                ops.push(UploadOp {
-                    image: gpu.image,
-                    old_layout: vk::ImageLayout::UNDEFINED,
-                    offset,
-                    extent: vk::Extent3D {
-                        width: tex.size[0],
-                        height: tex.size[1],
-                        depth: 1,
-                    },
-                    kind: UploadKind::New,
-                });
-            }
+        self.core
+            .device
+            .update_descriptor_sets(std::slice::from_ref(&write), &[]);
+        Ok((set, slot))
+    }
 
-            // Schedules patches.
-            for p in &delta.patches {
-                let Some(tex) = self.ui.textures.get(&p.id.0) else {
-                    continue;
-                };
-
-                let offset = cursor;
-                let bytes = p.rgba8.len() as vk::DeviceSize;
-                ptr::copy_nonoverlapping(
-                    p.rgba8.as_ptr(),
-                    mapped.add(offset as usize),
-                    bytes as usize,
-                );
-                cursor += bytes;
-
-                ops.push(UploadOp {
-                    image: tex.image,
-                    old_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
-                    offset,
-                    extent: vk::Extent3D {
-                        width: p.size[0],
-                        height: p.size[1],
-                        depth: 1,
-                    },
-                    kind: UploadKind::Patch { origin: p.origin },
-                });
+    /// Releases what `ui_bind_view` handed out for `tex`.
+    unsafe fn ui_unbind(&mut self, tex: &GpuUiTexture) {
+        if let Some(table) = self.ui.table.as_mut() {
+            if tex.desc_set == table.set {
+                table.release(tex.slot);
             }
+            return;
+        }
+        if tex.desc_set != vk::DescriptorSet::null()
+            && self.ui.desc_pool != vk::DescriptorPool::null()
+        {
+            let _ = self
+                .core
+                .device
+                .free_descriptor_sets(self.ui.desc_pool, &[tex.desc_set]);
+        }
+    }
 
-            debug_assert!(cursor == total_bytes);
-
-            self.core.device.unmap_memory(self.ui.staging_mem);
+    pub(super) unsafe fn ui_apply_delta(&mut self, delta: &UiTextureDelta) -> VkResult<()> {
+        // Creates/replaces textures and queues their uploads; the batch is flushed before the
+        // frame that samples them is submitted.
+        for (id, tex) in &delta.set {
+            let gpu = self.ui_create_texture_objects(*id, tex.size)?;
+            self.upload_image(
+                &tex.rgba8,
+                ImageUpload {
+                    image: gpu.image,
+                    mip_levels: 1,
+                    old_layout: vk::ImageLayout::UNDEFINED,
+                    region: ui_upload_region([0, 0], tex.size),
+                },
+            )?;
+            self.ui.shadow.insert(id.0, tex.clone());
+        }
 
-            // One submit for the whole delta.
-            immediate_submit(
-                &self.core.device,
-                self.frames.upload_command_pool,
-                self.core.queue,
-                |cmd| {
-                    for op in &ops {
-                        transition_image_layout(
-                            &self.core.device,
-                            cmd,
-                            op.image,
-                            op.old_layout,
-                            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-                        );
-
-                        let mut region = vk::BufferImageCopy::default()
-                            .buffer_offset(op.offset)
-                            .buffer_row_length(0)
-                            .buffer_image_height(0)
-                            .image_subresource(
-                                vk::ImageSubresourceLayers::default()
-                                    .aspect_mask(vk::ImageAspectFlags::COLOR)
-                                    .mip_level(0)
-                                    .base_array_layer(0)
-                                    .layer_count(1),
-                            )
-                            .image_extent(op.extent);
-
-                        if let UploadKind::Patch { origin } = op.kind {
-                            region = region.image_offset(vk::Offset3D {
-                                x: origin[0] as i32,
-                                y: origin[1] as i32,
-                                z: 0,
-                            });
-                        } else {
-                            region = region.image_offset(vk::Offset3D { x: 0, y: 0, z: 0 });
-                        }
-
-                        self.core.device.cmd_copy_buffer_to_image(
-                            cmd,
-                            self.ui.staging_buf,
-                            op.image,
-                            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-                            std::slice::from_ref(&region),
-                        );
-
-                        transition_image_layout(
-                            &self.core.device,
-                            cmd,
-                            op.image,
-                            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-                            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
-                        );
-                    }
+        // Queues patches.
+        for p in &delta.patches {
+            let Some(tex) = self.ui.textures.get(&p.id.0) else {
+                continue;
+            };
+            let image = tex.image;
+            self.upload_image(
+                &p.rgba8,
+                ImageUpload {
+                    image,
+                    mip_levels: 1,
+                    old_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                    region: ui_upload_region(p.origin, p.size),
                 },
             )?;
-        } else {
-            // No uploads, but we still may free textures.
-            for (id, tex) in &delta.set {
-                let _ = (id, tex);
+            if let Some(shadow) = self.ui.shadow.get_mut(&p.id.0) {
+                ui_patch_shadow(shadow, p.origin, p.size, &p.rgba8);
             }
         }
 
@@ -313,48 +362,57 @@ impl VulkanRenderer {
         Ok(())
     }
 
-    unsafe fn ui_free_texture(&mut self, id: UiTexId) {
+    /// Takes the CPU copies of the UI textures, for `ui_restore` on the renderer of a new
+    /// device.
+    #[inline]
+    pub(crate) fn ui_take_textures(&mut self) -> HashMap<u32, UiTexture> {
+        mem::take(&mut self.ui.shadow)
+    }
+
+    /// Recreates UI textures taken from the renderer of a lost device.
+    pub(crate) unsafe fn ui_restore(&mut self, textures: HashMap<u32, UiTexture>) -> VkResult<()> {
+        let mut delta = UiTextureDelta::new();
+        delta.set = textures
+            .into_iter()
+            .map(|(id, tex)| (UiTexId::new(id), tex))
+            .collect();
+        self.ui_apply_delta(&delta)
+    }
+
+    pub(crate) unsafe fn ui_free_texture(&mut self, id: UiTexId) {
+        self.ui.shadow.remove(&id.0);
         if let Some(tex) = self.ui.textures.remove(&id.0) {
-            if tex.desc_set != vk::DescriptorSet::null()
-                && self.ui.desc_pool != vk::DescriptorPool::null()
-            {
-                let _ = self
-                    .core
-                    .device
-                    .free_descriptor_sets(self.ui.desc_pool, &[tex.desc_set]);
-            }
+            self.forget_image(tex.image);
+            self.ui_unbind(&tex);
             self.core.device.destroy_image_view(tex.view, None);
             self.core.device.destroy_image(tex.image, None);
             self.core.device.free_memory(tex.mem, None);
         }
     }
 
-    unsafe fn ui_ensure_staging(&mut self, required: vk::DeviceSize) -> VkResult<()> {
-        if self.ui.staging_buf != vk::Buffer::null() && required <= self.ui.staging_size {
-            return Ok(());
-        }
-
-        if self.ui.staging_buf != vk::Buffer::null() {
-            self.core.device.destroy_buffer(self.ui.staging_buf, None);
-            self.ui.staging_buf = vk::Buffer::null();
-        }
-        if self.ui.staging_mem != vk::DeviceMemory::null() {
-            self.core.device.free_memory(self.ui.staging_mem, None);
-            self.ui.staging_mem = vk::DeviceMemory::null();
-        }
-
-        self.ui.staging_size = required.max(64 * 1024);
-        let (buf, mem) = create_buffer(
-            &self.core.instance,
-            self.core.physical_device,
-            &self.core.device,
-            self.ui.staging_size,
-            vk::BufferUsageFlags::TRANSFER_SRC,
-            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
-        )?;
+    /// Shows an image owned elsewhere (e.g. an offscreen render target) under `id`.
+    /// Only the descriptor set belongs to the overlay; the image must stay in
+    /// `SHADER_READ_ONLY_OPTIMAL` whenever a frame samples it.
+    pub(crate) unsafe fn ui_register_external(
+        &mut self,
+        id: UiTexId,
+        view: vk::ImageView,
+    ) -> VkResult<()> {
+        self.ui_free_texture(id);
 
-        self.ui.staging_buf = buf;
-        self.ui.staging_mem = mem;
+        let (desc_set, slot) = self.ui_bind_view(view)?;
+
+        // Null handles: `ui_free_texture` only releases the descriptor.
+        self.ui.textures.insert(
+            id.0,
+            GpuUiTexture {
+                image: vk::Image::null(),
+                mem: vk::DeviceMemory::null(),
+                view: vk::ImageView::null(),
+                desc_set,
+                slot,
+            },
+        );
         Ok(())
     }
 
@@ -414,89 +472,20 @@ impl VulkanRenderer {
 
         let view = self.core.device.create_image_view(&view_info, None)?;
 
-        let layouts = [self.ui.desc_set_layout];
-        let desc_set = self.core.device.allocate_descriptor_sets(
-            &vk::DescriptorSetAllocateInfo::default()
-                .descriptor_pool(self.ui.desc_pool)
-                .set_layouts(&layouts),
-        )?[0];
-
-        let image_info = vk::DescriptorImageInfo::default()
-            .sampler(self.ui.sampler)
-            .image_view(view)
-            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
-
-        let write = vk::WriteDescriptorSet::default()
-            .dst_set(desc_set)
-            .dst_binding(0)
-            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
-            .image_info(std::slice::from_ref(&image_info));
-
-        self.core
-            .device
-            .update_descriptor_sets(std::slice::from_ref(&write), &[]);
+        let (desc_set, slot) = self.ui_bind_view(view)?;
 
         let gpu = GpuUiTexture {
             image,
             mem,
             view,
             desc_set,
+            slot,
         };
 
         self.ui.textures.insert(id.0, gpu);
         Ok(gpu)
     }
 
-    pub(super) unsafe fn ui_ensure_buffers(
-        &mut self,
-        vb_bytes: vk::DeviceSize,
-        ib_bytes: vk::DeviceSize,
-    ) -> VkResult<()> {
-        if self.ui.vb == vk::Buffer::null() || vb_bytes > self.ui.vb_size {
-            if self.ui.vb != vk::Buffer::null() {
-                self.core.device.destroy_buffer(self.ui.vb, None);
-            }
-            if self.ui.vb_mem != vk::DeviceMemory::null() {
-                self.core.device.free_memory(self.ui.vb_mem, None);
-            }
-
-            self.ui.vb_size = vb_bytes.max(64 * 1024);
-            let (buf, mem) = create_buffer(
-                &self.core.instance,
-                self.core.physical_device,
-                &self.core.device,
-                self.ui.vb_size,
-                vk::BufferUsageFlags::VERTEX_BUFFER,
-                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
-            )?;
-            self.ui.vb = buf;
-            self.ui.vb_mem = mem;
-        }
-
-        if self.ui.ib == vk::Buffer::null() || ib_bytes > self.ui.ib_size {
-            if self.ui.ib != vk::Buffer::null() {
-                self.core.device.destroy_buffer(self.ui.ib, None);
-            }
-            if self.ui.ib_mem != vk::DeviceMemory::null() {
-                self.core.device.free_memory(self.ui.ib_mem, None);
-            }
-
-            self.ui.ib_size = ib_bytes.max(64 * 1024);
-            let (buf, mem) = create_buffer(
-                &self.core.instance,
-                self.core.physical_device,
-                &self.core.device,
-                self.ui.ib_size,
-                vk::BufferUsageFlags::INDEX_BUFFER,
-                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
-            )?;
-            self.ui.ib = buf;
-            self.ui.ib_mem = mem;
-        }
-
-        Ok(())
-    }
-
     pub(crate) unsafe fn ui_upload_and_draw(
         &mut self,
         cmd: vk::CommandBuffer,
@@ -508,11 +497,18 @@ impl VulkanRenderer {
             as vk::DeviceSize;
         let ib_bytes = (mem::size_of::<u32>() * list.mesh.indices.len()) as vk::DeviceSize;
 
-        self.ui_ensure_buffers(vb_bytes, ib_bytes)?;
+        // This slot's previous frame completed in `begin_frame`, so its buffers are free.
+        let slot = self.frames.frame_index;
+        let vb = std::mem::take(&mut self.ui.vertex_buffers[slot]);
+        let vb = self.ensure_frame_buffer(vb, vb_bytes, vk::BufferUsageFlags::VERTEX_BUFFER)?;
+        self.ui.vertex_buffers[slot] = vb;
+        let ib = std::mem::take(&mut self.ui.index_buffers[slot]);
+        let ib = self.ensure_frame_buffer(ib, ib_bytes, vk::BufferUsageFlags::INDEX_BUFFER)?;
+        self.ui.index_buffers[slot] = ib;
 
         if !list.mesh.vertices.is_empty() {
             let mapped = self.core.device.map_memory(
-                self.ui.vb_mem,
+                vb.mem,
                 0,
                 vb_bytes,
                 vk::MemoryMapFlags::empty(),
@@ -522,12 +518,12 @@ impl VulkanRenderer {
                 mapped,
                 vb_bytes as usize,
             );
-            self.core.device.unmap_memory(self.ui.vb_mem);
+            self.core.device.unmap_memory(vb.mem);
         }
 
         if !list.mesh.indices.is_empty() {
             let mapped = self.core.device.map_memory(
-                self.ui.ib_mem,
+                ib.mem,
                 0,
                 ib_bytes,
                 vk::MemoryMapFlags::empty(),
@@ -537,7 +533,7 @@ impl VulkanRenderer {
                 mapped,
                 ib_bytes as usize,
             );
-            self.core.device.unmap_memory(self.ui.ib_mem);
+            self.core.device.unmap_memory(ib.mem);
         }
 
         if list.mesh.indices.is_empty()
@@ -558,29 +554,36 @@ impl VulkanRenderer {
         self.core.device.cmd_push_constants(
             cmd,
             self.pipelines.ui_pipeline_layout,
-            vk::ShaderStageFlags::VERTEX,
+            ui_pc_stages(self.ui.table.is_some()),
             0,
             &pc,
         );
 
-        let vb = [self.ui.vb];
+        let vbs = [vb.buf];
         let offsets = [0u64];
         self.core
             .device
-            .cmd_bind_vertex_buffers(cmd, 0, &vb, &offsets);
+            .cmd_bind_vertex_buffers(cmd, 0, &vbs, &offsets);
         self.core
             .device
-            .cmd_bind_index_buffer(cmd, self.ui.ib, 0, vk::IndexType::UINT32);
+            .cmd_bind_index_buffer(cmd, ib.buf, 0, vk::IndexType::UINT32);
 
+        // Consecutive commands on the same texture (or any, with the table) skip the rebind.
+        let mut bound = None;
         for c in &list.mesh.cmds {
-            self.ui_draw_cmd(cmd, c)?;
+            self.ui_draw_cmd(cmd, c, &mut bound)?;
         }
 
         Ok(())
     }
 
-    unsafe fn ui_draw_cmd(&mut self, cmd: vk::CommandBuffer, c: &UiDrawCmd) -> VkResult<()> {
-        let Some(tex) = self.ui.textures.get(&c.texture.0) else {
+    unsafe fn ui_draw_cmd(
+        &mut self,
+        cmd: vk::CommandBuffer,
+        c: &UiDrawCmd,
+        bound: &mut Option<(vk::DescriptorSet, u32)>,
+    ) -> VkResult<()> {
+        let Some(&tex) = self.ui.textures.get(&c.texture.0) else {
             return Ok(());
         };
 
@@ -610,14 +613,26 @@ impl VulkanRenderer {
             .device
             .cmd_set_scissor(cmd, 0, std::slice::from_ref(&sc));
 
-        self.core.device.cmd_bind_descriptor_sets(
-            cmd,
-            vk::PipelineBindPoint::GRAPHICS,
-            self.pipelines.ui_pipeline_layout,
-            0,
-            std::slice::from_ref(&tex.desc_set),
-            &[],
-        );
+        if bound.map(|(set, _)| set) != Some(tex.desc_set) {
+            self.core.device.cmd_bind_descriptor_sets(
+                cmd,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.pipelines.ui_pipeline_layout,
+                0,
+                std::slice::from_ref(&tex.desc_set),
+                &[],
+            );
+        }
+        if self.ui.table.is_some() && bound.map(|(_, slot)| slot) != Some(tex.slot) {
+            self.core.device.cmd_push_constants(
+                cmd,
+                self.pipelines.ui_pipeline_layout,
+                ui_pc_stages(true),
+                UI_PC_TEXTURE_OFFSET,
+                &tex.slot.to_ne_bytes(),
+            );
+        }
+        *bound = Some((tex.desc_set, tex.slot));
 
         let first_index = c.index_range.start;
         let index_count = c.index_range.end.saturating_sub(c.index_range.start);
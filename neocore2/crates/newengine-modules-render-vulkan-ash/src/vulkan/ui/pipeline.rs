@@ -9,22 +9,42 @@ use super::super::pipeline::create_shader_module;
 #[derive(Clone, Copy)]
 struct UiPc {
     screen_size: [f32; 2],
-    _pad: [f32; 2],
+    /// Texture table element sampled by the fragment stage; unused without the table.
+    texture: u32,
+    _pad: u32,
 }
 
+/// Offset of `UiPc::texture`, pushed on its own between draws.
+pub(super) const UI_PC_TEXTURE_OFFSET: u32 = mem::offset_of!(UiPc, texture) as u32;
+
+/// Stages of the UI push constant range; the texture index is read by the fragment stage.
+#[inline]
+pub(super) fn ui_pc_stages(texture_table: bool) -> vk::ShaderStageFlags {
+    if texture_table {
+        vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT
+    } else {
+        vk::ShaderStageFlags::VERTEX
+    }
+}
+
+/// `texture_table` selects the fragment shader indexing one descriptor array instead of
+/// sampling a per-texture set.
 pub unsafe fn create_ui_pipeline(
     device: &ash::Device,
     render_pass: vk::RenderPass,
     set_layout: vk::DescriptorSetLayout,
+    texture_table: bool,
 ) -> VkResult<(vk::PipelineLayout, vk::Pipeline)> {
     let vert = create_shader_module(
         device,
         include_bytes!(concat!(env!("OUT_DIR"), "/ui.vert.spv")),
     )?;
-    let frag = create_shader_module(
-        device,
-        include_bytes!(concat!(env!("OUT_DIR"), "/ui.frag.spv")),
-    )?;
+    let frag_spv: &[u8] = if texture_table {
+        include_bytes!(concat!(env!("OUT_DIR"), "/ui_bindless.frag.spv"))
+    } else {
+        include_bytes!(concat!(env!("OUT_DIR"), "/ui.frag.spv"))
+    };
+    let frag = create_shader_module(device, frag_spv)?;
 
     let entry = std::ffi::CString::new("main").unwrap();
 
@@ -104,7 +124,7 @@ pub unsafe fn create_ui_pipeline(
     let ds = vk::PipelineDynamicStateCreateInfo::default().dynamic_states(&dyn_states);
 
     let push_ranges = [vk::PushConstantRange::default()
-        .stage_flags(vk::ShaderStageFlags::VERTEX)
+        .stage_flags(ui_pc_stages(texture_table))
         .offset(0)
         .size(mem::size_of::<UiPc>() as u32)];
 
@@ -144,7 +164,8 @@ pub unsafe fn create_ui_pipeline(
 pub(super) fn ui_pc_bytes(screen_size_px: [u32; 2]) -> [u8; std::mem::size_of::<UiPc>()] {
     let pc = UiPc {
         screen_size: [screen_size_px[0] as f32, screen_size_px[1] as f32],
-        _pad: [0.0, 0.0],
+        texture: 0,
+        _pad: 0,
     };
 
     unsafe { std::mem::transmute::<UiPc, [u8; std::mem::size_of::<UiPc>()]>(pc) }
@@ -1,6 +1,9 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+use std::path::PathBuf;
+
 use newengine_core::startup::UiBackend;
+use winit::keyboard::KeyCode;
 
 /// Window placement policy.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,6 +16,74 @@ pub enum WinitWindowPlacement {
     Absolute { x: i32, y: i32 },
 }
 
+/// Fullscreen mode. Monitor indices follow `WinitDisplay::monitors()`; `None` selects
+/// the monitor the window currently occupies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WinitFullscreen {
+    Windowed,
+    Borderless {
+        monitor: Option<usize>,
+    },
+    /// Exclusive video mode. Unset fields resolve to the largest size / highest refresh
+    /// the monitor offers; otherwise the closest available mode is used.
+    Exclusive {
+        monitor: Option<usize>,
+        size: Option<(u32, u32)>,
+        refresh_mhz: Option<u32>,
+    },
+}
+
+impl WinitFullscreen {
+    #[inline]
+    pub fn is_fullscreen(&self) -> bool {
+        !matches!(self, Self::Windowed)
+    }
+}
+
+impl Default for WinitFullscreen {
+    #[inline]
+    fn default() -> Self {
+        Self::Windowed
+    }
+}
+
+/// Key chord handled by the host before input is forwarded to plugins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WinitHotkey {
+    pub key: KeyCode,
+    pub alt: bool,
+    pub ctrl: bool,
+    pub shift: bool,
+}
+
+impl WinitHotkey {
+    #[inline]
+    pub const fn alt(key: KeyCode) -> Self {
+        Self {
+            key,
+            alt: true,
+            ctrl: false,
+            shift: false,
+        }
+    }
+}
+
+/// How `WinitAppConfig::size` is interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WinitDpiMode {
+    /// Physical pixels; the window looks smaller on high-DPI monitors.
+    Physical,
+    /// Logical pixels, scaled by the monitor's scale factor.
+    Logical,
+}
+
+impl Default for WinitDpiMode {
+    #[inline]
+    fn default() -> Self {
+        Self::Physical
+    }
+}
+
 /// Window icon payload (RGBA8).
 #[derive(Debug, Clone)]
 pub struct WinitAppIcon {
@@ -43,11 +114,18 @@ impl WinitAppIcon {
 pub struct WinitAppConfig {
     pub title: String,
     pub size: (u32, u32),
+    pub dpi_mode: WinitDpiMode,
     pub placement: WinitWindowPlacement,
     pub ui_backend: UiBackend,
+    pub fullscreen: WinitFullscreen,
+    /// Toggles between windowed and the last fullscreen mode. `None` disables the binding.
+    pub fullscreen_hotkey: Option<WinitHotkey>,
 
     /// Optional window icon.
     pub icon: Option<WinitAppIcon>,
+    /// Dropped files are copied into this directory under the assets root and queued for
+    /// import. `None` only reports drops as events.
+    pub drop_import_dir: Option<PathBuf>,
 }
 
 impl Default for WinitAppConfig {
@@ -56,9 +134,13 @@ impl Default for WinitAppConfig {
         Self {
             title: "NewEngine".to_owned(),
             size: (1280, 720),
+            dpi_mode: WinitDpiMode::Physical,
             placement: WinitWindowPlacement::Centered { offset: (0, 0) },
             ui_backend: UiBackend::Egui,
+            fullscreen: WinitFullscreen::Windowed,
+            fullscreen_hotkey: Some(WinitHotkey::alt(KeyCode::Enter)),
             icon: None,
+            drop_import_dir: None,
         }
     }
-}
\ No newline at end of file
+}
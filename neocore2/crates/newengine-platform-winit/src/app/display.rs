@@ -0,0 +1,310 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use std::cmp::Reverse;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::monitor::{MonitorHandle, VideoModeHandle};
+use winit::window::{Fullscreen, Window};
+
+use crate::app::config::WinitFullscreen;
+use crate::app::input_bridge::emit_plugin_json;
+
+/// One exclusive-fullscreen video mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WinitVideoModeInfo {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_mhz: u32,
+    pub bit_depth: u16,
+}
+
+/// Monitor snapshot. `index` is the value accepted by [`WinitFullscreen`].
+#[derive(Debug, Clone)]
+pub struct WinitMonitorInfo {
+    pub index: usize,
+    pub name: String,
+    pub position: (i32, i32),
+    pub size: (u32, u32),
+    pub scale_factor: f64,
+    pub refresh_mhz: Option<u32>,
+    pub primary: bool,
+    pub video_modes: Vec<WinitVideoModeInfo>,
+}
+
+#[derive(Default)]
+struct Shared {
+    monitors: Vec<WinitMonitorInfo>,
+    current: WinitFullscreen,
+    /// Main window scale factor; `None` until the window exists.
+    scale_factor: Option<f64>,
+    pending: Option<WinitFullscreen>,
+    toggle: bool,
+    rescan: bool,
+}
+
+/// Display resource installed by the winit host (`resources.get::<WinitDisplay>()`).
+///
+/// Monitor lists are refreshed on the host thread; mode changes are queued and applied
+/// before the next engine step.
+#[derive(Clone, Default)]
+pub struct WinitDisplay {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl WinitDisplay {
+    #[inline]
+    pub fn monitors(&self) -> Vec<WinitMonitorInfo> {
+        self.shared.lock().monitors.clone()
+    }
+
+    #[inline]
+    pub fn monitor(&self, index: usize) -> Option<WinitMonitorInfo> {
+        self.shared.lock().monitors.get(index).cloned()
+    }
+
+    /// Mode currently applied to the window.
+    #[inline]
+    pub fn fullscreen(&self) -> WinitFullscreen {
+        self.shared.lock().current
+    }
+
+    #[inline]
+    pub fn set_fullscreen(&self, mode: WinitFullscreen) {
+        let mut g = self.shared.lock();
+        g.pending = Some(mode);
+        g.toggle = false;
+    }
+
+    #[inline]
+    pub fn set_windowed(&self) {
+        self.set_fullscreen(WinitFullscreen::Windowed);
+    }
+
+    /// Switches between windowed and the most recently used fullscreen mode.
+    #[inline]
+    pub fn toggle_fullscreen(&self) {
+        let mut g = self.shared.lock();
+        g.pending = None;
+        g.toggle = !g.toggle;
+    }
+
+    /// DPI scale of the main window (1.0 before it is created).
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.shared.lock().scale_factor.unwrap_or(1.0)
+    }
+
+    /// Re-enumerates monitors and video modes (e.g. after hot-plugging a display).
+    #[inline]
+    pub fn rescan(&self) {
+        self.shared.lock().rescan = true;
+    }
+}
+
+/// Host-side fullscreen state. Lives on the event-loop thread.
+pub(crate) struct DisplayBridge {
+    shared: WinitDisplay,
+    /// Outer position and inner size captured when leaving windowed mode.
+    windowed: Option<(Option<PhysicalPosition<i32>>, PhysicalSize<u32>)>,
+    last_fullscreen: WinitFullscreen,
+}
+
+impl DisplayBridge {
+    pub(crate) fn new(initial: WinitFullscreen) -> Self {
+        let last_fullscreen = if initial.is_fullscreen() {
+            initial
+        } else {
+            WinitFullscreen::Borderless { monitor: None }
+        };
+
+        Self {
+            shared: WinitDisplay::default(),
+            windowed: None,
+            last_fullscreen,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn resource(&self) -> WinitDisplay {
+        self.shared.clone()
+    }
+
+    pub(crate) fn refresh_monitors(&self, window: &Window) {
+        let primary = window.primary_monitor();
+        let monitors: Vec<WinitMonitorInfo> = window
+            .available_monitors()
+            .enumerate()
+            .map(|(index, m)| describe_monitor(index, &m, primary.as_ref() == Some(&m)))
+            .collect();
+
+        let mut g = self.shared.shared.lock();
+        g.monitors = monitors;
+        g.rescan = false;
+    }
+
+    #[inline]
+    pub(crate) fn set_scale_factor(&self, scale_factor: f64) {
+        self.shared.shared.lock().scale_factor = Some(scale_factor);
+    }
+
+    /// Applies queued requests from [`WinitDisplay`].
+    pub(crate) fn poll(&mut self, window: &Window) {
+        let (pending, toggle, rescan) = {
+            let mut g = self.shared.shared.lock();
+            (g.pending.take(), std::mem::take(&mut g.toggle), g.rescan)
+        };
+
+        if rescan {
+            self.refresh_monitors(window);
+        }
+        if toggle {
+            self.toggle(window);
+        } else if let Some(mode) = pending {
+            self.apply(window, mode);
+        }
+    }
+
+    pub(crate) fn toggle(&mut self, window: &Window) {
+        let next = if window.fullscreen().is_some() {
+            WinitFullscreen::Windowed
+        } else {
+            self.last_fullscreen
+        };
+        self.apply(window, next);
+    }
+
+    pub(crate) fn apply(&mut self, window: &Window, mode: WinitFullscreen) {
+        let was_windowed = window.fullscreen().is_none();
+
+        let target = match mode {
+            WinitFullscreen::Windowed => None,
+            WinitFullscreen::Borderless { monitor } => {
+                Some(Fullscreen::Borderless(pick_monitor(window, monitor)))
+            }
+            WinitFullscreen::Exclusive {
+                monitor,
+                size,
+                refresh_mhz,
+            } => {
+                let Some(m) = pick_monitor(window, monitor) else {
+                    log::warn!("display: exclusive fullscreen requested but no monitor is available");
+                    return;
+                };
+                let Some(vm) = pick_video_mode(&m, size, refresh_mhz) else {
+                    log::warn!(
+                        "display: monitor '{}' reports no video modes",
+                        m.name().unwrap_or_default()
+                    );
+                    return;
+                };
+                Some(Fullscreen::Exclusive(vm))
+            }
+        };
+
+        match target {
+            Some(fs) => {
+                if was_windowed {
+                    self.windowed = Some((window.outer_position().ok(), window.inner_size()));
+                }
+                window.set_fullscreen(Some(fs));
+                self.last_fullscreen = mode;
+            }
+            None => {
+                if was_windowed {
+                    return;
+                }
+                window.set_fullscreen(None);
+                if let Some((pos, size)) = self.windowed.take() {
+                    let _ = window.request_inner_size(size);
+                    if let Some(pos) = pos {
+                        window.set_outer_position(pos);
+                    }
+                }
+            }
+        }
+
+        self.shared.shared.lock().current = mode;
+        log::info!("display: fullscreen={mode:?}");
+        emit_plugin_json(
+            "winit.fullscreen",
+            serde_json::json!({ "mode": mode_str(mode), "fullscreen": mode.is_fullscreen() }),
+        );
+    }
+}
+
+#[inline]
+fn mode_str(mode: WinitFullscreen) -> &'static str {
+    match mode {
+        WinitFullscreen::Windowed => "windowed",
+        WinitFullscreen::Borderless { .. } => "borderless",
+        WinitFullscreen::Exclusive { .. } => "exclusive",
+    }
+}
+
+fn describe_monitor(index: usize, m: &MonitorHandle, primary: bool) -> WinitMonitorInfo {
+    let PhysicalPosition { x, y } = m.position();
+    let PhysicalSize { width, height } = m.size();
+
+    let mut video_modes: Vec<WinitVideoModeInfo> = m
+        .video_modes()
+        .map(|vm| {
+            let PhysicalSize { width, height } = vm.size();
+            WinitVideoModeInfo {
+                width,
+                height,
+                refresh_mhz: vm.refresh_rate_millihertz(),
+                bit_depth: vm.bit_depth(),
+            }
+        })
+        .collect();
+    video_modes.sort_by_key(|v| Reverse((v.width * v.height, v.refresh_mhz, v.bit_depth)));
+    video_modes.dedup();
+
+    WinitMonitorInfo {
+        index,
+        name: m.name().unwrap_or_else(|| format!("monitor {index}")),
+        position: (x, y),
+        size: (width, height),
+        scale_factor: m.scale_factor(),
+        refresh_mhz: m.refresh_rate_millihertz(),
+        primary,
+        video_modes,
+    }
+}
+
+fn pick_monitor(window: &Window, index: Option<usize>) -> Option<MonitorHandle> {
+    match index {
+        Some(i) => match window.available_monitors().nth(i) {
+            Some(m) => Some(m),
+            None => {
+                log::warn!("display: monitor {i} not found; using the current monitor");
+                window.current_monitor()
+            }
+        },
+        None => window.current_monitor(),
+    }
+    .or_else(|| window.primary_monitor())
+}
+
+/// Exact or closest size first, then closest refresh rate, then deepest colour.
+fn pick_video_mode(
+    m: &MonitorHandle,
+    size: Option<(u32, u32)>,
+    refresh_mhz: Option<u32>,
+) -> Option<VideoModeHandle> {
+    m.video_modes().min_by_key(|vm| {
+        let PhysicalSize { width, height } = vm.size();
+        let size_cost = match size {
+            Some((w, h)) => (width as i64 - w as i64).abs() + (height as i64 - h as i64).abs(),
+            None => -(width as i64 * height as i64),
+        };
+        let refresh = vm.refresh_rate_millihertz() as i64;
+        let refresh_cost = match refresh_mhz {
+            Some(r) => (refresh - r as i64).abs(),
+            None => -refresh,
+        };
+        (size_cost, refresh_cost, Reverse(vm.bit_depth()))
+    })
+}
@@ -0,0 +1,340 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Replay, Ticks};
+use gilrs::{Axis, Button, EventType, Gilrs};
+use newengine_core::host_events::{
+    GamepadAxis, GamepadButton, GamepadHostEvent, GamepadId, HostEvent,
+};
+use newengine_core::Engine;
+use parking_lot::Mutex;
+
+use crate::app::input_bridge::emit_plugin_json;
+
+/// Snapshot of one controller.
+#[derive(Debug, Clone, Default)]
+pub struct GamepadState {
+    pub name: String,
+    pub connected: bool,
+    pub rumble: bool,
+    pub buttons: HashMap<GamepadButton, f32>,
+    pub axes: HashMap<GamepadAxis, f32>,
+}
+
+impl GamepadState {
+    #[inline]
+    pub fn button(&self, b: GamepadButton) -> f32 {
+        self.buttons.get(&b).copied().unwrap_or(0.0)
+    }
+
+    #[inline]
+    pub fn is_pressed(&self, b: GamepadButton) -> bool {
+        self.button(b) > 0.5
+    }
+
+    #[inline]
+    pub fn axis(&self, a: GamepadAxis) -> f32 {
+        self.axes.get(&a).copied().unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RumbleRequest {
+    id: GamepadId,
+    strong: f32,
+    weak: f32,
+    duration: Duration,
+}
+
+#[derive(Default)]
+struct Shared {
+    pads: HashMap<GamepadId, GamepadState>,
+    rumble: Vec<RumbleRequest>,
+}
+
+/// Gamepad resource installed by the winit host (`resources.get::<WinitGamepads>()`).
+///
+/// State is updated on the host thread before every engine step; rumble requests are
+/// queued and applied on the next poll.
+#[derive(Clone, Default)]
+pub struct WinitGamepads {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl WinitGamepads {
+    /// Connected controllers, sorted by id.
+    pub fn connected(&self) -> Vec<GamepadId> {
+        let g = self.shared.lock();
+        let mut ids: Vec<GamepadId> = g
+            .pads
+            .iter()
+            .filter(|(_, s)| s.connected)
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[inline]
+    pub fn state(&self, id: GamepadId) -> Option<GamepadState> {
+        self.shared.lock().pads.get(&id).cloned()
+    }
+
+    #[inline]
+    pub fn button(&self, id: GamepadId, b: GamepadButton) -> f32 {
+        self.shared
+            .lock()
+            .pads
+            .get(&id)
+            .map_or(0.0, |s| s.button(b))
+    }
+
+    #[inline]
+    pub fn axis(&self, id: GamepadId, a: GamepadAxis) -> f32 {
+        self.shared.lock().pads.get(&id).map_or(0.0, |s| s.axis(a))
+    }
+
+    /// Plays a dual-motor rumble. Magnitudes are 0..=1; a zero duration stops it.
+    pub fn rumble(&self, id: GamepadId, strong: f32, weak: f32, duration: Duration) {
+        self.shared.lock().rumble.push(RumbleRequest {
+            id,
+            strong: strong.clamp(0.0, 1.0),
+            weak: weak.clamp(0.0, 1.0),
+            duration,
+        });
+    }
+
+    #[inline]
+    pub fn stop_rumble(&self, id: GamepadId) {
+        self.rumble(id, 0.0, 0.0, Duration::ZERO);
+    }
+}
+
+/// Host-side gilrs owner. Lives on the event-loop thread.
+pub(crate) struct GamepadBridge {
+    gilrs: Option<Gilrs>,
+    shared: WinitGamepads,
+    ids: HashMap<GamepadId, gilrs::GamepadId>,
+    effects: HashMap<GamepadId, Effect>,
+}
+
+impl GamepadBridge {
+    pub(crate) fn new() -> Self {
+        let gilrs = match Gilrs::new() {
+            Ok(g) => Some(g),
+            Err(e) => {
+                log::warn!("gamepad: gilrs init failed: {e}");
+                None
+            }
+        };
+
+        Self {
+            gilrs,
+            shared: WinitGamepads::default(),
+            ids: HashMap::new(),
+            effects: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn resource(&self) -> WinitGamepads {
+        self.shared.clone()
+    }
+
+    /// Reports controllers that were already connected at startup.
+    pub(crate) fn announce_connected<E: Send + 'static>(&mut self, engine: &Engine<E>) {
+        let Some(gilrs) = self.gilrs.as_ref() else {
+            return;
+        };
+
+        let present: Vec<gilrs::GamepadId> = gilrs.gamepads().map(|(id, _)| id).collect();
+        for gid in present {
+            self.on_connected(engine, gid);
+        }
+    }
+
+    pub(crate) fn poll<E: Send + 'static>(&mut self, engine: &Engine<E>) {
+        if self.gilrs.is_none() {
+            return;
+        }
+
+        while let Some(ev) = self.gilrs.as_mut().and_then(|g| g.next_event()) {
+            let id = map_id(ev.id);
+
+            match ev.event {
+                EventType::Connected => self.on_connected(engine, ev.id),
+                EventType::Disconnected => {
+                    self.effects.remove(&id);
+                    if let Some(st) = self.shared.shared.lock().pads.get_mut(&id) {
+                        st.connected = false;
+                    }
+                    emit_plugin_json("winit.gamepad_disconnected", serde_json::json!({ "id": id.0 }));
+                    let _ = engine.emit(HostEvent::Gamepad(GamepadHostEvent::Disconnected { id }));
+                }
+                EventType::ButtonPressed(b, _) => self.on_button(engine, id, b, 1.0),
+                EventType::ButtonReleased(b, _) => self.on_button(engine, id, b, 0.0),
+                EventType::ButtonChanged(b, v, _) => self.on_button(engine, id, b, v),
+                EventType::AxisChanged(a, v, _) => self.on_axis(engine, id, a, v),
+                _ => {}
+            }
+        }
+
+        self.apply_rumble();
+    }
+
+    fn on_connected<E: Send + 'static>(&mut self, engine: &Engine<E>, gid: gilrs::GamepadId) {
+        let Some(gilrs) = self.gilrs.as_ref() else {
+            return;
+        };
+        let pad = gilrs.gamepad(gid);
+        let id = map_id(gid);
+        let name = pad.name().to_string();
+        let rumble = pad.is_ff_supported();
+
+        self.ids.insert(id, gid);
+        {
+            let mut g = self.shared.shared.lock();
+            let st = g.pads.entry(id).or_default();
+            st.name = name.clone();
+            st.connected = true;
+            st.rumble = rumble;
+        }
+
+        log::info!("gamepad: connected id={} name='{}' rumble={}", id.0, name, rumble);
+
+        emit_plugin_json(
+            "winit.gamepad_connected",
+            serde_json::json!({ "id": id.0, "name": name, "rumble": rumble }),
+        );
+        let _ = engine.emit(HostEvent::Gamepad(GamepadHostEvent::Connected { id, name, rumble }));
+    }
+
+    fn on_button<E: Send + 'static>(&mut self, engine: &Engine<E>, id: GamepadId, b: Button, value: f32) {
+        let button = map_button(b);
+        {
+            let mut g = self.shared.shared.lock();
+            let st = g.pads.entry(id).or_default();
+            if st.buttons.get(&button) == Some(&value) {
+                return;
+            }
+            st.buttons.insert(button, value);
+        }
+
+        emit_plugin_json(
+            "winit.gamepad_button",
+            serde_json::json!({ "id": id.0, "button": format!("{button:?}"), "value": value }),
+        );
+        let _ = engine.emit(HostEvent::Gamepad(GamepadHostEvent::Button { id, button, value }));
+    }
+
+    fn on_axis<E: Send + 'static>(&mut self, engine: &Engine<E>, id: GamepadId, a: Axis, value: f32) {
+        let axis = map_axis(a);
+        self.shared
+            .shared
+            .lock()
+            .pads
+            .entry(id)
+            .or_default()
+            .axes
+            .insert(axis, value);
+
+        emit_plugin_json(
+            "winit.gamepad_axis",
+            serde_json::json!({ "id": id.0, "axis": format!("{axis:?}"), "value": value }),
+        );
+        let _ = engine.emit(HostEvent::Gamepad(GamepadHostEvent::Axis { id, axis, value }));
+    }
+
+    fn apply_rumble(&mut self) {
+        let requests = std::mem::take(&mut self.shared.shared.lock().rumble);
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return;
+        };
+
+        for r in requests {
+            // Dropping the previous effect stops it.
+            self.effects.remove(&r.id);
+
+            if r.duration.is_zero() || (r.strong <= 0.0 && r.weak <= 0.0) {
+                continue;
+            }
+            let Some(gid) = self.ids.get(&r.id).copied() else {
+                continue;
+            };
+
+            let scheduling = Replay {
+                play_for: Ticks::from_ms(r.duration.as_millis().min(u32::MAX as u128) as u32),
+                ..Default::default()
+            };
+            let magnitude = |v: f32| (v * u16::MAX as f32) as u16;
+
+            let effect = EffectBuilder::new()
+                .add_effect(BaseEffect {
+                    kind: BaseEffectType::Strong {
+                        magnitude: magnitude(r.strong),
+                    },
+                    scheduling,
+                    ..Default::default()
+                })
+                .add_effect(BaseEffect {
+                    kind: BaseEffectType::Weak {
+                        magnitude: magnitude(r.weak),
+                    },
+                    scheduling,
+                    ..Default::default()
+                })
+                .gamepads(&[gid])
+                .finish(gilrs);
+
+            match effect.and_then(|e| e.play().map(|_| e)) {
+                Ok(e) => {
+                    self.effects.insert(r.id, e);
+                }
+                Err(e) => log::warn!("gamepad: rumble failed id={} err={e}", r.id.0),
+            }
+        }
+    }
+}
+
+#[inline]
+fn map_id(id: gilrs::GamepadId) -> GamepadId {
+    GamepadId(usize::from(id) as u32)
+}
+
+fn map_button(b: Button) -> GamepadButton {
+    match b {
+        Button::South => GamepadButton::South,
+        Button::East => GamepadButton::East,
+        Button::North => GamepadButton::North,
+        Button::West => GamepadButton::West,
+        Button::LeftTrigger => GamepadButton::LeftBumper,
+        Button::RightTrigger => GamepadButton::RightBumper,
+        Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+        Button::RightTrigger2 => GamepadButton::RightTrigger,
+        Button::Select => GamepadButton::Select,
+        Button::Start => GamepadButton::Start,
+        Button::Mode => GamepadButton::Mode,
+        Button::LeftThumb => GamepadButton::LeftStick,
+        Button::RightThumb => GamepadButton::RightStick,
+        Button::DPadUp => GamepadButton::DPadUp,
+        Button::DPadDown => GamepadButton::DPadDown,
+        Button::DPadLeft => GamepadButton::DPadLeft,
+        Button::DPadRight => GamepadButton::DPadRight,
+        other => GamepadButton::Other(other as u16),
+    }
+}
+
+fn map_axis(a: Axis) -> GamepadAxis {
+    match a {
+        Axis::LeftStickX => GamepadAxis::LeftStickX,
+        Axis::LeftStickY => GamepadAxis::LeftStickY,
+        Axis::RightStickX => GamepadAxis::RightStickX,
+        Axis::RightStickY => GamepadAxis::RightStickY,
+        Axis::LeftZ => GamepadAxis::LeftTrigger,
+        Axis::RightZ => GamepadAxis::RightTrigger,
+        other => GamepadAxis::Other(other as u16),
+    }
+}
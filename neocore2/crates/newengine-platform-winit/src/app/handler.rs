@@ -4,23 +4,32 @@ use std::time::Instant;
 
 use newengine_core::host_events::{HostEvent, WindowHostEvent};
 use newengine_core::startup::UiBackend;
-use newengine_core::{Engine, EngineError, EngineResult};
+use newengine_core::trace::{self, TraceKind};
+use newengine_core::{Engine, EngineError, EngineResult, SuspendReason};
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use winit::{
     application::ApplicationHandler,
-    dpi::{PhysicalPosition, PhysicalSize},
-    event::{ElementState, Ime, MouseScrollDelta, WindowEvent},
-    event_loop::ActiveEventLoop,
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
+    event::{DeviceEvent, DeviceId, ElementState, Ime, MouseScrollDelta, WindowEvent},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoopProxy},
     keyboard::PhysicalKey,
     window::{Icon, Window, WindowAttributes, WindowId},
 };
 
 use newengine_ui::draw::UiDrawList;
-use newengine_ui::{create_provider, UiBuildFn, UiFrameDesc, UiProvider, UiProviderKind, UiProviderOptions};
+use newengine_ui::{
+    accessibility, create_provider, UiBuildFn, UiFrameDesc, UiProvider, UiProviderKind,
+    UiProviderOptions, UiUserEvent,
+};
 
-use crate::app::config::{WinitAppConfig, WinitWindowPlacement};
-use crate::app::input_bridge::{emit_plugin_json, poll_input_frame};
+use crate::app::config::{WinitAppConfig, WinitDpiMode, WinitWindowPlacement};
+use crate::app::display::DisplayBridge;
+use crate::app::gamepad::GamepadBridge;
+use crate::app::hotkeys::{HostAction, HostHotkeys};
+use crate::app::input_bridge::{emit_plugin_json, poll_input_frame, FileDropBridge};
+use crate::app::platform::PlatformBridge;
 use crate::app::resources::{WinitWindowHandles, WinitWindowInitSize};
+use crate::app::windows::WindowBridge;
 
 pub(crate) struct App<E, F>
 where
@@ -38,9 +47,18 @@ where
 
     ui: Box<dyn UiProvider>,
     ui_build: Option<Box<dyn UiBuildFn>>,
+    /// Handed to the UI provider for AccessKit action/tree requests.
+    proxy: EventLoopProxy<UiUserEvent>,
 
     last_frame_instant: Option<Instant>,
     shutting_down: bool,
+
+    gamepads: GamepadBridge,
+    display: DisplayBridge,
+    windows: WindowBridge,
+    platform: PlatformBridge,
+    file_drops: FileDropBridge,
+    hotkeys: HostHotkeys,
 }
 
 impl<E, F> App<E, F>
@@ -62,6 +80,7 @@ where
         engine: Engine<E>,
         config: WinitAppConfig,
         ui_build: Option<Box<dyn UiBuildFn>>,
+        proxy: EventLoopProxy<UiUserEvent>,
         after_window: F,
     ) -> Self {
         let kind = Self::map_ui_backend_to_provider_kind(&config.ui_backend);
@@ -75,6 +94,21 @@ where
 
         let ui = create_provider(UiProviderOptions { kind });
 
+        let gamepads = GamepadBridge::new();
+        let mut engine = engine;
+        engine.resources_mut().insert(gamepads.resource());
+
+        let display = DisplayBridge::new(config.fullscreen);
+        engine.resources_mut().insert(display.resource());
+
+        let windows = WindowBridge::new(display.resource());
+        engine.resources_mut().insert(windows.resource());
+
+        let platform = PlatformBridge::new();
+        engine.resources_mut().insert(platform.resource());
+        let file_drops = FileDropBridge::new(config.drop_import_dir.clone());
+        let hotkeys = HostHotkeys::from_config(&config);
+
         Self {
             engine,
             after_window: Some(after_window),
@@ -85,17 +119,26 @@ where
             last_cursor_pos: None,
             ui,
             ui_build,
+            proxy,
             last_frame_instant: None,
             shutting_down: false,
+            gamepads,
+            display,
+            windows,
+            platform,
+            file_drops,
+            hotkeys,
         }
     }
 
     #[inline]
     fn build_window_attributes(event_loop: &ActiveEventLoop, config: &WinitAppConfig) -> WindowAttributes {
         let (width, height) = config.size;
-        let mut attrs = WindowAttributes::default()
-            .with_title(config.title.clone())
-            .with_inner_size(PhysicalSize::new(width, height));
+        let mut attrs = WindowAttributes::default().with_title(config.title.clone());
+        attrs = match config.dpi_mode {
+            WinitDpiMode::Physical => attrs.with_inner_size(PhysicalSize::new(width, height)),
+            WinitDpiMode::Logical => attrs.with_inner_size(LogicalSize::new(width, height)),
+        };
 
         // Install window icon (if provided).
         if let Some(icon) = config.icon.as_ref() {
@@ -122,6 +165,15 @@ where
                 let ms = monitor.size();
                 let mp = monitor.position();
 
+                // Centre on the size the window will actually have.
+                let (width, height) = match config.dpi_mode {
+                    WinitDpiMode::Physical => (width, height),
+                    WinitDpiMode::Logical => {
+                        let s = monitor.scale_factor();
+                        ((width as f64 * s).round() as u32, (height as f64 * s).round() as u32)
+                    }
+                };
+
                 let cx = mp.x.saturating_add(((ms.width as i32).saturating_sub(width as i32)) / 2);
                 let cy = mp.y.saturating_add(((ms.height as i32).saturating_sub(height as i32)) / 2);
 
@@ -152,6 +204,7 @@ where
     #[inline]
     fn emit_resized(&mut self, width: u32, height: u32) {
         self.engine.resources_mut().insert(WinitWindowInitSize { width, height });
+        trace::record(TraceKind::Marker, "window.resized", || format!("{width}x{height}"));
         let _ = self.engine.emit(HostEvent::Window(WindowHostEvent::Resized { width, height }));
     }
 
@@ -184,6 +237,20 @@ where
             .publish(HostEvent::Window(WindowHostEvent::Ready { width, height }));
     }
 
+    fn emit_scale_factor(&mut self, scale_factor: f64, width: u32, height: u32) {
+        log::info!("window: scale_factor={scale_factor} size={width}x{height}");
+        emit_plugin_json(
+            "winit.scale_factor",
+            serde_json::json!({ "scale_factor": scale_factor, "width": width, "height": height }),
+        );
+        let _ = self.engine.emit(HostEvent::Window(WindowHostEvent::ScaleFactorChanged {
+            window: None,
+            scale_factor,
+            width,
+            height,
+        }));
+    }
+
     #[inline]
     fn emit_focused(&mut self, focused: bool) {
         let _ = self.engine.emit(HostEvent::Window(WindowHostEvent::Focused(focused)));
@@ -226,6 +293,70 @@ where
         }
     }
 
+    fn suspend_engine(&mut self, event_loop: &ActiveEventLoop, reason: SuspendReason) {
+        if !self.started || self.engine.is_suspended() {
+            return;
+        }
+        if reason == SuspendReason::Minimized && !self.engine.suspend_policy().suspend_on_minimize {
+            return;
+        }
+        if let Err(e) = self.engine.suspend(reason) {
+            log::warn!("engine.suspend: {e}");
+        }
+        event_loop.set_control_flow(ControlFlow::WaitUntil(
+            Instant::now() + self.engine.suspend_policy().idle_interval(),
+        ));
+    }
+
+    fn resume_engine(&mut self, event_loop: &ActiveEventLoop) {
+        if !self.engine.is_suspended() {
+            return;
+        }
+        if let Err(e) = self.engine.resume() {
+            log::warn!("engine.resume: {e}");
+        }
+        self.last_frame_instant = Some(Instant::now());
+        event_loop.set_control_flow(ControlFlow::Wait);
+        self.request_redraw();
+    }
+
+    /// Minimized windows report a zero inner size on most platforms.
+    fn on_visibility_hint(&mut self, event_loop: &ActiveEventLoop, visible: bool) {
+        match (visible, self.engine.suspend_reason()) {
+            (false, None) => self.suspend_engine(event_loop, SuspendReason::Minimized),
+            (true, Some(SuspendReason::Minimized)) => self.resume_engine(event_loop),
+            _ => {}
+        }
+    }
+
+    /// Low-rate tick while suspended: no UI frame, no redraw requests.
+    fn idle_tick(&mut self, event_loop: &ActiveEventLoop) {
+        let interval = self.engine.suspend_policy().idle_interval();
+        let now = Instant::now();
+
+        let due = self
+            .last_frame_instant
+            .is_none_or(|prev| now.duration_since(prev) >= interval);
+
+        if due {
+            self.last_frame_instant = Some(now);
+            match self.engine.step() {
+                Ok(_) => {}
+                Err(EngineError::ExitRequested) => {
+                    self.shutdown_and_exit(event_loop);
+                    return;
+                }
+                Err(e) => {
+                    log::error!("engine.step failed: {e}");
+                    self.shutdown_and_exit(event_loop);
+                    return;
+                }
+            }
+        }
+
+        event_loop.set_control_flow(ControlFlow::WaitUntil(now + interval));
+    }
+
     fn set_fatal_and_exit(&mut self, event_loop: &ActiveEventLoop, e: EngineError) {
         log::error!("winit host fatal: {e}");
         self.fatal = Some(e);
@@ -251,13 +382,22 @@ where
     }
 }
 
-impl<E, F> ApplicationHandler for App<E, F>
+impl<E, F> ApplicationHandler<UiUserEvent> for App<E, F>
 where
     E: Send + 'static,
     F: FnOnce(&mut Engine<E>) -> EngineResult<()> + 'static,
 {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        let attrs = Self::build_window_attributes(event_loop, &self.config);
+        // Returning from OS suspend: the window (and its surface) is still ours.
+        if self.window.is_some() {
+            self.resume_engine(event_loop);
+            return;
+        }
+
+        // AccessKit must be attached before the window is first shown.
+        let screen_reader = accessibility().screen_reader;
+        let attrs = Self::build_window_attributes(event_loop, &self.config)
+            .with_visible(!screen_reader);
         let window = match event_loop.create_window(attrs) {
             Ok(w) => w,
             Err(e) => {
@@ -266,6 +406,17 @@ where
             }
         };
 
+        if screen_reader {
+            self.ui.init_accessibility(&window, &self.proxy);
+            window.set_visible(true);
+        }
+
+        self.display.refresh_monitors(&window);
+        self.display.set_scale_factor(window.scale_factor());
+        if self.config.fullscreen.is_fullscreen() {
+            self.display.apply(&window, self.config.fullscreen);
+        }
+
         self.window = Some(window);
 
         self.install_window_handles_resource();
@@ -285,16 +436,22 @@ where
             }
             self.started = true;
             self.last_frame_instant = Some(Instant::now());
+            self.gamepads.announce_connected(&self.engine);
         }
 
         self.emit_ready();
         self.request_redraw();
     }
 
-    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
         // IMPORTANT: No UI backend is allowed to consume platform input directly.
         // All input must flow through the INPUT plugin.
 
+        self.file_drops.window_event(self.windows.engine_id(id), &event);
+        if self.windows.window_event(&self.engine, id, &event) {
+            return;
+        }
+
         match event {
             WindowEvent::CloseRequested => {
                 self.shutdown_and_exit(event_loop);
@@ -303,20 +460,47 @@ where
 
             WindowEvent::Resized(PhysicalSize { width, height }) => {
                 self.emit_resized(width, height);
+                self.on_visibility_hint(event_loop, width != 0 && height != 0);
             }
 
-            WindowEvent::ScaleFactorChanged { .. } => {
+            WindowEvent::Occluded(occluded) => {
+                self.on_visibility_hint(event_loop, !occluded);
+            }
+
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                if let Some(w) = &self.window {
+                    self.display.refresh_monitors(w);
+                }
+                self.display.set_scale_factor(scale_factor);
                 if let Some((w, h)) = self.window_size() {
                     self.emit_resized(w, h);
+                    self.emit_scale_factor(scale_factor, w, h);
                 }
             }
 
             WindowEvent::Focused(focused) => {
+                if let Some(w) = &self.window {
+                    self.platform.on_focus(w, focused);
+                }
                 self.emit_focused(focused);
             }
 
-            // forward-only to input plugin
+            WindowEvent::ModifiersChanged(m) => {
+                self.hotkeys.set_modifiers(m.state());
+            }
+
+            // forward-only to input plugin (host hotkeys are matched first)
             WindowEvent::KeyboardInput { event, .. } => {
+                if let (ElementState::Pressed, false, PhysicalKey::Code(code)) =
+                    (event.state, event.repeat, event.physical_key)
+                {
+                    if let (Some(HostAction::ToggleFullscreen), Some(w)) =
+                        (self.hotkeys.on_key_pressed(code), self.window.as_ref())
+                    {
+                        self.display.toggle(w);
+                    }
+                }
+
                 let key = Self::key_u32_from_physical_key(&event.physical_key);
                 let state = Self::map_state_str(event.state);
                 let repeat = event.repeat;
@@ -375,7 +559,8 @@ where
                 let x = position.x as f32;
                 let y = position.y as f32;
 
-                if let Some((px, py)) = self.last_cursor_pos {
+                // A locked cursor reports motion through `device_event`.
+                if let (Some((px, py)), false) = (self.last_cursor_pos, self.platform.cursor_locked()) {
                     emit_plugin_json(
                         "winit.mouse_delta",
                         serde_json::json!({
@@ -413,13 +598,48 @@ where
                         }),
                     );
                 }
-                Ime::Enabled | Ime::Disabled => {}
+                Ime::Enabled | Ime::Disabled => {
+                    emit_plugin_json(
+                        "winit.ime_enabled",
+                        serde_json::json!({
+                            "enabled": matches!(ime, Ime::Enabled)
+                        }),
+                    );
+                }
             },
 
             _ => {}
         }
 
-        self.request_redraw();
+        if !self.engine.is_suspended() {
+            self.request_redraw();
+        }
+    }
+
+    fn device_event(&mut self, _event_loop: &ActiveEventLoop, _id: DeviceId, event: DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta: (dx, dy) } = event {
+            if self.platform.cursor_locked() {
+                emit_plugin_json(
+                    "winit.mouse_delta",
+                    serde_json::json!({
+                        "dx": dx as f32,
+                        "dy": dy as f32
+                    }),
+                );
+            }
+        }
+    }
+
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: UiUserEvent) {
+        let Some(w) = &self.window else { return; };
+        self.ui.on_user_event(w, &event);
+        if !self.engine.is_suspended() {
+            w.request_redraw();
+        }
+    }
+
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        self.suspend_engine(event_loop, SuspendReason::OsSuspend);
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
@@ -438,6 +658,19 @@ where
             return;
         }
 
+        self.gamepads.poll(&self.engine);
+        if let Some(w) = &self.window {
+            self.display.poll(w);
+            self.platform.poll(event_loop, w);
+        }
+        self.windows.poll(event_loop, &self.engine);
+        self.file_drops.flush(&mut self.engine);
+
+        if self.engine.is_suspended() {
+            self.idle_tick(event_loop);
+            return;
+        }
+
         let dt = self.frame_dt_seconds();
         let input = poll_input_frame(&self.engine);
 
@@ -460,4 +693,4 @@ where
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,53 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use winit::keyboard::{KeyCode, ModifiersState};
+
+use crate::app::config::{WinitAppConfig, WinitHotkey};
+
+/// Actions the host performs itself instead of (or in addition to) forwarding the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HostAction {
+    ToggleFullscreen,
+}
+
+/// Host-level key bindings, matched against the tracked modifier state.
+#[derive(Default)]
+pub(crate) struct HostHotkeys {
+    bindings: Vec<(WinitHotkey, HostAction)>,
+    modifiers: ModifiersState,
+}
+
+impl HostHotkeys {
+    pub(crate) fn from_config(config: &WinitAppConfig) -> Self {
+        let mut hk = Self::default();
+        if let Some(key) = config.fullscreen_hotkey {
+            hk.bind(key, HostAction::ToggleFullscreen);
+        }
+        hk
+    }
+
+    /// Replaces any previous binding for the same chord.
+    pub(crate) fn bind(&mut self, key: WinitHotkey, action: HostAction) {
+        self.bindings.retain(|(k, _)| *k != key);
+        self.bindings.push((key, action));
+    }
+
+    #[inline]
+    pub(crate) fn set_modifiers(&mut self, m: ModifiersState) {
+        self.modifiers = m;
+    }
+
+    /// Returns the bound action for a fresh (non-repeat) key press.
+    pub(crate) fn on_key_pressed(&self, key: KeyCode) -> Option<HostAction> {
+        let m = self.modifiers;
+        self.bindings
+            .iter()
+            .find(|(k, _)| {
+                k.key == key
+                    && k.alt == m.alt_key()
+                    && k.ctrl == m.control_key()
+                    && k.shift == m.shift_key()
+            })
+            .map(|(_, a)| *a)
+    }
+}
@@ -1,9 +1,14 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use abi_stable::std_types::RString;
-use newengine_core::Engine;
+use newengine_core::host_events::{FileDropEvent, HostEvent, WindowId};
+use newengine_core::{AssetManager, Engine};
 use newengine_plugin_api::Blob;
 use newengine_ui::UiInputFrame;
+use winit::event::WindowEvent;
 
 /// Emits JSON event into plugin host context.
 #[inline]
@@ -119,7 +124,109 @@ pub fn poll_input_frame(engine: &Engine<impl Send + 'static>) -> Option<UiInputF
         if let Some(s) = text.get("ime_preedit").and_then(|x| x.as_str()) {
             out.ime_preedit.push_str(s);
         }
+        out.ime_enabled = text
+            .get("ime_enabled")
+            .and_then(|x| x.as_bool())
+            .unwrap_or(false);
     }
 
     Some(out)
-}
\ No newline at end of file
+}
+
+/// Collects OS file drag-and-drop. Winit reports one event per file; paths are grouped per
+/// window and emitted once per event-loop iteration as [`FileDropEvent`]s and
+/// `winit.file_hover` / `winit.file_hover_cancel` / `winit.file_drop` plugin events.
+pub(crate) struct FileDropBridge {
+    /// Dropped files are copied here (relative to the assets root) and imported.
+    import_dir: Option<PathBuf>,
+    hovered: BTreeMap<Option<WindowId>, Vec<PathBuf>>,
+    cancelled: Vec<Option<WindowId>>,
+    dropped: BTreeMap<Option<WindowId>, Vec<PathBuf>>,
+}
+
+impl FileDropBridge {
+    pub(crate) fn new(import_dir: Option<PathBuf>) -> Self {
+        Self {
+            import_dir,
+            hovered: BTreeMap::new(),
+            cancelled: Vec::new(),
+            dropped: BTreeMap::new(),
+        }
+    }
+
+    /// Records drag-and-drop events; `window` is `None` for the main window.
+    pub(crate) fn window_event(&mut self, window: Option<WindowId>, event: &WindowEvent) {
+        match event {
+            WindowEvent::HoveredFile(path) => {
+                self.hovered.entry(window).or_default().push(path.clone());
+            }
+            WindowEvent::HoveredFileCancelled => {
+                self.hovered.remove(&window);
+                self.cancelled.push(window);
+            }
+            WindowEvent::DroppedFile(path) => {
+                self.hovered.remove(&window);
+                self.dropped.entry(window).or_default().push(path.clone());
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn flush<E: Send + 'static>(&mut self, engine: &mut Engine<E>) {
+        for window in std::mem::take(&mut self.cancelled) {
+            emit_plugin_json(
+                "winit.file_hover_cancel",
+                serde_json::json!({ "window": window.map(|w| w.0) }),
+            );
+            let _ = engine.emit(HostEvent::FileDrop(FileDropEvent::HoverCancelled { window }));
+        }
+
+        for (window, paths) in std::mem::take(&mut self.hovered) {
+            emit_plugin_json(
+                "winit.file_hover",
+                serde_json::json!({ "window": window.map(|w| w.0), "paths": paths }),
+            );
+            let _ = engine.emit(HostEvent::FileDrop(FileDropEvent::Hovered { window, paths }));
+        }
+
+        for (window, paths) in std::mem::take(&mut self.dropped) {
+            log::info!("file drop: {} file(s)", paths.len());
+            emit_plugin_json(
+                "winit.file_drop",
+                serde_json::json!({ "window": window.map(|w| w.0), "paths": paths }),
+            );
+            if let Some(dir) = self.import_dir.as_deref() {
+                import_dropped(engine, dir, &paths);
+            }
+            let _ = engine.emit(HostEvent::FileDrop(FileDropEvent::Dropped { window, paths }));
+        }
+    }
+}
+
+fn import_dropped<E: Send + 'static>(engine: &mut Engine<E>, dir: &std::path::Path, paths: &[PathBuf]) {
+    let imported: Vec<_> = {
+        let Some(am) = engine.resources_mut().get::<AssetManager>() else {
+            log::warn!("file drop: import skipped, AssetManager missing");
+            return;
+        };
+        paths
+            .iter()
+            .filter(|p| p.is_file())
+            .filter_map(|p| match am.import_file(p, dir) {
+                Ok((logical_path, asset)) => Some((p.clone(), logical_path, asset)),
+                Err(e) => {
+                    log::warn!("file drop: import '{}' failed: {}", p.display(), e.msg());
+                    None
+                }
+            })
+            .collect()
+    };
+
+    for (source, logical_path, asset) in imported {
+        let _ = engine.emit(HostEvent::FileDrop(FileDropEvent::Imported {
+            source,
+            logical_path,
+            asset,
+        }));
+    }
+}
@@ -1,11 +1,22 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 pub mod config;
+mod display;
+mod gamepad;
 mod handler;
+mod hotkeys;
 mod input_bridge;
+mod platform;
 mod resources;
 mod runner;
+mod windows;
 
-pub use config::{WinitAppConfig, WinitWindowPlacement};
+pub use config::{
+    WinitAppConfig, WinitDpiMode, WinitFullscreen, WinitHotkey, WinitWindowPlacement,
+};
+pub use display::{WinitDisplay, WinitMonitorInfo, WinitVideoModeInfo};
+pub use gamepad::{GamepadState, WinitGamepads};
+pub use platform::{CursorGrab, CursorIcon, PlatformApi, SystemCursor};
 pub use resources::{WinitWindowHandles, WinitWindowInitSize};
 pub use runner::{run_winit_app, run_winit_app_with_config};
+pub use windows::{WindowApi, WindowDesc};
@@ -0,0 +1,351 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use winit::dpi::PhysicalPosition;
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{CursorGrabMode, CustomCursor, Window};
+
+use crate::app::input_bridge::emit_plugin_json;
+
+/// How the cursor is held by the main window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorGrab {
+    None,
+    /// Cursor cannot leave the window.
+    Confined,
+    /// Cursor is pinned in place; movement arrives as relative deltas (FPS cameras).
+    /// Falls back to `Confined` where the platform cannot lock.
+    Locked,
+}
+
+impl Default for CursorGrab {
+    #[inline]
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// System cursor shapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemCursor {
+    Default,
+    Pointer,
+    Text,
+    Crosshair,
+    Move,
+    Grab,
+    Grabbing,
+    NotAllowed,
+    Wait,
+    Progress,
+    Help,
+    ResizeHorizontal,
+    ResizeVertical,
+    ResizeNwse,
+    ResizeNesw,
+}
+
+/// Cursor image of the main window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CursorIcon {
+    System(SystemCursor),
+    /// RGBA8 image; `hotspot` is the click point in pixels.
+    Custom {
+        rgba: Arc<[u8]>,
+        width: u16,
+        height: u16,
+        hotspot: (u16, u16),
+    },
+}
+
+impl Default for CursorIcon {
+    #[inline]
+    fn default() -> Self {
+        Self::System(SystemCursor::Default)
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    visible: bool,
+    /// Requested grab; `grab_applied` is what the platform accepted.
+    grab: CursorGrab,
+    grab_applied: CursorGrab,
+    icon: Option<CursorIcon>,
+    position: Option<(f64, f64)>,
+    dirty_visible: bool,
+    dirty_grab: bool,
+    clipboard: Option<arboard::Clipboard>,
+}
+
+/// Cursor and clipboard resource installed by the winit host
+/// (`resources.get::<PlatformApi>()`).
+///
+/// Cursor requests are queued and applied to the main window before the next engine step;
+/// clipboard access is immediate. While the window is unfocused the grab is released and
+/// restored on focus.
+#[derive(Clone)]
+pub struct PlatformApi {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Default for PlatformApi {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(Shared {
+                visible: true,
+                ..Shared::default()
+            })),
+        }
+    }
+}
+
+impl PlatformApi {
+    #[inline]
+    pub fn set_cursor_visible(&self, visible: bool) {
+        let mut g = self.shared.lock();
+        g.visible = visible;
+        g.dirty_visible = true;
+    }
+
+    #[inline]
+    pub fn cursor_visible(&self) -> bool {
+        self.shared.lock().visible
+    }
+
+    #[inline]
+    pub fn set_cursor_grab(&self, grab: CursorGrab) {
+        let mut g = self.shared.lock();
+        g.grab = grab;
+        g.dirty_grab = true;
+    }
+
+    /// Grab mode currently in effect; may differ from the request after a fallback or while
+    /// the window is unfocused.
+    #[inline]
+    pub fn cursor_grab(&self) -> CursorGrab {
+        self.shared.lock().grab_applied
+    }
+
+    /// Hides and locks the cursor (`true`) or restores it (`false`).
+    pub fn capture_cursor(&self, capture: bool) {
+        let mut g = self.shared.lock();
+        g.visible = !capture;
+        g.grab = if capture { CursorGrab::Locked } else { CursorGrab::None };
+        g.dirty_visible = true;
+        g.dirty_grab = true;
+    }
+
+    #[inline]
+    pub fn set_cursor_icon(&self, icon: CursorIcon) {
+        self.shared.lock().icon = Some(icon);
+    }
+
+    /// Moves the cursor to `(x, y)` in physical window coordinates.
+    #[inline]
+    pub fn set_cursor_position(&self, x: f64, y: f64) {
+        self.shared.lock().position = Some((x, y));
+    }
+
+    /// Text on the system clipboard, `None` if it is empty or holds no text.
+    pub fn clipboard_text(&self) -> Option<String> {
+        let mut g = self.shared.lock();
+        match clipboard(&mut g)?.get_text() {
+            Ok(text) => Some(text),
+            Err(arboard::Error::ContentNotAvailable) => None,
+            Err(e) => {
+                log::warn!("platform: clipboard read failed: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn set_clipboard_text(&self, text: impl Into<String>) -> bool {
+        let mut g = self.shared.lock();
+        let Some(cb) = clipboard(&mut g) else {
+            return false;
+        };
+        match cb.set_text(text.into()) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("platform: clipboard write failed: {e}");
+                false
+            }
+        }
+    }
+}
+
+/// Opens the clipboard on first use; stays `None` on platforms without one.
+fn clipboard(g: &mut Shared) -> Option<&mut arboard::Clipboard> {
+    if g.clipboard.is_none() {
+        match arboard::Clipboard::new() {
+            Ok(cb) => g.clipboard = Some(cb),
+            Err(e) => {
+                log::warn!("platform: clipboard unavailable: {e}");
+                return None;
+            }
+        }
+    }
+    g.clipboard.as_mut()
+}
+
+/// Host-side cursor state. Lives on the event-loop thread.
+pub(crate) struct PlatformBridge {
+    shared: PlatformApi,
+    focused: bool,
+}
+
+impl PlatformBridge {
+    pub(crate) fn new() -> Self {
+        Self {
+            shared: PlatformApi::default(),
+            focused: true,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn resource(&self) -> PlatformApi {
+        self.shared.clone()
+    }
+
+    /// `true` while movement should be read from raw device motion.
+    #[inline]
+    pub(crate) fn cursor_locked(&self) -> bool {
+        self.shared.shared.lock().grab_applied == CursorGrab::Locked
+    }
+
+    /// Applies queued requests from [`PlatformApi`].
+    pub(crate) fn poll(&mut self, event_loop: &ActiveEventLoop, window: &Window) {
+        let (visible, grab, icon, position) = {
+            let mut g = self.shared.shared.lock();
+            let visible = std::mem::take(&mut g.dirty_visible).then_some(g.visible);
+            let grab = std::mem::take(&mut g.dirty_grab).then_some(g.grab);
+            (visible, grab, g.icon.take(), g.position.take())
+        };
+
+        if let Some(visible) = visible {
+            window.set_cursor_visible(visible);
+        }
+        if let Some(grab) = grab {
+            if self.focused {
+                self.apply_grab(window, grab);
+            }
+        }
+        if let Some(icon) = icon {
+            apply_icon(event_loop, window, icon);
+        }
+        if let Some((x, y)) = position {
+            if let Err(e) = window.set_cursor_position(PhysicalPosition::new(x, y)) {
+                log::debug!("platform: set_cursor_position failed: {e}");
+            }
+        }
+    }
+
+    /// Releases the grab while the window is in the background so the user can leave it.
+    pub(crate) fn on_focus(&mut self, window: &Window, focused: bool) {
+        self.focused = focused;
+        let (grab, visible) = {
+            let g = self.shared.shared.lock();
+            (g.grab, g.visible)
+        };
+        if grab == CursorGrab::None {
+            return;
+        }
+        if focused {
+            window.set_cursor_visible(visible);
+            self.apply_grab(window, grab);
+        } else {
+            window.set_cursor_visible(true);
+            self.apply_grab(window, CursorGrab::None);
+        }
+    }
+
+    fn apply_grab(&self, window: &Window, grab: CursorGrab) {
+        let applied = match grab {
+            CursorGrab::None => {
+                let _ = window.set_cursor_grab(CursorGrabMode::None);
+                CursorGrab::None
+            }
+            CursorGrab::Confined => match window.set_cursor_grab(CursorGrabMode::Confined) {
+                Ok(()) => CursorGrab::Confined,
+                Err(e) => {
+                    log::warn!("platform: cursor confine failed: {e}");
+                    CursorGrab::None
+                }
+            },
+            CursorGrab::Locked => match window.set_cursor_grab(CursorGrabMode::Locked) {
+                Ok(()) => CursorGrab::Locked,
+                Err(_) => match window.set_cursor_grab(CursorGrabMode::Confined) {
+                    Ok(()) => CursorGrab::Confined,
+                    Err(e) => {
+                        log::warn!("platform: cursor grab failed: {e}");
+                        CursorGrab::None
+                    }
+                },
+            },
+        };
+
+        let prev = std::mem::replace(&mut self.shared.shared.lock().grab_applied, applied);
+        if prev != applied {
+            log::info!("platform: cursor_grab={applied:?}");
+            emit_plugin_json(
+                "winit.cursor_grab",
+                serde_json::json!({ "mode": grab_str(applied) }),
+            );
+        }
+    }
+}
+
+fn apply_icon(event_loop: &ActiveEventLoop, window: &Window, icon: CursorIcon) {
+    match icon {
+        CursorIcon::System(c) => window.set_cursor(system_icon(c)),
+        CursorIcon::Custom {
+            rgba,
+            width,
+            height,
+            hotspot,
+        } => {
+            let source = match CustomCursor::from_rgba(rgba.to_vec(), width, height, hotspot.0, hotspot.1) {
+                Ok(s) => s,
+                Err(e) => {
+                    log::warn!("platform: invalid cursor image: {e}");
+                    return;
+                }
+            };
+            window.set_cursor(event_loop.create_custom_cursor(source));
+        }
+    }
+}
+
+fn system_icon(c: SystemCursor) -> winit::window::CursorIcon {
+    use winit::window::CursorIcon as W;
+    match c {
+        SystemCursor::Default => W::Default,
+        SystemCursor::Pointer => W::Pointer,
+        SystemCursor::Text => W::Text,
+        SystemCursor::Crosshair => W::Crosshair,
+        SystemCursor::Move => W::Move,
+        SystemCursor::Grab => W::Grab,
+        SystemCursor::Grabbing => W::Grabbing,
+        SystemCursor::NotAllowed => W::NotAllowed,
+        SystemCursor::Wait => W::Wait,
+        SystemCursor::Progress => W::Progress,
+        SystemCursor::Help => W::Help,
+        SystemCursor::ResizeHorizontal => W::EwResize,
+        SystemCursor::ResizeVertical => W::NsResize,
+        SystemCursor::ResizeNwse => W::NwseResize,
+        SystemCursor::ResizeNesw => W::NeswResize,
+    }
+}
+
+#[inline]
+fn grab_str(grab: CursorGrab) -> &'static str {
+    match grab {
+        CursorGrab::None => "none",
+        CursorGrab::Confined => "confined",
+        CursorGrab::Locked => "locked",
+    }
+}
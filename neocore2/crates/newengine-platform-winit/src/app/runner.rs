@@ -3,7 +3,7 @@
 use newengine_core::{Engine, EngineError, EngineResult};
 use winit::event_loop::EventLoop;
 
-use newengine_ui::UiBuildFn;
+use newengine_ui::{UiBuildFn, UiUserEvent};
 
 use crate::app::config::WinitAppConfig;
 use crate::app::handler::App;
@@ -28,8 +28,11 @@ where
     E: Send + 'static,
     F: FnOnce(&mut Engine<E>) -> EngineResult<()> + 'static,
 {
-    let event_loop = EventLoop::new().map_err(|e| EngineError::Other(e.to_string()))?;
-    let mut app = App::new(engine, config, ui_build, after_window);
+    let event_loop = EventLoop::<UiUserEvent>::with_user_event()
+        .build()
+        .map_err(|e| EngineError::Other(e.to_string()))?;
+    let proxy = event_loop.create_proxy();
+    let mut app = App::new(engine, config, ui_build, proxy, after_window);
 
     event_loop
         .run_app(&mut app)
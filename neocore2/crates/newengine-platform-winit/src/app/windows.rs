@@ -0,0 +1,374 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use newengine_core::host_events::{HostEvent, WindowHostEvent, WindowId};
+use newengine_core::Engine;
+use parking_lot::Mutex;
+use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::WindowEvent;
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Window, WindowAttributes};
+
+use crate::app::config::WinitFullscreen;
+use crate::app::display::{WinitDisplay, WinitMonitorInfo};
+use crate::app::input_bridge::emit_plugin_json;
+use crate::app::resources::WinitWindowHandles;
+
+/// Secondary window description.
+#[derive(Debug, Clone)]
+pub struct WindowDesc {
+    pub title: String,
+    /// Inner size in physical pixels.
+    pub size: (u32, u32),
+    /// Outer position in desktop coordinates; `None` lets the OS decide.
+    pub position: Option<(i32, i32)>,
+    pub resizable: bool,
+    pub decorations: bool,
+}
+
+impl WindowDesc {
+    #[inline]
+    pub fn new(title: impl Into<String>, width: u32, height: u32) -> Self {
+        Self {
+            title: title.into(),
+            size: (width, height),
+            position: None,
+            resizable: true,
+            decorations: true,
+        }
+    }
+
+    #[inline]
+    pub fn with_position(mut self, x: i32, y: i32) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    #[inline]
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    #[inline]
+    pub fn with_decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+}
+
+struct WindowState {
+    /// `None` until the host has created the window.
+    handles: Option<WinitWindowHandles>,
+    size: (u32, u32),
+    scale_factor: f64,
+    closing: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    next_id: u64,
+    windows: BTreeMap<WindowId, WindowState>,
+    create: Vec<(WindowId, WindowDesc)>,
+    close: Vec<WindowId>,
+    titles: Vec<(WindowId, String)>,
+}
+
+/// Window resource installed by the winit host (`resources.get::<WindowApi>()`).
+///
+/// Manages secondary windows and forwards display control of the main window to
+/// [`WinitDisplay`]. Requests are queued and applied on the host thread before the next
+/// engine step; results arrive as [`WindowHostEvent`]s. Input is still read from the main
+/// window only.
+#[derive(Clone, Default)]
+pub struct WindowApi {
+    shared: Arc<Mutex<Shared>>,
+    display: WinitDisplay,
+}
+
+impl WindowApi {
+    /// Queues a new OS window. The id is valid immediately; handles become available once
+    /// `WindowHostEvent::Opened` has been emitted.
+    pub fn create_window(&self, desc: WindowDesc) -> WindowId {
+        let mut g = self.shared.lock();
+        g.next_id += 1;
+        let id = WindowId(g.next_id);
+        g.windows.insert(
+            id,
+            WindowState {
+                handles: None,
+                size: desc.size,
+                scale_factor: 1.0,
+                closing: false,
+            },
+        );
+        g.create.push((id, desc));
+        id
+    }
+
+    /// Closes `id`. The window disappears from [`windows`](Self::windows) right away and is
+    /// destroyed one engine step later, so the renderer can detach its surface first.
+    pub fn close_window(&self, id: WindowId) {
+        let mut g = self.shared.lock();
+        let Some(st) = g.windows.get_mut(&id) else {
+            return;
+        };
+        if !std::mem::replace(&mut st.closing, true) {
+            g.close.push(id);
+        }
+    }
+
+    #[inline]
+    pub fn set_title(&self, id: WindowId, title: impl Into<String>) {
+        self.shared.lock().titles.push((id, title.into()));
+    }
+
+    /// Open windows that have been created by the host, in creation order.
+    pub fn windows(&self) -> Vec<WindowId> {
+        self.shared
+            .lock()
+            .windows
+            .iter()
+            .filter(|(_, st)| st.handles.is_some() && !st.closing)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    #[inline]
+    pub fn handles(&self, id: WindowId) -> Option<WinitWindowHandles> {
+        self.shared
+            .lock()
+            .windows
+            .get(&id)
+            .filter(|st| !st.closing)
+            .and_then(|st| st.handles)
+    }
+
+    /// Current inner size in physical pixels.
+    #[inline]
+    pub fn size(&self, id: WindowId) -> Option<(u32, u32)> {
+        self.shared.lock().windows.get(&id).map(|st| st.size)
+    }
+
+    #[inline]
+    pub fn scale_factor(&self, id: WindowId) -> Option<f64> {
+        self.shared.lock().windows.get(&id).map(|st| st.scale_factor)
+    }
+
+    #[inline]
+    pub fn main_scale_factor(&self) -> f64 {
+        self.display.scale_factor()
+    }
+
+    #[inline]
+    pub fn monitors(&self) -> Vec<WinitMonitorInfo> {
+        self.display.monitors()
+    }
+
+    /// Fullscreen mode of the main window.
+    #[inline]
+    pub fn fullscreen(&self) -> WinitFullscreen {
+        self.display.fullscreen()
+    }
+
+    /// Switches the main window between windowed, borderless and exclusive fullscreen; the
+    /// monitor is selected by index into [`monitors`](Self::monitors).
+    #[inline]
+    pub fn set_fullscreen(&self, mode: WinitFullscreen) {
+        self.display.set_fullscreen(mode);
+    }
+
+    #[inline]
+    pub fn toggle_fullscreen(&self) {
+        self.display.toggle_fullscreen();
+    }
+}
+
+/// Host-side secondary windows. Lives on the event-loop thread.
+pub(crate) struct WindowBridge {
+    shared: WindowApi,
+    windows: HashMap<WindowId, Window>,
+    by_winit: HashMap<winit::window::WindowId, WindowId>,
+    /// Closed on the next poll; see [`WindowApi::close_window`].
+    closing: Vec<WindowId>,
+}
+
+impl WindowBridge {
+    pub(crate) fn new(display: WinitDisplay) -> Self {
+        Self {
+            shared: WindowApi {
+                shared: Arc::default(),
+                display,
+            },
+            windows: HashMap::new(),
+            by_winit: HashMap::new(),
+            closing: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn resource(&self) -> WindowApi {
+        self.shared.clone()
+    }
+
+    /// Applies queued requests from [`WindowApi`].
+    pub(crate) fn poll<E: Send + 'static>(&mut self, event_loop: &ActiveEventLoop, engine: &Engine<E>) {
+        let (create, close, titles) = {
+            let mut g = self.shared.shared.lock();
+            (
+                std::mem::take(&mut g.create),
+                std::mem::take(&mut g.close),
+                std::mem::take(&mut g.titles),
+            )
+        };
+
+        for id in std::mem::replace(&mut self.closing, close) {
+            self.destroy(engine, id);
+        }
+
+        for (id, desc) in create {
+            self.open(event_loop, engine, id, desc);
+        }
+
+        for (id, title) in titles {
+            if let Some(w) = self.windows.get(&id) {
+                w.set_title(&title);
+            }
+        }
+    }
+
+    fn open<E: Send + 'static>(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        engine: &Engine<E>,
+        id: WindowId,
+        desc: WindowDesc,
+    ) {
+        let (width, height) = desc.size;
+        let mut attrs = WindowAttributes::default()
+            .with_title(desc.title)
+            .with_inner_size(PhysicalSize::new(width, height))
+            .with_resizable(desc.resizable)
+            .with_decorations(desc.decorations);
+        if let Some((x, y)) = desc.position {
+            attrs = attrs.with_position(PhysicalPosition::new(x, y));
+        }
+
+        let window = match event_loop.create_window(attrs) {
+            Ok(w) => w,
+            Err(e) => {
+                log::warn!("window {}: create failed: {e}", id.0);
+                self.shared.shared.lock().windows.remove(&id);
+                let _ = engine.emit(HostEvent::Window(WindowHostEvent::Closed(id)));
+                return;
+            }
+        };
+
+        let handles = match (window.window_handle(), window.display_handle()) {
+            (Ok(w), Ok(d)) => Some(WinitWindowHandles {
+                window: w.as_raw(),
+                display: d.as_raw(),
+            }),
+            _ => None,
+        };
+        let PhysicalSize { width, height } = window.inner_size();
+
+        {
+            let mut g = self.shared.shared.lock();
+            match g.windows.get_mut(&id) {
+                Some(st) => {
+                    st.handles = handles;
+                    st.size = (width, height);
+                    st.scale_factor = window.scale_factor();
+                }
+                // Closed before the host got to create it.
+                None => return,
+            }
+        }
+
+        self.by_winit.insert(window.id(), id);
+        self.windows.insert(id, window);
+
+        log::info!("window {}: opened {width}x{height}", id.0);
+        emit_plugin_json(
+            "winit.window_opened",
+            serde_json::json!({ "window": id.0, "width": width, "height": height }),
+        );
+        let _ = engine.emit(HostEvent::Window(WindowHostEvent::Opened {
+            window: id,
+            width,
+            height,
+        }));
+    }
+
+    fn destroy<E: Send + 'static>(&mut self, engine: &Engine<E>, id: WindowId) {
+        self.shared.shared.lock().windows.remove(&id);
+        let Some(window) = self.windows.remove(&id) else {
+            return;
+        };
+        self.by_winit.remove(&window.id());
+        drop(window);
+
+        log::info!("window {}: closed", id.0);
+        emit_plugin_json("winit.window_closed", serde_json::json!({ "window": id.0 }));
+        let _ = engine.emit(HostEvent::Window(WindowHostEvent::Closed(id)));
+    }
+
+    /// Engine id of a secondary window; `None` for the main window.
+    #[inline]
+    pub(crate) fn engine_id(&self, winit_id: winit::window::WindowId) -> Option<WindowId> {
+        self.by_winit.get(&winit_id).copied()
+    }
+
+    /// Handles events of secondary windows; returns `false` for the main window.
+    pub(crate) fn window_event<E: Send + 'static>(
+        &mut self,
+        engine: &Engine<E>,
+        winit_id: winit::window::WindowId,
+        event: &WindowEvent,
+    ) -> bool {
+        let Some(&id) = self.by_winit.get(&winit_id) else {
+            return false;
+        };
+
+        match event {
+            WindowEvent::CloseRequested => {
+                let _ = engine.emit(HostEvent::Window(WindowHostEvent::SecondaryCloseRequested(id)));
+            }
+            WindowEvent::Resized(PhysicalSize { width, height }) => {
+                self.on_resized(engine, id, *width, *height);
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                if let Some(st) = self.shared.shared.lock().windows.get_mut(&id) {
+                    st.scale_factor = *scale_factor;
+                }
+                if let Some(w) = self.windows.get(&id) {
+                    let PhysicalSize { width, height } = w.inner_size();
+                    self.on_resized(engine, id, width, height);
+                    let _ = engine.emit(HostEvent::Window(WindowHostEvent::ScaleFactorChanged {
+                        window: Some(id),
+                        scale_factor: *scale_factor,
+                        width,
+                        height,
+                    }));
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn on_resized<E: Send + 'static>(&self, engine: &Engine<E>, id: WindowId, width: u32, height: u32) {
+        if let Some(st) = self.shared.shared.lock().windows.get_mut(&id) {
+            st.size = (width, height);
+        }
+        let _ = engine.emit(HostEvent::Window(WindowHostEvent::SecondaryResized {
+            window: id,
+            width,
+            height,
+        }));
+    }
+}
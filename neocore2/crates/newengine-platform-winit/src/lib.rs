@@ -4,6 +4,8 @@ pub use egui;
 pub use newengine_ui::UiBuildFn;
 
 pub use app::{
-    run_winit_app, run_winit_app_with_config, WinitAppConfig, WinitWindowHandles,
-    WinitWindowInitSize, WinitWindowPlacement,
+    run_winit_app, run_winit_app_with_config, CursorGrab, CursorIcon, GamepadState, PlatformApi,
+    SystemCursor, WindowApi, WindowDesc, WinitAppConfig, WinitDisplay, WinitDpiMode,
+    WinitFullscreen, WinitGamepads, WinitHotkey, WinitMonitorInfo, WinitVideoModeInfo,
+    WinitWindowHandles, WinitWindowInitSize, WinitWindowPlacement,
 };
@@ -0,0 +1,47 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Helpers shared by asset importer plugins.
+//!
+//! Importer frames are `[u32 meta_len_le][meta json][payload bytes]`; the `*_settings_v1`
+//! methods take `[u32 settings_len_le][settings json][source bytes]`.
+
+use crate::Blob;
+
+use abi_stable::std_types::{RResult, RString};
+use serde_json::Value;
+
+/// Splits a settings call payload into the settings JSON and the source bytes.
+pub fn split_settings(payload: &[u8]) -> Option<(&str, &[u8])> {
+    let len = u32::from_le_bytes(payload.get(..4)?.try_into().ok()?) as usize;
+    let json = payload.get(4..4usize.checked_add(len)?)?;
+    Some((std::str::from_utf8(json).ok()?, &payload[4 + len..]))
+}
+
+/// Copies the host's `.meta` settings into the frame's meta as `"import_settings"`, where
+/// asset readers pick them up. `prefix` starts the error messages (`"audio"`, `"image"`).
+pub fn with_import_settings(frame: Blob, settings: &str, prefix: &str) -> RResult<Blob, RString> {
+    let err = |msg: &str| RResult::RErr(RString::from(format!("{prefix}: {msg}")));
+
+    let Some(head) = frame.get(..4) else {
+        return err("frame too small");
+    };
+    let meta_len = u32::from_le_bytes([head[0], head[1], head[2], head[3]]) as usize;
+    let Some(meta) = frame.get(4..4 + meta_len) else {
+        return err("bad meta");
+    };
+    let Ok(Value::Object(mut meta)) = serde_json::from_slice::<Value>(meta) else {
+        return err("meta is not a JSON object");
+    };
+    let Ok(settings) = serde_json::from_str::<Value>(settings) else {
+        return err("import settings are not JSON");
+    };
+    meta.insert("import_settings".to_string(), settings);
+
+    let meta = Value::Object(meta).to_string();
+    let payload = &frame[4 + meta_len..];
+    let mut out = Vec::with_capacity(4 + meta.len() + payload.len());
+    out.extend_from_slice(&(meta.len() as u32).to_le_bytes());
+    out.extend_from_slice(meta.as_bytes());
+    out.extend_from_slice(payload);
+    RResult::ROk(Blob::from(out))
+}
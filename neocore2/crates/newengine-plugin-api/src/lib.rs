@@ -7,6 +7,10 @@ use abi_stable::sabi_types::VersionStrings;
 use abi_stable::std_types::{RResult, RString, RVec};
 use abi_stable::StableAbi;
 
+pub mod importer;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+
 pub type Blob = RVec<u8>;
 pub type CapabilityId = RString;
 pub type MethodName = RString;
@@ -31,6 +35,110 @@ pub trait EventSinkV1: Send + Sync {
 
 pub type EventSinkV1Dyn<'a> = EventSinkV1_TO<'a, abi_stable::std_types::RBox<()>>;
 
+/// Console command handler. `args` is the text after the command name.
+#[sabi_trait]
+pub trait ConsoleCommandV1: Send + Sync {
+    fn exec(&self, args: RString) -> RResult<RString, RString>;
+}
+
+pub type ConsoleCommandV1Dyn<'a> = ConsoleCommandV1_TO<'a, abi_stable::std_types::RBox<()>>;
+
+/// Event sink for typed events. `schema` names the payload layout and its version (see
+/// [`schemas`]); sinks should skip schemas they do not understand.
+#[sabi_trait]
+pub trait TypedEventSinkV2: Send + Sync {
+    fn on_event(&mut self, topic: RString, schema: RString, payload: Blob);
+}
+
+pub type TypedEventSinkV2Dyn<'a> = TypedEventSinkV2_TO<'a, abi_stable::std_types::RBox<()>>;
+
+/* =============================================================================================
+   Engine event topics and schema ids (payloads are JSON)
+   ============================================================================================= */
+
+pub mod schemas {
+    /// Namespace of events the engine emits. Engine events mirrored from its `EventHub` are
+    /// published under it; plugin events using it are not forwarded to the engine.
+    pub const ENGINE_TOPIC_PREFIX: &str = "engine.";
+
+    /// `{ "width": u32, "height": u32 }`, main window inner size in physical pixels.
+    pub const WINDOW_RESIZED_TOPIC: &str = "engine.window.resized";
+    pub const WINDOW_RESIZED_V1: &str = "newengine.window.resized@1";
+
+    /// `{ "focused": bool }`
+    pub const WINDOW_FOCUS_TOPIC: &str = "engine.window.focus";
+    pub const WINDOW_FOCUS_V1: &str = "newengine.window.focus@1";
+
+    /// `{ "scale_factor": f64, "width": u32, "height": u32 }`
+    pub const WINDOW_SCALE_FACTOR_TOPIC: &str = "engine.window.scale_factor";
+    pub const WINDOW_SCALE_FACTOR_V1: &str = "newengine.window.scale_factor@1";
+
+    /// `{ "state": "suspended" | "resumed" }`
+    pub const LIFECYCLE_TOPIC: &str = "engine.lifecycle";
+    pub const LIFECYCLE_V1: &str = "newengine.lifecycle@1";
+
+    /// Schema of events emitted through `HostApiV1::emit_event_v1`.
+    pub const UNTYPED: &str = "";
+}
+
+/* =============================================================================================
+   Host resources exposed by value
+   ============================================================================================= */
+
+/// Main window state. All zero before the window exists.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, StableAbi)]
+pub struct WindowStateV2 {
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    pub focused: bool,
+}
+
+/// Timing of the current variable frame, as passed to `update`/`render`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, StableAbi)]
+pub struct FrameTimingV2 {
+    pub frame_index: u64,
+    /// Scaled game delta; `0.0` while paused.
+    pub dt: f32,
+    /// Unscaled wall-clock delta.
+    pub real_dt: f32,
+    pub fixed_dt: f32,
+    pub time_scale: f32,
+    pub paused: bool,
+}
+
+/* =============================================================================================
+   Service descriptors
+   ============================================================================================= */
+
+/// One method of a [`ServiceDescriptorV1`].
+#[repr(C)]
+#[derive(Debug, Clone, Default, StableAbi)]
+pub struct MethodDescriptorV1 {
+    pub name: MethodName,
+    pub doc: RString,
+    /// JSON Schema of the request payload; empty when the payload is not JSON.
+    pub request_schema: RString,
+    /// JSON Schema of the response payload; empty when the response is not JSON.
+    pub response_schema: RString,
+}
+
+/// Structured description of a registered service, for tooling that calls services it was
+/// not built against. Registered with `HostApiV2::register_service_descriptor_v2` next to
+/// the `ServiceV1` it describes.
+#[repr(C)]
+#[derive(Debug, Clone, Default, StableAbi)]
+pub struct ServiceDescriptorV1 {
+    pub service_id: CapabilityId,
+    /// Version of the service's methods and payloads, `major[.minor[.patch]]`; empty if
+    /// unversioned.
+    pub version: RString,
+    pub doc: RString,
+    pub methods: RVec<MethodDescriptorV1>,
+}
+
 /* =============================================================================================
    Host API: pure bridge
    ============================================================================================= */
@@ -50,6 +158,43 @@ pub struct HostApiV1 {
 
     pub emit_event_v1: extern "C" fn(RString, Blob) -> RResult<(), RString>,
     pub subscribe_events_v1: extern "C" fn(EventSinkV1Dyn<'static>) -> RResult<(), RString>,
+
+    /// Add a console command: `(name, help, usage, handler)`. It is listed by `help` and
+    /// removed when the plugin is unloaded.
+    pub register_command_v1: extern "C" fn(
+        RString,
+        RString,
+        RString,
+        ConsoleCommandV1Dyn<'static>,
+    ) -> RResult<(), RString>,
+
+    /// Extended host API. Fields added after the initial prefix read as `Option` through
+    /// `HostApiV2Ref`, since an older host may lack them.
+    pub host_api_v2: extern "C" fn() -> HostApiV2Ref,
+}
+
+/// Versioned host extension: typed events and read access to engine resources.
+///
+/// A prefix type: new fields go at the end, and plugins built against a newer layout still
+/// load on an older host.
+#[repr(C)]
+#[derive(StableAbi)]
+#[sabi(kind(Prefix(prefix_ref = HostApiV2Ref)))]
+pub struct HostApiV2 {
+    /// Emit `(topic, schema, payload)`. Untyped (`HostApiV1`) sinks receive it as well.
+    pub emit_event_v2: extern "C" fn(RString, RString, Blob) -> RResult<(), RString>,
+    /// Subscribe to events whose topic starts with the prefix (empty for all). Untyped
+    /// events arrive with schema [`schemas::UNTYPED`].
+    pub subscribe_events_v2:
+        extern "C" fn(RString, TypedEventSinkV2Dyn<'static>) -> RResult<(), RString>,
+
+    pub window_state_v2: extern "C" fn() -> WindowStateV2,
+    #[sabi(last_prefix_field)]
+    pub frame_timing_v2: extern "C" fn() -> FrameTimingV2,
+
+    /// Attach a descriptor to a service the calling plugin registers (before or after the
+    /// service itself). Replaces an earlier descriptor for the same service.
+    pub register_service_descriptor_v2: extern "C" fn(ServiceDescriptorV1) -> RResult<(), RString>,
 }
 
 /* =============================================================================================
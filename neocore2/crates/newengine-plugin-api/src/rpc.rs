@@ -0,0 +1,124 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Typed services over the `ServiceV1` blob ABI.
+//!
+//! [`service`] turns a trait into a `<Trait>Server<T>` (`ServiceV1` dispatch) and a
+//! `<Trait>Client<C>` (typed stubs). Payloads are postcard: arguments as a tuple, the
+//! result as the return value, so both sides must be built from the same trait definition.
+//! A method's `Err` and decode failures travel as the call's error string.
+
+use crate::{Blob, HostApiV1};
+
+use abi_stable::std_types::RString;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+
+pub use newengine_service_derive::service;
+
+/// Payload encoding named in the generated describe JSON.
+pub const FORMAT: &str = "postcard";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcError {
+    /// The arguments could not be encoded.
+    Encode(String),
+    /// The reply does not match the method's return type.
+    Decode(String),
+    /// The service is missing or the call failed on its side.
+    Remote(String),
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::Encode(e) => write!(f, "rpc encode failed: {e}"),
+            RpcError::Decode(e) => write!(f, "rpc decode failed: {e}"),
+            RpcError::Remote(e) => f.write_str(e),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// How a generated client reaches the service.
+pub trait RpcTransport {
+    fn call(&self, service_id: &str, method: &str, payload: Vec<u8>) -> Result<Vec<u8>, String>;
+}
+
+impl RpcTransport for HostApiV1 {
+    fn call(&self, service_id: &str, method: &str, payload: Vec<u8>) -> Result<Vec<u8>, String> {
+        (self.call_service_v1)(
+            RString::from(service_id),
+            RString::from(method),
+            Blob::from(payload),
+        )
+        .into_result()
+        .map(|b| b.into_vec())
+        .map_err(|e| e.into_string())
+    }
+}
+
+/// Any `Fn(service_id, method, payload)`, e.g. the engine's `call_service_v1`.
+impl<F> RpcTransport for F
+where
+    F: Fn(&str, &str, &[u8]) -> Result<Vec<u8>, String>,
+{
+    #[inline]
+    fn call(&self, service_id: &str, method: &str, payload: Vec<u8>) -> Result<Vec<u8>, String> {
+        self(service_id, method, &payload)
+    }
+}
+
+#[inline]
+pub fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, RpcError> {
+    postcard::to_stdvec(value).map_err(|e| RpcError::Encode(e.to_string()))
+}
+
+#[inline]
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, RpcError> {
+    postcard::from_bytes(bytes).map_err(|e| RpcError::Decode(e.to_string()))
+}
+
+/// Used by the code `service` generates.
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    pub use abi_stable::sabi_trait::TD_Opaque;
+    pub use abi_stable::std_types::{RResult, RString};
+
+    pub fn decode_args<T: DeserializeOwned>(
+        service_id: &str,
+        method: &str,
+        payload: &[u8],
+    ) -> Result<T, RString> {
+        postcard::from_bytes(payload).map_err(|e| {
+            RString::from(format!(
+                "{service_id}::{method}: arguments do not match the method signature: {e}"
+            ))
+        })
+    }
+
+    pub fn reply<T: Serialize>(value: T) -> RResult<Blob, RString> {
+        match postcard::to_stdvec(&value) {
+            Ok(bytes) => RResult::ROk(Blob::from(bytes)),
+            Err(e) => RResult::RErr(RString::from(format!("rpc encode failed: {e}"))),
+        }
+    }
+
+    pub fn reply_result<T: Serialize, E: fmt::Display>(
+        result: Result<T, E>,
+    ) -> RResult<Blob, RString> {
+        match result {
+            Ok(value) => reply(value),
+            Err(e) => RResult::RErr(RString::from(e.to_string())),
+        }
+    }
+
+    pub fn unknown_method(service_id: &str, method: &str) -> RResult<Blob, RString> {
+        RResult::RErr(RString::from(format!(
+            "{service_id}: unknown method '{method}'"
+        )))
+    }
+}
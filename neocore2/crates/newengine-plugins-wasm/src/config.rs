@@ -0,0 +1,76 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_core::plugins::PluginPermissionPolicy;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Where WASM plugins are loaded from and how much each call into one may use.
+#[derive(Debug, Clone)]
+pub struct WasmPluginConfig {
+    /// Scanned (non-recursively) for `*.wasm` at init.
+    pub dir: PathBuf,
+    /// Fuel (roughly wasm instructions) per call into a guest; `0` is unlimited.
+    pub fuel_per_call: u64,
+    /// Wall-clock limit per call into a guest, rounded up to `epoch_tick`.
+    pub call_timeout: Duration,
+    /// How often the epoch advances; finer ticks interrupt sooner and cost more.
+    pub epoch_tick: Duration,
+    /// Linear memory cap per plugin.
+    pub max_memory_bytes: usize,
+    pub permissions: PluginPermissionPolicy,
+}
+
+impl Default for WasmPluginConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("plugins"),
+            fuel_per_call: 200_000_000,
+            call_timeout: Duration::from_secs(1),
+            epoch_tick: Duration::from_millis(10),
+            max_memory_bytes: 64 * 1024 * 1024,
+            permissions: PluginPermissionPolicy::default(),
+        }
+    }
+}
+
+impl WasmPluginConfig {
+    #[inline]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            ..Self::default()
+        }
+    }
+
+    #[inline]
+    pub fn with_fuel_per_call(mut self, fuel: u64) -> Self {
+        self.fuel_per_call = fuel;
+        self
+    }
+
+    #[inline]
+    pub fn with_call_timeout(mut self, timeout: Duration) -> Self {
+        self.call_timeout = timeout;
+        self
+    }
+
+    #[inline]
+    pub fn with_max_memory_bytes(mut self, bytes: usize) -> Self {
+        self.max_memory_bytes = bytes;
+        self
+    }
+
+    #[inline]
+    pub fn with_permissions(mut self, policy: PluginPermissionPolicy) -> Self {
+        self.permissions = policy;
+        self
+    }
+
+    /// Epoch ticks making up `call_timeout`.
+    #[inline]
+    pub(crate) fn deadline_ticks(&self) -> u64 {
+        let tick = self.epoch_tick.as_micros().max(1);
+        (self.call_timeout.as_micros().div_ceil(tick) as u64).max(1)
+    }
+}
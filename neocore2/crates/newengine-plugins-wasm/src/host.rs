@@ -0,0 +1,212 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_core::{call_service_as, emit_plugin_event_as};
+use wasmtime::{AsContext, AsContextMut, Caller, Linker, Memory, StoreLimits, TypedFunc};
+
+/// Import module name of the host functions.
+pub(crate) const HOST_MODULE: &str = "newengine";
+
+/// Store data of one plugin instance.
+pub(crate) struct GuestState {
+    pub plugin_id: String,
+    pub limits: StoreLimits,
+    /// True while `ne_init` runs, the only time `register_service` is accepted.
+    pub initializing: bool,
+    /// `(service_id, describe_json)` declared by `register_service`, registered with the host
+    /// once `ne_init` returns.
+    pub pending_services: Vec<(String, String)>,
+}
+
+pub(crate) fn read_bytes(
+    ctx: impl AsContext,
+    memory: Memory,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<Vec<u8>> {
+    let (ptr, len) = (ptr as u32 as usize, len as u32 as usize);
+    let data = memory.data(&ctx);
+    let Some(end) = ptr.checked_add(len).filter(|end| *end <= data.len()) else {
+        return Err(wasmtime::Error::msg(format!(
+            "guest buffer out of bounds: {ptr}+{len}"
+        )));
+    };
+    Ok(data[ptr..end].to_vec())
+}
+
+pub(crate) fn read_str(
+    ctx: impl AsContext,
+    memory: Memory,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<String> {
+    String::from_utf8(read_bytes(ctx, memory, ptr, len)?)
+        .map_err(|_| wasmtime::Error::msg("guest string is not valid UTF-8"))
+}
+
+/// Copies `bytes` into a fresh `ne_alloc` buffer and returns its pointer.
+pub(crate) fn write_alloc(
+    mut ctx: impl AsContextMut,
+    memory: Memory,
+    alloc: &TypedFunc<i32, i32>,
+    bytes: &[u8],
+) -> wasmtime::Result<i32> {
+    let len = i32::try_from(bytes.len())
+        .map_err(|_| wasmtime::Error::msg("buffer too large for the guest"))?;
+    let ptr = alloc.call(&mut ctx, len)?;
+    memory.write(&mut ctx, ptr as u32 as usize, bytes)?;
+    Ok(ptr)
+}
+
+/// Writes `bytes` to an `ne_alloc` buffer and its `(ptr, len)` to `out_ptr`.
+pub(crate) fn write_out(
+    mut ctx: impl AsContextMut,
+    memory: Memory,
+    alloc: &TypedFunc<i32, i32>,
+    out_ptr: i32,
+    bytes: &[u8],
+) -> wasmtime::Result<()> {
+    let ptr = write_alloc(&mut ctx, memory, alloc, bytes)?;
+    let mut out = [0u8; 8];
+    out[..4].copy_from_slice(&(ptr as u32).to_le_bytes());
+    out[4..].copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+    memory.write(&mut ctx, out_ptr as u32 as usize, &out)?;
+    Ok(())
+}
+
+/// Reads the `(ptr, len)` pair at `out_ptr`; returns `ptr` and a copy of the buffer.
+pub(crate) fn read_out(
+    ctx: impl AsContext,
+    memory: Memory,
+    out_ptr: i32,
+) -> wasmtime::Result<(i32, Vec<u8>)> {
+    let pair = read_bytes(&ctx, memory, out_ptr, 8)?;
+    let ptr = u32::from_le_bytes([pair[0], pair[1], pair[2], pair[3]]);
+    let len = u32::from_le_bytes([pair[4], pair[5], pair[6], pair[7]]);
+    let bytes = read_bytes(&ctx, memory, ptr as i32, len as i32)?;
+    Ok((ptr as i32, bytes))
+}
+
+fn caller_memory(caller: &mut Caller<'_, GuestState>) -> wasmtime::Result<Memory> {
+    caller
+        .get_export("memory")
+        .and_then(|e| e.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("guest does not export 'memory'"))
+}
+
+fn caller_alloc(caller: &mut Caller<'_, GuestState>) -> wasmtime::Result<TypedFunc<i32, i32>> {
+    caller
+        .get_export("ne_alloc")
+        .and_then(|e| e.into_func())
+        .ok_or_else(|| wasmtime::Error::msg("guest does not export 'ne_alloc'"))?
+        .typed::<i32, i32>(&caller)
+}
+
+/// Adds the `newengine` host functions. An `Err` from a host function traps the guest; it
+/// is reserved for ABI violations (bad pointers, invalid UTF-8), host-side failures are
+/// status codes.
+pub(crate) fn link(linker: &mut Linker<GuestState>) -> wasmtime::Result<()> {
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |mut caller: Caller<'_, GuestState>,
+         level: i32,
+         ptr: i32,
+         len: i32|
+         -> wasmtime::Result<()> {
+            let memory = caller_memory(&mut caller)?;
+            let msg = read_str(&caller, memory, ptr, len)?;
+            let id = caller.data().plugin_id.as_str();
+            match level {
+                2 => log::error!(target: "plugins", "[{}] {}", id, msg),
+                1 => log::warn!(target: "plugins", "[{}] {}", id, msg),
+                _ => log::info!(target: "plugins", "[{}] {}", id, msg),
+            }
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "register_service",
+        |mut caller: Caller<'_, GuestState>,
+         id_ptr: i32,
+         id_len: i32,
+         describe_ptr: i32,
+         describe_len: i32|
+         -> wasmtime::Result<i32> {
+            let memory = caller_memory(&mut caller)?;
+            let id = read_str(&caller, memory, id_ptr, id_len)?;
+            let describe = read_str(&caller, memory, describe_ptr, describe_len)?;
+
+            let st = caller.data_mut();
+            if !st.initializing {
+                log::warn!(
+                    target: "plugins",
+                    "wasm: register_service outside ne_init ignored id='{}' service='{}'",
+                    st.plugin_id,
+                    id
+                );
+                return Ok(1);
+            }
+            st.pending_services.push((id, describe));
+            Ok(0)
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "call_service",
+        |mut caller: Caller<'_, GuestState>,
+         id_ptr: i32,
+         id_len: i32,
+         method_ptr: i32,
+         method_len: i32,
+         payload_ptr: i32,
+         payload_len: i32,
+         out_ptr: i32|
+         -> wasmtime::Result<i32> {
+            let memory = caller_memory(&mut caller)?;
+            let alloc = caller_alloc(&mut caller)?;
+            let id = read_str(&caller, memory, id_ptr, id_len)?;
+            let method = read_str(&caller, memory, method_ptr, method_len)?;
+            let payload = read_bytes(&caller, memory, payload_ptr, payload_len)?;
+
+            let plugin_id = caller.data().plugin_id.clone();
+            let (status, bytes) = match call_service_as(&plugin_id, &id, &method, &payload) {
+                Ok(out) => (0, out),
+                Err(e) => (1, e.into_bytes()),
+            };
+            write_out(&mut caller, memory, &alloc, out_ptr, &bytes)?;
+            Ok(status)
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "emit_event",
+        |mut caller: Caller<'_, GuestState>,
+         topic_ptr: i32,
+         topic_len: i32,
+         payload_ptr: i32,
+         payload_len: i32|
+         -> wasmtime::Result<i32> {
+            let memory = caller_memory(&mut caller)?;
+            let topic = read_str(&caller, memory, topic_ptr, topic_len)?;
+            let payload = read_bytes(&caller, memory, payload_ptr, payload_len)?;
+            match emit_plugin_event_as(&caller.data().plugin_id, &topic, &payload) {
+                Ok(()) => Ok(0),
+                Err(e) => {
+                    log::warn!(
+                        target: "plugins",
+                        "wasm: emit_event failed id='{}': {}",
+                        caller.data().plugin_id,
+                        e
+                    );
+                    Ok(1)
+                }
+            }
+        },
+    )?;
+
+    Ok(())
+}
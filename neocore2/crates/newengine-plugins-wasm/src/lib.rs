@@ -0,0 +1,56 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! WebAssembly plugins, loaded with wasmtime next to the abi_stable DLL plugins.
+//!
+//! A `.wasm` file in the plugins directory is one plugin. It reaches the host through the
+//! same surface as a DLL plugin (registering `ServiceV1` services, importers included,
+//! calling services, emitting events) but runs sandboxed: every call into the guest gets a
+//! fuel budget and a wall-clock epoch deadline, and its linear memory is capped. A guest
+//! that traps or runs out of budget is disabled and reported as a `PluginFault`.
+//!
+//! Permissions and the plugin id come from the `<name>.plugin.toml` (or `.plugin.json`)
+//! manifest next to the module, as for DLL plugins; without a manifest id the file stem is
+//! the id. Manifest dependencies are not resolved for WASM plugins.
+//!
+//! # Guest ABI
+//!
+//! Pointers and lengths are `i32` offsets into the exported `memory`; strings are UTF-8.
+//!
+//! Imports from module `newengine`:
+//!
+//! - `log(level, ptr, len)`: level `0` info, `1` warn, `2` error.
+//! - `register_service(id_ptr, id_len, describe_ptr, describe_len) -> i32`: only during
+//!   `ne_init`; the describe JSON follows the DLL plugin conventions, so an importer
+//!   describe registers an asset importer.
+//! - `call_service(id_ptr, id_len, method_ptr, method_len, payload_ptr, payload_len,
+//!   out_ptr) -> i32`
+//! - `emit_event(topic_ptr, topic_len, payload_ptr, payload_len) -> i32`
+//!
+//! Exports:
+//!
+//! - `memory`, `ne_alloc(len) -> ptr` and `ne_free(ptr, len)`: the allocator the host uses
+//!   for every buffer it hands the guest.
+//! - `ne_init() -> i32`, `ne_update(dt: f32)`, `ne_shutdown()`: all optional.
+//! - `ne_service_call(id_ptr, id_len, method_ptr, method_len, payload_ptr, payload_len,
+//!   out_ptr) -> i32`: required when the plugin registers services.
+//!
+//! Calls returning `i32` return `0` on success. Those taking `out_ptr` write the result (or
+//! the error message on failure) to an `ne_alloc` buffer and store its pointer and length
+//! as two little-endian `u32`s at `out_ptr`.
+//!
+//! Buffer ownership: the result of the `call_service` import belongs to the guest, which
+//! frees it when done. For `ne_service_call` the host allocates the id, method, payload and
+//! `out_ptr` buffers and, once the call returns and it has copied the result out, calls
+//! `ne_free` on each of them and on the result buffer. The guest must not keep pointers to
+//! any of them past the call.
+
+pub mod config;
+mod host;
+pub mod module;
+mod plugin;
+mod service;
+
+pub use config::WasmPluginConfig;
+pub use module::{WasmPluginsModule, WASM_PLUGINS_MODULE_ID};
+
+pub use wasmtime;
@@ -0,0 +1,245 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::config::WasmPluginConfig;
+use crate::host::{link, GuestState};
+use crate::plugin::{enter, SharedPlugin, WasmPlugin};
+use crate::service::WasmService;
+
+use abi_stable::sabi_trait::TD_Opaque;
+use newengine_core::plugins::{plugin_permissions, PluginManifest};
+use newengine_core::{
+    grant_plugin_permissions, register_plugin_service, unregister_plugin, EngineError,
+    EngineResult, Module, ModuleCtx,
+};
+use newengine_plugin_api::ServiceV1_TO;
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use wasmtime::{Config, Engine, Linker};
+
+pub const WASM_PLUGINS_MODULE_ID: &str = "plugins.wasm";
+
+struct LoadedWasmPlugin {
+    id: String,
+    plugin: SharedPlugin,
+    /// Faulted and unregistered; kept only so the fault is published once.
+    disabled: bool,
+}
+
+/// Advances the engine epoch so guest calls past their deadline trap.
+struct EpochTicker {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl EpochTicker {
+    fn spawn(engine: Engine, tick: std::time::Duration) -> std::io::Result<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+        let thread = std::thread::Builder::new()
+            .name("wasm-epoch".into())
+            .spawn(move || {
+                while !flag.load(Ordering::Relaxed) {
+                    std::thread::sleep(tick);
+                    engine.increment_epoch();
+                }
+            })?;
+        Ok(Self {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for EpochTicker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+    }
+}
+
+/// Loads the `*.wasm` plugins of `WasmPluginConfig::dir` at init and calls their
+/// `ne_update` every frame. Services they declare are registered like DLL plugin services,
+/// importers included; a plugin that traps is unregistered and a `PluginFault` published.
+pub struct WasmPluginsModule {
+    config: WasmPluginConfig,
+    plugins: Vec<LoadedWasmPlugin>,
+    ticker: Option<EpochTicker>,
+}
+
+impl WasmPluginsModule {
+    #[inline]
+    pub fn new(config: WasmPluginConfig) -> Self {
+        Self {
+            config,
+            plugins: Vec::new(),
+            ticker: None,
+        }
+    }
+
+    /// Ids of the loaded plugins that have not faulted.
+    pub fn plugin_ids(&self) -> impl Iterator<Item = &str> {
+        self.plugins
+            .iter()
+            .filter(|p| !p.disabled)
+            .map(|p| p.id.as_str())
+    }
+
+    fn scan(dir: &Path) -> Vec<PathBuf> {
+        let Ok(rd) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut out: Vec<PathBuf> = rd
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| e.eq_ignore_ascii_case("wasm"))
+            })
+            .collect();
+        out.sort();
+        out
+    }
+
+    fn load_one(
+        &mut self,
+        engine: &Engine,
+        linker: &Linker<GuestState>,
+        path: &Path,
+    ) -> Result<(), String> {
+        let manifest = PluginManifest::load_for(path)?;
+        let id = manifest
+            .as_ref()
+            .and_then(|m| m.id.clone())
+            .or_else(|| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .ok_or("no plugin id")?;
+        if plugin_permissions(&id).is_some() || self.plugins.iter().any(|p| p.id == id) {
+            return Err(format!("plugin id '{id}' is already loaded"));
+        }
+
+        let granted = self.config.permissions.resolve(&id, manifest.as_ref())?;
+        let module = wasmtime::Module::from_file(engine, path).map_err(|e| format!("{e:#}"))?;
+
+        grant_plugin_permissions(&id, granted);
+        let (plugin, services) =
+            match WasmPlugin::instantiate(engine, linker, &module, &id, &self.config) {
+                Ok(v) => v,
+                Err(e) => {
+                    unregister_plugin(&id);
+                    return Err(e);
+                }
+            };
+        let plugin: SharedPlugin = Arc::new(Mutex::new(plugin));
+
+        for (service_id, describe_json) in services {
+            let svc = WasmService {
+                plugin_id: id.clone(),
+                service_id: service_id.clone(),
+                describe_json,
+                plugin: plugin.clone(),
+            };
+            let svc = ServiceV1_TO::from_value(svc, TD_Opaque);
+            if let Err(e) = register_plugin_service(&id, svc) {
+                unregister_plugin(&id);
+                plugin.lock().shutdown();
+                return Err(format!("service '{service_id}': {e}"));
+            }
+        }
+
+        log::info!(
+            target: "plugins",
+            "wasm: loaded id='{}' path='{}' permissions={}",
+            id,
+            path.display(),
+            granted
+        );
+        self.plugins.push(LoadedWasmPlugin {
+            id,
+            plugin,
+            disabled: false,
+        });
+        Ok(())
+    }
+
+    /// Unregisters plugins that trapped since the last frame and publishes their faults.
+    fn collect_faults<E: Send + 'static>(&mut self, ctx: &mut ModuleCtx<'_, E>) {
+        for p in self.plugins.iter_mut().filter(|p| !p.disabled) {
+            // A plugin busy on another thread reports on a later frame.
+            let Some(fault) = p.plugin.try_lock().and_then(|g| g.fault().cloned()) else {
+                continue;
+            };
+            p.disabled = true;
+            unregister_plugin(&p.id);
+            let _ = ctx.events().publish(fault);
+        }
+    }
+}
+
+impl<E: Send + 'static> Module<E> for WasmPluginsModule {
+    fn id(&self) -> &'static str {
+        WASM_PLUGINS_MODULE_ID
+    }
+
+    fn init(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let mut cfg = Config::new();
+        cfg.consume_fuel(true);
+        cfg.epoch_interruption(true);
+        let engine = Engine::new(&cfg)
+            .map_err(|e| EngineError::Other(format!("wasm: engine init failed: {e:#}")))?;
+
+        let mut linker = Linker::new(&engine);
+        link(&mut linker)
+            .map_err(|e| EngineError::Other(format!("wasm: host link failed: {e:#}")))?;
+
+        self.ticker = Some(
+            EpochTicker::spawn(engine.clone(), self.config.epoch_tick)
+                .map_err(|e| EngineError::Other(format!("wasm: epoch thread failed: {e}")))?,
+        );
+
+        let files = Self::scan(&self.config.dir);
+        for path in files.iter() {
+            if let Err(e) = self.load_one(&engine, &linker, path) {
+                log::error!(
+                    target: "plugins",
+                    "wasm: load failed path='{}': {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+        log::info!(
+            target: "plugins",
+            "wasm: dir='{}' found={} loaded={}",
+            self.config.dir.display(),
+            files.len(),
+            self.plugins.len()
+        );
+        Ok(())
+    }
+
+    fn update(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let dt = ctx.frame().map(|f| f.dt).unwrap_or(0.0);
+        for p in self.plugins.iter().filter(|p| !p.disabled) {
+            // Trapped updates set the plugin's fault, collected below.
+            let _ = enter(&p.plugin, &p.id, |g| g.update(dt));
+        }
+        self.collect_faults(ctx);
+        Ok(())
+    }
+
+    fn shutdown(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        for p in self.plugins.drain(..) {
+            if !p.disabled {
+                let _ = enter(&p.plugin, &p.id, |g| g.shutdown());
+            }
+            unregister_plugin(&p.id);
+        }
+        self.ticker = None;
+        Ok(())
+    }
+}
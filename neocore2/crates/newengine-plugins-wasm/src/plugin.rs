@@ -0,0 +1,323 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::config::WasmPluginConfig;
+use crate::host::{read_out, write_alloc, GuestState};
+
+use newengine_core::plugins::{PluginCallSite, PluginFault};
+use parking_lot::Mutex;
+use std::cell::RefCell;
+use std::sync::Arc;
+use wasmtime::{Engine, Linker, Memory, Module, Store, StoreLimitsBuilder, Trap, TypedFunc};
+
+type ServiceCallFn = TypedFunc<(i32, i32, i32, i32, i32, i32, i32), i32>;
+
+pub(crate) type SharedPlugin = Arc<Mutex<WasmPlugin>>;
+
+/// Failure of a call into a guest.
+pub(crate) enum GuestError {
+    /// The guest returned an error status; the plugin stays enabled.
+    Failed(String),
+    /// Trap, exhausted budget or ABI violation. The plugin is disabled.
+    Trapped(String),
+}
+
+impl GuestError {
+    #[inline]
+    pub(crate) fn message(&self) -> &str {
+        match self {
+            GuestError::Failed(m) | GuestError::Trapped(m) => m,
+        }
+    }
+}
+
+/// One instantiated WASM plugin.
+pub(crate) struct WasmPlugin {
+    id: String,
+    store: Store<GuestState>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    free: TypedFunc<(i32, i32), ()>,
+    update: Option<TypedFunc<f32, ()>>,
+    shutdown: Option<TypedFunc<(), ()>>,
+    service_call: Option<ServiceCallFn>,
+    fuel_per_call: u64,
+    deadline_ticks: u64,
+    /// Set by the first trap; a faulted plugin gets no further calls.
+    fault: Option<PluginFault>,
+}
+
+impl WasmPlugin {
+    /// Instantiates `module` and runs `ne_init`. Returns the services it declared.
+    pub(crate) fn instantiate(
+        engine: &Engine,
+        linker: &Linker<GuestState>,
+        module: &Module,
+        id: &str,
+        config: &WasmPluginConfig,
+    ) -> Result<(Self, Vec<(String, String)>), String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(config.max_memory_bytes)
+            .instances(1)
+            .build();
+        let mut store = Store::new(
+            engine,
+            GuestState {
+                plugin_id: id.to_string(),
+                limits,
+                initializing: false,
+                pending_services: Vec::new(),
+            },
+        );
+        store.limiter(|st| &mut st.limits);
+
+        arm_store(&mut store, config.fuel_per_call, config.deadline_ticks())?;
+        let instance = linker
+            .instantiate(&mut store, module)
+            .map_err(|e| format!("instantiate failed: {}", describe_error(&e)))?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("plugin does not export 'memory'")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "ne_alloc")
+            .map_err(|e| format!("'ne_alloc': {e}"))?;
+        let free = instance
+            .get_typed_func::<(i32, i32), ()>(&mut store, "ne_free")
+            .map_err(|e| format!("'ne_free': {e}"))?;
+        let update = instance.get_typed_func(&mut store, "ne_update").ok();
+        let shutdown = instance.get_typed_func(&mut store, "ne_shutdown").ok();
+        let service_call = instance.get_typed_func(&mut store, "ne_service_call").ok();
+
+        let mut plugin = Self {
+            id: id.to_string(),
+            store,
+            memory,
+            alloc,
+            free,
+            update,
+            shutdown,
+            service_call,
+            fuel_per_call: config.fuel_per_call,
+            deadline_ticks: config.deadline_ticks(),
+            fault: None,
+        };
+
+        let init = instance
+            .get_typed_func::<(), i32>(&mut plugin.store, "ne_init")
+            .ok();
+        if let Some(init) = init {
+            plugin.arm()?;
+            plugin.store.data_mut().initializing = true;
+            let res = init.call(&mut plugin.store, ());
+            plugin.store.data_mut().initializing = false;
+            match res {
+                Ok(0) => {}
+                Ok(code) => return Err(format!("ne_init failed with status {code}")),
+                Err(e) => return Err(format!("ne_init trapped: {}", describe_error(&e))),
+            }
+        }
+
+        let services = std::mem::take(&mut plugin.store.data_mut().pending_services);
+        if !services.is_empty() && plugin.service_call.is_none() {
+            return Err("plugin registers services but does not export 'ne_service_call'".into());
+        }
+        Ok((plugin, services))
+    }
+
+    #[inline]
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    #[inline]
+    pub(crate) fn fault(&self) -> Option<&PluginFault> {
+        self.fault.as_ref()
+    }
+
+    /// Refills the fuel and restarts the epoch deadline before a call into the guest.
+    #[inline]
+    fn arm(&mut self) -> Result<(), String> {
+        arm_store(&mut self.store, self.fuel_per_call, self.deadline_ticks)
+    }
+
+    fn trapped(
+        &mut self,
+        site: PluginCallSite,
+        target: Option<String>,
+        e: &wasmtime::Error,
+    ) -> GuestError {
+        let message = describe_error(e);
+        log::error!(
+            target: "plugins",
+            "wasm: plugin '{}' trapped in {}{}: {}",
+            self.id,
+            site.as_str(),
+            target.as_deref().map(|t| format!(" '{t}'")).unwrap_or_default(),
+            message
+        );
+        self.fault = Some(PluginFault {
+            plugin_id: self.id.clone(),
+            site,
+            target,
+            message: message.clone(),
+            panicked: true,
+        });
+        GuestError::Trapped(message)
+    }
+
+    fn ensure_live(&self) -> Result<(), GuestError> {
+        match &self.fault {
+            Some(f) => Err(GuestError::Trapped(format!(
+                "wasm plugin '{}' is disabled after a fault: {}",
+                self.id, f.message
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn update(&mut self, dt: f32) -> Result<(), GuestError> {
+        self.ensure_live()?;
+        let Some(update) = self.update.clone() else {
+            return Ok(());
+        };
+        self.arm().map_err(GuestError::Failed)?;
+        update
+            .call(&mut self.store, dt)
+            .map_err(|e| self.trapped(PluginCallSite::Update, None, &e))
+    }
+
+    pub(crate) fn call_service(
+        &mut self,
+        service_id: &str,
+        method: &str,
+        payload: &[u8],
+    ) -> Result<Vec<u8>, GuestError> {
+        self.ensure_live()?;
+        let Some(call) = self.service_call.clone() else {
+            return Err(GuestError::Failed("plugin has no 'ne_service_call'".into()));
+        };
+        let target = format!("{service_id}::{method}");
+        self.arm().map_err(GuestError::Failed)?;
+        match self.call_service_inner(&call, service_id, method, payload) {
+            Ok((0, out)) => Ok(out),
+            Ok((_, out)) => Err(GuestError::Failed(
+                String::from_utf8_lossy(&out).into_owned(),
+            )),
+            Err(e) => Err(self.trapped(PluginCallSite::Service, Some(target), &e)),
+        }
+    }
+
+    fn call_service_inner(
+        &mut self,
+        call: &ServiceCallFn,
+        service_id: &str,
+        method: &str,
+        payload: &[u8],
+    ) -> wasmtime::Result<(i32, Vec<u8>)> {
+        let (memory, alloc, free) = (self.memory, self.alloc.clone(), self.free.clone());
+        let store = &mut self.store;
+        let id_ptr = write_alloc(&mut *store, memory, &alloc, service_id.as_bytes())?;
+        let method_ptr = write_alloc(&mut *store, memory, &alloc, method.as_bytes())?;
+        let payload_ptr = write_alloc(&mut *store, memory, &alloc, payload)?;
+        let out_ptr = write_alloc(&mut *store, memory, &alloc, &[0u8; 8])?;
+
+        let status = call.call(
+            &mut *store,
+            (
+                id_ptr,
+                service_id.len() as i32,
+                method_ptr,
+                method.len() as i32,
+                payload_ptr,
+                payload.len() as i32,
+                out_ptr,
+            ),
+        )?;
+        let (out_buf, out) = read_out(&*store, memory, out_ptr)?;
+
+        // Every buffer of the call is back with the guest once the result is copied out.
+        free.call(&mut *store, (id_ptr, service_id.len() as i32))?;
+        free.call(&mut *store, (method_ptr, method.len() as i32))?;
+        free.call(&mut *store, (payload_ptr, payload.len() as i32))?;
+        free.call(&mut *store, (out_ptr, 8))?;
+        if out_buf != 0 {
+            free.call(&mut *store, (out_buf, out.len() as i32))?;
+        }
+        Ok((status, out))
+    }
+
+    pub(crate) fn shutdown(&mut self) {
+        if self.fault.is_some() {
+            return;
+        }
+        let Some(shutdown) = self.shutdown.clone() else {
+            return;
+        };
+        if self.arm().is_err() {
+            return;
+        }
+        if let Err(e) = shutdown.call(&mut self.store, ()) {
+            log::warn!(
+                target: "plugins",
+                "wasm: plugin '{}' trapped in ne_shutdown: {}",
+                self.id,
+                describe_error(&e)
+            );
+        }
+    }
+}
+
+fn arm_store(
+    store: &mut Store<GuestState>,
+    fuel_per_call: u64,
+    deadline_ticks: u64,
+) -> Result<(), String> {
+    let fuel = match fuel_per_call {
+        0 => u64::MAX,
+        n => n,
+    };
+    store.set_fuel(fuel).map_err(|e| format!("{e:#}"))?;
+    store.set_epoch_deadline(deadline_ticks);
+    Ok(())
+}
+
+thread_local! {
+    /// Plugins with a call in progress on this thread, to refuse re-entry instead of
+    /// deadlocking on the plugin's mutex.
+    static ACTIVE: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Locks `plugin` for a call unless this thread is already inside it.
+pub(crate) fn enter<R>(
+    plugin: &SharedPlugin,
+    id: &str,
+    f: impl FnOnce(&mut WasmPlugin) -> R,
+) -> Result<R, String> {
+    let reentrant = ACTIVE.with(|a| a.borrow().iter().any(|p| p == id));
+    if reentrant {
+        return Err(format!("re-entrant call into wasm plugin '{id}'"));
+    }
+    ACTIVE.with(|a| a.borrow_mut().push(id.to_string()));
+    let _active = ActiveGuard;
+    Ok(f(&mut plugin.lock()))
+}
+
+/// Pops the entry [`enter`] pushed, also when the call unwinds.
+struct ActiveGuard;
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        ACTIVE.with(|a| {
+            a.borrow_mut().pop();
+        });
+    }
+}
+
+/// Trap reason in plain words for budget and deadline traps.
+fn describe_error(e: &wasmtime::Error) -> String {
+    match e.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => "fuel exhausted".to_string(),
+        Some(Trap::Interrupt) => "call timed out".to_string(),
+        _ => format!("{e:#}"),
+    }
+}
@@ -0,0 +1,40 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::plugin::{enter, GuestError, SharedPlugin};
+
+use abi_stable::std_types::{RResult, RString};
+use newengine_plugin_api::{Blob, CapabilityId, MethodName, ServiceV1};
+
+/// A service declared by a WASM plugin; calls go to the guest's `ne_service_call`.
+pub(crate) struct WasmService {
+    pub plugin_id: String,
+    pub service_id: String,
+    pub describe_json: String,
+    pub plugin: SharedPlugin,
+}
+
+impl ServiceV1 for WasmService {
+    fn id(&self) -> CapabilityId {
+        RString::from(self.service_id.as_str())
+    }
+
+    fn describe(&self) -> RString {
+        RString::from(self.describe_json.as_str())
+    }
+
+    fn call(&self, method: MethodName, payload: Blob) -> RResult<Blob, RString> {
+        let out = enter(&self.plugin, &self.plugin_id, |p| {
+            p.call_service(&self.service_id, method.as_str(), payload.as_slice())
+        });
+        match out {
+            Ok(Ok(bytes)) => RResult::ROk(Blob::from(bytes)),
+            Ok(Err(GuestError::Failed(e))) => RResult::RErr(RString::from(e)),
+            Ok(Err(e @ GuestError::Trapped(_))) => RResult::RErr(RString::from(format!(
+                "service '{}' trapped: {}",
+                self.service_id,
+                e.message()
+            ))),
+            Err(e) => RResult::RErr(RString::from(e)),
+        }
+    }
+}
@@ -0,0 +1,233 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::error::SceneError;
+
+pub const SCENE_TYPE_ID: &str = "kalitech.asset.scene";
+pub const SCENE_FORMAT: &str = "ne.scene.json.v1";
+pub const SCENE_META_SCHEMA: &str = "kalitech.scene.meta.v1";
+
+/// Highest scene schema version this build understands.
+pub const SCENE_VERSION: u32 = 1;
+
+/// Serializable scene description (`.nescene` / `.scene.json`).
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "name": "level_01",
+///   "assets": [{ "path": "ui/hud.ui" }],
+///   "entities": [
+///     { "id": 1, "name": "root" },
+///     { "id": 2, "parent": 1,
+///       "transform": { "translation": [0, 1, 0], "scale": [2, 2, 2] },
+///       "components": { "light": { "color": [1, 0.9, 0.8], "intensity": 4 } },
+///       "assets": [{ "path": "models/lamp.ne3d", "usage": "mesh" }] }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneDesc {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub name: String,
+    /// Scene-wide asset references (loaded with the scene, not bound to an entity).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assets: Vec<SceneAssetRef>,
+    #[serde(default)]
+    pub entities: Vec<SceneEntity>,
+}
+
+impl Default for SceneDesc {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            version: SCENE_VERSION,
+            name: String::new(),
+            assets: Vec::new(),
+            entities: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneEntity {
+    /// Scene-local id, unique within the scene. Only used for parenting.
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<u32>,
+    #[serde(default)]
+    pub transform: SceneTransform,
+    /// Component name -> component data, decoded by the loader's `ComponentRegistry`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub components: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assets: Vec<SceneAssetRef>,
+}
+
+impl SceneEntity {
+    #[inline]
+    pub fn new(id: u32) -> Self {
+        Self {
+            id,
+            name: None,
+            parent: None,
+            transform: SceneTransform::default(),
+            components: BTreeMap::new(),
+            assets: Vec::new(),
+        }
+    }
+}
+
+/// Local transform relative to the parent entity (or the scene root).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SceneTransform {
+    #[serde(default)]
+    pub translation: [f32; 3],
+    /// Quaternion `[x, y, z, w]`.
+    #[serde(default = "identity_rotation")]
+    pub rotation: [f32; 4],
+    #[serde(default = "unit_scale")]
+    pub scale: [f32; 3],
+}
+
+impl Default for SceneTransform {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            translation: [0.0; 3],
+            rotation: identity_rotation(),
+            scale: unit_scale(),
+        }
+    }
+}
+
+/// Reference to an asset by logical path (relative to the assets root).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SceneAssetRef {
+    pub path: String,
+    /// Free-form role (`mesh`, `material`, ...). Reported as the dependency usage.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub usage: String,
+    /// Expected blob type id, if known.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub type_hint: String,
+}
+
+impl SceneAssetRef {
+    #[inline]
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            usage: String::new(),
+            type_hint: String::new(),
+        }
+    }
+}
+
+impl SceneDesc {
+    /// Parses and validates a scene document.
+    pub fn from_json(bytes: &[u8]) -> Result<Self, SceneError> {
+        let desc: SceneDesc =
+            serde_json::from_slice(bytes).map_err(|e| SceneError::Json(e.to_string()))?;
+        desc.validate()?;
+        Ok(desc)
+    }
+
+    pub fn to_json(&self) -> Result<Vec<u8>, SceneError> {
+        serde_json::to_vec(self).map_err(|e| SceneError::Json(e.to_string()))
+    }
+
+    pub fn to_json_pretty(&self) -> Result<String, SceneError> {
+        serde_json::to_string_pretty(self).map_err(|e| SceneError::Json(e.to_string()))
+    }
+
+    /// Checks the schema version, id uniqueness and that parents exist and form no cycle.
+    pub fn validate(&self) -> Result<(), SceneError> {
+        if self.version == 0 || self.version > SCENE_VERSION {
+            return Err(SceneError::UnsupportedVersion(self.version));
+        }
+
+        let mut parents: HashMap<u32, Option<u32>> = HashMap::with_capacity(self.entities.len());
+        for e in self.entities.iter() {
+            if parents.insert(e.id, e.parent).is_some() {
+                return Err(SceneError::DuplicateEntity(e.id));
+            }
+        }
+
+        for e in self.entities.iter() {
+            let Some(parent) = e.parent else {
+                continue;
+            };
+            if !parents.contains_key(&parent) {
+                return Err(SceneError::MissingParent { entity: e.id, parent });
+            }
+
+            // Walk up; a chain longer than the entity count must loop.
+            let mut cur = Some(parent);
+            let mut steps = 0usize;
+            while let Some(p) = cur {
+                if p == e.id || steps > parents.len() {
+                    return Err(SceneError::ParentCycle(e.id));
+                }
+                cur = parents.get(&p).copied().flatten();
+                steps += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Entities ordered so that every parent precedes its children.
+    pub fn entities_parent_first(&self) -> Vec<&SceneEntity> {
+        let index: HashMap<u32, &SceneEntity> = self.entities.iter().map(|e| (e.id, e)).collect();
+        let mut placed: HashSet<u32> = HashSet::with_capacity(self.entities.len());
+        let mut out = Vec::with_capacity(self.entities.len());
+
+        for e in self.entities.iter() {
+            let mut chain = Vec::new();
+            let mut cur = Some(e);
+            while let Some(n) = cur {
+                if placed.contains(&n.id) {
+                    break;
+                }
+                chain.push(n);
+                cur = n.parent.and_then(|p| index.get(&p).copied());
+            }
+            for n in chain.into_iter().rev() {
+                if placed.insert(n.id) {
+                    out.push(n);
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Scene-wide and per-entity asset references, in document order.
+    pub fn asset_refs(&self) -> impl Iterator<Item = &SceneAssetRef> {
+        self.assets
+            .iter()
+            .chain(self.entities.iter().flat_map(|e| e.assets.iter()))
+    }
+}
+
+#[inline]
+fn default_version() -> u32 {
+    SCENE_VERSION
+}
+
+#[inline]
+fn identity_rotation() -> [f32; 4] {
+    [0.0, 0.0, 0.0, 1.0]
+}
+
+#[inline]
+fn unit_scale() -> [f32; 3] {
+    [1.0; 3]
+}
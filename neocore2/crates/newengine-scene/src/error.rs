@@ -0,0 +1,62 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_assets::AssetError;
+use newengine_core::EngineError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneError {
+    Json(String),
+    UnsupportedVersion(u32),
+    DuplicateEntity(u32),
+    MissingParent { entity: u32, parent: u32 },
+    ParentCycle(u32),
+    /// Blob is not a `kalitech.asset.scene` / `ne.scene.json.v1` payload.
+    WrongBlob { type_id: String, format: String },
+    Component {
+        entity: u32,
+        component: String,
+        message: String,
+    },
+    Asset(String),
+    NoSuchInstance(u64),
+}
+
+impl std::fmt::Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneError::Json(e) => write!(f, "scene: invalid json: {e}"),
+            SceneError::UnsupportedVersion(v) => write!(f, "scene: unsupported version {v}"),
+            SceneError::DuplicateEntity(id) => write!(f, "scene: duplicate entity id {id}"),
+            SceneError::MissingParent { entity, parent } => {
+                write!(f, "scene: entity {entity} references missing parent {parent}")
+            }
+            SceneError::ParentCycle(id) => write!(f, "scene: parent cycle through entity {id}"),
+            SceneError::WrongBlob { type_id, format } => {
+                write!(f, "scene: unexpected blob type='{type_id}' format='{format}'")
+            }
+            SceneError::Component {
+                entity,
+                component,
+                message,
+            } => write!(f, "scene: entity {entity} component '{component}': {message}"),
+            SceneError::Asset(e) => write!(f, "scene: asset: {e}"),
+            SceneError::NoSuchInstance(id) => write!(f, "scene: no instance {id}"),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<SceneError> for AssetError {
+    #[inline]
+    fn from(e: SceneError) -> Self {
+        AssetError::new(e.to_string())
+    }
+}
+
+impl From<SceneError> for EngineError {
+    #[inline]
+    fn from(e: SceneError) -> Self {
+        EngineError::other(e.to_string())
+    }
+}
@@ -0,0 +1,105 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_assets::{
+    AssetBlob, AssetDependency, AssetError, AssetKey, BlobImporterDispatch, ImporterPriority,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::desc::{SceneDesc, SCENE_FORMAT, SCENE_META_SCHEMA, SCENE_TYPE_ID};
+use crate::error::SceneError;
+
+pub const SCENE_EXTENSIONS: &[&str] = &["nescene", "scene.json"];
+pub const SCENE_IMPORTER_VERSION: &str = "scene.v1";
+
+/// Imports `.nescene` / `.scene.json` documents.
+///
+/// The payload is the validated, re-serialized `SceneDesc` (`ne.scene.json.v1`); every asset
+/// reference becomes a blob dependency so edits to referenced assets mark the scene dirty.
+#[derive(Debug, Default)]
+pub struct SceneImporter;
+
+impl SceneImporter {
+    /// Decodes a blob produced by this importer.
+    pub fn decode(blob: &AssetBlob) -> Result<SceneDesc, SceneError> {
+        if &*blob.type_id != SCENE_TYPE_ID || &*blob.format != SCENE_FORMAT {
+            return Err(SceneError::WrongBlob {
+                type_id: blob.type_id.to_string(),
+                format: blob.format.to_string(),
+            });
+        }
+        SceneDesc::from_json(&blob.payload)
+    }
+}
+
+impl BlobImporterDispatch for SceneImporter {
+    fn import_blob(&self, bytes: &[u8], key: &AssetKey) -> Result<AssetBlob, AssetError> {
+        let desc = SceneDesc::from_json(bytes)?;
+        let payload = desc.to_json()?;
+
+        let dependencies: Vec<AssetDependency> = desc
+            .asset_refs()
+            .map(|r| AssetDependency {
+                logical_path: PathBuf::from(&r.path),
+                settings_hash: 0,
+                type_hint: Arc::from(r.type_hint.as_str()),
+                usage: Arc::from(if r.usage.is_empty() {
+                    "scene.asset".to_string()
+                } else {
+                    format!("scene.{}", r.usage)
+                }),
+            })
+            .collect();
+
+        let meta_json = serde_json::json!({
+            "schema": SCENE_META_SCHEMA,
+            "name": desc.name,
+            "version": desc.version,
+            "entities": desc.entities.len(),
+            "assets": dependencies.len(),
+        })
+        .to_string();
+
+        log::info!(
+            target: "scene",
+            "import.done path='{}' name='{}' entities={} assets={}",
+            key.logical_path.display(),
+            desc.name,
+            desc.entities.len(),
+            dependencies.len()
+        );
+
+        Ok(AssetBlob {
+            type_id: Arc::from(SCENE_TYPE_ID),
+            format: Arc::from(SCENE_FORMAT),
+            payload,
+            meta_json: Arc::from(meta_json),
+            dependencies,
+        })
+    }
+
+    #[inline]
+    fn output_type_id(&self) -> Arc<str> {
+        Arc::from(SCENE_TYPE_ID)
+    }
+
+    #[inline]
+    fn extensions(&self) -> Vec<String> {
+        SCENE_EXTENSIONS.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[inline]
+    fn priority(&self) -> ImporterPriority {
+        ImporterPriority::new(100)
+    }
+
+    #[inline]
+    fn stable_id(&self) -> Arc<str> {
+        Arc::from("scene_importer@newengine-scene")
+    }
+
+    #[inline]
+    fn version(&self) -> Arc<str> {
+        Arc::from(SCENE_IMPORTER_VERSION)
+    }
+}
@@ -0,0 +1,16 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+pub mod desc;
+pub mod error;
+pub mod importer;
+pub mod loader;
+pub mod module;
+
+pub use desc::{
+    SceneAssetRef, SceneDesc, SceneEntity, SceneTransform, SCENE_FORMAT, SCENE_META_SCHEMA, SCENE_TYPE_ID,
+    SCENE_VERSION,
+};
+pub use error::SceneError;
+pub use importer::{SceneImporter, SCENE_EXTENSIONS, SCENE_IMPORTER_VERSION};
+pub use loader::{ComponentRegistry, SceneAssets, SceneInstanceId, SceneLoader, SceneNode};
+pub use module::{SceneModule, SCENE_MODULE_ID};
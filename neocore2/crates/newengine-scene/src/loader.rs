@@ -0,0 +1,477 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_assets::{AssetBlob, AssetId, AssetState, AssetStore};
+use newengine_core::CancelToken;
+use newengine_ecs::glam::{Quat, Vec3};
+use newengine_ecs::{Component, Entity, Transform, World};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::desc::{SceneDesc, SceneTransform};
+use crate::error::SceneError;
+use crate::importer::SceneImporter;
+
+/// Identifies one instantiation of a scene. Loading the same scene twice yields two instances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SceneInstanceId(pub u64);
+
+impl std::fmt::Display for SceneInstanceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "scene#{}", self.0)
+    }
+}
+
+/// Attached to every entity spawned from a scene, next to its `Transform` (parents are
+/// linked with `World::set_parent`).
+#[derive(Debug, Clone)]
+pub struct SceneNode {
+    pub instance: SceneInstanceId,
+    /// `SceneEntity::id` inside the scene document.
+    pub local_id: u32,
+    pub name: Option<String>,
+}
+
+impl From<SceneTransform> for Transform {
+    #[inline]
+    fn from(t: SceneTransform) -> Self {
+        Transform {
+            translation: Vec3::from_array(t.translation),
+            rotation: Quat::from_array(t.rotation).normalize(),
+            scale: Vec3::from_array(t.scale),
+        }
+    }
+}
+
+/// Assets referenced by an entity, in document order. Loads are enqueued on instantiation.
+#[derive(Debug, Clone, Default)]
+pub struct SceneAssets(pub Vec<AssetId>);
+
+type InsertFn = Box<dyn Fn(&mut World, Entity, &serde_json::Value) -> Result<(), String> + Send + Sync>;
+
+/// Maps component names used in scene documents to ECS component types.
+#[derive(Default)]
+pub struct ComponentRegistry {
+    entries: HashMap<String, InsertFn>,
+}
+
+impl ComponentRegistry {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `T` under `name`, decoded from the component's JSON value with serde.
+    pub fn register<T: Component + DeserializeOwned>(&mut self, name: impl Into<String>) {
+        self.register_with(name, |world, e, value| {
+            let c: T = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+            world.insert(e, (c,)).map_err(|e| e.to_string())
+        });
+    }
+
+    /// Registers a custom decoder (multiple components, validation, defaults, ...).
+    pub fn register_with(
+        &mut self,
+        name: impl Into<String>,
+        f: impl Fn(&mut World, Entity, &serde_json::Value) -> Result<(), String> + Send + Sync + 'static,
+    ) {
+        let name = name.into();
+        if self.entries.insert(name.clone(), Box::new(f)).is_some() {
+            log::warn!(target: "scene", "registry.replace component='{}'", name);
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    #[inline]
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+}
+
+impl std::fmt::Debug for ComponentRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComponentRegistry")
+            .field("len", &self.entries.len())
+            .finish()
+    }
+}
+
+struct SceneSlot {
+    id: SceneInstanceId,
+    /// Set for instances loaded through the asset store; `None` for `instantiate`.
+    asset: Option<AssetId>,
+    path: Option<String>,
+    blob: Option<Arc<AssetBlob>>,
+    name: String,
+    entities: Vec<Entity>,
+    assets: Vec<AssetId>,
+    failure_reported: bool,
+    /// Child of the loader token; cancelled on unload so queued imports are dropped.
+    cancel: CancelToken,
+}
+
+/// Instantiates scenes into the `World` and tracks them for unloading.
+///
+/// Scenes loaded by path go through the asset store: they spawn on the first `poll` after the
+/// import finishes and are re-spawned when the `.nescene` is re-imported.
+pub struct SceneLoader {
+    store: Option<Arc<AssetStore>>,
+    cancel: CancelToken,
+    registry: ComponentRegistry,
+    slots: Vec<SceneSlot>,
+    next_id: u64,
+}
+
+impl SceneLoader {
+    #[inline]
+    pub fn new(store: Option<Arc<AssetStore>>) -> Self {
+        Self {
+            store,
+            cancel: CancelToken::new(),
+            registry: ComponentRegistry::new(),
+            slots: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Parents every instance token to `cancel` (normally the engine's `CancelToken`), so
+    /// shutdown drops imports queued by scenes.
+    #[inline]
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    #[inline]
+    pub fn registry(&self) -> &ComponentRegistry {
+        &self.registry
+    }
+
+    #[inline]
+    pub fn registry_mut(&mut self) -> &mut ComponentRegistry {
+        &mut self.registry
+    }
+
+    /// Enqueues a scene asset. The instance is spawned by `poll` once the import is ready.
+    pub fn load(&mut self, logical_path: &str) -> Result<SceneInstanceId, SceneError> {
+        let store = self
+            .store
+            .as_ref()
+            .ok_or_else(|| SceneError::Asset("no asset store attached".to_string()))?;
+        let cancel = self.cancel.child();
+        let asset = store
+            .load_path_cancellable(logical_path, Arc::new(cancel.clone()))
+            .map_err(|e| SceneError::Asset(format!("load '{logical_path}' failed: {e}")))?;
+
+        let id = self.alloc_id();
+        self.slots.push(SceneSlot {
+            id,
+            asset: Some(asset),
+            path: Some(logical_path.to_string()),
+            blob: None,
+            name: String::new(),
+            entities: Vec::new(),
+            assets: Vec::new(),
+            failure_reported: false,
+            cancel,
+        });
+
+        log::info!(target: "scene", "scene.load {} path='{}'", id, logical_path);
+        Ok(id)
+    }
+
+    /// Spawns a scene immediately. Nothing is spawned if any component fails to decode.
+    pub fn instantiate(&mut self, world: &mut World, desc: &SceneDesc) -> Result<SceneInstanceId, SceneError> {
+        desc.validate()?;
+
+        let id = self.alloc_id();
+        let cancel = self.cancel.child();
+        let (entities, assets) = self.spawn(world, id, desc, &cancel)?;
+
+        log::info!(
+            target: "scene",
+            "scene.instantiate {} name='{}' entities={}",
+            id,
+            desc.name,
+            entities.len()
+        );
+
+        self.slots.push(SceneSlot {
+            id,
+            asset: None,
+            path: None,
+            blob: None,
+            name: desc.name.clone(),
+            entities,
+            assets,
+            failure_reported: false,
+            cancel,
+        });
+        Ok(id)
+    }
+
+    /// Despawns the instance's entities and forgets it. The scene asset is unloaded from the
+    /// store when no other instance uses it; referenced assets stay cached.
+    pub fn unload(&mut self, world: &mut World, id: SceneInstanceId) -> Result<(), SceneError> {
+        let i = self
+            .slots
+            .iter()
+            .position(|s| s.id == id)
+            .ok_or(SceneError::NoSuchInstance(id.0))?;
+        let slot = self.slots.remove(i);
+        slot.cancel.cancel();
+
+        for e in slot.entities.iter().rev() {
+            world.despawn(*e);
+        }
+
+        if let (Some(asset), Some(store)) = (slot.asset, self.store.as_ref()) {
+            if !self.slots.iter().any(|s| s.asset == Some(asset)) {
+                store.unload(asset);
+            }
+        }
+
+        log::info!(
+            target: "scene",
+            "scene.unload {} name='{}' entities={}",
+            id,
+            slot.name,
+            slot.entities.len()
+        );
+        Ok(())
+    }
+
+    /// Unloads every instance.
+    pub fn unload_all(&mut self, world: &mut World) {
+        let ids: Vec<SceneInstanceId> = self.slots.iter().map(|s| s.id).collect();
+        for id in ids {
+            let _ = self.unload(world, id);
+        }
+    }
+
+    #[inline]
+    pub fn instances(&self) -> impl Iterator<Item = SceneInstanceId> + '_ {
+        self.slots.iter().map(|s| s.id)
+    }
+
+    /// True once the instance's entities exist.
+    #[inline]
+    pub fn is_ready(&self, id: SceneInstanceId) -> bool {
+        self.slots
+            .iter()
+            .any(|s| s.id == id && (s.asset.is_none() || s.blob.is_some()))
+    }
+
+    /// Root-to-leaf ordered entities of an instance.
+    #[inline]
+    pub fn entities(&self, id: SceneInstanceId) -> Option<&[Entity]> {
+        self.slots
+            .iter()
+            .find(|s| s.id == id)
+            .map(|s| s.entities.as_slice())
+    }
+
+    /// Scene-level asset references of an instance.
+    #[inline]
+    pub fn assets(&self, id: SceneInstanceId) -> Option<&[AssetId]> {
+        self.slots
+            .iter()
+            .find(|s| s.id == id)
+            .map(|s| s.assets.as_slice())
+    }
+
+    /// Spawns scenes whose import finished and re-spawns re-imported ones.
+    pub fn poll(&mut self, world: &mut World) {
+        let Some(store) = self.store.clone() else {
+            return;
+        };
+
+        for i in 0..self.slots.len() {
+            let Some(asset) = self.slots[i].asset else {
+                continue;
+            };
+
+            match store.state(asset) {
+                AssetState::Ready => self.slots[i].failure_reported = false,
+                AssetState::Failed(e) => {
+                    // The previous instance stays alive until a re-import succeeds.
+                    let slot = &mut self.slots[i];
+                    if !slot.failure_reported {
+                        slot.failure_reported = true;
+                        log::warn!(
+                            target: "scene",
+                            "scene.failed {} path='{}' err='{}'",
+                            slot.id,
+                            slot.path.as_deref().unwrap_or(""),
+                            e
+                        );
+                    }
+                    continue;
+                }
+                _ => continue,
+            }
+
+            let Some(blob) = store.get_blob(asset) else {
+                continue;
+            };
+            if self.slots[i].blob.as_ref().is_some_and(|b| Arc::ptr_eq(b, &blob)) {
+                continue;
+            }
+
+            let desc = match SceneImporter::decode(&blob) {
+                Ok(d) => d,
+                Err(e) => {
+                    log::warn!(target: "scene", "scene.decode failed {} err='{}'", self.slots[i].id, e);
+                    self.slots[i].blob = Some(blob);
+                    continue;
+                }
+            };
+
+            let id = self.slots[i].id;
+            let cancel = self.slots[i].cancel.clone();
+            match self.spawn(world, id, &desc, &cancel) {
+                Ok((entities, assets)) => {
+                    let slot = &mut self.slots[i];
+                    let reload = slot.blob.is_some();
+                    for e in slot.entities.iter().rev() {
+                        world.despawn(*e);
+                    }
+                    log::info!(
+                        target: "scene",
+                        "scene.ready {} name='{}' entities={} reload={}",
+                        id,
+                        desc.name,
+                        entities.len(),
+                        reload
+                    );
+                    slot.name = desc.name;
+                    slot.entities = entities;
+                    slot.assets = assets;
+                }
+                Err(e) => {
+                    log::warn!(target: "scene", "scene.spawn failed {} err='{}'", id, e);
+                }
+            }
+            self.slots[i].blob = Some(blob);
+        }
+    }
+
+    #[inline]
+    fn alloc_id(&mut self) -> SceneInstanceId {
+        let id = SceneInstanceId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Spawns all entities parent-first. On error every entity spawned so far is despawned.
+    fn spawn(
+        &self,
+        world: &mut World,
+        instance: SceneInstanceId,
+        desc: &SceneDesc,
+        cancel: &CancelToken,
+    ) -> Result<(Vec<Entity>, Vec<AssetId>), SceneError> {
+        let mut spawned: Vec<Entity> = Vec::with_capacity(desc.entities.len());
+
+        if let Err(e) = self.spawn_entities(world, instance, desc, cancel, &mut spawned) {
+            for e in spawned.iter().rev() {
+                world.despawn(*e);
+            }
+            return Err(e);
+        }
+
+        let assets = desc
+            .assets
+            .iter()
+            .filter_map(|r| self.request_asset(&r.path, cancel))
+            .collect();
+
+        Ok((spawned, assets))
+    }
+
+    fn spawn_entities(
+        &self,
+        world: &mut World,
+        instance: SceneInstanceId,
+        desc: &SceneDesc,
+        cancel: &CancelToken,
+        spawned: &mut Vec<Entity>,
+    ) -> Result<(), SceneError> {
+        let mut by_local: HashMap<u32, Entity> = HashMap::with_capacity(desc.entities.len());
+
+        for se in desc.entities_parent_first() {
+            let e = world.spawn((
+                SceneNode {
+                    instance,
+                    local_id: se.id,
+                    name: se.name.clone(),
+                },
+                Transform::from(se.transform),
+            ));
+            spawned.push(e);
+            by_local.insert(se.id, e);
+
+            if let Some(parent) = se.parent.and_then(|p| by_local.get(&p).copied()) {
+                world
+                    .set_parent(e, parent)
+                    .map_err(|err| SceneError::Asset(err.to_string()))?;
+            }
+
+            if !se.assets.is_empty() {
+                let ids = se
+                    .assets
+                    .iter()
+                    .filter_map(|r| self.request_asset(&r.path, cancel))
+                    .collect();
+                world
+                    .insert(e, (SceneAssets(ids),))
+                    .map_err(|err| SceneError::Asset(err.to_string()))?;
+            }
+
+            for (name, value) in se.components.iter() {
+                let Some(insert) = self.registry.entries.get(name) else {
+                    log::warn!(
+                        target: "scene",
+                        "scene.component unknown {} entity={} component='{}'",
+                        instance,
+                        se.id,
+                        name
+                    );
+                    continue;
+                };
+                insert(world, e, value).map_err(|message| SceneError::Component {
+                    entity: se.id,
+                    component: name.clone(),
+                    message,
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn request_asset(&self, path: &str, cancel: &CancelToken) -> Option<AssetId> {
+        let store = self.store.as_ref()?;
+        match store.load_path_cancellable(path, Arc::new(cancel.clone())) {
+            Ok(id) => Some(id),
+            Err(e) => {
+                log::warn!(target: "scene", "scene.asset failed path='{}' err='{}'", path, e);
+                None
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for SceneLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SceneLoader")
+            .field("instances", &self.slots.len())
+            .field("registry", &self.registry)
+            .finish()
+    }
+}
+
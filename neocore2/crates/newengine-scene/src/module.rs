@@ -0,0 +1,74 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_core::assets::AssetManager;
+use newengine_core::{CancelToken, EngineResult, Module, ModuleCtx};
+use newengine_ecs::{World, ECS_MODULE_ID};
+use std::sync::Arc;
+
+use crate::importer::SceneImporter;
+use crate::loader::SceneLoader;
+
+pub const SCENE_MODULE_ID: &str = "scene";
+
+/// Registers the scene importer and owns the `SceneLoader` resource.
+///
+/// Scenes loaded by path are spawned into the `World` resource during `update`; all instances
+/// are unloaded on shutdown.
+#[derive(Debug, Default)]
+pub struct SceneModule;
+
+impl SceneModule {
+    #[inline]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<E: Send + 'static> Module<E> for SceneModule {
+    fn id(&self) -> &'static str {
+        SCENE_MODULE_ID
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &[ECS_MODULE_ID]
+    }
+
+    fn init(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let store = ctx.resources().get::<AssetManager>().map(|am| am.store().clone());
+        match store.as_ref() {
+            Some(store) => store.add_importer(Arc::new(SceneImporter)),
+            None => log::warn!(target: "scene", "scene.init AssetManager missing; path loading disabled"),
+        }
+
+        if ctx.resources().get::<SceneLoader>().is_none() {
+            let mut loader = SceneLoader::new(store);
+            if let Some(cancel) = ctx.resources().get::<CancelToken>() {
+                loader = loader.with_cancel(cancel.clone());
+            }
+            ctx.resources_mut().insert(loader);
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        // Both live in `Resources`; take the loader out to borrow the world mutably.
+        let Some(mut loader) = ctx.resources_mut().remove::<SceneLoader>() else {
+            return Ok(());
+        };
+        if let Some(world) = ctx.resources_mut().get_mut::<World>() {
+            loader.poll(world);
+        }
+        ctx.resources_mut().insert(loader);
+        Ok(())
+    }
+
+    fn shutdown(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let Some(mut loader) = ctx.resources_mut().remove::<SceneLoader>() else {
+            return Ok(());
+        };
+        if let Some(world) = ctx.resources_mut().get_mut::<World>() {
+            loader.unload_all(world);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,402 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! `#[service]`: typed `ServiceV1` services from a Rust trait.
+//!
+//! ```ignore
+//! use newengine_plugin_api::rpc::service;
+//!
+//! #[service(id = "acme.counter", version = "1.0")]
+//! pub trait Counter {
+//!     /// Adds `by` and returns the new value.
+//!     fn add(&self, by: i64) -> Result<i64, String>;
+//!     fn value(&self) -> i64;
+//! }
+//! ```
+//!
+//! next to the trait generates `CounterServer<T>`, a `ServiceV1` dispatching calls to any
+//! `T: Counter`, and `CounterClient<C>`, with one method per trait method returning
+//! `Result<_, RpcError>` over an `RpcTransport`. Arguments travel as a postcard-encoded
+//! tuple, results as the postcard-encoded return value; the `Err` of a method returning
+//! `Result` becomes the call's error string. Use it through `newengine_plugin_api::rpc`
+//! (feature `rpc`), which holds the runtime side.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Expr, FnArg, GenericArgument, Ident, ItemTrait, Lit, LitStr,
+    Meta, Pat, PathArguments, ReturnType, TraitItem, TraitItemFn, Type,
+};
+
+#[derive(Default)]
+struct ServiceArgs {
+    id: Option<LitStr>,
+    version: Option<LitStr>,
+}
+
+struct Method {
+    ident: Ident,
+    docs: Vec<Attribute>,
+    doc_text: String,
+    arg_names: Vec<Ident>,
+    arg_tys: Vec<Type>,
+    /// Type the client returns on success.
+    ok_ty: TokenStream2,
+    returns_result: bool,
+}
+
+#[proc_macro_attribute]
+pub fn service(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = ServiceArgs::default();
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("id") {
+            args.id = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("version") {
+            args.version = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `id = \"...\"` or `version = \"...\"`"))
+        }
+    });
+    parse_macro_input!(attr with parser);
+    let item = parse_macro_input!(item as ItemTrait);
+
+    match expand(args, item) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(args: ServiceArgs, item: ItemTrait) -> syn::Result<TokenStream2> {
+    let Some(id) = args.id else {
+        return Err(syn::Error::new(
+            item.ident.span(),
+            "#[service] needs the service id: #[service(id = \"vendor.name\")]",
+        ));
+    };
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            item.generics.span(),
+            "#[service] traits cannot be generic",
+        ));
+    }
+
+    let mut methods = Vec::new();
+    for it in item.items.iter() {
+        match it {
+            TraitItem::Fn(f) => methods.push(parse_method(f)?),
+            other => {
+                return Err(syn::Error::new(
+                    other.span(),
+                    "#[service] traits can only contain methods",
+                ))
+            }
+        }
+    }
+    if methods.is_empty() {
+        return Err(syn::Error::new(
+            item.ident.span(),
+            "#[service] trait has no methods",
+        ));
+    }
+
+    let vis = &item.vis;
+    let trait_ident = &item.ident;
+    let server = format_ident!("{}Server", trait_ident);
+    let client = format_ident!("{}Client", trait_ident);
+    let describe = describe_json(&id, args.version.as_ref(), &item.attrs, &methods);
+
+    let api = quote!(::newengine_plugin_api);
+    let rpc = quote!(::newengine_plugin_api::rpc);
+    let private = quote!(::newengine_plugin_api::rpc::__private);
+
+    let arms = methods.iter().map(|m| {
+        let ident = &m.ident;
+        let name = ident.to_string();
+        let arg_names = &m.arg_names;
+        let arg_tys = &m.arg_tys;
+        let reply = if m.returns_result {
+            quote!(reply_result)
+        } else {
+            quote!(reply)
+        };
+        quote! {
+            #name => {
+                let (#(#arg_names,)*): (#(#arg_tys,)*) =
+                    match #private::decode_args(Self::SERVICE_ID, #name, payload.as_slice()) {
+                        ::core::result::Result::Ok(v) => v,
+                        ::core::result::Result::Err(e) => return #private::RResult::RErr(e),
+                    };
+                #private::#reply(#trait_ident::#ident(&self.inner, #(#arg_names),*))
+            }
+        }
+    });
+
+    let client_methods = methods.iter().map(|m| {
+        let ident = &m.ident;
+        let name = ident.to_string();
+        let docs = &m.docs;
+        let arg_names = &m.arg_names;
+        let arg_tys = &m.arg_tys;
+        let ok_ty = &m.ok_ty;
+        quote! {
+            #(#docs)*
+            pub fn #ident(
+                &self,
+                #(#arg_names: #arg_tys),*
+            ) -> ::core::result::Result<#ok_ty, #rpc::RpcError> {
+                let payload = #rpc::encode(&(#(#arg_names,)*))?;
+                let reply = self
+                    .transport
+                    .call(Self::SERVICE_ID, #name, payload)
+                    .map_err(#rpc::RpcError::Remote)?;
+                #rpc::decode(&reply)
+            }
+        }
+    });
+
+    let server_doc = format!(
+        "`ServiceV1` dispatching `{}` calls to a [`{}`].",
+        id.value(),
+        trait_ident
+    );
+    let client_doc = format!("Typed client of the `{}` service.", id.value());
+
+    Ok(quote! {
+        #item
+
+        #[doc = #server_doc]
+        #vis struct #server<T> {
+            inner: T,
+        }
+
+        impl<T> #server<T> {
+            pub const SERVICE_ID: &'static str = #id;
+            pub const DESCRIBE: &'static str = #describe;
+
+            #[inline]
+            pub fn new(inner: T) -> Self {
+                Self { inner }
+            }
+
+            #[inline]
+            pub fn inner(&self) -> &T {
+                &self.inner
+            }
+        }
+
+        impl<T: #trait_ident + Send + Sync + 'static> #server<T> {
+            /// Boxes the server for `HostApiV1::register_service_v1`.
+            pub fn into_service(self) -> #api::ServiceV1Dyn<'static> {
+                #api::ServiceV1_TO::from_value(self, #private::TD_Opaque)
+            }
+        }
+
+        impl<T: #trait_ident + Send + Sync> #api::ServiceV1 for #server<T> {
+            fn id(&self) -> #api::CapabilityId {
+                #private::RString::from(Self::SERVICE_ID)
+            }
+
+            fn describe(&self) -> #private::RString {
+                #private::RString::from(Self::DESCRIBE)
+            }
+
+            fn call(
+                &self,
+                method: #api::MethodName,
+                payload: #api::Blob,
+            ) -> #private::RResult<#api::Blob, #private::RString> {
+                match method.as_str() {
+                    #(#arms)*
+                    other => #private::unknown_method(Self::SERVICE_ID, other),
+                }
+            }
+        }
+
+        #[doc = #client_doc]
+        #vis struct #client<C> {
+            transport: C,
+        }
+
+        impl<C: #rpc::RpcTransport> #client<C> {
+            pub const SERVICE_ID: &'static str = #id;
+
+            #[inline]
+            pub fn new(transport: C) -> Self {
+                Self { transport }
+            }
+
+            #(#client_methods)*
+        }
+    })
+}
+
+fn parse_method(f: &TraitItemFn) -> syn::Result<Method> {
+    let sig = &f.sig;
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            sig.generics.span(),
+            "#[service] methods cannot be generic",
+        ));
+    }
+    if sig.asyncness.is_some() {
+        return Err(syn::Error::new(
+            sig.span(),
+            "#[service] methods cannot be async",
+        ));
+    }
+    if sig.ident == "new" {
+        return Err(syn::Error::new(
+            sig.ident.span(),
+            "`new` is the generated client's constructor; rename the method",
+        ));
+    }
+
+    let mut inputs = sig.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Receiver(r)) if r.reference.is_some() && r.mutability.is_none() => {}
+        _ => {
+            return Err(syn::Error::new(
+                sig.span(),
+                "#[service] methods take `&self` (services are called from any thread)",
+            ))
+        }
+    }
+
+    let mut arg_names = Vec::new();
+    let mut arg_tys = Vec::new();
+    for arg in inputs {
+        let FnArg::Typed(pt) = arg else {
+            continue;
+        };
+        let Pat::Ident(pi) = &*pt.pat else {
+            return Err(syn::Error::new(
+                pt.pat.span(),
+                "#[service] arguments must be plain identifiers",
+            ));
+        };
+        if matches!(&*pt.ty, Type::Reference(_)) {
+            return Err(syn::Error::new(
+                pt.ty.span(),
+                "#[service] arguments are decoded into owned values; use an owned type",
+            ));
+        }
+        arg_names.push(pi.ident.clone());
+        arg_tys.push((*pt.ty).clone());
+    }
+
+    let (ok_ty, returns_result) = match &sig.output {
+        ReturnType::Default => (quote!(()), false),
+        ReturnType::Type(_, ty) => match result_ok_type(ty) {
+            Some(ok) => (quote!(#ok), true),
+            None => (quote!(#ty), false),
+        },
+    };
+
+    let docs: Vec<Attribute> = f
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .cloned()
+        .collect();
+
+    Ok(Method {
+        ident: sig.ident.clone(),
+        doc_text: doc_text(&docs),
+        docs,
+        arg_names,
+        arg_tys,
+        ok_ty,
+        returns_result,
+    })
+}
+
+/// `T` of a `Result<T, E>` return type.
+fn result_ok_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(tp) = ty else {
+        return None;
+    };
+    let last = tp.path.segments.last()?;
+    if last.ident != "Result" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(ab) = &last.arguments else {
+        return None;
+    };
+    if ab.args.len() != 2 {
+        return None;
+    }
+    match ab.args.first()? {
+        GenericArgument::Type(t) => Some(t),
+        _ => None,
+    }
+}
+
+fn doc_text(attrs: &[Attribute]) -> String {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter_map(|a| match &a.meta {
+            Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+                Expr::Lit(l) => match &l.lit {
+                    Lit::Str(s) => Some(s.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    lines.join(" ").trim().to_string()
+}
+
+/// Describe JSON: the method list and doc strings follow the service descriptor
+/// conventions, `rpc` tells tools how payloads are encoded.
+fn describe_json(
+    id: &LitStr,
+    version: Option<&LitStr>,
+    trait_attrs: &[Attribute],
+    methods: &[Method],
+) -> String {
+    let mut out = String::from("{\"kind\":\"rpc\",\"rpc\":{\"format\":\"postcard\",\"version\":1}");
+    out.push_str(&format!(",\"id\":{}", json_str(&id.value())));
+    if let Some(v) = version {
+        out.push_str(&format!(",\"version\":{}", json_str(&v.value())));
+    }
+    let doc = doc_text(trait_attrs);
+    if !doc.is_empty() {
+        out.push_str(&format!(",\"doc\":{}", json_str(&doc)));
+    }
+    out.push_str(",\"methods\":[");
+    for (i, m) in methods.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&format!("{{\"name\":{}", json_str(&m.ident.to_string())));
+        if !m.doc_text.is_empty() {
+            out.push_str(&format!(",\"doc\":{}", json_str(&m.doc_text)));
+        }
+        out.push('}');
+    }
+    out.push_str("]}");
+    out
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
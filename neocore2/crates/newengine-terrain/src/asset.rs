@@ -0,0 +1,433 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::heightfield::Heightfield;
+use crate::splat::{normalize_weights, SplatMap, TerrainLayer, MAX_TERRAIN_LAYERS};
+
+use glam::{Vec2, Vec3};
+use newengine_assets::{
+    Asset, AssetBlob, AssetDependency, AssetError, AssetKey, AssetStore, BlobImporterDispatch,
+    ImporterPriority,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+
+pub const TERRAIN_TYPE_ID: &str = "kalitech.asset.terrain";
+pub const TERRAIN_FORMAT: &str = "ne.terrain.v1";
+pub const TERRAIN_META_SCHEMA: &str = "kalitech.terrain.meta.v1";
+
+const WIRE_MAGIC: &[u8; 4] = b"NETR";
+const WIRE_VERSION: u32 = 1;
+
+/// World placement of a terrain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainDesc {
+    /// World position of the heightfield corner at texel (0, 0), height 0.
+    pub origin: Vec3,
+    /// World extent along X and Z.
+    pub size: Vec2,
+    /// World units per heightfield sample unit.
+    pub height_scale: f32,
+}
+
+impl Default for TerrainDesc {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            origin: Vec3::ZERO,
+            size: Vec2::splat(1024.0),
+            height_scale: 256.0,
+        }
+    }
+}
+
+/// CPU-side terrain: heightfield, optional splat map and material layers.
+#[derive(Debug, Clone)]
+pub struct TerrainAsset {
+    pub desc: TerrainDesc,
+    pub heightfield: Heightfield,
+    pub splat: Option<SplatMap>,
+    pub layers: Vec<TerrainLayer>,
+}
+
+impl Asset for TerrainAsset {
+    #[inline]
+    fn type_name() -> &'static str {
+        "TerrainAsset"
+    }
+}
+
+impl TerrainAsset {
+    pub fn new(
+        desc: TerrainDesc,
+        heightfield: Heightfield,
+        splat: Option<SplatMap>,
+        mut layers: Vec<TerrainLayer>,
+    ) -> Self {
+        if layers.len() > MAX_TERRAIN_LAYERS {
+            log::warn!(
+                target: "terrain",
+                "asset.layers truncated count={} max={}",
+                layers.len(),
+                MAX_TERRAIN_LAYERS
+            );
+            layers.truncate(MAX_TERRAIN_LAYERS);
+        }
+        if layers.is_empty() {
+            layers.push(TerrainLayer::new("default", [0.42, 0.48, 0.33, 1.0]));
+        }
+
+        Self {
+            desc,
+            heightfield,
+            splat,
+            layers,
+        }
+    }
+
+    /// World-space AABB.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let (lo, hi) = self.heightfield.range();
+        let d = &self.desc;
+        (
+            d.origin + Vec3::new(0.0, lo * d.height_scale, 0.0),
+            d.origin + Vec3::new(d.size.x, hi * d.height_scale, d.size.y),
+        )
+    }
+
+    /// Normalized heightfield coordinates of a world XZ position (unclamped).
+    #[inline]
+    pub fn world_to_uv(&self, x: f32, z: f32) -> Vec2 {
+        let d = &self.desc;
+        Vec2::new(
+            (x - d.origin.x) / d.size.x.max(f32::EPSILON),
+            (z - d.origin.z) / d.size.y.max(f32::EPSILON),
+        )
+    }
+
+    #[inline]
+    pub fn uv_to_world_xz(&self, uv: Vec2) -> Vec2 {
+        let d = &self.desc;
+        Vec2::new(d.origin.x, d.origin.z) + uv * d.size
+    }
+
+    #[inline]
+    pub fn contains_xz(&self, x: f32, z: f32) -> bool {
+        let uv = self.world_to_uv(x, z);
+        (0.0..=1.0).contains(&uv.x) && (0.0..=1.0).contains(&uv.y)
+    }
+
+    /// World height at `(x, z)`, or `None` outside the terrain footprint.
+    #[inline]
+    pub fn height_at(&self, x: f32, z: f32) -> Option<f32> {
+        if !self.contains_xz(x, z) {
+            return None;
+        }
+        let uv = self.world_to_uv(x, z);
+        Some(self.height_at_uv(uv))
+    }
+
+    #[inline]
+    pub fn height_at_uv(&self, uv: Vec2) -> f32 {
+        self.desc.origin.y + self.heightfield.sample(uv.x, uv.y) * self.desc.height_scale
+    }
+
+    /// World-space surface normal (central differences, one texel apart).
+    pub fn normal_at_uv(&self, uv: Vec2) -> Vec3 {
+        let hf = &self.heightfield;
+        let du = 1.0 / (hf.width() - 1) as f32;
+        let dv = 1.0 / (hf.height() - 1) as f32;
+
+        let hl = self.height_at_uv(uv - Vec2::new(du, 0.0));
+        let hr = self.height_at_uv(uv + Vec2::new(du, 0.0));
+        let hd = self.height_at_uv(uv - Vec2::new(0.0, dv));
+        let hu = self.height_at_uv(uv + Vec2::new(0.0, dv));
+
+        let dx = 2.0 * du * self.desc.size.x;
+        let dz = 2.0 * dv * self.desc.size.y;
+
+        Vec3::new((hl - hr) / dx.max(f32::EPSILON), 1.0, (hd - hu) / dz.max(f32::EPSILON))
+            .normalize_or_zero()
+    }
+
+    /// Layer weights at normalized coordinates (sums to 1).
+    #[inline]
+    pub fn splat_at_uv(&self, uv: Vec2) -> [f32; 4] {
+        match &self.splat {
+            Some(s) => s.sample(uv.x, uv.y),
+            None => normalize_weights([1.0, 0.0, 0.0, 0.0]),
+        }
+    }
+
+    /* ============================
+    Wire format
+    ============================ */
+
+    /// Payload layout (little-endian):
+    /// `"NETR" | version u32 | hw u32 | hh u32 | sw u32 | sh u32 | f32[hw*hh] | rgba8[sw*sh]`.
+    /// `sw == sh == 0` means "no splat map".
+    pub fn encode(&self, heightmap: &str, splat: Option<&str>) -> (String, Vec<u8>) {
+        let hf = &self.heightfield;
+        let (sw, sh) = self
+            .splat
+            .as_ref()
+            .map(|s| (s.width(), s.height()))
+            .unwrap_or((0, 0));
+
+        let mut payload = Vec::with_capacity(
+            24 + hf.samples().len() * 4 + (sw as usize * sh as usize) * 4,
+        );
+        payload.extend_from_slice(WIRE_MAGIC);
+        for v in [WIRE_VERSION, hf.width(), hf.height(), sw, sh] {
+            payload.extend_from_slice(&v.to_le_bytes());
+        }
+        for s in hf.samples() {
+            payload.extend_from_slice(&s.to_le_bytes());
+        }
+        if let Some(s) = &self.splat {
+            for t in s.texels() {
+                payload.extend_from_slice(t);
+            }
+        }
+
+        let (lo, hi) = hf.range();
+        let meta = TerrainMeta {
+            schema: TERRAIN_META_SCHEMA.to_string(),
+            heightmap: heightmap.to_string(),
+            splat: splat.map(|s| s.to_string()),
+            origin: self.desc.origin.to_array(),
+            size: self.desc.size.to_array(),
+            height_scale: self.desc.height_scale,
+            height_min: lo,
+            height_max: hi,
+            layers: self.layers.clone(),
+        };
+
+        let meta_json = serde_json::to_string(&meta).unwrap_or_else(|_| "{}".to_string());
+        (meta_json, payload)
+    }
+
+    /// Decodes a blob produced by `TerrainImporter`.
+    pub fn from_blob(blob: &AssetBlob) -> Result<Self, AssetError> {
+        if blob.type_id.as_ref() != TERRAIN_TYPE_ID || blob.format.as_ref() != TERRAIN_FORMAT {
+            return Err(AssetError::new(format!(
+                "terrain: unexpected blob type='{}' format='{}'",
+                blob.type_id, blob.format
+            )));
+        }
+
+        let meta: TerrainMeta = serde_json::from_str(&blob.meta_json)
+            .map_err(|e| AssetError::new(format!("terrain: meta json: {e}")))?;
+
+        let bytes = blob.payload.as_slice();
+        if bytes.len() < 24 || &bytes[0..4] != WIRE_MAGIC {
+            return Err(AssetError::new("terrain: bad payload header"));
+        }
+
+        let read_u32 = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+
+        let version = read_u32(4);
+        if version != WIRE_VERSION {
+            return Err(AssetError::new(format!(
+                "terrain: unsupported payload version {version}"
+            )));
+        }
+
+        let (hw, hh, sw, sh) = (read_u32(8), read_u32(12), read_u32(16), read_u32(20));
+        let h_count = hw as usize * hh as usize;
+        let s_count = sw as usize * sh as usize;
+
+        let h_end = 24 + h_count * 4;
+        let s_end = h_end + s_count * 4;
+        if bytes.len() < s_end {
+            return Err(AssetError::new("terrain: truncated payload"));
+        }
+
+        let samples: Vec<f32> = bytes[24..h_end]
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+        let heightfield = Heightfield::new(hw, hh, samples)?;
+
+        let splat = if s_count > 0 {
+            let weights = bytes[h_end..s_end]
+                .chunks_exact(4)
+                .map(|c| [c[0], c[1], c[2], c[3]])
+                .collect();
+            Some(SplatMap::new(sw, sh, weights)?)
+        } else {
+            None
+        };
+
+        let desc = TerrainDesc {
+            origin: Vec3::from_array(meta.origin),
+            size: Vec2::from_array(meta.size),
+            height_scale: meta.height_scale,
+        };
+
+        Ok(Self::new(desc, heightfield, splat, meta.layers))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TerrainMeta {
+    schema: String,
+    heightmap: String,
+    #[serde(default)]
+    splat: Option<String>,
+    origin: [f32; 3],
+    size: [f32; 2],
+    height_scale: f32,
+    #[serde(default)]
+    height_min: f32,
+    #[serde(default)]
+    height_max: f32,
+    #[serde(default)]
+    layers: Vec<TerrainLayer>,
+}
+
+/// `.terrain` descriptor (JSON). Paths are relative to the descriptor's directory.
+///
+/// ```json
+/// {
+///   "heightmap": "island_height.png",
+///   "splat": "island_splat.png",
+///   "size": [2048, 2048],
+///   "height_scale": 320,
+///   "layers": [{ "name": "grass", "color": [0.3, 0.45, 0.2, 1] }]
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+struct TerrainDescriptor {
+    heightmap: String,
+    #[serde(default)]
+    splat: Option<String>,
+    #[serde(default)]
+    origin: Option<[f32; 3]>,
+    #[serde(default)]
+    size: Option<[f32; 2]>,
+    #[serde(default)]
+    height_scale: Option<f32>,
+    #[serde(default)]
+    layers: Vec<TerrainLayer>,
+}
+
+/// Importer for `.terrain` descriptors.
+///
+/// Referenced images are read through the store's sources and reported as blob
+/// dependencies, so editing a heightmap re-imports the terrain.
+pub struct TerrainImporter {
+    store: Weak<AssetStore>,
+}
+
+impl TerrainImporter {
+    #[inline]
+    pub fn new(store: &Arc<AssetStore>) -> Self {
+        Self {
+            store: Arc::downgrade(store),
+        }
+    }
+
+    #[inline]
+    fn resolve(descriptor: &Path, rel: &str) -> PathBuf {
+        match descriptor.parent() {
+            Some(dir) => dir.join(rel),
+            None => PathBuf::from(rel),
+        }
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>, AssetError> {
+        let store = self
+            .store
+            .upgrade()
+            .ok_or_else(|| AssetError::new("terrain: asset store is gone"))?;
+        store.read_source(path)
+    }
+}
+
+impl BlobImporterDispatch for TerrainImporter {
+    fn import_blob(&self, bytes: &[u8], key: &AssetKey) -> Result<AssetBlob, AssetError> {
+        let d: TerrainDescriptor = serde_json::from_slice(bytes)
+            .map_err(|e| AssetError::new(format!("terrain: descriptor json: {e}")))?;
+
+        let hm_path = Self::resolve(&key.logical_path, &d.heightmap);
+        let heightfield = Heightfield::decode(&self.read(&hm_path)?)?;
+
+        let splat_path = d.splat.as_deref().map(|s| Self::resolve(&key.logical_path, s));
+        let splat = match &splat_path {
+            Some(p) => Some(SplatMap::decode(&self.read(p)?)?),
+            None => None,
+        };
+
+        let defaults = TerrainDesc::default();
+        let desc = TerrainDesc {
+            origin: d.origin.map(Vec3::from_array).unwrap_or(defaults.origin),
+            size: d.size.map(Vec2::from_array).unwrap_or(defaults.size),
+            height_scale: d.height_scale.unwrap_or(defaults.height_scale),
+        };
+
+        let asset = TerrainAsset::new(desc, heightfield, splat, d.layers);
+
+        let hm_str = hm_path.to_string_lossy().into_owned();
+        let splat_str = splat_path.as_ref().map(|p| p.to_string_lossy().into_owned());
+        let (meta_json, payload) = asset.encode(&hm_str, splat_str.as_deref());
+
+        let mut dependencies = vec![AssetDependency {
+            logical_path: hm_path,
+            settings_hash: 0,
+            type_hint: Arc::from("kalitech.asset.texture"),
+            usage: Arc::from("terrain.heightmap"),
+        }];
+        if let Some(p) = splat_path {
+            dependencies.push(AssetDependency {
+                logical_path: p,
+                settings_hash: 0,
+                type_hint: Arc::from("kalitech.asset.texture"),
+                usage: Arc::from("terrain.splat"),
+            });
+        }
+
+        log::info!(
+            target: "terrain",
+            "import.done path='{}' heightfield={}x{} splat={} layers={}",
+            key.logical_path.display(),
+            asset.heightfield.width(),
+            asset.heightfield.height(),
+            asset.splat.is_some(),
+            asset.layers.len()
+        );
+
+        Ok(AssetBlob {
+            type_id: Arc::from(TERRAIN_TYPE_ID),
+            format: Arc::from(TERRAIN_FORMAT),
+            payload,
+            meta_json: Arc::from(meta_json),
+            dependencies,
+        })
+    }
+
+    #[inline]
+    fn output_type_id(&self) -> Arc<str> {
+        Arc::from(TERRAIN_TYPE_ID)
+    }
+
+    #[inline]
+    fn extensions(&self) -> Vec<String> {
+        vec!["terrain".to_string()]
+    }
+
+    #[inline]
+    fn priority(&self) -> ImporterPriority {
+        ImporterPriority::new(100)
+    }
+
+    #[inline]
+    fn stable_id(&self) -> Arc<str> {
+        Arc::from("terrain_importer@newengine-terrain")
+    }
+
+    #[inline]
+    fn version(&self) -> Arc<str> {
+        Arc::from(TERRAIN_FORMAT)
+    }
+}
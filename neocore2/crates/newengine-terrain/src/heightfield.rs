@@ -0,0 +1,228 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use image::ColorType;
+use newengine_assets::AssetError;
+
+/// Row-major grid of height samples (`z * width + x`).
+///
+/// Samples are unitless: 16-bit PNG heightmaps are normalized to `[0..1]`, EXR heightmaps
+/// keep their stored values. World height is `sample * TerrainDesc::height_scale`.
+#[derive(Debug, Clone)]
+pub struct Heightfield {
+    width: u32,
+    height: u32,
+    samples: Vec<f32>,
+    bounds: HeightBounds,
+}
+
+impl Heightfield {
+    pub fn new(width: u32, height: u32, samples: Vec<f32>) -> Result<Self, AssetError> {
+        if width < 2 || height < 2 {
+            return Err(AssetError::new(format!(
+                "terrain: heightfield must be at least 2x2 (got {width}x{height})"
+            )));
+        }
+        let expected = width as usize * height as usize;
+        if samples.len() != expected {
+            return Err(AssetError::new(format!(
+                "terrain: heightfield sample count mismatch (expected {expected}, got {})",
+                samples.len()
+            )));
+        }
+
+        let bounds = HeightBounds::build(width, height, &samples);
+        Ok(Self {
+            width,
+            height,
+            samples,
+            bounds,
+        })
+    }
+
+    #[inline]
+    pub fn flat(width: u32, height: u32) -> Result<Self, AssetError> {
+        Self::new(width, height, vec![0.0; width as usize * height as usize])
+    }
+
+    /// Decodes a heightmap image (16-bit grayscale PNG or single-channel EXR).
+    ///
+    /// 8-bit images are accepted but produce visible terracing; a warning is logged.
+    pub fn decode(bytes: &[u8]) -> Result<Self, AssetError> {
+        let img = image::load_from_memory(bytes)
+            .map_err(|e| AssetError::new(format!("terrain: heightmap decode failed: {e}")))?;
+
+        match img.color() {
+            ColorType::L8 | ColorType::La8 | ColorType::Rgb8 | ColorType::Rgba8 => {
+                log::warn!(
+                    target: "terrain",
+                    "heightmap.precision 8-bit source ({}x{}); use 16-bit PNG or EXR",
+                    img.width(),
+                    img.height()
+                );
+            }
+            _ => {}
+        }
+
+        let (w, h) = (img.width(), img.height());
+        let rgb = img.to_rgb32f();
+        let samples: Vec<f32> = rgb.as_raw().chunks_exact(3).map(|c| c[0]).collect();
+
+        Self::new(w, h, samples)
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Global `(min, max)` sample.
+    #[inline]
+    pub fn range(&self) -> (f32, f32) {
+        self.bounds.global()
+    }
+
+    /// Sample at integer texel coordinates (clamped to the edge).
+    #[inline]
+    pub fn texel(&self, x: i64, z: i64) -> f32 {
+        let x = x.clamp(0, self.width as i64 - 1) as usize;
+        let z = z.clamp(0, self.height as i64 - 1) as usize;
+        self.samples[z * self.width as usize + x]
+    }
+
+    /// Bilinear sample at normalized coordinates (`u`, `v` in `[0..1]`, clamped).
+    pub fn sample(&self, u: f32, v: f32) -> f32 {
+        let fx = u.clamp(0.0, 1.0) * (self.width - 1) as f32;
+        let fz = v.clamp(0.0, 1.0) * (self.height - 1) as f32;
+
+        let x0 = fx.floor() as i64;
+        let z0 = fz.floor() as i64;
+        let tx = fx - x0 as f32;
+        let tz = fz - z0 as f32;
+
+        let h00 = self.texel(x0, z0);
+        let h10 = self.texel(x0 + 1, z0);
+        let h01 = self.texel(x0, z0 + 1);
+        let h11 = self.texel(x0 + 1, z0 + 1);
+
+        let a = h00 + (h10 - h00) * tx;
+        let b = h01 + (h11 - h01) * tx;
+        a + (b - a) * tz
+    }
+
+    /// Conservative `(min, max)` over a normalized rectangle.
+    #[inline]
+    pub fn range_in(&self, u0: f32, v0: f32, u1: f32, v1: f32) -> (f32, f32) {
+        let sx = (self.width - 1) as f32;
+        let sz = (self.height - 1) as f32;
+        self.bounds.range(
+            (u0.clamp(0.0, 1.0) * sx).floor() as u32,
+            (v0.clamp(0.0, 1.0) * sz).floor() as u32,
+            (u1.clamp(0.0, 1.0) * sx).ceil() as u32,
+            (v1.clamp(0.0, 1.0) * sz).ceil() as u32,
+        )
+    }
+}
+
+/// Min/max pyramid used for conservative patch bounds (culling, raycast early-out).
+///
+/// Level 0 stores one `(min, max)` per texel; each further level reduces 2x2 cells.
+#[derive(Debug, Clone)]
+struct HeightBounds {
+    levels: Vec<BoundsLevel>,
+}
+
+#[derive(Debug, Clone)]
+struct BoundsLevel {
+    width: u32,
+    height: u32,
+    cells: Vec<(f32, f32)>,
+}
+
+impl HeightBounds {
+    /// Upper bound of cells visited per axis by `range`.
+    const MAX_SPAN: u32 = 8;
+
+    fn build(width: u32, height: u32, samples: &[f32]) -> Self {
+        let mut levels = vec![BoundsLevel {
+            width,
+            height,
+            cells: samples.iter().map(|&s| (s, s)).collect(),
+        }];
+
+        loop {
+            let prev = levels.last().expect("level 0 exists");
+            if prev.width == 1 && prev.height == 1 {
+                break;
+            }
+
+            let w = prev.width.div_ceil(2);
+            let h = prev.height.div_ceil(2);
+            let mut cells = Vec::with_capacity(w as usize * h as usize);
+
+            for z in 0..h {
+                for x in 0..w {
+                    let mut lo = f32::INFINITY;
+                    let mut hi = f32::NEG_INFINITY;
+                    for (dx, dz) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                        let px = (x * 2 + dx).min(prev.width - 1);
+                        let pz = (z * 2 + dz).min(prev.height - 1);
+                        let (a, b) = prev.cells[(pz * prev.width + px) as usize];
+                        lo = lo.min(a);
+                        hi = hi.max(b);
+                    }
+                    cells.push((lo, hi));
+                }
+            }
+
+            levels.push(BoundsLevel {
+                width: w,
+                height: h,
+                cells,
+            });
+        }
+
+        Self { levels }
+    }
+
+    #[inline]
+    fn global(&self) -> (f32, f32) {
+        self.levels.last().map(|l| l.cells[0]).unwrap_or((0.0, 0.0))
+    }
+
+    /// Conservative range over texels `[x0..=x1] x [z0..=z1]`.
+    fn range(&self, x0: u32, z0: u32, x1: u32, z1: u32) -> (f32, f32) {
+        let span = (x1.saturating_sub(x0)).max(z1.saturating_sub(z0)).max(1);
+
+        let mut level = 0usize;
+        while level + 1 < self.levels.len() && (span >> level) > Self::MAX_SPAN {
+            level += 1;
+        }
+
+        let l = &self.levels[level];
+        let cx0 = (x0 >> level).min(l.width - 1);
+        let cz0 = (z0 >> level).min(l.height - 1);
+        let cx1 = (x1 >> level).min(l.width - 1);
+        let cz1 = (z1 >> level).min(l.height - 1);
+
+        let mut lo = f32::INFINITY;
+        let mut hi = f32::NEG_INFINITY;
+        for z in cz0..=cz1 {
+            for x in cx0..=cx1 {
+                let (a, b) = l.cells[(z * l.width + x) as usize];
+                lo = lo.min(a);
+                hi = hi.max(b);
+            }
+        }
+        (lo, hi)
+    }
+}
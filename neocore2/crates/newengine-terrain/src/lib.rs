@@ -0,0 +1,19 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+pub mod asset;
+pub mod heightfield;
+pub mod lod;
+pub mod module;
+pub mod raycast;
+pub mod renderer;
+pub mod shaders;
+pub mod splat;
+
+pub use asset::*;
+pub use heightfield::*;
+pub use lod::*;
+pub use module::*;
+pub use raycast::*;
+pub use renderer::*;
+pub use shaders::*;
+pub use splat::*;
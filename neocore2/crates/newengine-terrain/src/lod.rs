@@ -0,0 +1,255 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::asset::TerrainAsset;
+
+use glam::{Vec2, Vec3};
+use newengine_camera::Frustum;
+
+/// Quadtree LOD parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LodConfig {
+    /// Quads per patch edge (every patch has the same vertex count).
+    pub patch_resolution: u32,
+    /// Deepest quadtree level (0 = whole terrain in one patch).
+    pub max_depth: u8,
+    /// A node splits while `distance < node_size * split_factor`.
+    pub split_factor: f32,
+    /// Skirt depth as a fraction of the patch edge length (hides LOD cracks).
+    pub skirt_ratio: f32,
+}
+
+impl Default for LodConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            patch_resolution: 32,
+            max_depth: 6,
+            split_factor: 2.0,
+            skirt_ratio: 0.05,
+        }
+    }
+}
+
+impl LodConfig {
+    #[inline]
+    pub fn with_patch_resolution(mut self, quads: u32) -> Self {
+        self.patch_resolution = quads.clamp(2, 254);
+        self
+    }
+
+    #[inline]
+    pub fn with_max_depth(mut self, depth: u8) -> Self {
+        self.max_depth = depth.min(16);
+        self
+    }
+
+    #[inline]
+    pub fn with_split_factor(mut self, factor: f32) -> Self {
+        self.split_factor = factor.max(0.5);
+        self
+    }
+
+    /// Vertices per patch (grid + skirt ring).
+    #[inline]
+    pub fn patch_vertex_count(&self) -> u32 {
+        let n = self.patch_resolution + 1;
+        n * n + 4 * n
+    }
+}
+
+/// Quadtree node address: `depth` and cell coordinates within the `2^depth` grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PatchKey {
+    pub depth: u8,
+    pub x: u32,
+    pub z: u32,
+}
+
+impl PatchKey {
+    pub const ROOT: PatchKey = PatchKey { depth: 0, x: 0, z: 0 };
+
+    #[inline]
+    pub fn children(&self) -> [PatchKey; 4] {
+        let d = self.depth + 1;
+        let (x, z) = (self.x * 2, self.z * 2);
+        [
+            PatchKey { depth: d, x, z },
+            PatchKey { depth: d, x: x + 1, z },
+            PatchKey { depth: d, x, z: z + 1 },
+            PatchKey { depth: d, x: x + 1, z: z + 1 },
+        ]
+    }
+
+    /// Normalized rectangle `(min_uv, max_uv)` covered by the node.
+    #[inline]
+    pub fn uv_rect(&self) -> (Vec2, Vec2) {
+        let cells = (1u32 << self.depth) as f32;
+        let min = Vec2::new(self.x as f32, self.z as f32) / cells;
+        (min, min + Vec2::splat(1.0 / cells))
+    }
+}
+
+/// Conservative world AABB of a node.
+pub fn patch_bounds(asset: &TerrainAsset, key: PatchKey) -> (Vec3, Vec3) {
+    let (uv0, uv1) = key.uv_rect();
+    let (lo, hi) = asset.heightfield.range_in(uv0.x, uv0.y, uv1.x, uv1.y);
+
+    let d = &asset.desc;
+    let xz0 = asset.uv_to_world_xz(uv0);
+    let xz1 = asset.uv_to_world_xz(uv1);
+    (
+        Vec3::new(xz0.x, d.origin.y + lo * d.height_scale, xz0.y),
+        Vec3::new(xz1.x, d.origin.y + hi * d.height_scale, xz1.y),
+    )
+}
+
+/// Selects the leaf patches to draw for a viewer at `eye`.
+///
+/// Nodes outside `frustum` (when given) are skipped entirely. Output is in depth-first order.
+pub fn select_patches(
+    asset: &TerrainAsset,
+    cfg: &LodConfig,
+    eye: Vec3,
+    frustum: Option<&Frustum>,
+    out: &mut Vec<PatchKey>,
+) {
+    out.clear();
+
+    let mut stack = vec![PatchKey::ROOT];
+    while let Some(key) = stack.pop() {
+        let (min, max) = patch_bounds(asset, key);
+
+        if let Some(f) = frustum {
+            if !f.contains_aabb(min, max) {
+                continue;
+            }
+        }
+
+        let size = (max.x - min.x).max(max.z - min.z);
+        let dist = distance_to_aabb(eye, min, max);
+
+        if key.depth < cfg.max_depth && dist < size * cfg.split_factor {
+            // Push in reverse so children are emitted in natural order.
+            for c in key.children().into_iter().rev() {
+                stack.push(c);
+            }
+        } else {
+            out.push(key);
+        }
+    }
+}
+
+#[inline]
+fn distance_to_aabb(p: Vec3, min: Vec3, max: Vec3) -> f32 {
+    let c = p.clamp(min, max);
+    (p - c).length()
+}
+
+/// Interleaved terrain vertex (40 bytes).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub splat: [f32; 4],
+}
+
+impl TerrainVertex {
+    pub const STRIDE: u32 = std::mem::size_of::<TerrainVertex>() as u32;
+
+    #[inline]
+    pub fn write_bytes(&self, out: &mut Vec<u8>) {
+        for f in self.position.iter().chain(&self.normal).chain(&self.splat) {
+            out.extend_from_slice(&f.to_ne_bytes());
+        }
+    }
+}
+
+/// Builds the world-space vertices of a patch: `(n+1)^2` grid vertices followed by a
+/// skirt ring of `4 * (n+1)` vertices (top, bottom, left, right edges).
+pub fn build_patch_vertices(asset: &TerrainAsset, cfg: &LodConfig, key: PatchKey) -> Vec<TerrainVertex> {
+    let n = cfg.patch_resolution;
+    let (uv0, uv1) = key.uv_rect();
+    let step = (uv1 - uv0) / n as f32;
+
+    let vertex = |ix: u32, iz: u32| {
+        let uv = uv0 + Vec2::new(ix as f32, iz as f32) * step;
+        let xz = asset.uv_to_world_xz(uv);
+        TerrainVertex {
+            position: [xz.x, asset.height_at_uv(uv), xz.y],
+            normal: asset.normal_at_uv(uv).to_array(),
+            splat: asset.splat_at_uv(uv),
+        }
+    };
+
+    let mut out = Vec::with_capacity(cfg.patch_vertex_count() as usize);
+    for iz in 0..=n {
+        for ix in 0..=n {
+            out.push(vertex(ix, iz));
+        }
+    }
+
+    let edge = (uv1.x - uv0.x) * asset.desc.size.x.max(asset.desc.size.y);
+    let skirt_depth = edge * cfg.skirt_ratio;
+    let skirt = |v: TerrainVertex| TerrainVertex {
+        position: [v.position[0], v.position[1] - skirt_depth, v.position[2]],
+        ..v
+    };
+
+    let grid = |ix: u32, iz: u32| out[(iz * (n + 1) + ix) as usize];
+    let mut ring = Vec::with_capacity(4 * (n as usize + 1));
+    for i in 0..=n {
+        ring.push(skirt(grid(i, 0)));
+    }
+    for i in 0..=n {
+        ring.push(skirt(grid(i, n)));
+    }
+    for i in 0..=n {
+        ring.push(skirt(grid(0, i)));
+    }
+    for i in 0..=n {
+        ring.push(skirt(grid(n, i)));
+    }
+    out.extend(ring);
+
+    out
+}
+
+/// Index list shared by every patch of the same resolution (grid + skirts).
+pub fn build_patch_indices(cfg: &LodConfig) -> Vec<u32> {
+    let n = cfg.patch_resolution;
+    let row = n + 1;
+    let g = |x: u32, z: u32| z * row + x;
+
+    let mut out = Vec::with_capacity((6 * n * n + 4 * 6 * n) as usize);
+
+    for z in 0..n {
+        for x in 0..n {
+            let (a, b, c, d) = (g(x, z), g(x + 1, z), g(x, z + 1), g(x + 1, z + 1));
+            out.extend_from_slice(&[a, c, b, b, c, d]);
+        }
+    }
+
+    let base = row * row;
+    let top = base;
+    let bottom = base + row;
+    let left = base + 2 * row;
+    let right = base + 3 * row;
+
+    for i in 0..n {
+        // Winding flips per edge so every skirt faces outward.
+        let (t0, t1, s0, s1) = (g(i, 0), g(i + 1, 0), top + i, top + i + 1);
+        out.extend_from_slice(&[t0, t1, s0, t1, s1, s0]);
+
+        let (t0, t1, s0, s1) = (g(i, n), g(i + 1, n), bottom + i, bottom + i + 1);
+        out.extend_from_slice(&[t0, s0, t1, t1, s0, s1]);
+
+        let (t0, t1, s0, s1) = (g(0, i), g(0, i + 1), left + i, left + i + 1);
+        out.extend_from_slice(&[t0, s0, t1, t1, s0, s1]);
+
+        let (t0, t1, s0, s1) = (g(n, i), g(n, i + 1), right + i, right + i + 1);
+        out.extend_from_slice(&[t0, t1, s0, t1, s1, s0]);
+    }
+
+    out
+}
@@ -0,0 +1,364 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::asset::{TerrainAsset, TerrainImporter};
+use crate::lod::LodConfig;
+use crate::raycast::TerrainHit;
+use crate::renderer::{TerrainDrawStats, TerrainRenderer};
+use crate::shaders::TerrainShaders;
+
+use glam::Vec3;
+use newengine_assets::{AssetBlob, AssetId, AssetState, AssetStore};
+use newengine_camera::CameraMatrices;
+use newengine_core::assets::AssetManager;
+use newengine_core::render::{RenderApi, TextureFormat};
+use newengine_core::{ApiProvide, ApiVersion, EngineError, EngineResult, Module, ModuleCtx};
+use parking_lot::{Mutex, MutexGuard};
+use std::sync::Arc;
+
+pub const TERRAIN_API_ID: &str = "terrain.api";
+pub const TERRAIN_API_VERSION: ApiVersion = ApiVersion::new(0, 1, 0);
+pub const TERRAIN_API_PROVIDE: ApiProvide = ApiProvide::new(TERRAIN_API_ID, TERRAIN_API_VERSION);
+
+/// Terrain module configuration.
+#[derive(Debug, Clone)]
+pub struct TerrainModuleConfig {
+    pub lod: LodConfig,
+    /// Without shaders terrains are imported and queryable but not drawn.
+    pub shaders: Option<TerrainShaders>,
+    pub color_format: TextureFormat,
+    pub depth_format: Option<TextureFormat>,
+    /// `.terrain` descriptors loaded on init.
+    pub preload: Vec<String>,
+}
+
+impl Default for TerrainModuleConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            lod: LodConfig::default(),
+            shaders: None,
+            color_format: TextureFormat::Bgra8Unorm,
+            depth_format: Some(TextureFormat::Depth32Float),
+            preload: Vec::new(),
+        }
+    }
+}
+
+impl TerrainModuleConfig {
+    #[inline]
+    pub fn with_lod(mut self, lod: LodConfig) -> Self {
+        self.lod = lod;
+        self
+    }
+
+    #[inline]
+    pub fn with_shaders(mut self, shaders: TerrainShaders) -> Self {
+        self.shaders = Some(shaders);
+        self
+    }
+
+    #[inline]
+    pub fn with_formats(mut self, color: TextureFormat, depth: Option<TextureFormat>) -> Self {
+        self.color_format = color;
+        self.depth_format = depth;
+        self
+    }
+
+    #[inline]
+    pub fn with_preload(mut self, logical_path: impl Into<String>) -> Self {
+        self.preload.push(logical_path.into());
+        self
+    }
+}
+
+struct TerrainSlot {
+    id: AssetId,
+    path: String,
+    blob: Option<Arc<AssetBlob>>,
+    asset: Option<Arc<TerrainAsset>>,
+    renderer: Option<TerrainRenderer>,
+    gpu_stale: bool,
+    failure_reported: bool,
+}
+
+/// Loaded terrains plus their GPU state. Shared through `TerrainApiRef`.
+pub struct TerrainWorld {
+    store: Arc<AssetStore>,
+    config: TerrainModuleConfig,
+    slots: Vec<TerrainSlot>,
+    /// Renderers of unloaded terrains, destroyed on the next `draw` (needs the render API).
+    retired: Vec<TerrainRenderer>,
+}
+
+impl TerrainWorld {
+    fn new(store: Arc<AssetStore>, config: TerrainModuleConfig) -> Self {
+        Self {
+            store,
+            config,
+            slots: Vec::new(),
+            retired: Vec::new(),
+        }
+    }
+
+    /// Enqueues a `.terrain` descriptor. Loading the same path twice returns the same id.
+    pub fn load(&mut self, logical_path: &str) -> EngineResult<AssetId> {
+        let id = self
+            .store
+            .load_path(logical_path)
+            .map_err(|e| EngineError::other(format!("terrain: load '{logical_path}' failed: {e}")))?;
+
+        if !self.slots.iter().any(|s| s.id == id) {
+            self.slots.push(TerrainSlot {
+                id,
+                path: logical_path.to_string(),
+                blob: None,
+                asset: None,
+                renderer: None,
+                gpu_stale: false,
+                failure_reported: false,
+            });
+        }
+        Ok(id)
+    }
+
+    pub fn unload(&mut self, id: AssetId) -> bool {
+        let Some(i) = self.slots.iter().position(|s| s.id == id) else {
+            return false;
+        };
+        let slot = self.slots.remove(i);
+        if let Some(r) = slot.renderer {
+            self.retired.push(r);
+        }
+        log::info!(target: "terrain", "terrain.unload path='{}'", slot.path);
+        true
+    }
+
+    #[inline]
+    pub fn ids(&self) -> Vec<AssetId> {
+        self.slots.iter().map(|s| s.id).collect()
+    }
+
+    #[inline]
+    pub fn terrain(&self, id: AssetId) -> Option<Arc<TerrainAsset>> {
+        self.slots
+            .iter()
+            .find(|s| s.id == id)
+            .and_then(|s| s.asset.clone())
+    }
+
+    /// Nearest hit over all ready terrains.
+    pub fn raycast(&self, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<(AssetId, TerrainHit)> {
+        self.ready()
+            .filter_map(|(id, t)| t.raycast(origin, dir, max_distance).map(|h| (id, h)))
+            .min_by(|a, b| a.1.distance.total_cmp(&b.1.distance))
+    }
+
+    /// Highest surface under `(x, z)` over all ready terrains.
+    pub fn height_at(&self, x: f32, z: f32) -> Option<f32> {
+        self.ready()
+            .filter_map(|(_, t)| t.height_at(x, z))
+            .max_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Draw stats of a terrain's last frame.
+    pub fn draw_stats(&self, id: AssetId) -> Option<TerrainDrawStats> {
+        self.slots
+            .iter()
+            .find(|s| s.id == id)
+            .and_then(|s| s.renderer.as_ref())
+            .map(|r| r.stats())
+    }
+
+    /// Records draws for all ready terrains. Call inside the host's frame
+    /// (between `begin_frame` and `end_frame`), after viewport/scissor are set.
+    pub fn draw(&mut self, r: &mut dyn RenderApi, camera: &CameraMatrices) -> EngineResult<()> {
+        for old in self.retired.drain(..) {
+            old.destroy(r);
+        }
+
+        let Some(shaders) = self.config.shaders.as_ref() else {
+            return Ok(());
+        };
+
+        for slot in self.slots.iter_mut() {
+            let Some(asset) = slot.asset.clone() else {
+                continue;
+            };
+
+            if slot.renderer.is_none() {
+                slot.renderer = Some(TerrainRenderer::new(
+                    r,
+                    shaders,
+                    self.config.lod,
+                    self.config.color_format,
+                    self.config.depth_format,
+                )?);
+                slot.gpu_stale = false;
+            }
+
+            let renderer = slot.renderer.as_mut().expect("renderer created above");
+            if slot.gpu_stale {
+                renderer.invalidate(r);
+                slot.gpu_stale = false;
+            }
+            renderer.draw(r, &asset, camera)?;
+        }
+
+        Ok(())
+    }
+
+    /// Releases every GPU resource (module shutdown / device loss).
+    pub fn release_gpu(&mut self, r: &mut dyn RenderApi) {
+        for old in self.retired.drain(..) {
+            old.destroy(r);
+        }
+        for slot in self.slots.iter_mut() {
+            if let Some(renderer) = slot.renderer.take() {
+                renderer.destroy(r);
+            }
+        }
+    }
+
+    #[inline]
+    fn ready(&self) -> impl Iterator<Item = (AssetId, &TerrainAsset)> {
+        self.slots
+            .iter()
+            .filter_map(|s| s.asset.as_deref().map(|a| (s.id, a)))
+    }
+
+    /// Picks up freshly imported (or re-imported) terrain blobs.
+    fn poll(&mut self) {
+        for slot in self.slots.iter_mut() {
+            match self.store.state(slot.id) {
+                AssetState::Ready => slot.failure_reported = false,
+                AssetState::Failed(e) => {
+                    // The last good terrain stays active until a re-import succeeds.
+                    if !slot.failure_reported {
+                        slot.failure_reported = true;
+                        log::warn!(target: "terrain", "terrain.failed path='{}' err='{}'", slot.path, e);
+                    }
+                    continue;
+                }
+                _ => continue,
+            }
+
+            let Some(blob) = self.store.get_blob(slot.id) else {
+                continue;
+            };
+            if slot.blob.as_ref().is_some_and(|b| Arc::ptr_eq(b, &blob)) {
+                continue;
+            }
+
+            match TerrainAsset::from_blob(&blob) {
+                Ok(asset) => {
+                    let reload = slot.asset.is_some();
+                    log::info!(
+                        target: "terrain",
+                        "terrain.ready path='{}' heightfield={}x{} reload={}",
+                        slot.path,
+                        asset.heightfield.width(),
+                        asset.heightfield.height(),
+                        reload
+                    );
+                    slot.asset = Some(Arc::new(asset));
+                    slot.gpu_stale = reload;
+                }
+                Err(e) => {
+                    log::warn!(target: "terrain", "terrain.decode failed path='{}' err='{}'", slot.path, e);
+                }
+            }
+            slot.blob = Some(blob);
+        }
+    }
+}
+
+/// Shared handle to the terrain world (registered as `TERRAIN_API_ID`).
+#[derive(Clone)]
+pub struct TerrainApiRef(Arc<Mutex<TerrainWorld>>);
+
+impl TerrainApiRef {
+    #[inline]
+    pub fn lock(&self) -> MutexGuard<'_, TerrainWorld> {
+        self.0.lock()
+    }
+}
+
+/// Imports `.terrain` descriptors, keeps terrains in sync with the asset store and
+/// exposes `TerrainApiRef` for drawing and raycast queries.
+///
+/// Drawing is host-driven: the app's render controller calls `TerrainWorld::draw`
+/// inside its frame, like every other render-API client.
+pub struct TerrainModule {
+    config: Option<TerrainModuleConfig>,
+    api: Option<TerrainApiRef>,
+}
+
+impl Default for TerrainModule {
+    fn default() -> Self {
+        Self::new(TerrainModuleConfig::default())
+    }
+}
+
+impl TerrainModule {
+    #[inline]
+    pub fn new(config: TerrainModuleConfig) -> Self {
+        Self {
+            config: Some(config),
+            api: None,
+        }
+    }
+}
+
+impl<E: Send + 'static> Module<E> for TerrainModule {
+    fn id(&self) -> &'static str {
+        "terrain"
+    }
+
+    fn provides(&self) -> &'static [ApiProvide] {
+        &[TERRAIN_API_PROVIDE]
+    }
+
+    fn init(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        let store = ctx
+            .resources()
+            .get::<AssetManager>()
+            .map(|am| am.store().clone())
+            .ok_or_else(|| EngineError::other("terrain: AssetManager resource is missing"))?;
+
+        store.add_importer(Arc::new(TerrainImporter::new(&store)));
+
+        let config = self.config.take().unwrap_or_default();
+        let preload = config.preload.clone();
+
+        let api = TerrainApiRef(Arc::new(Mutex::new(TerrainWorld::new(store, config))));
+        {
+            let mut world = api.lock();
+            for path in preload.iter() {
+                if let Err(e) = world.load(path) {
+                    log::warn!(target: "terrain", "terrain.preload failed path='{path}' err='{e}'");
+                }
+            }
+        }
+
+        ctx.resources_mut().register_api(TERRAIN_API_ID, api.clone())?;
+        self.api = Some(api);
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if let Some(api) = self.api.as_ref() {
+            api.lock().poll();
+        }
+        Ok(())
+    }
+
+    fn shutdown(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        if let Some(api) = self.api.take() {
+            if let Ok(render) = newengine_core::render::require_render_api(ctx) {
+                api.lock().release_gpu(&mut **render.lock());
+            }
+        }
+        let _ = ctx.resources_mut().unregister_api::<TerrainApiRef>(TERRAIN_API_ID);
+        Ok(())
+    }
+}
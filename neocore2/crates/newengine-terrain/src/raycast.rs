@@ -0,0 +1,123 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::asset::TerrainAsset;
+
+use glam::{Vec2, Vec3};
+
+/// Result of a terrain raycast.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainHit {
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub distance: f32,
+    /// Normalized heightfield coordinates of the hit (for splat/height painting).
+    pub uv: Vec2,
+}
+
+impl TerrainAsset {
+    /// Casts a ray against the heightfield surface.
+    ///
+    /// Marches at half-texel steps inside the terrain AABB, then refines the crossing by
+    /// bisection. Rays starting below the surface report no hit.
+    pub fn raycast(&self, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<TerrainHit> {
+        let dir = dir.normalize_or_zero();
+        if dir == Vec3::ZERO || max_distance <= 0.0 {
+            return None;
+        }
+
+        let (min, max) = self.bounds();
+        let (t_enter, t_exit) = ray_aabb(origin, dir, min, max)?;
+        let t_start = t_enter.max(0.0);
+        let t_end = t_exit.min(max_distance);
+        if t_start > t_end {
+            return None;
+        }
+
+        let above = |t: f32| -> Option<bool> {
+            let p = origin + dir * t;
+            self.height_at(p.x, p.z).map(|h| p.y >= h)
+        };
+
+        let texel = (self.desc.size.x / (self.heightfield.width() - 1) as f32)
+            .min(self.desc.size.y / (self.heightfield.height() - 1) as f32);
+        let step = (texel * 0.5).max(1e-4);
+
+        let mut t_prev = t_start;
+        if above(t_prev) == Some(false) {
+            return None;
+        }
+
+        let mut t = t_start;
+        while t < t_end {
+            t = (t + step).min(t_end);
+
+            if above(t) == Some(false) {
+                let (mut lo, mut hi) = (t_prev, t);
+                for _ in 0..16 {
+                    let mid = 0.5 * (lo + hi);
+                    if above(mid) == Some(false) {
+                        hi = mid;
+                    } else {
+                        lo = mid;
+                    }
+                }
+
+                let p = origin + dir * hi;
+                let uv = self.world_to_uv(p.x, p.z);
+                let position = Vec3::new(p.x, self.height_at_uv(uv), p.z);
+                return Some(TerrainHit {
+                    position,
+                    normal: self.normal_at_uv(uv),
+                    distance: hi,
+                    uv,
+                });
+            }
+
+            t_prev = t;
+        }
+
+        None
+    }
+
+    /// Projects `(x, z)` straight down onto the surface (for gameplay placement).
+    #[inline]
+    pub fn snap_to_surface(&self, x: f32, z: f32) -> Option<TerrainHit> {
+        let uv = self.world_to_uv(x, z);
+        let y = self.height_at(x, z)?;
+        Some(TerrainHit {
+            position: Vec3::new(x, y, z),
+            normal: self.normal_at_uv(uv),
+            distance: 0.0,
+            uv,
+        })
+    }
+}
+
+/// Slab test. Returns `(t_enter, t_exit)` along the ray, or `None` on miss.
+#[inline]
+fn ray_aabb(origin: Vec3, dir: Vec3, min: Vec3, max: Vec3) -> Option<(f32, f32)> {
+    let inv = dir.recip();
+    let t0 = (min - origin) * inv;
+    let t1 = (max - origin) * inv;
+
+    let tmin = t0.min(t1);
+    let tmax = t0.max(t1);
+
+    let enter = finite_or(tmin.x, f32::NEG_INFINITY)
+        .max(finite_or(tmin.y, f32::NEG_INFINITY))
+        .max(finite_or(tmin.z, f32::NEG_INFINITY));
+    let exit = finite_or(tmax.x, f32::INFINITY)
+        .min(finite_or(tmax.y, f32::INFINITY))
+        .min(finite_or(tmax.z, f32::INFINITY));
+
+    (exit >= enter && exit >= 0.0).then_some((enter, exit))
+}
+
+#[inline]
+fn finite_or(v: f32, fallback: f32) -> f32 {
+    if v.is_nan() {
+        fallback
+    } else {
+        v
+    }
+}
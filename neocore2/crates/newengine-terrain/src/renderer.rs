@@ -0,0 +1,315 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::asset::TerrainAsset;
+use crate::lod::{build_patch_indices, build_patch_vertices, select_patches, LodConfig, PatchKey, TerrainVertex};
+use crate::shaders::TerrainShaders;
+use crate::splat::MAX_TERRAIN_LAYERS;
+
+use glam::Vec3;
+use newengine_camera::{CameraMatrices, Frustum};
+use newengine_core::render::{
+    BindGroupDesc, BindGroupId, BindGroupLayoutDesc, BindGroupLayoutId, BindingKind, BufferBinding,
+    BufferDesc, BufferId, BufferSlice, BufferUsage, DrawIndexedArgs, IndexFormat, MemoryHint,
+    PipelineDesc, PipelineId, PrimitiveTopology, RenderApi, ShaderDesc, ShaderId, ShaderStage,
+    TextureFormat, VertexAttribute, VertexFormat, VertexLayout,
+};
+use newengine_core::EngineResult;
+use std::collections::HashMap;
+
+/// std140: mat4 view_proj | vec4 layers[4] | vec4 light_dir.
+const UBO_SIZE: u64 = 64 + 16 * MAX_TERRAIN_LAYERS as u64 + 16;
+
+/// Per-frame counters of the last `TerrainRenderer::draw`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerrainDrawStats {
+    pub patches_visible: u32,
+    pub patches_uploaded: u32,
+    pub patches_deferred: u32,
+    pub patches_cached: u32,
+}
+
+#[derive(Clone, Copy)]
+struct PatchGpu {
+    vb: BufferId,
+    last_used: u64,
+}
+
+/// GPU side of one terrain: shared index buffer, patch vertex cache and pipeline.
+///
+/// Patch meshes are built on demand and kept in an LRU cache; uploads are capped per frame
+/// so a fast camera move spreads the cost over several frames (coarser parents stay drawn).
+pub struct TerrainRenderer {
+    cfg: LodConfig,
+
+    vs: ShaderId,
+    fs: ShaderId,
+    bgl: BindGroupLayoutId,
+    bg: BindGroupId,
+    ubo: BufferId,
+    ib: BufferId,
+    pipeline: PipelineId,
+    index_count: u32,
+
+    patches: HashMap<PatchKey, PatchGpu>,
+    visible: Vec<PatchKey>,
+    frame: u64,
+
+    pub max_cached_patches: usize,
+    pub max_uploads_per_frame: u32,
+    pub light_dir: Vec3,
+
+    stats: TerrainDrawStats,
+}
+
+impl TerrainRenderer {
+    pub fn new(
+        r: &mut dyn RenderApi,
+        shaders: &TerrainShaders,
+        cfg: LodConfig,
+        color_format: TextureFormat,
+        depth_format: Option<TextureFormat>,
+    ) -> EngineResult<Self> {
+        let vs = r.create_shader(
+            ShaderDesc::new(ShaderStage::Vertex, "main", shaders.vertex_spirv.clone())
+                .with_label("terrain_vs"),
+        )?;
+        let fs = r.create_shader(
+            ShaderDesc::new(ShaderStage::Fragment, "main", shaders.fragment_spirv.clone())
+                .with_label("terrain_fs"),
+        )?;
+
+        let ubo = r.create_buffer(
+            BufferDesc::new(UBO_SIZE, BufferUsage::Uniform, MemoryHint::CpuToGpu)
+                .with_label("terrain_ubo"),
+        )?;
+
+        let bgl = r.create_bind_group_layout(
+            BindGroupLayoutDesc::new(vec![BindingKind::UniformBuffer]).with_label("terrain_bgl"),
+        )?;
+        let bg = r.create_bind_group(
+            BindGroupDesc::new(bgl)
+                .with_label("terrain_bg")
+                .with_uniform0(BufferBinding::new(ubo, 0, UBO_SIZE)),
+        )?;
+
+        let indices = build_patch_indices(&cfg);
+        let mut ibytes: Vec<u8> = Vec::with_capacity(indices.len() * 4);
+        for i in &indices {
+            ibytes.extend_from_slice(&i.to_ne_bytes());
+        }
+        let ib = r.create_buffer(
+            BufferDesc::new(ibytes.len() as u64, BufferUsage::Index, MemoryHint::CpuToGpu)
+                .with_label("terrain_ib"),
+        )?;
+        r.write_buffer(ib, 0, &ibytes)?;
+
+        let f32_size = std::mem::size_of::<f32>() as u32;
+        let layout = VertexLayout::new(
+            TerrainVertex::STRIDE,
+            vec![
+                VertexAttribute::new(0, 0, VertexFormat::Float32x3),
+                VertexAttribute::new(1, 3 * f32_size, VertexFormat::Float32x3),
+                VertexAttribute::new(2, 6 * f32_size, VertexFormat::Float32x4),
+            ],
+        );
+
+        let mut desc = PipelineDesc::new(vs, fs, color_format)
+            .with_label("terrain_pipeline")
+            .with_topology(PrimitiveTopology::TriangleList)
+            .with_vertex_layouts(vec![layout])
+            .with_bind_group_layouts(vec![bgl]);
+        if let Some(depth) = depth_format {
+            desc = desc.with_depth(depth);
+        }
+        let pipeline = r.create_pipeline(desc)?;
+
+        log::info!(
+            target: "terrain",
+            "renderer.init patch_resolution={} max_depth={} indices={}",
+            cfg.patch_resolution,
+            cfg.max_depth,
+            indices.len()
+        );
+
+        Ok(Self {
+            cfg,
+            vs,
+            fs,
+            bgl,
+            bg,
+            ubo,
+            ib,
+            pipeline,
+            index_count: indices.len() as u32,
+            patches: HashMap::new(),
+            visible: Vec::new(),
+            frame: 0,
+            max_cached_patches: 512,
+            max_uploads_per_frame: 16,
+            light_dir: Vec3::new(0.35, 0.75, 0.55),
+            stats: TerrainDrawStats::default(),
+        })
+    }
+
+    #[inline]
+    pub fn config(&self) -> &LodConfig {
+        &self.cfg
+    }
+
+    #[inline]
+    pub fn stats(&self) -> TerrainDrawStats {
+        self.stats
+    }
+
+    /// Drops all cached patch meshes (call after the terrain asset changed).
+    pub fn invalidate(&mut self, r: &mut dyn RenderApi) {
+        for (_, p) in self.patches.drain() {
+            r.destroy_buffer(p.vb);
+        }
+    }
+
+    /// Records terrain draws. Must be called between `begin_frame` and `end_frame`
+    /// with viewport/scissor already set.
+    pub fn draw(
+        &mut self,
+        r: &mut dyn RenderApi,
+        asset: &TerrainAsset,
+        camera: &CameraMatrices,
+    ) -> EngineResult<()> {
+        self.frame = self.frame.wrapping_add(1);
+        self.stats = TerrainDrawStats::default();
+
+        let frustum = Frustum::from_view_proj(camera.view_proj);
+        select_patches(asset, &self.cfg, camera.world_pos, Some(&frustum), &mut self.visible);
+        self.stats.patches_visible = self.visible.len() as u32;
+
+        self.write_uniforms(r, asset, camera)?;
+
+        r.set_pipeline(self.pipeline)?;
+        r.set_bind_group(0, self.bg)?;
+        r.set_index_buffer(BufferSlice::new(self.ib, 0), IndexFormat::U32)?;
+
+        let visible = std::mem::take(&mut self.visible);
+        for key in visible.iter().copied() {
+            let Some(vb) = self.resolve_patch(r, asset, key)? else {
+                continue;
+            };
+            r.set_vertex_buffer(0, BufferSlice::new(vb, 0))?;
+            r.draw_indexed(DrawIndexedArgs::new(self.index_count))?;
+        }
+        self.visible = visible;
+
+        self.evict(r);
+        self.stats.patches_cached = self.patches.len() as u32;
+        Ok(())
+    }
+
+    pub fn destroy(mut self, r: &mut dyn RenderApi) {
+        self.invalidate(r);
+        r.destroy_pipeline(self.pipeline);
+        r.destroy_bind_group(self.bg);
+        r.destroy_bind_group_layout(self.bgl);
+        r.destroy_buffer(self.ubo);
+        r.destroy_buffer(self.ib);
+        r.destroy_shader(self.vs);
+        r.destroy_shader(self.fs);
+    }
+
+    fn write_uniforms(
+        &self,
+        r: &mut dyn RenderApi,
+        asset: &TerrainAsset,
+        camera: &CameraMatrices,
+    ) -> EngineResult<()> {
+        let mut bytes: Vec<u8> = Vec::with_capacity(UBO_SIZE as usize);
+        for f in camera.view_proj.to_cols_array() {
+            bytes.extend_from_slice(&f.to_ne_bytes());
+        }
+        for i in 0..MAX_TERRAIN_LAYERS {
+            let c = asset.layers.get(i).map(|l| l.color).unwrap_or([0.0; 4]);
+            for f in c {
+                bytes.extend_from_slice(&f.to_ne_bytes());
+            }
+        }
+        for f in self.light_dir.normalize_or_zero().extend(0.0).to_array() {
+            bytes.extend_from_slice(&f.to_ne_bytes());
+        }
+        r.write_buffer(self.ubo, 0, &bytes)
+    }
+
+    /// Returns a vertex buffer for `key`, uploading it if the frame budget allows.
+    /// Over budget, the closest cached ancestor is used instead (may be drawn several times
+    /// for sibling keys; harmless and short-lived).
+    fn resolve_patch(
+        &mut self,
+        r: &mut dyn RenderApi,
+        asset: &TerrainAsset,
+        key: PatchKey,
+    ) -> EngineResult<Option<BufferId>> {
+        if let Some(p) = self.patches.get_mut(&key) {
+            p.last_used = self.frame;
+            return Ok(Some(p.vb));
+        }
+
+        if self.stats.patches_uploaded < self.max_uploads_per_frame {
+            let verts = build_patch_vertices(asset, &self.cfg, key);
+            let mut bytes: Vec<u8> = Vec::with_capacity(verts.len() * TerrainVertex::STRIDE as usize);
+            for v in &verts {
+                v.write_bytes(&mut bytes);
+            }
+
+            let vb = r.create_buffer(
+                BufferDesc::new(bytes.len() as u64, BufferUsage::Vertex, MemoryHint::CpuToGpu)
+                    .with_label("terrain_patch_vb"),
+            )?;
+            r.write_buffer(vb, 0, &bytes)?;
+
+            self.patches.insert(
+                key,
+                PatchGpu {
+                    vb,
+                    last_used: self.frame,
+                },
+            );
+            self.stats.patches_uploaded += 1;
+            return Ok(Some(vb));
+        }
+
+        self.stats.patches_deferred += 1;
+
+        let mut k = key;
+        while k.depth > 0 {
+            k = PatchKey {
+                depth: k.depth - 1,
+                x: k.x / 2,
+                z: k.z / 2,
+            };
+            if let Some(p) = self.patches.get_mut(&k) {
+                p.last_used = self.frame;
+                return Ok(Some(p.vb));
+            }
+        }
+        Ok(None)
+    }
+
+    fn evict(&mut self, r: &mut dyn RenderApi) {
+        if self.patches.len() <= self.max_cached_patches {
+            return;
+        }
+
+        let mut by_age: Vec<(u64, PatchKey)> = self
+            .patches
+            .iter()
+            .filter(|(_, p)| p.last_used != self.frame)
+            .map(|(k, p)| (p.last_used, *k))
+            .collect();
+        by_age.sort();
+
+        let excess = self.patches.len() - self.max_cached_patches;
+        for (_, k) in by_age.into_iter().take(excess) {
+            if let Some(p) = self.patches.remove(&k) {
+                r.destroy_buffer(p.vb);
+            }
+        }
+    }
+}
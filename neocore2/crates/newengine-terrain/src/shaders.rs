@@ -0,0 +1,69 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+/// Reference vertex shader (GLSL 450) matching `TerrainVertex` and the terrain uniform block.
+///
+/// The crate does not compile shaders; hosts compile these sources (e.g. with shaderc)
+/// and pass SPIR-V via `TerrainShaders`.
+pub const TERRAIN_VS_GLSL: &str = r#"#version 450
+layout(location = 0) in vec3 a_pos;
+layout(location = 1) in vec3 a_nrm;
+layout(location = 2) in vec4 a_splat;
+
+layout(set = 0, binding = 0) uniform TerrainUbo {
+    mat4 u_view_proj;
+    vec4 u_layers[4];
+    vec4 u_light_dir;
+} u;
+
+layout(location = 0) out vec3 v_nrm;
+layout(location = 1) out vec4 v_splat;
+
+void main() {
+    v_nrm = a_nrm;
+    v_splat = a_splat;
+    gl_Position = u.u_view_proj * vec4(a_pos, 1.0);
+}
+"#;
+
+/// Reference fragment shader: splat-weighted layer blend with half-Lambert lighting.
+pub const TERRAIN_FS_GLSL: &str = r#"#version 450
+layout(location = 0) in vec3 v_nrm;
+layout(location = 1) in vec4 v_splat;
+
+layout(set = 0, binding = 0) uniform TerrainUbo {
+    mat4 u_view_proj;
+    vec4 u_layers[4];
+    vec4 u_light_dir;
+} u;
+
+layout(location = 0) out vec4 o_col;
+
+void main() {
+    vec4 w = v_splat / max(dot(v_splat, vec4(1.0)), 1e-4);
+    vec3 albedo = u.u_layers[0].rgb * w.x
+                + u.u_layers[1].rgb * w.y
+                + u.u_layers[2].rgb * w.z
+                + u.u_layers[3].rgb * w.w;
+
+    vec3 n = normalize(v_nrm);
+    float ndl = clamp(dot(n, normalize(u.u_light_dir.xyz)) * 0.5 + 0.5, 0.0, 1.0);
+    o_col = vec4(albedo * ndl, 1.0);
+}
+"#;
+
+/// Compiled SPIR-V for the terrain pipeline.
+#[derive(Debug, Clone)]
+pub struct TerrainShaders {
+    pub vertex_spirv: Vec<u32>,
+    pub fragment_spirv: Vec<u32>,
+}
+
+impl TerrainShaders {
+    #[inline]
+    pub fn new(vertex_spirv: Vec<u32>, fragment_spirv: Vec<u32>) -> Self {
+        Self {
+            vertex_spirv,
+            fragment_spirv,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_assets::AssetError;
+use serde::{Deserialize, Serialize};
+
+/// Number of material layers a splat map can blend (one per RGBA channel).
+pub const MAX_TERRAIN_LAYERS: usize = 4;
+
+/// One blendable terrain material layer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TerrainLayer {
+    pub name: String,
+    /// Linear RGBA albedo.
+    #[serde(default = "default_layer_color")]
+    pub color: [f32; 4],
+    /// Detail repetitions per world unit (reserved for textured layers).
+    #[serde(default = "default_layer_tiling")]
+    pub tiling: f32,
+}
+
+#[inline]
+fn default_layer_color() -> [f32; 4] {
+    [0.5, 0.5, 0.5, 1.0]
+}
+
+#[inline]
+fn default_layer_tiling() -> f32 {
+    1.0
+}
+
+impl TerrainLayer {
+    #[inline]
+    pub fn new(name: impl Into<String>, color: [f32; 4]) -> Self {
+        Self {
+            name: name.into(),
+            color,
+            tiling: default_layer_tiling(),
+        }
+    }
+}
+
+/// RGBA8 layer weights; channel `i` is the weight of layer `i`.
+#[derive(Debug, Clone)]
+pub struct SplatMap {
+    width: u32,
+    height: u32,
+    weights: Vec<[u8; 4]>,
+}
+
+impl SplatMap {
+    pub fn new(width: u32, height: u32, weights: Vec<[u8; 4]>) -> Result<Self, AssetError> {
+        if width == 0 || height == 0 {
+            return Err(AssetError::new("terrain: splat map must not be empty"));
+        }
+        let expected = width as usize * height as usize;
+        if weights.len() != expected {
+            return Err(AssetError::new(format!(
+                "terrain: splat map texel count mismatch (expected {expected}, got {})",
+                weights.len()
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            weights,
+        })
+    }
+
+    /// Decodes an RGBA image (any format supported by the `image` crate).
+    pub fn decode(bytes: &[u8]) -> Result<Self, AssetError> {
+        let img = image::load_from_memory(bytes)
+            .map_err(|e| AssetError::new(format!("terrain: splat map decode failed: {e}")))?
+            .to_rgba8();
+
+        let (w, h) = img.dimensions();
+        let weights = img.pixels().map(|p| p.0).collect();
+        Self::new(w, h, weights)
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    pub fn texels(&self) -> &[[u8; 4]] {
+        &self.weights
+    }
+
+    #[inline]
+    fn texel(&self, x: i64, z: i64) -> [f32; 4] {
+        let x = x.clamp(0, self.width as i64 - 1) as usize;
+        let z = z.clamp(0, self.height as i64 - 1) as usize;
+        self.weights[z * self.width as usize + x].map(|c| c as f32 / 255.0)
+    }
+
+    /// Bilinear weights at normalized coordinates, renormalized to sum to 1.
+    ///
+    /// Fully black texels fall back to layer 0.
+    pub fn sample(&self, u: f32, v: f32) -> [f32; 4] {
+        let fx = u.clamp(0.0, 1.0) * (self.width.saturating_sub(1)) as f32;
+        let fz = v.clamp(0.0, 1.0) * (self.height.saturating_sub(1)) as f32;
+
+        let x0 = fx.floor() as i64;
+        let z0 = fz.floor() as i64;
+        let tx = fx - x0 as f32;
+        let tz = fz - z0 as f32;
+
+        let w00 = self.texel(x0, z0);
+        let w10 = self.texel(x0 + 1, z0);
+        let w01 = self.texel(x0, z0 + 1);
+        let w11 = self.texel(x0 + 1, z0 + 1);
+
+        let mut out = [0.0f32; 4];
+        for i in 0..4 {
+            let a = w00[i] + (w10[i] - w00[i]) * tx;
+            let b = w01[i] + (w11[i] - w01[i]) * tx;
+            out[i] = a + (b - a) * tz;
+        }
+
+        normalize_weights(out)
+    }
+}
+
+#[inline]
+pub(crate) fn normalize_weights(w: [f32; 4]) -> [f32; 4] {
+    let sum: f32 = w.iter().sum();
+    if sum <= 1e-6 {
+        return [1.0, 0.0, 0.0, 0.0];
+    }
+    w.map(|c| c / sum)
+}
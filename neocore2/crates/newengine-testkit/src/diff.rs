@@ -0,0 +1,193 @@
+use newengine_core::render::FrameCapture;
+
+/// Max YIQ squared distance between two RGBA8 colors (black vs white).
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DiffOptions {
+    /// Per-pixel perceptual threshold in `0..=1` (YIQ distance, 0.1 ~ barely visible).
+    pub threshold: f32,
+    /// Fraction of pixels allowed to differ before the comparison fails.
+    pub max_diff_ratio: f32,
+    /// Pixels whose value matches a 1px neighbor in the other image are treated as
+    /// rasterization/anti-aliasing noise and not counted.
+    pub ignore_antialiasing: bool,
+}
+
+impl Default for DiffOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            max_diff_ratio: 0.001,
+            ignore_antialiasing: true,
+        }
+    }
+}
+
+impl DiffOptions {
+    #[inline]
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_max_diff_ratio(mut self, ratio: f32) -> Self {
+        self.max_diff_ratio = ratio.max(0.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_antialiasing(mut self, ignore: bool) -> Self {
+        self.ignore_antialiasing = ignore;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffReport {
+    pub width: u32,
+    pub height: u32,
+    pub diff_pixels: u64,
+    pub aa_pixels: u64,
+    /// Largest normalized perceptual delta (`0..=1`).
+    pub max_delta: f32,
+    pub passed: bool,
+    /// Visualization: faded expected image, red = difference, yellow = ignored AA.
+    pub diff_image: FrameCapture,
+}
+
+impl DiffReport {
+    #[inline]
+    pub fn diff_ratio(&self) -> f32 {
+        let total = self.width as u64 * self.height as u64;
+        if total == 0 {
+            return 0.0;
+        }
+        self.diff_pixels as f32 / total as f32
+    }
+}
+
+/// Compares two captures. Size mismatch is reported as a full failure.
+pub fn compare_frames(expected: &FrameCapture, actual: &FrameCapture, opts: &DiffOptions) -> DiffReport {
+    let (w, h) = (expected.width, expected.height);
+
+    if actual.width != w || actual.height != h {
+        return DiffReport {
+            width: w,
+            height: h,
+            diff_pixels: w as u64 * h as u64,
+            aa_pixels: 0,
+            max_delta: 1.0,
+            passed: false,
+            diff_image: FrameCapture::new(w, h, vec![255, 0, 0, 255].repeat((w * h) as usize)),
+        };
+    }
+
+    let max_delta_allowed = MAX_YIQ_DELTA * opts.threshold * opts.threshold;
+    let mut out = Vec::with_capacity(expected.rgba.len());
+    let mut diff_pixels = 0u64;
+    let mut aa_pixels = 0u64;
+    let mut max_delta = 0.0f32;
+
+    for y in 0..h {
+        for x in 0..w {
+            let a = expected.pixel(x, y);
+            let b = actual.pixel(x, y);
+            let d = color_delta(a, b);
+            max_delta = max_delta.max(d / MAX_YIQ_DELTA);
+
+            if d <= max_delta_allowed {
+                let g = faded_gray(a);
+                out.extend_from_slice(&[g, g, g, 255]);
+                continue;
+            }
+
+            let aa = opts.ignore_antialiasing
+                && (has_close_neighbor(actual, x, y, a, max_delta_allowed)
+                    || has_close_neighbor(expected, x, y, b, max_delta_allowed));
+
+            if aa {
+                aa_pixels += 1;
+                out.extend_from_slice(&[255, 255, 0, 255]);
+            } else {
+                diff_pixels += 1;
+                out.extend_from_slice(&[255, 0, 0, 255]);
+            }
+        }
+    }
+
+    let total = (w as u64 * h as u64).max(1);
+    let passed = diff_pixels as f32 / total as f32 <= opts.max_diff_ratio;
+
+    DiffReport {
+        width: w,
+        height: h,
+        diff_pixels,
+        aa_pixels,
+        max_delta: max_delta.min(1.0),
+        passed,
+        diff_image: FrameCapture::new(w, h, out),
+    }
+}
+
+/// Squared YIQ distance with alpha blended over white (pixelmatch metric).
+fn color_delta(a: [u8; 4], b: [u8; 4]) -> f32 {
+    let (r1, g1, b1) = blend_white(a);
+    let (r2, g2, b2) = blend_white(b);
+
+    let y = rgb_to_y(r1, g1, b1) - rgb_to_y(r2, g2, b2);
+    let i = rgb_to_i(r1, g1, b1) - rgb_to_i(r2, g2, b2);
+    let q = rgb_to_q(r1, g1, b1) - rgb_to_q(r2, g2, b2);
+
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+#[inline]
+fn blend_white(c: [u8; 4]) -> (f32, f32, f32) {
+    let a = c[3] as f32 / 255.0;
+    let f = |v: u8| 255.0 + (v as f32 - 255.0) * a;
+    (f(c[0]), f(c[1]), f(c[2]))
+}
+
+#[inline]
+fn rgb_to_y(r: f32, g: f32, b: f32) -> f32 {
+    r * 0.298_895_31 + g * 0.586_622_47 + b * 0.114_482_23
+}
+
+#[inline]
+fn rgb_to_i(r: f32, g: f32, b: f32) -> f32 {
+    r * 0.595_977_99 - g * 0.274_176_1 - b * 0.321_801_89
+}
+
+#[inline]
+fn rgb_to_q(r: f32, g: f32, b: f32) -> f32 {
+    r * 0.211_470_17 - g * 0.522_617_44 + b * 0.311_147_27
+}
+
+#[inline]
+fn faded_gray(c: [u8; 4]) -> u8 {
+    let (r, g, b) = blend_white(c);
+    let y = rgb_to_y(r, g, b);
+    (255.0 + (y - 255.0) * 0.1).clamp(0.0, 255.0) as u8
+}
+
+fn has_close_neighbor(img: &FrameCapture, x: u32, y: u32, c: [u8; 4], max_delta: f32) -> bool {
+    let x0 = x.saturating_sub(1);
+    let y0 = y.saturating_sub(1);
+    let x1 = (x + 1).min(img.width - 1);
+    let y1 = (y + 1).min(img.height - 1);
+
+    for ny in y0..=y1 {
+        for nx in x0..=x1 {
+            if (nx, ny) == (x, y) {
+                continue;
+            }
+            if color_delta(img.pixel(nx, ny), c) <= max_delta {
+                return true;
+            }
+        }
+    }
+    false
+}
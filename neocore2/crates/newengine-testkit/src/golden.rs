@@ -0,0 +1,169 @@
+use crate::diff::{compare_frames, DiffOptions, DiffReport};
+
+use log::{info, warn};
+use newengine_core::render::FrameCapture;
+use std::path::{Path, PathBuf};
+
+/// Set to `1` to (re)write golden images instead of comparing against them.
+pub const GOLDEN_UPDATE_ENV: &str = "NE_UPDATE_GOLDENS";
+
+#[derive(Debug)]
+pub enum GoldenError {
+    Missing { name: String, path: PathBuf },
+    Io(String),
+    Image(String),
+    Mismatch {
+        name: String,
+        diff_pixels: u64,
+        diff_ratio: f32,
+        actual: PathBuf,
+        diff: PathBuf,
+    },
+}
+
+impl std::fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoldenError::Missing { name, path } => write!(
+                f,
+                "golden '{name}' missing at '{}' (run with {GOLDEN_UPDATE_ENV}=1 to create it)",
+                path.display()
+            ),
+            GoldenError::Io(e) => write!(f, "golden io: {e}"),
+            GoldenError::Image(e) => write!(f, "golden image: {e}"),
+            GoldenError::Mismatch {
+                name,
+                diff_pixels,
+                diff_ratio,
+                actual,
+                diff,
+            } => write!(
+                f,
+                "golden '{name}' mismatch: {diff_pixels} px ({:.4}%) differ; actual='{}' diff='{}'",
+                diff_ratio * 100.0,
+                actual.display(),
+                diff.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+/// Directory of reference PNGs (`<dir>/<name>.png`).
+///
+/// On mismatch `<out_dir>/<name>.actual.png` and `<out_dir>/<name>.diff.png` are written.
+#[derive(Debug, Clone)]
+pub struct GoldenStore {
+    dir: PathBuf,
+    out_dir: PathBuf,
+    options: DiffOptions,
+    update: bool,
+}
+
+impl GoldenStore {
+    /// `update` defaults to the `NE_UPDATE_GOLDENS` environment variable.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let update = std::env::var(GOLDEN_UPDATE_ENV).is_ok_and(|v| v == "1" || v == "true");
+        Self {
+            out_dir: dir.join("failures"),
+            dir,
+            options: DiffOptions::default(),
+            update,
+        }
+    }
+
+    #[inline]
+    pub fn with_out_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = dir.into();
+        self
+    }
+
+    #[inline]
+    pub fn with_options(mut self, options: DiffOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    #[inline]
+    pub fn with_update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    #[inline]
+    pub fn golden_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.png"))
+    }
+
+    /// Compares `actual` against the golden image `name`.
+    pub fn check(&self, name: &str, actual: &FrameCapture) -> Result<DiffReport, GoldenError> {
+        let path = self.golden_path(name);
+
+        if self.update {
+            save_png(&path, actual)?;
+            info!(target: "testkit", "golden.update name='{}' file='{}'", name, path.display());
+            return Ok(compare_frames(actual, actual, &self.options));
+        }
+
+        if !path.exists() {
+            return Err(GoldenError::Missing {
+                name: name.to_string(),
+                path,
+            });
+        }
+
+        let expected = load_png(&path)?;
+        let report = compare_frames(&expected, actual, &self.options);
+
+        if report.passed {
+            return Ok(report);
+        }
+
+        let actual_path = self.out_dir.join(format!("{name}.actual.png"));
+        let diff_path = self.out_dir.join(format!("{name}.diff.png"));
+        save_png(&actual_path, actual)?;
+        save_png(&diff_path, &report.diff_image)?;
+
+        warn!(
+            target: "testkit",
+            "golden.mismatch name='{}' diff_px={} aa_px={} max_delta={:.3}",
+            name,
+            report.diff_pixels,
+            report.aa_pixels,
+            report.max_delta
+        );
+
+        Err(GoldenError::Mismatch {
+            name: name.to_string(),
+            diff_pixels: report.diff_pixels,
+            diff_ratio: report.diff_ratio(),
+            actual: actual_path,
+            diff: diff_path,
+        })
+    }
+}
+
+pub fn load_png(path: &Path) -> Result<FrameCapture, GoldenError> {
+    let img = image::open(path)
+        .map_err(|e| GoldenError::Image(format!("'{}': {e}", path.display())))?
+        .to_rgba8();
+    let (w, h) = img.dimensions();
+    Ok(FrameCapture::new(w, h, img.into_raw()))
+}
+
+pub fn save_png(path: &Path, frame: &FrameCapture) -> Result<(), GoldenError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| GoldenError::Io(format!("'{}': {e}", parent.display())))?;
+    }
+    image::save_buffer(
+        path,
+        &frame.rgba,
+        frame.width,
+        frame.height,
+        image::ExtendedColorType::Rgba8,
+    )
+    .map_err(|e| GoldenError::Image(format!("'{}': {e}", path.display())))
+}
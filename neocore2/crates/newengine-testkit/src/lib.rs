@@ -0,0 +1,9 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+pub mod diff;
+pub mod golden;
+pub mod scene;
+
+pub use diff::{compare_frames, DiffOptions, DiffReport};
+pub use golden::{GoldenError, GoldenStore, GOLDEN_UPDATE_ENV};
+pub use scene::{capture_scene, CaptureOptions, TestScene};
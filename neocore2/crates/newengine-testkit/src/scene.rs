@@ -0,0 +1,114 @@
+use newengine_core::render::{BeginFrameDesc, Color4, FrameCapture, RenderApi};
+use newengine_core::{EngineError, EngineResult};
+
+/// Predefined scene for visual regression tests.
+///
+/// Scenes talk to the backend only through `RenderApi`, so the same scene runs against any
+/// backend that supports `request_frame_capture` (a hidden window is enough; the backend
+/// decides how it obtains a surface).
+pub trait TestScene {
+    fn name(&self) -> &str;
+
+    fn setup(&mut self, api: &mut dyn RenderApi) -> EngineResult<()>;
+
+    /// Records draw commands; called between begin_frame/end_frame.
+    fn draw(&mut self, api: &mut dyn RenderApi, frame: u32) -> EngineResult<()>;
+
+    fn teardown(&mut self, _api: &mut dyn RenderApi) {}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CaptureOptions {
+    pub width: u32,
+    pub height: u32,
+    pub clear_color: Color4,
+    /// Frames rendered before the captured one (swapchain recreation, async uploads).
+    pub warmup_frames: u32,
+}
+
+impl Default for CaptureOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            width: 256,
+            height: 256,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            warmup_frames: 2,
+        }
+    }
+}
+
+impl CaptureOptions {
+    #[inline]
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    #[inline]
+    pub fn with_clear_color(mut self, color: Color4) -> Self {
+        self.clear_color = color;
+        self
+    }
+
+    #[inline]
+    pub fn with_warmup_frames(mut self, frames: u32) -> Self {
+        self.warmup_frames = frames;
+        self
+    }
+}
+
+/// Renders `scene` for `warmup_frames + 1` frames and returns the last one.
+pub fn capture_scene(
+    api: &mut dyn RenderApi,
+    scene: &mut dyn TestScene,
+    opts: &CaptureOptions,
+) -> EngineResult<FrameCapture> {
+    api.resize(opts.width, opts.height)?;
+    scene.setup(api)?;
+
+    let res = render_frames(api, scene, opts);
+    scene.teardown(api);
+    let capture = res?;
+
+    if capture.width != opts.width || capture.height != opts.height {
+        log::warn!(
+            target: "testkit",
+            "capture.size_mismatch scene='{}' requested={}x{} got={}x{}",
+            scene.name(),
+            opts.width,
+            opts.height,
+            capture.width,
+            capture.height
+        );
+    }
+
+    Ok(capture)
+}
+
+fn render_frames(
+    api: &mut dyn RenderApi,
+    scene: &mut dyn TestScene,
+    opts: &CaptureOptions,
+) -> EngineResult<FrameCapture> {
+    for frame in 0..=opts.warmup_frames {
+        let last = frame == opts.warmup_frames;
+        if last && !api.request_frame_capture() {
+            return Err(EngineError::other(
+                "testkit: render backend does not support frame capture",
+            ));
+        }
+
+        api.begin_frame(BeginFrameDesc::new(opts.clear_color))?;
+        scene.draw(api, frame)?;
+        api.end_frame()?;
+    }
+
+    api.take_frame_capture().ok_or_else(|| {
+        EngineError::other(format!(
+            "testkit: scene '{}' produced no capture (frame skipped?)",
+            scene.name()
+        ))
+    })
+}
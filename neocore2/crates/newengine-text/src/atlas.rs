@@ -0,0 +1,241 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_ui::draw::{UiTexId, UiTexture, UiTextureDelta, UiTexturePatch};
+use std::collections::HashMap;
+
+/// Empty texels kept around each glyph so linear filtering does not bleed neighbours in.
+const PADDING: u32 = 1;
+
+/// Rasterized glyph: face index in the renderer, glyph id and pixel size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct GlyphKey {
+    pub(crate) face: u16,
+    pub(crate) glyph: u16,
+    pub(crate) size_px: u16,
+}
+
+/// Where a glyph lives in the atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct GlyphEntry {
+    /// Texel rectangle `[x, y, w, h]`.
+    pub(crate) rect: [u32; 4],
+    /// Top-left of the bitmap relative to the pen position on the baseline.
+    pub(crate) offset: [f32; 2],
+}
+
+/// Coverage bitmap of one glyph, one byte per texel.
+pub(crate) struct GlyphBitmap {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) offset: [f32; 2],
+    pub(crate) coverage: Vec<u8>,
+}
+
+/// The atlas has no room left for a glyph; [`GlyphAtlas::reset`] it and lay the text out
+/// again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AtlasFull;
+
+#[derive(Debug, Clone, Copy)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// Next free column.
+    x: u32,
+}
+
+/// Square RGBA8 glyph atlas packed in shelves. Texels are white with the coverage in alpha,
+/// so the UI and sprite shaders tint them with the vertex color.
+///
+/// Changes are tracked separately for the UI texture (as a dirty rectangle sent through the
+/// UI texture delta) and the render texture (re-uploaded whole).
+pub(crate) struct GlyphAtlas {
+    size: u32,
+    pixels: Vec<u8>,
+    shelves: Vec<Shelf>,
+    /// Top of the unused area below the last shelf.
+    next_y: u32,
+    /// `None` for glyphs without a bitmap (spaces).
+    entries: HashMap<GlyphKey, Option<GlyphEntry>>,
+    /// The UI copy needs the whole atlas, not a patch.
+    ui_full: bool,
+    /// Texels changed since the last UI flush, `[x0, y0, x1, y1]`.
+    ui_dirty: Option<[u32; 4]>,
+    gpu_dirty: bool,
+}
+
+impl GlyphAtlas {
+    pub(crate) fn new(size: u32) -> Self {
+        let size = size.max(64);
+        Self {
+            size,
+            pixels: blank(size),
+            shelves: Vec::new(),
+            next_y: 0,
+            entries: HashMap::new(),
+            ui_full: true,
+            ui_dirty: None,
+            gpu_dirty: true,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn size(&self) -> u32 {
+        self.size
+    }
+
+    #[inline]
+    pub(crate) fn glyph_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub(crate) fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Drops every glyph. Geometry built against the old contents must be rebuilt.
+    pub(crate) fn reset(&mut self) {
+        self.pixels = blank(self.size);
+        self.shelves.clear();
+        self.next_y = 0;
+        self.entries.clear();
+        self.ui_full = true;
+        self.ui_dirty = None;
+        self.gpu_dirty = true;
+    }
+
+    /// Entry of `key`, rasterizing it with `raster` on first use.
+    pub(crate) fn get_or_insert(
+        &mut self,
+        key: GlyphKey,
+        raster: impl FnOnce() -> Option<GlyphBitmap>,
+    ) -> Result<Option<GlyphEntry>, AtlasFull> {
+        if let Some(e) = self.entries.get(&key) {
+            return Ok(*e);
+        }
+        let entry = match raster() {
+            Some(bmp) if bmp.width > 0 && bmp.height > 0 => Some(self.insert(&bmp)?),
+            _ => None,
+        };
+        self.entries.insert(key, entry);
+        Ok(entry)
+    }
+
+    /// Moves pending changes into `delta`: the whole atlas after a reset, else one patch
+    /// covering every glyph added since the last flush.
+    pub(crate) fn flush_ui(&mut self, id: UiTexId, delta: &mut UiTextureDelta) {
+        if self.ui_full {
+            self.ui_full = false;
+            self.ui_dirty = None;
+            delta.set.insert(
+                id,
+                UiTexture {
+                    size: [self.size, self.size],
+                    rgba8: self.pixels.clone(),
+                },
+            );
+            return;
+        }
+        let Some([x0, y0, x1, y1]) = self.ui_dirty.take() else {
+            return;
+        };
+
+        let (w, h) = (x1 - x0, y1 - y0);
+        let mut rgba8 = Vec::with_capacity((w * h * 4) as usize);
+        for y in y0..y1 {
+            let row = ((y * self.size + x0) * 4) as usize;
+            rgba8.extend_from_slice(&self.pixels[row..row + (w * 4) as usize]);
+        }
+        delta.patches.push(UiTexturePatch {
+            id,
+            origin: [x0, y0],
+            size: [w, h],
+            rgba8,
+        });
+    }
+
+    /// Whether the render texture is out of date; clears the flag.
+    #[inline]
+    pub(crate) fn take_gpu_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.gpu_dirty)
+    }
+
+    /// Marks the render texture as missing, e.g. after it was destroyed.
+    #[inline]
+    pub(crate) fn mark_gpu_dirty(&mut self) {
+        self.gpu_dirty = true;
+    }
+
+    /// Marks the UI texture as missing, e.g. after it was freed.
+    #[inline]
+    pub(crate) fn mark_ui_full(&mut self) {
+        self.ui_full = true;
+    }
+
+    fn insert(&mut self, bmp: &GlyphBitmap) -> Result<GlyphEntry, AtlasFull> {
+        let (x, y) = self.allocate(bmp.width, bmp.height).ok_or(AtlasFull)?;
+
+        for row in 0..bmp.height {
+            let src = &bmp.coverage[(row * bmp.width) as usize..((row + 1) * bmp.width) as usize];
+            let dst = (((y + row) * self.size + x) * 4) as usize;
+            for (i, &c) in src.iter().enumerate() {
+                self.pixels[dst + i * 4 + 3] = c;
+            }
+        }
+
+        let r = [x, y, x + bmp.width, y + bmp.height];
+        self.ui_dirty = Some(match self.ui_dirty {
+            Some(d) => [
+                d[0].min(r[0]),
+                d[1].min(r[1]),
+                d[2].max(r[2]),
+                d[3].max(r[3]),
+            ],
+            None => r,
+        });
+        self.gpu_dirty = true;
+
+        Ok(GlyphEntry {
+            rect: [x, y, bmp.width, bmp.height],
+            offset: bmp.offset,
+        })
+    }
+
+    /// Top-left of a free `w` x `h` area: the first shelf tall enough (and not much taller)
+    /// with room left, else a new shelf.
+    fn allocate(&mut self, w: u32, h: u32) -> Option<(u32, u32)> {
+        let (pw, ph) = (w + PADDING, h + PADDING);
+        if pw > self.size || ph > self.size {
+            return None;
+        }
+
+        let size = self.size;
+        if let Some(s) = self
+            .shelves
+            .iter_mut()
+            .find(|s| s.height >= ph && s.height <= ph + ph / 2 + 2 && s.x + pw <= size)
+        {
+            let x = s.x;
+            s.x += pw;
+            return Some((x, s.y));
+        }
+
+        if self.next_y + ph > self.size {
+            return None;
+        }
+        let y = self.next_y;
+        self.next_y += ph;
+        self.shelves.push(Shelf {
+            y,
+            height: ph,
+            x: pw,
+        });
+        Some((0, y))
+    }
+}
+
+/// White, fully transparent RGBA8 texels.
+fn blank(size: u32) -> Vec<u8> {
+    [255u8, 255, 255, 0].repeat((size * size) as usize)
+}
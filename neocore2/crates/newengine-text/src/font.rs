@@ -0,0 +1,127 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use ab_glyph::{Font, FontArc};
+use newengine_assets::{
+    Asset, AssetBlob, AssetError, AssetKey, BlobImporterDispatch, ImporterPriority,
+};
+use std::sync::Arc;
+
+pub const FONT_TYPE_ID: &str = "kalitech.asset.font";
+pub const FONT_SFNT_FORMAT: &str = "ne.font.sfnt.v1";
+pub const FONT_VERSION: &str = "font.v1";
+
+/// TrueType / OpenType font file. The payload is the original file; faces are parsed when a
+/// [`TextRenderer`](crate::TextRenderer) takes the font.
+#[derive(Clone)]
+pub struct FontAsset {
+    pub data: Arc<[u8]>,
+}
+
+impl std::fmt::Debug for FontAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FontAsset")
+            .field("bytes", &self.data.len())
+            .finish()
+    }
+}
+
+impl Asset for FontAsset {
+    #[inline]
+    fn type_name() -> &'static str {
+        "FontAsset"
+    }
+}
+
+impl FontAsset {
+    /// Checks that `bytes` hold a usable face.
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>) -> Result<Self, AssetError> {
+        let data = bytes.into();
+        ab_glyph::FontRef::try_from_slice(&data)
+            .map_err(|e| AssetError::new(format!("font: {e}")))?;
+        Ok(Self { data })
+    }
+
+    #[inline]
+    pub fn from_blob(blob: &AssetBlob) -> Result<Self, AssetError> {
+        if &*blob.type_id != FONT_TYPE_ID {
+            return Err(AssetError::new(format!(
+                "font: blob type '{}' is not a font",
+                blob.type_id
+            )));
+        }
+        Self::from_bytes(blob.payload.as_slice())
+    }
+
+    /// Parsed face, for glyph lookup and rasterization.
+    pub(crate) fn face(&self) -> Result<FontArc, AssetError> {
+        FontArc::try_from_vec(self.data.to_vec()).map_err(|e| AssetError::new(format!("font: {e}")))
+    }
+}
+
+/// In-process importer for `.ttf` / `.otf` fonts. The file is passed through after
+/// validation; the metadata records the glyph count and container.
+#[derive(Debug, Default)]
+pub struct FontImporter;
+
+impl BlobImporterDispatch for FontImporter {
+    fn import_blob(&self, bytes: &[u8], key: &AssetKey) -> Result<AssetBlob, AssetError> {
+        let face = ab_glyph::FontRef::try_from_slice(bytes)
+            .map_err(|e| AssetError::new(format!("font: {e}")))?;
+        let container = match bytes.get(..4) {
+            Some(b"OTTO") => "otf",
+            _ => "ttf",
+        };
+
+        log::info!(
+            target: "assets",
+            "font.import path='{}' glyphs={} container={}",
+            key.logical_path.display(),
+            face.glyph_count(),
+            container
+        );
+
+        Ok(AssetBlob {
+            type_id: Arc::from(FONT_TYPE_ID),
+            format: Arc::from(FONT_SFNT_FORMAT),
+            payload: bytes.to_vec(),
+            meta_json: Arc::from(format!(
+                "{{\"schema\":\"kalitech.font.meta.v1\",\"container\":\"{container}\",\"glyphs\":{}}}",
+                face.glyph_count()
+            )),
+            dependencies: Vec::new(),
+        })
+    }
+
+    #[inline]
+    fn output_type_id(&self) -> Arc<str> {
+        Arc::from(FONT_TYPE_ID)
+    }
+
+    #[inline]
+    fn extensions(&self) -> Vec<String> {
+        vec!["ttf".to_string(), "otf".to_string()]
+    }
+
+    #[inline]
+    fn priority(&self) -> ImporterPriority {
+        ImporterPriority::new(100)
+    }
+
+    #[inline]
+    fn stable_id(&self) -> Arc<str> {
+        Arc::from("font_importer@newengine-text")
+    }
+
+    #[inline]
+    fn version(&self) -> Arc<str> {
+        Arc::from(FONT_VERSION)
+    }
+
+    fn mime_type(&self, ext: &str) -> Option<Arc<str>> {
+        match ext {
+            "ttf" => Some(Arc::from("font/ttf")),
+            "otf" => Some(Arc::from("font/otf")),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,177 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::atlas::GlyphKey;
+
+use ab_glyph::{Font, FontArc, GlyphId, PxScale, ScaleFont};
+use newengine_core::render::Color4;
+use newengine_ui::text::{visual_order, TextDirection};
+
+/// Horizontal alignment of lines within the text block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// How a string is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    /// Font size in pixels; rounded to whole pixels so glyphs can be shared.
+    pub size: f32,
+    pub color: Color4,
+    pub align: TextAlign,
+    /// Multiplier of the font's line height.
+    pub line_spacing: f32,
+    pub direction: TextDirection,
+    /// Sprite layer of 3D labels.
+    pub layer: i32,
+}
+
+impl Default for TextStyle {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            size: 16.0,
+            color: [1.0; 4],
+            align: TextAlign::Left,
+            line_spacing: 1.0,
+            direction: TextDirection::Auto,
+            layer: 0,
+        }
+    }
+}
+
+impl TextStyle {
+    #[inline]
+    pub fn new(size: f32) -> Self {
+        Self {
+            size,
+            ..Self::default()
+        }
+    }
+
+    #[inline]
+    pub fn with_color(mut self, color: Color4) -> Self {
+        self.color = color;
+        self
+    }
+
+    #[inline]
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    #[inline]
+    pub fn with_line_spacing(mut self, spacing: f32) -> Self {
+        self.line_spacing = spacing.max(0.0);
+        self
+    }
+
+    #[inline]
+    pub fn with_direction(mut self, direction: TextDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    #[inline]
+    pub fn with_layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Pixel size glyphs are rasterized and laid out at.
+    #[inline]
+    pub(crate) fn size_px(&self) -> u16 {
+        self.size.round().clamp(1.0, u16::MAX as f32) as u16
+    }
+}
+
+/// A font face registered with a [`TextRenderer`](crate::TextRenderer).
+pub(crate) struct Face {
+    pub(crate) name: String,
+    pub(crate) font: FontArc,
+}
+
+/// One glyph of laid-out text.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LaidGlyph {
+    pub(crate) key: GlyphKey,
+    /// Pen position on the baseline, relative to the top-left of the text block.
+    pub(crate) pen: [f32; 2],
+}
+
+/// Lays `text` out into `out` and returns the block size. Each character uses the first face
+/// that has it (the first face draws the "missing" glyph otherwise); line metrics come from
+/// the first face.
+pub(crate) fn layout(
+    faces: &[Face],
+    text: &str,
+    style: &TextStyle,
+    out: &mut Vec<LaidGlyph>,
+) -> [f32; 2] {
+    out.clear();
+    let Some(primary) = faces.first() else {
+        return [0.0, 0.0];
+    };
+    let size_px = style.size_px();
+    let scale = PxScale::from(size_px as f32);
+    let metrics = primary.font.as_scaled(scale);
+    let ascent = metrics.ascent();
+    let line_height = metrics.height() + metrics.line_gap();
+    let advance_y = line_height * style.line_spacing;
+
+    let text = visual_order(text, style.direction);
+    // (first glyph, width) of each line, for alignment.
+    let mut lines: Vec<(usize, f32)> = Vec::new();
+    for (i, line) in text.split('\n').enumerate() {
+        let baseline = ascent + i as f32 * advance_y;
+        let first = out.len();
+        let mut x = 0.0f32;
+        let mut prev: Option<(usize, GlyphId)> = None;
+
+        for c in line.chars().filter(|c| !c.is_control()) {
+            let face = faces
+                .iter()
+                .position(|f| f.font.glyph_id(c).0 != 0)
+                .unwrap_or(0);
+            let font = faces[face].font.as_scaled(scale);
+            let id = font.glyph_id(c);
+            if let Some((pf, pid)) = prev {
+                if pf == face {
+                    x += font.kern(pid, id);
+                }
+            }
+            out.push(LaidGlyph {
+                key: GlyphKey {
+                    face: face as u16,
+                    glyph: id.0,
+                    size_px,
+                },
+                pen: [x, baseline],
+            });
+            x += font.h_advance(id);
+            prev = Some((face, id));
+        }
+        lines.push((first, x));
+    }
+
+    let width = lines.iter().fold(0.0f32, |w, &(_, lw)| w.max(lw));
+    if style.align != TextAlign::Left {
+        for (n, &(first, line_w)) in lines.iter().enumerate() {
+            let end = lines.get(n + 1).map_or(out.len(), |&(next, _)| next);
+            let shift = match style.align {
+                TextAlign::Center => (width - line_w) * 0.5,
+                _ => width - line_w,
+            };
+            for g in &mut out[first..end] {
+                g.pen[0] += shift;
+            }
+        }
+    }
+
+    let height = line_height + (lines.len() - 1) as f32 * advance_y;
+    [width, height]
+}
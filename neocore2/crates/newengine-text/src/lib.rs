@@ -0,0 +1,14 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+mod atlas;
+mod font;
+mod layout;
+mod module;
+mod renderer;
+
+pub use font::{FontAsset, FontImporter, FONT_SFNT_FORMAT, FONT_TYPE_ID, FONT_VERSION};
+pub use layout::{TextAlign, TextStyle};
+pub use module::{TextModule, TEXT_MODULE_ID};
+pub use renderer::{TextRenderer, DEFAULT_ATLAS_SIZE};
+
+pub use newengine_ui::text::TextDirection;
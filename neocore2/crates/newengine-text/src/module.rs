@@ -0,0 +1,56 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use newengine_core::assets::AssetManager;
+use newengine_core::{EngineResult, Module, ModuleCtx};
+use std::sync::Arc;
+
+use crate::font::FontImporter;
+use crate::renderer::TextRenderer;
+
+pub const TEXT_MODULE_ID: &str = "text";
+
+/// Registers the font importer and owns the `TextRenderer` resource.
+///
+/// Fonts are added by the application (`TextRenderer::add_font`) once their assets are loaded.
+#[derive(Debug, Default)]
+pub struct TextModule {
+    atlas_size: Option<u32>,
+}
+
+impl TextModule {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Side of the glyph atlas in texels.
+    #[inline]
+    pub fn with_atlas_size(mut self, size: u32) -> Self {
+        self.atlas_size = Some(size);
+        self
+    }
+}
+
+impl<E: Send + 'static> Module<E> for TextModule {
+    fn id(&self) -> &'static str {
+        TEXT_MODULE_ID
+    }
+
+    fn init(&mut self, ctx: &mut ModuleCtx<'_, E>) -> EngineResult<()> {
+        match ctx.resources().get::<AssetManager>() {
+            Some(am) => am.store().add_importer(Arc::new(FontImporter)),
+            None => {
+                log::warn!(target: "text", "text.init AssetManager missing; font import disabled")
+            }
+        }
+
+        if ctx.resources().get::<TextRenderer>().is_none() {
+            let mut renderer = TextRenderer::new();
+            if let Some(size) = self.atlas_size {
+                renderer = renderer.with_atlas_size(size);
+            }
+            ctx.resources_mut().insert(renderer);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,375 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::atlas::{AtlasFull, GlyphAtlas, GlyphBitmap, GlyphEntry, GlyphKey};
+use crate::font::FontAsset;
+use crate::layout::{layout, Face, LaidGlyph, TextStyle};
+
+use ab_glyph::{point, Font, GlyphId, PxScale};
+use newengine_assets::{AssetError, TextureAsset};
+use newengine_core::render::{Extent2D, RenderApi, Sprite, SpriteBatch, SpriteTexture};
+use newengine_core::EngineResult;
+use newengine_ui::draw::{UiDrawCmd, UiDrawList, UiRect, UiTexId, UiVertex};
+use newengine_ui::texture::reserved;
+
+/// Side of the glyph atlas unless configured otherwise.
+pub const DEFAULT_ATLAS_SIZE: u32 = 1024;
+
+/// A glyph of laid-out text resolved against the atlas.
+struct PlacedGlyph {
+    /// Top-left of the bitmap relative to the text block.
+    pos: [f32; 2],
+    entry: GlyphEntry,
+}
+
+/// Text drawing on top of a dynamic glyph atlas.
+///
+/// Fonts are tried in registration order for every character, so register the main face
+/// first and fallbacks (symbols, CJK, ...) after it. Glyphs are rasterized on first use at
+/// whole pixel sizes and packed into one RGBA8 atlas, which reaches the GPU in two ways:
+///
+/// - [`TextRenderer::draw_text`] appends geometry to a [`UiDrawList`] and ships new glyphs as
+///   patches of the UI texture delta, so text shows up in the UI overlay pass.
+/// - [`TextRenderer::draw_label`] projects a world position and pushes the glyphs as sprites
+///   into a [`SpriteBatch`] drawn with [`SpriteBatch::screen_projection`]; the atlas is a
+///   render texture re-uploaded when glyphs were added.
+///
+/// When the atlas is full it is cleared and refilled by the current string; text drawn
+/// earlier in the same frame may show wrong glyphs for that frame.
+pub struct TextRenderer {
+    faces: Vec<Face>,
+    atlas: GlyphAtlas,
+    ui_texture: UiTexId,
+    sprite_texture: Option<SpriteTexture>,
+    laid: Vec<LaidGlyph>,
+    placed: Vec<PlacedGlyph>,
+}
+
+impl Default for TextRenderer {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextRenderer {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            faces: Vec::new(),
+            atlas: GlyphAtlas::new(DEFAULT_ATLAS_SIZE),
+            ui_texture: reserved::TEXT_ATLAS,
+            sprite_texture: None,
+            laid: Vec::new(),
+            placed: Vec::new(),
+        }
+    }
+
+    /// Side of the square atlas in texels. Must be set before first use.
+    #[inline]
+    pub fn with_atlas_size(mut self, size: u32) -> Self {
+        self.atlas = GlyphAtlas::new(size);
+        self
+    }
+
+    /// UI texture id of the atlas (default `reserved::TEXT_ATLAS`).
+    #[inline]
+    pub fn with_ui_texture(mut self, id: UiTexId) -> Self {
+        self.ui_texture = id;
+        self
+    }
+
+    /// Registers a face under `name`; a face with the same name is replaced.
+    pub fn add_font(
+        &mut self,
+        name: impl Into<String>,
+        font: &FontAsset,
+    ) -> Result<(), AssetError> {
+        let name = name.into();
+        let parsed = font.face()?;
+        log::info!(
+            target: "text",
+            "text.font name='{}' glyphs={}",
+            name,
+            parsed.glyph_count()
+        );
+        match self.faces.iter_mut().find(|f| f.name == name) {
+            Some(f) => {
+                f.font = parsed;
+                // Cached glyphs of the old face are keyed by its index.
+                self.atlas.reset();
+            }
+            None => self.faces.push(Face { name, font: parsed }),
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn has_fonts(&self) -> bool {
+        !self.faces.is_empty()
+    }
+
+    #[inline]
+    pub fn ui_texture(&self) -> UiTexId {
+        self.ui_texture
+    }
+
+    /// Size of `text` laid out with `style`, in pixels.
+    pub fn measure(&mut self, text: &str, style: &TextStyle) -> [f32; 2] {
+        layout(&self.faces, text, style, &mut self.laid)
+    }
+
+    /// Appends `text` with its top-left corner at `pos` (pixels) to `list`, clipped to the
+    /// screen. Returns the size of the text block.
+    pub fn draw_text(
+        &mut self,
+        list: &mut UiDrawList,
+        text: &str,
+        pos: [f32; 2],
+        style: &TextStyle,
+    ) -> [f32; 2] {
+        let [w, h] = list.screen_size_px;
+        let clip = UiRect {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: w as f32,
+            max_y: h as f32,
+        };
+        self.draw_text_clipped(list, text, pos, style, clip)
+    }
+
+    /// [`TextRenderer::draw_text`] with an explicit clip rectangle in pixels.
+    pub fn draw_text_clipped(
+        &mut self,
+        list: &mut UiDrawList,
+        text: &str,
+        pos: [f32; 2],
+        style: &TextStyle,
+        clip: UiRect,
+    ) -> [f32; 2] {
+        let size = self.place(text, style);
+        self.atlas
+            .flush_ui(self.ui_texture, &mut list.texture_delta);
+        if self.placed.is_empty() {
+            return size;
+        }
+
+        let atlas = self.atlas.size() as f32;
+        let color = u32::from_le_bytes(
+            style
+                .color
+                .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8),
+        );
+        let mesh = &mut list.mesh;
+        let first_index = mesh.indices.len() as u32;
+        for g in &self.placed {
+            let [tx, ty, tw, th] = g.entry.rect.map(|v| v as f32);
+            let (x0, y0) = (pos[0] + g.pos[0], pos[1] + g.pos[1]);
+            let (x1, y1) = (x0 + tw, y0 + th);
+            let (u0, v0, u1, v1) = (tx / atlas, ty / atlas, (tx + tw) / atlas, (ty + th) / atlas);
+
+            let base = mesh.vertices.len() as u32;
+            mesh.vertices.extend_from_slice(&[
+                UiVertex {
+                    pos: [x0, y0],
+                    uv: [u0, v0],
+                    color,
+                },
+                UiVertex {
+                    pos: [x1, y0],
+                    uv: [u1, v0],
+                    color,
+                },
+                UiVertex {
+                    pos: [x1, y1],
+                    uv: [u1, v1],
+                    color,
+                },
+                UiVertex {
+                    pos: [x0, y1],
+                    uv: [u0, v1],
+                    color,
+                },
+            ]);
+            mesh.indices
+                .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+        let end = mesh.indices.len() as u32;
+
+        // Consecutive text with the same clip shares one command.
+        match mesh.cmds.last_mut() {
+            Some(cmd)
+                if cmd.texture == self.ui_texture
+                    && cmd.clip_rect == clip
+                    && cmd.index_range.end == first_index =>
+            {
+                cmd.index_range.end = end;
+            }
+            _ => mesh.cmds.push(UiDrawCmd {
+                texture: self.ui_texture,
+                clip_rect: clip,
+                index_range: first_index..end,
+            }),
+        }
+        size
+    }
+
+    /// Pushes `text` as a label above the world position `world` into `batch`.
+    ///
+    /// `view_proj` (column-major) is the camera of the 3D pass and `target` the size of the
+    /// pass in pixels; the label keeps its pixel size at any distance and is centered
+    /// horizontally with its bottom edge on the projected point. Draw `batch` with
+    /// `SpriteBatch::screen_projection(target)` after the 3D geometry; labels are not depth
+    /// tested. Returns `false` when the point is behind the camera.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_label(
+        &mut self,
+        r: &mut dyn RenderApi,
+        batch: &mut SpriteBatch,
+        text: &str,
+        world: [f32; 3],
+        view_proj: &[f32; 16],
+        target: Extent2D,
+        style: &TextStyle,
+    ) -> EngineResult<bool> {
+        let m = view_proj;
+        let [x, y, z] = world;
+        let cw = m[3] * x + m[7] * y + m[11] * z + m[15];
+        if cw <= 0.0 {
+            return Ok(false);
+        }
+        let cx = (m[0] * x + m[4] * y + m[8] * z + m[12]) / cw;
+        let cy = (m[1] * x + m[5] * y + m[9] * z + m[13]) / cw;
+        let px = (cx + 1.0) * 0.5 * target.width as f32;
+        let py = (cy + 1.0) * 0.5 * target.height as f32;
+
+        let [w, h] = self.place(text, style);
+        let texture = self.sync_sprite_texture(r, batch)?;
+        let origin = [(px - w * 0.5).round(), (py - h).round()];
+        for g in &self.placed {
+            let [tx, ty, tw, th] = g.entry.rect;
+            batch.push(
+                Sprite::new(texture, [origin[0] + g.pos[0], origin[1] + g.pos[1]])
+                    .with_region(tx, ty, tw, th)
+                    .with_anchor([0.0, 0.0])
+                    .with_tint(style.color)
+                    .with_layer(style.layer),
+            );
+        }
+        Ok(true)
+    }
+
+    /// Destroys the render texture of the atlas. The UI texture is released with
+    /// [`TextRenderer::release_ui`].
+    pub fn destroy(&mut self, r: &mut dyn RenderApi, batch: &mut SpriteBatch) {
+        if let Some(t) = self.sprite_texture.take() {
+            batch.destroy_texture(r, t);
+            self.atlas.mark_gpu_dirty();
+        }
+    }
+
+    /// Frees the UI texture of the atlas through `list`; it is sent again on the next draw.
+    pub fn release_ui(&mut self, list: &mut UiDrawList) {
+        list.texture_delta.free.push(self.ui_texture);
+        self.atlas.mark_ui_full();
+    }
+
+    /// Lays `text` out and resolves its glyphs into `self.placed`, resetting the atlas once
+    /// if it runs out of room.
+    fn place(&mut self, text: &str, style: &TextStyle) -> [f32; 2] {
+        let size = layout(&self.faces, text, style, &mut self.laid);
+        for attempt in 0..2 {
+            match self.resolve() {
+                Ok(()) => break,
+                Err(AtlasFull) if attempt == 0 => {
+                    log::warn!(
+                        target: "text",
+                        "text.atlas full glyphs={} size={}; cleared",
+                        self.atlas.glyph_count(),
+                        self.atlas.size()
+                    );
+                    self.atlas.reset();
+                }
+                Err(AtlasFull) => log::warn!(
+                    target: "text",
+                    "text.atlas too small for one string glyphs={} size={}",
+                    self.laid.len(),
+                    self.atlas.size()
+                ),
+            }
+        }
+        size
+    }
+
+    /// Atlas entries of `self.laid`. Glyphs that do not fit are left out.
+    fn resolve(&mut self) -> Result<(), AtlasFull> {
+        self.placed.clear();
+        let mut full = false;
+        for g in &self.laid {
+            let face = &self.faces[g.key.face as usize];
+            let entry = match self.atlas.get_or_insert(g.key, || rasterize(face, g.key)) {
+                Ok(e) => e,
+                Err(AtlasFull) => {
+                    full = true;
+                    continue;
+                }
+            };
+            if let Some(entry) = entry {
+                self.placed.push(PlacedGlyph {
+                    pos: [g.pen[0] + entry.offset[0], g.pen[1] + entry.offset[1]],
+                    entry,
+                });
+            }
+        }
+        if full {
+            Err(AtlasFull)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Render texture of the atlas, created on first use and re-uploaded when it changed.
+    fn sync_sprite_texture(
+        &mut self,
+        r: &mut dyn RenderApi,
+        batch: &mut SpriteBatch,
+    ) -> EngineResult<SpriteTexture> {
+        match self.sprite_texture {
+            Some(t) => {
+                if self.atlas.take_gpu_dirty() {
+                    r.write_texture(t.texture, 0, self.atlas.pixels())?;
+                }
+                Ok(t)
+            }
+            None => {
+                let size = self.atlas.size();
+                let asset = TextureAsset::rgba8(size, size, self.atlas.pixels().to_vec(), false);
+                let t = batch.upload_texture(r, &asset)?;
+                self.atlas.take_gpu_dirty();
+                self.sprite_texture = Some(t);
+                Ok(t)
+            }
+        }
+    }
+}
+
+/// Coverage bitmap of `key` with the pen at the origin.
+fn rasterize(face: &Face, key: GlyphKey) -> Option<GlyphBitmap> {
+    let glyph = GlyphId(key.glyph)
+        .with_scale_and_position(PxScale::from(key.size_px as f32), point(0.0, 0.0));
+    let outline = face.font.outline_glyph(glyph)?;
+    let b = outline.px_bounds();
+    let width = b.width() as u32;
+    let height = b.height() as u32;
+    let mut coverage = vec![0u8; (width * height) as usize];
+    outline.draw(|x, y, c| {
+        if x < width && y < height {
+            coverage[(y * width + x) as usize] = (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+    });
+    Some(GlyphBitmap {
+        width,
+        height,
+        offset: [b.min.x, b.min.y],
+        coverage,
+    })
+}
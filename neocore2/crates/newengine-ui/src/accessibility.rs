@@ -0,0 +1,149 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Process-wide accessibility options consumed by UI providers and animation code.
+//!
+//! The host (settings service, startup config) writes them with [`set_accessibility`];
+//! providers compare [`accessibility_generation`] once per frame and re-apply on change.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+pub const UI_SCALE_MIN: f32 = 0.5;
+pub const UI_SCALE_MAX: f32 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiAccessibility {
+    /// Global UI scale multiplier applied on top of the OS scale factor.
+    pub ui_scale: f32,
+    /// Forces the high-contrast theme regardless of what markup requests.
+    pub high_contrast: bool,
+    /// Animations and sequencer tweens snap to their end state.
+    pub reduced_motion: bool,
+    /// Exposes the UI tree to screen readers (AccessKit). Read when the window is created.
+    pub screen_reader: bool,
+}
+
+impl Default for UiAccessibility {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.0,
+            high_contrast: false,
+            reduced_motion: false,
+            screen_reader: true,
+        }
+    }
+}
+
+impl UiAccessibility {
+    #[inline]
+    pub fn clamped(mut self) -> Self {
+        self.ui_scale = if self.ui_scale.is_finite() {
+            self.ui_scale.clamp(UI_SCALE_MIN, UI_SCALE_MAX)
+        } else {
+            1.0
+        };
+        self
+    }
+
+    /// Multiplier for animation durations: `0.0` when reduced motion is requested.
+    #[inline]
+    pub fn motion_scale(&self) -> f32 {
+        if self.reduced_motion {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+static CURRENT: OnceLock<RwLock<UiAccessibility>> = OnceLock::new();
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[inline]
+fn current() -> &'static RwLock<UiAccessibility> {
+    CURRENT.get_or_init(|| RwLock::new(UiAccessibility::default()))
+}
+
+#[inline]
+pub fn accessibility() -> UiAccessibility {
+    current().read().map(|g| *g).unwrap_or_default()
+}
+
+/// Replaces the active options. Bumps the generation only when something changed.
+pub fn set_accessibility(value: UiAccessibility) {
+    let value = value.clamped();
+    let Ok(mut g) = current().write() else {
+        return;
+    };
+    if *g != value {
+        *g = value;
+        GENERATION.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+#[inline]
+pub fn accessibility_generation() -> u64 {
+    GENERATION.load(Ordering::Acquire)
+}
+
+/// Shortcut for animation code: `duration * motion_scale()`.
+#[inline]
+pub fn motion_scale() -> f32 {
+    accessibility().motion_scale()
+}
+
+/// Host user event delivered to the UI provider through the platform event loop.
+#[derive(Debug)]
+pub enum UiUserEvent {
+    #[cfg(feature = "provider-egui")]
+    AccessKit(egui_winit::accesskit_winit::Event),
+}
+
+#[cfg(feature = "provider-egui")]
+impl From<egui_winit::accesskit_winit::Event> for UiUserEvent {
+    #[inline]
+    fn from(e: egui_winit::accesskit_winit::Event) -> Self {
+        Self::AccessKit(e)
+    }
+}
+
+/// Black background, white text and outlines, yellow selection/activation.
+#[cfg(feature = "provider-egui")]
+pub(crate) fn high_contrast_visuals() -> egui::Visuals {
+    use egui::{Color32, Stroke};
+
+    let yellow = Color32::from_rgb(255, 230, 0);
+    let mut v = egui::Visuals::dark();
+
+    v.hyperlink_color = Color32::from_rgb(0, 230, 255);
+    v.panel_fill = Color32::BLACK;
+    v.window_fill = Color32::BLACK;
+    v.extreme_bg_color = Color32::BLACK;
+    v.faint_bg_color = Color32::from_gray(24);
+    v.code_bg_color = Color32::from_gray(24);
+    v.window_stroke = Stroke::new(2.0, Color32::WHITE);
+    v.selection.bg_fill = yellow;
+    v.selection.stroke = Stroke::new(2.0, Color32::BLACK);
+
+    for w in [
+        &mut v.widgets.noninteractive,
+        &mut v.widgets.inactive,
+        &mut v.widgets.hovered,
+        &mut v.widgets.active,
+        &mut v.widgets.open,
+    ] {
+        w.bg_fill = Color32::BLACK;
+        w.weak_bg_fill = Color32::BLACK;
+        w.bg_stroke = Stroke::new(1.5, Color32::WHITE);
+        w.fg_stroke = Stroke::new(1.5, Color32::WHITE);
+    }
+    v.widgets.hovered.bg_stroke = Stroke::new(2.5, yellow);
+    v.widgets.active.bg_fill = yellow;
+    v.widgets.active.weak_bg_fill = yellow;
+    v.widgets.active.fg_stroke = Stroke::new(2.0, Color32::BLACK);
+
+    v
+}
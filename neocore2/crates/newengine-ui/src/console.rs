@@ -0,0 +1,216 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Process-wide developer console.
+//!
+//! The active UI provider draws a drop-down overlay (toggled with `~`) over each frame. Lines
+//! entered there run through the installed [`ConsoleBackend`], which the engine points at its
+//! command service; completions come from the same backend.
+
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Oldest output lines are dropped beyond this many.
+const MAX_LINES: usize = 4000;
+/// Oldest history entries are dropped beyond this many.
+const MAX_HISTORY: usize = 256;
+
+/// One completion candidate. `insert` is the whole input line after accepting it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ConsoleSuggestion {
+    #[serde(default)]
+    pub kind: String,
+    #[serde(default)]
+    pub display: String,
+    #[serde(default)]
+    pub insert: String,
+    #[serde(default)]
+    pub help: String,
+    #[serde(default)]
+    pub usage: String,
+}
+
+/// Completions for the current input; `signature` is the usage of the command being typed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ConsoleSuggestions {
+    #[serde(default)]
+    pub signature: String,
+    #[serde(default)]
+    pub items: Vec<ConsoleSuggestion>,
+}
+
+/// Runs console lines and completes partial input. Called on the UI thread.
+pub trait ConsoleBackend: Send + Sync {
+    /// Output of the command on success, the error message otherwise.
+    fn exec(&self, line: &str) -> Result<String, String>;
+
+    fn suggest(&self, input: &str) -> ConsoleSuggestions;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleLineKind {
+    /// Echo of an entered line.
+    Input,
+    Output,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsoleLine {
+    pub kind: ConsoleLineKind,
+    pub text: String,
+}
+
+#[derive(Default)]
+struct Hub {
+    open: bool,
+    lines: VecDeque<ConsoleLine>,
+    /// Bumped on every change to `lines`, so renderers only re-copy them when needed.
+    revision: u64,
+    history: Vec<String>,
+    prefill: Option<String>,
+    backend: Option<Arc<dyn ConsoleBackend>>,
+}
+
+impl Hub {
+    fn push(&mut self, kind: ConsoleLineKind, text: &str) {
+        for l in text.trim_end().lines() {
+            if self.lines.len() >= MAX_LINES {
+                self.lines.pop_front();
+            }
+            self.lines.push_back(ConsoleLine {
+                kind,
+                text: l.to_string(),
+            });
+        }
+        self.revision = self.revision.wrapping_add(1);
+    }
+}
+
+static HUB: OnceLock<Mutex<Hub>> = OnceLock::new();
+
+#[inline]
+fn hub() -> &'static Mutex<Hub> {
+    HUB.get_or_init(|| Mutex::new(Hub::default()))
+}
+
+/// Entry point for driving the developer console overlay.
+pub struct ConsoleApi;
+
+impl ConsoleApi {
+    /// Installs (or with `None`, removes) the backend that runs lines and completes input.
+    pub fn set_backend(backend: Option<Arc<dyn ConsoleBackend>>) {
+        if let Ok(mut g) = hub().lock() {
+            g.backend = backend;
+        }
+    }
+
+    #[inline]
+    pub fn has_backend() -> bool {
+        hub().lock().map(|g| g.backend.is_some()).unwrap_or(false)
+    }
+
+    #[inline]
+    pub fn is_open() -> bool {
+        hub().lock().map(|g| g.open).unwrap_or(false)
+    }
+
+    pub fn set_open(open: bool) {
+        if let Ok(mut g) = hub().lock() {
+            g.open = open;
+        }
+    }
+
+    pub fn toggle() {
+        if let Ok(mut g) = hub().lock() {
+            g.open = !g.open;
+        }
+    }
+
+    /// Opens the console with `line` in the input field, e.g. a command awaiting arguments.
+    pub fn prefill(line: impl Into<String>) {
+        if let Ok(mut g) = hub().lock() {
+            g.open = true;
+            g.prefill = Some(line.into());
+        }
+    }
+
+    /// Called by providers: input requested by [`ConsoleApi::prefill`] since the last call.
+    pub fn take_prefill() -> Option<String> {
+        hub().lock().ok().and_then(|mut g| g.prefill.take())
+    }
+
+    /// Appends output to the console log.
+    pub fn print(kind: ConsoleLineKind, text: &str) {
+        if let Ok(mut g) = hub().lock() {
+            g.push(kind, text);
+        }
+    }
+
+    pub fn clear() {
+        if let Ok(mut g) = hub().lock() {
+            g.lines.clear();
+            g.revision = g.revision.wrapping_add(1);
+        }
+    }
+
+    /// Echoes `line`, records it in the history and runs it through the backend. Returns
+    /// `true` when the command succeeded.
+    pub fn exec(line: &str) -> bool {
+        let line = line.trim();
+        if line.is_empty() {
+            return false;
+        }
+
+        let backend = {
+            let Ok(mut g) = hub().lock() else {
+                return false;
+            };
+            g.push(ConsoleLineKind::Input, &format!("> {line}"));
+            if g.history.last().map(String::as_str) != Some(line) {
+                if g.history.len() >= MAX_HISTORY {
+                    g.history.remove(0);
+                }
+                g.history.push(line.to_string());
+            }
+            g.backend.clone()
+        };
+
+        // The backend may re-enter the console (e.g. a command printing), so run it unlocked.
+        let result = match backend {
+            Some(b) => b.exec(line),
+            None => Err("no console backend installed".to_string()),
+        };
+
+        let ok = result.is_ok();
+        match result {
+            Ok(out) => Self::print(ConsoleLineKind::Output, &out),
+            Err(e) => Self::print(ConsoleLineKind::Error, &format!("ERR: {e}")),
+        }
+        ok
+    }
+
+    /// Completions for `input`; empty without a backend.
+    pub fn suggest(input: &str) -> ConsoleSuggestions {
+        let backend = hub().lock().ok().and_then(|g| g.backend.clone());
+        backend.map(|b| b.suggest(input)).unwrap_or_default()
+    }
+
+    /// Entered lines, oldest first.
+    pub fn history() -> Vec<String> {
+        hub().lock().map(|g| g.history.clone()).unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn revision() -> u64 {
+        hub().lock().map(|g| g.revision).unwrap_or(0)
+    }
+
+    /// The log with its revision, oldest line first.
+    pub fn lines() -> (u64, Vec<ConsoleLine>) {
+        hub()
+            .lock()
+            .map(|g| (g.revision, g.lines.iter().cloned().collect()))
+            .unwrap_or_default()
+    }
+}
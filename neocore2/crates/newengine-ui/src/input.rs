@@ -20,11 +20,21 @@ pub struct UiInputFrame {
     /// Text typed since last `text_take_json` in input plugin.
     pub text: String,
 
-    /// IME preedit is typically frame-local. Provided if you choose to expose it later.
+    /// Current IME composition; empty when nothing is being composed.
     pub ime_preedit: String,
 
     /// IME commit text (taken via `ime_commit_take_json`).
     pub ime_commit: String,
+
+    /// The platform IME is active (between `Ime::Enabled` and `Ime::Disabled`).
+    pub ime_enabled: bool,
+}
+
+/// Keyboard focus move requested with Tab / Shift+Tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiFocusStep {
+    Next,
+    Prev,
 }
 
 impl UiInputFrame {
@@ -48,3 +58,19 @@ impl UiInputFrame {
         self.mouse_pressed.contains(&btn)
     }
 }
+
+/// Focus steps are taken out of the toolkit's input by the provider and handed to the UI build
+/// through the context, so the markup `UiState` owns the tab order.
+#[cfg(feature = "provider-egui")]
+const FOCUS_STEPS_KEY: &str = "newengine.ui.focus_steps";
+
+#[cfg(feature = "provider-egui")]
+pub(crate) fn put_focus_steps(ctx: &egui::Context, steps: Vec<UiFocusStep>) {
+    ctx.data_mut(|d| d.insert_temp(egui::Id::new(FOCUS_STEPS_KEY), steps));
+}
+
+#[cfg(feature = "egui")]
+pub(crate) fn take_focus_steps(ctx: &egui::Context) -> Vec<UiFocusStep> {
+    ctx.data_mut(|d| d.remove_temp::<Vec<UiFocusStep>>(egui::Id::new(FOCUS_STEPS_KEY)))
+        .unwrap_or_default()
+}
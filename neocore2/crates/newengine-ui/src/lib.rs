@@ -1,18 +1,30 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+pub mod accessibility;
+pub mod console;
 pub mod draw;
+pub mod text;
 pub mod texture;
 
 pub mod input;
+pub mod notify;
 pub mod provider;
 pub mod providers;
 
 pub mod markup;
 
-pub use input::UiInputFrame;
+pub use accessibility::{
+    accessibility, accessibility_generation, set_accessibility, UiAccessibility, UiUserEvent,
+};
+pub use console::{
+    ConsoleApi, ConsoleBackend, ConsoleLine, ConsoleLineKind, ConsoleSuggestion, ConsoleSuggestions,
+};
+pub use input::{UiFocusStep, UiInputFrame};
+pub use notify::{NotifyApi, Toast, ToastAction, ToastLevel};
 pub use provider::{
     UiBuildFn, UiFrameDesc, UiFrameOutput, UiProvider, UiProviderKind, UiProviderOptions,
 };
 pub use providers::create_provider;
+pub use text::{set_fonts, Script, TextDirection, UiFonts};
 
-pub use markup::{UiMarkupDoc, UiState};
+pub use markup::{UiBindings, UiMarkupDoc, UiState, UiValue};
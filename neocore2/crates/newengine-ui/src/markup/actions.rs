@@ -2,10 +2,68 @@
 
 use smallvec::SmallVec;
 
+use crate::markup::element::XmlElement;
 use crate::markup::state::UiEventKind;
 
+/// One markup action, as written in `on_click` / `on_change` / `on_submit` / `on_select` /
+/// `on_activate` / `on`.
+///
+/// `console:render.wireframe on` runs a console command, `emit:MyEvent` (optionally followed
+/// by an argument, `emit:SetSpeed 2`) publishes a named event. In both, `$value` is replaced
+/// by the widget value of the event. Anything else is an app-defined action string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UiAction {
+    Console(String),
+    Emit { name: String, arg: Option<String> },
+    Custom(String),
+}
+
+impl UiAction {
+    pub fn parse(s: &str) -> Self {
+        let s = s.trim();
+        let Some((scheme, rest)) = s.split_once(':') else {
+            return UiAction::Custom(s.to_string());
+        };
+        let rest = rest.trim();
+
+        match scheme.trim().to_ascii_lowercase().as_str() {
+            "console" | "cmd" if !rest.is_empty() => UiAction::Console(rest.to_string()),
+            "emit" if !rest.is_empty() => {
+                let (name, arg) = match rest.split_once(char::is_whitespace) {
+                    Some((n, a)) => (n, Some(a.trim().to_string()).filter(|a| !a.is_empty())),
+                    None => (rest, None),
+                };
+                UiAction::Emit {
+                    name: name.to_string(),
+                    arg,
+                }
+            }
+            _ => UiAction::Custom(s.to_string()),
+        }
+    }
+
+    /// Replaces `$value` with `value` (an empty string when the event has none).
+    pub fn with_value(self, value: Option<&str>) -> Self {
+        let fill = |s: String| {
+            if s.contains("$value") {
+                s.replace("$value", value.unwrap_or(""))
+            } else {
+                s
+            }
+        };
+        match self {
+            UiAction::Console(c) => UiAction::Console(fill(c)),
+            UiAction::Emit { name, arg } => UiAction::Emit {
+                name,
+                arg: arg.map(fill),
+            },
+            custom => custom,
+        }
+    }
+}
+
 pub(crate) fn parse_actions_for(
-    node: &roxmltree::Node<'_, '_>,
+    node: &XmlElement,
     kind: UiEventKind,
     out: &mut SmallVec<[String; 2]>,
 ) {
@@ -25,6 +83,16 @@ pub(crate) fn parse_actions_for(
                 split_actions_into(v, out);
             }
         }
+        UiEventKind::Select => {
+            if let Some(v) = node.attribute("on_select") {
+                split_actions_into(v, out);
+            }
+        }
+        UiEventKind::Activate => {
+            if let Some(v) = node.attribute("on_activate") {
+                split_actions_into(v, out);
+            }
+        }
     }
 
     if let Some(v) = node.attribute("on") {
@@ -44,6 +112,8 @@ pub(crate) fn parse_actions_for(
                 "click" | "on_click" => UiEventKind::Click,
                 "change" | "on_change" => UiEventKind::Change,
                 "submit" | "on_submit" => UiEventKind::Submit,
+                "select" | "on_select" => UiEventKind::Select,
+                "activate" | "on_activate" => UiEventKind::Activate,
                 _ => continue,
             };
 
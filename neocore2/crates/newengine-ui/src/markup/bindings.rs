@@ -0,0 +1,380 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Reactive values for markup bindings.
+//!
+//! `{{player.health}}` in a text attribute reads `player.health` from the state's
+//! [`UiBindings`]; `{{speed:.1}}` formats a number with one decimal. A textbox with
+//! `bind="{{player.name}}"` also writes edits back. Modules may set values from any thread
+//! every frame: writing an unchanged value is a no-op, and widgets re-format their text only
+//! when a value they reference changed.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use ahash::AHashMap;
+use smallvec::SmallVec;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiValue {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl UiValue {
+    /// Formats with an optional spec: `.N` sets the decimals of a number.
+    pub fn format(&self, spec: Option<&str>) -> String {
+        let precision = spec
+            .and_then(|s| s.trim().strip_prefix('.'))
+            .and_then(|p| p.parse::<usize>().ok());
+        match (self, precision) {
+            (UiValue::Float(v), Some(p)) => format!("{v:.p$}"),
+            (UiValue::Int(v), Some(p)) if p > 0 => format!("{:.p$}", *v as f64),
+            _ => self.to_string(),
+        }
+    }
+
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            UiValue::Int(v) => Some(*v as f64),
+            UiValue::Float(v) => Some(*v),
+            UiValue::Str(s) => s.trim().parse().ok(),
+            UiValue::Bool(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for UiValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UiValue::Str(s) => f.write_str(s),
+            UiValue::Int(v) => write!(f, "{v}"),
+            UiValue::Float(v) => write!(f, "{v}"),
+            UiValue::Bool(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl From<String> for UiValue {
+    #[inline]
+    fn from(v: String) -> Self {
+        UiValue::Str(v)
+    }
+}
+
+impl From<&str> for UiValue {
+    #[inline]
+    fn from(v: &str) -> Self {
+        UiValue::Str(v.to_string())
+    }
+}
+
+impl From<bool> for UiValue {
+    #[inline]
+    fn from(v: bool) -> Self {
+        UiValue::Bool(v)
+    }
+}
+
+impl From<i32> for UiValue {
+    #[inline]
+    fn from(v: i32) -> Self {
+        UiValue::Int(i64::from(v))
+    }
+}
+
+impl From<i64> for UiValue {
+    #[inline]
+    fn from(v: i64) -> Self {
+        UiValue::Int(v)
+    }
+}
+
+impl From<u32> for UiValue {
+    #[inline]
+    fn from(v: u32) -> Self {
+        UiValue::Int(i64::from(v))
+    }
+}
+
+impl From<usize> for UiValue {
+    #[inline]
+    fn from(v: usize) -> Self {
+        UiValue::Int(v as i64)
+    }
+}
+
+impl From<f32> for UiValue {
+    #[inline]
+    fn from(v: f32) -> Self {
+        UiValue::Float(f64::from(v))
+    }
+}
+
+impl From<f64> for UiValue {
+    #[inline]
+    fn from(v: f64) -> Self {
+        UiValue::Float(v)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Store {
+    /// key -> (value, revision of the last change)
+    values: AHashMap<String, (UiValue, u64)>,
+    revision: u64,
+    /// Revision of the last removal; text showing a removed key must be re-rendered.
+    removed: u64,
+}
+
+impl Store {
+    fn set(&mut self, key: String, value: UiValue) -> bool {
+        if self.values.get(&key).is_some_and(|(v, _)| *v == value) {
+            return false;
+        }
+        self.revision += 1;
+        self.values.insert(key, (value, self.revision));
+        true
+    }
+
+    fn remove(&mut self, key: &str) -> bool {
+        if self.values.remove(key).is_none() {
+            return false;
+        }
+        self.revision += 1;
+        self.removed = self.revision;
+        true
+    }
+}
+
+/// Shared key/value store behind `{{...}}` bindings. Cloning shares the store.
+#[derive(Debug, Clone, Default)]
+pub struct UiBindings {
+    inner: Arc<Mutex<Store>>,
+}
+
+static SHARED: OnceLock<UiBindings> = OnceLock::new();
+
+impl UiBindings {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process-wide store for modules that do not own the UI state; hosts attach it with
+    /// `UiState::set_bindings`.
+    #[inline]
+    pub fn shared() -> UiBindings {
+        SHARED.get_or_init(UiBindings::new).clone()
+    }
+
+    /// Sets `key`; returns `false` if the value was already current.
+    pub fn set(&self, key: impl Into<String>, value: impl Into<UiValue>) -> bool {
+        let Ok(mut g) = self.inner.lock() else {
+            return false;
+        };
+        g.set(key.into(), value.into())
+    }
+
+    /// Sets every scalar in `value` under `prefix`, joining object keys with `.` and array
+    /// indices as `prefix.0`, `prefix.1`, ... `null` removes the key.
+    pub fn set_json(&self, prefix: &str, value: &serde_json::Value) {
+        let Ok(mut g) = self.inner.lock() else {
+            return;
+        };
+        set_json_into(&mut g, prefix, value);
+    }
+
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<UiValue> {
+        let g = self.inner.lock().ok()?;
+        g.values.get(key).map(|(v, _)| v.clone())
+    }
+
+    pub fn remove(&self, key: &str) -> bool {
+        let Ok(mut g) = self.inner.lock() else {
+            return false;
+        };
+        g.remove(key)
+    }
+
+    /// Bumped by every change; compare against a previous value to detect updates.
+    #[inline]
+    pub fn revision(&self) -> u64 {
+        self.inner.lock().map(|g| g.revision).unwrap_or(0)
+    }
+
+    /// Values changed after revision `since`, and the current revision.
+    pub fn changed_since(&self, since: u64) -> (u64, Vec<(String, UiValue)>) {
+        let Ok(g) = self.inner.lock() else {
+            return (since, Vec::new());
+        };
+        let changed = g
+            .values
+            .iter()
+            .filter(|(_, (_, rev))| *rev > since)
+            .map(|(k, (v, _))| (k.clone(), v.clone()))
+            .collect();
+        (g.revision, changed)
+    }
+
+    /// All values, formatted.
+    pub fn snapshot(&self) -> AHashMap<String, String> {
+        let Ok(g) = self.inner.lock() else {
+            return AHashMap::new();
+        };
+        g.values
+            .iter()
+            .map(|(k, (v, _))| (k.clone(), v.to_string()))
+            .collect()
+    }
+
+    /// Newest change among `keys`, including any removal.
+    pub(crate) fn keys_revision(&self, keys: &[String]) -> u64 {
+        let Ok(g) = self.inner.lock() else {
+            return 0;
+        };
+        keys.iter()
+            .filter_map(|k| g.values.get(k).map(|(_, rev)| *rev))
+            .fold(g.removed, u64::max)
+    }
+
+    /// Last change of `key`; `0` if it is not set.
+    #[inline]
+    pub(crate) fn key_revision(&self, key: &str) -> u64 {
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|g| g.values.get(key).map(|(_, rev)| *rev))
+            .unwrap_or(0)
+    }
+
+    /// Expands `{{key}}` references in `src`. Unknown keys are left as written.
+    pub(crate) fn render(&self, src: &str) -> (String, u64) {
+        let Ok(g) = self.inner.lock() else {
+            return (src.to_string(), 0);
+        };
+
+        let mut out = String::with_capacity(src.len());
+        for part in parse_template(src) {
+            match part {
+                TemplatePart::Text(t) => out.push_str(t),
+                TemplatePart::Binding { raw, key, spec } => match g.values.get(key) {
+                    Some((v, _)) => out.push_str(&v.format(spec)),
+                    None => out.push_str(raw),
+                },
+            }
+        }
+        (out, g.revision)
+    }
+}
+
+fn set_json_into(g: &mut Store, prefix: &str, value: &serde_json::Value) {
+    use serde_json::Value;
+
+    let child = |k: &str| {
+        if prefix.is_empty() {
+            k.to_string()
+        } else {
+            format!("{prefix}.{k}")
+        }
+    };
+
+    match value {
+        Value::Null => {
+            g.remove(prefix);
+        }
+        Value::Bool(b) => {
+            g.set(prefix.to_string(), UiValue::Bool(*b));
+        }
+        Value::Number(n) => {
+            let v = match n.as_i64() {
+                Some(i) => UiValue::Int(i),
+                None => UiValue::Float(n.as_f64().unwrap_or(0.0)),
+            };
+            g.set(prefix.to_string(), v);
+        }
+        Value::String(s) => {
+            g.set(prefix.to_string(), UiValue::Str(s.clone()));
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                set_json_into(g, &child(&i.to_string()), v);
+            }
+        }
+        Value::Object(map) => {
+            for (k, v) in map.iter() {
+                set_json_into(g, &child(k), v);
+            }
+        }
+    }
+}
+
+pub(crate) enum TemplatePart<'a> {
+    Text(&'a str),
+    Binding {
+        raw: &'a str,
+        key: &'a str,
+        spec: Option<&'a str>,
+    },
+}
+
+#[inline]
+pub(crate) fn has_bindings(src: &str) -> bool {
+    src.contains("{{")
+}
+
+/// Splits `src` into literal text and `{{key}}` / `{{key:spec}}` references. An unclosed
+/// `{{` is kept as text.
+pub(crate) fn parse_template(src: &str) -> SmallVec<[TemplatePart<'_>; 4]> {
+    let mut out = SmallVec::new();
+    let mut rest = src;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        if start > 0 {
+            out.push(TemplatePart::Text(&rest[..start]));
+        }
+        let inner = after[..end].trim();
+        let (key, spec) = match inner.split_once(':') {
+            Some((k, s)) => (k.trim(), Some(s.trim())),
+            None => (inner, None),
+        };
+        out.push(TemplatePart::Binding {
+            raw: &rest[start..start + 2 + end + 2],
+            key,
+            spec,
+        });
+        rest = &after[end + 2..];
+    }
+    if !rest.is_empty() {
+        out.push(TemplatePart::Text(rest));
+    }
+
+    out
+}
+
+/// Keys referenced by `src`, without duplicates.
+pub(crate) fn template_keys(src: &str) -> SmallVec<[String; 2]> {
+    let mut keys = SmallVec::<[String; 2]>::new();
+    for part in parse_template(src) {
+        if let TemplatePart::Binding { key, .. } = part {
+            if !keys.iter().any(|k| k == key) {
+                keys.push(key.to_string());
+            }
+        }
+    }
+    keys
+}
+
+/// Key of a two-way `bind="{{key}}"` attribute.
+#[inline]
+pub(crate) fn bind_key(bind: &str) -> Option<&str> {
+    let inner = bind.trim().strip_prefix("{{")?.strip_suffix("}}")?.trim();
+    (!inner.is_empty() && !inner.contains("{{")).then_some(inner)
+}
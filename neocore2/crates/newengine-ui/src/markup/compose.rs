@@ -0,0 +1,298 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use ahash::AHashMap;
+use roxmltree::Document;
+
+use crate::markup::element::XmlElement;
+use crate::markup::error::UiMarkupError;
+use crate::markup::substitute::substitute_params;
+
+/// Nesting guard for includes + component instantiation.
+const MAX_DEPTH: usize = 32;
+
+/// Loads the XML text of an included file by logical path.
+pub(crate) type IncludeLoader<'a> = dyn FnMut(&str) -> Result<String, UiMarkupError> + 'a;
+
+#[derive(Debug, Clone)]
+struct ComponentDef {
+    /// `(name, default)`; `None` default means the parameter is required.
+    params: Vec<(String, Option<String>)>,
+    body: Vec<XmlElement>,
+    origin: String,
+}
+
+/// Expands composition elements into plain markup:
+///
+/// - `<include src="ui/widgets/row.xml" params="a=1;b=2"/>` splices the children of the
+///   included file's root; extra attributes are parameters too.
+/// - `<component name="row" params="label, action=noop">...</component>` defines a reusable
+///   fragment (in the document or in any included file).
+/// - `<use component="row" label="Save"/>` instantiates it; `${name}` in attributes is
+///   replaced at load time and child elements of `<use>`/`<include>` fill `<slot/>`.
+pub(crate) struct Composer<'a> {
+    load: &'a mut IncludeLoader<'a>,
+    components: AHashMap<String, ComponentDef>,
+    stack: Vec<String>,
+    includes: Vec<String>,
+}
+
+impl<'a> Composer<'a> {
+    #[inline]
+    pub(crate) fn new(load: &'a mut IncludeLoader<'a>) -> Self {
+        Self {
+            load,
+            components: AHashMap::new(),
+            stack: Vec::new(),
+            includes: Vec::new(),
+        }
+    }
+
+    /// Included logical paths in first-use order (for dependency tracking).
+    #[inline]
+    pub(crate) fn into_includes(self) -> Vec<String> {
+        self.includes
+    }
+
+    pub(crate) fn compose_root(
+        &mut self,
+        mut root: XmlElement,
+        origin: &str,
+    ) -> Result<XmlElement, UiMarkupError> {
+        self.collect_components(&mut root.children, origin)?;
+
+        self.stack.push(origin.to_string());
+        let children = std::mem::take(&mut root.children);
+        let expanded = self.expand_list(children, &AHashMap::new(), &[], origin);
+        self.stack.pop();
+
+        root.children = expanded?;
+        Ok(root)
+    }
+
+    fn collect_components(
+        &mut self,
+        list: &mut Vec<XmlElement>,
+        origin: &str,
+    ) -> Result<(), UiMarkupError> {
+        let mut kept = Vec::with_capacity(list.len());
+
+        for el in list.drain(..) {
+            if el.tag != "component" {
+                kept.push(el);
+                continue;
+            }
+
+            let name = el
+                .attribute("name")
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .ok_or_else(|| {
+                    UiMarkupError::Invalid(format!("{origin}: <component> requires name"))
+                })?
+                .to_string();
+
+            let params = el.attribute("params").map(parse_param_decls).unwrap_or_default();
+
+            self.components.insert(
+                name,
+                ComponentDef {
+                    params,
+                    body: el.children,
+                    origin: origin.to_string(),
+                },
+            );
+        }
+
+        *list = kept;
+        Ok(())
+    }
+
+    fn expand_list(
+        &mut self,
+        list: Vec<XmlElement>,
+        params: &AHashMap<String, String>,
+        slot: &[XmlElement],
+        origin: &str,
+    ) -> Result<Vec<XmlElement>, UiMarkupError> {
+        let mut out = Vec::with_capacity(list.len());
+        for el in list {
+            self.expand_into(el, params, slot, origin, &mut out)?;
+        }
+        Ok(out)
+    }
+
+    fn expand_into(
+        &mut self,
+        mut el: XmlElement,
+        params: &AHashMap<String, String>,
+        slot: &[XmlElement],
+        origin: &str,
+        out: &mut Vec<XmlElement>,
+    ) -> Result<(), UiMarkupError> {
+        if self.stack.len() > MAX_DEPTH {
+            return Err(UiMarkupError::Invalid(format!(
+                "composition nested deeper than {MAX_DEPTH} levels at '{origin}': {}",
+                self.stack.join(" -> ")
+            )));
+        }
+
+        for (_, v) in el.attrs.iter_mut() {
+            *v = substitute_params(v, params);
+        }
+
+        match el.tag.as_str() {
+            "include" => {
+                let src = el
+                    .attribute("src")
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .ok_or_else(|| UiMarkupError::Invalid(format!("{origin}: <include> requires src")))?;
+                let path = resolve_include_path(origin, src);
+
+                if self.stack.iter().any(|p| p == &path) {
+                    return Err(UiMarkupError::Include {
+                        path,
+                        reason: format!("cycle: {}", self.stack.join(" -> ")),
+                    });
+                }
+
+                let mut inc_params = el.attribute("params").map(parse_param_values).unwrap_or_default();
+                for (k, v) in el.attrs.iter() {
+                    if k != "src" && k != "params" {
+                        inc_params.insert(k.clone(), v.clone());
+                    }
+                }
+
+                let slot_children = self.expand_list(std::mem::take(&mut el.children), params, slot, origin)?;
+
+                let text = (self.load)(&path).map_err(|e| UiMarkupError::Include {
+                    path: path.clone(),
+                    reason: e.to_string(),
+                })?;
+                if !self.includes.iter().any(|p| p == &path) {
+                    self.includes.push(path.clone());
+                }
+
+                let doc = Document::parse(&text).map_err(|e| UiMarkupError::Include {
+                    path: path.clone(),
+                    reason: format!("xml parse failed: {e}"),
+                })?;
+                let mut inc_root = XmlElement::from_node(doc.root_element());
+                self.collect_components(&mut inc_root.children, &path)?;
+
+                self.stack.push(path.clone());
+                let expanded = self.expand_list(inc_root.children, &inc_params, &slot_children, &path);
+                self.stack.pop();
+
+                out.extend(expanded?);
+            }
+            "use" => {
+                let name = el
+                    .attribute("component")
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .ok_or_else(|| UiMarkupError::Invalid(format!("{origin}: <use> requires component")))?
+                    .to_string();
+
+                let def = self.components.get(&name).cloned().ok_or_else(|| {
+                    UiMarkupError::Invalid(format!("{origin}: unknown component '{name}'"))
+                })?;
+
+                let frame = format!("component:{name}");
+                if self.stack.iter().any(|p| p == &frame) {
+                    return Err(UiMarkupError::Invalid(format!(
+                        "recursive component '{name}': {}",
+                        self.stack.join(" -> ")
+                    )));
+                }
+
+                let mut args: AHashMap<String, String> = AHashMap::new();
+                for (k, default) in def.params.iter() {
+                    if let Some(d) = default {
+                        args.insert(k.clone(), d.clone());
+                    }
+                }
+                for (k, v) in el.attrs.iter() {
+                    if k != "component" {
+                        args.insert(k.clone(), v.clone());
+                    }
+                }
+                if let Some((missing, _)) = def.params.iter().find(|(k, _)| !args.contains_key(k)) {
+                    return Err(UiMarkupError::Invalid(format!(
+                        "{origin}: component '{name}' requires parameter '{missing}'"
+                    )));
+                }
+
+                let slot_children = self.expand_list(std::mem::take(&mut el.children), params, slot, origin)?;
+
+                self.stack.push(frame);
+                let expanded = self.expand_list(def.body, &args, &slot_children, &def.origin);
+                self.stack.pop();
+
+                out.extend(expanded?);
+            }
+            "slot" => {
+                out.extend(slot.iter().cloned());
+            }
+            _ => {
+                el.children = self.expand_list(std::mem::take(&mut el.children), params, slot, origin)?;
+                out.push(el);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// `src` is asset-root relative; `./` and `../` prefixes resolve against the including file.
+fn resolve_include_path(origin: &str, src: &str) -> String {
+    if !(src.starts_with("./") || src.starts_with("../")) {
+        return src.to_string();
+    }
+
+    let mut parts: Vec<&str> = origin.split('/').collect();
+    parts.pop();
+
+    for seg in src.split('/') {
+        match seg {
+            "." | "" => {}
+            ".." => {
+                parts.pop();
+            }
+            s => parts.push(s),
+        }
+    }
+
+    parts.join("/")
+}
+
+/// `"a=1; b=two"` -> `{a: "1", b: "two"}`.
+fn parse_param_values(s: &str) -> AHashMap<String, String> {
+    let mut out = AHashMap::new();
+    for chunk in s.split(';') {
+        let Some((k, v)) = chunk.split_once('=') else {
+            continue;
+        };
+        let k = k.trim();
+        if !k.is_empty() {
+            out.insert(k.to_string(), v.trim().to_string());
+        }
+    }
+    out
+}
+
+/// `"label, action=noop"` -> `[("label", None), ("action", Some("noop"))]`.
+fn parse_param_decls(s: &str) -> Vec<(String, Option<String>)> {
+    s.split([',', ';'])
+        .filter_map(|chunk| {
+            let chunk = chunk.trim();
+            if chunk.is_empty() {
+                return None;
+            }
+            Some(match chunk.split_once('=') {
+                Some((k, v)) => (k.trim().to_string(), Some(v.trim().to_string())),
+                None => (chunk.to_string(), None),
+            })
+        })
+        .collect()
+}
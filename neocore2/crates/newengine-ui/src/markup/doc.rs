@@ -5,8 +5,10 @@ use std::time::{Duration, Instant};
 
 use roxmltree::Document;
 
-use newengine_assets::{AssetKey, AssetState, AssetStore, TextReader};
+use newengine_assets::{AssetId, AssetKey, AssetState, AssetStore, TextReader};
 
+use crate::markup::compose::Composer;
+use crate::markup::element::XmlElement;
 use crate::markup::error::UiMarkupError;
 use crate::markup::parser::{parse_theme, parse_ui_root};
 use crate::markup::theme::UiThemeDesc;
@@ -16,9 +18,15 @@ use crate::markup::ui_node::UiNode;
 pub struct UiMarkupDoc {
     pub(crate) root: UiNode,
     pub(crate) theme: UiThemeDesc,
+    source: Option<AssetId>,
+    includes: Vec<String>,
+    include_ids: Vec<AssetId>,
 }
 
 impl UiMarkupDoc {
+    /// Loads and composes a document. Every `<include>` is loaded through the same store and
+    /// linked as a dependency of the document asset, so editing an included file marks the
+    /// document dirty (see `AssetStore::is_dirty` / `AssetEvent::DependencyChanged`).
     pub fn load_from_store<P>(
         store: &AssetStore,
         mut pump: P,
@@ -28,56 +36,49 @@ impl UiMarkupDoc {
     where
         P: FnMut(),
     {
-        let key = AssetKey::new(logical_path, 0);
-
-        let id = store
-            .load(key)
-            .map_err(|e| UiMarkupError::Enqueue(e.to_string()))?;
-
-        let t0 = Instant::now();
-        let mut spin: u32 = 0;
-
-        loop {
-            pump();
-
-            match store.state(id) {
-                AssetState::Ready => break,
-                AssetState::Failed(msg) => return Err(UiMarkupError::Failed(msg.to_string())),
-                AssetState::Loading | AssetState::Unloaded => {}
-            }
-
-            if t0.elapsed() >= timeout {
-                return Err(UiMarkupError::Timeout {
-                    path: logical_path.to_string(),
-                });
-            }
-
-            spin = spin.saturating_add(1);
-            if spin < 32 {
-                thread::yield_now();
-            } else if spin < 128 {
-                thread::sleep(Duration::from_millis(1));
-            } else {
-                thread::sleep(Duration::from_millis(3));
-            }
-        }
-
-        let blob = store.get_blob(id).ok_or(UiMarkupError::BlobMissing)?;
-
-        let doc = TextReader::from_blob_parts(&blob.meta_json, &blob.payload)
-            .map_err(|e| UiMarkupError::TextRead(e.to_string()))?;
-
-        Self::parse(&doc.text)
+        let (id, text) = load_text(store, &mut pump, logical_path, timeout)?;
+
+        let mut include_ids: Vec<AssetId> = Vec::new();
+        let mut load = |path: &str| -> Result<String, UiMarkupError> {
+            let (inc_id, text) = load_text(store, &mut pump, path, timeout)?;
+            include_ids.push(inc_id);
+            Ok(text)
+        };
+
+        let (root, theme, includes) = compose_and_parse(&text, logical_path, &mut load)?;
+
+        include_ids.sort();
+        include_ids.dedup();
+        store.link_dependencies(id, include_ids.iter().copied());
+
+        Ok(Self {
+            root,
+            theme,
+            source: Some(id),
+            includes,
+            include_ids,
+        })
     }
 
+    /// Parses a standalone document. Components are supported; `<include>` requires
+    /// `load_from_store`.
     pub fn parse(xml_text: &str) -> Result<Self, UiMarkupError> {
-        let parsed =
-            Document::parse(xml_text).map_err(|e| UiMarkupError::XmlParse(e.to_string()))?;
-
-        let root = parse_ui_root(&parsed).map_err(UiMarkupError::Invalid)?;
-        let theme = parse_theme(&parsed);
-
-        Ok(Self { root, theme })
+        let mut load = |path: &str| -> Result<String, UiMarkupError> {
+            Err(UiMarkupError::Include {
+                path: path.to_string(),
+                reason: "includes require an asset store".to_string(),
+            })
+        };
+
+        let (root, theme, includes) = compose_and_parse(xml_text, "", &mut load)?;
+
+        Ok(Self {
+            root,
+            theme,
+            source: None,
+            includes,
+            include_ids: Vec::new(),
+        })
     }
 
     #[cfg(feature = "egui")]
@@ -89,4 +90,91 @@ impl UiMarkupDoc {
     pub fn theme(&self) -> &UiThemeDesc {
         &self.theme
     }
-}
\ No newline at end of file
+
+    /// Asset id of the document source (None for `parse`).
+    #[inline]
+    pub fn source(&self) -> Option<AssetId> {
+        self.source
+    }
+
+    /// Logical paths of all included files, in first-use order.
+    #[inline]
+    pub fn includes(&self) -> &[String] {
+        &self.includes
+    }
+
+    /// True if `id` is the document source or one of its includes (hot-reload filter).
+    #[inline]
+    pub fn depends_on(&self, id: AssetId) -> bool {
+        self.source == Some(id) || self.include_ids.contains(&id)
+    }
+}
+
+fn compose_and_parse(
+    xml_text: &str,
+    origin: &str,
+    load: &mut dyn FnMut(&str) -> Result<String, UiMarkupError>,
+) -> Result<(UiNode, UiThemeDesc, Vec<String>), UiMarkupError> {
+    let parsed = Document::parse(xml_text).map_err(|e| UiMarkupError::XmlParse(e.to_string()))?;
+    let element = XmlElement::from_node(parsed.root_element());
+
+    let mut composer = Composer::new(load);
+    let composed = composer.compose_root(element, origin)?;
+    let includes = composer.into_includes();
+
+    let root = parse_ui_root(&composed).map_err(UiMarkupError::Invalid)?;
+    let theme = parse_theme(&composed);
+
+    Ok((root, theme, includes))
+}
+
+fn load_text<P>(
+    store: &AssetStore,
+    pump: &mut P,
+    logical_path: &str,
+    timeout: Duration,
+) -> Result<(AssetId, String), UiMarkupError>
+where
+    P: FnMut(),
+{
+    let key = AssetKey::new(logical_path, 0);
+
+    let id = store
+        .load(key)
+        .map_err(|e| UiMarkupError::Enqueue(e.to_string()))?;
+
+    let t0 = Instant::now();
+    let mut spin: u32 = 0;
+
+    loop {
+        pump();
+
+        match store.state(id) {
+            AssetState::Ready => break,
+            AssetState::Failed(msg) => return Err(UiMarkupError::Failed(msg.to_string())),
+            AssetState::Loading | AssetState::Unloaded => {}
+        }
+
+        if t0.elapsed() >= timeout {
+            return Err(UiMarkupError::Timeout {
+                path: logical_path.to_string(),
+            });
+        }
+
+        spin = spin.saturating_add(1);
+        if spin < 32 {
+            thread::yield_now();
+        } else if spin < 128 {
+            thread::sleep(Duration::from_millis(1));
+        } else {
+            thread::sleep(Duration::from_millis(3));
+        }
+    }
+
+    let blob = store.get_blob(id).ok_or(UiMarkupError::BlobMissing)?;
+
+    let doc = TextReader::from_blob_parts(&blob.meta_json, &blob.payload)
+        .map_err(|e| UiMarkupError::TextRead(e.to_string()))?;
+
+    Ok((id, doc.text))
+}
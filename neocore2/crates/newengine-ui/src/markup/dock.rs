@@ -0,0 +1,294 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use serde::{Deserialize, Serialize};
+
+/// Axis of a [`DockNode::Split`]. `Horizontal` places `first` left of `second`, `Vertical`
+/// places it above.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DockSplit {
+    Horizontal,
+    Vertical,
+}
+
+impl DockSplit {
+    #[inline]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "horizontal" | "h" | "row" => Some(DockSplit::Horizontal),
+            "vertical" | "v" | "column" | "col" => Some(DockSplit::Vertical),
+            _ => None,
+        }
+    }
+}
+
+/// Where a panel goes relative to the tab group of its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DockTarget {
+    /// Another tab in the same group.
+    Tab,
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// Layout tree of a `<dock>`. Leaves are tab groups holding panel ids.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DockNode {
+    Split {
+        dir: DockSplit,
+        /// Share of the space taken by `first`, in `0.05..=0.95`.
+        ratio: f32,
+        first: Box<DockNode>,
+        second: Box<DockNode>,
+    },
+    Tabs {
+        tabs: Vec<String>,
+        #[serde(default)]
+        active: usize,
+    },
+}
+
+impl DockNode {
+    #[inline]
+    pub fn tabs(panel: impl Into<String>) -> Self {
+        DockNode::Tabs {
+            tabs: vec![panel.into()],
+            active: 0,
+        }
+    }
+
+    #[inline]
+    pub fn split(dir: DockSplit, ratio: f32, first: DockNode, second: DockNode) -> Self {
+        DockNode::Split {
+            dir,
+            ratio: ratio.clamp(0.05, 0.95),
+            first: Box::new(first),
+            second: Box::new(second),
+        }
+    }
+
+    pub fn contains(&self, panel: &str) -> bool {
+        match self {
+            DockNode::Split { first, second, .. } => {
+                first.contains(panel) || second.contains(panel)
+            }
+            DockNode::Tabs { tabs, .. } => tabs.iter().any(|t| t == panel),
+        }
+    }
+
+    fn collect_panels(&self, out: &mut Vec<String>) {
+        match self {
+            DockNode::Split { first, second, .. } => {
+                first.collect_panels(out);
+                second.collect_panels(out);
+            }
+            DockNode::Tabs { tabs, .. } => out.extend(tabs.iter().cloned()),
+        }
+    }
+
+    fn group_of(&self, panel: &str) -> Option<&[String]> {
+        match self {
+            DockNode::Split { first, second, .. } => {
+                first.group_of(panel).or_else(|| second.group_of(panel))
+            }
+            DockNode::Tabs { tabs, .. } => {
+                tabs.iter().any(|t| t == panel).then_some(tabs.as_slice())
+            }
+        }
+    }
+
+    fn activate(&mut self, panel: &str) -> bool {
+        match self {
+            DockNode::Split { first, second, .. } => {
+                first.activate(panel) || second.activate(panel)
+            }
+            DockNode::Tabs { tabs, active } => match tabs.iter().position(|t| t == panel) {
+                Some(i) => {
+                    *active = i;
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    /// Drops panels rejected by `keep`; empty groups and splits collapse.
+    fn retain(self, keep: &dyn Fn(&str) -> bool) -> Option<DockNode> {
+        match self {
+            DockNode::Split {
+                dir,
+                ratio,
+                first,
+                second,
+            } => match (first.retain(keep), second.retain(keep)) {
+                (Some(a), Some(b)) => Some(DockNode::split(dir, ratio, a, b)),
+                (Some(n), None) | (None, Some(n)) => Some(n),
+                (None, None) => None,
+            },
+            DockNode::Tabs { tabs, active } => {
+                let current = tabs.get(active).cloned();
+                let tabs: Vec<String> = tabs.into_iter().filter(|t| keep(t)).collect();
+                if tabs.is_empty() {
+                    return None;
+                }
+                let active = current
+                    .and_then(|c| tabs.iter().position(|t| *t == c))
+                    .unwrap_or_else(|| active.min(tabs.len() - 1));
+                Some(DockNode::Tabs { tabs, active })
+            }
+        }
+    }
+
+    /// Puts `panel` next to the group containing `anchor`.
+    fn insert(self, anchor: &str, panel: &str, at: DockTarget) -> DockNode {
+        match self {
+            DockNode::Split {
+                dir,
+                ratio,
+                first,
+                second,
+            } => DockNode::Split {
+                dir,
+                ratio,
+                first: Box::new(first.insert(anchor, panel, at)),
+                second: Box::new(second.insert(anchor, panel, at)),
+            },
+            DockNode::Tabs { mut tabs, active } => {
+                if !tabs.iter().any(|t| t == anchor) {
+                    return DockNode::Tabs { tabs, active };
+                }
+                let group = |tabs, active| DockNode::Tabs { tabs, active };
+                let new = DockNode::tabs(panel);
+                match at {
+                    DockTarget::Tab => {
+                        tabs.push(panel.to_string());
+                        let active = tabs.len() - 1;
+                        group(tabs, active)
+                    }
+                    DockTarget::Left => {
+                        DockNode::split(DockSplit::Horizontal, 0.5, new, group(tabs, active))
+                    }
+                    DockTarget::Right => {
+                        DockNode::split(DockSplit::Horizontal, 0.5, group(tabs, active), new)
+                    }
+                    DockTarget::Top => {
+                        DockNode::split(DockSplit::Vertical, 0.5, new, group(tabs, active))
+                    }
+                    DockTarget::Bottom => {
+                        DockNode::split(DockSplit::Vertical, 0.5, group(tabs, active), new)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Live layout of one `<dock>`: which panels are open and how they are arranged.
+///
+/// Serializes to JSON for persistence; panels that no longer exist in the markup are dropped
+/// when the layout is next rendered.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DockLayout {
+    #[serde(default)]
+    pub root: Option<DockNode>,
+}
+
+impl DockLayout {
+    #[inline]
+    pub fn new(root: DockNode) -> Self {
+        Self { root: Some(root) }
+    }
+
+    #[inline]
+    pub fn contains(&self, panel: &str) -> bool {
+        self.root.as_ref().is_some_and(|r| r.contains(panel))
+    }
+
+    /// Open panels in layout order.
+    pub fn panels(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Some(r) = self.root.as_ref() {
+            r.collect_panels(&mut out);
+        }
+        out
+    }
+
+    /// Makes `panel` the active tab of its group.
+    #[inline]
+    pub fn focus(&mut self, panel: &str) -> bool {
+        self.root.as_mut().is_some_and(|r| r.activate(panel))
+    }
+
+    /// Removes `panel` from the layout; returns `false` if it was not open.
+    pub fn close(&mut self, panel: &str) -> bool {
+        if !self.contains(panel) {
+            return false;
+        }
+        self.retain_panels(|p| p != panel);
+        true
+    }
+
+    /// Moves `panel` (or opens it, if it is not in the layout) next to `target`.
+    ///
+    /// With no target, or a target that is not open, the panel joins the first tab group. Moving
+    /// a panel relative to itself splits it out of its own group; this is a no-op when it is the
+    /// only tab there.
+    pub fn dock(&mut self, panel: &str, target: Option<&str>, at: DockTarget) -> bool {
+        let target = target.filter(|t| self.contains(t));
+
+        let anchor = match target {
+            Some(t) if t == panel => {
+                if at == DockTarget::Tab {
+                    return self.focus(panel);
+                }
+                let sibling = self
+                    .root
+                    .as_ref()
+                    .and_then(|r| r.group_of(panel))
+                    .and_then(|g| g.iter().find(|t| *t != panel).cloned());
+                match sibling {
+                    Some(s) => Some(s),
+                    None => return false,
+                }
+            }
+            Some(t) => Some(t.to_string()),
+            None => None,
+        };
+
+        self.retain_panels(|p| p != panel);
+
+        let anchor = anchor.or_else(|| self.panels().into_iter().next());
+        let (root, anchor) = match (self.root.take(), anchor) {
+            (Some(root), Some(anchor)) => (root, anchor),
+            _ => {
+                self.root = Some(DockNode::tabs(panel));
+                return true;
+            }
+        };
+        let at = if target.is_some() {
+            at
+        } else {
+            DockTarget::Tab
+        };
+        self.root = Some(root.insert(&anchor, panel, at));
+        true
+    }
+
+    /// Keeps only panels accepted by `keep`.
+    pub fn retain_panels(&mut self, keep: impl Fn(&str) -> bool) {
+        self.root = self.root.take().and_then(|r| r.retain(&keep));
+    }
+
+    #[inline]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    #[inline]
+    pub fn from_json(s: &str) -> Result<Self, String> {
+        serde_json::from_str(s).map_err(|e| e.to_string())
+    }
+}
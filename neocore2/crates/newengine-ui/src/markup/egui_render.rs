@@ -1,18 +1,44 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 #[cfg(feature = "egui")]
-use crate::markup::substitute::substitute_vars;
+use std::borrow::Cow;
+
+#[cfg(feature = "egui")]
+use crate::accessibility::{accessibility, high_contrast_visuals};
+#[cfg(feature = "egui")]
+use crate::input::take_focus_steps;
+#[cfg(feature = "egui")]
+use crate::markup::dock::{DockLayout, DockNode, DockSplit, DockTarget};
 #[cfg(feature = "egui")]
 use crate::markup::theme::{UiDensity, UiThemeDesc, UiVisuals};
 #[cfg(feature = "egui")]
-use crate::markup::ui_node::UiNode;
+use crate::markup::ui_node::{DockPanel, ListViewNode, UiNode};
 #[cfg(feature = "egui")]
 use crate::markup::{UiEvent, UiEventKind, UiMarkupDoc, UiState};
+#[cfg(feature = "egui")]
+use crate::text::egui_text::widget_text;
 
 #[cfg(feature = "egui")]
 pub(crate) fn render_doc(doc: &UiMarkupDoc, ctx: &egui::Context, state: &mut UiState) {
     apply_theme(ctx, &doc.theme);
+    if state.take_bindings_changed() {
+        ctx.request_repaint();
+    }
+    for step in take_focus_steps(ctx) {
+        state.move_focus(step);
+    }
+
+    state.focus_mut().begin_frame();
     render_root(&doc.root, ctx, state);
+    state.focus_mut().end_frame();
+}
+
+/// Enters a rendered widget into the tab order and hands it focus when requested.
+#[cfg(feature = "egui")]
+fn visit_focus(state: &mut UiState, id: &str, resp: &egui::Response) {
+    if state.focus_mut().visit(id, resp.has_focus()) {
+        resp.request_focus();
+    }
 }
 
 #[cfg(feature = "egui")]
@@ -44,6 +70,11 @@ fn render_root(root: &UiNode, ctx: &egui::Context, state: &mut UiState) {
                 }
             });
         }
+        UiNode::Dock { id, layout, panels } => {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                render_dock(ui, id, layout, panels, state);
+            });
+        }
         _ => {}
     }
 }
@@ -65,22 +96,33 @@ fn render_in_ui(node: &UiNode, ui: &mut egui::Ui, state: &mut UiState) {
                 }
             });
         }
-        UiNode::Label { id, text } => {
-            let base = if let Some(id) = id.as_deref() {
-                state
-                    .strings
-                    .get(id)
-                    .map(String::as_str)
-                    .unwrap_or(text.as_str())
-            } else {
-                text.as_str()
+        UiNode::Label { id, text, dir } => {
+            let base = match id.as_deref().and_then(|id| state.strings.get(id)) {
+                Some(s) => Cow::Owned(s.clone()),
+                None => Cow::Borrowed(text.as_str()),
             };
-            let s = substitute_vars(base, &state.vars);
-            ui.label(s.as_ref());
+            let s = state.resolve_text(&base);
+            let font = egui::TextStyle::Body.resolve(ui.style());
+            let (text, rtl) = widget_text(ui.ctx(), &s, *dir, font);
+            if rtl && ui.layout().is_vertical() {
+                ui.with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
+                    ui.label(text);
+                });
+            } else {
+                ui.label(text);
+            }
         }
-        UiNode::Button { id, text, on_click } => {
-            let s = substitute_vars(text, &state.vars);
-            if ui.button(s.as_ref()).clicked() {
+        UiNode::Button {
+            id,
+            text,
+            dir,
+            on_click,
+        } => {
+            let s = state.resolve_text(text);
+            let font = egui::TextStyle::Button.resolve(ui.style());
+            let resp = ui.button(widget_text(ui.ctx(), &s, *dir, font).0);
+            visit_focus(state, id, &resp);
+            if resp.clicked() {
                 state.clicked.insert(id.clone(), true);
 
                 if !on_click.is_empty() {
@@ -98,35 +140,63 @@ fn render_in_ui(node: &UiNode, ui: &mut egui::Ui, state: &mut UiState) {
             hint,
             bind,
             multiline,
+            password,
+            readonly,
+            max_length,
             on_change,
             on_submit,
         } => {
-            let hint = substitute_vars(hint, &state.vars);
+            let hint = state.resolve_text(hint);
+            state.pull_bound_input(bind);
 
-            let (changed, submit_now, value_snapshot) = {
-                let entry = state.strings.entry(bind.clone()).or_default();
+            let edit_id = egui::Id::new(("ui.textbox", id.as_str()));
+            if let Some(range) = state.take_selection_request(id) {
+                let len = state.strings.get(bind).map_or(0, |s| s.chars().count());
+                let mut es = egui::TextEdit::load_state(ui.ctx(), edit_id).unwrap_or_default();
+                es.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+                    egui::text::CCursor::new(range.start.min(len)),
+                    egui::text::CCursor::new(range.end.min(len)),
+                )));
+                es.store(ui.ctx(), edit_id);
+            }
 
-                let resp = if *multiline {
-                    ui.add(
-                        egui::TextEdit::multiline(entry)
-                            .hint_text(hint.as_ref())
-                            .desired_width(f32::INFINITY),
-                    )
+            let (output, value_snapshot) = {
+                let entry = state.strings.entry(bind.clone()).or_default();
+                let mut show = |buffer: &mut dyn egui::TextBuffer| {
+                    let mut edit = if *multiline {
+                        egui::TextEdit::multiline(buffer)
+                    } else {
+                        egui::TextEdit::singleline(buffer)
+                    };
+                    edit = edit
+                        .id(edit_id)
+                        .hint_text(hint.as_ref())
+                        .password(*password)
+                        .desired_width(f32::INFINITY);
+                    if let Some(n) = max_length {
+                        edit = edit.char_limit(*n);
+                    }
+                    edit.show(ui)
+                };
+                let output = if *readonly {
+                    // `&str` is an immutable buffer: selectable and copyable, not editable.
+                    show(&mut entry.as_str())
                 } else {
-                    ui.add(
-                        egui::TextEdit::singleline(entry)
-                            .hint_text(hint.as_ref())
-                            .desired_width(f32::INFINITY),
-                    )
+                    show(entry)
                 };
-
-                let changed = resp.changed();
-                let submit_now = resp.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
-                (changed, submit_now, entry.clone())
+                (output, entry.clone())
             };
 
+            let resp = &output.response;
+            visit_focus(state, id, resp);
+            state.put_text_selection(id, output.cursor_range.map(|r| r.as_sorted_char_range()));
+
+            let changed = resp.changed();
+            let submit_now = resp.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
             if changed {
                 state.vars.insert(id.clone(), value_snapshot.clone());
+                state.push_bound_input(bind, &value_snapshot);
 
                 if !on_change.is_empty() {
                     state.push_event(UiEvent {
@@ -148,6 +218,8 @@ fn render_in_ui(node: &UiNode, ui: &mut egui::Ui, state: &mut UiState) {
             }
         }
         UiNode::Spacer => ui.add_space(8.0),
+        UiNode::List { id, view } => render_list(ui, id, view, state),
+        UiNode::Tree { id, view } => render_tree(ui, id, view, state),
         UiNode::TopBar { children } => {
             ui.horizontal(|ui| {
                 for c in children {
@@ -156,6 +228,7 @@ fn render_in_ui(node: &UiNode, ui: &mut egui::Ui, state: &mut UiState) {
             });
         }
         UiNode::Window { .. } => {}
+        UiNode::Dock { id, layout, panels } => render_dock(ui, id, layout, panels, state),
         UiNode::Ui { children } => {
             for c in children {
                 render_in_ui(c, ui, state);
@@ -170,6 +243,419 @@ fn render_in_ui(node: &UiNode, ui: &mut egui::Ui, state: &mut UiState) {
     }
 }
 
+/// Used when neither `height` is set nor the parent bounds the space (e.g. inside a scroll area).
+#[cfg(feature = "egui")]
+const LIST_FALLBACK_HEIGHT: f32 = 320.0;
+
+/// Row interaction of one frame, applied after the rows were laid out.
+#[cfg(feature = "egui")]
+struct ListHit {
+    key: u64,
+    value: String,
+    activate: bool,
+}
+
+#[cfg(feature = "egui")]
+fn list_scroll_area(ui: &egui::Ui, id: &str, view: &ListViewNode) -> (egui::ScrollArea, f32) {
+    let height = view.height.unwrap_or_else(|| {
+        let h = ui.available_height();
+        if h.is_finite() && h > 0.0 {
+            h
+        } else {
+            LIST_FALLBACK_HEIGHT
+        }
+    });
+    let row_h = view
+        .row_height
+        .unwrap_or_else(|| ui.spacing().interact_size.y);
+    let area = egui::ScrollArea::vertical()
+        .id_salt(("ui_list", id))
+        .max_height(height)
+        .auto_shrink([false, true]);
+    (area, row_h)
+}
+
+/// One fixed-height selectable row; `indent` leaves room for the tree toggle.
+#[cfg(feature = "egui")]
+fn list_row(
+    ui: &mut egui::Ui,
+    row_h: f32,
+    indent: f32,
+    selected: bool,
+    text: &str,
+) -> egui::Response {
+    let size = egui::vec2(ui.available_width(), row_h);
+    let (rect, resp) = ui.allocate_exact_size(size, egui::Sense::click());
+    if ui.is_rect_visible(rect) {
+        let visuals = ui.style().interact_selectable(&resp, selected);
+        if selected || resp.hovered() {
+            ui.painter().rect(
+                rect,
+                visuals.rounding,
+                visuals.weak_bg_fill,
+                visuals.bg_stroke,
+            );
+        }
+        let font = egui::TextStyle::Body.resolve(ui.style());
+        ui.painter().with_clip_rect(rect).text(
+            egui::pos2(rect.left() + indent + 4.0, rect.center().y),
+            egui::Align2::LEFT_CENTER,
+            text,
+            font,
+            visuals.text_color(),
+        );
+    }
+    resp
+}
+
+#[cfg(feature = "egui")]
+fn push_list_hit(
+    state: &mut UiState,
+    id: &str,
+    view: &ListViewNode,
+    prev: Option<u64>,
+    hit: ListHit,
+) {
+    if prev != Some(hit.key) {
+        state.vars.insert(id.to_string(), hit.value.clone());
+        if !view.on_select.is_empty() {
+            state.push_event(UiEvent {
+                kind: UiEventKind::Select,
+                target_id: id.to_string(),
+                value: Some(hit.value.clone()),
+                actions: view.on_select.clone(),
+            });
+        }
+    }
+    if hit.activate && !view.on_activate.is_empty() {
+        state.push_event(UiEvent {
+            kind: UiEventKind::Activate,
+            target_id: id.to_string(),
+            value: Some(hit.value),
+            actions: view.on_activate.clone(),
+        });
+    }
+}
+
+#[cfg(feature = "egui")]
+fn render_list(ui: &mut egui::Ui, id: &str, view: &ListViewNode, state: &mut UiState) {
+    let list = state.list_view(id);
+    let Some(model) = list.model.clone() else {
+        ui.weak(format!("list '{id}': no model"));
+        return;
+    };
+    let selected = list.selected;
+
+    let (area, row_h) = list_scroll_area(ui, id, view);
+    let mut hit = None;
+    area.show_rows(ui, row_h, model.len(), |ui, range| {
+        for row in range {
+            let key = model.key(row);
+            let resp = list_row(ui, row_h, 0.0, selected == Some(key), &model.label(row));
+            if resp.clicked() || resp.double_clicked() {
+                hit = Some(ListHit {
+                    key,
+                    value: model.value(row).into_owned(),
+                    activate: resp.double_clicked(),
+                });
+            }
+        }
+    });
+
+    if let Some(hit) = hit {
+        state.list_view(id).selected = Some(hit.key);
+        push_list_hit(state, id, view, selected, hit);
+    }
+}
+
+#[cfg(feature = "egui")]
+fn render_tree(ui: &mut egui::Ui, id: &str, view: &ListViewNode, state: &mut UiState) {
+    let tree = state.tree_view(id);
+    let Some(model) = tree.model.clone() else {
+        ui.weak(format!("tree '{id}': no model"));
+        return;
+    };
+    let selected = tree.selected;
+    let indent = ui.spacing().indent;
+    let icon = ui.spacing().icon_width;
+
+    let (area, row_h) = list_scroll_area(ui, id, view);
+    let mut hit = None;
+    let mut toggle = None;
+    let rows = tree.rows();
+    area.show_rows(ui, row_h, rows.len(), |ui, range| {
+        for r in &rows[range] {
+            let depth = f32::from(r.depth) * indent;
+            let resp = list_row(
+                ui,
+                row_h,
+                depth + icon,
+                selected == Some(r.node),
+                &model.label(r.node),
+            );
+
+            if r.has_children {
+                let icon_rect = egui::Rect::from_center_size(
+                    egui::pos2(resp.rect.left() + depth + icon * 0.5, resp.rect.center().y),
+                    egui::vec2(icon, icon),
+                );
+                let icon_resp =
+                    ui.interact(icon_rect, resp.id.with("toggle"), egui::Sense::click());
+                let open = if r.expanded { 1.0 } else { 0.0 };
+                egui::collapsing_header::paint_default_icon(ui, open, &icon_resp);
+                if icon_resp.clicked() {
+                    toggle = Some(r.node);
+                    continue;
+                }
+            }
+
+            if resp.clicked() || resp.double_clicked() {
+                hit = Some(ListHit {
+                    key: r.node,
+                    value: model.value(r.node).into_owned(),
+                    activate: resp.double_clicked(),
+                });
+            }
+        }
+    });
+
+    let tree = state.tree_view(id);
+    if let Some(node) = toggle {
+        let open = tree.expanded.contains(&node);
+        tree.set_expanded(node, !open);
+    }
+    if let Some(hit) = hit {
+        tree.selected = Some(hit.key);
+        // Double-clicking a branch toggles it as well as activating it.
+        if hit.activate && model.has_children(hit.key) {
+            let open = tree.expanded.contains(&hit.key);
+            tree.set_expanded(hit.key, !open);
+        }
+        push_list_hit(state, id, view, selected, hit);
+    }
+}
+
+/// Per-frame results collected while walking the dock tree; applied once the walk is done.
+#[cfg(feature = "egui")]
+#[derive(Default)]
+struct DockFrame {
+    close: Option<String>,
+    drag_start: Option<String>,
+    /// Tab group under the pointer while a tab is dragged: `(panel in that group, zone)`.
+    drop: Option<(String, DockTarget)>,
+}
+
+#[cfg(feature = "egui")]
+fn render_dock(
+    ui: &mut egui::Ui,
+    dock_id: &str,
+    initial: &DockLayout,
+    panels: &[DockPanel],
+    state: &mut UiState,
+) {
+    let mut layout = state.take_dock_layout(dock_id, initial);
+    let before = layout.clone();
+    layout.retain_panels(|p| panels.iter().any(|x| x.id == p));
+
+    let rect = ui.available_rect_before_wrap();
+    ui.allocate_rect(rect, egui::Sense::hover());
+
+    let dragging = state.dock_drag(dock_id).map(str::to_string);
+    let mut frame = DockFrame::default();
+    if let Some(root) = layout.root.as_mut() {
+        let path = ui.id().with(("dock", dock_id));
+        render_dock_node(
+            ui,
+            path,
+            root,
+            rect,
+            panels,
+            dragging.is_some(),
+            state,
+            &mut frame,
+        );
+    }
+
+    if let Some(panel) = frame.drag_start.take() {
+        state.set_dock_drag(Some((dock_id.to_string(), panel)));
+    }
+    if let Some(panel) = frame.close.take() {
+        layout.close(&panel);
+    }
+
+    if let Some(panel) = dragging {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+        if !ui.input(|i| i.pointer.primary_down()) {
+            if let Some((target, at)) = frame.drop {
+                layout.dock(&panel, Some(&target), at);
+            }
+            state.set_dock_drag(None);
+        }
+    }
+
+    let changed = layout != before;
+    state.put_dock_layout(dock_id, layout, changed);
+}
+
+#[cfg(feature = "egui")]
+#[allow(clippy::too_many_arguments)]
+fn render_dock_node(
+    ui: &mut egui::Ui,
+    path: egui::Id,
+    node: &mut DockNode,
+    rect: egui::Rect,
+    panels: &[DockPanel],
+    dragging: bool,
+    state: &mut UiState,
+    frame: &mut DockFrame,
+) {
+    match node {
+        DockNode::Split {
+            dir,
+            ratio,
+            first,
+            second,
+        } => {
+            const GAP: f32 = 6.0;
+
+            let (a, sep, b) = match dir {
+                DockSplit::Horizontal => {
+                    let x = rect.left() + rect.width() * *ratio;
+                    let (a, rest) = rect.split_left_right_at_x(x - GAP * 0.5);
+                    let (sep, b) = rest.split_left_right_at_x(x + GAP * 0.5);
+                    (a, sep, b)
+                }
+                DockSplit::Vertical => {
+                    let y = rect.top() + rect.height() * *ratio;
+                    let (a, rest) = rect.split_top_bottom_at_y(y - GAP * 0.5);
+                    let (sep, b) = rest.split_top_bottom_at_y(y + GAP * 0.5);
+                    (a, sep, b)
+                }
+            };
+
+            let resp = ui.interact(sep, path.with("sep"), egui::Sense::drag());
+            if resp.hovered() || resp.dragged() {
+                ui.ctx().set_cursor_icon(match dir {
+                    DockSplit::Horizontal => egui::CursorIcon::ResizeHorizontal,
+                    DockSplit::Vertical => egui::CursorIcon::ResizeVertical,
+                });
+            }
+            if resp.dragged() {
+                if let Some(p) = resp.interact_pointer_pos() {
+                    let r = match dir {
+                        DockSplit::Horizontal => (p.x - rect.left()) / rect.width().max(1.0),
+                        DockSplit::Vertical => (p.y - rect.top()) / rect.height().max(1.0),
+                    };
+                    *ratio = r.clamp(0.05, 0.95);
+                }
+            }
+
+            let stroke = if resp.hovered() || resp.dragged() {
+                ui.visuals().widgets.hovered.fg_stroke
+            } else {
+                ui.visuals().widgets.noninteractive.bg_stroke
+            };
+            let line = match dir {
+                DockSplit::Horizontal => [sep.center_top(), sep.center_bottom()],
+                DockSplit::Vertical => [sep.left_center(), sep.right_center()],
+            };
+            ui.painter().line_segment(line, stroke);
+
+            render_dock_node(ui, path.with(0), first, a, panels, dragging, state, frame);
+            render_dock_node(ui, path.with(1), second, b, panels, dragging, state, frame);
+        }
+        DockNode::Tabs { tabs, active } => {
+            if tabs.is_empty() {
+                return;
+            }
+            *active = (*active).min(tabs.len() - 1);
+
+            let bar_h = ui.spacing().interact_size.y + 6.0;
+            let (bar, body) = rect.split_top_bottom_at_y(rect.top() + bar_h);
+            ui.painter()
+                .rect_filled(bar, 0.0, ui.visuals().faint_bg_color);
+
+            ui.allocate_new_ui(
+                egui::UiBuilder::new()
+                    .max_rect(bar.shrink2(egui::vec2(4.0, 2.0)))
+                    .layout(egui::Layout::left_to_right(egui::Align::Center)),
+                |ui| {
+                    ui.set_clip_rect(bar);
+                    for (i, tab) in tabs.iter().enumerate() {
+                        let Some(panel) = panels.iter().find(|p| p.id == *tab) else {
+                            continue;
+                        };
+                        let title = state.resolve_text(&panel.title);
+                        let resp = ui
+                            .add(egui::SelectableLabel::new(i == *active, title.as_ref()))
+                            .interact(egui::Sense::click_and_drag());
+                        if resp.clicked() {
+                            *active = i;
+                        }
+                        if resp.drag_started() {
+                            frame.drag_start = Some(tab.clone());
+                        }
+                        if panel.closable && ui.small_button("x").on_hover_text("Close").clicked() {
+                            frame.close = Some(tab.clone());
+                        }
+                    }
+                },
+            );
+
+            let shown = tabs
+                .get(*active)
+                .and_then(|t| panels.iter().find(|p| p.id == *t));
+            if let Some(panel) = shown {
+                ui.allocate_new_ui(egui::UiBuilder::new().max_rect(body.shrink(4.0)), |ui| {
+                    ui.set_clip_rect(body);
+                    ui.push_id(path.with(&panel.id), |ui| {
+                        egui::ScrollArea::both()
+                            .auto_shrink([false, false])
+                            .show(ui, |ui| {
+                                for c in &panel.children {
+                                    render_in_ui(c, ui, state);
+                                }
+                            });
+                    });
+                });
+            }
+
+            if !dragging {
+                return;
+            }
+            let Some(p) = ui.ctx().pointer_latest_pos().filter(|p| rect.contains(*p)) else {
+                return;
+            };
+
+            let rel = (p - rect.min) / rect.size();
+            let at = if rel.x < 0.25 {
+                DockTarget::Left
+            } else if rel.x > 0.75 {
+                DockTarget::Right
+            } else if rel.y < 0.25 {
+                DockTarget::Top
+            } else if rel.y > 0.75 {
+                DockTarget::Bottom
+            } else {
+                DockTarget::Tab
+            };
+            let preview = match at {
+                DockTarget::Tab => rect,
+                DockTarget::Left => rect.split_left_right_at_fraction(0.5).0,
+                DockTarget::Right => rect.split_left_right_at_fraction(0.5).1,
+                DockTarget::Top => rect.split_top_bottom_at_fraction(0.5).0,
+                DockTarget::Bottom => rect.split_top_bottom_at_fraction(0.5).1,
+            };
+            ui.painter().rect_filled(
+                preview.shrink(2.0),
+                4.0,
+                ui.visuals().selection.bg_fill.gamma_multiply(0.35),
+            );
+            frame.drop = Some((tabs[*active].clone(), at));
+        }
+    }
+}
+
 #[cfg(feature = "egui")]
 fn apply_theme(ctx: &egui::Context, theme: &UiThemeDesc) {
     let mut style = (*ctx.style()).clone();
@@ -178,6 +664,10 @@ fn apply_theme(ctx: &egui::Context, theme: &UiThemeDesc) {
         UiVisuals::Auto => {}
         UiVisuals::Dark => style.visuals = egui::Visuals::dark(),
         UiVisuals::Light => style.visuals = egui::Visuals::light(),
+        UiVisuals::HighContrast => style.visuals = high_contrast_visuals(),
+    }
+    if accessibility().high_contrast {
+        style.visuals = high_contrast_visuals();
     }
 
     let s = theme.scale;
@@ -205,4 +695,4 @@ fn apply_theme(ctx: &egui::Context, theme: &UiThemeDesc) {
 
     style.override_font_id = Some(egui::FontId::proportional(theme.font_size));
     ctx.set_style(style);
-}
\ No newline at end of file
+}
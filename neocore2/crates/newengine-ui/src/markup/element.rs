@@ -0,0 +1,37 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use roxmltree::Node;
+
+/// Owned XML element. Markup is composed (includes/components) on this tree
+/// before it is turned into `UiNode`s.
+#[derive(Debug, Clone)]
+pub(crate) struct XmlElement {
+    pub tag: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<XmlElement>,
+}
+
+impl XmlElement {
+    pub(crate) fn from_node(n: Node) -> Self {
+        Self {
+            tag: n.tag_name().name().to_string(),
+            attrs: n
+                .attributes()
+                .map(|a| (a.name().to_string(), a.value().to_string()))
+                .collect(),
+            children: n
+                .children()
+                .filter(|c| c.is_element())
+                .map(XmlElement::from_node)
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn attribute(&self, key: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
@@ -9,6 +9,8 @@ pub enum UiMarkupError {
     TextRead(String),
     XmlParse(String),
     Invalid(String),
+    Include { path: String, reason: String },
+    Remote(String),
 }
 
 impl std::fmt::Display for UiMarkupError {
@@ -21,6 +23,10 @@ impl std::fmt::Display for UiMarkupError {
             UiMarkupError::TextRead(e) => write!(f, "ui: TextReader failed: {e}"),
             UiMarkupError::XmlParse(e) => write!(f, "ui: xml parse failed: {e}"),
             UiMarkupError::Invalid(e) => write!(f, "ui: markup invalid: {e}"),
+            UiMarkupError::Include { path, reason } => {
+                write!(f, "ui: include '{path}' failed: {reason}")
+            }
+            UiMarkupError::Remote(e) => write!(f, "ui: remote event rejected: {e}"),
         }
     }
 }
@@ -0,0 +1,77 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use crate::input::UiFocusStep;
+
+/// Keyboard focus across markup widgets. The tab order is the order focusable widgets were
+/// rendered in during the last frame; Tab and Shift+Tab walk it and wrap around.
+#[derive(Debug, Default)]
+pub(crate) struct FocusChain {
+    /// Widget that held focus at the end of the last frame.
+    focused: Option<String>,
+    order: Vec<String>,
+    /// Collected while the current frame renders.
+    #[cfg(feature = "egui")]
+    next_order: Vec<String>,
+    #[cfg(feature = "egui")]
+    next_focused: Option<String>,
+    /// Widget to hand focus to when it is next rendered.
+    request: Option<String>,
+}
+
+impl FocusChain {
+    #[inline]
+    pub(crate) fn focused(&self) -> Option<&str> {
+        self.focused.as_deref()
+    }
+
+    #[inline]
+    pub(crate) fn request(&mut self, id: impl Into<String>) {
+        self.request = Some(id.into());
+    }
+
+    /// Requests focus for the neighbour of the focused widget in tab order.
+    pub(crate) fn step(&mut self, step: UiFocusStep) {
+        if self.order.is_empty() {
+            return;
+        }
+        let n = self.order.len();
+        let from = self
+            .request
+            .as_deref()
+            .or(self.focused.as_deref())
+            .and_then(|id| self.order.iter().position(|o| o == id));
+        let to = match (from, step) {
+            (Some(i), UiFocusStep::Next) => (i + 1) % n,
+            (Some(i), UiFocusStep::Prev) => (i + n - 1) % n,
+            (None, UiFocusStep::Next) => 0,
+            (None, UiFocusStep::Prev) => n - 1,
+        };
+        self.request = Some(self.order[to].clone());
+    }
+
+    #[cfg(feature = "egui")]
+    pub(crate) fn begin_frame(&mut self) {
+        self.next_order.clear();
+        self.next_focused = None;
+    }
+
+    /// Adds a rendered widget to the tab order; returns `true` when it should take focus now.
+    #[cfg(feature = "egui")]
+    pub(crate) fn visit(&mut self, id: &str, has_focus: bool) -> bool {
+        self.next_order.push(id.to_string());
+        if has_focus {
+            self.next_focused = Some(id.to_string());
+        }
+        if self.request.as_deref() == Some(id) {
+            self.request = None;
+            return true;
+        }
+        false
+    }
+
+    #[cfg(feature = "egui")]
+    pub(crate) fn end_frame(&mut self) {
+        std::mem::swap(&mut self.order, &mut self.next_order);
+        self.focused = self.next_focused.take();
+    }
+}
@@ -0,0 +1,175 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use ahash::AHashSet;
+
+/// Rows of a `<list>` widget. Only the rows scrolled into view are queried each frame, so a
+/// model may front tens of thousands of entries.
+pub trait UiListModel: Send + Sync {
+    fn len(&self) -> usize;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stable identity of `row`, used for the selection; defaults to the row index.
+    #[inline]
+    fn key(&self, row: usize) -> u64 {
+        row as u64
+    }
+
+    fn label(&self, row: usize) -> Cow<'_, str>;
+
+    /// Value carried by select/activate events (`$value` in actions); defaults to the label.
+    #[inline]
+    fn value(&self, row: usize) -> Cow<'_, str> {
+        self.label(row)
+    }
+}
+
+impl UiListModel for Vec<String> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    #[inline]
+    fn label(&self, row: usize) -> Cow<'_, str> {
+        Cow::Borrowed(self[row].as_str())
+    }
+}
+
+/// Nodes of a `<tree>` widget, addressed by caller-chosen ids. `None` as a parent means the
+/// roots. Children of collapsed nodes are never queried.
+pub trait UiTreeModel: Send + Sync {
+    fn child_count(&self, parent: Option<u64>) -> usize;
+
+    fn child(&self, parent: Option<u64>, index: usize) -> u64;
+
+    fn label(&self, node: u64) -> Cow<'_, str>;
+
+    /// Value carried by select/activate events; defaults to the label.
+    #[inline]
+    fn value(&self, node: u64) -> Cow<'_, str> {
+        self.label(node)
+    }
+
+    #[inline]
+    fn has_children(&self, node: u64) -> bool {
+        self.child_count(Some(node)) > 0
+    }
+
+    /// Must change whenever the structure changes; the visible rows are only re-flattened
+    /// when it or the expansion does.
+    fn revision(&self) -> u64;
+}
+
+#[derive(Default)]
+pub(crate) struct ListView {
+    pub(crate) model: Option<Arc<dyn UiListModel>>,
+    pub(crate) selected: Option<u64>,
+}
+
+impl std::fmt::Debug for ListView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ListView")
+            .field("rows", &self.model.as_ref().map(|m| m.len()))
+            .field("selected", &self.selected)
+            .finish()
+    }
+}
+
+#[cfg(feature = "egui")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TreeRow {
+    pub(crate) node: u64,
+    pub(crate) depth: u16,
+    pub(crate) has_children: bool,
+    pub(crate) expanded: bool,
+}
+
+#[derive(Default)]
+pub(crate) struct TreeView {
+    pub(crate) model: Option<Arc<dyn UiTreeModel>>,
+    pub(crate) selected: Option<u64>,
+    pub(crate) expanded: AHashSet<u64>,
+    #[cfg(feature = "egui")]
+    rows: Vec<TreeRow>,
+    /// Model revision `rows` was flattened for; `None` when the expansion changed since.
+    rows_rev: Option<u64>,
+}
+
+impl std::fmt::Debug for TreeView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TreeView")
+            .field("has_model", &self.model.is_some())
+            .field("selected", &self.selected)
+            .field("expanded", &self.expanded.len())
+            .finish()
+    }
+}
+
+impl TreeView {
+    #[inline]
+    pub(crate) fn set_model(&mut self, model: Arc<dyn UiTreeModel>) {
+        self.model = Some(model);
+        self.rows_rev = None;
+    }
+
+    /// Returns `true` when the expansion changed.
+    pub(crate) fn set_expanded(&mut self, node: u64, expanded: bool) -> bool {
+        let changed = if expanded {
+            self.expanded.insert(node)
+        } else {
+            self.expanded.remove(&node)
+        };
+        if changed {
+            self.rows_rev = None;
+        }
+        changed
+    }
+
+    #[cfg(feature = "egui")]
+    /// Visible rows in display order: expanded subtrees inlined depth-first.
+    pub(crate) fn rows(&mut self) -> &[TreeRow] {
+        let Some(model) = self.model.as_ref() else {
+            self.rows.clear();
+            return &self.rows;
+        };
+
+        let rev = model.revision();
+        if self.rows_rev != Some(rev) {
+            self.rows.clear();
+            flatten(model.as_ref(), None, 0, &self.expanded, &mut self.rows);
+            self.rows_rev = Some(rev);
+        }
+        &self.rows
+    }
+}
+
+#[cfg(feature = "egui")]
+fn flatten(
+    model: &dyn UiTreeModel,
+    parent: Option<u64>,
+    depth: u16,
+    expanded: &AHashSet<u64>,
+    out: &mut Vec<TreeRow>,
+) {
+    for i in 0..model.child_count(parent) {
+        let node = model.child(parent, i);
+        let has_children = model.has_children(node);
+        let open = has_children && expanded.contains(&node);
+        out.push(TreeRow {
+            node,
+            depth,
+            has_children,
+            expanded: open,
+        });
+        if open {
+            flatten(model, Some(node), depth.saturating_add(1), expanded, out);
+        }
+    }
+}
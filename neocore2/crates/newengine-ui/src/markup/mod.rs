@@ -1,16 +1,28 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 mod actions;
+mod bindings;
+mod compose;
 mod doc;
+mod dock;
 mod egui_render;
+mod element;
 mod error;
+mod focus;
+mod list_view;
 mod parser;
+mod remote;
 mod state;
 mod substitute;
 mod theme;
 mod ui_node;
 
+pub use actions::UiAction;
+pub use bindings::{UiBindings, UiValue};
 pub use doc::UiMarkupDoc;
+pub use dock::{DockLayout, DockNode, DockSplit, DockTarget};
 pub use error::UiMarkupError;
+pub use list_view::{UiListModel, UiTreeModel};
+pub use remote::{UiRemoteEvent, UiStateDiff, UiStateSync};
 pub use state::{UiEvent, UiEventKind, UiState};
 pub use theme::{UiDensity, UiThemeDesc, UiVisuals};
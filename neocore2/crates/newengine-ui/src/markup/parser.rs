@@ -1,17 +1,17 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
-use roxmltree::{Document, Node};
 use smallvec::SmallVec;
 
 use crate::markup::actions::parse_actions_for;
+use crate::markup::dock::{DockLayout, DockNode, DockSplit};
+use crate::markup::element::XmlElement;
 use crate::markup::state::UiEventKind;
 use crate::markup::theme::{UiDensity, UiThemeDesc, UiVisuals};
-use crate::markup::ui_node::UiNode;
+use crate::markup::ui_node::{DockPanel, ListViewNode, UiNode};
+use crate::text::TextDirection;
 
-pub(crate) fn parse_ui_root(doc: &Document) -> Result<UiNode, String> {
-    let root = doc.root_element();
-
-    let tag = root.tag_name().name();
+pub(crate) fn parse_ui_root(root: &XmlElement) -> Result<UiNode, String> {
+    let tag = root.tag.as_str();
     if tag != "ui" {
         return Err(format!("root tag must be <ui>, got <{tag}>"));
     }
@@ -21,15 +21,14 @@ pub(crate) fn parse_ui_root(doc: &Document) -> Result<UiNode, String> {
     })
 }
 
-pub(crate) fn parse_theme(doc: &Document) -> UiThemeDesc {
-    let root = doc.root_element();
-
+pub(crate) fn parse_theme(root: &XmlElement) -> UiThemeDesc {
     let mut theme = UiThemeDesc::default();
 
     let visuals = attr_any(root, &["visuals", "theme"]).unwrap_or("auto");
     theme.visuals = match visuals.trim().to_ascii_lowercase().as_str() {
         "dark" => UiVisuals::Dark,
         "light" => UiVisuals::Light,
+        "high_contrast" | "high-contrast" | "contrast" => UiVisuals::HighContrast,
         _ => UiVisuals::Auto,
     };
 
@@ -47,16 +46,16 @@ pub(crate) fn parse_theme(doc: &Document) -> UiThemeDesc {
     theme
 }
 
-fn parse_children(parent: Node) -> Result<Vec<UiNode>, String> {
+fn parse_children(parent: &XmlElement) -> Result<Vec<UiNode>, String> {
     let mut out = Vec::new();
-    for n in parent.children().filter(|n| n.is_element()) {
+    for n in parent.children.iter() {
         out.push(parse_node(n)?);
     }
     Ok(out)
 }
 
-fn parse_node(n: Node) -> Result<UiNode, String> {
-    let tag = n.tag_name().name();
+fn parse_node(n: &XmlElement) -> Result<UiNode, String> {
+    let tag = n.tag.as_str();
     match tag {
         "topbar" => Ok(UiNode::TopBar {
             children: parse_children(n)?,
@@ -93,39 +92,63 @@ fn parse_node(n: Node) -> Result<UiNode, String> {
         "label" => Ok(UiNode::Label {
             id: attr_opt(n, "id"),
             text: attr(n, "text").unwrap_or_default(),
+            dir: attr_dir(n),
         }),
         "button" => {
             let id = attr(n, "id").ok_or_else(|| "button requires id".to_string())?;
             let text = attr(n, "text").unwrap_or_else(|| "Button".to_string());
 
             let mut on_click = SmallVec::<[String; 2]>::new();
-            parse_actions_for(&n, UiEventKind::Click, &mut on_click);
+            parse_actions_for(n, UiEventKind::Click, &mut on_click);
 
-            Ok(UiNode::Button { id, text, on_click })
+            Ok(UiNode::Button {
+                id,
+                text,
+                dir: attr_dir(n),
+                on_click,
+            })
         }
-        "textbox" | "input" => {
+        "textbox" | "textfield" | "input" => {
             let id = attr(n, "id").unwrap_or_else(|| "textbox".to_string());
             let bind = attr(n, "bind").unwrap_or_else(|| id.clone());
             let hint = attr(n, "hint").unwrap_or_default();
             let multiline = attr(n, "multiline")
                 .map(|v| v == "true" || v == "1" || v == "yes")
                 .unwrap_or(false);
+            let password = attr_bool(n, "password").unwrap_or(false);
+            let readonly = attr_bool(n, "readonly").unwrap_or(false);
+            let max_length = attr_str(n, "max_length")
+                .and_then(|s| s.parse::<usize>().ok())
+                .filter(|n| *n > 0);
 
             let mut on_change = SmallVec::<[String; 2]>::new();
             let mut on_submit = SmallVec::<[String; 2]>::new();
-            parse_actions_for(&n, UiEventKind::Change, &mut on_change);
-            parse_actions_for(&n, UiEventKind::Submit, &mut on_submit);
+            parse_actions_for(n, UiEventKind::Change, &mut on_change);
+            parse_actions_for(n, UiEventKind::Submit, &mut on_submit);
 
             Ok(UiNode::TextBox {
                 id,
                 hint,
                 bind,
                 multiline,
+                password,
+                readonly,
+                max_length,
                 on_change,
                 on_submit,
             })
         }
         "spacer" => Ok(UiNode::Spacer),
+        "list" | "tree" => {
+            let id = attr(n, "id").ok_or_else(|| format!("{tag} requires id"))?;
+            let view = parse_list_view(n);
+            Ok(if tag == "list" {
+                UiNode::List { id, view }
+            } else {
+                UiNode::Tree { id, view }
+            })
+        }
+        "dock" => parse_dock(n),
         _ => Ok(UiNode::Unknown {
             tag: tag.to_string(),
             children: parse_children(n)?,
@@ -133,21 +156,138 @@ fn parse_node(n: Node) -> Result<UiNode, String> {
     }
 }
 
-fn attr(n: Node, key: &str) -> Option<String> {
+fn parse_list_view(n: &XmlElement) -> ListViewNode {
+    let mut on_select = SmallVec::<[String; 2]>::new();
+    let mut on_activate = SmallVec::<[String; 2]>::new();
+    parse_actions_for(n, UiEventKind::Select, &mut on_select);
+    parse_actions_for(n, UiEventKind::Activate, &mut on_activate);
+
+    ListViewNode {
+        row_height: attr_f32(n, "row_height").map(|h| h.clamp(8.0, 256.0)),
+        height: attr_f32(n, "height").filter(|h| *h > 0.0),
+        on_select,
+        on_activate,
+    }
+}
+
+fn parse_dock(n: &XmlElement) -> Result<UiNode, String> {
+    let id = attr(n, "id").ok_or_else(|| "dock requires id".to_string())?;
+
+    let mut panels = Vec::new();
+    let mut parts = Vec::new();
+    for c in n.children.iter() {
+        if let Some(part) = parse_dock_node(c, &mut panels)? {
+            parts.push(part);
+        }
+    }
+
+    for (i, p) in panels.iter().enumerate() {
+        if panels[..i].iter().any(|q| q.id == p.id) {
+            return Err(format!("dock '{id}': duplicate panel id '{}'", p.id));
+        }
+    }
+
+    Ok(UiNode::Dock {
+        id,
+        layout: DockLayout {
+            root: join_dock_nodes(parts, DockSplit::Horizontal, None),
+        },
+        panels,
+    })
+}
+
+fn parse_dock_node(
+    n: &XmlElement,
+    panels: &mut Vec<DockPanel>,
+) -> Result<Option<DockNode>, String> {
+    match n.tag.as_str() {
+        "panel" => {
+            let p = parse_dock_panel(n)?;
+            let node = DockNode::tabs(p.id.clone());
+            panels.push(p);
+            Ok(Some(node))
+        }
+        "tabs" => {
+            let mut tabs = Vec::new();
+            for c in n.children.iter() {
+                if c.tag != "panel" {
+                    return Err(format!("<tabs> may only contain <panel>, got <{}>", c.tag));
+                }
+                let p = parse_dock_panel(c)?;
+                tabs.push(p.id.clone());
+                panels.push(p);
+            }
+            if tabs.is_empty() {
+                return Ok(None);
+            }
+            let active = attr_str(n, "active")
+                .and_then(|a| tabs.iter().position(|t| t == a).or_else(|| a.parse().ok()))
+                .unwrap_or(0)
+                .min(tabs.len() - 1);
+            Ok(Some(DockNode::Tabs { tabs, active }))
+        }
+        "split" => {
+            let dir = attr_str(n, "dir")
+                .and_then(DockSplit::parse)
+                .unwrap_or(DockSplit::Horizontal);
+            let mut parts = Vec::new();
+            for c in n.children.iter() {
+                if let Some(part) = parse_dock_node(c, panels)? {
+                    parts.push(part);
+                }
+            }
+            Ok(join_dock_nodes(parts, dir, attr_f32(n, "ratio")))
+        }
+        tag => Err(format!(
+            "<dock> may only contain <split>, <tabs> and <panel>, got <{tag}>"
+        )),
+    }
+}
+
+fn parse_dock_panel(n: &XmlElement) -> Result<DockPanel, String> {
+    let id = attr(n, "id").ok_or_else(|| "panel requires id".to_string())?;
+    let title = attr(n, "title").unwrap_or_else(|| id.clone());
+    let closable = attr(n, "closable")
+        .map(|v| v == "true" || v == "1" || v == "yes")
+        .unwrap_or(false);
+
+    Ok(DockPanel {
+        id,
+        title,
+        closable,
+        children: parse_children(n)?,
+    })
+}
+
+/// Nests more than two split children as `a | (b | (c ...))`, sharing the space evenly. An
+/// explicit ratio only applies to a two-way split.
+fn join_dock_nodes(parts: Vec<DockNode>, dir: DockSplit, ratio: Option<f32>) -> Option<DockNode> {
+    let count = parts.len();
+    let mut it = parts.into_iter().rev();
+    let mut acc = it.next()?;
+    for (m, part) in it.enumerate() {
+        let even = 1.0 / (m + 2) as f32;
+        let ratio = ratio.filter(|_| count == 2).unwrap_or(even);
+        acc = DockNode::split(dir, ratio, part, acc);
+    }
+    Some(acc)
+}
+
+fn attr(n: &XmlElement, key: &str) -> Option<String> {
     n.attribute(key).map(|s| s.to_string())
 }
 
-fn attr_opt(n: Node, key: &str) -> Option<String> {
+fn attr_opt(n: &XmlElement, key: &str) -> Option<String> {
     n.attribute(key).map(|s| s.to_string())
 }
 
 #[inline]
-fn attr_str<'a>(n: Node<'a, 'a>, key: &str) -> Option<&'a str> {
+fn attr_str<'a>(n: &'a XmlElement, key: &str) -> Option<&'a str> {
     n.attribute(key).map(|s| s.trim()).filter(|s| !s.is_empty())
 }
 
 #[inline]
-fn attr_any<'a>(n: Node<'a, 'a>, keys: &[&str]) -> Option<&'a str> {
+fn attr_any<'a>(n: &'a XmlElement, keys: &[&str]) -> Option<&'a str> {
     for k in keys {
         if let Some(v) = attr_str(n, k) {
             return Some(v);
@@ -157,6 +297,17 @@ fn attr_any<'a>(n: Node<'a, 'a>, keys: &[&str]) -> Option<&'a str> {
 }
 
 #[inline]
-fn attr_f32(n: Node<'_, '_>, key: &str) -> Option<f32> {
+fn attr_bool(n: &XmlElement, key: &str) -> Option<bool> {
+    attr_str(n, key).map(|v| v == "true" || v == "1" || v == "yes")
+}
+
+#[inline]
+fn attr_dir(n: &XmlElement) -> TextDirection {
+    attr_str(n, "dir")
+        .and_then(TextDirection::parse)
+        .unwrap_or_default()
+}
+
+fn attr_f32(n: &XmlElement, key: &str) -> Option<f32> {
     attr_str(n, key).and_then(|s| s.parse::<f32>().ok())
-}
\ No newline at end of file
+}
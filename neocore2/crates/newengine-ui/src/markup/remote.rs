@@ -0,0 +1,439 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use ahash::AHashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use smallvec::SmallVec;
+use std::collections::BTreeMap;
+
+use crate::markup::error::UiMarkupError;
+use crate::markup::theme::{UiDensity, UiVisuals};
+use crate::markup::ui_node::{ListViewNode, UiNode};
+use crate::markup::{UiEvent, UiEventKind, UiMarkupDoc, UiState};
+
+/// Event as seen by a remote mirror: either reported by the engine UI or sent back by the tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiRemoteEvent {
+    /// `click`, `change`, `submit`, `select` or `activate`.
+    pub kind: String,
+    pub target_id: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<String>,
+}
+
+impl From<&UiEvent> for UiRemoteEvent {
+    fn from(ev: &UiEvent) -> Self {
+        Self {
+            kind: ev.kind.as_str().to_string(),
+            target_id: ev.target_id.clone(),
+            value: ev.value.clone(),
+            actions: ev.actions.iter().cloned().collect(),
+        }
+    }
+}
+
+/// State changes since the previous diff. `None` values mark removed keys.
+///
+/// A `full` diff replaces the remote state entirely; it is sent first and whenever the remote
+/// falls behind the retained history.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UiStateDiff {
+    pub seq: u64,
+    pub full: bool,
+    pub strings: BTreeMap<String, Option<String>>,
+    pub vars: BTreeMap<String, Option<String>>,
+    /// Formatted `UiBindings` values.
+    #[serde(default)]
+    pub bindings: BTreeMap<String, Option<String>>,
+    pub events: Vec<UiRemoteEvent>,
+}
+
+impl UiStateDiff {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        !self.full
+            && self.strings.is_empty()
+            && self.vars.is_empty()
+            && self.bindings.is_empty()
+            && self.events.is_empty()
+    }
+}
+
+/// Tracks what a remote mirror has already seen and produces incremental diffs.
+#[derive(Debug, Default)]
+pub struct UiStateSync {
+    seq: u64,
+    strings: AHashMap<String, String>,
+    vars: AHashMap<String, String>,
+    bindings: AHashMap<String, String>,
+    bindings_rev: u64,
+}
+
+impl UiStateSync {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sequence number of the last produced diff (0 = nothing sent yet).
+    #[inline]
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Full snapshot; resets the baseline.
+    pub fn full(&mut self, state: &UiState) -> UiStateDiff {
+        self.seq += 1;
+        self.strings = state.strings.clone();
+        self.vars = state.vars.clone();
+        self.bindings_rev = state.bindings().revision();
+        self.bindings = state.bindings().snapshot();
+
+        UiStateDiff {
+            seq: self.seq,
+            full: true,
+            strings: state
+                .strings
+                .iter()
+                .map(|(k, v)| (k.clone(), Some(v.clone())))
+                .collect(),
+            vars: state
+                .vars
+                .iter()
+                .map(|(k, v)| (k.clone(), Some(v.clone())))
+                .collect(),
+            bindings: self
+                .bindings
+                .iter()
+                .map(|(k, v)| (k.clone(), Some(v.clone())))
+                .collect(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Changes since the last call, plus `events` emitted this frame. `None` if nothing changed.
+    pub fn diff(&mut self, state: &UiState, events: &[UiEvent]) -> Option<UiStateDiff> {
+        if self.seq == 0 {
+            let mut d = self.full(state);
+            d.events = events.iter().map(UiRemoteEvent::from).collect();
+            return Some(d);
+        }
+
+        let rev = state.bindings().revision();
+        let bindings = if rev != self.bindings_rev {
+            self.bindings_rev = rev;
+            diff_map(&mut self.bindings, &state.bindings().snapshot())
+        } else {
+            BTreeMap::new()
+        };
+
+        let mut d = UiStateDiff {
+            strings: diff_map(&mut self.strings, &state.strings),
+            vars: diff_map(&mut self.vars, &state.vars),
+            bindings,
+            events: events.iter().map(UiRemoteEvent::from).collect(),
+            ..Default::default()
+        };
+
+        if d.is_empty() {
+            return None;
+        }
+
+        self.seq += 1;
+        d.seq = self.seq;
+        Some(d)
+    }
+}
+
+fn diff_map(
+    seen: &mut AHashMap<String, String>,
+    now: &AHashMap<String, String>,
+) -> BTreeMap<String, Option<String>> {
+    let mut out = BTreeMap::new();
+
+    for (k, v) in now.iter() {
+        if seen.get(k) != Some(v) {
+            out.insert(k.clone(), Some(v.clone()));
+        }
+    }
+    for k in seen.keys() {
+        if !now.contains_key(k) {
+            out.insert(k.clone(), None);
+        }
+    }
+
+    for (k, v) in out.iter() {
+        match v {
+            Some(v) => {
+                seen.insert(k.clone(), v.clone());
+            }
+            None => {
+                seen.remove(k);
+            }
+        }
+    }
+
+    out
+}
+
+impl UiMarkupDoc {
+    /// Composed document tree and theme as JSON, for rendering outside the engine.
+    pub fn to_remote_json(&self) -> Value {
+        let visuals = match self.theme.visuals {
+            UiVisuals::Auto => "auto",
+            UiVisuals::Dark => "dark",
+            UiVisuals::Light => "light",
+            UiVisuals::HighContrast => "high_contrast",
+        };
+        let density = match self.theme.density {
+            UiDensity::Default => "default",
+            UiDensity::Compact => "compact",
+            UiDensity::Dense => "dense",
+            UiDensity::Tight => "tight",
+        };
+
+        json!({
+            "theme": {
+                "visuals": visuals,
+                "scale": self.theme.scale,
+                "font_size": self.theme.font_size,
+                "density": density,
+            },
+            "root": node_json(&self.root),
+        })
+    }
+
+    /// Applies an event sent by a remote mirror as if the local user had interacted with the
+    /// widget: state is updated and the usual `UiEvent` (with markup actions) is queued.
+    pub fn apply_remote_event(
+        &self,
+        state: &mut UiState,
+        ev: &UiRemoteEvent,
+    ) -> Result<(), UiMarkupError> {
+        let kind = UiEventKind::parse(&ev.kind)
+            .ok_or_else(|| UiMarkupError::Remote(format!("unknown event kind '{}'", ev.kind)))?;
+
+        let node = find_node(&self.root, &ev.target_id).ok_or_else(|| {
+            UiMarkupError::Remote(format!("unknown target '{}'", ev.target_id))
+        })?;
+
+        match (kind, node) {
+            (UiEventKind::Click, UiNode::Button { id, on_click, .. }) => {
+                state.clicked.insert(id.clone(), true);
+                if !on_click.is_empty() {
+                    state.push_event(UiEvent {
+                        kind,
+                        target_id: id.clone(),
+                        value: None,
+                        actions: on_click.clone(),
+                    });
+                }
+                Ok(())
+            }
+            (
+                UiEventKind::Change,
+                UiNode::TextBox {
+                    id,
+                    bind,
+                    readonly: false,
+                    on_change,
+                    ..
+                },
+            ) => {
+                let value = ev.value.clone().unwrap_or_default();
+                state.strings.insert(bind.clone(), value.clone());
+                state.vars.insert(id.clone(), value.clone());
+                state.push_bound_input(bind, &value);
+                if !on_change.is_empty() {
+                    state.push_event(UiEvent {
+                        kind,
+                        target_id: id.clone(),
+                        value: Some(value),
+                        actions: on_change.clone(),
+                    });
+                }
+                Ok(())
+            }
+            (
+                UiEventKind::Submit,
+                UiNode::TextBox {
+                    id,
+                    bind,
+                    on_submit,
+                    ..
+                },
+            ) => {
+                let value = match ev.value.clone() {
+                    Some(v) => {
+                        state.strings.insert(bind.clone(), v.clone());
+                        state.push_bound_input(bind, &v);
+                        v
+                    }
+                    None => state.strings.get(bind).cloned().unwrap_or_default(),
+                };
+                if !on_submit.is_empty() {
+                    state.push_event(UiEvent {
+                        kind,
+                        target_id: id.clone(),
+                        value: Some(value),
+                        actions: on_submit.clone(),
+                    });
+                }
+                Ok(())
+            }
+            // Rows are not mirrored, so a remote select/activate only raises the event; the
+            // local selection is left alone.
+            (
+                UiEventKind::Select | UiEventKind::Activate,
+                UiNode::List { id, view } | UiNode::Tree { id, view },
+            ) => {
+                let actions = match kind {
+                    UiEventKind::Select => &view.on_select,
+                    _ => &view.on_activate,
+                };
+                if !actions.is_empty() {
+                    state.push_event(UiEvent {
+                        kind,
+                        target_id: id.clone(),
+                        value: ev.value.clone(),
+                        actions: actions.clone(),
+                    });
+                }
+                Ok(())
+            }
+            _ => Err(UiMarkupError::Remote(format!(
+                "event '{}' does not apply to '{}'",
+                ev.kind, ev.target_id
+            ))),
+        }
+    }
+}
+
+fn find_node<'a>(node: &'a UiNode, target: &str) -> Option<&'a UiNode> {
+    match node {
+        UiNode::Button { id, .. }
+        | UiNode::TextBox { id, .. }
+        | UiNode::List { id, .. }
+        | UiNode::Tree { id, .. }
+            if id == target =>
+        {
+            Some(node)
+        }
+        UiNode::Ui { children }
+        | UiNode::TopBar { children }
+        | UiNode::Window { children, .. }
+        | UiNode::Row { children }
+        | UiNode::Column { children }
+        | UiNode::Unknown { children, .. } => children.iter().find_map(|c| find_node(c, target)),
+        UiNode::Dock { panels, .. } => panels
+            .iter()
+            .flat_map(|p| p.children.iter())
+            .find_map(|c| find_node(c, target)),
+        _ => None,
+    }
+}
+
+#[inline]
+fn actions_json(a: &SmallVec<[String; 2]>) -> Value {
+    Value::from(a.iter().cloned().collect::<Vec<_>>())
+}
+
+#[inline]
+fn children_json(children: &[UiNode]) -> Value {
+    Value::from(children.iter().map(node_json).collect::<Vec<_>>())
+}
+
+fn list_view_json(ty: &str, id: &str, view: &ListViewNode) -> Value {
+    json!({
+        "type": ty,
+        "id": id,
+        "row_height": view.row_height,
+        "height": view.height,
+        "on_select": actions_json(&view.on_select),
+        "on_activate": actions_json(&view.on_activate),
+    })
+}
+
+fn node_json(node: &UiNode) -> Value {
+    match node {
+        UiNode::Ui { children } => json!({ "type": "ui", "children": children_json(children) }),
+        UiNode::TopBar { children } => {
+            json!({ "type": "topbar", "children": children_json(children) })
+        }
+        UiNode::Window {
+            title,
+            open,
+            children,
+        } => json!({
+            "type": "window",
+            "title": title,
+            "open": open,
+            "children": children_json(children),
+        }),
+        UiNode::Row { children } => json!({ "type": "row", "children": children_json(children) }),
+        UiNode::Column { children } => {
+            json!({ "type": "column", "children": children_json(children) })
+        }
+        UiNode::Label { id, text, dir } => {
+            json!({ "type": "label", "id": id, "text": text, "dir": dir.as_str() })
+        }
+        UiNode::Button {
+            id,
+            text,
+            dir,
+            on_click,
+        } => json!({
+            "type": "button",
+            "id": id,
+            "text": text,
+            "dir": dir.as_str(),
+            "on_click": actions_json(on_click),
+        }),
+        UiNode::TextBox {
+            id,
+            hint,
+            bind,
+            multiline,
+            password,
+            readonly,
+            max_length,
+            on_change,
+            on_submit,
+        } => json!({
+            "type": "textbox",
+            "id": id,
+            "hint": hint,
+            "bind": bind,
+            "multiline": multiline,
+            "password": password,
+            "readonly": readonly,
+            "max_length": max_length,
+            "on_change": actions_json(on_change),
+            "on_submit": actions_json(on_submit),
+        }),
+        UiNode::Spacer => json!({ "type": "spacer" }),
+        UiNode::List { id, view } => list_view_json("list", id, view),
+        UiNode::Tree { id, view } => list_view_json("tree", id, view),
+        UiNode::Dock { id, layout, panels } => json!({
+            "type": "dock",
+            "id": id,
+            "layout": layout,
+            "panels": panels
+                .iter()
+                .map(|p| json!({
+                    "id": p.id,
+                    "title": p.title,
+                    "closable": p.closable,
+                    "children": children_json(&p.children),
+                }))
+                .collect::<Vec<_>>(),
+        }),
+        UiNode::Unknown { tag, children } => json!({
+            "type": "unknown",
+            "tag": tag,
+            "children": children_json(children),
+        }),
+    }
+}
@@ -1,13 +1,54 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
-use ahash::AHashMap;
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::Arc;
+
+use ahash::{AHashMap, AHashSet};
 use smallvec::SmallVec;
 
+use crate::input::UiFocusStep;
+use crate::markup::actions::UiAction;
+use crate::markup::bindings::{bind_key, has_bindings, template_keys, UiBindings};
+use crate::markup::dock::{DockLayout, DockTarget};
+use crate::markup::focus::FocusChain;
+use crate::markup::list_view::{ListView, TreeView, UiListModel, UiTreeModel};
+use crate::markup::substitute::substitute_vars;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UiEventKind {
     Click,
     Change,
     Submit,
+    /// A list or tree row was selected; the value is the row's model value.
+    Select,
+    /// A list or tree row was double-clicked.
+    Activate,
+}
+
+impl UiEventKind {
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UiEventKind::Click => "click",
+            UiEventKind::Change => "change",
+            UiEventKind::Submit => "submit",
+            UiEventKind::Select => "select",
+            UiEventKind::Activate => "activate",
+        }
+    }
+
+    #[inline]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "click" => Some(UiEventKind::Click),
+            "change" => Some(UiEventKind::Change),
+            "submit" => Some(UiEventKind::Submit),
+            "select" => Some(UiEventKind::Select),
+            "activate" => Some(UiEventKind::Activate),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -18,6 +59,24 @@ pub struct UiEvent {
     pub actions: SmallVec<[String; 2]>,
 }
 
+impl UiEvent {
+    /// Markup actions of this event, parsed, with `$value` filled in.
+    pub fn resolved_actions(&self) -> impl Iterator<Item = UiAction> + '_ {
+        self.actions
+            .iter()
+            .map(|a| UiAction::parse(a).with_value(self.value.as_deref()))
+    }
+}
+
+/// Cached expansion of one `{{...}}` template.
+#[derive(Debug)]
+struct BoundText {
+    keys: SmallVec<[String; 2]>,
+    /// Bindings revision the text is known to be current for.
+    rev: u64,
+    text: String,
+}
+
 #[derive(Debug, Default)]
 pub struct UiState {
     pub strings: AHashMap<String, String>,
@@ -26,6 +85,26 @@ pub struct UiState {
     pub unknown_tags: AHashMap<String, u32>,
 
     events: Vec<UiEvent>,
+
+    docks: AHashMap<String, DockLayout>,
+    docks_changed: AHashSet<String>,
+    /// `(dock, panel)` whose tab is being dragged.
+    dock_drag: Option<(String, String)>,
+
+    lists: AHashMap<String, ListView>,
+    trees: AHashMap<String, TreeView>,
+
+    focus: FocusChain,
+    /// Textbox selections (char indices) as of the last frame.
+    selections: AHashMap<String, Range<usize>>,
+    /// Selections to apply when the textbox is next rendered.
+    selection_requests: AHashMap<String, Range<usize>>,
+
+    bindings: UiBindings,
+    bound_text: AHashMap<String, BoundText>,
+    /// Two-way textbox bind -> binding revision last copied into `strings`.
+    bound_inputs: AHashMap<String, u64>,
+    seen_revision: u64,
 }
 
 impl UiState {
@@ -39,6 +118,90 @@ impl UiState {
         self.vars.insert(k.into(), v.into());
     }
 
+    #[inline]
+    pub fn bindings(&self) -> &UiBindings {
+        &self.bindings
+    }
+
+    /// Replaces the binding store, e.g. with `UiBindings::shared()` so modules can feed the UI.
+    pub fn set_bindings(&mut self, bindings: UiBindings) {
+        self.bindings = bindings;
+        self.bound_text.clear();
+        self.bound_inputs.clear();
+        self.seen_revision = 0;
+    }
+
+    #[cfg(feature = "egui")]
+    /// Expands `{{...}}` bindings, then `$vars`. Bound text is re-formatted only when a value
+    /// it references changed.
+    pub(crate) fn resolve_text<'a>(&mut self, src: &'a str) -> Cow<'a, str> {
+        if !has_bindings(src) {
+            return substitute_vars(src, &self.vars);
+        }
+
+        let rev = self.bindings.revision();
+        let stale = match self.bound_text.get_mut(src) {
+            Some(b) if b.rev == rev => false,
+            Some(b) if self.bindings.keys_revision(&b.keys) <= b.rev => {
+                b.rev = rev;
+                false
+            }
+            _ => true,
+        };
+        if stale {
+            let (text, rev) = self.bindings.render(src);
+            self.bound_text.insert(
+                src.to_string(),
+                BoundText {
+                    keys: template_keys(src),
+                    rev,
+                    text,
+                },
+            );
+        }
+
+        let text = self
+            .bound_text
+            .get(src)
+            .map(|b| b.text.as_str())
+            .unwrap_or(src);
+        Cow::Owned(substitute_vars(text, &self.vars).into_owned())
+    }
+
+    #[cfg(feature = "egui")]
+    /// Refreshes the edit buffer of a `bind="{{key}}"` textbox when the value changed outside
+    /// the UI.
+    pub(crate) fn pull_bound_input(&mut self, bind: &str) {
+        let Some(key) = bind_key(bind) else {
+            return;
+        };
+        let rev = self.bindings.key_revision(key);
+        if self.bound_inputs.get(bind).is_some_and(|seen| *seen >= rev) {
+            return;
+        }
+        if let Some(v) = self.bindings.get(key) {
+            self.strings.insert(bind.to_string(), v.to_string());
+        }
+        self.bound_inputs.insert(bind.to_string(), rev);
+    }
+
+    /// Writes an edited textbox value back to its binding, if `bind` is one.
+    pub(crate) fn push_bound_input(&mut self, bind: &str, value: &str) {
+        let Some(key) = bind_key(bind) else {
+            return;
+        };
+        self.bindings.set(key, value);
+        let rev = self.bindings.key_revision(key);
+        self.bound_inputs.insert(bind.to_string(), rev);
+    }
+
+    #[cfg(feature = "egui")]
+    /// `true` once per bindings change; providers use it to schedule a repaint.
+    pub(crate) fn take_bindings_changed(&mut self) -> bool {
+        let rev = self.bindings.revision();
+        rev != std::mem::replace(&mut self.seen_revision, rev)
+    }
+
     #[inline]
     pub fn drain_events(&mut self) -> Vec<UiEvent> {
         std::mem::take(&mut self.events)
@@ -48,4 +211,216 @@ impl UiState {
     pub(crate) fn push_event(&mut self, ev: UiEvent) {
         self.events.push(ev);
     }
-}
\ No newline at end of file
+
+    /// Current layout of dock `dock`; `None` until the dock has been rendered or a layout has
+    /// been set.
+    #[inline]
+    pub fn dock_layout(&self, dock: &str) -> Option<&DockLayout> {
+        self.docks.get(dock)
+    }
+
+    /// Replaces the layout of `dock`. Panels unknown to the markup are dropped on the next frame.
+    #[inline]
+    pub fn set_dock_layout(&mut self, dock: impl Into<String>, layout: DockLayout) {
+        let dock = dock.into();
+        self.docks.insert(dock.clone(), layout);
+        self.docks_changed.insert(dock);
+    }
+
+    /// Layout of `dock` as JSON, for persisting between sessions.
+    #[inline]
+    pub fn dock_layout_json(&self, dock: &str) -> Option<String> {
+        self.docks.get(dock).map(DockLayout::to_json)
+    }
+
+    pub fn load_dock_layout_json(
+        &mut self,
+        dock: impl Into<String>,
+        json: &str,
+    ) -> Result<(), String> {
+        let layout = DockLayout::from_json(json)?;
+        self.set_dock_layout(dock, layout);
+        Ok(())
+    }
+
+    /// Opens or moves `panel` next to `target`; see [`DockLayout::dock`].
+    pub fn dock_panel(
+        &mut self,
+        dock: &str,
+        panel: &str,
+        target: Option<&str>,
+        at: DockTarget,
+    ) -> bool {
+        self.with_dock(dock, |l| l.dock(panel, target, at))
+    }
+
+    #[inline]
+    pub fn close_dock_panel(&mut self, dock: &str, panel: &str) -> bool {
+        self.with_dock(dock, |l| l.close(panel))
+    }
+
+    #[inline]
+    pub fn focus_dock_panel(&mut self, dock: &str, panel: &str) -> bool {
+        self.with_dock(dock, |l| l.focus(panel))
+    }
+
+    /// `true` once after the layout of `dock` changed, whether by the user or through this API.
+    #[inline]
+    pub fn take_dock_changed(&mut self, dock: &str) -> bool {
+        self.docks_changed.remove(dock)
+    }
+
+    fn with_dock(&mut self, dock: &str, f: impl FnOnce(&mut DockLayout) -> bool) -> bool {
+        let Some(layout) = self.docks.get_mut(dock) else {
+            return false;
+        };
+        let changed = f(layout);
+        if changed {
+            self.docks_changed.insert(dock.to_string());
+        }
+        changed
+    }
+
+    /// Feeds `<list id>` from `model`. The selection is kept as long as its key still exists.
+    pub fn set_list_model(&mut self, id: impl Into<String>, model: Arc<dyn UiListModel>) {
+        self.lists.entry(id.into()).or_default().model = Some(model);
+    }
+
+    /// Feeds `<tree id>` from `model`; expanded nodes and the selection are kept.
+    pub fn set_tree_model(&mut self, id: impl Into<String>, model: Arc<dyn UiTreeModel>) {
+        self.trees.entry(id.into()).or_default().set_model(model);
+    }
+
+    /// Key of the selected row of list `id`, or node of tree `id`.
+    #[inline]
+    pub fn selected(&self, id: &str) -> Option<u64> {
+        match self.lists.get(id) {
+            Some(l) => l.selected,
+            None => self.trees.get(id).and_then(|t| t.selected),
+        }
+    }
+
+    /// Selects a row (list key or tree node) without raising a select event.
+    pub fn set_selected(&mut self, id: &str, key: Option<u64>) {
+        if let Some(l) = self.lists.get_mut(id) {
+            l.selected = key;
+        } else {
+            self.trees.entry(id.to_string()).or_default().selected = key;
+        }
+    }
+
+    #[inline]
+    pub fn is_tree_expanded(&self, id: &str, node: u64) -> bool {
+        self.trees
+            .get(id)
+            .is_some_and(|t| t.expanded.contains(&node))
+    }
+
+    /// Returns `true` when the expansion changed.
+    pub fn set_tree_expanded(&mut self, id: &str, node: u64, expanded: bool) -> bool {
+        if !expanded && !self.trees.contains_key(id) {
+            return false;
+        }
+        self.trees
+            .entry(id.to_string())
+            .or_default()
+            .set_expanded(node, expanded)
+    }
+
+    /// Markup id of the textbox or button holding keyboard focus.
+    #[inline]
+    pub fn focused(&self) -> Option<&str> {
+        self.focus.focused()
+    }
+
+    /// Gives keyboard focus to textbox or button `id` when it is next rendered.
+    #[inline]
+    pub fn request_focus(&mut self, id: impl Into<String>) {
+        self.focus.request(id);
+    }
+
+    /// Moves focus along the tab order (render order), as Tab and Shift+Tab do.
+    #[inline]
+    pub fn move_focus(&mut self, step: UiFocusStep) {
+        self.focus.step(step);
+    }
+
+    /// Selected characters of textbox `id` as char indices; empty at the caret, `None` when it
+    /// has no cursor.
+    #[inline]
+    pub fn text_selection(&self, id: &str) -> Option<Range<usize>> {
+        self.selections.get(id).cloned()
+    }
+
+    /// Selects characters of textbox `id`, clamped to its text, when it is next rendered.
+    #[inline]
+    pub fn set_text_selection(&mut self, id: impl Into<String>, range: Range<usize>) {
+        self.selection_requests.insert(id.into(), range);
+    }
+
+    #[inline]
+    pub fn select_all_text(&mut self, id: impl Into<String>) {
+        self.set_text_selection(id, 0..usize::MAX);
+    }
+
+    #[cfg(feature = "egui")]
+    #[inline]
+    pub(crate) fn focus_mut(&mut self) -> &mut FocusChain {
+        &mut self.focus
+    }
+
+    #[cfg(feature = "egui")]
+    #[inline]
+    pub(crate) fn take_selection_request(&mut self, id: &str) -> Option<Range<usize>> {
+        self.selection_requests.remove(id)
+    }
+
+    #[cfg(feature = "egui")]
+    pub(crate) fn put_text_selection(&mut self, id: &str, range: Option<Range<usize>>) {
+        match range {
+            Some(r) => {
+                self.selections.insert(id.to_string(), r);
+            }
+            None => {
+                self.selections.remove(id);
+            }
+        }
+    }
+
+    #[cfg(feature = "egui")]
+    pub(crate) fn list_view(&mut self, id: &str) -> &mut ListView {
+        self.lists.entry(id.to_string()).or_default()
+    }
+
+    #[cfg(feature = "egui")]
+    pub(crate) fn tree_view(&mut self, id: &str) -> &mut TreeView {
+        self.trees.entry(id.to_string()).or_default()
+    }
+
+    /// Takes the layout of `dock` out for rendering; `initial` is used the first time.
+    #[cfg(feature = "egui")]
+    pub(crate) fn take_dock_layout(&mut self, dock: &str, initial: &DockLayout) -> DockLayout {
+        self.docks.remove(dock).unwrap_or_else(|| initial.clone())
+    }
+
+    #[cfg(feature = "egui")]
+    pub(crate) fn put_dock_layout(&mut self, dock: &str, layout: DockLayout, changed: bool) {
+        if changed {
+            self.docks_changed.insert(dock.to_string());
+        }
+        self.docks.insert(dock.to_string(), layout);
+    }
+
+    #[cfg(feature = "egui")]
+    pub(crate) fn dock_drag(&self, dock: &str) -> Option<&str> {
+        self.dock_drag
+            .as_ref()
+            .filter(|(d, _)| d == dock)
+            .map(|(_, p)| p.as_str())
+    }
+
+    #[cfg(feature = "egui")]
+    pub(crate) fn set_dock_drag(&mut self, drag: Option<(String, String)>) {
+        self.dock_drag = drag;
+    }
+}
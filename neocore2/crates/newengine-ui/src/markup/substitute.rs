@@ -40,4 +40,36 @@ pub fn substitute_vars<'a>(src: &'a str, vars: &AHashMap<String, String>) -> Cow
 #[inline]
 fn is_var_char(c: u8) -> bool {
     matches!(c, b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'.' | b'-')
-}
\ No newline at end of file
+}
+/// Load-time component/include parameters: replaces `${name}`.
+/// Unknown names are left untouched so runtime `$vars` keep working.
+pub(crate) fn substitute_params(src: &str, params: &AHashMap<String, String>) -> String {
+    if params.is_empty() || !src.contains("${") {
+        return src.to_string();
+    }
+
+    let mut out = String::with_capacity(src.len());
+    let mut rest = src;
+
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                let key = after[..end].trim();
+                match params.get(key) {
+                    Some(v) => out.push_str(v),
+                    None => out.push_str(&rest[start..start + 2 + end + 1]),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+
+    out
+}
@@ -5,6 +5,7 @@ pub enum UiVisuals {
     Auto,
     Dark,
     Light,
+    HighContrast,
 }
 
 impl Default for UiVisuals {
@@ -2,6 +2,9 @@
 
 use smallvec::SmallVec;
 
+use crate::markup::dock::DockLayout;
+use crate::text::TextDirection;
+
 #[derive(Debug, Clone)]
 pub(crate) enum UiNode {
     Ui {
@@ -22,28 +25,76 @@ pub(crate) enum UiNode {
         children: Vec<UiNode>,
     },
 
+    /// `dir="ltr|rtl|auto"` sets the paragraph direction of the text.
     Label {
         id: Option<String>,
         text: String,
+        dir: TextDirection,
     },
     Button {
         id: String,
         text: String,
+        dir: TextDirection,
         on_click: SmallVec<[String; 2]>,
     },
+    /// `<textbox>` / `<textfield>`: editable text with selection, clipboard and IME input.
     TextBox {
         id: String,
         hint: String,
         bind: String,
         multiline: bool,
+        /// Shows bullets instead of the characters.
+        password: bool,
+        /// Selectable and copyable, not editable.
+        readonly: bool,
+        /// Maximum length in characters.
+        max_length: Option<usize>,
         on_change: SmallVec<[String; 2]>,
         on_submit: SmallVec<[String; 2]>,
     },
 
     Spacer,
 
+    /// `<list id>`: flat rows from the model set with `UiState::set_list_model`; only the rows
+    /// in view are laid out.
+    List {
+        id: String,
+        view: ListViewNode,
+    },
+    /// `<tree id>`: expandable rows from the model set with `UiState::set_tree_model`.
+    Tree {
+        id: String,
+        view: ListViewNode,
+    },
+
+    /// `<dock id>` holding `<split dir ratio>`, `<tabs active>` and `<panel id title closable>`;
+    /// `layout` is the arrangement as written, the live one is kept in `UiState`.
+    Dock {
+        id: String,
+        layout: DockLayout,
+        panels: Vec<DockPanel>,
+    },
+
     Unknown {
         tag: String,
         children: Vec<UiNode>,
     },
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ListViewNode {
+    /// Fixed row height; the theme's interact height when unset.
+    pub(crate) row_height: Option<f32>,
+    /// Height of the scroll area; the remaining space when unset.
+    pub(crate) height: Option<f32>,
+    pub(crate) on_select: SmallVec<[String; 2]>,
+    pub(crate) on_activate: SmallVec<[String; 2]>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DockPanel {
+    pub(crate) id: String,
+    pub(crate) title: String,
+    pub(crate) closable: bool,
+    pub(crate) children: Vec<UiNode>,
+}
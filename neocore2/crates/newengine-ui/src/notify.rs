@@ -0,0 +1,172 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Process-wide toast notifications.
+//!
+//! Any thread may post through [`NotifyApi::toast`]; the active UI provider draws the live
+//! toasts on top of each frame. Clicking a toast action queues its console command, which
+//! the host drains with [`NotifyApi::take_activated`] and executes.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Oldest toasts are dropped beyond this many.
+const MAX_TOASTS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToastLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl ToastLevel {
+    /// How long a toast stays up without being dismissed.
+    #[inline]
+    pub fn ttl(self) -> Duration {
+        match self {
+            Self::Info => Duration::from_secs(4),
+            Self::Warning => Duration::from_secs(8),
+            Self::Error => Duration::from_secs(15),
+        }
+    }
+}
+
+/// Button shown on a toast; `command` is a console line run by the host when clicked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToastAction {
+    pub label: String,
+    pub command: String,
+}
+
+impl ToastAction {
+    #[inline]
+    pub fn new(label: impl Into<String>, command: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            command: command.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub id: u64,
+    pub level: ToastLevel,
+    pub message: String,
+    pub action: Option<ToastAction>,
+    /// Last time the toast was posted; repeats of the same message refresh it.
+    pub posted: Instant,
+    /// Number of identical posts folded into this toast.
+    pub count: u32,
+}
+
+impl Toast {
+    /// Fraction of the lifetime left, `1.0` when fresh.
+    #[inline]
+    pub fn remaining(&self, now: Instant) -> f32 {
+        let ttl = self.level.ttl().as_secs_f32();
+        let age = now.saturating_duration_since(self.posted).as_secs_f32();
+        (1.0 - age / ttl).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Default)]
+struct Hub {
+    next_id: u64,
+    toasts: VecDeque<Toast>,
+    activated: Vec<ToastAction>,
+}
+
+static HUB: OnceLock<Mutex<Hub>> = OnceLock::new();
+
+#[inline]
+fn hub() -> &'static Mutex<Hub> {
+    HUB.get_or_init(|| Mutex::new(Hub::default()))
+}
+
+/// Entry point for posting and consuming toast notifications.
+pub struct NotifyApi;
+
+impl NotifyApi {
+    /// Posts a toast and returns its id. An identical live toast is refreshed instead of
+    /// stacking a duplicate.
+    pub fn toast(
+        level: ToastLevel,
+        message: impl Into<String>,
+        action: Option<ToastAction>,
+    ) -> u64 {
+        let message = message.into();
+        let Ok(mut g) = hub().lock() else {
+            return 0;
+        };
+
+        let now = Instant::now();
+        if let Some(t) = g
+            .toasts
+            .iter_mut()
+            .find(|t| t.level == level && t.message == message && t.action == action)
+        {
+            t.posted = now;
+            t.count = t.count.saturating_add(1);
+            return t.id;
+        }
+
+        g.next_id += 1;
+        let id = g.next_id;
+        if g.toasts.len() >= MAX_TOASTS {
+            g.toasts.pop_front();
+        }
+        g.toasts.push_back(Toast {
+            id,
+            level,
+            message,
+            action,
+            posted: now,
+            count: 1,
+        });
+        id
+    }
+
+    pub fn dismiss(id: u64) {
+        if let Ok(mut g) = hub().lock() {
+            g.toasts.retain(|t| t.id != id);
+        }
+    }
+
+    /// Live toasts, oldest first. Expired ones are dropped here.
+    pub fn active() -> Vec<Toast> {
+        let Ok(mut g) = hub().lock() else {
+            return Vec::new();
+        };
+        let now = Instant::now();
+        g.toasts
+            .retain(|t| now.saturating_duration_since(t.posted) < t.level.ttl());
+        g.toasts.iter().cloned().collect()
+    }
+
+    /// Called by providers when a toast's action button is clicked; closes the toast.
+    pub fn activate(id: u64) {
+        let Ok(mut g) = hub().lock() else {
+            return;
+        };
+        let Some(pos) = g.toasts.iter().position(|t| t.id == id) else {
+            return;
+        };
+        if let Some(t) = g.toasts.remove(pos) {
+            if let Some(a) = t.action {
+                g.activated.push(a);
+            }
+        }
+    }
+
+    /// Actions clicked since the last call, in click order.
+    pub fn take_activated() -> Vec<ToastAction> {
+        hub()
+            .lock()
+            .map(|mut g| std::mem::take(&mut g.activated))
+            .unwrap_or_default()
+    }
+}
@@ -84,6 +84,13 @@ pub trait UiProvider: Send {
     /// IMPORTANT: UI must not consume platform input directly; input must come from INPUT plugin.
     fn on_platform_event(&mut self, _window: &dyn Any, _event: &dyn Any) {}
 
+    /// Attach screen-reader support to a freshly created, not yet visible window.
+    /// `proxy` is the host event loop proxy carrying `UiUserEvent`.
+    fn init_accessibility(&mut self, _window: &dyn Any, _proxy: &dyn Any) {}
+
+    /// Feed a `UiUserEvent` delivered through the host event loop (optional).
+    fn on_user_event(&mut self, _window: &dyn Any, _event: &dyn Any) {}
+
     /// Run one UI frame.
     fn run_frame(
         &mut self,
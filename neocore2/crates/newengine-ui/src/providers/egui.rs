@@ -1,16 +1,37 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+use crate::accessibility::{
+    accessibility, accessibility_generation, high_contrast_visuals, UiUserEvent,
+};
+use crate::console::ConsoleApi;
 use crate::draw::UiDrawList;
-use crate::input::UiInputFrame;
+use crate::input::{put_focus_steps, UiFocusStep, UiInputFrame};
 use crate::provider::{UiBuildFn, UiFrameDesc, UiFrameOutput, UiProvider, UiProviderKind};
+use crate::text::egui_text::font_definitions;
+use crate::text::{fonts, fonts_generation};
 use std::any::Any;
 
+mod console;
+mod toasts;
 mod translate;
 
 pub struct EguiUiProvider {
     ctx: egui::Context,
     state: Option<egui_winit::State>,
     draw_list: UiDrawList,
+
+    /// Accessibility generation last applied to `ctx`; `u64::MAX` forces the first apply.
+    a11y_gen: u64,
+    /// Fonts generation last applied to `ctx`; 0 keeps egui's built-in fonts.
+    fonts_gen: u64,
+
+    /// IME state last forwarded to egui; the input snapshot is stateful, egui wants edges.
+    ime_enabled: bool,
+    ime_preedit: String,
+
+    console: console::ConsoleOverlay,
+    /// Tab presses taken by the console input this frame.
+    console_tabs: Vec<UiFocusStep>,
 }
 
 impl EguiUiProvider {
@@ -20,7 +41,61 @@ impl EguiUiProvider {
             ctx: egui::Context::default(),
             state: None,
             draw_list: UiDrawList::new(),
+            a11y_gen: u64::MAX,
+            fonts_gen: 0,
+            ime_enabled: false,
+            ime_preedit: String::new(),
+            console: console::ConsoleOverlay::default(),
+            console_tabs: Vec::new(),
+        }
+    }
+
+    /// Re-applies scale, contrast and motion options when the settings generation moved.
+    fn apply_accessibility(&mut self) {
+        let gen = accessibility_generation();
+        if gen == self.a11y_gen {
+            return;
+        }
+        let first = self.a11y_gen == u64::MAX;
+        self.a11y_gen = gen;
+
+        let a = accessibility();
+        self.ctx.set_zoom_factor(a.ui_scale);
+        self.ctx.style_mut(|style| {
+            if a.high_contrast {
+                style.visuals = high_contrast_visuals();
+            } else if !first {
+                // Leaving high contrast; markup themes re-apply their own visuals next frame.
+                style.visuals = egui::Visuals::dark();
+            }
+            style.animation_time = egui::Style::default().animation_time * a.motion_scale();
+        });
+
+        log::info!(
+            "ui.accessibility scale={} high_contrast={} reduced_motion={} screen_reader={}",
+            a.ui_scale,
+            a.high_contrast,
+            a.reduced_motion,
+            a.screen_reader
+        );
+    }
+
+    /// Rebuilds the egui font definitions when the UI fonts changed. egui picks them up at
+    /// the start of the next pass.
+    fn apply_fonts(&mut self) {
+        let gen = fonts_generation();
+        if gen == self.fonts_gen {
+            return;
         }
+        self.fonts_gen = gen;
+
+        let fonts = fonts();
+        self.ctx.set_fonts(font_definitions(&fonts));
+        log::info!(
+            "ui.fonts faces={} scripts={}",
+            fonts.faces().len(),
+            fonts.scripts().count()
+        );
     }
 
     #[inline]
@@ -58,6 +133,14 @@ impl EguiUiProvider {
         let insert = winit::keyboard::KeyCode::Insert as u32;
         let delete = winit::keyboard::KeyCode::Delete as u32;
 
+        // Letters used by text editing shortcuts (select all, undo/redo, clipboard).
+        let key_a = winit::keyboard::KeyCode::KeyA as u32;
+        let key_c = winit::keyboard::KeyCode::KeyC as u32;
+        let key_v = winit::keyboard::KeyCode::KeyV as u32;
+        let key_x = winit::keyboard::KeyCode::KeyX as u32;
+        let key_y = winit::keyboard::KeyCode::KeyY as u32;
+        let key_z = winit::keyboard::KeyCode::KeyZ as u32;
+
         Some(match u {
             x if x == backspace => egui::Key::Backspace,
             x if x == enter => egui::Key::Enter,
@@ -76,6 +159,13 @@ impl EguiUiProvider {
             x if x == insert => egui::Key::Insert,
             x if x == delete => egui::Key::Delete,
 
+            x if x == key_a => egui::Key::A,
+            x if x == key_c => egui::Key::C,
+            x if x == key_v => egui::Key::V,
+            x if x == key_x => egui::Key::X,
+            x if x == key_y => egui::Key::Y,
+            x if x == key_z => egui::Key::Z,
+
             _ => return None,
         })
     }
@@ -102,12 +192,43 @@ impl EguiUiProvider {
         }
     }
 
-    fn inject_input_events(raw: &mut egui::RawInput, input: &UiInputFrame) {
+    /// Clipboard text for a paste, with line endings normalized; `None` when empty.
+    fn clipboard_text(&mut self) -> Option<String> {
+        let text = self.state.as_mut()?.clipboard_text()?.replace("\r\n", "\n");
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// Turns clipboard shortcuts into egui's clipboard events, as egui-winit does. Returns
+    /// `false` when `key` is not one.
+    fn clipboard_shortcut(
+        &mut self,
+        modifiers: egui::Modifiers,
+        key: egui::Key,
+        events: &mut Vec<egui::Event>,
+    ) -> bool {
+        match key {
+            egui::Key::X if modifiers.command => events.push(egui::Event::Cut),
+            egui::Key::Delete if modifiers.shift => events.push(egui::Event::Cut),
+            egui::Key::C if modifiers.command => events.push(egui::Event::Copy),
+            egui::Key::Insert if modifiers.ctrl => events.push(egui::Event::Copy),
+            egui::Key::V if modifiers.command => {
+                events.extend(self.clipboard_text().map(egui::Event::Paste));
+            }
+            egui::Key::Insert if modifiers.shift => {
+                events.extend(self.clipboard_text().map(egui::Event::Paste));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn inject_input_events(&mut self, raw: &mut egui::RawInput, input: &UiInputFrame) {
         raw.modifiers = Self::compute_modifiers(input);
+        let zoom = self.ctx.zoom_factor();
 
         // egui expects positions in "points" (logical units).
-        // INPUT plugin usually reports physical pixels.
-        let ppp = raw.viewport().native_pixels_per_point.unwrap_or(1.0).max(0.0001);
+        // INPUT plugin usually reports physical pixels; the UI scale multiplier zooms on top.
+        let ppp = (raw.viewport().native_pixels_per_point.unwrap_or(1.0) * zoom).max(0.0001);
         let to_pt = |v: f32| v / ppp;
 
         let mouse_pos_pt = input
@@ -162,8 +283,31 @@ impl EguiUiProvider {
             });
         }
 
+        // Tab is not given to egui: the markup `UiState` owns the focus order, or the console
+        // input completes with it. `~` toggles the console.
+        let tab = winit::keyboard::KeyCode::Tab as u32;
+        let backquote = winit::keyboard::KeyCode::Backquote as u32;
+        let mut focus_steps = Vec::new();
+        let mut toggled_console = false;
+
         for &k in input.keys_pressed.iter() {
+            if k == backquote && !raw.modifiers.ctrl && !raw.modifiers.alt {
+                ConsoleApi::toggle();
+                toggled_console = true;
+                continue;
+            }
+            if k == tab {
+                focus_steps.push(if raw.modifiers.shift {
+                    UiFocusStep::Prev
+                } else {
+                    UiFocusStep::Next
+                });
+                continue;
+            }
             if let Some(key) = Self::egui_key_from_input(k) {
+                if self.clipboard_shortcut(raw.modifiers, key, &mut raw.events) {
+                    continue;
+                }
                 raw.events.push(egui::Event::Key {
                     key,
                     physical_key: None,
@@ -174,6 +318,9 @@ impl EguiUiProvider {
             }
         }
         for &k in input.keys_released.iter() {
+            if k == tab {
+                continue;
+            }
             if let Some(key) = Self::egui_key_from_input(k) {
                 raw.events.push(egui::Event::Key {
                     key,
@@ -185,18 +332,57 @@ impl EguiUiProvider {
             }
         }
 
-        if !input.text.is_empty() {
-            raw.events.push(egui::Event::Text(input.text.clone()));
+        if self.console.wants_tab() && ConsoleApi::is_open() {
+            self.console_tabs = focus_steps;
+            put_focus_steps(&self.ctx, Vec::new());
+        } else {
+            self.console_tabs.clear();
+            put_focus_steps(&self.ctx, focus_steps);
         }
 
-        if !input.ime_commit.is_empty() {
-            raw.events.push(egui::Event::Text(input.ime_commit.clone()));
+        // Shortcuts also type control characters (Ctrl+C is U+0003); AltGr reports Ctrl+Alt.
+        // The key that toggled the console must not type into it.
+        let shortcut = raw.modifiers.ctrl && !raw.modifiers.alt;
+        let text: String = input
+            .text
+            .chars()
+            .filter(|c| !c.is_control())
+            .filter(|c| !toggled_console || !matches!(c, '`' | '~'))
+            .collect();
+        if !text.is_empty() && !shortcut {
+            raw.events.push(egui::Event::Text(text));
         }
 
-        if !input.ime_preedit.is_empty() {
+        self.inject_ime_events(raw, input);
+    }
+
+    /// The snapshot carries the IME state; egui expects enable, preedit, commit and disable
+    /// edges in that order.
+    fn inject_ime_events(&mut self, raw: &mut egui::RawInput, input: &UiInputFrame) {
+        if input.ime_enabled && !self.ime_enabled {
+            raw.events.push(egui::Event::Ime(egui::ImeEvent::Enabled));
+        }
+
+        if input.ime_preedit != self.ime_preedit {
+            self.ime_preedit.clone_from(&input.ime_preedit);
             raw.events
                 .push(egui::Event::Ime(egui::ImeEvent::Preedit(input.ime_preedit.clone())));
         }
+
+        if !input.ime_commit.is_empty() {
+            let text = input.ime_commit.clone();
+            raw.events.push(if input.ime_enabled || self.ime_enabled {
+                egui::Event::Ime(egui::ImeEvent::Commit(text))
+            } else {
+                egui::Event::Text(text)
+            });
+        }
+
+        if !input.ime_enabled && self.ime_enabled {
+            self.ime_preedit.clear();
+            raw.events.push(egui::Event::Ime(egui::ImeEvent::Disabled));
+        }
+        self.ime_enabled = input.ime_enabled;
     }
 }
 
@@ -220,6 +406,46 @@ impl UiProvider for EguiUiProvider {
         // HARD NOOP: input must come exclusively from INPUT plugin.
     }
 
+    fn init_accessibility(&mut self, window: &dyn Any, proxy: &dyn Any) {
+        let Some(w) = window.downcast_ref::<winit::window::Window>() else {
+            return;
+        };
+        let Some(proxy) = proxy.downcast_ref::<winit::event_loop::EventLoopProxy<UiUserEvent>>()
+        else {
+            return;
+        };
+        if !accessibility().screen_reader {
+            return;
+        }
+
+        self.ensure_state(w).init_accesskit(w, proxy.clone());
+        log::info!("ui.accessibility accesskit adapter attached");
+    }
+
+    fn on_user_event(&mut self, window: &dyn Any, event: &dyn Any) {
+        let Some(UiUserEvent::AccessKit(ev)) = event.downcast_ref::<UiUserEvent>() else {
+            return;
+        };
+        let Some(w) = window.downcast_ref::<winit::window::Window>() else {
+            return;
+        };
+
+        use egui_winit::accesskit_winit::WindowEvent as AkEvent;
+        match &ev.window_event {
+            AkEvent::InitialTreeRequested => {
+                self.ctx.enable_accesskit();
+                w.request_redraw();
+            }
+            AkEvent::ActionRequested(req) => {
+                self.ensure_state(w).on_accesskit_action_request(req.clone());
+                w.request_redraw();
+            }
+            AkEvent::AccessibilityDeactivated => {
+                self.ctx.disable_accesskit();
+            }
+        }
+    }
+
     fn run_frame(
         &mut self,
         window: &dyn Any,
@@ -236,13 +462,19 @@ impl UiProvider for EguiUiProvider {
             state.take_egui_input(w)
         };
 
+        self.apply_accessibility();
+        self.apply_fonts();
+
         // Inject canonical input from INPUT plugin snapshot.
         if let Some(ref input) = frame.input {
-            Self::inject_input_events(&mut raw_input, input);
+            self.inject_input_events(&mut raw_input, input);
         }
 
         self.ctx.begin_pass(raw_input);
         build.build(&mut self.ctx);
+        let tabs = std::mem::take(&mut self.console_tabs);
+        self.console.show(&self.ctx, &tabs);
+        toasts::show(&self.ctx);
         let full_output = self.ctx.end_pass();
 
         {
@@ -0,0 +1,368 @@
+use crate::console::{ConsoleApi, ConsoleLine, ConsoleLineKind, ConsoleSuggestions};
+use crate::input::UiFocusStep;
+
+/// Share of the screen height the console drops down over.
+const HEIGHT_FRACTION: f32 = 0.4;
+const MIN_HEIGHT: f32 = 200.0;
+const MAX_HEIGHT: f32 = 620.0;
+const SUGGEST_LIST_HEIGHT: f32 = 140.0;
+
+/// Drop-down console drawn over the UI build. Its input owns Tab (completion), Up/Down
+/// (history or suggestion selection), Enter and Escape while it has keyboard focus.
+#[derive(Default)]
+pub(super) struct ConsoleOverlay {
+    input: String,
+    /// Copy of the console log, refreshed when its revision moves.
+    lines: Vec<ConsoleLine>,
+    lines_rev: Option<u64>,
+    /// Steps back into the history; 0 is the line being typed.
+    hist_cursor: usize,
+    suggest: ConsoleSuggestions,
+    /// Input `suggest` was fetched for.
+    suggest_for: Option<String>,
+    suggest_open: bool,
+    selected: usize,
+    /// Scroll the selected suggestion into view on the next frame.
+    scroll_to_selected: bool,
+    /// Give the input focus back on the next frame (after a suggestion was clicked).
+    refocus: bool,
+    was_open: bool,
+    /// The input held keyboard focus at the end of the last frame.
+    focused: bool,
+}
+
+impl ConsoleOverlay {
+    /// Tab presses go to completion instead of the markup focus order.
+    #[inline]
+    pub(super) fn wants_tab(&self) -> bool {
+        self.focused
+    }
+
+    pub(super) fn show(&mut self, ctx: &egui::Context, tabs: &[UiFocusStep]) {
+        let open = ConsoleApi::is_open();
+        let mut focus = open && !self.was_open;
+        self.was_open = open;
+        if !open {
+            self.focused = false;
+            return;
+        }
+
+        let mut cursor_to_end = std::mem::take(&mut self.refocus);
+        focus |= cursor_to_end;
+        if let Some(line) = ConsoleApi::take_prefill() {
+            self.set_input(line);
+            self.suggest_open = true;
+            focus = true;
+            cursor_to_end = true;
+        }
+
+        if self.focused {
+            focus |= self.handle_keys(ctx, tabs, &mut cursor_to_end);
+            if !ConsoleApi::is_open() {
+                self.was_open = false;
+                self.focused = false;
+                return;
+            }
+        }
+
+        if self.suggest_open {
+            self.refresh_suggest();
+        }
+
+        let rev = ConsoleApi::revision();
+        if self.lines_rev != Some(rev) {
+            let (rev, lines) = ConsoleApi::lines();
+            self.lines = lines;
+            self.lines_rev = Some(rev);
+        }
+
+        let input_id = egui::Id::new("newengine.console.input");
+        if cursor_to_end {
+            let n = self.input.chars().count();
+            let mut es = egui::TextEdit::load_state(ctx, input_id).unwrap_or_default();
+            es.cursor.set_char_range(Some(egui::text::CCursorRange::one(
+                egui::text::CCursor::new(n),
+            )));
+            es.store(ctx, input_id);
+        }
+
+        let screen = ctx.screen_rect();
+        let height = (screen.height() * HEIGHT_FRACTION).clamp(MIN_HEIGHT, MAX_HEIGHT);
+        let margin = egui::Margin::symmetric(12.0, 10.0);
+
+        egui::Area::new(egui::Id::new("newengine.console"))
+            .fixed_pos(screen.min)
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::none()
+                    .fill(egui::Color32::from_rgba_premultiplied(12, 12, 14, 238))
+                    .stroke(egui::Stroke::new(1.0, egui::Color32::from_gray(60)))
+                    .inner_margin(margin)
+                    .show(ui, |ui| {
+                        ui.set_width(screen.width() - margin.sum().x);
+                        ui.set_height(height - margin.sum().y);
+
+                        self.header_row(ui);
+                        ui.add_space(4.0);
+
+                        let showing = self.showing_suggestions();
+                        let reserved = if showing {
+                            SUGGEST_LIST_HEIGHT + 40.0
+                        } else {
+                            0.0
+                        };
+                        let row_h = ui.spacing().interact_size.y + 8.0;
+                        let log_h = (ui.available_height() - row_h - reserved).max(40.0);
+                        self.log_area(ui, log_h);
+
+                        ui.add_space(4.0);
+                        self.input_row(ui, input_id, focus);
+
+                        if showing {
+                            ui.add_space(4.0);
+                            self.suggest_panel(ui);
+                        }
+                    });
+            });
+    }
+
+    /// Navigation keys typed into the focused input. Returns `true` when the input should
+    /// keep focus (egui would otherwise drop it on Enter/Escape).
+    fn handle_keys(
+        &mut self,
+        ctx: &egui::Context,
+        tabs: &[UiFocusStep],
+        cursor_to_end: &mut bool,
+    ) -> bool {
+        let none = egui::Modifiers::NONE;
+        let (enter, esc, up, down) = ctx.input_mut(|i| {
+            (
+                i.consume_key(none, egui::Key::Enter),
+                i.consume_key(none, egui::Key::Escape),
+                i.consume_key(none, egui::Key::ArrowUp),
+                i.consume_key(none, egui::Key::ArrowDown),
+            )
+        });
+
+        for step in tabs {
+            match step {
+                UiFocusStep::Next => self.accept_suggestion(),
+                UiFocusStep::Prev => self.move_selection(-1),
+            }
+            *cursor_to_end = true;
+        }
+
+        if esc {
+            if self.suggest_open {
+                self.suggest_open = false;
+            } else {
+                ConsoleApi::set_open(false);
+                return false;
+            }
+        }
+
+        if up || down {
+            if self.showing_suggestions() {
+                self.move_selection(if up { -1 } else { 1 });
+            } else {
+                if up {
+                    self.history_step(1);
+                } else {
+                    self.history_step(-1);
+                }
+                *cursor_to_end = true;
+            }
+        }
+
+        if enter {
+            let line = std::mem::take(&mut self.input);
+            self.hist_cursor = 0;
+            self.suggest_open = false;
+            ConsoleApi::exec(&line);
+        }
+
+        enter || esc || up || down || !tabs.is_empty()
+    }
+
+    #[inline]
+    fn showing_suggestions(&self) -> bool {
+        self.suggest_open && !self.suggest.items.is_empty()
+    }
+
+    #[inline]
+    fn set_input(&mut self, line: String) {
+        self.input = line;
+        self.hist_cursor = 0;
+        self.selected = 0;
+    }
+
+    fn refresh_suggest(&mut self) {
+        if self.suggest_for.as_deref() == Some(self.input.as_str()) {
+            return;
+        }
+        self.suggest = ConsoleApi::suggest(&self.input);
+        self.suggest_for = Some(self.input.clone());
+        self.selected = self
+            .selected
+            .min(self.suggest.items.len().saturating_sub(1));
+    }
+
+    /// Replaces the input with the selected completion and keeps completing from there.
+    fn accept_suggestion(&mut self) {
+        self.refresh_suggest();
+        self.suggest_open = true;
+        let Some(item) = self.suggest.items.get(self.selected) else {
+            return;
+        };
+        if !item.insert.is_empty() {
+            let insert = item.insert.clone();
+            self.set_input(insert);
+            self.refresh_suggest();
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let n = self.suggest.items.len();
+        if n == 0 {
+            return;
+        }
+        self.selected = (self.selected as isize + delta).rem_euclid(n as isize) as usize;
+        self.scroll_to_selected = true;
+    }
+
+    /// `delta > 0` walks back to older entries.
+    fn history_step(&mut self, delta: isize) {
+        let history = ConsoleApi::history();
+        if history.is_empty() {
+            return;
+        }
+        let cursor = (self.hist_cursor as isize + delta).clamp(0, history.len() as isize) as usize;
+        self.hist_cursor = cursor;
+        self.input = match cursor {
+            0 => String::new(),
+            c => history[history.len() - c].clone(),
+        };
+    }
+
+    fn header_row(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new("Console")
+                    .strong()
+                    .monospace()
+                    .color(egui::Color32::from_gray(220)),
+            );
+            if !ConsoleApi::has_backend() {
+                ui.weak("(no command backend)");
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("✕").on_hover_text("Close (~)").clicked() {
+                    ConsoleApi::set_open(false);
+                }
+                if ui.small_button("Clear").clicked() {
+                    ConsoleApi::clear();
+                }
+            });
+        });
+    }
+
+    fn log_area(&self, ui: &mut egui::Ui, height: f32) {
+        let row_h = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::vertical()
+            .max_height(height)
+            .min_scrolled_height(height)
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show_rows(ui, row_h, self.lines.len(), |ui, range| {
+                for l in &self.lines[range] {
+                    let color = match l.kind {
+                        ConsoleLineKind::Input => egui::Color32::from_rgb(128, 220, 140),
+                        ConsoleLineKind::Output => egui::Color32::from_gray(200),
+                        ConsoleLineKind::Error => egui::Color32::from_rgb(255, 96, 96),
+                    };
+                    ui.label(egui::RichText::new(&l.text).monospace().color(color));
+                }
+            });
+    }
+
+    fn input_row(&mut self, ui: &mut egui::Ui, input_id: egui::Id, focus: bool) {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(">").monospace().strong());
+
+            let resp = ui.add(
+                egui::TextEdit::singleline(&mut self.input)
+                    .id(input_id)
+                    .desired_width(f32::INFINITY)
+                    .font(egui::TextStyle::Monospace)
+                    .hint_text("Type a command (Tab completes)"),
+            );
+
+            if resp.changed() {
+                self.hist_cursor = 0;
+                self.selected = 0;
+                self.suggest_open = !self.input.trim().is_empty();
+                if self.suggest_open {
+                    self.refresh_suggest();
+                }
+            }
+            if focus {
+                resp.request_focus();
+            }
+            self.focused = resp.has_focus() || focus;
+        });
+    }
+
+    fn suggest_panel(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::none()
+            .fill(egui::Color32::from_rgba_premultiplied(16, 16, 18, 245))
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_gray(55)))
+            .inner_margin(egui::Margin::symmetric(10.0, 6.0))
+            .rounding(egui::Rounding::same(6.0))
+            .show(ui, |ui| {
+                if !self.suggest.signature.is_empty() {
+                    ui.label(
+                        egui::RichText::new(&self.suggest.signature)
+                            .monospace()
+                            .color(egui::Color32::from_gray(200)),
+                    );
+                }
+
+                let mut accept = None;
+                egui::ScrollArea::vertical()
+                    .id_salt("newengine.console.suggest")
+                    .max_height(SUGGEST_LIST_HEIGHT)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        for (i, it) in self.suggest.items.iter().enumerate() {
+                            let selected = i == self.selected;
+                            let text = if it.help.is_empty() {
+                                it.display.clone()
+                            } else {
+                                format!("{}  -  {}", it.display, it.help)
+                            };
+                            let resp = ui
+                                .selectable_label(selected, egui::RichText::new(text).monospace());
+                            if selected && self.scroll_to_selected {
+                                resp.scroll_to_me(None);
+                            }
+                            let resp = if it.usage.is_empty() {
+                                resp
+                            } else {
+                                resp.on_hover_text(&it.usage)
+                            };
+                            if resp.clicked() {
+                                accept = Some(i);
+                            }
+                        }
+                    });
+                self.scroll_to_selected = false;
+
+                if let Some(i) = accept {
+                    self.selected = i;
+                    self.accept_suggestion();
+                    self.refocus = true;
+                }
+            });
+    }
+}
@@ -0,0 +1,75 @@
+use crate::notify::{NotifyApi, Toast, ToastLevel};
+use std::time::Instant;
+
+const TOAST_WIDTH: f32 = 340.0;
+
+/// Draws live toasts stacked in the bottom-right corner, newest at the bottom.
+pub(super) fn show(ctx: &egui::Context) {
+    let toasts = NotifyApi::active();
+    if toasts.is_empty() {
+        return;
+    }
+
+    let now = Instant::now();
+    egui::Area::new(egui::Id::new("newengine.toasts"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+        .order(egui::Order::Foreground)
+        .interactable(true)
+        .show(ctx, |ui| {
+            ui.set_width(TOAST_WIDTH);
+            for t in &toasts {
+                toast_frame(ui, t, now);
+                ui.add_space(6.0);
+            }
+        });
+
+    // Keep fading/expiring without waiting for input.
+    ctx.request_repaint_after(std::time::Duration::from_millis(250));
+}
+
+fn toast_frame(ui: &mut egui::Ui, t: &Toast, now: Instant) {
+    let accent = match t.level {
+        ToastLevel::Info => egui::Color32::from_rgb(90, 160, 255),
+        ToastLevel::Warning => egui::Color32::from_rgb(240, 180, 40),
+        ToastLevel::Error => egui::Color32::from_rgb(235, 70, 70),
+    };
+    let title = match t.level {
+        ToastLevel::Info => "Info",
+        ToastLevel::Warning => "Warning",
+        ToastLevel::Error => "Error",
+    };
+
+    egui::Frame::popup(ui.style())
+        .stroke(egui::Stroke::new(1.5, accent))
+        .show(ui, |ui| {
+            ui.set_width(TOAST_WIDTH);
+            ui.horizontal(|ui| {
+                ui.colored_label(accent, egui::RichText::new(title).strong());
+                if t.count > 1 {
+                    ui.weak(format!("×{}", t.count));
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("✕").on_hover_text("Dismiss").clicked() {
+                        NotifyApi::dismiss(t.id);
+                    }
+                });
+            });
+
+            ui.label(&t.message);
+
+            if let Some(a) = &t.action {
+                if ui
+                    .button(&a.label)
+                    .on_hover_text(a.command.as_str())
+                    .clicked()
+                {
+                    NotifyApi::activate(t.id);
+                }
+            }
+
+            let bar = egui::ProgressBar::new(t.remaining(now))
+                .desired_height(2.0)
+                .fill(accent);
+            ui.add(bar);
+        });
+}
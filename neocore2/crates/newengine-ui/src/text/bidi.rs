@@ -0,0 +1,362 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Simplified bidirectional layout: Arabic contextual shaping and visual reordering of
+//! mixed left-to-right / right-to-left lines.
+//!
+//! Both text paths lay glyphs out strictly left to right, so RTL text is shaped and reordered
+//! here before layout. This covers paragraphs, numbers and bracket mirroring (rules W/N/L2 of
+//! UAX #9 in reduced form); explicit embeddings and isolates are not supported.
+
+use super::script::{strength_of, Strength};
+
+use serde::{Deserialize, Serialize};
+
+/// Paragraph direction of a text node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextDirection {
+    Ltr,
+    Rtl,
+    /// Taken from the first strong character; left to right if there is none.
+    #[default]
+    Auto,
+}
+
+impl TextDirection {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ltr" => Some(Self::Ltr),
+            "rtl" => Some(Self::Rtl),
+            "auto" => Some(Self::Auto),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ltr => "ltr",
+            Self::Rtl => "rtl",
+            Self::Auto => "auto",
+        }
+    }
+
+    /// Resolves `Auto` against `text`.
+    #[inline]
+    pub fn resolve(self, text: &str) -> Self {
+        match self {
+            Self::Auto => base_direction(text),
+            d => d,
+        }
+    }
+
+    #[inline]
+    pub fn is_rtl(self) -> bool {
+        self == Self::Rtl
+    }
+}
+
+/// Direction of the first strong character in `text`.
+pub fn base_direction(text: &str) -> TextDirection {
+    for c in text.chars() {
+        match strength_of(c) {
+            Strength::Ltr => return TextDirection::Ltr,
+            Strength::Rtl => return TextDirection::Rtl,
+            _ => {}
+        }
+    }
+    TextDirection::Ltr
+}
+
+/// Whether `text` needs [`visual_order`] at all.
+#[inline]
+pub fn has_rtl(text: &str) -> bool {
+    text.chars().any(|c| strength_of(c) == Strength::Rtl)
+}
+
+/// Shapes and reorders `text` line by line so that drawing it left to right shows it
+/// correctly. `dir` is the paragraph direction (`Auto` resolves per line). Pure LTR text is
+/// returned unchanged.
+pub fn visual_order(text: &str, dir: TextDirection) -> String {
+    if !has_rtl(text) && dir != TextDirection::Rtl {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let rtl = dir.resolve(line).is_rtl();
+        reorder_line(&shape_arabic(line), rtl, &mut out);
+    }
+    out
+}
+
+/// Embedding level per character (0 = LTR, 1 = RTL, 2 = LTR inside RTL), then rule L2.
+fn reorder_line(line: &str, rtl: bool, out: &mut String) {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.is_empty() {
+        return;
+    }
+    let base = u8::from(rtl);
+    let ltr_level = if rtl { 2 } else { 0 };
+
+    let strengths: Vec<Strength> = chars.iter().map(|&c| strength_of(c)).collect();
+    let mut levels: Vec<u8> = strengths
+        .iter()
+        .map(|s| match s {
+            Strength::Rtl => 1,
+            Strength::Ltr | Strength::Number => ltr_level,
+            Strength::Neutral => u8::MAX,
+        })
+        .collect();
+
+    // Neutrals between two characters of the same direction take that direction, the others
+    // the paragraph direction. Numbers count as RTL context for this (rule N1).
+    let mut i = 0;
+    while i < levels.len() {
+        if levels[i] != u8::MAX {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < levels.len() && levels[i] == u8::MAX {
+            i += 1;
+        }
+        let side = |s: Option<&Strength>| match s {
+            Some(Strength::Rtl) => Some(true),
+            Some(Strength::Number) => Some(rtl),
+            Some(Strength::Ltr) => Some(false),
+            _ => None,
+        };
+        let before = start
+            .checked_sub(1)
+            .map_or(Some(rtl), |j| side(strengths.get(j)));
+        let after = if i < levels.len() {
+            side(strengths.get(i))
+        } else {
+            Some(rtl)
+        };
+        let level = match (before, after) {
+            (Some(a), Some(b)) if a == b => {
+                if a {
+                    1
+                } else {
+                    ltr_level
+                }
+            }
+            _ => base,
+        };
+        levels[start..i].fill(level);
+    }
+
+    // Trailing whitespace stays at the paragraph level (rule L1).
+    for (l, c) in levels.iter_mut().zip(&chars).rev() {
+        if !c.is_whitespace() {
+            break;
+        }
+        *l = base;
+    }
+
+    let mut order: Vec<usize> = (0..chars.len()).collect();
+    let max = levels.iter().copied().max().unwrap_or(0);
+    let min_odd = levels
+        .iter()
+        .copied()
+        .filter(|l| l % 2 == 1)
+        .min()
+        .unwrap_or(max + 1);
+    for level in (min_odd..=max).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] < level {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < order.len() && levels[order[i]] >= level {
+                i += 1;
+            }
+            order[start..i].reverse();
+        }
+    }
+
+    out.extend(order.into_iter().map(|i| {
+        if levels[i] % 2 == 1 {
+            mirror(chars[i])
+        } else {
+            chars[i]
+        }
+    }));
+}
+
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        c => c,
+    }
+}
+
+/// Letter, its isolated presentation form, and whether it also joins to the following letter
+/// (dual joining). Forms follow the isolated one: final, then initial and medial for dual
+/// joining letters.
+const ARABIC_FORMS: &[(char, u32, bool)] = &[
+    ('\u{0621}', 0xFE80, false),
+    ('\u{0622}', 0xFE81, false),
+    ('\u{0623}', 0xFE83, false),
+    ('\u{0624}', 0xFE85, false),
+    ('\u{0625}', 0xFE87, false),
+    ('\u{0626}', 0xFE89, true),
+    ('\u{0627}', 0xFE8D, false),
+    ('\u{0628}', 0xFE8F, true),
+    ('\u{0629}', 0xFE93, false),
+    ('\u{062A}', 0xFE95, true),
+    ('\u{062B}', 0xFE99, true),
+    ('\u{062C}', 0xFE9D, true),
+    ('\u{062D}', 0xFEA1, true),
+    ('\u{062E}', 0xFEA5, true),
+    ('\u{062F}', 0xFEA9, false),
+    ('\u{0630}', 0xFEAB, false),
+    ('\u{0631}', 0xFEAD, false),
+    ('\u{0632}', 0xFEAF, false),
+    ('\u{0633}', 0xFEB1, true),
+    ('\u{0634}', 0xFEB5, true),
+    ('\u{0635}', 0xFEB9, true),
+    ('\u{0636}', 0xFEBD, true),
+    ('\u{0637}', 0xFEC1, true),
+    ('\u{0638}', 0xFEC5, true),
+    ('\u{0639}', 0xFEC9, true),
+    ('\u{063A}', 0xFECD, true),
+    ('\u{0641}', 0xFED1, true),
+    ('\u{0642}', 0xFED5, true),
+    ('\u{0643}', 0xFED9, true),
+    ('\u{0644}', 0xFEDD, true),
+    ('\u{0645}', 0xFEE1, true),
+    ('\u{0646}', 0xFEE5, true),
+    ('\u{0647}', 0xFEE9, true),
+    ('\u{0648}', 0xFEED, false),
+    ('\u{0649}', 0xFEEF, false),
+    ('\u{064A}', 0xFEF1, true),
+    // Persian / Urdu.
+    ('\u{067E}', 0xFB56, true),
+    ('\u{0686}', 0xFB7A, true),
+    ('\u{0698}', 0xFB8A, false),
+    ('\u{06A9}', 0xFB8E, true),
+    ('\u{06AF}', 0xFB92, true),
+    ('\u{06CC}', 0xFBFC, true),
+];
+
+const TATWEEL: char = '\u{0640}';
+const LAM: char = '\u{0644}';
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Joining {
+    None,
+    /// Joins only to the preceding letter.
+    Right,
+    Dual,
+    /// Combining marks; skipped when looking for neighbours.
+    Transparent,
+}
+
+#[inline]
+fn forms(c: char) -> Option<(u32, bool)> {
+    ARABIC_FORMS
+        .iter()
+        .find(|(l, _, _)| *l == c)
+        .map(|&(_, iso, dual)| (iso, dual))
+}
+
+fn joining(c: char) -> Joining {
+    if c == TATWEEL {
+        return Joining::Dual;
+    }
+    if matches!(c as u32, 0x0610..=0x061A | 0x064B..=0x065F | 0x0670 | 0x06D6..=0x06ED) {
+        return Joining::Transparent;
+    }
+    match forms(c) {
+        Some((_, true)) => Joining::Dual,
+        // Hamza does not join at all.
+        Some((_, false)) if c != '\u{0621}' => Joining::Right,
+        _ => Joining::None,
+    }
+}
+
+/// Isolated and final form of the lam-alef ligature for the alef variant `c`.
+#[inline]
+fn lam_alef(c: char) -> Option<u32> {
+    match c {
+        '\u{0622}' => Some(0xFEF5),
+        '\u{0623}' => Some(0xFEF7),
+        '\u{0625}' => Some(0xFEF9),
+        '\u{0627}' => Some(0xFEFB),
+        _ => None,
+    }
+}
+
+/// Replaces Arabic letters with their contextual presentation forms (logical order kept).
+pub fn shape_arabic(text: &str) -> String {
+    if !text.chars().any(|c| forms(c).is_some()) {
+        return text.to_string();
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let joins: Vec<Joining> = chars.iter().map(|&c| joining(c)).collect();
+    let neighbour = |from: usize, forward: bool| -> Option<usize> {
+        let mut i = from;
+        loop {
+            i = if forward {
+                i.checked_add(1).filter(|&n| n < chars.len())?
+            } else {
+                i.checked_sub(1)?
+            };
+            if joins[i] != Joining::Transparent {
+                return Some(i);
+            }
+        }
+    };
+
+    let mut out = String::with_capacity(text.len() * 2);
+    // Characters up to here were consumed by a lam-alef ligature.
+    let mut consumed = 0;
+    for (i, &c) in chars.iter().enumerate() {
+        if i < consumed {
+            continue;
+        }
+        let Some((iso, dual)) = forms(c) else {
+            out.push(c);
+            continue;
+        };
+        let prev_joins = neighbour(i, false).is_some_and(|p| joins[p] == Joining::Dual);
+        let next = neighbour(i, true);
+
+        if c == LAM {
+            if let Some((n, lig)) = next.and_then(|n| lam_alef(chars[n]).map(|l| (n, l))) {
+                out.push(char::from_u32(lig + u32::from(prev_joins)).unwrap_or(c));
+                // Marks between lam and alef are kept, after the ligature.
+                out.extend(&chars[i + 1..n]);
+                consumed = n + 1;
+                continue;
+            }
+        }
+
+        let next_joins = next.is_some_and(|n| matches!(joins[n], Joining::Dual | Joining::Right));
+        let offset = match (prev_joins, dual && next_joins) {
+            (true, true) => 3,
+            (true, false) if joining(c) != Joining::None => 1,
+            (false, true) => 2,
+            _ => 0,
+        };
+        out.push(char::from_u32(iso + offset).unwrap_or(c));
+    }
+    out
+}
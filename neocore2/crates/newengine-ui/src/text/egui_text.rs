@@ -0,0 +1,115 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use super::bidi::{has_rtl, visual_order, TextDirection};
+use super::fonts::{fonts, UiFonts};
+use super::script::{script_of, Script};
+
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, FontFamily, FontId};
+
+/// egui family holding the fallback chain of `script`.
+#[inline]
+pub(crate) fn script_family(script: Script) -> FontFamily {
+    FontFamily::Name(format!("script.{}", script.as_str()).into())
+}
+
+fn push_unique(out: &mut Vec<String>, names: impl IntoIterator<Item = impl AsRef<str>>) {
+    for n in names {
+        let n = n.as_ref();
+        if !out.iter().any(|o| o == n) {
+            out.push(n.to_string());
+        }
+    }
+}
+
+/// egui fonts for `fonts`. Proportional text tries the default chain, egui's built-in fonts,
+/// then every other face; monospace keeps its built-in face first. Each script with a chain
+/// gets a [`script_family`] that tries that chain before the proportional list.
+pub(crate) fn font_definitions(fonts: &UiFonts) -> egui::FontDefinitions {
+    let mut defs = egui::FontDefinitions::default();
+    for f in fonts.faces() {
+        defs.font_data
+            .insert(f.name.clone(), egui::FontData::from_owned(f.data.to_vec()));
+    }
+
+    let fallback = fonts.fallback_order();
+    let builtin = |defs: &egui::FontDefinitions, family: &FontFamily| {
+        defs.families.get(family).cloned().unwrap_or_default()
+    };
+
+    let mut prop = Vec::new();
+    push_unique(&mut prop, fonts.default_chain());
+    push_unique(&mut prop, builtin(&defs, &FontFamily::Proportional));
+    push_unique(&mut prop, &fallback);
+    prop.retain(|n| defs.font_data.contains_key(n));
+
+    let mut mono = builtin(&defs, &FontFamily::Monospace);
+    push_unique(&mut mono, &fallback);
+
+    for script in fonts.scripts() {
+        let mut chain = Vec::new();
+        push_unique(&mut chain, fonts.chain_for(script));
+        push_unique(&mut chain, &prop);
+        defs.families.insert(script_family(script), chain);
+    }
+    defs.families.insert(FontFamily::Proportional, prop);
+    defs.families.insert(FontFamily::Monospace, mono);
+    defs
+}
+
+/// Markup text ready for a widget: shaped and reordered for `dir`, with runs of scripts that
+/// have their own chain set in that chain's family. Returns whether the paragraph is RTL.
+pub(crate) fn widget_text(
+    ctx: &egui::Context,
+    text: &str,
+    dir: TextDirection,
+    font: FontId,
+) -> (egui::WidgetText, bool) {
+    let fonts = fonts();
+    let has_chains = fonts.scripts().next().is_some();
+    if !has_chains && dir != TextDirection::Rtl && !has_rtl(text) {
+        return (text.into(), false);
+    }
+
+    let rtl = dir.resolve(text).is_rtl();
+    let visual = visual_order(text, dir);
+    if !has_chains {
+        return (visual.into(), rtl);
+    }
+
+    let families = ctx.fonts(|f| f.families());
+    let family_of = |c: char| {
+        let script = script_of(c);
+        if script == Script::Common {
+            return None;
+        }
+        let fam = script_family(script);
+        Some(if families.contains(&fam) {
+            fam
+        } else {
+            font.family.clone()
+        })
+    };
+
+    let mut job = LayoutJob::default();
+    let mut run_start = 0;
+    let mut run_family = font.family.clone();
+    for (i, c) in visual.char_indices() {
+        // Common characters (spaces, digits, punctuation) stay in the surrounding run.
+        let Some(fam) = family_of(c) else {
+            continue;
+        };
+        if fam != run_family {
+            if i > run_start {
+                let format =
+                    TextFormat::simple(FontId::new(font.size, run_family), Color32::PLACEHOLDER);
+                job.append(&visual[run_start..i], 0.0, format);
+            }
+            run_start = i;
+            run_family = fam;
+        }
+    }
+    let format = TextFormat::simple(FontId::new(font.size, run_family), Color32::PLACEHOLDER);
+    job.append(&visual[run_start..], 0.0, format);
+    (job.into(), rtl)
+}
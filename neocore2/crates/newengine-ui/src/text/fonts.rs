@@ -0,0 +1,210 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use super::script::Script;
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// A font file (TTF/OTF) registered under a name.
+#[derive(Clone)]
+pub struct FontFace {
+    pub name: String,
+    pub data: Arc<[u8]>,
+}
+
+impl std::fmt::Debug for FontFace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FontFace")
+            .field("name", &self.name)
+            .field("bytes", &self.data.len())
+            .finish()
+    }
+}
+
+/// Font faces and the order they are tried in.
+///
+/// Text is split by [`Script`]; each script tries its own chain first, then the default
+/// chain, then the provider's built-in fonts. A glyph missing from every face falls through to
+/// the next one at draw time, so a chain only needs to list what it prefers.
+#[derive(Debug, Clone, Default)]
+pub struct UiFonts {
+    faces: Vec<FontFace>,
+    default_chain: Vec<String>,
+    chains: BTreeMap<Script, Vec<String>>,
+}
+
+impl UiFonts {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a face; a face with the same name is replaced.
+    pub fn add_face(&mut self, name: impl Into<String>, data: impl Into<Arc<[u8]>>) -> &mut Self {
+        let name = name.into();
+        let data = data.into();
+        match self.faces.iter_mut().find(|f| f.name == name) {
+            Some(f) => f.data = data,
+            None => self.faces.push(FontFace { name, data }),
+        }
+        self
+    }
+
+    pub fn load_face(
+        &mut self,
+        name: impl Into<String>,
+        path: &Path,
+    ) -> std::io::Result<&mut Self> {
+        let bytes = std::fs::read(path)?;
+        Ok(self.add_face(name, bytes))
+    }
+
+    /// Faces tried for every script, before the built-in fonts.
+    pub fn set_default_chain<I, S>(&mut self, names: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.default_chain = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Faces tried first for characters of `script`.
+    pub fn set_chain<I, S>(&mut self, script: Script, names: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.chains
+            .insert(script, names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    #[inline]
+    pub fn faces(&self) -> &[FontFace] {
+        &self.faces
+    }
+
+    #[inline]
+    pub fn face(&self, name: &str) -> Option<&FontFace> {
+        self.faces.iter().find(|f| f.name == name)
+    }
+
+    #[inline]
+    pub fn default_chain(&self) -> &[String] {
+        &self.default_chain
+    }
+
+    /// Scripts with their own chain.
+    #[inline]
+    pub fn scripts(&self) -> impl Iterator<Item = Script> + '_ {
+        self.chains.keys().copied()
+    }
+
+    /// Registered face names tried for `script`, in order, without duplicates.
+    pub fn chain_for(&self, script: Script) -> Vec<&str> {
+        let own = self.chains.get(&script).map(Vec::as_slice).unwrap_or(&[]);
+        let mut out: Vec<&str> = Vec::new();
+        for name in own.iter().chain(&self.default_chain) {
+            if self.face(name).is_some() && !out.contains(&name.as_str()) {
+                out.push(name);
+            }
+        }
+        out
+    }
+
+    /// Every registered face in fallback order: default chain, the script chains in
+    /// [`Script`] order, then faces no chain mentions.
+    pub fn fallback_order(&self) -> Vec<&str> {
+        let mut out: Vec<&str> = Vec::new();
+        let listed = self
+            .default_chain
+            .iter()
+            .chain(self.chains.values().flatten())
+            .map(String::as_str)
+            .chain(self.faces.iter().map(|f| f.name.as_str()));
+        for name in listed {
+            if self.face(name).is_some() && !out.contains(&name) {
+                out.push(name);
+            }
+        }
+        out
+    }
+
+    /// Loads the faces of `config`; relative paths are resolved against `base_dir`. Faces
+    /// that fail to load are skipped with a warning.
+    pub fn from_config(config: &UiFontConfig, base_dir: &Path) -> Self {
+        let mut fonts = Self::new();
+        for f in &config.faces {
+            let path = base_dir.join(&f.path);
+            if let Err(e) = fonts.load_face(f.name.clone(), &path) {
+                log::warn!(
+                    "ui.fonts: cannot load '{}' ({}): {e}",
+                    f.name,
+                    path.display()
+                );
+            }
+        }
+        fonts.set_default_chain(config.default.iter().cloned());
+        for (script, names) in &config.scripts {
+            match Script::parse(script) {
+                Some(s) => {
+                    fonts.set_chain(s, names.iter().cloned());
+                }
+                None => log::warn!("ui.fonts: unknown script '{script}'"),
+            }
+        }
+        fonts
+    }
+}
+
+/// Serialized form of [`UiFonts`], e.g. a `fonts` section of a localization config:
+///
+/// ```json
+/// { "faces": [{ "name": "noto-arabic", "path": "fonts/NotoSansArabic.ttf" }],
+///   "default": ["noto-sans"],
+///   "scripts": { "arabic": ["noto-arabic"], "han": ["noto-sc", "noto-jp"] } }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiFontConfig {
+    pub faces: Vec<UiFontFaceConfig>,
+    pub default: Vec<String>,
+    pub scripts: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UiFontFaceConfig {
+    pub name: String,
+    pub path: String,
+}
+
+static CURRENT: OnceLock<RwLock<Arc<UiFonts>>> = OnceLock::new();
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[inline]
+fn current() -> &'static RwLock<Arc<UiFonts>> {
+    CURRENT.get_or_init(|| RwLock::new(Arc::new(UiFonts::default())))
+}
+
+/// Active fonts shared by the UI provider and the renderer's text path.
+#[inline]
+pub fn fonts() -> Arc<UiFonts> {
+    current().read().map(|g| g.clone()).unwrap_or_default()
+}
+
+/// Replaces the active fonts; text paths rebuild their atlases on the next frame.
+pub fn set_fonts(value: UiFonts) {
+    if let Ok(mut g) = current().write() {
+        *g = Arc::new(value);
+        GENERATION.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+#[inline]
+pub fn fonts_generation() -> u64 {
+    GENERATION.load(Ordering::Acquire)
+}
@@ -0,0 +1,18 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+//! Text support shared by the UI provider and renderer-side text: per-script font fallback
+//! chains, right-to-left layout and glyph rasterization.
+
+mod bidi;
+#[cfg(feature = "provider-egui")]
+pub(crate) mod egui_text;
+mod fonts;
+mod raster;
+mod script;
+
+pub use bidi::{base_direction, has_rtl, shape_arabic, visual_order, TextDirection};
+pub use fonts::{
+    fonts, fonts_generation, set_fonts, FontFace, UiFontConfig, UiFontFaceConfig, UiFonts,
+};
+pub use raster::GlyphRasterizer;
+pub use script::{script_of, Script};
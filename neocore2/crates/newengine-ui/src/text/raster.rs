@@ -0,0 +1,83 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use super::fonts::{fonts, fonts_generation};
+use super::script::script_of;
+
+use ab_glyph::{point, Font, FontArc, PxScale, ScaleFont};
+use std::collections::HashMap;
+
+/// CPU glyph rasterizer for renderer-side text that has no font engine of its own.
+///
+/// Glyphs are looked up along the fallback chain of their script in the active [`fonts`];
+/// the first face that has the glyph draws it. Faces are re-parsed when the fonts change.
+#[derive(Default)]
+pub struct GlyphRasterizer {
+    generation: Option<u64>,
+    faces: HashMap<String, FontArc>,
+}
+
+impl GlyphRasterizer {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the fonts changed since the last call; glyphs cached by the caller are stale
+    /// when this returns `true`.
+    pub fn refresh(&mut self) -> bool {
+        let gen = fonts_generation();
+        if self.generation == Some(gen) {
+            return false;
+        }
+        self.generation = Some(gen);
+        self.faces.clear();
+        for f in fonts().faces() {
+            match FontArc::try_from_vec(f.data.to_vec()) {
+                Ok(font) => {
+                    self.faces.insert(f.name.clone(), font);
+                }
+                Err(e) => log::warn!("ui.fonts: '{}' is not a usable font: {e}", f.name),
+            }
+        }
+        true
+    }
+
+    /// Name of the first face in `c`'s chain that has a glyph for it.
+    pub fn face_for(&mut self, c: char) -> Option<String> {
+        self.refresh();
+        let fonts = fonts();
+        fonts
+            .chain_for(script_of(c))
+            .into_iter()
+            .find(|name| self.faces.get(*name).is_some_and(|f| f.glyph_id(c).0 != 0))
+            .map(str::to_string)
+    }
+
+    /// Coverage of `c` (one byte per pixel, row-major) fitted into a `width` x `height` cell
+    /// with the baseline at the face's ascent. `None` when no face has the glyph.
+    pub fn rasterize(&mut self, c: char, width: u32, height: u32) -> Option<Vec<u8>> {
+        let name = self.face_for(c)?;
+        let font = self.faces.get(&name)?;
+
+        let scale = PxScale::from(height as f32);
+        let scaled = font.as_scaled(scale);
+        let id = font.glyph_id(c);
+        let advance = scaled.h_advance(id);
+        let x = ((width as f32 - advance) * 0.5).max(0.0);
+        let glyph = id.with_scale_and_position(scale, point(x, scaled.ascent()));
+
+        let mut out = vec![0u8; (width * height) as usize];
+        if let Some(outline) = font.outline_glyph(glyph) {
+            let b = outline.px_bounds();
+            outline.draw(|gx, gy, cov| {
+                let px = gx as i32 + b.min.x as i32;
+                let py = gy as i32 + b.min.y as i32;
+                if px >= 0 && py >= 0 && (px as u32) < width && (py as u32) < height {
+                    let i = py as usize * width as usize + px as usize;
+                    out[i] = out[i].max((cov.clamp(0.0, 1.0) * 255.0) as u8);
+                }
+            });
+        }
+        Some(out)
+    }
+}
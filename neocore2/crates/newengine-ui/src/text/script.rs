@@ -0,0 +1,150 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+use serde::{Deserialize, Serialize};
+
+/// Writing system of a character, used to pick its font fallback chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Thai,
+    Hangul,
+    /// Hiragana and katakana.
+    Kana,
+    /// CJK ideographs and CJK punctuation.
+    Han,
+    /// Arrows, box drawing, emoji.
+    Symbol,
+    /// Digits, punctuation and whitespace shared by every script.
+    Common,
+}
+
+impl Script {
+    pub const ALL: [Script; 13] = [
+        Script::Latin,
+        Script::Greek,
+        Script::Cyrillic,
+        Script::Armenian,
+        Script::Hebrew,
+        Script::Arabic,
+        Script::Devanagari,
+        Script::Thai,
+        Script::Hangul,
+        Script::Kana,
+        Script::Han,
+        Script::Symbol,
+        Script::Common,
+    ];
+
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Script::Latin => "latin",
+            Script::Greek => "greek",
+            Script::Cyrillic => "cyrillic",
+            Script::Armenian => "armenian",
+            Script::Hebrew => "hebrew",
+            Script::Arabic => "arabic",
+            Script::Devanagari => "devanagari",
+            Script::Thai => "thai",
+            Script::Hangul => "hangul",
+            Script::Kana => "kana",
+            Script::Han => "han",
+            Script::Symbol => "symbol",
+            Script::Common => "common",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_ascii_lowercase();
+        let s = match s.as_str() {
+            // Common aliases used in localization configs.
+            "cjk" => "han",
+            "japanese" => "kana",
+            "korean" => "hangul",
+            "hiragana" | "katakana" => "kana",
+            other => other,
+        };
+        Self::ALL.into_iter().find(|sc| sc.as_str() == s)
+    }
+
+    /// Scripts written right to left.
+    #[inline]
+    pub fn is_rtl(self) -> bool {
+        matches!(self, Script::Hebrew | Script::Arabic)
+    }
+
+    /// Scripts whose glyphs are usually twice as wide as Latin ones.
+    #[inline]
+    pub fn is_wide(self) -> bool {
+        matches!(self, Script::Han | Script::Kana | Script::Hangul)
+    }
+}
+
+/// Script of `c`. Unassigned and shared characters are [`Script::Common`].
+pub fn script_of(c: char) -> Script {
+    let u = c as u32;
+    match u {
+        0x0041..=0x005A | 0x0061..=0x007A => Script::Latin,
+        0x0000..=0x00BF => Script::Common,
+        0x00C0..=0x024F | 0x1E00..=0x1EFF | 0x2C60..=0x2C7F | 0xA720..=0xA7FF => {
+            if u == 0x00D7 || u == 0x00F7 {
+                Script::Common
+            } else {
+                Script::Latin
+            }
+        }
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x0400..=0x052F | 0x1C80..=0x1C8F | 0x2DE0..=0x2DFF | 0xA640..=0xA69F => Script::Cyrillic,
+        0x0530..=0x058F => Script::Armenian,
+        0x0590..=0x05FF | 0xFB1D..=0xFB4F => Script::Hebrew,
+        0x0600..=0x06FF | 0x0750..=0x077F | 0x08A0..=0x08FF | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => {
+            Script::Arabic
+        }
+        0x0900..=0x097F | 0xA8E0..=0xA8FF => Script::Devanagari,
+        0x0E00..=0x0E7F => Script::Thai,
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xA960..=0xA97F | 0xAC00..=0xD7FF => Script::Hangul,
+        0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => Script::Kana,
+        0x2E80..=0x2FDF
+        | 0x3000..=0x303F
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xF900..=0xFAFF
+        | 0xFF00..=0xFF65
+        | 0x20000..=0x3134F => Script::Han,
+        0x2190..=0x2BFF | 0x1F000..=0x1FAFF => Script::Symbol,
+        _ => Script::Common,
+    }
+}
+
+/// Bidi class of a character as far as [`super::bidi`] cares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Strength {
+    Ltr,
+    Rtl,
+    /// European digits: left to right, but do not decide the paragraph direction.
+    Number,
+    Neutral,
+}
+
+pub(crate) fn strength_of(c: char) -> Strength {
+    if c.is_ascii_digit() {
+        return Strength::Number;
+    }
+    // Arabic-Indic digits are laid out left to right like European ones.
+    if matches!(c as u32, 0x0660..=0x0669 | 0x06F0..=0x06F9) {
+        return Strength::Number;
+    }
+    match script_of(c) {
+        Script::Common | Script::Symbol => Strength::Neutral,
+        s if s.is_rtl() => Strength::Rtl,
+        _ if c.is_alphabetic() => Strength::Ltr,
+        _ => Strength::Neutral,
+    }
+}
@@ -4,7 +4,11 @@ pub mod reserved {
     use super::UiTexId;
 
     pub const FONT_ATLAS: UiTexId = UiTexId(1);
+    /// Glyph atlas of `newengine-text`.
+    pub const TEXT_ATLAS: UiTexId = UiTexId(2);
     pub const USER_BEGIN: u32 = 16;
+    /// Textures registered by the render backend (render targets shown as UI images).
+    pub const EXTERNAL_BEGIN: u32 = 1 << 30;
 }
 
 #[derive(Debug, Default)]